@@ -72,16 +72,31 @@ pub trait SourceDatabase: FileLoader + std::fmt::Debug {
 
     #[salsa::transparent]
     fn toolchain_channel(&self, krate: CrateId) -> Option<ReleaseChannel>;
+
+    /// The edition the file is parsed with, the newest one of the crates it belongs to.
+    ///
+    /// This is memoized so that changes to the crate graph only reparse files whose edition
+    /// actually changed.
+    fn file_edition(&self, file_id: FileId) -> Edition;
 }
 
 fn toolchain_channel(db: &dyn SourceDatabase, krate: CrateId) -> Option<ReleaseChannel> {
     db.toolchain(krate).as_ref().and_then(|v| ReleaseChannel::from_str(&v.pre))
 }
 
+fn file_edition(db: &dyn SourceDatabase, file_id: FileId) -> Edition {
+    let crate_graph = db.crate_graph();
+    db.relevant_crates(file_id)
+        .iter()
+        .map(|&krate| crate_graph[krate].edition)
+        .max()
+        .unwrap_or(Edition::CURRENT)
+}
+
 fn parse(db: &dyn SourceDatabase, file_id: FileId) -> Parse<ast::SourceFile> {
     let _p = tracing::span!(tracing::Level::INFO, "parse_query", ?file_id).entered();
     let text = db.file_text(file_id);
-    SourceFile::parse(&text)
+    SourceFile::parse_with_edition(&text, db.file_edition(file_id))
 }
 
 /// We don't want to give HIR knowledge of source roots, hence we extract these
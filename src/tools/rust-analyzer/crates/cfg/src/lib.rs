@@ -119,7 +119,7 @@ fn into_iter(self) -> Self::IntoIter {
     }
 }
 
-#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct CfgDiff {
     // Invariants: No duplicates, no atom that's both in `enable` and `disable`.
     enable: Vec<CfgAtom>,
@@ -147,6 +147,16 @@ pub fn len(&self) -> usize {
     pub fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// The atoms this diff enables.
+    pub fn enable(&self) -> &[CfgAtom] {
+        &self.enable
+    }
+
+    /// The atoms this diff disables.
+    pub fn disable(&self) -> &[CfgAtom] {
+        &self.disable
+    }
 }
 
 impl fmt::Display for CfgDiff {
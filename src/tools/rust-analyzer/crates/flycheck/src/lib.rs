@@ -15,13 +15,16 @@
 use command_group::{CommandGroup, GroupChild};
 use crossbeam_channel::{never, select, unbounded, Receiver, Sender};
 use paths::{AbsPath, AbsPathBuf};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Deserialize;
 use stdx::process::streaming_output;
 
-pub use cargo_metadata::diagnostic::{
-    Applicability, Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan,
-    DiagnosticSpanMacroExpansion,
+pub use cargo_metadata::{
+    diagnostic::{
+        Applicability, Diagnostic, DiagnosticCode, DiagnosticLevel, DiagnosticSpan,
+        DiagnosticSpanMacroExpansion,
+    },
+    PackageId,
 };
 use toolchain::Tool;
 
@@ -104,13 +107,16 @@ pub fn spawn(
 
     /// Schedule a re-start of the cargo check worker to do a workspace wide check.
     pub fn restart_workspace(&self, saved_file: Option<AbsPathBuf>) {
-        self.sender.send(StateChange::Restart { package: None, saved_file }).unwrap();
+        self.sender.send(StateChange::Restart { scope: Scope::Workspace, saved_file }).unwrap();
     }
 
-    /// Schedule a re-start of the cargo check worker to do a package wide check.
-    pub fn restart_for_package(&self, package: String) {
+    /// Schedule a re-start of the cargo check worker to only check the given
+    /// packages.
+    ///
+    /// Diagnostics of other packages are kept from previous checks.
+    pub fn restart_for_packages(&self, packages: Vec<String>) {
         self.sender
-            .send(StateChange::Restart { package: Some(package), saved_file: None })
+            .send(StateChange::Restart { scope: Scope::Packages(packages), saved_file: None })
             .unwrap();
     }
 
@@ -126,7 +132,18 @@ pub fn id(&self) -> usize {
 
 pub enum Message {
     /// Request adding a diagnostic with fixes included to a file
-    AddDiagnostic { id: usize, workspace_root: AbsPathBuf, diagnostic: Diagnostic },
+    AddDiagnostic {
+        id: usize,
+        workspace_root: AbsPathBuf,
+        diagnostic: Diagnostic,
+        /// The package the diagnostic was reported for, if the check command
+        /// tells us.
+        package_id: Option<PackageId>,
+    },
+
+    /// Request clearing diagnostics of previous checks, as new ones are coming
+    /// in.
+    ClearDiagnostics { id: usize, kind: ClearDiagnosticsKind },
 
     /// Request check progress notification to client
     Progress {
@@ -139,12 +156,16 @@ pub enum Message {
 impl fmt::Debug for Message {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Message::AddDiagnostic { id, workspace_root, diagnostic } => f
+            Message::AddDiagnostic { id, workspace_root, diagnostic, package_id } => f
                 .debug_struct("AddDiagnostic")
                 .field("id", id)
                 .field("workspace_root", workspace_root)
+                .field("package_id", package_id)
                 .field("diagnostic_code", &diagnostic.code.as_ref().map(|it| &it.code))
                 .finish(),
+            Message::ClearDiagnostics { id, kind } => {
+                f.debug_struct("ClearDiagnostics").field("id", id).field("kind", kind).finish()
+            }
             Message::Progress { id, progress } => {
                 f.debug_struct("Progress").field("id", id).field("progress", progress).finish()
             }
@@ -161,11 +182,40 @@ pub enum Progress {
     DidFailToRestart(String),
 }
 
+#[derive(Debug)]
+pub enum ClearDiagnosticsKind {
+    /// The diagnostics of all packages.
+    All,
+    /// The diagnostics reported for a package, or those reported without one
+    /// if `None`.
+    Package(Option<PackageId>),
+}
+
 enum StateChange {
-    Restart { package: Option<String>, saved_file: Option<AbsPathBuf> },
+    Restart { scope: Scope, saved_file: Option<AbsPathBuf> },
     Cancel,
 }
 
+enum Scope {
+    Workspace,
+    Packages(Vec<String>),
+}
+
+impl Scope {
+    /// The scope of a check covering both `self` and `other`.
+    fn merge(self, other: Scope) -> Scope {
+        match (self, other) {
+            (Scope::Packages(mut packages), Scope::Packages(other)) => {
+                packages.extend(other);
+                packages.sort();
+                packages.dedup();
+                Scope::Packages(packages)
+            }
+            _ => Scope::Workspace,
+        }
+    }
+}
+
 /// A [`FlycheckActor`] is a single check instance of a workspace.
 struct FlycheckActor {
     /// The workspace id of this flycheck instance.
@@ -182,6 +232,14 @@ struct FlycheckActor {
     /// have to wrap sub-processes output handling in a thread and pass messages
     /// back over a channel.
     command_handle: Option<CommandHandle>,
+    /// Packages whose diagnostics from previous checks were cleared by the
+    /// running check.
+    diagnostics_cleared_for: FxHashSet<PackageId>,
+    /// Whether the running check cleared the diagnostics of all packages.
+    diagnostics_cleared_for_all: bool,
+    /// Whether the running check covers the whole workspace rather than only
+    /// some of its packages.
+    checks_workspace: bool,
 }
 
 enum Event {
@@ -207,6 +265,9 @@ fn new(
             sysroot_root,
             root: workspace_root,
             command_handle: None,
+            diagnostics_cleared_for: FxHashSet::default(),
+            diagnostics_cleared_for_all: false,
+            checks_workspace: false,
         }
     }
 
@@ -233,21 +294,28 @@ fn run(mut self, inbox: Receiver<StateChange>) {
                     tracing::debug!(flycheck_id = self.id, "flycheck cancelled");
                     self.cancel_check_process();
                 }
-                Event::RequestStateChange(StateChange::Restart { package, saved_file }) => {
+                Event::RequestStateChange(StateChange::Restart { mut scope, mut saved_file }) => {
                     // Cancel the previously spawned process
                     self.cancel_check_process();
                     while let Ok(restart) = inbox.recv_timeout(Duration::from_millis(50)) {
-                        // restart chained with a stop, so just cancel
-                        if let StateChange::Cancel = restart {
-                            continue 'event;
+                        match restart {
+                            // restart chained with a stop, so just cancel
+                            StateChange::Cancel => continue 'event,
+                            // a newer save supersedes this one, check what both asked for
+                            StateChange::Restart {
+                                scope: new_scope,
+                                saved_file: new_saved_file,
+                            } => {
+                                scope = scope.merge(new_scope);
+                                saved_file = new_saved_file.or(saved_file);
+                            }
                         }
                     }
 
-                    let command =
-                        match self.check_command(package.as_deref(), saved_file.as_deref()) {
-                            Some(c) => c,
-                            None => continue,
-                        };
+                    let command = match self.check_command(&scope, saved_file.as_deref()) {
+                        Some(c) => c,
+                        None => continue,
+                    };
                     let formatted_command = format!("{:?}", command);
 
                     tracing::debug!(?command, "will restart flycheck");
@@ -255,6 +323,10 @@ fn run(mut self, inbox: Receiver<StateChange>) {
                         Ok(command_handle) => {
                             tracing::debug!(command = formatted_command, "did  restart flycheck");
                             self.command_handle = Some(command_handle);
+                            self.diagnostics_cleared_for.clear();
+                            self.diagnostics_cleared_for_all = false;
+                            self.checks_workspace = matches!(scope, Scope::Workspace)
+                                || matches!(self.config, FlycheckConfig::CustomCommand { .. });
                             self.report_progress(Progress::DidStart);
                         }
                         Err(error) => {
@@ -278,6 +350,17 @@ fn run(mut self, inbox: Receiver<StateChange>) {
                             "Flycheck failed to run the following command: {}",
                             formatted_handle
                         );
+                    } else if self.checks_workspace
+                        && self.diagnostics_cleared_for.is_empty()
+                        && !self.diagnostics_cleared_for_all
+                    {
+                        // The check didn't report anything, so nothing in the workspace has
+                        // diagnostics anymore. A check of some packages reports an artifact
+                        // for each of them, so their diagnostics have been cleared already.
+                        self.send(Message::ClearDiagnostics {
+                            id: self.id,
+                            kind: ClearDiagnosticsKind::All,
+                        });
                     }
                     self.report_progress(Progress::DidFinish(res));
                 }
@@ -288,19 +371,24 @@ fn run(mut self, inbox: Receiver<StateChange>) {
                             artifact = msg.target.name,
                             "artifact received"
                         );
-                        self.report_progress(Progress::DidCheckCrate(msg.target.name));
+                        self.clear_diagnostics_for(Some(msg.package_id));
+                        if !msg.fresh {
+                            self.report_progress(Progress::DidCheckCrate(msg.target.name));
+                        }
                     }
 
-                    CargoMessage::Diagnostic(msg) => {
+                    CargoMessage::Diagnostic { diagnostic, package_id } => {
                         tracing::trace!(
                             flycheck_id = self.id,
-                            message = msg.message,
+                            message = diagnostic.message,
                             "diagnostic received"
                         );
+                        self.clear_diagnostics_for(package_id.clone());
                         self.send(Message::AddDiagnostic {
                             id: self.id,
                             workspace_root: self.root.clone(),
-                            diagnostic: msg,
+                            diagnostic,
+                            package_id,
                         });
                     }
                 },
@@ -310,6 +398,34 @@ fn run(mut self, inbox: Receiver<StateChange>) {
         self.cancel_check_process();
     }
 
+    /// Clears the diagnostics of previous checks for the given package the
+    /// first time the running check reports on it. If we don't know which
+    /// package is being reported on, a workspace check clears the diagnostics
+    /// of all packages, while a check of some packages only clears those that
+    /// were reported without a package.
+    fn clear_diagnostics_for(&mut self, package_id: Option<PackageId>) {
+        if self.diagnostics_cleared_for_all {
+            return;
+        }
+        let kind = match package_id {
+            Some(package_id) => {
+                if !self.diagnostics_cleared_for.insert(package_id.clone()) {
+                    return;
+                }
+                ClearDiagnosticsKind::Package(Some(package_id))
+            }
+            None => {
+                self.diagnostics_cleared_for_all = true;
+                if self.checks_workspace {
+                    ClearDiagnosticsKind::All
+                } else {
+                    ClearDiagnosticsKind::Package(None)
+                }
+            }
+        };
+        self.send(Message::ClearDiagnostics { id: self.id, kind });
+    }
+
     fn cancel_check_process(&mut self) {
         if let Some(command_handle) = self.command_handle.take() {
             tracing::debug!(
@@ -324,11 +440,7 @@ fn cancel_check_process(&mut self) {
     /// Construct a `Command` object for checking the user's code. If the user
     /// has specified a custom command with placeholders that we cannot fill,
     /// return None.
-    fn check_command(
-        &self,
-        package: Option<&str>,
-        saved_file: Option<&AbsPath>,
-    ) -> Option<Command> {
+    fn check_command(&self, scope: &Scope, saved_file: Option<&AbsPath>) -> Option<Command> {
         let (mut cmd, args) = match &self.config {
             FlycheckConfig::CargoCommand {
                 command,
@@ -349,9 +461,15 @@ fn check_command(
                 cmd.arg(command);
                 cmd.current_dir(&self.root);
 
-                match package {
-                    Some(pkg) => cmd.arg("-p").arg(pkg),
-                    None => cmd.arg("--workspace"),
+                match scope {
+                    Scope::Packages(packages) => {
+                        for pkg in packages {
+                            cmd.arg("-p").arg(pkg);
+                        }
+                    }
+                    Scope::Workspace => {
+                        cmd.arg("--workspace");
+                    }
                 };
 
                 cmd.arg(if *ansi_color_output {
@@ -552,16 +670,26 @@ fn run(self) -> io::Result<(bool, String)> {
                 match message {
                     // Skip certain kinds of messages to only spend time on what's useful
                     JsonMessage::Cargo(message) => match message {
-                        cargo_metadata::Message::CompilerArtifact(artifact) if !artifact.fresh => {
+                        cargo_metadata::Message::CompilerArtifact(artifact) => {
                             self.sender.send(CargoMessage::CompilerArtifact(artifact)).unwrap();
                         }
                         cargo_metadata::Message::CompilerMessage(msg) => {
-                            self.sender.send(CargoMessage::Diagnostic(msg.message)).unwrap();
+                            self.sender
+                                .send(CargoMessage::Diagnostic {
+                                    diagnostic: msg.message,
+                                    package_id: Some(msg.package_id),
+                                })
+                                .unwrap();
                         }
                         _ => (),
                     },
                     JsonMessage::Rustc(message) => {
-                        self.sender.send(CargoMessage::Diagnostic(message)).unwrap();
+                        self.sender
+                            .send(CargoMessage::Diagnostic {
+                                diagnostic: message,
+                                package_id: None,
+                            })
+                            .unwrap();
                     }
                 }
                 return true;
@@ -600,7 +728,7 @@ fn run(self) -> io::Result<(bool, String)> {
 #[allow(clippy::large_enum_variant)]
 enum CargoMessage {
     CompilerArtifact(cargo_metadata::Artifact),
-    Diagnostic(Diagnostic),
+    Diagnostic { diagnostic: Diagnostic, package_id: Option<PackageId> },
 }
 
 #[derive(Deserialize)]
@@ -609,3 +737,106 @@ enum JsonMessage {
     Cargo(cargo_metadata::Message),
     Rustc(Diagnostic),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn actor(config: FlycheckConfig) -> (FlycheckActor, Receiver<Message>) {
+        let (sender, receiver) = unbounded();
+        let sender = Box::new(move |msg| sender.send(msg).unwrap());
+        let root = AbsPathBuf::assert(std::env::temp_dir().join("flycheck-workspace"));
+        (FlycheckActor::new(0, sender, config, None, root), receiver)
+    }
+
+    fn cargo_check() -> FlycheckConfig {
+        FlycheckConfig::CargoCommand {
+            command: "check".to_owned(),
+            target_triples: Vec::new(),
+            all_targets: false,
+            no_default_features: false,
+            all_features: false,
+            features: Vec::new(),
+            extra_args: Vec::new(),
+            extra_env: FxHashMap::default(),
+            ansi_color_output: false,
+            target_dir: None,
+        }
+    }
+
+    fn package(name: &str) -> PackageId {
+        PackageId { repr: name.to_owned() }
+    }
+
+    /// The packages whose diagnostics were cleared, `"<all>"` for all of them
+    /// and `"<unknown>"` for those reported without a package.
+    fn cleared(receiver: &Receiver<Message>) -> Vec<String> {
+        receiver
+            .try_iter()
+            .filter_map(|msg| match msg {
+                Message::ClearDiagnostics { kind, .. } => Some(match kind {
+                    ClearDiagnosticsKind::All => "<all>".to_owned(),
+                    ClearDiagnosticsKind::Package(None) => "<unknown>".to_owned(),
+                    ClearDiagnosticsKind::Package(Some(id)) => id.repr,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn command_args(command: &Command) -> Vec<&str> {
+        command.get_args().map(|it| it.to_str().unwrap()).collect()
+    }
+
+    #[test]
+    fn package_check_only_checks_its_packages() {
+        let (actor, _) = actor(cargo_check());
+        let scope = Scope::Packages(vec!["a".to_owned(), "b".to_owned()]);
+        let command = actor.check_command(&scope, None).unwrap();
+        let args = command_args(&command);
+        assert_eq!(args[..5], ["check", "-p", "a", "-p", "b"]);
+        assert!(!args.contains(&"--workspace"));
+
+        let command = actor.check_command(&Scope::Workspace, None).unwrap();
+        let args = command_args(&command);
+        assert_eq!(args[..2], ["check", "--workspace"]);
+        assert!(!args.contains(&"-p"));
+    }
+
+    #[test]
+    fn merged_scopes() {
+        let packages =
+            |names: &[&str]| Scope::Packages(names.iter().map(|&it| it.to_owned()).collect());
+        let merged = packages(&["b", "a"]).merge(packages(&["c", "a"]));
+        assert!(matches!(merged, Scope::Packages(it) if it == ["a", "b", "c"]));
+        assert!(matches!(packages(&["a"]).merge(Scope::Workspace), Scope::Workspace));
+        assert!(matches!(Scope::Workspace.merge(packages(&["a"])), Scope::Workspace));
+    }
+
+    #[test]
+    fn package_check_only_clears_its_packages() {
+        let (mut actor, receiver) = actor(cargo_check());
+        actor.checks_workspace = false;
+        actor.clear_diagnostics_for(Some(package("a")));
+        actor.clear_diagnostics_for(Some(package("a")));
+        actor.clear_diagnostics_for(Some(package("b")));
+        assert_eq!(cleared(&receiver), ["a", "b"]);
+
+        // Diagnostics without a package only clear others without one, not
+        // those of packages that weren't checked.
+        actor.clear_diagnostics_for(None);
+        actor.clear_diagnostics_for(None);
+        actor.clear_diagnostics_for(Some(package("c")));
+        assert_eq!(cleared(&receiver), ["<unknown>"]);
+    }
+
+    #[test]
+    fn workspace_check_clears_all_packages() {
+        let (mut actor, receiver) = actor(cargo_check());
+        actor.checks_workspace = true;
+        actor.clear_diagnostics_for(Some(package("a")));
+        actor.clear_diagnostics_for(None);
+        actor.clear_diagnostics_for(Some(package("b")));
+        assert_eq!(cleared(&receiver), ["a", "<all>"]);
+    }
+}
@@ -170,6 +170,34 @@ pub fn has_doc_notable_trait(&self) -> bool {
         })
     }
 
+    pub fn has_doc_no_inline(&self) -> bool {
+        self.by_key("doc").tt_values().any(|tt| {
+            tt.delimiter.kind == DelimiterKind::Parenthesis &&
+                matches!(&*tt.token_trees, [tt::TokenTree::Leaf(tt::Leaf::Ident(ident))] if ident.text == "no_inline")
+        })
+    }
+
+    /// The condition of the `#[doc(cfg(..))]` attributes, under which the item is documented to be
+    /// available.
+    pub fn doc_cfg(&self) -> Option<CfgExpr> {
+        let mut cfgs = self.by_key("doc").tt_values().filter_map(|tt| match &*tt.token_trees {
+            [tt::TokenTree::Leaf(tt::Leaf::Ident(ident)), tt::TokenTree::Subtree(cfg)]
+                if ident.text == "cfg" =>
+            {
+                Some(CfgExpr::parse(cfg))
+            }
+            _ => None,
+        });
+        let first = cfgs.next()?;
+        match cfgs.next() {
+            Some(second) => {
+                let cfgs = [first, second].into_iter().chain(cfgs);
+                Some(CfgExpr::All(cfgs.collect()))
+            }
+            None => Some(first),
+        }
+    }
+
     pub fn doc_exprs(&self) -> impl Iterator<Item = DocExpr> + '_ {
         self.by_key("doc").tt_values().map(DocExpr::parse)
     }
@@ -297,20 +297,14 @@ fn maybe_collect_expr(&mut self, expr: ast::Expr) -> Option<ExprId> {
                         })
                     })
                 }
-                Some(ast::BlockModifier::Const(_)) => {
-                    self.with_label_rib(RibKind::Constant, |this| {
-                        let (result_expr_id, prev_binding_owner) =
-                            this.initialize_binding_owner(syntax_ptr);
-                        let inner_expr = this.collect_block(e);
-                        let it = this.db.intern_anonymous_const(ConstBlockLoc {
-                            parent: this.owner,
-                            root: inner_expr,
-                        });
-                        this.body.exprs[result_expr_id] = Expr::Const(it);
-                        this.current_binding_owner = prev_binding_owner;
-                        result_expr_id
+                Some(ast::BlockModifier::Gen(_)) => self.with_label_rib(RibKind::Closure, |this| {
+                    this.collect_block_(e, |id, statements, tail| Expr::Gen {
+                        id,
+                        statements,
+                        tail,
                     })
-                }
+                }),
+                Some(ast::BlockModifier::Const(_)) => self.collect_const_block(e, syntax_ptr),
                 None => self.collect_block(e),
             },
             ast::Expr::LoopExpr(e) => {
@@ -682,8 +676,22 @@ fn initialize_binding_owner(
         (result_expr_id, prev_binding_owner)
     }
 
+    /// Collects the block of a `const { ... }` expression or pattern as an anonymous const.
+    fn collect_const_block(&mut self, block: BlockExpr, syntax_ptr: AstPtr<ast::Expr>) -> ExprId {
+        self.with_label_rib(RibKind::Constant, |this| {
+            let (result_expr_id, prev_binding_owner) = this.initialize_binding_owner(syntax_ptr);
+            let inner_expr = this.collect_block(block);
+            let it = this
+                .db
+                .intern_anonymous_const(ConstBlockLoc { parent: this.owner, root: inner_expr });
+            this.body.exprs[result_expr_id] = Expr::Const(it);
+            this.current_binding_owner = prev_binding_owner;
+            result_expr_id
+        })
+    }
+
     /// FIXME: This function is bad. It will produce a dangling `Missing` expr which wastes memory. Currently
-    /// it is used only for const blocks and repeat expressions, which are also hacky and ideally should have
+    /// it is used only for repeat expressions, which are also hacky and ideally should have
     /// their own body. Don't add more usage for this function so that we can remove this function after
     /// separating those bodies.
     fn collect_as_a_binding_owner_bad(
@@ -1319,6 +1327,9 @@ fn collect_pat(&mut self, pat: ast::Pat, binding_list: &mut BindingList) -> PatI
                 Pat::Or(pats.into())
             }
             ast::Pat::ParenPat(p) => return self.collect_pat_opt(p.pat(), binding_list),
+            // FIXME: Lower the guard condition, guard patterns are still unstable so we only care
+            // about the bindings of the pattern for now.
+            ast::Pat::GuardPat(p) => return self.collect_pat_opt(p.pat(), binding_list),
             ast::Pat::TuplePat(p) => {
                 let (args, ellipsis) = self.collect_tuple_pat(
                     p.fields(),
@@ -1383,14 +1394,8 @@ fn collect_pat(&mut self, pat: ast::Pat, binding_list: &mut BindingList) -> PatI
             }
             ast::Pat::ConstBlockPat(const_block_pat) => {
                 if let Some(block) = const_block_pat.block_expr() {
-                    let expr_id = self.with_label_rib(RibKind::Constant, |this| {
-                        let syntax_ptr = AstPtr::new(&block.clone().into());
-                        this.collect_as_a_binding_owner_bad(
-                            |this| this.collect_block(block),
-                            syntax_ptr,
-                        )
-                    });
-                    Pat::ConstBlock(expr_id)
+                    let syntax_ptr = AstPtr::new(&block.clone().into());
+                    Pat::ConstBlock(self.collect_const_block(block, syntax_ptr))
                 } else {
                     Pat::Missing
                 }
@@ -1735,15 +1740,15 @@ fn collect_format_args(
         //         unsafe { ::core::fmt::UnsafeArg::new() }
         //     )
 
-        let Some(new_v1_formatted) =
-            LangItem::FormatArguments.ty_rel_path(self.db, self.krate, name![new_v1_formatted])
-        else {
-            return self.missing_expr();
-        };
-        let Some(unsafe_arg_new) =
-            LangItem::FormatUnsafeArg.ty_rel_path(self.db, self.krate, name![new])
-        else {
-            return self.missing_expr();
+        let (Some(new_v1_formatted), Some(unsafe_arg_new)) = (
+            LangItem::FormatArguments.ty_rel_path(self.db, self.krate, name![new_v1_formatted]),
+            LangItem::FormatUnsafeArg.ty_rel_path(self.db, self.krate, name![new]),
+        ) else {
+            // Still record the template mappings so that the implicit captures can be resolved
+            // by the IDE even without the `core::fmt` lang items.
+            let idx = self.alloc_expr(Expr::Missing, syntax_ptr);
+            self.source_map.format_args_template_map.insert(idx, mappings);
+            return idx;
         };
         let new_v1_formatted = self.alloc_expr_desugared(Expr::Path(new_v1_formatted));
 
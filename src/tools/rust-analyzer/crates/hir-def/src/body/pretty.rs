@@ -456,8 +456,12 @@ fn print_expr(&mut self, expr: ExprId) {
             Expr::Async { id: _, statements, tail } => {
                 self.print_block(Some("async "), statements, tail);
             }
-            Expr::Const(id) => {
-                w!(self, "const {{ /* {id:?} */ }}");
+            Expr::Gen { id: _, statements, tail } => {
+                self.print_block(Some("gen "), statements, tail);
+            }
+            &Expr::Const(id) => {
+                w!(self, "const ");
+                self.print_expr(self.db.lookup_intern_anonymous_const(id).root);
             }
         }
     }
@@ -595,10 +599,7 @@ fn print_pat(&mut self, pat: PatId) {
                 w!(self, "box ");
                 self.print_pat(*inner);
             }
-            Pat::ConstBlock(c) => {
-                w!(self, "const ");
-                self.print_expr(*c);
-            }
+            Pat::ConstBlock(c) => self.print_expr(*c),
         }
     }
 
@@ -46,7 +46,7 @@ pub struct ScopeData {
 impl ExprScopes {
     pub(crate) fn expr_scopes_query(db: &dyn DefDatabase, def: DefWithBodyId) -> Arc<ExprScopes> {
         let body = db.body(def);
-        let mut scopes = ExprScopes::new(&body);
+        let mut scopes = ExprScopes::new(db, &body);
         scopes.shrink_to_fit();
         Arc::new(scopes)
     }
@@ -89,7 +89,7 @@ fn empty_entries(idx: usize) -> IdxRange<ScopeEntry> {
 }
 
 impl ExprScopes {
-    fn new(body: &Body) -> ExprScopes {
+    fn new(db: &dyn DefDatabase, body: &Body) -> ExprScopes {
         let mut scopes = ExprScopes {
             scopes: Arena::default(),
             scope_entries: Arena::default(),
@@ -98,6 +98,19 @@ fn new(body: &Body) -> ExprScopes {
         let mut root = scopes.root_scope();
         scopes.add_params_bindings(body, root, &body.params);
         compute_expr_scopes(body.body_expr, body, &mut scopes, &mut root);
+        // Const blocks can't refer to the locals around them, so their scopes start from a new
+        // root. This also covers the const blocks in patterns, which aren't reached above.
+        for (_, expr) in body.exprs.iter() {
+            if let &Expr::Const(id) = expr {
+                let mut root = scopes.root_scope();
+                compute_expr_scopes(
+                    db.lookup_intern_anonymous_const(id).root,
+                    body,
+                    &mut scopes,
+                    &mut root,
+                );
+            }
+        }
         scopes
     }
 
@@ -218,10 +231,11 @@ fn compute_expr_scopes(expr: ExprId, body: &Body, scopes: &mut ExprScopes, scope
             scopes.set_scope(expr, scope);
             compute_block_scopes(statements, *tail, body, scopes, &mut scope);
         }
-        Expr::Const(_) => {
-            // FIXME: This is broken.
-        }
-        Expr::Unsafe { id, statements, tail } | Expr::Async { id, statements, tail } => {
+        // The scopes of const blocks are computed separately, see `ExprScopes::new`.
+        Expr::Const(_) => (),
+        Expr::Unsafe { id, statements, tail }
+        | Expr::Async { id, statements, tail }
+        | Expr::Gen { id, statements, tail } => {
             let mut scope = scopes.new_block_scope(*scope, *id, None);
             // Overwrite the old scope for the block expr, so that every block scope can be found
             // via the block itself (important for blocks that only contain items, no expressions).
@@ -599,4 +613,32 @@ fn test() {
             100,
         );
     }
+
+    #[test]
+    fn const_block_scope() {
+        do_check(
+            r"
+            fn quux(foo: i32) {
+                let bar = const {
+                    let baz = 1;
+                    $0
+                };
+            }",
+            &["baz"],
+        );
+    }
+
+    #[test]
+    fn const_block_pattern_scope() {
+        do_check(
+            r"
+            fn quux(foo: i32) {
+                match foo {
+                    const { let baz = 1; $0 } => {}
+                    _ => {}
+                }
+            }",
+            &["baz"],
+        );
+    }
 }
@@ -18,17 +18,17 @@
     },
     generics::GenericParams,
     import_map::ImportMap,
-    item_tree::{AttrOwner, ItemTree},
+    item_tree::{persist::PersistedItemTrees, AttrOwner, ItemTree},
     lang_item::{self, LangItem, LangItemTarget, LangItems},
     nameres::{diagnostics::DefDiagnostics, DefMap},
     visibility::{self, Visibility},
     AttrDefId, BlockId, BlockLoc, ConstBlockId, ConstBlockLoc, ConstId, ConstLoc, DefWithBodyId,
     EnumId, EnumLoc, EnumVariantId, EnumVariantLoc, ExternBlockId, ExternBlockLoc, ExternCrateId,
     ExternCrateLoc, FunctionId, FunctionLoc, GenericDefId, ImplId, ImplLoc, InTypeConstId,
-    InTypeConstLoc, LocalFieldId, Macro2Id, Macro2Loc, MacroId, MacroRulesId, MacroRulesLoc,
-    MacroRulesLocFlags, ProcMacroId, ProcMacroLoc, StaticId, StaticLoc, StructId, StructLoc,
-    TraitAliasId, TraitAliasLoc, TraitId, TraitLoc, TypeAliasId, TypeAliasLoc, UnionId, UnionLoc,
-    UseId, UseLoc, VariantId,
+    InTypeConstLoc, LocalFieldId, LocalModuleId, Macro2Id, Macro2Loc, MacroId, MacroRulesId,
+    MacroRulesLoc, MacroRulesLocFlags, ProcMacroId, ProcMacroLoc, StaticId, StaticLoc, StructId,
+    StructLoc, TraitAliasId, TraitAliasLoc, TraitId, TraitLoc, TypeAliasId, TypeAliasLoc, UnionId,
+    UnionLoc, UseId, UseLoc, VariantId,
 };
 
 #[salsa::query_group(InternDatabaseStorage)]
@@ -83,6 +83,16 @@ pub trait DefDatabase: InternDatabase + ExpandDatabase + Upcast<dyn ExpandDataba
     #[salsa::input]
     fn expand_proc_attr_macros(&self) -> bool;
 
+    /// Item trees persisted by an earlier session, see [`crate::item_tree::persist`].
+    #[salsa::input]
+    fn persisted_item_trees(&self) -> Arc<PersistedItemTrees>;
+
+    /// The persisted item tree of `file_id`, if its text didn't change since. This sits between
+    /// [`Self::persisted_item_trees`] and the item trees, so that adding the trees of new crates
+    /// doesn't invalidate the item trees of all other files.
+    #[salsa::invoke(ItemTree::persisted_item_tree_query)]
+    fn persisted_item_tree(&self, file_id: FileId) -> Option<Arc<ItemTree>>;
+
     #[salsa::invoke(ItemTree::file_item_tree_query)]
     fn file_item_tree(&self, file_id: HirFileId) -> Arc<ItemTree>;
 
@@ -177,6 +187,11 @@ fn enum_variant_data_with_diagnostics(
     #[salsa::invoke(Body::body_with_source_map_query)]
     fn body_with_source_map(&self, def: DefWithBodyId) -> (Arc<Body>, Arc<BodySourceMap>);
 
+    /// The lowered body, without the source map.
+    ///
+    /// It refers to the source only through ids, so it is the same before and after edits to the
+    /// trivia of the body, like comments and formatting. Queries on top of it, like inference, are
+    /// not re-run for such edits.
     #[salsa::invoke(Body::body_query)]
     fn body(&self, def: DefWithBodyId) -> Arc<Body>;
 
@@ -236,19 +251,22 @@ fn fields_attrs_source_map(
 
     fn crate_supports_no_std(&self, crate_id: CrateId) -> bool;
 
-    fn include_macro_invoc(&self, crate_id: CrateId) -> Vec<(MacroCallId, FileId)>;
+    fn include_macro_invoc(&self, crate_id: CrateId) -> Vec<(MacroCallId, FileId, LocalModuleId)>;
 }
 
-// return: macro call id and include file id
-fn include_macro_invoc(db: &dyn DefDatabase, krate: CrateId) -> Vec<(MacroCallId, FileId)> {
+// return: macro call id, include file id and the module the macro call is in
+fn include_macro_invoc(
+    db: &dyn DefDatabase,
+    krate: CrateId,
+) -> Vec<(MacroCallId, FileId, LocalModuleId)> {
     db.crate_def_map(krate)
         .modules
-        .values()
-        .flat_map(|m| m.scope.iter_macro_invoc())
-        .filter_map(|invoc| {
+        .iter()
+        .flat_map(|(module, data)| data.scope.iter_macro_invoc().map(move |invoc| (module, invoc)))
+        .filter_map(|(module, invoc)| {
             db.lookup_intern_macro_call(*invoc.1)
                 .include_file_id(db.upcast(), *invoc.1)
-                .map(|x| (*invoc.1, x))
+                .map(|x| (*invoc.1, x, module))
         })
         .collect()
 }
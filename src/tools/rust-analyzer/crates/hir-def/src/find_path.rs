@@ -27,9 +27,17 @@ pub fn find_path(
     prefer_prelude: bool,
 ) -> Option<ModPath> {
     let _p = tracing::span!(tracing::Level::INFO, "find_path").entered();
-    find_path_inner(FindPathCtx { db, prefixed: None, prefer_no_std, prefer_prelude }, item, from)
+    find_path_inner(
+        FindPathCtx { db, prefixed: None, prefer_no_std, prefer_prelude, prefer_reexports: false },
+        item,
+        from,
+    )
 }
 
+/// Like [`find_path`], but for paths in use items, which start with the prefix of `prefix_kind`.
+///
+/// With `prefer_reexports`, an item of an upstream crate is preferably imported through the
+/// public re-export of another crate, like a facade crate, over a path into the crate defining it.
 pub fn find_path_prefixed(
     db: &dyn DefDatabase,
     item: ItemInNs,
@@ -37,10 +45,17 @@ pub fn find_path_prefixed(
     prefix_kind: PrefixKind,
     prefer_no_std: bool,
     prefer_prelude: bool,
+    prefer_reexports: bool,
 ) -> Option<ModPath> {
     let _p = tracing::span!(tracing::Level::INFO, "find_path_prefixed").entered();
     find_path_inner(
-        FindPathCtx { db, prefixed: Some(prefix_kind), prefer_no_std, prefer_prelude },
+        FindPathCtx {
+            db,
+            prefixed: Some(prefix_kind),
+            prefer_no_std,
+            prefer_prelude,
+            prefer_reexports,
+        },
         item,
         from,
     )
@@ -96,6 +111,7 @@ struct FindPathCtx<'db> {
     prefixed: Option<PrefixKind>,
     prefer_no_std: bool,
     prefer_prelude: bool,
+    prefer_reexports: bool,
 }
 
 /// Attempts to find a path to refer to the given `item` visible from the `from` ModuleId
@@ -359,9 +375,13 @@ fn calculate_best_path(
                 path.0.push_segment(name);
 
                 let new_path = match best_path.take() {
-                    Some(best_path) => {
-                        select_best_path(best_path, path, ctx.prefer_no_std, ctx.prefer_prelude)
-                    }
+                    Some(best_path) => select_best_path(
+                        best_path,
+                        path,
+                        ctx.prefer_no_std,
+                        ctx.prefer_prelude,
+                        None,
+                    ),
                     None => path,
                 };
                 best_path_len = new_path.0.len();
@@ -373,6 +393,15 @@ fn calculate_best_path(
         // too (unless we can't name it at all). It could *also* be (re)exported by the same crate
         // that wants to import it here, but we always prefer to use the external path here.
 
+        // The name under which the crate defining the item is a dependency, if it is a direct one.
+        let original_crate =
+            ctx.prefer_reexports.then(|| item.krate(ctx.db)).flatten().and_then(|krate| {
+                ctx.db.crate_graph()[from.krate]
+                    .dependencies
+                    .iter()
+                    .find(|dep| dep.crate_id == krate)
+                    .map(|dep| dep.as_name())
+            });
         for dep in &ctx.db.crate_graph()[from.krate].dependencies {
             let import_map = ctx.db.import_map(dep.crate_id);
             let Some(import_info_for) = import_map.import_info_for(item) else { continue };
@@ -409,6 +438,7 @@ fn calculate_best_path(
                         path_with_stab,
                         ctx.prefer_no_std,
                         ctx.prefer_prelude,
+                        original_crate.as_ref(),
                     ),
                     None => path_with_stab,
                 };
@@ -433,12 +463,14 @@ fn calculate_best_path(
 
 /// Select the best (most relevant) path between two paths.
 /// This accounts for stability, path length whether std should be chosen over alloc/core paths as
-/// well as ignoring prelude like paths or not.
+/// well as ignoring prelude like paths or not. If `original_crate` is given, paths going through
+/// a re-export in another crate are chosen over paths starting with `original_crate`.
 fn select_best_path(
     old_path @ (_, old_stability): (ModPath, Stability),
     new_path @ (_, new_stability): (ModPath, Stability),
     prefer_no_std: bool,
     prefer_prelude: bool,
+    original_crate: Option<&Name>,
 ) -> (ModPath, Stability) {
     match (old_stability, new_stability) {
         (Stable, Unstable) => return old_path,
@@ -495,6 +527,11 @@ fn select_best_path(
                 Ordering::Greater => new_path,
             }
         }
+        (Some(old), Some(new)) => match original_crate {
+            Some(krate) if old == krate && new != krate => new_path,
+            Some(krate) if old != krate && new == krate => old_path,
+            _ => choose(new_path, old_path),
+        },
         _ => choose(new_path, old_path),
     }
 }
@@ -605,6 +642,7 @@ fn check_found_path_(
         path: &str,
         prefix_kind: Option<PrefixKind>,
         prefer_prelude: bool,
+        prefer_reexports: bool,
     ) {
         let (db, pos) = TestDB::with_position(ra_fixture);
         let module = db.module_at_position(pos);
@@ -628,7 +666,13 @@ fn check_found_path_(
             .expect("path does not resolve to a type");
 
         let found_path = find_path_inner(
-            FindPathCtx { prefer_no_std: false, db: &db, prefixed: prefix_kind, prefer_prelude },
+            FindPathCtx {
+                prefer_no_std: false,
+                db: &db,
+                prefixed: prefix_kind,
+                prefer_prelude,
+                prefer_reexports,
+            },
             ItemInNs::Types(resolved),
             module,
         );
@@ -643,10 +687,10 @@ fn check_found_path(
         absolute: &str,
         self_prefixed: &str,
     ) {
-        check_found_path_(ra_fixture, unprefixed, None, false);
-        check_found_path_(ra_fixture, prefixed, Some(PrefixKind::Plain), false);
-        check_found_path_(ra_fixture, absolute, Some(PrefixKind::ByCrate), false);
-        check_found_path_(ra_fixture, self_prefixed, Some(PrefixKind::BySelf), false);
+        check_found_path_(ra_fixture, unprefixed, None, false, false);
+        check_found_path_(ra_fixture, prefixed, Some(PrefixKind::Plain), false, false);
+        check_found_path_(ra_fixture, absolute, Some(PrefixKind::ByCrate), false, false);
+        check_found_path_(ra_fixture, self_prefixed, Some(PrefixKind::BySelf), false, false);
     }
 
     fn check_found_path_prelude(
@@ -656,10 +700,10 @@ fn check_found_path_prelude(
         absolute: &str,
         self_prefixed: &str,
     ) {
-        check_found_path_(ra_fixture, unprefixed, None, true);
-        check_found_path_(ra_fixture, prefixed, Some(PrefixKind::Plain), true);
-        check_found_path_(ra_fixture, absolute, Some(PrefixKind::ByCrate), true);
-        check_found_path_(ra_fixture, self_prefixed, Some(PrefixKind::BySelf), true);
+        check_found_path_(ra_fixture, unprefixed, None, true, false);
+        check_found_path_(ra_fixture, prefixed, Some(PrefixKind::Plain), true, false);
+        check_found_path_(ra_fixture, absolute, Some(PrefixKind::ByCrate), true, false);
+        check_found_path_(ra_fixture, self_prefixed, Some(PrefixKind::BySelf), true, false);
     }
 
     #[test]
@@ -903,6 +947,28 @@ fn different_crate_reexport() {
         );
     }
 
+    #[test]
+    fn prefer_facade_reexport() {
+        let ra_fixture = r#"
+//- /main.rs crate:main deps:facade,imp
+$0
+//- /imp.rs crate:imp
+pub struct Thing;
+//- /facade.rs crate:facade deps:imp
+pub mod things {
+    pub use imp::Thing;
+}
+        "#;
+        check_found_path_(ra_fixture, "imp::Thing", Some(PrefixKind::Plain), false, false);
+        check_found_path_(
+            ra_fixture,
+            "facade::things::Thing",
+            Some(PrefixKind::Plain),
+            false,
+            true,
+        );
+    }
+
     #[test]
     fn prelude() {
         check_found_path(
@@ -181,6 +181,11 @@ pub enum Expr {
         statements: Box<[Statement]>,
         tail: Option<ExprId>,
     },
+    Gen {
+        id: Option<BlockId>,
+        statements: Box<[Statement]>,
+        tail: Option<ExprId>,
+    },
     Const(ConstBlockId),
     // FIXME: Fold this into Block with an unsafe flag?
     Unsafe {
@@ -377,7 +382,8 @@ pub fn walk_child_exprs(&self, mut f: impl FnMut(ExprId)) {
             Expr::Const(_) => (),
             Expr::Block { statements, tail, .. }
             | Expr::Unsafe { statements, tail, .. }
-            | Expr::Async { statements, tail, .. } => {
+            | Expr::Async { statements, tail, .. }
+            | Expr::Gen { statements, tail, .. } => {
                 for stmt in statements.iter() {
                     match stmt {
                         Statement::Let { initializer, else_branch, .. } => {
@@ -16,7 +16,7 @@
 
 use crate::{
     db::DefDatabase,
-    per_ns::PerNs,
+    per_ns::{Namespace, PerNs},
     visibility::{Visibility, VisibilityExplicitness},
     AdtId, BuiltinType, ConstId, ExternCrateId, HasModule, ImplId, LocalModuleId, Lookup, MacroId,
     ModuleDefId, ModuleId, TraitId, UseId,
@@ -218,6 +218,34 @@ pub fn fully_resolve_import(&self, db: &dyn DefDatabase, mut import: ImportId) -
         res
     }
 
+    /// The imports through which `name` is brought into this scope in the namespace `ns`, starting
+    /// with the one of this scope and following re-exports towards the definition. Empty if the
+    /// name is declared here or glob imported.
+    pub fn import_chain(&self, db: &dyn DefDatabase, name: &Name, ns: Namespace) -> Vec<ImportId> {
+        let mut import = match ns {
+            Namespace::Types => self.types.get(name).and_then(|&(.., i)| i?.into_import()),
+            Namespace::Values => self.values.get(name).and_then(|&(.., i)| i),
+            Namespace::Macros => self.macros.get(name).and_then(|&(.., i)| i),
+        };
+        let mut res = Vec::new();
+        let mut def_map;
+        let mut scope = self;
+        while let Some(i) = import {
+            res.push(i);
+            let next = match ns {
+                Namespace::Types => scope.use_imports_types.get(&ImportOrExternCrate::Import(i)),
+                Namespace::Values => scope.use_imports_values.get(&i),
+                Namespace::Macros => scope.use_imports_macros.get(&i),
+            };
+            let Some(&ImportOrDef::Import(next)) = next else { break };
+            let module_id = next.import.lookup(db).container;
+            def_map = module_id.def_map(db);
+            scope = &def_map[module_id.local_id].scope;
+            import = Some(next);
+        }
+        res
+    }
+
     pub fn declarations(&self) -> impl Iterator<Item = ModuleDefId> + '_ {
         self.declarations.iter().copied()
     }
@@ -34,6 +34,7 @@
 //! encompassing span!
 
 mod lower;
+pub mod persist;
 mod pretty;
 #[cfg(test)]
 mod tests;
@@ -45,7 +46,7 @@
 };
 
 use ast::{AstNode, HasName, StructKind};
-use base_db::CrateId;
+use base_db::{CrateId, FileId};
 use either::Either;
 use hir_expand::{
     ast_id_map::{AstIdNode, FileAstId},
@@ -108,9 +109,24 @@ pub struct ItemTree {
 }
 
 impl ItemTree {
+    pub(crate) fn persisted_item_tree_query(
+        db: &dyn DefDatabase,
+        file_id: FileId,
+    ) -> Option<Arc<ItemTree>> {
+        let persisted = db.persisted_item_trees();
+        if persisted.is_empty() {
+            return None;
+        }
+        persisted.item_tree(file_id, &db.file_text(file_id)).map(Arc::new)
+    }
+
     pub(crate) fn file_item_tree_query(db: &dyn DefDatabase, file_id: HirFileId) -> Arc<ItemTree> {
         let _p = tracing::span!(tracing::Level::INFO, "file_item_tree_query", ?file_id).entered();
 
+        if let Some(item_tree) = file_id.file_id().and_then(|it| db.persisted_item_tree(it)) {
+            return item_tree;
+        }
+
         let syntax = db.parse_or_expand(file_id);
 
         let ctx = lower::Ctx::new(db, file_id);
@@ -878,7 +894,7 @@ fn concat_mod_paths(
         ) -> Option<(ModPath, ImportKind)> {
             match (prefix, &path.kind) {
                 (None, _) => Some((path.clone(), ImportKind::Plain)),
-                (Some(mut prefix), PathKind::Plain) => {
+                (Some(mut prefix), PathKind::Plain | PathKind::DefSite(_)) => {
                     for segment in path.segments() {
                         prefix.push_segment(segment.clone());
                     }
@@ -0,0 +1,871 @@
+//! A compact binary encoding of item trees, used to persist the item trees of files that rarely
+//! change, like the ones of dependencies, between sessions.
+//!
+//! Only item trees of real files can be encoded. Everything in them either is plain data or
+//! refers to the file they were lowered from, so an encoded tree can be decoded for any file with
+//! the same text. Trees that refer to anything else, like the syntax contexts or files of macro
+//! expansions, are not encoded.
+
+use std::ops::Range;
+
+use base_db::FileId;
+use hir_expand::{
+    ast_id_map::{AstIdNode, FileAstId},
+    attrs::{Attr, AttrId, AttrInput, RawAttrs},
+    name::Name,
+    ExpandTo, HirFileId, InFile,
+};
+use intern::{Internable, Interned};
+use la_arena::{Arena, Idx, IdxRange, RawIdx};
+use rustc_hash::{FxHashMap, FxHashSet};
+use sha2::{Digest, Sha256};
+use smallvec::SmallVec;
+use span::{Span, SpanAnchor, SyntaxContextId};
+use syntax::{SmolStr, TextRange, TextSize};
+
+use crate::{
+    generics::{
+        ConstParamData, GenericParams, LifetimeParamData, TypeOrConstParamData, TypeParamData,
+        TypeParamProvenance, WherePredicate, WherePredicateTypeTarget,
+    },
+    hir::type_ref::{ConstRef, LiteralConstRef},
+    item_tree::*,
+    path::{AssociatedTypeBinding, GenericArg, GenericArgs, ImportAlias, ModPath, Path, PathKind},
+    type_ref::{LifetimeRef, Mutability, TraitBoundModifier, TraitRef, TypeBound, TypeRef},
+    visibility::{RawVisibility, VisibilityExplicitness},
+};
+
+/// Bumped whenever the encoding changes, so that trees persisted by other versions are ignored.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// The hash of the text of a file.
+pub type ContentHash = [u8; 32];
+
+pub fn content_hash(text: &str) -> ContentHash {
+    Sha256::digest(text.as_bytes()).into()
+}
+
+/// Encoded item trees, by the hash of the text of the file they were lowered from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PersistedItemTrees {
+    trees: FxHashMap<ContentHash, Box<[u8]>>,
+    /// The caches the trees were read from, so that each one is only read once.
+    sources: FxHashSet<Box<str>>,
+}
+
+impl PersistedItemTrees {
+    pub fn new(trees: FxHashMap<ContentHash, Box<[u8]>>) -> Self {
+        PersistedItemTrees { trees, sources: FxHashSet::default() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trees.is_empty()
+    }
+
+    /// Whether the trees of the cache `source` were already added.
+    pub fn has_source(&self, source: &str) -> bool {
+        self.sources.contains(source)
+    }
+
+    /// Adds the trees read from the cache `source`.
+    pub fn extend(
+        &mut self,
+        source: &str,
+        trees: impl IntoIterator<Item = (ContentHash, Box<[u8]>)>,
+    ) {
+        self.sources.insert(source.into());
+        self.trees.extend(trees);
+    }
+
+    /// Decodes the persisted item tree of the file `file_id` with the text `text`, if any.
+    pub fn item_tree(&self, file_id: FileId, text: &str) -> Option<ItemTree> {
+        let encoded = self.trees.get(&content_hash(text))?;
+        decode(encoded, file_id)
+    }
+}
+
+/// Encodes the item tree of the file `file_id`, if it can be persisted.
+pub fn encode(tree: &ItemTree, file_id: FileId) -> Option<Vec<u8>> {
+    let mut e = Encoder { buf: Vec::new(), file_id };
+    tree.encode(&mut e)?;
+    Some(e.buf)
+}
+
+/// Decodes an item tree encoded with [`encode`] for the file `file_id`.
+pub fn decode(bytes: &[u8], file_id: FileId) -> Option<ItemTree> {
+    let mut d = Decoder { buf: bytes, file_id };
+    let tree = ItemTree::decode(&mut d)?;
+    d.buf.is_empty().then_some(tree)
+}
+
+struct Encoder {
+    buf: Vec<u8>,
+    file_id: FileId,
+}
+
+impl Encoder {
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    /// LEB128, most numbers in item trees are small.
+    fn u64(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.buf.push(byte);
+                return;
+            }
+            self.buf.push(byte | 0x80);
+        }
+    }
+
+    fn len(&mut self, len: usize) {
+        self.u64(len as u64);
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.len(bytes.len());
+        self.buf.extend_from_slice(bytes);
+    }
+}
+
+struct Decoder<'a> {
+    buf: &'a [u8],
+    file_id: FileId,
+}
+
+impl Decoder<'_> {
+    fn u8(&mut self) -> Option<u8> {
+        let (&first, rest) = self.buf.split_first()?;
+        self.buf = rest;
+        Some(first)
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn len(&mut self) -> Option<usize> {
+        let len = usize::try_from(self.u64()?).ok()?;
+        // Every element takes at least one byte, this stops corrupt lengths early.
+        (len <= self.buf.len()).then_some(len)
+    }
+
+    fn bytes(&mut self) -> Option<&[u8]> {
+        let len = self.len()?;
+        let (bytes, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Some(bytes)
+    }
+}
+
+trait Persist: Sized {
+    /// Returns `None` if the value can't be persisted.
+    fn encode(&self, e: &mut Encoder) -> Option<()>;
+    fn decode(d: &mut Decoder<'_>) -> Option<Self>;
+}
+
+macro_rules! persist_struct {
+    ($($ty:ident { $($field:ident),* $(,)? })*) => {$(
+        impl Persist for $ty {
+            fn encode(&self, e: &mut Encoder) -> Option<()> {
+                let $ty { $($field),* } = self;
+                $($field.encode(e)?;)*
+                Some(())
+            }
+
+            fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+                Some($ty { $($field: Persist::decode(d)?),* })
+            }
+        }
+    )*};
+}
+
+macro_rules! persist_enum {
+    ($($ty:ident {
+        $($tag:literal => $variant:ident $(( $($t:ident),* ))? $({ $($n:ident),* })?),* $(,)?
+    })*) => {$(
+        impl Persist for $ty {
+            fn encode(&self, e: &mut Encoder) -> Option<()> {
+                match self {
+                    $($ty::$variant $(( $($t),* ))? $({ $($n),* })? => {
+                        e.u8($tag);
+                        $($($t.encode(e)?;)*)?
+                        $($($n.encode(e)?;)*)?
+                    })*
+                }
+                Some(())
+            }
+
+            fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+                Some(match d.u8()? {
+                    $($tag => $ty::$variant
+                        $(( $({ let $t = Persist::decode(d)?; $t }),* ))?
+                        $({ $($n: Persist::decode(d)?),* })?,)*
+                    _ => return None,
+                })
+            }
+        }
+    )*};
+}
+
+impl Persist for u8 {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.u8(*self);
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        d.u8()
+    }
+}
+
+impl Persist for u32 {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.u64(u64::from(*self));
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        d.u64()?.try_into().ok()
+    }
+}
+
+impl Persist for u128 {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.buf.extend_from_slice(&self.to_le_bytes());
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let bytes = d.buf.get(..16)?.try_into().ok()?;
+        d.buf = &d.buf[16..];
+        Some(u128::from_le_bytes(bytes))
+    }
+}
+
+impl Persist for i128 {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        (*self as u128).encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(u128::decode(d)? as i128)
+    }
+}
+
+impl Persist for usize {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.len(*self);
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        d.u64()?.try_into().ok()
+    }
+}
+
+impl Persist for bool {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.u8(*self as u8);
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        match d.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl Persist for char {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        (*self as u32).encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        char::from_u32(u32::decode(d)?)
+    }
+}
+
+impl Persist for SmolStr {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.bytes(self.as_bytes());
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        std::str::from_utf8(d.bytes()?).ok().map(SmolStr::new)
+    }
+}
+
+impl<T: Persist> Persist for Option<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        match self {
+            None => e.u8(0),
+            Some(it) => {
+                e.u8(1);
+                it.encode(e)?;
+            }
+        }
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        match d.u8()? {
+            0 => Some(None),
+            1 => Some(Some(T::decode(d)?)),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Persist, B: Persist> Persist for (A, B) {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.0.encode(e)?;
+        self.1.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some((A::decode(d)?, B::decode(d)?))
+    }
+}
+
+fn encode_seq<'a, T: Persist + 'a>(
+    e: &mut Encoder,
+    items: impl ExactSizeIterator<Item = &'a T>,
+) -> Option<()> {
+    e.len(items.len());
+    items.into_iter().try_for_each(|it| it.encode(e))
+}
+
+fn decode_seq<T: Persist, C: FromIterator<T>>(d: &mut Decoder<'_>) -> Option<C> {
+    let len = d.len()?;
+    (0..len).map(|_| T::decode(d)).collect()
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        encode_seq(e, self.iter())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        decode_seq(d)
+    }
+}
+
+impl<T: Persist> Persist for Box<[T]> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        encode_seq(e, self.iter())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        decode_seq(d)
+    }
+}
+
+impl<T: Persist, const N: usize> Persist for SmallVec<[T; N]> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        encode_seq(e, self.iter())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        decode_seq(d)
+    }
+}
+
+impl<T: Persist> Persist for Box<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        (**self).encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        T::decode(d).map(Box::new)
+    }
+}
+
+impl<T: Persist + Internable> Persist for Interned<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        (**self).encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        T::decode(d).map(Interned::new)
+    }
+}
+
+impl Persist for Interned<str> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.bytes(self.as_bytes());
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        std::str::from_utf8(d.bytes()?).ok().map(Interned::new_str)
+    }
+}
+
+impl<T: Persist> Persist for Arena<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        encode_seq(e, self.values())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let len = d.len()?;
+        let mut arena = Arena::with_capacity(len);
+        for _ in 0..len {
+            arena.alloc(T::decode(d)?);
+        }
+        Some(arena)
+    }
+}
+
+impl<T> Persist for Idx<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.into_raw().into_u32().encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(Idx::from_raw(RawIdx::from_u32(u32::decode(d)?)))
+    }
+}
+
+impl<T> Persist for IdxRange<T> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.start().encode(e)?;
+        self.end().encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(IdxRange::new(Idx::decode(d)?..Idx::decode(d)?))
+    }
+}
+
+impl<N> Persist for FileItemTreeId<N> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.0.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Idx::decode(d).map(FileItemTreeId)
+    }
+}
+
+impl<N> Persist for Range<FileItemTreeId<N>> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.start.encode(e)?;
+        self.end.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(FileItemTreeId::decode(d)?..FileItemTreeId::decode(d)?)
+    }
+}
+
+impl<N: AstIdNode> Persist for FileAstId<N> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.erase().encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Idx::decode(d).map(FileAstId::from_erased)
+    }
+}
+
+/// Ids of nodes in other files, like in macro expansions, can't be persisted.
+impl<N: AstIdNode> Persist for InFile<FileAstId<N>> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        if self.file_id != HirFileId::from(e.file_id) {
+            return None;
+        }
+        self.value.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let file_id = d.file_id.into();
+        FileAstId::decode(d).map(|value| InFile::new(file_id, value))
+    }
+}
+
+impl Persist for Span {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        if self.anchor.file_id != e.file_id || !self.ctx.is_root() {
+            return None;
+        }
+        u32::from(self.range.start()).encode(e)?;
+        u32::from(self.range.end()).encode(e)?;
+        self.anchor.ast_id.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let start = TextSize::from(u32::decode(d)?);
+        let end = TextSize::from(u32::decode(d)?);
+        Some(Span {
+            range: (start <= end).then(|| TextRange::new(start, end))?,
+            anchor: SpanAnchor { file_id: d.file_id, ast_id: Idx::decode(d)? },
+            ctx: SyntaxContextId::ROOT,
+        })
+    }
+}
+
+impl Persist for Name {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        match (self.as_tuple_index(), self.as_text()) {
+            (Some(idx), _) => {
+                e.u8(0);
+                idx.encode(e)
+            }
+            (None, Some(text)) => {
+                e.u8(1);
+                text.encode(e)
+            }
+            (None, None) => None,
+        }
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        match d.u8()? {
+            0 => usize::decode(d).map(Name::new_tuple_field),
+            1 => SmolStr::decode(d).map(Name::new_text_dont_use),
+            _ => None,
+        }
+    }
+}
+
+impl Persist for PathKind {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        match self {
+            PathKind::Plain => e.u8(0),
+            PathKind::Super(n) => {
+                e.u8(1);
+                e.u8(*n);
+            }
+            PathKind::Crate => e.u8(2),
+            PathKind::Abs => e.u8(3),
+            // These only occur in macro expansions.
+            PathKind::DollarCrate(_) | PathKind::DefSite(_) => return None,
+        }
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(match d.u8()? {
+            0 => PathKind::Plain,
+            1 => PathKind::Super(d.u8()?),
+            2 => PathKind::Crate,
+            3 => PathKind::Abs,
+            _ => return None,
+        })
+    }
+}
+
+impl Persist for ModPath {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.kind.encode(e)?;
+        encode_seq(e, self.segments().iter())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let kind = PathKind::decode(d)?;
+        let segments: Vec<Name> = decode_seq(d)?;
+        Some(ModPath::from_segments(kind, segments))
+    }
+}
+
+impl Persist for Path {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        match self {
+            Path::Normal { type_anchor, mod_path, generic_args } => {
+                type_anchor.encode(e)?;
+                mod_path.encode(e)?;
+                generic_args.encode(e)
+            }
+            // Lang item paths are only created when lowering bodies.
+            Path::LangItem(..) => None,
+        }
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(Path::Normal {
+            type_anchor: Persist::decode(d)?,
+            mod_path: Persist::decode(d)?,
+            generic_args: Persist::decode(d)?,
+        })
+    }
+}
+
+impl Persist for AttrId {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.into_raw().encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        u32::decode(d).map(AttrId::from_raw)
+    }
+}
+
+impl Persist for RawAttrs {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        encode_seq(e, self.iter())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let attrs: Vec<Attr> = decode_seq(d)?;
+        Some(RawAttrs::from_attrs(attrs))
+    }
+}
+
+impl Persist for tt::Subtree<Span> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.delimiter.encode(e)?;
+        self.token_trees.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(tt::Subtree { delimiter: Persist::decode(d)?, token_trees: Persist::decode(d)? })
+    }
+}
+
+impl Persist for tt::Delimiter<Span> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.open.encode(e)?;
+        self.close.encode(e)?;
+        self.kind.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(tt::Delimiter {
+            open: Persist::decode(d)?,
+            close: Persist::decode(d)?,
+            kind: Persist::decode(d)?,
+        })
+    }
+}
+
+impl Persist for tt::TokenTree<Span> {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        match self {
+            tt::TokenTree::Leaf(tt::Leaf::Literal(tt::Literal { text, span })) => {
+                e.u8(0);
+                text.encode(e)?;
+                span.encode(e)
+            }
+            tt::TokenTree::Leaf(tt::Leaf::Punct(tt::Punct { char, spacing, span })) => {
+                e.u8(1);
+                char.encode(e)?;
+                spacing.encode(e)?;
+                span.encode(e)
+            }
+            tt::TokenTree::Leaf(tt::Leaf::Ident(tt::Ident { text, span })) => {
+                e.u8(2);
+                text.encode(e)?;
+                span.encode(e)
+            }
+            tt::TokenTree::Subtree(subtree) => {
+                e.u8(3);
+                subtree.encode(e)
+            }
+        }
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(match d.u8()? {
+            0 => tt::Leaf::Literal(tt::Literal {
+                text: Persist::decode(d)?,
+                span: Persist::decode(d)?,
+            })
+            .into(),
+            1 => tt::Leaf::Punct(tt::Punct {
+                char: Persist::decode(d)?,
+                spacing: Persist::decode(d)?,
+                span: Persist::decode(d)?,
+            })
+            .into(),
+            2 => {
+                tt::Leaf::Ident(tt::Ident { text: Persist::decode(d)?, span: Persist::decode(d)? })
+                    .into()
+            }
+            3 => tt::TokenTree::Subtree(Persist::decode(d)?),
+            _ => return None,
+        })
+    }
+}
+
+impl Persist for FnFlags {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.bits().encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        FnFlags::from_bits(d.u8()?)
+    }
+}
+
+impl Persist for RawVisibilityId {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        self.0.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        u32::decode(d).map(RawVisibilityId)
+    }
+}
+
+impl Persist for ItemTree {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        let ItemTree { _c: _, top_level, attrs, data } = self;
+        top_level.encode(e)?;
+        e.len(attrs.len());
+        for (owner, attrs) in attrs {
+            owner.encode(e)?;
+            attrs.encode(e)?;
+        }
+        data.encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        let top_level = Persist::decode(d)?;
+        let len = d.len()?;
+        let mut attrs = FxHashMap::default();
+        for _ in 0..len {
+            attrs.insert(AttrOwner::decode(d)?, RawAttrs::decode(d)?);
+        }
+        Some(ItemTree { _c: Default::default(), top_level, attrs, data: Persist::decode(d)? })
+    }
+}
+
+persist_struct! {
+    ItemTreeData {
+        uses, extern_crates, extern_blocks, functions, params, structs, fields, unions, enums,
+        variants, consts, statics, traits, trait_aliases, impls, type_aliases, mods, macro_calls,
+        macro_rules, macro_defs, vis,
+    }
+    ItemVisibilities { arena }
+    Use { visibility, ast_id, use_tree }
+    UseTree { index, kind }
+    ExternCrate { name, alias, visibility, ast_id }
+    ExternBlock { abi, ast_id, children }
+    Function {
+        name, visibility, explicit_generic_params, abi, params, ret_type, ast_id, flags,
+    }
+    Param { type_ref, ast_id }
+    Struct { name, visibility, generic_params, fields, ast_id }
+    Union { name, visibility, generic_params, fields, ast_id }
+    Enum { name, visibility, generic_params, variants, ast_id }
+    Variant { name, fields, ast_id }
+    Field { name, type_ref, visibility, ast_id }
+    Const { name, visibility, type_ref, ast_id }
+    Static { name, visibility, mutable, type_ref, ast_id }
+    Trait { name, visibility, generic_params, is_auto, is_unsafe, items, ast_id }
+    TraitAlias { name, visibility, generic_params, ast_id }
+    Impl { generic_params, target_trait, self_ty, is_negative, is_unsafe, items, ast_id }
+    TypeAlias { name, visibility, bounds, generic_params, type_ref, ast_id }
+    Mod { name, visibility, kind, ast_id }
+    MacroCall { path, ast_id, expand_to, call_site }
+    MacroRules { name, ast_id }
+    Macro2 { name, visibility, ast_id }
+    Attr { id, path, input, span }
+    GenericParams { type_or_consts, lifetimes, where_predicates }
+    TypeParamData { name, default, provenance }
+    LifetimeParamData { name }
+    ConstParamData { name, ty, default }
+    GenericArgs { args, has_self_type, bindings, desugared_from_fn }
+    AssociatedTypeBinding { name, args, type_ref, bounds }
+    TraitRef { path }
+    LifetimeRef { name }
+}
+
+persist_enum! {
+    ModItem {
+        0 => Use(it), 1 => ExternCrate(it), 2 => ExternBlock(it), 3 => Function(it),
+        4 => Struct(it), 5 => Union(it), 6 => Enum(it), 7 => Const(it), 8 => Static(it),
+        9 => Trait(it), 10 => TraitAlias(it), 11 => Impl(it), 12 => TypeAlias(it), 13 => Mod(it),
+        14 => MacroCall(it), 15 => MacroRules(it), 16 => Macro2(it),
+    }
+    AttrOwner {
+        0 => ModItem(it), 1 => TopLevel, 2 => Variant(it), 3 => Field(it), 4 => Param(it),
+        5 => TypeOrConstParamData(it), 6 => LifetimeParamData(it),
+    }
+    AssocItem { 0 => Function(it), 1 => TypeAlias(it), 2 => Const(it), 3 => MacroCall(it) }
+    UseTreeKind {
+        0 => Single { path, alias },
+        1 => Glob { path },
+        2 => Prefixed { prefix, list },
+    }
+    ParamAstId { 0 => Param(it), 1 => SelfParam(it) }
+    Fields { 0 => Record(it), 1 => Tuple(it), 2 => Unit }
+    FieldAstId { 0 => Record(it), 1 => Tuple(it) }
+    ModKind { 0 => Inline { items }, 1 => Outline }
+    ImportAlias { 0 => Underscore, 1 => Alias(it) }
+    RawVisibility { 0 => Module(path, explicitness), 1 => Public }
+    VisibilityExplicitness { 0 => Explicit, 1 => Implicit }
+    ExpandTo { 0 => Statements, 1 => Items, 2 => Pattern, 3 => Type, 4 => Expr }
+    AttrInput { 0 => Literal(it), 1 => TokenTree(it) }
+    TypeOrConstParamData { 0 => TypeParamData(it), 1 => ConstParamData(it) }
+    TypeParamProvenance { 0 => TypeParamList, 1 => TraitSelf, 2 => ArgumentImplTrait }
+    WherePredicate {
+        0 => TypeBound { target, bound },
+        1 => Lifetime { target, bound },
+        2 => ForLifetime { lifetimes, target, bound },
+    }
+    WherePredicateTypeTarget { 0 => TypeRef(it), 1 => TypeOrConstParam(it) }
+    TypeRef {
+        0 => Never, 1 => Placeholder, 2 => Tuple(types), 3 => Path(path),
+        4 => RawPtr(ty, mutability), 5 => Reference(ty, lifetime, mutability),
+        6 => Array(ty, len), 7 => Slice(ty), 8 => Fn(params, is_varargs, is_unsafe, abi),
+        9 => ImplTrait(bounds), 10 => DynTrait(bounds), 11 => Macro(call), 12 => Error,
+    }
+    Mutability { 0 => Shared, 1 => Mut }
+    TypeBound {
+        0 => Path(path, modifier), 1 => ForLifetime(lifetimes, path), 2 => Lifetime(it),
+        3 => Error,
+    }
+    TraitBoundModifier { 0 => None, 1 => Maybe }
+    GenericArg { 0 => Type(it), 1 => Lifetime(it), 2 => Const(it) }
+    ConstRef { 0 => Scalar(it), 1 => Path(it), 2 => Complex(it) }
+    LiteralConstRef { 0 => Int(it), 1 => UInt(it), 2 => Bool(it), 3 => Char(it), 4 => Unknown }
+}
+
+impl Persist for tt::DelimiterKind {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        e.u8(match self {
+            tt::DelimiterKind::Parenthesis => 0,
+            tt::DelimiterKind::Brace => 1,
+            tt::DelimiterKind::Bracket => 2,
+            tt::DelimiterKind::Invisible => 3,
+        });
+        Some(())
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(match d.u8()? {
+            0 => tt::DelimiterKind::Parenthesis,
+            1 => tt::DelimiterKind::Brace,
+            2 => tt::DelimiterKind::Bracket,
+            3 => tt::DelimiterKind::Invisible,
+            _ => return None,
+        })
+    }
+}
+
+impl Persist for tt::Spacing {
+    fn encode(&self, e: &mut Encoder) -> Option<()> {
+        (*self == tt::Spacing::Joint).encode(e)
+    }
+
+    fn decode(d: &mut Decoder<'_>) -> Option<Self> {
+        Some(if bool::decode(d)? { tt::Spacing::Joint } else { tt::Spacing::Alone })
+    }
+}
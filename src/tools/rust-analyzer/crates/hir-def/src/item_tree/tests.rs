@@ -1,7 +1,13 @@
+use base_db::SourceDatabaseExt;
 use expect_test::{expect, Expect};
 use test_fixture::WithFixture;
+use triomphe::Arc;
 
-use crate::{db::DefDatabase, test_db::TestDB};
+use crate::{
+    db::DefDatabase,
+    item_tree::persist::{self, PersistedItemTrees},
+    test_db::TestDB,
+};
 
 fn check(ra_fixture: &str, expect: Expect) {
     let (db, file_id) = TestDB::with_single_file(ra_fixture);
@@ -447,3 +453,71 @@ fn pub_self() {
         "#]],
     )
 }
+
+#[test]
+fn persisted_item_trees_roundtrip() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+#![cfg_attr(feature = "std", doc = "docs")]
+extern crate self as this;
+pub(crate) use a::{b::{self, *}, c as _};
+
+/// Docs.
+#[derive(Debug)]
+pub struct Record<'a, T: ?Sized + Trait<Assoc = u8>, const N: usize = 3>
+where
+    for<'b> &'b T: Fn(i32) -> bool,
+{
+    pub(super) field: &'a mut [T; N],
+    #[cfg(test)]
+    raw: *const dyn Trait + 'a,
+}
+struct Tuple(pub u8, (), !);
+union U { a: u32, b: f32 }
+enum E { A, B(u8), C { c: impl Sized } }
+const C: i128 = -1;
+static mut S: Option<fn(&str) -> _> = None;
+pub unsafe trait Trait<T = Self>: Sized {
+    type Assoc: Copy;
+    const C: usize;
+    fn method(&self, _: T) -> <Self as Trait>::Assoc;
+    m!();
+}
+impl<T> !Send for Record<'_, T> {}
+type Alias<T> = [T];
+mod inline {
+    pub(in crate::inline) extern "C" {
+        fn foreign(x: i32, ...) -> i32;
+    }
+}
+macro_rules! m { () => { $crate::x!() }; }
+pub macro m2($e:expr) { $e }
+m!(a + b);
+pub async const unsafe fn f<T: ?Sized>(self: Box<Self>, x: [u8; { 1 + 1 }]) {}
+        "#,
+    );
+    let item_tree = db.file_item_tree(file_id.into());
+    let encoded = persist::encode(&item_tree, file_id).expect("item tree can't be encoded");
+    let decoded = persist::decode(&encoded, file_id).expect("item tree can't be decoded");
+    assert_eq!(*item_tree, decoded);
+    assert_eq!(persist::decode(&encoded[..encoded.len() - 1], file_id), None);
+}
+
+#[test]
+fn persisted_item_trees_are_used() {
+    let (mut db, file_id) = TestDB::with_single_file("struct Lowered;");
+    let persisted = TestDB::with_single_file("struct Persisted;");
+    let persisted_tree = persisted.0.file_item_tree(persisted.1.into());
+    let encoded = persist::encode(&persisted_tree, file_id).unwrap();
+
+    let text = db.file_text(file_id);
+    db.set_persisted_item_trees(Arc::new(PersistedItemTrees::new(
+        [(persist::content_hash(&text), encoded.into_boxed_slice())].into_iter().collect(),
+    )));
+    let item_tree = db.file_item_tree(file_id.into());
+    assert_eq!(*item_tree, *persisted_tree);
+
+    db.set_file_text(file_id, Arc::from("struct Changed;"));
+    let item_tree = db.file_item_tree(file_id.into());
+    assert!(item_tree.pretty_print(&db).contains("struct Changed"));
+}
@@ -62,7 +62,7 @@
 use base_db::{CrateId, Edition, FileId};
 use hir_expand::{
     ast_id_map::FileAstId, name::Name, proc_macro::ProcMacroKind, HirFileId, InFile, MacroCallId,
-    MacroDefId,
+    MacroDefId, MacroDefKind,
 };
 use itertools::Itertools;
 use la_arena::Arena;
@@ -119,6 +119,10 @@ pub struct DefMap {
     /// attributes.
     derive_helpers_in_scope: FxHashMap<AstId<ast::Item>, Vec<(Name, MacroId, MacroCallId)>>,
 
+    /// The modules `macro` (macros 2.0) definitions in this map are declared in, which is where
+    /// the identifiers they produce are resolved.
+    macro2_def_sites: FxHashMap<AstId<ast::Macro>, LocalModuleId>,
+
     /// The diagnostics that need to be emitted for this crate.
     diagnostics: Vec<DefDiagnostic>,
 
@@ -371,6 +375,7 @@ fn empty(krate: CrateId, edition: Edition, module_data: ModuleData) -> DefMap {
             prelude: None,
             macro_use_prelude: FxHashMap::default(),
             derive_helpers_in_scope: FxHashMap::default(),
+            macro2_def_sites: FxHashMap::default(),
             diagnostics: Vec::new(),
             enum_definitions: FxHashMap::default(),
             data: Arc::new(DefMapCrateData {
@@ -488,6 +493,22 @@ pub(crate) fn resolve_path(
         (res.resolved_def, res.segment_index)
     }
 
+    /// Returns the module the `macro` definition invoked by `call` is declared in.
+    fn macro2_def_site(&self, db: &dyn DefDatabase, call: MacroCallId) -> Option<ModuleId> {
+        let def = db.lookup_intern_macro_call(call).def;
+        let MacroDefKind::Declarative(ast_id) = def.kind else { return None };
+        if def.krate == self.krate {
+            self.with_ancestor_maps(db, Self::ROOT, &mut |def_map, _| {
+                let &local_id = def_map.macro2_def_sites.get(&ast_id)?;
+                Some(def_map.module_id(local_id))
+            })
+        } else {
+            let def_map = db.crate_def_map(def.krate);
+            let &local_id = def_map.macro2_def_sites.get(&ast_id)?;
+            Some(def_map.module_id(local_id))
+        }
+    }
+
     pub(crate) fn resolve_path_locally(
         &self,
         db: &dyn DefDatabase,
@@ -611,6 +632,7 @@ fn shrink_to_fit(&mut self) {
             diagnostics,
             modules,
             derive_helpers_in_scope,
+            macro2_def_sites,
             block: _,
             krate: _,
             prelude: _,
@@ -622,6 +644,7 @@ fn shrink_to_fit(&mut self) {
         diagnostics.shrink_to_fit();
         modules.shrink_to_fit();
         derive_helpers_in_scope.shrink_to_fit();
+        macro2_def_sites.shrink_to_fit();
         enum_definitions.shrink_to_fit();
         for (_, module) in modules.iter_mut() {
             module.children.shrink_to_fit();
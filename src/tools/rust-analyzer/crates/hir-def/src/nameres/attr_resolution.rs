@@ -14,7 +14,7 @@
     db::DefDatabase,
     item_scope::BuiltinShadowMode,
     nameres::path_resolution::ResolveMode,
-    path::{self, ModPath, PathKind},
+    path::{self, ModPath},
     AstIdWithPath, LocalModuleId, MacroId, UnresolvedMacro,
 };
 
@@ -72,7 +72,7 @@ pub(crate) fn resolve_attr_macro(
     }
 
     pub(crate) fn is_builtin_or_registered_attr(&self, path: &ModPath) -> bool {
-        if path.kind != PathKind::Plain {
+        if !path.kind.is_plain() {
             return false;
         }
 
@@ -2289,6 +2289,10 @@ fn collect_macro_def(&mut self, id: FileItemTreeId<Macro2>, module: ModuleId) {
             edition: self.def_collector.def_map.data.edition,
         }
         .intern(self.def_collector.db);
+        self.def_collector
+            .def_map
+            .macro2_def_sites
+            .insert(InFile::new(self.file_id(), mac.ast_id.upcast()), self.module_id);
         self.def_collector.define_macro_def(
             self.module_id,
             mac.name.clone(),
@@ -134,6 +134,19 @@ pub(super) fn resolve_path_fp_with_macro(
         // resolving them to. Pass `None` otherwise, e.g. when we're resolving import paths.
         expected_macro_subns: Option<MacroSubNs>,
     ) -> ResolvePathResult {
+        if let PathKind::DefSite(_) = path.kind {
+            // Def-site paths are resolved relative to the macro definition, independent of the
+            // block we start in.
+            return self.resolve_path_fp_with_macro_single(
+                db,
+                mode,
+                original_module,
+                path,
+                shadow,
+                expected_macro_subns,
+            );
+        }
+
         let mut result = ResolvePathResult::empty(ReachedFixedPoint::No);
 
         let mut arc;
@@ -197,6 +210,46 @@ pub(super) fn resolve_path_fp_with_macro_single(
                 }
             }
             PathKind::Crate => PerNs::types(self.crate_root().into(), Visibility::Public, None),
+            PathKind::DefSite(call) => {
+                let plain =
+                    ModPath::from_segments(PathKind::Plain, path.segments().iter().cloned());
+                if let Some(def_site) = self.macro2_def_site(db, call) {
+                    let result =
+                        if def_site.krate == self.krate && def_site.block == self.block_id() {
+                            self.resolve_path_fp_with_macro(
+                                db,
+                                mode,
+                                def_site.local_id,
+                                &plain,
+                                shadow,
+                                expected_macro_subns,
+                            )
+                        } else {
+                            def_site.def_map(db).resolve_path_fp_with_macro(
+                                db,
+                                mode,
+                                def_site.local_id,
+                                &plain,
+                                shadow,
+                                expected_macro_subns,
+                            )
+                        };
+                    if !result.resolved_def.is_none() {
+                        return result;
+                    }
+                }
+                // Items the expansion defines itself live in the invoking module, so fall back to
+                // resolving there.
+                cov_mark::hit!(macro2_def_site_fallback);
+                return self.resolve_path_fp_with_macro(
+                    db,
+                    mode,
+                    original_module,
+                    &plain,
+                    shadow,
+                    expected_macro_subns,
+                );
+            }
             // plain import or absolute path in 2015: crate-relative with
             // fallback to extern prelude (with the simplification in
             // rust-lang/rust#57745)
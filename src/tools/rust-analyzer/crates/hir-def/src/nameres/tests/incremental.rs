@@ -254,7 +254,8 @@ fn quux() { 1$0 }
             let (_, module_data) = crate_def_map.modules.iter().last().unwrap();
             assert_eq!(module_data.scope.resolutions().count(), 4);
         });
-        let n_recalculated_item_trees = events.iter().filter(|it| it.contains("item_tree")).count();
+        let n_recalculated_item_trees =
+            events.iter().filter(|it| it.contains("file_item_tree")).count();
         assert_eq!(n_recalculated_item_trees, 6);
         let n_reparsed_macros =
             events.iter().filter(|it| it.contains("parse_macro_expansion(")).count();
@@ -275,7 +276,8 @@ fn quux() { 92 }
             let (_, module_data) = crate_def_map.modules.iter().last().unwrap();
             assert_eq!(module_data.scope.resolutions().count(), 4);
         });
-        let n_recalculated_item_trees = events.iter().filter(|it| it.contains("item_tree")).count();
+        let n_recalculated_item_trees =
+            events.iter().filter(|it| it.contains("file_item_tree")).count();
         assert_eq!(n_recalculated_item_trees, 1);
         let n_reparsed_macros =
             events.iter().filter(|it| it.contains("parse_macro_expansion(")).count();
@@ -309,7 +311,8 @@ impl Tr for () {}
         let events = db.log_executed(|| {
             db.file_item_tree(pos.file_id.into());
         });
-        let n_calculated_item_trees = events.iter().filter(|it| it.contains("item_tree")).count();
+        let n_calculated_item_trees =
+            events.iter().filter(|it| it.contains("file_item_tree")).count();
         assert_eq!(n_calculated_item_trees, 1);
         let n_parsed_files = events.iter().filter(|it| it.contains("parse(")).count();
         assert_eq!(n_parsed_files, 1);
@@ -353,3 +356,23 @@ impl Tr for () {}
         assert_eq!(n_reparsed_files, 0);
     }
 }
+
+#[test]
+fn crate_graph_changes_do_not_reparse_files() {
+    let (mut db, file_id) = TestDB::with_single_file(
+        r"
+//- /lib.rs crate:foo edition:2021
+fn foo() -> i32 { 92 }
+",
+    );
+    db.parse(file_id);
+
+    let crate_graph = (*db.crate_graph()).clone();
+    db.set_crate_graph(Arc::new(crate_graph));
+
+    let events = db.log_executed(|| {
+        db.parse(file_id);
+    });
+    let n_reparsed_files = events.iter().filter(|it| it.contains("parse(")).count();
+    assert_eq!(n_reparsed_files, 0, "{events:#?}");
+}
@@ -1401,6 +1401,50 @@ macro_rules! bar {
     );
 }
 
+#[test]
+fn macro2_paths_resolve_at_def_site() {
+    check(
+        r#"
+//- /main.rs crate:main deps:lib
+lib::make!();
+
+//- /lib.rs crate:lib
+mod helpers {
+    pub struct Thing;
+}
+pub macro make() {
+    use helpers::Thing as Made;
+}
+"#,
+        expect![[r#"
+            crate
+            Made: ti vi
+        "#]],
+    );
+}
+
+#[test]
+fn macro2_paths_fall_back_to_call_site() {
+    cov_mark::check!(macro2_def_site_fallback);
+    check(
+        r#"
+//- /main.rs crate:main deps:lib
+lib::make!();
+
+//- /lib.rs crate:lib
+pub macro make() {
+    struct Local;
+    use Local as Alias;
+}
+"#,
+        expect![[r#"
+            crate
+            Alias: ti vi
+            Local: t v
+        "#]],
+    );
+}
+
 #[test]
 fn macro_use_prelude_is_eagerly_expanded() {
     // See FIXME in `ModCollector::collect_macro_call()`.
@@ -5,7 +5,7 @@
 use crate::{lower::LowerCtx, type_ref::ConstRef};
 
 use hir_expand::{
-    mod_path::resolve_crate_root,
+    mod_path::{plain_path_kind, resolve_crate_root},
     name::{name, AsName},
 };
 use intern::Interned;
@@ -41,6 +41,9 @@ pub(super) fn lower_path(ctx: &LowerCtx<'_>, mut path: ast::Path) -> Option<Path
                     .map(PathKind::DollarCrate)
                     .unwrap_or(PathKind::Crate);
                 }
+                if qualifier(&path).is_none() && kind == PathKind::Plain {
+                    kind = plain_path_kind(ctx.db.upcast(), span_map, &name_ref);
+                }
                 let name = name_ref.as_name();
                 let args = segment
                     .generic_arg_list()
@@ -56,7 +56,7 @@ pub(crate) fn print_path(db: &dyn DefDatabase, path: &Path, buf: &mut dyn Write)
             write!(buf, ">::")?;
         }
         None => match path.kind() {
-            PathKind::Plain => {}
+            PathKind::Plain | PathKind::DefSite(_) => {}
             PathKind::Super(0) => write!(buf, "self")?,
             PathKind::Super(n) => {
                 for i in 0..*n {
@@ -74,7 +74,7 @@ pub(crate) fn print_path(db: &dyn DefDatabase, path: &Path, buf: &mut dyn Write)
     }
 
     for (i, segment) in path.segments().iter().enumerate() {
-        if i != 0 || !matches!(path.kind(), PathKind::Plain) {
+        if i != 0 || !path.kind().is_plain() {
             write!(buf, "::")?;
         }
 
@@ -22,7 +22,7 @@
     item_scope::{BuiltinShadowMode, ImportId, ImportOrExternCrate, BUILTIN_SCOPE},
     lang_item::LangItemTarget,
     nameres::{DefMap, MacroSubNs},
-    path::{ModPath, Path, PathKind},
+    path::{ModPath, Path},
     per_ns::PerNs,
     visibility::{RawVisibility, Visibility},
     AdtId, ConstId, ConstParamId, CrateRootModuleId, DefWithBodyId, EnumId, EnumVariantId,
@@ -175,7 +175,7 @@ pub fn resolve_path_in_type_ns(
             }
         };
         let first_name = path.segments().first()?;
-        let skip_to_mod = path.kind != PathKind::Plain;
+        let skip_to_mod = !path.kind.is_plain();
         if skip_to_mod {
             return self.module_scope.resolve_path_in_type_ns(db, path);
         }
@@ -290,7 +290,7 @@ pub fn resolve_path_in_value_ns(
         let n_segments = path.segments().len();
         let tmp = name![self];
         let first_name = if path.is_self() { &tmp } else { path.segments().first()? };
-        let skip_to_mod = path.kind != PathKind::Plain && !path.is_self();
+        let skip_to_mod = !path.kind.is_plain() && !path.is_self();
         if skip_to_mod {
             return self.module_scope.resolve_path_in_value_ns(db, path);
         }
@@ -376,7 +376,7 @@ pub fn resolve_path_in_value_ns(
         // If a path of the shape `u16::from_le_bytes` failed to resolve at all, then we fall back
         // to resolving to the primitive type, to allow this to still work in the presence of
         // `use core::u16;`.
-        if path.kind == PathKind::Plain && n_segments > 1 {
+        if path.kind.is_plain() && n_segments > 1 {
             if let Some(builtin) = BuiltinType::by_name(first_name) {
                 return Some(ResolveValueResult::Partial(TypeNs::BuiltinType(builtin), 1, None));
             }
@@ -91,6 +91,11 @@ pub fn upcast<M: AstIdNode>(self) -> FileAstId<M>
     pub fn erase(self) -> ErasedFileAstId {
         self.raw
     }
+
+    /// Restores an id erased with [`FileAstId::erase`]. The id has to point to a node of type `N`.
+    pub fn from_erased(raw: ErasedFileAstId) -> Self {
+        FileAstId { raw, covariant: PhantomData }
+    }
 }
 
 pub trait AstIdNode: AstNode {}
@@ -61,6 +61,11 @@ pub fn new(
         Self { entries: if entries.is_empty() { None } else { Some(entries) } }
     }
 
+    pub fn from_attrs(attrs: impl IntoIterator<Item = Attr>) -> Self {
+        let entries: Arc<[Attr]> = Arc::from_iter(attrs);
+        Self { entries: if entries.is_empty() { None } else { Some(entries) } }
+    }
+
     pub fn from_attrs_owner(
         db: &dyn ExpandDatabase,
         owner: InFile<&dyn ast::HasAttrs>,
@@ -166,6 +171,14 @@ pub fn cfg_attr_index(&self) -> Option<usize> {
     pub fn with_cfg_attr(self, idx: usize) -> AttrId {
         AttrId { id: self.id | (idx as u32) << Self::AST_INDEX_BITS | Self::CFG_ATTR_SET_BITS }
     }
+
+    pub fn from_raw(id: u32) -> AttrId {
+        AttrId { id }
+    }
+
+    pub fn into_raw(self) -> u32 {
+        self.id
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
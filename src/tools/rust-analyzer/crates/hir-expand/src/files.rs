@@ -5,7 +5,10 @@
 use span::{FileId, FileRange, HirFileId, HirFileIdRepr, MacroFileId, SyntaxContextId};
 use syntax::{AstNode, SyntaxNode, SyntaxToken, TextRange, TextSize};
 
-use crate::{db, map_node_range_up, span_for_offset, MacroFileIdExt};
+use crate::{
+    db, hygiene::marks_rev, map_node_range_up, map_node_range_up_rooted, span_for_offset,
+    MacroFileIdExt,
+};
 
 /// `InFile<T>` stores a value of `T` inside a particular file/syntax tree.
 ///
@@ -130,6 +133,10 @@ pub fn ancestors_with_macros_skip_attr_item(
                     // macro call was an attributed item, skip it
                     // FIXME: does this fail if this is a direct expansion of another macro?
                     parent_node.map(|node| node.parent()).transpose()
+                } else if macro_file_id.is_builtin_derive(db) || macro_file_id.is_custom_derive(db)
+                {
+                    // macro call was a derive of the item owning the attribute, skip both
+                    parent_node.map(|node| node.parent().and_then(|it| it.parent())).transpose()
                 } else {
                     Some(parent_node)
                 }
@@ -155,6 +162,9 @@ pub fn original_file_range(self, db: &dyn db::ExpandDatabase) -> FileRange {
                         return res;
                     }
                 }
+                if let Some(res) = map_attr_range_up_rooted(db, mac_file, self.value.text_range()) {
+                    return res;
+                }
                 // Fall back to whole macro call.
                 let loc = db.lookup_intern_macro_call(mac_file.macro_call_id);
                 loc.kind.original_call_range(db)
@@ -326,10 +336,10 @@ pub fn original_node_file_range_rooted(self, db: &dyn db::ExpandDatabase) -> Fil
             HirFileIdRepr::MacroFile(mac_file) => {
                 match map_node_range_up(db, &db.expansion_span_map(mac_file), self.value) {
                     Some((it, SyntaxContextId::ROOT)) => it,
-                    _ => {
+                    _ => map_attr_range_up_rooted(db, mac_file, self.value).unwrap_or_else(|| {
                         let loc = db.lookup_intern_macro_call(mac_file.macro_call_id);
                         loc.kind.original_call_range(db)
-                    }
+                    }),
                 }
             }
         }
@@ -348,6 +358,23 @@ pub fn original_node_file_range_opt(
             }
         }
     }
+
+    /// Returns whether the range lies in an attribute macro expansion and only covers tokens the
+    /// attribute macro generated itself, as opposed to the user-written ones it passed through.
+    pub fn is_attr_macro_generated(self, db: &dyn db::ExpandDatabase) -> bool {
+        let HirFileIdRepr::MacroFile(mac_file) = self.file_id.repr() else {
+            return false;
+        };
+        if !mac_file.is_attr_macro(db) || mac_file.is_derive_attr_pseudo_expansion(db) {
+            return false;
+        }
+        let span_map = db.expansion_span_map(mac_file);
+        let mut spans = span_map.spans_for_range(self.value).peekable();
+        spans.peek().is_some()
+            && spans.all(|span| {
+                marks_rev(span.ctx, db).any(|(expn, _)| expn == Some(mac_file.macro_call_id))
+            })
+    }
 }
 
 impl<N: AstNode> InFile<N> {
@@ -382,3 +409,16 @@ pub fn original_ast_node_rooted(self, db: &dyn db::ExpandDatabase) -> Option<InR
         Some(InRealFile::new(file_id, value))
     }
 }
+
+/// Attribute macros pass most of the user-written tokens through, so nodes that mix them with
+/// generated tokens are mapped up to the written tokens they cover.
+fn map_attr_range_up_rooted(
+    db: &dyn db::ExpandDatabase,
+    mac_file: MacroFileId,
+    range: TextRange,
+) -> Option<FileRange> {
+    if !mac_file.is_attr_macro(db) {
+        return None;
+    }
+    map_node_range_up_rooted(db, &db.expansion_span_map(mac_file), range)
+}
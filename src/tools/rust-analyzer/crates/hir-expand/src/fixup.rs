@@ -358,7 +358,7 @@ fn reverse_fixups_(tt: &mut Subtree, undo_info: &[Subtree]) {
 
 #[cfg(test)]
 mod tests {
-    use base_db::FileId;
+    use base_db::{Edition, FileId};
     use expect_test::{expect, Expect};
     use syntax::TextRange;
     use triomphe::Arc;
@@ -417,7 +417,8 @@ fn check(ra_fixture: &str, mut expect: Expect) {
         expect.assert_eq(&actual);
 
         // the fixed-up tree should be syntactically valid
-        let (parse, _) = mbe::token_tree_to_syntax_node(&tt, ::mbe::TopEntryPoint::MacroItems);
+        let (parse, _) =
+            mbe::token_tree_to_syntax_node(&tt, ::mbe::TopEntryPoint::MacroItems, Edition::CURRENT);
         assert!(
             parse.errors().is_empty(),
             "parse has syntax errors. parse tree:\n{:#?}",
@@ -822,6 +822,32 @@ pub fn map_node_range_up(
     ))
 }
 
+/// Maps up the parts of the text range that were written at the root context, ignoring the tokens
+/// macros generated themselves, and returns the range covering them.
+///
+/// Returns `None` if the range consists of generated tokens only, or if the written ones come from
+/// different files.
+pub fn map_node_range_up_rooted(
+    db: &dyn ExpandDatabase,
+    exp_map: &ExpansionSpanMap,
+    range: TextRange,
+) -> Option<FileRange> {
+    let mut res: Option<FileRange> = None;
+    for Span { range, anchor, .. } in exp_map.spans_for_range(range).filter(|it| it.ctx.is_root()) {
+        let anchor_offset =
+            db.ast_id_map(anchor.file_id.into()).get_erased(anchor.ast_id).text_range().start();
+        let range = range + anchor_offset;
+        res = Some(match res {
+            None => FileRange { file_id: anchor.file_id, range },
+            Some(it) if it.file_id == anchor.file_id => {
+                FileRange { file_id: it.file_id, range: it.range.cover(range) }
+            }
+            Some(_) => return None,
+        });
+    }
+    res
+}
+
 /// Looks up the span at the given offset.
 pub fn span_for_offset(
     db: &dyn ExpandDatabase,
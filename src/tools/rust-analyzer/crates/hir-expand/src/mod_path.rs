@@ -10,7 +10,7 @@
     hygiene::{marks_rev, SyntaxContextExt, Transparency},
     name::{known, AsName, Name},
     span_map::SpanMapRef,
-    tt,
+    tt, MacroCallId, MacroDefKind,
 };
 use base_db::CrateId;
 use smallvec::SmallVec;
@@ -43,6 +43,16 @@ pub enum PathKind {
     // FIXME: Can we remove this somehow?
     /// `$crate` from macro expansion
     DollarCrate(CrateId),
+    /// A plain path produced by the body of a `macro` (macros 2.0) definition, which resolves at
+    /// the definition site of the macro invoked by the given call.
+    DefSite(MacroCallId),
+}
+
+impl PathKind {
+    /// Returns whether this is a plain path, i.e. one without any leading special segment.
+    pub fn is_plain(self) -> bool {
+        matches!(self, PathKind::Plain | PathKind::DefSite(_))
+    }
 }
 
 impl ModPath {
@@ -86,7 +96,7 @@ pub fn pop_segment(&mut self) -> Option<Name> {
     pub fn len(&self) -> usize {
         self.segments.len()
             + match self.kind {
-                PathKind::Plain => 0,
+                PathKind::Plain | PathKind::DefSite(_) => 0,
                 PathKind::Super(i) => i as usize,
                 PathKind::Crate => 1,
                 PathKind::Abs => 0,
@@ -96,7 +106,7 @@ pub fn len(&self) -> usize {
 
     pub fn textual_len(&self) -> usize {
         let base = match self.kind {
-            PathKind::Plain => 0,
+            PathKind::Plain | PathKind::DefSite(_) => 0,
             PathKind::Super(0) => "self".len(),
             PathKind::Super(i) => "super".len() * i as usize,
             PathKind::Crate => "crate".len(),
@@ -119,13 +129,12 @@ pub fn is_self(&self) -> bool {
 
     #[allow(non_snake_case)]
     pub fn is_Self(&self) -> bool {
-        self.kind == PathKind::Plain
-            && matches!(&*self.segments, [name] if *name == known::SELF_TYPE)
+        self.kind.is_plain() && matches!(&*self.segments, [name] if *name == known::SELF_TYPE)
     }
 
     /// If this path is a single identifier, like `foo`, return its name.
     pub fn as_ident(&self) -> Option<&Name> {
-        if self.kind != PathKind::Plain {
+        if !self.kind.is_plain() {
             return None;
         }
 
@@ -187,7 +196,7 @@ fn display_fmt_path(
         Ok(())
     };
     match path.kind {
-        PathKind::Plain => {}
+        PathKind::Plain | PathKind::DefSite(_) => {}
         PathKind::Super(0) => add_segment("self")?,
         PathKind::Super(n) => {
             for _ in 0..n {
@@ -232,9 +241,11 @@ fn convert_path(
                     .unwrap_or(PathKind::Crate),
                 )
             } else {
-                let mut res = ModPath::from_kind(
-                    segment.coloncolon_token().map_or(PathKind::Plain, |_| PathKind::Abs),
-                );
+                let mut res =
+                    ModPath::from_kind(segment.coloncolon_token().map_or_else(
+                        || plain_path_kind(db, span_map, &name_ref),
+                        |_| PathKind::Abs,
+                    ));
                 res.segments.push(name_ref.as_name());
                 res
             }
@@ -281,7 +292,7 @@ fn convert_path(
     // Basically, even in rustc it is quite hacky:
     // https://github.com/rust-lang/rust/blob/614f273e9388ddd7804d5cbc80b8865068a3744e/src/librustc_resolve/macros.rs#L456
     // We follow what it did anyway :)
-    if mod_path.segments.len() == 1 && mod_path.kind == PathKind::Plain {
+    if mod_path.segments.len() == 1 && mod_path.kind.is_plain() {
         if let Some(_macro_call) = path.syntax().parent().and_then(ast::MacroCall::cast) {
             let syn_ctx = span_map.span_for_range(segment.syntax().text_range()).ctx;
             if let Some(macro_call_id) = db.lookup_intern_syntax_context(syn_ctx).outer_expn {
@@ -338,6 +349,33 @@ fn convert_path_tt(db: &dyn ExpandDatabase, tt: &[tt::TokenTree]) -> Option<ModP
     Some(ModPath { kind, segments })
 }
 
+/// Returns the kind of a path whose first segment is `name_ref`, which has no leading `::`.
+///
+/// Identifiers produced by the body of a `macro` definition are hygienic: they resolve at the
+/// definition site of the macro rather than where it was invoked.
+pub fn plain_path_kind(
+    db: &dyn ExpandDatabase,
+    span_map: SpanMapRef<'_>,
+    name_ref: &ast::NameRef,
+) -> PathKind {
+    // Only macro expansions can contain def-site identifiers.
+    let SpanMapRef::ExpansionSpanMap(span_map) = span_map else {
+        return PathKind::Plain;
+    };
+    let ctxt = span_map.span_at(name_ref.syntax().text_range().start()).ctx;
+    match ctxt.normalize_to_macros_2_0(db).outer_mark(db) {
+        (Some(call), Transparency::Opaque)
+            if matches!(
+                db.lookup_intern_macro_call(call).def.kind,
+                MacroDefKind::Declarative(_)
+            ) =>
+        {
+            PathKind::DefSite(call)
+        }
+        _ => PathKind::Plain,
+    }
+}
+
 pub fn resolve_crate_root(db: &dyn ExpandDatabase, mut ctxt: SyntaxContextId) -> Option<CrateId> {
     // When resolving `$crate` from a `macro_rules!` invoked in a `macro`,
     // we don't want to pretend that the `macro_rules!` definition is in the `macro`
@@ -357,6 +357,7 @@ macro_rules! known_names {
         // Components of known path (function name)
         filter_map,
         next,
+        nth,
         iter_mut,
         len,
         is_empty,
@@ -154,6 +154,18 @@ pub fn expand(
                         {
                             ExpandResult { value: tt.clone(), err: Some(ExpandError::other(text)) }
                         }
+                        // Attributes commonly fail on code that is still being typed, keep the item
+                        // so that its contents can still be analyzed.
+                        ProcMacroExpansionError::Panic(text)
+                            if proc_macro.kind == ProcMacroKind::Attr =>
+                        {
+                            ExpandResult {
+                                value: tt.clone(),
+                                err: Some(ExpandError::ProcMacroPanic(Box::new(
+                                    text.into_boxed_str(),
+                                ))),
+                            }
+                        }
                         ProcMacroExpansionError::System(text)
                         | ProcMacroExpansionError::Panic(text) => ExpandResult::new(
                             tt::Subtree::empty(tt::DelimSpan { open: call_site, close: call_site }),
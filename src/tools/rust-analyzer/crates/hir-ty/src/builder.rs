@@ -273,17 +273,13 @@ pub fn build(self) -> Substitution {
     }
 }
 
-impl TyBuilder<hir_def::AdtId> {
-    pub fn adt(db: &dyn HirDatabase, def: hir_def::AdtId) -> TyBuilder<hir_def::AdtId> {
-        TyBuilder::subst_for_def(db, def, None).with_data(def)
-    }
-
+impl<D: Copy + Into<GenericDefId>> TyBuilder<D> {
     pub fn fill_with_defaults(
         mut self,
         db: &dyn HirDatabase,
         mut fallback: impl FnMut() -> Ty,
     ) -> Self {
-        // Note that we're building ADT, so we never have parent generic parameters.
+        // Note that ADTs and traits never have parent generic parameters.
         let defaults = db.generic_defaults(self.data.into());
         let dummy_ty = TyKind::Error.intern(Interner).cast(Interner);
         for default_ty in defaults.iter().skip(self.vec.len()) {
@@ -308,6 +304,12 @@ pub fn fill_with_defaults(
         }
         self
     }
+}
+
+impl TyBuilder<hir_def::AdtId> {
+    pub fn adt(db: &dyn HirDatabase, def: hir_def::AdtId) -> TyBuilder<hir_def::AdtId> {
+        TyBuilder::subst_for_def(db, def, None).with_data(def)
+    }
 
     pub fn build(self) -> Ty {
         let (adt, subst) = self.build_internal();
@@ -5,27 +5,32 @@
 
 use tracing::debug;
 
-use chalk_ir::{cast::Caster, fold::shift::Shift, CanonicalVarKinds};
+use chalk_ir::{
+    cast::{Cast, Caster},
+    fold::shift::Shift,
+    CanonicalVarKinds,
+};
 use chalk_solve::rust_ir::{self, OpaqueTyDatumBound, WellKnownTrait};
 
-use base_db::CrateId;
+use base_db::{salsa::Cycle, CrateId};
 use hir_def::{
+    data::adt::StructFlags,
     hir::Movability,
     lang_item::{LangItem, LangItemTarget},
-    AssocItemId, BlockId, GenericDefId, HasModule, ItemContainerId, Lookup, TypeAliasId,
+    AssocItemId, BlockId, GenericDefId, HasModule, ItemContainerId, Lookup, TypeAliasId, VariantId,
 };
 use hir_expand::name::name;
 
 use crate::{
     db::{HirDatabase, InternedCoroutine},
     display::HirDisplay,
-    from_assoc_type_id, from_chalk_trait_id, from_foreign_def_id, make_binders,
-    make_single_type_binders,
+    fold_tys, from_assoc_type_id, from_chalk_trait_id, from_foreign_def_id, from_placeholder_idx,
+    make_binders, make_single_type_binders,
     mapping::{from_chalk, ToChalk, TypeAliasAsValue},
     method_resolution::{TraitImpls, TyFingerprint, ALL_FLOAT_FPS, ALL_INT_FPS},
     to_assoc_type_id, to_chalk_trait_id,
     traits::ChalkContext,
-    utils::{generics, ClosureSubst},
+    utils::{generics, iterator_trait, ClosureSubst},
     wrap_empty_binders, AliasEq, AliasTy, BoundVar, CallableDefId, DebruijnIndex, FnDefId,
     Interner, ProjectionTy, ProjectionTyExt, QuantifiedWhereClause, Substitution, TraitRef,
     TraitRefExt, Ty, TyBuilder, TyExt, TyKind, WhereClause,
@@ -40,6 +45,7 @@
 pub(crate) type AssocTypeId = chalk_ir::AssocTypeId<Interner>;
 pub(crate) type TraitId = chalk_ir::TraitId<Interner>;
 pub(crate) type AdtId = chalk_ir::AdtId<Interner>;
+pub(crate) type OpaqueTyId = chalk_ir::OpaqueTyId<Interner>;
 pub(crate) type ImplId = chalk_ir::ImplId<Interner>;
 pub(crate) type AssociatedTyValueId = chalk_solve::rust_ir::AssociatedTyValueId<Interner>;
 pub(crate) type AssociatedTyValue = chalk_solve::rust_ir::AssociatedTyValue<Interner>;
@@ -209,7 +215,19 @@ fn binder_kind(
     }
     fn impl_provided_for(&self, auto_trait_id: TraitId, kind: &chalk_ir::TyKind<Interner>) -> bool {
         debug!("impl_provided_for {:?}, {:?}", auto_trait_id, kind);
-        false // FIXME
+        // An explicit impl of an auto trait, positive or negative, replaces the structural one.
+        let self_ty = kind.clone().intern(Interner);
+        self.impls_for_trait(
+            auto_trait_id,
+            &[self_ty.clone().cast(Interner)],
+            &CanonicalVarKinds::empty(Interner),
+        )
+        .into_iter()
+        .any(|impl_id| {
+            let impl_id: hir_def::ImplId = from_chalk(self.db, impl_id);
+            // NOTE(skip_binders): only the type constructors are compared
+            self.db.impl_self_ty(impl_id).skip_binders().equals_ctor(&self_ty)
+        })
     }
     fn associated_ty_value(&self, id: AssociatedTyValueId) -> Arc<AssociatedTyValue> {
         self.db.associated_ty_value(self.krate, id)
@@ -261,7 +279,7 @@ fn opaque_ty_data(&self, id: chalk_ir::OpaqueTyId<Interner>) -> Arc<OpaqueTyDatu
                 chalk_ir::Binders::new(binders, bound)
             }
             crate::ImplTraitId::AsyncBlockTypeImplTrait(..) => {
-                if let Some((future_trait, future_output)) = self
+                let future = self
                     .db
                     .lang_item(self.krate, LangItem::Future)
                     .and_then(|item| item.as_trait())
@@ -269,86 +287,23 @@ fn opaque_ty_data(&self, id: chalk_ir::OpaqueTyId<Interner>) -> Arc<OpaqueTyDatu
                         let alias =
                             self.db.trait_data(trait_).associated_type_by_name(&name![Output])?;
                         Some((trait_, alias))
-                    })
-                {
-                    // Making up Symbol’s value as variable is void: AsyncBlock<T>:
-                    //
-                    // |--------------------OpaqueTyDatum-------------------|
-                    //        |-------------OpaqueTyDatumBound--------------|
-                    // for<T> <Self> [Future<Self>, Future::Output<Self> = T]
-                    //     ^1  ^0            ^0                    ^0      ^1
-                    let impl_bound = WhereClause::Implemented(TraitRef {
-                        trait_id: to_chalk_trait_id(future_trait),
-                        // Self type as the first parameter.
-                        substitution: Substitution::from1(
-                            Interner,
-                            TyKind::BoundVar(BoundVar {
-                                debruijn: DebruijnIndex::INNERMOST,
-                                index: 0,
-                            })
-                            .intern(Interner),
-                        ),
                     });
-                    let mut binder = vec![];
-                    binder.push(crate::wrap_empty_binders(impl_bound));
-                    let sized_trait = self
-                        .db
-                        .lang_item(self.krate, LangItem::Sized)
-                        .and_then(|item| item.as_trait());
-                    if let Some(sized_trait_) = sized_trait {
-                        let sized_bound = WhereClause::Implemented(TraitRef {
-                            trait_id: to_chalk_trait_id(sized_trait_),
-                            // Self type as the first parameter.
-                            substitution: Substitution::from1(
-                                Interner,
-                                TyKind::BoundVar(BoundVar {
-                                    debruijn: DebruijnIndex::INNERMOST,
-                                    index: 0,
-                                })
-                                .intern(Interner),
-                            ),
-                        });
-                        binder.push(crate::wrap_empty_binders(sized_bound));
-                    }
-                    let proj_bound = WhereClause::AliasEq(AliasEq {
-                        alias: AliasTy::Projection(ProjectionTy {
-                            associated_ty_id: to_assoc_type_id(future_output),
-                            // Self type as the first parameter.
-                            substitution: Substitution::from1(
-                                Interner,
-                                TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0))
-                                    .intern(Interner),
-                            ),
-                        }),
-                        // The parameter of the opaque type.
-                        ty: TyKind::BoundVar(BoundVar { debruijn: DebruijnIndex::ONE, index: 0 })
-                            .intern(Interner),
-                    });
-                    binder.push(crate::wrap_empty_binders(proj_bound));
-                    let bound = OpaqueTyDatumBound {
-                        bounds: make_single_type_binders(binder),
-                        where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
-                    };
-                    // The opaque type has 1 parameter.
-                    make_single_type_binders(bound)
-                } else {
-                    // If failed to find Symbol’s value as variable is void: Future::Output, return empty bounds as fallback.
-                    let bound = OpaqueTyDatumBound {
-                        bounds: chalk_ir::Binders::empty(Interner, vec![]),
-                        where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
-                    };
-                    // The opaque type has 1 parameter.
-                    make_single_type_binders(bound)
-                }
+                self.block_opaque_ty_bound(future)
+            }
+            crate::ImplTraitId::GenBlockTypeImplTrait(..) => {
+                let iterator = iterator_trait(self.db.upcast(), self.krate).and_then(|trait_| {
+                    let alias = self.db.trait_data(trait_).associated_type_by_name(&name![Item])?;
+                    Some((trait_, alias))
+                });
+                self.block_opaque_ty_bound(iterator)
             }
         };
 
         Arc::new(OpaqueTyDatum { opaque_ty_id: id, bound })
     }
 
-    fn hidden_opaque_type(&self, _id: chalk_ir::OpaqueTyId<Interner>) -> chalk_ir::Ty<Interner> {
-        // FIXME: actually provide the hidden type; it is relevant for auto traits
-        TyKind::Error.intern(Interner)
+    fn hidden_opaque_type(&self, id: chalk_ir::OpaqueTyId<Interner>) -> chalk_ir::Ty<Interner> {
+        self.db.hidden_opaque_type(id)
     }
 
     fn is_object_safe(&self, _trait_id: chalk_ir::TraitId<Interner>) -> bool {
@@ -489,6 +444,80 @@ fn unification_database(&self) -> &dyn chalk_ir::UnificationDatabase<Interner> {
     }
 }
 
+impl ChalkContext<'_> {
+    /// The bounds of the opaque type of an `async` or `gen` block, `Future<Output = T>` or
+    /// `Iterator<Item = T>` for its single parameter `T`.
+    fn block_opaque_ty_bound(
+        &self,
+        trait_and_assoc: Option<(hir_def::TraitId, TypeAliasId)>,
+    ) -> chalk_ir::Binders<OpaqueTyDatumBound<Interner>> {
+        if let Some((trait_, assoc)) = trait_and_assoc {
+            // Making up Symbol’s value as variable is void: AsyncBlock<T>:
+            //
+            // |--------------------OpaqueTyDatum-------------------|
+            //        |-------------OpaqueTyDatumBound--------------|
+            // for<T> <Self> [Future<Self>, Future::Output<Self> = T]
+            //     ^1  ^0            ^0                    ^0      ^1
+            //
+            // and likewise `Iterator::Item` for gen blocks.
+            let impl_bound = WhereClause::Implemented(TraitRef {
+                trait_id: to_chalk_trait_id(trait_),
+                // Self type as the first parameter.
+                substitution: Substitution::from1(
+                    Interner,
+                    TyKind::BoundVar(BoundVar { debruijn: DebruijnIndex::INNERMOST, index: 0 })
+                        .intern(Interner),
+                ),
+            });
+            let mut binder = vec![];
+            binder.push(crate::wrap_empty_binders(impl_bound));
+            let sized_trait =
+                self.db.lang_item(self.krate, LangItem::Sized).and_then(|item| item.as_trait());
+            if let Some(sized_trait_) = sized_trait {
+                let sized_bound = WhereClause::Implemented(TraitRef {
+                    trait_id: to_chalk_trait_id(sized_trait_),
+                    // Self type as the first parameter.
+                    substitution: Substitution::from1(
+                        Interner,
+                        TyKind::BoundVar(BoundVar { debruijn: DebruijnIndex::INNERMOST, index: 0 })
+                            .intern(Interner),
+                    ),
+                });
+                binder.push(crate::wrap_empty_binders(sized_bound));
+            }
+            let proj_bound = WhereClause::AliasEq(AliasEq {
+                alias: AliasTy::Projection(ProjectionTy {
+                    associated_ty_id: to_assoc_type_id(assoc),
+                    // Self type as the first parameter.
+                    substitution: Substitution::from1(
+                        Interner,
+                        TyKind::BoundVar(BoundVar::new(DebruijnIndex::INNERMOST, 0))
+                            .intern(Interner),
+                    ),
+                }),
+                // The parameter of the opaque type.
+                ty: TyKind::BoundVar(BoundVar { debruijn: DebruijnIndex::ONE, index: 0 })
+                    .intern(Interner),
+            });
+            binder.push(crate::wrap_empty_binders(proj_bound));
+            let bound = OpaqueTyDatumBound {
+                bounds: make_single_type_binders(binder),
+                where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
+            };
+            // The opaque type has 1 parameter.
+            make_single_type_binders(bound)
+        } else {
+            // If failed to find the trait or its associated type, return empty bounds as fallback.
+            let bound = OpaqueTyDatumBound {
+                bounds: chalk_ir::Binders::empty(Interner, vec![]),
+                where_clauses: chalk_ir::Binders::empty(Interner, vec![]),
+            };
+            // The opaque type has 1 parameter.
+            make_single_type_binders(bound)
+        }
+    }
+}
+
 impl chalk_ir::UnificationDatabase<Interner> for &dyn HirDatabase {
     fn fn_def_variance(
         &self,
@@ -663,20 +692,35 @@ pub(crate) fn struct_datum_query(
         let bound_vars = generic_params.bound_vars_subst(db, DebruijnIndex::INNERMOST);
         convert_where_clauses(db, adt_id.into(), &bound_vars)
     };
-    let flags = rust_ir::AdtFlags {
-        upstream,
-        // FIXME set fundamental and phantom_data flags correctly
-        fundamental: false,
-        phantom_data: false,
+    let struct_flags = match adt_id {
+        hir_def::AdtId::StructId(it) => db.struct_data(it).flags.clone(),
+        hir_def::AdtId::UnionId(it) => db.union_data(it).flags.clone(),
+        hir_def::AdtId::EnumId(_) => StructFlags::NO_FLAGS,
+    };
+    let fundamental = struct_flags.contains(StructFlags::IS_FUNDAMENTAL);
+    let phantom_data = struct_flags.contains(StructFlags::IS_PHANTOM_DATA);
+    let flags = rust_ir::AdtFlags { upstream, fundamental, phantom_data };
+
+    // The fields are what auto traits and `Sized` are implemented structurally from.
+    let bound_vars = generic_params.bound_vars_subst(db, DebruijnIndex::INNERMOST);
+    let variant_datum = |variant: VariantId| rust_ir::AdtVariantDatum {
+        fields: db
+            .field_types(variant)
+            .iter()
+            .map(|(_, ty)| ty.clone().substitute(Interner, &bound_vars))
+            .collect(),
     };
-    // FIXME provide enum variants properly (for auto traits)
-    let variant = rust_ir::AdtVariantDatum {
-        fields: Vec::new(), // FIXME add fields (only relevant for auto traits),
+    let (kind, variants) = match adt_id {
+        hir_def::AdtId::StructId(it) => (rust_ir::AdtKind::Struct, vec![variant_datum(it.into())]),
+        hir_def::AdtId::UnionId(it) => (rust_ir::AdtKind::Union, vec![variant_datum(it.into())]),
+        hir_def::AdtId::EnumId(it) => (
+            rust_ir::AdtKind::Enum,
+            db.enum_data(it).variants.iter().map(|&(it, _)| variant_datum(it.into())).collect(),
+        ),
     };
-    let struct_datum_bound = rust_ir::AdtDatumBound { variants: vec![variant], where_clauses };
+    let struct_datum_bound = rust_ir::AdtDatumBound { variants, where_clauses };
     let struct_datum = StructDatum {
-        // FIXME set ADT kind
-        kind: rust_ir::AdtKind::Struct,
+        kind,
         id: struct_id,
         binders: make_binders(db, &generic_params, struct_datum_bound),
         flags,
@@ -838,6 +882,44 @@ pub(crate) fn fn_def_variance_query(db: &dyn HirDatabase, fn_def_id: FnDefId) ->
     )
 }
 
+/// The concrete type behind an opaque type, with the generic parameters of its defining function
+/// as bound variables. Chalk only uses it to leak auto traits through the opaque type.
+pub(crate) fn hidden_opaque_type_query(db: &dyn HirDatabase, id: OpaqueTyId) -> Ty {
+    match db.lookup_intern_impl_trait_id(id.into()) {
+        crate::ImplTraitId::ReturnTypeImplTrait(func, idx) => {
+            let infer = db.infer(func.into());
+            let Some(hidden_ty) = infer.type_of_rpit.get(idx) else {
+                return TyKind::Error.intern(Interner);
+            };
+            let generics = generics(db.upcast(), func.into());
+            fold_tys(
+                hidden_ty.clone(),
+                |ty, outer_binder| match ty.kind(Interner) {
+                    TyKind::Placeholder(idx) => generics
+                        .param_idx(from_placeholder_idx(db, *idx))
+                        .map_or(ty, |idx| BoundVar::new(outer_binder, idx).to_ty(Interner)),
+                    _ => ty,
+                },
+                DebruijnIndex::INNERMOST,
+            )
+        }
+        // FIXME: provide the hidden type of async blocks. It is the coroutine witness of
+        // everything held across `.await`s, which we don't compute, so auto traits don't leak
+        // through async blocks yet.
+        crate::ImplTraitId::AsyncBlockTypeImplTrait(..)
+        | crate::ImplTraitId::GenBlockTypeImplTrait(..) => TyKind::Error.intern(Interner),
+    }
+}
+
+pub(crate) fn hidden_opaque_type_recover(
+    _db: &dyn HirDatabase,
+    _cycle: &Cycle,
+    _id: &OpaqueTyId,
+) -> Ty {
+    // The opaque type is used while inferring its own defining function.
+    TyKind::Error.intern(Interner)
+}
+
 pub(crate) fn adt_variance_query(
     db: &dyn HirDatabase,
     chalk_ir::AdtId(adt_id): AdtId,
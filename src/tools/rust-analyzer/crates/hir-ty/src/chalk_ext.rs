@@ -241,18 +241,23 @@ fn impl_trait_bounds(&self, db: &dyn HirDatabase) -> Option<Vec<QuantifiedWhereC
         match self.kind(Interner) {
             TyKind::OpaqueType(opaque_ty_id, subst) => {
                 match db.lookup_intern_impl_trait_id((*opaque_ty_id).into()) {
-                    ImplTraitId::AsyncBlockTypeImplTrait(def, _expr) => {
+                    id @ (ImplTraitId::AsyncBlockTypeImplTrait(def, _)
+                    | ImplTraitId::GenBlockTypeImplTrait(def, _)) => {
                         let krate = def.module(db.upcast()).krate();
-                        if let Some(future_trait) =
-                            db.lang_item(krate, LangItem::Future).and_then(|item| item.as_trait())
-                        {
+                        let trait_ = match id {
+                            ImplTraitId::AsyncBlockTypeImplTrait(..) => db
+                                .lang_item(krate, LangItem::Future)
+                                .and_then(|item| item.as_trait()),
+                            _ => crate::utils::iterator_trait(db.upcast(), krate),
+                        };
+                        if let Some(trait_) = trait_ {
                             // This is only used by type walking.
                             // Parameters will be walked outside, and projection predicate is not used.
-                            // So just provide the Future trait.
+                            // So just provide the Future or Iterator trait.
                             let impl_bound = Binders::empty(
                                 Interner,
                                 WhereClause::Implemented(TraitRef {
-                                    trait_id: to_chalk_trait_id(future_trait),
+                                    trait_id: to_chalk_trait_id(trait_),
                                     substitution: Substitution::empty(Interner),
                                 }),
                             );
@@ -280,8 +285,9 @@ fn impl_trait_bounds(&self, db: &dyn HirDatabase) -> Option<Vec<QuantifiedWhereC
                             data.substitute(Interner, &opaque_ty.substitution)
                         })
                     }
-                    // It always has an parameter for Future::Output type.
-                    ImplTraitId::AsyncBlockTypeImplTrait(..) => unreachable!(),
+                    // It always has an parameter for Future::Output or Iterator::Item type.
+                    ImplTraitId::AsyncBlockTypeImplTrait(..)
+                    | ImplTraitId::GenBlockTypeImplTrait(..) => unreachable!(),
                 };
 
                 predicates.map(|it| it.into_value_and_skipped_binders().0)
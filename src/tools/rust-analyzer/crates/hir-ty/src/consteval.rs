@@ -4,11 +4,11 @@
 use chalk_ir::{cast::Cast, BoundVar, DebruijnIndex};
 use hir_def::{
     body::Body,
-    hir::{Expr, ExprId},
+    hir::{BindingId, Expr, ExprId},
     path::Path,
     resolver::{Resolver, ValueNs},
     type_ref::LiteralConstRef,
-    ConstBlockLoc, EnumVariantId, GeneralConstId, StaticId,
+    ConstBlockLoc, DefWithBodyId, EnumVariantId, GeneralConstId, StaticId,
 };
 use hir_expand::Lookup;
 use stdx::never;
@@ -17,11 +17,14 @@
 use crate::{
     db::HirDatabase, infer::InferenceContext, lower::ParamLoweringMode,
     mir::monomorphize_mir_body_bad, to_placeholder_idx, utils::Generics, Const, ConstData,
-    ConstScalar, ConstValue, GenericArg, Interner, MemoryMap, Substitution, TraitEnvironment, Ty,
-    TyBuilder,
+    ConstScalar, ConstValue, GenericArg, Interner, MemoryMap, Scalar, Substitution,
+    TraitEnvironment, Ty, TyBuilder, TyKind,
 };
 
-use super::mir::{interpret_mir, lower_to_mir, pad16, MirEvalError, MirLowerError};
+use super::mir::{
+    interpret_mir, interpret_mir_with_args, lower_expr_to_mir, lower_to_mir, pad16, MirEvalError,
+    MirLowerError,
+};
 
 /// Extension trait for [`Const`]
 pub trait ConstExt {
@@ -183,6 +186,23 @@ pub fn try_const_usize(db: &dyn HirDatabase, c: &Const) -> Option<u128> {
     }
 }
 
+/// Like [`try_const_usize`], but sign-extends the value.
+pub fn try_const_isize(db: &dyn HirDatabase, c: &Const) -> Option<i128> {
+    match &c.data(Interner).value {
+        chalk_ir::ConstValue::BoundVar(_) => None,
+        chalk_ir::ConstValue::InferenceVar(_) => None,
+        chalk_ir::ConstValue::Placeholder(_) => None,
+        chalk_ir::ConstValue::Concrete(c) => match &c.interned {
+            ConstScalar::Bytes(it, _) => Some(i128::from_le_bytes(pad16(it, true))),
+            ConstScalar::UnevaluatedConst(c, subst) => {
+                let ec = db.const_eval(*c, subst.clone(), None).ok()?;
+                try_const_isize(db, &ec)
+            }
+            _ => None,
+        },
+    }
+}
+
 pub(crate) fn const_eval_recover(
     _: &dyn HirDatabase,
     _: &Cycle,
@@ -274,7 +294,40 @@ pub(crate) fn const_eval_discriminant_variant(
         db.trait_environment_for_body(def),
     )?;
     let c = interpret_mir(db, mir_body, false, None).0?;
-    let c = try_const_usize(db, &c).unwrap() as i128;
+    let c = match c.data(Interner).ty.kind(Interner) {
+        TyKind::Scalar(Scalar::Int(_)) => try_const_isize(db, &c).unwrap(),
+        _ => try_const_usize(db, &c).unwrap() as i128,
+    };
+    Ok(c)
+}
+
+/// Evaluates an expression of the body of `owner` on its own, with `args` as the values of the
+/// bindings it uses from outside of it. Only values without pointers are supported for those.
+pub fn eval_expr(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    expr: ExprId,
+    args: &[(BindingId, Const)],
+) -> Result<Const, ConstEvalError> {
+    let body = db.body(owner);
+    let infer = db.infer(owner);
+    let free_bindings: Vec<_> = args.iter().map(|&(it, _)| it).collect();
+    let mir_body = lower_expr_to_mir(db, owner, &body, &infer, expr, &free_bindings)?;
+    let args = args
+        .iter()
+        .map(|(_, value)| match &value.data(Interner).value {
+            ConstValue::Concrete(chalk_ir::ConcreteConst {
+                interned: ConstScalar::Bytes(bytes, memory_map),
+            }) => match memory_map {
+                MemoryMap::Empty => Ok(bytes.to_vec()),
+                _ => Err(MirEvalError::NotSupported("arguments with pointers".to_owned())),
+            },
+            _ => Err(MirEvalError::InvalidConst(value.clone())),
+        })
+        .collect::<Result<_, _>>()?;
+    // The other bindings of the body are not used, but they can have placeholder types.
+    let trait_env = Some(db.trait_environment_for_body(owner));
+    let c = interpret_mir_with_args(db, Arc::new(mir_body), args, true, trait_env).0?;
     Ok(c)
 }
 
@@ -2490,6 +2490,24 @@ const fn f<T>() -> usize {
     );
 }
 
+#[test]
+fn anonymous_const_block_pattern() {
+    check_number(
+        r#"
+    const fn f(x: i32) -> i32 {
+        match x {
+            const { 1 + 1 } => 10,
+            const { let y = 3; y } => 20,
+            _ => 30,
+        }
+    }
+
+    const GOAL: i32 = f(2) + f(3) + f(4);
+    "#,
+        60,
+    );
+}
+
 #[test]
 fn const_impl_assoc() {
     check_number(
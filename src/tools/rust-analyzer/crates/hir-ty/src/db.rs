@@ -38,6 +38,12 @@ pub trait HirDatabase: DefDatabase + Upcast<dyn DefDatabase> {
     #[salsa::invoke(crate::infer::infer_query)]
     fn infer_query(&self, def: DefWithBodyId) -> Arc<InferenceResult>;
 
+    #[salsa::invoke(crate::diagnostics::BodyValidationDiagnostic::collect_query)]
+    fn body_validation_diagnostics(
+        &self,
+        def: DefWithBodyId,
+    ) -> Arc<[crate::diagnostics::BodyValidationDiagnostic]>;
+
     // region:mir
 
     #[salsa::invoke(crate::mir::mir_body_query)]
@@ -250,6 +256,10 @@ fn associated_ty_value(
         id: chalk_db::AssociatedTyValueId,
     ) -> sync::Arc<chalk_db::AssociatedTyValue>;
 
+    #[salsa::invoke(chalk_db::hidden_opaque_type_query)]
+    #[salsa::cycle(chalk_db::hidden_opaque_type_recover)]
+    fn hidden_opaque_type(&self, id: chalk_db::OpaqueTyId) -> Ty;
+
     #[salsa::invoke(crate::traits::normalize_projection_query)]
     #[salsa::transparent]
     fn normalize_projection(
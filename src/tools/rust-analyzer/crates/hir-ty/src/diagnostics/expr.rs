@@ -4,11 +4,16 @@
 
 use std::fmt;
 
+use base_db::CrateId;
+use chalk_ir::{FloatTy, IntTy, UintTy};
 use either::Either;
 use hir_def::lang_item::LangItem;
-use hir_def::{resolver::HasResolver, AdtId, AssocItemId, DefWithBodyId, HasModule};
+use hir_def::{
+    resolver::{resolver_for_expr, HasResolver, ValueNs},
+    AdtId, AssocItemId, DefWithBodyId, FunctionId, HasModule,
+};
 use hir_def::{ItemContainerId, Lookup};
-use hir_expand::name;
+use hir_expand::name::{name, Name};
 use itertools::Itertools;
 use rustc_hash::FxHashSet;
 use rustc_pattern_analysis::usefulness::{compute_match_usefulness, ValidityConstraint};
@@ -16,21 +21,23 @@
 use typed_arena::Arena;
 
 use crate::{
+    consteval::try_const_usize,
     db::HirDatabase,
     diagnostics::match_check::{
         self,
         pat_analysis::{self, DeconstructedPat, MatchCheckCtx, WitnessPat},
     },
     display::HirDisplay,
-    InferenceResult, Ty, TyExt,
+    InferenceResult, Interner, Scalar, Substitution, Ty, TyExt, TyKind,
 };
 
 pub(crate) use hir_def::{
     body::Body,
-    hir::{Expr, ExprId, MatchArm, Pat, PatId, Statement},
+    hir::{Expr, ExprId, Literal, MatchArm, Pat, PatId, Statement, UnaryOp},
     LocalFieldId, VariantId,
 };
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum BodyValidationDiagnostic {
     RecordMissingFields {
         record: Either<ExprId, PatId>,
@@ -40,6 +47,9 @@ pub enum BodyValidationDiagnostic {
     ReplaceFilterMapNextWithFindMap {
         method_call_expr: ExprId,
     },
+    IterNthZero {
+        method_call_expr: ExprId,
+    },
     MissingMatchArms {
         match_expr: ExprId,
         uncovered_patterns: String,
@@ -50,16 +60,52 @@ pub enum BodyValidationDiagnostic {
     RemoveUnnecessaryElse {
         if_expr: ExprId,
     },
+    ArrayIndexOutOfBounds {
+        index_expr: ExprId,
+        index: u128,
+        len: u128,
+    },
+    ArrayPatternLenMismatch {
+        pat: PatId,
+        /// The number of elements the pattern matches, not counting its rest pattern.
+        required: usize,
+        has_rest: bool,
+        len: u128,
+    },
+    LossyCast {
+        cast_expr: ExprId,
+        from: Ty,
+        to: Ty,
+        lossiness: CastLossiness,
+    },
+}
+
+/// How an `as` cast between numeric types may change the value it casts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CastLossiness {
+    /// The value may not fit into the narrower target type, like `u32 as u8`.
+    Truncating,
+    /// Negative values lose their sign, or large values wrap around to negative ones, like
+    /// `i32 as u32` or `u32 as i32`.
+    SignChanging,
+    /// Floats are rounded toward zero, and clamped to the range of the target type, like
+    /// `f64 as i32`.
+    Saturating,
 }
 
 impl BodyValidationDiagnostic {
-    pub fn collect(db: &dyn HirDatabase, owner: DefWithBodyId) -> Vec<BodyValidationDiagnostic> {
+    /// Diagnostics only refer to the body by ids, so edits that leave the body as it is, like
+    /// changing comments or formatting, reuse them instead of validating the body again.
+    pub(crate) fn collect_query(
+        db: &dyn HirDatabase,
+        owner: DefWithBodyId,
+    ) -> Arc<[BodyValidationDiagnostic]> {
         let _p =
             tracing::span!(tracing::Level::INFO, "BodyValidationDiagnostic::collect").entered();
         let infer = db.infer(owner);
         let mut validator = ExprValidator::new(owner, infer);
         validator.validate_body(db);
-        validator.diagnostics
+        validator.diagnostics.into()
     }
 }
 
@@ -98,7 +144,7 @@ fn validate_body(&mut self, db: &dyn HirDatabase) {
                     self.validate_match(id, *expr, arms, db);
                 }
                 Expr::Call { .. } | Expr::MethodCall { .. } => {
-                    self.validate_call(db, id, expr, &mut filter_map_next_checker);
+                    self.validate_call(db, &body, id, expr, &mut filter_map_next_checker);
                 }
                 Expr::Closure { body: body_expr, .. } => {
                     self.check_for_trailing_return(*body_expr, &body);
@@ -106,6 +152,12 @@ fn validate_body(&mut self, db: &dyn HirDatabase) {
                 Expr::If { .. } => {
                     self.check_for_unnecessary_else(id, expr, &body);
                 }
+                &Expr::Index { base, index, .. } => {
+                    self.check_for_array_index_out_of_bounds(db, id, base, index, &body);
+                }
+                &Expr::Cast { expr, .. } => {
+                    self.check_for_lossy_cast(id, expr, &body);
+                }
                 _ => {}
             }
         }
@@ -120,12 +172,31 @@ fn validate_body(&mut self, db: &dyn HirDatabase) {
                     missed_fields,
                 });
             }
+            if let Pat::Slice { prefix, slice, suffix } = pat {
+                let required = prefix.len() + suffix.len();
+                let has_rest = slice.is_some();
+                // Without a rest pattern, the array must have exactly `required` elements.
+                let mismatches =
+                    |len| if has_rest { required as u128 > len } else { required as u128 != len };
+                match array_len(db, &self.infer[id]) {
+                    Some(len) if mismatches(len) => {
+                        self.diagnostics.push(BodyValidationDiagnostic::ArrayPatternLenMismatch {
+                            pat: id,
+                            required,
+                            has_rest,
+                            len,
+                        });
+                    }
+                    _ => (),
+                }
+            }
         }
     }
 
     fn validate_call(
         &mut self,
         db: &dyn HirDatabase,
+        body: &Body,
         call_id: ExprId,
         expr: &Expr,
         filter_map_next_checker: &mut Option<FilterMapNextChecker>,
@@ -135,7 +206,7 @@ fn validate_call(
         if self.infer.expr_type_mismatches().next().is_some() {
             // FIXME: Due to shortcomings in the current type system implementation, only emit
             // this diagnostic if there are no type mismatches in the containing function.
-        } else if let Expr::MethodCall { receiver, .. } = expr {
+        } else if let Expr::MethodCall { receiver, args, .. } = expr {
             let (callee, _) = match self.infer.method_resolution(call_id) {
                 Some(it) => it,
                 None => return,
@@ -152,6 +223,17 @@ fn validate_call(
                     method_call_expr: call_id,
                 });
             }
+
+            // check for instances of .nth(0)
+            if let [arg] = **args {
+                if matches!(body[arg], Expr::Literal(Literal::Int(0, _) | Literal::Uint(0, _)))
+                    && iterator_method(db, self.owner.module(db.upcast()).krate(), &name![nth])
+                        == Some(callee)
+                {
+                    self.diagnostics
+                        .push(BodyValidationDiagnostic::IterNthZero { method_call_expr: call_id });
+                }
+            }
         }
     }
 
@@ -308,6 +390,156 @@ fn check_for_unnecessary_else(&mut self, id: ExprId, expr: &Expr, body: &Body) {
             }
         }
     }
+
+    fn check_for_array_index_out_of_bounds(
+        &mut self,
+        db: &dyn HirDatabase,
+        index_expr: ExprId,
+        base: ExprId,
+        index: ExprId,
+        body: &Body,
+    ) {
+        let Some(len) = array_len(db, &self.infer[base]) else { return };
+        let index_value = match &body[index] {
+            &Expr::Literal(Literal::Uint(it, _)) => it,
+            &Expr::Literal(Literal::Int(it, _)) => match u128::try_from(it) {
+                Ok(it) => it,
+                Err(_) => return,
+            },
+            Expr::Path(path) => {
+                let resolver = resolver_for_expr(db.upcast(), self.owner, index);
+                let Some(ValueNs::ConstId(konst)) =
+                    resolver.resolve_path_in_value_ns_fully(db.upcast(), path)
+                else {
+                    return;
+                };
+                let Ok(konst) = db.const_eval(konst.into(), Substitution::empty(Interner), None)
+                else {
+                    return;
+                };
+                match try_const_usize(db, &konst) {
+                    Some(it) => it,
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        if index_value >= len {
+            self.diagnostics.push(BodyValidationDiagnostic::ArrayIndexOutOfBounds {
+                index_expr,
+                index: index_value,
+                len,
+            });
+        }
+    }
+
+    fn check_for_lossy_cast(&mut self, cast_expr: ExprId, expr: ExprId, body: &Body) {
+        // The value of a literal is right there, and its type may be inferred from the cast.
+        let mut inner = expr;
+        while let Expr::UnaryOp { expr, op: UnaryOp::Neg } = body[inner] {
+            inner = expr;
+        }
+        if matches!(body[inner], Expr::Literal(_)) {
+            return;
+        }
+        let (from, to) = (&self.infer[expr], &self.infer[cast_expr]);
+        let (TyKind::Scalar(from_scalar), TyKind::Scalar(to_scalar)) =
+            (from.kind(Interner), to.kind(Interner))
+        else {
+            return;
+        };
+        if let Some(lossiness) = cast_lossiness(*from_scalar, *to_scalar) {
+            self.diagnostics.push(BodyValidationDiagnostic::LossyCast {
+                cast_expr,
+                from: from.clone(),
+                to: to.clone(),
+                lossiness,
+            });
+        }
+    }
+}
+
+/// How casting `from` to `to` may change the value, if it may.
+fn cast_lossiness(from: Scalar, to: Scalar) -> Option<CastLossiness> {
+    match (from, to) {
+        (Scalar::Float(FloatTy::F64), Scalar::Float(FloatTy::F32)) => {
+            Some(CastLossiness::Truncating)
+        }
+        (Scalar::Float(_), Scalar::Int(_) | Scalar::Uint(_)) => Some(CastLossiness::Saturating),
+        _ => {
+            let (from_signed, from_min, from_max) = int_bits(from)?;
+            let (to_signed, to_min, to_max) = int_bits(to)?;
+            // Pointer-sized integers always have the same size as each other.
+            let both_pointer_sized = from_min != from_max && to_min != to_max;
+            if from_max > to_min && !both_pointer_sized {
+                Some(CastLossiness::Truncating)
+            } else if from_signed != to_signed && (from_signed || from_max >= to_min) {
+                Some(CastLossiness::SignChanging)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Whether the integer type is signed, and the least and most bits its values may need. The size
+/// of pointer-sized integers depends on the target, so any size they may have is considered.
+fn int_bits(scalar: Scalar) -> Option<(bool, u32, u32)> {
+    let (signed, bits) = match scalar {
+        // Chars are scalar values, which are at most `0x10FFFF`.
+        Scalar::Char => (false, Some(21)),
+        Scalar::Int(it) => (
+            true,
+            match it {
+                IntTy::Isize => None,
+                IntTy::I8 => Some(8),
+                IntTy::I16 => Some(16),
+                IntTy::I32 => Some(32),
+                IntTy::I64 => Some(64),
+                IntTy::I128 => Some(128),
+            },
+        ),
+        Scalar::Uint(it) => (
+            false,
+            match it {
+                UintTy::Usize => None,
+                UintTy::U8 => Some(8),
+                UintTy::U16 => Some(16),
+                UintTy::U32 => Some(32),
+                UintTy::U64 => Some(64),
+                UintTy::U128 => Some(128),
+            },
+        ),
+        Scalar::Bool | Scalar::Float(_) => return None,
+    };
+    Some(match bits {
+        Some(bits) => (signed, bits, bits),
+        None => (signed, 32, 64),
+    })
+}
+
+/// The length of the array `ty` is, or refers to, if it's known. Lengths that are const generic
+/// parameters are unknown, but those that were substituted with a constant get evaluated.
+fn array_len(db: &dyn HirDatabase, ty: &Ty) -> Option<u128> {
+    let mut ty = ty;
+    while let Some((inner, ..)) = ty.as_reference() {
+        ty = inner;
+    }
+    match ty.kind(Interner) {
+        TyKind::Array(_, len) => try_const_usize(db, len),
+        _ => None,
+    }
+}
+
+/// Finds the method of the `Iterator` trait with the given name.
+fn iterator_method(db: &dyn HirDatabase, krate: CrateId, name: &Name) -> Option<FunctionId> {
+    let next_function_id = db.lang_item(krate, LangItem::IteratorNext)?.as_function()?;
+    match next_function_id.lookup(db.upcast()).container {
+        ItemContainerId::TraitId(iterator_trait_id) => {
+            db.trait_data(iterator_trait_id).method_by_name(name)
+        }
+        _ => None,
+    }
 }
 
 struct FilterMapNextChecker {
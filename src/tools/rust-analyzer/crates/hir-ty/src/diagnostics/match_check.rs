@@ -9,18 +9,21 @@
 
 pub(crate) mod pat_analysis;
 
-use chalk_ir::Mutability;
+use chalk_ir::{Mutability, Scalar};
 use hir_def::{
-    body::Body, data::adt::VariantData, hir::PatId, AdtId, EnumVariantId, LocalFieldId, VariantId,
+    body::Body, data::adt::VariantData, hir::PatId, AdtId, EnumVariantId, GeneralConstId,
+    LocalFieldId, VariantId,
 };
 use hir_expand::name::Name;
 use stdx::{always, never};
 
 use crate::{
+    consteval::try_const_usize,
     db::HirDatabase,
     display::{HirDisplay, HirDisplayError, HirFormatter},
     infer::BindingMode,
     lang_items::is_box,
+    utils::generics,
     InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
 };
 
@@ -192,6 +195,8 @@ fn lower_pattern_unadjusted(&mut self, pat: PatId) -> Pat {
 
             hir_def::hir::Pat::Or(ref pats) => PatKind::Or { pats: self.lower_patterns(pats) },
 
+            hir_def::hir::Pat::ConstBlock(expr) => self.lower_const_block(expr),
+
             _ => {
                 self.errors.push(PatternError::Unimplemented);
                 PatKind::Wild
@@ -277,6 +282,29 @@ fn lower_path(&mut self, pat: PatId, _path: &hir_def::path::Path) -> Pat {
         }
     }
 
+    fn lower_const_block(&mut self, expr: hir_def::hir::ExprId) -> PatKind {
+        if let hir_def::hir::Expr::Const(id) = self.body[expr] {
+            let const_id = GeneralConstId::from(id);
+            // The blocks of generic items can only be evaluated once they're monomorphized.
+            let is_generic = const_id
+                .generic_def(self.db.upcast())
+                .is_some_and(|def| generics(self.db.upcast(), def).len() > 0);
+            let is_bool = matches!(self.infer[expr].kind(Interner), TyKind::Scalar(Scalar::Bool));
+            if !is_generic && is_bool {
+                let value = self
+                    .db
+                    .const_eval(const_id, Substitution::empty(Interner), None)
+                    .ok()
+                    .and_then(|it| try_const_usize(self.db, &it));
+                if let Some(value) = value {
+                    return PatKind::LiteralBool { value: value != 0 };
+                }
+            }
+        }
+        self.errors.push(PatternError::Unimplemented);
+        PatKind::Wild
+    }
+
     fn lower_lit(&mut self, expr: hir_def::hir::ExprId) -> PatKind {
         use hir_def::hir::{Expr, Literal::Bool};
 
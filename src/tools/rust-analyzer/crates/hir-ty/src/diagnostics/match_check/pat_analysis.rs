@@ -418,6 +418,7 @@ fn ctors_for_ty(
             TyKind::Adt(AdtId(hir_def::AdtId::EnumId(enum_id)), subst) => {
                 let enum_data = cx.db.enum_data(*enum_id);
                 let is_declared_nonexhaustive = cx.is_foreign_non_exhaustive_enum(ty);
+                let is_local = enum_id.module(cx.db.upcast()).krate() == cx.module.krate();
 
                 if enum_data.variants.is_empty() && !is_declared_nonexhaustive {
                     ConstructorSet::NoConstructors
@@ -426,8 +427,12 @@ fn ctors_for_ty(
                     for &(variant, _) in enum_data.variants.iter() {
                         let is_uninhabited =
                             is_enum_variant_uninhabited_from(variant, subst, cx.module, cx.db);
+                        // `#[doc(hidden)]` variants of other crates are only mentioned in
+                        // diagnostics if they're matched explicitly, they're covered by a `_`.
                         let visibility = if is_uninhabited {
                             VariantVisibility::Empty
+                        } else if !is_local && cx.db.attrs(variant.into()).has_doc_hidden() {
+                            VariantVisibility::Hidden
                         } else {
                             VariantVisibility::Visible
                         };
@@ -1,18 +1,21 @@
 //! Provides validations for unsafe code. Currently checks if unsafe functions are missing
 //! unsafe blocks.
 
+use either::Either;
 use hir_def::{
     body::Body,
-    hir::{Expr, ExprId, UnaryOp},
+    hir::{BinaryOp, Expr, ExprId, UnaryOp},
     resolver::{resolver_for_expr, ResolveValueResult, ValueNs},
-    DefWithBodyId,
+    DefWithBodyId, VariantId,
 };
 
 use crate::{
     db::HirDatabase, utils::is_fn_unsafe_to_call, InferenceResult, Interner, TyExt, TyKind,
 };
 
-pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<ExprId> {
+/// Returns the unsafe operations outside of `unsafe` blocks, and whether `def` is an `unsafe fn`,
+/// in which case they are only linted against by `unsafe_op_in_unsafe_fn`.
+pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> (Vec<ExprId>, bool) {
     let infer = db.infer(def);
     let mut res = Vec::new();
 
@@ -23,23 +26,35 @@ pub fn missing_unsafe(db: &dyn HirDatabase, def: DefWithBodyId) -> Vec<ExprId> {
         | DefWithBodyId::VariantId(_)
         | DefWithBodyId::InTypeConstId(_) => false,
     };
-    if is_unsafe {
-        return res;
-    }
 
     let body = db.body(def);
     unsafe_expressions(db, &infer, def, &body, body.body_expr, &mut |expr| {
-        if !expr.inside_unsafe_block {
+        if expr.unsafe_block.is_none() {
             res.push(expr.expr);
         }
     });
 
-    res
+    (res, is_unsafe)
 }
 
 pub struct UnsafeExpr {
     pub expr: ExprId,
-    pub inside_unsafe_block: bool,
+    pub kind: UnsafeExprKind,
+    /// The innermost `unsafe` block containing the expression.
+    pub unsafe_block: Option<ExprId>,
+}
+
+/// The operation that makes an expression require `unsafe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnsafeExprKind {
+    /// Dereferencing a raw pointer.
+    RawPtrDeref,
+    /// Calling an `unsafe fn`, including foreign functions.
+    UnsafeFnCall,
+    /// Reading a field of a union.
+    UnionField,
+    /// Accessing a `static mut`.
+    MutableStatic,
 }
 
 // FIXME: Move this out, its not a diagnostic only thing anymore, and handle unsafe pattern accesses as well
@@ -51,7 +66,7 @@ pub fn unsafe_expressions(
     current: ExprId,
     unsafe_expr_cb: &mut dyn FnMut(UnsafeExpr),
 ) {
-    walk_unsafe(db, infer, def, body, current, false, unsafe_expr_cb)
+    walk_unsafe(db, infer, def, body, current, None, unsafe_expr_cb)
 }
 
 fn walk_unsafe(
@@ -60,15 +75,16 @@ fn walk_unsafe(
     def: DefWithBodyId,
     body: &Body,
     current: ExprId,
-    inside_unsafe_block: bool,
+    unsafe_block: Option<ExprId>,
     unsafe_expr_cb: &mut dyn FnMut(UnsafeExpr),
 ) {
+    let mut report = |kind| unsafe_expr_cb(UnsafeExpr { expr: current, kind, unsafe_block });
     let expr = &body.exprs[current];
     match expr {
         &Expr::Call { callee, .. } => {
             if let Some(func) = infer[callee].as_fn_def(db) {
                 if is_fn_unsafe_to_call(db, func) {
-                    unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                    report(UnsafeExprKind::UnsafeFnCall);
                 }
             }
         }
@@ -77,7 +93,7 @@ fn walk_unsafe(
             let value_or_partial = resolver.resolve_path_in_value_ns(db.upcast(), path);
             if let Some(ResolveValueResult::ValueNs(ValueNs::StaticId(id), _)) = value_or_partial {
                 if db.static_data(id).mutable {
-                    unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                    report(UnsafeExprKind::MutableStatic);
                 }
             }
         }
@@ -87,23 +103,48 @@ fn walk_unsafe(
                 .map(|(func, _)| is_fn_unsafe_to_call(db, func))
                 .unwrap_or(false)
             {
-                unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                report(UnsafeExprKind::UnsafeFnCall);
             }
         }
         Expr::UnaryOp { expr, op: UnaryOp::Deref } => {
             if let TyKind::Raw(..) = &infer[*expr].kind(Interner) {
-                unsafe_expr_cb(UnsafeExpr { expr: current, inside_unsafe_block });
+                report(UnsafeExprKind::RawPtrDeref);
+            }
+        }
+        Expr::Field { .. } => {
+            if is_union_field(infer, current) {
+                report(UnsafeExprKind::UnionField);
             }
         }
+        &Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op: None }) }
+            if is_union_field(infer, lhs) =>
+        {
+            // Assigning to a union field is safe, only the receiver needs to be walked.
+            body.exprs[lhs].walk_child_exprs(|child| {
+                walk_unsafe(db, infer, def, body, child, unsafe_block, unsafe_expr_cb);
+            });
+            return walk_unsafe(db, infer, def, body, rhs, unsafe_block, unsafe_expr_cb);
+        }
         Expr::Unsafe { .. } => {
             return expr.walk_child_exprs(|child| {
-                walk_unsafe(db, infer, def, body, child, true, unsafe_expr_cb);
+                walk_unsafe(db, infer, def, body, child, Some(current), unsafe_expr_cb);
             });
         }
+        &Expr::Const(id) => {
+            let root = db.lookup_intern_anonymous_const(id).root;
+            return walk_unsafe(db, infer, def, body, root, unsafe_block, unsafe_expr_cb);
+        }
         _ => {}
     }
 
     expr.walk_child_exprs(|child| {
-        walk_unsafe(db, infer, def, body, child, inside_unsafe_block, unsafe_expr_cb);
+        walk_unsafe(db, infer, def, body, child, unsafe_block, unsafe_expr_cb);
     });
 }
+
+fn is_union_field(infer: &InferenceResult, expr: ExprId) -> bool {
+    matches!(
+        infer.field_resolution(expr),
+        Some(Either::Left(field)) if matches!(field.parent, VariantId::UnionId(_))
+    )
+}
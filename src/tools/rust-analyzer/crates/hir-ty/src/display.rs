@@ -49,6 +49,9 @@
 pub trait HirWrite: fmt::Write {
     fn start_location_link(&mut self, _location: ModuleDefId) {}
     fn end_location_link(&mut self) {}
+    /// Called before every (possibly nested) type is written, see [`StructuredDisplay`].
+    fn start_type_node(&mut self, _ty: &Ty) {}
+    fn end_type_node(&mut self) {}
 }
 
 // String will ignore link metadata
@@ -76,6 +79,14 @@ fn start_location_link(&mut self, location: ModuleDefId) {
     fn end_location_link(&mut self) {
         self.fmt.end_location_link();
     }
+
+    fn start_type_node(&mut self, ty: &Ty) {
+        self.fmt.start_type_node(ty);
+    }
+
+    fn end_type_node(&mut self) {
+        self.fmt.end_type_node();
+    }
 }
 
 pub trait HirDisplay {
@@ -169,6 +180,39 @@ fn display_source_code<'a>(
         Ok(result)
     }
 
+    /// Returns the human-readable rendering of `self` together with the tree of types it is made
+    /// of, see [`StructuredDisplay`].
+    fn display_structured(
+        &self,
+        db: &dyn HirDatabase,
+        max_size: Option<usize>,
+        closure_style: ClosureStyle,
+    ) -> StructuredDisplay
+    where
+        Self: Sized,
+    {
+        let mut result = StructuredDisplay::default();
+        let mut writer = StructuredWriter { result: &mut result, stack: Vec::new() };
+        match self.hir_fmt(&mut HirFormatter {
+            db,
+            fmt: &mut writer,
+            buf: String::with_capacity(20),
+            curr_size: 0,
+            max_size,
+            omit_verbose_types: false,
+            closure_style,
+            display_target: DisplayTarget::Diagnostics,
+        }) {
+            Ok(()) => {}
+            Err(HirDisplayError::FmtError) => panic!("Writing to String can't fail!"),
+            Err(HirDisplayError::DisplaySourceCodeError(_)) => {
+                // This should never happen
+                panic!("HirDisplay::hir_fmt failed with DisplaySourceCodeError when displaying structurally!")
+            }
+        }
+        result
+    }
+
     /// Returns a String representation of `self` for test purposes
     fn display_test<'a>(&'a self, db: &'a dyn HirDatabase) -> HirDisplayWrapper<'a, Self>
     where
@@ -351,6 +395,72 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// A rendering of a type that additionally records which parts of the text belong to which
+/// nested type.
+///
+/// `nodes` are stored in pre-order: a node always precedes its children, and the first node (if
+/// any) is the outermost type.
+#[derive(Debug, Default)]
+pub struct StructuredDisplay {
+    pub text: String,
+    pub nodes: Vec<StructuredTypeNode>,
+}
+
+#[derive(Debug)]
+pub struct StructuredTypeNode {
+    pub ty: Ty,
+    /// The part of [`StructuredDisplay::text`] this type was rendered to.
+    pub range: std::ops::Range<usize>,
+    /// The first definition linked to while rendering this type, excluding its children. For
+    /// nominal types this is the definition of the type itself.
+    pub definition: Option<ModuleDefId>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+struct StructuredWriter<'a> {
+    result: &'a mut StructuredDisplay,
+    stack: Vec<usize>,
+}
+
+impl fmt::Write for StructuredWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.result.text.push_str(s);
+        Ok(())
+    }
+}
+
+impl HirWrite for StructuredWriter<'_> {
+    fn start_location_link(&mut self, location: ModuleDefId) {
+        if let Some(&idx) = self.stack.last() {
+            self.result.nodes[idx].definition.get_or_insert(location);
+        }
+    }
+
+    fn start_type_node(&mut self, ty: &Ty) {
+        let idx = self.result.nodes.len();
+        let offset = self.result.text.len();
+        let parent = self.stack.last().copied();
+        if let Some(parent) = parent {
+            self.result.nodes[parent].children.push(idx);
+        }
+        self.result.nodes.push(StructuredTypeNode {
+            ty: ty.clone(),
+            range: offset..offset,
+            definition: None,
+            parent,
+            children: Vec::new(),
+        });
+        self.stack.push(idx);
+    }
+
+    fn end_type_node(&mut self) {
+        if let Some(idx) = self.stack.pop() {
+            self.result.nodes[idx].range.end = self.result.text.len();
+        }
+    }
+}
+
 const TYPE_HINT_TRUNCATION: &str = "…";
 
 impl<T: HirDisplay> HirDisplay for &T {
@@ -732,7 +842,20 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
 }
 
 impl HirDisplay for Ty {
-    fn hir_fmt(
+    fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
+        f.start_type_node(self);
+        let res = self.hir_fmt_kind(f);
+        f.end_type_node();
+        res
+    }
+}
+
+trait HirDisplayTyKind {
+    fn hir_fmt_kind(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError>;
+}
+
+impl HirDisplayTyKind for Ty {
+    fn hir_fmt_kind(
         &self,
         f @ &mut HirFormatter { db, .. }: &mut HirFormatter<'_>,
     ) -> Result<(), HirDisplayError> {
@@ -1033,27 +1156,38 @@ fn hir_fmt(
                         )?;
                         // FIXME: it would maybe be good to distinguish this from the alias type (when debug printing), and to show the substitution
                     }
-                    ImplTraitId::AsyncBlockTypeImplTrait(body, ..) => {
-                        let future_trait = db
-                            .lang_item(body.module(db.upcast()).krate(), LangItem::Future)
-                            .and_then(LangItemTarget::as_trait);
-                        let output = future_trait.and_then(|t| {
-                            db.trait_data(t).associated_type_by_name(&hir_expand::name!(Output))
-                        });
+                    ImplTraitId::AsyncBlockTypeImplTrait(body, ..)
+                    | ImplTraitId::GenBlockTypeImplTrait(body, ..) => {
+                        let krate = body.module(db.upcast()).krate();
+                        let (trait_, trait_name, assoc_name) = match impl_trait_id {
+                            ImplTraitId::AsyncBlockTypeImplTrait(..) => (
+                                db.lang_item(krate, LangItem::Future)
+                                    .and_then(LangItemTarget::as_trait),
+                                "Future",
+                                hir_expand::name!(Output),
+                            ),
+                            _ => (
+                                utils::iterator_trait(db.upcast(), krate),
+                                "Iterator",
+                                hir_expand::name!(Item),
+                            ),
+                        };
+                        let assoc = trait_
+                            .and_then(|t| db.trait_data(t).associated_type_by_name(&assoc_name));
                         write!(f, "impl ")?;
-                        if let Some(t) = future_trait {
+                        if let Some(t) = trait_ {
                             f.start_location_link(t.into());
                         }
-                        write!(f, "Future")?;
-                        if future_trait.is_some() {
+                        write!(f, "{trait_name}")?;
+                        if trait_.is_some() {
                             f.end_location_link();
                         }
                         write!(f, "<")?;
-                        if let Some(t) = output {
+                        if let Some(t) = assoc {
                             f.start_location_link(t.into());
                         }
-                        write!(f, "Output")?;
-                        if output.is_some() {
+                        write!(f, "{}", assoc_name.display(db.upcast()))?;
+                        if assoc.is_some() {
                             f.end_location_link();
                         }
                         write!(f, " = ")?;
@@ -1201,6 +1335,9 @@ fn hir_fmt(
                     ImplTraitId::AsyncBlockTypeImplTrait(..) => {
                         write!(f, "{{async block}}")?;
                     }
+                    ImplTraitId::GenBlockTypeImplTrait(..) => {
+                        write!(f, "{{gen block}}")?;
+                    }
                 };
             }
             TyKind::Error => {
@@ -1803,7 +1940,7 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
                 anchor.hir_fmt(f)?;
                 write!(f, ">")?;
             }
-            (_, PathKind::Plain) => {}
+            (_, PathKind::Plain | PathKind::DefSite(_)) => {}
             (_, PathKind::Abs) => {}
             (_, PathKind::Crate) => write!(f, "crate")?,
             (_, PathKind::Super(0)) => write!(f, "self")?,
@@ -1849,7 +1986,7 @@ fn hir_fmt(&self, f: &mut HirFormatter<'_>) -> Result<(), HirDisplayError> {
         }
 
         for (seg_idx, segment) in self.segments().iter().enumerate() {
-            if !matches!(self.kind(), PathKind::Plain) || seg_idx > 0 {
+            if !self.kind().is_plain() || seg_idx > 0 {
                 write!(f, "::")?;
             }
             write!(f, "{}", segment.name.display(f.db.upcast()))?;
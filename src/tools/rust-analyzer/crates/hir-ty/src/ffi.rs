@@ -0,0 +1,256 @@
+//! Checks of the types that cross a foreign function interface
+//!
+//! This follows rustc's `improper_ctypes` and `clashing_extern_declarations` lints, but is more
+//! lenient: whenever we are unsure (e.g. because of unknown types, type parameters or layouts that
+//! can't be computed), the type is considered FFI-safe and two types are considered the same.
+
+use chalk_ir::{IntTy, UintTy};
+use hir_def::{data::adt::StructFlags, AdtId, EnumId, FunctionId, VariantId};
+use rustc_hash::FxHashSet;
+use triomphe::Arc;
+
+use crate::{
+    db::HirDatabase, AliasTy, Interner, Scalar, Substitution, TraitEnvironment, Ty, TyKind,
+};
+
+/// The reason why a type can't be used in the signature of a function with a foreign ABI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FfiUnsafeReason {
+    Char,
+    Str,
+    Slice,
+    DynTrait,
+    Tuple,
+    /// An array passed by value rather than behind a pointer or inside a struct.
+    ArrayByValue,
+    /// `i128` and `u128`, which have no stable ABI.
+    Int128,
+    /// A function item, closure, coroutine or `impl Trait` type.
+    Opaque,
+    StructWithoutRepr,
+    UnionWithoutRepr,
+    /// An enum without a `#[repr]` that isn't `Option`-like.
+    EnumWithoutRepr,
+}
+
+/// The type that makes a type FFI-unsafe, which is either the type itself or one of its fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FfiUnsafe {
+    pub ty: Ty,
+    pub reason: FfiUnsafeReason,
+}
+
+/// Checks whether `ty` can be used as the type of a parameter or the return type of a function
+/// with a foreign ABI.
+pub fn ffi_safety(
+    db: &dyn HirDatabase,
+    ty: &Ty,
+    env: Arc<TraitEnvironment>,
+) -> Result<(), FfiUnsafe> {
+    if let TyKind::Array(..) = ty.kind(Interner) {
+        return Err(FfiUnsafe { ty: ty.clone(), reason: FfiUnsafeReason::ArrayByValue });
+    }
+    FfiSafetyChecker { db, env, seen: FxHashSet::default() }.check(ty)
+}
+
+/// Whether two foreign functions declared with the same symbol name have signatures the linker
+/// can't tell apart. Structs, unions and enums of different definitions, like the copies of a
+/// C struct in two crates, are the same when their fields are.
+pub fn foreign_signatures_match(db: &dyn HirDatabase, a: FunctionId, b: FunctionId) -> bool {
+    if db.function_data(a).is_varargs() != db.function_data(b).is_varargs() {
+        return false;
+    }
+    let sig_a = db.callable_item_signature(a.into());
+    let sig_b = db.callable_item_signature(b.into());
+    let (sig_a, sig_b) = (sig_a.skip_binders(), sig_b.skip_binders());
+    sig_a.params().len() == sig_b.params().len() && {
+        let mut checker = StructuralEqChecker { db, seen: FxHashSet::default() };
+        sig_a
+            .params()
+            .iter()
+            .chain([sig_a.ret()])
+            .zip(sig_b.params().iter().chain([sig_b.ret()]))
+            .all(|(a, b)| checker.same(a, b))
+    }
+}
+
+struct FfiSafetyChecker<'a> {
+    db: &'a dyn HirDatabase,
+    env: Arc<TraitEnvironment>,
+    /// The ADTs that are being or have been checked, to not loop on recursive types.
+    seen: FxHashSet<Ty>,
+}
+
+impl FfiSafetyChecker<'_> {
+    fn check(&mut self, ty: &Ty) -> Result<(), FfiUnsafe> {
+        let unsafe_ = |reason| Err(FfiUnsafe { ty: ty.clone(), reason });
+        match ty.kind(Interner) {
+            TyKind::Scalar(Scalar::Char) => unsafe_(FfiUnsafeReason::Char),
+            TyKind::Scalar(Scalar::Int(IntTy::I128) | Scalar::Uint(UintTy::U128)) => {
+                unsafe_(FfiUnsafeReason::Int128)
+            }
+            TyKind::Str => unsafe_(FfiUnsafeReason::Str),
+            TyKind::Slice(_) => unsafe_(FfiUnsafeReason::Slice),
+            TyKind::Dyn(_) => unsafe_(FfiUnsafeReason::DynTrait),
+            TyKind::Tuple(..) => unsafe_(FfiUnsafeReason::Tuple),
+            TyKind::Array(elem, _) => self.check(elem),
+            // A pointer to a sized type is a C pointer whatever it points to, but pointers to
+            // unsized types carry metadata.
+            TyKind::Ref(_, _, pointee) | TyKind::Raw(_, pointee) => match pointee.kind(Interner) {
+                TyKind::Str | TyKind::Slice(_) | TyKind::Dyn(_) => self.check(pointee),
+                _ => Ok(()),
+            },
+            // FIXME: Function pointers with the Rust ABI aren't FFI-safe, but as `FnAbi` is
+            // ignored when comparing types, an interned `fn()` may carry the ABI of another pointer.
+            TyKind::Function(_) => Ok(()),
+            TyKind::FnDef(..)
+            | TyKind::Closure(..)
+            | TyKind::Coroutine(..)
+            | TyKind::CoroutineWitness(..)
+            | TyKind::OpaqueType(..)
+            | TyKind::Alias(AliasTy::Opaque(_)) => unsafe_(FfiUnsafeReason::Opaque),
+            TyKind::Adt(crate::AdtId(adt), subst) => {
+                if !self.seen.insert(ty.clone()) {
+                    return Ok(());
+                }
+                self.check_adt(ty, *adt, subst)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_adt(&mut self, ty: &Ty, adt: AdtId, subst: &Substitution) -> Result<(), FfiUnsafe> {
+        let db = self.db;
+        let unsafe_ = |reason| Err(FfiUnsafe { ty: ty.clone(), reason });
+        match adt {
+            AdtId::StructId(id) => {
+                let data = db.struct_data(id);
+                if data.flags.contains(StructFlags::IS_PHANTOM_DATA) {
+                    return Ok(());
+                }
+                if !data.repr.map_or(false, |repr| repr.c() || repr.transparent()) {
+                    return unsafe_(FfiUnsafeReason::StructWithoutRepr);
+                }
+                self.check_fields(id.into(), subst)
+            }
+            AdtId::UnionId(id) => {
+                if !db.union_data(id).repr.map_or(false, |repr| repr.c() || repr.transparent()) {
+                    return unsafe_(FfiUnsafeReason::UnionWithoutRepr);
+                }
+                self.check_fields(id.into(), subst)
+            }
+            AdtId::EnumId(id) => {
+                let data = db.enum_data(id);
+                if data
+                    .repr
+                    .map_or(false, |repr| repr.c() || repr.transparent() || repr.int.is_some())
+                {
+                    for &(variant, _) in data.variants.iter() {
+                        self.check_fields(variant.into(), subst)?;
+                    }
+                    return Ok(());
+                }
+                match self.option_like_payload(ty, id, subst) {
+                    Some(payload) => self.check(&payload),
+                    None => unsafe_(FfiUnsafeReason::EnumWithoutRepr),
+                }
+            }
+        }
+    }
+
+    fn check_fields(&mut self, variant: VariantId, subst: &Substitution) -> Result<(), FfiUnsafe> {
+        for (_, field_ty) in self.db.field_types(variant).iter() {
+            self.check(&field_ty.clone().substitute(Interner, subst))?;
+        }
+        Ok(())
+    }
+
+    /// The type of the field of the data-carrying variant of an enum like `Option<&T>` whose
+    /// empty variant is stored in a niche of the field, which makes it as FFI-safe as the field.
+    fn option_like_payload(&self, ty: &Ty, id: EnumId, subst: &Substitution) -> Option<Ty> {
+        let data = self.db.enum_data(id);
+        let [(a, _), (b, _)] = &*data.variants else { return None };
+        let (a, b) = (self.db.field_types((*a).into()), self.db.field_types((*b).into()));
+        let payload = match (a.iter().count(), b.iter().count()) {
+            (0, 1) => b.iter().next()?.1,
+            (1, 0) => a.iter().next()?.1,
+            _ => return None,
+        };
+        let payload = payload.clone().substitute(Interner, subst);
+        // Scalars like `bool` have niches too, but C has no use for them.
+        if let TyKind::Scalar(_) = payload.kind(Interner) {
+            return None;
+        }
+        let layout = self.db.layout_of_ty(ty.clone(), self.env.clone()).ok()?;
+        let payload_layout = self.db.layout_of_ty(payload.clone(), self.env.clone()).ok()?;
+        (layout.size == payload_layout.size).then_some(payload)
+    }
+}
+
+struct StructuralEqChecker<'a> {
+    db: &'a dyn HirDatabase,
+    /// The pairs of ADTs that are being or have been compared, to not loop on recursive types.
+    seen: FxHashSet<(Ty, Ty)>,
+}
+
+impl StructuralEqChecker<'_> {
+    fn same(&mut self, a: &Ty, b: &Ty) -> bool {
+        if a == b {
+            return true;
+        }
+        match (a.kind(Interner), b.kind(Interner)) {
+            (
+                TyKind::Adt(crate::AdtId(a_id), a_subst),
+                TyKind::Adt(crate::AdtId(b_id), b_subst),
+            ) => {
+                if !self.seen.insert((a.clone(), b.clone())) {
+                    return true;
+                }
+                let (a_variants, b_variants) =
+                    (self.variants(*a_id, a_subst), self.variants(*b_id, b_subst));
+                std::mem::discriminant(a_id) == std::mem::discriminant(b_id)
+                    && a_variants.len() == b_variants.len()
+                    && a_variants.iter().zip(&b_variants).all(|(a, b)| {
+                        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| self.same(a, b))
+                    })
+            }
+            (TyKind::Array(a, a_len), TyKind::Array(b, b_len)) => a_len == b_len && self.same(a, b),
+            (TyKind::Slice(a), TyKind::Slice(b)) => self.same(a, b),
+            (TyKind::Ref(a_mut, _, a), TyKind::Ref(b_mut, _, b))
+            | (TyKind::Raw(a_mut, a), TyKind::Raw(b_mut, b)) => a_mut == b_mut && self.same(a, b),
+            (TyKind::Tuple(_, a), TyKind::Tuple(_, b)) => self.same_substs(a, b),
+            (TyKind::Function(a), TyKind::Function(b)) => {
+                a.sig.variadic == b.sig.variadic
+                    && self.same_substs(&a.substitution.0, &b.substitution.0)
+            }
+            _ => false,
+        }
+    }
+
+    fn same_substs(&mut self, a: &Substitution, b: &Substitution) -> bool {
+        let (a, b) = (a.type_parameters(Interner), b.type_parameters(Interner));
+        let (a, b): (Vec<_>, Vec<_>) = (a.collect(), b.collect());
+        a.len() == b.len() && a.iter().zip(&b).all(|(a, b)| self.same(a, b))
+    }
+
+    /// The field types of each variant of `adt`, a struct or union having a single one.
+    fn variants(&self, adt: AdtId, subst: &Substitution) -> Vec<Vec<Ty>> {
+        let variants: Vec<VariantId> = match adt {
+            AdtId::StructId(id) => vec![id.into()],
+            AdtId::UnionId(id) => vec![id.into()],
+            AdtId::EnumId(id) => {
+                self.db.enum_data(id).variants.iter().map(|&(it, _)| it.into()).collect()
+            }
+        };
+        variants
+            .into_iter()
+            .map(|variant| {
+                self.db
+                    .field_types(variant)
+                    .iter()
+                    .map(|(_, ty)| ty.clone().substitute(Interner, subst))
+                    .collect()
+            })
+            .collect()
+    }
+}
@@ -22,11 +22,11 @@
 mod path;
 pub(crate) mod unify;
 
-use std::{convert::identity, ops::Index};
+use std::{convert::identity, mem, ops::Index};
 
 use chalk_ir::{
-    cast::Cast, fold::TypeFoldable, interner::HasInterner, DebruijnIndex, Mutability, Safety,
-    Scalar, TyKind, TypeFlags, Variance,
+    cast::Cast, fold::TypeFoldable, interner::HasInterner, DebruijnIndex, GoalData, Mutability,
+    Safety, Scalar, TyKind, TypeFlags, Variance,
 };
 use either::Either;
 use hir_def::{
@@ -60,7 +60,7 @@
     utils::{InTypeConstIdMetadata, UnevaluatedConstEvaluatorFolder},
     AliasEq, AliasTy, Binders, ClosureId, Const, DomainGoal, GenericArg, Goal, ImplTraitId,
     InEnvironment, Interner, Lifetime, ProjectionTy, RpitId, Substitution, TraitEnvironment,
-    TraitRef, Ty, TyBuilder, TyExt,
+    TraitRef, Ty, TyBuilder, TyExt, WhereClause,
 };
 
 // This lint has a false positive here. See the link below for details.
@@ -413,6 +413,9 @@ pub struct InferenceResult {
     pub tuple_field_access_types: FxHashMap<TupleId, Substitution>,
     pub diagnostics: Vec<InferenceDiagnostic>,
     pub type_of_expr: ArenaMap<ExprId, Ty>,
+    /// For each expression that was inferred against a known type, like a function argument or
+    /// an initializer of an annotated `let`, records that type.
+    pub expected_type_of_expr: ArenaMap<ExprId, Ty>,
     /// For each pattern record the type it resolves to.
     ///
     /// **Note**: When a pattern type is resolved it may still contain
@@ -423,6 +426,9 @@ pub struct InferenceResult {
     /// Type of the result of `.into_iter()` on the for. `ExprId` is the one of the whole for loop.
     pub type_of_for_iterator: FxHashMap<ExprId, Ty>,
     type_mismatches: FxHashMap<ExprOrPatId, TypeMismatch>,
+    /// Where clauses required by the body that don't hold, e.g. `T: Add<T>` for `a + b` on a type
+    /// parameter `T` without that bound.
+    pub unfulfilled_where_clauses: Vec<WhereClause>,
     /// Interned common types to return references to.
     standard_types: InternedStandardTypes,
     /// Stores the types which were implicitly dereferenced in pattern binding modes.
@@ -643,11 +649,13 @@ pub(crate) fn resolve_all(self) -> InferenceResult {
             assoc_resolutions,
             diagnostics,
             type_of_expr,
+            expected_type_of_expr,
             type_of_pat,
             type_of_binding,
             type_of_rpit,
             type_of_for_iterator,
             type_mismatches,
+            unfulfilled_where_clauses,
             standard_types: _,
             pat_adjustments,
             binding_modes: _,
@@ -677,6 +685,9 @@ pub(crate) fn resolve_all(self) -> InferenceResult {
         for ty in type_of_expr.values_mut() {
             *ty = table.resolve_completely(ty.clone());
         }
+        for ty in expected_type_of_expr.values_mut() {
+            *ty = table.resolve_completely(ty.clone());
+        }
         for ty in type_of_pat.values_mut() {
             *ty = table.resolve_completely(ty.clone());
         }
@@ -728,6 +739,16 @@ pub(crate) fn resolve_all(self) -> InferenceResult {
             }
             true
         });
+        for obligation in mem::take(&mut table.unfulfilled_obligations) {
+            let GoalData::DomainGoal(DomainGoal::Holds(clause)) = obligation.goal.data(Interner)
+            else {
+                continue;
+            };
+            let clause = table.resolve_completely(clause.clone());
+            if !unfulfilled_where_clauses.contains(&clause) {
+                unfulfilled_where_clauses.push(clause);
+            }
+        }
         for (_, subst) in method_resolutions.values_mut() {
             *subst = table.resolve_completely(subst.clone());
         }
@@ -463,6 +463,7 @@ fn walk_expr_without_adjust(&mut self, tgt_expr: ExprId) {
                 }
             }
             Expr::Async { statements, tail, .. }
+            | Expr::Gen { statements, tail, .. }
             | Expr::Unsafe { statements, tail, .. }
             | Expr::Block { statements, tail, .. } => {
                 for s in statements.iter() {
@@ -14,7 +14,7 @@
     },
     lang_item::{LangItem, LangItemTarget},
     path::{GenericArg, GenericArgs},
-    BlockId, ConstParamId, FieldId, ItemContainerId, Lookup, TupleFieldId, TupleId,
+    BlockId, ConstBlockId, ConstParamId, FieldId, ItemContainerId, Lookup, TupleFieldId, TupleId,
 };
 use hir_expand::name::{name, Name};
 use stdx::always;
@@ -124,6 +124,9 @@ fn infer_expr_coerce_never(&mut self, expr: ExprId, expected: &Expectation) -> T
 
     fn infer_expr_inner(&mut self, tgt_expr: ExprId, expected: &Expectation) -> Ty {
         self.db.unwind_if_cancelled();
+        if let Some(expected_ty) = expected.only_has_type(&mut self.table) {
+            self.result.expected_type_of_expr.insert(tgt_expr, expected_ty);
+        }
 
         let ty = match &self.body[tgt_expr] {
             Expr::Missing => self.err_ty(),
@@ -171,16 +174,13 @@ fn infer_expr_inner(&mut self, tgt_expr: ExprId, expected: &Expectation) -> Ty {
             Expr::Unsafe { id, statements, tail } => {
                 self.infer_block(tgt_expr, *id, statements, *tail, None, expected)
             }
-            Expr::Const(id) => {
-                self.with_breakable_ctx(BreakableKind::Border, None, None, |this| {
-                    let loc = this.db.lookup_intern_anonymous_const(*id);
-                    this.infer_expr(loc.root, expected)
-                })
-                .1
-            }
+            &Expr::Const(id) => self.infer_const_block(id, expected),
             Expr::Async { id, statements, tail } => {
                 self.infer_async_block(tgt_expr, id, statements, tail)
             }
+            Expr::Gen { id, statements, tail } => {
+                self.infer_gen_block(tgt_expr, id, statements, tail)
+            }
             &Expr::Loop { body, label } => {
                 // FIXME: should be:
                 // let ty = expected.coercion_target_type(&mut self.table);
@@ -934,6 +934,54 @@ fn infer_async_block(
         self.lower_async_block_type_impl_trait(inner_ty, tgt_expr)
     }
 
+    /// Infers a `gen` block as a coroutine yielding its items and returning `()`, its type is an
+    /// `impl Iterator<Item = Y>` over the type of the yielded values.
+    fn infer_gen_block(
+        &mut self,
+        tgt_expr: ExprId,
+        id: &Option<BlockId>,
+        statements: &[Statement],
+        tail: &Option<ExprId>,
+    ) -> Ty {
+        let unit = self.result.standard_types.unit.clone();
+        let yield_ty = self.table.new_type_var();
+        let prev_diverges = mem::replace(&mut self.diverges, Diverges::Maybe);
+        let prev_ret_ty = mem::replace(&mut self.return_ty, unit.clone());
+        let prev_ret_coercion =
+            mem::replace(&mut self.return_coercion, Some(CoerceMany::new(unit.clone())));
+        let prev_resume_yield_tys =
+            mem::replace(&mut self.resume_yield_tys, Some((unit.clone(), yield_ty.clone())));
+
+        self.with_breakable_ctx(BreakableKind::Border, None, None, |this| {
+            this.infer_block(tgt_expr, *id, statements, *tail, None, &Expectation::has_type(unit))
+        });
+
+        self.diverges = prev_diverges;
+        self.return_ty = prev_ret_ty;
+        self.return_coercion = prev_ret_coercion;
+        self.resume_yield_tys = prev_resume_yield_tys;
+
+        let impl_trait_id = crate::ImplTraitId::GenBlockTypeImplTrait(self.owner, tgt_expr);
+        let opaque_ty_id = self.db.intern_impl_trait_id(impl_trait_id).into();
+        TyKind::OpaqueType(opaque_ty_id, Substitution::from1(Interner, yield_ty)).intern(Interner)
+    }
+
+    /// Infers a const block like the body of a separate const item. It shares the inference table
+    /// of its parent, but can't `return` from the parent or affect whether it diverges.
+    fn infer_const_block(&mut self, id: ConstBlockId, expected: &Expectation) -> Ty {
+        let root = self.db.lookup_intern_anonymous_const(id).root;
+        let prev_diverges = mem::replace(&mut self.diverges, Diverges::Maybe);
+        let prev_ret_coercion = self.return_coercion.take();
+
+        let (_, ty) = self.with_breakable_ctx(BreakableKind::Border, None, None, |this| {
+            this.infer_expr(root, expected)
+        });
+
+        self.diverges = prev_diverges;
+        self.return_coercion = prev_ret_coercion;
+        ty
+    }
+
     pub(crate) fn lower_async_block_type_impl_trait(
         &mut self,
         inner_ty: Ty,
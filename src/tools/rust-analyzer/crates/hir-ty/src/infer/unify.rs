@@ -178,6 +178,8 @@ pub(crate) struct InferenceTable<'a> {
     var_unification_table: ChalkInferenceTable,
     type_variable_table: Vec<TypeVariableFlags>,
     pending_obligations: Vec<Canonicalized<InEnvironment<Goal>>>,
+    /// Obligations for which the trait solver found no solution at all.
+    pub(crate) unfulfilled_obligations: Vec<InEnvironment<Goal>>,
     /// Double buffer used in [`Self::resolve_obligations_as_possible`] to cut down on
     /// temporary allocations.
     resolve_obligations_buffer: Vec<Canonicalized<InEnvironment<Goal>>>,
@@ -186,6 +188,7 @@ pub(crate) struct InferenceTable<'a> {
 pub(crate) struct InferenceTableSnapshot {
     var_table_snapshot: chalk_solve::infer::InferenceSnapshot<Interner>,
     pending_obligations: Vec<Canonicalized<InEnvironment<Goal>>>,
+    unfulfilled_obligations_len: usize,
     type_variable_table_snapshot: Vec<TypeVariableFlags>,
 }
 
@@ -197,6 +200,7 @@ pub(crate) fn new(db: &'a dyn HirDatabase, trait_env: Arc<TraitEnvironment>) ->
             var_unification_table: ChalkInferenceTable::new(),
             type_variable_table: Vec::new(),
             pending_obligations: Vec::new(),
+            unfulfilled_obligations: Vec::new(),
             resolve_obligations_buffer: Vec::new(),
         }
     }
@@ -512,6 +516,7 @@ pub(crate) fn snapshot(&mut self) -> InferenceTableSnapshot {
         InferenceTableSnapshot {
             var_table_snapshot,
             pending_obligations,
+            unfulfilled_obligations_len: self.unfulfilled_obligations.len(),
             type_variable_table_snapshot,
         }
     }
@@ -521,6 +526,7 @@ pub(crate) fn rollback_to(&mut self, snapshot: InferenceTableSnapshot) {
         self.var_unification_table.rollback_to(snapshot.var_table_snapshot);
         self.type_variable_table = snapshot.type_variable_table_snapshot;
         self.pending_obligations = snapshot.pending_obligations;
+        self.unfulfilled_obligations.truncate(snapshot.unfulfilled_obligations_len);
     }
 
     #[tracing::instrument(skip_all)]
@@ -549,8 +555,14 @@ pub(crate) fn register_obligation(&mut self, goal: Goal) {
     fn register_obligation_in_env(&mut self, goal: InEnvironment<Goal>) {
         let canonicalized = self.canonicalize(goal);
         let solution = self.try_resolve_obligation(&canonicalized);
-        if matches!(solution, Some(Solution::Ambig(_))) {
-            self.pending_obligations.push(canonicalized);
+        match solution {
+            Some(Solution::Ambig(_)) => self.pending_obligations.push(canonicalized),
+            Some(Solution::Unique(_)) => (),
+            None => self.unfulfilled_obligations.push(chalk_ir::Substitute::apply(
+                &canonicalized.free_vars,
+                canonicalized.value.value,
+                Interner,
+            )),
         }
     }
 
@@ -28,7 +28,7 @@
 };
 
 pub use self::{
-    adt::{layout_of_adt_query, layout_of_adt_recover},
+    adt::{layout_of_adt_query, layout_of_adt_recover, layout_of_adt_with_default_repr},
     target::target_data_layout_query,
 };
 
@@ -389,7 +389,8 @@ pub fn layout_of_ty_query(
                     let infer = db.infer(func.into());
                     return db.layout_of_ty(infer.type_of_rpit[idx].clone(), trait_env);
                 }
-                crate::ImplTraitId::AsyncBlockTypeImplTrait(_, _) => {
+                crate::ImplTraitId::AsyncBlockTypeImplTrait(_, _)
+                | crate::ImplTraitId::GenBlockTypeImplTrait(_, _) => {
                     return Err(LayoutError::NotImplemented)
                 }
             }
@@ -30,6 +30,26 @@ pub fn layout_of_adt_query(
     def: AdtId,
     subst: Substitution,
     trait_env: Arc<TraitEnvironment>,
+) -> Result<Arc<Layout>, LayoutError> {
+    layout_of_adt_with_repr(db, def, subst, trait_env, |repr| repr)
+}
+
+/// Computes the layout the ADT would have without its `#[repr]` attribute.
+pub fn layout_of_adt_with_default_repr(
+    db: &dyn HirDatabase,
+    def: AdtId,
+    subst: Substitution,
+    trait_env: Arc<TraitEnvironment>,
+) -> Result<Arc<Layout>, LayoutError> {
+    layout_of_adt_with_repr(db, def, subst, trait_env, |_| None)
+}
+
+fn layout_of_adt_with_repr(
+    db: &dyn HirDatabase,
+    def: AdtId,
+    subst: Substitution,
+    trait_env: Arc<TraitEnvironment>,
+    map_repr: impl Fn(Option<ReprOptions>) -> Option<ReprOptions>,
 ) -> Result<Arc<Layout>, LayoutError> {
     let krate = trait_env.krate;
     let Ok(target) = db.target_data_layout(krate) else {
@@ -48,13 +68,13 @@ pub fn layout_of_adt_query(
             let data = db.struct_data(s);
             let mut r = SmallVec::<[_; 1]>::new();
             r.push(handle_variant(s.into(), &data.variant_data)?);
-            (r, data.repr.unwrap_or_default())
+            (r, map_repr(data.repr).unwrap_or_default())
         }
         AdtId::UnionId(id) => {
             let data = db.union_data(id);
             let mut r = SmallVec::new();
             r.push(handle_variant(id.into(), &data.variant_data)?);
-            (r, data.repr.unwrap_or_default())
+            (r, map_repr(data.repr).unwrap_or_default())
         }
         AdtId::EnumId(e) => {
             let data = db.enum_data(e);
@@ -63,7 +83,7 @@ pub fn layout_of_adt_query(
                 .iter()
                 .map(|&(v, _)| handle_variant(v.into(), &db.enum_variant_data(v).variant_data))
                 .collect::<Result<SmallVec<_>, _>>()?;
-            (r, data.repr.unwrap_or_default())
+            (r, map_repr(data.repr).unwrap_or_default())
         }
     };
     let variants = variants
@@ -34,10 +34,12 @@
 pub mod db;
 pub mod diagnostics;
 pub mod display;
+pub mod ffi;
 pub mod lang_items;
 pub mod layout;
 pub mod method_resolution;
 pub mod mir;
+pub mod object_safety;
 pub mod primitive;
 pub mod traits;
 
@@ -84,17 +86,18 @@
 };
 pub use interner::Interner;
 pub use lower::{
-    associated_type_shorthand_candidates, CallableDefId, ImplTraitLoweringMode, ParamLoweringMode,
-    TyDefId, TyLoweringContext, ValueTyDefId,
+    associated_type_shorthand_candidates, fn_sig_lifetimes, CallableDefId, FnSigLifetimes,
+    ImplTraitLoweringMode, InputLifetime, LifetimePosition, ParamLoweringMode, TyDefId,
+    TyLoweringContext, ValueTyDefId,
 };
 pub use mapping::{
     from_assoc_type_id, from_chalk_trait_id, from_foreign_def_id, from_placeholder_idx,
     lt_from_placeholder_idx, to_assoc_type_id, to_chalk_trait_id, to_foreign_def_id,
     to_placeholder_idx,
 };
-pub use method_resolution::check_orphan_rules;
+pub use method_resolution::{check_orphan_rules, orphan_check_impl, OrphanCheckError};
 pub use traits::TraitEnvironment;
-pub use utils::{all_super_traits, is_fn_unsafe_to_call};
+pub use utils::{all_super_trait_refs, all_super_traits, is_fn_unsafe_to_call};
 
 pub use chalk_ir::{
     cast::Cast, AdtId, BoundVar, DebruijnIndex, Mutability, Safety, Scalar, TyVariableKind,
@@ -586,6 +589,7 @@ fn try_fold_with<E>(
 pub enum ImplTraitId {
     ReturnTypeImplTrait(hir_def::FunctionId, RpitId),
     AsyncBlockTypeImplTrait(hir_def::DefWithBodyId, ExprId),
+    GenBlockTypeImplTrait(hir_def::DefWithBodyId, ExprId),
 }
 impl_intern_value_trivial!(ImplTraitId);
 
@@ -931,6 +935,22 @@ pub fn collect_placeholders<T>(value: &T, db: &dyn HirDatabase) -> Vec<TypeOrCon
     collector.placeholders.into_iter().collect()
 }
 
+/// Replaces the type placeholders in `value` with the types `f` returns for them.
+pub fn replace_placeholders<T: HasInterner<Interner = Interner> + TypeFoldable<Interner>>(
+    value: T,
+    db: &dyn HirDatabase,
+    mut f: impl FnMut(TypeOrConstParamId) -> Option<Ty>,
+) -> T {
+    fold_tys(
+        value,
+        |ty, _| match ty.kind(Interner) {
+            TyKind::Placeholder(idx) => f(from_placeholder_idx(db, *idx)).unwrap_or(ty),
+            _ => ty,
+        },
+        DebruijnIndex::INNERMOST,
+    )
+}
+
 pub fn known_const_to_ast(konst: &Const, db: &dyn HirDatabase) -> Option<ConstArg> {
     if let ConstValue::Concrete(c) = &konst.interned().value {
         match c.interned {
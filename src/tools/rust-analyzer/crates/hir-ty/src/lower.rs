@@ -5,6 +5,9 @@
 //!  - Building the type for an item: This happens through the `ty` query.
 //!
 //! This usually involves resolving names, collecting generic arguments etc.
+//!
+//! Types are lowered without their lifetimes. The lifetimes of function signatures are collected
+//! and resolved by the elision rules in `fn_sig_lifetimes`.
 use std::{
     cell::{Cell, RefCell, RefMut},
     iter,
@@ -30,7 +33,9 @@
     nameres::MacroSubNs,
     path::{GenericArg, GenericArgs, ModPath, Path, PathKind, PathSegment, PathSegments},
     resolver::{HasResolver, Resolver, TypeNs},
-    type_ref::{ConstRef, TraitBoundModifier, TraitRef as HirTraitRef, TypeBound, TypeRef},
+    type_ref::{
+        ConstRef, LifetimeRef, TraitBoundModifier, TraitRef as HirTraitRef, TypeBound, TypeRef,
+    },
     AdtId, AssocItemId, ConstId, ConstParamId, DefWithBodyId, EnumId, EnumVariantId, FunctionId,
     GenericDefId, HasModule, ImplId, InTypeConstLoc, ItemContainerId, LocalFieldId, Lookup,
     ModuleDefId, StaticId, StructId, TraitId, TypeAliasId, TypeOrConstParamId, TypeOwnerId,
@@ -1683,6 +1688,208 @@ fn fn_sig_for_fn(db: &dyn HirDatabase, def: FunctionId) -> PolyFnSig {
     make_binders(db, &generics, sig)
 }
 
+/// The lifetimes in the signature of a function, with the elided ones resolved by the lifetime
+/// elision rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FnSigLifetimes {
+    /// The lifetime positions of every parameter, `self` included, in the order they are written.
+    pub params: Vec<Vec<LifetimePosition>>,
+    /// The lifetime positions of the return type, in the order they are written.
+    pub ret: Vec<LifetimePosition>,
+    /// The input lifetime the elided lifetimes of the return type stand for, if the elision rules
+    /// determine one.
+    pub output: Option<InputLifetime>,
+}
+
+/// A place in a type that names a lifetime or leaves it out.
+///
+/// Function pointers and the parenthesized `Fn` traits aren't looked into, as they have their own
+/// elision scope.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LifetimePosition {
+    /// A named lifetime, `'a` or `'static`.
+    Named(Name),
+    /// The anonymous lifetime, `'_`.
+    Anonymous,
+    /// A reference without a lifetime, `&T`.
+    Ref,
+    /// A path to a type without lifetime arguments, with the number of lifetime parameters of
+    /// the type, which are all elided.
+    Path(usize),
+}
+
+impl LifetimePosition {
+    /// Whether the position leaves its lifetimes to elision.
+    pub fn is_elided(&self) -> bool {
+        !matches!(self, LifetimePosition::Named(_) | LifetimePosition::Path(0))
+    }
+
+    fn from_ref(lifetime: &LifetimeRef) -> LifetimePosition {
+        match lifetime.name.as_str() {
+            Some("'_") => LifetimePosition::Anonymous,
+            _ => LifetimePosition::Named(lifetime.name.clone()),
+        }
+    }
+
+    /// The number of lifetimes at the position.
+    pub fn lifetime_count(&self) -> usize {
+        match self {
+            LifetimePosition::Path(count) => *count,
+            _ => 1,
+        }
+    }
+}
+
+/// A lifetime of the parameters of a function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputLifetime {
+    Named(Name),
+    /// The lifetime elided at `params[param][position]` of [`FnSigLifetimes`].
+    Elided {
+        param: usize,
+        position: usize,
+    },
+}
+
+/// Collects the lifetime positions of the signature of `def` and resolves the elided lifetimes
+/// of its return type.
+pub fn fn_sig_lifetimes(db: &dyn HirDatabase, def: FunctionId) -> FnSigLifetimes {
+    let data = db.function_data(def);
+    let resolver = def.resolver(db.upcast());
+    let collect = |type_ref: &TypeRef| {
+        let mut positions = Vec::new();
+        lifetime_positions(db, &resolver, type_ref, &mut positions);
+        positions
+    };
+    let params: Vec<_> = data.params.iter().map(|it| collect(it)).collect();
+    let ret = collect(&data.ret_type);
+
+    // The lifetime of a reference `self` wins, otherwise there has to be exactly one lifetime in
+    // the parameters. Every elided lifetime of the parameters is a distinct one.
+    let self_lifetime = match data.params.first().map(|it| &**it) {
+        Some(TypeRef::Reference(..)) if data.has_self_param() => {
+            params[0].first().map(|it| match it {
+                LifetimePosition::Named(name) => InputLifetime::Named(name.clone()),
+                _ => InputLifetime::Elided { param: 0, position: 0 },
+            })
+        }
+        _ => None,
+    };
+    let output = self_lifetime.or_else(|| {
+        let mut inputs = params.iter().enumerate().flat_map(|(param, positions)| {
+            positions.iter().enumerate().flat_map(move |(position, it)| match it {
+                LifetimePosition::Named(name) => vec![InputLifetime::Named(name.clone())],
+                _ => vec![InputLifetime::Elided { param, position }; it.lifetime_count()],
+            })
+        });
+        let first = inputs.next()?;
+        let is_only = inputs.all(|it| match (&it, &first) {
+            (InputLifetime::Named(it), InputLifetime::Named(first)) => it == first,
+            _ => false,
+        });
+        is_only.then_some(first)
+    });
+
+    FnSigLifetimes { params, ret, output }
+}
+
+fn lifetime_positions(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    type_ref: &TypeRef,
+    acc: &mut Vec<LifetimePosition>,
+) {
+    match type_ref {
+        TypeRef::Reference(inner, lt, _) => {
+            acc.push(lt.as_ref().map_or(LifetimePosition::Ref, LifetimePosition::from_ref));
+            lifetime_positions(db, resolver, inner, acc);
+        }
+        TypeRef::Tuple(types) => {
+            types.iter().for_each(|it| lifetime_positions(db, resolver, it, acc))
+        }
+        TypeRef::RawPtr(inner, _) | TypeRef::Array(inner, _) | TypeRef::Slice(inner) => {
+            lifetime_positions(db, resolver, inner, acc)
+        }
+        TypeRef::Path(path) => path_lifetime_positions(db, resolver, path, true, acc),
+        TypeRef::ImplTrait(bounds) | TypeRef::DynTrait(bounds) => {
+            bounds_lifetime_positions(db, resolver, bounds, acc)
+        }
+        TypeRef::Fn(..)
+        | TypeRef::Never
+        | TypeRef::Placeholder
+        | TypeRef::Macro(_)
+        | TypeRef::Error => {}
+    }
+}
+
+fn bounds_lifetime_positions(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    bounds: &[Interned<TypeBound>],
+    acc: &mut Vec<LifetimePosition>,
+) {
+    for bound in bounds {
+        match &**bound {
+            TypeBound::Path(path, _) | TypeBound::ForLifetime(_, path) => {
+                path_lifetime_positions(db, resolver, path, false, acc)
+            }
+            TypeBound::Lifetime(lt) => acc.push(LifetimePosition::from_ref(lt)),
+            TypeBound::Error => {}
+        }
+    }
+}
+
+/// Collects the lifetime positions of `path`. `Self` has none, and qualified paths like
+/// `<T as Trait>::Assoc` aren't looked into.
+fn path_lifetime_positions(
+    db: &dyn HirDatabase,
+    resolver: &Resolver,
+    path: &Path,
+    is_type: bool,
+    acc: &mut Vec<LifetimePosition>,
+) {
+    let segments = path.segments();
+    if path.is_self_type()
+        || path.type_anchor().is_some()
+        || segments.iter().any(|it| it.args_and_bindings.map_or(false, |it| it.has_self_type))
+    {
+        return;
+    }
+    for (idx, segment) in segments.iter().enumerate() {
+        let args = segment.args_and_bindings;
+        if args.map_or(false, |it| it.desugared_from_fn) {
+            continue;
+        }
+        let has_lifetime_args =
+            args.map_or(false, |it| it.args.iter().any(|it| matches!(it, GenericArg::Lifetime(_))));
+        if is_type && idx == segments.len() - 1 && !has_lifetime_args {
+            let def: Option<GenericDefId> =
+                match resolver.resolve_path_in_type_ns_fully(db.upcast(), path) {
+                    Some(TypeNs::AdtId(it)) => Some(it.into()),
+                    Some(TypeNs::TypeAliasId(it)) => Some(it.into()),
+                    _ => None,
+                };
+            acc.push(LifetimePosition::Path(
+                def.map_or(0, |it| db.generic_params(it).lifetimes.len()),
+            ));
+        }
+        let Some(args) = args else { continue };
+        for arg in args.args.iter() {
+            match arg {
+                GenericArg::Type(ty) => lifetime_positions(db, resolver, ty, acc),
+                GenericArg::Lifetime(lt) => acc.push(LifetimePosition::from_ref(lt)),
+                GenericArg::Const(_) => {}
+            }
+        }
+        for binding in args.bindings.iter() {
+            if let Some(ty) = &binding.type_ref {
+                lifetime_positions(db, resolver, ty, acc);
+            }
+            bounds_lifetime_positions(db, resolver, &binding.bounds, acc);
+        }
+    }
+}
+
 /// Build the declared type of a function. This should not need to look at the
 /// function body.
 fn type_for_fn(db: &dyn HirDatabase, def: FunctionId) -> Binders<Ty> {
@@ -2013,7 +2220,7 @@ pub(crate) fn generic_arg_to_chalk<'a, T>(
             // it works.
             if let TypeRef::Path(p) = t {
                 let p = p.mod_path()?;
-                if p.kind == PathKind::Plain {
+                if p.kind.is_plain() {
                     if let [n] = p.segments() {
                         let c = ConstRef::Path(n.clone());
                         return Some(for_const(this, &c, c_ty).cast(Interner));
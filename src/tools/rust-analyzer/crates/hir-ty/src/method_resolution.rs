@@ -10,7 +10,7 @@
     data::{adt::StructFlags, ImplData},
     nameres::DefMap,
     AssocItemId, BlockId, ConstId, FunctionId, HasModule, ImplId, ItemContainerId, Lookup,
-    ModuleId, TraitId,
+    ModuleId, TraitId, TypeParamId,
 };
 use hir_expand::name::Name;
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -21,7 +21,7 @@
 use crate::{
     autoderef::{self, AutoderefKind},
     db::HirDatabase,
-    from_chalk_trait_id, from_foreign_def_id,
+    from_chalk_trait_id, from_foreign_def_id, from_placeholder_idx,
     infer::{unify::InferenceTable, Adjust, Adjustment, OverloadedDeref, PointerCast},
     primitive::{FloatTy, IntTy, UintTy},
     static_lifetime, to_chalk_trait_id,
@@ -843,6 +843,20 @@ fn is_inherent_impl_coherent(
     }
 }
 
+/// Why an impl violates the orphan rules, see [`orphan_check_impl`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrphanCheckError {
+    /// None of the types `T0..=Tn` is a local type.
+    NonLocalInputType,
+    /// A type parameter appears uncovered before the first local type.
+    UncoveredTyParam(TypeParamId),
+}
+
+/// Checks whether the impl satisfies the orphan rules.
+pub fn check_orphan_rules(db: &dyn HirDatabase, impl_: ImplId) -> bool {
+    orphan_check_impl(db, impl_).is_ok()
+}
+
 /// Checks whether the impl satisfies the orphan rules.
 ///
 /// Given `impl<P1..=Pn> Trait<T1..=Tn> for T0`, an `impl`` is valid only if at least one of the following is true:
@@ -850,11 +864,11 @@ fn is_inherent_impl_coherent(
 /// - All of
 ///   - At least one of the types `T0..=Tn`` must be a local type. Let `Ti`` be the first such type.
 ///   - No uncovered type parameters `P1..=Pn` may appear in `T0..Ti`` (excluding `Ti`)
-pub fn check_orphan_rules(db: &dyn HirDatabase, impl_: ImplId) -> bool {
+pub fn orphan_check_impl(db: &dyn HirDatabase, impl_: ImplId) -> Result<(), OrphanCheckError> {
     let substs = TyBuilder::placeholder_subst(db, impl_);
     let Some(impl_trait) = db.impl_trait(impl_) else {
         // not a trait impl
-        return true;
+        return Ok(());
     };
 
     let local_crate = impl_.lookup(db.upcast()).container.krate();
@@ -864,39 +878,45 @@ pub fn check_orphan_rules(db: &dyn HirDatabase, impl_: ImplId) -> bool {
     let trait_id = from_chalk_trait_id(trait_ref.trait_id);
     if is_local(trait_id.module(db.upcast()).krate()) {
         // trait to be implemented is local
-        return true;
+        return Ok(());
     }
 
-    let unwrap_fundamental = |ty: Ty| match ty.kind(Interner) {
-        TyKind::Ref(_, _, referenced) => referenced.clone(),
-        &TyKind::Adt(AdtId(hir_def::AdtId::StructId(s)), ref subs) => {
-            let struct_data = db.struct_data(s);
-            if struct_data.flags.contains(StructFlags::IS_FUNDAMENTAL) {
-                let next = subs.type_parameters(Interner).next();
-                match next {
-                    Some(ty) => ty,
-                    None => ty,
-                }
-            } else {
-                ty
+    // Fundamental types like `&T` and `Box<T>` don't cover the types they wrap.
+    let unwrap_fundamental = |mut ty: Ty| loop {
+        let next = match ty.kind(Interner) {
+            TyKind::Ref(_, _, referenced) => Some(referenced.clone()),
+            &TyKind::Adt(AdtId(hir_def::AdtId::StructId(s)), ref subs)
+                if db.struct_data(s).flags.contains(StructFlags::IS_FUNDAMENTAL) =>
+            {
+                subs.type_parameters(Interner).next()
             }
+            _ => None,
+        };
+        match next {
+            Some(next) => ty = next,
+            None => break ty,
         }
-        _ => ty,
     };
     //   - At least one of the types `T0..=Tn`` must be a local type. Let `Ti`` be the first such type.
-    let is_not_orphan = trait_ref.substitution.type_parameters(Interner).any(|ty| {
-        match unwrap_fundamental(ty).kind(Interner) {
+    //   - No uncovered type parameters `P1..=Pn` may appear in `T0..Ti`` (excluding `Ti`)
+    for ty in trait_ref.substitution.type_parameters(Interner) {
+        let is_local_ty = match unwrap_fundamental(ty).kind(Interner) {
             &TyKind::Adt(AdtId(id), _) => is_local(id.module(db.upcast()).krate()),
             TyKind::Error => true,
             TyKind::Dyn(it) => it.principal().map_or(false, |trait_ref| {
                 is_local(from_chalk_trait_id(trait_ref.trait_id).module(db.upcast()).krate())
             }),
+            &TyKind::Placeholder(idx) => {
+                let param = TypeParamId::from_unchecked(from_placeholder_idx(db, idx));
+                return Err(OrphanCheckError::UncoveredTyParam(param));
+            }
             _ => false,
+        };
+        if is_local_ty {
+            return Ok(());
         }
-    });
-    // FIXME: param coverage
-    //   - No uncovered type parameters `P1..=Pn` may appear in `T0..Ti`` (excluding `Ti`)
-    is_not_orphan
+    }
+    Err(OrphanCheckError::NonLocalInputType)
 }
 
 pub fn iterate_path_candidates(
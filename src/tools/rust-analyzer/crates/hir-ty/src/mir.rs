@@ -29,10 +29,12 @@
 
 pub use borrowck::{borrowck_query, BorrowckResult, MutabilityReason};
 pub use eval::{
-    interpret_mir, pad16, render_const_using_debug_impl, Evaluator, MirEvalError, VTableMap,
+    interpret_mir, interpret_mir_with_args, pad16, render_const_using_debug_impl, Evaluator,
+    MirEvalError, VTableMap,
 };
 pub use lower::{
-    lower_to_mir, mir_body_for_closure_query, mir_body_query, mir_body_recover, MirLowerError,
+    lower_expr_to_mir, lower_to_mir, mir_body_for_closure_query, mir_body_query, mir_body_recover,
+    MirLowerError,
 };
 pub use monomorphization::{
     monomorphize_mir_body_bad, monomorphized_mir_body_for_closure_query,
@@ -1,6 +1,20 @@
 //! This module provides a MIR interpreter, which is used in const eval.
-
-use std::{borrow::Cow, cell::RefCell, fmt::Write, iter, mem, ops::Range};
+//!
+//! The interpreter runs in-process, inside the query that needs the evaluated value, as it reads
+//! MIR bodies, types and layouts through the database. To keep pathological code from hanging the
+//! server, evaluation is bounded by executed blocks, wall-clock time, stack depth and memory, and
+//! it is cancelled like any other query when the database changes. Panics of the interpreter are
+//! turned into errors.
+
+use std::{
+    borrow::Cow,
+    cell::RefCell,
+    fmt::Write,
+    iter, mem,
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    time::{Duration, Instant},
+};
 
 use base_db::{CrateId, FileId};
 use chalk_ir::{cast::Cast, Mutability};
@@ -178,6 +192,8 @@ pub struct Evaluator<'a> {
     assert_placeholder_ty_is_unused: bool,
     /// A general limit on execution, to prevent non terminating programs from breaking r-a main process
     execution_limit: usize,
+    /// A limit on wall-clock time, for programs that make slow progress without hitting `execution_limit`
+    deadline: Instant,
     /// An additional limit on stack depth, to prevent stack overflow
     stack_depth_limit: usize,
     /// Maximum count of bytes that heap and stack can grow
@@ -331,6 +347,7 @@ pub enum MirEvalError {
     InvalidConst(Const),
     InFunction(Box<MirEvalError>, Vec<(Either<FunctionId, ClosureId>, MirSpan, DefWithBodyId)>),
     ExecutionLimitExceeded,
+    TimeLimitExceeded,
     StackOverflow,
     /// FIXME: Fold this into InternalError
     InvalidVTableId(usize),
@@ -338,6 +355,8 @@ pub enum MirEvalError {
     CoerceUnsizedError(Ty),
     /// These should not occur, usually indicates a bug in mir lowering.
     InternalError(Box<str>),
+    /// The evaluator itself panicked, which indicates a bug in it.
+    EvaluatorPanicked(Box<str>),
 }
 
 impl MirEvalError {
@@ -417,13 +436,28 @@ pub fn pretty_print(
             | MirEvalError::NotSupported(_)
             | MirEvalError::InvalidConst(_)
             | MirEvalError::ExecutionLimitExceeded
+            | MirEvalError::TimeLimitExceeded
             | MirEvalError::StackOverflow
             | MirEvalError::CoerceUnsizedError(_)
             | MirEvalError::InternalError(_)
+            | MirEvalError::EvaluatorPanicked(_)
             | MirEvalError::InvalidVTableId(_) => writeln!(f, "{:?}", err)?,
         }
         Ok(())
     }
+
+    /// Whether the error depends on more than the evaluated code, so that evaluating it again
+    /// may succeed.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            MirEvalError::TimeLimitExceeded | MirEvalError::EvaluatorPanicked(_) => true,
+            MirEvalError::InFunction(e, _) => e.is_transient(),
+            MirEvalError::ConstEvalError(_, e) => {
+                matches!(&**e, ConstEvalError::MirEvalError(e) if e.is_transient())
+            }
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Debug for MirEvalError {
@@ -444,6 +478,7 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             }
             Self::TypeIsUnsized(ty, it) => write!(f, "{ty:?} is unsized. {it} should be sized."),
             Self::ExecutionLimitExceeded => write!(f, "execution limit exceeded"),
+            Self::TimeLimitExceeded => write!(f, "time limit exceeded"),
             Self::StackOverflow => write!(f, "stack overflow"),
             Self::MirLowerError(arg0, arg1) => {
                 f.debug_tuple("MirLowerError").field(arg0).field(arg1).finish()
@@ -455,6 +490,9 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
                 f.debug_tuple("CoerceUnsizedError").field(arg0).finish()
             }
             Self::InternalError(arg0) => f.debug_tuple("InternalError").field(arg0).finish(),
+            Self::EvaluatorPanicked(arg0) => {
+                f.debug_tuple("EvaluatorPanicked").field(arg0).finish()
+            }
             Self::InvalidVTableId(arg0) => f.debug_tuple("InvalidVTableId").field(arg0).finish(),
             Self::NotSupported(arg0) => f.debug_tuple("NotSupported").field(arg0).finish(),
             Self::InvalidConst(arg0) => {
@@ -529,6 +567,17 @@ pub fn interpret_mir(
     // (and probably should) do better here, for example by excluding bindings outside of the target expression.
     assert_placeholder_ty_is_unused: bool,
     trait_env: Option<Arc<TraitEnvironment>>,
+) -> (Result<Const>, MirOutput) {
+    interpret_mir_with_args(db, body, Vec::new(), assert_placeholder_ty_is_unused, trait_env)
+}
+
+/// Like [`interpret_mir`], but for a body with parameters, which get the bytes of `args`.
+pub fn interpret_mir_with_args(
+    db: &dyn HirDatabase,
+    body: Arc<MirBody>,
+    args: Vec<Vec<u8>>,
+    assert_placeholder_ty_is_unused: bool,
+    trait_env: Option<Arc<TraitEnvironment>>,
 ) -> (Result<Const>, MirOutput) {
     let ty = body.locals[return_slot()].ty.clone();
     let mut evaluator =
@@ -536,11 +585,12 @@ pub fn interpret_mir(
             Ok(it) => it,
             Err(e) => return (Err(e), MirOutput { stdout: vec![], stderr: vec![] }),
         };
-    let it: Result<Const> = (|| {
+    let it: Result<Const> = catch_evaluator_panic(|| {
         if evaluator.ptr_size() != std::mem::size_of::<usize>() {
             not_supported!("targets with different pointer size from host");
         }
-        let interval = evaluator.interpret_mir(body.clone(), None.into_iter())?;
+        let args = args.into_iter().map(IntervalOrOwned::Owned);
+        let interval = evaluator.interpret_mir(body.clone(), args)?;
         let bytes = interval.get(&evaluator)?;
         let mut memory_map = evaluator.create_memory_map(
             bytes,
@@ -556,15 +606,42 @@ pub fn interpret_mir(
             MemoryMap::Complex(Box::new(memory_map))
         };
         Ok(intern_const_scalar(ConstScalar::Bytes(bytes, memory_map), ty))
-    })();
+    });
+    if it.as_ref().is_err_and(MirEvalError::is_transient) {
+        // Don't let the query evaluating this remember the error past the current revision.
+        db.salsa_runtime().report_untracked_read();
+    }
     (it, MirOutput { stdout: evaluator.stdout, stderr: evaluator.stderr })
 }
 
+/// Turns a panic inside the evaluator into an error, so that user code hitting a bug in the
+/// interpreter fails only its own evaluation instead of the whole request. Salsa unwinds with
+/// non-string payloads for cancellation and cycles, those are propagated untouched.
+fn catch_evaluator_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(it) => it,
+        Err(payload) => {
+            let msg = match payload.downcast::<String>() {
+                Ok(msg) => *msg,
+                Err(payload) => match payload.downcast::<&'static str>() {
+                    Ok(msg) => (*msg).to_owned(),
+                    Err(payload) => panic::resume_unwind(payload),
+                },
+            };
+            Err(MirEvalError::EvaluatorPanicked(msg.into()))
+        }
+    }
+}
+
 #[cfg(test)]
 const EXECUTION_LIMIT: usize = 100_000;
 #[cfg(not(test))]
 const EXECUTION_LIMIT: usize = 10_000_000;
 
+const EXECUTION_TIME_LIMIT: Duration = Duration::from_secs(5);
+/// How many basic blocks are executed between two checks of the deadline and for cancellation.
+const DEADLINE_CHECK_INTERVAL: usize = 1 << 10;
+
 impl Evaluator<'_> {
     pub fn new(
         db: &dyn HirDatabase,
@@ -595,6 +672,7 @@ pub fn new(
             assert_placeholder_ty_is_unused,
             stack_depth_limit: 100,
             execution_limit: EXECUTION_LIMIT,
+            deadline: Instant::now() + EXECUTION_TIME_LIMIT,
             memory_limit: 1_000_000_000, // 2GB, 1GB for stack and 1GB for heap
             layout_cache: RefCell::new(Default::default()),
             projected_ty_cache: RefCell::new(Default::default()),
@@ -853,6 +931,13 @@ fn interpret_mir(
                     } else {
                         return Err(MirEvalError::ExecutionLimitExceeded);
                     }
+                    if self.execution_limit % DEADLINE_CHECK_INTERVAL == 0 {
+                        // Edits wait for running queries, so don't make them wait for the limits.
+                        self.db.unwind_if_cancelled();
+                        if Instant::now() > self.deadline {
+                            return Err(MirEvalError::TimeLimitExceeded);
+                        }
+                    }
                     for statement in &current_block.statements {
                         match &statement.kind {
                             StatementKind::Assign(l, r) => {
@@ -1,13 +1,25 @@
-use base_db::FileId;
+use std::{
+    iter,
+    panic::{self, AssertUnwindSafe},
+    sync::mpsc,
+    thread,
+    time::Instant,
+};
+
+use base_db::{
+    salsa::{self, Database, Durability, ParallelDatabase},
+    FileId,
+};
 use hir_def::db::DefDatabase;
 use syntax::{TextRange, TextSize};
 use test_fixture::WithFixture;
+use triomphe::Arc;
 
-use crate::{db::HirDatabase, test_db::TestDB, Interner, Substitution};
+use crate::{db::HirDatabase, mir::MirBody, test_db::TestDB, Interner, Substitution};
 
-use super::{interpret_mir, MirEvalError};
+use super::{catch_evaluator_panic, interpret_mir, Evaluator, MirEvalError};
 
-fn eval_main(db: &TestDB, file_id: FileId) -> Result<(String, String), MirEvalError> {
+fn main_mir_body(db: &TestDB, file_id: FileId) -> Result<Arc<MirBody>, MirEvalError> {
     let module_id = db.module_for_file(file_id);
     let def_map = module_id.def_map(db);
     let scope = &def_map[module_id.local_id].scope;
@@ -24,13 +36,16 @@ fn eval_main(db: &TestDB, file_id: FileId) -> Result<(String, String), MirEvalEr
             _ => None,
         })
         .expect("no main function found");
-    let body = db
-        .monomorphized_mir_body(
-            func_id.into(),
-            Substitution::empty(Interner),
-            db.trait_environment(func_id.into()),
-        )
-        .map_err(|e| MirEvalError::MirLowerError(func_id, e))?;
+    db.monomorphized_mir_body(
+        func_id.into(),
+        Substitution::empty(Interner),
+        db.trait_environment(func_id.into()),
+    )
+    .map_err(|e| MirEvalError::MirLowerError(func_id, e))
+}
+
+fn eval_main(db: &TestDB, file_id: FileId) -> Result<(String, String), MirEvalError> {
+    let body = main_mir_body(db, file_id)?;
     let (result, output) = interpret_mir(db, body, false, None);
     result?;
     Ok((output.stdout().into_owned(), output.stderr().into_owned()))
@@ -833,3 +848,67 @@ fn main() {
 "#,
     );
 }
+
+#[test]
+fn time_limit() {
+    let (db, file_id) = TestDB::with_single_file(
+        r#"
+fn main() {
+    loop {}
+}
+"#,
+    );
+    let body = main_mir_body(&db, file_id).unwrap();
+    let mut evaluator = Evaluator::new(&db, body.owner, false, None).unwrap();
+    evaluator.deadline = Instant::now();
+    let err = evaluator.interpret_mir(body, iter::empty()).unwrap_err();
+    assert!(
+        matches!(&err, MirEvalError::InFunction(e, _) if **e == MirEvalError::TimeLimitExceeded),
+        "{err:?}"
+    );
+    assert!(err.is_transient());
+}
+
+#[test]
+fn edits_cancel_evaluation() {
+    let (mut db, file_id) = TestDB::with_single_file(
+        r#"
+fn main() {
+    loop {}
+}
+"#,
+    );
+    let body = main_mir_body(&db, file_id).unwrap();
+    let snapshot = db.snapshot();
+    let (started_sender, started) = mpsc::sync_channel(0);
+    let evaluation = thread::spawn(move || {
+        let mut evaluator = Evaluator::new(&*snapshot, body.owner, false, None).unwrap();
+        evaluator.execution_limit = usize::MAX;
+        started_sender.send(()).unwrap();
+        panic::catch_unwind(AssertUnwindSafe(|| {
+            evaluator.interpret_mir(body, iter::empty()).map(drop)
+        }))
+    });
+    started.recv().unwrap();
+    // This waits for the evaluation to unwind, which happens well before the time limit.
+    db.salsa_runtime_mut().synthetic_write(Durability::LOW);
+    let payload = evaluation.join().unwrap().unwrap_err();
+    assert!(payload.is::<salsa::Cancelled>());
+}
+
+#[test]
+fn evaluator_panic_is_an_error() {
+    let err = catch_evaluator_panic::<()>(|| panic!("evaluator bug")).unwrap_err();
+    assert_eq!(err, MirEvalError::EvaluatorPanicked("evaluator bug".into()));
+    assert!(err.is_transient());
+}
+
+#[test]
+fn salsa_unwinding_is_not_caught() {
+    // Cancellation and cycles unwind with payloads that aren't strings.
+    let payload = panic::catch_unwind(|| {
+        catch_evaluator_panic::<()>(|| panic::resume_unwind(Box::new(42_u32)))
+    })
+    .unwrap_err();
+    assert_eq!(payload.downcast_ref::<u32>(), Some(&42));
+}
@@ -14,8 +14,8 @@
     lang_item::{LangItem, LangItemTarget},
     path::Path,
     resolver::{resolver_for_expr, HasResolver, ResolveValueResult, ValueNs},
-    AdtId, EnumVariantId, GeneralConstId, HasModule, ItemContainerId, LocalFieldId,
-    Lookup, TraitId, TupleId, TypeOrConstParamId,
+    AdtId, EnumVariantId, GeneralConstId, HasModule, ItemContainerId, LocalFieldId, Lookup,
+    TraitId, TupleId, TypeOrConstParamId,
 };
 use hir_expand::name::Name;
 use syntax::TextRange;
@@ -857,6 +857,7 @@ fn lower_expr_to_place_without_adjust(
             Expr::Await { .. } => not_supported!("await"),
             Expr::Yeet { .. } => not_supported!("yeet"),
             Expr::Async { .. } => not_supported!("async block"),
+            Expr::Gen { .. } => not_supported!("gen block"),
             &Expr::Const(id) => {
                 let subst = self.placeholder_subst();
                 self.lower_const(
@@ -2121,7 +2122,11 @@ pub fn lower_to_mir(
         if root_expr == body.body_expr {
             owner.is_none()
         } else {
-            owner == Some(root_expr)
+            // The bindings of a const block are owned by its `Expr::Const`, not by its block.
+            owner.is_some_and(|owner| match body[owner] {
+                Expr::Const(id) => db.lookup_intern_anonymous_const(id).root == root_expr,
+                _ => owner == root_expr,
+            })
         }
     };
     // 1 to param_len is for params
@@ -2150,3 +2155,34 @@ pub fn lower_to_mir(
     }
     Ok(ctx.result)
 }
+
+/// Lowers an expression of a body on its own. The bindings it uses from outside of it become the
+/// parameters of the result, in the order of `free_bindings`.
+pub fn lower_expr_to_mir(
+    db: &dyn HirDatabase,
+    owner: DefWithBodyId,
+    body: &Body,
+    infer: &InferenceResult,
+    root_expr: ExprId,
+    free_bindings: &[BindingId],
+) -> Result<MirBody> {
+    if let Some((_, it)) = infer.type_mismatches().next() {
+        return Err(MirLowerError::TypeMismatch(it.clone()));
+    }
+    let mut ctx = MirLowerCtx::new(db, owner, body, infer);
+    ctx.result.locals.alloc(Local { ty: ctx.expr_ty_after_adjustments(root_expr) });
+    for &binding in free_bindings {
+        let local = ctx.result.locals.alloc(Local { ty: infer[binding].clone() });
+        ctx.result.param_locals.push(local);
+        ctx.result.binding_locals.insert(binding, local);
+    }
+    // The bindings of closures and const blocks are lowered with their own bodies.
+    let current = ctx.lower_params_and_bindings([].into_iter(), |b| {
+        !body.binding_owners.get(&b).is_some_and(|&owner| owner != root_expr)
+    })?;
+    if let Some(current) = ctx.lower_expr_to_place(root_expr, return_slot().into(), current)? {
+        let current = ctx.pop_drop_scope_assert_finished(current, root_expr.into())?;
+        ctx.set_terminator(current, TerminatorKind::Return, root_expr.into());
+    }
+    Ok(ctx.result)
+}
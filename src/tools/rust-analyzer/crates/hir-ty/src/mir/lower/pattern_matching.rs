@@ -420,7 +420,19 @@ fn pattern_match_inner(
                 self.pattern_match_inner(current, current_else, cond_place, *pat, mode)?
             }
             Pat::Box { .. } => not_supported!("box pattern"),
-            Pat::ConstBlock(_) => not_supported!("const block pattern"),
+            Pat::ConstBlock(c) => match self.body.exprs[*c] {
+                Expr::Const(id) => {
+                    if mode == MatchingMode::Check {
+                        let subst = self.placeholder_subst();
+                        let ty = self.infer[pattern].clone();
+                        let c = self.lower_const_to_operand(subst, id.into(), ty)?;
+                        self.pattern_match_const(current_else, current, c, cond_place, pattern)?
+                    } else {
+                        (current, current_else)
+                    }
+                }
+                _ => not_supported!("const block pattern"),
+            },
         })
     }
 
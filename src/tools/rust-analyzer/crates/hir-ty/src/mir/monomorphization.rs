@@ -85,6 +85,9 @@ fn try_fold_ty(
                     crate::ImplTraitId::AsyncBlockTypeImplTrait(_, _) => {
                         not_supported!("async block impl trait");
                     }
+                    crate::ImplTraitId::GenBlockTypeImplTrait(_, _) => {
+                        not_supported!("gen block impl trait");
+                    }
                 }
             }
             _ => ty.try_super_fold_with(self.as_dyn(), outer_binder),
@@ -0,0 +1,324 @@
+//! Compute the object-safety of a trait
+//!
+//! This follows the rules of rustc's `object_safety` module, but is more lenient: whenever we are
+//! unsure (e.g. because of unknown types), the trait is considered object safe.
+
+use std::ops::ControlFlow;
+
+use base_db::CrateId;
+use chalk_ir::{
+    visit::{TypeSuperVisitable, TypeVisitable, TypeVisitor},
+    DebruijnIndex,
+};
+use hir_def::{
+    generics::{WherePredicate, WherePredicateTypeTarget},
+    lang_item::LangItem,
+    resolver::{HasResolver, TypeNs},
+    type_ref::{TraitBoundModifier, TypeRef},
+    AssocItemId, ConstId, FunctionId, HasModule, TraitId, TypeAliasId, TypeOrConstParamId,
+};
+
+use crate::{
+    all_super_traits, db::HirDatabase, to_placeholder_idx, utils::generics, AdtId, AliasTy,
+    Interner, PlaceholderIndex, ProjectionTyExt, TraitRefExt, Ty, TyData, TyKind, TypeFlags,
+    WhereClause,
+};
+
+/// The reason why a trait is not object safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectSafetyViolation {
+    /// The trait requires `Self: Sized`.
+    SizedSelf,
+    /// A supertrait of the trait mentions `Self` as a type argument, e.g. `trait Foo: PartialEq`.
+    SelfReferential,
+    Method(FunctionId, MethodViolationCode),
+    AssocConst(ConstId),
+    GAT(TypeAliasId),
+    /// One of the supertraits is not object safe.
+    HasNonSafeSuperTrait(TraitId),
+}
+
+/// The reason why a method makes its trait not object safe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MethodViolationCode {
+    /// The method has no receiver, e.g. `fn new() -> Self`.
+    StaticMethod,
+    /// `Self` appears in a parameter other than the receiver, e.g. `fn eq(&self, other: &Self)`.
+    ReferencesSelfInput,
+    /// `Self` appears in the return type, e.g. `fn clone(&self) -> Self`.
+    ReferencesSelfOutput,
+    /// The method returns `impl Trait`.
+    ReferencesImplTraitInTrait,
+    AsyncFn,
+    /// The method has type or const parameters.
+    Generic,
+    /// The receiver can't be used for dynamic dispatch, e.g. `self: Vec<Self>`.
+    UndispatchableReceiver,
+}
+
+/// Returns the first reason why `trait_` can't be made into a trait object, or `None` if it is
+/// object safe.
+pub fn object_safety(db: &dyn HirDatabase, trait_: TraitId) -> Option<ObjectSafetyViolation> {
+    for super_trait in all_super_traits(db.upcast(), trait_) {
+        let mut res = None;
+        let _ = object_safety_of_trait_itself(db, super_trait, &mut |violation| {
+            res = Some(violation);
+            ControlFlow::Break(())
+        });
+        if let Some(violation) = res {
+            return Some(match violation {
+                // `Self: Sized` and self-referential bounds are inherited as is.
+                ObjectSafetyViolation::SizedSelf | ObjectSafetyViolation::SelfReferential => {
+                    violation
+                }
+                _ if super_trait != trait_ => {
+                    ObjectSafetyViolation::HasNonSafeSuperTrait(super_trait)
+                }
+                _ => violation,
+            });
+        }
+    }
+    None
+}
+
+/// Returns every reason why `trait_` can't be made into a trait object. Unlike [`object_safety`],
+/// the violations of supertraits are reported as is, pointing at the offending items of the
+/// supertraits, and a method is reported with the first rule it breaks.
+pub fn object_safety_violations(
+    db: &dyn HirDatabase,
+    trait_: TraitId,
+) -> Vec<ObjectSafetyViolation> {
+    let mut res = Vec::new();
+    for super_trait in all_super_traits(db.upcast(), trait_) {
+        let _ = object_safety_of_trait_itself(db, super_trait, &mut |violation| {
+            if !res.contains(&violation) {
+                res.push(violation);
+            }
+            ControlFlow::Continue(())
+        });
+    }
+    res
+}
+
+fn object_safety_of_trait_itself(
+    db: &dyn HirDatabase,
+    trait_: TraitId,
+    cb: &mut dyn FnMut(ObjectSafetyViolation) -> ControlFlow<()>,
+) -> ControlFlow<()> {
+    let Some(self_param) = trait_self_param(db, trait_) else { return ControlFlow::Continue(()) };
+    let self_placeholder = to_placeholder_idx(db, self_param);
+    let sized_trait = db
+        .lang_item(trait_.module(db.upcast()).krate(), LangItem::Sized)
+        .and_then(|it| it.as_trait());
+
+    let trait_subst = generics(db.upcast(), trait_.into()).placeholder_subst(db);
+    for pred in db.generic_predicates(trait_.into()).iter() {
+        let pred = pred.clone().substitute(Interner, &trait_subst);
+        let (self_ty, args, projected_ty) = match pred.skip_binders() {
+            WhereClause::Implemented(trait_ref) => {
+                let self_ty = trait_ref.self_type_parameter(Interner);
+                if Some(trait_ref.hir_trait_id()) == sized_trait
+                    && is_self(&self_ty, self_placeholder)
+                {
+                    cb(ObjectSafetyViolation::SizedSelf)?;
+                    continue;
+                }
+                (self_ty, &trait_ref.substitution, None)
+            }
+            WhereClause::AliasEq(alias_eq) => match &alias_eq.alias {
+                AliasTy::Projection(proj) => {
+                    (proj.self_type_parameter(db), &proj.substitution, Some(&alias_eq.ty))
+                }
+                AliasTy::Opaque(_) => continue,
+            },
+            _ => continue,
+        };
+        if !is_self(&self_ty, self_placeholder) {
+            continue;
+        }
+        let mut args = args.iter(Interner).filter_map(|arg| arg.ty(Interner)).skip(1);
+        if args.any(|ty| contains_self(ty, self_placeholder))
+            || projected_ty.map_or(false, |ty| contains_self(ty, self_placeholder))
+        {
+            cb(ObjectSafetyViolation::SelfReferential)?;
+        }
+    }
+
+    for (_, item) in db.trait_data(trait_).items.iter() {
+        match *item {
+            AssocItemId::FunctionId(func) => {
+                if let Some(code) = method_violation(db, func, self_placeholder, sized_trait) {
+                    cb(ObjectSafetyViolation::Method(func, code))?;
+                }
+            }
+            AssocItemId::ConstId(konst) => cb(ObjectSafetyViolation::AssocConst(konst))?,
+            AssocItemId::TypeAliasId(alias) => {
+                let params = db.generic_params(alias.into());
+                if !params.type_or_consts.is_empty() || !params.lifetimes.is_empty() {
+                    cb(ObjectSafetyViolation::GAT(alias))?;
+                }
+            }
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+fn method_violation(
+    db: &dyn HirDatabase,
+    func: FunctionId,
+    self_placeholder: PlaceholderIndex,
+    sized_trait: Option<TraitId>,
+) -> Option<MethodViolationCode> {
+    // Methods that require `Self: Sized` can't be called on trait objects anyway.
+    if has_self_sized_bound(db, func, sized_trait) {
+        return None;
+    }
+
+    let data = db.function_data(func);
+    if !data.has_self_param() {
+        return Some(MethodViolationCode::StaticMethod);
+    }
+    if data.has_async_kw() {
+        return Some(MethodViolationCode::AsyncFn);
+    }
+
+    let generics = generics(db.upcast(), func.into());
+    let (_, _, type_params, const_params, impl_trait_params) = generics.provenance_split();
+    if type_params + const_params + impl_trait_params > 0 {
+        return Some(MethodViolationCode::Generic);
+    }
+
+    let sig = db.callable_item_signature(func.into());
+    let sig = sig.substitute(Interner, &generics.placeholder_subst(db));
+    let (receiver, params) = sig.params().split_first()?;
+    let krate = func.module(db.upcast()).krate();
+    if !is_dispatchable_receiver(db, krate, receiver, self_placeholder) {
+        return Some(MethodViolationCode::UndispatchableReceiver);
+    }
+    if params.iter().any(|ty| contains_self(ty, self_placeholder)) {
+        return Some(MethodViolationCode::ReferencesSelfInput);
+    }
+    match sig.ret().kind(Interner) {
+        TyKind::OpaqueType(..) | TyKind::Alias(AliasTy::Opaque(_)) => {
+            return Some(MethodViolationCode::ReferencesImplTraitInTrait)
+        }
+        _ => (),
+    }
+    if contains_self(sig.ret(), self_placeholder) {
+        return Some(MethodViolationCode::ReferencesSelfOutput);
+    }
+    None
+}
+
+/// Checks for an explicit `where Self: Sized` clause on the method.
+fn has_self_sized_bound(
+    db: &dyn HirDatabase,
+    func: FunctionId,
+    sized_trait: Option<TraitId>,
+) -> bool {
+    let resolver = func.resolver(db.upcast());
+    db.generic_params(func.into()).where_predicates.iter().any(|pred| match pred {
+        WherePredicate::TypeBound {
+            target: WherePredicateTypeTarget::TypeRef(type_ref),
+            bound,
+        } => {
+            let is_self = matches!(&**type_ref, TypeRef::Path(p) if p.is_self_type());
+            is_self
+                && matches!(
+                    bound.as_path(),
+                    Some((path, TraitBoundModifier::None))
+                        if matches!(
+                            resolver.resolve_path_in_type_ns_fully(db.upcast(), path),
+                            Some(TypeNs::TraitId(t)) if Some(t) == sized_trait
+                        )
+                )
+        }
+        _ => false,
+    })
+}
+
+fn trait_self_param(db: &dyn HirDatabase, trait_: TraitId) -> Option<TypeOrConstParamId> {
+    let local_id = db.generic_params(trait_.into()).find_trait_self_param()?;
+    Some(TypeOrConstParamId { parent: trait_.into(), local_id })
+}
+
+fn is_self(ty: &Ty, self_placeholder: PlaceholderIndex) -> bool {
+    matches!(ty.kind(Interner), TyKind::Placeholder(idx) if *idx == self_placeholder)
+}
+
+/// `self`, `&self`, `&mut self`, or a pointer to them that implements `DispatchFromDyn`, such as
+/// `self: Box<Self>`, `self: Rc<Self>` or `self: Pin<&mut Self>`. `self: Vec<Self>` is not one.
+fn is_dispatchable_receiver(
+    db: &dyn HirDatabase,
+    krate: CrateId,
+    ty: &Ty,
+    self_placeholder: PlaceholderIndex,
+) -> bool {
+    match ty.kind(Interner) {
+        TyKind::Placeholder(idx) => *idx == self_placeholder,
+        TyKind::Ref(_, _, inner) => is_self(inner, self_placeholder),
+        &TyKind::Adt(AdtId(adt), ref subst) => {
+            implements_dispatch_from_dyn(db, krate, adt)
+                && subst.iter(Interner).find_map(|arg| arg.ty(Interner)).map_or(false, |inner| {
+                    is_dispatchable_receiver(db, krate, inner, self_placeholder)
+                })
+        }
+        // Be lenient about types we failed to lower.
+        TyKind::Error => true,
+        _ => false,
+    }
+}
+
+/// Whether `adt` implements `DispatchFromDyn`, as `Box`, `Rc`, `Arc` and `Pin` do.
+fn implements_dispatch_from_dyn(db: &dyn HirDatabase, krate: CrateId, adt: hir_def::AdtId) -> bool {
+    let Some(dispatch_from_dyn) =
+        db.lang_item(krate, LangItem::DispatchFromDyn).and_then(|it| it.as_trait())
+    else {
+        return false;
+    };
+    db.trait_impls_in_deps(krate).iter().flat_map(|impls| impls.for_trait(dispatch_from_dyn)).any(
+        |impl_| {
+            matches!(db.impl_self_ty(impl_).skip_binders().kind(Interner),
+                TyKind::Adt(AdtId(it), _) if *it == adt)
+        },
+    )
+}
+
+/// Checks whether `ty` mentions `Self`, except as the self type of a projection such as
+/// `Self::Item`.
+fn contains_self(ty: &Ty, self_placeholder: PlaceholderIndex) -> bool {
+    ty.visit_with(&mut SelfVisitor { self_placeholder }, DebruijnIndex::INNERMOST).is_break()
+}
+
+struct SelfVisitor {
+    self_placeholder: PlaceholderIndex,
+}
+
+impl TypeVisitor<Interner> for SelfVisitor {
+    type BreakTy = ();
+
+    fn as_dyn(&mut self) -> &mut dyn TypeVisitor<Interner, BreakTy = Self::BreakTy> {
+        self
+    }
+
+    fn interner(&self) -> Interner {
+        Interner
+    }
+
+    fn visit_ty(&mut self, ty: &Ty, outer_binder: DebruijnIndex) -> ControlFlow<Self::BreakTy> {
+        let TyData { kind, flags } = ty.data(Interner);
+        match kind {
+            TyKind::Placeholder(idx) if *idx == self.self_placeholder => ControlFlow::Break(()),
+            TyKind::Alias(AliasTy::Projection(_)) | TyKind::AssociatedType(..) => {
+                ControlFlow::Continue(())
+            }
+            _ if flags.intersects(TypeFlags::HAS_TY_PLACEHOLDER) => {
+                ty.super_visit_with(self, outer_binder)
+            }
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,273 @@
+use base_db::FileId;
+use hir_def::{db::DefDatabase, ModuleDefId, TraitId};
+use test_fixture::WithFixture;
+
+use crate::{
+    object_safety::{object_safety, object_safety_violations, ObjectSafetyViolation},
+    test_db::TestDB,
+};
+
+/// Checks the object safety of the trait named `Goal`, rendering the violation with item names.
+fn check(ra_fixture: &str, expected: &str) {
+    let (db, trait_) = goal(ra_fixture);
+    let actual = object_safety(&db, trait_).map_or_else(|| "None".to_owned(), |it| render(&db, it));
+    assert_eq!(actual, expected);
+}
+
+/// Checks all the object safety violations of the trait named `Goal`.
+fn check_all(ra_fixture: &str, expected: &[&str]) {
+    let (db, trait_) = goal(ra_fixture);
+    let actual: Vec<_> =
+        object_safety_violations(&db, trait_).into_iter().map(|it| render(&db, it)).collect();
+    assert_eq!(actual, expected);
+}
+
+fn goal(ra_fixture: &str) -> (TestDB, TraitId) {
+    let (db, file_ids) = TestDB::with_many_files(ra_fixture);
+    let trait_ = file_ids
+        .into_iter()
+        .find_map(|file_id: FileId| {
+            let module_id = db.module_for_file(file_id);
+            let def_map = module_id.def_map(&db);
+            let scope = &def_map[module_id.local_id].scope;
+            let trait_ = scope.declarations().find_map(|it| match it {
+                ModuleDefId::TraitId(it) if db.trait_data(it).name.to_smol_str() == "Goal" => {
+                    Some(it)
+                }
+                _ => None,
+            });
+            trait_
+        })
+        .expect("no trait named `Goal`");
+    (db, trait_)
+}
+
+/// Renders `violation` with item names.
+fn render(db: &TestDB, violation: ObjectSafetyViolation) -> String {
+    match violation {
+        ObjectSafetyViolation::Method(func, code) => {
+            format!("Method({}, {code:?})", db.function_data(func).name.to_smol_str())
+        }
+        ObjectSafetyViolation::AssocConst(konst) => format!(
+            "AssocConst({})",
+            db.const_data(konst).name.as_ref().map_or_else(Default::default, |it| it.to_smol_str())
+        ),
+        ObjectSafetyViolation::GAT(alias) => {
+            format!("GAT({})", db.type_alias_data(alias).name.to_smol_str())
+        }
+        ObjectSafetyViolation::HasNonSafeSuperTrait(it) => {
+            format!("HasNonSafeSuperTrait({})", db.trait_data(it).name.to_smol_str())
+        }
+        violation => format!("{violation:?}"),
+    }
+}
+
+#[test]
+fn object_safe_traits() {
+    check(
+        r#"
+//- minicore: sized, deref, pin, dispatch_from_dyn
+use core::{marker::Unsize, ops::DispatchFromDyn};
+struct Box<T: ?Sized>(T);
+impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<Box<U>> for Box<T> {}
+trait Goal {
+    type Item;
+    fn by_ref(&self);
+    fn by_mut(&mut self, x: i32) -> Self::Item;
+    fn by_box(self: Box<Self>);
+    fn by_pin(self: core::pin::Pin<&mut Self>);
+    fn by_value(self);
+    fn new() -> Self where Self: Sized;
+    fn generic<T>(&self, t: T) where Self: Sized;
+}
+"#,
+        "None",
+    );
+}
+
+#[test]
+fn sized_self() {
+    check(
+        r#"
+//- minicore: sized
+trait Goal: Sized {}
+"#,
+        "SizedSelf",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal where Self: Sized {}
+"#,
+        "SizedSelf",
+    );
+}
+
+#[test]
+fn self_referential_supertrait() {
+    check(
+        r#"
+//- minicore: sized, eq
+trait Goal: PartialEq {}
+"#,
+        "SelfReferential",
+    );
+}
+
+#[test]
+fn method_violations() {
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn new() -> i32;
+}
+"#,
+        "Method(new, StaticMethod)",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn eq(&self, other: &Self) -> bool;
+}
+"#,
+        "Method(eq, ReferencesSelfInput)",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn dup(&self) -> Self;
+}
+"#,
+        "Method(dup, ReferencesSelfOutput)",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn map<T>(&self, t: T);
+}
+"#,
+        "Method(map, Generic)",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn visit(&self, t: impl Copy);
+}
+"#,
+        "Method(visit, Generic)",
+    );
+    check(
+        r#"
+//- minicore: sized, future
+trait Goal {
+    async fn run(&self);
+}
+"#,
+        "Method(run, AsyncFn)",
+    );
+    check(
+        r#"
+//- minicore: sized
+struct Wrapper<T>(T);
+trait Goal {
+    fn take(self: Wrapper<i32>);
+}
+"#,
+        "Method(take, UndispatchableReceiver)",
+    );
+    check_all(
+        r#"
+//- minicore: sized, option, dispatch_from_dyn
+struct Vec<T>(T);
+struct Wrapper<T>(T);
+trait Goal {
+    fn by_vec(self: Vec<Self>);
+    fn by_option(self: Option<Self>);
+    fn by_wrapper(self: Wrapper<Self>);
+    fn by_pin_vec(self: core::pin::Pin<Vec<Self>>);
+}
+"#,
+        &[
+            "Method(by_vec, UndispatchableReceiver)",
+            "Method(by_option, UndispatchableReceiver)",
+            "Method(by_wrapper, UndispatchableReceiver)",
+            "Method(by_pin_vec, UndispatchableReceiver)",
+        ],
+    );
+}
+
+#[test]
+fn assoc_items() {
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    const N: usize;
+}
+"#,
+        "AssocConst(N)",
+    );
+    check(
+        r#"
+//- minicore: sized
+trait Goal {
+    type Item<'a>;
+}
+"#,
+        "GAT(Item)",
+    );
+}
+
+#[test]
+fn non_safe_supertrait() {
+    check(
+        r#"
+//- minicore: sized
+trait Base {
+    fn new() -> i32;
+}
+trait Goal: Base {}
+"#,
+        "HasNonSafeSuperTrait(Base)",
+    );
+}
+
+#[test]
+fn all_violations() {
+    check_all(
+        r#"
+//- minicore: sized
+trait Base {
+    fn new() -> i32;
+    fn fine(&self);
+}
+trait Goal: Base + Sized {
+    const N: usize;
+    fn map<T>(&self, t: T);
+    fn dup(&self) -> Self;
+    fn skipped<T>(&self, t: T) where Self: Sized;
+}
+"#,
+        &[
+            "SizedSelf",
+            "AssocConst(N)",
+            "Method(map, Generic)",
+            "Method(dup, ReferencesSelfOutput)",
+            "Method(new, StaticMethod)",
+        ],
+    );
+    check_all(
+        r#"
+//- minicore: sized
+trait Goal {
+    fn by_ref(&self);
+}
+"#,
+        &[],
+    );
+}
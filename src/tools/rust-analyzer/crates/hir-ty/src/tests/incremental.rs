@@ -47,6 +47,62 @@ fn foo() -> i32 {
     }
 }
 
+#[test]
+fn editing_comments_inside_a_function_should_not_invalidate_types_or_diagnostics() {
+    let (mut db, pos) = TestDB::with_position(
+        "
+//- /lib.rs
+macro_rules! m { ($e:expr) => { $e + 1 } }
+fn foo() -> i32 {
+    $0fn inner() -> i32 { 1 }
+    struct S { f: i32 }
+    let s = S { f: inner() };
+    let c = |x: i32| x + { 1 };
+    let x = m!(s.f);
+    match x {}
+}",
+    );
+    {
+        let events = db.log_executed(|| {
+            let module = db.module_for_file(pos.file_id);
+            let crate_def_map = module.def_map(&db);
+            visit_module(&db, &crate_def_map, module.local_id, &mut |def| {
+                db.body_validation_diagnostics(def);
+            });
+        });
+        assert!(format!("{events:?}").contains("infer"));
+        assert!(format!("{events:?}").contains("body_validation_diagnostics"));
+    }
+
+    let new_text = "
+macro_rules! m { ($e:expr) => { $e + 1 } }
+fn foo() -> i32 {
+    // a comment
+    fn inner() -> i32 {   1 }
+    /* another */ struct S { f: i32 }
+    let s = S { f:   inner() };
+    let c = |x: i32| x + { 1 };
+    let x = m!( s.f );
+    match x {}
+}";
+
+    db.set_file_text(pos.file_id, Arc::from(new_text));
+
+    {
+        let events = db.log_executed(|| {
+            let module = db.module_for_file(pos.file_id);
+            let crate_def_map = module.def_map(&db);
+            visit_module(&db, &crate_def_map, module.local_id, &mut |def| {
+                db.body_validation_diagnostics(def);
+            });
+        });
+        // The body is lowered again, but compares equal to the old one.
+        assert!(format!("{events:?}").contains("body_with_source_map"), "{events:#?}");
+        assert!(!format!("{events:?}").contains("infer"), "{events:#?}");
+        assert!(!format!("{events:?}").contains("body_validation_diagnostics"), "{events:#?}");
+    }
+}
+
 #[test]
 fn typing_inside_a_function_should_not_invalidate_types_in_another() {
     let (mut db, pos) = TestDB::with_position(
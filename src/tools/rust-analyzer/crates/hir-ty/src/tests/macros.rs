@@ -1045,6 +1045,27 @@ fn test() {
     );
 }
 
+#[test]
+fn infer_derive_clone_in_cfg_attr() {
+    check_types(
+        r#"
+//- minicore: derive, clone
+//- /main.rs crate:main cfg:feature=std
+#[cfg_attr(feature = "std", derive(Clone))]
+struct S;
+#[cfg_attr(feature = "alloc", derive(Clone))]
+struct T;
+#[cfg_attr(feature = "std", allow(dead_code), derive(Clone))]
+struct U;
+fn test() {
+    let x = (S.clone(), T.clone(), U.clone());
+    x;
+  //^ (S, {unknown}, U)
+}
+"#,
+    );
+}
+
 #[test]
 fn infer_custom_derive_simple() {
     // FIXME: this test current now do nothing
@@ -1404,3 +1425,25 @@ fn foo(t: Tensor) {
 "#,
     );
 }
+
+#[test]
+fn macro2_resolves_names_at_def_site() {
+    check_types(
+        r#"
+mod m {
+    struct Private;
+    fn helper() -> Private { Private }
+    pub macro call() {{
+        let local = helper();
+        local
+    }}
+}
+
+fn main() {
+    let x = m::call!();
+    x;
+  //^ Private
+}
+"#,
+    );
+}
@@ -1753,6 +1753,54 @@ fn main() {
     );
 }
 
+#[test]
+fn postfix_match() {
+    check_types(
+        r#"
+//- minicore: option
+fn main() {
+    let x = Some(1u8).match {
+        Some(y if y > 0) => y,
+           //^ u8
+        _ => 0,
+    };
+    x;
+  //^ u8
+}"#,
+    );
+}
+
+#[test]
+fn gen_block_depends_on_edition() {
+    check_types(
+        r#"
+//- minicore: iterator
+//- /main.rs crate:main edition:2024
+fn main() {
+    let a = 1u8;
+    let it = gen {
+        let b = a;
+        b;
+      //^ u8
+        yield b;
+    };
+    it;
+  //^^ impl Iterator<Item = u8>
+}"#,
+    );
+    check_types(
+        r#"
+//- /main.rs crate:main edition:2021
+#[allow(non_camel_case_types)]
+struct gen {}
+fn main() {
+    let x = gen {};
+    x;
+  //^ gen
+}"#,
+    );
+}
+
 #[test]
 fn const_eval_array_repeat_expr() {
     check_types(
@@ -2143,6 +2191,25 @@ async fn main() {
     )
 }
 
+#[test]
+fn const_block_is_inferred_separately() {
+    check_no_mismatches(
+        r#"
+fn f() -> u32 {
+    let x: u8 = const {
+        if false {
+            // This doesn't return from `f`.
+            return;
+        }
+        let y = 2;
+        y
+    };
+    x as u32
+}
+"#,
+    );
+}
+
 #[test]
 fn async_fn_and_try_operator() {
     check_no_mismatches(
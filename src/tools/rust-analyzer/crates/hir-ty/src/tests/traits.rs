@@ -4553,3 +4553,104 @@ fn foo() {
 "#,
     );
 }
+
+#[test]
+fn negative_impls_of_auto_traits() {
+    check_types(
+        r#"
+//- minicore: sized
+auto trait Send {}
+trait IsSend { fn is_send(&self) -> u8; }
+impl<T: Send> IsSend for T { fn is_send(&self) -> u8 { 0 } }
+struct Rc;
+impl !Send for Rc {}
+struct Holds(Rc);
+struct Wrapper<T>(T);
+struct Fine(u32);
+fn f() {
+    Rc.is_send();
+  //^^^^^^^^^^^^ {unknown}
+    Holds(Rc).is_send();
+  //^^^^^^^^^^^^^^^^^^^ {unknown}
+    Wrapper(Rc).is_send();
+  //^^^^^^^^^^^^^^^^^^^^^ {unknown}
+    Wrapper(0u32).is_send();
+  //^^^^^^^^^^^^^^^^^^^^^^^ u8
+    Fine(0).is_send();
+  //^^^^^^^^^^^^^^^^^ u8
+}
+"#,
+    );
+}
+
+#[test]
+fn negative_impls_of_auto_traits_in_enums_and_unions() {
+    check_types(
+        r#"
+//- minicore: sized, manually_drop, phantom_data
+auto trait Send {}
+trait IsSend { fn is_send(&self) -> u8; }
+impl<T: Send> IsSend for T { fn is_send(&self) -> u8 { 0 } }
+struct Rc;
+impl !Send for Rc {}
+enum Either<L, R> { Left(L), Right(R) }
+enum Holds { Nothing, Rc(Rc) }
+union Raw { n: u32, rc: core::mem::ManuallyDrop<Rc> }
+union Number { n: u32, f: f32 }
+fn f(
+    either: Either<u32, Rc>,
+    fine: Either<u32, u8>,
+    holds: Holds,
+    raw: Raw,
+    number: Number,
+    phantom: core::marker::PhantomData<Rc>,
+) {
+    either.is_send();
+  //^^^^^^^^^^^^^^^^ {unknown}
+    fine.is_send();
+  //^^^^^^^^^^^^^^ u8
+    holds.is_send();
+  //^^^^^^^^^^^^^^^ {unknown}
+    raw.is_send();
+  //^^^^^^^^^^^^^ {unknown}
+    number.is_send();
+  //^^^^^^^^^^^^^^^^ u8
+    phantom.is_send();
+  //^^^^^^^^^^^^^^^^^ {unknown}
+}
+"#,
+    );
+}
+
+#[test]
+fn auto_traits_leak_through_return_position_impl_trait() {
+    check_types(
+        r#"
+//- minicore: sized
+auto trait Send {}
+trait IsSend { fn is_send(&self) -> u8; }
+impl<T: Send> IsSend for T { fn is_send(&self) -> u8 { 0 } }
+struct Rc;
+impl !Send for Rc {}
+struct Wrapper<T>(T);
+fn rc() -> impl Sized { Rc }
+fn number() -> impl Sized { 0u32 }
+fn wrap<T>(t: T) -> impl Sized { Wrapper(t) }
+fn recursive() -> impl Sized {
+    // The hidden type isn't known yet while inferring the defining function.
+    recursive().is_send();
+    0u32
+}
+fn f() {
+    rc().is_send();
+  //^^^^^^^^^^^^^^ {unknown}
+    number().is_send();
+  //^^^^^^^^^^^^^^^^^^ u8
+    wrap(Rc).is_send();
+  //^^^^^^^^^^^^^^^^^^ {unknown}
+    wrap(0u32).is_send();
+  //^^^^^^^^^^^^^^^^^^^^ u8
+}
+"#,
+    );
+}
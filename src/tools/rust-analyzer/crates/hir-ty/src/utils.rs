@@ -48,6 +48,15 @@ pub(crate) fn fn_traits(
         .flat_map(|it| it.as_trait())
 }
 
+/// The `Iterator` trait, found through its `next` lang item as it has none itself.
+pub(crate) fn iterator_trait(db: &dyn DefDatabase, krate: CrateId) -> Option<TraitId> {
+    let next = db.lang_item(krate, LangItem::IteratorNext)?.as_function()?;
+    match next.lookup(db).container {
+        ItemContainerId::TraitId(trait_) => Some(trait_),
+        _ => None,
+    }
+}
+
 /// Returns an iterator over the whole super trait hierarchy (including the
 /// trait itself).
 pub fn all_super_traits(db: &dyn DefDatabase, trait_: TraitId) -> SmallVec<[TraitId; 4]> {
@@ -74,7 +83,7 @@ pub(crate) fn fn_traits(
 /// `all_super_traits` is that we keep track of type parameters; for example if
 /// we have `Self: Trait<u32, i32>` and `Trait<T, U>: OtherTrait<U>` we'll get
 /// `Self: OtherTrait<i32>`.
-pub(super) fn all_super_trait_refs<T>(
+pub fn all_super_trait_refs<T>(
     db: &dyn HirDatabase,
     trait_ref: TraitRef,
     cb: impl FnMut(TraitRef) -> Option<T>,
@@ -2,6 +2,7 @@
 
 use std::ops::ControlFlow;
 
+use base_db::Version;
 use hir_def::{
     attr::AttrsWithOwner,
     item_scope::ItemInNs,
@@ -23,6 +24,17 @@ pub trait HasAttrs {
     fn attrs(self, db: &dyn HirDatabase) -> AttrsWithOwner;
     #[doc(hidden)]
     fn attr_id(self) -> AttrDefId;
+
+    /// The Rust version this item was stabilized in, from its `#[stable(since = "..")]`
+    /// attribute. Only the standard library has these.
+    fn stable_since(self, db: &dyn HirDatabase) -> Option<Version>
+    where
+        Self: Sized,
+    {
+        let attrs = self.attrs(db);
+        let since = attrs.by_key("stable").find_string_value_in_tt("since")?;
+        parse_rust_version(since.trim_matches('"'))
+    }
 }
 
 macro_rules! impl_has_attrs {
@@ -89,6 +101,15 @@ fn attr_id(self) -> AttrDefId {
     }
 }
 
+/// Parses a Rust version like `1.70.0`, or `1.70` as used by `rust-version` in manifests.
+pub(crate) fn parse_rust_version(version: &str) -> Option<Version> {
+    let mut parts = version.trim().splitn(3, '.').map(|it| it.parse::<u64>().ok());
+    let major = parts.next()??;
+    let minor = parts.next()??;
+    let patch = parts.next().unwrap_or(Some(0))?;
+    Some(Version::new(major, minor, patch))
+}
+
 /// Resolves the item `link` points to in the scope of `def`.
 pub fn resolve_doc_path_on(
     db: &dyn HirDatabase,
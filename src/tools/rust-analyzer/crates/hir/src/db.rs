@@ -15,8 +15,8 @@
     InternFunctionQuery, InternImplQuery, InternInTypeConstQuery, InternMacro2Query,
     InternMacroRulesQuery, InternProcMacroQuery, InternStaticQuery, InternStructQuery,
     InternTraitAliasQuery, InternTraitQuery, InternTypeAliasQuery, InternUnionQuery,
-    InternUseQuery, LangItemQuery, Macro2DataQuery, MacroRulesDataQuery, ProcMacroDataQuery,
-    StaticDataQuery, StructDataWithDiagnosticsQuery, TraitAliasDataQuery,
+    InternUseQuery, LangItemQuery, Macro2DataQuery, MacroRulesDataQuery, PersistedItemTreeQuery,
+    ProcMacroDataQuery, StaticDataQuery, StructDataWithDiagnosticsQuery, TraitAliasDataQuery,
     TraitDataWithDiagnosticsQuery, TypeAliasDataQuery, UnionDataWithDiagnosticsQuery,
 };
 pub use hir_expand::db::{
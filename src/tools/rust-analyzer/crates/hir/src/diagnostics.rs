@@ -3,7 +3,7 @@
 //!
 //! This probably isn't the best way to do this -- ideally, diagnostics should
 //! be expressed in terms of hir types themselves.
-pub use hir_ty::diagnostics::{CaseType, IncorrectCase};
+pub use hir_ty::diagnostics::{CaseType, CastLossiness, IncorrectCase};
 use hir_ty::{db::HirDatabase, diagnostics::BodyValidationDiagnostic, InferenceDiagnostic};
 
 use base_db::CrateId;
@@ -13,7 +13,7 @@
 use hir_expand::{name::Name, HirFileId, InFile};
 use syntax::{ast, AstPtr, SyntaxError, SyntaxNodePtr, TextRange};
 
-use crate::{AssocItem, Field, Local, MacroKind, Trait, Type};
+use crate::{AssocItem, Field, Local, MacroKind, Trait, Type, TypeParam};
 
 macro_rules! diagnostics {
     ($($diag:ident,)*) => {
@@ -47,12 +47,16 @@ fn from(d: $diag) -> AnyDiagnostic {
 // ]
 
 diagnostics![
+    ArrayIndexOutOfBounds,
+    ArrayPatternLenMismatch,
     BreakOutsideOfLoop,
     ExpectedFunction,
     InactiveCode,
     IncoherentImpl,
     IncorrectCase,
     InvalidDeriveTarget,
+    IterNthZero,
+    LossyCast,
     MacroDefError,
     MacroError,
     MacroExpansionParseError,
@@ -91,6 +95,23 @@ fn from(d: $diag) -> AnyDiagnostic {
     UnusedVariable,
 ];
 
+#[derive(Debug)]
+pub struct ArrayIndexOutOfBounds {
+    /// The whole indexing expression.
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub index: u128,
+    pub len: u128,
+}
+
+#[derive(Debug)]
+pub struct ArrayPatternLenMismatch {
+    pub pat: InFile<AstPtr<ast::SlicePat>>,
+    /// The number of elements the pattern matches, not counting its rest pattern.
+    pub required: usize,
+    pub has_rest: bool,
+    pub len: u128,
+}
+
 #[derive(Debug)]
 pub struct BreakOutsideOfLoop {
     pub expr: InFile<AstPtr<ast::Expr>>,
@@ -250,6 +271,9 @@ pub struct PrivateField {
 #[derive(Debug)]
 pub struct MissingUnsafe {
     pub expr: InFile<AstPtr<ast::Expr>>,
+    /// The operation is in the body of an `unsafe fn`, so it's only reported by the
+    /// `unsafe_op_in_unsafe_fn` lint.
+    pub only_lint: bool,
 }
 
 #[derive(Debug)]
@@ -267,6 +291,21 @@ pub struct ReplaceFilterMapNextWithFindMap {
     pub next_expr: AstPtr<ast::Expr>,
 }
 
+#[derive(Debug)]
+pub struct IterNthZero {
+    /// The whole `.nth(0)` method call.
+    pub expr: InFile<AstPtr<ast::Expr>>,
+}
+
+#[derive(Debug)]
+pub struct LossyCast {
+    /// The whole `as` cast.
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    pub from: Type,
+    pub to: Type,
+    pub lossiness: CastLossiness,
+}
+
 #[derive(Debug)]
 pub struct MismatchedArgCount {
     pub call_expr: InFile<AstPtr<ast::Expr>>,
@@ -319,6 +358,9 @@ pub struct IncoherentImpl {
 pub struct TraitImplOrphan {
     pub file_id: HirFileId,
     pub impl_: AstPtr<ast::Impl>,
+    /// The type parameter appearing uncovered before the first local type, if that is why the
+    /// impl is an orphan.
+    pub uncovered_ty_param: Option<TypeParam>,
 }
 
 // FIXME: Split this off into the corresponding 4 rustc errors
@@ -357,6 +399,7 @@ pub struct RemoveUnnecessaryElse {
 impl AnyDiagnostic {
     pub(crate) fn body_validation_diagnostic(
         db: &dyn HirDatabase,
+        def: DefWithBodyId,
         diagnostic: BodyValidationDiagnostic,
         source_map: &hir_def::body::BodySourceMap,
     ) -> Option<AnyDiagnostic> {
@@ -431,6 +474,11 @@ pub(crate) fn body_validation_diagnostic(
                     );
                 }
             }
+            BodyValidationDiagnostic::IterNthZero { method_call_expr } => {
+                if let Ok(source_ptr) = source_map.expr_syntax(method_call_expr) {
+                    return Some(IterNthZero { expr: source_ptr }.into());
+                }
+            }
             BodyValidationDiagnostic::MissingMatchArms { match_expr, uncovered_patterns } => {
                 match source_map.expr_syntax(match_expr) {
                     Ok(source_ptr) => {
@@ -469,6 +517,39 @@ pub(crate) fn body_validation_diagnostic(
                     }
                 }
             }
+            BodyValidationDiagnostic::ArrayIndexOutOfBounds { index_expr, index, len } => {
+                if let Ok(source_ptr) = source_map.expr_syntax(index_expr) {
+                    return Some(ArrayIndexOutOfBounds { expr: source_ptr, index, len }.into());
+                }
+            }
+            BodyValidationDiagnostic::ArrayPatternLenMismatch { pat, required, has_rest, len } => {
+                if let Ok(source_ptr) = source_map.pat_syntax(pat) {
+                    if let Some(ptr) = source_ptr.value.cast::<ast::SlicePat>() {
+                        return Some(
+                            ArrayPatternLenMismatch {
+                                pat: InFile::new(source_ptr.file_id, ptr),
+                                required,
+                                has_rest,
+                                len,
+                            }
+                            .into(),
+                        );
+                    }
+                }
+            }
+            BodyValidationDiagnostic::LossyCast { cast_expr, from, to, lossiness } => {
+                if let Ok(source_ptr) = source_map.expr_syntax(cast_expr) {
+                    return Some(
+                        LossyCast {
+                            expr: source_ptr,
+                            from: Type::new(db, def, from),
+                            to: Type::new(db, def, to),
+                            lossiness,
+                        }
+                        .into(),
+                    );
+                }
+            }
             BodyValidationDiagnostic::RemoveUnnecessaryElse { if_expr } => {
                 if let Ok(source_ptr) = source_map.expr_syntax(if_expr) {
                     if let Some(ptr) = source_ptr.value.cast::<ast::IfExpr>() {
@@ -65,6 +65,19 @@ pub fn is_inline(self, db: &dyn HirDatabase) -> bool {
         def_map[self.id.local_id].origin.is_inline()
     }
 
+    /// Returns the files `include!`d into this module, whose items are part of it.
+    pub fn included_files(self, db: &dyn HirDatabase) -> Vec<FileId> {
+        // Block modules can't be found in the crate `DefMap`.
+        if self.id.containing_block().is_some() {
+            return Vec::new();
+        }
+        db.include_macro_invoc(self.id.krate())
+            .into_iter()
+            .filter(|&(_, _, module)| module == self.id.local_id)
+            .map(|(_, file_id, _)| file_id)
+            .collect()
+    }
+
     /// Returns a node which declares this module, either a `mod foo;` or a `mod foo {}`.
     /// `None` for the crate root.
     pub fn declaration_source(self, db: &dyn HirDatabase) -> Option<InFile<ast::Module>> {
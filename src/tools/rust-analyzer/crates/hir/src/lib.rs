@@ -38,12 +38,15 @@
 use std::{iter, mem::discriminant, ops::ControlFlow};
 
 use arrayvec::ArrayVec;
-use base_db::{CrateDisplayName, CrateId, CrateOrigin, Edition, FileId};
+use base_db::{CrateDisplayName, CrateId, CrateOrigin, Edition, FileId, Version};
 use either::Either;
 use hir_def::{
     body::{BodyDiagnostic, SyntheticSyntax},
     data::adt::VariantData,
-    generics::{LifetimeParamData, TypeOrConstParamData, TypeParamProvenance},
+    generics::{
+        LifetimeParamData, TypeOrConstParamData, TypeParamProvenance, WherePredicate,
+        WherePredicateTypeTarget,
+    },
     hir::{BindingAnnotation, BindingId, ExprOrPatId, LabelId, Pat},
     item_tree::ItemTreeNode,
     lang_item::LangItemTarget,
@@ -53,6 +56,7 @@
     per_ns::PerNs,
     resolver::{HasResolver, Resolver},
     src::HasSource as _,
+    type_ref::{TraitBoundModifier, TypeBound},
     AssocItemId, AssocItemLoc, AttrDefId, ConstId, ConstParamId, CrateRootModuleId, DefWithBodyId,
     EnumId, EnumVariantId, ExternCrateId, FunctionId, GenericDefId, GenericParamId, HasModule,
     ImplId, InTypeConstId, ItemContainerId, LifetimeParamId, LocalFieldId, Lookup, MacroExpander,
@@ -64,21 +68,21 @@
     all_super_traits, autoderef, check_orphan_rules,
     consteval::{try_const_usize, unknown_const_as_generic, ConstExt},
     db::InternedClosure,
-    diagnostics::BodyValidationDiagnostic,
     known_const_to_ast,
     layout::{Layout as TyLayout, RustcEnumVariantIdx, RustcFieldIdx, TagEncoding},
     method_resolution::{self, TyFingerprint},
     mir::interpret_mir,
+    orphan_check_impl,
     primitive::UintTy,
     traits::FnTrait,
     AliasTy, CallableDefId, CallableSig, Canonical, CanonicalVarKinds, Cast, ClosureId, GenericArg,
-    GenericArgData, Interner, ParamKind, QuantifiedWhereClause, Scalar, Substitution,
-    TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind, ValueTyDefId,
-    WhereClause,
+    GenericArgData, Interner, OrphanCheckError, ParamKind, ProjectionTyExt, QuantifiedWhereClause,
+    Scalar, Substitution, TraitEnvironment, TraitRefExt, Ty, TyBuilder, TyDefId, TyExt, TyKind,
+    ValueTyDefId, WhereClause,
 };
 use itertools::Itertools;
 use nameres::diagnostics::DefDiagnosticKind;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 use stdx::{impl_from, never};
 use syntax::{
     ast::{self, HasAttrs as _, HasName},
@@ -93,7 +97,8 @@
     diagnostics::*,
     has_source::HasSource,
     semantics::{
-        DescendPreference, PathResolution, Semantics, SemanticsScope, TypeInfo, VisibleTraits,
+        DescendPreference, InferredGenericArgs, MacroInvocation, MacroInvocationKind,
+        PathResolution, Semantics, SemanticsScope, TypeInfo, VisibleTraits,
     },
 };
 
@@ -113,6 +118,7 @@
         data::adt::StructKind,
         find_path::PrefixKind,
         import_map,
+        item_tree::persist as item_tree_persist,
         lang_item::LangItem,
         nameres::{DefMap, ModuleSource},
         path::{ModPath, PathKind},
@@ -134,10 +140,13 @@
     },
     hir_ty::{
         consteval::ConstEvalError,
+        diagnostics::UnsafeExprKind,
         display::{ClosureStyle, HirDisplay, HirDisplayError, HirWrite},
+        ffi::FfiUnsafeReason,
         layout::LayoutError,
         mir::{MirEvalError, MirLowerError},
-        PointerCast, Safety,
+        object_safety::MethodViolationCode,
+        FnSigLifetimes, InputLifetime, LifetimePosition, PointerCast, Safety,
     },
     // FIXME: Properly encapsulate mir
     hir_ty::{mir, Interner as ChalkTyInterner},
@@ -232,6 +241,18 @@ pub fn display_name(self, db: &dyn HirDatabase) -> Option<CrateDisplayName> {
         db.crate_graph()[self.id].display_name.clone()
     }
 
+    /// The minimum supported Rust version of this crate, from the `rust-version` field of its
+    /// manifest.
+    pub fn rust_version(self, db: &dyn HirDatabase) -> Option<Version> {
+        let rust_version = db.crate_graph()[self.id].env.get("CARGO_PKG_RUST_VERSION")?;
+        attrs::parse_rust_version(&rust_version)
+    }
+
+    /// The value of the environment variable `key` as seen by `env!` in this crate.
+    pub fn env_var(self, db: &dyn HirDatabase, key: &str) -> Option<String> {
+        db.crate_graph()[self.id].env.get(key)
+    }
+
     pub fn query_external_importables(
         self,
         db: &dyn DefDatabase,
@@ -539,6 +560,36 @@ pub fn scope(
             .collect()
     }
 
+    /// Returns the items in the scope of this module that are visible from everywhere, like
+    /// `pub` items and `pub use` re-exports.
+    pub fn public_scope(self, db: &dyn HirDatabase) -> Vec<(Name, ScopeDef)> {
+        self.id.def_map(db.upcast())[self.id.local_id]
+            .scope
+            .entries()
+            .map(|(name, def)| (name, def.filter_visibility(|vis| vis == Visibility::Public)))
+            .filter(|(_, def)| !def.is_none())
+            .flat_map(|(name, def)| {
+                ScopeDef::all_items(def).into_iter().map(move |item| (name.clone(), item))
+            })
+            .collect()
+    }
+
+    /// Returns the `#[cfg]` predicate of an item named `name` that this module declares, but
+    /// that the crate's cfg options leave out.
+    pub fn cfg_disabled_item(self, db: &dyn HirDatabase, name: &str) -> Option<CfgExpr> {
+        let def_map = self.id.def_map(db.upcast());
+        def_map.diagnostics().iter().filter(|diag| diag.in_module == self.id.local_id).find_map(
+            |diag| {
+                let DefDiagnosticKind::UnconfiguredCode { ast, cfg, .. } = &diag.kind else {
+                    return None;
+                };
+                let node = ast.to_ptr(db.upcast()).to_node(&db.parse_or_expand(ast.file_id));
+                let item_name = <ast::AnyHasName as AstNode>::cast(node)?.name()?;
+                (item_name.text() == name).then(|| cfg.clone())
+            },
+        )
+    }
+
     /// Fills `acc` with the module's diagnostics.
     pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
         let name = self.name(db);
@@ -624,8 +675,19 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
                 acc.push(IncoherentImpl { impl_: ast_id_map.get(node.ast_id()), file_id }.into())
             }
 
-            if !impl_def.check_orphan_rules(db) {
-                acc.push(TraitImplOrphan { impl_: ast_id_map.get(node.ast_id()), file_id }.into())
+            if let Err(err) = orphan_check_impl(db, impl_def.id) {
+                let uncovered_ty_param = match err {
+                    OrphanCheckError::NonLocalInputType => None,
+                    OrphanCheckError::UncoveredTyParam(id) => Some(TypeParam { id }),
+                };
+                acc.push(
+                    TraitImplOrphan {
+                        impl_: ast_id_map.get(node.ast_id()),
+                        file_id,
+                        uncovered_ty_param,
+                    }
+                    .into(),
+                )
             }
 
             let trait_ = impl_def.trait_(db);
@@ -790,6 +852,7 @@ pub fn find_use_path_prefixed(
         prefix_kind: PrefixKind,
         prefer_no_std: bool,
         prefer_prelude: bool,
+        prefer_reexports: bool,
     ) -> Option<ModPath> {
         hir_def::find_path::find_path_prefixed(
             db,
@@ -798,6 +861,7 @@ pub fn find_use_path_prefixed(
             prefix_kind,
             prefer_no_std,
             prefer_prelude,
+            prefer_reexports,
         )
     }
 }
@@ -1422,6 +1486,21 @@ pub fn layout(self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
         .map(|layout| Layout(layout, db.target_data_layout(krate).unwrap()))
     }
 
+    /// The layout this ADT would have without its `#[repr]` attribute.
+    pub fn layout_with_default_repr(self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
+        if db.generic_params(self.into()).iter().count() != 0 {
+            return Err(LayoutError::HasPlaceholder);
+        }
+        let krate = self.krate(db).id;
+        hir_ty::layout::layout_of_adt_with_default_repr(
+            db,
+            self.into(),
+            Substitution::empty(Interner),
+            db.trait_environment(self.into()),
+        )
+        .map(|layout| Layout(layout, db.target_data_layout(krate).unwrap()))
+    }
+
     /// Turns this ADT into a type. Any type parameters of the ADT will be
     /// turned into unknown types, which is good for e.g. finding the most
     /// general set of completions, but will not look very nice when printed.
@@ -1615,6 +1694,28 @@ pub fn debug_mir(self, db: &dyn HirDatabase) -> String {
         }
     }
 
+    /// The operations in this def's body that require `unsafe`, in source order.
+    ///
+    /// Operations that were desugared from other syntax aren't included.
+    pub fn unsafe_operations(self, db: &dyn HirDatabase) -> Vec<UnsafeOperation> {
+        let (body, source_map) = db.body_with_source_map(self.id());
+        let infer = db.infer(self.id());
+        let mut res = Vec::new();
+        hir_ty::diagnostics::unsafe_expressions(
+            db,
+            &infer,
+            self.id(),
+            &body,
+            body.body_expr,
+            &mut |it| {
+                let Ok(expr) = source_map.expr_syntax(it.expr) else { return };
+                let unsafe_block = it.unsafe_block.and_then(|it| source_map.expr_syntax(it).ok());
+                res.push(UnsafeOperation { kind: it.kind, expr, unsafe_block });
+            },
+        );
+        res
+    }
+
     pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
         db.unwind_if_cancelled();
         let krate = self.module(db).id.krate();
@@ -1691,9 +1792,10 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
             );
         }
 
-        for expr in hir_ty::diagnostics::missing_unsafe(db, self.into()) {
+        let (unsafe_exprs, only_lint) = hir_ty::diagnostics::missing_unsafe(db, self.into());
+        for expr in unsafe_exprs {
             match source_map.expr_syntax(expr) {
-                Ok(expr) => acc.push(MissingUnsafe { expr }.into()),
+                Ok(expr) => acc.push(MissingUnsafe { expr, only_lint }.into()),
                 Err(SyntheticSyntax) => {
                     // FIXME: Here and elsewhere in this file, the `expr` was
                     // desugared, report or assert that this doesn't happen.
@@ -1783,8 +1885,13 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
             }
         }
 
-        for diagnostic in BodyValidationDiagnostic::collect(db, self.into()) {
-            acc.extend(AnyDiagnostic::body_validation_diagnostic(db, diagnostic, &source_map));
+        for diagnostic in db.body_validation_diagnostics(self.into()).iter().cloned() {
+            acc.extend(AnyDiagnostic::body_validation_diagnostic(
+                db,
+                self.into(),
+                diagnostic,
+                &source_map,
+            ));
         }
 
         let def: ModuleDef = match self {
@@ -1800,11 +1907,35 @@ pub fn diagnostics(self, db: &dyn HirDatabase, acc: &mut Vec<AnyDiagnostic>) {
         }
     }
 }
+
+/// An expression that requires `unsafe`, see [`DefWithBody::unsafe_operations`].
+#[derive(Debug, Clone)]
+pub struct UnsafeOperation {
+    pub kind: UnsafeExprKind,
+    pub expr: InFile<AstPtr<ast::Expr>>,
+    /// The innermost `unsafe` block containing the operation, `None` if it's in the body of an
+    /// `unsafe fn` or is missing an `unsafe` block.
+    pub unsafe_block: Option<InFile<AstPtr<ast::Expr>>>,
+}
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Function {
     pub(crate) id: FunctionId,
 }
 
+/// An estimate of the stack space a call to a function needs, see [`Function::stack_usage`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StackUsage {
+    /// The size of the function's own frame, the sum of the sizes of the locals of its MIR.
+    pub frame_size: u64,
+    /// The size of the frame plus the worst case stack usage of its callees.
+    pub worst_case: u64,
+    /// The calls that need the most stack, starting with a callee of the function.
+    pub worst_path: Vec<Function>,
+    /// Whether the estimate is only a lower bound, because the depth limit was reached, a call
+    /// is recursive or goes through a function pointer, or some local couldn't be laid out.
+    pub incomplete: bool,
+}
+
 impl Function {
     pub fn module(self, db: &dyn HirDatabase) -> Module {
         self.id.module(db.upcast()).into()
@@ -1827,6 +1958,12 @@ pub fn ret_type(self, db: &dyn HirDatabase) -> Type {
         Type::new_with_resolver_inner(db, &resolver, ty)
     }
 
+    /// The lifetimes in this function's signature, with the elided lifetimes of the return type
+    /// resolved.
+    pub fn sig_lifetimes(self, db: &dyn HirDatabase) -> FnSigLifetimes {
+        hir_ty::fn_sig_lifetimes(db, self.id)
+    }
+
     // FIXME: Find better API to also handle const generics
     pub fn ret_type_with_args(
         self,
@@ -1856,6 +1993,62 @@ pub fn ret_type_with_args(
         Type::new_with_resolver_inner(db, &resolver, ty)
     }
 
+    /// Returns the trait bounds the body of this function relies on without them being implied by
+    /// its where clauses, e.g. `T: Add<Output = T>` for a function returning `a + b`.
+    pub fn missing_bounds(self, db: &dyn HirDatabase) -> Vec<MissingBound> {
+        let infer = db.infer(self.id.into());
+        let resolver = self.id.resolver(db.upcast());
+        let clauses = &infer.unfulfilled_where_clauses;
+        // Without the bound, associated types of the trait stay opaque and fail to unify with the
+        // types the body expects of them.
+        let projections = clauses
+            .iter()
+            .filter_map(|clause| match clause {
+                WhereClause::AliasEq(hir_ty::AliasEq {
+                    alias: AliasTy::Projection(projection),
+                    ty,
+                }) => Some((projection.clone(), ty)),
+                _ => None,
+            })
+            .chain(infer.type_mismatches().filter_map(|(_, mismatch)| {
+                let projection = match mismatch.actual.kind(Interner) {
+                    TyKind::AssociatedType(assoc_ty_id, subst) => hir_ty::ProjectionTy {
+                        associated_ty_id: *assoc_ty_id,
+                        substitution: subst.clone(),
+                    },
+                    TyKind::Alias(AliasTy::Projection(projection)) => projection.clone(),
+                    _ => return None,
+                };
+                Some((projection, &mismatch.expected))
+            }))
+            .filter(|(_, ty)| !ty.contains_unknown())
+            .collect::<Vec<_>>();
+
+        clauses
+            .iter()
+            .filter_map(|clause| match clause {
+                WhereClause::Implemented(trait_ref) if !trait_ref_contains_unknown(trait_ref) => {
+                    Some(trait_ref)
+                }
+                _ => None,
+            })
+            .map(|trait_ref| {
+                let mut assoc_types: Vec<(TypeAlias, Type)> = Vec::new();
+                for (projection, ty) in &projections {
+                    let alias = hir_ty::from_assoc_type_id(projection.associated_ty_id).into();
+                    if projection.trait_ref(db) == *trait_ref
+                        && assoc_types.iter().all(|(it, _)| *it != alias)
+                    {
+                        let ty = Type::new_with_resolver_inner(db, &resolver, (*ty).clone());
+                        assoc_types.push((alias, ty));
+                    }
+                }
+                let trait_ref = TraitRef::new_with_resolver(db, &resolver, trait_ref.clone());
+                MissingBound { trait_ref, assoc_types }
+            })
+            .collect()
+    }
+
     pub fn async_ret_type(self, db: &dyn HirDatabase) -> Option<Type> {
         if !self.is_async(db) {
             return None;
@@ -2017,6 +2210,17 @@ pub fn has_body(self, db: &dyn HirDatabase) -> bool {
         db.function_data(self.id).has_body()
     }
 
+    /// Whether this function is declared in an `extern` block.
+    pub fn is_foreign(self, db: &dyn HirDatabase) -> bool {
+        matches!(self.id.lookup(db.upcast()).container, ItemContainerId::ExternBlockId(_))
+    }
+
+    /// Whether two foreign functions have signatures that are the same to the linker, comparing
+    /// the structs, unions and enums of different definitions by their fields.
+    pub fn has_same_foreign_signature(self, db: &dyn HirDatabase, other: Function) -> bool {
+        hir_ty::ffi::foreign_signatures_match(db, self.id, other.id)
+    }
+
     pub fn as_proc_macro(self, db: &dyn HirDatabase) -> Option<Macro> {
         let function_data = db.function_data(self.id);
         let attrs = &function_data.attrs;
@@ -2031,6 +2235,21 @@ pub fn as_proc_macro(self, db: &dyn HirDatabase) -> Option<Macro> {
         def_map.fn_as_proc_macro(self.id).map(|id| Macro { id: id.into() })
     }
 
+    /// Estimates the stack space a call to this function needs, from the layouts of the locals of
+    /// its MIR and of that of its callees, following at most `depth_limit` nested calls.
+    ///
+    /// Returns `None` if the function's MIR can't be built.
+    pub fn stack_usage(self, db: &dyn HirDatabase, depth_limit: usize) -> Option<StackUsage> {
+        stack_usage(
+            db,
+            self.id,
+            TyBuilder::placeholder_subst(db, self.id),
+            depth_limit,
+            &mut Vec::new(),
+            &mut FxHashMap::default(),
+        )
+    }
+
     pub fn eval(
         self,
         db: &dyn HirDatabase,
@@ -2281,22 +2500,8 @@ pub fn ty(self, db: &dyn HirDatabase) -> Type {
 
     pub fn render_eval(self, db: &dyn HirDatabase) -> Result<String, ConstEvalError> {
         let c = db.const_eval(self.id.into(), Substitution::empty(Interner), None)?;
-        let data = &c.data(Interner);
-        if let TyKind::Scalar(s) = data.ty.kind(Interner) {
-            if matches!(s, Scalar::Int(_) | Scalar::Uint(_)) {
-                if let hir_ty::ConstValue::Concrete(c) = &data.value {
-                    if let hir_ty::ConstScalar::Bytes(b, _) = &c.interned {
-                        let value = u128::from_le_bytes(mir::pad16(b, false));
-                        let value_signed =
-                            i128::from_le_bytes(mir::pad16(b, matches!(s, Scalar::Int(_))));
-                        if value >= 10 {
-                            return Ok(format!("{} ({:#X})", value_signed, value));
-                        } else {
-                            return Ok(format!("{}", value_signed));
-                        }
-                    }
-                }
-            }
+        if let Some(s) = render_int_const(&c) {
+            return Ok(s);
         }
         if let Ok(s) = mir::render_const_using_debug_impl(db, self.id, &c) {
             Ok(s)
@@ -2306,6 +2511,23 @@ pub fn render_eval(self, db: &dyn HirDatabase) -> Result<String, ConstEvalError>
     }
 }
 
+/// Renders an integer constant in decimal, and also in hex when it has several digits.
+pub(crate) fn render_int_const(c: &hir_ty::Const) -> Option<String> {
+    let data = &c.data(Interner);
+    let TyKind::Scalar(s @ (Scalar::Int(_) | Scalar::Uint(_))) = data.ty.kind(Interner) else {
+        return None;
+    };
+    let hir_ty::ConstValue::Concrete(c) = &data.value else { return None };
+    let hir_ty::ConstScalar::Bytes(b, _) = &c.interned else { return None };
+    let value = u128::from_le_bytes(mir::pad16(b, false));
+    let value_signed = i128::from_le_bytes(mir::pad16(b, matches!(s, Scalar::Int(_))));
+    if value >= 10 {
+        Some(format!("{} ({:#X})", value_signed, value))
+    } else {
+        Some(format!("{}", value_signed))
+    }
+}
+
 impl HasVisibility for Const {
     fn visibility(&self, db: &dyn HirDatabase) -> Visibility {
         db.const_visibility(self.id)
@@ -2350,6 +2572,20 @@ pub struct Trait {
     pub(crate) id: TraitId,
 }
 
+/// A reason why a trait can't be made into a trait object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObjectSafetyViolation {
+    /// The trait requires `Self: Sized`.
+    SizedSelf,
+    /// A supertrait of the trait mentions `Self` as a type argument, e.g. `trait Foo: PartialEq`.
+    SelfReferential,
+    Method(Function, MethodViolationCode),
+    AssocConst(Const),
+    GAT(TypeAlias),
+    /// One of the supertraits is not object safe.
+    HasNonSafeSuperTrait(Trait),
+}
+
 impl Trait {
     pub fn lang(db: &dyn HirDatabase, krate: Crate, name: &Name) -> Option<Trait> {
         db.lang_item(krate.into(), LangItem::from_name(name)?)
@@ -2382,6 +2618,32 @@ pub fn is_unsafe(&self, db: &dyn HirDatabase) -> bool {
         db.trait_data(self.id).is_unsafe
     }
 
+    /// Whether `dyn Trait` can be formed from this trait.
+    pub fn is_object_safe(self, db: &dyn HirDatabase) -> bool {
+        hir_ty::object_safety::object_safety(db, self.id).is_none()
+    }
+
+    /// All the reasons why `dyn Trait` can't be formed from this trait, including the ones coming
+    /// from its supertraits.
+    pub fn object_safety_violations(self, db: &dyn HirDatabase) -> Vec<ObjectSafetyViolation> {
+        hir_ty::object_safety::object_safety_violations(db, self.id)
+            .into_iter()
+            .map(|violation| {
+                use hir_ty::object_safety::ObjectSafetyViolation as Violation;
+                match violation {
+                    Violation::SizedSelf => ObjectSafetyViolation::SizedSelf,
+                    Violation::SelfReferential => ObjectSafetyViolation::SelfReferential,
+                    Violation::Method(it, code) => ObjectSafetyViolation::Method(it.into(), code),
+                    Violation::AssocConst(it) => ObjectSafetyViolation::AssocConst(it.into()),
+                    Violation::GAT(it) => ObjectSafetyViolation::GAT(it.into()),
+                    Violation::HasNonSafeSuperTrait(it) => {
+                        ObjectSafetyViolation::HasNonSafeSuperTrait(it.into())
+                    }
+                }
+            })
+            .collect()
+    }
+
     pub fn type_or_const_param_count(
         &self,
         db: &dyn HirDatabase,
@@ -3245,6 +3507,34 @@ pub fn is_implicit(self, db: &dyn HirDatabase) -> bool {
         }
     }
 
+    /// Does this type parameter have the implicit `Sized` bound? Every type parameter except
+    /// `Self` of a trait has it, unless it's relaxed with `?Sized`.
+    pub fn is_implicitly_sized(self, db: &dyn HirDatabase) -> bool {
+        let params = db.generic_params(self.id.parent());
+        let Some(data) = params.type_or_consts[self.id.local_id()].type_param() else {
+            return false;
+        };
+        if data.provenance == TypeParamProvenance::TraitSelf {
+            return false;
+        }
+        let is_self = |target: &WherePredicateTypeTarget| match target {
+            WherePredicateTypeTarget::TypeOrConstParam(id) => *id == self.id.local_id(),
+            WherePredicateTypeTarget::TypeRef(ty) => match &**ty {
+                TypeRef::Path(path) => {
+                    data.name.is_some()
+                        && path.mod_path().and_then(|it| it.as_ident()) == data.name.as_ref()
+                }
+                _ => false,
+            },
+        };
+        !params.where_predicates.iter().any(|pred| match pred {
+            WherePredicate::TypeBound { target, bound } => {
+                is_self(target) && matches!(&**bound, TypeBound::Path(_, TraitBoundModifier::Maybe))
+            }
+            _ => false,
+        })
+    }
+
     pub fn ty(self, db: &dyn HirDatabase) -> Type {
         let resolver = self.id.parent().resolver(db.upcast());
         let ty =
@@ -3551,6 +3841,18 @@ pub fn check_orphan_rules(self, db: &dyn HirDatabase) -> bool {
     }
 }
 
+/// A trait bound that is required but not declared, see [`Function::missing_bounds`].
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+pub struct MissingBound {
+    pub trait_ref: TraitRef,
+    /// The associated types the body relies on, e.g. `Output = T` for `T: Add`.
+    pub assoc_types: Vec<(TypeAlias, Type)>,
+}
+
+fn trait_ref_contains_unknown(trait_ref: &hir_ty::TraitRef) -> bool {
+    trait_ref.substitution.type_parameters(Interner).any(|ty| ty.contains_unknown())
+}
+
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
 pub struct TraitRef {
     env: Arc<TraitEnvironment>,
@@ -3579,6 +3881,27 @@ pub fn self_ty(&self) -> Type {
         Type { env: self.env.clone(), ty }
     }
 
+    /// Returns the type arguments of this trait reference after `Self`, leaving out the trailing
+    /// ones which are the same as their defaults.
+    pub fn explicit_type_arguments(&self, db: &dyn HirDatabase) -> Vec<Type> {
+        let defaults = db.generic_defaults(self.trait_ref.hir_trait_id().into());
+        let subst = &self.trait_ref.substitution;
+        let args = subst.as_slice(Interner);
+        let explicit_len = (1..args.len())
+            .rev()
+            .find(|&idx| {
+                defaults.get(idx).map_or(true, |default| {
+                    default.clone().substitute(Interner, subst) != args[idx]
+                })
+            })
+            .map_or(1, |idx| idx + 1);
+        args[1..explicit_len]
+            .iter()
+            .filter_map(|arg| arg.ty(Interner))
+            .map(|ty| Type { env: self.env.clone(), ty: ty.clone() })
+            .collect()
+    }
+
     /// Returns `idx`-th argument of this trait reference if it is a type argument. Note that the
     /// first argument is the `Self` type.
     pub fn get_type_argument(&self, idx: usize) -> Option<Type> {
@@ -3965,6 +4288,43 @@ pub fn impls_trait(&self, db: &dyn HirDatabase, trait_: Trait, args: &[Type]) ->
         db.trait_solve(self.env.krate, self.env.block, goal).is_some()
     }
 
+    /// Whether the bound `self: implied<implied_args>` follows from the bound
+    /// `self: trait_<args>`, because `implied<implied_args>` is `trait_<args>` or one of its
+    /// supertraits. Missing arguments are filled in with their defaults.
+    pub fn trait_bound_implies(
+        &self,
+        db: &dyn HirDatabase,
+        (trait_, args): (Trait, &[Type]),
+        (implied, implied_args): (Trait, &[Type]),
+    ) -> bool {
+        let trait_ref = |trait_: Trait, args: &[Type]| {
+            let params = GenericDef::from(trait_).type_or_const_params(db);
+            if params.iter().any(|it| it.as_const_param(db).is_some()) {
+                return None;
+            }
+            let mut builder = TyBuilder::trait_ref(db, trait_.id).push(self.ty.clone());
+            for arg in args {
+                if builder.remaining() == 0 {
+                    return None;
+                }
+                builder = builder.push(arg.ty.clone());
+            }
+            let trait_ref =
+                builder.fill_with_defaults(db, || TyKind::Error.intern(Interner)).build();
+            let is_known = trait_ref
+                .substitution
+                .iter(Interner)
+                .all(|it| it.ty(Interner).map_or(false, |it| !it.contains_unknown()));
+            is_known.then_some(trait_ref)
+        };
+        let (Some(trait_ref), Some(implied)) =
+            (trait_ref(trait_, args), trait_ref(implied, implied_args))
+        else {
+            return false;
+        };
+        hir_ty::all_super_trait_refs(db, trait_ref, |it| (it == implied).then_some(())).is_some()
+    }
+
     pub fn normalize_trait_assoc_type(
         &self,
         db: &dyn HirDatabase,
@@ -4614,6 +4974,18 @@ pub fn as_type_param(&self, db: &dyn HirDatabase) -> Option<TypeParam> {
         }
     }
 
+    /// Replaces the type parameters in this type with the types `args` maps them to.
+    pub fn substitute_type_params(
+        &self,
+        db: &dyn HirDatabase,
+        args: &FxHashMap<TypeParam, Type>,
+    ) -> Type {
+        let ty = hir_ty::replace_placeholders(self.ty.clone(), db, |id| {
+            Some(args.get(&TypeParam { id: TypeParamId::from_unchecked(id) })?.ty.clone())
+        });
+        self.derived(ty)
+    }
+
     /// Returns unique `GenericParam`s contained in this type.
     pub fn generic_params(&self, db: &dyn HirDatabase) -> FxHashSet<GenericParam> {
         hir_ty::collect_placeholders(&self.ty, db)
@@ -4626,6 +4998,109 @@ pub fn layout(&self, db: &dyn HirDatabase) -> Result<Layout, LayoutError> {
         db.layout_of_ty(self.ty.clone(), self.env.clone())
             .map(|layout| Layout(layout, db.target_data_layout(self.env.krate).unwrap()))
     }
+
+    /// Checks whether this type can be used in the signature of a function with a foreign ABI.
+    pub fn ffi_safety(&self, db: &dyn HirDatabase) -> Result<(), FfiUnsafe> {
+        hir_ty::ffi::ffi_safety(db, &self.ty, self.env.clone())
+            .map_err(|it| FfiUnsafe { ty: self.derived(it.ty), reason: it.reason })
+    }
+
+    /// Renders this type as a tree of the types it is made of. Associated type projections and
+    /// `impl Trait` return types additionally get the type they stand for as a child node.
+    pub fn display_tree(&self, db: &dyn HirDatabase) -> TypeTree {
+        let mut tree = TypeTree { nodes: Vec::new() };
+        self.push_display_tree(db, &mut tree, None, TypeTreeNodeKind::Type, 0);
+        tree
+    }
+
+    fn push_display_tree(
+        &self,
+        db: &dyn HirDatabase,
+        tree: &mut TypeTree,
+        parent: Option<usize>,
+        kind: TypeTreeNodeKind,
+        depth: usize,
+    ) {
+        // Hidden types and normalized projections may refer back to the type being expanded.
+        const MAX_EXPANSION_DEPTH: usize = 4;
+
+        let display = self.ty.display_structured(db, None, ClosureStyle::ImplFn);
+        let base = tree.nodes.len();
+        if display.nodes.is_empty() {
+            return;
+        }
+        if let Some(parent) = parent {
+            tree.nodes[parent].children.push(base);
+        }
+        tree.nodes.extend(display.nodes.iter().map(|node| TypeTreeNode {
+            label: display.text[node.range.clone()].to_owned(),
+            kind: if node.parent.is_none() { kind } else { TypeTreeNodeKind::Type },
+            definition: node.definition.map(ModuleDef::from),
+            parent: node.parent.map(|it| base + it).or(parent),
+            children: node.children.iter().map(|it| base + it).collect(),
+        }));
+
+        if depth >= MAX_EXPANSION_DEPTH {
+            return;
+        }
+        for (idx, node) in display.nodes.iter().enumerate() {
+            let expansion = match node.ty.kind(Interner) {
+                TyKind::Alias(AliasTy::Projection(projection)) => {
+                    let ty = db.normalize_projection(projection.clone(), self.env.clone());
+                    (!ty.is_unknown() && ty != node.ty)
+                        .then_some((TypeTreeNodeKind::Normalized, ty))
+                }
+                TyKind::OpaqueType(opaque_ty_id, _)
+                | TyKind::Alias(AliasTy::Opaque(hir_ty::OpaqueTy { opaque_ty_id, .. })) => {
+                    match db.lookup_intern_impl_trait_id((*opaque_ty_id).into()) {
+                        // FIXME: The hidden type is expressed in terms of the function's own
+                        // generic parameters, substitute the opaque type's arguments instead.
+                        hir_ty::ImplTraitId::ReturnTypeImplTrait(func, idx) => db
+                            .infer(func.into())
+                            .type_of_rpit
+                            .get(idx)
+                            .filter(|ty| !ty.is_unknown())
+                            .map(|ty| (TypeTreeNodeKind::HiddenType, ty.clone())),
+                        hir_ty::ImplTraitId::AsyncBlockTypeImplTrait(..)
+                        | hir_ty::ImplTraitId::GenBlockTypeImplTrait(..) => None,
+                    }
+                }
+                _ => None,
+            };
+            if let Some((kind, ty)) = expansion {
+                self.derived(ty).push_display_tree(db, tree, Some(base + idx), kind, depth + 1);
+            }
+        }
+    }
+}
+
+/// A type rendered as a tree of its component types, see [`Type::display_tree`].
+///
+/// Nodes are stored in pre-order, the first node is the root.
+#[derive(Debug)]
+pub struct TypeTree {
+    pub nodes: Vec<TypeTreeNode>,
+}
+
+#[derive(Debug)]
+pub struct TypeTreeNode {
+    /// The rendering of the whole subtree.
+    pub label: String,
+    pub kind: TypeTreeNodeKind,
+    /// The definition of the outermost type constructor, if it has one.
+    pub definition: Option<ModuleDef>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeTreeNodeKind {
+    /// A type or one of its components, like a generic argument or a tuple field.
+    Type,
+    /// The type an associated type projection normalizes to.
+    Normalized,
+    /// The type hidden behind an `impl Trait` return type.
+    HiddenType,
 }
 
 // FIXME: Document this
@@ -4720,11 +5195,121 @@ pub fn params(
     pub fn return_type(&self) -> Type {
         self.ty.derived(self.sig.ret().clone())
     }
+    /// The arguments the generic parameters of the callee are instantiated with, those of the
+    /// callee itself followed by those of its impl or trait. Const arguments are `None`.
+    pub fn generic_args(&self) -> Vec<Option<Type>> {
+        match self.ty.ty.kind(Interner) {
+            TyKind::FnDef(_, substs) => substs
+                .iter(Interner)
+                .map(|arg| arg.ty(Interner).map(|ty| self.ty.derived(ty.clone())))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    /// The type parameters of the called function, those of the function itself followed by those
+    /// of its impl or trait, together with the types they are instantiated with.
+    pub fn type_param_args(&self, db: &dyn HirDatabase) -> Vec<(TypeParam, Type)> {
+        let CallableKind::Function(func) = self.kind() else { return Vec::new() };
+        let mut params = GenericDef::Function(func).type_or_const_params(db);
+        match func.as_assoc_item(db).map(|it| it.container(db)) {
+            Some(AssocItemContainer::Impl(it)) => {
+                params.extend(GenericDef::Impl(it).type_or_const_params(db))
+            }
+            Some(AssocItemContainer::Trait(it)) => {
+                params.extend(GenericDef::Trait(it).type_or_const_params(db))
+            }
+            None => (),
+        }
+        params
+            .into_iter()
+            .zip(self.generic_args())
+            .filter_map(|(param, arg)| Some((param.as_type_param(db)?, arg?)))
+            .collect()
+    }
     pub fn sig(&self) -> &CallableSig {
         &self.sig
     }
 }
 
+fn stack_usage(
+    db: &dyn HirDatabase,
+    func: FunctionId,
+    subst: Substitution,
+    depth_limit: usize,
+    stack: &mut Vec<FunctionId>,
+    cache: &mut FxHashMap<(FunctionId, Substitution, usize), Option<StackUsage>>,
+) -> Option<StackUsage> {
+    let key = (func, subst.clone(), depth_limit);
+    if let Some(it) = cache.get(&key) {
+        return it.clone();
+    }
+    let env = db.trait_environment(func.into());
+    let body = db.monomorphized_mir_body(func.into(), subst, env.clone()).ok()?;
+
+    let mut incomplete = false;
+    let frame_size = body
+        .locals
+        .iter()
+        .map(|(_, local)| match db.layout_of_ty(local.ty.clone(), env.clone()) {
+            Ok(layout) => layout.size.bytes(),
+            Err(_) => {
+                incomplete = true;
+                0
+            }
+        })
+        .sum::<u64>();
+
+    let mut worst_callee: Option<(FunctionId, StackUsage)> = None;
+    stack.push(func);
+    for (_, block) in body.basic_blocks.iter() {
+        let Some(mir::Terminator { kind: mir::TerminatorKind::Call { func: callee, .. }, .. }) =
+            &block.terminator
+        else {
+            continue;
+        };
+        let callee_ty = match callee {
+            mir::Operand::Constant(konst) => konst.data(Interner).ty.clone(),
+            _ => {
+                incomplete = true;
+                continue;
+            }
+        };
+        let TyKind::FnDef(_, callee_subst) = callee_ty.kind(Interner) else {
+            incomplete = true;
+            continue;
+        };
+        // Calls of tuple struct and variant constructors don't need a frame.
+        let Some(CallableDefId::FunctionId(callee)) = callee_ty.callable_def(db) else { continue };
+        let (callee, callee_subst) =
+            db.lookup_impl_method(env.clone(), callee, callee_subst.clone());
+        if depth_limit == 0 || stack.contains(&callee) {
+            incomplete = true;
+            continue;
+        }
+        let Some(usage) = stack_usage(db, callee, callee_subst, depth_limit - 1, stack, cache)
+        else {
+            incomplete = true;
+            continue;
+        };
+        incomplete |= usage.incomplete;
+        if worst_callee.as_ref().map_or(true, |(_, it)| usage.worst_case > it.worst_case) {
+            worst_callee = Some((callee, usage));
+        }
+    }
+    stack.pop();
+
+    let (worst_case, worst_path) = match worst_callee {
+        Some((callee, usage)) => (
+            frame_size + usage.worst_case,
+            iter::once(Function { id: callee }).chain(usage.worst_path).collect(),
+        ),
+        None => (frame_size, Vec::new()),
+    };
+    let res = Some(StackUsage { frame_size, worst_case, worst_path, incomplete });
+    cache.insert(key, res.clone());
+    res
+}
+
 fn closure_source(db: &dyn HirDatabase, closure: ClosureId) -> Option<ast::ClosureExpr> {
     let InternedClosure(owner, expr_id) = db.lookup_intern_closure(closure.into());
     let (_, source_map) = db.body_with_source_map(owner);
@@ -4737,9 +5322,26 @@ fn closure_source(db: &dyn HirDatabase, closure: ClosureId) -> Option<ast::Closu
     }
 }
 
+/// The type that makes a type unusable in an FFI signature, the type itself or one of its fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FfiUnsafe {
+    pub ty: Type,
+    pub reason: FfiUnsafeReason,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Layout(Arc<TyLayout>, Arc<TargetDataLayout>);
 
+/// Where an enum with several variants stores which variant it holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnumTag {
+    /// In a field of its own, which holds the discriminant.
+    Direct { offset: u64, size: u64 },
+    /// In the invalid values of a field of the variant at index `untagged_variant`, which is the
+    /// variant when the field holds a valid value.
+    Niche { offset: u64, size: u64, untagged_variant: usize },
+}
+
 impl Layout {
     pub fn size(&self) -> u64 {
         self.0.size.bytes()
@@ -4793,6 +5395,21 @@ pub fn enum_tag_size(&self) -> Option<usize> {
             };
         Some(tag_size)
     }
+
+    pub fn enum_tag(&self) -> Option<EnumTag> {
+        let layout::Variants::Multiple { tag, tag_encoding, tag_field, .. } = &self.0.variants
+        else {
+            return None;
+        };
+        let offset = self.0.fields.offset(*tag_field).bytes();
+        let size = tag.size(&*self.1).bytes();
+        Some(match tag_encoding {
+            TagEncoding::Direct => EnumTag::Direct { offset, size },
+            TagEncoding::Niche { untagged_variant, .. } => {
+                EnumTag::Niche { offset, size, untagged_variant: untagged_variant.0 }
+            }
+        })
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
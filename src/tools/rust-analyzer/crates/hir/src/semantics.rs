@@ -16,7 +16,7 @@
     nameres::MacroSubNs,
     resolver::{self, HasResolver, Resolver, TypeNs},
     type_ref::Mutability,
-    AsMacroCall, DefWithBodyId, FunctionId, MacroId, TraitId, VariantId,
+    AsMacroCall, AttrDefId, DefWithBodyId, FunctionId, Lookup, MacroId, TraitId, VariantId,
 };
 use hir_expand::{
     attrs::collect_attrs, db::ExpandDatabase, files::InRealFile, name::AsName, ExpansionInfo,
@@ -38,10 +38,11 @@
     db::HirDatabase,
     semantics::source_to_def::{ChildContainer, SourceToDefCache, SourceToDefCtx},
     source_analyzer::{resolve_hir_path, SourceAnalyzer},
-    Access, Adjust, Adjustment, AutoBorrow, BindingMode, BuiltinAttr, Callable, ConstParam, Crate,
-    DeriveHelper, Field, Function, HasSource, HirFileId, Impl, InFile, Label, LifetimeParam, Local,
-    Macro, Module, ModuleDef, Name, OverloadedDeref, Path, ScopeDef, Struct, ToolModule, Trait,
-    TupleField, Type, TypeAlias, TypeParam, VariantDef,
+    Access, Adjust, Adjustment, AttrsWithOwner, AutoBorrow, BindingMode, BuiltinAttr, Callable,
+    ConstEvalError, ConstParam, Crate, DeriveHelper, Field, Function, HasSource, HirFileId, Impl,
+    InFile, Label, LifetimeParam, Local, Macro, Module, ModuleDef, Name, Namespace,
+    OverloadedDeref, Path, ScopeDef, Struct, ToolModule, Trait, TupleField, Type, TypeAlias,
+    TypeParam, VariantDef,
 };
 
 pub enum DescendPreference {
@@ -105,6 +106,17 @@ pub struct TypeInfo {
     pub adjusted: Option<Type>,
 }
 
+/// The generic arguments inference chose for a call of a generic function.
+#[derive(Debug)]
+pub struct InferredGenericArgs {
+    pub function: Function,
+    /// The arguments of the function's own generic parameters.
+    pub own: Vec<Type>,
+    /// For a method of a generic trait, the trait and the arguments of its generic parameters,
+    /// other than `Self`.
+    pub trait_: Option<(Trait, Vec<Type>)>,
+}
+
 impl TypeInfo {
     pub fn original(self) -> Type {
         self.original
@@ -120,6 +132,24 @@ pub fn adjusted(self) -> Type {
     }
 }
 
+/// The macro invocation some syntax was produced by, see [`Semantics::macro_invocation`].
+#[derive(Debug)]
+pub struct MacroInvocation {
+    /// The invoked macro, if it could be resolved.
+    pub macro_: Option<Macro>,
+    pub kind: MacroInvocationKind,
+    /// The macro call, the attribute of an attribute macro or the `#[derive]` attribute
+    /// containing the derive.
+    pub call: InFile<SyntaxNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MacroInvocationKind {
+    FnLike,
+    Derive,
+    Attr,
+}
+
 /// Primary API to get semantic information, like types, from syntax trees.
 pub struct Semantics<'db, DB> {
     pub db: &'db DB,
@@ -314,6 +344,34 @@ pub fn expand_derive_macro(&self, attr: &ast::Attr) -> Option<Vec<SyntaxNode>> {
         Some(res)
     }
 
+    /// Returns the macro invocation that produced the syntax of `file_id`, if it is the expansion
+    /// of a macro.
+    pub fn macro_invocation(&self, file_id: HirFileId) -> Option<MacroInvocation> {
+        let macro_call_id = file_id.macro_file()?.macro_call_id;
+        let loc = self.db.lookup_intern_macro_call(macro_call_id);
+        let mut call = loc.to_node(self.db.upcast());
+        let kind = match loc.kind {
+            hir_expand::MacroCallKind::FnLike { .. } => MacroInvocationKind::FnLike,
+            hir_expand::MacroCallKind::Derive { .. } => MacroInvocationKind::Derive,
+            hir_expand::MacroCallKind::Attr { invoc_attr_index, .. } => {
+                if !loc.def.is_attribute_derive() {
+                    // `to_node` gives us the attributed item, narrow it down to the attribute
+                    let attr = ast::AnyHasAttrs::cast(call.value.clone()).and_then(|item| {
+                        collect_attrs(&item).nth(invoc_attr_index.ast_index())?.1.left()
+                    });
+                    if let Some(attr) = attr {
+                        call = call.with_value(attr.syntax().clone());
+                    }
+                }
+                MacroInvocationKind::Attr
+            }
+        };
+        let macro_ = self
+            .with_ctx(|ctx| macro_call_to_macro_id(ctx, self.db.upcast(), macro_call_id))
+            .map(|id| Macro { id });
+        Some(MacroInvocation { macro_, kind, call })
+    }
+
     fn derive_macro_calls(&self, attr: &ast::Attr) -> Option<Vec<Option<MacroCallId>>> {
         let adt = attr.syntax().parent().and_then(ast::Adt::cast)?;
         let file_id = self.find_file(adt.syntax()).file_id;
@@ -624,12 +682,12 @@ fn is_from_include_file(
         let mut cache = self.expansion_info_cache.borrow_mut();
 
         // iterate related crates and find all include! invocations that include_file_id matches
-        for (invoc, _) in self
+        for (invoc, _, _) in self
             .db
             .relevant_crates(file_id)
             .iter()
             .flat_map(|krate| self.db.include_macro_invoc(*krate))
-            .filter(|&(_, include_file_id)| include_file_id == file_id)
+            .filter(|&(_, include_file_id, _)| include_file_id == file_id)
         {
             let macro_file = invoc.as_macro_file();
             let expansion_info = cache
@@ -1091,6 +1149,49 @@ pub fn type_of_expr(&self, expr: &ast::Expr) -> Option<TypeInfo> {
             .map(|(ty, coerced)| TypeInfo { original: ty, adjusted: coerced })
     }
 
+    /// Evaluates `expr` on its own with the MIR interpreter, and renders its value. The locals it
+    /// uses from outside of it get the values of `args`, which are literals of numbers, `bool`s or
+    /// `char`s, where numbers can be negated.
+    pub fn eval_expr(
+        &self,
+        expr: &ast::Expr,
+        args: &[(Local, ast::Expr)],
+    ) -> Option<Result<String, ConstEvalError>> {
+        self.analyze(expr.syntax())?.eval_expr(self.db, expr, args)
+    }
+
+    /// Returns whether `expr` evaluates to the `Default` value of its type, which must be a primitive
+    /// number, `bool` or `char`. `None` if it has another type or can't be evaluated.
+    pub fn is_default_scalar(&self, expr: &ast::Expr) -> Option<bool> {
+        self.analyze(expr.syntax())?.is_default_scalar(self.db, expr)
+    }
+
+    /// Returns the generic arguments that inference chose for `call`, which must be a call or a
+    /// method call expression. `None` if the function has const or `impl Trait` parameters, as
+    /// those can't be spelled out in a turbofish.
+    pub fn inferred_generic_args(&self, call: &ast::Expr) -> Option<InferredGenericArgs> {
+        self.analyze(call.syntax())?.inferred_generic_args(self.db, call)
+    }
+
+    /// Returns the type the context of `expr` expects it to have, as determined by type inference.
+    ///
+    /// This is known for expressions in coercion sites, like function arguments, struct
+    /// fields, tail expressions of functions and initializers of `let`s with a type annotation, but
+    /// also for expressions nested in those when inference could propagate the type, like the
+    /// elements of an array.
+    pub fn expected_type_of_expr(&self, expr: &ast::Expr) -> Option<Type> {
+        self.analyze(expr.syntax())?
+            .expected_type_of_expr(self.db, expr)
+            .filter(|ty| !ty.is_unknown())
+    }
+
+    /// Returns the residual type of the operand of `try_expr` and the type it is converted into
+    /// by `?`, e.g. `Result<Infallible, io::Error>` and the `Result<T, MyError>` the enclosing
+    /// function returns.
+    pub fn try_expr_conversion(&self, try_expr: &ast::TryExpr) -> Option<(Type, Type)> {
+        self.analyze(try_expr.syntax())?.try_expr_conversion(self.db, try_expr)
+    }
+
     pub fn type_of_pat(&self, pat: &ast::Pat) -> Option<TypeInfo> {
         self.analyze(pat.syntax())?
             .type_of_pat(self.db, pat)
@@ -1204,6 +1305,43 @@ pub fn resolve_path(&self, path: &ast::Path) -> Option<PathResolution> {
         self.analyze(path.syntax())?.resolve_path(self.db, path)
     }
 
+    /// The `use` items through which the last segment of `path` is imported, starting with the
+    /// one of the module the path goes through and following re-exports towards the definition.
+    /// Each comes with the module containing it, which is where the links in its docs resolve.
+    pub fn imports_of_path(&self, path: &ast::Path) -> Vec<(Module, AttrsWithOwner)> {
+        self.imports_of_path_(path).unwrap_or_default()
+    }
+
+    fn imports_of_path_(&self, path: &ast::Path) -> Option<Vec<(Module, AttrsWithOwner)>> {
+        let name = path.segment()?.name_ref()?.as_name();
+        let module = match path.qualifier() {
+            Some(qualifier) => match self.resolve_path(&qualifier)? {
+                PathResolution::Def(ModuleDef::Module(it)) => it,
+                _ => return None,
+            },
+            None => self.scope(path.syntax())?.module(),
+        };
+        let ns = match self.resolve_path(path)? {
+            PathResolution::Def(ModuleDef::Macro(_)) => Namespace::Macros,
+            PathResolution::Def(
+                ModuleDef::Function(_) | ModuleDef::Const(_) | ModuleDef::Static(_),
+            ) => Namespace::Values,
+            PathResolution::Def(_) => Namespace::Types,
+            _ => return None,
+        };
+        let def_map = module.id.def_map(self.db.upcast());
+        let imports = def_map[module.id.local_id].scope.import_chain(self.db.upcast(), &name, ns);
+        let res = imports
+            .into_iter()
+            .map(|it| {
+                let module = it.import.lookup(self.db.upcast()).container;
+                let attrs = AttrsWithOwner::new(self.db.upcast(), AttrDefId::UseId(it.import));
+                (module.into(), attrs)
+            })
+            .collect();
+        Some(res)
+    }
+
     fn resolve_variant(&self, record_lit: ast::RecordExpr) -> Option<VariantId> {
         self.analyze(record_lit.syntax())?.resolve_variant(self.db, record_lit)
     }
@@ -83,7 +83,10 @@
 //! At the moment, we don't really handle this well and return the first answer
 //! that works. Ideally, we should first let the caller to pick a specific
 //! active crate for a given position, and then provide an API to resolve all
-//! syntax nodes against this specific crate.
+//! syntax nodes against this specific crate. What we do is to pick the crate
+//! per top-level item of a file, preferring the first crate in which the item
+//! isn't cfg'd out, so that code only active for some variants of a crate,
+//! like those analyzed for extra targets, still resolves.
 
 use base_db::FileId;
 use hir_def::{
@@ -128,6 +131,19 @@ impl SourceToDefCtx<'_, '_> {
                     .map(|local_id| crate_def_map.module_id(local_id)),
             )
         }
+        if mods.is_empty() {
+            // A file that isn't a module can still be `include!`d into one.
+            for &crate_id in self.db.relevant_crates(file).iter() {
+                let crate_def_map = self.db.crate_def_map(crate_id);
+                mods.extend(
+                    self.db
+                        .include_macro_invoc(crate_id)
+                        .into_iter()
+                        .filter(|&(_, include_file_id, _)| include_file_id == file)
+                        .map(|(_, _, local_id)| crate_def_map.module_id(local_id)),
+                )
+            }
+        }
         mods
     }
 
@@ -138,18 +154,23 @@ pub(super) fn module_to_def(&self, src: InFile<ast::Module>) -> Option<ModuleId>
             .ancestors_with_macros_skip_attr_item(self.db.upcast())
             .find_map(|it| it.map(ast::Module::cast).transpose());
 
-        let parent_module = match parent_declaration {
-            Some(parent_declaration) => self.module_to_def(parent_declaration),
+        let parent_modules = match parent_declaration {
+            Some(parent_declaration) => {
+                self.module_to_def(parent_declaration).into_iter().collect()
+            }
             None => {
                 let file_id = src.file_id.original_file(self.db.upcast());
-                self.file_to_def(file_id).first().copied()
+                self.file_to_def(file_id)
             }
-        }?;
+        };
 
         let child_name = src.value.name()?.as_name();
-        let def_map = parent_module.def_map(self.db.upcast());
-        let &child_id = def_map[parent_module.local_id].children.get(&child_name)?;
-        Some(def_map.module_id(child_id))
+        // The module may be cfg'd out in some of the crates the file is part of.
+        parent_modules.into_iter().find_map(|parent_module| {
+            let def_map = parent_module.def_map(self.db.upcast());
+            let &child_id = def_map[parent_module.local_id].children.get(&child_name)?;
+            Some(def_map.module_id(child_id))
+        })
     }
 
     pub(super) fn source_file_to_def(&self, src: InFile<ast::SourceFile>) -> Option<ModuleId> {
@@ -361,16 +382,67 @@ pub(super) fn proc_macro_to_def(&mut self, src: InFile<ast::Fn>) -> Option<Macro
     }
 
     pub(super) fn find_container(&mut self, src: InFile<&SyntaxNode>) -> Option<ChildContainer> {
+        // The ancestor directly below the root of the original file.
+        let mut top_level = src.cloned();
         for container in src.ancestors_with_macros_skip_attr_item(self.db.upcast()) {
-            if let Some(res) = self.container_to_def(container) {
+            if let Some(res) = self.container_to_def(container.clone()) {
                 return Some(res);
             }
+            if container.value.parent().is_some() {
+                top_level = container;
+            }
         }
 
-        let def = self.file_to_def(src.file_id.original_file(self.db.upcast())).first().copied()?;
+        let file_id = src.file_id.original_file(self.db.upcast());
+        let modules = self.file_to_def(file_id);
+        let def = match ast::Item::cast(top_level.value) {
+            Some(item) if modules.len() > 1 => {
+                let item = InFile::new(top_level.file_id, item);
+                modules
+                    .iter()
+                    .copied()
+                    .find(|&module| self.module_has_item(module, item.as_ref()) == Some(true))
+                    .or_else(|| modules.first().copied())
+            }
+            _ => modules.first().copied(),
+        }?;
         Some(def.into())
     }
 
+    /// Whether `item`, a top-level item of the file of `module`, is part of `module`, that is it
+    /// isn't cfg'd out. Returns `None` for items that can't be looked up.
+    fn module_has_item(&mut self, module: ModuleId, item: InFile<&ast::Item>) -> Option<bool> {
+        if let ast::Item::Module(it) = item.value {
+            let name = it.name()?.as_name();
+            let def_map = module.def_map(self.db.upcast());
+            return Some(def_map[module.local_id].children.contains_key(&name));
+        }
+        let dyn_map = self.cache_for(module.into(), item.file_id);
+        let found = match item.value {
+            ast::Item::Const(it) => dyn_map[keys::CONST].get(it).is_some(),
+            ast::Item::Enum(it) => dyn_map[keys::ENUM].get(it).is_some(),
+            ast::Item::ExternCrate(it) => dyn_map[keys::EXTERN_CRATE].get(it).is_some(),
+            ast::Item::Fn(it) => {
+                dyn_map[keys::FUNCTION].get(it).is_some()
+                    || dyn_map[keys::PROC_MACRO].get(it).is_some()
+            }
+            ast::Item::Impl(it) => dyn_map[keys::IMPL].get(it).is_some(),
+            ast::Item::MacroDef(it) => dyn_map[keys::MACRO2].get(it).is_some(),
+            ast::Item::MacroRules(it) => dyn_map[keys::MACRO_RULES].get(it).is_some(),
+            ast::Item::Static(it) => dyn_map[keys::STATIC].get(it).is_some(),
+            ast::Item::Struct(it) => dyn_map[keys::STRUCT].get(it).is_some(),
+            ast::Item::Trait(it) => dyn_map[keys::TRAIT].get(it).is_some(),
+            ast::Item::TraitAlias(it) => dyn_map[keys::TRAIT_ALIAS].get(it).is_some(),
+            ast::Item::TypeAlias(it) => dyn_map[keys::TYPE_ALIAS].get(it).is_some(),
+            ast::Item::Union(it) => dyn_map[keys::UNION].get(it).is_some(),
+            ast::Item::Use(it) => dyn_map[keys::USE].get(it).is_some(),
+            ast::Item::ExternBlock(_) | ast::Item::MacroCall(_) | ast::Item::Module(_) => {
+                return None
+            }
+        };
+        Some(found || dyn_map[keys::ATTR_MACRO_CALL].get(item.value).is_some())
+    }
+
     fn container_to_def(&mut self, container: InFile<SyntaxNode>) -> Option<ChildContainer> {
         let cont = if let Some(item) = ast::Item::cast(container.value.clone()) {
             match item {
@@ -13,7 +13,8 @@
         scope::{ExprScopes, ScopeId},
         Body, BodySourceMap,
     },
-    hir::{BindingId, ExprId, Pat, PatId},
+    generics::{TypeOrConstParamData, TypeParamData, TypeParamProvenance},
+    hir::{BindingId, Expr, ExprId, Literal, Pat, PatId},
     lang_item::LangItem,
     lower::LowerCtx,
     nameres::MacroSubNs,
@@ -31,13 +32,17 @@
     HirFileId, InFile, MacroFileId, MacroFileIdExt,
 };
 use hir_ty::{
+    consteval::{self, ConstEvalError},
     diagnostics::{
         record_literal_missing_fields, record_pattern_missing_fields, unsafe_expressions,
         UnsafeExpr,
     },
+    display::HirDisplay,
     lang_items::lang_items_for_bin_op,
-    method_resolution, Adjustment, InferenceResult, Interner, Substitution, Ty, TyExt, TyKind,
-    TyLoweringContext,
+    method_resolution,
+    mir::MirEvalError,
+    Adjustment, CallableDefId, ConstScalar, InferenceResult, Interner, MemoryMap, Scalar,
+    Substitution, Ty, TyExt, TyKind, TyLoweringContext,
 };
 use itertools::Itertools;
 use smallvec::SmallVec;
@@ -48,9 +53,12 @@
 use triomphe::Arc;
 
 use crate::{
-    db::HirDatabase, semantics::PathResolution, Adt, AssocItem, BindingMode, BuiltinAttr,
-    BuiltinType, Callable, Const, DeriveHelper, Field, Function, Local, Macro, ModuleDef, Static,
-    Struct, ToolModule, Trait, TraitAlias, TupleField, Type, TypeAlias, Variant,
+    db::HirDatabase,
+    render_int_const,
+    semantics::{InferredGenericArgs, PathResolution},
+    Adt, AssocItem, BindingMode, BuiltinAttr, BuiltinType, Callable, Const, DeriveHelper, Field,
+    Function, Local, Macro, ModuleDef, Static, Struct, ToolModule, Trait, TraitAlias, TupleField,
+    Type, TypeAlias, Variant,
 };
 
 /// `SourceAnalyzer` is a convenience wrapper which exposes HIR API in terms of
@@ -190,6 +198,131 @@ pub(crate) fn type_of_expr(
         Some((mk_ty(ty), coerced.map(mk_ty)))
     }
 
+    pub(crate) fn eval_expr(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+        args: &[(Local, ast::Expr)],
+    ) -> Option<Result<String, ConstEvalError>> {
+        let (def, ..) = self.def.as_ref()?;
+        let expr_id = self.expr_id(db, expr)?;
+        let infer = self.infer.as_ref()?;
+        let args = args
+            .iter()
+            .filter(|(local, _)| local.parent == *def)
+            .map(|(local, value)| {
+                let value = literal_to_const(db, *def, value, &infer[local.binding_id])?;
+                Ok((local.binding_id, value))
+            })
+            .collect::<Result<Vec<_>, ConstEvalError>>();
+        let value = args.and_then(|args| consteval::eval_expr(db, *def, expr_id, &args));
+        Some(value.map(|it| render_int_const(&it).unwrap_or_else(|| it.display(db).to_string())))
+    }
+
+    pub(crate) fn is_default_scalar(&self, db: &dyn HirDatabase, expr: &ast::Expr) -> Option<bool> {
+        let (def, ..) = self.def.as_ref()?;
+        let expr_id = self.expr_id(db, expr)?;
+        let ty = self.infer.as_ref()?.type_of_expr.get(expr_id)?;
+        if !matches!(ty.kind(Interner), TyKind::Scalar(_)) {
+            return None;
+        }
+        let value = consteval::eval_expr(db, *def, expr_id, &[]).ok()?;
+        let hir_ty::ConstValue::Concrete(c) = &value.data(Interner).value else { return None };
+        let ConstScalar::Bytes(bytes, _) = &c.interned else { return None };
+        // The default of all the primitive scalars is zero, and `-0.0` isn't.
+        Some(bytes.iter().all(|&it| it == 0))
+    }
+
+    pub(crate) fn inferred_generic_args(
+        &self,
+        db: &dyn HirDatabase,
+        call: &ast::Expr,
+    ) -> Option<InferredGenericArgs> {
+        let infer = self.infer.as_ref()?;
+        let (func, substs) = match call {
+            ast::Expr::MethodCallExpr(_) => infer.method_resolution(self.expr_id(db, call)?)?,
+            ast::Expr::CallExpr(it) => {
+                let callee = &infer[self.expr_id(db, &it.expr()?)?];
+                let (CallableDefId::FunctionId(func), TyKind::FnDef(_, substs)) =
+                    (callee.callable_def(db)?, callee.kind(Interner))
+                else {
+                    return None;
+                };
+                (func, substs.clone())
+            }
+            _ => return None,
+        };
+        // The substitution has the arguments of the function's own parameters first, then those
+        // of its parent.
+        let mut args = substs.iter(Interner).map(|it| {
+            it.ty(Interner).map(|ty| Type::new_with_resolver(db, &self.resolver, ty.clone()))
+        });
+        let mut own = Vec::new();
+        for (_, param) in db.generic_params(func.into()).type_or_consts.iter() {
+            match param {
+                TypeOrConstParamData::TypeParamData(TypeParamData {
+                    provenance: TypeParamProvenance::TypeParamList,
+                    ..
+                }) => own.push(args.next()??),
+                _ => return None,
+            }
+        }
+        let trait_ = match func.lookup(db.upcast()).container {
+            ItemContainerId::TraitId(trait_) => {
+                let mut trait_args = Vec::new();
+                for (_, param) in db.generic_params(trait_.into()).type_or_consts.iter() {
+                    let arg = args.next().flatten();
+                    match param {
+                        TypeOrConstParamData::TypeParamData(TypeParamData {
+                            provenance: TypeParamProvenance::TraitSelf,
+                            ..
+                        }) => (),
+                        _ => trait_args.push(arg),
+                    }
+                }
+                trait_args
+                    .into_iter()
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|it| !it.is_empty())
+                    .map(|it| (trait_.into(), it))
+            }
+            _ => None,
+        };
+        Some(InferredGenericArgs { function: func.into(), own, trait_ })
+    }
+
+    pub(crate) fn expected_type_of_expr(
+        &self,
+        db: &dyn HirDatabase,
+        expr: &ast::Expr,
+    ) -> Option<Type> {
+        let expr_id = self.expr_id(db, expr)?;
+        let ty = self.infer.as_ref()?.expected_type_of_expr.get(expr_id)?.clone();
+        Some(Type::new_with_resolver(db, &self.resolver, ty))
+    }
+
+    pub(crate) fn try_expr_conversion(
+        &self,
+        db: &dyn HirDatabase,
+        try_expr: &ast::TryExpr,
+    ) -> Option<(Type, Type)> {
+        // `<expr>?` is desugared into a match whose second arm converts the residual with
+        // `Try::from_residual(residual)`, see `ExprCollector::collect_try_operator`
+        let expr_id = self.expr_id(db, &try_expr.clone().into())?;
+        let body = self.body()?;
+        let Expr::Match { arms, .. } = &body[expr_id] else { return None };
+        let (Expr::Return { expr: Some(call) } | Expr::Break { expr: Some(call), .. }) =
+            &body[arms.get(1)?.expr]
+        else {
+            return None;
+        };
+        let Expr::Call { args, .. } = &body[*call] else { return None };
+        let [residual] = &**args else { return None };
+        let infer = self.infer.as_ref()?;
+        let mk_ty = |ty: &Ty| Type::new_with_resolver(db, &self.resolver, ty.clone());
+        Some((mk_ty(&infer[*residual]), mk_ty(&infer[*call])))
+    }
+
     pub(crate) fn type_of_pat(
         &self,
         db: &dyn HirDatabase,
@@ -834,7 +967,7 @@ pub(crate) fn is_unsafe_macro_call(
                     *def,
                     body,
                     expanded_expr,
-                    &mut |UnsafeExpr { inside_unsafe_block, .. }| is_unsafe |= !inside_unsafe_block,
+                    &mut |UnsafeExpr { unsafe_block, .. }| is_unsafe |= unsafe_block.is_none(),
                 );
                 return is_unsafe;
             }
@@ -1239,3 +1372,49 @@ fn resolve_hir_path_qualifier(
             .map(|it| PathResolution::Def(it.into()))
     })
 }
+
+/// Interns a literal of a number, `bool` or `char`, where numbers can be negated, as a value of `ty`.
+fn literal_to_const(
+    db: &dyn HirDatabase,
+    def: DefWithBodyId,
+    value: &ast::Expr,
+    ty: &Ty,
+) -> Result<hir_ty::Const, ConstEvalError> {
+    let unsupported =
+        || MirEvalError::NotSupported(format!("`{value}` as a value of type `{}`", ty.display(db)));
+    let (literal, negated) = match value {
+        ast::Expr::Literal(it) => (it.clone(), false),
+        ast::Expr::PrefixExpr(it) if it.op_kind() == Some(ast::UnaryOp::Neg) => match it.expr() {
+            Some(ast::Expr::Literal(it)) => (it, true),
+            _ => return Err(unsupported().into()),
+        },
+        _ => return Err(unsupported().into()),
+    };
+    let TyKind::Scalar(scalar) = ty.kind(Interner) else { return Err(unsupported().into()) };
+    let size = db
+        .layout_of_ty(ty.clone(), db.trait_environment_for_body(def))
+        .map_err(|e| MirEvalError::LayoutError(e, ty.clone()))?
+        .size
+        .bytes_usize();
+    let bytes: Box<[u8]> = match (Literal::from(literal.kind()), scalar) {
+        (Literal::Int(it, _), Scalar::Int(_) | Scalar::Uint(_)) => {
+            let it = if negated { -it } else { it };
+            it.to_le_bytes()[..size].into()
+        }
+        (Literal::Uint(it, _), Scalar::Int(_) | Scalar::Uint(_)) => {
+            let it = if negated { (it as i128).wrapping_neg() as u128 } else { it };
+            it.to_le_bytes()[..size].into()
+        }
+        (Literal::Float(it, _), Scalar::Float(_)) => {
+            let it = if negated { -it.into_f64() } else { it.into_f64() };
+            match size {
+                4 => (it as f32).to_le_bytes().into(),
+                _ => it.to_le_bytes().into(),
+            }
+        }
+        (Literal::Bool(it), Scalar::Bool) if !negated => Box::new([it as u8]),
+        (Literal::Char(it), Scalar::Char) if !negated => (it as u32).to_le_bytes().into(),
+        _ => return Err(unsupported().into()),
+    };
+    Ok(consteval::intern_const_scalar(ConstScalar::Bytes(bytes, MemoryMap::Empty), ty.clone()))
+}
@@ -41,6 +41,7 @@ fn mod_item_path(
             PrefixKind::ByCrate,
             prefer_no_std,
             prefer_prelude,
+            false,
         ),
     }
 }
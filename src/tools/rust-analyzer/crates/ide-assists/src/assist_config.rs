@@ -16,4 +16,24 @@ pub struct AssistConfig {
     pub prefer_no_std: bool,
     pub prefer_prelude: bool,
     pub assist_emit_must_use: bool,
+    pub error_handling_style: ErrorHandlingStyle,
+    pub bounds_style: BoundsStyle,
+}
+
+/// The style `Box<dyn Error>` returns get converted to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorHandlingStyle {
+    /// Return `anyhow::Result`.
+    Anyhow,
+    /// Return a dedicated error enum deriving `thiserror::Error`.
+    Thiserror,
+}
+
+/// Where the bounds of generic parameters are placed when assists rewrite them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundsStyle {
+    /// In the `where` clause, like `fn f<T>() where T: Clone`.
+    WhereClause,
+    /// In the generic parameter list, like `fn f<T: Clone>()`.
+    Inline,
 }
@@ -0,0 +1,205 @@
+use hir::{HirDisplay, ModuleDef};
+use ide_db::helpers::mod_path_to_ast;
+use itertools::Itertools;
+use syntax::ast::{self, edit_in_place::GenericParamsOwnerEdit, make, AstNode, HasName};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: add_missing_bounds
+//
+// Adds the trait bounds a type parameter needs to satisfy for the function body to type check.
+//
+// ```
+// # //- minicore: add
+// fn sum<$0T>(a: T, b: T) -> T {
+//     a + b
+// }
+// ```
+// ->
+// ```
+// fn sum<T>(a: T, b: T) -> T where T: core::ops::Add<Output = T> {
+//     a + b
+// }
+// ```
+pub(crate) fn add_missing_bounds(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let type_param = ctx.find_node_at_offset::<ast::TypeParam>()?;
+    let name = type_param.name()?;
+    let fn_ = type_param.syntax().ancestors().nth(2).and_then(ast::Fn::cast)?;
+    let param = ctx.sema.to_def(&type_param)?;
+    let func = ctx.sema.to_def(&fn_)?;
+    let module = ctx.sema.scope(fn_.syntax())?.module();
+
+    let bounds = func
+        .missing_bounds(ctx.db())
+        .into_iter()
+        .filter(|bound| bound.trait_ref.self_ty().as_type_param(ctx.db()) == Some(param))
+        .map(|bound| {
+            let trait_ = bound.trait_ref.trait_();
+            let path = mod_path_to_ast(&module.find_use_path(
+                ctx.db(),
+                ModuleDef::Trait(trait_),
+                ctx.config.prefer_no_std,
+                ctx.config.prefer_prelude,
+            )?);
+            let type_args = bound.trait_ref.explicit_type_arguments(ctx.db());
+            let type_args = type_args
+                .iter()
+                .map(|ty| ty.display_source_code(ctx.db(), module.into(), true).ok())
+                .collect::<Option<Vec<_>>>()?;
+            let assoc_types = bound
+                .assoc_types
+                .iter()
+                .map(|(alias, ty)| {
+                    let ty = ty.display_source_code(ctx.db(), module.into(), true).ok()?;
+                    Some(format!("{} = {ty}", alias.name(ctx.db()).display(ctx.db())))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            let args = type_args.into_iter().chain(assoc_types).join(", ");
+            let bound = if args.is_empty() { path.to_string() } else { format!("{path}<{args}>") };
+            Some(make::type_bound(make::ty(&bound)))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if bounds.is_empty() {
+        return None;
+    }
+
+    acc.add(
+        AssistId("add_missing_bounds", AssistKind::QuickFix),
+        format!("Add missing bounds to `{name}`"),
+        type_param.syntax().text_range(),
+        |edit| {
+            let fn_ = edit.make_mut(fn_);
+            let predicate = make::where_pred(make::ext::ident_path(&name.text()), bounds);
+            fn_.get_or_create_where_clause().add_predicate(predicate.clone_for_update());
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn add_bound_with_assoc_type() {
+        check_assist(
+            add_missing_bounds,
+            r#"
+//- minicore: add
+fn sum<$0T>(a: T, b: T) -> T {
+    a + b
+}
+"#,
+            r#"
+fn sum<T>(a: T, b: T) -> T where T: core::ops::Add<Output = T> {
+    a + b
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_bound_without_assoc_type() {
+        check_assist(
+            add_missing_bounds,
+            r#"
+//- minicore: add
+fn sum<$0T>(a: T, b: T) {
+    let _ = a + b;
+}
+"#,
+            r#"
+fn sum<T>(a: T, b: T) where T: core::ops::Add {
+    let _ = a + b;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_bounds_with_non_default_args() {
+        check_assist(
+            add_missing_bounds,
+            r#"
+//- minicore: add
+fn add_one<$0T>(a: T) -> T {
+    a + 1i32
+}
+"#,
+            r#"
+fn add_one<T>(a: T) -> T where T: core::ops::Add<i32, Output = T> {
+    a + 1i32
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_display_bound_to_existing_where_clause() {
+        check_assist(
+            add_missing_bounds,
+            r#"
+//- minicore: fmt, clone
+fn show<T, $0U>(t: T, u: U) -> T
+where
+    T: Clone,
+{
+    core::format_args!("{}", u);
+    t.clone()
+}
+"#,
+            r#"
+fn show<T, U>(t: T, u: U) -> T
+where
+    T: Clone, U: core::fmt::Display
+{
+    core::format_args!("{}", u);
+    t.clone()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_multiple_bounds() {
+        check_assist(
+            add_missing_bounds,
+            r#"
+//- minicore: add, fmt
+fn sum<$0T>(a: T, b: T) -> T {
+    core::format_args!("{}", a);
+    a + b
+}
+"#,
+            r#"
+fn sum<T>(a: T, b: T) -> T where T: core::fmt::Display + core::ops::Add<Output = T> {
+    core::format_args!("{}", a);
+    a + b
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_bounds_are_satisfied() {
+        check_assist_not_applicable(
+            add_missing_bounds,
+            r#"
+//- minicore: add
+fn sum<$0T: core::ops::Add<Output = T>>(a: T, b: T) -> T {
+    a + b
+}
+"#,
+        );
+        check_assist_not_applicable(
+            add_missing_bounds,
+            r#"
+//- minicore: add
+fn sum<$0T, U>(a: U, b: U) -> U where U: core::ops::Add<Output = U> {
+    a + b
+}
+"#,
+        );
+    }
+}
@@ -1,12 +1,12 @@
 use std::iter::{self, Peekable};
 
 use either::Either;
-use hir::{Adt, Crate, HasAttrs, HasSource, ModuleDef, Semantics};
+use hir::{Adt, Crate, HasAttrs, ModuleDef, Semantics};
 use ide_db::RootDatabase;
-use ide_db::{famous_defs::FamousDefs, helpers::mod_path_to_ast};
+use ide_db::{famous_defs::FamousDefs, helpers::mod_path_to_ast, patterns};
 use itertools::Itertools;
 use syntax::ast::edit_in_place::Removable;
-use syntax::ast::{self, make, AstNode, HasName, MatchArmList, MatchExpr, Pat};
+use syntax::ast::{self, make, AstNode, MatchArmList, MatchExpr, Pat};
 
 use crate::{utils, AssistContext, AssistId, AssistKind, Assists};
 
@@ -72,6 +72,7 @@ pub(crate) fn add_missing_match_arms(acc: &mut Assists, ctx: &AssistContext<'_>)
         .collect();
 
     let module = ctx.sema.scope(expr.syntax())?.module();
+    let scrutinee_ty = ctx.sema.type_of_expr(&expr)?.adjusted();
     let (mut missing_pats, is_non_exhaustive, has_hidden_variants): (
         Peekable<Box<dyn Iterator<Item = (ast::Pat, bool)>>>,
         bool,
@@ -155,7 +156,8 @@ pub(crate) fn add_missing_match_arms(acc: &mut Assists, ctx: &AssistContext<'_>)
                     )
                 });
 
-                (ast::Pat::from(make::tuple_pat(patterns)), is_hidden)
+                let pat = make::tuple_pat(patterns).into();
+                (patterns::ref_pat_for(&scrutinee_ty, pat), is_hidden)
             })
             .filter(|(variant_pat, _)| is_variant_missing(&top_lvl_pats, variant_pat));
         (
@@ -193,7 +195,8 @@ pub(crate) fn add_missing_match_arms(acc: &mut Assists, ctx: &AssistContext<'_>)
                         ctx.config.prefer_prelude,
                     )
                 });
-                (ast::Pat::from(make::slice_pat(patterns)), is_hidden)
+                let pat = make::slice_pat(patterns).into();
+                (patterns::ref_pat_for(&scrutinee_ty, pat), is_hidden)
             })
             .filter(|(variant_pat, _)| is_variant_missing(&top_lvl_pats, variant_pat));
         (
@@ -350,6 +353,9 @@ fn is_variant_missing(existing_pats: &[Pat], var: &Pat) -> bool {
 fn does_pat_match_variant(pat: &Pat, var: &Pat) -> bool {
     match (pat, var) {
         (Pat::WildcardPat(_), _) => true,
+        // Existing arms may rely on default binding modes instead of reference patterns.
+        (_, Pat::RefPat(rvar)) => rvar.pat().map_or(false, |v| does_pat_match_variant(pat, &v)),
+        (Pat::RefPat(rpat), _) => rpat.pat().map_or(false, |p| does_pat_match_variant(&p, var)),
         (Pat::SlicePat(spat), Pat::SlicePat(svar)) => {
             spat.pats().zip(svar.pats()).all(|(p, v)| does_pat_match_variant(&p, &v))
         }
@@ -424,6 +430,7 @@ fn resolve_tuple_of_enum_def(
 ) -> Option<Vec<ExtendedEnum>> {
     sema.type_of_expr(expr)?
         .adjusted()
+        .strip_references()
         .tuple_fields(sema.db)
         .iter()
         .map(|ty| {
@@ -445,12 +452,14 @@ fn resolve_array_of_enum_def(
     sema: &Semantics<'_, RootDatabase>,
     expr: &ast::Expr,
 ) -> Option<(ExtendedEnum, usize)> {
-    sema.type_of_expr(expr)?.adjusted().as_array(sema.db).and_then(|(ty, len)| {
-        ty.autoderef(sema.db).find_map(|ty| match ty.as_adt() {
-            Some(Adt::Enum(e)) => Some((lift_enum(e), len)),
-            _ => ty.is_bool().then_some((ExtendedEnum::Bool, len)),
-        })
-    })
+    sema.type_of_expr(expr)?.adjusted().strip_references().as_array(sema.db).and_then(
+        |(ty, len)| {
+            ty.autoderef(sema.db).find_map(|ty| match ty.as_adt() {
+                Some(Adt::Enum(e)) => Some((lift_enum(e), len)),
+                _ => ty.is_bool().then_some((ExtendedEnum::Bool, len)),
+            })
+        },
+    )
 }
 
 fn build_pat(
@@ -468,25 +477,7 @@ fn build_pat(
                 prefer_no_std,
                 prefer_prelude,
             )?);
-
-            // FIXME: use HIR for this; it doesn't currently expose struct vs. tuple vs. unit variants though
-            Some(match var.source(db)?.value.kind() {
-                ast::StructKind::Tuple(field_list) => {
-                    let pats =
-                        iter::repeat(make::wildcard_pat().into()).take(field_list.fields().count());
-                    make::tuple_struct_pat(path, pats).into()
-                }
-                ast::StructKind::Record(field_list) => {
-                    let pats = field_list.fields().map(|f| {
-                        make::ext::simple_ident_pat(
-                            f.name().expect("Record field must have a name"),
-                        )
-                        .into()
-                    });
-                    make::record_pat(path, pats).into()
-                }
-                ast::StructKind::Unit => make::path_pat(path),
-            })
+            Some(patterns::variant_pat(db, path, var))
         }
         ExtendedVariant::True => Some(ast::Pat::from(make::literal_pat("true"))),
         ExtendedVariant::False => Some(ast::Pat::from(make::literal_pat("false"))),
@@ -1072,6 +1063,62 @@ fn main() {
         );
     }
 
+    #[test]
+    fn add_missing_match_arms_ref_to_tuple_of_enum() {
+        check_assist(
+            add_missing_match_arms,
+            r#"
+enum A { One, Two }
+enum B { One(u8), Two { x: u8 } }
+
+fn f(pair: &(A, B)) {
+    match pair$0 {
+        (A::Two, _) => {}
+    }
+}
+"#,
+            r#"
+enum A { One, Two }
+enum B { One(u8), Two { x: u8 } }
+
+fn f(pair: &(A, B)) {
+    match pair {
+        (A::Two, _) => {}
+        &(A::One, B::One(${0:_})) => todo!(),
+        &(A::One, B::Two { x }) => todo!(),
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_missing_match_arms_ref_to_array_of_enum() {
+        check_assist(
+            add_missing_match_arms,
+            r#"
+enum A { One, Two }
+
+fn f(pair: &mut &[A; 2]) {
+    match pair$0 {
+        &mut &[A::One, _] => {}
+    }
+}
+"#,
+            r#"
+enum A { One, Two }
+
+fn f(pair: &mut &[A; 2]) {
+    match pair {
+        &mut &[A::One, _] => {}
+        $0&mut &[A::Two, A::One] => todo!(),
+        &mut &[A::Two, A::Two] => todo!(),
+    }
+}
+"#,
+        );
+    }
+
     #[test]
     fn add_missing_match_arms_tuple_of_enum_partial() {
         check_assist(
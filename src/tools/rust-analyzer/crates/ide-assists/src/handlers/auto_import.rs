@@ -68,6 +68,14 @@
 //
 // In `VS Code` the configuration for this is `rust-analyzer.imports.prefix`.
 //
+// .rustfmt Configuration
+//
+// If the workspace root contains a `rustfmt.toml` or `.rustfmt.toml`, its `imports_granularity`
+// and `group_imports` options are used instead of the settings above, so that inserted imports
+// don't change when the file is formatted. With `group_imports = "StdExternalCrate"`, `crate`,
+// `self` and `super` imports share a single group. This can be turned off with the
+// `imports.rustfmt.enable` setting.
+//
 // image::https://user-images.githubusercontent.com/48062697/113020673-b85be580-917a-11eb-9022-59585f35d4f8.gif[]
 
 // Assist: auto_import
@@ -97,6 +105,7 @@ pub(crate) fn auto_import(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<
             ctx.config.insert_use.prefix_kind,
             ctx.config.prefer_no_std,
             ctx.config.prefer_no_std,
+            ctx.config.insert_use.prefer_reexports,
         )
         .collect();
     if proposed_imports.is_empty() {
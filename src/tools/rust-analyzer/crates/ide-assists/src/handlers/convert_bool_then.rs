@@ -99,28 +99,10 @@ pub(crate) fn convert_if_to_bool_then(acc: &mut Assists, ctx: &AssistContext<'_>
                 e => e,
             };
 
-            let parenthesize = matches!(
-                cond,
-                ast::Expr::BinExpr(_)
-                    | ast::Expr::BlockExpr(_)
-                    | ast::Expr::BreakExpr(_)
-                    | ast::Expr::CastExpr(_)
-                    | ast::Expr::ClosureExpr(_)
-                    | ast::Expr::ContinueExpr(_)
-                    | ast::Expr::ForExpr(_)
-                    | ast::Expr::IfExpr(_)
-                    | ast::Expr::LoopExpr(_)
-                    | ast::Expr::MacroExpr(_)
-                    | ast::Expr::MatchExpr(_)
-                    | ast::Expr::PrefixExpr(_)
-                    | ast::Expr::RangeExpr(_)
-                    | ast::Expr::RefExpr(_)
-                    | ast::Expr::ReturnExpr(_)
-                    | ast::Expr::WhileExpr(_)
-                    | ast::Expr::YieldExpr(_)
-            );
             let cond = if invert_cond { invert_boolean_expression(cond) } else { cond };
-            let cond = if parenthesize { make::expr_paren(cond) } else { cond };
+            // `make::expr_method_call` takes care of precedence, but a receiver like `{ c }` or
+            // `match c {}` would still end the expression when it starts a statement
+            let cond = if cond.is_block_like() { make::expr_paren(cond) } else { cond };
             let arg_list = make::arg_list(Some(make::expr_closure(None, closure_body)));
             let mcall = make::expr_method_call(cond, make::name_ref("then"), arg_list);
             builder.replace(target, mcall.to_string());
@@ -0,0 +1,641 @@
+use hir::{HirDisplay, Module, Semantics};
+use ide_db::{
+    famous_defs::FamousDefs,
+    imports::insert_use::{insert_use, ImportScope},
+    syntax_helpers::node_ext::walk_expr,
+    RootDatabase,
+};
+use itertools::Itertools;
+use stdx::to_camel_case;
+use syntax::{
+    ast::{self, edit::IndentLevel, make, HasArgList, HasModuleItem, HasName, HasVisibility},
+    AstNode, TextRange,
+};
+
+use crate::{assist_config::ErrorHandlingStyle, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: convert_box_dyn_error
+//
+// Converts a function returning `Result<T, Box<dyn Error>>`, or all such functions of a module,
+// to the error handling style configured by `rust-analyzer.assist.errorHandlingStyle`: either
+// returning `anyhow::Result`, or a dedicated error enum deriving `thiserror::Error`.
+//
+// ```
+// # //- minicore: result, error, from, try
+// # mod boxed { pub struct Box<T: ?Sized>(T); }
+// use boxed::Box;
+// use core::error::Error;
+//
+// fn check(ok: bool) -> Result<(), Box<dyn Error>>$0 {
+//     if !ok {
+//         return Err("not ok".into());
+//     }
+//     Ok(())
+// }
+// ```
+// ->
+// ```
+// # mod boxed { pub struct Box<T: ?Sized>(T); }
+// use anyhow::anyhow;
+// use boxed::Box;
+// use core::error::Error;
+//
+// fn check(ok: bool) -> anyhow::Result<()> {
+//     if !ok {
+//         return Err(anyhow!("not ok"));
+//     }
+//     Ok(())
+// }
+// ```
+pub(crate) fn convert_box_dyn_error(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let krate = ctx.sema.to_module_def(ctx.file_id())?.krate();
+    let famous_defs = FamousDefs(&ctx.sema, krate);
+    let error_trait = famous_defs.core_error_Error()?;
+
+    let (fns, enum_name, target, scope_name) = match ctx.find_node_at_offset::<ast::Fn>() {
+        // Only offered on the signature, the body has assists of its own.
+        Some(fn_)
+            if fn_.body().map_or(true, |it| !it.syntax().text_range().contains(ctx.offset())) =>
+        {
+            boxed_error_ok_ty(ctx, &fn_, error_trait)?;
+            let name = fn_.name()?;
+            let enum_name = format!("{}Error", to_camel_case(&name.text()));
+            let target = fn_.syntax().text_range();
+            (vec![fn_], enum_name, target, None)
+        }
+        Some(_) => return None,
+        None => {
+            let module = ctx.find_node_at_offset::<ast::Module>()?;
+            let name = module.name()?;
+            let item_list = module.item_list()?;
+            // Only offered on the module header.
+            if item_list.syntax().text_range().contains(ctx.offset()) {
+                return None;
+            }
+            let fns = item_list
+                .items()
+                .flat_map(|item| match item {
+                    ast::Item::Fn(fn_) => vec![fn_],
+                    ast::Item::Impl(impl_) if impl_.trait_().is_none() => impl_
+                        .assoc_item_list()
+                        .into_iter()
+                        .flat_map(|it| it.assoc_items())
+                        .filter_map(|it| match it {
+                            ast::AssocItem::Fn(fn_) => Some(fn_),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                })
+                .filter(|fn_| boxed_error_ok_ty(ctx, fn_, error_trait).is_some())
+                .collect::<Vec<_>>();
+            if fns.is_empty() {
+                return None;
+            }
+            let enum_name = format!("{}Error", to_camel_case(&name.text()));
+            let target = module.syntax().text_range();
+            (fns, enum_name, target, Some(name.text().to_string()))
+        }
+    };
+    let converted =
+        fns.iter().map(|fn_| ctx.sema.to_def(fn_)).collect::<Option<Vec<hir::Function>>>()?;
+    let module = ctx.sema.scope(fns[0].syntax())?.module();
+
+    let style = ctx.config.error_handling_style;
+    let label = match style {
+        ErrorHandlingStyle::Anyhow => "Convert `Box<dyn Error>` to `anyhow::Result`",
+        ErrorHandlingStyle::Thiserror => "Convert `Box<dyn Error>` to a `thiserror` error enum",
+    };
+    let label = match &scope_name {
+        Some(name) => format!("{label} in `mod {name}`"),
+        None => label.to_owned(),
+    };
+
+    let mut conversion = Conversion {
+        sema: &ctx.sema,
+        module,
+        error_trait,
+        style,
+        enum_name,
+        converted,
+        edits: Vec::new(),
+        variants: Vec::new(),
+        uses_anyhow_macro: false,
+    };
+    for fn_ in &fns {
+        conversion.convert_fn(fn_)?;
+    }
+
+    acc.add(AssistId("convert_box_dyn_error", AssistKind::RefactorRewrite), label, target, |edit| {
+        let Conversion { edits, variants, uses_anyhow_macro, enum_name, .. } = conversion;
+        for (range, text) in edits {
+            edit.replace(range, text);
+        }
+        if style == ErrorHandlingStyle::Thiserror {
+            let first_fn = &fns[0];
+            let anchor = first_fn
+                .syntax()
+                .ancestors()
+                .find(|it| ast::Impl::can_cast(it.kind()))
+                .unwrap_or_else(|| first_fn.syntax().clone());
+            let indent = IndentLevel::from_node(&anchor);
+            let vis = first_fn.visibility().map(|it| format!("{it} ")).unwrap_or_default();
+            let variants = variants
+                .iter()
+                .map(|variant| {
+                    let (attr, field) = match &variant.kind {
+                        VariantKind::From(ty) => ("transparent", format!("#[from] {ty}")),
+                        VariantKind::Message => ("\"{0}\"", "String".to_owned()),
+                    };
+                    format!(
+                        "\n{indent}    #[error({attr})]\n{indent}    {}({field}),",
+                        variant.name
+                    )
+                })
+                .join("");
+            let closing = if variants.is_empty() { String::new() } else { format!("\n{indent}") };
+            let derive = "#[derive(Debug, thiserror::Error)]";
+            let error_enum = format!("{vis}enum {enum_name} {{{variants}{closing}}}");
+            edit.insert(
+                anchor.text_range().start(),
+                format!("{derive}\n{indent}{error_enum}\n\n{indent}"),
+            );
+        }
+        if uses_anyhow_macro {
+            if let Some(scope) = ImportScope::find_insert_use_container(fns[0].syntax(), &ctx.sema)
+            {
+                let scope = match scope {
+                    ImportScope::File(it) => ImportScope::File(edit.make_mut(it)),
+                    ImportScope::Module(it) => ImportScope::Module(edit.make_mut(it)),
+                    ImportScope::Block(it) => ImportScope::Block(edit.make_mut(it)),
+                };
+                insert_use(&scope, make::path_from_text("anyhow::anyhow"), &ctx.config.insert_use);
+            }
+        }
+    })
+}
+
+struct Conversion<'a, 'db> {
+    sema: &'a Semantics<'db, RootDatabase>,
+    module: Module,
+    error_trait: hir::Trait,
+    style: ErrorHandlingStyle,
+    enum_name: String,
+    /// The functions being converted, calls to them already return the new error type.
+    converted: Vec<hir::Function>,
+    edits: Vec<(TextRange, String)>,
+    variants: Vec<Variant>,
+    uses_anyhow_macro: bool,
+}
+
+struct Variant {
+    name: String,
+    kind: VariantKind,
+}
+
+#[derive(PartialEq, Eq)]
+enum VariantKind {
+    /// `#[error(transparent)] Name(#[from] Type)`
+    From(String),
+    /// `#[error("{0}")] Other(String)`
+    Message,
+}
+
+impl Conversion<'_, '_> {
+    fn convert_fn(&mut self, fn_: &ast::Fn) -> Option<()> {
+        let ret_ty = fn_.ret_type()?.ty()?;
+        let ast::Type::PathType(path_ty) = &ret_ty else { return None };
+        let args = path_ty.path()?.segment()?.generic_arg_list()?;
+        let ok_ty = args.generic_args().next()?;
+        let new_ret_ty = match self.style {
+            ErrorHandlingStyle::Anyhow => format!("anyhow::Result<{ok_ty}>"),
+            ErrorHandlingStyle::Thiserror => format!("Result<{ok_ty}, {}>", self.enum_name),
+        };
+        self.edits.push((ret_ty.syntax().text_range(), new_ret_ty));
+
+        let body = ast::Expr::BlockExpr(fn_.body()?);
+        let mut err_calls = Vec::new();
+        let mut try_exprs = Vec::new();
+        walk_expr(&body, &mut |expr| {
+            // `?` and `Err` within closures don't return from the function.
+            let in_closure = expr
+                .syntax()
+                .ancestors()
+                .take_while(|it| it != body.syntax())
+                .any(|it| ast::ClosureExpr::can_cast(it.kind()));
+            if in_closure {
+                return;
+            }
+            match expr {
+                ast::Expr::CallExpr(call) if is_err_call(&call) => err_calls.push(call),
+                ast::Expr::TryExpr(try_expr) => try_exprs.push(try_expr),
+                _ => (),
+            }
+        });
+
+        let mut replaced = Vec::new();
+        for call in err_calls {
+            let Some(arg) = call.arg_list().and_then(|it| it.args().exactly_one().ok()) else {
+                continue;
+            };
+            let Some(source) = error_source(&arg) else { continue };
+            let Some(source_ty) = self.sema.type_of_expr(&source).map(|it| it.original) else {
+                continue;
+            };
+            let new_arg = if is_string(self.sema.db, &source_ty) {
+                match self.style {
+                    ErrorHandlingStyle::Anyhow => {
+                        self.uses_anyhow_macro = true;
+                        format!("anyhow!({source})")
+                    }
+                    ErrorHandlingStyle::Thiserror => {
+                        let variant = self.add_variant("Other", VariantKind::Message);
+                        format!("{}::{variant}({source}.into())", self.enum_name)
+                    }
+                }
+            } else if source_ty.impls_trait(self.sema.db, self.error_trait, &[]) {
+                if self.style == ErrorHandlingStyle::Thiserror {
+                    self.add_from_variant(&source_ty)?;
+                }
+                if source.syntax().text_range() == arg.syntax().text_range() {
+                    continue;
+                }
+                format!("{source}.into()")
+            } else {
+                continue;
+            };
+            replaced.push(arg.syntax().text_range());
+            self.edits.push((arg.syntax().text_range(), new_arg));
+        }
+
+        for try_expr in try_exprs {
+            let Some(question_mark) = try_expr.question_mark_token() else { continue };
+            let question_mark = question_mark.text_range();
+            if replaced.iter().any(|it| it.contains_range(question_mark)) {
+                continue;
+            }
+            let Some(operand) = try_expr.expr() else { continue };
+            if self.calls_converted_fn(&operand) {
+                continue;
+            }
+            let Some(operand_ty) = self.sema.type_of_expr(&operand).map(|it| it.original) else {
+                continue;
+            };
+            let Some(err_ty) = operand_ty.type_arguments().nth(1) else { continue };
+            if is_boxed_error(self.sema.db, &err_ty, self.error_trait) {
+                // `Box<dyn Error>` doesn't implement `Error`, so it has to go through its message.
+                let map_err = match self.style {
+                    ErrorHandlingStyle::Anyhow => {
+                        self.uses_anyhow_macro = true;
+                        ".map_err(|e| anyhow!(\"{e}\"))".to_owned()
+                    }
+                    ErrorHandlingStyle::Thiserror => {
+                        let variant = self.add_variant("Other", VariantKind::Message);
+                        format!(".map_err(|e| {}::{variant}(e.to_string()))", self.enum_name)
+                    }
+                };
+                self.edits.push((TextRange::empty(question_mark.start()), map_err));
+            } else if self.style == ErrorHandlingStyle::Thiserror
+                && err_ty.impls_trait(self.sema.db, self.error_trait, &[])
+            {
+                self.add_from_variant(&err_ty)?;
+            }
+        }
+        Some(())
+    }
+
+    fn calls_converted_fn(&self, expr: &ast::Expr) -> bool {
+        let func = match expr {
+            ast::Expr::MethodCallExpr(call) => self.sema.resolve_method_call(call),
+            ast::Expr::CallExpr(call) => match call.expr() {
+                Some(ast::Expr::PathExpr(path_expr)) => {
+                    match path_expr.path().and_then(|it| self.sema.resolve_path(&it)) {
+                        Some(hir::PathResolution::Def(hir::ModuleDef::Function(it))) => Some(it),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        func.map_or(false, |it| self.converted.contains(&it))
+    }
+
+    fn add_from_variant(&mut self, ty: &hir::Type) -> Option<()> {
+        let db = self.sema.db;
+        let ty_text = ty.display_source_code(db, self.module.into(), true).ok()?;
+        let name = match ty.as_adt() {
+            Some(adt) => {
+                let name = adt.name(db).display(db).to_string();
+                match name.strip_suffix("Error") {
+                    Some("") => adt
+                        .module(db)
+                        .name(db)
+                        .map_or(name.clone(), |it| to_camel_case(&it.display(db).to_string())),
+                    Some(stripped) => stripped.to_owned(),
+                    None => name,
+                }
+            }
+            None => to_camel_case(&ty_text.replace(|c: char| !c.is_alphanumeric(), "_")),
+        };
+        self.add_variant(&name, VariantKind::From(ty_text));
+        Some(())
+    }
+
+    /// Adds a variant unless there already is an equal one, returning its name.
+    fn add_variant(&mut self, name: &str, kind: VariantKind) -> String {
+        if let Some(existing) = self.variants.iter().find(|it| it.kind == kind) {
+            return existing.name.clone();
+        }
+        let mut unique_name = name.to_owned();
+        let mut idx = 1;
+        while self.variants.iter().any(|it| it.name == unique_name) {
+            idx += 1;
+            unique_name = format!("{name}{idx}");
+        }
+        self.variants.push(Variant { name: unique_name.clone(), kind });
+        unique_name
+    }
+}
+
+/// Checks that `fn_` returns `Result<T, Box<dyn Error>>`, returning `T`.
+fn boxed_error_ok_ty(
+    ctx: &AssistContext<'_>,
+    fn_: &ast::Fn,
+    error_trait: hir::Trait,
+) -> Option<ast::GenericArg> {
+    let ast::Type::PathType(path_ty) = fn_.ret_type()?.ty()? else { return None };
+    let segment = path_ty.path()?.segment()?;
+    if segment.name_ref()?.text() != "Result" {
+        return None;
+    }
+    let (ok_ty, err_ty) = segment.generic_arg_list()?.generic_args().collect_tuple()?;
+    let ast::GenericArg::TypeArg(err_ty) = err_ty else { return None };
+    let err_ty = ctx.sema.resolve_type(&err_ty.ty()?)?;
+    is_boxed_error(ctx.db(), &err_ty, error_trait).then_some(ok_ty)
+}
+
+fn is_boxed_error(db: &RootDatabase, ty: &hir::Type, error_trait: hir::Trait) -> bool {
+    let Some(hir::Adt::Struct(strukt)) = ty.as_adt() else { return false };
+    strukt.name(db).display(db).to_string() == "Box"
+        && ty.type_arguments().next().and_then(|it| it.as_dyn_trait()) == Some(error_trait)
+}
+
+fn is_string(db: &RootDatabase, ty: &hir::Type) -> bool {
+    if let Some(inner) = ty.remove_ref() {
+        if ty.is_reference() {
+            return inner.as_builtin().map_or(false, |it| it.is_str());
+        }
+    }
+    matches!(ty.as_adt(), Some(adt) if adt.name(db).display(db).to_string() == "String")
+}
+
+fn is_err_call(call: &ast::CallExpr) -> bool {
+    let Some(ast::Expr::PathExpr(path_expr)) = call.expr() else { return false };
+    path_expr.path().map_or(false, |path| path.to_string() == "Err")
+}
+
+/// Finds the error in the usual ways to box one: `e.into()`, `Box::new(e)` and `From::from(e)`.
+fn error_source(arg: &ast::Expr) -> Option<ast::Expr> {
+    match arg {
+        ast::Expr::MethodCallExpr(call)
+            if call.name_ref()?.text() == "into" && call.arg_list()?.args().next().is_none() =>
+        {
+            call.receiver()
+        }
+        ast::Expr::CallExpr(call) => {
+            let ast::Expr::PathExpr(path_expr) = call.expr()? else { return None };
+            let path = path_expr.path()?;
+            let fn_name = path.segment()?.name_ref()?;
+            let qualifier = path.qualifier()?.segment()?.name_ref()?;
+            match (qualifier.text().as_str(), fn_name.text().as_str()) {
+                ("Box", "new") | ("From", "from") | ("Box", "from") => {
+                    call.arg_list()?.args().exactly_one().ok()
+                }
+                _ => None,
+            }
+        }
+        _ => Some(arg.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_assist, check_assist_not_applicable, check_assist_with_config, TEST_CONFIG},
+        AssistConfig,
+    };
+
+    use super::*;
+
+    const THISERROR_CONFIG: AssistConfig =
+        AssistConfig { error_handling_style: ErrorHandlingStyle::Thiserror, ..TEST_CONFIG };
+
+    #[test]
+    fn convert_to_anyhow() {
+        check_assist(
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error, from, fmt, try
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+use core::error::Error;
+
+#[derive(Debug)]
+struct ParseError;
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { Ok(()) }
+}
+impl Error for ParseError {}
+
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn legacy() -> Result<(), Box<dyn Error>> { Ok(()) }
+
+fn run$0(s: &str) -> Result<u32, Box<dyn Error>> {
+    legacy()?;
+    if s.is_empty() {
+        return Err(Box::new(ParseError));
+    }
+    let f = |x: Result<u32, ParseError>| x?;
+    Ok(parse(s)?)
+}
+"#,
+            r#"
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use anyhow::anyhow;
+use boxed::Box;
+use core::error::Error;
+
+#[derive(Debug)]
+struct ParseError;
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { Ok(()) }
+}
+impl Error for ParseError {}
+
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn legacy() -> Result<(), Box<dyn Error>> { Ok(()) }
+
+fn run(s: &str) -> anyhow::Result<u32> {
+    legacy().map_err(|e| anyhow!("{e}"))?;
+    if s.is_empty() {
+        return Err(ParseError.into());
+    }
+    let f = |x: Result<u32, ParseError>| x?;
+    Ok(parse(s)?)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_to_thiserror() {
+        check_assist_with_config(
+            THISERROR_CONFIG,
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error, from, fmt, try
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+use core::error::Error;
+
+#[derive(Debug)]
+struct ParseError;
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { Ok(()) }
+}
+impl Error for ParseError {}
+
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn legacy() -> Result<(), Box<dyn Error>> { Ok(()) }
+
+pub fn read_config$0(s: &str) -> Result<u32, Box<dyn Error>> {
+    legacy()?;
+    if s.is_empty() {
+        return Err("empty config".into());
+    }
+    Ok(parse(s)?)
+}
+"#,
+            r#"
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+use core::error::Error;
+
+#[derive(Debug)]
+struct ParseError;
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { Ok(()) }
+}
+impl Error for ParseError {}
+
+fn parse(s: &str) -> Result<u32, ParseError> { Ok(0) }
+fn legacy() -> Result<(), Box<dyn Error>> { Ok(()) }
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReadConfigError {
+    #[error("{0}")]
+    Other(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+}
+
+pub fn read_config(s: &str) -> Result<u32, ReadConfigError> {
+    legacy().map_err(|e| ReadConfigError::Other(e.to_string()))?;
+    if s.is_empty() {
+        return Err(ReadConfigError::Other("empty config".into()));
+    }
+    Ok(parse(s)?)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn convert_module_to_thiserror() {
+        check_assist_with_config(
+            THISERROR_CONFIG,
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error, from, fmt, try
+mod boxed { pub struct Box<T: ?Sized>(T); }
+mod con$0fig {
+    use crate::boxed::Box;
+    use core::error::Error;
+
+    fn check(s: &str) -> Result<(), Box<dyn Error>> {
+        Err(From::from("invalid"))
+    }
+
+    pub fn load(s: &str) -> Result<u32, Box<dyn Error>> {
+        check(s)?;
+        Ok(1)
+    }
+
+    fn unrelated() -> u32 { 0 }
+}
+"#,
+            r#"
+mod boxed { pub struct Box<T: ?Sized>(T); }
+mod config {
+    use crate::boxed::Box;
+    use core::error::Error;
+
+    #[derive(Debug, thiserror::Error)]
+    enum ConfigError {
+        #[error("{0}")]
+        Other(String),
+    }
+
+    fn check(s: &str) -> Result<(), ConfigError> {
+        Err(ConfigError::Other("invalid".into()))
+    }
+
+    pub fn load(s: &str) -> Result<u32, ConfigError> {
+        check(s)?;
+        Ok(1)
+    }
+
+    fn unrelated() -> u32 { 0 }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_other_errors() {
+        check_assist_not_applicable(
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+trait Error {}
+
+fn run$0() -> Result<(), Box<dyn Error>> { Ok(()) }
+"#,
+        );
+        check_assist_not_applicable(
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error
+fn run$0() -> Result<(), ()> { Ok(()) }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_body() {
+        check_assist_not_applicable(
+            convert_box_dyn_error,
+            r#"
+//- minicore: result, error
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+use core::error::Error;
+
+fn run() -> Result<(), Box<dyn Error>> { Ok($0()) }
+"#,
+        );
+    }
+}
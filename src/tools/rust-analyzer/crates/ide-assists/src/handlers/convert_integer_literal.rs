@@ -14,11 +14,15 @@
 // const _: i32 = 0b1010;
 // ```
 pub(crate) fn convert_integer_literal(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
-    let literal = ctx.find_node_at_offset::<ast::Literal>()?;
-    let literal = match literal.kind() {
+    let expr = ctx.find_node_at_offset::<ast::Literal>()?;
+    let literal = match expr.kind() {
         ast::LiteralKind::IntNumber(it) => it,
         _ => return None,
     };
+    // Literals like `10f32` are floats, which can only be written in decimal.
+    if ctx.sema.type_of_expr(&ast::Expr::Literal(expr)).is_some_and(|it| it.original.is_float()) {
+        return None;
+    }
     let radix = literal.radix();
     let value = literal.value().ok()?;
     let suffix = literal.suffix();
@@ -259,6 +263,12 @@ fn convert_integer_with_suffix() {
         );
     }
 
+    #[test]
+    fn float_suffix_not_applicable() {
+        check_assist_not_applicable(convert_integer_literal, "const _: f32 = 10f32$0;");
+        check_assist_not_applicable(convert_integer_literal, "const _: f64 = 1_000_f64$0;");
+    }
+
     #[test]
     fn convert_overflowing_literal() {
         let before = "const _: i32 =
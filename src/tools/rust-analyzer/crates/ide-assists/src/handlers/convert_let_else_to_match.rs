@@ -117,6 +117,7 @@ fn binders_in_pat(
             Some(())
         }
         ParenPat(p) => p.pat().and_then(|p| binders_in_pat(acc, &p, sem)),
+        GuardPat(p) => p.pat().and_then(|p| binders_in_pat(acc, &p, sem)),
         RangePat(p) => {
             if let Some(st) = p.start() {
                 binders_in_pat(acc, &st, sem)?
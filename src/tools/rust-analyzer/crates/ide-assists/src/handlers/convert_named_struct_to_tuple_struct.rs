@@ -1,8 +1,7 @@
 use either::Either;
 use ide_db::{defs::Definition, search::FileReference};
-use itertools::Itertools;
 use syntax::{
-    ast::{self, AstNode, HasGenericParams, HasVisibility},
+    ast::{self, AstNode, HasGenericParams, HasName, HasVisibility},
     match_ast, SyntaxKind,
 };
 
@@ -73,7 +72,11 @@ pub(crate) fn convert_named_struct_to_tuple_struct(
         strukt.syntax().text_range(),
         |edit| {
             edit_field_references(ctx, edit, record_fields.fields());
-            edit_struct_references(ctx, edit, strukt_def);
+            let field_names: Vec<_> = record_fields
+                .fields()
+                .map(|f| f.name().map(|it| it.text().to_string()).unwrap_or_default())
+                .collect();
+            edit_struct_references(ctx, edit, strukt_def, &field_names);
             edit_struct_def(ctx, edit, &strukt, record_fields);
         },
     )
@@ -135,6 +138,7 @@ fn edit_struct_references(
     ctx: &AssistContext<'_>,
     edit: &mut SourceChangeBuilder,
     strukt: Either<hir::Struct, hir::Variant>,
+    field_names: &[String],
 ) {
     let strukt_def = match strukt {
         Either::Left(s) => Definition::Adt(hir::Adt::Struct(s)),
@@ -145,7 +149,7 @@ fn edit_struct_references(
     for (file_id, refs) in usages {
         edit.edit_file(file_id);
         for r in refs {
-            process_struct_name_reference(ctx, r, edit);
+            process_struct_name_reference(ctx, r, edit, field_names);
         }
     }
 }
@@ -154,6 +158,7 @@ fn process_struct_name_reference(
     ctx: &AssistContext<'_>,
     r: FileReference,
     edit: &mut SourceChangeBuilder,
+    field_names: &[String],
 ) -> Option<()> {
     // First check if it's the last semgnet of a path that directly belongs to a record
     // expression/pattern.
@@ -176,16 +181,28 @@ fn process_struct_name_reference(
                 // When we failed to get the original range for the whole struct expression node,
                 // we can't provide any reasonable edit. Leave it untouched.
                 let file_range = ctx.sema.original_range_opt(record_struct_pat.syntax())?;
+                let field_list = record_struct_pat.record_pat_field_list()?;
+                let mut pats = vec![None; field_names.len()];
+                for field in field_list.fields() {
+                    let name = field.field_name()?.to_string();
+                    let idx = field_names.iter().position(|it| *it == name)?;
+                    pats[idx] = Some(field.pat()?);
+                }
+                // ignored fields in between are matched with `_`, trailing ones with `..`
+                let mut has_rest = false;
+                if field_list.rest_pat().is_some() {
+                    while pats.last().map_or(false, Option::is_none) {
+                        pats.pop();
+                        has_rest = true;
+                    }
+                }
+                let pats = pats
+                    .into_iter()
+                    .map(|it| it.unwrap_or_else(|| ast::make::wildcard_pat().into()))
+                    .chain(has_rest.then(|| ast::make::rest_pat().into()));
                 edit.replace(
                     file_range.range,
-                    ast::make::tuple_struct_pat(
-                        record_struct_pat.path()?,
-                        record_struct_pat
-                            .record_pat_field_list()?
-                            .fields()
-                            .filter_map(|pat| pat.pat())
-                    )
-                    .to_string()
+                    ast::make::tuple_struct_pat(record_struct_pat.path()?, pats).to_string()
                 );
             },
             ast::RecordExpr(record_expr) => {
@@ -193,10 +210,25 @@ fn process_struct_name_reference(
                 // we can't provide any reasonable edit. Leave it untouched.
                 let file_range = ctx.sema.original_range_opt(record_expr.syntax())?;
                 let path = record_expr.path()?;
-                let args = record_expr
-                    .record_expr_field_list()?
-                    .fields()
-                    .filter_map(|f| f.expr())
+                let field_list = record_expr.record_expr_field_list()?;
+                let mut args = vec![None; field_names.len()];
+                for field in field_list.fields() {
+                    let name = field.field_name()?.to_string();
+                    let idx = field_names.iter().position(|it| *it == name)?;
+                    args[idx] = Some(field.expr()?.to_string());
+                }
+                // fields taken from the base of a struct update can only be accessed by index,
+                // which is only done for a plain path so that it isn't evaluated repeatedly
+                let spread = field_list.spread();
+                let args = args
+                    .into_iter()
+                    .enumerate()
+                    .map(|(idx, arg)| match (arg, &spread) {
+                        (Some(arg), _) => Some(arg),
+                        (None, Some(spread @ ast::Expr::PathExpr(_))) => Some(format!("{spread}.{idx}")),
+                        (None, _) => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?
                     .join(", ");
 
                 edit.replace(file_range.range, format!("{path}({args})"));
@@ -287,6 +319,31 @@ fn into_inner(self) -> Inner {
         );
     }
 
+    #[test]
+    fn convert_reordered_and_partial_fields() {
+        check_assist(
+            convert_named_struct_to_tuple_struct,
+            r#"
+struct A$0 { a: u32, b: u32, c: u32 }
+
+fn f(base: A) -> A {
+    let A { c, a, .. } = base;
+    let A { b, .. } = base;
+    A { c: b, ..base }
+}
+"#,
+            r#"
+struct A(u32, u32, u32);
+
+fn f(base: A) -> A {
+    let A(a, _, c) = base;
+    let A(_, b, ..) = base;
+    A(base.0, base.1, b)
+}
+"#,
+        );
+    }
+
     #[test]
     fn convert_struct_referenced_via_self_kw() {
         check_assist(
@@ -207,6 +207,7 @@ fn augment_references_with_imports(
                         ctx.config.insert_use.prefix_kind,
                         ctx.config.prefer_no_std,
                         ctx.config.prefer_prelude,
+                        ctx.config.insert_use.prefer_reexports,
                     )
                     .map(|mod_path| {
                         make::path_concat(
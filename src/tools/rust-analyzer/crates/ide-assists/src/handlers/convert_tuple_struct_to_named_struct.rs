@@ -1,15 +1,27 @@
+use std::cmp::Reverse;
+
 use either::Either;
-use ide_db::defs::{Definition, NameRefClass};
+use ide_db::{
+    defs::{Definition, NameRefClass},
+    search::UsageSearchResult,
+    FxHashSet,
+};
+use itertools::Itertools;
 use syntax::{
-    ast::{self, AstNode, HasGenericParams, HasVisibility},
+    ast::{self, AstNode, HasArgList, HasGenericParams, HasName, HasVisibility},
     match_ast, SyntaxNode,
 };
 
-use crate::{assist_context::SourceChangeBuilder, AssistContext, AssistId, AssistKind, Assists};
+use crate::{
+    assist_context::SourceChangeBuilder, utils::suggest_name, AssistContext, AssistId, AssistKind,
+    Assists,
+};
 
 // Assist: convert_tuple_struct_to_named_struct
 //
 // Converts tuple struct to struct with named fields, and analogously for tuple enum variants.
+// Field names are derived from the values the struct is constructed with and the bindings of its
+// patterns, falling back to `field1`, `field2`, ...
 //
 // ```
 // struct Point$0(f32, f32);
@@ -30,19 +42,19 @@
 // ```
 // ->
 // ```
-// struct Point { field1: f32, field2: f32 }
+// struct Point { x: f32, y: f32 }
 //
 // impl Point {
 //     pub fn new(x: f32, y: f32) -> Self {
-//         Point { field1: x, field2: y }
+//         Point { x, y }
 //     }
 //
 //     pub fn x(&self) -> f32 {
-//         self.field1
+//         self.x
 //     }
 //
 //     pub fn y(&self) -> f32 {
-//         self.field2
+//         self.y
 //     }
 // }
 // ```
@@ -68,9 +80,14 @@ pub(crate) fn convert_tuple_struct_to_named_struct(
         "Convert to named struct",
         target,
         |edit| {
-            let names = generate_names(tuple_fields.fields());
+            let strukt_def = match strukt_def {
+                Either::Left(s) => Definition::Adt(hir::Adt::Struct(s)),
+                Either::Right(v) => Definition::Variant(v),
+            };
+            let usages = strukt_def.usages(&ctx.sema).include_self_refs().all();
+            let names = generate_names(ctx, strukt_def, tuple_fields.fields(), &usages);
             edit_field_references(ctx, edit, tuple_fields.fields(), &names);
-            edit_struct_references(ctx, edit, strukt_def, &names);
+            edit_struct_references(ctx, edit, strukt_def, usages, &names);
             edit_struct_def(ctx, edit, &strukt, tuple_fields, names);
         },
     )
@@ -121,47 +138,40 @@ fn edit_struct_def(
 fn edit_struct_references(
     ctx: &AssistContext<'_>,
     edit: &mut SourceChangeBuilder,
-    strukt: Either<hir::Struct, hir::Variant>,
+    strukt_def: Definition,
+    usages: UsageSearchResult,
     names: &[ast::Name],
 ) {
-    let strukt_def = match strukt {
-        Either::Left(s) => Definition::Adt(hir::Adt::Struct(s)),
-        Either::Right(v) => Definition::Variant(v),
-    };
-    let usages = strukt_def.usages(&ctx.sema).include_self_refs().all();
-
     let edit_node = |edit: &mut SourceChangeBuilder, node: SyntaxNode| -> Option<()> {
         match_ast! {
             match node {
                 ast::TupleStructPat(tuple_struct_pat) => {
+                    let (fields, has_rest) = tuple_pat_fields(&tuple_struct_pat, names.len());
+                    let fields = fields.into_iter().filter_map(|(idx, pat)| {
+                        let name = names.get(idx)?;
+                        let name_ref = ast::make::name_ref(&name.to_string());
+                        Some(match &pat {
+                            ast::Pat::IdentPat(it) if is_shorthand_pat(it, name) => {
+                                ast::make::record_pat_field_shorthand(name_ref)
+                            }
+                            _ => ast::make::record_pat_field(name_ref, pat),
+                        })
+                    });
                     edit.replace(
                         tuple_struct_pat.syntax().text_range(),
                         ast::make::record_pat_with_fields(
                             tuple_struct_pat.path()?,
-                            ast::make::record_pat_field_list(tuple_struct_pat.fields().zip(names).map(
-                                |(pat, name)| {
-                                    ast::make::record_pat_field(
-                                        ast::make::name_ref(&name.to_string()),
-                                        pat,
-                                    )
-                                },
-                            )),
+                            ast::make::record_pat_field_list(
+                                fields,
+                                has_rest.then(ast::make::rest_pat),
+                            ),
                         )
                         .to_string(),
                     );
                 },
                 // for tuple struct creations like Foo(42)
                 ast::CallExpr(call_expr) => {
-                    let path = call_expr.syntax().descendants().find_map(ast::PathExpr::cast).and_then(|expr| expr.path())?;
-
-                    // this also includes method calls like Foo::new(42), we should skip them
-                    if let Some(name_ref) = path.segment().and_then(|s| s.name_ref()) {
-                        match NameRefClass::classify(&ctx.sema, &name_ref) {
-                            Some(NameRefClass::Definition(Definition::SelfType(_))) => {},
-                            Some(NameRefClass::Definition(def)) if def == strukt_def => {},
-                            _ => return None,
-                        };
-                    }
+                    let path = constructor_path(ctx, strukt_def, &call_expr)?;
 
                     let arg_list = call_expr.syntax().descendants().find_map(ast::ArgList::cast)?;
 
@@ -171,9 +181,12 @@ fn edit_struct_references(
                             path,
                             ast::make::record_expr_field_list(arg_list.args().zip(names).map(
                                 |(expr, name)| {
+                                    let is_shorthand = matches!(&expr, ast::Expr::PathExpr(it)
+                                        if it.path().and_then(|it| it.as_single_name_ref())
+                                            .map_or(false, |it| it.text() == name.text()));
                                     ast::make::record_expr_field(
                                         ast::make::name_ref(&name.to_string()),
-                                        Some(expr),
+                                        (!is_shorthand).then_some(expr),
                                     )
                                 },
                             )),
@@ -223,12 +236,116 @@ fn edit_field_references(
     }
 }
 
-fn generate_names(fields: impl Iterator<Item = ast::TupleField>) -> Vec<ast::Name> {
-    fields
+/// The subpatterns of `pat` with the index of the field they match, and whether it has a `..`
+/// rest pattern.
+fn tuple_pat_fields(
+    pat: &ast::TupleStructPat,
+    field_count: usize,
+) -> (Vec<(usize, ast::Pat)>, bool) {
+    let pats: Vec<_> = pat.fields().collect();
+    let rest_idx = pats.iter().position(|it| matches!(it, ast::Pat::RestPat(_)));
+    let fields = pats
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, pat)| {
+            let field_idx = match rest_idx {
+                Some(rest_idx) if idx == rest_idx => return None,
+                Some(rest_idx) if idx > rest_idx => (field_count + idx).checked_sub(pats.len())?,
+                _ => idx,
+            };
+            Some((field_idx, pat.clone()))
+        })
+        .collect();
+    (fields, rest_idx.is_some())
+}
+
+/// Whether `pat` can be written as the field shorthand `Foo { name }`.
+fn is_shorthand_pat(pat: &ast::IdentPat, name: &ast::Name) -> bool {
+    pat.ref_token().is_none()
+        && pat.mut_token().is_none()
+        && pat.pat().is_none()
+        && pat.name().map_or(false, |it| it.text() == name.text())
+}
+
+/// The path of `call_expr` if it constructs the struct, as opposed to e.g. calling `Foo::new`.
+fn constructor_path(
+    ctx: &AssistContext<'_>,
+    strukt_def: Definition,
+    call_expr: &ast::CallExpr,
+) -> Option<ast::Path> {
+    let path = call_expr
+        .syntax()
+        .descendants()
+        .find_map(ast::PathExpr::cast)
+        .and_then(|expr| expr.path())?;
+    if let Some(name_ref) = path.segment().and_then(|s| s.name_ref()) {
+        match NameRefClass::classify(&ctx.sema, &name_ref) {
+            Some(NameRefClass::Definition(Definition::SelfType(_))) => {}
+            Some(NameRefClass::Definition(def)) if def == strukt_def => {}
+            _ => return None,
+        };
+    }
+    Some(path)
+}
+
+/// Names each field after the values it's most often constructed with or bound to, e.g.
+/// `Point(x, y)`, or `fieldN` if none of them is named.
+fn generate_names(
+    ctx: &AssistContext<'_>,
+    strukt_def: Definition,
+    fields: impl Iterator<Item = ast::TupleField>,
+    usages: &UsageSearchResult,
+) -> Vec<ast::Name> {
+    let mut candidates: Vec<Vec<String>> = fields.map(|_| Vec::new()).collect();
+    for (_, refs) in usages.iter() {
+        for r in refs {
+            let Some(node) = r.name.syntax().ancestors().find(|it| {
+                ast::TupleStructPat::can_cast(it.kind()) || ast::CallExpr::can_cast(it.kind())
+            }) else {
+                continue;
+            };
+            let names: Vec<_> = match_ast! {
+                match node {
+                    ast::TupleStructPat(pat) => {
+                        let mut names = vec![None; candidates.len()];
+                        for (idx, pat) in tuple_pat_fields(&pat, candidates.len()).0 {
+                            if let Some(name) = names.get_mut(idx) {
+                                *name = suggest_name::for_field(&ctx.sema, Either::Right(&pat));
+                            }
+                        }
+                        names
+                    },
+                    ast::CallExpr(call_expr) => {
+                        if constructor_path(ctx, strukt_def, &call_expr).is_none() {
+                            continue;
+                        }
+                        let Some(arg_list) = call_expr.arg_list() else { continue };
+                        arg_list.args().map(|it| suggest_name::for_field(&ctx.sema, Either::Left(&it))).collect()
+                    },
+                    _ => continue,
+                }
+            };
+            for (candidates, name) in candidates.iter_mut().zip(names) {
+                candidates.extend(name);
+            }
+        }
+    }
+
+    let mut taken = FxHashSet::default();
+    candidates
+        .into_iter()
         .enumerate()
-        .map(|(i, _)| {
-            let idx = i + 1;
-            ast::make::name(&format!("field{idx}"))
+        .map(|(idx, names)| {
+            let counts = names.iter().counts();
+            let name = names
+                .iter()
+                .unique()
+                .sorted_by_key(|it| Reverse(counts[it]))
+                .find(|it| !taken.contains(*it))
+                .cloned()
+                .unwrap_or_else(|| format!("field{}", idx + 1));
+            taken.insert(name.clone());
+            ast::make::name(&name)
         })
         .collect()
 }
@@ -247,6 +364,76 @@ fn not_applicable_other_than_tuple_struct() {
         );
         check_assist_not_applicable(convert_tuple_struct_to_named_struct, r#"struct Foo$0;"#);
     }
+    #[test]
+    fn names_derived_from_usages() {
+        check_assist(
+            convert_tuple_struct_to_named_struct,
+            r#"
+struct Size$0(u32, u32, bool);
+struct Rect { w: u32 }
+impl Rect {
+    fn get_height(&self) -> u32 { 0 }
+}
+
+fn from_rect(rect: &Rect) -> Size {
+    Size(rect.w, rect.get_height(), true)
+}
+
+fn area(size: Size) -> u32 {
+    let Size(width, height, _) = size;
+    width * height
+}
+
+fn width(Size(width, ..): Size) -> u32 {
+    width
+}
+"#,
+            r#"
+struct Size { width: u32, height: u32, field3: bool }
+struct Rect { w: u32 }
+impl Rect {
+    fn get_height(&self) -> u32 { 0 }
+}
+
+fn from_rect(rect: &Rect) -> Size {
+    Size { width: rect.w, height: rect.get_height(), field3: true }
+}
+
+fn area(size: Size) -> u32 {
+    let Size { width, height, field3: _ } = size;
+    width * height
+}
+
+fn width(Size { width, .. }: Size) -> u32 {
+    width
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn duplicate_and_non_local_names_are_not_used() {
+        check_assist(
+            convert_tuple_struct_to_named_struct,
+            r#"
+struct Unit;
+struct Pair$0(u32, u32, Unit);
+
+fn f(x: u32) -> Pair {
+    Pair(x, x, Unit)
+}
+"#,
+            r#"
+struct Unit;
+struct Pair { x: u32, field2: u32, field3: Unit }
+
+fn f(x: u32) -> Pair {
+    Pair { x, field2: x, field3: Unit }
+}
+"#,
+        );
+    }
+
     #[test]
     fn convert_in_macro_args() {
         check_assist(
@@ -289,11 +476,11 @@ fn into_inner(self) -> Inner {
 }"#,
             r#"
 struct Inner;
-struct A { field1: Inner }
+struct A { inner: Inner }
 
 impl A {
     fn new(inner: Inner) -> A {
-        A { field1: inner }
+        A { inner }
     }
 
     fn new_with_default() -> A {
@@ -301,7 +488,7 @@ fn new_with_default() -> A {
     }
 
     fn into_inner(self) -> Inner {
-        self.field1
+        self.inner
     }
 }"#,
         );
@@ -330,11 +517,11 @@ fn into_inner(self) -> Inner {
 }"#,
             r#"
 struct Inner;
-struct A { field1: Inner }
+struct A { inner: Inner }
 
 impl A {
     fn new(inner: Inner) -> Self {
-        Self { field1: inner }
+        Self { inner }
     }
 
     fn new_with_default() -> Self {
@@ -342,7 +529,7 @@ fn new_with_default() -> Self {
     }
 
     fn into_inner(self) -> Inner {
-        self.field1
+        self.inner
     }
 }"#,
         );
@@ -369,16 +556,16 @@ fn into_inner_via_self(self) -> Inner {
 }"#,
             r#"
 struct Inner;
-struct A { field1: Inner }
+struct A { first: Inner }
 
 impl A {
     fn into_inner(self) -> Inner {
-        let A { field1: first } = self;
+        let A { first } = self;
         first
     }
 
     fn into_inner_via_self(self) -> Inner {
-        let Self { field1: first } = self;
+        let Self { first } = self;
         first
     }
 }"#,
@@ -447,20 +634,20 @@ fn into_inner_destructed(self) -> u32 {
     }
 }"#,
             r#"
-struct Inner { field1: u32 }
+struct Inner { x: u32 }
 struct Outer(Inner);
 
 impl Outer {
     fn new() -> Self {
-        Self(Inner { field1: 42 })
+        Self(Inner { x: 42 })
     }
 
     fn into_inner(self) -> u32 {
-        (self.0).field1
+        (self.0).x
     }
 
     fn into_inner_destructed(self) -> u32 {
-        let Outer(Inner { field1: x }) = self;
+        let Outer(Inner { x }) = self;
         x
     }
 }"#,
@@ -619,12 +806,12 @@ fn value(self) -> usize {
 }"#,
             r#"
 enum A {
-    Variant { field1: usize },
+    Variant { value: usize },
 }
 
 impl A {
     fn new(value: usize) -> A {
-        A::Variant { field1: value }
+        A::Variant { value }
     }
 
     fn new_with_default() -> A {
@@ -633,7 +820,7 @@ fn new_with_default() -> A {
 
     fn value(self) -> usize {
         match self {
-            A::Variant { field1: value } => value,
+            A::Variant { value } => value,
         }
     }
 }"#,
@@ -666,12 +853,12 @@ fn value(self) -> usize {
 }"#,
             r#"
 enum A {
-    Variant { field1: usize },
+    Variant { value: usize },
 }
 
 impl A {
     fn new(value: usize) -> A {
-        Self::Variant { field1: value }
+        Self::Variant { value }
     }
 
     fn new_with_default() -> A {
@@ -680,7 +867,7 @@ fn new_with_default() -> A {
 
     fn value(self) -> usize {
         match self {
-            Self::Variant { field1: value } => value,
+            Self::Variant { value } => value,
         }
     }
 }"#,
@@ -709,17 +896,17 @@ fn into_inner_via_self(self) -> usize {
 }"#,
             r#"
 enum A {
-    Variant { field1: usize },
+    Variant { first: usize },
 }
 
 impl A {
     fn into_inner(self) -> usize {
-        let A::Variant { field1: first } = self;
+        let A::Variant { first } = self;
         first
     }
 
     fn into_inner_via_self(self) -> usize {
-        let Self::Variant { field1: first } = self;
+        let Self::Variant { first } = self;
         first
     }
 }"#,
@@ -750,7 +937,7 @@ fn into_inner_destructed(self) -> u32 {
 }"#,
             r#"
 enum Inner {
-    Variant { field1: usize },
+    Variant { x: usize },
 }
 enum Outer {
     Variant(Inner),
@@ -758,11 +945,11 @@ enum Outer {
 
 impl Outer {
     fn new() -> Self {
-        Self::Variant(Inner::Variant { field1: 42 })
+        Self::Variant(Inner::Variant { x: 42 })
     }
 
     fn into_inner_destructed(self) -> u32 {
-        let Outer::Variant(Inner::Variant { field1: x }) = self;
+        let Outer::Variant(Inner::Variant { x }) = self;
         x
     }
 }"#,
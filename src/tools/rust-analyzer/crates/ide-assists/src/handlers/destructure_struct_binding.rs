@@ -0,0 +1,766 @@
+use either::Either;
+use hir::{Adt, HasAttrs, HasVisibility, ModuleDef, StructKind};
+use ide_db::{
+    assists::{AssistId, AssistKind},
+    defs::Definition,
+    helpers::mod_path_to_ast,
+    patterns,
+    search::SearchScope,
+    FxHashSet,
+};
+use itertools::Itertools;
+use syntax::{
+    algo::find_node_at_range,
+    ast::{self, make, AstNode, HasName},
+    match_ast, SyntaxNode, TextRange,
+};
+
+use crate::assist_context::{AssistContext, Assists, SourceChangeBuilder};
+
+// Assist: destructure_struct_binding
+//
+// Destructures a struct binding in place.
+//
+// ```
+// struct Foo {
+//     bar: i32,
+//     baz: i32,
+// }
+// fn main() {
+//     let $0foo = Foo { bar: 1, baz: 2 };
+//     let bar2 = foo.bar;
+//     let baz2 = &foo.baz;
+// }
+// ```
+// ->
+// ```
+// struct Foo {
+//     bar: i32,
+//     baz: i32,
+// }
+// fn main() {
+//     let Foo { bar, baz } = Foo { bar: 1, baz: 2 };
+//     let bar2 = bar;
+//     let baz2 = &baz;
+// }
+// ```
+pub(crate) fn destructure_struct_binding(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let ident_pat = ctx.find_node_at_offset::<ast::IdentPat>()?;
+    let data = collect_data(ident_pat, ctx)?;
+    let target = data.ident_pat.syntax().text_range();
+    let id = AssistId("destructure_struct_binding", AssistKind::RefactorRewrite);
+
+    if data.whole_usages.is_empty() || data.can_reconstruct() {
+        acc.add(id, "Destructure struct binding", target, |edit| {
+            destructure_edit(edit, &data, Mode::Full)
+        });
+    }
+    if !data.whole_usages.is_empty() {
+        return Some(());
+    }
+    if data.fields.iter().any(|field| field.is_visible && field.accesses.is_empty()) {
+        acc.add(id, "Destructure struct binding, ignoring unused fields", target, |edit| {
+            destructure_edit(edit, &data, Mode::UsedFields)
+        });
+    }
+    if data.fields.iter().any(FieldData::is_nestable) {
+        acc.add(id, "Destructure struct binding with nested patterns", target, |edit| {
+            destructure_edit(edit, &data, Mode::Nested)
+        });
+    }
+    Some(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// Binds every visible field.
+    Full,
+    /// Binds the fields that are used, and ignores the rest with `..`.
+    UsedFields,
+    /// Like `UsedFields`, but destructures fields that are only used through their own fields.
+    Nested,
+}
+
+struct StructData {
+    ident_pat: ast::IdentPat,
+    path: ast::Path,
+    kind: StructKind,
+    /// Whether the new bindings are references, either through `ref` or by destructuring a
+    /// reference.
+    is_ref: bool,
+    is_non_exhaustive: bool,
+    fields: Vec<FieldData>,
+    /// Usages of the binding that aren't field accesses.
+    whole_usages: Vec<TextRange>,
+    names_in_scope: FxHashSet<String>,
+}
+
+struct FieldData {
+    /// The field name for record fields, the index for tuple fields.
+    name: String,
+    is_visible: bool,
+    /// The `foo.field` expressions, with the field of `field` they access, if any.
+    accesses: Vec<(ast::FieldExpr, Option<hir::Field>)>,
+    nested: Option<NestedStruct>,
+}
+
+/// A record struct a field can be destructured into.
+struct NestedStruct {
+    path: ast::Path,
+    fields: Vec<hir::Field>,
+    has_hidden_fields: bool,
+}
+
+impl StructData {
+    fn has_hidden_fields(&self) -> bool {
+        self.is_non_exhaustive || self.fields.iter().any(|field| !field.is_visible)
+    }
+
+    /// Whether usages of the whole binding can be replaced by a struct expression.
+    fn can_reconstruct(&self) -> bool {
+        !self.is_ref && !self.has_hidden_fields()
+    }
+}
+
+impl FieldData {
+    fn is_nestable(&self) -> bool {
+        self.nested.is_some()
+            && !self.accesses.is_empty()
+            && self.accesses.iter().all(|(_, sub_field)| sub_field.is_some())
+    }
+}
+
+fn collect_data(ident_pat: ast::IdentPat, ctx: &AssistContext<'_>) -> Option<StructData> {
+    if ident_pat.at_token().is_some() {
+        cov_mark::hit!(destructure_struct_subpattern);
+        return None;
+    }
+    let db = ctx.db();
+    let ty = ctx.sema.type_of_binding_in_pat(&ident_pat)?;
+    let is_ref = ty.is_reference();
+    let Some(Adt::Struct(strukt)) = ty.strip_references().as_adt() else { return None };
+    let kind = strukt.kind(db);
+    if kind == StructKind::Unit {
+        return None;
+    }
+
+    let module = ctx.sema.scope(ident_pat.syntax())?.module();
+    let path = struct_path(ctx, module, strukt)?;
+    let is_non_exhaustive = strukt.attrs(db).by_key("non_exhaustive").exists()
+        && strukt.module(db).krate() != module.krate();
+
+    let struct_fields = strukt.fields(db);
+    let mut fields: Vec<_> = struct_fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| FieldData {
+            name: match kind {
+                StructKind::Record => field.name(db).display(db).to_string(),
+                _ => idx.to_string(),
+            },
+            is_visible: field.is_visible_from(db, module),
+            accesses: Vec::new(),
+            nested: nested_struct(ctx, module, field),
+        })
+        .collect();
+
+    let local = ctx.sema.to_def(&ident_pat)?;
+    let usages = Definition::Local(local)
+        .usages(&ctx.sema)
+        .in_scope(&SearchScope::single_file(ctx.file_id()))
+        .all();
+    let source_file = ctx.sema.parse(ctx.file_id());
+    let source_file = source_file.syntax();
+    let mut whole_usages = Vec::new();
+    for usage in usages.references.get(&ctx.file_id()).into_iter().flatten() {
+        let in_macro = source_file
+            .covering_element(usage.range)
+            .ancestors()
+            .any(|it| ast::TokenTree::can_cast(it.kind()));
+        if in_macro {
+            cov_mark::hit!(destructure_struct_usage_in_macro);
+            return None;
+        }
+        let path_expr = find_node_at_range::<ast::PathExpr>(source_file, usage.range)?;
+        let field_access =
+            path_expr.syntax().parent().and_then(ast::FieldExpr::cast).and_then(|field_expr| {
+                match ctx.sema.resolve_field(&field_expr)? {
+                    Either::Left(field) => {
+                        let idx = struct_fields.iter().position(|it| *it == field)?;
+                        let sub_field = field_expr
+                            .syntax()
+                            .parent()
+                            .and_then(ast::FieldExpr::cast)
+                            .and_then(|it| ctx.sema.resolve_field(&it)?.left());
+                        Some((idx, (field_expr, sub_field)))
+                    }
+                    Either::Right(_) => None,
+                }
+            });
+        match field_access {
+            Some((idx, access)) if fields[idx].is_visible => fields[idx].accesses.push(access),
+            _ => whole_usages.push(path_expr.syntax().text_range()),
+        }
+    }
+
+    let body = ident_pat
+        .syntax()
+        .ancestors()
+        .find(|it| ast::Fn::can_cast(it.kind()) || ast::Const::can_cast(it.kind()))
+        .unwrap_or_else(|| source_file.clone());
+    let names_in_scope = names_in(&body);
+
+    Some(StructData {
+        ident_pat,
+        path,
+        kind,
+        is_ref,
+        is_non_exhaustive,
+        fields,
+        whole_usages,
+        names_in_scope,
+    })
+}
+
+fn struct_path(
+    ctx: &AssistContext<'_>,
+    module: hir::Module,
+    strukt: hir::Struct,
+) -> Option<ast::Path> {
+    let path = module.find_use_path(
+        ctx.db(),
+        ModuleDef::Adt(Adt::Struct(strukt)),
+        ctx.config.prefer_no_std,
+        ctx.config.prefer_prelude,
+    )?;
+    Some(mod_path_to_ast(&path))
+}
+
+fn nested_struct(
+    ctx: &AssistContext<'_>,
+    module: hir::Module,
+    field: &hir::Field,
+) -> Option<NestedStruct> {
+    let db = ctx.db();
+    let Some(Adt::Struct(strukt)) = field.ty(db).as_adt() else { return None };
+    if strukt.kind(db) != StructKind::Record {
+        return None;
+    }
+    let all_fields = strukt.fields(db);
+    let fields: Vec<_> =
+        all_fields.iter().copied().filter(|it| it.is_visible_from(db, module)).collect();
+    let has_hidden_fields = fields.len() != all_fields.len()
+        || (strukt.attrs(db).by_key("non_exhaustive").exists()
+            && strukt.module(db).krate() != module.krate());
+    Some(NestedStruct { path: struct_path(ctx, module, strukt)?, fields, has_hidden_fields })
+}
+
+/// The names of the bindings and single segment paths in `body`, which new bindings shouldn't
+/// shadow.
+fn names_in(body: &SyntaxNode) -> FxHashSet<String> {
+    body.descendants()
+        .filter_map(|node| {
+            match_ast! {
+                match node {
+                    ast::IdentPat(it) => Some(it.name()?.text().to_string()),
+                    ast::Path(it) => Some(it.as_single_name_ref()?.text().to_string()),
+                    _ => None,
+                }
+            }
+        })
+        .collect()
+}
+
+struct NameGenerator {
+    taken: FxHashSet<String>,
+}
+
+impl NameGenerator {
+    fn fresh(&mut self, base: &str) -> String {
+        let mut name = base.to_owned();
+        let mut idx = 1;
+        while self.taken.contains(&name) {
+            name = format!("{base}_{idx}");
+            idx += 1;
+        }
+        self.taken.insert(name.clone());
+        name
+    }
+}
+
+fn destructure_edit(edit: &mut SourceChangeBuilder, data: &StructData, mode: Mode) {
+    let mut names = NameGenerator { taken: data.names_in_scope.clone() };
+    let original = &data.ident_pat;
+    let is_ref = original.ref_token().is_some();
+    // `mut` would reset the binding mode when destructuring a reference
+    let is_mut = original.mut_token().is_some() && (is_ref || !data.is_ref);
+    let binding = |name: &str| ast::Pat::from(make::ident_pat(is_ref, is_mut, make::name(name)));
+    let is_record = data.kind == StructKind::Record;
+
+    let mut field_pats = Vec::new();
+    let mut bound_fields = Vec::new();
+    for field in &data.fields {
+        if !field.is_visible || (mode != Mode::Full && field.accesses.is_empty()) {
+            field_pats.push((field.name.clone(), None));
+            continue;
+        }
+        let pat = match &field.nested {
+            Some(nested) if mode == Mode::Nested && field.is_nestable() => {
+                let mut sub_pats = Vec::new();
+                for sub_field in &nested.fields {
+                    let accesses: Vec<_> = field
+                        .accesses
+                        .iter()
+                        .filter(|(_, it)| *it == Some(*sub_field))
+                        .filter_map(|(field_expr, _)| {
+                            field_expr.syntax().parent().and_then(ast::FieldExpr::cast)
+                        })
+                        .collect();
+                    if accesses.is_empty() {
+                        continue;
+                    }
+                    let sub_name = sub_field_name(&field.accesses, sub_field);
+                    let name = names.fresh(&sub_name);
+                    sub_pats.push((sub_name, Some(binding(&name))));
+                    for access in accesses {
+                        replace_access(edit, data, &access, &name);
+                    }
+                }
+                let has_rest = sub_pats.len() != nested.fields.len() || nested.has_hidden_fields;
+                patterns::struct_pat(nested.path.clone(), StructKind::Record, sub_pats, has_rest)
+            }
+            _ => {
+                let base = if is_record { field.name.clone() } else { format!("_{}", field.name) };
+                let name = names.fresh(&base);
+                for (field_expr, _) in &field.accesses {
+                    replace_access(edit, data, field_expr, &name);
+                }
+                bound_fields.push((field, name.clone()));
+                binding(&name)
+            }
+        };
+        field_pats.push((field.name.clone(), Some(pat)));
+    }
+
+    let pat =
+        patterns::struct_pat(data.path.clone(), data.kind, field_pats, data.is_non_exhaustive);
+    edit.replace(data.ident_pat.syntax().text_range(), pat.to_string());
+
+    if mode == Mode::Full && !data.whole_usages.is_empty() {
+        let struct_expr = if is_record {
+            let fields = bound_fields.iter().map(|(field, name)| {
+                if field.name == *name {
+                    name.clone()
+                } else {
+                    format!("{}: {name}", field.name)
+                }
+            });
+            format!("{} {{ {} }}", data.path, fields.format(", "))
+        } else {
+            format!("{}({})", data.path, bound_fields.iter().map(|(_, name)| name).format(", "))
+        };
+        for &range in &data.whole_usages {
+            edit.replace(range, struct_expr.clone());
+        }
+    }
+}
+
+fn sub_field_name(
+    accesses: &[(ast::FieldExpr, Option<hir::Field>)],
+    sub_field: &hir::Field,
+) -> String {
+    accesses
+        .iter()
+        .filter(|(_, it)| *it == Some(*sub_field))
+        .find_map(|(field_expr, _)| {
+            let outer = field_expr.syntax().parent().and_then(ast::FieldExpr::cast)?;
+            Some(outer.name_ref()?.text().to_string())
+        })
+        .unwrap_or_default()
+}
+
+/// Replaces the field access `field_expr` with the binding `name`, dereferencing it if the
+/// binding is a reference and the access isn't already borrowed or auto-dereferenced.
+fn replace_access(
+    edit: &mut SourceChangeBuilder,
+    data: &StructData,
+    field_expr: &ast::FieldExpr,
+    name: &str,
+) {
+    let field_range = field_expr.syntax().text_range();
+    if !data.is_ref {
+        edit.replace(field_range, name.to_owned());
+        return;
+    }
+    let parent = field_expr.syntax().parent().and_then(ast::Expr::cast);
+    match parent {
+        Some(ast::Expr::RefExpr(ref_expr)) => {
+            edit.replace(ref_expr.syntax().text_range(), name.to_owned())
+        }
+        Some(ast::Expr::MethodCallExpr(call))
+            if call.receiver().map_or(false, |it| it.syntax() == field_expr.syntax()) =>
+        {
+            edit.replace(field_range, name.to_owned())
+        }
+        Some(ast::Expr::FieldExpr(_)) => edit.replace(field_range, name.to_owned()),
+        Some(ast::Expr::IndexExpr(index))
+            if index.base().map_or(false, |it| it.syntax() == field_expr.syntax()) =>
+        {
+            edit.replace(field_range, name.to_owned())
+        }
+        _ => edit.replace(field_range, format!("*{name}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    fn check_full(ra_fixture_before: &str, ra_fixture_after: &str) {
+        check_assist_by_label(
+            destructure_struct_binding,
+            ra_fixture_before,
+            ra_fixture_after,
+            "Destructure struct binding",
+        )
+    }
+
+    #[test]
+    fn record_struct() {
+        check_full(
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let $0foo = Foo { bar: 1, baz: 2 };
+    let bar2 = foo.bar;
+    let baz2 = &foo.baz;
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let Foo { bar, baz } = Foo { bar: 1, baz: 2 };
+    let bar2 = bar;
+    let baz2 = &baz;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn tuple_struct() {
+        check_full(
+            r#"
+struct Foo(i32, i32);
+
+fn f($0foo: Foo) -> i32 {
+    foo.0 + foo.1
+}
+"#,
+            r#"
+struct Foo(i32, i32);
+
+fn f(Foo(_0, _1): Foo) -> i32 {
+    _0 + _1
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn rename_conflicting_bindings() {
+        check_full(
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let bar = 0;
+    let $0foo = Foo { bar: 1, baz: 2 };
+    let sum = foo.bar + foo.baz + bar;
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let bar = 0;
+    let Foo { bar: bar_1, baz } = Foo { bar: 1, baz: 2 };
+    let sum = bar_1 + baz + bar;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn keep_ref_and_mut() {
+        check_full(
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let ref mut $0foo = Foo { bar: 1, baz: 2 };
+    foo.bar = 3;
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn main() {
+    let Foo { ref mut bar, ref mut baz } = Foo { bar: 1, baz: 2 };
+    *bar = 3;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn reference_binding() {
+        check_full(
+            r#"
+struct Foo { bar: i32, baz: Vec<i32> }
+
+fn f(foo: &Foo) -> i32 {
+    let $0foo = foo;
+    let _ = &foo.bar;
+    let _ = foo.baz[0];
+    foo.bar + 1
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: Vec<i32> }
+
+fn f(foo: &Foo) -> i32 {
+    let Foo { bar, baz } = foo;
+    let _ = bar;
+    let _ = baz[0];
+    *bar + 1
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn whole_usages_are_reconstructed() {
+        check_full(
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn consume(_: Foo) {}
+
+fn main() {
+    let $0foo = Foo { bar: 1, baz: 2 };
+    let bar = foo.bar;
+    consume(foo);
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: i32 }
+
+fn consume(_: Foo) {}
+
+fn main() {
+    let Foo { bar: bar_1, baz } = Foo { bar: 1, baz: 2 };
+    let bar = bar_1;
+    consume(Foo { bar: bar_1, baz });
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn hidden_fields_use_rest_pattern() {
+        check_full(
+            r#"
+mod m {
+    pub struct Foo { pub bar: i32, baz: i32 }
+}
+
+fn f($0foo: m::Foo) -> i32 {
+    foo.bar
+}
+"#,
+            r#"
+mod m {
+    pub struct Foo { pub bar: i32, baz: i32 }
+}
+
+fn f(m::Foo { bar, .. }: m::Foo) -> i32 {
+    bar
+}
+"#,
+        );
+        check_full(
+            r#"
+mod m {
+    pub struct Tup(pub i32, i32, pub i32);
+}
+
+fn f($0tup: m::Tup) -> i32 {
+    tup.0 + tup.2
+}
+"#,
+            r#"
+mod m {
+    pub struct Tup(pub i32, i32, pub i32);
+}
+
+fn f(m::Tup(_0, _, _2): m::Tup) -> i32 {
+    _0 + _2
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn foreign_non_exhaustive_struct() {
+        check_full(
+            r#"
+//- /main.rs crate:main deps:dep
+fn f($0foo: dep::Foo) -> i32 {
+    foo.bar
+}
+//- /dep.rs crate:dep
+#[non_exhaustive]
+pub struct Foo { pub bar: i32 }
+"#,
+            r#"
+fn f(dep::Foo { bar, .. }: dep::Foo) -> i32 {
+    bar
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn ignore_unused_fields() {
+        check_assist_by_label(
+            destructure_struct_binding,
+            r#"
+struct Foo { bar: i32, baz: i32, qux: i32 }
+
+fn f($0foo: Foo) -> i32 {
+    foo.baz
+}
+"#,
+            r#"
+struct Foo { bar: i32, baz: i32, qux: i32 }
+
+fn f(Foo { baz, .. }: Foo) -> i32 {
+    baz
+}
+"#,
+            "Destructure struct binding, ignoring unused fields",
+        );
+        check_assist_by_label(
+            destructure_struct_binding,
+            r#"
+struct Tup(i32, i32, i32);
+
+fn f($0tup: Tup) -> i32 {
+    tup.1
+}
+"#,
+            r#"
+struct Tup(i32, i32, i32);
+
+fn f(Tup(_, _1, ..): Tup) -> i32 {
+    _1
+}
+"#,
+            "Destructure struct binding, ignoring unused fields",
+        );
+    }
+
+    #[test]
+    fn nested_patterns() {
+        check_assist_by_label(
+            destructure_struct_binding,
+            r#"
+struct Inner { a: i32, b: i32, c: i32 }
+struct Outer { inner: Inner, d: i32 }
+
+fn f($0outer: Outer) -> i32 {
+    outer.inner.a + outer.inner.b + outer.d
+}
+"#,
+            r#"
+struct Inner { a: i32, b: i32, c: i32 }
+struct Outer { inner: Inner, d: i32 }
+
+fn f(Outer { inner: Inner { a, b, .. }, d }: Outer) -> i32 {
+    a + b + d
+}
+"#,
+            "Destructure struct binding with nested patterns",
+        )
+    }
+
+    #[test]
+    fn not_applicable_to_whole_usages_of_reference() {
+        check_assist_not_applicable(
+            destructure_struct_binding,
+            r#"
+struct Foo { bar: i32 }
+
+fn consume(_: &Foo) {}
+
+fn f($0foo: &Foo) {
+    consume(foo);
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn not_applicable_to_sub_pattern() {
+        cov_mark::check!(destructure_struct_subpattern);
+        check_assist_not_applicable(
+            destructure_struct_binding,
+            r#"
+struct Foo { bar: i32 }
+
+fn f(foo: Foo) {
+    let $0x @ Foo { .. } = foo;
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn not_applicable_to_usage_in_macro() {
+        cov_mark::check!(destructure_struct_usage_in_macro);
+        check_assist_not_applicable(
+            destructure_struct_binding,
+            r#"
+macro_rules! id { ($e:expr) => { $e }; }
+struct Foo { bar: i32 }
+
+fn f($0foo: Foo) -> i32 {
+    id!(foo.bar)
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn not_applicable_to_unit_struct_and_non_structs() {
+        check_assist_not_applicable(
+            destructure_struct_binding,
+            r#"
+struct Unit;
+
+fn f($0unit: Unit) {}
+"#,
+        );
+        check_assist_not_applicable(
+            destructure_struct_binding,
+            r#"
+fn f($0x: i32) {}
+"#,
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use ide_db::{
     assists::{AssistId, AssistKind},
     defs::Definition,
+    patterns,
     search::{FileReference, SearchScope, UsageSearchResult},
 };
 use itertools::Itertools;
@@ -168,11 +169,8 @@ fn edit_tuple_assignment(
         let original = &data.ident_pat;
         let is_ref = original.ref_token().is_some();
         let is_mut = original.mut_token().is_some();
-        let fields = data
-            .field_names
-            .iter()
-            .map(|name| ast::Pat::from(make::ident_pat(is_ref, is_mut, make::name(name))));
-        make::tuple_pat(fields).clone_for_update()
+        let names = data.field_names.iter().map(|name| make::name(name));
+        patterns::tuple_pat_with_bindings(names, is_ref, is_mut).clone_for_update()
     };
 
     if let Some(cap) = ctx.config.snippet_cap {
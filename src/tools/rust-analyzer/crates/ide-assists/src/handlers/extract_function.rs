@@ -4,7 +4,7 @@
 use either::Either;
 use hir::{
     DescendPreference, HasSource, HirDisplay, InFile, Local, LocalSource, ModuleDef,
-    PathResolution, Semantics, TypeInfo, TypeParam,
+    PathResolution, Semantics, TypeInfo,
 };
 use ide_db::{
     defs::{Definition, NameRefClass},
@@ -16,14 +16,14 @@
     syntax_helpers::node_ext::{
         for_each_tail_expr, preorder_expr, walk_expr, walk_pat, walk_patterns_in_expr,
     },
-    FxIndexSet, RootDatabase,
+    FxHashSet, FxIndexSet, RootDatabase,
 };
 use syntax::{
     ast::{
         self, edit::IndentLevel, edit_in_place::Indent, AstNode, AstToken, HasGenericParams,
         HasName,
     },
-    match_ast, ted, SyntaxElement,
+    match_ast, ted, AstPtr, SyntaxElement,
     SyntaxKind::{self, COMMENT},
     SyntaxNode, SyntaxToken, TextRange, TextSize, TokenAtOffset, WalkEvent, T,
 };
@@ -215,6 +215,7 @@ pub(crate) fn extract_function(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
                         ctx.config.insert_use.prefix_kind,
                         ctx.config.prefer_no_std,
                         ctx.config.prefer_prelude,
+                        ctx.config.insert_use.prefer_reexports,
                     );
 
                     if let Some(mod_path) = mod_path {
@@ -935,7 +936,15 @@ fn analyze_container(
 
     fn return_ty(&self, ctx: &AssistContext<'_>) -> Option<RetType> {
         match self.tail_expr() {
-            Some(expr) => ctx.sema.type_of_expr(&expr).map(TypeInfo::original).map(RetType::Expr),
+            Some(expr) => {
+                let ty = ctx.sema.type_of_expr(&expr)?.original;
+                // The type of a closure can't be named, but the type expected of it often can.
+                let ty = match ty.is_closure() {
+                    true => ctx.sema.expected_type_of_expr(&expr).unwrap_or(ty),
+                    false => ty,
+                };
+                Some(RetType::Expr(ty))
+            }
             None => Some(RetType::Stmt),
         }
     }
@@ -964,9 +973,7 @@ fn external_control_flow(
         let mut break_expr = None;
         let mut continue_expr = None;
         let mut is_async = false;
-        let mut _is_unsafe = false;
 
-        let mut unsafe_depth = 0;
         let mut loop_depth = 0;
 
         self.preorder_expr(&mut |expr| {
@@ -977,9 +984,6 @@ fn external_control_flow(
                         ast::Expr::LoopExpr(_)
                         | ast::Expr::ForExpr(_)
                         | ast::Expr::WhileExpr(_) => loop_depth -= 1,
-                        ast::Expr::BlockExpr(block_expr) if block_expr.unsafe_token().is_some() => {
-                            unsafe_depth -= 1
-                        }
                         _ => (),
                     }
                     return false;
@@ -989,8 +993,16 @@ fn external_control_flow(
                 ast::Expr::LoopExpr(_) | ast::Expr::ForExpr(_) | ast::Expr::WhileExpr(_) => {
                     loop_depth += 1;
                 }
-                ast::Expr::BlockExpr(block_expr) if block_expr.unsafe_token().is_some() => {
-                    unsafe_depth += 1
+                // `.await`s, `return`s and `?`s in these belong to a different body, this is
+                // already handled for nested ones by `preorder_expr`
+                ast::Expr::ClosureExpr(_) => return true,
+                ast::Expr::BlockExpr(block_expr)
+                    if matches!(
+                        block_expr.modifier(),
+                        Some(ast::BlockModifier::Async(_) | ast::BlockModifier::Gen(_))
+                    ) =>
+                {
+                    return true
                 }
                 ast::Expr::ReturnExpr(it) => {
                     ret_expr = Some(it);
@@ -1005,9 +1017,6 @@ fn external_control_flow(
                     continue_expr = Some(it);
                 }
                 ast::Expr::AwaitExpr(_) => is_async = true,
-                // FIXME: Do unsafe analysis on expression, sem highlighting knows this so we should be able
-                // to just lift that out of there
-                // expr if unsafe_depth ==0 && expr.is_unsafe => is_unsafe = true,
                 _ => {}
             }
             false
@@ -1038,7 +1047,36 @@ fn external_control_flow(
             (None, None, None, None) => None,
         };
 
-        Some(ControlFlow { kind, is_async, is_unsafe: _is_unsafe })
+        let is_unsafe = self.requires_unsafe(ctx);
+        Some(ControlFlow { kind, is_async, is_unsafe })
+    }
+
+    /// Whether the body contains operations requiring `unsafe` that aren't wrapped in an `unsafe`
+    /// block inside of the body itself, so that the extracted function has to be an `unsafe fn`.
+    fn requires_unsafe(&self, ctx: &AssistContext<'_>) -> bool {
+        let sema = &ctx.sema;
+        let Some(def) = self.parent().and_then(|parent| {
+            parent.ancestors().find_map(|node| {
+                match_ast! {
+                    match node {
+                        ast::Fn(it) => sema.to_def(&it).map(hir::DefWithBody::from),
+                        ast::Const(it) => sema.to_def(&it).map(hir::DefWithBody::from),
+                        ast::Static(it) => sema.to_def(&it).map(hir::DefWithBody::from),
+                        _ => None,
+                    }
+                }
+            })
+        }) else {
+            return false;
+        };
+        let in_body = |ptr: InFile<AstPtr<ast::Expr>>| {
+            let node = ptr.value.to_node(&sema.parse_or_expand(ptr.file_id));
+            let range = InFile::new(ptr.file_id, node.syntax()).original_file_range(sema.db);
+            range.file_id == ctx.file_id() && self.contains_range(range.range)
+        };
+        def.unsafe_operations(sema.db)
+            .into_iter()
+            .any(|op| in_body(op.expr) && op.unsafe_block.map_or(true, |block| !in_body(block)))
     }
 
     /// find variables that should be extracted as params
@@ -1076,7 +1114,10 @@ fn extracted_function_params(
                 // We can move the value into the function call if it's not used after the call,
                 // if the var is not used but defined outside a loop we are extracting from we can't move it either
                 // as the function will reuse it in the next iteration.
-                let move_local = (!has_usages && defined_outside_parent_loop) || ty.is_reference();
+                // A value that's moved out of in the body has to be moved into the function.
+                let move_local = (!has_usages && defined_outside_parent_loop)
+                    || ty.is_reference()
+                    || has_moving_usages(ctx, &usages, self);
                 Param { var, ty, move_local, requires_mut, is_copy }
             })
             .collect()
@@ -1143,6 +1184,16 @@ fn has_exclusive_usages(
         .any(|reference| reference_is_exclusive(reference, body, ctx))
 }
 
+/// checks if relevant var is moved out of inside body
+fn has_moving_usages(ctx: &AssistContext<'_>, usages: &LocalUsages, body: &FunctionBody) -> bool {
+    usages
+        .iter()
+        .filter(|reference| body.contains_range(reference.range))
+        .filter(|reference| reference.category != Some(ReferenceCategory::Write))
+        .filter_map(|reference| path_element_of_reference(body, reference))
+        .any(|path| expr_moves_value(ctx, &path).unwrap_or(false))
+}
+
 /// checks if this reference requires `&mut` access inside node
 fn reference_is_exclusive(
     reference: &FileReference,
@@ -1170,6 +1221,15 @@ fn expr_require_exclusive_access(ctx: &AssistContext<'_>, expr: &ast::Expr) -> O
         return None;
     }
 
+    let autoref_mut = ctx.sema.expr_adjustments(expr).map_or(false, |adjustments| {
+        adjustments.iter().any(|it| {
+            matches!(it.kind, hir::Adjust::Borrow(hir::AutoBorrow::Ref(hir::Mutability::Mut)))
+        })
+    });
+    if autoref_mut {
+        return Some(true);
+    }
+
     let parent = expr.syntax().parent()?;
 
     if let Some(bin_expr) = ast::BinExpr::cast(parent.clone()) {
@@ -1191,13 +1251,69 @@ fn expr_require_exclusive_access(ctx: &AssistContext<'_>, expr: &ast::Expr) -> O
         return Some(matches!(access, hir::Access::Exclusive));
     }
 
-    if let Some(field) = ast::FieldExpr::cast(parent) {
+    if let Some(field) = ast::FieldExpr::cast(parent.clone()) {
         return expr_require_exclusive_access(ctx, &field.into());
     }
 
+    if let Some(index) = ast::IndexExpr::cast(parent) {
+        if index.base()?.syntax() == expr.syntax() {
+            return expr_require_exclusive_access(ctx, &index.into());
+        }
+    }
+
     Some(false)
 }
 
+/// checks if this expr moves the value out of the variable, recurses on field access
+fn expr_moves_value(ctx: &AssistContext<'_>, expr: &ast::Expr) -> Option<bool> {
+    if let ast::Expr::MacroExpr(_) = expr {
+        return None;
+    }
+
+    let ty = ctx.sema.type_of_expr(expr)?.original;
+    if ty.is_scalar() || ty.is_copy(ctx.db()) {
+        return Some(false);
+    }
+    let autoref = ctx.sema.expr_adjustments(expr).map_or(false, |adjustments| {
+        adjustments.iter().any(|it| matches!(it.kind, hir::Adjust::Borrow(_)))
+    });
+    if autoref {
+        return Some(false);
+    }
+
+    let parent = expr.syntax().parent()?;
+    let is_child =
+        |child: Option<ast::Expr>| child.map_or(false, |it| it.syntax() == expr.syntax());
+    match_ast! {
+        match parent {
+            ast::FieldExpr(field) => expr_moves_value(ctx, &field.into()),
+            ast::ParenExpr(paren) => expr_moves_value(ctx, &paren.into()),
+            ast::MethodCallExpr(call) => {
+                if !is_child(call.receiver()) {
+                    return Some(true);
+                }
+                let func = ctx.sema.resolve_method_call(&call)?;
+                let access = func.self_param(ctx.db())?.access(ctx.db());
+                Some(matches!(access, hir::Access::Owned))
+            },
+            // these only use the place, the value stays where it is
+            ast::RefExpr(_) => Some(false),
+            ast::IndexExpr(_) => Some(false),
+            ast::PrefixExpr(prefix) => Some(prefix.op_kind()? != ast::UnaryOp::Deref),
+            ast::BinExpr(bin_expr) => {
+                let is_assignment =
+                    matches!(bin_expr.op_kind()?, ast::BinaryOp::Assignment { .. });
+                Some(!(is_assignment && is_child(bin_expr.lhs())))
+            },
+            // whether the scrutinee is moved depends on the bindings of the patterns, which keep
+            // working through a reference thanks to default binding modes
+            ast::MatchExpr(_) => Some(false),
+            ast::LetExpr(_) => Some(false),
+            _ => Some(true),
+        }
+    }
+}
+
 trait HasTokenAtOffset {
     fn token_at_offset(&self, offset: TextSize) -> TokenAtOffset<SyntaxToken>;
 }
@@ -1599,10 +1715,31 @@ fn make_generic_params_and_where_clause(
     ctx: &AssistContext<'_>,
     fun: &Function,
 ) -> (Option<ast::GenericParamList>, Option<ast::WhereClause>) {
-    let used_type_params = fun.type_params(ctx);
+    let mut used_generic_params = fun.generic_params(ctx);
+
+    // Lifetimes in the bounds of the required params and predicates are required as well.
+    let lifetimes_in_bounds: Vec<_> = fun
+        .mods
+        .generic_param_lists
+        .iter()
+        .flat_map(|it| it.generic_params())
+        .filter(|param| param_is_required(ctx, param, &used_generic_params))
+        .map(|param| param.syntax().clone())
+        .chain(
+            fun.mods
+                .where_clauses
+                .iter()
+                .flat_map(|it| it.predicates())
+                .filter(|pred| pred_is_required(ctx, pred, &used_generic_params))
+                .map(|pred| pred.syntax().clone()),
+        )
+        .flat_map(|node| node.descendants().filter_map(ast::Lifetime::cast).collect::<Vec<_>>())
+        .filter_map(|lifetime| ctx.sema.resolve_lifetime_param(&lifetime))
+        .collect();
+    used_generic_params.extend(lifetimes_in_bounds.into_iter().map(hir::GenericParam::from));
 
-    let generic_param_list = make_generic_param_list(ctx, fun, &used_type_params);
-    let where_clause = make_where_clause(ctx, fun, &used_type_params);
+    let generic_param_list = make_generic_param_list(ctx, fun, &used_generic_params);
+    let where_clause = make_where_clause(ctx, fun, &used_generic_params);
 
     (generic_param_list, where_clause)
 }
@@ -1610,7 +1747,7 @@ fn make_generic_params_and_where_clause(
 fn make_generic_param_list(
     ctx: &AssistContext<'_>,
     fun: &Function,
-    used_type_params: &[TypeParam],
+    used_generic_params: &FxHashSet<hir::GenericParam>,
 ) -> Option<ast::GenericParamList> {
     let mut generic_params = fun
         .mods
@@ -1619,7 +1756,7 @@ fn make_generic_param_list(
         .flat_map(|parent_params| {
             parent_params
                 .generic_params()
-                .filter(|param| param_is_required(ctx, param, used_type_params))
+                .filter(|param| param_is_required(ctx, param, used_generic_params))
         })
         .peekable();
 
@@ -1633,21 +1770,20 @@ fn make_generic_param_list(
 fn param_is_required(
     ctx: &AssistContext<'_>,
     param: &ast::GenericParam,
-    used_type_params: &[TypeParam],
+    used_generic_params: &FxHashSet<hir::GenericParam>,
 ) -> bool {
-    match param {
-        ast::GenericParam::ConstParam(_) | ast::GenericParam::LifetimeParam(_) => false,
-        ast::GenericParam::TypeParam(type_param) => match &ctx.sema.to_def(type_param) {
-            Some(def) => used_type_params.contains(def),
-            _ => false,
-        },
-    }
+    let def = match param {
+        ast::GenericParam::ConstParam(it) => ctx.sema.to_def(it).map(hir::GenericParam::from),
+        ast::GenericParam::LifetimeParam(it) => ctx.sema.to_def(it).map(hir::GenericParam::from),
+        ast::GenericParam::TypeParam(it) => ctx.sema.to_def(it).map(hir::GenericParam::from),
+    };
+    def.map_or(false, |it| used_generic_params.contains(&it))
 }
 
 fn make_where_clause(
     ctx: &AssistContext<'_>,
     fun: &Function,
-    used_type_params: &[TypeParam],
+    used_generic_params: &FxHashSet<hir::GenericParam>,
 ) -> Option<ast::WhereClause> {
     let mut predicates = fun
         .mods
@@ -1656,7 +1792,7 @@ fn make_where_clause(
         .flat_map(|parent_where_clause| {
             parent_where_clause
                 .predicates()
-                .filter(|pred| pred_is_required(ctx, pred, used_type_params))
+                .filter(|pred| pred_is_required(ctx, pred, used_generic_params))
         })
         .peekable();
 
@@ -1670,36 +1806,61 @@ fn make_where_clause(
 fn pred_is_required(
     ctx: &AssistContext<'_>,
     pred: &ast::WherePred,
-    used_type_params: &[TypeParam],
+    used_generic_params: &FxHashSet<hir::GenericParam>,
 ) -> bool {
-    match resolved_type_param(ctx, pred) {
-        Some(it) => used_type_params.contains(&it),
+    match resolved_generic_param(ctx, pred) {
+        Some(it) => used_generic_params.contains(&it),
         None => false,
     }
 }
 
-fn resolved_type_param(ctx: &AssistContext<'_>, pred: &ast::WherePred) -> Option<TypeParam> {
+/// The type or lifetime param bounded by `pred`.
+fn resolved_generic_param(
+    ctx: &AssistContext<'_>,
+    pred: &ast::WherePred,
+) -> Option<hir::GenericParam> {
+    if let Some(lifetime) = pred.lifetime() {
+        return ctx.sema.resolve_lifetime_param(&lifetime).map(Into::into);
+    }
     let path = match pred.ty()? {
         ast::Type::PathType(path_type) => path_type.path(),
         _ => None,
     }?;
 
     match ctx.sema.resolve_path(&path)? {
-        PathResolution::TypeParam(type_param) => Some(type_param),
+        PathResolution::TypeParam(type_param) => Some(type_param.into()),
         _ => None,
     }
 }
 
 impl Function {
-    /// Collect all the `TypeParam`s used in the `body` and `params`.
-    fn type_params(&self, ctx: &AssistContext<'_>) -> Vec<TypeParam> {
-        let type_params_in_descendant_paths =
-            self.body.descendant_paths().filter_map(|it| match ctx.sema.resolve_path(&it) {
-                Some(PathResolution::TypeParam(type_param)) => Some(type_param),
-                _ => None,
-            });
-        let type_params_in_params = self.params.iter().filter_map(|p| p.ty.as_type_param(ctx.db()));
-        type_params_in_descendant_paths.chain(type_params_in_params).collect()
+    /// Collect all the generic params used in the `body`, the `params` and the return type.
+    fn generic_params(&self, ctx: &AssistContext<'_>) -> FxHashSet<hir::GenericParam> {
+        let db = ctx.db();
+        let mut res = FxHashSet::default();
+        for path in self.body.descendant_paths() {
+            match ctx.sema.resolve_path(&path) {
+                Some(PathResolution::TypeParam(it)) => res.insert(it.into()),
+                Some(PathResolution::ConstParam(it)) => res.insert(it.into()),
+                _ => continue,
+            };
+        }
+        let lifetimes = self
+            .body
+            .descendants()
+            .filter(|it| self.body.contains_node(it))
+            .filter_map(ast::Lifetime::cast);
+        res.extend(
+            lifetimes
+                .filter_map(|it| ctx.sema.resolve_lifetime_param(&it))
+                .map(hir::GenericParam::from),
+        );
+        res.extend(self.params.iter().flat_map(|param| param.ty.generic_params(db)));
+        if let RetType::Expr(ty) = &self.ret_ty {
+            res.extend(ty.generic_params(db));
+        }
+        res.extend(self.outliving_locals.iter().flat_map(|it| it.local.ty(db).generic_params(db)));
+        res
     }
 
     fn make_param_list(&self, ctx: &AssistContext<'_>, module: hir::Module) -> ast::ParamList {
@@ -1782,7 +1943,7 @@ fn make_body(ctx: &AssistContext<'_>, old_indent: IndentLevel, fun: &Function) -
             let expr = rewrite_body_segment(ctx, &fun.params, &handler, expr.syntax());
             let expr = ast::Expr::cast(expr).expect("Body segment should be an expr");
             match expr {
-                ast::Expr::BlockExpr(block) => {
+                ast::Expr::BlockExpr(block) if block.modifier().is_none() => {
                     // If the extracted expression is itself a block, there is no need to wrap it inside another block.
                     block.dedent(old_indent);
                     let elements = block.stmt_list().map_or_else(
@@ -1997,6 +2158,11 @@ fn fix_param_usages(ctx: &AssistContext<'_>, params: &[Param], syntax: &SyntaxNo
                 Some(ast::Expr::MethodCallExpr(_) | ast::Expr::FieldExpr(_)) => {
                     // do nothing
                 }
+                Some(ast::Expr::IndexExpr(index))
+                    if index.base().map_or(false, |it| it.syntax() == usage.syntax()) =>
+                {
+                    // indexing auto-derefs its base
+                }
                 Some(ast::Expr::RefExpr(node))
                     if param.kind() == ParamKind::MutRef && node.mut_token().is_some() =>
                 {
@@ -2134,6 +2300,35 @@ fn $0fun_name() -> i32 {
         );
     }
 
+    #[test]
+    fn closure_returns_expected_fn_pointer() {
+        check_assist(
+            extract_function,
+            r#"
+fn apply(f: fn(i32) -> i32) -> i32 {
+    f(1)
+}
+
+fn foo() {
+    apply($0|x| x + 1$0);
+}
+"#,
+            r#"
+fn apply(f: fn(i32) -> i32) -> i32 {
+    f(1)
+}
+
+fn foo() {
+    apply(fun_name());
+}
+
+fn $0fun_name() -> fn(i32) -> i32 {
+    |x| x + 1
+}
+"#,
+        );
+    }
+
     #[test]
     fn no_args_from_binary_expr_in_module() {
         check_assist(
@@ -4626,6 +4821,161 @@ async fn $0fun_name() {
 
 async fn some_function() {
 
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_await_in_async_block_is_not_async() {
+        check_assist(
+            extract_function,
+            r#"
+//- minicore: future
+fn main() {
+    let f = $0async { some_function().await }$0;
+}
+
+async fn some_function() {}
+"#,
+            r#"
+fn main() {
+    let f = fun_name();
+}
+
+fn $0fun_name() -> impl Future<Output = ()> {
+    async { some_function().await }
+}
+
+async fn some_function() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_unsafe_operations() {
+        check_assist(
+            extract_function,
+            r#"
+fn foo(p: *const i32) -> i32 {
+    unsafe {
+        $0let v = *p;
+        v + 1$0
+    }
+}
+"#,
+            r#"
+fn foo(p: *const i32) -> i32 {
+    unsafe {
+        fun_name(p)
+    }
+}
+
+unsafe fn $0fun_name(p: *const i32) -> i32 {
+    let v = *p;
+    v + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extract_unsafe_block() {
+        check_assist(
+            extract_function,
+            r#"
+fn foo(p: *const i32) -> i32 {
+    $0let v = unsafe { *p };
+    v + 1$0
+}
+"#,
+            r#"
+fn foo(p: *const i32) -> i32 {
+    fun_name(p)
+}
+
+fn $0fun_name(p: *const i32) -> i32 {
+    let v = unsafe { *p };
+    v + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn preserve_generics_in_nested_types() {
+        check_assist(
+            extract_function,
+            r#"
+struct W<T>(T);
+fn foo<'a, T: Clone + 'a, U, const N: usize>(w: &W<T>, u: U, a: [u8; N]) {
+    $0let x = &w.0;
+    let b = a;$0
+}
+"#,
+            r#"
+struct W<T>(T);
+fn foo<'a, T: Clone + 'a, U, const N: usize>(w: &W<T>, u: U, a: [u8; N]) {
+    fun_name(w, a);
+}
+
+fn $0fun_name<'a, T: Clone + 'a, const N: usize>(w: &W<T>, a: [u8; N]) {
+    let x = &w.0;
+    let b = a;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn index_assignment_takes_mut_ref() {
+        check_assist(
+            extract_function,
+            r#"
+fn foo() {
+    let mut a = [1, 2];
+    $0a[0] = 3;$0
+    let b = a;
+}
+"#,
+            r#"
+fn foo() {
+    let mut a = [1, 2];
+    fun_name(&mut a);
+    let b = a;
+}
+
+fn $0fun_name(a: &mut [i32; 2]) {
+    a[0] = 3;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moved_value_is_taken_by_value() {
+        check_assist(
+            extract_function,
+            r#"
+struct S;
+fn consume(s: S) {}
+fn foo() {
+    let mut s = S;
+    $0consume(s);$0
+    s = S;
+}
+"#,
+            r#"
+struct S;
+fn consume(s: S) {}
+fn foo() {
+    let mut s = S;
+    fun_name(s);
+    s = S;
+}
+
+fn $0fun_name(s: S) {
+    consume(s);
 }
 "#,
         );
@@ -5602,7 +5952,6 @@ fn $0fun_name<T: Debug>(i: T) {
 
     #[test]
     fn dont_emit_type_with_hidden_lifetime_parameter() {
-        // FIXME: We should emit a `<T: Debug>` generic argument for the generated function
         check_assist(
             extract_function,
             r#"
@@ -5617,7 +5966,7 @@ fn func<T: Debug>(i: Struct<'_, T>) {
     fun_name(i);
 }
 
-fn $0fun_name(i: Struct<'_, T>) {
+fn $0fun_name<T: Debug>(i: Struct<'_, T>) {
     foo(i);
 }
 "#,
@@ -392,6 +392,7 @@ fn process_references(
                     ctx.config.insert_use.prefix_kind,
                     ctx.config.prefer_no_std,
                     ctx.config.prefer_prelude,
+                    ctx.config.insert_use.prefer_reexports,
                 );
                 if let Some(mut mod_path) = mod_path {
                     mod_path.pop_segment();
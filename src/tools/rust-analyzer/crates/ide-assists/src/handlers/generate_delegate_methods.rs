@@ -1,20 +1,23 @@
-use hir::{HasCrate, HasVisibility};
+use hir::{AsAssocItem, HasCrate, HasVisibility, HirDisplay};
 use ide_db::{path_transform::PathTransform, FxHashSet};
+use itertools::Itertools;
 use syntax::{
     ast::{
         self, edit_in_place::Indent, make, AstNode, HasGenericParams, HasName, HasVisibility as _,
     },
-    ted,
+    ted, SyntaxKind,
 };
 
 use crate::{
+    assist_context::SourceChangeBuilder,
     utils::{convert_param_list_to_arg_list, find_struct_impl},
     AssistContext, AssistId, AssistKind, Assists, GroupLabel,
 };
 
 // Assist: generate_delegate_methods
 //
-// Generate delegate methods.
+// Generate delegate methods, for one or all of the methods of a field, including the methods of
+// the traits in scope it implements.
 //
 // ```
 // struct Age(u8);
@@ -49,7 +52,6 @@
 // ```
 pub(crate) fn generate_delegate_methods(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
     let strukt = ctx.find_node_at_offset::<ast::Struct>()?;
-    let strukt_name = strukt.name()?;
     let current_module = ctx.sema.scope(strukt.syntax())?.module();
 
     let (field_name, field_ty, target) = match ctx.find_node_at_offset::<ast::RecordField>() {
@@ -67,150 +69,299 @@ pub(crate) fn generate_delegate_methods(acc: &mut Assists, ctx: &AssistContext<'
         }
     };
 
+    let db = ctx.db();
     let sema_field_ty = ctx.sema.resolve_type(&field_ty)?;
     let mut methods = vec![];
     let mut seen_names = FxHashSet::default();
 
-    for ty in sema_field_ty.autoderef(ctx.db()) {
-        let krate = ty.krate(ctx.db());
-        ty.iterate_assoc_items(ctx.db(), krate, |item| {
+    for (deref_depth, ty) in sema_field_ty.autoderef(db).enumerate() {
+        let krate = ty.krate(db);
+        ty.iterate_assoc_items(db, krate, |item| {
             if let hir::AssocItem::Function(f) = item {
-                let name = f.name(ctx.db());
-                if f.self_param(ctx.db()).is_some()
-                    && f.is_visible_from(ctx.db(), current_module)
+                let name = f.name(db);
+                if f.is_visible_from(db, current_module)
+                    && is_delegatable(ctx, f, deref_depth)
                     && seen_names.insert(name.clone())
                 {
-                    methods.push((name, f))
+                    methods.push((name, f, ty.clone()))
                 }
             }
             Option::<()>::None
         });
     }
-    methods.sort_by(|(a, _), (b, _)| a.cmp(b));
-    for (name, method) in methods {
-        let adt = ast::Adt::Struct(strukt.clone());
-        let name = name.display(ctx.db()).to_string();
+
+    // methods of the traits in scope, which the delegates can call as well
+    let traits = ctx.sema.scope(strukt.syntax())?.visible_traits().0;
+    let mut trait_methods = vec![];
+    for trait_ in traits.into_iter().map(hir::Trait::from) {
+        if trait_.type_or_const_param_count(db, false) != 0
+            || !sema_field_ty.impls_trait(db, trait_, &[])
+        {
+            continue;
+        }
+        for item in trait_.items(db) {
+            if let hir::AssocItem::Function(f) = item {
+                let name = f.name(db);
+                if is_delegatable(ctx, f, 0)
+                    && !mentions_self_type(ctx, f)
+                    && !seen_names.contains(&name)
+                {
+                    trait_methods.push((name, f, sema_field_ty.clone()));
+                }
+            }
+        }
+    }
+    // methods of several traits can share a name, and calling those would be ambiguous
+    let trait_method_counts = trait_methods.iter().map(|(name, ..)| name.clone()).counts();
+    methods.extend(trait_methods.into_iter().filter(|(name, ..)| trait_method_counts[name] == 1));
+    methods.sort_by(|(a, ..), (b, ..)| a.cmp(b));
+
+    let adt = ast::Adt::Struct(strukt.clone());
+    let mut delegates = vec![];
+    for (name, method, self_ty) in methods {
+        let name = name.display(db).to_string();
         // if `find_struct_impl` returns None, that means that a function named `name` already exists.
         let Some(impl_def) = find_struct_impl(ctx, &adt, std::slice::from_ref(&name)) else {
             continue;
         };
+        delegates.push(Delegate { name, method, self_ty, impl_def });
+    }
 
-        let field = make::ext::field_from_idents(["self", &field_name])?;
-
+    let group = GroupLabel("Generate delegate methods…".to_owned());
+    let id = AssistId("generate_delegate_methods", AssistKind::Generate);
+    if delegates.len() > 1 {
+        acc.add_group(
+            &group,
+            id,
+            format!("Generate delegates for all methods of `{field_name}`"),
+            target,
+            |edit| generate_delegates(ctx, edit, &strukt, &field_name, &delegates),
+        );
+    }
+    for delegate in &delegates {
         acc.add_group(
-            &GroupLabel("Generate delegate methods…".to_owned()),
-            AssistId("generate_delegate_methods", AssistKind::Generate),
-            format!("Generate delegate for `{field_name}.{name}()`",),
+            &group,
+            id,
+            format!("Generate delegate for `{field_name}.{}()`", delegate.name),
             target,
             |edit| {
-                // Create the function
-                let method_source = match ctx.sema.source(method) {
-                    Some(source) => {
-                        let v = source.value.clone_for_update();
-                        let source_scope = ctx.sema.scope(v.syntax());
-                        let target_scope = ctx.sema.scope(strukt.syntax());
-                        if let (Some(s), Some(t)) = (source_scope, target_scope) {
-                            PathTransform::generic_transformation(&t, &s).apply(v.syntax());
-                        }
-                        v
-                    }
-                    None => return,
-                };
-
-                let vis = method_source.visibility();
-                let is_async = method_source.async_token().is_some();
-                let is_const = method_source.const_token().is_some();
-                let is_unsafe = method_source.unsafe_token().is_some();
-
-                let fn_name = make::name(&name);
-
-                let type_params = method_source.generic_param_list();
-                let where_clause = method_source.where_clause();
-                let params =
-                    method_source.param_list().unwrap_or_else(|| make::param_list(None, []));
-
-                // compute the `body`
-                let arg_list = method_source
-                    .param_list()
-                    .map(convert_param_list_to_arg_list)
-                    .unwrap_or_else(|| make::arg_list([]));
-
-                let tail_expr = make::expr_method_call(field, make::name_ref(&name), arg_list);
-                let tail_expr_finished =
-                    if is_async { make::expr_await(tail_expr) } else { tail_expr };
-                let body = make::block_expr([], Some(tail_expr_finished));
-
-                let ret_type = method_source.ret_type();
-
-                let f = make::fn_(
-                    vis,
-                    fn_name,
-                    type_params,
-                    where_clause,
-                    params,
-                    body,
-                    ret_type,
-                    is_async,
-                    is_const,
-                    is_unsafe,
-                )
-                .clone_for_update();
-
-                // Get the impl to update, or create one if we need to.
-                let impl_def = match impl_def {
-                    Some(impl_def) => edit.make_mut(impl_def),
-                    None => {
-                        let name = &strukt_name.to_string();
-                        let ty_params = strukt.generic_param_list();
-                        let ty_args = ty_params.as_ref().map(|it| it.to_generic_args());
-                        let where_clause = strukt.where_clause();
-
-                        let impl_def = make::impl_(
-                            ty_params,
-                            ty_args,
-                            make::ty_path(make::ext::ident_path(name)),
-                            where_clause,
-                            None,
-                        )
-                        .clone_for_update();
-
-                        // Fixup impl_def indentation
-                        let indent = strukt.indent_level();
-                        impl_def.reindent_to(indent);
-
-                        // Insert the impl block.
-                        let strukt = edit.make_mut(strukt.clone());
-                        ted::insert_all(
-                            ted::Position::after(strukt.syntax()),
-                            vec![
-                                make::tokens::whitespace(&format!("\n\n{indent}")).into(),
-                                impl_def.syntax().clone().into(),
-                            ],
-                        );
-
-                        impl_def
-                    }
-                };
-
-                // Fixup function indentation.
-                // FIXME: Should really be handled by `AssocItemList::add_item`
-                f.reindent_to(impl_def.indent_level() + 1);
-
-                let assoc_items = impl_def.get_or_create_assoc_item_list();
-                assoc_items.add_item(f.clone().into());
-
-                if let Some(cap) = ctx.config.snippet_cap {
-                    edit.add_tabstop_before(cap, f)
-                }
+                generate_delegates(ctx, edit, &strukt, &field_name, std::slice::from_ref(delegate))
             },
-        )?;
+        );
     }
     Some(())
 }
 
+struct Delegate {
+    name: String,
+    method: hir::Function,
+    /// The type the method is called on, after autoderef.
+    self_ty: hir::Type,
+    /// The inherent impl of the struct to add the delegate to, if there is one.
+    impl_def: Option<ast::Impl>,
+}
+
+/// Whether a delegate for `method` can be written with the same receiver, when the method is
+/// called `deref_depth` derefs away from the field.
+fn is_delegatable(ctx: &AssistContext<'_>, method: hir::Function, deref_depth: usize) -> bool {
+    let db = ctx.db();
+    let Some(self_param) = method.self_param(db) else { return false };
+    // values can't be moved out of a dereference
+    if deref_depth > 0 && self_param.access(db) == hir::Access::Owned {
+        return false;
+    }
+    // receivers like `self: Box<Self>` can't be forwarded from a field
+    let Some(source) = self_param.source(db) else { return false };
+    match source.value.ty() {
+        None => true,
+        Some(ast::Type::RefType(ref_ty)) => ref_ty.ty().map_or(false, |it| is_self_ty(&it)),
+        Some(ty) => is_self_ty(&ty),
+    }
+}
+
+fn is_self_ty(ty: &ast::Type) -> bool {
+    matches!(ty, ast::Type::PathType(it) if it.path().map_or(false, |it| it.to_string() == "Self"))
+}
+
+/// Whether the signature of the trait method `method` refers to `Self` outside of its receiver,
+/// which would have to be the type of the field in the delegate.
+fn mentions_self_type(ctx: &AssistContext<'_>, method: hir::Function) -> bool {
+    let Some(source) = ctx.sema.source(method) else { return true };
+    let params = source.value.param_list().into_iter().flat_map(|it| it.params());
+    params
+        .map(|it| it.syntax().clone())
+        .chain(source.value.ret_type().map(|it| it.syntax().clone()))
+        .chain(source.value.generic_param_list().map(|it| it.syntax().clone()))
+        .chain(source.value.where_clause().map(|it| it.syntax().clone()))
+        .any(|node| node.descendants_with_tokens().any(|it| it.kind() == SyntaxKind::SELF_TYPE_KW))
+}
+
+fn generate_delegates(
+    ctx: &AssistContext<'_>,
+    edit: &mut SourceChangeBuilder,
+    strukt: &ast::Struct,
+    field_name: &str,
+    delegates: &[Delegate],
+) {
+    let Some(strukt_name) = strukt.name() else { return };
+    let Some(first) = delegates.first() else { return };
+
+    // Get the impl to update, or create one if we need to.
+    let impl_def = match &first.impl_def {
+        Some(impl_def) => edit.make_mut(impl_def.clone()),
+        None => {
+            let name = &strukt_name.to_string();
+            let ty_params = strukt.generic_param_list();
+            let ty_args = ty_params.as_ref().map(|it| it.to_generic_args());
+            let where_clause = strukt.where_clause();
+
+            let impl_def = make::impl_(
+                ty_params,
+                ty_args,
+                make::ty_path(make::ext::ident_path(name)),
+                where_clause,
+                None,
+            )
+            .clone_for_update();
+
+            // Fixup impl_def indentation
+            let indent = strukt.indent_level();
+            impl_def.reindent_to(indent);
+
+            // Insert the impl block.
+            let strukt = edit.make_mut(strukt.clone());
+            ted::insert_all(
+                ted::Position::after(strukt.syntax()),
+                vec![
+                    make::tokens::whitespace(&format!("\n\n{indent}")).into(),
+                    impl_def.syntax().clone().into(),
+                ],
+            );
+
+            impl_def
+        }
+    };
+
+    let assoc_items = impl_def.get_or_create_assoc_item_list();
+    let mut first_fn = None;
+    for delegate in delegates {
+        let Some(f) = delegate_fn(ctx, strukt, field_name, delegate) else { continue };
+
+        // Fixup function indentation.
+        // FIXME: Should really be handled by `AssocItemList::add_item`
+        f.reindent_to(impl_def.indent_level() + 1);
+        assoc_items.add_item(f.clone().into());
+        first_fn.get_or_insert(f);
+    }
+
+    if let (Some(cap), Some(f)) = (ctx.config.snippet_cap, first_fn) {
+        edit.add_tabstop_before(cap, f)
+    }
+}
+
+fn delegate_fn(
+    ctx: &AssistContext<'_>,
+    strukt: &ast::Struct,
+    field_name: &str,
+    delegate: &Delegate,
+) -> Option<ast::Fn> {
+    let Delegate { name, method, self_ty, .. } = delegate;
+    let field = make::ext::field_from_idents(["self", field_name])?;
+
+    // Create the function
+    let source = ctx.sema.source(*method)?;
+    let method_source = source.value.clone_for_update();
+    let source_scope = ctx.sema.scope(source.value.syntax());
+    let target_scope = ctx.sema.scope(strukt.syntax());
+    if let (Some(s), Some(t)) = (source_scope, target_scope) {
+        let transform = match impl_generic_args(ctx, *method, self_ty, t.module()) {
+            Some((impl_, args)) => PathTransform::impl_transformation(&t, &s, impl_, args),
+            None => PathTransform::generic_transformation(&t, &s),
+        };
+        transform.apply(method_source.syntax());
+    }
+
+    let vis = method_source.visibility();
+    let is_async = method_source.async_token().is_some();
+    let is_const = method_source.const_token().is_some();
+    let is_unsafe = method_source.unsafe_token().is_some();
+
+    let fn_name = make::name(name);
+
+    let type_params = method_source.generic_param_list();
+    let where_clause = method_source.where_clause();
+    let params = method_source.param_list().unwrap_or_else(|| make::param_list(None, []));
+
+    // compute the `body`
+    let arg_list = method_source
+        .param_list()
+        .map(convert_param_list_to_arg_list)
+        .unwrap_or_else(|| make::arg_list([]));
+
+    let tail_expr = make::expr_method_call(field, make::name_ref(name), arg_list);
+    let tail_expr_finished = if is_async { make::expr_await(tail_expr) } else { tail_expr };
+    let body = make::block_expr([], Some(tail_expr_finished));
+
+    let ret_type = method_source.ret_type();
+
+    let f = make::fn_(
+        vis,
+        fn_name,
+        type_params,
+        where_clause,
+        params,
+        body,
+        ret_type,
+        is_async,
+        is_const,
+        is_unsafe,
+    )
+    .clone_for_update();
+    Some(f)
+}
+
+/// The arguments of the type parameters of the inherent impl defining `method`, as instantiated by
+/// `self_ty`, e.g. `u32` for `T` when calling a method of `impl<T> Wrapper<T>` on `Wrapper<u32>`.
+fn impl_generic_args(
+    ctx: &AssistContext<'_>,
+    method: hir::Function,
+    self_ty: &hir::Type,
+    target_module: hir::Module,
+) -> Option<(hir::Impl, ast::GenericArgList)> {
+    let db = ctx.db();
+    let hir::AssocItemContainer::Impl(impl_) = method.as_assoc_item(db)?.container(db) else {
+        return None;
+    };
+    let impl_source = ctx.sema.source(impl_)?.value;
+    let type_params = impl_source.generic_param_list()?;
+    let ast::Type::PathType(impl_self_ty) = impl_source.self_ty()? else { return None };
+    let impl_self_args: Vec<_> = impl_self_ty
+        .path()?
+        .segment()?
+        .generic_arg_list()?
+        .generic_args()
+        .filter_map(|it| match it {
+            ast::GenericArg::TypeArg(it) => Some(it.ty()?.to_string()),
+            _ => None,
+        })
+        .collect();
+    let self_ty_args: Vec<_> = self_ty.type_arguments().collect();
+
+    let mut args = vec![];
+    for param in type_params.type_or_const_params() {
+        let ast::TypeOrConstParam::Type(param) = param else { return None };
+        let name = param.name()?.to_string();
+        let idx = impl_self_args.iter().position(|it| *it == name)?;
+        let arg =
+            self_ty_args.get(idx)?.display_source_code(db, target_module.into(), true).ok()?;
+        args.push(ast::GenericArg::TypeArg(make::type_arg(make::ty(&arg))));
+    }
+    Some((impl_, make::generic_arg_list(args)))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_assist, check_assist_not_applicable};
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
 
     use super::*;
 
@@ -714,4 +865,151 @@ fn age(&self) -> u8 { 0 }
 "#,
         );
     }
+
+    #[test]
+    fn test_generate_delegates_for_all_methods() {
+        check_assist_by_label(
+            generate_delegate_methods,
+            r#"
+struct Age(u8);
+impl Age {
+    fn age(&self) -> u8 {
+        self.0
+    }
+    fn set_age(&mut self, age: u8) {
+        self.0 = age;
+    }
+}
+
+struct Person {
+    ag$0e: Age,
+}"#,
+            r#"
+struct Age(u8);
+impl Age {
+    fn age(&self) -> u8 {
+        self.0
+    }
+    fn set_age(&mut self, age: u8) {
+        self.0 = age;
+    }
+}
+
+struct Person {
+    age: Age,
+}
+
+impl Person {
+    $0fn age(&self) -> u8 {
+        self.age.age()
+    }
+
+    fn set_age(&mut self, age: u8) {
+        self.age.set_age(age)
+    }
+}"#,
+            "Generate delegates for all methods of `age`",
+        );
+    }
+
+    #[test]
+    fn test_generate_delegate_substitutes_impl_generics() {
+        check_assist(
+            generate_delegate_methods,
+            r#"
+struct Wrapper<T>(T);
+impl<T> Wrapper<T> {
+    fn get(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Person {
+    ag$0e: Wrapper<u8>,
+}"#,
+            r#"
+struct Wrapper<T>(T);
+impl<T> Wrapper<T> {
+    fn get(&self) -> &T {
+        &self.0
+    }
+}
+
+struct Person {
+    age: Wrapper<u8>,
+}
+
+impl Person {
+    $0fn get(&self) -> &u8 {
+        self.age.get()
+    }
+}"#,
+        );
+    }
+
+    #[test]
+    fn test_generate_delegate_trait_method() {
+        check_assist(
+            generate_delegate_methods,
+            r#"
+trait Describe {
+    fn describe(&self, verbose: bool) -> u32;
+    fn same(&self, other: &Self) -> bool;
+}
+
+struct Age(u8);
+impl Describe for Age {
+    fn describe(&self, verbose: bool) -> u32 { 0 }
+    fn same(&self, other: &Self) -> bool { true }
+}
+
+struct Person {
+    ag$0e: Age,
+}"#,
+            r#"
+trait Describe {
+    fn describe(&self, verbose: bool) -> u32;
+    fn same(&self, other: &Self) -> bool;
+}
+
+struct Age(u8);
+impl Describe for Age {
+    fn describe(&self, verbose: bool) -> u32 { 0 }
+    fn same(&self, other: &Self) -> bool { true }
+}
+
+struct Person {
+    age: Age,
+}
+
+impl Person {
+    $0fn describe(&self, verbose: bool) -> u32 {
+        self.age.describe(verbose)
+    }
+}"#,
+        );
+    }
+
+    #[test]
+    fn test_generate_not_eligible_for_unforwardable_receivers() {
+        check_assist_not_applicable(
+            generate_delegate_methods,
+            r#"
+//- minicore: deref
+struct Box<T>(T);
+struct Age(u8);
+impl Age {
+    fn boxed(self: Box<Self>) -> u8 { 0 }
+}
+struct AgeDeref(Age);
+impl core::ops::Deref for AgeDeref { type Target = Age; }
+impl Age {
+    fn into_inner(self) -> u8 { self.0 }
+}
+
+struct Person {
+    ag$0e: AgeDeref,
+}"#,
+        );
+    }
 }
@@ -0,0 +1,524 @@
+use hir::{Access, ModuleDef, PathResolution};
+use ide_db::{famous_defs::FamousDefs, helpers::mod_path_to_ast, path_transform::PathTransform};
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{
+        self,
+        edit::IndentLevel,
+        edit_in_place::{AttrsOwnerEdit, Indent},
+        HasAttrs, HasGenericParams, HasModuleItem, HasName,
+    },
+    AstNode, SyntaxNode, TextSize,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: generate_mock_trait_impl
+//
+// Generates a test double implementing the trait, either recording the arguments of each call or
+// with `todo!()` bodies. It is placed in the `#[cfg(test)]` module next to the trait.
+//
+// ```
+// trait $0Clock {
+//     fn now(&self) -> u64;
+//     fn sleep(&mut self, millis: u64);
+// }
+// ```
+// ->
+// ```
+// trait Clock {
+//     fn now(&self) -> u64;
+//     fn sleep(&mut self, millis: u64);
+// }
+//
+// #[cfg(test)]
+// mod tests {
+//     use super::*;
+//
+//     #[derive(Default)]
+//     struct MockClock {
+//         now_calls: std::cell::RefCell<Vec<()>>,
+//         sleep_calls: Vec<u64>,
+//     }
+//
+//     impl Clock for MockClock {
+//         fn now(&self) -> u64 {
+//             self.now_calls.borrow_mut().push(());
+//             todo!()
+//         }
+//
+//         fn sleep(&mut self, millis: u64) {
+//             self.sleep_calls.push(millis);
+//         }
+//     }
+// }
+// ```
+pub(crate) fn generate_mock_trait_impl(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let (trait_, anchor) = trait_at_cursor(ctx)?;
+    // Picking the generic arguments is left to the user.
+    if trait_.type_or_const_param_count(ctx.db(), false) != 0 {
+        return None;
+    }
+    let trait_src = ctx.sema.source(trait_)?;
+    if trait_src.file_id.is_macro() {
+        return None;
+    }
+    let items: Vec<_> = trait_src
+        .value
+        .assoc_item_list()?
+        .assoc_items()
+        .filter(|item| match item {
+            ast::AssocItem::Fn(it) => it.body().is_none(),
+            ast::AssocItem::Const(it) => it.body().is_none(),
+            ast::AssocItem::TypeAlias(it) => it.ty().is_none(),
+            ast::AssocItem::MacroCall(_) => false,
+        })
+        .collect();
+
+    let container = anchor.ancestors().find(|it| {
+        ast::SourceFile::can_cast(it.kind())
+            || ast::ItemList::cast(it.clone()).map_or(false, |it| {
+                it.syntax().parent().map_or(false, |p| ast::Module::can_cast(p.kind()))
+            })
+    })?;
+    let target_scope = ctx.sema.scope(&container)?;
+    let source_scope = ctx.sema.scope(trait_src.value.syntax())?;
+    let trait_path = mod_path_to_ast(&target_scope.module().find_use_path(
+        ctx.db(),
+        ModuleDef::Trait(trait_),
+        ctx.config.prefer_no_std,
+        ctx.config.prefer_prelude,
+    )?);
+    let mock_name = format!("Mock{}", trait_.name(ctx.db()).display(ctx.db()));
+    let transform = PathTransform::generic_transformation(&target_scope, &source_scope);
+
+    let (offset, module_indent, test_module) = insert_position(&container)?;
+    let target = anchor.text_range();
+    for recording in [true, false] {
+        let label = if recording {
+            format!("Generate mock implementation of `{trait_path}` recording calls")
+        } else {
+            format!("Generate stub implementation of `{trait_path}`")
+        };
+        acc.add(
+            AssistId("generate_mock_trait_impl", AssistKind::Generate),
+            label,
+            target,
+            |edit| {
+                let mut fields = Vec::new();
+                let impl_items = items
+                    .iter()
+                    .filter_map(|original| {
+                        let source_indent = IndentLevel::from_node(original.syntax());
+                        let item = original.clone_for_update();
+                        transform.apply(item.syntax());
+                        item.remove_attrs_and_docs();
+                        item.dedent(source_indent);
+                        let decl = item.syntax().text().to_string();
+                        let decl = decl.trim_end_matches(';');
+                        Some(match &item {
+                            ast::AssocItem::Fn(fn_) => {
+                                let ast::AssocItem::Fn(original) = original else { return None };
+                                let func = ctx.sema.to_def(original)?;
+                                let mut body = String::new();
+                                if recording {
+                                    if let Some(call) = record_call(ctx, func, fn_) {
+                                        format_to!(body, "\n    self.{}", call.field);
+                                        if call.shared {
+                                            body.push_str(".borrow_mut()");
+                                        }
+                                        format_to!(body, ".push({});", call.args);
+                                        let ty = if call.shared {
+                                            format!("std::cell::RefCell<Vec<{}>>", call.ty)
+                                        } else {
+                                            format!("Vec<{}>", call.ty)
+                                        };
+                                        fields.push(format!("{}: {ty},", call.field));
+                                    }
+                                }
+                                if let Some(ret) = return_expr(ctx, func, recording) {
+                                    format_to!(body, "\n    {ret}");
+                                }
+                                format!("{decl} {{{body}\n}}")
+                            }
+                            ast::AssocItem::Const(_) => format!("{decl} = todo!();"),
+                            ast::AssocItem::TypeAlias(type_alias) => {
+                                let name = type_alias.name()?;
+                                let generics = type_alias
+                                    .generic_param_list()
+                                    .map(|it| it.to_string())
+                                    .unwrap_or_default();
+                                format!("type {name}{generics} = ();")
+                            }
+                            ast::AssocItem::MacroCall(_) => return None,
+                        })
+                    })
+                    .map(|item| indent_text(&item, IndentLevel(1)))
+                    .join("\n\n");
+
+                let mut code = String::new();
+                if recording {
+                    code.push_str("#[derive(Default)]\n");
+                }
+                if fields.is_empty() {
+                    format_to!(code, "struct {mock_name};\n\n");
+                } else {
+                    let fields = fields.iter().map(|it| format!("    {it}\n")).join("");
+                    format_to!(code, "struct {mock_name} {{\n{fields}}}\n\n");
+                }
+                format_to!(code, "impl {trait_path} for {mock_name} {{\n{impl_items}\n}}");
+
+                let text = if test_module {
+                    indent_text(&code, module_indent + 1)
+                } else {
+                    let module = format!(
+                        "#[cfg(test)]\nmod tests {{\n    use super::*;\n\n{}\n}}",
+                        indent_text(&code, IndentLevel(1))
+                    );
+                    indent_text(&module, module_indent)
+                };
+                edit.insert(offset, format!("\n\n{text}"));
+            },
+        );
+    }
+    Some(())
+}
+
+fn trait_at_cursor(ctx: &AssistContext<'_>) -> Option<(hir::Trait, SyntaxNode)> {
+    if let Some(trait_) = ctx.find_node_at_offset::<ast::Trait>() {
+        // Only offer the assist on the trait's header, not on all of its items.
+        let name = trait_.name()?;
+        let header_start = trait_.trait_token()?.text_range().start();
+        if (header_start..=name.syntax().text_range().end()).contains(&ctx.offset()) {
+            return Some((ctx.sema.to_def(&trait_)?, trait_.syntax().clone()));
+        }
+        return None;
+    }
+    let dyn_trait = ctx.find_node_at_offset::<ast::DynTraitType>()?;
+    let trait_ = dyn_trait.type_bound_list()?.bounds().find_map(|bound| {
+        let ast::Type::PathType(path_type) = bound.ty()? else { return None };
+        match ctx.sema.resolve_path(&path_type.path()?)? {
+            PathResolution::Def(ModuleDef::Trait(it)) if !it.is_auto(ctx.db()) => Some(it),
+            _ => None,
+        }
+    })?;
+    Some((trait_, dyn_trait.syntax().clone()))
+}
+
+/// Returns the offset to insert at, the indentation of the surrounding module, and whether the
+/// offset is inside an existing test module.
+fn insert_position(container: &SyntaxNode) -> Option<(TextSize, IndentLevel, bool)> {
+    let (items, indent, end): (Vec<ast::Item>, _, _) = match_container(container)?;
+    let test_module = items.iter().find_map(|item| match item {
+        ast::Item::Module(module)
+            if module.name().map_or(false, |it| it.text() == "tests")
+                && module.attrs().any(|attr| attr.syntax().text() == "#[cfg(test)]") =>
+        {
+            module.item_list()
+        }
+        _ => None,
+    });
+    if let Some(item_list) = test_module {
+        let offset = match item_list.items().last() {
+            Some(item) => item.syntax().text_range().end(),
+            None => item_list.l_curly_token()?.text_range().end(),
+        };
+        return Some((offset, IndentLevel::from_node(item_list.syntax()), true));
+    }
+    let offset = items.last().map_or(end, |it| it.syntax().text_range().end());
+    Some((offset, indent, false))
+}
+
+fn match_container(container: &SyntaxNode) -> Option<(Vec<ast::Item>, IndentLevel, TextSize)> {
+    if let Some(file) = ast::SourceFile::cast(container.clone()) {
+        return Some((file.items().collect(), IndentLevel(0), file.syntax().text_range().end()));
+    }
+    let item_list = ast::ItemList::cast(container.clone())?;
+    let indent = IndentLevel::from_node(item_list.syntax()) + 1;
+    let end = item_list.l_curly_token()?.text_range().end();
+    Some((item_list.items().collect(), indent, end))
+}
+
+struct RecordedCall {
+    field: String,
+    ty: String,
+    args: String,
+    /// Whether the method only takes `&self`, so the calls have to be recorded in a `RefCell`.
+    shared: bool,
+}
+
+/// Records the arguments that can be stored without borrowing from the caller.
+fn record_call(
+    ctx: &AssistContext<'_>,
+    func: hir::Function,
+    fn_: &ast::Fn,
+) -> Option<RecordedCall> {
+    let db = ctx.db();
+    let shared = match func.self_param(db)?.access(db) {
+        Access::Shared => true,
+        Access::Exclusive | Access::Owned => false,
+    };
+    let famous_defs = FamousDefs(&ctx.sema, func.module(db).krate());
+    let clone_trait = famous_defs.core_clone_Clone();
+
+    let (tys, args): (Vec<_>, Vec<_>) = fn_
+        .param_list()?
+        .params()
+        .zip(func.params_without_self(db))
+        .filter_map(|(param, hir_param)| {
+            let ast::Pat::IdentPat(pat) = param.pat()? else { return None };
+            let name = pat.name()?;
+            let ty = hir_param.ty();
+            if !ty.generic_params(db).is_empty() || ty.contains_unknown() {
+                return None;
+            }
+            match param.ty()? {
+                ast::Type::RefType(ref_type) => {
+                    let (inner, _) = ty.as_reference()?;
+                    if inner.as_builtin().map_or(false, |it| it.is_str()) {
+                        return Some(("String".to_owned(), format!("{name}.to_owned()")));
+                    }
+                    match ref_type.ty()? {
+                        ast::Type::SliceType(slice) => {
+                            Some((format!("Vec<{}>", slice.ty()?), format!("{name}.to_vec()")))
+                        }
+                        inner_ty
+                            if !inner.contains_reference(db)
+                                && clone_trait
+                                    .map_or(false, |it| inner.impls_trait(db, it, &[])) =>
+                        {
+                            Some((inner_ty.to_string(), format!("{name}.clone()")))
+                        }
+                        _ => None,
+                    }
+                }
+                param_ty if !ty.contains_reference(db) => {
+                    Some((param_ty.to_string(), name.to_string()))
+                }
+                _ => None,
+            }
+        })
+        .unzip();
+
+    let (ty, args) = match (&*tys, &*args) {
+        ([ty], [arg]) => (ty.clone(), arg.clone()),
+        _ => (format!("({})", tys.join(", ")), format!("({})", args.join(", "))),
+    };
+    Some(RecordedCall { field: format!("{}_calls", fn_.name()?), ty, args, shared })
+}
+
+fn return_expr(ctx: &AssistContext<'_>, func: hir::Function, recording: bool) -> Option<String> {
+    let ret_ty = func.ret_type(ctx.db());
+    if ret_ty.is_unit() {
+        return (!recording).then(|| "todo!()".to_owned());
+    }
+    let famous_defs = FamousDefs(&ctx.sema, func.module(ctx.db()).krate());
+    let is_default = famous_defs
+        .core_default_Default()
+        .map_or(false, |it| ret_ty.impls_trait(ctx.db(), it, &[]));
+    Some(if recording && is_default { "Default::default()" } else { "todo!()" }.to_owned())
+}
+
+fn indent_text(text: &str, indent: IndentLevel) -> String {
+    text.lines()
+        .map(|line| if line.is_empty() { line.to_owned() } else { format!("{indent}{line}") })
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn records_owned_copies_of_arguments() {
+        check_assist_by_label(
+            generate_mock_trait_impl,
+            r#"
+//- minicore: clone, default, sized
+struct Key(u32);
+impl Clone for Key {
+    fn clone(&self) -> Self { Key(self.0) }
+}
+struct Value;
+impl Default for Value {
+    fn default() -> Self { Value }
+}
+
+trait $0Store {
+    /// Stores a value.
+    fn put(&mut self, name: &str, bytes: &[u8], key: &Key, value: u32);
+    fn get(&self, key: &Key) -> Value;
+    fn visit(&mut self, f: impl FnMut(u32), _: bool);
+    fn provided(&self) {}
+}
+"#,
+            r#"
+struct Key(u32);
+impl Clone for Key {
+    fn clone(&self) -> Self { Key(self.0) }
+}
+struct Value;
+impl Default for Value {
+    fn default() -> Self { Value }
+}
+
+trait Store {
+    /// Stores a value.
+    fn put(&mut self, name: &str, bytes: &[u8], key: &Key, value: u32);
+    fn get(&self, key: &Key) -> Value;
+    fn visit(&mut self, f: impl FnMut(u32), _: bool);
+    fn provided(&self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockStore {
+        put_calls: Vec<(String, Vec<u8>, Key, u32)>,
+        get_calls: std::cell::RefCell<Vec<Key>>,
+        visit_calls: Vec<()>,
+    }
+
+    impl Store for MockStore {
+        fn put(&mut self, name: &str, bytes: &[u8], key: &Key, value: u32) {
+            self.put_calls.push((name.to_owned(), bytes.to_vec(), key.clone(), value));
+        }
+
+        fn get(&self, key: &Key) -> Value {
+            self.get_calls.borrow_mut().push(key.clone());
+            Default::default()
+        }
+
+        fn visit(&mut self, f: impl FnMut(u32), _: bool) {
+            self.visit_calls.push(());
+        }
+    }
+}
+"#,
+            "Generate mock implementation of `Store` recording calls",
+        );
+    }
+
+    #[test]
+    fn generates_stubs_into_existing_test_module() {
+        check_assist_by_label(
+            generate_mock_trait_impl,
+            r#"
+trait $0Clock {
+    type Instant;
+    const EPOCH: u64;
+    fn now(&self) -> Self::Instant;
+    fn new() -> Self where Self: Sized;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper() {}
+}
+"#,
+            r#"
+trait Clock {
+    type Instant;
+    const EPOCH: u64;
+    fn now(&self) -> Self::Instant;
+    fn new() -> Self where Self: Sized;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn helper() {}
+
+    struct MockClock;
+
+    impl Clock for MockClock {
+        type Instant = ();
+
+        const EPOCH: u64 = todo!();
+
+        fn now(&self) -> Self::Instant {
+            todo!()
+        }
+
+        fn new() -> Self where Self: Sized {
+            todo!()
+        }
+    }
+}
+"#,
+            "Generate stub implementation of `Clock`",
+        );
+    }
+
+    #[test]
+    fn generates_from_dyn_usage_in_nested_module() {
+        check_assist(
+            generate_mock_trait_impl,
+            r#"
+mod clock {
+    pub struct Time;
+    pub trait Clock {
+        fn now(&self) -> Time;
+    }
+}
+
+mod app {
+    pub fn run(clock: &dyn crate::clock::Clo$0ck) {}
+}
+"#,
+            r#"
+mod clock {
+    pub struct Time;
+    pub trait Clock {
+        fn now(&self) -> Time;
+    }
+}
+
+mod app {
+    pub fn run(clock: &dyn crate::clock::Clock) {}
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        struct MockClock;
+
+        impl crate::clock::Clock for MockClock {
+            fn now(&self) -> crate::clock::Time {
+                todo!()
+            }
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_trait_body_or_for_generic_traits() {
+        check_assist_not_applicable(
+            generate_mock_trait_impl,
+            r#"
+trait Clock {
+    fn now(&self) -> u64$0;
+}
+"#,
+        );
+        check_assist_not_applicable(
+            generate_mock_trait_impl,
+            r#"
+trait $0Clock<T> {
+    fn now(&self) -> T;
+}
+"#,
+        );
+    }
+}
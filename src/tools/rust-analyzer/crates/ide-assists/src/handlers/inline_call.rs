@@ -1,8 +1,8 @@
-use std::collections::BTreeSet;
+use std::iter;
 
 use ast::make;
 use either::Either;
-use hir::{db::HirDatabase, PathResolution, Semantics, TypeInfo};
+use hir::{db::HirDatabase, AsAssocItem, HirDisplay, PathResolution, Semantics, TypeInfo};
 use ide_db::{
     base_db::{FileId, FileRange},
     defs::Definition,
@@ -10,12 +10,15 @@
     path_transform::PathTransform,
     search::{FileReference, FileReferenceNode, SearchScope},
     source_change::SourceChangeBuilder,
-    syntax_helpers::{insert_whitespace_into_node::insert_ws_into, node_ext::expr_as_name_ref},
-    RootDatabase,
+    syntax_helpers::{
+        insert_whitespace_into_node::insert_ws_into,
+        node_ext::{expr_as_name_ref, walk_expr},
+    },
+    FxHashSet, RootDatabase,
 };
 use itertools::{izip, Itertools};
 use syntax::{
-    ast::{self, edit::IndentLevel, edit_in_place::Indent, HasArgList, Pat, PathExpr},
+    ast::{self, edit::IndentLevel, edit_in_place::Indent, HasArgList, HasName, Pat, PathExpr},
     ted, AstNode, NodeOrToken, SyntaxKind,
 };
 
@@ -324,35 +327,59 @@ fn inline(
             .unwrap_or_default()
             .into_iter()
     };
-    let param_use_nodes: Vec<Vec<_>> = params
+    // `None` if the usages of the parameter aren't all known
+    let param_use_nodes: Vec<Option<Vec<_>>> = params
         .iter()
         .map(|(pat, _, param)| {
             if !matches!(pat, ast::Pat::IdentPat(pat) if pat.is_simple_ident()) {
-                return Vec::new();
+                return None;
             }
             // FIXME: we need to fetch all locals declared in the parameter here
             // not only the local if it is a simple binding
-            match param.as_local(sema.db) {
-                Some(l) => usages_for_locals(l)
-                    .map(|FileReference { name, range, .. }| match name {
-                        FileReferenceNode::NameRef(_) => body
-                            .syntax()
-                            .covering_element(range)
-                            .ancestors()
-                            .nth(3)
-                            .and_then(ast::PathExpr::cast),
-                        _ => None,
-                    })
-                    .collect::<Option<Vec<_>>>()
-                    .unwrap_or_default(),
-                None => Vec::new(),
-            }
+            usages_for_locals(param.as_local(sema.db)?)
+                .map(|FileReference { name, range, .. }| match name {
+                    FileReferenceNode::NameRef(_) => body
+                        .syntax()
+                        .covering_element(range)
+                        .ancestors()
+                        .nth(3)
+                        .and_then(ast::PathExpr::cast),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
         })
         .collect();
 
+    // grab all of the local variable declarations in the function
+    let func_let_vars: FxHashSet<String> = fn_body
+        .syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter_map(|ident_pat| Some(ident_pat.name()?.text().to_string()))
+        .collect();
+    let arg_names: Vec<FxHashSet<String>> = arguments.iter().map(names_in_expr).collect();
+    // A name bound by a `let` statement for a parameter can't be used by the other arguments.
+    let is_name_taken = |name: &str, arg_idx: usize| {
+        func_let_vars.contains(name)
+            || arg_names
+                .iter()
+                .enumerate()
+                .any(|(idx, names)| idx != arg_idx && names.contains(name))
+    };
+    let fresh_name = |name: &str, arg_idx: usize| {
+        (1..)
+            .map(|idx| format!("{name}_{idx}"))
+            .find(|it| !is_name_taken(it, arg_idx) && !arg_names[arg_idx].contains(it))
+            .expect("there are infinitely many candidates")
+    };
+    let this_name = match is_name_taken("this", 0) {
+        true => fresh_name("this", 0),
+        false => "this".to_owned(),
+    };
+
     if function.self_param(sema.db).is_some() {
         let this = || {
-            make::name_ref("this")
+            make::name_ref(&this_name)
                 .syntax()
                 .clone_for_update()
                 .first_token()
@@ -378,42 +405,96 @@ fn inline(
     {
         if !node.syntax().ancestors().any(|anc| &anc == imp.syntax()) {
             if let Some(t) = imp.self_ty() {
+                let type_path = match &t {
+                    ast::Type::PathType(path_type) => path_type.path(),
+                    _ => None,
+                };
+                // `Foo<T>::new()` has to be written as `Foo::<T>::new()` in expressions
+                let expr_path = type_path.as_ref().and_then(|path| {
+                    let path = path.clone_subtree().clone_for_update();
+                    let generic_args: Vec<_> = path
+                        .syntax()
+                        .descendants()
+                        .filter_map(ast::GenericArgList::cast)
+                        .filter(|it| it.coloncolon_token().is_none())
+                        .filter(|it| {
+                            it.syntax().parent().and_then(ast::PathSegment::cast).is_some()
+                        })
+                        .collect();
+                    if generic_args.is_empty() {
+                        return None;
+                    }
+                    for args in generic_args {
+                        let turbofish = make::turbofish_generic_arg_list(args.generic_args());
+                        ted::replace(args.syntax(), turbofish.clone_for_update().syntax());
+                    }
+                    Some(path)
+                });
                 while let Some(self_tok) = body
                     .syntax()
                     .descendants_with_tokens()
                     .filter_map(NodeOrToken::into_token)
                     .find(|tok| tok.kind() == SyntaxKind::SELF_TYPE_KW)
                 {
-                    let replace_with = t.clone_subtree().syntax().clone_for_update();
-                    ted::replace(self_tok, replace_with);
-                }
-            }
-        }
-    }
-
-    let mut func_let_vars: BTreeSet<String> = BTreeSet::new();
-
-    // grab all of the local variable declarations in the function
-    for stmt in fn_body.statements() {
-        if let Some(let_stmt) = ast::LetStmt::cast(stmt.syntax().to_owned()) {
-            for has_token in let_stmt.syntax().children_with_tokens() {
-                if let Some(node) = has_token.as_node() {
-                    if let Some(ident_pat) = ast::IdentPat::cast(node.to_owned()) {
-                        func_let_vars.insert(ident_pat.syntax().text().to_string());
+                    let self_path = self_tok
+                        .parent_ancestors()
+                        .find_map(ast::PathSegment::cast)
+                        .map(|segment| segment.parent_path());
+                    let replace_with = self_path.as_ref().and_then(|self_path| {
+                        let parent = self_path.top_path().syntax().parent();
+                        match parent.map_or(false, |it| ast::PathType::can_cast(it.kind())) {
+                            true => type_path.as_ref(),
+                            false => expr_path.as_ref().or(type_path.as_ref()),
+                        }
+                    });
+                    match self_path.zip(replace_with) {
+                        Some((self_path, replace_with)) => {
+                            let replace_with = replace_with.clone_subtree().clone_for_update();
+                            ted::replace(self_path.syntax(), replace_with.syntax());
+                        }
+                        None => {
+                            let replace_with = t.clone_subtree().syntax().clone_for_update();
+                            ted::replace(self_tok, replace_with);
+                        }
                     }
                 }
             }
         }
     }
 
+    let target_scope = sema.scope(node.syntax());
+    let source_scope = sema.scope(fn_body.syntax());
+    let generic_arg_list = generic_arg_list
+        .clone()
+        .or_else(|| infer_generic_args(sema, function, node, target_scope.as_ref()?.module()));
+    let function_transform = generic_arg_list.and_then(|generic_arg_list| {
+        let (target, source) = target_scope.as_ref().zip(source_scope.as_ref())?;
+        Some(PathTransform::function_call(target, source, function, generic_arg_list))
+    });
+    let impl_transform = infer_impl_generic_args(sema, function, node).and_then(|(imp, args)| {
+        let (target, source) = target_scope.as_ref().zip(source_scope.as_ref())?;
+        Some(PathTransform::impl_transformation(target, source, imp, args))
+    });
+
     let mut let_stmts = Vec::new();
 
     // Inline parameter expressions or generate `let` statements depending on whether inlining works or not.
-    for ((pat, param_ty, param), usages, expr) in izip!(params, param_use_nodes, arguments) {
+    for (arg_idx, ((pat, param_ty, param), usages, expr)) in
+        izip!(params, param_use_nodes, arguments).enumerate()
+    {
         // izip confuses RA due to our lack of hygiene info currently losing us type info causing incorrect errors
-        let usages: &[ast::PathExpr] = &usages;
+        let usages: Option<&[ast::PathExpr]> = usages.as_deref();
         let expr: &ast::Expr = expr;
 
+        let inline_direct = |usage, replacement: &ast::Expr| {
+            if let Some(field) = path_expr_as_record_field(usage) {
+                cov_mark::hit!(inline_call_inline_direct_field);
+                field.replace_expr(replacement.clone_for_update());
+            } else {
+                ted::replace(usage.syntax(), &replacement.syntax().clone_for_update());
+            }
+        };
+
         let mut insert_let_stmt = || {
             let param_ty = match param_ty {
                 None => None,
@@ -431,6 +512,11 @@ fn inline(
                     }
                 }
             };
+            if let Some(param_ty) = &param_ty {
+                function_transform.iter().chain(&impl_transform).for_each(|transform| {
+                    transform.apply(param_ty.syntax());
+                });
+            }
             let ty: Option<syntax::ast::Type> =
                 sema.type_of_expr(expr).filter(TypeInfo::has_adjustment).and(param_ty);
 
@@ -440,7 +526,7 @@ fn inline(
                 .is_some_and(|name| name == "self");
 
             if is_self {
-                let mut this_pat = make::ident_pat(false, false, make::name("this"));
+                let mut this_pat = make::ident_pat(false, false, make::name(&this_name));
                 let mut expr = expr.clone();
                 if let Pat::IdentPat(pat) = pat {
                     match (pat.ref_token(), pat.mut_token()) {
@@ -448,7 +534,7 @@ fn inline(
                         (None, None) => {}
                         // mut self => let mut this = obj
                         (None, Some(_)) => {
-                            this_pat = make::ident_pat(false, true, make::name("this"));
+                            this_pat = make::ident_pat(false, true, make::name(&this_name));
                         }
                         // &self => let this = &obj
                         (Some(_), None) => {
@@ -471,29 +557,31 @@ fn inline(
                 let_stmts
                     .push(make::let_stmt(this_pat.into(), ty, Some(expr)).clone_for_update().into())
             } else {
-                let_stmts.push(
-                    make::let_stmt(pat.clone(), ty, Some(expr.clone())).clone_for_update().into(),
-                );
+                let mut pat = pat.clone();
+                let name = param.as_local(sema.db).map(|it| it.name(sema.db));
+                if let (Some(name), Some(usages)) = (name, usages) {
+                    let name = name.display(sema.db).to_string();
+                    if is_name_taken(&name, arg_idx) {
+                        cov_mark::hit!(inline_call_rename_param);
+                        let new_name = fresh_name(&name, arg_idx);
+                        pat = make::ident_pat(false, false, make::name(&new_name)).into();
+                        let path = make::expr_path(make::ext::ident_path(&new_name));
+                        usages.iter().for_each(|usage| inline_direct(usage, &path));
+                    }
+                }
+                let_stmts
+                    .push(make::let_stmt(pat, ty, Some(expr.clone())).clone_for_update().into());
             }
         };
 
-        // check if there is a local var in the function that conflicts with parameter
-        // if it does then emit a let statement and continue
-        if func_let_vars.contains(&expr.syntax().text().to_string()) {
+        // check if there is a local var in the function that conflicts with a name used by the
+        // argument, if it does then emit a let statement and continue
+        if arg_names[arg_idx].iter().any(|name| func_let_vars.contains(name)) {
             insert_let_stmt();
             continue;
         }
 
-        let inline_direct = |usage, replacement: &ast::Expr| {
-            if let Some(field) = path_expr_as_record_field(usage) {
-                cov_mark::hit!(inline_call_inline_direct_field);
-                field.replace_expr(replacement.clone_for_update());
-            } else {
-                ted::replace(usage.syntax(), &replacement.syntax().clone_for_update());
-            }
-        };
-
-        match usages {
+        match usages.unwrap_or_default() {
             // inline single use closure arguments
             [usage]
                 if matches!(expr, ast::Expr::ClosureExpr(_))
@@ -509,7 +597,7 @@ fn inline(
                 inline_direct(usage, expr);
             }
             // inline direct local arguments
-            [_, ..] if expr_as_name_ref(expr).is_some() => {
+            usages @ [_, ..] if expr_as_name_ref(expr).is_some() => {
                 cov_mark::hit!(inline_call_inline_locals);
                 usages.iter().for_each(|usage| inline_direct(usage, expr));
             }
@@ -520,15 +608,13 @@ fn inline(
         }
     }
 
-    if let Some(generic_arg_list) = generic_arg_list.clone() {
-        if let Some((target, source)) = &sema.scope(node.syntax()).zip(sema.scope(fn_body.syntax()))
-        {
-            PathTransform::function_call(target, source, function, generic_arg_list)
-                .apply(body.syntax());
-        }
-    }
+    function_transform.iter().chain(&impl_transform).for_each(|transform| {
+        transform.apply(body.syntax());
+    });
 
     let is_async_fn = function.is_async(sema.db);
+    // `return`s in an async fn return from the async block the body becomes
+    let label = if is_async_fn { None } else { replace_returns_with_breaks(&body) };
     if is_async_fn {
         cov_mark::hit!(inline_call_async_fn);
         body = make::async_move_block_expr(body.statements(), body.tail_expr()).clone_for_update();
@@ -552,7 +638,7 @@ fn inline(
     };
     body.reindent_to(original_indentation);
 
-    let no_stmts = body.statements().next().is_none();
+    let no_stmts = body.statements().next().is_none() && label.is_none();
     match body.tail_expr() {
         Some(expr) if matches!(expr, ast::Expr::ClosureExpr(_)) && no_stmts => {
             make::expr_paren(expr).clone_for_update()
@@ -572,6 +658,130 @@ fn inline(
     }
 }
 
+/// The names `expr` refers to.
+fn names_in_expr(expr: &ast::Expr) -> FxHashSet<String> {
+    expr.syntax()
+        .descendants_with_tokens()
+        .filter_map(NodeOrToken::into_token)
+        .filter(|it| matches!(it.kind(), SyntaxKind::IDENT | SyntaxKind::SELF_KW))
+        .map(|it| it.text().to_owned())
+        .collect()
+}
+
+/// The generic arguments of a call to `function` for its own generic parameters, inferred from the
+/// call site.
+fn infer_generic_args(
+    sema: &Semantics<'_, RootDatabase>,
+    function: hir::Function,
+    node: &ast::CallableExpr,
+    target_module: hir::Module,
+) -> Option<ast::GenericArgList> {
+    let db = sema.db;
+    let params = hir::GenericDef::from(function).type_or_const_params(db);
+    if params.is_empty() {
+        return None;
+    }
+    let args = callable_of_call(sema, node)?.generic_args();
+    let args = params.into_iter().zip(args).map(|(param, arg)| {
+        let param = param.as_type_param(db)?;
+        // `impl Trait` parameters can't be referred to in the body
+        if param.is_implicit(db) {
+            return Some(make::type_arg(make::ty_placeholder()).into());
+        }
+        type_arg(db, arg?, target_module)
+    });
+    Some(make::generic_arg_list(args.collect::<Option<Vec<_>>>()?))
+}
+
+/// The impl of `function` together with the generic arguments for its parameters inferred from
+/// the call site, if `function` is an inherent method of a generic impl.
+fn infer_impl_generic_args(
+    sema: &Semantics<'_, RootDatabase>,
+    function: hir::Function,
+    node: &ast::CallableExpr,
+) -> Option<(hir::Impl, ast::GenericArgList)> {
+    let db = sema.db;
+    let hir::AssocItemContainer::Impl(imp) = function.as_assoc_item(db)?.container(db) else {
+        return None;
+    };
+    let params = hir::GenericDef::from(imp).type_or_const_params(db);
+    if params.is_empty() || imp.trait_(db).is_some() {
+        return None;
+    }
+    let callable = callable_of_call(sema, node)?;
+    // the arguments of a trait method call are those of the trait, not of the impl
+    match callable.kind() {
+        hir::CallableKind::Function(callee) if callee == function => (),
+        _ => return None,
+    }
+    let target_module = sema.scope(node.syntax())?.module();
+    let own_params = hir::GenericDef::from(function).type_or_const_params(db).len();
+    let args = params.into_iter().zip(callable.generic_args().into_iter().skip(own_params)).map(
+        |(param, arg)| {
+            param.as_type_param(db)?;
+            type_arg(db, arg?, target_module)
+        },
+    );
+    Some((imp, make::generic_arg_list(args.collect::<Option<Vec<_>>>()?)))
+}
+
+fn callable_of_call(
+    sema: &Semantics<'_, RootDatabase>,
+    node: &ast::CallableExpr,
+) -> Option<hir::Callable> {
+    match node {
+        ast::CallableExpr::Call(call) => {
+            sema.type_of_expr(&call.expr()?)?.original.as_callable(sema.db)
+        }
+        ast::CallableExpr::MethodCall(call) => sema.resolve_method_call_as_callable(call),
+    }
+}
+
+fn type_arg(db: &RootDatabase, ty: hir::Type, module: hir::Module) -> Option<ast::GenericArg> {
+    if ty.contains_unknown() {
+        return None;
+    }
+    let ty = ty.display_source_code(db, module.into(), false).ok()?;
+    Some(make::type_arg(make::ty(&ty)).into())
+}
+
+/// Turns the `return`s of the inlined body into `break`s out of the body, which gets labeled for
+/// that. Returns the label if there were any `return`s.
+fn replace_returns_with_breaks(body: &ast::BlockExpr) -> Option<ast::Lifetime> {
+    let mut returns = Vec::new();
+    walk_expr(&ast::Expr::BlockExpr(body.clone()), &mut |expr| {
+        if let ast::Expr::ReturnExpr(it) = expr {
+            returns.push(it);
+        }
+    });
+    if returns.is_empty() {
+        return None;
+    }
+    cov_mark::hit!(inline_call_return_to_break);
+    let labels: FxHashSet<String> = body
+        .syntax()
+        .descendants()
+        .filter_map(ast::Lifetime::cast)
+        .map(|it| it.to_string())
+        .collect();
+    let label = iter::once("'inline".to_owned())
+        .chain((1..).map(|idx| format!("'inline{idx}")))
+        .find(|it| !labels.contains(it))?;
+    let lifetime = make::lifetime(&label);
+    for ret in returns {
+        let brk = make::expr_break(Some(lifetime.clone()), ret.expr()).clone_for_update();
+        ted::replace(ret.syntax(), brk.syntax());
+    }
+    ted::insert_all(
+        ted::Position::first_child_of(body.syntax()),
+        vec![
+            make::label(lifetime.clone()).clone_for_update().syntax().clone().into(),
+            make::tokens::single_space().into(),
+        ],
+    );
+    Some(lifetime)
+}
+
 fn path_expr_as_record_field(usage: &PathExpr) -> Option<ast::RecordExprField> {
     let path = usage.path()?;
     let name_ref = path.as_single_name_ref()?;
@@ -1795,4 +2005,218 @@ fn _hash2(self_: &u64, state: &mut u64) {
 "#,
         )
     }
+
+    #[test]
+    fn inline_call_infers_generics() {
+        check_assist(
+            inline_call,
+            r#"
+struct W<T>(T);
+fn wrap<T>(x: T) -> W<T> {
+    let w: W<T> = W(x);
+    w
+}
+fn main() {
+    let a = wrap$0(1u32);
+}
+"#,
+            r#"
+struct W<T>(T);
+fn wrap<T>(x: T) -> W<T> {
+    let w: W<T> = W(x);
+    w
+}
+fn main() {
+    let a = {
+        let w: W<u32> = W(1u32);
+        w
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inline_method_of_generic_impl() {
+        check_assist(
+            inline_call,
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn new(x: T) -> Self {
+        Self(x)
+    }
+    fn replace(&self, x: T) -> S<T> {
+        let s: Self = Self::new(x);
+        s
+    }
+}
+fn main() {
+    let s = S(1u32);
+    let t = s.replace$0(2u32);
+}
+"#,
+            r#"
+struct S<T>(T);
+impl<T> S<T> {
+    fn new(x: T) -> Self {
+        Self(x)
+    }
+    fn replace(&self, x: T) -> S<T> {
+        let s: Self = Self::new(x);
+        s
+    }
+}
+fn main() {
+    let s = S(1u32);
+    let t = {
+        let this = &s;
+        let s: S<u32> = S::<u32>::new(2u32);
+        s
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_param_used_by_other_argument() {
+        cov_mark::check!(inline_call_rename_param);
+        check_assist(
+            inline_call,
+            r#"
+fn mul(a: u32, b: u32) -> u32 {
+    a * b
+}
+fn main() {
+    let a = 1;
+    let r = mul$0(a + 1, a);
+}
+"#,
+            r#"
+fn mul(a: u32, b: u32) -> u32 {
+    a * b
+}
+fn main() {
+    let a = 1;
+    let r = {
+        let a_1 = a + 1;
+        a_1 * a
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_this_used_by_other_argument() {
+        check_assist(
+            inline_call,
+            r#"
+struct S(u32);
+impl S {
+    fn add(&self, other: &S) -> u32 {
+        self.0 + other.0
+    }
+}
+fn main() {
+    let this = S(1);
+    let r = S(2).add$0(&this);
+}
+"#,
+            r#"
+struct S(u32);
+impl S {
+    fn add(&self, other: &S) -> u32 {
+        self.0 + other.0
+    }
+}
+fn main() {
+    let this = S(1);
+    let r = {
+        let this_1 = &S(2);
+        let other = &this;
+        this_1.0 + other.0
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn closure_argument_capturing_shadowed_local() {
+        check_assist(
+            inline_call,
+            r#"
+fn apply(f: impl Fn() -> u32) -> u32 {
+    let x = 2;
+    f() * x
+}
+fn main() {
+    let x = 1;
+    let r = apply$0(|| x);
+}
+"#,
+            r#"
+fn apply(f: impl Fn() -> u32) -> u32 {
+    let x = 2;
+    f() * x
+}
+fn main() {
+    let x = 1;
+    let r = {
+        let f = || x;
+        let x = 2;
+        f() * x
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn early_return_becomes_labeled_break() {
+        cov_mark::check!(inline_call_return_to_break);
+        check_assist(
+            inline_call,
+            r#"
+fn clamp(x: u32) -> u32 {
+    if x > 10 {
+        return 10;
+    }
+    let f = || {
+        return 1;
+    };
+    x
+}
+fn main() {
+    let y = 5;
+    let c = clamp$0(y);
+}
+"#,
+            r#"
+fn clamp(x: u32) -> u32 {
+    if x > 10 {
+        return 10;
+    }
+    let f = || {
+        return 1;
+    };
+    x
+}
+fn main() {
+    let y = 5;
+    let c = 'inline: {
+        if y > 10 {
+            break 'inline 10;
+        }
+        let f = || {
+            return 1;
+        };
+        y
+    };
+}
+"#,
+        );
+    }
 }
@@ -72,41 +72,12 @@ pub(crate) fn inline_local_variable(acc: &mut Assists, ctx: &AssistContext<'_>)
                 // FIXME: This feels like a bad heuristic for macros
                 return None;
             }
-            let usage_node =
-                name_ref.syntax().ancestors().find(|it| ast::PathExpr::can_cast(it.kind()));
-            let usage_parent_option =
-                usage_node.and_then(|it| it.parent()).and_then(ast::Expr::cast);
-            let usage_parent = match usage_parent_option {
-                Some(u) => u,
-                None => return Some((range, name_ref, false)),
-            };
-            let initializer = matches!(
-                initializer_expr,
-                ast::Expr::CallExpr(_)
-                    | ast::Expr::IndexExpr(_)
-                    | ast::Expr::MethodCallExpr(_)
-                    | ast::Expr::FieldExpr(_)
-                    | ast::Expr::TryExpr(_)
-                    | ast::Expr::Literal(_)
-                    | ast::Expr::TupleExpr(_)
-                    | ast::Expr::ArrayExpr(_)
-                    | ast::Expr::ParenExpr(_)
-                    | ast::Expr::PathExpr(_)
-                    | ast::Expr::BlockExpr(_),
-            );
-            let parent = matches!(
-                usage_parent,
-                ast::Expr::TupleExpr(_)
-                    | ast::Expr::ArrayExpr(_)
-                    | ast::Expr::ParenExpr(_)
-                    | ast::Expr::ForExpr(_)
-                    | ast::Expr::WhileExpr(_)
-                    | ast::Expr::BreakExpr(_)
-                    | ast::Expr::ReturnExpr(_)
-                    | ast::Expr::MatchExpr(_)
-                    | ast::Expr::BlockExpr(_)
-            );
-            Some((range, name_ref, !(initializer || parent)))
+            let should_wrap = name_ref
+                .syntax()
+                .ancestors()
+                .find_map(ast::PathExpr::cast)
+                .map_or(false, |usage| initializer_expr.needs_parens_in_place_of(&usage.into()));
+            Some((range, name_ref, should_wrap))
         })
         .collect::<Option<Vec<_>>>()?;
 
@@ -282,11 +253,11 @@ fn foo() {
             r"
 fn bar(a: usize) {}
 fn foo() {
-    (1 + 1) + 1;
-    if (1 + 1) > 10 {
+    1 + 1 + 1;
+    if 1 + 1 > 10 {
     }
 
-    while (1 + 1) > 10 {
+    while 1 + 1 > 10 {
 
     }
     let b = (1 + 1) * 10;
@@ -350,14 +321,14 @@ fn foo() {
             r"
 fn bar(a: usize): usize { a }
 fn foo() {
-    (bar(1) as u64) + 1;
-    if (bar(1) as u64) > 10 {
+    bar(1) as u64 + 1;
+    if bar(1) as u64 > 10 {
     }
 
-    while (bar(1) as u64) > 10 {
+    while bar(1) as u64 > 10 {
 
     }
-    let b = (bar(1) as u64) * 10;
+    let b = bar(1) as u64 * 10;
     bar(bar(1) as u64);
 }",
         );
@@ -574,7 +545,29 @@ fn foo() {
             r"
 fn foo() {
     let bar = 10;
-    let b = (&bar) * 10;
+    let b = &bar * 10;
+}",
+        );
+    }
+
+    #[test]
+    fn test_cast_expr_precedence() {
+        check_assist(
+            inline_local_variable,
+            r"
+fn foo(a: i32) {
+    let x$0 = a as i64;
+    let _ = -x;
+    let _ = x + 1;
+    let _ = x < 0;
+    let _ = x << 1;
+}",
+            r"
+fn foo(a: i32) {
+    let _ = -(a as i64);
+    let _ = a as i64 + 1;
+    let _ = (a as i64) < 0;
+    let _ = (a as i64) << 1;
 }",
         );
     }
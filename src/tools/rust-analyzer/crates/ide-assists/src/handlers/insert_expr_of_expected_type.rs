@@ -0,0 +1,143 @@
+use hir::term_search::{TermSearchConfig, TermSearchCtx};
+use ide_db::assists::{AssistId, AssistKind, GroupLabel};
+use itertools::Itertools;
+use syntax::{ast, AstNode};
+
+use crate::assist_context::{AssistContext, Assists};
+
+// Assist: insert_expr_of_expected_type
+//
+// Replaces an expression that doesn't have the type its context expects with an expression
+// that does, built from the items and locals in scope.
+//
+// ```
+// struct Meters(u32);
+// fn walk(distance: Meters) {}
+// fn main() {
+//     let m = Meters(5);
+//     walk($05);
+// }
+// ```
+// ->
+// ```
+// struct Meters(u32);
+// fn walk(distance: Meters) {}
+// fn main() {
+//     let m = Meters(5);
+//     walk(m);
+// }
+// ```
+pub(crate) fn insert_expr_of_expected_type(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let expr = ctx.find_node_at_offset::<ast::Expr>()?;
+    let goal_range = expr.syntax().text_range();
+
+    let expected = ctx.sema.expected_type_of_expr(&expr)?;
+    let actual = ctx.sema.type_of_expr(&expr)?.original;
+    if !actual.is_unknown() && actual.could_coerce_to(ctx.db(), &expected) {
+        return None;
+    }
+
+    let scope = ctx.sema.scope(expr.syntax())?;
+    // The body has a type mismatch, so it can't be borrow checked.
+    let config = TermSearchConfig { enable_borrowcheck: false, ..Default::default() };
+    let term_search_ctx = TermSearchCtx { sema: &ctx.sema, scope: &scope, goal: expected, config };
+    let paths = hir::term_search::term_search(&term_search_ctx);
+
+    let mut formatter = |_: &hir::Type| String::from("todo!()");
+    let current = expr.syntax().text().to_string();
+    let paths = paths
+        .into_iter()
+        .filter_map(|path| {
+            path.gen_source_code(
+                &scope,
+                &mut formatter,
+                ctx.config.prefer_no_std,
+                ctx.config.prefer_prelude,
+            )
+            .ok()
+        })
+        .filter(|code| *code != current)
+        .unique();
+
+    for code in paths {
+        acc.add_group(
+            &GroupLabel(String::from("Insert expression of expected type")),
+            AssistId("insert_expr_of_expected_type", AssistKind::RefactorRewrite),
+            format!("Replace with {code}"),
+            goal_range,
+            |builder| builder.replace(goal_range, code),
+        );
+    }
+
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replaces_mismatched_argument() {
+        check_assist(
+            insert_expr_of_expected_type,
+            r#"
+fn take(x: u32) {}
+fn f() { let a: u32 = 1; take(tr$0ue) }"#,
+            r#"
+fn take(x: u32) {}
+fn f() { let a: u32 = 1; take(a) }"#,
+        )
+    }
+
+    #[test]
+    fn replaces_mismatched_struct_field() {
+        check_assist_by_label(
+            insert_expr_of_expected_type,
+            r#"
+struct S { name: bool }
+fn f() { let b = false; S { name: $01 }; }"#,
+            r#"
+struct S { name: bool }
+fn f() { let b = false; S { name: b }; }"#,
+            "Replace with b",
+        )
+    }
+
+    #[test]
+    fn replaces_mismatched_tail_expr() {
+        check_assist_by_label(
+            insert_expr_of_expected_type,
+            r#"
+struct Wrapper(u8);
+fn f(x: u8) -> Wrapper { $0x }"#,
+            r#"
+struct Wrapper(u8);
+fn f(x: u8) -> Wrapper { Wrapper(x) }"#,
+            "Replace with Wrapper(x)",
+        )
+    }
+
+    #[test]
+    fn not_applicable_if_types_match() {
+        check_assist_not_applicable(
+            insert_expr_of_expected_type,
+            r#"
+fn take(x: u32) {}
+fn f() { let a: u32 = 1; take(a$0) }"#,
+        )
+    }
+
+    #[test]
+    fn not_applicable_without_expectation() {
+        check_assist_not_applicable(
+            insert_expr_of_expected_type,
+            r#"
+fn f() { let a: u32 = 1; let b = tr$0ue; }"#,
+        )
+    }
+}
@@ -3,7 +3,10 @@
     ted,
 };
 
-use crate::{utils::suggest_name, AssistContext, AssistId, AssistKind, Assists};
+use crate::{
+    utils::{suggest_name, TurbofishUsages},
+    AssistContext, AssistId, AssistKind, Assists,
+};
 
 // Assist: introduce_named_generic
 //
@@ -22,6 +25,7 @@ pub(crate) fn introduce_named_generic(acc: &mut Assists, ctx: &AssistContext<'_>
     let fn_ = param.syntax().ancestors().find_map(ast::Fn::cast)?;
 
     let type_bound_list = impl_trait_type.type_bound_list()?;
+    let turbofishes = TurbofishUsages::find(ctx, ctx.sema.to_def(&fn_)?);
 
     let target = fn_.syntax().text_range();
     acc.add(
@@ -31,6 +35,7 @@ pub(crate) fn introduce_named_generic(acc: &mut Assists, ctx: &AssistContext<'_>
         |edit| {
             let impl_trait_type = edit.make_mut(impl_trait_type);
             let fn_ = edit.make_mut(fn_);
+            let turbofishes = turbofishes.make_mut(edit);
             let fn_generic_param_list = fn_.get_or_create_generic_param_list();
             let type_param_name =
                 suggest_name::for_impl_trait_as_generic(&impl_trait_type, &fn_generic_param_list);
@@ -49,6 +54,12 @@ pub(crate) fn introduce_named_generic(acc: &mut Assists, ctx: &AssistContext<'_>
                     edit.add_tabstop_before(cap, generic_param);
                 }
             }
+
+            // Call sites spelling out the generic arguments now have to fill in the new one.
+            turbofishes.edit(edit, |args| {
+                let arg = make::type_arg(make::ty_placeholder()).clone_for_update();
+                args.add_generic_arg(arg.into());
+            });
         },
     )
 }
@@ -175,4 +186,25 @@ fn replace_impl_inside() {
             r#"fn f<$0I: Iterator<Item = i32>>(x: &mut Vec<I>) {}"#,
         );
     }
+
+    #[test]
+    fn introduce_named_generic_fills_turbofish_call_sites() {
+        check_assist(
+            introduce_named_generic,
+            r#"
+fn foo<T>(t: T, bar: $0impl Bar) {}
+fn main() {
+    foo::<i32>(1, ());
+    foo(1, ());
+}
+"#,
+            r#"
+fn foo<T, $0B: Bar>(t: T, bar: B) {}
+fn main() {
+    foo::<i32, _>(1, ());
+    foo(1, ());
+}
+"#,
+        );
+    }
 }
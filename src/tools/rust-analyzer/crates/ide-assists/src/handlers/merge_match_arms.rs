@@ -1,10 +1,10 @@
-use hir::Type;
+use hir::{BindingMode, Type};
 use ide_db::FxHashMap;
 use std::iter::successors;
 use syntax::{
     algo::neighbor,
     ast::{self, AstNode, HasName},
-    Direction,
+    Direction, SyntaxNode,
 };
 
 use crate::{AssistContext, AssistId, AssistKind, Assists, TextRange};
@@ -35,25 +35,26 @@
 // ```
 pub(crate) fn merge_match_arms(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
     let current_arm = ctx.find_node_at_offset::<ast::MatchArm>()?;
-    // Don't try to handle arms with guards for now - can add support for this later
-    if current_arm.guard().is_some() {
-        return None;
-    }
     let current_expr = current_arm.expr()?;
     let current_text_range = current_arm.syntax().text_range();
-    let current_arm_types = get_arm_types(ctx, &current_arm);
+    let current_arm_bindings = get_arm_bindings(ctx, &current_arm);
 
     // We check if the following match arms match this one. We could, but don't,
     // compare to the previous match arm as well.
-    let arms_to_merge = successors(Some(current_arm), |it| neighbor(it, Direction::Next))
+    let arms_to_merge = successors(Some(current_arm.clone()), |it| neighbor(it, Direction::Next))
         .take_while(|arm| match arm.expr() {
-            Some(expr) if arm.guard().is_none() => {
-                let same_text = expr.syntax().text() == current_expr.syntax().text();
-                if !same_text {
-                    return false;
-                }
-
-                are_same_types(&current_arm_types, arm, ctx)
+            Some(expr) => {
+                // Arms can only be merged along with their guard if it is the same for both.
+                let same_guard = match (arm.guard(), current_arm.guard()) {
+                    (Some(guard), Some(current_guard)) => {
+                        is_same_syntax(guard.syntax(), current_guard.syntax())
+                    }
+                    (None, None) => true,
+                    _ => false,
+                };
+                same_guard
+                    && is_same_syntax(expr.syntax(), current_expr.syntax())
+                    && are_same_bindings(&current_arm_bindings, &get_arm_bindings(ctx, arm))
             }
             _ => false,
         })
@@ -79,7 +80,10 @@ pub(crate) fn merge_match_arms(acc: &mut Assists, ctx: &AssistContext<'_>) -> Op
                     .join(" | ")
             };
 
-            let arm = format!("{pats} => {current_expr},");
+            let arm = match current_arm.guard() {
+                Some(guard) => format!("{pats} {guard} => {current_expr},"),
+                None => format!("{pats} => {current_expr},"),
+            };
 
             if let [first, .., last] = &*arms_to_merge {
                 let start = first.syntax().text_range().start();
@@ -95,74 +99,52 @@ fn contains_placeholder(a: &ast::MatchArm) -> bool {
     matches!(a.pat(), Some(ast::Pat::WildcardPat(..)))
 }
 
-fn are_same_types(
-    current_arm_types: &FxHashMap<String, Option<Type>>,
-    arm: &ast::MatchArm,
-    ctx: &AssistContext<'_>,
-) -> bool {
-    let arm_types = get_arm_types(ctx, arm);
-    for (other_arm_type_name, other_arm_type) in arm_types {
-        match (current_arm_types.get(&other_arm_type_name), other_arm_type) {
-            (Some(Some(current_arm_type)), Some(other_arm_type))
-                if other_arm_type == *current_arm_type => {}
-            _ => return false,
-        }
-    }
-
-    true
+/// Compares the tokens of both nodes, ignoring whitespace and comments.
+fn is_same_syntax(a: &SyntaxNode, b: &SyntaxNode) -> bool {
+    let tokens = |node: &SyntaxNode| {
+        node.descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| !it.kind().is_trivia())
+            .map(|it| (it.kind(), it.text().to_owned()))
+            .collect::<Vec<_>>()
+    };
+    tokens(a) == tokens(b)
 }
 
-fn get_arm_types(
-    context: &AssistContext<'_>,
-    arm: &ast::MatchArm,
-) -> FxHashMap<String, Option<Type>> {
-    let mut mapping: FxHashMap<String, Option<Type>> = FxHashMap::default();
+/// An or-pattern has to bind the same names in all of its alternatives, with the same types and
+/// binding modes, so arms can only be merged when their bindings agree on all of these.
+struct Binding {
+    ty: Option<Type>,
+    mode: Option<BindingMode>,
+    is_mut: bool,
+}
 
-    fn recurse(
-        map: &mut FxHashMap<String, Option<Type>>,
-        ctx: &AssistContext<'_>,
-        pat: &Option<ast::Pat>,
-    ) {
-        if let Some(local_pat) = pat {
-            match local_pat {
-                ast::Pat::TupleStructPat(tuple) => {
-                    for field in tuple.fields() {
-                        recurse(map, ctx, &Some(field));
-                    }
-                }
-                ast::Pat::TuplePat(tuple) => {
-                    for field in tuple.fields() {
-                        recurse(map, ctx, &Some(field));
-                    }
-                }
-                ast::Pat::RecordPat(record) => {
-                    if let Some(field_list) = record.record_pat_field_list() {
-                        for field in field_list.fields() {
-                            recurse(map, ctx, &field.pat());
-                        }
-                    }
-                }
-                ast::Pat::ParenPat(parentheses) => {
-                    recurse(map, ctx, &parentheses.pat());
-                }
-                ast::Pat::SlicePat(slice) => {
-                    for slice_pat in slice.pats() {
-                        recurse(map, ctx, &Some(slice_pat));
-                    }
-                }
-                ast::Pat::IdentPat(ident_pat) => {
-                    if let Some(name) = ident_pat.name() {
-                        let pat_type = ctx.sema.type_of_binding_in_pat(ident_pat);
-                        map.insert(name.text().to_string(), pat_type);
-                    }
-                }
-                _ => (),
-            }
-        }
-    }
+fn are_same_bindings(a: &FxHashMap<String, Binding>, b: &FxHashMap<String, Binding>) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|(name, a)| {
+            let Some(b) = b.get(name) else { return false };
+            // Types we fail to infer are never considered equal.
+            let same_ty = matches!((&a.ty, &b.ty), (Some(a), Some(b)) if a == b);
+            same_ty && a.mode == b.mode && a.is_mut == b.is_mut
+        })
+}
 
-    recurse(&mut mapping, context, &arm.pat());
-    mapping
+fn get_arm_bindings(ctx: &AssistContext<'_>, arm: &ast::MatchArm) -> FxHashMap<String, Binding> {
+    let Some(pat) = arm.pat() else { return FxHashMap::default() };
+    // Unit structs, enum variants and constants are parsed as `IdentPat`s as well.
+    pat.syntax()
+        .descendants()
+        .filter_map(ast::IdentPat::cast)
+        .filter(|it| ctx.sema.to_def(it).is_some())
+        .filter_map(|ident_pat| {
+            let binding = Binding {
+                ty: ctx.sema.type_of_binding_in_pat(&ident_pat),
+                mode: ctx.sema.binding_mode_of_pat(&ident_pat),
+                is_mut: ident_pat.mut_token().is_some(),
+            };
+            Some((ident_pat.name()?.text().to_string(), binding))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -820,4 +802,106 @@ fn func(binary: &[u8]) {
         "#,
         )
     }
+
+    #[test]
+    fn merge_match_arms_rejects_missing_bindings() {
+        check_assist_not_applicable(
+            merge_match_arms,
+            r#"
+enum X { A(i32), B }
+
+fn func(x: X) {
+    match x {
+        X::A(a) => $0"",
+        X::B => "",
+    };
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn merge_match_arms_rejects_different_binding_modes() {
+        check_assist_not_applicable(
+            merge_match_arms,
+            r#"
+//- minicore: option
+fn func(x: Option<&i32>) {
+    match x {
+        Some(a) => $0"",
+        Some(&ref a) => "",
+        None => "",
+    };
+}
+"#,
+        );
+        check_assist_not_applicable(
+            merge_match_arms,
+            r#"
+fn func(x: (i32, i32)) {
+    match x {
+        (mut a, 0) => $0"",
+        (a, _) => "",
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn merge_match_arms_ignores_formatting() {
+        check_assist(
+            merge_match_arms,
+            r#"
+fn func(x: i32) {
+    match x {
+        0 => $0{ foo(1, 2) }
+        1 => {
+            // same call
+            foo(1,2)
+        }
+        _ => {}
+    };
+}
+"#,
+            r#"
+fn func(x: i32) {
+    match x {
+        0 | 1 => { foo(1, 2) },
+        _ => {}
+    };
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn merge_match_arms_with_same_guard() {
+        check_assist(
+            merge_match_arms,
+            r#"
+enum X { A(i32), B(i32), C(i32) }
+
+fn func(x: X, flag: bool) {
+    match x {
+        X::A(a) if flag => $0a,
+        X::B(a) if flag => a,
+        X::C(a) => a,
+        _ => 0,
+    };
+}
+"#,
+            r#"
+enum X { A(i32), B(i32), C(i32) }
+
+fn func(x: X, flag: bool) {
+    match x {
+        X::A(a) | X::B(a) if flag => a,
+        X::C(a) => a,
+        _ => 0,
+    };
+}
+"#,
+        )
+    }
 }
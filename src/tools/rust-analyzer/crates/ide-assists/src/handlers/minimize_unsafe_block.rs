@@ -0,0 +1,410 @@
+use hir::{HirFileId, InFile};
+use ide_db::syntax_helpers::node_ext::{block_as_lone_tail, pick_best_node_to_add_unsafe_block};
+use syntax::{ast, match_ast, AstNode, AstPtr, SyntaxNode, TextRange, T};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: minimize_unsafe_block
+//
+// Shrinks an `unsafe` block to the operations that actually require `unsafe`, wrapping each of
+// them in an `unsafe` block of its own.
+//
+// ```
+// unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+// fn main() {
+//     let x = 92;
+//     let y = unsafe$0 { read(&x) + 1 };
+// }
+// ```
+// ->
+// ```
+// unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+// fn main() {
+//     let x = 92;
+//     let y = unsafe { read(&x) } + 1;
+// }
+// ```
+pub(crate) fn minimize_unsafe_block(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let unsafe_token = ctx.find_token_syntax_at_offset(T![unsafe])?;
+    let block = unsafe_token.parent().and_then(ast::BlockExpr::cast)?;
+    let stmt_list = block.stmt_list()?;
+    let def = block.syntax().ancestors().find_map(|node| {
+        match_ast! {
+            match node {
+                ast::Fn(it) => ctx.sema.to_def(&it).map(hir::DefWithBody::from),
+                ast::Const(it) => ctx.sema.to_def(&it).map(hir::DefWithBody::from),
+                ast::Static(it) => ctx.sema.to_def(&it).map(hir::DefWithBody::from),
+                _ => None,
+            }
+        }
+    })?;
+
+    let file_id = HirFileId::from(ctx.file_id());
+    let block_ptr = AstPtr::new(&ast::Expr::BlockExpr(block.clone()));
+    let mut unsafe_exprs = Vec::new();
+    for op in def.unsafe_operations(ctx.db()) {
+        // Operations inside nested `unsafe` blocks don't depend on this one.
+        if op.unsafe_block != Some(InFile::new(file_id, block_ptr)) {
+            continue;
+        }
+        // Operations in macro expansions are covered by wrapping the whole macro call.
+        let root = ctx.sema.parse_or_expand(op.expr.file_id);
+        let node = op.expr.value.to_node(&root);
+        let range = InFile::new(op.expr.file_id, node.syntax()).original_file_range(ctx.db());
+        if range.file_id != ctx.file_id() {
+            return None;
+        }
+        let covering = stmt_list.syntax().covering_element(range.range);
+        unsafe_exprs.push(covering.ancestors().find_map(ast::Expr::cast)?);
+    }
+    // Inline assembly is unsafe, but isn't an operation of the body.
+    unsafe_exprs.extend(
+        stmt_list
+            .syntax()
+            .descendants()
+            .filter_map(ast::MacroCall::cast)
+            .filter(|it| innermost_unsafe_block(it.syntax()).as_ref() == Some(&block))
+            .filter(|it| ctx.sema.is_unsafe_macro_call(it))
+            .filter_map(|it| it.syntax().parent().and_then(ast::Expr::cast)),
+    );
+
+    let lone_tail = block_as_lone_tail(&block);
+    // A lone tail expression replaces the block, and so takes over its position.
+    let in_statement_position = |node: &SyntaxNode| match node.parent() {
+        Some(parent) if lone_tail.is_some() && parent == *stmt_list.syntax() => {
+            is_statement(block.syntax())
+        }
+        Some(_) => is_statement(node),
+        None => false,
+    };
+    let mut ranges: Vec<TextRange> = Vec::new();
+    for expr in unsafe_exprs {
+        let node = node_to_wrap(&expr, &in_statement_position)?;
+        if !stmt_list.syntax().text_range().contains_range(node.text_range()) {
+            return None;
+        }
+        ranges.push(node.text_range());
+    }
+    ranges.sort_by_key(|it| (it.start(), std::cmp::Reverse(it.end())));
+    ranges.dedup_by(|it, outer| outer.contains_range(*it));
+
+    let target = block.syntax().text_range();
+    let replacement = match lone_tail {
+        Some(tail) => {
+            if ranges.as_slice() == [tail.syntax().text_range()] {
+                // The block is already as small as it can be.
+                return None;
+            }
+            let mut text = wrap_ranges(tail.syntax(), &ranges);
+            if tail.needs_parens_in_place_of(&block.clone().into()) {
+                text = format!("({text})");
+            }
+            let parent = block.syntax().parent().and_then(ast::ExprStmt::cast);
+            if parent.map_or(false, |it| it.semicolon_token().is_none()) {
+                text.push(';');
+            }
+            text
+        }
+        None => wrap_ranges(stmt_list.syntax(), &ranges),
+    };
+
+    acc.add(
+        AssistId("minimize_unsafe_block", AssistKind::RefactorRewrite),
+        "Minimize unsafe block",
+        target,
+        |builder| {
+            builder.replace(
+                TextRange::new(unsafe_token.text_range().start(), target.end()),
+                replacement,
+            );
+        },
+    )
+}
+
+/// Picks the node to wrap in an `unsafe` block for `unsafe_expr`, making sure the new block doesn't
+/// start an expression statement, where it would end the statement.
+fn node_to_wrap(
+    unsafe_expr: &ast::Expr,
+    is_statement: &dyn Fn(&SyntaxNode) -> bool,
+) -> Option<SyntaxNode> {
+    let node = pick_best_node_to_add_unsafe_block(unsafe_expr)?;
+    let statement = node
+        .ancestors()
+        .take_while(|it| it.text_range().start() == node.text_range().start())
+        .filter(|it| ast::Expr::can_cast(it.kind()))
+        .find(|it| is_statement(it));
+    Some(statement.unwrap_or(node))
+}
+
+fn is_statement(expr: &SyntaxNode) -> bool {
+    expr.parent().map_or(false, |parent| {
+        ast::ExprStmt::can_cast(parent.kind()) || ast::StmtList::can_cast(parent.kind())
+    })
+}
+
+fn innermost_unsafe_block(node: &SyntaxNode) -> Option<ast::BlockExpr> {
+    node.ancestors().filter_map(ast::BlockExpr::cast).find(|it| it.unsafe_token().is_some())
+}
+
+/// Returns the text of `node`, with each of `ranges` wrapped in an `unsafe` block.
+fn wrap_ranges(node: &SyntaxNode, ranges: &[TextRange]) -> String {
+    let text = node.text().to_string();
+    let offset = node.text_range().start();
+    let mut res = String::with_capacity(text.len());
+    let mut last = 0;
+    for range in ranges {
+        let range = *range - offset;
+        let (start, end) = (usize::from(range.start()), usize::from(range.end()));
+        res.push_str(&text[last..start]);
+        res.push_str("unsafe { ");
+        res.push_str(&text[start..end]);
+        res.push_str(" }");
+        last = end;
+    }
+    res.push_str(&text[last..]);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn wraps_unsafe_call_in_tail() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn f(x: i32) -> i32 {
+    return unsafe$0 { read(&x) * 2 };
+}
+"#,
+            r#"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn f(x: i32) -> i32 {
+    return unsafe { read(&x) } * 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_statements_intact() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn f(x: i32) -> i32 {
+    unsafe$0 {
+        let y = x + 1;
+        read(&y) * 2
+    }
+}
+"#,
+            r#"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn f(x: i32) -> i32 {
+    {
+        let y = x + 1;
+        unsafe { read(&y) * 2 }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_raw_pointer_deref_in_argument() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    unsafe$0 { consume(*ptr) };
+}
+"#,
+            r#"
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    consume(unsafe { *ptr });
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn adds_semicolon_after_block_like_statement() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    unsafe$0 { consume(*ptr) }
+    consume(0);
+}
+"#,
+            r#"
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    consume(unsafe { *ptr });
+    consume(0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn splits_into_several_blocks() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+static mut COUNTER: u32 = 0;
+unsafe fn reset() {}
+fn f(ptr: *mut u32) {
+    unsafe$0 {
+        let value = *ptr + 1;
+        let doubled = value * 2;
+        COUNTER += doubled;
+        reset();
+    }
+}
+"#,
+            r#"
+static mut COUNTER: u32 = 0;
+unsafe fn reset() {}
+fn f(ptr: *mut u32) {
+    {
+        let value = unsafe { *ptr } + 1;
+        let doubled = value * 2;
+        unsafe { COUNTER += doubled };
+        unsafe { reset() };
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn parenthesizes_tail_when_needed() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+fn f(ptr: *const i32) {
+    let x = unsafe$0 { 1 + *ptr } * 2;
+}
+"#,
+            r#"
+fn f(ptr: *const i32) {
+    let x = (1 + unsafe { *ptr }) * 2;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_place_expressions_together() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+struct S { field: i32 }
+fn f(ptr: *mut S) {
+    let r = unsafe$0 { (&mut (*ptr).field, 0) };
+}
+"#,
+            r#"
+struct S { field: i32 }
+fn f(ptr: *mut S) {
+    let r = (unsafe { &mut (*ptr).field }, 0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn leaves_nested_unsafe_blocks_alone() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+unsafe fn g() -> i32 { 0 }
+fn f() -> i32 {
+    unsafe$0 {
+        let x = unsafe { g() };
+        x + 1
+    }
+}
+"#,
+            r#"
+unsafe fn g() -> i32 { 0 }
+fn f() -> i32 {
+    {
+        let x = unsafe { g() };
+        x + 1
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn wraps_macro_calls_with_unsafe_operations() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+macro_rules! deref { ($e:expr) => { *$e }; }
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    unsafe$0 { consume(deref!(ptr)) };
+}
+"#,
+            r#"
+macro_rules! deref { ($e:expr) => { *$e }; }
+fn consume(x: i32) {}
+fn f(ptr: *const i32) {
+    consume(unsafe { deref!(ptr) });
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_unneeded_unsafe() {
+        check_assist(
+            minimize_unsafe_block,
+            r#"
+fn f(x: i32) -> i32 {
+    unsafe$0 { x + 1 }
+}
+"#,
+            r#"
+fn f(x: i32) -> i32 {
+    x + 1
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_minimal_block() {
+        check_assist_not_applicable(
+            minimize_unsafe_block,
+            r#"
+unsafe fn g() {}
+fn f() {
+    unsafe$0 { g() };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_unsafe_fn() {
+        check_assist_not_applicable(
+            minimize_unsafe_block,
+            r#"
+unsafe$0 fn g() {}
+"#,
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use ide_db::lifetime_elision::ElidedLifetimes;
+use syntax::{ast, AstNode, TextRange};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: name_elided_lifetimes
+//
+// Names all lifetimes elided in a function signature, which shows the lifetimes the return
+// type borrows from.
+//
+// ```
+// struct Parser<'a>(&'a str);
+// impl Parser<'_> {
+//     fn next_$0token(&mut self, skip: &str) -> Option<&str> { None }
+// }
+// ```
+// ->
+// ```
+// struct Parser<'a>(&'a str);
+// impl Parser<'_> {
+//     fn next_token<'a, 'b>(&'a mut self, skip: &'b str) -> Option<&'a str> { None }
+// }
+// ```
+pub(crate) fn name_elided_lifetimes(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let func = ctx.find_node_at_offset::<ast::Fn>()?;
+    let signature_end = match func.body() {
+        Some(body) => body.syntax().text_range().start(),
+        None => func.syntax().text_range().end(),
+    };
+    if ctx.offset() > signature_end {
+        return None;
+    }
+    let elided = ElidedLifetimes::new(&ctx.sema, &func)?;
+
+    let target = TextRange::new(func.syntax().text_range().start(), signature_end);
+    acc.add(
+        AssistId("name_elided_lifetimes", AssistKind::RefactorRewrite),
+        "Name elided lifetimes",
+        target,
+        |builder| {
+            let func = builder.make_mut(func);
+            elided.apply(&func);
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn names_refs_and_output() {
+        check_assist(
+            name_elided_lifetimes,
+            r#"
+fn $0longest(a: &str) -> &str { a }
+"#,
+            r#"
+fn longest<'a>(a: &'a str) -> &'a str { a }
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_existing_params() {
+        check_assist(
+            name_elided_lifetimes,
+            r#"
+struct Wrapper<'w, T>(&'w T);
+fn wr$0ap<'a, T: Clone>(a: &'a T, b: Wrapper<T>, c: &'_ mut Wrapper<'_, T>) {}
+"#,
+            r#"
+struct Wrapper<'w, T>(&'w T);
+fn wrap<'a, 'b, 'c, 'd, T: Clone>(a: &'a T, b: Wrapper<'b, T>, c: &'c mut Wrapper<'d, T>) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn impl_trait_and_async() {
+        check_assist(
+            name_elided_lifetimes,
+            r#"
+//- minicore: iterator, future
+async fn $0first(items: impl Iterator<Item = &u8>) -> Option<&u8> { None }
+"#,
+            r#"
+async fn first<'a>(items: impl Iterator<Item = &'a u8>) -> Option<&'a u8> { None }
+"#,
+        );
+    }
+
+    #[test]
+    fn skips_fn_pointers() {
+        check_assist(
+            name_elided_lifetimes,
+            r#"
+fn ap$0ply(f: fn(&u8) -> &u8, x: &u8) -> &u8 { f(x) }
+"#,
+            r#"
+fn apply<'a>(f: fn(&u8) -> &u8, x: &'a u8) -> &'a u8 { f(x) }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_body() {
+        check_assist_not_applicable(
+            name_elided_lifetimes,
+            r#"
+fn longest(a: &str) -> &str { a$0 }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_elided_lifetimes() {
+        check_assist_not_applicable(
+            name_elided_lifetimes,
+            r#"
+fn $0longest<'a>(a: &'a str) -> &'a str { a }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_ambiguous_output() {
+        check_assist_not_applicable(
+            name_elided_lifetimes,
+            r#"
+fn $0longest(a: &str, b: &str) -> &str { a }
+"#,
+        );
+    }
+}
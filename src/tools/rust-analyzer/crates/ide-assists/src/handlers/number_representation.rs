@@ -1,4 +1,5 @@
-use syntax::{ast, ast::Radix, AstToken};
+use ide_db::syntax_helpers::digit_grouping::NumberLiteral;
+use syntax::{ast, AstNode};
 
 use crate::{AssistContext, AssistId, AssistKind, Assists, GroupLabel};
 
@@ -6,7 +7,7 @@
 
 // Assist: reformat_number_literal
 //
-// Adds or removes separators from integer literal.
+// Adds, regroups or removes separators from number literal.
 //
 // ```
 // const _: i32 = 1012345$0;
@@ -17,73 +18,37 @@
 // ```
 pub(crate) fn reformat_number_literal(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
     let literal = ctx.find_node_at_offset::<ast::Literal>()?;
-    let literal = match literal.kind() {
-        ast::LiteralKind::IntNumber(it) => it,
-        _ => return None,
-    };
-
-    let text = literal.text();
-    if text.contains('_') {
-        return remove_separators(acc, literal);
-    }
-
-    let (prefix, value, suffix) = literal.split_into_parts();
-    if value.len() < MIN_NUMBER_OF_DIGITS_TO_FORMAT {
-        return None;
-    }
-
-    let radix = literal.radix();
-    let mut converted = prefix.to_owned();
-    converted.push_str(&add_group_separators(value, group_size(radix)));
-    converted.push_str(suffix);
+    let kind = literal.kind();
+    let parts = NumberLiteral::new(&kind)?;
 
     let group_id = GroupLabel("Reformat number literal".into());
-    let label = format!("Convert {literal} to {converted}");
+    let id = AssistId("reformat_number_literal", AssistKind::RefactorInline);
     let range = literal.syntax().text_range();
-    acc.add_group(
-        &group_id,
-        AssistId("reformat_number_literal", AssistKind::RefactorInline),
-        label,
-        range,
-        |builder| builder.replace(range, converted),
-    )
-}
-
-fn remove_separators(acc: &mut Assists, literal: ast::IntNumber) -> Option<()> {
-    let group_id = GroupLabel("Reformat number literal".into());
-    let range = literal.syntax().text_range();
-    acc.add_group(
-        &group_id,
-        AssistId("reformat_number_literal", AssistKind::RefactorInline),
-        "Remove digit separators",
-        range,
-        |builder| builder.replace(range, literal.text().replace('_', "")),
-    )
-}
-
-const fn group_size(r: Radix) -> usize {
-    match r {
-        Radix::Binary => 4,
-        Radix::Octal => 3,
-        Radix::Decimal => 3,
-        Radix::Hexadecimal => 4,
+    if parts.has_separators() {
+        if !parts.is_consistently_grouped() {
+            let converted = parts.regrouped();
+            acc.add_group(&group_id, id, "Regroup digits", range, |builder| {
+                builder.replace(range, converted)
+            });
+        }
+        return acc.add_group(&group_id, id, "Remove digit separators", range, |builder| {
+            builder.replace(range, parts.without_separators())
+        });
     }
-}
 
-fn add_group_separators(s: &str, group_size: usize) -> String {
-    let mut chars = Vec::new();
-    for (i, ch) in s.chars().filter(|&ch| ch != '_').rev().enumerate() {
-        if i > 0 && i % group_size == 0 {
-            chars.push('_');
-        }
-        chars.push(ch);
+    if parts.max_part_len() < MIN_NUMBER_OF_DIGITS_TO_FORMAT {
+        return None;
     }
 
-    chars.into_iter().rev().collect()
+    let converted = parts.regrouped();
+    let label = format!("Convert {literal} to {converted}");
+    acc.add_group(&group_id, id, label, range, |builder| builder.replace(range, converted))
 }
 
 #[cfg(test)]
 mod tests {
+    use ide_db::syntax_helpers::digit_grouping::group_integer_digits;
+
     use crate::tests::{check_assist_by_label, check_assist_not_applicable, check_assist_target};
 
     use super::*;
@@ -110,7 +75,7 @@ fn group_separators() {
 
         for case in cases {
             let (input, group_size, expected) = case;
-            assert_eq!(add_group_separators(input, group_size), expected)
+            assert_eq!(group_integer_digits(input, group_size), expected)
         }
     }
 
@@ -141,6 +106,8 @@ fn bad_targets() {
             "const _: i32 = 999$0;",
             "const _: i32 = 0xFF$0;",
             "const _: i32 = 0xFFFF$0;",
+            "const _: f64 = 1234.5678$0;",
+            "const _: f64 = 1e10000$0;",
         ];
 
         for case in cases {
@@ -173,6 +140,19 @@ fn labels() {
                 "Convert 10000i32 to 10_000i32",
             ),
             ("const _: i32 = 1_0_0_0_i32$0;", "const _: i32 = 1000i32;", "Remove digit separators"),
+            ("const _: i32 = 12_34_567$0;", "const _: i32 = 1_234_567;", "Regroup digits"),
+            ("const _: i32 = 12_34_567$0;", "const _: i32 = 1234567;", "Remove digit separators"),
+            (
+                "const _: f64 = 12345.678901$0;",
+                "const _: f64 = 12_345.678_901;",
+                "Convert 12345.678901 to 12_345.678_901",
+            ),
+            (
+                "const _: f32 = 1.00000e10f32$0;",
+                "const _: f32 = 1.000_00e10f32;",
+                "Convert 1.00000e10f32 to 1.000_00e10f32",
+            ),
+            ("const _: f64 = 1_00_000.5_f64$0;", "const _: f64 = 100_000.5f64;", "Regroup digits"),
         ];
 
         for case in cases {
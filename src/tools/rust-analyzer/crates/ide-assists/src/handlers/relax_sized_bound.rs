@@ -0,0 +1,177 @@
+use syntax::{
+    ast::{self, HasGenericParams, HasName, HasTypeBounds},
+    AstNode,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: relax_sized_bound
+//
+// Relaxes the implicit `Sized` bound of a function's type parameter with `?Sized`, taking
+// parameters of that type by reference and returning it boxed, as unsized values can't be
+// passed around by value.
+//
+// ```
+// fn len<$0T: AsRef<[u8]>>(value: T) -> usize {
+//     value.as_ref().len()
+// }
+// ```
+// ->
+// ```
+// fn len<T: AsRef<[u8]> + ?Sized>(value: &T) -> usize {
+//     value.as_ref().len()
+// }
+// ```
+pub(crate) fn relax_sized_bound(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let type_param = ctx.find_node_at_offset::<ast::TypeParam>()?;
+    let name = type_param.name()?;
+    let func = type_param.syntax().ancestors().nth(2).and_then(ast::Fn::cast)?;
+
+    let is_param = |ty: &ast::Type| match ty {
+        ast::Type::PathType(it) => it
+            .path()
+            .and_then(|it| it.as_single_name_ref())
+            .map_or(false, |it| it.text() == name.text()),
+        _ => false,
+    };
+    let is_maybe_sized = |bound: ast::TypeBound| {
+        bound.question_mark_token().is_some()
+            && bound.ty().map_or(false, |it| it.syntax().text() == "Sized")
+    };
+    let bounds_in_where_clause = func
+        .where_clause()
+        .into_iter()
+        .flat_map(|it| it.predicates())
+        .filter(|pred| pred.ty().map_or(false, |it| is_param(&it)))
+        .filter_map(|pred| pred.type_bound_list());
+    if type_param
+        .type_bound_list()
+        .into_iter()
+        .chain(bounds_in_where_clause)
+        .flat_map(|it| it.bounds())
+        .any(is_maybe_sized)
+    {
+        return None;
+    }
+
+    let by_value_params: Vec<_> = func
+        .param_list()
+        .into_iter()
+        .flat_map(|it| it.params())
+        .filter_map(|param| param.ty())
+        .filter(is_param)
+        .collect();
+    let by_value_ret = func.ret_type().and_then(|it| it.ty()).filter(is_param);
+
+    acc.add(
+        AssistId("relax_sized_bound", AssistKind::RefactorRewrite),
+        format!("Relax `Sized` bound of `{name}`"),
+        type_param.syntax().text_range(),
+        |builder| {
+            match type_param.type_bound_list().filter(|it| it.bounds().next().is_some()) {
+                Some(bounds) => builder.insert(bounds.syntax().text_range().end(), " + ?Sized"),
+                None => match type_param.colon_token() {
+                    Some(colon) => builder.insert(colon.text_range().end(), " ?Sized"),
+                    None => builder.insert(name.syntax().text_range().end(), ": ?Sized"),
+                },
+            }
+            for ty in by_value_params {
+                builder.insert(ty.syntax().text_range().start(), "&");
+            }
+            if let Some(ty) = by_value_ret {
+                builder.replace(ty.syntax().text_range(), format!("Box<{ty}>"));
+            }
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn relax_unbounded_param() {
+        check_assist(
+            relax_sized_bound,
+            r#"
+fn print<$0T>(value: &T) {}
+"#,
+            r#"
+fn print<T: ?Sized>(value: &T) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn relax_bounded_param() {
+        check_assist(
+            relax_sized_bound,
+            r#"
+fn print<T, $0U: Clone + Default = ()>(value: U, other: U, t: T) -> U { value }
+"#,
+            r#"
+fn print<T, U: Clone + Default + ?Sized = ()>(value: &U, other: &U, t: T) -> Box<U> { value }
+"#,
+        );
+    }
+
+    #[test]
+    fn relax_param_with_empty_bounds() {
+        check_assist(
+            relax_sized_bound,
+            r#"
+fn print<$0T:>(value: T) where T: Clone {}
+"#,
+            r#"
+fn print<T: ?Sized>(value: &T) where T: Clone {}
+"#,
+        );
+    }
+
+    #[test]
+    fn relax_method_param() {
+        check_assist(
+            relax_sized_bound,
+            r#"
+struct S;
+impl S {
+    fn print<$0T>(&self, value: T) {}
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn print<T: ?Sized>(&self, value: &T) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn already_relaxed() {
+        check_assist_not_applicable(
+            relax_sized_bound,
+            r#"
+fn print<$0T: Clone + ?Sized>(value: &T) {}
+"#,
+        );
+        check_assist_not_applicable(
+            relax_sized_bound,
+            r#"
+fn print<$0T>(value: &T) where T: ?Sized {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_outside_fns() {
+        check_assist_not_applicable(
+            relax_sized_bound,
+            r#"
+struct S<$0T>(T);
+"#,
+        );
+    }
+}
@@ -108,43 +108,8 @@ fn compute_dbg_replacement(macro_expr: ast::MacroExpr) -> Option<(TextRange, Opt
         }
         // dbg!(expr0)
         [expr] => {
-            // dbg!(expr, &parent);
-            let wrap = match ast::Expr::cast(parent) {
-                Some(parent) => match (expr, parent) {
-                    (ast::Expr::CastExpr(_), ast::Expr::CastExpr(_)) => false,
-                    (
-                        ast::Expr::PrefixExpr(_) | ast::Expr::RefExpr(_) | ast::Expr::MacroExpr(_),
-                        ast::Expr::AwaitExpr(_)
-                        | ast::Expr::CallExpr(_)
-                        | ast::Expr::CastExpr(_)
-                        | ast::Expr::FieldExpr(_)
-                        | ast::Expr::IndexExpr(_)
-                        | ast::Expr::MethodCallExpr(_)
-                        | ast::Expr::RangeExpr(_)
-                        | ast::Expr::TryExpr(_),
-                    ) => true,
-                    (
-                        ast::Expr::BinExpr(_)
-                        | ast::Expr::CastExpr(_)
-                        | ast::Expr::RangeExpr(_)
-                        | ast::Expr::MacroExpr(_),
-                        ast::Expr::AwaitExpr(_)
-                        | ast::Expr::BinExpr(_)
-                        | ast::Expr::CallExpr(_)
-                        | ast::Expr::CastExpr(_)
-                        | ast::Expr::FieldExpr(_)
-                        | ast::Expr::IndexExpr(_)
-                        | ast::Expr::MethodCallExpr(_)
-                        | ast::Expr::PrefixExpr(_)
-                        | ast::Expr::RangeExpr(_)
-                        | ast::Expr::RefExpr(_)
-                        | ast::Expr::TryExpr(_),
-                    ) => true,
-                    _ => false,
-                },
-                None => false,
-            };
             let expr = replace_nested_dbgs(expr.clone());
+            let wrap = expr.needs_parens_in_place_of(&macro_expr.clone().into());
             let expr = if wrap { make::expr_paren(expr) } else { expr.clone_subtree() };
             (macro_call.syntax().text_range(), Some(expr))
         }
@@ -352,7 +317,7 @@ fn test_range_partial() {
     fn test_nested_dbg() {
         check(
             r#"$0let x = dbg!(dbg!(dbg!(dbg!(0 + 1)) * 2) + dbg!(3));$0"#,
-            r#"let x = ((0 + 1) * 2) + 3;"#,
+            r#"let x = (0 + 1) * 2 + 3;"#,
         );
         check(r#"$0dbg!(10, dbg!(), dbg!(20, 30))$0"#, r#"(10, (), (20, 30))"#);
     }
@@ -369,7 +334,7 @@ fn f() {
 "#,
             r#"
 fn f() {
-    let x = ((0 + 1) + 2) + 3;
+    let x = 0 + 1 + 2 + 3;
     (10, (), (20, 30));
 }
 "#,
@@ -0,0 +1,233 @@
+use syntax::{
+    ast::{self, HasAttrs},
+    ted, AstNode, SyntaxKind,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: reorder_fields_by_layout
+//
+// Reorders the fields of a struct from the largest alignment to the smallest, which minimizes the
+// padding between them. The label shows the size of the struct with its fields laid out in the
+// declared order and in the reordered one.
+//
+// ```
+// struct $0Foo {
+//     a: u8,
+//     b: u64,
+//     c: u16,
+// }
+// ```
+// ->
+// ```
+// struct Foo {
+//     b: u64,
+//     c: u16,
+//     a: u8,
+// }
+// ```
+pub(crate) fn reorder_fields_by_layout(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let name = ctx.find_node_at_offset::<ast::Name>()?;
+    let strukt = ast::Struct::cast(name.syntax().parent()?)?;
+    let field_list = match strukt.field_list()? {
+        ast::FieldList::RecordFieldList(it) => it,
+        // Reordering the fields of a tuple struct changes what `.0`, `.1` and so on refer to.
+        ast::FieldList::TupleFieldList(_) => return None,
+    };
+    if has_order_dependent_derive(&strukt) {
+        cov_mark::hit!(reorder_fields_by_layout_ord_derive);
+        return None;
+    }
+
+    let db = ctx.db();
+    let def = ctx.sema.to_def(&strukt)?;
+    if def.repr(db).is_some() {
+        // `repr(C)`, `repr(packed)` and the like make the declared order significant.
+        cov_mark::hit!(reorder_fields_by_layout_repr);
+        return None;
+    }
+    let fields: Vec<_> = field_list.fields().collect();
+    if fields.len() < 2 {
+        return None;
+    }
+    let layouts = fields
+        .iter()
+        .map(|field| {
+            let layout = ctx.sema.to_def(field)?.layout(db).ok()?;
+            Some((layout.size(), layout.align()))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut order: Vec<usize> = (0..fields.len()).collect();
+    // A stable sort keeps the declared order between fields of the same alignment.
+    order.sort_by_key(|&idx| std::cmp::Reverse(layouts[idx].1));
+    let declared_size = sequential_size(layouts.iter().copied());
+    let reordered_size = sequential_size(order.iter().map(|&idx| layouts[idx]));
+    if reordered_size >= declared_size {
+        cov_mark::hit!(reorder_fields_by_layout_no_savings);
+        return None;
+    }
+
+    acc.add(
+        AssistId("reorder_fields_by_layout", AssistKind::RefactorRewrite),
+        format!(
+            "Reorder fields to minimize padding ({declared_size} → {reordered_size} bytes, saves {} bytes)",
+            declared_size - reordered_size
+        ),
+        name.syntax().text_range(),
+        |builder| {
+            let reordered: Vec<_> =
+                order.iter().map(|&idx| fields[idx].clone_for_update()).collect();
+            let field_list = builder.make_mut(field_list);
+            for (old, new) in field_list.fields().zip(reordered) {
+                ted::replace(old.syntax(), new.syntax());
+            }
+        },
+    )
+}
+
+/// The size of a `repr(C)`-like layout of fields with the given sizes and alignments, in order.
+fn sequential_size(fields: impl Iterator<Item = (u64, u64)>) -> u64 {
+    let mut size: u64 = 0;
+    let mut max_align: u64 = 1;
+    for (field_size, align) in fields {
+        size = size.next_multiple_of(align) + field_size;
+        max_align = max_align.max(align);
+    }
+    size.next_multiple_of(max_align)
+}
+
+/// Whether the struct derives a trait whose behavior depends on the order of the fields, like the
+/// lexicographic comparison of `PartialOrd` and `Ord`.
+fn has_order_dependent_derive(strukt: &ast::Struct) -> bool {
+    strukt
+        .attrs()
+        .filter_map(|attr| attr.as_simple_call())
+        .filter(|(name, _)| name == "derive")
+        .flat_map(|(_, tt)| tt.syntax().children_with_tokens().filter_map(|it| it.into_token()))
+        .filter(|token| token.kind() == SyntaxKind::IDENT)
+        .any(|token| matches!(token.text(), "PartialOrd" | "Ord"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        check_assist, check_assist_by_label, check_assist_not_applicable, check_assist_target,
+    };
+
+    use super::*;
+
+    #[test]
+    fn reorders_by_alignment() {
+        check_assist(
+            reorder_fields_by_layout,
+            r#"
+struct $0Foo {
+    /// Flag.
+    a: u8,
+    b: u64,
+    #[allow(dead_code)]
+    c: u16,
+    d: u8,
+}
+"#,
+            r#"
+struct Foo {
+    b: u64,
+    #[allow(dead_code)]
+    c: u16,
+    /// Flag.
+    a: u8,
+    d: u8,
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn label_shows_sizes() {
+        check_assist_by_label(
+            reorder_fields_by_layout,
+            r#"
+struct $0Foo { a: u8, b: u64, c: u16 }
+"#,
+            r#"
+struct Foo { b: u64, c: u16, a: u8 }
+"#,
+            "Reorder fields to minimize padding (24 → 16 bytes, saves 8 bytes)",
+        );
+    }
+
+    #[test]
+    fn target_is_the_name() {
+        check_assist_target(
+            reorder_fields_by_layout,
+            r#"
+struct Fo$0o { a: u8, b: u32, c: u8 }
+"#,
+            "Foo",
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_savings() {
+        cov_mark::check!(reorder_fields_by_layout_no_savings);
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+struct $0Foo { a: u32, b: u8, c: u8 }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_repr() {
+        cov_mark::check!(reorder_fields_by_layout_repr);
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+#[repr(C)]
+struct $0Foo { a: u8, b: u64, c: u8 }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_ord_derive() {
+        cov_mark::check!(reorder_fields_by_layout_ord_derive);
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+//- minicore: derive, ord
+#[derive(PartialEq, PartialOrd)]
+struct $0Foo { a: u8, b: u64, c: u8 }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_to_tuple_and_generic_structs() {
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+struct $0Foo(u8, u64, u8);
+"#,
+        );
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+struct $0Foo<T> { a: u8, b: T, c: u8 }
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_outside_the_name() {
+        check_assist_not_applicable(
+            reorder_fields_by_layout,
+            r#"
+struct Foo { a: u8, $0b: u64, c: u8 }
+"#,
+        );
+    }
+}
@@ -1,18 +1,24 @@
-use hir::{InFile, MacroFileIdExt, ModuleDef};
-use ide_db::{helpers::mod_path_to_ast, imports::import_assets::NameToImport, items_locator};
+use hir::{AsAssocItem, InFile, MacroFileIdExt, ModuleDef, PathResolution};
+use ide_db::{
+    famous_defs::FamousDefs, helpers::mod_path_to_ast, imports::import_assets::NameToImport,
+    items_locator, traits::resolve_target_trait,
+};
 use itertools::Itertools;
 use syntax::{
-    ast::{self, make, AstNode, HasName},
-    ted,
+    ast::{
+        self, edit_in_place::AttrsOwnerEdit, make, AstNode, HasArgList, HasAttrs, HasGenericParams,
+        HasName,
+    },
+    ted, Direction, NodeOrToken,
     SyntaxKind::WHITESPACE,
-    T,
+    SyntaxNode, SyntaxToken, T,
 };
 
 use crate::{
     assist_context::{AssistContext, Assists, SourceChangeBuilder},
     utils::{
-        add_trait_assoc_items_to_impl, filter_assoc_items, gen_trait_fn_body, generate_trait_impl,
-        DefaultMethods, IgnoreAssocItems,
+        add_trait_assoc_items_to_impl, default_variant_attr, filter_assoc_items, gen_trait_fn_body,
+        generate_trait_impl, DefaultMethods, IgnoreAssocItems,
     },
     AssistId, AssistKind,
 };
@@ -55,18 +61,13 @@ pub(crate) fn replace_derive_with_manual_impl(
         // FIXME: make this work in macro files
         return None;
     }
-    // collect the derive paths from the #[derive] expansion
-    let current_derives = ctx
-        .sema
-        .parse_or_expand(macro_file.into())
-        .descendants()
-        .filter_map(ast::Attr::cast)
-        .filter_map(|attr| attr.path())
-        .collect::<Vec<_>>();
 
     let adt = value.parent().and_then(ast::Adt::cast)?;
     let attr = ast::Attr::cast(value)?;
-    let args = attr.token_tree()?;
+    // The attribute may be a `cfg_attr` with several derive lists.
+    let args = derive_lists(&attr)
+        .into_iter()
+        .find(|it| it.syntax().text_range().contains(ctx.offset()))?;
 
     let current_module = ctx.sema.scope(adt.syntax())?.module();
     let current_crate = current_module.krate();
@@ -96,20 +97,10 @@ pub(crate) fn replace_derive_with_manual_impl(
 
     let mut no_traits_found = true;
     for (replace_trait_path, trait_) in found_traits.inspect(|_| no_traits_found = false) {
-        add_assist(
-            acc,
-            ctx,
-            &attr,
-            &current_derives,
-            &args,
-            &path,
-            &replace_trait_path,
-            Some(trait_),
-            &adt,
-        )?;
+        add_assist(acc, ctx, &attr, &args, &path, &replace_trait_path, Some(trait_), &adt)?;
     }
     if no_traits_found {
-        add_assist(acc, ctx, &attr, &current_derives, &args, &path, &path, None, &adt)?;
+        add_assist(acc, ctx, &attr, &args, &path, &path, None, &adt)?;
     }
     Some(())
 }
@@ -118,7 +109,6 @@ fn add_assist(
     acc: &mut Assists,
     ctx: &AssistContext<'_>,
     attr: &ast::Attr,
-    old_derives: &[ast::Path],
     old_tree: &ast::TokenTree,
     old_trait_path: &ast::Path,
     replace_trait_path: &ast::Path,
@@ -138,19 +128,27 @@ fn add_assist(
 
             let impl_def_with_items =
                 impl_def_from_trait(&ctx.sema, adt, &annotated_name, trait_, replace_trait_path);
-            update_attribute(builder, old_derives, old_tree, old_trait_path, attr);
+            let is_default = replace_trait_path
+                .segment()
+                .and_then(|it| it.name_ref())
+                .map_or(false, |it| it.text() == "Default");
+            let default_attr =
+                default_variant_attr(adt).filter(|_| is_default).map(|it| builder.make_mut(it));
+            // A derive that only applies under some `cfg` is replaced with an impl that does too.
+            let cfg = cfg_attr_predicate(attr).map(|predicate| {
+                make::attr_outer(make::meta_token_tree(make::ext::ident_path("cfg"), predicate))
+                    .clone_for_update()
+            });
+            update_attribute(builder, old_tree, old_trait_path, attr);
+            if let Some(default_attr) = default_attr {
+                // `#[default]` is only allowed along with `#[derive(Default)]`.
+                remove_attr(default_attr);
+            }
 
             let trait_path = make::ty_path(replace_trait_path.clone());
 
-            match (ctx.config.snippet_cap, impl_def_with_items) {
-                (None, _) => {
-                    let impl_def = generate_trait_impl(adt, trait_path);
-
-                    ted::insert_all(
-                        insert_after,
-                        vec![make::tokens::blank_line().into(), impl_def.syntax().clone().into()],
-                    );
-                }
+            let impl_def = match (ctx.config.snippet_cap, impl_def_with_items) {
+                (None, _) => generate_trait_impl(adt, trait_path),
                 (Some(cap), None) => {
                     let impl_def = generate_trait_impl(adt, trait_path);
 
@@ -159,11 +157,7 @@ fn add_assist(
                     {
                         builder.add_tabstop_after_token(cap, l_curly);
                     }
-
-                    ted::insert_all(
-                        insert_after,
-                        vec![make::tokens::blank_line().into(), impl_def.syntax().clone().into()],
-                    );
+                    impl_def
                 }
                 (Some(cap), Some((impl_def, first_assoc_item))) => {
                     let mut added_snippet = false;
@@ -182,13 +176,16 @@ fn add_assist(
                         // If we haven't already added a snippet, add a tabstop before the generated function
                         builder.add_tabstop_before(cap, first_assoc_item);
                     }
-
-                    ted::insert_all(
-                        insert_after,
-                        vec![make::tokens::blank_line().into(), impl_def.syntax().clone().into()],
-                    );
+                    impl_def
                 }
             };
+            if let Some(cfg) = cfg {
+                impl_def.add_attr(cfg);
+            }
+            ted::insert_all(
+                insert_after,
+                vec![make::tokens::blank_line().into(), impl_def.syntax().clone().into()],
+            );
         },
     )
 }
@@ -231,46 +228,388 @@ fn impl_def_from_trait(
 
 fn update_attribute(
     builder: &mut SourceChangeBuilder,
-    old_derives: &[ast::Path],
     old_tree: &ast::TokenTree,
     old_trait_path: &ast::Path,
     attr: &ast::Attr,
 ) {
-    let new_derives = old_derives
-        .iter()
-        .filter(|t| t.to_string() != old_trait_path.to_string())
+    let old_trait_path = old_trait_path.to_string();
+    let new_derives = split_token_tree(old_tree)
+        .into_iter()
+        .filter(|derive| derive.iter().join("") != old_trait_path)
         .collect::<Vec<_>>();
     let has_more_derives = !new_derives.is_empty();
 
     if has_more_derives {
         let old_tree = builder.make_mut(old_tree.clone());
 
-        // Make the paths into flat lists of tokens in a vec
-        let tt = new_derives.iter().map(|path| path.syntax().clone()).map(|node| {
-            node.descendants_with_tokens()
-                .filter_map(|element| element.into_token())
-                .collect::<Vec<_>>()
-        });
-        // ...which are interspersed with ", "
-        let tt = Itertools::intersperse(tt, vec![make::token(T![,]), make::tokens::single_space()]);
-        // ...wrap them into the appropriate `NodeOrToken` variant
-        let tt = tt.flatten().map(syntax::NodeOrToken::Token);
-        // ...and make them into a flat list of tokens
-        let tt = tt.collect::<Vec<_>>();
+        // Intersperse the derives with ", "
+        let tt = Itertools::intersperse(
+            new_derives.into_iter(),
+            vec![
+                NodeOrToken::Token(make::token(T![,])),
+                NodeOrToken::Token(make::tokens::single_space()),
+            ],
+        );
+        let tt = tt.flatten().collect::<Vec<_>>();
 
         let new_tree = make::token_tree(T!['('], tt).clone_for_update();
         ted::replace(old_tree.syntax(), new_tree.syntax());
+    } else if cfg_attr_predicate(attr).is_some() && cfg_attr_len(attr) > 1 {
+        // Keep the other attributes of the `cfg_attr`, removing `, derive(..)`.
+        let old_tree = builder.make_mut(old_tree.clone());
+        let start = old_tree
+            .syntax()
+            .siblings_with_tokens(Direction::Prev)
+            .find(|it| it.kind() == T![,])
+            .unwrap_or_else(|| old_tree.syntax().clone().into());
+        ted::remove_all(start..=old_tree.syntax().clone().into());
     } else {
-        // Remove the attr and any trailing whitespace
-        let attr = builder.make_mut(attr.clone());
+        remove_attr(builder.make_mut(attr.clone()));
+    }
+}
+
+/// The derive lists of `attr`, which is either `#[derive(..)]` or
+/// `#[cfg_attr(predicate, derive(..), ..)]`.
+fn derive_lists(attr: &ast::Attr) -> Vec<ast::TokenTree> {
+    match attr.as_simple_call() {
+        Some((name, tt)) if name == "derive" => vec![tt],
+        Some((name, tt)) if name == "cfg_attr" => split_token_tree(&tt)
+            .into_iter()
+            .skip(1)
+            .filter_map(|part| match part.as_slice() {
+                [NodeOrToken::Token(name), NodeOrToken::Node(tt)] if name.text() == "derive" => {
+                    Some(tt.clone())
+                }
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The predicate of `#[cfg_attr(predicate, ..)]`, as the token tree of a `#[cfg]` attribute.
+fn cfg_attr_predicate(attr: &ast::Attr) -> Option<ast::TokenTree> {
+    let (name, tt) = attr.as_simple_call()?;
+    if name != "cfg_attr" {
+        return None;
+    }
+    let predicate = split_token_tree(&tt).into_iter().next()?;
+    Some(make::token_tree(T!['('], predicate))
+}
+
+/// The number of attributes a `cfg_attr` applies.
+fn cfg_attr_len(attr: &ast::Attr) -> usize {
+    attr.token_tree().map_or(0, |tt| split_token_tree(&tt).len().saturating_sub(1))
+}
+
+/// Splits the contents of `tt` at its commas, without the whitespace around each part.
+fn split_token_tree(tt: &ast::TokenTree) -> Vec<Vec<NodeOrToken<ast::TokenTree, SyntaxToken>>> {
+    let mut elements = tt.token_trees_and_tokens().collect::<Vec<_>>();
+    // Skip the delimiters.
+    if elements.len() < 2 {
+        return Vec::new();
+    }
+    elements.pop();
+    elements.remove(0);
+    elements
+        .split(|it| matches!(it, NodeOrToken::Token(it) if it.kind() == T![,]))
+        .map(|part| {
+            let is_whitespace = |it: &NodeOrToken<_, SyntaxToken>| {
+                it.as_token().map_or(false, |it| it.kind() == WHITESPACE)
+            };
+            let start = part.iter().position(|it| !is_whitespace(it)).unwrap_or(part.len());
+            let end = part.iter().rposition(|it| !is_whitespace(it)).map_or(start, |it| it + 1);
+            part[start..end].to_vec()
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Removes the attr and any trailing whitespace.
+fn remove_attr(attr: ast::Attr) {
+    if let Some(line_break) =
+        attr.syntax().next_sibling_or_token().filter(|t| t.kind() == WHITESPACE)
+    {
+        ted::remove(line_break)
+    }
+
+    ted::remove(attr.syntax())
+}
+
+// Assist: replace_default_impl_with_derive
+//
+// Replaces a manual `Default` impl that only uses the default values of the fields with
+// `#[derive(Default)]`. For an enum, the returned variant is marked with `#[default]`.
+//
+// ```
+// # //- minicore: default, derive
+// struct S {
+//     count: usize,
+//     ready: bool,
+// }
+//
+// impl Default$0 for S {
+//     fn default() -> Self {
+//         Self { count: 0, ready: Default::default() }
+//     }
+// }
+// ```
+// ->
+// ```
+// #[derive(Default)]
+// struct S {
+//     count: usize,
+//     ready: bool,
+// }
+// ```
+pub(crate) fn replace_default_impl_with_derive(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let impl_ = ctx.find_node_at_offset::<ast::Impl>()?;
+    let assoc_items = impl_.assoc_item_list()?;
+    if assoc_items.syntax().text_range().contains(ctx.offset()) {
+        return None;
+    }
+    let krate = ctx.sema.scope(impl_.syntax())?.krate();
+    let famous_defs = FamousDefs(&ctx.sema, krate);
+    if resolve_target_trait(&ctx.sema, &impl_)? != famous_defs.core_default_Default()? {
+        return None;
+    }
+
+    let ast::AssocItem::Fn(func) = assoc_items.assoc_items().exactly_one().ok()? else {
+        return None;
+    };
+    let body = func.body()?.stmt_list()?;
+    if body.statements().next().is_some() {
+        return None;
+    }
+    let tail = body.tail_expr()?;
+
+    let adt = match ctx.sema.resolve_type(&impl_.self_ty()?)?.as_adt()? {
+        hir::Adt::Union(_) => return None,
+        it => it,
+    };
+    let adt_src = ctx.sema.source(adt)?;
+    if adt_src.file_id != ctx.file_id().into() || adt_src.value.generic_param_list().is_some() {
+        // Deriving would add `T: Default` bounds for the generic parameters.
+        return None;
+    }
+    let adt = adt_src.value;
+
+    let default_variant = match &adt {
+        ast::Adt::Enum(_) => Some(default_variant(ctx, &tail)?),
+        _ => {
+            if !constructs_default_fields(ctx, &famous_defs, &tail)? {
+                return None;
+            }
+            None
+        }
+    };
+
+    // An impl that only exists under some `cfg` becomes a derive under the same `cfg`.
+    let cfg = impl_
+        .attrs()
+        .filter_map(|attr| attr.as_simple_call())
+        .find(|(name, _)| name == "cfg")
+        .map(|(_, tt)| tt);
+    let derive_tt = match &cfg {
+        Some(cfg) => {
+            let cfg = strip_whitespace(cfg.syntax());
+            adt.attrs()
+                .filter(|attr| {
+                    cfg_attr_predicate(attr)
+                        .map_or(false, |it| strip_whitespace(it.syntax()) == cfg)
+                })
+                .find_map(|attr| derive_lists(&attr).into_iter().next())
+        }
+        None => adt
+            .attrs()
+            .filter_map(|attr| attr.as_simple_call())
+            .find(|(name, _)| name == "derive")
+            .map(|(_, tt)| tt),
+    };
+
+    acc.add(
+        AssistId("replace_default_impl_with_derive", AssistKind::Refactor),
+        "Replace with `#[derive(Default)]`",
+        impl_.syntax().text_range(),
+        |builder| {
+            let impl_ = builder.make_mut(impl_.clone());
+            let default_variant = default_variant.map(|it| builder.make_mut(it));
+            let derive_tt = derive_tt.map(|it| builder.make_mut(it));
+            let adt = builder.make_mut(adt.clone());
+
+            if let Some(ws) =
+                impl_.syntax().prev_sibling_or_token().filter(|it| it.kind() == WHITESPACE)
+            {
+                ted::remove(ws);
+            }
+            ted::remove(impl_.syntax());
+
+            if let Some(variant) = default_variant {
+                let default = make::tokens::ident("default");
+                let attr = match &cfg {
+                    Some(cfg) => make::meta_token_tree(
+                        make::ext::ident_path("cfg_attr"),
+                        append_to_token_tree(cfg, vec![NodeOrToken::Token(default)]),
+                    ),
+                    None => make::meta_path(make::ext::ident_path("default")),
+                };
+                variant.add_attr(make::attr_outer(attr).clone_for_update());
+            }
+
+            let default = NodeOrToken::Token(make::tokens::ident("Default"));
+            match derive_tt {
+                Some(tt) => {
+                    let new_tt = append_to_token_tree(&tt, vec![default]).clone_for_update();
+                    ted::replace(tt.syntax(), new_tt.syntax());
+                }
+                None => {
+                    let derive = make::token_tree(T!['('], vec![default]);
+                    let attr = match &cfg {
+                        Some(cfg) => make::meta_token_tree(
+                            make::ext::ident_path("cfg_attr"),
+                            append_to_token_tree(
+                                cfg,
+                                vec![
+                                    NodeOrToken::Token(make::tokens::ident("derive")),
+                                    NodeOrToken::Node(derive),
+                                ],
+                            ),
+                        ),
+                        None => make::meta_token_tree(make::ext::ident_path("derive"), derive),
+                    };
+                    adt.add_attr(make::attr_outer(attr).clone_for_update());
+                }
+            }
+        },
+    )
+}
+
+/// `tt` with `entry` appended as another comma-separated entry.
+fn append_to_token_tree(
+    tt: &ast::TokenTree,
+    entry: Vec<NodeOrToken<ast::TokenTree, SyntaxToken>>,
+) -> ast::TokenTree {
+    let mut tokens = tt.token_trees_and_tokens().skip(1).collect::<Vec<_>>();
+    // Drop the closing delimiter and any whitespace before it.
+    tokens.pop();
+    while matches!(tokens.last(), Some(NodeOrToken::Token(it)) if it.kind() == WHITESPACE) {
+        tokens.pop();
+    }
+    match tokens.last() {
+        None => (),
+        Some(NodeOrToken::Token(it)) if it.kind() == T![,] => {
+            tokens.push(NodeOrToken::Token(make::tokens::single_space()))
+        }
+        Some(_) => tokens.extend([
+            NodeOrToken::Token(make::token(T![,])),
+            NodeOrToken::Token(make::tokens::single_space()),
+        ]),
+    }
+    tokens.extend(entry);
+    make::token_tree(T!['('], tokens)
+}
+
+fn strip_whitespace(node: &SyntaxNode) -> String {
+    node.descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() != WHITESPACE)
+        .map(|it| it.text().to_owned())
+        .collect()
+}
+
+/// The unit variant that `expr` evaluates to, which `#[derive(Default)]` can use.
+fn default_variant(ctx: &AssistContext<'_>, expr: &ast::Expr) -> Option<ast::Variant> {
+    let ast::Expr::PathExpr(path) = expr else { return None };
+    let Some(PathResolution::Def(ModuleDef::Variant(variant))) =
+        ctx.sema.resolve_path(&path.path()?)
+    else {
+        return None;
+    };
+    if !variant.fields(ctx.db()).is_empty() {
+        return None;
+    }
+    Some(ctx.sema.source(variant)?.value)
+}
 
-        if let Some(line_break) =
-            attr.syntax().next_sibling_or_token().filter(|t| t.kind() == WHITESPACE)
-        {
-            ted::remove(line_break)
+/// Whether `expr` constructs a struct from the default values of its fields.
+fn constructs_default_fields(
+    ctx: &AssistContext<'_>,
+    famous_defs: &FamousDefs<'_, '_>,
+    expr: &ast::Expr,
+) -> Option<bool> {
+    let is_struct = |path: &ast::Path| {
+        matches!(
+            ctx.sema.resolve_path(path),
+            Some(PathResolution::Def(ModuleDef::Adt(_)) | PathResolution::SelfType(_))
+        )
+    };
+    let res = match expr {
+        ast::Expr::RecordExpr(it) => {
+            let fields = it.record_expr_field_list()?;
+            is_struct(&it.path()?)
+                && fields.spread().is_none()
+                && fields.fields().all(|field| {
+                    field.name_ref().is_some()
+                        && field.expr().map_or(false, |it| is_default_value(ctx, famous_defs, &it))
+                })
         }
+        ast::Expr::CallExpr(it) => {
+            let ast::Expr::PathExpr(callee) = it.expr()? else { return Some(false) };
+            is_struct(&callee.path()?)
+                && it.arg_list()?.args().all(|it| is_default_value(ctx, famous_defs, &it))
+        }
+        ast::Expr::PathExpr(it) => is_struct(&it.path()?),
+        _ => false,
+    };
+    Some(res)
+}
 
-        ted::remove(attr.syntax())
+/// Whether `expr` is known to evaluate to `Default::default()` of its type.
+fn is_default_value(
+    ctx: &AssistContext<'_>,
+    famous_defs: &FamousDefs<'_, '_>,
+    expr: &ast::Expr,
+) -> bool {
+    match expr {
+        ast::Expr::ParenExpr(it) => {
+            it.expr().map_or(false, |it| is_default_value(ctx, famous_defs, &it))
+        }
+        ast::Expr::TupleExpr(it) => it.fields().all(|it| is_default_value(ctx, famous_defs, &it)),
+        ast::Expr::CallExpr(call) => {
+            let is_default_fn = |func: hir::Function| {
+                func.name(ctx.db()).display(ctx.db()).to_string() == "default"
+                    && func
+                        .as_assoc_item(ctx.db())
+                        .and_then(|it| it.container_or_implemented_trait(ctx.db()))
+                        == famous_defs.core_default_Default()
+            };
+            let callee = match call.expr() {
+                Some(ast::Expr::PathExpr(it)) => {
+                    it.path().and_then(|it| ctx.sema.resolve_path(&it))
+                }
+                _ => None,
+            };
+            call.arg_list().map_or(false, |it| it.args().next().is_none())
+                && matches!(
+                    callee,
+                    Some(PathResolution::Def(ModuleDef::Function(func))) if is_default_fn(func)
+                )
+        }
+        ast::Expr::PathExpr(path) => {
+            // `Option`'s default is `None`.
+            let none = famous_defs.core_option_Option().and_then(|it| {
+                it.variants(ctx.db())
+                    .into_iter()
+                    .find(|it| it.name(ctx.db()).display(ctx.db()).to_string() == "None")
+            });
+            none.is_some()
+                && path.path().and_then(|it| ctx.sema.resolve_path(&it))
+                    == none.map(|it| PathResolution::Def(it.into()))
+        }
+        _ => ctx.sema.is_default_scalar(expr) == Some(true),
     }
 }
 
@@ -502,6 +841,34 @@ impl Default for Foo {
         )
     }
 
+    #[test]
+    fn add_custom_impl_default_enum() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: default, derive
+#[derive(Defau$0lt)]
+enum Foo {
+    Bar(usize),
+    #[default]
+    Baz,
+}
+"#,
+            r#"
+enum Foo {
+    Bar(usize),
+    Baz,
+}
+
+impl Default for Foo {
+    $0fn default() -> Self {
+        Self::Baz
+    }
+}
+"#,
+        )
+    }
+
     #[test]
     fn add_custom_impl_hash_record_struct() {
         check_assist(
@@ -1379,4 +1746,314 @@ impl core::fmt::Debug for Foo {
 "#,
         )
     }
+
+    #[test]
+    fn add_custom_impl_cfg_attr_derive() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: fmt, derive, clone
+//- /main.rs crate:main cfg:feature=std
+#[cfg_attr(feature = "std", derive(Clone, Debu$0g))]
+struct Foo {
+    bar: String,
+}
+"#,
+            r#"
+#[cfg_attr(feature = "std", derive(Clone))]
+struct Foo {
+    bar: String,
+}
+
+#[cfg(feature = "std")]
+impl core::fmt::Debug for Foo {
+    $0fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Foo").field("bar", &self.bar).finish()
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_cfg_attr_keeps_other_attrs() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: derive, clone
+//- /main.rs crate:main cfg:feature=std
+#[cfg_attr(feature = "std", allow(dead_code), derive(Cl$0one))]
+struct Foo;
+"#,
+            r#"
+#[cfg_attr(feature = "std", allow(dead_code))]
+struct Foo;
+
+#[cfg(feature = "std")]
+impl Clone for Foo {
+    $0fn clone(&self) -> Self {
+        Self {  }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn add_custom_impl_cfg_attr_removes_empty_attr() {
+        check_assist(
+            replace_derive_with_manual_impl,
+            r#"
+//- minicore: derive, clone
+//- /main.rs crate:main cfg:feature=std
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Cl$0one))]
+struct Foo;
+"#,
+            r#"
+#[derive(Debug)]
+struct Foo;
+
+#[cfg(feature = "std")]
+impl Clone for Foo {
+    $0fn clone(&self) -> Self {
+        Self {  }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn replace_default_impl_record_struct() {
+        check_assist(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive, option
+struct Foo {
+    count: usize,
+    ratio: f32,
+    ready: bool,
+    pair: (i8, char),
+    name: Option<Bar>,
+    bar: Bar,
+}
+
+struct Bar;
+
+impl Default for Bar {
+    fn default() -> Self {
+        Bar
+    }
+}
+
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Foo {
+            count: 1 - 1,
+            ratio: 0.0,
+            ready: (false),
+            pair: (0, '\0'),
+            name: None,
+            bar: Bar::default(),
+        }
+    }
+}
+"#,
+            r#"
+#[derive(Default)]
+struct Foo {
+    count: usize,
+    ratio: f32,
+    ready: bool,
+    pair: (i8, char),
+    name: Option<Bar>,
+    bar: Bar,
+}
+
+struct Bar;
+
+impl Default for Bar {
+    fn default() -> Self {
+        Bar
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn replace_default_impl_tuple_struct_with_derive() {
+        check_assist(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive, clone
+#[derive(Clone)]
+struct Foo(u8, bool);
+
+impl $0Default for Foo {
+    fn default() -> Self {
+        Self(Default::default(), <bool as Default>::default())
+    }
+}
+"#,
+            r#"
+#[derive(Clone, Default)]
+struct Foo(u8, bool);
+"#,
+        )
+    }
+
+    #[test]
+    fn replace_default_impl_enum() {
+        check_assist(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+/// A direction.
+enum Direction {
+    Up,
+    /// Towards the ground.
+    Down,
+}
+
+impl Default for Direction$0 {
+    fn default() -> Self {
+        Self::Down
+    }
+}
+"#,
+            r#"
+/// A direction.
+#[derive(Default)]
+enum Direction {
+    Up,
+    /// Towards the ground.
+    #[default]
+    Down,
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn replace_cfg_default_impl_with_cfg_attr_derive() {
+        check_assist(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive, clone, eq
+//- /main.rs crate:main cfg:feature=std
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(PartialEq))]
+struct Foo(u8);
+
+#[cfg(feature = "std")]
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Self(0)
+    }
+}
+"#,
+            r#"
+#[derive(Clone)]
+#[cfg_attr(feature = "std", derive(PartialEq, Default))]
+struct Foo(u8);
+"#,
+        );
+        check_assist(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+//- /main.rs crate:main cfg:feature=std
+#[derive(Debug)]
+enum Foo { Bar, Baz }
+
+#[cfg(feature = "std")]
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Self::Baz
+    }
+}
+"#,
+            r#"
+#[derive(Debug)]
+#[cfg_attr(feature = "std", derive(Default))]
+enum Foo { Bar, #[cfg_attr(feature = "std", default)]
+Baz }
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_default_impl_not_applicable() {
+        // Not a default value.
+        check_assist_not_applicable(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+struct Foo { count: usize, ready: bool }
+
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Self { count: 1, ready: false }
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+struct Foo(f64);
+
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Self(-0.0)
+    }
+}
+"#,
+        );
+        // Deriving adds `T: Default`.
+        check_assist_not_applicable(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive, option
+struct Foo<T>(Option<T>);
+
+impl<T> Default$0 for Foo<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+"#,
+        );
+        // Only unit variants can be `#[default]`.
+        check_assist_not_applicable(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+enum Foo { Bar(u8), Baz }
+
+impl Default$0 for Foo {
+    fn default() -> Self {
+        Self::Bar(0)
+    }
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_default_impl_with_derive,
+            r#"
+//- minicore: default, derive
+struct Foo { count: usize }
+
+impl Default for Foo {
+    fn default() -> Self {
+        let count = 0;
+        Self { count$0 }
+    }
+}
+"#,
+        );
+    }
 }
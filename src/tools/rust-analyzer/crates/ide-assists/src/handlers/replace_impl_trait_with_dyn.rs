@@ -0,0 +1,429 @@
+use hir::{ModuleDef, PathResolution};
+use ide_db::{base_db::FileId, defs::Definition};
+use syntax::{
+    ast::{self, HasArgList},
+    AstNode, TextRange,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_impl_trait_with_dyn
+//
+// Replaces an `impl Trait` function argument with a trait object, adjusting the call sites. Only
+// available when the trait is object safe.
+//
+// ```
+// trait Shape {}
+// struct Circle;
+// impl Shape for Circle {}
+//
+// fn draw(shape: $0impl Shape) {}
+//
+// fn main() {
+//     draw(Circle);
+// }
+// ```
+// ->
+// ```
+// trait Shape {}
+// struct Circle;
+// impl Shape for Circle {}
+//
+// fn draw(shape: &dyn Shape) {}
+//
+// fn main() {
+//     draw(&Circle);
+// }
+// ```
+pub(crate) fn replace_impl_trait_with_dyn(
+    acc: &mut Assists,
+    ctx: &AssistContext<'_>,
+) -> Option<()> {
+    let impl_trait_type = ctx.find_node_at_offset::<ast::ImplTraitType>()?;
+    let type_bound_list = impl_trait_type.type_bound_list()?;
+    if !is_object_safe_bound_list(ctx, &type_bound_list) {
+        return None;
+    }
+    let has_many_bounds = type_bound_list.bounds().nth(1).is_some();
+    let bounds = type_bound_list.syntax().text();
+    let target = impl_trait_type.syntax().text_range();
+
+    let parent = impl_trait_type.syntax().parent()?;
+    if let Some(ref_type) = ast::RefType::cast(parent.clone()) {
+        // `&impl Trait` arguments coerce to `&dyn Trait` as they are.
+        ref_type.syntax().parent().and_then(ast::Param::cast)?;
+        let new_ty =
+            if has_many_bounds { format!("(dyn {bounds})") } else { format!("dyn {bounds}") };
+        return acc.add(
+            AssistId("replace_impl_trait_with_dyn", AssistKind::RefactorRewrite),
+            "Replace impl trait with trait object",
+            target,
+            |edit| edit.replace(target, new_ty),
+        );
+    }
+
+    let param = ast::Param::cast(parent)?;
+    let fn_ = param.syntax().ancestors().find_map(ast::Fn::cast)?;
+    let param_list = fn_.param_list()?;
+    let param_idx = param_list.params().position(|it| it == param)?;
+    let func = ctx.sema.to_def(&fn_)?;
+    let args = call_site_args(ctx, func, param_idx, param_list.self_param().is_some());
+
+    let ref_ty =
+        if has_many_bounds { format!("&(dyn {bounds})") } else { format!("&dyn {bounds}") };
+    acc.add(
+        AssistId("replace_impl_trait_with_dyn", AssistKind::RefactorRewrite),
+        "Replace impl trait with `&dyn` trait object",
+        target,
+        |edit| {
+            edit.replace(target, ref_ty);
+            for (file_id, args) in &args {
+                edit.edit_file(*file_id);
+                for (range, arg) in args {
+                    if matches!(arg, ast::Expr::RefExpr(_)) {
+                        continue;
+                    }
+                    if needs_parens(arg) {
+                        edit.insert(range.start(), "&(");
+                        edit.insert(range.end(), ")");
+                    } else {
+                        edit.insert(range.start(), "&");
+                    }
+                }
+            }
+        },
+    );
+    acc.add(
+        AssistId("replace_impl_trait_with_dyn", AssistKind::RefactorRewrite),
+        "Replace impl trait with `Box<dyn>` trait object",
+        target,
+        |edit| {
+            edit.replace(target, format!("Box<dyn {bounds}>"));
+            for (file_id, args) in &args {
+                edit.edit_file(*file_id);
+                for (range, _) in args {
+                    edit.insert(range.start(), "Box::new(");
+                    edit.insert(range.end(), ")");
+                }
+            }
+        },
+    )
+}
+
+/// `dyn` allows a single object safe trait, along with auto traits and lifetimes.
+fn is_object_safe_bound_list(ctx: &AssistContext<'_>, bounds: &ast::TypeBoundList) -> bool {
+    let mut principal_traits = 0;
+    for bound in bounds.bounds() {
+        if bound.lifetime().is_some() {
+            continue;
+        }
+        // `?Sized`, `~const` and the like can't be used with trait objects.
+        if bound.question_mark_token().is_some() || bound.tilde_token().is_some() {
+            return false;
+        }
+        let Some(ast::Type::PathType(path_type)) = bound.ty() else { return false };
+        let Some(PathResolution::Def(ModuleDef::Trait(trait_))) =
+            path_type.path().and_then(|path| ctx.sema.resolve_path(&path))
+        else {
+            return false;
+        };
+        if trait_.is_auto(ctx.db()) {
+            continue;
+        }
+        principal_traits += 1;
+        if principal_traits > 1 || !trait_.is_object_safe(ctx.db()) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Finds the argument passed for the param at `param_idx` at each call of `func`, grouped by file.
+fn call_site_args(
+    ctx: &AssistContext<'_>,
+    func: hir::Function,
+    param_idx: usize,
+    has_self_param: bool,
+) -> Vec<(FileId, Vec<(TextRange, ast::Expr)>)> {
+    let usages = Definition::Function(func).usages(&ctx.sema).all();
+    usages
+        .into_iter()
+        .map(|(file_id, refs)| {
+            let args = refs
+                .iter()
+                .filter_map(|it| {
+                    let arg = call_site_arg(it.name.as_name_ref()?, param_idx, has_self_param)?;
+                    let range = ctx.sema.original_range_opt(arg.syntax())?;
+                    (range.file_id == file_id).then_some((range.range, arg))
+                })
+                .collect::<Vec<_>>();
+            (file_id, args)
+        })
+        .filter(|(_, args)| !args.is_empty())
+        .collect()
+}
+
+fn call_site_arg(
+    name_ref: &ast::NameRef,
+    param_idx: usize,
+    has_self_param: bool,
+) -> Option<ast::Expr> {
+    let parent = name_ref.syntax().parent()?;
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return method_call.arg_list()?.args().nth(param_idx);
+    }
+    let path = ast::PathSegment::cast(parent)?.parent_path();
+    let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+    let call = ast::CallExpr::cast(path_expr.syntax().parent()?)?;
+    if call.expr()?.syntax() != path_expr.syntax() {
+        return None;
+    }
+    // `Type::method(receiver, ..)` passes the receiver as the first argument.
+    call.arg_list()?.args().nth(param_idx + has_self_param as usize)
+}
+
+fn needs_parens(arg: &ast::Expr) -> bool {
+    matches!(arg, ast::Expr::BinExpr(_) | ast::Expr::RangeExpr(_) | ast::Expr::CastExpr(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_by_label, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn replace_with_ref_dyn() {
+        check_assist_by_label(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: $0impl Shape, scale: i32) {}
+
+fn main() {
+    let c = Circle;
+    draw(c, 1);
+    draw(&Circle, 2);
+}
+"#,
+            r#"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: &dyn Shape, scale: i32) {}
+
+fn main() {
+    let c = Circle;
+    draw(&c, 1);
+    draw(&Circle, 2);
+}
+"#,
+            "Replace impl trait with `&dyn` trait object",
+        );
+    }
+
+    #[test]
+    fn replace_with_box_dyn() {
+        check_assist(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: $0impl Shape) {}
+
+fn main() {
+    draw(Circle);
+}
+"#,
+            r#"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: Box<dyn Shape>) {}
+
+fn main() {
+    draw(Box::new(Circle));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_method_param_updates_both_call_syntaxes() {
+        check_assist_by_label(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {}
+impl Shape for i32 {}
+struct Canvas;
+impl Canvas {
+    fn draw(&self, shape: $0impl Shape) {}
+}
+
+fn main() {
+    Canvas.draw(1 + 2);
+    Canvas::draw(&Canvas, 3);
+}
+"#,
+            r#"
+trait Shape {}
+impl Shape for i32 {}
+struct Canvas;
+impl Canvas {
+    fn draw(&self, shape: &dyn Shape) {}
+}
+
+fn main() {
+    Canvas.draw(&(1 + 2));
+    Canvas::draw(&Canvas, &3);
+}
+"#,
+            "Replace impl trait with `&dyn` trait object",
+        );
+    }
+
+    #[test]
+    fn replace_keeps_auto_traits_and_parenthesizes_refs() {
+        check_assist_by_label(
+            replace_impl_trait_with_dyn,
+            r#"
+//- minicore: send, sync
+trait Shape {}
+fn draw(shape: $0impl Shape + Send) {}
+"#,
+            r#"
+trait Shape {}
+fn draw(shape: &(dyn Shape + Send)) {}
+"#,
+            "Replace impl trait with `&dyn` trait object",
+        );
+        check_assist(
+            replace_impl_trait_with_dyn,
+            r#"
+//- minicore: send, sync
+trait Shape {}
+fn draw(shape: &mut $0impl Shape + Send) {}
+"#,
+            r#"
+trait Shape {}
+fn draw(shape: &mut (dyn Shape + Send)) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_fn_trait() {
+        check_assist(
+            replace_impl_trait_with_dyn,
+            r#"
+//- minicore: fn
+fn apply(f: $0impl Fn(i32) -> i32) -> i32 { f(1) }
+
+fn main() {
+    apply(|x| x + 1);
+}
+"#,
+            r#"
+fn apply(f: Box<dyn Fn(i32) -> i32>) -> i32 { f(1) }
+
+fn main() {
+    apply(Box::new(|x| x + 1));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_updates_call_sites_in_other_files() {
+        check_assist(
+            replace_impl_trait_with_dyn,
+            r#"
+//- /main.rs
+mod shapes;
+pub trait Shape {}
+pub fn draw(shape: $0impl Shape) {}
+//- /shapes.rs
+struct Circle;
+impl crate::Shape for Circle {}
+fn f() {
+    crate::draw(Circle);
+}
+"#,
+            r#"
+//- /main.rs
+mod shapes;
+pub trait Shape {}
+pub fn draw(shape: Box<dyn Shape>) {}
+//- /shapes.rs
+struct Circle;
+impl crate::Shape for Circle {}
+fn f() {
+    crate::draw(Box::new(Circle));
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_non_object_safe_traits() {
+        check_assist_not_applicable(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {
+    fn dup(&self) -> Self;
+}
+fn draw(shape: $0impl Shape) {}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {
+    fn map<T>(&self, t: T);
+}
+fn draw(shape: &$0impl Shape) {}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_impl_trait_with_dyn,
+            r#"
+//- minicore: sized, dispatch_from_dyn
+struct Vec<T>(T);
+trait Shape {
+    fn all(self: Vec<Self>);
+}
+fn draw(shape: &$0impl Shape) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_multiple_principal_traits() {
+        check_assist_not_applicable(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {}
+trait Named {}
+fn draw(shape: $0impl Shape + Named) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_nested_types() {
+        check_assist_not_applicable(
+            replace_impl_trait_with_dyn,
+            r#"
+trait Shape {}
+fn draw(shapes: Vec<$0impl Shape>) {}
+"#,
+        );
+    }
+}
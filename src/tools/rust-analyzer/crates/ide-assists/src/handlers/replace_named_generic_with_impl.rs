@@ -14,7 +14,7 @@
 };
 use text_edit::TextRange;
 
-use crate::{AssistContext, AssistId, AssistKind, Assists};
+use crate::{utils::TurbofishUsages, AssistContext, AssistId, AssistKind, Assists};
 
 // Assist: replace_named_generic_with_impl
 //
@@ -42,6 +42,14 @@ pub(crate) fn replace_named_generic_with_impl(
     let fn_ = type_param.syntax().ancestors().find_map(ast::Fn::cast)?;
     let param_list_text_range = fn_.param_list()?.syntax().text_range();
 
+    // Position of the param in turbofishes, which can't name the lifetime params.
+    let arg_idx = fn_
+        .generic_param_list()?
+        .generic_params()
+        .filter(|it| !matches!(it, ast::GenericParam::LifetimeParam(_)))
+        .position(|it| it.syntax() == type_param.syntax())?;
+    let turbofishes = TurbofishUsages::find(ctx, ctx.sema.to_def(&fn_)?);
+
     let type_param_hir_def = ctx.sema.to_def(&type_param)?;
     let type_param_def = Definition::GenericParam(hir::GenericParam::TypeParam(type_param_hir_def));
 
@@ -76,6 +84,7 @@ pub(crate) fn replace_named_generic_with_impl(
         |edit| {
             let type_param = edit.make_mut(type_param);
             let fn_ = edit.make_mut(fn_);
+            let turbofishes = turbofishes.make_mut(edit);
 
             let path_types_to_replace = path_types_to_replace
                 .into_iter()
@@ -94,6 +103,20 @@ pub(crate) fn replace_named_generic_with_impl(
             for path_type in path_types_to_replace.iter().rev() {
                 ted::replace(path_type.syntax(), new_bounds.clone_for_update().syntax());
             }
+
+            // `impl Trait` params can't be named in turbofishes.
+            turbofishes.edit(edit, |args| {
+                let arg = args
+                    .generic_args()
+                    .filter(|it| !matches!(it, ast::GenericArg::LifetimeArg(_)))
+                    .nth(arg_idx);
+                if let Some(arg) = arg {
+                    args.remove_generic_arg(arg);
+                }
+                if args.generic_args().next().is_none() {
+                    ted::remove(args.syntax());
+                }
+            });
         },
     )
 }
@@ -351,4 +374,50 @@ fn new(p: impl Send + Sync) {}
             "#,
         );
     }
+
+    #[test]
+    fn replace_generic_removes_turbofish_args() {
+        check_assist(
+            replace_named_generic_with_impl,
+            r#"
+fn foo<'a, T, P$0: ToString>(t: &'a T, p: P) {}
+fn bar<P$0: ToString>(p: P) {}
+fn main() {
+    foo::<i32, String>(&1, String::new());
+    foo(&1, 2);
+}
+"#,
+            r#"
+fn foo<'a, T>(t: &'a T, p: impl ToString) {}
+fn bar<P: ToString>(p: P) {}
+fn main() {
+    foo::<i32>(&1, String::new());
+    foo(&1, 2);
+}
+"#,
+        );
+        check_assist(
+            replace_named_generic_with_impl,
+            r#"
+struct S;
+impl S {
+    fn bar<P$0: ToString>(&self, p: P) {}
+}
+fn main() {
+    S.bar::<String>(String::new());
+    S::bar::<String>(&S, String::new());
+}
+"#,
+            r#"
+struct S;
+impl S {
+    fn bar(&self, p: impl ToString) {}
+}
+fn main() {
+    S.bar(String::new());
+    S::bar(&S, String::new());
+}
+"#,
+        );
+    }
 }
@@ -69,6 +69,7 @@ pub(crate) fn replace_qualified_name_with_use(
                 ctx.config.insert_use.prefix_kind,
                 ctx.config.prefer_no_std,
                 ctx.config.prefer_prelude,
+                ctx.config.insert_use.prefer_reexports,
             )
         })
         .flatten();
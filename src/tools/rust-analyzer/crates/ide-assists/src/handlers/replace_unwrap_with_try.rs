@@ -0,0 +1,539 @@
+use hir::{AsAssocItem, AssocItemContainer, HirDisplay};
+use ide_db::{
+    defs::Definition,
+    famous_defs::FamousDefs,
+    syntax_helpers::node_ext::{for_each_tail_expr, walk_expr},
+};
+use syntax::{
+    ast::{self, edit::IndentLevel, HasArgList},
+    AstNode, TextRange, TextSize,
+};
+
+use crate::{AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: replace_unwrap_with_try
+//
+// Replaces an `unwrap()` or `expect()` call with the `?` operator, making the function return a
+// `Result` or an `Option` if it doesn't already. Calls of the function get unwrapped, so the same
+// conversion can be repeated up the call chain.
+//
+// ```
+// # //- minicore: result
+// fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+// fn double(s: &str) -> u32 {
+//     parse(s).unwrap$0() * 2
+// }
+// fn main() {
+//     let x = double("1");
+// }
+// ```
+// ->
+// ```
+// fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+// fn double(s: &str) -> Result<u32, ()> {
+//     Ok(parse(s)? * 2)
+// }
+// fn main() {
+//     let x = double("1").unwrap();
+// }
+// ```
+pub(crate) fn replace_unwrap_with_try(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let method_call = ctx.find_node_at_offset::<ast::MethodCallExpr>()?;
+    let name_ref = method_call.name_ref()?;
+    if !matches!(name_ref.text().as_str(), "unwrap" | "expect") {
+        return None;
+    }
+    let receiver = method_call.receiver()?;
+    let receiver_ty = ctx.sema.type_of_expr(&receiver)?.original;
+    let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(method_call.syntax())?.krate());
+    let kind = TryKind::of(&famous_defs, &receiver_ty)?;
+
+    // `?` returns from the innermost function, which has to be a real one for us to change it.
+    let fn_ = method_call.syntax().ancestors().find_map(|it| {
+        if ast::ClosureExpr::can_cast(it.kind()) {
+            Some(None)
+        } else {
+            ast::Fn::cast(it).map(Some)
+        }
+    })??;
+    if fn_.const_token().is_some() {
+        return None;
+    }
+    let body = fn_.body()?;
+    let func = ctx.sema.to_def(&fn_)?;
+    let ret_ty = func.ret_type(ctx.db());
+
+    let unwrap_range = TextRange::new(
+        receiver.syntax().text_range().end(),
+        method_call.syntax().text_range().end(),
+    );
+    let target = name_ref.syntax().text_range();
+    match TryKind::of(&famous_defs, &ret_ty) {
+        Some(ret_kind) => {
+            let try_op = match (kind, ret_kind) {
+                (TryKind::Result, TryKind::Result) | (TryKind::Option, TryKind::Option) => "?",
+                (TryKind::Result, TryKind::Option) => ".ok()?",
+                // There is no error to return for a `None`.
+                (TryKind::Option, TryKind::Result) => return None,
+            };
+            acc.add(
+                AssistId("replace_unwrap_with_try", AssistKind::RefactorRewrite),
+                format!("Replace `{name_ref}` with `{try_op}`"),
+                target,
+                |edit| edit.replace(unwrap_range, try_op),
+            )
+        }
+        None => {
+            // The signature of trait methods can't change on its own.
+            if let Some(assoc) = func.as_assoc_item(ctx.db()) {
+                match assoc.container(ctx.db()) {
+                    AssocItemContainer::Trait(_) => return None,
+                    AssocItemContainer::Impl(impl_) if impl_.trait_(ctx.db()).is_some() => {
+                        return None
+                    }
+                    AssocItemContainer::Impl(_) => (),
+                }
+            }
+            let module = ctx.sema.scope(fn_.syntax())?.module();
+            let new_ret_ty = match kind {
+                TryKind::Result => {
+                    let err_ty = receiver_ty.type_arguments().nth(1)?;
+                    let err_ty = err_ty.display_source_code(ctx.db(), module.into(), true).ok()?;
+                    match fn_.ret_type().and_then(|it| it.ty()) {
+                        Some(ty) => format!("Result<{ty}, {err_ty}>"),
+                        None => format!("Result<(), {err_ty}>"),
+                    }
+                }
+                TryKind::Option => match fn_.ret_type().and_then(|it| it.ty()) {
+                    Some(ty) => format!("Option<{ty}>"),
+                    None => "Option<()>".to_owned(),
+                },
+            };
+            let callers = callers(ctx, &famous_defs, func, kind, &receiver_ty)?;
+
+            acc.add(
+                AssistId("replace_unwrap_with_try", AssistKind::RefactorRewrite),
+                format!("Replace `{name_ref}` with `?` and return `{}`", kind.name()),
+                target,
+                |edit| {
+                    edit.edit_file(ctx.file_id());
+                    // Edits inside of wrapped expressions have to come first.
+                    edit.replace(unwrap_range, "?");
+                    if let Some(call_sites) = callers.iter().find(|(it, _)| *it == ctx.file_id()) {
+                        for (offset, text) in &call_sites.1 {
+                            edit.insert(*offset, *text);
+                        }
+                    }
+
+                    let wrapper = kind.wrapper();
+                    match fn_.ret_type() {
+                        Some(ret_type) => {
+                            if let Some(ty) = ret_type.ty() {
+                                edit.replace(ty.syntax().text_range(), new_ret_ty);
+                            }
+                        }
+                        None => {
+                            if let Some(param_list) = fn_.param_list() {
+                                edit.insert(
+                                    param_list.syntax().text_range().end(),
+                                    format!(" -> {new_ret_ty}"),
+                                );
+                            }
+                        }
+                    }
+                    let body_expr = ast::Expr::BlockExpr(body.clone());
+                    let mut exprs_to_wrap = Vec::new();
+                    let mut empty_returns = Vec::new();
+                    walk_expr(&body_expr, &mut |expr| {
+                        if let ast::Expr::ReturnExpr(ret_expr) = expr {
+                            match ret_expr.expr() {
+                                Some(arg) => for_each_tail_expr(&arg, &mut |e| {
+                                    tail_exprs(&mut exprs_to_wrap, e)
+                                }),
+                                None => empty_returns.push(ret_expr.syntax().text_range()),
+                            }
+                        }
+                    });
+                    let has_tail = body.stmt_list().and_then(|it| it.tail_expr()).is_some();
+                    if has_tail {
+                        for_each_tail_expr(&body_expr, &mut |e| tail_exprs(&mut exprs_to_wrap, e));
+                    } else if let Some(stmt_list) = body.stmt_list() {
+                        let indent = IndentLevel::from_node(body.syntax()) + 1;
+                        let end = stmt_list
+                            .statements()
+                            .last()
+                            .map(|it| it.syntax().text_range().end())
+                            .or_else(|| Some(stmt_list.l_curly_token()?.text_range().end()));
+                        if let Some(end) = end {
+                            edit.insert(end, format!("\n{indent}{wrapper}(())"));
+                        }
+                    }
+                    for range in empty_returns {
+                        edit.insert(range.end(), format!(" {wrapper}(())"));
+                    }
+                    for expr in exprs_to_wrap {
+                        let range = expr.syntax().text_range();
+                        edit.insert(range.start(), format!("{wrapper}("));
+                        edit.insert(range.end(), ")");
+                    }
+
+                    for (file_id, call_sites) in &callers {
+                        if *file_id == ctx.file_id() {
+                            continue;
+                        }
+                        edit.edit_file(*file_id);
+                        for (offset, text) in call_sites {
+                            edit.insert(*offset, *text);
+                        }
+                    }
+                },
+            )
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TryKind {
+    Result,
+    Option,
+}
+
+impl TryKind {
+    fn of(famous_defs: &FamousDefs<'_, '_>, ty: &hir::Type) -> Option<TryKind> {
+        let hir::Adt::Enum(enum_) = ty.as_adt()? else { return None };
+        if Some(enum_) == famous_defs.core_result_Result() {
+            Some(TryKind::Result)
+        } else if Some(enum_) == famous_defs.core_option_Option() {
+            Some(TryKind::Option)
+        } else {
+            None
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            TryKind::Result => "Result",
+            TryKind::Option => "Option",
+        }
+    }
+
+    fn wrapper(self) -> &'static str {
+        match self {
+            TryKind::Result => "Ok",
+            TryKind::Option => "Some",
+        }
+    }
+}
+
+fn tail_exprs(acc: &mut Vec<ast::Expr>, e: &ast::Expr) {
+    match e {
+        ast::Expr::BreakExpr(break_expr) => {
+            if let Some(break_expr_arg) = break_expr.expr() {
+                for_each_tail_expr(&break_expr_arg, &mut |e| tail_exprs(acc, e))
+            }
+        }
+        // all return expressions are handled separately
+        ast::Expr::ReturnExpr(_) => (),
+        e => acc.push(e.clone()),
+    }
+}
+
+/// Finds the calls of `func`, which now have to handle the error: with `?` in functions returning
+/// the same kind of error, and by unwrapping elsewhere. Returns `None` if `func` is referenced
+/// other than by calling it.
+fn callers(
+    ctx: &AssistContext<'_>,
+    famous_defs: &FamousDefs<'_, '_>,
+    func: hir::Function,
+    kind: TryKind,
+    receiver_ty: &hir::Type,
+) -> Option<Vec<(ide_db::base_db::FileId, Vec<(TextSize, &'static str)>)>> {
+    let usages = Definition::Function(func).usages(&ctx.sema).all();
+    usages
+        .into_iter()
+        .map(|(file_id, refs)| {
+            let call_sites = refs
+                .iter()
+                .map(|reference| {
+                    let name_ref = reference.name.as_name_ref()?;
+                    let call = call_of(name_ref)?;
+                    let range = ctx.sema.original_range_opt(call.syntax())?.range;
+                    let caller = call.syntax().ancestors().find_map(|it| {
+                        if ast::ClosureExpr::can_cast(it.kind()) {
+                            Some(None)
+                        } else {
+                            ast::Fn::cast(it).map(Some)
+                        }
+                    });
+                    let caller = caller.flatten().and_then(|it| ctx.sema.to_def(&it));
+                    let propagates = caller.map_or(false, |caller| {
+                        // Recursive calls are within the function that now returns the error.
+                        caller == func
+                            || returns_same_error(ctx, famous_defs, caller, kind, receiver_ty)
+                    });
+                    Some((range.end(), if propagates { "?" } else { ".unwrap()" }))
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((file_id, call_sites))
+        })
+        .collect()
+}
+
+fn call_of(name_ref: &ast::NameRef) -> Option<ast::Expr> {
+    let parent = name_ref.syntax().parent()?;
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return Some(method_call.into());
+    }
+    let path = ast::PathSegment::cast(parent)?.parent_path();
+    let path_expr = ast::PathExpr::cast(path.syntax().parent()?)?;
+    let call = ast::CallExpr::cast(path_expr.syntax().parent()?)?;
+    (call.expr()?.syntax() == path_expr.syntax() && call.arg_list().is_some()).then(|| call.into())
+}
+
+fn returns_same_error(
+    ctx: &AssistContext<'_>,
+    famous_defs: &FamousDefs<'_, '_>,
+    caller: hir::Function,
+    kind: TryKind,
+    receiver_ty: &hir::Type,
+) -> bool {
+    let ret_ty = caller.ret_type(ctx.db());
+    match TryKind::of(famous_defs, &ret_ty) {
+        Some(TryKind::Option) => kind == TryKind::Option,
+        Some(TryKind::Result) => {
+            kind == TryKind::Result
+                && ret_ty.type_arguments().nth(1) == receiver_ty.type_arguments().nth(1)
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_assist, check_assist_not_applicable};
+
+    use super::*;
+
+    #[test]
+    fn in_fn_returning_result() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Result<u32, ()> {
+    let x = parse(s).unwr$0ap();
+    Ok(x * 2)
+}
+"#,
+            r#"
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Result<u32, ()> {
+    let x = parse(s)?;
+    Ok(x * 2)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn result_in_fn_returning_option() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result, option
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Option<u32> {
+    Some(parse(s).expect$0("valid number") * 2)
+}
+"#,
+            r#"
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Option<u32> {
+    Some(parse(s).ok()? * 2)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn changes_unit_fn_and_unwraps_callers() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result
+struct Error;
+fn parse(s: &str) -> Result<u32, Error> { Ok(0) }
+fn check(s: &str) {
+    if s.is_empty() {
+        return;
+    }
+    parse(s).$0unwrap();
+}
+fn main() {
+    check("1");
+}
+"#,
+            r#"
+struct Error;
+fn parse(s: &str) -> Result<u32, Error> { Ok(0) }
+fn check(s: &str) -> Result<(), Error> {
+    if s.is_empty() {
+        return Ok(());
+    }
+    parse(s)?;
+    Ok(())
+}
+fn main() {
+    check("1").unwrap();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn propagates_in_callers_returning_same_error() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result
+struct Error;
+fn parse(s: &str) -> Result<u32, Error> { Ok(0) }
+fn double(s: &str) -> u32 {
+    if s == "0" {
+        return 0;
+    }
+    parse(s).unwrap$0() * 2
+}
+fn quadruple(s: &str) -> Result<u32, Error> {
+    Ok(double(s) * 2)
+}
+fn sum(s: &str) -> u32 {
+    double(s) + double(s)
+}
+"#,
+            r#"
+struct Error;
+fn parse(s: &str) -> Result<u32, Error> { Ok(0) }
+fn double(s: &str) -> Result<u32, Error> {
+    if s == "0" {
+        return Ok(0);
+    }
+    Ok(parse(s)? * 2)
+}
+fn quadruple(s: &str) -> Result<u32, Error> {
+    Ok(double(s)? * 2)
+}
+fn sum(s: &str) -> u32 {
+    double(s).unwrap() + double(s).unwrap()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn recursive_calls_propagate() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+fn count(v: Option<u32>, n: u32) -> u32 {
+    if n == 0 { v.unwrap$0() } else { count(v, n - 1) }
+}
+"#,
+            r#"
+fn count(v: Option<u32>, n: u32) -> Option<u32> {
+    if n == 0 { Some(v?) } else { Some(count(v, n - 1)?) }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn updates_callers_in_other_files() {
+        check_assist(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+//- /main.rs
+mod user;
+pub fn first(v: Option<u32>) -> u32 {
+    v.unwrap$0()
+}
+//- /user.rs
+pub fn f() -> Option<u32> {
+    Some(crate::first(None) + 1)
+}
+pub fn g() {
+    let _ = crate::first(Some(1));
+}
+"#,
+            r#"
+//- /main.rs
+mod user;
+pub fn first(v: Option<u32>) -> Option<u32> {
+    Some(v?)
+}
+//- /user.rs
+pub fn f() -> Option<u32> {
+    Some(crate::first(None)? + 1)
+}
+pub fn g() {
+    let _ = crate::first(Some(1)).unwrap();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_for_option_in_fn_returning_result() {
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: result, option
+fn f(v: Option<u32>) -> Result<u32, ()> {
+    Ok(v.unwrap$0())
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_in_closures_and_trait_impls() {
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+fn f(v: Option<u32>) {
+    let g = || v.unwrap$0();
+}
+"#,
+        );
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+trait Get { fn get(&self) -> u32; }
+impl Get for Option<u32> {
+    fn get(&self) -> u32 { self.unwrap$0() }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_when_fn_is_not_only_called() {
+        check_assist_not_applicable(
+            replace_unwrap_with_try,
+            r#"
+//- minicore: option
+fn first(v: Option<u32>) -> u32 {
+    v.unwrap$0()
+}
+fn main() {
+    let f = first;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use hir::PathResolution;
+use ide_db::famous_defs::FamousDefs;
+use itertools::Itertools;
+use stdx::format_to;
+use syntax::{
+    ast::{self, edit::IndentLevel, HasGenericParams, HasName, HasTypeBounds},
+    AstNode, Direction, SyntaxKind, TextRange, T,
+};
+
+use crate::{assist_config::BoundsStyle, AssistContext, AssistId, AssistKind, Assists};
+
+// Assist: simplify_bounds
+//
+// Removes the bounds of a function's generic parameters that are repeated or already implied by
+// their other bounds, like supertraits, and collects the bounds of each parameter in the `where`
+// clause, or in the parameter list with `rust-analyzer.assist.boundsStyle` set to `inline`.
+//
+// ```
+// trait Shape {}
+// trait Circle: Shape {}
+// fn dr$0aw<T: Shape + Circle>(shape: T) where T: Circle {}
+// ```
+// ->
+// ```
+// trait Shape {}
+// trait Circle: Shape {}
+// fn draw<T>(shape: T) where T: Circle {}
+// ```
+pub(crate) fn simplify_bounds(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let fn_ = ctx.find_node_at_offset::<ast::Fn>()?;
+    let generic_param_list = fn_.generic_param_list()?;
+    let where_clause = fn_.where_clause();
+    let signature_ranges = [
+        fn_.name().map(|it| it.syntax().text_range()),
+        Some(generic_param_list.syntax().text_range()),
+        where_clause.as_ref().map(|it| it.syntax().text_range()),
+    ];
+    if !signature_ranges.iter().flatten().any(|it| it.contains_inclusive(ctx.offset())) {
+        return None;
+    }
+
+    let mut params: Vec<(ast::TypeParam, Vec<ast::TypeBound>)> = generic_param_list
+        .type_or_const_params()
+        .filter_map(|it| match it {
+            ast::TypeOrConstParam::Type(it) => Some(it),
+            ast::TypeOrConstParam::Const(_) => None,
+        })
+        .map(|it| {
+            let bounds = it.type_bound_list().into_iter().flat_map(|it| it.bounds()).collect();
+            (it, bounds)
+        })
+        .collect();
+    let has_inline_bounds = params.iter().any(|(_, bounds)| !bounds.is_empty());
+    let mut other_predicates = Vec::new();
+    let mut predicates_per_param = vec![0; params.len()];
+    for predicate in where_clause.iter().flat_map(|it| it.predicates()) {
+        match predicate_param(&predicate, &params) {
+            Some(idx) => {
+                predicates_per_param[idx] += 1;
+                params[idx]
+                    .1
+                    .extend(predicate.type_bound_list().into_iter().flat_map(|it| it.bounds()));
+            }
+            None => other_predicates.push(predicate),
+        }
+    }
+
+    let sized = FamousDefs(&ctx.sema, ctx.sema.scope(fn_.syntax())?.krate()).core_marker_Sized();
+    let mut removed_any = false;
+    let mut param_bounds = Vec::with_capacity(params.len());
+    for (param, bounds) in &params {
+        let kept = simplify(ctx, param, bounds, sized)?;
+        removed_any |= kept.len() < bounds.len();
+        param_bounds.push(kept);
+    }
+    let moves_bounds = match ctx.config.bounds_style {
+        BoundsStyle::WhereClause => {
+            has_inline_bounds || predicates_per_param.iter().any(|&it| it > 1)
+        }
+        BoundsStyle::Inline => predicates_per_param.iter().any(|&it| it > 0),
+    };
+    if !removed_any && !moves_bounds {
+        return None;
+    }
+
+    let mut new_params = Vec::new();
+    let mut new_predicates = Vec::new();
+    let mut params = params.iter().zip(&param_bounds);
+    for generic_param in generic_param_list.generic_params() {
+        let ast::GenericParam::TypeParam(param) = generic_param else {
+            new_params.push(generic_param.to_string());
+            continue;
+        };
+        let (_, bounds) = params.next()?;
+        let name = param.name()?;
+        let mut text = name.to_string();
+        if !bounds.is_empty() {
+            let bounds = bounds.iter().join(" + ");
+            match ctx.config.bounds_style {
+                BoundsStyle::WhereClause => new_predicates.push(format!("{name}: {bounds}")),
+                BoundsStyle::Inline => format_to!(text, ": {bounds}"),
+            }
+        }
+        if let Some(default) = param.default_type() {
+            format_to!(text, " = {default}");
+        }
+        new_params.push(text);
+    }
+    new_predicates.extend(other_predicates.iter().map(|it| it.to_string()));
+
+    let target = generic_param_list.syntax().text_range();
+    acc.add(
+        AssistId("simplify_bounds", AssistKind::RefactorRewrite),
+        "Simplify bounds",
+        target,
+        |builder| {
+            builder.replace(target, format!("<{}>", new_params.iter().format(", ")));
+            match where_clause {
+                Some(where_clause) if new_predicates.is_empty() => {
+                    let (range, replacement) = where_clause_removal(&where_clause);
+                    builder.replace(range, replacement);
+                }
+                Some(where_clause) => {
+                    let text = if where_clause.syntax().text().contains_char('\n') {
+                        let indent = IndentLevel::from_node(fn_.syntax()) + 1;
+                        new_predicates.iter().map(|it| format!("\n{indent}{it},")).join("")
+                    } else {
+                        format!(" {}", new_predicates.iter().format(", "))
+                    };
+                    builder.replace(where_clause.syntax().text_range(), format!("where{text}"));
+                }
+                None if new_predicates.is_empty() => (),
+                None => {
+                    let anchor = match fn_.ret_type() {
+                        Some(ret_type) => ret_type.syntax().text_range(),
+                        None => fn_.param_list().map_or(target, |it| it.syntax().text_range()),
+                    };
+                    builder.insert(
+                        anchor.end(),
+                        format!(" where {}", new_predicates.iter().format(", ")),
+                    );
+                }
+            }
+        },
+    )
+}
+
+/// Returns the index of the parameter `predicate` bounds, if it's a plain `T: Bound` predicate.
+fn predicate_param(
+    predicate: &ast::WherePred,
+    params: &[(ast::TypeParam, Vec<ast::TypeBound>)],
+) -> Option<usize> {
+    if predicate.generic_param_list().is_some() {
+        return None;
+    }
+    let ast::Type::PathType(ty) = predicate.ty()? else { return None };
+    let name_ref = ty.path()?.as_single_name_ref()?;
+    params
+        .iter()
+        .position(|(param, _)| param.name().map_or(false, |name| name.text() == name_ref.text()))
+}
+
+/// Returns the bounds of `param` without repeated and implied ones.
+fn simplify(
+    ctx: &AssistContext<'_>,
+    param: &ast::TypeParam,
+    bounds: &[ast::TypeBound],
+    sized: Option<hir::Trait>,
+) -> Option<Vec<ast::TypeBound>> {
+    let ty = ctx.sema.to_def(param)?.ty(ctx.db());
+    let resolved: Vec<_> = bounds.iter().map(|it| resolve_bound(ctx, it)).collect();
+    let is_maybe_sized = bounds.iter().any(|it| it.question_mark_token().is_some());
+    let key = |bound: &ast::TypeBound| {
+        bound
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| it.kind() != SyntaxKind::WHITESPACE)
+            .map(|it| it.to_string())
+            .collect::<String>()
+    };
+
+    // Repeated bounds are removed first, so that a bound isn't considered implied by its copy.
+    let mut keep: Vec<_> = bounds
+        .iter()
+        .enumerate()
+        .map(|(idx, bound)| !bounds[..idx].iter().any(|it| key(it) == key(bound)))
+        .collect();
+    for idx in 0..bounds.len() {
+        if !keep[idx] {
+            continue;
+        }
+        let Some(implied) = &resolved[idx] else { continue };
+        if implied.has_bindings {
+            continue;
+        }
+        if Some(implied.trait_) == sized && implied.args.is_empty() && !is_maybe_sized {
+            keep[idx] = false;
+            continue;
+        }
+        let is_implied = resolved.iter().enumerate().any(|(other_idx, other)| {
+            let Some(other) = other.as_ref().filter(|_| other_idx != idx && keep[other_idx]) else {
+                return false;
+            };
+            ty.trait_bound_implies(
+                ctx.db(),
+                (other.trait_, &other.args),
+                (implied.trait_, &implied.args),
+            )
+        });
+        if is_implied {
+            keep[idx] = false;
+        }
+    }
+    Some(bounds.iter().zip(keep).filter(|(_, keep)| *keep).map(|(it, _)| it.clone()).collect())
+}
+
+struct TraitBound {
+    trait_: hir::Trait,
+    args: Vec<hir::Type>,
+    /// Whether the bound also constrains associated types, like `Iterator<Item = u8>`.
+    has_bindings: bool,
+}
+
+fn resolve_bound(ctx: &AssistContext<'_>, bound: &ast::TypeBound) -> Option<TraitBound> {
+    if bound.question_mark_token().is_some()
+        || bound.tilde_token().is_some()
+        || bound.const_token().is_some()
+        || bound.async_token().is_some()
+    {
+        return None;
+    }
+    let ast::Type::PathType(ty) = bound.ty()? else { return None };
+    let path = ty.path()?;
+    let segment = path.segment()?;
+    if segment.param_list().is_some() {
+        // `Fn(A) -> B` sugar
+        return None;
+    }
+    let PathResolution::Def(hir::ModuleDef::Trait(trait_)) = ctx.sema.resolve_path(&path)? else {
+        return None;
+    };
+    let mut args = Vec::new();
+    let mut has_bindings = false;
+    for arg in segment.generic_arg_list().into_iter().flat_map(|it| it.generic_args()) {
+        match arg {
+            ast::GenericArg::TypeArg(it) => args.push(ctx.sema.resolve_type(&it.ty()?)?),
+            ast::GenericArg::AssocTypeArg(_) => has_bindings = true,
+            ast::GenericArg::LifetimeArg(_) => (),
+            ast::GenericArg::ConstArg(_) => return None,
+        }
+    }
+    Some(TraitBound { trait_, args, has_bindings })
+}
+
+/// Returns the range to replace to remove `where_clause`, with the replacement keeping the function
+/// body on the same line.
+fn where_clause_removal(where_clause: &ast::WhereClause) -> (TextRange, &'static str) {
+    let syntax = where_clause.syntax();
+    let start = match syntax.prev_sibling_or_token() {
+        Some(it) if it.kind() == SyntaxKind::WHITESPACE => it.text_range().start(),
+        _ => syntax.text_range().start(),
+    };
+    let next = syntax
+        .siblings_with_tokens(Direction::Next)
+        .skip(1)
+        .find(|it| it.kind() != SyntaxKind::WHITESPACE);
+    match next {
+        Some(next) if next.kind() == T![;] => {
+            (TextRange::new(start, next.text_range().start()), "")
+        }
+        Some(next) => (TextRange::new(start, next.text_range().start()), " "),
+        None => (TextRange::new(start, syntax.text_range().end()), ""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_assist, check_assist_not_applicable, check_assist_with_config, TEST_CONFIG},
+        AssistConfig,
+    };
+
+    use super::*;
+
+    #[test]
+    fn removes_duplicated_bounds() {
+        check_assist(
+            simplify_bounds,
+            r#"
+trait Foo {}
+trait Bar {}
+fn f$0<T: Foo + Bar + Foo>(x: T) where T: Bar {}
+"#,
+            r#"
+trait Foo {}
+trait Bar {}
+fn f<T>(x: T) where T: Foo + Bar {}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_supertraits() {
+        check_assist(
+            simplify_bounds,
+            r#"
+//- minicore: eq, ord, copy, clone
+fn f$0<T: Ord + PartialOrd + Eq + Clone, U>(x: T, y: U) where U: Copy + Clone {}
+"#,
+            r#"
+fn f<T, U>(x: T, y: U) where T: Ord + Clone, U: Copy {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_supertraits_with_other_arguments() {
+        check_assist(
+            simplify_bounds,
+            r#"
+trait Convert<T> {}
+trait Number: Convert<u32> {}
+fn f$0<T: Number + Convert<u32> + Convert<u64>>(x: T) {}
+"#,
+            r#"
+trait Convert<T> {}
+trait Number: Convert<u32> {}
+fn f<T>(x: T) where T: Number + Convert<u64> {}
+"#,
+        );
+    }
+
+    #[test]
+    fn fills_in_default_arguments() {
+        check_assist(
+            simplify_bounds,
+            r#"
+trait Compare<Rhs = Self> {}
+trait Total: Compare {}
+trait Other: Compare<u8> {}
+fn f$0<T: Total + Compare, U: Other + Compare>(x: T, y: U) {}
+"#,
+            r#"
+trait Compare<Rhs = Self> {}
+trait Total: Compare {}
+trait Other: Compare<u8> {}
+fn f<T, U>(x: T, y: U) where T: Total, U: Other + Compare {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_bounds_with_associated_types() {
+        check_assist(
+            simplify_bounds,
+            r#"
+//- minicore: iterator
+fn f$0<I: DoubleEndedIterator<Item = u8> + Iterator<Item = u8>>(iter: I) {}
+"#,
+            r#"
+fn f<I>(iter: I) where I: DoubleEndedIterator<Item = u8> + Iterator<Item = u8> {}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_sized() {
+        check_assist(
+            simplify_bounds,
+            r#"
+//- minicore: sized
+trait Foo {}
+fn f$0<T: Sized + Foo, U: ?Sized + Sized>(x: T, y: &U) {}
+"#,
+            r#"
+trait Foo {}
+fn f<T, U>(x: T, y: &U) where T: Foo, U: ?Sized + Sized {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_other_predicates() {
+        check_assist(
+            simplify_bounds,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f$0<'a, T: Foo, const N: usize>(x: &'a T) where [T; N]: Foo, T: Bar + 'a {}
+"#,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f<'a, T, const N: usize>(x: &'a T) where T: Bar + 'a, [T; N]: Foo {}
+"#,
+        );
+    }
+
+    #[test]
+    fn keeps_multiline_where_clause() {
+        check_assist(
+            simplify_bounds,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f$0<T: Foo, U>(x: T, y: U) -> U
+where
+    T: Bar,
+    U: Foo,
+{
+    y
+}
+"#,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f<T, U>(x: T, y: U) -> U
+where
+    T: Bar,
+    U: Foo,
+{
+    y
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn moves_bounds_inline() {
+        check_assist_with_config(
+            AssistConfig { bounds_style: BoundsStyle::Inline, ..TEST_CONFIG },
+            simplify_bounds,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f$0<T: Foo, U>(x: T, y: U) -> U
+where
+    T: Bar,
+    U: Foo,
+    Vec<T>: Foo,
+{
+    y
+}
+"#,
+            r#"
+trait Foo {}
+trait Bar: Foo {}
+fn f<T: Bar, U: Foo>(x: T, y: U) -> U
+where
+    Vec<T>: Foo,
+{
+    y
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn removes_empty_where_clause() {
+        check_assist_with_config(
+            AssistConfig { bounds_style: BoundsStyle::Inline, ..TEST_CONFIG },
+            simplify_bounds,
+            r#"
+trait Foo {}
+fn f$0<T>(x: T)
+where
+    T: Foo,
+{
+}
+"#,
+            r#"
+trait Foo {}
+fn f<T: Foo>(x: T) {
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_without_changes() {
+        check_assist_not_applicable(
+            simplify_bounds,
+            r#"
+trait Foo {}
+trait Bar {}
+fn f$0<T, U>(x: T, y: U) where T: Foo + Bar, U: Foo {}
+"#,
+        );
+        check_assist_not_applicable(
+            simplify_bounds,
+            r#"
+trait Foo {}
+fn f<T: Foo>(x: T) {
+    $0
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,701 @@
+use either::Either;
+use hir::{HirDisplay, Semantics};
+use ide_db::{
+    assists::{AssistId, AssistKind, GroupLabel},
+    defs::{Definition, NameRefClass},
+    famous_defs::FamousDefs,
+    source_change::SourceChangeBuilder,
+    RootDatabase,
+};
+use syntax::{
+    ast::{self, edit::IndentLevel},
+    hacks::parse_expr_from_str,
+    match_ast, ted, AstNode, NodeOrToken, SyntaxNode, TextRange, WalkEvent,
+};
+
+use crate::{AssistContext, Assists};
+
+// Assist: surround_with
+//
+// Surrounds the selected statements or expression with a common construct, fixing up the control
+// flow the construct would otherwise change.
+//
+// ```
+// fn foo() -> i32 {
+//     $0let x = 1;
+//     x + 1$0
+// }
+// ```
+// ->
+// ```
+// fn foo() -> i32 {
+//     unsafe {
+//         let x = 1;
+//         x + 1
+//     }
+// }
+// ```
+pub(crate) fn surround_with(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
+    let range = ctx.selection_trimmed();
+    if range.is_empty() {
+        return None;
+    }
+    let selection = Selection::new(ctx, range)?;
+    if selection.has_escaping_bindings(&ctx.sema) {
+        return None;
+    }
+
+    let tail_ty = selection.tail.as_ref().and_then(|tail| ctx.sema.type_of_expr(tail));
+    let has_value = tail_ty.map_or(false, |ty| !ty.original.is_unit() && !ty.original.is_never());
+    let escapes_loop = selection.contains_escaping_break();
+    let target = selection.range;
+    let group = GroupLabel("Surround with…".to_owned());
+    let id = AssistId("surround_with", AssistKind::RefactorRewrite);
+
+    acc.add_group(&group, id, "Surround with `unsafe` block", target, |builder| {
+        builder.replace(target, selection.wrap("unsafe {", None, "}"));
+    });
+
+    acc.add_group(&group, id, "Surround with `if let`", target, |builder| {
+        let mut footer = "}".to_owned();
+        if has_value {
+            let indent = selection.indent;
+            footer.push_str(&format!(" else {{\n{}todo!()\n{indent}}}", indent + 1));
+        }
+        let text = selection.wrap("if let Some(it) = todo!() {", None, &footer);
+        selection.replace_with_placeholders(builder, ctx, text, |expr| {
+            let ast::Expr::IfExpr(if_expr) = expr else { return vec![] };
+            let Some(ast::Expr::LetExpr(let_expr)) = if_expr.condition() else { return vec![] };
+            let pat = let_expr.pat().map(Either::Left);
+            let scrutinee = let_expr.expr().map(Either::Right);
+            pat.into_iter().chain(scrutinee).collect()
+        });
+    });
+
+    acc.add_group(&group, id, "Surround with `match`", target, |builder| {
+        let header = format!("match todo!() {{\n{}_ => {{", selection.indent + 1);
+        let footer = format!("}}\n{}}}", selection.indent);
+        let text = selection.wrap_nested(&header, None, &footer, 2);
+        selection.replace_with_placeholders(builder, ctx, text, |expr| {
+            let ast::Expr::MatchExpr(match_expr) = expr else { return vec![] };
+            let scrutinee = match_expr.expr().map(Either::Right);
+            let pat = match_expr
+                .match_arm_list()
+                .and_then(|it| it.arms().next())
+                .and_then(|arm| arm.pat())
+                .map(Either::Left);
+            scrutinee.into_iter().chain(pat).collect()
+        });
+    });
+
+    if !escapes_loop {
+        acc.add_group(&group, id, "Surround with `loop`", target, |builder| {
+            let tail = has_value.then(|| "break ".to_owned());
+            builder.replace(
+                target,
+                selection.wrap("loop {", tail.as_ref().map(|it| (it.as_str(), "")), "}"),
+            );
+        });
+    }
+
+    if !escapes_loop && !selection.contains_return() {
+        acc.add_group(&group, id, "Surround with `async` block", target, |builder| {
+            let enclosing = EnclosingBody::new(ctx, &selection);
+            let is_async = enclosing.as_ref().map_or(false, |it| it.is_async);
+            let mut footer = if is_async { "}.await" } else { "}" }.to_owned();
+            let wrap_ok = enclosing
+                .as_ref()
+                .and_then(|it| it.try_wrapper.as_deref())
+                .filter(|_| selection.contains_try());
+            let text = match wrap_ok {
+                Some(wrapper) => {
+                    if is_async {
+                        footer.push('?');
+                    }
+                    let open = format!("{wrapper}(");
+                    if has_value {
+                        selection.wrap("async {", Some((&open, ")")), &footer)
+                    } else {
+                        selection.wrap_with_trailing("async {", &format!("{wrapper}(())"), &footer)
+                    }
+                }
+                None => selection.wrap("async {", None, &footer),
+            };
+            // `async {}.await` is not block-like, so it needs a semicolon in statement position
+            let needs_semi = is_async && selection.tail.is_none();
+            builder.replace(target, if needs_semi { format!("{text};") } else { text });
+        });
+    }
+
+    Some(())
+}
+
+/// The statements or expression a surround-with assist wraps.
+struct Selection {
+    range: TextRange,
+    /// The selected nodes, in source order.
+    nodes: Vec<SyntaxNode>,
+    /// The expression producing the value of the selection, if any.
+    tail: Option<ast::Expr>,
+    /// The statement list the selection is part of, if statements were selected.
+    stmt_list: Option<ast::StmtList>,
+    indent: IndentLevel,
+}
+
+impl Selection {
+    fn new(ctx: &AssistContext<'_>, range: TextRange) -> Option<Selection> {
+        let mut node = match ctx.covering_element() {
+            NodeOrToken::Node(node) => node,
+            NodeOrToken::Token(token) => token.parent()?,
+        };
+        while let Some(parent) = node.parent().filter(|it| it.text_range() == range) {
+            node = parent;
+        }
+
+        let stmt_list = if node.text_range() == range {
+            match node.parent().and_then(ast::StmtList::cast) {
+                Some(stmt_list) => stmt_list,
+                None => {
+                    let expr = ast::Expr::cast(node)?;
+                    return Some(Selection {
+                        range,
+                        nodes: vec![expr.syntax().clone()],
+                        indent: IndentLevel::from_node(expr.syntax()),
+                        tail: Some(expr),
+                        stmt_list: None,
+                    });
+                }
+            }
+        } else {
+            ast::StmtList::cast(node)?
+        };
+
+        let nodes: Vec<_> = stmt_list
+            .syntax()
+            .children()
+            .filter(|it| ast::Stmt::can_cast(it.kind()) || ast::Expr::can_cast(it.kind()))
+            .filter(|it| range.contains_range(it.text_range()))
+            .collect();
+        let first = nodes.first()?;
+        let last = nodes.last()?;
+        if first.text_range().start() != range.start() || last.text_range().end() != range.end() {
+            return None;
+        }
+        let tail = stmt_list.tail_expr().filter(|tail| tail.syntax() == last);
+        let indent = IndentLevel::from_node(first);
+        Some(Selection { range, nodes, tail, stmt_list: Some(stmt_list), indent })
+    }
+
+    /// Replaces the selection with `text`, turning the nodes picked by `placeholders` out of the
+    /// parsed replacement into snippet placeholders if the client supports them.
+    fn replace_with_placeholders(
+        &self,
+        builder: &mut SourceChangeBuilder,
+        ctx: &AssistContext<'_>,
+        text: String,
+        placeholders: impl FnOnce(&ast::Expr) -> Vec<Either<ast::Pat, ast::Expr>>,
+    ) {
+        let (Some(cap), Some(expr)) = (ctx.config.snippet_cap, parse_expr_from_str(&text)) else {
+            builder.replace(self.range, text);
+            return;
+        };
+        let expr = expr.clone_for_update();
+        let nodes: Vec<_> =
+            self.nodes.iter().map(|it| builder.make_syntax_mut(it.clone())).collect();
+        let (Some(first), Some(last)) = (nodes.first(), nodes.last()) else { return };
+        ted::replace_all(
+            first.clone().into()..=last.clone().into(),
+            vec![expr.syntax().clone().into()],
+        );
+        for node in placeholders(&expr) {
+            builder.add_placeholder_snippet(cap, node);
+        }
+    }
+
+    fn is_single_line(&self) -> bool {
+        self.stmt_list.is_none()
+            && !self.nodes.iter().any(|it| it.text().to_string().contains('\n'))
+    }
+
+    /// Renders `header`, the selection and `footer`, optionally surrounding the tail expression
+    /// with `tail_wrap`.
+    fn wrap(&self, header: &str, tail_wrap: Option<(&str, &str)>, footer: &str) -> String {
+        if self.is_single_line() {
+            let (open, close) = tail_wrap.unwrap_or_default();
+            let body = self.nodes.iter().map(|it| it.text().to_string()).collect::<String>();
+            return format!("{header} {open}{body}{close} {footer}");
+        }
+        self.wrap_nested(header, tail_wrap, footer, 1)
+    }
+
+    /// Like [`Selection::wrap`], but appends `trailing` as a new tail expression.
+    fn wrap_with_trailing(&self, header: &str, trailing: &str, footer: &str) -> String {
+        if self.is_single_line() {
+            let body = self.nodes.iter().map(|it| it.text().to_string()).collect::<String>();
+            return format!("{header} {body}; {trailing} {footer}");
+        }
+        let body = self.body(None, 1);
+        let indent = self.indent + 1;
+        format!("{header}\n{body}\n{indent}{trailing}\n{}{footer}", self.indent)
+    }
+
+    fn wrap_nested(
+        &self,
+        header: &str,
+        tail_wrap: Option<(&str, &str)>,
+        footer: &str,
+        depth: u8,
+    ) -> String {
+        let body = self.body(tail_wrap, depth);
+        let footer_indent = self.indent + (depth - 1);
+        format!("{header}\n{body}\n{footer_indent}{footer}")
+    }
+
+    fn body(&self, tail_wrap: Option<(&str, &str)>, depth: u8) -> String {
+        let mut text = String::new();
+        let mut prev_end = None;
+        for node in &self.nodes {
+            if let Some(prev_end) = prev_end {
+                let between = TextRange::new(prev_end, node.text_range().start());
+                let root = node.ancestors().last().unwrap();
+                text.push_str(&root.text().slice(between).to_string());
+            }
+            let node_text = node.text().to_string();
+            match (tail_wrap, self.tail.as_ref()) {
+                (Some((open, close)), Some(tail)) if tail.syntax() == node => {
+                    text.push_str(&format!("{open}{node_text}{close}"))
+                }
+                _ => text.push_str(&node_text),
+            }
+            prev_end = Some(node.text_range().end());
+        }
+        let extra = "    ".repeat(depth as usize);
+        let indent = self.indent + depth;
+        let mut lines = text.lines();
+        let mut body = format!("{indent}{}", lines.next().unwrap_or_default());
+        for line in lines {
+            body.push('\n');
+            if !line.trim().is_empty() {
+                body.push_str(&extra);
+            }
+            body.push_str(line);
+        }
+        body
+    }
+
+    /// Whether a `let` in the selection declares a binding that is used after it, which would go
+    /// out of scope once the selection is wrapped.
+    fn has_escaping_bindings(&self, sema: &Semantics<'_, RootDatabase>) -> bool {
+        let Some(stmt_list) = &self.stmt_list else { return false };
+        let locals: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|it| ast::LetStmt::can_cast(it.kind()))
+            .flat_map(|it| it.descendants().filter_map(ast::IdentPat::cast))
+            .filter_map(|pat| sema.to_def(&pat))
+            .collect();
+        if locals.is_empty() {
+            return false;
+        }
+        stmt_list
+            .syntax()
+            .descendants()
+            .filter(|it| it.text_range().start() >= self.range.end())
+            .filter_map(ast::NameRef::cast)
+            .any(|name_ref| match NameRefClass::classify(sema, &name_ref) {
+                Some(NameRefClass::Definition(Definition::Local(local))) => locals.contains(&local),
+                Some(NameRefClass::FieldShorthand { local_ref, .. }) => locals.contains(&local_ref),
+                _ => false,
+            })
+    }
+
+    /// Iterates over the selected nodes, not descending into closures, nested functions or async
+    /// blocks as those have their own control flow.
+    fn flow_descendants(&self) -> impl Iterator<Item = SyntaxNode> + '_ {
+        self.nodes.iter().flat_map(|node| {
+            let mut preorder = node.preorder();
+            std::iter::from_fn(move || loop {
+                match preorder.next()? {
+                    WalkEvent::Enter(node) => {
+                        let is_boundary = match_ast! {
+                            match (node.clone()) {
+                                ast::ClosureExpr(_) => true,
+                                ast::Fn(_) => true,
+                                ast::BlockExpr(it) => it.async_token().is_some(),
+                                _ => false,
+                            }
+                        };
+                        if is_boundary {
+                            preorder.skip_subtree();
+                        }
+                        return Some(node);
+                    }
+                    WalkEvent::Leave(_) => continue,
+                }
+            })
+        })
+    }
+
+    fn contains_try(&self) -> bool {
+        self.flow_descendants().any(|it| ast::TryExpr::can_cast(it.kind()))
+    }
+
+    fn contains_return(&self) -> bool {
+        self.flow_descendants().any(|it| ast::ReturnExpr::can_cast(it.kind()))
+    }
+
+    /// Whether a `break` or `continue` in the selection targets a loop outside of it.
+    fn contains_escaping_break(&self) -> bool {
+        self.flow_descendants()
+            .filter(|it| {
+                ast::BreakExpr::can_cast(it.kind()) || ast::ContinueExpr::can_cast(it.kind())
+            })
+            .any(|it| {
+                !it.ancestors().take_while(|it| self.range.contains_range(it.text_range())).any(
+                    |it| {
+                        matches!(
+                            ast::Expr::cast(it),
+                            Some(
+                                ast::Expr::LoopExpr(_)
+                                    | ast::Expr::WhileExpr(_)
+                                    | ast::Expr::ForExpr(_)
+                            )
+                        )
+                    },
+                )
+            })
+    }
+}
+
+/// The function, closure or async block whose control flow the selection is part of.
+struct EnclosingBody {
+    is_async: bool,
+    /// The constructor to wrap the value of a fallible selection in, if the body returns a
+    /// `Result` or an `Option`.
+    try_wrapper: Option<String>,
+}
+
+impl EnclosingBody {
+    fn new(ctx: &AssistContext<'_>, selection: &Selection) -> Option<EnclosingBody> {
+        let node = selection.nodes.first()?.ancestors().skip(1).find(|it| {
+            ast::Fn::can_cast(it.kind())
+                || ast::ClosureExpr::can_cast(it.kind())
+                || ast::BlockExpr::cast(it.clone()).map_or(false, |it| it.async_token().is_some())
+        })?;
+        let fn_ = match ast::Fn::cast(node.clone()) {
+            Some(fn_) => fn_,
+            None => {
+                let is_async = match_ast! {
+                    match node {
+                        ast::ClosureExpr(it) => it.async_token().is_some(),
+                        _ => true,
+                    }
+                };
+                return Some(EnclosingBody { is_async, try_wrapper: None });
+            }
+        };
+        let func = ctx.sema.to_def(&fn_)?;
+        let is_async = func.is_async(ctx.db());
+        let ret_ty =
+            if is_async { func.async_ret_type(ctx.db())? } else { func.ret_type(ctx.db()) };
+
+        let famous_defs = FamousDefs(&ctx.sema, ctx.sema.scope(fn_.syntax())?.krate());
+        let module = ctx.sema.scope(fn_.syntax())?.module();
+        let adt = ret_ty.as_adt();
+        let try_wrapper =
+            if adt.is_some() && adt == famous_defs.core_option_Option().map(Into::into) {
+                Some("Some".to_owned())
+            } else if adt.is_some() && adt == famous_defs.core_result_Result().map(Into::into) {
+                let err = ret_ty.type_arguments().nth(1)?;
+                let err = err.display_source_code(ctx.db(), module.into(), true).ok()?;
+                Some(format!("Ok::<_, {err}>"))
+            } else {
+                None
+            };
+        Some(EnclosingBody { is_async, try_wrapper })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{
+        check_assist_by_label, check_assist_not_applicable, check_assist_not_applicable_by_label,
+    };
+
+    use super::*;
+
+    #[test]
+    fn surround_statements_with_unsafe() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    $0let x = 1;
+    bar(x);$0
+    baz();
+}
+"#,
+            r#"
+fn foo() {
+    unsafe {
+        let x = 1;
+        bar(x);
+    }
+    baz();
+}
+"#,
+            "Surround with `unsafe` block",
+        );
+    }
+
+    #[test]
+    fn surround_expr_with_unsafe() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    let x = $0bar()$0;
+}
+"#,
+            r#"
+fn foo() {
+    let x = unsafe { bar() };
+}
+"#,
+            "Surround with `unsafe` block",
+        );
+    }
+
+    #[test]
+    fn surround_with_if_let() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    $0bar();
+    baz();$0
+}
+"#,
+            r#"
+fn foo() {
+    if let ${1:Some(it)} = ${0:todo!()} {
+        bar();
+        baz();
+    }
+}
+"#,
+            "Surround with `if let`",
+        );
+    }
+
+    #[test]
+    fn surround_value_with_if_let_adds_else() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() -> i32 {
+    $0bar();
+    1$0
+}
+"#,
+            r#"
+fn foo() -> i32 {
+    if let ${1:Some(it)} = ${0:todo!()} {
+        bar();
+        1
+    } else {
+        todo!()
+    }
+}
+"#,
+            "Surround with `if let`",
+        );
+    }
+
+    #[test]
+    fn surround_with_match() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    $0bar();
+    baz();$0
+}
+"#,
+            r#"
+fn foo() {
+    match ${1:todo!()} {
+        ${0:_} => {
+            bar();
+            baz();
+        }
+    }
+}
+"#,
+            "Surround with `match`",
+        );
+    }
+
+    #[test]
+    fn surround_value_with_loop_adds_break() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() -> i32 {
+    $0let x = 1;
+    x + 1$0
+}
+"#,
+            r#"
+fn foo() -> i32 {
+    loop {
+        let x = 1;
+        break x + 1
+    }
+}
+"#,
+            "Surround with `loop`",
+        );
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    let x = $01 + 1$0;
+}
+"#,
+            r#"
+fn foo() {
+    let x = loop { break 1 + 1 };
+}
+"#,
+            "Surround with `loop`",
+        );
+    }
+
+    #[test]
+    fn surround_with_async_adds_await() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+//- minicore: future
+async fn foo() -> i32 {
+    $0let x = 1;
+    x + 1$0
+}
+"#,
+            r#"
+async fn foo() -> i32 {
+    async {
+        let x = 1;
+        x + 1
+    }.await
+}
+"#,
+            "Surround with `async` block",
+        );
+    }
+
+    #[test]
+    fn surround_fallible_with_async_wraps_in_ok() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+//- minicore: result, try, future
+async fn foo() -> Result<(), i32> {
+    $0bar()?;
+    bar()?;$0
+    Ok(())
+}
+async fn bar() -> Result<(), i32> { Ok(()) }
+"#,
+            r#"
+async fn foo() -> Result<(), i32> {
+    async {
+        bar()?;
+        bar()?;
+        Ok::<_, i32>(())
+    }.await?;
+    Ok(())
+}
+async fn bar() -> Result<(), i32> { Ok(()) }
+"#,
+            "Surround with `async` block",
+        );
+    }
+
+    #[test]
+    fn no_loop_or_async_with_escaping_break() {
+        check_assist_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    loop {
+        $0bar();
+        break;$0
+    }
+}
+"#,
+            r#"
+fn foo() {
+    loop {
+        unsafe {
+            bar();
+            break;
+        }
+    }
+}
+"#,
+            "Surround with `unsafe` block",
+        );
+        check_assist_not_applicable_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    loop {
+        $0bar();
+        break;$0
+    }
+}
+"#,
+            "Surround with `loop`",
+        );
+        check_assist_not_applicable_by_label(
+            surround_with,
+            r#"
+fn foo() {
+    loop {
+        $0bar();
+        break;$0
+    }
+}
+"#,
+            "Surround with `async` block",
+        );
+    }
+
+    #[test]
+    fn not_applicable_with_escaping_bindings() {
+        check_assist_not_applicable(
+            surround_with,
+            r#"
+fn foo() {
+    $0let x = 1;$0
+    bar(x);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_applicable_on_partial_statement() {
+        check_assist_not_applicable(
+            surround_with,
+            r#"
+fn foo() {
+    let x = 1$0;
+    bar(x);$0
+}
+"#,
+        );
+    }
+}
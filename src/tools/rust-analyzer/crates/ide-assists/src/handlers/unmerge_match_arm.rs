@@ -1,8 +1,9 @@
+use itertools::Itertools;
 use syntax::{
     algo::neighbor,
     ast::{self, edit::IndentLevel, make, AstNode},
     ted::{self, Position},
-    Direction, SyntaxKind, T,
+    Direction, SyntaxKind, SyntaxToken, TextRange, T,
 };
 
 use crate::{AssistContext, AssistId, AssistKind, Assists};
@@ -33,7 +34,11 @@
 // ```
 pub(crate) fn unmerge_match_arm(acc: &mut Assists, ctx: &AssistContext<'_>) -> Option<()> {
     let pipe_token = ctx.find_token_syntax_at_offset(T![|])?;
-    let or_pat = ast::OrPat::cast(pipe_token.parent()?)?.clone_for_update();
+    let or_pat = ast::OrPat::cast(pipe_token.parent()?)?;
+    if !ast::MatchArm::can_cast(or_pat.syntax().parent()?.kind()) {
+        return unmerge_nested_or_pat(acc, pipe_token, or_pat);
+    }
+    let or_pat = or_pat.clone_for_update();
     let match_arm = ast::MatchArm::cast(or_pat.syntax().parent()?)?;
     let match_arm_body = match_arm.expr()?;
 
@@ -112,6 +117,59 @@ pub(crate) fn unmerge_match_arm(acc: &mut Assists, ctx: &AssistContext<'_>) -> O
     )
 }
 
+/// Splits an or-pattern nested in the pattern of an arm, such as `Some(A | B)`, by duplicating
+/// the whole arm.
+fn unmerge_nested_or_pat(
+    acc: &mut Assists,
+    pipe_token: SyntaxToken,
+    or_pat: ast::OrPat,
+) -> Option<()> {
+    let match_arm = or_pat
+        .syntax()
+        .ancestors()
+        .find(|it| {
+            !ast::Pat::can_cast(it.kind())
+                && !matches!(
+                    it.kind(),
+                    SyntaxKind::RECORD_PAT_FIELD | SyntaxKind::RECORD_PAT_FIELD_LIST
+                )
+        })
+        .and_then(ast::MatchArm::cast)?;
+    let match_arm_body = match_arm.expr()?;
+
+    let pipe_offset = pipe_token.text_range().start();
+    let (pats_before, pats_after): (Vec<_>, Vec<_>) =
+        or_pat.pats().partition(|pat| pat.syntax().text_range().end() <= pipe_offset);
+    if pats_before.is_empty() || pats_after.is_empty() {
+        return None;
+    }
+
+    let range = TextRange::new(
+        match_arm.syntax().text_range().start(),
+        match_arm_body.syntax().text_range().end(),
+    );
+    let or_pat_range = or_pat.syntax().text_range() - range.start();
+    let arm_text = match_arm.syntax().text().slice(..range.len()).to_string();
+    let arm_with = |pats: Vec<ast::Pat>| {
+        let mut arm = arm_text.clone();
+        arm.replace_range(std::ops::Range::<usize>::from(or_pat_range), &pats.iter().join(" | "));
+        arm
+    };
+    let indent = IndentLevel::from_node(match_arm.syntax());
+
+    acc.add(
+        AssistId("unmerge_match_arm", AssistKind::RefactorRewrite),
+        "Unmerge match arm",
+        pipe_token.text_range(),
+        |edit| {
+            let first = arm_with(pats_before);
+            let second = arm_with(pats_after);
+            // The comma after the original arm, if any, ends up after the second one.
+            edit.replace(range, format!("{first},\n{indent}{second}"));
+        },
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use crate::tests::{check_assist, check_assist_not_applicable};
@@ -286,6 +344,76 @@ fn main() {
         X::B => {},
     }
 }
+"#,
+        );
+    }
+
+    #[test]
+    fn unmerge_nested_or_pattern() {
+        check_assist(
+            unmerge_match_arm,
+            r#"
+enum X { A, B, C }
+fn main() {
+    match Some(X::A) {
+        Some(X::A | X::B $0| X::C) if true => 1,
+        None => 0,
+    };
+}
+"#,
+            r#"
+enum X { A, B, C }
+fn main() {
+    match Some(X::A) {
+        Some(X::A | X::B) if true => 1,
+        Some(X::C) if true => 1,
+        None => 0,
+    };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unmerge_or_pattern_in_record_field() {
+        check_assist(
+            unmerge_match_arm,
+            r#"
+struct S { x: i32, y: i32 }
+fn f(s: S) {
+    match s {
+        S { x: 1 $0| 2, y } => {
+            y
+        }
+        S { .. } => 0,
+    }
+}
+"#,
+            r#"
+struct S { x: i32, y: i32 }
+fn f(s: S) {
+    match s {
+        S { x: 1, y } => {
+            y
+        },
+        S { x: 2, y } => {
+            y
+        }
+        S { .. } => 0,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unmerge_nested_or_pattern_outside_match_arm() {
+        check_assist_not_applicable(
+            unmerge_match_arm,
+            r#"
+fn f(x: Option<i32>) {
+    if let Some(1 $0| 2) = x {}
+}
 "#,
         );
     }
@@ -72,7 +72,7 @@
 
 pub(crate) use crate::assist_context::{AssistContext, Assists};
 
-pub use assist_config::AssistConfig;
+pub use assist_config::{AssistConfig, BoundsStyle, ErrorHandlingStyle};
 pub use ide_db::assists::{
     Assist, AssistId, AssistKind, AssistResolveStrategy, GroupLabel, SingleResolve,
 };
@@ -105,6 +105,7 @@ mod handlers {
     mod add_explicit_type;
     mod add_label_to_loop;
     mod add_lifetime_to_type;
+    mod add_missing_bounds;
     mod add_missing_impl_members;
     mod add_missing_match_arms;
     mod add_return_type;
@@ -115,6 +116,7 @@ mod handlers {
     mod bool_to_enum;
     mod change_visibility;
     mod convert_bool_then;
+    mod convert_box_dyn_error;
     mod convert_comment_block;
     mod convert_integer_literal;
     mod convert_into_to_from;
@@ -128,6 +130,7 @@ mod handlers {
     mod convert_tuple_struct_to_named_struct;
     mod convert_two_arm_bool_match_to_matches_macro;
     mod convert_while_to_loop;
+    mod destructure_struct_binding;
     mod destructure_tuple_binding;
     mod desugar_doc_comment;
     mod expand_glob_import;
@@ -157,6 +160,7 @@ mod handlers {
     mod generate_getter_or_setter;
     mod generate_impl;
     mod generate_is_empty_from_len;
+    mod generate_mock_trait_impl;
     mod generate_mut_trait_impl;
     mod generate_new;
     mod generate_trait_from_impl;
@@ -165,6 +169,7 @@ mod handlers {
     mod inline_local_variable;
     mod inline_macro;
     mod inline_type_alias;
+    mod insert_expr_of_expected_type;
     mod into_to_qualified_from;
     mod introduce_named_generic;
     mod introduce_named_lifetime;
@@ -172,12 +177,14 @@ mod handlers {
     mod merge_imports;
     mod merge_match_arms;
     mod merge_nested_if;
+    mod minimize_unsafe_block;
     mod move_bounds;
     mod move_const_to_impl;
     mod move_from_mod_rs;
     mod move_guard;
     mod move_module_to_file;
     mod move_to_mod_rs;
+    mod name_elided_lifetimes;
     mod normalize_import;
     mod number_representation;
     mod promote_local_to_const;
@@ -185,16 +192,19 @@ mod handlers {
     mod qualify_method_call;
     mod qualify_path;
     mod raw_string;
+    mod relax_sized_bound;
     mod remove_dbg;
     mod remove_mut;
     mod remove_parentheses;
     mod remove_unused_imports;
     mod remove_unused_param;
     mod reorder_fields;
+    mod reorder_fields_by_layout;
     mod reorder_impl_items;
     mod replace_arith_op;
     mod replace_derive_with_manual_impl;
     mod replace_if_let_with_match;
+    mod replace_impl_trait_with_dyn;
     mod replace_is_method_with_if_let_method;
     mod replace_let_with_if_let;
     mod replace_method_eager_lazy;
@@ -203,8 +213,11 @@ mod handlers {
     mod replace_string_with_char;
     mod replace_try_expr_with_match;
     mod replace_turbofish_with_explicit_type;
+    mod replace_unwrap_with_try;
+    mod simplify_bounds;
     mod sort_items;
     mod split_import;
+    mod surround_with;
     mod term_search;
     mod toggle_ignore;
     mod unmerge_match_arm;
@@ -222,6 +235,7 @@ pub(crate) fn all() -> &'static [Handler] {
             add_braces::add_braces,
             add_explicit_type::add_explicit_type,
             add_label_to_loop::add_label_to_loop,
+            add_missing_bounds::add_missing_bounds,
             add_missing_match_arms::add_missing_match_arms,
             add_lifetime_to_type::add_lifetime_to_type,
             add_return_type::add_return_type,
@@ -234,6 +248,7 @@ pub(crate) fn all() -> &'static [Handler] {
             change_visibility::change_visibility,
             convert_bool_then::convert_bool_then_to_if,
             convert_bool_then::convert_if_to_bool_then,
+            convert_box_dyn_error::convert_box_dyn_error,
             convert_comment_block::convert_comment_block,
             convert_integer_literal::convert_integer_literal,
             convert_into_to_from::convert_into_to_from,
@@ -249,6 +264,7 @@ pub(crate) fn all() -> &'static [Handler] {
             convert_two_arm_bool_match_to_matches_macro::convert_two_arm_bool_match_to_matches_macro,
             convert_while_to_loop::convert_while_to_loop,
             desugar_doc_comment::desugar_doc_comment,
+            destructure_struct_binding::destructure_struct_binding,
             destructure_tuple_binding::destructure_tuple_binding,
             expand_glob_import::expand_glob_import,
             extract_expressions_from_format_string::extract_expressions_from_format_string,
@@ -273,6 +289,7 @@ pub(crate) fn all() -> &'static [Handler] {
             generate_function::generate_function,
             generate_impl::generate_impl,
             generate_impl::generate_trait_impl,
+            generate_mock_trait_impl::generate_mock_trait_impl,
             generate_mut_trait_impl::generate_mut_trait_impl,
             generate_is_empty_from_len::generate_is_empty_from_len,
             generate_new::generate_new,
@@ -283,6 +300,7 @@ pub(crate) fn all() -> &'static [Handler] {
             inline_local_variable::inline_local_variable,
             inline_type_alias::inline_type_alias,
             inline_type_alias::inline_type_alias_uses,
+            insert_expr_of_expected_type::insert_expr_of_expected_type,
             into_to_qualified_from::into_to_qualified_from,
             introduce_named_generic::introduce_named_generic,
             introduce_named_lifetime::introduce_named_lifetime,
@@ -290,6 +308,7 @@ pub(crate) fn all() -> &'static [Handler] {
             merge_imports::merge_imports,
             merge_match_arms::merge_match_arms,
             merge_nested_if::merge_nested_if,
+            minimize_unsafe_block::minimize_unsafe_block,
             move_bounds::move_bounds_to_where_clause,
             move_const_to_impl::move_const_to_impl,
             move_guard::move_arm_cond_to_match_guard,
@@ -297,6 +316,7 @@ pub(crate) fn all() -> &'static [Handler] {
             move_module_to_file::move_module_to_file,
             move_to_mod_rs::move_to_mod_rs,
             move_from_mod_rs::move_from_mod_rs,
+            name_elided_lifetimes::name_elided_lifetimes,
             normalize_import::normalize_import,
             number_representation::reformat_number_literal,
             pull_assignment_up::pull_assignment_up,
@@ -306,16 +326,20 @@ pub(crate) fn all() -> &'static [Handler] {
             raw_string::add_hash,
             raw_string::make_usual_string,
             raw_string::remove_hash,
+            relax_sized_bound::relax_sized_bound,
             remove_mut::remove_mut,
             remove_unused_imports::remove_unused_imports,
             remove_unused_param::remove_unused_param,
             remove_parentheses::remove_parentheses,
             reorder_fields::reorder_fields,
+            reorder_fields_by_layout::reorder_fields_by_layout,
             reorder_impl_items::reorder_impl_items,
             replace_try_expr_with_match::replace_try_expr_with_match,
             replace_derive_with_manual_impl::replace_derive_with_manual_impl,
+            replace_derive_with_manual_impl::replace_default_impl_with_derive,
             replace_if_let_with_match::replace_if_let_with_match,
             replace_if_let_with_match::replace_match_with_if_let,
+            replace_impl_trait_with_dyn::replace_impl_trait_with_dyn,
             replace_is_method_with_if_let_method::replace_is_method_with_if_let_method,
             replace_let_with_if_let::replace_let_with_if_let,
             replace_method_eager_lazy::replace_with_eager_method,
@@ -323,10 +347,13 @@ pub(crate) fn all() -> &'static [Handler] {
             replace_named_generic_with_impl::replace_named_generic_with_impl,
             replace_turbofish_with_explicit_type::replace_turbofish_with_explicit_type,
             replace_qualified_name_with_use::replace_qualified_name_with_use,
+            replace_unwrap_with_try::replace_unwrap_with_try,
             replace_arith_op::replace_arith_with_wrapping,
             replace_arith_op::replace_arith_with_checked,
             replace_arith_op::replace_arith_with_saturating,
+            simplify_bounds::simplify_bounds,
             sort_items::sort_items,
+            surround_with::surround_with,
             split_import::split_import,
             term_search::term_search,
             toggle_ignore::toggle_ignore,
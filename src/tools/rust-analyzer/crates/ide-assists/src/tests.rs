@@ -6,7 +6,7 @@
 use hir::Semantics;
 use ide_db::{
     base_db::{FileId, FileRange, SourceDatabaseExt},
-    imports::insert_use::{ImportGranularity, InsertUseConfig},
+    imports::insert_use::{ImportGranularity, ImportGroupStyle, InsertUseConfig},
     source_change::FileSystemEdit,
     RootDatabase, SnippetCap,
 };
@@ -17,7 +17,7 @@
 
 use crate::{
     assists, handlers::Handler, Assist, AssistConfig, AssistContext, AssistKind,
-    AssistResolveStrategy, Assists, SingleResolve,
+    AssistResolveStrategy, Assists, BoundsStyle, ErrorHandlingStyle, SingleResolve,
 };
 
 pub(crate) const TEST_CONFIG: AssistConfig = AssistConfig {
@@ -28,11 +28,15 @@
         prefix_kind: hir::PrefixKind::Plain,
         enforce_granularity: true,
         group: true,
+        group_style: ImportGroupStyle::Default,
         skip_glob_imports: true,
+        prefer_reexports: false,
     },
     prefer_no_std: false,
     prefer_prelude: true,
     assist_emit_must_use: false,
+    error_handling_style: ErrorHandlingStyle::Anyhow,
+    bounds_style: BoundsStyle::WhereClause,
 };
 
 pub(crate) const TEST_CONFIG_NO_SNIPPET_CAP: AssistConfig = AssistConfig {
@@ -43,11 +47,15 @@
         prefix_kind: hir::PrefixKind::Plain,
         enforce_granularity: true,
         group: true,
+        group_style: ImportGroupStyle::Default,
         skip_glob_imports: true,
+        prefer_reexports: false,
     },
     prefer_no_std: false,
     prefer_prelude: true,
     assist_emit_must_use: false,
+    error_handling_style: ErrorHandlingStyle::Anyhow,
+    bounds_style: BoundsStyle::WhereClause,
 };
 
 pub(crate) const TEST_CONFIG_IMPORT_ONE: AssistConfig = AssistConfig {
@@ -58,11 +66,15 @@
         prefix_kind: hir::PrefixKind::Plain,
         enforce_granularity: true,
         group: true,
+        group_style: ImportGroupStyle::Default,
         skip_glob_imports: true,
+        prefer_reexports: false,
     },
     prefer_no_std: false,
     prefer_prelude: true,
     assist_emit_must_use: false,
+    error_handling_style: ErrorHandlingStyle::Anyhow,
+    bounds_style: BoundsStyle::WhereClause,
 };
 
 pub(crate) fn with_single_file(text: &str) -> (RootDatabase, FileId) {
@@ -107,6 +119,23 @@ pub(crate) fn check_assist_import_one(
     );
 }
 
+#[track_caller]
+pub(crate) fn check_assist_with_config(
+    config: AssistConfig,
+    assist: Handler,
+    ra_fixture_before: &str,
+    ra_fixture_after: &str,
+) {
+    let ra_fixture_after = trim_indent(ra_fixture_after);
+    check_with_config(
+        config,
+        assist,
+        ra_fixture_before,
+        ExpectedResult::After(&ra_fixture_after),
+        None,
+    );
+}
+
 // There is no way to choose what assist within a group you want to test against,
 // so this is here to allow you choose.
 pub(crate) fn check_assist_by_label(
@@ -355,6 +384,7 @@ pub fn test_some_range(a: int) -> bool {
 
     expect![[r#"
         Convert integer base
+        Surround with…
         Extract into variable
         Extract into function
         Replace if let with match
@@ -384,6 +414,7 @@ pub fn test_some_range(a: int) -> bool {
 
         expect![[r#"
             Convert integer base
+            Surround with…
             Extract into variable
             Extract into function
             Replace if let with match
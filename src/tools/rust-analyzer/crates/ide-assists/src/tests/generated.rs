@@ -169,6 +169,24 @@ struct Point<'a> {
     )
 }
 
+#[test]
+fn doctest_add_missing_bounds() {
+    check_doc_test(
+        "add_missing_bounds",
+        r#####"
+//- minicore: add
+fn sum<$0T>(a: T, b: T) -> T {
+    a + b
+}
+"#####,
+        r#####"
+fn sum<T>(a: T, b: T) -> T where T: core::ops::Add<Output = T> {
+    a + b
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_add_missing_match_arms() {
     check_doc_test(
@@ -367,6 +385,39 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_convert_box_dyn_error() {
+    check_doc_test(
+        "convert_box_dyn_error",
+        r#####"
+//- minicore: result, error, from, try
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use boxed::Box;
+use core::error::Error;
+
+fn check(ok: bool) -> Result<(), Box<dyn Error>>$0 {
+    if !ok {
+        return Err("not ok".into());
+    }
+    Ok(())
+}
+"#####,
+        r#####"
+mod boxed { pub struct Box<T: ?Sized>(T); }
+use anyhow::anyhow;
+use boxed::Box;
+use core::error::Error;
+
+fn check(ok: bool) -> anyhow::Result<()> {
+    if !ok {
+        return Err(anyhow!("not ok"));
+    }
+    Ok(())
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_convert_for_loop_with_for_each() {
     check_doc_test(
@@ -659,19 +710,19 @@ pub fn y(&self) -> f32 {
 }
 "#####,
         r#####"
-struct Point { field1: f32, field2: f32 }
+struct Point { x: f32, y: f32 }
 
 impl Point {
     pub fn new(x: f32, y: f32) -> Self {
-        Point { field1: x, field2: y }
+        Point { x, y }
     }
 
     pub fn x(&self) -> f32 {
-        self.field1
+        self.x
     }
 
     pub fn y(&self) -> f32 {
-        self.field2
+        self.y
     }
 }
 "#####,
@@ -722,6 +773,35 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_destructure_struct_binding() {
+    check_doc_test(
+        "destructure_struct_binding",
+        r#####"
+struct Foo {
+    bar: i32,
+    baz: i32,
+}
+fn main() {
+    let $0foo = Foo { bar: 1, baz: 2 };
+    let bar2 = foo.bar;
+    let baz2 = &foo.baz;
+}
+"#####,
+        r#####"
+struct Foo {
+    bar: i32,
+    baz: i32,
+}
+fn main() {
+    let Foo { bar, baz } = Foo { bar: 1, baz: 2 };
+    let bar2 = bar;
+    let baz2 = &baz;
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_destructure_tuple_binding() {
     check_doc_test(
@@ -1550,6 +1630,47 @@ pub fn is_empty(&self) -> bool {
     )
 }
 
+#[test]
+fn doctest_generate_mock_trait_impl() {
+    check_doc_test(
+        "generate_mock_trait_impl",
+        r#####"
+trait $0Clock {
+    fn now(&self) -> u64;
+    fn sleep(&mut self, millis: u64);
+}
+"#####,
+        r#####"
+trait Clock {
+    fn now(&self) -> u64;
+    fn sleep(&mut self, millis: u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockClock {
+        now_calls: std::cell::RefCell<Vec<()>>,
+        sleep_calls: Vec<u64>,
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> u64 {
+            self.now_calls.borrow_mut().push(());
+            todo!()
+        }
+
+        fn sleep(&mut self, millis: u64) {
+            self.sleep_calls.push(millis);
+        }
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_generate_mut_trait_impl() {
     check_doc_test(
@@ -1883,6 +2004,29 @@ fn foo() {
     )
 }
 
+#[test]
+fn doctest_insert_expr_of_expected_type() {
+    check_doc_test(
+        "insert_expr_of_expected_type",
+        r#####"
+struct Meters(u32);
+fn walk(distance: Meters) {}
+fn main() {
+    let m = Meters(5);
+    walk($05);
+}
+"#####,
+        r#####"
+struct Meters(u32);
+fn walk(distance: Meters) {}
+fn main() {
+    let m = Meters(5);
+    walk(m);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_into_to_qualified_from() {
     check_doc_test(
@@ -2080,6 +2224,27 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_minimize_unsafe_block() {
+    check_doc_test(
+        "minimize_unsafe_block",
+        r#####"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn main() {
+    let x = 92;
+    let y = unsafe$0 { read(&x) + 1 };
+}
+"#####,
+        r#####"
+unsafe fn read(ptr: *const i32) -> i32 { *ptr }
+fn main() {
+    let x = 92;
+    let y = unsafe { read(&x) } + 1;
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_move_arm_cond_to_match_guard() {
     check_doc_test(
@@ -2229,6 +2394,25 @@ fn t() {}
     )
 }
 
+#[test]
+fn doctest_name_elided_lifetimes() {
+    check_doc_test(
+        "name_elided_lifetimes",
+        r#####"
+struct Parser<'a>(&'a str);
+impl Parser<'_> {
+    fn next_$0token(&mut self, skip: &str) -> Option<&str> { None }
+}
+"#####,
+        r#####"
+struct Parser<'a>(&'a str);
+impl Parser<'_> {
+    fn next_token<'a, 'b>(&'a mut self, skip: &'b str) -> Option<&'a str> { None }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_normalize_import() {
     check_doc_test(
@@ -2359,6 +2543,23 @@ fn doctest_reformat_number_literal() {
     )
 }
 
+#[test]
+fn doctest_relax_sized_bound() {
+    check_doc_test(
+        "relax_sized_bound",
+        r#####"
+fn len<$0T: AsRef<[u8]>>(value: T) -> usize {
+    value.as_ref().len()
+}
+"#####,
+        r#####"
+fn len<T: AsRef<[u8]> + ?Sized>(value: &T) -> usize {
+    value.as_ref().len()
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_remove_dbg() {
     check_doc_test(
@@ -2481,6 +2682,27 @@ fn doctest_reorder_fields() {
     )
 }
 
+#[test]
+fn doctest_reorder_fields_by_layout() {
+    check_doc_test(
+        "reorder_fields_by_layout",
+        r#####"
+struct $0Foo {
+    a: u8,
+    b: u64,
+    c: u16,
+}
+"#####,
+        r#####"
+struct Foo {
+    b: u64,
+    c: u16,
+    a: u8,
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_reorder_impl_items() {
     check_doc_test(
@@ -2584,6 +2806,33 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_replace_default_impl_with_derive() {
+    check_doc_test(
+        "replace_default_impl_with_derive",
+        r#####"
+//- minicore: default, derive
+struct S {
+    count: usize,
+    ready: bool,
+}
+
+impl Default$0 for S {
+    fn default() -> Self {
+        Self { count: 0, ready: Default::default() }
+    }
+}
+"#####,
+        r#####"
+#[derive(Default)]
+struct S {
+    count: usize,
+    ready: bool,
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_derive_with_manual_impl() {
     check_doc_test(
@@ -2636,6 +2885,35 @@ fn handle(action: Action) {
     )
 }
 
+#[test]
+fn doctest_replace_impl_trait_with_dyn() {
+    check_doc_test(
+        "replace_impl_trait_with_dyn",
+        r#####"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: $0impl Shape) {}
+
+fn main() {
+    draw(Circle);
+}
+"#####,
+        r#####"
+trait Shape {}
+struct Circle;
+impl Shape for Circle {}
+
+fn draw(shape: &dyn Shape) {}
+
+fn main() {
+    draw(&Circle);
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_is_some_with_if_let_some() {
     check_doc_test(
@@ -2796,6 +3074,32 @@ fn main() {
     )
 }
 
+#[test]
+fn doctest_replace_unwrap_with_try() {
+    check_doc_test(
+        "replace_unwrap_with_try",
+        r#####"
+//- minicore: result
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> u32 {
+    parse(s).unwrap$0() * 2
+}
+fn main() {
+    let x = double("1");
+}
+"#####,
+        r#####"
+fn parse(s: &str) -> Result<u32, ()> { Ok(0) }
+fn double(s: &str) -> Result<u32, ()> {
+    Ok(parse(s)? * 2)
+}
+fn main() {
+    let x = double("1").unwrap();
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_replace_with_eager_method() {
     check_doc_test(
@@ -2836,6 +3140,23 @@ fn foo() {
     )
 }
 
+#[test]
+fn doctest_simplify_bounds() {
+    check_doc_test(
+        "simplify_bounds",
+        r#####"
+trait Shape {}
+trait Circle: Shape {}
+fn dr$0aw<T: Shape + Circle>(shape: T) where T: Circle {}
+"#####,
+        r#####"
+trait Shape {}
+trait Circle: Shape {}
+fn draw<T>(shape: T) where T: Circle {}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_sort_items() {
     check_doc_test(
@@ -2940,6 +3261,27 @@ fn doctest_split_import() {
     )
 }
 
+#[test]
+fn doctest_surround_with() {
+    check_doc_test(
+        "surround_with",
+        r#####"
+fn foo() -> i32 {
+    $0let x = 1;
+    x + 1$0
+}
+"#####,
+        r#####"
+fn foo() -> i32 {
+    unsafe {
+        let x = 1;
+        x + 1
+    }
+}
+"#####,
+    )
+}
+
 #[test]
 fn doctest_toggle_ignore() {
     check_doc_test(
@@ -2,17 +2,13 @@
 
 pub(crate) use gen_trait_fn_body::gen_trait_fn_body;
 use hir::{db::HirDatabase, HasAttrs as HirHasAttrs, HirDisplay, InFile, Semantics};
-use ide_db::{
-    famous_defs::FamousDefs, path_transform::PathTransform,
-    syntax_helpers::insert_whitespace_into_node::insert_ws_into, RootDatabase,
-};
+pub use ide_db::traits::add_trait_assoc_items_to_impl;
+use ide_db::{base_db::FileId, defs::Definition, famous_defs::FamousDefs, RootDatabase};
 use stdx::format_to;
 use syntax::{
     ast::{
-        self,
-        edit::{AstNodeEdit, IndentLevel},
-        edit_in_place::{AttrsOwnerEdit, Indent, Removable},
-        make, HasArgList, HasAttrs, HasGenericParams, HasName, HasTypeBounds, Whitespace,
+        self, edit_in_place::AttrsOwnerEdit, make, HasArgList, HasAttrs, HasGenericParams, HasName,
+        HasTypeBounds, Whitespace,
     },
     ted, AstNode, AstToken, Direction, SourceFile,
     SyntaxKind::*,
@@ -151,70 +147,6 @@ fn has_def_name(item: &InFile<ast::AssocItem>) -> bool {
     }
 }
 
-/// Given `original_items` retrieved from the trait definition (usually by
-/// [`filter_assoc_items()`]), clones each item for update and applies path transformation to it,
-/// then inserts into `impl_`. Returns the modified `impl_` and the first associated item that got
-/// inserted.
-pub fn add_trait_assoc_items_to_impl(
-    sema: &Semantics<'_, RootDatabase>,
-    original_items: &[InFile<ast::AssocItem>],
-    trait_: hir::Trait,
-    impl_: &ast::Impl,
-    target_scope: hir::SemanticsScope<'_>,
-) -> ast::AssocItem {
-    let new_indent_level = IndentLevel::from_node(impl_.syntax()) + 1;
-    let items = original_items.iter().map(|InFile { file_id, value: original_item }| {
-        let cloned_item = {
-            if file_id.is_macro() {
-                if let Some(formatted) =
-                    ast::AssocItem::cast(insert_ws_into(original_item.syntax().clone()))
-                {
-                    return formatted;
-                } else {
-                    stdx::never!("formatted `AssocItem` could not be cast back to `AssocItem`");
-                }
-            }
-            original_item.clone_for_update()
-        };
-
-        if let Some(source_scope) = sema.scope(original_item.syntax()) {
-            // FIXME: Paths in nested macros are not handled well. See
-            // `add_missing_impl_members::paths_in_nested_macro_should_get_transformed` test.
-            let transform =
-                PathTransform::trait_impl(&target_scope, &source_scope, trait_, impl_.clone());
-            transform.apply(cloned_item.syntax());
-        }
-        cloned_item.remove_attrs_and_docs();
-        cloned_item.reindent_to(new_indent_level);
-        cloned_item
-    });
-
-    let assoc_item_list = impl_.get_or_create_assoc_item_list();
-    let mut first_item = None;
-    for item in items {
-        first_item.get_or_insert_with(|| item.clone());
-        match &item {
-            ast::AssocItem::Fn(fn_) if fn_.body().is_none() => {
-                let body = AstNodeEdit::indent(
-                    &make::block_expr(None, Some(make::ext::expr_todo())),
-                    new_indent_level,
-                );
-                ted::replace(fn_.get_or_create_body().syntax(), body.clone_for_update().syntax())
-            }
-            ast::AssocItem::TypeAlias(type_alias) => {
-                if let Some(type_bound_list) = type_alias.type_bound_list() {
-                    type_bound_list.remove()
-                }
-            }
-            _ => {}
-        }
-
-        assoc_item_list.add_item(item)
-    }
-
-    first_item.unwrap()
-}
-
 pub(crate) fn vis_offset(node: &SyntaxNode) -> TextSize {
     node.children_with_tokens()
         .find(|it| !matches!(it.kind(), WHITESPACE | COMMENT | ATTR))
@@ -238,8 +170,7 @@ fn invert_special_case(expr: &ast::Expr) -> Option<ast::Expr> {
                 T![<=] => T![>],
                 T![>] => T![<=],
                 T![>=] => T![<],
-                // Parenthesize other expressions before prefixing `!`
-                _ => return Some(make::expr_prefix(T![!], make::expr_paren(expr.clone()))),
+                _ => return Some(make::expr_prefix(T![!], expr.clone())),
             };
             ted::replace(op_token, make::token(rev_token));
             Some(bin.into())
@@ -310,6 +241,7 @@ fn calc_depth(pat: &ast::Pat, depth: usize) -> usize {
         | ast::Pat::LiteralPat(_)
         | ast::Pat::MacroPat(_)
         | ast::Pat::OrPat(_)
+        | ast::Pat::GuardPat(_)
         | ast::Pat::ParenPat(_)
         | ast::Pat::PathPat(_)
         | ast::Pat::WildcardPat(_)
@@ -902,3 +834,66 @@ pub(crate) fn replace_record_field_expr(
         edit.replace(file_range.range, initializer.syntax().text());
     }
 }
+
+/// The turbofishes of all references to a function, such as `foo::<T>()` or `x.foo::<T>()`.
+pub(crate) struct TurbofishUsages {
+    current_file: Vec<ast::GenericArgList>,
+    other_files: Vec<(FileId, Vec<ast::GenericArgList>)>,
+}
+
+impl TurbofishUsages {
+    pub(crate) fn find(ctx: &AssistContext<'_>, func: hir::Function) -> TurbofishUsages {
+        let mut current_file = Vec::new();
+        let mut other_files = Vec::new();
+        for (file_id, refs) in Definition::Function(func).usages(&ctx.sema).all() {
+            let arg_lists: Vec<_> =
+                refs.iter().filter_map(|it| turbofish_of(it.name.as_name_ref()?)).collect();
+            if file_id == ctx.file_id() {
+                current_file = arg_lists;
+            } else if !arg_lists.is_empty() {
+                other_files.push((file_id, arg_lists));
+            }
+        }
+        TurbofishUsages { current_file, other_files }
+    }
+
+    /// Has to be called before the current file gets mutated.
+    pub(crate) fn make_mut(self, edit: &mut SourceChangeBuilder) -> TurbofishUsages {
+        let current_file = self.current_file.into_iter().map(|it| edit.make_mut(it)).collect();
+        TurbofishUsages { current_file, ..self }
+    }
+
+    /// Switches the builder over to other files, so this has to come after all other edits to the
+    /// current file.
+    pub(crate) fn edit(
+        self,
+        edit: &mut SourceChangeBuilder,
+        edit_args: impl Fn(&ast::GenericArgList),
+    ) {
+        self.current_file.iter().for_each(&edit_args);
+        for (file_id, arg_lists) in self.other_files {
+            edit.edit_file(file_id);
+            for arg_list in arg_lists {
+                edit_args(&edit.make_mut(arg_list));
+            }
+        }
+    }
+}
+
+fn turbofish_of(name_ref: &ast::NameRef) -> Option<ast::GenericArgList> {
+    let parent = name_ref.syntax().parent()?;
+    if let Some(method_call) = ast::MethodCallExpr::cast(parent.clone()) {
+        return method_call.generic_arg_list();
+    }
+    ast::PathSegment::cast(parent)?.generic_arg_list()
+}
+
+/// The `#[default]` attribute of the variant of `adt` that `#[derive(Default)]` uses.
+pub(crate) fn default_variant_attr(adt: &ast::Adt) -> Option<ast::Attr> {
+    let ast::Adt::Enum(enum_) = adt else { return None };
+    enum_
+        .variant_list()?
+        .variants()
+        .flat_map(|variant| variant.attrs())
+        .find(|attr| attr.simple_name().as_deref() == Some("default"))
+}
@@ -6,6 +6,8 @@
     ted,
 };
 
+use super::default_variant_attr;
+
 /// Generate custom trait bodies without default implementation where possible.
 ///
 /// If `func` is defined within an existing impl block, pass [`TraitRef`]. Otherwise pass `None`.
@@ -264,8 +266,8 @@ fn gen_debug_impl(adt: &ast::Adt, func: &ast::Fn) -> Option<()> {
                         let name = field.name()?;
                         let f_name = make::expr_literal(&(format!("\"{name}\""))).into();
                         let f_path = make::expr_path(make::ext::ident_path("self"));
-                        let f_path = make::expr_ref(f_path, false);
                         let f_path = make::expr_field(f_path, &format!("{name}"));
+                        let f_path = make::expr_ref(f_path, false);
                         let args = make::arg_list([f_name, f_path]);
                         expr = make::expr_method_call(expr, make::name_ref("field"), args);
                     }
@@ -278,8 +280,8 @@ fn gen_debug_impl(adt: &ast::Adt, func: &ast::Fn) -> Option<()> {
                     let mut expr = make::expr_method_call(target, method, args);
                     for (i, _) in field_list.fields().enumerate() {
                         let f_path = make::expr_path(make::ext::ident_path("self"));
-                        let f_path = make::expr_ref(f_path, false);
                         let f_path = make::expr_field(f_path, &format!("{i}"));
+                        let f_path = make::expr_ref(f_path, false);
                         let method = make::name_ref("field");
                         expr = make::expr_method_call(expr, method, make::arg_list(Some(f_path)));
                     }
@@ -296,17 +298,25 @@ fn gen_debug_impl(adt: &ast::Adt, func: &ast::Fn) -> Option<()> {
     }
 }
 
-/// Generate a `Debug` impl based on the fields and members of the target type.
+/// Generate a `Default` impl based on the fields and members of the target type.
 fn gen_default_impl(adt: &ast::Adt, func: &ast::Fn) -> Option<()> {
     fn gen_default_call() -> Option<ast::Expr> {
         let fn_name = make::ext::path_from_idents(["Default", "default"])?;
         Some(make::expr_call(make::expr_path(fn_name), make::arg_list(None)))
     }
     match adt {
-        // `Debug` cannot be derived for unions, so no default impl can be provided.
+        // `Default` cannot be derived for unions, so no default impl can be provided.
         ast::Adt::Union(_) => None,
-        // Deriving `Debug` for enums is not stable yet.
-        ast::Adt::Enum(_) => None,
+        // The derive uses the variant marked with `#[default]`.
+        ast::Adt::Enum(_) => {
+            let variant =
+                default_variant_attr(adt)?.syntax().parent().and_then(ast::Variant::cast)?;
+            let variant_name = variant.name()?.to_string();
+            let expr = make::expr_path(make::ext::path_from_idents(["Self", &variant_name])?);
+            let body = make::block_expr(None, Some(expr)).indent(ast::edit::IndentLevel(1));
+            ted::replace(func.body()?.syntax(), body.clone_for_update().syntax());
+            Some(())
+        }
         ast::Adt::Struct(strukt) => {
             let expr = match strukt.field_list() {
                 Some(ast::FieldList::RecordFieldList(field_list)) => {
@@ -415,7 +425,7 @@ fn gen_record_pat_field(field_name: &str, pat_name: &str) -> ast::RecordPatField
     }
 
     fn gen_record_pat(record_name: ast::Path, fields: Vec<ast::RecordPatField>) -> ast::RecordPat {
-        let list = make::record_pat_field_list(fields);
+        let list = make::record_pat_field_list(fields, None);
         make::record_pat_with_fields(record_name, list)
     }
 
@@ -113,6 +113,40 @@ pub(crate) fn for_impl_trait_as_generic(
     for_unique_generic_name(c.encode_utf8(&mut [0; 4]), existing_params)
 }
 
+/// Suggest name of a field for the value it's initialized with or bound to
+///
+/// Unlike [`for_variable`], only names spelled out in `value` are used: the name of a local or
+/// binding, of an accessed field, or of a getter (`get_x()` -> `x`).
+pub(crate) fn for_field(
+    sema: &Semantics<'_, RootDatabase>,
+    value: either::Either<&ast::Expr, &ast::Pat>,
+) -> Option<String> {
+    match value {
+        either::Either::Left(ast::Expr::PathExpr(path)) => {
+            let path = path.path()?;
+            let name_ref = path.as_single_name_ref()?;
+            if !matches!(sema.resolve_path(&path)?, hir::PathResolution::Local(_)) {
+                return None;
+            }
+            normalize(name_ref.text().trim_start_matches('_'))
+        }
+        either::Either::Left(ast::Expr::RefExpr(inner)) => {
+            for_field(sema, either::Either::Left(&inner.expr()?))
+        }
+        either::Either::Left(expr) => from_field_name(expr).or_else(|| {
+            let name = from_method_call(expr)?;
+            match name.strip_prefix("get_") {
+                Some(name) => normalize(name),
+                None => Some(name),
+            }
+        }),
+        either::Either::Right(pat) => {
+            let name = var_name_from_pat(pat)?;
+            normalize(name.text().trim_start_matches('_'))
+        }
+    }
+}
+
 /// Suggest name of variable for given expression
 ///
 /// **NOTE**: it is caller's responsibility to guarantee uniqueness of the name.
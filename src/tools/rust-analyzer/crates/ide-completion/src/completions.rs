@@ -445,6 +445,32 @@ pub(crate) fn add_qualified_enum_variant(
         }
     }
 
+    /// Adds `Enum::Variant`, along with an import of the enum.
+    pub(crate) fn add_enum_variant_with_import(
+        &mut self,
+        ctx: &CompletionContext<'_>,
+        path_ctx: &PathCompletionCtx,
+        variant: hir::Variant,
+        import: LocatedImport,
+    ) {
+        if !ctx.check_stability(Some(&variant.attrs(ctx.db))) {
+            return;
+        }
+        let path = hir::ModPath::from_segments(
+            hir::PathKind::Plain,
+            [variant.parent_enum(ctx.db).name(ctx.db), variant.name(ctx.db)],
+        );
+        if let Some(builder) = render_variant_lit(
+            RenderContext::new(ctx).import_to_add(Some(import)),
+            path_ctx,
+            None,
+            variant,
+            Some(path),
+        ) {
+            self.add(builder.build(ctx.db));
+        }
+    }
+
     pub(crate) fn add_enum_variant(
         &mut self,
         ctx: &CompletionContext<'_>,
@@ -705,6 +731,7 @@ pub(super) fn complete_name_ref(
 
                     dot::complete_undotted_self(acc, ctx, path_ctx, expr_ctx);
                     item_list::complete_item_list_in_expr(acc, ctx, path_ctx, expr_ctx);
+                    lifetime::complete_label_in_expr(acc, ctx, path_ctx);
                     snippet::complete_expr_snippet(acc, ctx, path_ctx, expr_ctx);
                 }
                 PathKind::Type { location } => {
@@ -6,7 +6,7 @@
 use crate::{
     context::{
         CompletionContext, DotAccess, DotAccessExprCtx, DotAccessKind, PathCompletionCtx,
-        PathExprCtx, Qualified,
+        PathExprCtx, Qualified, ReturnPosition,
     },
     CompletionItem, CompletionItemKind, Completions,
 };
@@ -88,6 +88,8 @@ pub(crate) fn complete_undotted_self(
                     ctx: DotAccessExprCtx {
                         in_block_expr: expr_ctx.in_block_expr,
                         in_breakable: expr_ctx.in_breakable,
+                        in_return_position: ReturnPosition::None,
+                        innermost_ret_ty: None,
                     },
                 },
                 Some(hir::known::SELF_PARAM),
@@ -109,6 +111,8 @@ pub(crate) fn complete_undotted_self(
                 ctx: DotAccessExprCtx {
                     in_block_expr: expr_ctx.in_block_expr,
                     in_breakable: expr_ctx.in_breakable,
+                    in_return_position: ReturnPosition::None,
+                    innermost_ret_ty: None,
                 },
             },
             func,
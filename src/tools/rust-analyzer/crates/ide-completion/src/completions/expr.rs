@@ -1,7 +1,8 @@
 //! Completion of names from the current scope in expression position.
 
-use hir::ScopeDef;
-use syntax::ast;
+use hir::{ItemInNs, ModuleDef, ScopeDef};
+use ide_db::imports::import_assets::LocatedImport;
+use syntax::ast::{self, make};
 
 use crate::{
     completions::record::add_default_update,
@@ -208,13 +209,22 @@ pub(crate) fn complete_expr_path(
                         }
                     }
                     hir::Adt::Enum(e) => {
+                        let import = enum_import(ctx, e);
                         super::enum_variants_with_paths(
                             acc,
                             ctx,
                             e,
                             impl_,
-                            |acc, ctx, variant, path| {
-                                acc.add_qualified_enum_variant(ctx, path_ctx, variant, path)
+                            |acc, ctx, variant, path| match &import {
+                                // Import the enum rather than spelling out its whole path.
+                                Some(import) if path.segments().len() > 2 => acc
+                                    .add_enum_variant_with_import(
+                                        ctx,
+                                        path_ctx,
+                                        variant,
+                                        import.clone(),
+                                    ),
+                                _ => acc.add_qualified_enum_variant(ctx, path_ctx, variant, path),
                             },
                         );
                     }
@@ -384,3 +394,27 @@ pub(crate) fn complete_expr(acc: &mut Completions, ctx: &CompletionContext<'_>)
         }
     }
 }
+
+/// The import that brings `enum_` into scope, if it is not in scope yet and its name isn't taken.
+fn enum_import(ctx: &CompletionContext<'_>, enum_: hir::Enum) -> Option<LocatedImport> {
+    if !ctx.config.enable_imports_on_the_fly {
+        return None;
+    }
+    let item = ItemInNs::from(ModuleDef::from(enum_));
+    let import_path = ctx.module.find_use_path_prefixed(
+        ctx.db,
+        item,
+        ctx.config.insert_use.prefix_kind,
+        ctx.config.prefer_no_std,
+        ctx.config.prefer_prelude,
+        ctx.config.insert_use.prefer_reexports,
+    )?;
+    if import_path.segments().len() < 2 {
+        return None;
+    }
+    let name = enum_.name(ctx.db).display(ctx.db).to_string();
+    if ctx.scope.speculative_resolve(&make::ext::ident_path(&name)).is_some() {
+        return None;
+    }
+    Some(LocatedImport::new(import_path, item, item))
+}
@@ -263,6 +263,7 @@ fn import_on_the_fly(
             ctx.config.insert_use.prefix_kind,
             ctx.config.prefer_no_std,
             ctx.config.prefer_prelude,
+            ctx.config.insert_use.prefer_reexports,
         )
         .filter(ns_filter)
         .filter(|import| {
@@ -314,6 +315,7 @@ fn import_on_the_fly_pat_(
             ctx.config.insert_use.prefix_kind,
             ctx.config.prefer_no_std,
             ctx.config.prefer_prelude,
+            ctx.config.insert_use.prefer_reexports,
         )
         .filter(ns_filter)
         .filter(|import| {
@@ -361,6 +363,7 @@ fn import_on_the_fly_method(
             ctx.config.insert_use.prefix_kind,
             ctx.config.prefer_no_std,
             ctx.config.prefer_prelude,
+            ctx.config.insert_use.prefer_reexports,
         )
         .filter(|import| {
             !ctx.is_item_hidden(&import.item_to_import)
@@ -2,17 +2,25 @@
 //!
 //! These completions work a bit differently in that they are only shown when what the user types
 //! has a `'` preceding it, as our fake syntax tree is invalid otherwise (due to us not inserting
-//! a lifetime but an ident for obvious reasons).
+//! a lifetime but an ident for obvious reasons). The exception are labels directly following a
+//! `break` or `continue` keyword, which are completed from the expression path context instead.
 //! Due to this all the tests for lifetimes and labels live in this module for the time being as
 //! there is no value in lifting these out into the outline module test since they will either not
 //! show up for normal completions, or they won't show completions other than lifetimes depending
 //! on the fixture input.
 use hir::{known, ScopeDef};
-use syntax::{ast, TokenText};
+use ide_db::SymbolKind;
+use syntax::{
+    algo::skip_trivia_token,
+    ast::{self, HasGenericParams, HasName},
+    match_ast, AstNode, Direction, SmolStr, SyntaxKind, TokenText, T,
+};
+use text_edit::TextEdit;
 
 use crate::{
     completions::Completions,
-    context::{CompletionContext, LifetimeContext, LifetimeKind},
+    context::{CompletionContext, LifetimeContext, LifetimeKind, PathCompletionCtx},
+    CompletionItem,
 };
 
 /// Completes lifetimes.
@@ -37,18 +45,88 @@ pub(crate) fn complete_lifetime(
     let param_lifetime = param_lifetime.as_ref().map(ast::Lifetime::text);
     let param_lifetime = param_lifetime.as_ref().map(TokenText::as_str);
 
+    let mut in_scope = Vec::new();
     ctx.process_all_names_raw(&mut |name, res| {
-        if matches!(
-            res,
-            ScopeDef::GenericParam(hir::GenericParam::LifetimeParam(_))
-                 if param_lifetime != Some(&*name.to_smol_str())
-        ) {
-            acc.add_lifetime(ctx, name);
+        if matches!(res, ScopeDef::GenericParam(hir::GenericParam::LifetimeParam(_))) {
+            let text = name.to_smol_str();
+            if param_lifetime != Some(&*text) {
+                acc.add_lifetime(ctx, name);
+            }
+            in_scope.push(text);
         }
     });
     if param_lifetime.is_none() {
         acc.add_lifetime(ctx, known::STATIC_LIFETIME);
     }
+    if lp.is_none() {
+        add_new_lifetime_param(acc, ctx, &in_scope);
+    }
+}
+
+/// Completes a fresh lifetime name, declaring it in the generic parameter list of the item whose
+/// signature we are in.
+fn add_new_lifetime_param(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    in_scope: &[SmolStr],
+) {
+    let Some(owner) = ctx
+        .original_token
+        .parent_ancestors()
+        .find_map(|node| {
+            match_ast! {
+                match node {
+                    // lifetimes can't be introduced from within a body
+                    ast::BlockExpr(_) => Some(None),
+                    ast::Fn(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::Impl(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::Struct(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::Enum(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::Union(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::Trait(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    ast::TypeAlias(it) => Some(Some(ast::AnyHasGenericParams::new(it))),
+                    _ => None,
+                }
+            }
+        })
+        .flatten()
+    else {
+        return;
+    };
+    let Some(name) =
+        ('a'..='z').map(|c| format!("'{c}")).find(|name| !in_scope.iter().any(|it| it == name))
+    else {
+        return;
+    };
+
+    let (offset, decl) = match owner.generic_param_list() {
+        Some(list) => {
+            let Some(l_angle) = list.l_angle_token() else { return };
+            let decl = match list.generic_params().next() {
+                Some(_) => format!("{name}, "),
+                None => name.clone(),
+            };
+            (l_angle.text_range().end(), decl)
+        }
+        None => {
+            let offset = match ast::Impl::cast(owner.syntax().clone()) {
+                Some(impl_) => impl_.impl_token().map(|it| it.text_range().end()),
+                None => ast::AnyHasName::cast(owner.syntax().clone())
+                    .and_then(|it| it.name())
+                    .map(|it| it.syntax().text_range().end()),
+            };
+            let Some(offset) = offset else { return };
+            (offset, format!("<{name}>"))
+        }
+    };
+
+    let source_range = ctx.source_range();
+    let mut builder = TextEdit::builder();
+    builder.insert(offset, decl);
+    builder.replace(source_range, name.clone());
+    let mut item = CompletionItem::new(SymbolKind::LifetimeParam, source_range, name);
+    item.detail("new lifetime parameter").text_edit(builder.finish());
+    item.add_to(acc, ctx.db);
 }
 
 /// Completes labels.
@@ -57,16 +135,77 @@ pub(crate) fn complete_label(
     ctx: &CompletionContext<'_>,
     lifetime_ctx: &LifetimeContext,
 ) {
-    if !matches!(lifetime_ctx, LifetimeContext { kind: LifetimeKind::LabelRef, .. }) {
+    let LifetimeContext { kind: LifetimeKind::LabelRef { is_continue }, .. } = lifetime_ctx else {
+        return;
+    };
+    add_reachable_labels(acc, ctx, *is_continue);
+}
+
+/// Completes labels directly after a `break` or `continue` keyword, before any `'` was typed.
+pub(crate) fn complete_label_in_expr(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    path_ctx: &PathCompletionCtx,
+) {
+    if !path_ctx.is_trivial_path() {
         return;
     }
+    let mut token = ctx.original_token.clone();
+    if token.kind() == SyntaxKind::IDENT {
+        match token.prev_token() {
+            Some(prev) => token = prev,
+            None => return,
+        }
+    }
+    let Some(token) = skip_trivia_token(token, Direction::Prev) else { return };
+    match token.kind() {
+        T![break] => add_reachable_labels(acc, ctx, false),
+        T![continue] => add_reachable_labels(acc, ctx, true),
+        _ => (),
+    }
+}
+
+fn add_reachable_labels(acc: &mut Completions, ctx: &CompletionContext<'_>, is_continue: bool) {
     ctx.process_all_names_raw(&mut |name, res| {
-        if let ScopeDef::Label(_) = res {
-            acc.add_label(ctx, name);
+        if let ScopeDef::Label(label) = res {
+            if is_label_reachable(ctx, label, is_continue) {
+                acc.add_label(ctx, name);
+            }
         }
     });
 }
 
+/// Checks whether `label` can be targeted from the cursor position, that is whether it labels a
+/// loop (or a block, for `break`) that encloses the cursor without an intermediate closure, async
+/// or const block.
+fn is_label_reachable(ctx: &CompletionContext<'_>, label: hir::Label, is_continue: bool) -> bool {
+    let src = label.source(ctx.db);
+    if src.file_id != ctx.position.file_id.into() {
+        return true;
+    }
+    let Some(target) = src.value.syntax().parent() else { return false };
+    if is_continue && ast::BlockExpr::can_cast(target.kind()) {
+        return false;
+    }
+    for node in ctx.original_token.parent_ancestors() {
+        if node.text_range() == target.text_range() && node.kind() == target.kind() {
+            return true;
+        }
+        let is_boundary = match_ast! {
+            match node {
+                ast::ClosureExpr(_) => true,
+                ast::BlockExpr(it) => it.async_token().is_some() || it.const_token().is_some(),
+                ast::Fn(_) => true,
+                _ => false,
+            }
+        };
+        if is_boundary {
+            return false;
+        }
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use expect_test::{expect, Expect};
@@ -108,6 +247,7 @@ fn complete_lifetime_in_ref() {
 fn foo<'lifetime>(foo: &'a$0 usize) {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -121,6 +261,7 @@ fn complete_lifetime_in_ref_missing_ty() {
 fn foo<'lifetime>(foo: &'a$0) {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -136,6 +277,7 @@ fn foo<'func>(&'a$0 self) {}
 }
 "#,
             expect![[r#"
+                lt 'a      new lifetime parameter
                 lt 'func
                 lt 'impl
                 lt 'static
@@ -151,6 +293,7 @@ fn complete_lifetime_in_arg_list() {
 fn foo<'lifetime>(_: Foo<'a$0>) {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -164,6 +307,7 @@ fn complete_lifetime_in_where_pred() {
 fn foo2<'lifetime, T>() where 'a$0 {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -177,6 +321,7 @@ fn complete_lifetime_in_ty_bound() {
 fn foo2<'lifetime, T>() where T: 'a$0 {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -186,6 +331,7 @@ fn foo2<'lifetime, T>() where T: 'a$0 {}
 fn foo2<'lifetime, T>() where T: Trait<'a$0> {}
 "#,
             expect![[r#"
+                lt 'a        new lifetime parameter
                 lt 'lifetime
                 lt 'static
             "#]],
@@ -333,6 +479,155 @@ fn complete_label_in_for_iterable() {
 fn foo() {
     'outer: for _ in [{ 'inner: loop { break '$0 } }] {}
 }
+"#,
+            expect![[r#"
+                lb 'inner
+            "#]],
+        );
+    }
+
+    #[test]
+    fn complete_new_lifetime_param_edit() {
+        check_edit(
+            "'a",
+            r#"
+fn foo(x: &'$0 str) {}
+"#,
+            r#"
+fn foo<'a>(x: &'a str) {}
+"#,
+        );
+        check_edit(
+            "'b",
+            r#"
+fn foo<'a, T>(x: &'a T, y: &'$0 str) {}
+"#,
+            r#"
+fn foo<'b, 'a, T>(x: &'a T, y: &'b str) {}
+"#,
+        );
+        check_edit(
+            "'a",
+            r#"
+struct Foo<T>(&'$0 T);
+"#,
+            r#"
+struct Foo<'a, T>(&'a T);
+"#,
+        );
+        check_edit(
+            "'a",
+            r#"
+struct Foo<'a>(&'a ());
+impl Foo<'$0> {}
+"#,
+            r#"
+struct Foo<'a>(&'a ());
+impl<'a> Foo<'a> {}
+"#,
+        );
+    }
+
+    #[test]
+    fn complete_new_lifetime_param_skips_names_in_scope() {
+        check(
+            r#"
+struct Foo;
+impl<'a> Foo {
+    fn foo<'b>(&'$0 self) {}
+}
+"#,
+            expect![[r#"
+                lt 'a
+                lt 'b
+                lt 'c      new lifetime parameter
+                lt 'static
+            "#]],
+        );
+    }
+
+    #[test]
+    fn dont_complete_new_lifetime_param_in_body() {
+        check(
+            r#"
+fn foo<'lifetime>() {
+    let _: &'$0 str;
+}
+"#,
+            expect![[r#"
+                lt 'lifetime
+                lt 'static
+            "#]],
+        );
+    }
+
+    #[test]
+    fn complete_label_after_break_keyword() {
+        check_edit(
+            "'label",
+            r#"
+fn foo() {
+    'label: loop {
+        break $0
+    }
+}
+"#,
+            r#"
+fn foo() {
+    'label: loop {
+        break 'label
+    }
+}
+"#,
+        );
+        check_edit(
+            "'label",
+            r#"
+fn foo() {
+    'label: loop {
+        continue la$0
+    }
+}
+"#,
+            r#"
+fn foo() {
+    'label: loop {
+        continue 'label
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn dont_complete_block_label_after_continue() {
+        check(
+            r#"
+fn foo() {
+    'block: {
+        'foop: loop {
+            continue '$0
+        }
+    }
+}
+"#,
+            expect![[r#"
+                lb 'foop
+            "#]],
+        );
+    }
+
+    #[test]
+    fn dont_complete_label_across_closure() {
+        check(
+            r#"
+fn foo() {
+    'outer: loop {
+        let _ = || 'inner: loop {
+            break '$0
+        };
+    }
+}
 "#,
             expect![[r#"
                 lb 'inner
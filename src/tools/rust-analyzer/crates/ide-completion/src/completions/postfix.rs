@@ -18,7 +18,7 @@
 
 use crate::{
     completions::postfix::format_like::add_format_like_completions,
-    context::{BreakableKind, CompletionContext, DotAccess, DotAccessKind},
+    context::{BreakableKind, CompletionContext, DotAccess, DotAccessKind, ReturnPosition},
     item::{Builder, CompletionRelevancePostfixMatch},
     CompletionItem, CompletionItemKind, CompletionRelevance, Completions, SnippetScope,
 };
@@ -212,6 +212,10 @@ pub(crate) fn complete_postfix(
         }
     }
 
+    if let Some(try_enum) = &try_enum {
+        add_return_completions(acc, ctx, &postfix_snippet, dot_access, *try_enum, &receiver_text);
+    }
+
     postfix_snippet("box", "Box::new(expr)", &format!("Box::new({receiver_text})"))
         .add_to(acc, ctx.db);
     postfix_snippet("dbg", "dbg!(expr)", &format!("dbg!({receiver_text})")).add_to(acc, ctx.db); // fixme
@@ -257,6 +261,62 @@ pub(crate) fn complete_postfix(
     }
 }
 
+/// Completions for a `Result` or `Option` that's returned, which handle the variant that doesn't
+/// fit the return type of the function by returning early.
+fn add_return_completions(
+    acc: &mut Completions,
+    ctx: &CompletionContext<'_>,
+    postfix_snippet: impl Fn(&str, &str, &str) -> Builder,
+    dot_access: &DotAccess,
+    receiver: TryEnum,
+    receiver_text: &str,
+) {
+    let expr_ctx = &dot_access.ctx;
+    if expr_ctx.in_return_position == ReturnPosition::None {
+        return;
+    }
+    let Some(ret) =
+        expr_ctx.innermost_ret_ty.as_ref().and_then(|ty| TryEnum::from_ty(&ctx.sema, ty))
+    else {
+        return;
+    };
+
+    if let (TryEnum::Result, TryEnum::Result) = (receiver, ret) {
+        postfix_snippet("try", "Ok(expr?)", &format!("Ok({receiver_text}?)")).add_to(acc, ctx.db);
+    }
+
+    let (receiver_pat, ret_variant) = (receiver.happy_case(), ret.happy_case());
+    let other_arm = match (receiver, ret) {
+        (TryEnum::Result, TryEnum::Result) => "Err(err) => Err(err.into())",
+        (TryEnum::Result, TryEnum::Option) => "Err(_) => None",
+        (TryEnum::Option, TryEnum::Result) => "None => Err($2)",
+        (TryEnum::Option, TryEnum::Option) => "None => None",
+    };
+    postfix_snippet(
+        "matchr",
+        &format!("match expr {{ {receiver_pat}(..) => {ret_variant}(..), .. }}"),
+        &format!(
+            "match {receiver_text} {{\n    {receiver_pat}(${{1:value}}) => {ret_variant}($1),\n    {other_arm},\n}}$0"
+        ),
+    )
+    .add_to(acc, ctx.db);
+
+    if expr_ctx.in_return_position == ReturnPosition::Tail && expr_ctx.in_block_expr {
+        let early_return = match ret {
+            TryEnum::Result => "Err($2)",
+            TryEnum::Option => "None",
+        };
+        postfix_snippet(
+            "letr",
+            &format!("let {receiver_pat} else {{ return }}"),
+            &format!(
+                "let {receiver_pat}(${{1:value}}) = {receiver_text} else {{\n    return {early_return};\n}};\n$0"
+            ),
+        )
+        .add_to(acc, ctx.db);
+    }
+}
+
 fn get_receiver_text(receiver: &ast::Expr, receiver_is_ambiguous_float_literal: bool) -> String {
     let text = if receiver_is_ambiguous_float_literal {
         let text = receiver.syntax().text();
@@ -568,6 +628,173 @@ fn main() {
         );
     }
 
+    #[test]
+    fn result_in_result_tail() {
+        check(
+            r#"
+//- minicore: result
+fn foo() -> Result<u32, ()> { Ok(0) }
+fn main() -> Result<u8, ()> {
+    foo().$0
+}
+"#,
+            expect![[r#"
+                sn box    Box::new(expr)
+                sn call   function(expr)
+                sn dbg    dbg!(expr)
+                sn dbgr   dbg!(&expr)
+                sn deref  *expr
+                sn ifl    if let Ok {}
+                sn let    let
+                sn lete   let Ok else {}
+                sn letm   let mut
+                sn letr   let Ok else { return }
+                sn match  match expr {}
+                sn matchr match expr { Ok(..) => Ok(..), .. }
+                sn ref    &expr
+                sn refm   &mut expr
+                sn return return expr
+                sn try    Ok(expr?)
+                sn unsafe unsafe {}
+                sn while  while let Ok {}
+            "#]],
+        );
+        check_edit(
+            "try",
+            r#"
+//- minicore: result
+fn foo() -> Result<u32, ()> { Ok(0) }
+fn main() -> Result<u32, ()> {
+    foo().$0
+}
+"#,
+            r#"
+fn foo() -> Result<u32, ()> { Ok(0) }
+fn main() -> Result<u32, ()> {
+    Ok(foo()?)
+}
+"#,
+        );
+        check_edit(
+            "matchr",
+            r#"
+//- minicore: result
+fn foo() -> Result<u32, ()> { Ok(0) }
+fn main() -> Result<u32, ()> {
+    foo().$0
+}
+"#,
+            r#"
+fn foo() -> Result<u32, ()> { Ok(0) }
+fn main() -> Result<u32, ()> {
+    match foo() {
+    Ok(${1:value}) => Ok($1),
+    Err(err) => Err(err.into()),
+}$0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn option_in_result_tail() {
+        check_edit(
+            "letr",
+            r#"
+//- minicore: option, result
+fn main() -> Result<u32, ()> {
+    let bar = Some(1);
+    bar.$0
+}
+"#,
+            r#"
+fn main() -> Result<u32, ()> {
+    let bar = Some(1);
+    let Some(${1:value}) = bar else {
+    return Err($2);
+};
+$0
+}
+"#,
+        );
+        check_edit(
+            "matchr",
+            r#"
+//- minicore: option, result
+fn main() -> Result<u32, ()> {
+    let bar = Some(1);
+    return bar.$0
+}
+"#,
+            r#"
+fn main() -> Result<u32, ()> {
+    let bar = Some(1);
+    return match bar {
+    Some(${1:value}) => Ok($1),
+    None => Err($2),
+}$0
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn result_in_option_closure() {
+        check_edit(
+            "matchr",
+            r#"
+//- minicore: option, result, fn
+fn main() {
+    let f = || -> Option<u32> { Ok::<u32, ()>(1).$0 };
+}
+"#,
+            r#"
+fn main() {
+    let f = || -> Option<u32> { match Ok::<u32, ()>(1) {
+    Ok(${1:value}) => Some($1),
+    Err(_) => None,
+}$0 };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_return_completions_outside_of_return_position() {
+        check(
+            r#"
+//- minicore: option
+fn main() -> Option<u32> {
+    let bar = Some(1);
+    bar.$0;
+    None
+}
+"#,
+            expect![[r#"
+                me and(…)       fn(self, Option<U>) -> Option<U>
+                me as_ref()     const fn(&self) -> Option<&T>
+                me ok_or(…)     const fn(self, E) -> Result<T, E>
+                me unwrap()     const fn(self) -> T
+                me unwrap_or(…) fn(self, T) -> T
+                sn box          Box::new(expr)
+                sn call         function(expr)
+                sn dbg          dbg!(expr)
+                sn dbgr         dbg!(&expr)
+                sn deref        *expr
+                sn ifl          if let Some {}
+                sn let          let
+                sn lete         let Some else {}
+                sn letm         let mut
+                sn match        match expr {}
+                sn ref          &expr
+                sn refm         &mut expr
+                sn return       return expr
+                sn unsafe       unsafe {}
+                sn while        while let Some {}
+            "#]],
+        );
+    }
+
     #[test]
     fn postfix_completion_works_for_ambiguous_float_literal() {
         check_edit("refm", r#"fn main() { 42.$0 }"#, r#"fn main() { &mut 42 }"#)
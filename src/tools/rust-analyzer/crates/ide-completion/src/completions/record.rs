@@ -128,6 +128,8 @@ fn complete_fields(
                 ctx: DotAccessExprCtx {
                     in_block_expr: false,
                     in_breakable: crate::context::BreakableKind::None,
+                    in_return_position: crate::context::ReturnPosition::None,
+                    innermost_ret_ty: None,
                 },
             },
             None,
@@ -293,7 +293,7 @@ pub(crate) struct LifetimeContext {
 pub(crate) enum LifetimeKind {
     LifetimeParam { is_decl: bool, param: ast::LifetimeParam },
     Lifetime,
-    LabelRef,
+    LabelRef { is_continue: bool },
     LabelDef,
 }
 
@@ -400,6 +400,19 @@ pub(crate) enum DotAccessKind {
 pub(crate) struct DotAccessExprCtx {
     pub(crate) in_block_expr: bool,
     pub(crate) in_breakable: BreakableKind,
+    pub(crate) in_return_position: ReturnPosition,
+    /// The return type of the innermost function or closure, only computed in return position.
+    pub(crate) innermost_ret_ty: Option<hir::Type>,
+}
+
+/// Whether an expression is the value returned by the innermost function or closure.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum ReturnPosition {
+    None,
+    /// The tail expression of the body.
+    Tail,
+    /// The operand of `return`.
+    Return,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -667,7 +680,7 @@ pub(crate) fn new(
         let file_with_fake_ident = {
             let parse = db.parse(file_id);
             let edit = Indel::insert(offset, COMPLETION_MARKER.to_owned());
-            parse.reparse(&edit).tree()
+            parse.reparse(&edit, db.file_edition(file_id)).tree()
         };
 
         // always pick the token to the immediate left of the cursor, as that is what we are actually
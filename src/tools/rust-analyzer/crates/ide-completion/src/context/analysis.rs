@@ -14,8 +14,8 @@
     AttrCtx, BreakableKind, CompletionAnalysis, DotAccess, DotAccessExprCtx, DotAccessKind,
     ItemListKind, LifetimeContext, LifetimeKind, NameContext, NameKind, NameRefContext,
     NameRefKind, ParamContext, ParamKind, PathCompletionCtx, PathExprCtx, PathKind, PatternContext,
-    PatternRefutability, Qualified, QualifierCtx, TypeAscriptionTarget, TypeLocation,
-    COMPLETION_MARKER,
+    PatternRefutability, Qualified, QualifierCtx, ReturnPosition, TypeAscriptionTarget,
+    TypeLocation, COMPLETION_MARKER,
 };
 
 struct ExpansionResult {
@@ -489,7 +489,28 @@ fn expected_type_and_name(
             }
         };
     };
-    (ty.map(strip_refs), name)
+    let ty = expected_type_of_completed_expr(sema, token).or_else(|| ty.map(strip_refs));
+    (ty, name)
+}
+
+/// Returns what type inference expected of the expression being completed, if the token is part of
+/// one that exists in the original file.
+fn expected_type_of_completed_expr(
+    sema: &Semantics<'_, RootDatabase>,
+    token: &SyntaxToken,
+) -> Option<Type> {
+    let name_ref = token.parent().and_then(ast::NameRef::cast)?;
+    let expr = match_ast! {
+        match (name_ref.syntax().parent()?) {
+            ast::FieldExpr(it) => ast::Expr::from(it),
+            ast::PathSegment(it) => {
+                let path = it.parent_path().top_path();
+                ast::Expr::from(path.syntax().parent().and_then(ast::PathExpr::cast)?)
+            },
+            _ => return None,
+        }
+    };
+    sema.expected_type_of_expr(&expr)
 }
 
 fn classify_lifetime(
@@ -508,8 +529,8 @@ fn classify_lifetime(
                 is_decl: param.lifetime().as_ref() == Some(&lifetime),
                 param
             },
-            ast::BreakExpr(_) => LifetimeKind::LabelRef,
-            ast::ContinueExpr(_) => LifetimeKind::LabelRef,
+            ast::BreakExpr(_) => LifetimeKind::LabelRef { is_continue: false },
+            ast::ContinueExpr(_) => LifetimeKind::LabelRef { is_continue: true },
             ast::Label(_) => LifetimeKind::LabelDef,
             _ => LifetimeKind::Lifetime,
         }
@@ -624,8 +645,8 @@ fn classify_name_ref(
                 let kind = NameRefKind::DotAccess(DotAccess {
                     receiver_ty: receiver.as_ref().and_then(|it| sema.type_of_expr(it)),
                     kind: DotAccessKind::Field { receiver_is_ambiguous_float_literal },
+                    ctx: dot_access_expr_ctx(sema, field.syntax(), receiver.as_ref()),
                     receiver,
-                    ctx: DotAccessExprCtx { in_block_expr: is_in_block(field.syntax()), in_breakable: is_in_breakable(field.syntax()) }
                 });
                 return Some(make_res(kind));
             },
@@ -638,8 +659,8 @@ fn classify_name_ref(
                 let kind = NameRefKind::DotAccess(DotAccess {
                     receiver_ty: receiver.as_ref().and_then(|it| sema.type_of_expr(it)),
                     kind: DotAccessKind::Method { has_parens: method.arg_list().map_or(false, |it| it.l_paren_token().is_some()) },
+                    ctx: dot_access_expr_ctx(sema, method.syntax(), receiver.as_ref()),
                     receiver,
-                    ctx: DotAccessExprCtx { in_block_expr: is_in_block(method.syntax()), in_breakable: is_in_breakable(method.syntax()) }
                 });
                 return Some(make_res(kind));
             },
@@ -933,28 +954,6 @@ fn classify_name_ref(
         let ref_expr_parent =
             path.as_single_name_ref().and_then(|_| it.parent()).and_then(ast::RefExpr::cast);
         let (innermost_ret_ty, self_param) = {
-            let find_ret_ty = |it: SyntaxNode| {
-                if let Some(item) = ast::Item::cast(it.clone()) {
-                    match item {
-                        ast::Item::Fn(f) => Some(sema.to_def(&f).map(|it| it.ret_type(sema.db))),
-                        ast::Item::MacroCall(_) => None,
-                        _ => Some(None),
-                    }
-                } else {
-                    let expr = ast::Expr::cast(it)?;
-                    let callable = match expr {
-                        // FIXME
-                        // ast::Expr::BlockExpr(b) if b.async_token().is_some() || b.try_token().is_some() => sema.type_of_expr(b),
-                        ast::Expr::ClosureExpr(_) => sema.type_of_expr(&expr),
-                        _ => return None,
-                    };
-                    Some(
-                        callable
-                            .and_then(|c| c.adjusted().as_callable(sema.db))
-                            .map(|it| it.return_type()),
-                    )
-                }
-            };
             let find_fn_self_param = |it| match it {
                 ast::Item::Fn(fn_) => Some(sema.to_def(&fn_).and_then(|it| it.self_param(sema.db))),
                 ast::Item::MacroCall(_) => None,
@@ -963,10 +962,7 @@ fn classify_name_ref(
 
             match find_node_in_file_compensated(sema, original_file, &expr) {
                 Some(it) => {
-                    let innermost_ret_ty = sema
-                        .ancestors_with_macros(it.syntax().clone())
-                        .find_map(find_ret_ty)
-                        .flatten();
+                    let innermost_ret_ty = innermost_ret_ty(sema, it.syntax());
 
                     let self_param = sema
                         .ancestors_with_macros(it.syntax().clone())
@@ -1483,6 +1479,76 @@ fn is_in_breakable(node: &SyntaxNode) -> BreakableKind {
         .unwrap_or(BreakableKind::None)
 }
 
+/// The return type of the innermost function or closure containing `node`, which has to be in the
+/// original file.
+fn innermost_ret_ty(sema: &Semantics<'_, RootDatabase>, node: &SyntaxNode) -> Option<Type> {
+    let find_ret_ty = |it: SyntaxNode| {
+        if let Some(item) = ast::Item::cast(it.clone()) {
+            match item {
+                ast::Item::Fn(f) => Some(sema.to_def(&f).map(|it| it.ret_type(sema.db))),
+                ast::Item::MacroCall(_) => None,
+                _ => Some(None),
+            }
+        } else {
+            let expr = ast::Expr::cast(it)?;
+            let callable = match expr {
+                // FIXME
+                // ast::Expr::BlockExpr(b) if b.async_token().is_some() || b.try_token().is_some() => sema.type_of_expr(b),
+                ast::Expr::ClosureExpr(_) => sema.type_of_expr(&expr),
+                _ => return None,
+            };
+            Some(
+                callable.and_then(|c| c.adjusted().as_callable(sema.db)).map(|it| it.return_type()),
+            )
+        }
+    };
+    sema.ancestors_with_macros(node.clone()).find_map(find_ret_ty).flatten()
+}
+
+fn dot_access_expr_ctx(
+    sema: &Semantics<'_, RootDatabase>,
+    node: &SyntaxNode,
+    original_receiver: Option<&ast::Expr>,
+) -> DotAccessExprCtx {
+    let in_return_position = return_position(node);
+    let innermost_ret_ty = match in_return_position {
+        ReturnPosition::None => None,
+        ReturnPosition::Tail | ReturnPosition::Return => {
+            original_receiver.and_then(|it| innermost_ret_ty(sema, it.syntax()))
+        }
+    };
+    DotAccessExprCtx {
+        in_block_expr: is_in_block(node),
+        in_breakable: is_in_breakable(node),
+        in_return_position,
+        innermost_ret_ty,
+    }
+}
+
+fn return_position(node: &SyntaxNode) -> ReturnPosition {
+    let Some(parent) = node.parent() else { return ReturnPosition::None };
+    match_ast! {
+        match parent {
+            ast::ReturnExpr(_) => ReturnPosition::Return,
+            ast::ClosureExpr(_) => ReturnPosition::Tail,
+            ast::StmtList(stmt_list) => {
+                let is_body = stmt_list
+                    .syntax()
+                    .parent()
+                    .and_then(|block| block.parent())
+                    .map_or(false, |it| matches!(it.kind(), SyntaxKind::FN | SyntaxKind::CLOSURE_EXPR));
+                let is_tail = stmt_list.tail_expr().map_or(false, |it| it.syntax() == node);
+                if is_body && is_tail {
+                    ReturnPosition::Tail
+                } else {
+                    ReturnPosition::None
+                }
+            },
+            _ => ReturnPosition::None,
+        }
+    }
+}
+
 fn is_in_block(node: &SyntaxNode) -> bool {
     node.parent()
         .map(|node| ast::ExprStmt::can_cast(node.kind()) || ast::StmtList::can_cast(node.kind()))
@@ -423,3 +423,15 @@ fn f(thing: u32) -> &u32 {
         expect!["ty: u32, name: ?"],
     );
 }
+
+#[test]
+fn expected_type_array_element_with_leading_char() {
+    check_expected_type_and_name(
+        r#"
+fn foo() {
+    let x: [u8; 2] = [0, c$0];
+}
+"#,
+        expect![[r#"ty: u8, name: x"#]],
+    );
+}
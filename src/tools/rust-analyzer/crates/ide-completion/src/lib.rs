@@ -266,6 +266,7 @@ pub fn resolve_completion_edits(
                     config.insert_use.prefix_kind,
                     config.prefer_no_std,
                     config.prefer_prelude,
+                    config.insert_use.prefer_reexports,
                 )
             })
             .find(|mod_path| mod_path.display(db).to_string() == full_import_path);
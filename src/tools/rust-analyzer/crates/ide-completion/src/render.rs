@@ -852,8 +852,8 @@ fn main() {
 }
 "#,
             expect![[r#"
-                ev dep::test_mod_b::Enum::variant [type_could_unify]
                 en Enum (use dep::test_mod_b::Enum) [type_could_unify+requires_import]
+                ev Enum::variant (use dep::test_mod_b::Enum) [type_could_unify+requires_import]
                 fn main() []
                 fn test(…) []
                 md dep []
@@ -889,7 +889,7 @@ fn main() {
 }
 "#,
             expect![[r#"
-                ev dep::test_mod_b::Enum::Variant [type_could_unify]
+                ev Enum::Variant (use dep::test_mod_b::Enum) [type_could_unify+requires_import]
                 fn main() []
                 fn test(…) []
                 md dep []
@@ -1241,15 +1241,15 @@ fn main() { let _: m::Spam = S$0 }
                         ),
                     },
                     CompletionItem {
-                        label: "m::Spam::Bar(…)",
+                        label: "Spam::Bar(…)",
                         source_range: 75..76,
                         delete: 75..76,
-                        insert: "m::Spam::Bar(${1:()})$0",
+                        insert: "Spam::Bar(${1:()})$0",
                         kind: SymbolKind(
                             Variant,
                         ),
                         lookup: "Spam::Bar()",
-                        detail: "m::Spam::Bar(i32)",
+                        detail: "Spam::Bar(i32)",
                         relevance: CompletionRelevance {
                             exact_name_match: false,
                             type_match: Some(
@@ -1259,7 +1259,7 @@ fn main() { let _: m::Spam = S$0 }
                             is_item_from_trait: false,
                             is_item_from_notable_trait: false,
                             is_name_already_imported: false,
-                            requires_import: false,
+                            requires_import: true,
                             is_op_method: false,
                             is_private_editable: false,
                             postfix_match: None,
@@ -1269,15 +1269,14 @@ fn main() { let _: m::Spam = S$0 }
                         trigger_call_info: true,
                     },
                     CompletionItem {
-                        label: "m::Spam::Foo",
+                        label: "Spam::Foo",
                         source_range: 75..76,
                         delete: 75..76,
-                        insert: "m::Spam::Foo$0",
+                        insert: "Spam::Foo$0",
                         kind: SymbolKind(
                             Variant,
                         ),
-                        lookup: "Spam::Foo",
-                        detail: "m::Spam::Foo",
+                        detail: "Spam::Foo",
                         relevance: CompletionRelevance {
                             exact_name_match: false,
                             type_match: Some(
@@ -1287,7 +1286,7 @@ fn main() { let _: m::Spam = S$0 }
                             is_item_from_trait: false,
                             is_item_from_notable_trait: false,
                             is_name_already_imported: false,
-                            requires_import: false,
+                            requires_import: true,
                             is_op_method: false,
                             is_private_editable: false,
                             postfix_match: None,
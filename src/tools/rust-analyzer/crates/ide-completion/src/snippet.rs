@@ -180,6 +180,7 @@ fn import_edits(ctx: &CompletionContext<'_>, requires: &[GreenNode]) -> Option<V
             ctx.config.insert_use.prefix_kind,
             ctx.config.prefer_no_std,
             ctx.config.prefer_prelude,
+            ctx.config.insert_use.prefer_reexports,
         )?;
         Some((path.len() > 1).then(|| LocatedImport::new(path.clone(), item, item)))
     };
@@ -27,7 +27,7 @@
 use hir::PrefixKind;
 use ide_db::{
     base_db::{FileLoader, FilePosition},
-    imports::insert_use::{ImportGranularity, InsertUseConfig},
+    imports::insert_use::{ImportGranularity, ImportGroupStyle, InsertUseConfig},
     RootDatabase, SnippetCap,
 };
 use itertools::Itertools;
@@ -76,7 +76,9 @@ union Union { field: i32 }
         prefix_kind: PrefixKind::Plain,
         enforce_granularity: true,
         group: true,
+        group_style: ImportGroupStyle::Default,
         skip_glob_imports: true,
+        prefer_reexports: false,
     },
     snippets: Vec::new(),
     limit: None,
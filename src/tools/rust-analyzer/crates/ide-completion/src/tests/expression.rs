@@ -754,6 +754,60 @@ fn brr()           fn()
     );
 }
 
+#[test]
+fn expected_enum_variant_imports_enum() {
+    check_edit(
+        "Shape::Rect{}",
+        r#"
+mod shapes {
+    pub enum Shape { Circle(f32), Rect { w: f32, h: f32 }, Empty }
+}
+fn area(shape: shapes::Shape) -> f32 { 0.0 }
+fn main() {
+    area($0);
+}
+"#,
+        r#"
+use shapes::Shape;
+
+mod shapes {
+    pub enum Shape { Circle(f32), Rect { w: f32, h: f32 }, Empty }
+}
+fn area(shape: shapes::Shape) -> f32 { 0.0 }
+fn main() {
+    area(Shape::Rect { w: ${1:()}, h: ${2:()} }$0);
+}
+"#,
+    );
+}
+
+#[test]
+fn expected_enum_variant_keeps_path_when_name_is_taken() {
+    check_edit(
+        "Shape::Empty",
+        r#"
+mod shapes {
+    pub enum Shape { Circle(f32), Empty }
+}
+struct Shape;
+fn area(shape: shapes::Shape) -> f32 { 0.0 }
+fn main() {
+    area($0);
+}
+"#,
+        r#"
+mod shapes {
+    pub enum Shape { Circle(f32), Empty }
+}
+struct Shape;
+fn area(shape: shapes::Shape) -> f32 { 0.0 }
+fn main() {
+    area(shapes::Shape::Empty$0);
+}
+"#,
+    );
+}
+
 #[test]
 fn return_unit_block() {
     cov_mark::check!(return_unit_block);
@@ -1309,3 +1363,31 @@ fn main() {
         "#]],
     );
 }
+
+#[test]
+fn completes_locals_in_code_active_for_another_crate_variant() {
+    check_edit(
+        "wasm_local",
+        r#"
+//- /x86.rs crate:x86 cfg:target_arch=x86_64
+#[path = "shared.rs"]
+mod shared;
+//- /wasm.rs crate:wasm cfg:target_arch=wasm32
+#[path = "shared.rs"]
+mod shared;
+//- /shared.rs
+#[cfg(target_arch = "wasm32")]
+fn wasm_only() {
+    let wasm_local = 92;
+    wasm_lo$0
+}
+"#,
+        r#"
+#[cfg(target_arch = "wasm32")]
+fn wasm_only() {
+    let wasm_local = 92;
+    wasm_local
+}
+"#,
+    );
+}
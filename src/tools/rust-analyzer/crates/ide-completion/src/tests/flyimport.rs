@@ -700,6 +700,49 @@ fn main() {
     );
 }
 
+#[test]
+fn prefer_reexports_config() {
+    let fixture = r#"
+//- /main.rs crate:main deps:facade,imp
+fn main() {
+    Thi$0
+}
+//- /imp.rs crate:imp
+pub struct Thing;
+//- /facade.rs crate:facade deps:imp
+pub mod things {
+    pub use imp::Thing;
+}
+"#;
+    let mut config = TEST_CONFIG;
+    check_edit_with_config(
+        config.clone(),
+        "Thing",
+        fixture,
+        r#"
+use imp::Thing;
+
+fn main() {
+    Thing
+}
+"#,
+    );
+
+    config.insert_use.prefer_reexports = true;
+    check_edit_with_config(
+        config,
+        "Thing",
+        fixture,
+        r#"
+use facade::things::Thing;
+
+fn main() {
+    Thing
+}
+"#,
+    );
+}
+
 #[test]
 fn unresolved_qualifier() {
     let fixture = r#"
@@ -173,3 +173,36 @@ fn main() {
         expect![[r#""#]],
     )
 }
+
+#[test]
+fn complete_dot_in_attr_failing_on_incomplete_code() {
+    check(
+        r#"
+//- proc_macros: reject_incomplete
+pub struct Foo;
+impl Foo {
+    fn foo(&self) {}
+}
+
+#[proc_macros::reject_incomplete]
+fn main() {
+    Foo.$0
+}
+"#,
+        expect![[r#"
+            me foo()  fn(&self)
+            sn box    Box::new(expr)
+            sn call   function(expr)
+            sn dbg    dbg!(expr)
+            sn dbgr   dbg!(&expr)
+            sn deref  *expr
+            sn let    let
+            sn letm   let mut
+            sn match  match expr {}
+            sn ref    &expr
+            sn refm   &mut expr
+            sn return return expr
+            sn unsafe unsafe {}
+        "#]],
+    )
+}
@@ -87,11 +87,15 @@ macro_rules! purge_each_query {
         purge_each_query![
             // SymbolsDatabase
             crate::symbol_index::ModuleSymbolsQuery
+            crate::symbol_index::CrateSymbolShardQuery
+            crate::symbol_index::CrateSymbolShardModulesQuery
             crate::symbol_index::LibrarySymbolsQuery
             crate::symbol_index::LocalRootsQuery
             crate::symbol_index::LibraryRootsQuery
+            crate::symbol_index::TraitImplIndexQuery
             // HirDatabase
             hir::db::InferQueryQuery
+            hir::db::BodyValidationDiagnosticsQuery
             hir::db::MirBodyQuery
             hir::db::BorrowckQuery
             hir::db::TyQuery
@@ -134,6 +138,7 @@ macro_rules! purge_each_query {
             hir::db::ProgramClausesForChalkEnvQuery
 
             // DefDatabase
+            hir::db::PersistedItemTreeQuery
             hir::db::FileItemTreeQuery
             hir::db::CrateDefMapQueryQuery
             hir::db::BlockDefMapQuery
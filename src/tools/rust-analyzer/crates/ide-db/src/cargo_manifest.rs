@@ -0,0 +1,417 @@
+//! The dependencies declared in a `Cargo.toml`, read with a TOML parser that keeps track of where
+//! in the file keys and values are, so that they can be pointed at and edited.
+
+use std::ops::Range;
+
+use toml_edit::{ImDocument, Item, TableLike};
+
+/// A dependency declared in a `Cargo.toml` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestDependency {
+    /// The name the dependency is declared as.
+    pub key: String,
+    /// The name of the crate on crates.io, which differs from `key` for renamed dependencies.
+    pub package: String,
+    pub version_req: Option<String>,
+    pub features: Vec<String>,
+    pub default_features: bool,
+    /// Whether this is declared in `[workspace.dependencies]`, rather than by a package.
+    pub is_workspace: bool,
+    /// The byte range of `key` in the manifest.
+    pub range: Range<usize>,
+}
+
+/// Finds the dependencies declared in the given `Cargo.toml` text, in the `[dependencies]` tables
+/// and their `dev-`, `build-`, `target.*.` and `workspace.` variants, in the order they appear in.
+///
+/// Returns nothing if the text isn't valid TOML, like while a manifest is being edited.
+pub fn manifest_dependencies(text: &str) -> Vec<ManifestDependency> {
+    let Ok(doc) = ImDocument::parse(text) else { return Vec::new() };
+    let mut res: Vec<_> = dependency_tables(&doc)
+        .flat_map(|(is_workspace, table)| {
+            table.iter().filter_map(move |(key, item)| {
+                let range = table.key(key)?.span()?;
+                let mut dep = ManifestDependency {
+                    key: key.to_owned(),
+                    package: key.to_owned(),
+                    version_req: item.as_str().map(ToOwned::to_owned),
+                    features: Vec::new(),
+                    default_features: true,
+                    is_workspace,
+                    range,
+                };
+                if let Some(fields) = item.as_table_like() {
+                    let str_field = |name| fields.get(name).and_then(Item::as_str);
+                    if let Some(package) = str_field("package") {
+                        package.clone_into(&mut dep.package);
+                    }
+                    dep.version_req = str_field("version").map(ToOwned::to_owned);
+                    dep.features = features_array(fields)
+                        .map(|it| {
+                            it.iter().filter_map(|it| Some(it.as_str()?.to_owned())).collect()
+                        })
+                        .unwrap_or_default();
+                    dep.default_features = ["default-features", "default_features"]
+                        .iter()
+                        .find_map(|&name| fields.get(name)?.as_bool())
+                        .unwrap_or(true);
+                }
+                Some(dep)
+            })
+        })
+        .collect();
+    res.sort_by_key(|it| it.range.start);
+    res
+}
+
+/// Edits the declaration of `dependency` in the manifest `text` to enable `features`, merging them
+/// into its `features` array if it already has one. Dependencies declared by the package are
+/// preferred over `[workspace.dependencies]`, and `-` and `_` are considered equal in the name.
+///
+/// Returns the range to replace and its replacement, or `None` if the dependency isn't declared or
+/// already enables all of `features`.
+pub fn add_dependency_features(
+    text: &str,
+    dependency: &str,
+    features: &[&str],
+) -> Option<(Range<usize>, String)> {
+    let doc = ImDocument::parse(text).ok()?;
+    let normalize = |it: &str| it.replace('-', "_");
+    let dependency = normalize(dependency);
+    let (key, item) = dependency_tables(&doc)
+        .filter_map(|(is_workspace, table)| {
+            let (key, item) = table.iter().find(|(key, _)| normalize(key) == dependency)?;
+            Some((is_workspace, table.key(key)?, item))
+        })
+        .min_by_key(|&(is_workspace, ..)| is_workspace)
+        .map(|(_, key, item)| (key, item))?;
+
+    let existing = item.as_table_like().and_then(features_array);
+    let new: Vec<_> = features
+        .iter()
+        .filter(|&&feature| {
+            existing.map_or(true, |it| !it.iter().any(|it| it.as_str() == Some(feature)))
+        })
+        .map(|it| format!("{it:?}"))
+        .collect();
+    if new.is_empty() {
+        return None;
+    }
+    let new = new.join(", ");
+
+    if let Some(array) = existing {
+        let span = array.span()?;
+        return Some(match array.iter().last() {
+            Some(last) => (last.span()?.end..last.span()?.end, format!(", {new}")),
+            None => (span.start + 1..span.end - 1, new),
+        });
+    }
+    match item {
+        Item::Value(value) if value.is_str() => {
+            let span = value.span()?;
+            let version = &text[span.clone()];
+            Some((span, format!("{{ version = {version}, features = [{new}] }}")))
+        }
+        Item::Value(value) => {
+            let table = value.as_inline_table()?;
+            let span = value.span()?;
+            Some(match table.iter().last() {
+                Some((_, last)) => {
+                    let end = last.span()?.end;
+                    (end..end, format!(", features = [{new}]"))
+                }
+                None => (span, format!("{{ features = [{new}] }}")),
+            })
+        }
+        Item::Table(table) => {
+            let (end, line) =
+                match table.iter().filter_map(|(_, it)| it.span()).map(|it| it.end).max() {
+                    // `dep.version = ".."` in a dependencies table.
+                    Some(end) if table.is_dotted() => {
+                        (end, format!("{}.features = [{new}]", key.display_repr()))
+                    }
+                    Some(end) => (end, format!("features = [{new}]")),
+                    // Right after the `[dependencies.dep]` header.
+                    None if !table.is_dotted() => {
+                        let end = key.span()?.end;
+                        (end + text[end..].find(']')?, format!("features = [{new}]"))
+                    }
+                    None => return None,
+                };
+            // Start a new line after the one of the last field, which may end with a comment.
+            Some(match text[end..].find('\n') {
+                Some(idx) => (end + idx + 1..end + idx + 1, format!("{line}\n")),
+                None => (text.len()..text.len(), format!("\n{line}")),
+            })
+        }
+        Item::None | Item::ArrayOfTables(_) => None,
+    }
+}
+
+/// The dependency tables of a manifest, and whether they are `[workspace.dependencies]`.
+fn dependency_tables<'a>(
+    doc: &'a ImDocument<&str>,
+) -> impl Iterator<Item = (bool, &'a dyn TableLike)> + 'a {
+    const KINDS: [&str; 5] = [
+        "dependencies",
+        "dev-dependencies",
+        "dev_dependencies",
+        "build-dependencies",
+        "build_dependencies",
+    ];
+    let root = doc.as_table();
+    let targets = root
+        .get("target")
+        .and_then(Item::as_table_like)
+        .into_iter()
+        .flat_map(|targets| targets.iter().filter_map(|(_, it)| it.as_table_like()));
+    let workspace = root
+        .get("workspace")
+        .and_then(|it| it.get("dependencies"))
+        .and_then(Item::as_table_like)
+        .map(|it| (true, it));
+    std::iter::once(root as &dyn TableLike)
+        .chain(targets)
+        .flat_map(|table| KINDS.iter().filter_map(|&kind| table.get(kind)?.as_table_like()))
+        .map(|it| (false, it))
+        .chain(workspace)
+}
+
+fn features_array(dependency: &dyn TableLike) -> Option<&toml_edit::Array> {
+    dependency.get("features")?.as_array()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use super::{add_dependency_features, manifest_dependencies};
+
+    #[test]
+    fn dependencies() {
+        let text = r#"
+[package]
+name = "foo"
+version = "0.1.0"
+description = "my-log = '0.1'"
+
+[dependencies]
+serde = "1.0"
+tokio = { version = "1", features = ["rt", "macros"], default-features = false }
+my-log = { package = "log", version = "0.4" } # renamed
+anyhow = { version = "1.0", features = [
+    "backtrace",
+] }
+
+[target.'cfg(unix)'.dev-dependencies]
+libc = "0.2"
+
+[build-dependencies.cc]
+version = "1.0"
+features = ["parallel"]
+
+[workspace.dependencies]
+"either".workspace = true
+"#;
+        let deps: Vec<_> = manifest_dependencies(text)
+            .into_iter()
+            .map(|it| {
+                assert_eq!(&text[it.range.clone()].trim_matches('"'), &it.key);
+                (
+                    it.key,
+                    it.package,
+                    it.version_req,
+                    it.features,
+                    it.default_features,
+                    it.is_workspace,
+                )
+            })
+            .collect();
+        let s = |it: &str| it.to_owned();
+        assert_eq!(
+            deps,
+            [
+                (s("serde"), s("serde"), Some(s("1.0")), vec![], true, false),
+                (s("tokio"), s("tokio"), Some(s("1")), vec![s("rt"), s("macros")], false, false),
+                (s("my-log"), s("log"), Some(s("0.4")), vec![], true, false),
+                (s("anyhow"), s("anyhow"), Some(s("1.0")), vec![s("backtrace")], true, false),
+                (s("libc"), s("libc"), Some(s("0.2")), vec![], true, false),
+                (s("cc"), s("cc"), Some(s("1.0")), vec![s("parallel")], true, false),
+                (s("either"), s("either"), None, vec![], true, true),
+            ]
+        );
+        assert!(manifest_dependencies("[dependencies]\nserde = ").is_empty());
+    }
+
+    fn check_add_features(dependency: &str, before: &str, after: Expect) {
+        let res = match add_dependency_features(before, dependency, &["foo", "bar"]) {
+            Some((range, replacement)) => {
+                let mut res = before.to_owned();
+                res.replace_range(range, &replacement);
+                res
+            }
+            None => "None".to_owned(),
+        };
+        after.assert_eq(&res);
+    }
+
+    #[test]
+    fn add_features() {
+        check_add_features(
+            "dep_b",
+            r#"[dependencies]
+dep-a = "1.0"
+dep-b = "1.0" # comment
+"#,
+            expect![[r#"
+                [dependencies]
+                dep-a = "1.0"
+                dep-b = { version = "1.0", features = ["foo", "bar"] } # comment
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep = { version = "1.0", path = "features" }
+"#,
+            expect![[r#"
+                [dependencies]
+                dep = { version = "1.0", path = "features", features = ["foo", "bar"] }
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep = {}
+"#,
+            expect![[r#"
+                [dependencies]
+                dep = { features = ["foo", "bar"] }
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep.version = "1.0"
+"#,
+            expect![[r#"
+                [dependencies]
+                dep.version = "1.0"
+                dep.features = ["foo", "bar"]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn merge_features() {
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep = { version = "1.0", features = ["foo"] }
+"#,
+            expect![[r#"
+                [dependencies]
+                dep = { version = "1.0", features = ["foo", "bar"] }
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep = { version = "1.0", features = [] }
+"#,
+            expect![[r#"
+                [dependencies]
+                dep = { version = "1.0", features = ["foo", "bar"] }
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies]
+dep = { version = "1.0", features = ["bar", "foo"] }
+"#,
+            expect!["None"],
+        );
+        check_add_features(
+            "my_dep",
+            r#"[dev-dependencies]
+my-dep = { path = "..", features = ["a"] }
+"#,
+            expect![[r#"
+                [dev-dependencies]
+                my-dep = { path = "..", features = ["a", "foo", "bar"] }
+            "#]],
+        );
+    }
+
+    #[test]
+    fn add_features_to_dependency_table() {
+        check_add_features(
+            "dep",
+            r#"[dependencies.dep]
+version = "1.0"
+features = [
+    "baz",
+]
+
+[dependencies.other]
+version = "1.0"
+"#,
+            expect![[r#"
+                [dependencies.dep]
+                version = "1.0"
+                features = [
+                    "baz", "foo", "bar",
+                ]
+
+                [dependencies.other]
+                version = "1.0"
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies.dep]
+version = "1.0" # comment
+
+[dev-dependencies]
+"#,
+            expect![[r#"
+                [dependencies.dep]
+                version = "1.0" # comment
+                features = ["foo", "bar"]
+
+                [dev-dependencies]
+            "#]],
+        );
+        check_add_features(
+            "dep",
+            r#"[dependencies.dep]
+"#,
+            expect![[r#"
+                [dependencies.dep]
+                features = ["foo", "bar"]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn prefers_package_dependencies() {
+        check_add_features(
+            "dep",
+            r#"[workspace.dependencies]
+dep = "1.0"
+
+[dependencies]
+dep = { workspace = true }
+"#,
+            expect![[r#"
+                [workspace.dependencies]
+                dep = "1.0"
+
+                [dependencies]
+                dep = { workspace = true, features = ["foo", "bar"] }
+            "#]],
+        );
+        check_add_features("dep", "[dependencies]\nother = \"1.0\"\n", expect!["None"]);
+        check_add_features("dep", "[package]\ndep = \"1.0\"\n", expect!["None"]);
+    }
+}
@@ -0,0 +1,282 @@
+//! Crate features, as named by `feature = "..."` predicates of `#[cfg]`, `#[cfg_attr]` and `cfg!`,
+//! and as declared in the `[features]` table of the package's `Cargo.toml`.
+
+use base_db::{FileId, SourceDatabaseExt};
+use hir::{Crate, Semantics};
+use itertools::Itertools;
+use syntax::{
+    algo::non_trivia_sibling,
+    ast::{self, IsString},
+    AstNode, AstToken, Direction, SyntaxKind, SyntaxToken, TextRange, TextSize, T,
+};
+
+use crate::RootDatabase;
+
+/// The features declared in a `Cargo.toml`.
+#[derive(Debug)]
+pub struct CargoFeatures {
+    pub file_id: FileId,
+    /// The declared features, with the ranges of their keys.
+    pub declarations: Vec<(String, TextRange)>,
+    /// The features enabled by other features, with the ranges of their names.
+    pub references: Vec<(String, TextRange)>,
+}
+
+impl CargoFeatures {
+    pub fn declaration(&self, name: &str) -> Option<TextRange> {
+        self.declarations.iter().find(|(it, _)| it == name).map(|&(_, range)| range)
+    }
+}
+
+/// The `Cargo.toml` of the package `krate` belongs to, if it's part of the crate's source root.
+pub fn cargo_toml(db: &RootDatabase, krate: Crate) -> Option<FileId> {
+    let root_file = krate.root_file(db);
+    let source_root = db.source_root(db.file_source_root(root_file));
+    let mut dir = source_root.path_for_file(&root_file)?.parent();
+    loop {
+        let current = dir?;
+        let manifest = current.join("Cargo.toml").and_then(|it| source_root.file_for_path(&it));
+        if let Some(&manifest) = manifest {
+            return Some(manifest);
+        }
+        dir = current.parent();
+    }
+}
+
+/// The features declared in the `Cargo.toml` of `krate`.
+pub fn cargo_features(db: &RootDatabase, krate: Crate) -> Option<CargoFeatures> {
+    let file_id = cargo_toml(db, krate)?;
+    Some(parse_cargo_features(file_id, &db.file_text(file_id)))
+}
+
+/// The name of the feature `token` refers to, if it's the string of a `feature = "..."`
+/// predicate.
+pub fn cfg_feature_name(sema: &Semantics<'_, RootDatabase>, token: &SyntaxToken) -> Option<String> {
+    let string = ast::String::cast(token.clone())?;
+    let eq = non_trivia_sibling(token.clone().into(), Direction::Prev)?;
+    if eq.kind() != T![=] {
+        return None;
+    }
+    let key = non_trivia_sibling(eq, Direction::Prev)?.into_token()?;
+    if key.kind() != SyntaxKind::IDENT || key.text() != "feature" {
+        return None;
+    }
+
+    // Predicates may be nested in `all(..)`, `any(..)` and `not(..)`.
+    let tt =
+        token.parent_ancestors().take_while(|it| ast::TokenTree::can_cast(it.kind())).last()?;
+    let parent = tt.parent()?;
+    if let Some(meta) = ast::Meta::cast(parent.clone()) {
+        let name = meta.path()?.as_single_name_ref()?;
+        if !matches!(&*name.text(), "cfg" | "cfg_attr") {
+            return None;
+        }
+    } else {
+        let macro_call = ast::MacroCall::cast(parent)?;
+        let name = macro_call.path()?.segment()?.name_ref()?;
+        if name.text() != "cfg" {
+            return None;
+        }
+        // Ignore non-built-in macros to account for shadowing
+        if let Some(it) = sema.resolve_macro_call(&macro_call) {
+            if !matches!(it.kind(sema.db), hir::MacroKind::BuiltIn) {
+                return None;
+            }
+        }
+    }
+    Some(string.value()?.into_owned())
+}
+
+/// The feature names of the `feature = "..."` predicates in `file_id`, with the ranges between
+/// their quotes.
+pub fn cfg_features_in_file(
+    sema: &Semantics<'_, RootDatabase>,
+    file_id: FileId,
+) -> Vec<(String, TextRange)> {
+    sema.parse(file_id)
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| it.kind() == SyntaxKind::STRING)
+        .filter_map(|token| {
+            let name = cfg_feature_name(sema, &token)?;
+            let range = ast::String::cast(token)?.text_range_between_quotes()?;
+            Some((name, range))
+        })
+        .collect()
+}
+
+/// The `feature = "name"` predicates in the crates of the package `krate` belongs to.
+pub fn cfg_feature_references(
+    sema: &Semantics<'_, RootDatabase>,
+    krate: Crate,
+    name: &str,
+) -> Vec<(FileId, TextRange)> {
+    let db = sema.db;
+    // The binaries, tests and examples of a package share its features.
+    let crates = match cargo_toml(db, krate) {
+        Some(manifest) => {
+            let source_root = db.file_source_root(manifest);
+            Crate::all(db)
+                .into_iter()
+                .filter(|it| {
+                    db.file_source_root(it.root_file(db)) == source_root
+                        && cargo_toml(db, *it) == Some(manifest)
+                })
+                .collect()
+        }
+        None => vec![krate],
+    };
+    crates
+        .into_iter()
+        .flat_map(|krate| krate.modules(db))
+        .filter_map(|module| module.as_source_file_id(db))
+        .unique()
+        .flat_map(|file_id| {
+            cfg_features_in_file(sema, file_id)
+                .into_iter()
+                .filter(|(it, _)| it == name)
+                .map(move |(_, range)| (file_id, range))
+        })
+        .collect()
+}
+
+/// Whether `name` can be the name of a feature.
+pub fn is_valid_feature_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().map_or(false, |it| it.is_alphanumeric() || it == '_')
+        && chars.all(|it| it.is_alphanumeric() || matches!(it, '_' | '-' | '+' | '.'))
+}
+
+/// Finds the features of a `Cargo.toml` without parsing it fully, so only the common ways to
+/// write the `[features]` table are supported.
+fn parse_cargo_features(file_id: FileId, text: &str) -> CargoFeatures {
+    let mut res = CargoFeatures { file_id, declarations: Vec::new(), references: Vec::new() };
+    let mut in_features = false;
+    // The nesting of the arrays the current line is in, which may span several lines.
+    let mut depth = 0usize;
+    let mut line_start = 0;
+    for line in text.split_inclusive('\n') {
+        let offset = line_start;
+        line_start += line.len();
+        let trimmed = line.trim();
+        if depth == 0 && trimmed.starts_with('[') {
+            in_features = trimmed.trim_matches(|it| matches!(it, '[' | ']')).trim() == "features";
+            continue;
+        }
+
+        let range = |start: usize, end: usize| {
+            TextRange::new(
+                TextSize::from((offset + start) as u32),
+                TextSize::from((offset + end) as u32),
+            )
+        };
+        // Lines in an array continue a value, other lines start with a key.
+        let mut in_key = depth == 0;
+        let mut key = None;
+        let mut chars = line.char_indices();
+        while let Some((idx, c)) = chars.next() {
+            match c {
+                '#' => break,
+                '"' => {
+                    let start = idx + 1;
+                    let mut end = line.len();
+                    while let Some((idx, c)) = chars.next() {
+                        match c {
+                            '\\' => _ = chars.next(),
+                            '"' => {
+                                end = idx;
+                                break;
+                            }
+                            _ => (),
+                        }
+                    }
+                    let value = &line[start..end];
+                    if in_key {
+                        key = Some((value, range(start, end)));
+                    } else if in_features && !value.contains(['/', ':']) {
+                        // `dep:name` and `name/feature` refer to dependencies.
+                        res.references.push((value.to_owned(), range(start, end)));
+                    }
+                }
+                '=' if in_key => {
+                    in_key = false;
+                    let key = key.take().or_else(|| {
+                        let bare = line[..idx].trim_end();
+                        let start = bare.len() - bare.trim_start().len();
+                        Some((bare.trim_start(), range(start, bare.len())))
+                    });
+                    if let Some((name, range)) = key.filter(|_| in_features) {
+                        if !name.is_empty() {
+                            res.declarations.push((name.to_owned(), range));
+                        }
+                    }
+                }
+                '[' => depth += 1,
+                ']' => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+        }
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::FileId;
+    use expect_test::{expect, Expect};
+
+    use super::parse_cargo_features;
+
+    fn check(text: &str, expect: Expect) {
+        let features = parse_cargo_features(FileId::from_raw(0), text);
+        let render = |items: &[(String, syntax::TextRange)]| {
+            items
+                .iter()
+                .map(|(name, range)| {
+                    assert_eq!(&text[*range], name);
+                    format!("{name} {range:?}\n")
+                })
+                .collect::<String>()
+        };
+        expect.assert_eq(&format!(
+            "declarations:\n{}references:\n{}",
+            render(&features.declarations),
+            render(&features.references)
+        ));
+    }
+
+    #[test]
+    fn features_table() {
+        check(
+            r#"
+[package]
+name = "foo"
+default = "not a feature"
+
+[features]
+default = ["std"]
+std = ["alloc", "serde?/std"] # alloc is implied
+"alloc" = []
+full = [
+    "std",
+    "dep:serde",
+]
+
+[dependencies]
+serde = { version = "1", optional = true }
+"#,
+            expect![[r#"
+                declarations:
+                default 62..69
+                std 80..83
+                alloc 130..135
+                full 142..146
+                references:
+                std 74..77
+                alloc 88..93
+                std 156..159
+            "#]],
+        );
+    }
+}
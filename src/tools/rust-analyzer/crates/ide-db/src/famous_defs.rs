@@ -70,6 +70,14 @@ pub fn core_default_Default(&self) -> Option<Trait> {
         self.find_trait("core:default:Default")
     }
 
+    pub fn core_clone_Clone(&self) -> Option<Trait> {
+        self.find_trait("core:clone:Clone")
+    }
+
+    pub fn core_error_Error(&self) -> Option<Trait> {
+        self.find_trait("core:error:Error")
+    }
+
     pub fn core_iter_Iterator(&self) -> Option<Trait> {
         self.find_trait("core:iter:traits:iterator:Iterator")
     }
@@ -106,6 +114,14 @@ pub fn core_marker_Copy(&self) -> Option<Trait> {
         self.find_trait("core:marker:Copy")
     }
 
+    pub fn core_marker_Sized(&self) -> Option<Trait> {
+        self.find_trait("core:marker:Sized")
+    }
+
+    pub fn core_marker_Sync(&self) -> Option<Trait> {
+        self.find_trait("core:marker:Sync")
+    }
+
     pub fn core_macros_builtin_derive(&self) -> Option<Macro> {
         self.find_macro("core:macros:builtin:derive")
     }
@@ -6,7 +6,7 @@
 use hir::{Crate, DescendPreference, ItemInNs, ModuleDef, Name, Semantics};
 use syntax::{
     ast::{self, make},
-    AstToken, SyntaxKind, SyntaxToken, TokenAtOffset,
+    AstNode, AstToken, SyntaxKind, SyntaxToken, TokenAtOffset,
 };
 
 use crate::{
@@ -40,7 +40,7 @@ pub fn mod_path_to_ast(path: &hir::ModPath) -> ast::Path {
     let mut segments = Vec::new();
     let mut is_abs = false;
     match path.kind {
-        hir::PathKind::Plain => {}
+        hir::PathKind::Plain | hir::PathKind::DefSite(_) => {}
         hir::PathKind::Super(0) => segments.push(make::path_segment_self()),
         hir::PathKind::Super(n) => segments.extend((0..n).map(|_| make::path_segment_super())),
         hir::PathKind::DollarCrate(_) | hir::PathKind::Crate => {
@@ -113,6 +113,35 @@ pub fn is_editable_crate(krate: Crate, db: &RootDatabase) -> bool {
     !db.source_root(source_root_id).is_library
 }
 
+/// If `token` is the variable name argument of a built-in `env!` or `option_env!` call, returns the
+/// name of the queried environment variable.
+pub fn env_var_name(sema: &Semantics<'_, RootDatabase>, token: &SyntaxToken) -> Option<String> {
+    let string = ast::String::cast(token.clone())?;
+    let tt = token.parent().and_then(ast::TokenTree::cast)?;
+    let first_arg = tt
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| !it.kind().is_trivia())
+        .nth(1)?;
+    if first_arg != *token {
+        return None;
+    }
+    let macro_call = tt.syntax().parent().and_then(ast::MacroCall::cast)?;
+    let name = macro_call.path()?.segment()?.name_ref()?;
+    if !matches!(&*name.text(), "env" | "option_env") {
+        return None;
+    }
+
+    // Ignore non-built-in macros to account for shadowing
+    if let Some(it) = sema.resolve_macro_call(&macro_call) {
+        if !matches!(it.kind(sema.db), hir::MacroKind::BuiltIn) {
+            return None;
+        }
+    }
+    Some(string.value()?.into_owned())
+}
+
 pub fn get_definition(
     sema: &Semantics<'_, RootDatabase>,
     token: SyntaxToken,
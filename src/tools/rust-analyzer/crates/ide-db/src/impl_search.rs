@@ -0,0 +1,297 @@
+//! Searches for the impls of a trait whose type arguments, associated types and self type match a
+//! pattern, like
+//!
+//! ```text
+//! From<_> for MyError
+//! Iterator<Item = String>
+//! ```
+//!
+//! The paths of a pattern are resolved in a scope, usually the one of the cursor. `_` matches any
+//! type, and so do the type parameters of generic impls: `impl<T: Display> From<T> for MyError` is
+//! found by `From<String> for MyError`.
+//!
+//! The impls of each trait are indexed by the ADT they are implemented for, so that a pattern with
+//! a known self type only looks at the impls for that type and at the blanket ones.
+
+use std::fmt;
+
+use hir::{
+    db::HirDatabase, Adt, AssocItem, BuiltinType, Impl, ModuleDef, Mutability, PathResolution,
+    SemanticsScope, Trait, Type,
+};
+use rustc_hash::FxHashMap;
+use syntax::{
+    ast::{self, HasModuleItem},
+    SourceFile,
+};
+use triomphe::Arc;
+
+use crate::{symbol_index::SymbolsDatabase, RootDatabase};
+
+/// The impls of a trait in the crates that can see it.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TraitImplIndex {
+    by_adt: FxHashMap<Adt, Vec<Impl>>,
+    /// The impls for types that aren't ADTs, including the blanket impls.
+    others: Vec<Impl>,
+}
+
+impl TraitImplIndex {
+    pub(crate) fn trait_impl_index_query(
+        db: &dyn SymbolsDatabase,
+        trait_: Trait,
+    ) -> Arc<TraitImplIndex> {
+        let _p = tracing::span!(tracing::Level::INFO, "trait_impl_index").entered();
+        let db = db.upcast();
+        let mut index = TraitImplIndex::default();
+        for impl_ in Impl::all_for_trait(db, trait_) {
+            match impl_.self_ty(db).as_adt() {
+                Some(adt) => index.by_adt.entry(adt).or_default().push(impl_),
+                None => index.others.push(impl_),
+            }
+        }
+        Arc::new(index)
+    }
+
+    /// The impls that may be for a type matching `self_ty`.
+    fn candidates<'a>(&'a self, self_ty: &TypePattern) -> Box<dyn Iterator<Item = Impl> + 'a> {
+        match self_ty {
+            TypePattern::Adt(adt, _) => {
+                Box::new(self.by_adt.get(adt).into_iter().flatten().chain(&self.others).copied())
+            }
+            _ => Box::new(self.by_adt.values().flatten().chain(&self.others).copied()),
+        }
+    }
+}
+
+/// A pattern matching types, from the syntax of a type with `_` standing for any type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypePattern {
+    Any,
+    /// An ADT with its type arguments, the missing trailing ones matching any type.
+    Adt(Adt, Vec<TypePattern>),
+    Builtin(BuiltinType),
+    Ref(Mutability, Box<TypePattern>),
+    Tuple(Vec<TypePattern>),
+    Slice(Box<TypePattern>),
+}
+
+impl TypePattern {
+    fn lower(scope: &SemanticsScope<'_>, ty: &ast::Type) -> Result<TypePattern, ImplPatternError> {
+        let lower_opt = |ty: Option<ast::Type>| match ty {
+            Some(ty) => TypePattern::lower(scope, &ty),
+            None => Err(ImplPatternError::new("missing type")),
+        };
+        match ty {
+            ast::Type::InferType(_) => Ok(TypePattern::Any),
+            ast::Type::ParenType(it) => lower_opt(it.ty()),
+            ast::Type::RefType(it) => {
+                let mutability =
+                    if it.mut_token().is_some() { Mutability::Mut } else { Mutability::Shared };
+                Ok(TypePattern::Ref(mutability, Box::new(lower_opt(it.ty())?)))
+            }
+            ast::Type::TupleType(it) => Ok(TypePattern::Tuple(
+                it.fields().map(|it| TypePattern::lower(scope, &it)).collect::<Result<_, _>>()?,
+            )),
+            ast::Type::SliceType(it) => Ok(TypePattern::Slice(Box::new(lower_opt(it.ty())?))),
+            ast::Type::PathType(it) => {
+                let Some(path) = it.path() else {
+                    return Err(ImplPatternError::new("missing path"));
+                };
+                match scope.speculative_resolve(&path) {
+                    Some(PathResolution::Def(ModuleDef::Adt(adt))) => {
+                        let args = type_args(&path)
+                            .map(|it| TypePattern::lower(scope, &it))
+                            .collect::<Result<_, _>>()?;
+                        Ok(TypePattern::Adt(adt, args))
+                    }
+                    Some(PathResolution::Def(ModuleDef::BuiltinType(it))) => {
+                        Ok(TypePattern::Builtin(it))
+                    }
+                    Some(PathResolution::Def(ModuleDef::TypeAlias(alias))) => {
+                        Ok(TypePattern::from_type(scope.db, &alias.ty(scope.db)))
+                    }
+                    _ => Err(ImplPatternError(format!("failed to resolve `{path}` to a type"))),
+                }
+            }
+            _ => Err(ImplPatternError(format!("unsupported type `{ty}`"))),
+        }
+    }
+
+    /// The pattern matching `ty`, with its type parameters and unknown parts matching any type.
+    fn from_type(db: &dyn HirDatabase, ty: &Type) -> TypePattern {
+        if let Some(adt) = ty.as_adt() {
+            let args = ty.type_arguments().map(|it| TypePattern::from_type(db, &it)).collect();
+            TypePattern::Adt(adt, args)
+        } else if let Some(builtin) = ty.as_builtin() {
+            TypePattern::Builtin(builtin)
+        } else if let Some((inner, mutability)) = ty.as_reference() {
+            TypePattern::Ref(mutability, Box::new(TypePattern::from_type(db, &inner)))
+        } else if ty.is_tuple() {
+            TypePattern::Tuple(
+                ty.tuple_fields(db).iter().map(|it| TypePattern::from_type(db, it)).collect(),
+            )
+        } else if let Some(elem) = ty.as_slice() {
+            TypePattern::Slice(Box::new(TypePattern::from_type(db, &elem)))
+        } else {
+            TypePattern::Any
+        }
+    }
+
+    pub fn matches(&self, db: &RootDatabase, ty: &Type) -> bool {
+        // A type parameter of a generic impl could be any type. The parameters are unknown in the
+        // types of the associated types of the impl.
+        if ty.as_type_param(db).is_some() || ty.is_unknown() {
+            return true;
+        }
+        match self {
+            TypePattern::Any => true,
+            TypePattern::Adt(adt, args) => {
+                ty.as_adt() == Some(*adt)
+                    && args.iter().zip(ty.type_arguments()).all(|(arg, ty)| arg.matches(db, &ty))
+            }
+            TypePattern::Builtin(builtin) => ty.as_builtin() == Some(*builtin),
+            TypePattern::Ref(mutability, inner) => ty
+                .as_reference()
+                .map_or(false, |(ty, it)| it == *mutability && inner.matches(db, &ty)),
+            TypePattern::Tuple(fields) => {
+                let tys = ty.tuple_fields(db);
+                ty.is_tuple()
+                    && tys.len() == fields.len()
+                    && fields.iter().zip(&tys).all(|(field, ty)| field.matches(db, ty))
+            }
+            TypePattern::Slice(elem) => ty.as_slice().map_or(false, |ty| elem.matches(db, &ty)),
+        }
+    }
+}
+
+/// A pattern matching the impls of a trait.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplPattern {
+    pub trait_: Trait,
+    /// The type arguments of the trait after `Self`, the missing trailing ones matching any type.
+    pub trait_args: Vec<TypePattern>,
+    /// The values of associated types, like the `String` of `Iterator<Item = String>`.
+    pub assoc_types: Vec<(String, TypePattern)>,
+    pub self_ty: TypePattern,
+}
+
+impl ImplPattern {
+    /// Parses a pattern like `From<_> for MyError`, or `Iterator<Item = String>` for any self
+    /// type.
+    pub fn parse(scope: &SemanticsScope<'_>, text: &str) -> Result<ImplPattern, ImplPatternError> {
+        let parse = SourceFile::parse(&format!("impl {text} {{}}"));
+        if let Some(error) = parse.errors().first() {
+            return Err(ImplPatternError(format!("failed to parse `{text}`: {error}")));
+        }
+        let mut items = parse.tree().items();
+        let (Some(ast::Item::Impl(impl_)), None) = (items.next(), items.next()) else {
+            return Err(ImplPatternError(format!("failed to parse `{text}`")));
+        };
+        let (trait_ty, self_ty) = match impl_.trait_() {
+            Some(trait_ty) => (trait_ty, impl_.self_ty()),
+            None => match impl_.self_ty() {
+                Some(trait_ty) => (trait_ty, None),
+                None => return Err(ImplPatternError::new("missing trait")),
+            },
+        };
+        let ast::Type::PathType(trait_path) = &trait_ty else {
+            return Err(ImplPatternError(format!("`{trait_ty}` is not a trait")));
+        };
+        let Some(trait_path) = trait_path.path() else {
+            return Err(ImplPatternError::new("missing trait"));
+        };
+        ImplPattern::from_trait_path(scope, &trait_path, self_ty.as_ref())
+    }
+
+    /// The pattern of the impls of the trait `path` refers to with the generic arguments of the
+    /// path, like `Iterator<Item = String>` in a bound.
+    pub fn from_trait_path(
+        scope: &SemanticsScope<'_>,
+        path: &ast::Path,
+        self_ty: Option<&ast::Type>,
+    ) -> Result<ImplPattern, ImplPatternError> {
+        let Some(PathResolution::Def(ModuleDef::Trait(trait_))) = scope.speculative_resolve(path)
+        else {
+            return Err(ImplPatternError(format!("failed to resolve `{path}` to a trait")));
+        };
+        let mut trait_args = Vec::new();
+        let mut assoc_types = Vec::new();
+        let args = path.segment().and_then(|it| it.generic_arg_list());
+        for arg in args.into_iter().flat_map(|it| it.generic_args()) {
+            match arg {
+                ast::GenericArg::TypeArg(it) => match it.ty() {
+                    Some(ty) => trait_args.push(TypePattern::lower(scope, &ty)?),
+                    None => return Err(ImplPatternError::new("missing type")),
+                },
+                ast::GenericArg::AssocTypeArg(it) => {
+                    let (Some(name), Some(ty)) = (it.name_ref(), it.ty()) else {
+                        return Err(ImplPatternError(format!(
+                            "unsupported associated type `{it}`"
+                        )));
+                    };
+                    assoc_types.push((name.text().to_string(), TypePattern::lower(scope, &ty)?));
+                }
+                ast::GenericArg::LifetimeArg(_) | ast::GenericArg::ConstArg(_) => (),
+            }
+        }
+        let self_ty = match self_ty {
+            Some(ty) => TypePattern::lower(scope, ty)?,
+            None => TypePattern::Any,
+        };
+        Ok(ImplPattern { trait_, trait_args, assoc_types, self_ty })
+    }
+
+    /// The impls matching the pattern in the crates that can see the trait.
+    pub fn search(&self, db: &RootDatabase) -> Vec<Impl> {
+        let index = db.trait_impl_index(self.trait_);
+        index.candidates(&self.self_ty).filter(|&it| self.matches(db, it)).collect()
+    }
+
+    fn matches(&self, db: &RootDatabase, impl_: Impl) -> bool {
+        let Some(trait_ref) = impl_.trait_ref(db).filter(|_| !impl_.is_negative(db)) else {
+            return false;
+        };
+        self.self_ty.matches(db, &trait_ref.self_ty())
+            && self.trait_args.iter().enumerate().all(|(idx, arg)| {
+                trait_ref.get_type_argument(idx + 1).map_or(false, |ty| arg.matches(db, &ty))
+            })
+            && self.assoc_types.iter().all(|(name, pattern)| {
+                impl_.items(db).into_iter().any(|item| match item {
+                    AssocItem::TypeAlias(alias) => {
+                        alias.name(db).display(db).to_string() == *name
+                            && pattern.matches(db, &alias.ty(db))
+                    }
+                    _ => false,
+                })
+            })
+    }
+}
+
+fn type_args(path: &ast::Path) -> impl Iterator<Item = ast::Type> {
+    path.segment()
+        .and_then(|it| it.generic_arg_list())
+        .into_iter()
+        .flat_map(|it| it.generic_args())
+        .filter_map(|it| match it {
+            ast::GenericArg::TypeArg(it) => it.ty(),
+            _ => None,
+        })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImplPatternError(String);
+
+impl ImplPatternError {
+    fn new(message: &str) -> ImplPatternError {
+        ImplPatternError(message.to_owned())
+    }
+}
+
+impl fmt::Display for ImplPatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid impl pattern: {}", self.0)
+    }
+}
+
+impl std::error::Error for ImplPatternError {}
@@ -207,10 +207,11 @@ pub fn search_for_imports(
         prefix_kind: PrefixKind,
         prefer_no_std: bool,
         prefer_prelude: bool,
+        prefer_reexports: bool,
     ) -> impl Iterator<Item = LocatedImport> {
         let _p =
             tracing::span!(tracing::Level::INFO, "import_assets::search_for_imports").entered();
-        self.search_for(sema, Some(prefix_kind), prefer_no_std, prefer_prelude)
+        self.search_for(sema, Some(prefix_kind), prefer_no_std, prefer_prelude, prefer_reexports)
     }
 
     /// This may return non-absolute paths if a part of the returned path is already imported into scope.
@@ -222,7 +223,7 @@ pub fn search_for_relative_paths(
     ) -> impl Iterator<Item = LocatedImport> {
         let _p = tracing::span!(tracing::Level::INFO, "import_assets::search_for_relative_paths")
             .entered();
-        self.search_for(sema, None, prefer_no_std, prefer_prelude)
+        self.search_for(sema, None, prefer_no_std, prefer_prelude, false)
     }
 
     /// Requires imports to by prefix instead of fuzzily.
@@ -261,6 +262,7 @@ fn search_for(
         prefixed: Option<PrefixKind>,
         prefer_no_std: bool,
         prefer_prelude: bool,
+        prefer_reexports: bool,
     ) -> impl Iterator<Item = LocatedImport> {
         let _p = tracing::span!(tracing::Level::INFO, "import_assets::search_for").entered();
 
@@ -279,6 +281,7 @@ fn search_for(
                 prefixed,
                 prefer_no_std,
                 prefer_prelude,
+                prefer_reexports,
             )
             .filter(|path| path.len() > 1)
         };
@@ -611,6 +614,7 @@ fn get_mod_path(
     prefixed: Option<PrefixKind>,
     prefer_no_std: bool,
     prefer_prelude: bool,
+    prefer_reexports: bool,
 ) -> Option<ModPath> {
     if let Some(prefix_kind) = prefixed {
         module_with_candidate.find_use_path_prefixed(
@@ -619,6 +623,7 @@ fn get_mod_path(
             prefix_kind,
             prefer_no_std,
             prefer_prelude,
+            prefer_reexports,
         )
     } else {
         module_with_candidate.find_use_path(db, item_to_search, prefer_no_std, prefer_prelude)
@@ -50,13 +50,59 @@ fn from(granularity: ImportGranularity) -> Self {
     }
 }
 
+/// How imports are split into groups separated by blank lines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImportGroupStyle {
+    /// Separate groups for `std` and `core`, external crates, `crate`, `self` and `super`.
+    Default,
+    /// rustfmt's `group_imports = "StdExternalCrate"`: `std`, `core` and `alloc` first, then
+    /// external crates, then `crate`, `self` and `super` together.
+    StdExternalCrate,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct InsertUseConfig {
     pub granularity: ImportGranularity,
     pub enforce_granularity: bool,
     pub prefix_kind: PrefixKind,
     pub group: bool,
+    pub group_style: ImportGroupStyle,
     pub skip_glob_imports: bool,
+    /// Whether to import items of other crates through re-exports in facade crates, rather than
+    /// from the crate defining them.
+    pub prefer_reexports: bool,
+}
+
+impl InsertUseConfig {
+    /// Applies the `imports_granularity` and `group_imports` options of a `rustfmt.toml`, so that
+    /// inserted imports already look the way rustfmt would format them.
+    pub fn with_rustfmt_config(mut self, rustfmt_toml: &str) -> InsertUseConfig {
+        for line in rustfmt_toml.lines() {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let value = value.split('#').next().unwrap_or_default().trim().trim_matches('"');
+            match (key.trim(), value) {
+                ("imports_granularity", granularity) => {
+                    let granularity = match granularity {
+                        "Crate" => ImportGranularity::Crate,
+                        "Module" => ImportGranularity::Module,
+                        "Item" => ImportGranularity::Item,
+                        "One" => ImportGranularity::One,
+                        _ => continue,
+                    };
+                    // rustfmt rewrites every import to this granularity anyway.
+                    self.granularity = granularity;
+                    self.enforce_granularity = true;
+                }
+                ("group_imports", "StdExternalCrate") => {
+                    self.group = true;
+                    self.group_style = ImportGroupStyle::StdExternalCrate;
+                }
+                ("group_imports", "One") => self.group = false,
+                _ => (),
+            }
+        }
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -236,7 +282,7 @@ fn insert_use_with_alias_option(
 
     // either we weren't allowed to merge or there is no import that fits the merge conditions
     // so look for the place we have to insert to
-    insert_use_(scope, use_item, cfg.group);
+    insert_use_(scope, use_item, cfg.group.then_some(cfg.group_style));
 }
 
 pub fn ast_to_remove_for_path_in_use_stmt(path: &ast::Path) -> Option<Box<dyn Removable>> {
@@ -272,7 +318,7 @@ enum ImportGroup {
 }
 
 impl ImportGroup {
-    fn new(use_tree: &ast::UseTree) -> ImportGroup {
+    fn new(use_tree: &ast::UseTree, style: ImportGroupStyle) -> ImportGroup {
         if use_tree.path().is_none() && use_tree.use_tree_list().is_some() {
             return ImportGroup::One;
         }
@@ -283,18 +329,23 @@ fn new(use_tree: &ast::UseTree) -> ImportGroup {
         };
 
         let kind = first_segment.kind().unwrap_or(PathSegmentKind::SelfKw);
-        match kind {
-            PathSegmentKind::SelfKw => ImportGroup::ThisModule,
-            PathSegmentKind::SuperKw => ImportGroup::SuperModule,
-            PathSegmentKind::CrateKw => ImportGroup::ThisCrate,
-            PathSegmentKind::Name(name) => match name.text().as_str() {
-                "std" => ImportGroup::Std,
-                "core" => ImportGroup::Std,
+        match (kind, style) {
+            (
+                PathSegmentKind::SelfKw | PathSegmentKind::SuperKw | PathSegmentKind::CrateKw,
+                ImportGroupStyle::StdExternalCrate,
+            ) => ImportGroup::ThisCrate,
+            (PathSegmentKind::SelfKw, _) => ImportGroup::ThisModule,
+            (PathSegmentKind::SuperKw, _) => ImportGroup::SuperModule,
+            (PathSegmentKind::CrateKw, _) => ImportGroup::ThisCrate,
+            (PathSegmentKind::Name(name), _) => match (name.text().as_str(), style) {
+                ("std" | "core", _) => ImportGroup::Std,
+                ("alloc", ImportGroupStyle::StdExternalCrate) => ImportGroup::Std,
                 _ => ImportGroup::ExternCrate,
             },
             // these aren't valid use paths, so fall back to something random
-            PathSegmentKind::SelfTypeKw => ImportGroup::ExternCrate,
-            PathSegmentKind::Type { .. } => ImportGroup::ExternCrate,
+            (PathSegmentKind::SelfTypeKw | PathSegmentKind::Type { .. }, _) => {
+                ImportGroup::ExternCrate
+            }
         }
     }
 }
@@ -397,11 +448,10 @@ fn guess_granularity_from_scope(scope: &ImportScope) -> ImportGranularityGuess {
     }
 }
 
-fn insert_use_(scope: &ImportScope, use_item: ast::Use, group_imports: bool) {
+fn insert_use_(scope: &ImportScope, use_item: ast::Use, group_style: Option<ImportGroupStyle>) {
     let scope_syntax = scope.as_syntax_node();
     let insert_use_tree =
         use_item.use_tree().expect("`use_item` should have a use tree for `insert_path`");
-    let group = ImportGroup::new(&insert_use_tree);
     let path_node_iter = scope_syntax
         .children()
         .filter_map(|node| ast::Use::cast(node.clone()).zip(Some(node)))
@@ -410,13 +460,14 @@ fn insert_use_(scope: &ImportScope, use_item: ast::Use, group_imports: bool) {
             Some((tree, node))
         });
 
-    if group_imports {
+    if let Some(style) = group_style {
+        let group = ImportGroup::new(&insert_use_tree, style);
         // Iterator that discards anything that's not in the required grouping
         // This implementation allows the user to rearrange their import groups as this only takes the first group that fits
         let group_iter = path_node_iter
             .clone()
-            .skip_while(|(use_tree, ..)| ImportGroup::new(use_tree) != group)
-            .take_while(|(use_tree, ..)| ImportGroup::new(use_tree) == group);
+            .skip_while(|(use_tree, ..)| ImportGroup::new(use_tree, style) != group)
+            .take_while(|(use_tree, ..)| ImportGroup::new(use_tree, style) == group);
 
         // track the last element we iterated over, if this is still None after the iteration then that means we never iterated in the first place
         let mut last = None;
@@ -442,7 +493,7 @@ fn insert_use_(scope: &ImportScope, use_item: ast::Use, group_imports: bool) {
         // find the group that comes after where we want to insert
         let post_group = path_node_iter
             .inspect(|(.., node)| last = Some(node.clone()))
-            .find(|(use_tree, ..)| ImportGroup::new(use_tree) > group);
+            .find(|(use_tree, ..)| ImportGroup::new(use_tree, style) > group);
         if let Some((.., node)) = post_group {
             cov_mark::hit!(insert_group_new_group);
             ted::insert(ted::Position::before(&node), use_item.syntax());
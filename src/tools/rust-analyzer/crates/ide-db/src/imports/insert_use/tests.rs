@@ -97,7 +97,9 @@ fn insert_not_group() {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -359,6 +361,77 @@ fn insert_missing_group_self() {
     )
 }
 
+#[test]
+fn insert_std_external_crate_groups() {
+    let config = InsertUseConfig {
+        granularity: ImportGranularity::Item,
+        enforce_granularity: true,
+        prefix_kind: PrefixKind::Plain,
+        group: true,
+        group_style: ImportGroupStyle::StdExternalCrate,
+        skip_glob_imports: true,
+        prefer_reexports: false,
+    };
+    check_with_config(
+        "super::fmt",
+        r"
+use alloc::vec::Vec;
+
+use foo::bar::A;
+
+use self::c::D;
+use crate::a::B;",
+        r"
+use alloc::vec::Vec;
+
+use foo::bar::A;
+
+use self::c::D;
+use super::fmt;
+use crate::a::B;",
+        &config,
+    );
+    check_with_config(
+        "alloc::string::String",
+        r"
+use foo::bar::A;",
+        r"
+use alloc::string::String;
+
+use foo::bar::A;",
+        &config,
+    );
+}
+
+#[test]
+fn rustfmt_config() {
+    let config = InsertUseConfig {
+        granularity: ImportGranularity::Crate,
+        enforce_granularity: false,
+        prefix_kind: PrefixKind::Plain,
+        group: true,
+        group_style: ImportGroupStyle::Default,
+        skip_glob_imports: true,
+        prefer_reexports: false,
+    };
+    assert_eq!(config.with_rustfmt_config("edition = \"2021\"\n"), config);
+    assert_eq!(
+        config.with_rustfmt_config(
+            "imports_granularity = \"Item\" # one per line\ngroup_imports = \"StdExternalCrate\"\n"
+        ),
+        InsertUseConfig {
+            granularity: ImportGranularity::Item,
+            enforce_granularity: true,
+            group_style: ImportGroupStyle::StdExternalCrate,
+            ..config
+        }
+    );
+    assert_eq!(
+        config.with_rustfmt_config("imports_granularity = \"Preserve\"\ngroup_imports = \"One\""),
+        InsertUseConfig { group: false, ..config }
+    );
+}
+
 #[test]
 fn insert_no_imports() {
     check_crate(
@@ -397,7 +470,9 @@ fn insert_empty_file() {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -432,7 +507,9 @@ mod x {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -462,7 +539,9 @@ fn insert_after_inner_attr() {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -870,7 +949,9 @@ fn merge_mod_into_glob() {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: false,
+            prefer_reexports: false,
         },
     )
 }
@@ -886,7 +967,9 @@ fn merge_self_glob() {
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: false,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: false,
+            prefer_reexports: false,
         },
     )
 }
@@ -1198,7 +1281,9 @@ fn insert_with_renamed_import_simple_use() {
             prefix_kind: hir::PrefixKind::BySelf,
             enforce_granularity: true,
             group: true,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -1218,7 +1303,9 @@ fn insert_with_renamed_import_complex_use() {
             prefix_kind: hir::PrefixKind::BySelf,
             enforce_granularity: true,
             group: true,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     );
 }
@@ -1271,7 +1358,9 @@ fn check(
             enforce_granularity: true,
             prefix_kind: PrefixKind::Plain,
             group: true,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: true,
+            prefer_reexports: false,
         },
     )
 }
@@ -0,0 +1,267 @@
+//! Persists the item trees of library crates in a cache directory, so that the next session
+//! doesn't have to parse and lower all dependencies again before name resolution can start.
+//!
+//! There is one cache file per library crate, named after the hash of its name, version, edition
+//! and root file path, and the version of rust-analyzer that wrote it. Each one contains the encoded item trees of the crate's module files, by the hash of
+//! their text, so an entry is only used if the file is unchanged. Def maps and everything further
+//! down the line refer to session specific interned ids, so they are computed anew.
+//!
+//! The cache can be restricted to the sysroot crates. Their root paths differ between toolchains,
+//! so each installed toolchain's standard library gets its own cache files. Files that weren't
+//! written for [`MAX_AGE`] belong to crates no longer in use, and are removed when saving.
+
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use base_db::{salsa::Durability, CrateId, FileId, SourceDatabase, SourceDatabaseExt};
+use hir::{
+    db::DefDatabase,
+    item_tree_persist::{self, ContentHash, PersistedItemTrees},
+};
+use rustc_hash::FxHashMap;
+
+use triomphe::Arc;
+
+use crate::RootDatabase;
+
+const MAGIC: &[u8; 4] = b"RAIT";
+
+/// How long a cache file is kept after it was last written.
+const MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Loads the persisted item trees of all library crates, or only the sysroot crates if
+/// `sysroot_only` is set, from `dir`. The trees of crates loaded by an earlier call are kept as
+/// they are, so that only the item trees of new crates are invalidated.
+pub fn load(db: &mut RootDatabase, dir: &Path, sysroot_only: bool, version: &str) {
+    let _p = tracing::span!(tracing::Level::INFO, "item_tree_cache::load").entered();
+    let loaded = db.persisted_item_trees();
+    let mut persisted: Option<PersistedItemTrees> = None;
+    for krate in library_crates(db, sysroot_only) {
+        let Some(key) = crate_key(db, krate, version) else { continue };
+        if loaded.has_source(&key) {
+            continue;
+        }
+        let mut trees = FxHashMap::default();
+        if let Ok(bytes) = fs::read(dir.join(&key)) {
+            if read_entries(&bytes, &mut trees).is_none() {
+                tracing::warn!("ignoring corrupt item tree cache of {krate:?}");
+                trees.clear();
+            }
+        }
+        persisted.get_or_insert_with(|| (*loaded).clone()).extend(&key, trees);
+    }
+    if let Some(persisted) = persisted {
+        db.set_persisted_item_trees_with_durability(Arc::new(persisted), Durability::HIGH);
+    }
+}
+
+/// Writes the item trees of all library crates, or only the sysroot crates if `sysroot_only` is
+/// set, to `dir`.
+pub fn save(db: &RootDatabase, dir: &Path, sysroot_only: bool, version: &str) -> io::Result<()> {
+    let _p = tracing::span!(tracing::Level::INFO, "item_tree_cache::save").entered();
+    fs::create_dir_all(dir)?;
+    for krate in library_crates(db, sysroot_only) {
+        let Some(key) = crate_key(db, krate, version) else { continue };
+        let mut buf = MAGIC.to_vec();
+        buf.extend_from_slice(&item_tree_persist::FORMAT_VERSION.to_le_bytes());
+        for file_id in module_files(db, krate) {
+            let item_tree = db.file_item_tree(file_id.into());
+            let Some(encoded) = item_tree_persist::encode(&item_tree, file_id) else { continue };
+            buf.extend_from_slice(&item_tree_persist::content_hash(&db.file_text(file_id)));
+            buf.extend_from_slice(&(encoded.len() as u64).to_le_bytes());
+            buf.extend_from_slice(&encoded);
+        }
+        // Write to a temporary file first, so that concurrent sessions never see partial files.
+        let tmp = dir.join(format!("{key}.{}.tmp", std::process::id()));
+        fs::write(&tmp, &buf)?;
+        fs::rename(&tmp, dir.join(key))?;
+    }
+    remove_expired(dir);
+    Ok(())
+}
+
+fn library_crates(db: &RootDatabase, sysroot_only: bool) -> Vec<CrateId> {
+    let crate_graph = db.crate_graph();
+    crate_graph
+        .iter()
+        .filter(|&krate| {
+            let data = &crate_graph[krate];
+            (!sysroot_only || data.origin.is_lang())
+                && db.source_root(db.file_source_root(data.root_file_id)).is_library
+        })
+        .collect()
+}
+
+/// Removes the cache files in `dir` that weren't written for [`MAX_AGE`].
+fn remove_expired(dir: &Path) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let modified = entry.metadata().and_then(|it| it.modified());
+        let expired = modified.map_or(false, |modified| {
+            now.duration_since(modified).map_or(false, |age| age > MAX_AGE)
+        });
+        if expired {
+            if let Err(e) = fs::remove_file(entry.path()) {
+                tracing::warn!("failed to remove item tree cache {}: {e}", entry.path().display());
+            }
+        }
+    }
+}
+
+/// The name of the cache file of `krate`, written by rust-analyzer `version`.
+fn crate_key(db: &RootDatabase, krate: CrateId, version: &str) -> Option<String> {
+    let crate_graph = db.crate_graph();
+    let data = &crate_graph[krate];
+    let source_root = db.source_root(db.file_source_root(data.root_file_id));
+    let root_path = source_root.path_for_file(&data.root_file_id)?;
+    let id = format!(
+        "{}\0{}\0{}\0{}\0{version}",
+        data.display_name.as_deref().unwrap_or_default(),
+        data.version.as_deref().unwrap_or_default(),
+        data.edition,
+        root_path,
+    );
+    let hash = item_tree_persist::content_hash(&id);
+    Some(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+fn module_files(db: &RootDatabase, krate: CrateId) -> Vec<FileId> {
+    let mut files: Vec<_> = hir::Crate::from(krate)
+        .modules(db)
+        .into_iter()
+        .filter_map(|module| module.as_source_file_id(db))
+        .collect();
+    files.sort_unstable();
+    files.dedup();
+    files
+}
+
+fn read_entries(mut bytes: &[u8], trees: &mut FxHashMap<ContentHash, Box<[u8]>>) -> Option<()> {
+    fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+        let taken = bytes.get(..len)?;
+        *bytes = &bytes[len..];
+        Some(taken)
+    }
+
+    if take(&mut bytes, 4)? != MAGIC
+        || take(&mut bytes, 4)? != item_tree_persist::FORMAT_VERSION.to_le_bytes()
+    {
+        return None;
+    }
+    while !bytes.is_empty() {
+        let hash = take(&mut bytes, 32)?.try_into().ok()?;
+        let len = u64::from_le_bytes(take(&mut bytes, 8)?.try_into().ok()?);
+        let encoded = take(&mut bytes, usize::try_from(len).ok()?)?;
+        trees.insert(hash, encoded.into());
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use base_db::{SourceDatabase, SourceDatabaseExt};
+    use hir::db::DefDatabase;
+    use test_fixture::WithFixture;
+    use test_utils::TempDir;
+    use triomphe::Arc;
+
+    use crate::RootDatabase;
+
+    const FIXTURE: &str = r#"
+//- /main.rs crate:main deps:dep
+use dep::S;
+//- /dep/lib.rs crate:dep new_source_root:library
+pub mod m;
+pub struct S;
+//- /dep/m.rs
+#[derive(Debug)]
+pub struct T(u8);
+"#;
+
+    #[test]
+    fn item_trees_roundtrip_through_the_cache() {
+        let temp_dir = TempDir::new("item-tree-cache");
+        let dir = temp_dir.path();
+        let db = RootDatabase::with_files(FIXTURE);
+        super::save(&db, dir, false, "0.0.0").unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+        let mut fresh = RootDatabase::with_files(FIXTURE);
+        super::load(&mut fresh, dir, false, "0.0.0");
+
+        let persisted = fresh.persisted_item_trees();
+        let library_files: Vec<_> = fresh
+            .crate_graph()
+            .iter()
+            .map(|krate| fresh.file_source_root(fresh.crate_graph()[krate].root_file_id))
+            .filter(|&root| fresh.source_root(root).is_library)
+            .flat_map(|root| fresh.source_root(root).iter().collect::<Vec<_>>())
+            .collect();
+        assert_eq!(library_files.len(), 2);
+        for file_id in library_files {
+            let lowered = db.file_item_tree(file_id.into());
+            let text = fresh.file_text(file_id);
+            assert_eq!(persisted.item_tree(file_id, &text).as_ref(), Some(&*lowered));
+        }
+    }
+
+    #[test]
+    fn sysroot_only_cache_skips_other_libraries() {
+        const FIXTURE: &str = r#"
+//- /main.rs crate:main deps:core,dep
+//- /core/lib.rs crate:core new_source_root:library
+pub struct Core;
+//- /dep/lib.rs crate:dep new_source_root:library
+pub struct Dep;
+"#;
+        let temp_dir = TempDir::new("sysroot-tree-cache");
+        let dir = temp_dir.path();
+        let db = RootDatabase::with_files(FIXTURE);
+        super::save(&db, dir, true, "0.0.0").unwrap();
+        assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+        let mut fresh = RootDatabase::with_files(FIXTURE);
+        super::load(&mut fresh, dir, true, "0.0.0");
+
+        let persisted = fresh.persisted_item_trees();
+        let crate_graph = fresh.crate_graph();
+        for krate in crate_graph.iter() {
+            let data = &crate_graph[krate];
+            let text = fresh.file_text(data.root_file_id);
+            let cached = persisted.item_tree(data.root_file_id, &text).is_some();
+            assert_eq!(cached, data.origin.is_lang(), "{:?}", data.display_name);
+        }
+    }
+
+    #[test]
+    fn reloading_keeps_the_trees_of_loaded_crates() {
+        let temp_dir = TempDir::new("item-tree-cache-reload");
+        let dir = temp_dir.path();
+        let db = RootDatabase::with_files(FIXTURE);
+        super::save(&db, dir, false, "0.0.0").unwrap();
+
+        let mut fresh = RootDatabase::with_files(FIXTURE);
+        super::load(&mut fresh, dir, false, "0.0.0");
+        let persisted = fresh.persisted_item_trees();
+        assert!(!persisted.is_empty());
+
+        super::load(&mut fresh, dir, false, "0.0.0");
+        assert!(Arc::ptr_eq(&persisted, &fresh.persisted_item_trees()));
+    }
+
+    #[test]
+    fn caches_of_other_versions_are_ignored() {
+        let temp_dir = TempDir::new("item-tree-cache-version");
+        let dir = temp_dir.path();
+        let db = RootDatabase::with_files(FIXTURE);
+        super::save(&db, dir, false, "1.0.0").unwrap();
+
+        let mut fresh = RootDatabase::with_files(FIXTURE);
+        super::load(&mut fresh, dir, false, "2.0.0");
+        assert!(fresh.persisted_item_trees().is_empty());
+    }
+}
@@ -8,18 +8,26 @@
 
 pub mod active_parameter;
 pub mod assists;
+pub mod cargo_manifest;
+pub mod crate_features;
 pub mod defs;
 pub mod documentation;
 pub mod famous_defs;
 pub mod helpers;
+pub mod impl_search;
+pub mod item_tree_cache;
 pub mod items_locator;
 pub mod label;
+pub mod lifetime_elision;
 pub mod path_transform;
+pub mod patterns;
+pub mod query_stats;
 pub mod rename;
 pub mod rust_doc;
 pub mod search;
 pub mod source_change;
 pub mod symbol_index;
+pub mod todo_comments;
 pub mod traits;
 pub mod ty_filter;
 pub mod use_trivial_constructor;
@@ -35,6 +43,7 @@ pub mod generated {
 }
 
 pub mod syntax_helpers {
+    pub mod digit_grouping;
     pub mod format_string;
     pub mod format_string_exprs;
     pub mod insert_whitespace_into_node;
@@ -129,7 +138,11 @@ fn relevant_crates(&self, file_id: FileId) -> Arc<[CrateId]> {
     }
 }
 
-impl salsa::Database for RootDatabase {}
+impl salsa::Database for RootDatabase {
+    fn salsa_event(&self, event: salsa::Event) {
+        query_stats::record(self, &event);
+    }
+}
 
 impl Default for RootDatabase {
     fn default() -> RootDatabase {
@@ -145,6 +158,7 @@ pub fn new(lru_capacity: Option<usize>) -> RootDatabase {
         db.set_local_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_library_roots_with_durability(Default::default(), Durability::HIGH);
         db.set_expand_proc_attr_macros_with_durability(false, Durability::HIGH);
+        db.set_persisted_item_trees_with_durability(Default::default(), Durability::HIGH);
         db.update_base_query_lru_capacities(lru_capacity);
         db.setup_syntax_context_root();
         db
@@ -214,6 +228,7 @@ macro_rules! update_lru_capacity_per_query {
             hir_db::ParseMacroExpansionErrorQuery
 
             // DefDatabase
+            hir_db::PersistedItemTreeQuery
             hir_db::FileItemTreeQuery
             hir_db::CrateDefMapQueryQuery
             hir_db::BlockDefMapQuery
@@ -248,6 +263,7 @@ macro_rules! update_lru_capacity_per_query {
 
             // HirDatabase
             hir_db::InferQueryQuery
+            hir_db::BodyValidationDiagnosticsQuery
             hir_db::MirBodyQuery
             hir_db::BorrowckQuery
             hir_db::TyQuery
@@ -291,7 +307,10 @@ macro_rules! update_lru_capacity_per_query {
 
             // SymbolsDatabase
             symbol_index::ModuleSymbolsQuery
+            symbol_index::CrateSymbolShardQuery
+            symbol_index::CrateSymbolShardModulesQuery
             symbol_index::LibrarySymbolsQuery
+            symbol_index::TraitImplIndexQuery
             // symbol_index::LocalRootsQuery
             // symbol_index::LibraryRootsQuery
 
@@ -316,6 +335,74 @@ macro_rules! update_lru_capacity_per_query {
             // hir_db::InternMacroRulesQuery
         ];
     }
+
+    /// Evicts the function bodies and inference results, which make up the bulk of the memory
+    /// usage, of the given crates. They are computed again when they are needed.
+    ///
+    /// Returns the number of values evicted from each query.
+    pub fn evict_crate_bodies(
+        &mut self,
+        crates: &FxHashSet<CrateId>,
+    ) -> Vec<(&'static str, usize)> {
+        use base_db::salsa::debug::{DebugQueryTable, TableEntry};
+        use hir::db as hir_db;
+
+        let mut evicted = Vec::new();
+        macro_rules! evict_per_query {
+            ($( $module:ident :: $query:ident )*) => {$(
+                let keys: Vec<_> = $module::$query
+                    .in_db(self)
+                    .entries::<Vec<TableEntry<_, _>>>()
+                    .into_iter()
+                    .filter(|entry| entry.value.is_some() && crates.contains(&self.body_crate(entry.key)))
+                    .map(|entry| entry.key)
+                    .collect();
+                let mut table = $module::$query.in_db_mut(self);
+                let count = keys.iter().filter(|key| table.evict(key)).count();
+                evicted.push((stringify!($query), count));
+            )*}
+        }
+        evict_per_query![
+            hir_db::BodyWithSourceMapQuery
+            hir_db::BodyQuery
+            hir_db::ExprScopesQuery
+            hir_db::InferQueryQuery
+            hir_db::MirBodyQuery
+        ];
+        evicted
+    }
+
+    /// Returns the crates with function bodies or inference results in memory.
+    pub fn crates_with_bodies(&self) -> FxHashSet<CrateId> {
+        use base_db::salsa::debug::{DebugQueryTable, TableEntry};
+        use hir::db as hir_db;
+
+        let mut crates = FxHashSet::default();
+        macro_rules! collect_per_query {
+            ($( $module:ident :: $query:ident )*) => {$(
+                crates.extend(
+                    $module::$query
+                        .in_db(self)
+                        .entries::<Vec<TableEntry<_, _>>>()
+                        .into_iter()
+                        .filter(|entry| entry.value.is_some())
+                        .map(|entry| self.body_crate(entry.key)),
+                );
+            )*}
+        }
+        collect_per_query![
+            hir_db::BodyWithSourceMapQuery
+            hir_db::BodyQuery
+            hir_db::ExprScopesQuery
+            hir_db::InferQueryQuery
+            hir_db::MirBodyQuery
+        ];
+        crates
+    }
+
+    fn body_crate(&self, def: impl Into<hir::DefWithBody>) -> CrateId {
+        def.into().module(self).krate().into()
+    }
 }
 
 impl salsa::ParallelDatabase for RootDatabase {
@@ -414,6 +501,7 @@ pub const fn new(allow_snippets: bool) -> Option<SnippetCap> {
 
 #[cfg(test)]
 mod tests {
+    mod evict;
     mod line_index;
     mod sourcegen_lints;
 }
@@ -0,0 +1,454 @@
+//! Names the lifetimes elided in a function signature, see [`ElidedLifetimes`].
+
+use hir::{InputLifetime, LifetimePosition, Semantics};
+use syntax::{
+    ast::{self, edit_in_place::GenericParamsOwnerEdit, make, HasGenericParams, HasTypeBounds},
+    format_smolstr, ted, AstNode, AstPtr, SmolStr, T,
+};
+
+use crate::{FxHashMap, FxHashSet, RootDatabase};
+
+/// The lifetimes elided in the signature of a function, together with fresh names for them.
+///
+/// Which lifetimes are elided, and what the elided lifetimes of the return type stand for, comes
+/// from lowering the signature in hir-ty. Every elided lifetime of a parameter becomes a new
+/// lifetime parameter of the function.
+#[derive(Debug)]
+pub struct ElidedLifetimes {
+    /// The lifetime parameters the function needs to get, in order.
+    pub new_params: Vec<SmolStr>,
+    /// The lifetimes of the parameters, named or not, without duplicates.
+    pub input_lifetimes: Vec<SmolStr>,
+    /// The lifetime the elided lifetimes of the return type stand for, if it has any.
+    pub output: Option<SmolStr>,
+    sites: Vec<Site>,
+}
+
+#[derive(Debug)]
+enum Site {
+    /// A reference without a lifetime, `&T`.
+    Ref(AstPtr<ast::RefType>, SmolStr),
+    /// A reference `self` parameter without a lifetime, `&self`.
+    SelfRef(AstPtr<ast::SelfParam>, SmolStr),
+    /// The anonymous lifetime, `'_`.
+    Anonymous(AstPtr<ast::Lifetime>, SmolStr),
+    /// A path to a type whose lifetime arguments are all omitted, like `Foo` for `Foo<'a>`.
+    Path(AstPtr<ast::PathSegment>, Vec<SmolStr>),
+}
+
+/// The syntax of a lifetime position of the signature.
+enum SyntaxPosition {
+    Lifetime(ast::Lifetime),
+    Ref(AstPtr<ast::RefType>),
+    SelfRef(AstPtr<ast::SelfParam>),
+    Path(AstPtr<ast::PathSegment>),
+}
+
+impl ElidedLifetimes {
+    /// Finds the lifetimes elided in the signature of `func`.
+    ///
+    /// Returns `None` if nothing is elided, or if the return type elides a lifetime that can't
+    /// be inferred from the parameters.
+    pub fn new(sema: &Semantics<'_, RootDatabase>, func: &ast::Fn) -> Option<ElidedLifetimes> {
+        let lifetimes = sema.to_def(func)?.sig_lifetimes(sema.db);
+        let param_list = func.param_list()?;
+        let mut params = Vec::new();
+        if let Some(self_param) = param_list.self_param() {
+            let mut positions = Vec::new();
+            if self_param.amp_token().is_some() {
+                positions.push(match self_param.lifetime() {
+                    Some(lt) => SyntaxPosition::Lifetime(lt),
+                    None => SyntaxPosition::SelfRef(AstPtr::new(&self_param)),
+                });
+            }
+            if let Some(ty) = self_param.ty() {
+                type_positions(&ty, &mut positions);
+            }
+            params.push(positions);
+        }
+        for param in param_list.params().filter(|it| it.dotdotdot_token().is_none()) {
+            let mut positions = Vec::new();
+            if let Some(ty) = param.ty() {
+                type_positions(&ty, &mut positions);
+            }
+            params.push(positions);
+        }
+        // Parameters disabled by `#[cfg]` aren't lowered.
+        if params.len() != lifetimes.params.len() {
+            return None;
+        }
+
+        let mut res = ElidedLifetimes {
+            new_params: Vec::new(),
+            input_lifetimes: Vec::new(),
+            output: None,
+            sites: Vec::new(),
+        };
+        let mut names = FreshNames::new(func);
+        let mut elided_names = FxHashMap::default();
+        for (param, (positions, syntax)) in lifetimes.params.iter().zip(params).enumerate() {
+            if positions.len() != syntax.len() {
+                return None;
+            }
+            for (position, (lifetime, syntax)) in positions.iter().zip(syntax).enumerate() {
+                let lifetime_names = match lifetime {
+                    LifetimePosition::Named(name) => vec![name.to_smol_str()],
+                    _ if !lifetime.is_elided() => continue,
+                    _ => {
+                        let count = lifetime.lifetime_count();
+                        let fresh: Vec<_> = (0..count).map(|_| names.next()).collect();
+                        res.new_params.extend(fresh.iter().cloned());
+                        res.sites.extend(site(lifetime, syntax, &fresh));
+                        fresh
+                    }
+                };
+                if let Some(first) = lifetime_names.first() {
+                    elided_names.insert((param, position), first.clone());
+                }
+                for name in lifetime_names {
+                    if !res.input_lifetimes.contains(&name) {
+                        res.input_lifetimes.push(name);
+                    }
+                }
+            }
+        }
+
+        if let Some(ty) = func.ret_type().and_then(|it| it.ty()) {
+            let mut syntax = Vec::new();
+            type_positions(&ty, &mut syntax);
+            if syntax.len() != lifetimes.ret.len() {
+                return None;
+            }
+            let output = lifetimes.output.as_ref().and_then(|it| match it {
+                InputLifetime::Named(name) => Some(name.to_smol_str()),
+                &InputLifetime::Elided { param, position } => {
+                    elided_names.get(&(param, position)).cloned()
+                }
+            });
+            for (lifetime, syntax) in lifetimes.ret.iter().zip(syntax) {
+                if !lifetime.is_elided() {
+                    continue;
+                }
+                // The return type elides a lifetime the elision rules don't determine.
+                let output = output.clone()?;
+                res.sites.extend(site(
+                    lifetime,
+                    syntax,
+                    &vec![output.clone(); lifetime.lifetime_count()],
+                ));
+                res.output = Some(output);
+            }
+        }
+
+        if res.sites.is_empty() {
+            return None;
+        }
+        Some(res)
+    }
+
+    /// Names the elided lifetimes in `func`, which has to be a mutable version of the function
+    /// the lifetimes were found in.
+    pub fn apply(&self, func: &ast::Fn) {
+        let root = func.syntax().ancestors().last().unwrap_or_else(|| func.syntax().clone());
+        let lifetime = |name: &SmolStr| make::lifetime(name).clone_for_update();
+        // Resolve all sites before editing, as edits shift the ranges of the pointers.
+        let sites: Vec<_> = self
+            .sites
+            .iter()
+            .filter_map(|site| match site {
+                Site::Ref(ptr, name) => Some(Edit::AfterAmp(ptr.to_node(&root).amp_token()?, name)),
+                Site::SelfRef(ptr, name) => {
+                    Some(Edit::AfterAmp(ptr.to_node(&root).amp_token()?, name))
+                }
+                Site::Anonymous(ptr, name) => Some(Edit::Replace(ptr.to_node(&root), name)),
+                Site::Path(ptr, names) => Some(Edit::Args(ptr.to_node(&root), names)),
+            })
+            .collect();
+        for site in sites {
+            match site {
+                Edit::AfterAmp(amp, name) => ted::insert_all(
+                    ted::Position::after(amp),
+                    vec![
+                        lifetime(name).syntax().clone().into(),
+                        make::tokens::single_space().into(),
+                    ],
+                ),
+                Edit::Replace(old, name) => ted::replace(old.syntax(), lifetime(name).syntax()),
+                Edit::Args(segment, names) => {
+                    let args = names.iter().map(|it| make::lifetime_arg(lifetime(it)).into());
+                    match segment.generic_arg_list() {
+                        Some(arg_list) => {
+                            let Some(l_angle) = arg_list.l_angle_token() else { continue };
+                            let list = make::generic_arg_list(args).clone_for_update();
+                            let mut elements = Vec::new();
+                            for arg in list.generic_args() {
+                                elements.push(arg.syntax().clone().into());
+                                elements.push(make::token(T![,]).into());
+                                elements.push(make::tokens::single_space().into());
+                            }
+                            ted::insert_all(ted::Position::after(l_angle), elements);
+                        }
+                        None => {
+                            let Some(name_ref) = segment.name_ref() else { continue };
+                            let list = make::generic_arg_list(args).clone_for_update();
+                            ted::insert(ted::Position::after(name_ref.syntax()), list.syntax());
+                        }
+                    }
+                }
+            }
+        }
+
+        if self.new_params.is_empty() {
+            return;
+        }
+        let param_list = func.get_or_create_generic_param_list();
+        let first_non_lifetime = param_list
+            .generic_params()
+            .find(|it| !matches!(it, ast::GenericParam::LifetimeParam(_)));
+        let mut elements = Vec::new();
+        for name in &self.new_params {
+            let param = make::lifetime_param(lifetime(name)).clone_for_update();
+            if first_non_lifetime.is_some() {
+                elements.push(param.syntax().clone().into());
+                elements.push(make::token(T![,]).into());
+                elements.push(make::tokens::single_space().into());
+            } else {
+                param_list.add_generic_param(param.into());
+            }
+        }
+        if let Some(first_non_lifetime) = first_non_lifetime {
+            ted::insert_all(ted::Position::before(first_non_lifetime.syntax()), elements);
+        }
+    }
+}
+
+enum Edit<'a> {
+    AfterAmp(syntax::SyntaxToken, &'a SmolStr),
+    Replace(ast::Lifetime, &'a SmolStr),
+    Args(ast::PathSegment, &'a [SmolStr]),
+}
+
+/// The edit site for the elided `lifetime` at `syntax`, if the two agree.
+fn site(lifetime: &LifetimePosition, syntax: SyntaxPosition, names: &[SmolStr]) -> Option<Site> {
+    let first = || names.first().cloned().unwrap_or_default();
+    let site = match (lifetime, syntax) {
+        (LifetimePosition::Anonymous, SyntaxPosition::Lifetime(lt)) => {
+            Site::Anonymous(AstPtr::new(&lt), first())
+        }
+        (LifetimePosition::Ref, SyntaxPosition::Ref(ptr)) => Site::Ref(ptr, first()),
+        (LifetimePosition::Ref, SyntaxPosition::SelfRef(ptr)) => Site::SelfRef(ptr, first()),
+        (LifetimePosition::Path(_), SyntaxPosition::Path(ptr)) => Site::Path(ptr, names.to_vec()),
+        _ => return None,
+    };
+    Some(site)
+}
+
+/// Collects the lifetime positions of `ty` in the order hir-ty lowers them in.
+fn type_positions(ty: &ast::Type, acc: &mut Vec<SyntaxPosition>) {
+    match ty {
+        ast::Type::RefType(it) => {
+            acc.push(match it.lifetime() {
+                Some(lt) => SyntaxPosition::Lifetime(lt),
+                None => SyntaxPosition::Ref(AstPtr::new(it)),
+            });
+            if let Some(ty) = it.ty() {
+                type_positions(&ty, acc);
+            }
+        }
+        ast::Type::TupleType(it) => it.fields().for_each(|it| type_positions(&it, acc)),
+        ast::Type::ParenType(it) => it.ty().into_iter().for_each(|it| type_positions(&it, acc)),
+        ast::Type::PtrType(it) => it.ty().into_iter().for_each(|it| type_positions(&it, acc)),
+        ast::Type::ArrayType(it) => it.ty().into_iter().for_each(|it| type_positions(&it, acc)),
+        ast::Type::SliceType(it) => it.ty().into_iter().for_each(|it| type_positions(&it, acc)),
+        ast::Type::ForType(it) => it.ty().into_iter().for_each(|it| type_positions(&it, acc)),
+        ast::Type::PathType(it) => {
+            if let Some(path) = it.path() {
+                path_positions(&path, true, acc);
+            }
+        }
+        ast::Type::ImplTraitType(it) => bounds_positions(it.type_bound_list(), acc),
+        ast::Type::DynTraitType(it) => bounds_positions(it.type_bound_list(), acc),
+        ast::Type::FnPtrType(_)
+        | ast::Type::NeverType(_)
+        | ast::Type::InferType(_)
+        | ast::Type::MacroType(_) => {}
+    }
+}
+
+fn bounds_positions(bounds: Option<ast::TypeBoundList>, acc: &mut Vec<SyntaxPosition>) {
+    for bound in bounds.into_iter().flat_map(|it| it.bounds()) {
+        match bound.kind() {
+            ast::TypeBoundKind::PathType(it) => {
+                if let Some(path) = it.path() {
+                    path_positions(&path, false, acc);
+                }
+            }
+            ast::TypeBoundKind::ForType(it) => {
+                if let Some(ast::Type::PathType(it)) = it.ty() {
+                    if let Some(path) = it.path() {
+                        path_positions(&path, false, acc);
+                    }
+                }
+            }
+            ast::TypeBoundKind::Lifetime(lt) => acc.push(SyntaxPosition::Lifetime(lt)),
+        }
+    }
+}
+
+fn path_positions(path: &ast::Path, is_type: bool, acc: &mut Vec<SyntaxPosition>) {
+    let segments: Vec<_> = path.segments().collect();
+    let is_self_type = path.qualifier().is_none()
+        && path.segment().map_or(false, |it| it.self_type_token().is_some());
+    if is_self_type || segments.first().map_or(false, |it| it.ty().is_some()) {
+        return;
+    }
+    for (idx, segment) in segments.iter().enumerate() {
+        if segment.param_list().is_some() {
+            continue;
+        }
+        let args: Vec<_> =
+            segment.generic_arg_list().into_iter().flat_map(|it| it.generic_args()).collect();
+        let has_lifetime_args = args.iter().any(|it| matches!(it, ast::GenericArg::LifetimeArg(_)));
+        if is_type && idx == segments.len() - 1 && !has_lifetime_args {
+            acc.push(SyntaxPosition::Path(AstPtr::new(segment)));
+        }
+        let mut bindings = Vec::new();
+        for arg in args {
+            match arg {
+                ast::GenericArg::TypeArg(it) => {
+                    it.ty().into_iter().for_each(|it| type_positions(&it, acc))
+                }
+                ast::GenericArg::LifetimeArg(it) => {
+                    acc.extend(it.lifetime().map(SyntaxPosition::Lifetime))
+                }
+                ast::GenericArg::AssocTypeArg(it) if it.param_list().is_none() => bindings.push(it),
+                ast::GenericArg::AssocTypeArg(_) | ast::GenericArg::ConstArg(_) => {}
+            }
+        }
+        for binding in bindings {
+            if let Some(ty) = binding.ty() {
+                type_positions(&ty, acc);
+            }
+            bounds_positions(binding.type_bound_list(), acc);
+        }
+    }
+}
+
+/// Generates lifetime names that aren't in scope of the function yet.
+struct FreshNames {
+    used: FxHashSet<SmolStr>,
+    next: usize,
+}
+
+impl FreshNames {
+    fn new(func: &ast::Fn) -> FreshNames {
+        let used = func
+            .syntax()
+            .ancestors()
+            .filter_map(|it| {
+                let list = match_generic_param_list(&it)?;
+                Some(list.lifetime_params().filter_map(|it| it.lifetime()).collect::<Vec<_>>())
+            })
+            .flatten()
+            .map(|it| SmolStr::from(it.text().as_str()))
+            .collect();
+        FreshNames { used, next: 0 }
+    }
+
+    fn next(&mut self) -> SmolStr {
+        loop {
+            let idx = self.next;
+            self.next += 1;
+            let name = match idx {
+                0..=25 => format_smolstr!("'{}", (b'a' + idx as u8) as char),
+                _ => format_smolstr!("'l{}", idx - 26),
+            };
+            if self.used.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+fn match_generic_param_list(node: &syntax::SyntaxNode) -> Option<ast::GenericParamList> {
+    if let Some(it) = ast::Fn::cast(node.clone()) {
+        return it.generic_param_list();
+    }
+    if let Some(it) = ast::Impl::cast(node.clone()) {
+        return it.generic_param_list();
+    }
+    ast::Trait::cast(node.clone())?.generic_param_list()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use hir::Semantics;
+    use syntax::{ast, AstNode};
+    use test_fixture::WithFixture;
+
+    use crate::RootDatabase;
+
+    use super::ElidedLifetimes;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (db, file_id) = RootDatabase::with_single_file(ra_fixture);
+        let sema = Semantics::new(&db);
+        let file = sema.parse(file_id);
+        let mut actual = String::new();
+        for func in file.syntax().descendants().filter_map(ast::Fn::cast) {
+            let Some(elided) = ElidedLifetimes::new(&sema, &func) else {
+                actual += "-\n";
+                continue;
+            };
+            let func = func.clone_for_update();
+            elided.apply(&func);
+            actual += &func.syntax().to_string();
+            actual.push('\n');
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn names_elided_lifetimes() {
+        check(
+            r#"
+struct S<'a>(&'a ());
+type Alias<'a, T> = &'a T;
+fn none(x: u32) {}
+fn refs(x: &u32, y: &mut &u8) {}
+fn output(x: &u32) -> &u32 {}
+fn anonymous<T>(x: S<'_>, y: Alias<T>) {}
+fn path_output(x: S) -> S {}
+fn ambiguous(x: &u32, y: &u32) -> &u32 {}
+fn named<'a>(x: &'a u32, y: &u32) {}
+fn same_named<'a>(x: &'a u32, y: &'a u32) -> &u32 {}
+fn nested(x: Option<&(u8, S)>) -> Option<&S> {}
+async fn fetch(x: &u32) -> &u32 {}
+fn fn_ptrs(f: fn(&u32) -> &u32, g: impl Fn(&u8) -> &u8) {}
+fn impl_trait(x: impl Iterator<Item = &u8>) -> impl Iterator<Item = &u8> + '_ {}
+struct T;
+impl<'a> T {
+    fn method(&self, x: &u32) -> &u32 {}
+    fn typed_self(self: &Self) -> S {}
+}
+"#,
+            expect![[r#"
+                -
+                fn refs<'a, 'b, 'c>(x: &'a u32, y: &'b mut &'c u8) {}
+                fn output<'a>(x: &'a u32) -> &'a u32 {}
+                fn anonymous<'a, 'b, T>(x: S<'a>, y: Alias<'b, T>) {}
+                fn path_output<'a>(x: S<'a>) -> S<'a> {}
+                -
+                fn named<'a, 'b>(x: &'a u32, y: &'b u32) {}
+                fn same_named<'a>(x: &'a u32, y: &'a u32) -> &'a u32 {}
+                -
+                async fn fetch<'a>(x: &'a u32) -> &'a u32 {}
+                -
+                fn impl_trait<'a>(x: impl Iterator<Item = &'a u8>) -> impl Iterator<Item = &'a u8> + 'a {}
+                fn method<'b, 'c>(&'b self, x: &'c u32) -> &'b u32 {}
+                fn typed_self<'b>(self: &'b Self) -> S<'b> {}
+            "#]],
+        );
+    }
+}
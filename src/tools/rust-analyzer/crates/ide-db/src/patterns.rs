@@ -0,0 +1,200 @@
+//! Functionality for building patterns from hir definitions.
+
+use hir::{Mutability, StructKind};
+use syntax::ast::{self, make, HasName};
+
+use crate::RootDatabase;
+
+/// Builds a pattern matching every value of `variant`, referred to by `path`.
+///
+/// Record fields are bound by their own names, tuple fields are matched with `_`.
+pub fn variant_pat(db: &RootDatabase, path: ast::Path, variant: hir::Variant) -> ast::Pat {
+    let kind = variant.kind(db);
+    let fields = variant.fields(db).into_iter().enumerate().map(|(idx, field)| match kind {
+        StructKind::Record => {
+            let name = field.name(db).display(db).to_string();
+            let pat = make::ext::simple_ident_pat(make::name(&name)).into();
+            (name, Some(pat))
+        }
+        _ => (idx.to_string(), Some(make::wildcard_pat().into())),
+    });
+    struct_pat(path, kind, fields, false)
+}
+
+/// Builds a pattern for a struct or enum variant of the given `kind`, referred to by `path`, from
+/// the patterns of its fields in declaration order. The patterns may be struct patterns
+/// themselves, to destructure fields in the same pattern.
+///
+/// Fields are named by their name, or their index for tuple fields. A field without a pattern is
+/// left out of a record pattern and matched with `_` in a tuple pattern, or with `..` if only such
+/// fields follow it. With `has_rest`, the pattern ends in `..` either way, for fields that aren't
+/// listed.
+pub fn struct_pat(
+    path: ast::Path,
+    kind: StructKind,
+    fields: impl IntoIterator<Item = (String, Option<ast::Pat>)>,
+    mut has_rest: bool,
+) -> ast::Pat {
+    match kind {
+        StructKind::Record => {
+            let fields: Vec<_> = fields
+                .into_iter()
+                .filter_map(|(name, pat)| {
+                    has_rest |= pat.is_none();
+                    Some(record_field_pat(&name, pat?))
+                })
+                .collect();
+            let fields = make::record_pat_field_list(fields, has_rest.then(make::rest_pat));
+            make::record_pat_with_fields(path, fields).into()
+        }
+        StructKind::Tuple => {
+            let mut pats: Vec<_> = fields.into_iter().map(|(_, pat)| pat).collect();
+            while pats.last().map_or(false, Option::is_none) {
+                pats.pop();
+                has_rest = true;
+            }
+            let pats = pats
+                .into_iter()
+                .map(|it| it.unwrap_or_else(|| make::wildcard_pat().into()))
+                .chain(has_rest.then(|| make::rest_pat().into()));
+            make::tuple_struct_pat(path, pats).into()
+        }
+        StructKind::Unit => make::path_pat(path),
+    }
+}
+
+/// Builds the pattern of the record field `name`, in shorthand if `pat` only binds a variable of
+/// the same name.
+pub fn record_field_pat(name: &str, pat: ast::Pat) -> ast::RecordPatField {
+    match pat {
+        ast::Pat::IdentPat(it)
+            if it.pat().is_none() && it.name().map_or(false, |it| it.text() == name) =>
+        {
+            make::record_pat_field_shorthand_binding(it)
+        }
+        pat => make::record_pat_field(make::name_ref(name), pat),
+    }
+}
+
+/// Wraps `pat` in a reference pattern for every reference `ty` is behind, so that `(a, b)` matches
+/// a `&(A, B)` as `&(a, b)`.
+pub fn ref_pat_for(ty: &hir::Type, pat: ast::Pat) -> ast::Pat {
+    let mut mutabilities = Vec::new();
+    let mut ty = ty.clone();
+    while let Some((inner, mutability)) = ty.as_reference() {
+        mutabilities.push(mutability);
+        ty = inner;
+    }
+    mutabilities
+        .into_iter()
+        .rev()
+        .fold(pat, |pat, mutability| make::ref_pat(pat, mutability == Mutability::Mut).into())
+}
+
+/// Builds a tuple pattern binding each field to the corresponding name in `names`.
+pub fn tuple_pat_with_bindings(
+    names: impl IntoIterator<Item = ast::Name>,
+    ref_: bool,
+    mut_: bool,
+) -> ast::TuplePat {
+    make::tuple_pat(names.into_iter().map(|name| make::ident_pat(ref_, mut_, name).into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use super::*;
+
+    fn binding(name: &str, ref_: bool, mut_: bool) -> Option<ast::Pat> {
+        Some(make::ident_pat(ref_, mut_, make::name(name)).into())
+    }
+
+    fn check(pat: ast::Pat, expect: &str) {
+        assert_eq!(pat.to_string(), expect);
+    }
+
+    #[test]
+    fn record_pats() {
+        let path = || make::ext::ident_path("Foo");
+        let fields = |pats: Vec<Option<ast::Pat>>| {
+            ["a", "b", "c"].iter().map(|it| it.to_string()).zip(pats).collect::<Vec<_>>()
+        };
+        check(
+            struct_pat(
+                path(),
+                StructKind::Record,
+                fields(vec![binding("a", false, false), binding("x", false, false)]),
+                false,
+            ),
+            "Foo { a, b: x }",
+        );
+        check(
+            struct_pat(
+                path(),
+                StructKind::Record,
+                fields(vec![binding("a", true, true), None, binding("c", false, true)]),
+                false,
+            ),
+            "Foo { ref mut a, mut c, .. }",
+        );
+        check(struct_pat(path(), StructKind::Record, fields(vec![]), true), "Foo { .. }");
+    }
+
+    #[test]
+    fn tuple_pats() {
+        let path = || make::ext::ident_path("Foo");
+        let fields = |pats: Vec<Option<ast::Pat>>| {
+            pats.into_iter()
+                .enumerate()
+                .map(|(idx, pat)| (idx.to_string(), pat))
+                .collect::<Vec<_>>()
+        };
+        check(
+            struct_pat(
+                path(),
+                StructKind::Tuple,
+                fields(vec![None, binding("_1", false, false), None, None]),
+                false,
+            ),
+            "Foo(_, _1, ..)",
+        );
+        check(
+            struct_pat(path(), StructKind::Tuple, fields(vec![binding("_0", true, false)]), true),
+            "Foo(ref _0, ..)",
+        );
+    }
+
+    #[test]
+    fn nested_pats() {
+        let inner = struct_pat(
+            make::ext::ident_path("Inner"),
+            StructKind::Record,
+            iter::once(("a".to_owned(), binding("a", false, false))),
+            true,
+        );
+        let variant = struct_pat(
+            make::path_from_text("E::V"),
+            StructKind::Tuple,
+            [("0".to_owned(), Some(inner)), ("1".to_owned(), None)],
+            false,
+        );
+        check(
+            struct_pat(
+                make::ext::ident_path("Outer"),
+                StructKind::Record,
+                iter::once(("e".to_owned(), Some(variant))),
+                false,
+            ),
+            "Outer { e: E::V(Inner { a, .. }, ..) }",
+        );
+    }
+
+    #[test]
+    fn ref_pats() {
+        let pat = || make::tuple_pat([make::wildcard_pat().into()]).into();
+        check(make::ref_pat(pat(), false).into(), "&(_,)");
+        check(make::ref_pat(pat(), true).into(), "&mut (_,)");
+        check(make::ref_pat(binding("x", false, true).unwrap(), false).into(), "&(mut x)");
+    }
+}
@@ -0,0 +1,178 @@
+//! Collects how often each salsa query is executed, and how much time and memory these executions
+//! take, for profiling rust-analyzer on a whole project.
+//!
+//! Collection is process wide and off by default, see [`start`] and [`finish`]. The language server
+//! can keep it running and show what was collected so far, see [`current`] and [`reset`].
+
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use base_db::salsa::{self, DatabaseKeyIndex, EventKind};
+use profile::{memory_usage, Bytes};
+
+use crate::{FxHashMap, RootDatabase};
+
+/// Execution statistics of a single query, summed over all of its executions.
+#[derive(Debug, Clone)]
+pub struct QueryStat {
+    pub name: String,
+    pub executions: u64,
+    /// Time spent executing the query, including the queries it executed in turn.
+    pub total_time: Duration,
+    /// Time spent executing the query itself, excluding the queries it executed in turn.
+    pub self_time: Duration,
+    /// Memory allocated by the query itself and not freed by the time it finished.
+    pub self_memory: Bytes,
+}
+
+#[derive(Debug, Default)]
+pub struct QueryStats {
+    /// Sorted by descending self time.
+    pub queries: Vec<QueryStat>,
+    /// The self time of each stack of queries, outermost query first, in the "folded stacks"
+    /// format flamegraph tools consume: `outer;inner <microseconds>`.
+    pub folded_stacks: String,
+}
+
+type QueryId = (u16, u16);
+
+#[derive(Default)]
+struct Collected {
+    queries: FxHashMap<QueryId, QueryStat>,
+    stacks: FxHashMap<Vec<QueryId>, Duration>,
+}
+
+struct Frame {
+    key: DatabaseKeyIndex,
+    start: Instant,
+    start_memory: Bytes,
+    children_time: Duration,
+    children_memory: Bytes,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static COLLECTED: Mutex<Option<Collected>> = Mutex::new(None);
+
+thread_local! {
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Starts collecting statistics for all query executions, discarding previously collected ones.
+pub fn start() {
+    *COLLECTED.lock().unwrap() = Some(Collected::default());
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Stops collecting statistics and returns the ones collected since [`start`] or [`reset`].
+pub fn finish() -> QueryStats {
+    ENABLED.store(false, Ordering::SeqCst);
+    COLLECTED.lock().unwrap().take().map_or_else(QueryStats::default, |it| to_stats(&it))
+}
+
+/// Returns the statistics collected since [`start`] or [`reset`], without stopping the collection.
+///
+/// Returns `None` if statistics aren't being collected.
+pub fn current() -> Option<QueryStats> {
+    COLLECTED.lock().unwrap().as_ref().map(to_stats)
+}
+
+/// Discards the statistics collected so far, if statistics are being collected.
+pub fn reset() {
+    if let Some(collected) = COLLECTED.lock().unwrap().as_mut() {
+        *collected = Collected::default();
+    }
+}
+
+fn to_stats(collected: &Collected) -> QueryStats {
+    let mut folded_stacks = String::new();
+    let mut stacks: Vec<_> = collected
+        .stacks
+        .iter()
+        .map(|(stack, time)| {
+            // queries that unwound have no name recorded
+            let names: Vec<&str> = stack
+                .iter()
+                .map(|id| collected.queries.get(id).map_or("?", |query| query.name.as_str()))
+                .collect();
+            (names.join(";"), time.as_micros())
+        })
+        .collect();
+    stacks.sort();
+    for (stack, micros) in stacks {
+        folded_stacks.push_str(&format!("{stack} {micros}\n"));
+    }
+
+    let mut queries: Vec<_> = collected.queries.values().cloned().collect();
+    queries.sort_by(|a, b| b.self_time.cmp(&a.self_time).then_with(|| a.name.cmp(&b.name)));
+    QueryStats { queries, folded_stacks }
+}
+
+pub(crate) fn record(db: &RootDatabase, event: &salsa::Event) {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    match event.kind {
+        EventKind::WillExecute { database_key } => STACK.with_borrow_mut(|stack| {
+            stack.push(Frame {
+                key: database_key,
+                start: Instant::now(),
+                start_memory: memory_usage().allocated,
+                children_time: Duration::ZERO,
+                children_memory: Bytes::default(),
+            })
+        }),
+        EventKind::DidExecute { database_key } => STACK.with_borrow_mut(|stack| {
+            // frames of queries that unwound are never finished, skip them
+            let Some(idx) = stack.iter().rposition(|frame| frame.key == database_key) else {
+                return;
+            };
+            stack.truncate(idx + 1);
+            let frame = stack.pop().unwrap();
+            let time = frame.start.elapsed();
+            let memory = memory_usage().allocated - frame.start_memory;
+            if let Some(parent) = stack.last_mut() {
+                parent.children_time += time;
+                parent.children_memory += memory;
+            }
+
+            let id = query_id(database_key);
+            let mut collected = COLLECTED.lock().unwrap();
+            let Some(collected) = collected.as_mut() else { return };
+            let stat = collected.queries.entry(id).or_insert_with(|| QueryStat {
+                name: query_name(db, database_key),
+                executions: 0,
+                total_time: Duration::ZERO,
+                self_time: Duration::ZERO,
+                self_memory: Bytes::default(),
+            });
+            let self_time = time.saturating_sub(frame.children_time);
+            stat.executions += 1;
+            stat.total_time += time;
+            stat.self_time += self_time;
+            stat.self_memory += memory - frame.children_memory;
+
+            let path = stack.iter().map(|frame| query_id(frame.key)).chain(Some(id)).collect();
+            *collected.stacks.entry(path).or_default() += self_time;
+        }),
+        _ => (),
+    }
+}
+
+fn query_id(key: DatabaseKeyIndex) -> QueryId {
+    (key.group_index(), key.query_index())
+}
+
+/// The name of the query, without the key it was executed for.
+fn query_name(db: &RootDatabase, key: DatabaseKeyIndex) -> String {
+    let mut name = format!("{:?}", key.debug(db));
+    if let Some(idx) = name.find('(') {
+        name.truncate(idx);
+    }
+    name
+}
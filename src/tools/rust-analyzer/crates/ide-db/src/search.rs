@@ -206,12 +206,14 @@ pub fn module_and_children(db: &RootDatabase, module: hir::Module) -> SearchScop
             }
         };
         entries.insert(file_id, range);
+        entries.extend(module.included_files(db).into_iter().map(|file_id| (file_id, None)));
 
         let mut to_visit: Vec<_> = module.children(db).collect();
         while let Some(module) = to_visit.pop() {
             if let Some(file_id) = module.as_source_file_id(db) {
                 entries.insert(file_id, None);
             }
+            entries.extend(module.included_files(db).into_iter().map(|file_id| (file_id, None)));
             to_visit.extend(module.children(db));
         }
         SearchScope { entries }
@@ -372,10 +374,11 @@ fn search_scope(&self, db: &RootDatabase) -> SearchScope {
             ModuleSource::BlockExpr(b) => Some(b.syntax().text_range()),
             ModuleSource::SourceFile(_) => None,
         };
-        match range {
-            Some(range) => SearchScope::file_range(FileRange { file_id, range }),
-            None => SearchScope::single_file(file_id),
-        }
+        let mut entries = IntMap::default();
+        entries.insert(file_id, range);
+        // Items of the module can also be referenced from the files `include!`d into it.
+        entries.extend(module.included_files(db).into_iter().map(|file_id| (file_id, None)));
+        SearchScope::new(entries)
     }
 
     pub fn usages<'a>(self, sema: &'a Semantics<'_, RootDatabase>) -> FindUsages<'a> {
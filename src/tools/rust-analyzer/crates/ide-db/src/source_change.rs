@@ -5,15 +5,22 @@
 
 use std::{collections::hash_map::Entry, iter, mem};
 
-use crate::SnippetCap;
-use base_db::{AnchoredPathBuf, FileId};
+use crate::{RootDatabase, SnippetCap};
+use base_db::{AnchoredPathBuf, FileId, FileRange, SourceDatabaseExt};
+use hir::{db::ExpandDatabase, HirFileId, HirFileIdExt, InFile};
 use itertools::Itertools;
 use nohash_hasher::IntMap;
 use stdx::never;
 use syntax::{
-    algo, AstNode, SyntaxElement, SyntaxNode, SyntaxNodePtr, SyntaxToken, TextRange, TextSize,
+    algo, ast, AstNode, Direction, SyntaxElement, SyntaxNode, SyntaxNodePtr, SyntaxToken,
+    TextRange, TextSize, TokenAtOffset,
 };
 use text_edit::{TextEdit, TextEditBuilder};
+use triomphe::Arc;
+
+mod transaction;
+
+pub use self::transaction::{SourceChangeConflict, SourceChangeTransaction};
 
 #[derive(Default, Debug, Clone)]
 pub struct SourceChange {
@@ -129,6 +136,160 @@ fn from_iter<T: IntoIterator<Item = (FileId, TextEdit)>>(iter: T) -> Self {
     }
 }
 
+/// Maps `edit`, an edit of the syntax tree of `file_id`, to the file the edited tokens were written
+/// in, which makes it possible to edit the input of macro calls through their expansion.
+///
+/// Expansions lose the formatting of the macro input, so instead of replacing the input with the
+/// edited expansion, every deletion is mapped onto the user-written tokens it covers and every
+/// insertion is placed next to the token it is attached to. This keeps the formatting of
+/// everything the edit doesn't touch. See [`attaches_to_next_token`] for how insertions are
+/// placed.
+///
+/// Returns `None` if some part of the edit covers tokens that aren't part of the input of the
+/// macro call, like the ones coming from the macro definition, or tokens that are not contiguous in
+/// the input.
+pub fn map_edit_to_original_file(
+    db: &RootDatabase,
+    file_id: HirFileId,
+    edit: &TextEdit,
+) -> Option<(FileId, TextEdit)> {
+    if !file_id.is_macro() {
+        return Some((file_id.original_file(db), edit.clone()));
+    }
+    let call = file_id.original_call_node(db)?;
+    let input_range = match ast::MacroCall::cast(call.value.clone()) {
+        Some(call) => call.token_tree()?.syntax().text_range(),
+        // Attributes and derives get the item they are applied to as input.
+        None => call.value.text_range(),
+    };
+    let mapper = ExpansionEditMapper {
+        db,
+        file_id,
+        root: db.parse_or_expand(file_id),
+        input: FileRange { file_id: call.file_id, range: input_range },
+    };
+
+    let mut indels = Vec::new();
+    for indel in edit.iter() {
+        let text = mapper.root.text().slice(indel.delete).to_string();
+        let trimmed = text.trim();
+        if trimmed.is_empty() {
+            // Deleted whitespace has no counterpart in the macro input, so only insert the text.
+            if !indel.insert.is_empty() {
+                indels.push(mapper.map_insertion(indel.delete.start(), &indel.insert)?);
+            }
+        } else {
+            let start =
+                indel.delete.start() + TextSize::of(&text[..text.len() - text.trim_start().len()]);
+            let range = mapper.map_range(TextRange::at(start, TextSize::of(trimmed)))?;
+            indels.push((range, indel.insert.clone()));
+        }
+    }
+
+    indels.sort_by_key(|(range, _)| (range.start(), range.end()));
+    if indels.iter().tuple_windows().any(|((l, _), (r, _))| l.end() > r.start()) {
+        return None;
+    }
+    let mut builder = TextEdit::builder();
+    for (range, insert) in indels {
+        builder.replace(range, insert);
+    }
+    Some((call.file_id, builder.finish()))
+}
+
+/// Returns whether text inserted between two tokens belongs in front of the token after it,
+/// rather than behind the token before it.
+///
+/// Text that starts with whitespace or with punctuation that ends a list element or a delimited
+/// group, like `, b: 0`, continues the token before it. Other text that ends with whitespace, like
+/// `unsafe { `, leads into the token after it.
+fn attaches_to_next_token(insert: &str) -> bool {
+    let continues_previous =
+        insert.starts_with(|c: char| c.is_whitespace() || matches!(c, ',' | ';' | ')' | ']' | '}'));
+    !continues_previous && insert.ends_with(char::is_whitespace)
+}
+
+struct ExpansionEditMapper<'a> {
+    db: &'a RootDatabase,
+    file_id: HirFileId,
+    root: SyntaxNode,
+    /// The token tree of the outermost macro call the expansion comes from.
+    input: FileRange,
+}
+
+impl ExpansionEditMapper<'_> {
+    /// Maps `range` of the expansion into the macro input, if it covers tokens that are contiguous
+    /// there.
+    fn map_range(&self, range: TextRange) -> Option<TextRange> {
+        let (original, _) =
+            InFile::new(self.file_id, range).original_node_file_range_opt(self.db)?;
+        if original.file_id != self.input.file_id
+            || !self.input.range.contains_range(original.range)
+        {
+            return None;
+        }
+        // The mapped range spans from the first to the last mapped token, so make sure nothing
+        // else ended up in between, like tokens the macro reordered.
+        let expanded = self.root.text().slice(range).to_string();
+        let written = &self.input_text()[original.range];
+        let without_whitespace = |text: &str| text.split_whitespace().collect::<String>();
+        (without_whitespace(&expanded) == without_whitespace(written)).then_some(original.range)
+    }
+
+    /// Maps text inserted at `offset` of the expansion into the macro input by attaching it to the
+    /// token before or after it.
+    ///
+    /// The whitespace of the macro input is kept, so whitespace the inserted text has on the side
+    /// facing the token it is attached to is dropped if the input already has some there.
+    fn map_insertion(&self, offset: TextSize, insert: &str) -> Option<(TextRange, String)> {
+        let (before, after) = match self.root.token_at_offset(offset) {
+            TokenAtOffset::None => return None,
+            TokenAtOffset::Single(token) if token.text_range().start() == offset => {
+                (None, Some(token))
+            }
+            TokenAtOffset::Single(token) if token.text_range().end() == offset => {
+                (Some(token), None)
+            }
+            // Inserting in the middle of a token can't be expressed in terms of the macro input.
+            TokenAtOffset::Single(token) if !token.kind().is_trivia() => return None,
+            TokenAtOffset::Single(token) => (Some(token.clone()), Some(token)),
+            TokenAtOffset::Between(before, after) => (Some(before), Some(after)),
+        };
+        let non_trivia = |token: Option<SyntaxToken>, direction| {
+            token?
+                .siblings_with_tokens(direction)
+                .filter_map(|it| it.into_token())
+                .find(|it| !it.kind().is_trivia())
+        };
+        let before = non_trivia(before, Direction::Prev);
+        let after = non_trivia(after, Direction::Next);
+
+        let text = self.input_text();
+        let behind_before = || {
+            let offset = self.map_range(before.as_ref()?.text_range())?.end();
+            let followed_by_whitespace =
+                text[usize::from(offset)..].starts_with(char::is_whitespace);
+            let insert = if followed_by_whitespace { insert.trim_end() } else { insert };
+            Some((TextRange::empty(offset), insert.to_owned()))
+        };
+        let in_front_of_after = || {
+            let offset = self.map_range(after.as_ref()?.text_range())?.start();
+            let preceded_by_whitespace = text[..usize::from(offset)].ends_with(char::is_whitespace);
+            let insert = if preceded_by_whitespace { insert.trim_start() } else { insert };
+            Some((TextRange::empty(offset), insert.to_owned()))
+        };
+        if attaches_to_next_token(insert) {
+            in_front_of_after().or_else(behind_before)
+        } else {
+            behind_before().or_else(in_front_of_after)
+        }
+    }
+
+    fn input_text(&self) -> Arc<str> {
+        self.db.file_text(self.input.file_id)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SnippetEdit(Vec<(u32, TextRange)>);
 
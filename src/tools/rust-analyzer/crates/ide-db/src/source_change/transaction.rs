@@ -0,0 +1,339 @@
+//! Combining many [`SourceChange`]s into one, e.g. to apply every fix in a file at once.
+
+use base_db::{AnchoredPathBuf, FileId, SourceDatabaseExt};
+use line_index::{LineCol, LineIndex};
+use stdx::format_to;
+use syntax::{TextRange, TextSize};
+use text_edit::TextEdit;
+
+use crate::{
+    source_change::{FileSystemEdit, SourceChange},
+    RootDatabase,
+};
+
+/// Why a [`SourceChange`] could not be added to a [`SourceChangeTransaction`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceChangeConflict {
+    /// The change edits text in this file that an earlier change already edits.
+    OverlappingEdits(FileId),
+    /// The change creates or moves something to a path an earlier change already uses.
+    SameDestination(AnchoredPathBuf),
+    /// The change moves a file or directory that an earlier change already moves.
+    MovedTwice(FileId),
+}
+
+/// Builds a single [`SourceChange`] out of changes computed independently of each other.
+///
+/// Every change is either added as a whole or rejected with the conflict it would cause, so the
+/// result never contains half of a change. Snippets are dropped, as they only make sense for a
+/// single change the user is interacting with.
+#[derive(Debug, Default)]
+pub struct SourceChangeTransaction {
+    change: SourceChange,
+}
+
+impl SourceChangeTransaction {
+    pub fn new() -> SourceChangeTransaction {
+        SourceChangeTransaction::default()
+    }
+
+    /// Adds `change`, or leaves the transaction untouched if it conflicts with a change that was
+    /// added before.
+    pub fn add(&mut self, change: SourceChange) -> Result<(), SourceChangeConflict> {
+        self.check(&change)?;
+        for (file_id, (edit, _)) in change.source_file_edits {
+            self.change.insert_source_edit(file_id, edit);
+        }
+        self.change.file_system_edits.extend(change.file_system_edits);
+        Ok(())
+    }
+
+    /// Adds all of `changes` that don't conflict with each other, in order, and returns the
+    /// conflicts of the ones that were skipped.
+    pub fn add_all(
+        &mut self,
+        changes: impl IntoIterator<Item = SourceChange>,
+    ) -> Vec<SourceChangeConflict> {
+        changes.into_iter().filter_map(|change| self.add(change).err()).collect()
+    }
+
+    fn check(&self, change: &SourceChange) -> Result<(), SourceChangeConflict> {
+        for (&file_id, (edit, _)) in &change.source_file_edits {
+            if let Some((existing, _)) = self.change.source_file_edits.get(&file_id) {
+                if existing.clone().union(edit.clone()).is_err() {
+                    return Err(SourceChangeConflict::OverlappingEdits(file_id));
+                }
+            }
+        }
+        let existing = &self.change.file_system_edits;
+        for (idx, edit) in change.file_system_edits.iter().enumerate() {
+            // Also check against the earlier edits of the same change.
+            let earlier = existing.iter().chain(&change.file_system_edits[..idx]);
+            for other in earlier {
+                if let (Some(dst), Some(other_dst)) = (destination(edit), destination(other)) {
+                    if dst == other_dst {
+                        return Err(SourceChangeConflict::SameDestination(dst.clone()));
+                    }
+                }
+                if let (Some(src), Some(other_src)) = (moved_file(edit), moved_file(other)) {
+                    if src == other_src {
+                        return Err(SourceChangeConflict::MovedTwice(src));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the combined change.
+    ///
+    /// File system edits are ordered so that files are created before anything is moved, and
+    /// files are moved before the directories that might contain them, as all paths refer to the
+    /// file system as it was before the change.
+    pub fn finish(self) -> SourceChange {
+        let mut change = self.change;
+        change.file_system_edits.sort_by_key(|edit| match edit {
+            FileSystemEdit::CreateFile { .. } => 0,
+            FileSystemEdit::MoveFile { .. } => 1,
+            FileSystemEdit::MoveDir { .. } => 2,
+        });
+        change
+    }
+
+    /// Renders the combined change as a unified diff without context lines.
+    pub fn preview(&self, db: &RootDatabase) -> String {
+        let mut buf = String::new();
+        let mut edits = self.change.source_file_edits.iter().collect::<Vec<_>>();
+        edits.sort_by_key(|&(&file_id, _)| (file_path(db, file_id), file_id));
+        for (&file_id, (edit, _)) in edits {
+            let path = file_path(db, file_id);
+            format_to!(buf, "--- a{path}\n+++ b{path}\n");
+            text_diff(&mut buf, &db.file_text(file_id), edit);
+        }
+        for edit in &self.change.file_system_edits {
+            match edit {
+                FileSystemEdit::CreateFile { dst, initial_contents } => {
+                    let lines = initial_contents.lines().count();
+                    format_to!(buf, "--- /dev/null\n+++ b{}\n", anchored_path(db, dst));
+                    format_to!(buf, "@@ -0,0 +1,{lines} @@\n");
+                    for line in initial_contents.lines() {
+                        format_to!(buf, "+{line}\n");
+                    }
+                }
+                FileSystemEdit::MoveFile { src, dst } => {
+                    format_to!(buf, "rename from {}\n", file_path(db, *src));
+                    format_to!(buf, "rename to {}\n", anchored_path(db, dst));
+                }
+                FileSystemEdit::MoveDir { src, dst, .. } => {
+                    format_to!(buf, "rename from {}\n", anchored_path(db, src));
+                    format_to!(buf, "rename to {}\n", anchored_path(db, dst));
+                }
+            }
+        }
+        buf
+    }
+}
+
+fn destination(edit: &FileSystemEdit) -> Option<&AnchoredPathBuf> {
+    match edit {
+        FileSystemEdit::CreateFile { dst, .. }
+        | FileSystemEdit::MoveFile { dst, .. }
+        | FileSystemEdit::MoveDir { dst, .. } => Some(dst),
+    }
+}
+
+fn moved_file(edit: &FileSystemEdit) -> Option<FileId> {
+    match edit {
+        FileSystemEdit::CreateFile { .. } => None,
+        FileSystemEdit::MoveFile { src, .. } => Some(*src),
+        FileSystemEdit::MoveDir { src_id, .. } => Some(*src_id),
+    }
+}
+
+fn file_path(db: &RootDatabase, file_id: FileId) -> String {
+    let source_root = db.source_root(db.file_source_root(file_id));
+    match source_root.path_for_file(&file_id) {
+        Some(path) => path.to_string(),
+        None => format!("{file_id:?}"),
+    }
+}
+
+fn anchored_path(db: &RootDatabase, path: &AnchoredPathBuf) -> String {
+    let source_root = db.source_root(db.file_source_root(path.anchor));
+    let resolved = source_root
+        .path_for_file(&path.anchor)
+        .and_then(|anchor| anchor.parent())
+        .and_then(|dir| dir.join(&path.path));
+    match resolved {
+        Some(resolved) => resolved.to_string(),
+        None => path.path.clone(),
+    }
+}
+
+/// Writes one hunk per group of edited lines.
+fn text_diff(buf: &mut String, text: &str, edit: &TextEdit) {
+    let line_index = LineIndex::new(text);
+    let len = TextSize::of(text);
+    let line_start = |line: u32| line_index.offset(LineCol { line, col: 0 }).unwrap_or(len);
+
+    // Lines of the old text each indel touches, merged when they share a line.
+    let mut hunks: Vec<(u32, u32, Vec<_>)> = Vec::new();
+    for indel in edit.iter() {
+        let first = line_index.line_col(indel.delete.start()).line;
+        let end = line_index.line_col(indel.delete.end());
+        // A deletion up to the start of a line leaves that line alone.
+        let last = if end.col == 0 && !indel.delete.is_empty() { end.line } else { end.line + 1 };
+        match hunks.last_mut() {
+            Some((_, end, indels)) if first < *end => {
+                *end = (*end).max(last);
+                indels.push(indel);
+            }
+            _ => hunks.push((first, last, vec![indel])),
+        }
+    }
+
+    let mut line_delta = 0i64;
+    for (first, end, indels) in hunks {
+        let range = TextRange::new(line_start(first), line_start(end));
+        let old = &text[range];
+        let mut new = old.to_owned();
+        for indel in indels.iter().rev() {
+            let delete = indel.delete - range.start();
+            new.replace_range(std::ops::Range::<usize>::from(delete), &indel.insert);
+        }
+        let old_lines = old.lines().count() as i64;
+        let new_lines = new.lines().count() as i64;
+        let new_first = first as i64 + line_delta;
+        format_to!(
+            buf,
+            "@@ -{} +{} @@\n",
+            hunk_range(first as i64, old_lines),
+            hunk_range(new_first, new_lines)
+        );
+        for line in old.lines() {
+            format_to!(buf, "-{line}\n");
+        }
+        for line in new.lines() {
+            format_to!(buf, "+{line}\n");
+        }
+        line_delta += new_lines - old_lines;
+    }
+}
+
+/// Formats a zero-based line range the way unified diffs do.
+fn hunk_range(first: i64, len: i64) -> String {
+    match len {
+        // An empty range refers to the line before it.
+        0 => format!("{first},0"),
+        1 => format!("{}", first + 1),
+        _ => format!("{},{len}", first + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use test_fixture::WithFixture;
+
+    use super::*;
+
+    fn change(file_id: FileId, range: (u32, u32), insert: &str) -> SourceChange {
+        let range = TextRange::new(range.0.into(), range.1.into());
+        SourceChange::from_text_edit(file_id, TextEdit::replace(range, insert.to_owned()))
+    }
+
+    fn check_preview(
+        ra_fixture: &str,
+        changes: impl FnOnce(FileId) -> Vec<SourceChange>,
+        expect: Expect,
+    ) {
+        let (db, file_id) = RootDatabase::with_single_file(ra_fixture);
+        let mut tx = SourceChangeTransaction::new();
+        let conflicts = tx.add_all(changes(file_id));
+        assert!(conflicts.is_empty(), "{conflicts:?}");
+        expect.assert_eq(&tx.preview(&db));
+    }
+
+    #[test]
+    fn rejects_overlapping_edits() {
+        let file_id = FileId::from_raw(0);
+        let mut tx = SourceChangeTransaction::new();
+        assert_eq!(tx.add(change(file_id, (0, 4), "a")), Ok(()));
+        assert_eq!(
+            tx.add(change(file_id, (2, 6), "b")),
+            Err(SourceChangeConflict::OverlappingEdits(file_id))
+        );
+        assert_eq!(tx.add(change(file_id, (4, 6), "c")), Ok(()));
+        let (edit, _) = tx.finish().source_file_edits.remove(&file_id).unwrap();
+        assert_eq!(edit.len(), 2);
+    }
+
+    #[test]
+    fn rejects_moving_a_file_twice() {
+        let file_id = FileId::from_raw(0);
+        let dst = |path: &str| AnchoredPathBuf { anchor: file_id, path: path.to_owned() };
+        let mut tx = SourceChangeTransaction::new();
+        let conflicts = tx.add_all([
+            FileSystemEdit::MoveFile { src: file_id, dst: dst("a.rs") }.into(),
+            FileSystemEdit::CreateFile { dst: dst("a.rs"), initial_contents: String::new() }.into(),
+            FileSystemEdit::MoveFile { src: file_id, dst: dst("b.rs") }.into(),
+        ]);
+        assert_eq!(
+            conflicts,
+            [
+                SourceChangeConflict::SameDestination(dst("a.rs")),
+                SourceChangeConflict::MovedTwice(file_id),
+            ]
+        );
+    }
+
+    #[test]
+    fn creates_files_before_moving() {
+        let file_id = FileId::from_raw(0);
+        let dst = |path: &str| AnchoredPathBuf { anchor: file_id, path: path.to_owned() };
+        let mut tx = SourceChangeTransaction::new();
+        tx.add_all([
+            FileSystemEdit::MoveDir { src: dst("foo"), src_id: file_id, dst: dst("bar") }.into(),
+            FileSystemEdit::CreateFile { dst: dst("foo/a.rs"), initial_contents: String::new() }
+                .into(),
+        ]);
+        let edits = tx.finish().file_system_edits;
+        assert!(matches!(
+            edits.as_slice(),
+            [FileSystemEdit::CreateFile { .. }, FileSystemEdit::MoveDir { .. }]
+        ));
+    }
+
+    #[test]
+    fn preview_edits() {
+        check_preview(
+            r#"
+fn main() {
+    foo();
+    bar();
+}
+"#,
+            |file_id| {
+                vec![
+                    change(file_id, (16, 19), "baz"),
+                    change(file_id, (0, 0), "use foo::baz;\n\n"),
+                    change(file_id, (23, 34), ""),
+                ]
+            },
+            expect![[r#"
+                --- a/main.rs
+                +++ b/main.rs
+                @@ -1 +1,3 @@
+                -fn main() {
+                +use foo::baz;
+                +
+                +fn main() {
+                @@ -2 +4 @@
+                -    foo();
+                +    baz();
+                @@ -3 +4,0 @@
+                -    bar();
+            "#]],
+        );
+    }
+}
@@ -16,9 +16,17 @@
 //!
 //! `fst` does not support cheap updating of the index, but it supports unioning
 //! of state machines. So, to account for changing source code, we build an FST
-//! for each library (which is assumed to never change) and an FST for each Rust
-//! file in the current workspace, and run a query against the union of all
-//! those FSTs.
+//! for each library (which is assumed to never change) and an FST for each
+//! module in the current workspace, so that edits only rebuild the FSTs of the
+//! modules they change.
+//!
+//! Querying the union of the FSTs of thousands of modules is slow, so the
+//! modules of each crate are grouped into shards of up to `MODULES_PER_SHARD`
+//! modules, whose FSTs are merged into one. A merged FST refers to the symbols
+//! of its modules rather than copying them. Shards are merged when their crate
+//! is first queried, the crates being indexed in parallel, and an edit merges
+//! again only the shard of the module it changed. Queries run against the
+//! union of the shard FSTs.
 
 use std::{
     cmp::Ordering,
@@ -36,13 +44,13 @@
     db::HirDatabase,
     import_map::{AssocSearchMode, SearchMode},
     symbols::{FileSymbol, SymbolCollector},
-    Crate, Module,
+    Crate, Module, Trait,
 };
 use rayon::prelude::*;
 use rustc_hash::FxHashSet;
 use triomphe::Arc;
 
-use crate::RootDatabase;
+use crate::{impl_search::TraitImplIndex, RootDatabase};
 
 #[derive(Debug, Clone)]
 pub struct Query {
@@ -112,6 +120,18 @@ pub trait SymbolsDatabase: HirDatabase + SourceDatabaseExt + Upcast<dyn HirDatab
     /// The symbol indices of modules that make up a given crate.
     fn crate_symbols(&self, krate: Crate) -> Box<[Arc<SymbolIndex>]>;
 
+    /// The `shard`th group of `MODULES_PER_SHARD` modules of a given crate. Shards depend on this
+    /// rather than the def map, so that they are only merged again if their own modules changed.
+    fn crate_symbol_shard_modules(&self, krate: Crate, shard: usize) -> Box<[Module]>;
+
+    /// The symbol indices of the `shard`th group of `MODULES_PER_SHARD` modules of a given crate,
+    /// merged into one. These crates should only be in source roots that are inside local_roots.
+    fn crate_symbol_shard(&self, krate: Crate, shard: usize) -> Arc<SymbolIndex>;
+
+    #[salsa::transparent]
+    /// The merged symbol indices of all modules of a given crate.
+    fn crate_symbol_shards(&self, krate: Crate) -> Box<[Arc<SymbolIndex>]>;
+
     /// The set of "local" (that is, from the current workspace) roots.
     /// Files in local roots are assumed to change frequently.
     #[salsa::input]
@@ -121,6 +141,10 @@ pub trait SymbolsDatabase: HirDatabase + SourceDatabaseExt + Upcast<dyn HirDatab
     /// Files in libraries are assumed to never change.
     #[salsa::input]
     fn library_roots(&self) -> Arc<FxHashSet<SourceRootId>>;
+
+    /// The impls of a trait, by the type they are implemented for.
+    #[salsa::invoke(TraitImplIndex::trait_impl_index_query)]
+    fn trait_impl_index(&self, trait_: Trait) -> Arc<TraitImplIndex>;
 }
 
 fn library_symbols(db: &dyn SymbolsDatabase, source_root_id: SourceRootId) -> Arc<SymbolIndex> {
@@ -153,6 +177,31 @@ pub fn crate_symbols(db: &dyn SymbolsDatabase, krate: Crate) -> Box<[Arc<SymbolI
     krate.modules(db.upcast()).into_iter().map(|module| db.module_symbols(module)).collect()
 }
 
+/// How many module indices are merged into one shard. Edits merge their shard again, so this
+/// trades the cost of an edit against the number of FSTs a query runs on.
+const MODULES_PER_SHARD: usize = 64;
+
+fn crate_symbol_shard(db: &dyn SymbolsDatabase, krate: Crate, shard: usize) -> Arc<SymbolIndex> {
+    let _p = tracing::span!(tracing::Level::INFO, "crate_symbol_shard").entered();
+    let modules = db.crate_symbol_shard_modules(krate, shard);
+    let indices = modules.iter().map(|&module| db.module_symbols(module)).collect();
+    Arc::new(SymbolIndex::merge(indices))
+}
+
+fn crate_symbol_shard_modules(
+    db: &dyn SymbolsDatabase,
+    krate: Crate,
+    shard: usize,
+) -> Box<[Module]> {
+    let modules = krate.modules(db.upcast());
+    modules.chunks(MODULES_PER_SHARD).nth(shard).unwrap_or_default().into()
+}
+
+fn crate_symbol_shards(db: &dyn SymbolsDatabase, krate: Crate) -> Box<[Arc<SymbolIndex>]> {
+    let shards = krate.modules(db.upcast()).len().div_ceil(MODULES_PER_SHARD);
+    (0..shards).map(|shard| db.crate_symbol_shard(krate, shard)).collect()
+}
+
 /// Need to wrap Snapshot to provide `Clone` impl for `map_with`
 struct Snap<DB>(DB);
 impl<DB: ParallelDatabase> Snap<salsa::Snapshot<DB>> {
@@ -213,11 +262,11 @@ pub fn world_symbols(db: &RootDatabase, query: Query) -> Vec<FileSymbol> {
         for &root in db.local_roots().iter() {
             crates.extend(db.source_root_crates(root).iter().copied())
         }
-        let indices: Vec<_> = crates
+        crates
             .into_par_iter()
-            .map_with(Snap::new(db), |snap, krate| snap.crate_symbols(krate.into()))
-            .collect();
-        indices.iter().flat_map(|indices| indices.iter().cloned()).collect()
+            .map_with(Snap::new(db), |snap, krate| snap.crate_symbol_shards(krate.into()))
+            .flat_map_iter(|shards| shards.into_vec())
+            .collect()
     };
 
     let mut res = vec![];
@@ -227,13 +276,29 @@ pub fn world_symbols(db: &RootDatabase, query: Query) -> Vec<FileSymbol> {
 
 #[derive(Default)]
 pub struct SymbolIndex {
-    symbols: Vec<FileSymbol>,
+    symbols: Symbols,
     map: fst::Map<Vec<u8>>,
 }
 
+/// The symbols the values of the map of a [`SymbolIndex`] refer to.
+#[derive(PartialEq, Eq, Hash)]
+enum Symbols {
+    /// The symbols, sorted by name. Map values are ranges of them.
+    Owned(Vec<FileSymbol>),
+    /// The symbols of merged indices. Map values are ranges of `ranges`, which are ranges of the
+    /// symbols of one of the `indices` each.
+    Merged { indices: Box<[Arc<SymbolIndex>]>, ranges: Vec<(u32, u32, u32)> },
+}
+
+impl Default for Symbols {
+    fn default() -> Self {
+        Symbols::Owned(Vec::new())
+    }
+}
+
 impl fmt::Debug for SymbolIndex {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_struct("SymbolIndex").field("n_symbols", &self.symbols.len()).finish()
+        f.debug_struct("SymbolIndex").field("n_symbols", &self.len()).finish()
     }
 }
 
@@ -289,15 +354,73 @@ fn cmp(lhs: &FileSymbol, rhs: &FileSymbol) -> Ordering {
             buf
         })
         .unwrap();
-        SymbolIndex { symbols, map }
+        SymbolIndex { symbols: Symbols::Owned(symbols), map }
+    }
+
+    /// Merges the maps of `indices` into one, which refers to the symbols of `indices` instead of
+    /// copying them. The maps are unioned in key order, so unlike `new` this doesn't sort the
+    /// symbols.
+    fn merge(indices: Box<[Arc<SymbolIndex>]>) -> SymbolIndex {
+        let mut op = fst::map::OpBuilder::new();
+        for index in indices.iter() {
+            op = op.add(index.map.stream());
+        }
+        let mut stream = op.union();
+
+        let mut ranges = Vec::new();
+        let mut builder = fst::MapBuilder::memory();
+        while let Some((key, indexed_values)) = stream.next() {
+            // The union yields the values of a key in no particular order.
+            let mut indexed_values = indexed_values.to_vec();
+            indexed_values.sort_unstable_by_key(|it| it.index);
+
+            let start = ranges.len();
+            ranges.extend(indexed_values.iter().map(|&IndexedValue { index, value }| {
+                let (index_start, index_end) = SymbolIndex::map_value_to_range(value);
+                (index as u32, index_start as u32, index_end as u32)
+            }));
+            builder.insert(key, SymbolIndex::range_to_map_value(start, ranges.len())).unwrap();
+        }
+        drop(stream);
+        ranges.shrink_to_fit();
+
+        let map = fst::Map::new({
+            let mut buf = builder.into_inner().unwrap();
+            buf.shrink_to_fit();
+            buf
+        })
+        .unwrap();
+        SymbolIndex { symbols: Symbols::Merged { indices, ranges }, map }
     }
 
     pub fn len(&self) -> usize {
-        self.symbols.len()
+        match &self.symbols {
+            Symbols::Owned(symbols) => symbols.len(),
+            Symbols::Merged { indices, .. } => indices.iter().map(|it| it.len()).sum(),
+        }
     }
 
+    /// The memory used by the index, not counting the indices it was merged from.
     pub fn memory_size(&self) -> usize {
-        self.map.as_fst().size() + self.symbols.len() * mem::size_of::<FileSymbol>()
+        let symbols = match &self.symbols {
+            Symbols::Owned(symbols) => symbols.len() * mem::size_of::<FileSymbol>(),
+            Symbols::Merged { ranges, .. } => ranges.len() * mem::size_of::<(u32, u32, u32)>(),
+        };
+        self.map.as_fst().size() + symbols
+    }
+
+    /// Calls `cb` with the symbols of the map value `value`.
+    fn for_each_symbol<'sym>(&'sym self, value: u64, cb: &mut dyn FnMut(&'sym FileSymbol)) {
+        let (start, end) = SymbolIndex::map_value_to_range(value);
+        match &self.symbols {
+            Symbols::Owned(symbols) => symbols[start..end].iter().for_each(cb),
+            Symbols::Merged { indices, ranges } => {
+                for &(index, start, end) in &ranges[start..end] {
+                    let range = SymbolIndex::range_to_map_value(start as usize, end as usize);
+                    indices[index as usize].for_each_symbol(range, cb);
+                }
+            }
+        }
     }
 
     fn range_to_map_value(start: usize, end: usize) -> u64 {
@@ -358,10 +481,7 @@ fn search_maps<'sym>(
     ) {
         while let Some((_, indexed_values)) = stream.next() {
             for &IndexedValue { index, value } in indexed_values {
-                let symbol_index = &indices[index];
-                let (start, end) = SymbolIndex::map_value_to_range(value);
-
-                for symbol in &symbol_index.symbols[start..end] {
+                indices[index].for_each_symbol(value, &mut |symbol| {
                     let non_type_for_type_only_query = self.only_types
                         && !matches!(
                             symbol.def,
@@ -372,12 +492,12 @@ fn search_maps<'sym>(
                                 | hir::ModuleDef::Trait(..)
                         );
                     if non_type_for_type_only_query || !self.matches_assoc_mode(symbol.is_assoc) {
-                        continue;
+                        return;
                     }
                     if self.mode.check(&self.query, self.case_sensitive, &symbol.name) {
                         cb(symbol);
                     }
-                }
+                });
             }
         }
     }
@@ -395,6 +515,7 @@ mod tests {
 
     use expect_test::expect_file;
     use hir::symbols::SymbolCollector;
+    use stdx::format_to;
     use test_fixture::WithFixture;
 
     use super::*;
@@ -516,4 +637,60 @@ fn test_doc_alias() {
 
         expect_file!["./test_data/test_doc_alias.txt"].assert_debug_eq(&symbols);
     }
+
+    #[test]
+    fn crate_symbol_shards_merge_module_indices() {
+        // Enough modules for the crate to be split into two shards.
+        let mut fixture = String::from("//- /main.rs crate:main\nstruct Foo;\n");
+        for i in 0..MODULES_PER_SHARD {
+            format_to!(fixture, "mod m{i};\n");
+        }
+        for i in 0..MODULES_PER_SHARD {
+            format_to!(fixture, "//- /m{i}.rs\nstruct FooInM{i};\n");
+        }
+        fixture.push_str("//- /other.rs crate:other\nstruct FooInOther;\n");
+        let (mut db, files) = RootDatabase::with_many_files(&fixture);
+        let krate = Crate::all(&db)
+            .into_iter()
+            .find(|it| it.display_name(&db).map(|name| name.to_string()).as_deref() == Some("main"))
+            .unwrap();
+        let search = |shards: &[Arc<SymbolIndex>], query: &str| {
+            let mut query = Query::new(query.to_owned());
+            query.prefix();
+            let mut names = Vec::new();
+            query.search(shards, |it| names.push(it.name.to_string()));
+            names
+        };
+
+        let shards = db.crate_symbol_shards(krate);
+        let module_indices = db.crate_symbols(krate);
+        assert_eq!(shards.len(), 2);
+        assert_eq!(
+            shards.iter().map(|it| it.len()).sum::<usize>(),
+            module_indices.iter().map(|it| it.len()).sum::<usize>()
+        );
+        assert_eq!(search(&shards, "foo").len(), MODULES_PER_SHARD + 1);
+        assert_eq!(
+            search(&shards, "fooinm1"),
+            [
+                "FooInM1", "FooInM10", "FooInM11", "FooInM12", "FooInM13", "FooInM14", "FooInM15",
+                "FooInM16", "FooInM17", "FooInM18", "FooInM19"
+            ]
+        );
+
+        // Changes to other crates don't merge the shards again.
+        let other = *files.last().unwrap();
+        db.set_file_text(other, Arc::from("struct FooInOther;\nstruct BarInOther;\n"));
+        let new_shards = db.crate_symbol_shards(krate);
+        assert!(shards.iter().zip(new_shards.iter()).all(|(it, new)| Arc::ptr_eq(it, new)));
+
+        // Changes to a module only merge its shard again.
+        let last = files[MODULES_PER_SHARD];
+        db.set_file_text(last, Arc::from("struct FooInLast;\nfn bar() {}\n"));
+        let new_shards = db.crate_symbol_shards(krate);
+        assert!(Arc::ptr_eq(&shards[0], &new_shards[0]));
+        assert!(!Arc::ptr_eq(&shards[1], &new_shards[1]));
+        assert_eq!(search(&new_shards, "fooinl"), ["FooInLast"]);
+        assert_eq!(search(&new_shards, "bar"), ["bar"]);
+    }
 }
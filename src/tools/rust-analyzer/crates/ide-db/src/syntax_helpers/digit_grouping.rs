@@ -0,0 +1,187 @@
+//! Splits number literals into their digit parts to check and change how the digits are grouped
+//! with `_` separators, keeping the base prefix, exponent and type suffix as they are.
+use syntax::ast::{self, Radix};
+
+/// The parts of an integer or float literal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NumberLiteral<'a> {
+    pub radix: Radix,
+    /// The base prefix, like `0x`.
+    pub prefix: &'a str,
+    /// The digits before the decimal point.
+    pub integer: &'a str,
+    /// The digits after the decimal point, if there is one.
+    pub fraction: Option<&'a str>,
+    /// The exponent, including its `e`.
+    pub exponent: &'a str,
+    /// The type suffix, like `u32` or `f64`.
+    pub suffix: &'a str,
+}
+
+impl<'a> NumberLiteral<'a> {
+    pub fn new(literal: &'a ast::LiteralKind) -> Option<NumberLiteral<'a>> {
+        match literal {
+            ast::LiteralKind::IntNumber(it) => {
+                let (prefix, integer, suffix) = it.split_into_parts();
+                Some(NumberLiteral {
+                    radix: it.radix(),
+                    prefix,
+                    integer,
+                    fraction: None,
+                    exponent: "",
+                    suffix,
+                })
+            }
+            ast::LiteralKind::FloatNumber(it) => {
+                let (text, suffix) = it.split_into_parts();
+                let (mantissa, exponent) =
+                    text.split_at(text.find(['e', 'E']).unwrap_or(text.len()));
+                let (integer, fraction) = match mantissa.split_once('.') {
+                    Some((integer, fraction)) => (integer, Some(fraction)),
+                    None => (mantissa, None),
+                };
+                Some(NumberLiteral {
+                    radix: Radix::Decimal,
+                    prefix: "",
+                    integer,
+                    fraction,
+                    exponent,
+                    suffix,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// The number of digits in the longer of the integer and fractional parts.
+    pub fn max_part_len(&self) -> usize {
+        let digits = |part: &str| part.chars().filter(|&c| c != '_').count();
+        digits(self.integer).max(self.fraction.map_or(0, digits))
+    }
+
+    pub fn has_separators(&self) -> bool {
+        self.integer.contains('_') || self.fraction.is_some_and(|it| it.contains('_'))
+    }
+
+    /// Whether the groups of digits between separators all have the same size, except for the
+    /// leftmost group of the integer part and the rightmost group of the fractional part, which can
+    /// be shorter.
+    pub fn is_consistently_grouped(&self) -> bool {
+        let groups = |part: &'a str| part.split('_').filter(|it| !it.is_empty());
+        let integer_ok = {
+            let mut groups = groups(self.integer);
+            let first = groups.next();
+            is_consistent(first, groups)
+        };
+        let fraction_ok = self.fraction.map_or(true, |fraction| {
+            let mut groups = groups(fraction).rev();
+            let last = groups.next();
+            is_consistent(last, groups)
+        });
+        integer_ok && fraction_ok
+    }
+
+    /// The literal with its digits grouped by the usual group size of its base.
+    pub fn regrouped(&self) -> String {
+        let size = group_size(self.radix);
+        let mut res = self.prefix.to_owned();
+        res.push_str(&group_integer_digits(self.integer, size));
+        if let Some(fraction) = self.fraction {
+            res.push('.');
+            res.push_str(&group_fraction_digits(fraction, size));
+        }
+        res.push_str(self.exponent);
+        res.push_str(self.suffix);
+        res
+    }
+
+    /// The literal with the separators of its integer and fractional parts removed.
+    pub fn without_separators(&self) -> String {
+        let mut res = self.prefix.to_owned();
+        res.push_str(&self.integer.replace('_', ""));
+        if let Some(fraction) = self.fraction {
+            res.push('.');
+            res.push_str(&fraction.replace('_', ""));
+        }
+        res.push_str(self.exponent);
+        res.push_str(self.suffix);
+        res
+    }
+}
+
+/// Checks that all groups after `edge` have the same size, and that `edge` isn't longer.
+fn is_consistent<'a>(edge: Option<&'a str>, mut rest: impl Iterator<Item = &'a str>) -> bool {
+    let Some(first) = rest.next() else { return true };
+    rest.all(|it| it.len() == first.len()) && edge.map_or(true, |it| it.len() <= first.len())
+}
+
+pub const fn group_size(radix: Radix) -> usize {
+    match radix {
+        Radix::Binary => 4,
+        Radix::Octal => 3,
+        Radix::Decimal => 3,
+        Radix::Hexadecimal => 4,
+    }
+}
+
+/// Groups `digits` from the right, dropping the separators that were already there.
+pub fn group_integer_digits(digits: &str, group_size: usize) -> String {
+    let mut chars = Vec::new();
+    for (i, ch) in digits.chars().filter(|&ch| ch != '_').rev().enumerate() {
+        if i > 0 && i % group_size == 0 {
+            chars.push('_');
+        }
+        chars.push(ch);
+    }
+
+    chars.into_iter().rev().collect()
+}
+
+/// Groups `digits` from the left, dropping the separators that were already there.
+pub fn group_fraction_digits(digits: &str, group_size: usize) -> String {
+    let mut res = String::new();
+    for (i, ch) in digits.chars().filter(|&ch| ch != '_').enumerate() {
+        if i > 0 && i % group_size == 0 {
+            res.push('_');
+        }
+        res.push(ch);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use syntax::{ast, AstNode, SourceFile};
+
+    use super::NumberLiteral;
+
+    fn check(literal: &str, consistent: bool, regrouped: &str) {
+        let file = SourceFile::parse(&format!("const _: () = {literal};")).tree();
+        let literal = file.syntax().descendants().find_map(ast::Literal::cast).unwrap();
+        let kind = literal.kind();
+        let parts = NumberLiteral::new(&kind).unwrap();
+        assert_eq!(parts.is_consistently_grouped(), consistent, "{literal}");
+        assert_eq!(parts.regrouped(), regrouped, "{literal}");
+    }
+
+    #[test]
+    fn integers() {
+        check("1234567", true, "1_234_567");
+        check("1_234_567u64", true, "1_234_567u64");
+        check("12_34_567", false, "1_234_567");
+        check("1234_567", false, "1_234_567");
+        check("0xFF_FFFF_i32", true, "0xFF_FFFFi32");
+        check("0x1234_56", false, "0x12_3456");
+        check("0b1_0000_0000", true, "0b1_0000_0000");
+    }
+
+    #[test]
+    fn floats() {
+        check("1234.5678", true, "1_234.567_8");
+        check("1_234.567_8f64", true, "1_234.567_8f64");
+        check("1_234.5_678", false, "1_234.567_8");
+        check("123_4_567.5", false, "1_234_567.5");
+        check("1_000e1_0f32", true, "1_000e1_0f32");
+        check("1.", true, "1.");
+    }
+}
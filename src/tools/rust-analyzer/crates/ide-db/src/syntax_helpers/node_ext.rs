@@ -3,7 +3,7 @@
 use parser::T;
 use syntax::{
     ast::{self, HasLoopBody, MacroCall, PathSegmentKind, VisibilityKind},
-    AstNode, AstToken, Preorder, RustLanguage, WalkEvent,
+    match_ast, AstNode, AstToken, Preorder, RustLanguage, SyntaxNode, WalkEvent,
 };
 
 pub fn expr_as_name_ref(expr: &ast::Expr) -> Option<ast::NameRef> {
@@ -77,6 +77,7 @@ pub fn preorder_expr(start: &ast::Expr, cb: &mut dyn FnMut(WalkEvent<ast::Expr>)
                                 block_expr.modifier(),
                                 Some(
                                     ast::BlockModifier::Async(_)
+                                        | ast::BlockModifier::Gen(_)
                                         | ast::BlockModifier::Try(_)
                                         | ast::BlockModifier::Const(_)
                                 )
@@ -128,6 +129,7 @@ pub fn walk_patterns_in_expr(start: &ast::Expr, cb: &mut dyn FnMut(ast::Pat)) {
                                 block_expr.modifier(),
                                 Some(
                                     ast::BlockModifier::Async(_)
+                                        | ast::BlockModifier::Gen(_)
                                         | ast::BlockModifier::Try(_)
                                         | ast::BlockModifier::Const(_)
                                 )
@@ -267,6 +269,7 @@ pub fn for_each_tail_expr(expr: &ast::Expr, cb: &mut dyn FnMut(&ast::Expr)) {
             match b.modifier() {
                 Some(
                     ast::BlockModifier::Async(_)
+                    | ast::BlockModifier::Gen(_)
                     | ast::BlockModifier::Try(_)
                     | ast::BlockModifier::Const(_),
                 ) => return cb(expr),
@@ -472,3 +475,58 @@ pub fn macro_call_for_string_token(string: &ast::String) -> Option<MacroCall> {
     let macro_call = string.syntax().parent_ancestors().find_map(ast::MacroCall::cast)?;
     Some(macro_call)
 }
+
+/// Pick the first ancestor expression of the unsafe `expr` that is not a
+/// receiver of a method call, a field access, the left-hand side of an
+/// assignment, a reference, or parenthesized. As all of those cases would incur
+/// a forced move if wrapped which might not be wanted. That is:
+/// - `unsafe_expr.foo` -> `unsafe { unsafe_expr.foo }`
+/// - `unsafe_expr.foo.bar` -> `unsafe { unsafe_expr.foo.bar }`
+/// - `unsafe_expr.foo()` -> `unsafe { unsafe_expr.foo() }`
+/// - `unsafe_expr.foo.bar()` -> `unsafe { unsafe_expr.foo.bar() }`
+/// - `unsafe_expr += 1` -> `unsafe { unsafe_expr += 1 }`
+/// - `&unsafe_expr` -> `unsafe { &unsafe_expr }`
+/// - `&&unsafe_expr` -> `unsafe { &&unsafe_expr }`
+pub fn pick_best_node_to_add_unsafe_block(unsafe_expr: &ast::Expr) -> Option<SyntaxNode> {
+    // The `unsafe_expr` might be:
+    // - `ast::CallExpr`: call an unsafe function
+    // - `ast::MethodCallExpr`: call an unsafe method
+    // - `ast::PrefixExpr`: dereference a raw pointer
+    // - `ast::PathExpr`: access a static mut variable
+    for (node, parent) in
+        unsafe_expr.syntax().ancestors().zip(unsafe_expr.syntax().ancestors().skip(1))
+    {
+        match_ast! {
+            match parent {
+                // If the `parent` is a `MethodCallExpr`, that means the `node`
+                // is the receiver of the method call, because only the receiver
+                // can be a direct child of a method call. The method name
+                // itself is not an expression but a `NameRef`, and an argument
+                // is a direct child of an `ArgList`.
+                ast::MethodCallExpr(_) => continue,
+                ast::FieldExpr(_) => continue,
+                ast::RefExpr(_) => continue,
+                ast::ParenExpr(_) => continue,
+                ast::BinExpr(it) => {
+                    // Check if the `node` is the left-hand side of an
+                    // assignment, if so, we don't want to wrap it in an unsafe
+                    // block, e.g. `unsafe_expr += 1`
+                    let is_left_hand_side_of_assignment = {
+                        if let Some(ast::BinaryOp::Assignment { .. }) = it.op_kind() {
+                            it.lhs().map(|lhs| lhs.syntax().text_range().contains_range(node.text_range())).unwrap_or(false)
+                        } else {
+                            false
+                        }
+                    };
+                    if !is_left_hand_side_of_assignment {
+                        return Some(node);
+                    }
+                },
+                _ => { return Some(node); }
+
+            }
+        }
+    }
+    // The whole expansion of an expression macro call is the expression itself.
+    unsafe_expr.syntax().ancestors().last().filter(|it| ast::Expr::can_cast(it.kind()))
+}
@@ -0,0 +1,39 @@
+use base_db::CrateId;
+use hir::Crate;
+use test_fixture::WithFixture;
+
+use crate::{FxHashSet, RootDatabase};
+
+#[test]
+fn evict_crate_bodies() {
+    let (mut db, _) = RootDatabase::with_many_files(
+        r#"
+//- /main.rs crate:main deps:lib
+fn main() {
+    let _ = lib::foo();
+}
+//- /lib.rs crate:lib
+pub fn foo() -> u32 {
+    92
+}
+"#,
+    );
+    let crates: FxHashSet<CrateId> = Crate::all(&db).into_iter().map(Into::into).collect();
+    for &krate in &crates {
+        Crate::from(krate).root_module().diagnostics(&db, &mut Vec::new());
+    }
+    assert_eq!(db.crates_with_bodies(), crates);
+
+    let (evicted, kept): (FxHashSet<_>, FxHashSet<_>) = crates
+        .iter()
+        .partition(|&&krate| Crate::from(krate).display_name(&db).unwrap().to_string() == "lib");
+    let counts = db.evict_crate_bodies(&evicted);
+    assert!(counts.iter().any(|&(query, count)| query == "InferQueryQuery" && count == 1));
+    assert_eq!(db.crates_with_bodies(), kept);
+
+    // Evicted bodies are computed again when needed.
+    for &krate in &evicted {
+        Crate::from(krate).root_module().diagnostics(&db, &mut Vec::new());
+    }
+    assert_eq!(db.crates_with_bodies(), crates);
+}
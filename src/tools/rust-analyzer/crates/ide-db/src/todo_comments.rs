@@ -0,0 +1,219 @@
+//! Finds the to-do markers in comments, like
+//!
+//! ```text
+//! // TODO: handle errors
+//! // FIXME(alice): this is quadratic
+//! // HACK(bob, 2024-01-31): remove once the upstream fix is released
+//! ```
+//!
+//! A marker has to start a line of its comment. The optional tag in parentheses holds the owner
+//! and the date the marker was written, in any order.
+
+use std::fmt;
+
+use syntax::{ast, AstNode, AstToken, SourceFile, TextRange, TextSize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TodoKind {
+    Todo,
+    Fixme,
+    Hack,
+}
+
+impl TodoKind {
+    const ALL: [TodoKind; 3] = [TodoKind::Todo, TodoKind::Fixme, TodoKind::Hack];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TodoKind::Todo => "TODO",
+            TodoKind::Fixme => "FIXME",
+            TodoKind::Hack => "HACK",
+        }
+    }
+}
+
+/// A calendar date, from a `YYYY-MM-DD` part of a tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TodoDate {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl TodoDate {
+    fn parse(text: &str) -> Option<TodoDate> {
+        let mut parts = text.splitn(3, '-');
+        let year = parts.next().filter(|it| it.len() == 4)?.parse().ok()?;
+        let month = parts.next().filter(|it| it.len() == 2)?.parse().ok()?;
+        let day = parts.next().filter(|it| it.len() == 2)?.parse().ok()?;
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return None;
+        }
+        Some(TodoDate { year, month, day })
+    }
+
+    /// The number of days from 1970-01-01 to this date.
+    pub fn days_since_epoch(self) -> i64 {
+        // Howard Hinnant's `days_from_civil`.
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let month = i64::from(self.month);
+        let day_of_year =
+            (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146097 + day_of_era - 719468
+    }
+}
+
+impl fmt::Display for TodoDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TodoComment {
+    pub kind: TodoKind,
+    /// The owner named in the tag, without a leading `@`.
+    pub owner: Option<String>,
+    /// The date in the tag.
+    pub date: Option<TodoDate>,
+    /// The rest of the line after the marker, its tag and a `:`.
+    pub text: String,
+    /// The range from the marker to the end of its line.
+    pub range: TextRange,
+}
+
+pub fn todo_comments(file: &SourceFile) -> Vec<TodoComment> {
+    let mut res = Vec::new();
+    let comments = file
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token().and_then(ast::Comment::cast));
+    for comment in comments {
+        let comment_start = comment.syntax().text_range().start();
+        let mut line_start = 0;
+        for line in comment.text().split_inclusive('\n') {
+            let offset = line_start;
+            line_start += line.len();
+
+            let mut line = line.trim_end();
+            if comment.kind().shape.is_block() {
+                line = line.strip_suffix("*/").unwrap_or(line).trim_end();
+            }
+            let body = line
+                .trim_start_matches(|c: char| matches!(c, '/' | '*' | '!') || c.is_whitespace());
+            let body_start = offset + line.len() - body.len();
+            let range = TextRange::at(
+                comment_start + TextSize::from(body_start as u32),
+                TextSize::of(body),
+            );
+            res.extend(parse_marker(body, range));
+        }
+    }
+    res
+}
+
+fn parse_marker(body: &str, range: TextRange) -> Option<TodoComment> {
+    let kind = TodoKind::ALL.into_iter().find(|it| body.starts_with(it.as_str()))?;
+    let rest = &body[kind.as_str().len()..];
+    if rest.starts_with(|c: char| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let mut owner = None;
+    let mut date = None;
+    let rest = match rest.strip_prefix('(') {
+        Some(rest) => {
+            let (tag, rest) = rest.split_once(')')?;
+            for part in tag.split(',').map(str::trim).filter(|it| !it.is_empty()) {
+                match TodoDate::parse(part) {
+                    Some(it) => date = Some(it),
+                    None if owner.is_none() => {
+                        owner = Some(part.trim_start_matches('@').to_owned())
+                    }
+                    None => (),
+                }
+            }
+            rest
+        }
+        None => rest,
+    };
+    let text = rest.trim_start().trim_start_matches(':').trim().to_owned();
+    Some(TodoComment { kind, owner, date, text, range })
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use syntax::SourceFile;
+
+    use super::{todo_comments, TodoDate};
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let file = SourceFile::parse(ra_fixture).tree();
+        let actual = todo_comments(&file)
+            .into_iter()
+            .map(|it| {
+                format!(
+                    "{} owner={:?} date={:?} {:?} `{}`\n",
+                    it.kind.as_str(),
+                    it.owner,
+                    it.date.map(|it| it.to_string()),
+                    it.text,
+                    &ra_fixture[it.range],
+                )
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn markers_and_tags() {
+        check(
+            r#"
+// TODO: handle errors
+fn f() {
+    // FIXME(alice): this is quadratic
+    let x = 1; // HACK(@bob, 2024-01-31) remove this
+    // TODO(2023-12-01, carol)
+}
+"#,
+            expect![[r#"
+                TODO owner=None date=None "handle errors" `TODO: handle errors`
+                FIXME owner=Some("alice") date=None "this is quadratic" `FIXME(alice): this is quadratic`
+                HACK owner=Some("bob") date=Some("2024-01-31") "remove this" `HACK(@bob, 2024-01-31) remove this`
+                TODO owner=Some("carol") date=Some("2023-12-01") "" `TODO(2023-12-01, carol)`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn markers_in_block_and_doc_comments() {
+        check(
+            r#"
+/// Does things.
+///
+/// TODO: document the edge cases
+fn f() {}
+/* Some text.
+ * FIXME(dave): wrong on 32-bit targets */
+/* TODOS are not markers, neither is a TODO in the middle of a line */
+"#,
+            expect![[r#"
+                TODO owner=None date=None "document the edge cases" `TODO: document the edge cases`
+                FIXME owner=Some("dave") date=None "wrong on 32-bit targets" `FIXME(dave): wrong on 32-bit targets`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn days_since_epoch() {
+        let date = |year, month, day| TodoDate { year, month, day }.days_since_epoch();
+        assert_eq!(date(1970, 1, 1), 0);
+        assert_eq!(date(2000, 3, 1), 11017);
+        assert_eq!(date(2024, 1, 31), 19753);
+        assert_eq!(date(1969, 12, 31), -1);
+    }
+}
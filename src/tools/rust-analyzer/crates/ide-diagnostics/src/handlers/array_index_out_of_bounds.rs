@@ -0,0 +1,76 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: array-index-out-of-bounds
+//
+// This diagnostic is triggered when an array is indexed with a constant that is at least its
+// length, which always panics. The length can be a constant or a const generic argument.
+pub(crate) fn array_index_out_of_bounds(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::ArrayIndexOutOfBounds,
+) -> Diagnostic {
+    let mut diagnostic = Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::RustcLint("unconditional_panic"),
+        format!(
+            "this operation will panic at runtime: index out of bounds: the length is {} but the index is {}",
+            d.len, d.index
+        ),
+        d.expr.map(|it| it.into()),
+    );
+    // The lint is deny-by-default.
+    diagnostic.severity = Severity::Error;
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn constant_index() {
+        check_diagnostics(
+            r#"
+const LEN: usize = 2 * 2;
+const LAST: usize = LEN - 1;
+fn f(arr: [u8; LEN], r: &[u8; 3]) {
+    arr[3];
+    arr[LAST];
+    arr[4];
+  //^^^^^^ error: this operation will panic at runtime: index out of bounds: the length is 4 but the index is 4
+    r[3];
+  //^^^^ error: this operation will panic at runtime: index out of bounds: the length is 3 but the index is 3
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn const_generic_length() {
+        check_diagnostics(
+            r#"
+struct Buf<const N: usize> {
+    data: [u8; N],
+}
+fn generic<const N: usize>(buf: Buf<N>) -> u8 {
+    buf.data[8]
+}
+fn concrete(buf: Buf<{ 2 + 2 }>) -> u8 {
+    buf.data[8]
+  //^^^^^^^^^^^ error: this operation will panic at runtime: index out of bounds: the length is 4 but the index is 8
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed() {
+        check_diagnostics(
+            r#"
+#[allow(unconditional_panic)]
+fn f(arr: [u8; 1]) {
+    arr[1];
+}
+"#,
+        );
+    }
+}
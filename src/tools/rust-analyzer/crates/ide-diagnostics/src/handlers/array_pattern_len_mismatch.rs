@@ -0,0 +1,49 @@
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: array-pattern-len-mismatch
+//
+// This diagnostic is triggered when a slice pattern matching an array needs more elements than
+// the array has, or a different number of elements if it has no rest pattern. The length can be a
+// constant or a const generic argument.
+pub(crate) fn array_pattern_len_mismatch(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::ArrayPatternLenMismatch,
+) -> Diagnostic {
+    let (code, at_least) = if d.has_rest { ("E0528", "at least ") } else { ("E0527", "") };
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::RustcHardError(code),
+        format!("pattern requires {at_least}{} elements but array has {}", d.required, d.len),
+        d.pat.map(|it| it.into()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn len_mismatch() {
+        check_diagnostics(
+            r#"
+struct Buf<const N: usize>([u8; N]);
+fn f(arr: [u8; 3], buf: Buf<2>) {
+    let [_, _, _] = arr;
+    let [_, .., _] = arr;
+    let [_, _, _, _] = arr;
+      //^^^^^^^^^^^^ error: pattern requires 4 elements but array has 3
+    let [_, _] = arr;
+      //^^^^^^ error: pattern requires 2 elements but array has 3
+    let [_, ..] = arr;
+    let [_, _, _, ..] = buf.0;
+      //^^^^^^^^^^^^^ error: pattern requires at least 3 elements but array has 2
+    match &arr {
+        [_, _, _, _, _rest @ ..] => (),
+      //^^^^^^^^^^^^^^^^^^^^^^^^ error: pattern requires at least 4 elements but array has 3
+        _ => (),
+    }
+}
+"#,
+        );
+    }
+}
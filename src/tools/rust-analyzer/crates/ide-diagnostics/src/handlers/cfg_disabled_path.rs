@@ -0,0 +1,241 @@
+use cfg::{CfgAtom, DnfExpr};
+use hir::{HirFileIdExt, InFile, PathResolution};
+use ide_db::{
+    assists::Assist,
+    base_db::{FileId, SourceDatabaseExt},
+    cargo_manifest::add_dependency_features,
+    crate_features::cargo_toml,
+    source_change::SourceChange,
+};
+use syntax::{ast, AstNode, SmolStr, SyntaxKind, SyntaxNodePtr, TextRange, TextSize};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: cfg-disabled-path
+//
+// This diagnostic is triggered if a path in an expression, pattern or type doesn't resolve
+// because the item it names is disabled by a `#[cfg]`. It says which cfg that is, and if it is a
+// feature of a dependency, it offers to enable the feature in `Cargo.toml`.
+pub(crate) fn cfg_disabled_path(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let sema = &ctx.sema;
+    let source_file = sema.parse(file_id);
+    // Only look at whole paths, their qualifiers are checked along with them.
+    let paths = source_file.syntax().descendants().filter_map(ast::Path::cast).filter(|path| {
+        path.syntax().parent().map_or(false, |parent| {
+            matches!(
+                parent.kind(),
+                SyntaxKind::PATH_EXPR
+                    | SyntaxKind::PATH_PAT
+                    | SyntaxKind::PATH_TYPE
+                    | SyntaxKind::RECORD_EXPR
+                    | SyntaxKind::RECORD_PAT
+                    | SyntaxKind::TUPLE_STRUCT_PAT
+            )
+        })
+    });
+    for path in paths {
+        if sema.resolve_path(&path).is_some() {
+            continue;
+        }
+        let in_type = path.syntax().parent().map_or(false, |it| ast::PathType::can_cast(it.kind()));
+        let node = InFile::new(file_id.into(), SyntaxNodePtr::new(path.syntax()));
+        acc.extend(cfg_disabled_path_diagnostic(ctx, &path, node, |segment_is_last| {
+            match (segment_is_last, in_type) {
+                (false, _) => (DiagnosticCode::RustcHardError("E0433"), "failed to resolve"),
+                (true, true) => (DiagnosticCode::RustcHardError("E0412"), "unresolved type"),
+                (true, false) => (DiagnosticCode::RustcHardError("E0425"), "unresolved path"),
+            }
+        }));
+    }
+}
+
+/// The diagnostic for an unresolved `path`, at `node`, if it doesn't resolve because one of its
+/// segments names an item that is disabled by a `#[cfg]`. `describe` returns the code and the
+/// start of the message, given whether that segment is the last one.
+pub(super) fn cfg_disabled_path_diagnostic(
+    ctx: &DiagnosticsContext<'_>,
+    path: &ast::Path,
+    node: InFile<SyntaxNodePtr>,
+    describe: impl FnOnce(bool) -> (DiagnosticCode, &'static str),
+) -> Option<Diagnostic> {
+    let db = ctx.sema.db;
+    let scope = ctx.sema.scope(path.syntax())?;
+
+    // Find the first segment that doesn't resolve, and the module it should be in.
+    let mut unresolved = path.clone();
+    let module = loop {
+        match unresolved.qualifier() {
+            Some(qualifier) => match ctx.sema.resolve_path(&qualifier) {
+                Some(PathResolution::Def(hir::ModuleDef::Module(module))) => break module,
+                Some(_) => return None,
+                None => unresolved = qualifier,
+            },
+            None => break scope.module(),
+        }
+    };
+    let name = unresolved.segment()?.name_ref()?;
+    let cfg = module.cfg_disabled_item(db, name.text().as_str())?;
+
+    let krate = module.krate();
+    let opts = krate.cfg(db);
+    let features = DnfExpr::new(cfg.clone())
+        .compute_enable_hints(&opts)
+        .filter(|diff| diff.disable().is_empty())
+        .map(|diff| {
+            diff.enable()
+                .iter()
+                .map(|atom| match atom {
+                    CfgAtom::KeyValue { key, value } if key == "feature" => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+        })
+        .find_map(|it| it);
+    let dependency = scope.krate().dependencies(db).into_iter().find(|dep| dep.krate == krate);
+
+    let (code, prefix) = describe(&unresolved == path);
+    let message = match (&features, &dependency) {
+        (Some(features), Some(dependency)) => format!(
+            "{prefix}: `{name}` needs the {} of `{}`",
+            feature_list(features),
+            dependency.name.display(db),
+        ),
+        _ => match DnfExpr::new(cfg).why_inactive(&opts) {
+            Some(reason) => format!("{prefix}: `{name}` is inactive because {reason}"),
+            None => format!("{prefix}: `{name}` is inactive due to #[cfg] directives"),
+        },
+    };
+    let fixes = features.zip(dependency).and_then(|(features, dependency)| {
+        let file_id = node.file_id.original_file(db);
+        let name = dependency.name.display(db).to_string();
+        enable_features_fix(ctx, file_id, &name, &features, path.syntax().text_range())
+    });
+    Some(Diagnostic::new_with_syntax_node_ptr(ctx, code, message, node).with_fixes(fixes))
+}
+
+fn feature_list(features: &[SmolStr]) -> String {
+    let quoted: Vec<_> = features.iter().map(|it| format!("`{it}`")).collect();
+    match quoted.as_slice() {
+        [feature] => format!("{feature} feature"),
+        _ => format!("{} features", quoted.join(", ")),
+    }
+}
+
+/// Adds `features` to the `dependency` entry of the `Cargo.toml` of the crate `file_id` is in.
+fn enable_features_fix(
+    ctx: &DiagnosticsContext<'_>,
+    file_id: FileId,
+    dependency: &str,
+    features: &[SmolStr],
+    target: TextRange,
+) -> Option<Vec<Assist>> {
+    let db = ctx.sema.db;
+    let krate = ctx.sema.to_module_def(file_id)?.krate();
+    let manifest = cargo_toml(db, krate)?;
+    let text = db.file_text(manifest);
+    let names: Vec<_> = features.iter().map(|it| it.as_str()).collect();
+    let (range, replacement) = add_dependency_features(&text, dependency, &names)?;
+    let range =
+        TextRange::new(TextSize::try_from(range.start).ok()?, TextSize::try_from(range.end).ok()?);
+    Some(vec![fix(
+        "enable_dependency_feature",
+        &format!("Enable the {} of `{dependency}` in Cargo.toml", feature_list(features)),
+        SourceChange::from_text_edit(manifest, TextEdit::replace(range, replacement)),
+        target,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn paths_disabled_by_dependency_feature() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:dep
+fn f(_: dep::Gated) {
+      //^^^^^^^^^^ error: unresolved type: `Gated` needs the `gated` feature of `dep`
+    dep::gated_fn();
+  //^^^^^^^^^^^^^ error: unresolved path: `gated_fn` needs the `gated` feature of `dep`
+    dep::gated::Thing;
+  //^^^^^^^^^^^^^^^^^ error: failed to resolve: `gated` needs the `gated` feature of `dep`
+    dep::always();
+}
+//- /dep/lib.rs crate:dep
+pub fn always() {}
+#[cfg(feature = "gated")]
+pub struct Gated;
+#[cfg(feature = "gated")]
+pub fn gated_fn() {}
+#[cfg(feature = "gated")]
+pub mod gated {
+    pub struct Thing;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn path_disabled_by_cfg() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main
+fn f() {
+    let testing::Helper = testing::helper();
+      //^^^^^^^^^^^^^^^ error: unresolved path: `Helper` is inactive because test is disabled
+                        //^^^^^^^^^^^^^^^ error: unresolved path: `helper` is inactive because test is disabled
+}
+
+mod testing {
+    #[cfg(test)]
+    pub struct Helper;
+    #[cfg(test)]
+    pub fn helper() {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unresolved_paths_without_cfg_are_left_alone() {
+        check_diagnostics(
+            r#"
+fn f() {
+    m::does_not_exist();
+    does_not_exist();
+}
+mod m {}
+"#,
+        );
+    }
+
+    #[test]
+    fn enable_feature_for_path() {
+        check_fix(
+            r#"
+//- /main.rs crate:main deps:dep
+fn f() {
+    dep::gated_fn$0();
+}
+//- /Cargo.toml
+[dependencies.dep]
+version = "1.0"
+features = ["other"]
+//- /dep/lib.rs crate:dep
+#[cfg(feature = "gated")]
+pub fn gated_fn() {}
+"#,
+            r#"
+[dependencies.dep]
+version = "1.0"
+features = ["other", "gated"]
+"#,
+        );
+    }
+}
@@ -0,0 +1,147 @@
+use hir::{Crate, Function, HasAttrs, HasSource, HirDisplay, InFile, ModuleDef};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    FxHashMap, RootDatabase,
+};
+use syntax::{
+    ast::{self, HasName},
+    AstNode, SmolStr,
+};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: clashing-extern-declarations
+//
+// This diagnostic is triggered when a function of an `extern` block is declared elsewhere in the
+// workspace under the same symbol name, but with a different signature. Both declarations refer to
+// the same foreign function, so calling it through the wrong one is undefined behavior. Both
+// declarations are reported, as neither of them is more right than the other.
+//
+// Structs, unions and enums of different definitions are compared by their fields, so copies of a
+// C struct in several crates don't clash.
+pub(crate) fn clashing_extern_declarations(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let db = ctx.sema.db;
+    let source_file = ctx.sema.parse(file_id);
+    // Only collected when the file declares a foreign function, as it walks the whole workspace.
+    let mut by_symbol = None;
+    for func in source_file.syntax().descendants().filter_map(ast::Fn::cast) {
+        let Some(name) = func.name() else { continue };
+        let Some(def) = ctx.sema.to_def(&func) else { continue };
+        if !def.is_foreign(db) {
+            continue;
+        }
+        let symbol = symbol_name(db, def);
+        let by_symbol = by_symbol.get_or_insert_with(|| foreign_functions(db));
+        let Some(&other) = by_symbol.get(&symbol).and_then(|decls| {
+            decls.iter().find(|&&other| other != def && !def.has_same_foreign_signature(db, other))
+        }) else {
+            continue;
+        };
+
+        let mut diagnostic = Diagnostic::new(
+            DiagnosticCode::RustcLint("clashing_extern_declarations"),
+            format!("`{symbol}` redeclared with a different signature"),
+            FileRange { file_id, range: name.syntax().text_range() },
+        )
+        .with_main_node(InFile::new(file_id.into(), func.syntax().clone()));
+        if let Some(src) = other.source(db) {
+            if let Some(other_name) = src.value.name() {
+                diagnostic = diagnostic.with_related(
+                    src.with_value(other_name.syntax()).original_file_range(db),
+                    format!("`{symbol}` is also declared as `{}`", other.display(db)),
+                );
+            }
+        }
+        acc.push(diagnostic);
+    }
+}
+
+/// The functions of the `extern` blocks of the local crates, by symbol name.
+fn foreign_functions(db: &RootDatabase) -> FxHashMap<String, Vec<Function>> {
+    let mut res: FxHashMap<_, Vec<_>> = FxHashMap::default();
+    for krate in Crate::all(db).into_iter().filter(|it| it.origin(db).is_local()) {
+        for module in krate.modules(db) {
+            for decl in module.declarations(db) {
+                let ModuleDef::Function(func) = decl else { continue };
+                if func.is_foreign(db) {
+                    res.entry(symbol_name(db, func)).or_default().push(func);
+                }
+            }
+        }
+    }
+    res
+}
+
+/// The name the linker knows a foreign function by.
+fn symbol_name(db: &RootDatabase, func: Function) -> String {
+    match func.attrs(db).by_key("link_name").string_value() {
+        Some(link_name) => SmolStr::to_string(link_name),
+        None => func.name(db).display(db).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn clashing_declarations_across_crates() {
+        check_diagnostics(
+            r#"
+//- /lib.rs crate:ffi
+#[repr(C)]
+pub struct Point { x: f64, y: f64 }
+extern "C" {
+    pub fn abs(x: i32) -> i32;
+         //^^^ warn: `abs` redeclared with a different signature
+    pub fn strlen(s: *const u8) -> usize;
+    pub fn translate(point: Point) -> Point;
+         //^^^^^^^^^ warn: `translate` redeclared with a different signature
+}
+//- /main.rs crate:main deps:ffi
+mod sys {
+    #[repr(C)]
+    pub struct Point { x: f64, y: f64 }
+    #[repr(C)]
+    pub struct Point3 { x: f64, y: f64, z: f64 }
+    extern "C" {
+        pub fn abs(x: u32) -> u32;
+             //^^^ warn: `abs` redeclared with a different signature
+        pub fn strlen(s: *const u8) -> usize;
+        pub fn translate(point: Point) -> Point;
+             //^^^^^^^^^ warn: `translate` redeclared with a different signature
+        pub fn exit(code: i32) -> !;
+    }
+    extern "C" {
+        pub fn translate3(point: Point3) -> Point3;
+        #[link_name = "translate"]
+        pub fn translate_3d(point: Point3) -> Point3;
+             //^^^^^^^^^^^^ warn: `translate` redeclared with a different signature
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allow_attribute() {
+        check_diagnostics(
+            r#"
+//- /lib.rs crate:ffi
+extern "C" {
+    pub fn abs(x: i32) -> i32;
+         //^^^ warn: `abs` redeclared with a different signature
+}
+//- /main.rs crate:main deps:ffi
+#[allow(clashing_extern_declarations)]
+extern "C" {
+    fn abs(x: u32) -> u32;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use hir::{AsAssocItem, AssocItemContainer, HasAttrs, HasVisibility, InFile, Visibility};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    defs::Definition,
+    source_change::SourceChange,
+};
+use syntax::{
+    ast::{self, HasName},
+    match_ast, AstNode, SyntaxKind, SyntaxNode, TextRange, T,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: dead-code
+//
+// This diagnostic is triggered when a function, struct or struct field that can't be used from
+// outside of its crate is never referenced anywhere in the workspace.
+//
+// Only reported when `rust-analyzer.diagnostics.deadCode.enable` is set, as it searches for the
+// references of every such item.
+pub(crate) fn dead_code(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>, file_id: FileId) {
+    let source_file = ctx.sema.parse(file_id);
+    for node in source_file.syntax().descendants() {
+        let (kind, name, unused, remove_range) = match_ast! {
+            match node {
+                ast::Fn(it) => ("function", it.name(), is_unused_fn(ctx, &it), item_range(&node)),
+                ast::Struct(it) => ("struct", it.name(), is_unused_struct(ctx, &it), item_range(&node)),
+                ast::RecordField(it) => ("field", it.name(), is_unused_field(ctx, &it), field_range(&node)),
+                _ => continue,
+            }
+        };
+        let Some(name) = name.filter(|_| unused) else { continue };
+
+        let name_range = name.syntax().text_range();
+        acc.push(
+            Diagnostic::new(
+                DiagnosticCode::RustcLint("dead_code"),
+                format!("{kind} `{name}` is never used"),
+                FileRange { file_id, range: name_range },
+            )
+            .with_main_node(InFile::new(file_id.into(), node.clone()))
+            .with_unused(true)
+            .with_fixes(Some(vec![fix(
+                "remove_dead_code",
+                &format!("Remove unused {kind}"),
+                SourceChange::from_text_edit(file_id, TextEdit::delete(remove_range)),
+                name_range,
+            )])),
+        );
+    }
+}
+
+fn is_unused_fn(ctx: &DiagnosticsContext<'_>, fn_: &ast::Fn) -> bool {
+    let db = ctx.sema.db;
+    if fn_.syntax().parent().map_or(false, |it| it.kind() == SyntaxKind::EXTERN_ITEM_LIST) {
+        return false;
+    }
+    let Some(func) = ctx.sema.to_def(fn_) else { return false };
+    if let Some(assoc) = func.as_assoc_item(db) {
+        match assoc.container(db) {
+            AssocItemContainer::Trait(_) => return false,
+            AssocItemContainer::Impl(impl_) if impl_.trait_(db).is_some() => return false,
+            AssocItemContainer::Impl(_) => (),
+        }
+    }
+    let attrs = func.attrs(db);
+    // These are called by the test harness or linked to by name rather than through paths.
+    if func.is_main(db)
+        || func.is_test(db)
+        || func.is_bench(db)
+        || attrs.by_key("no_mangle").exists()
+        || attrs.by_key("export_name").exists()
+    {
+        return false;
+    }
+    is_unused(ctx, func.visibility(db), Definition::Function(func))
+}
+
+fn is_unused_struct(ctx: &DiagnosticsContext<'_>, struct_: &ast::Struct) -> bool {
+    let Some(strukt) = ctx.sema.to_def(struct_) else { return false };
+    is_unused(ctx, strukt.visibility(ctx.sema.db), Definition::Adt(strukt.into()))
+}
+
+fn is_unused_field(ctx: &DiagnosticsContext<'_>, field: &ast::RecordField) -> bool {
+    let db = ctx.sema.db;
+    let Some(field) = ctx.sema.to_def(field) else { return false };
+    // The fields of enum variants are as visible as the enum itself.
+    if !matches!(field.parent_def(db), hir::VariantDef::Struct(_)) {
+        return false;
+    }
+    is_unused(ctx, field.visibility(db), Definition::Field(field))
+}
+
+/// Whether `def` can't be used outside of its crate and isn't used inside of it either.
+///
+/// The search scope of a non-public item is the whole crate, so this also catches `pub(crate)`
+/// items and references from macro expansions and tests.
+fn is_unused(ctx: &DiagnosticsContext<'_>, visibility: Visibility, def: Definition) -> bool {
+    visibility != Visibility::Public && !def.usages(&ctx.sema).at_least_one()
+}
+
+/// The range of an item together with the whitespace in front of it, so that removing it doesn't
+/// leave an empty line behind.
+fn item_range(node: &SyntaxNode) -> TextRange {
+    let range = node.text_range();
+    match node.prev_sibling_or_token().filter(|it| it.kind() == SyntaxKind::WHITESPACE) {
+        Some(ws) => TextRange::new(ws.text_range().start(), range.end()),
+        None => match node.next_sibling_or_token().filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+        {
+            Some(ws) => TextRange::new(range.start(), ws.text_range().end()),
+            None => range,
+        },
+    }
+}
+
+/// The range of a field together with its trailing comma and the whitespace after that.
+fn field_range(node: &SyntaxNode) -> TextRange {
+    let mut end = node.text_range().end();
+    let mut next = node.next_sibling_or_token();
+    if let Some(comma) = next.clone().filter(|it| it.kind() == T![,]) {
+        end = comma.text_range().end();
+        next = comma.next_sibling_or_token();
+    }
+    if let Some(ws) = next.filter(|it| it.kind() == SyntaxKind::WHITESPACE) {
+        end = ws.text_range().end();
+    }
+    TextRange::new(node.text_range().start(), end)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics_with_config, check_nth_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn config() -> DiagnosticsConfig {
+        DiagnosticsConfig { dead_code: true, ..DiagnosticsConfig::test_sample() }
+    }
+
+    #[test]
+    fn unused_private_items() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+struct Used { used: u32, unused: u32 }
+                       //^^^^^^ 💡 warn: field `unused` is never used
+struct Unused;
+     //^^^^^^ 💡 warn: struct `Unused` is never used
+pub struct Public { pub field: u32 }
+
+fn unused() {}
+ //^^^^^^ 💡 warn: function `unused` is never used
+pub(crate) fn unused_in_crate() {}
+            //^^^^^^^^^^^^^^^ 💡 warn: function `unused_in_crate` is never used
+pub fn public() {}
+
+impl Used {
+    fn method(&self) {}
+     //^^^^^^ 💡 warn: function `method` is never used
+}
+
+fn make() -> Used {
+    loop {}
+}
+
+fn main() {
+    let _ = make().used;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn field_only_written_is_used() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+struct S { written: u32 }
+
+fn main() {
+    S { written: 0 };
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn used_in_macros_and_tests() {
+        let mut config = config();
+        // `#[test]` itself doesn't resolve without the real standard library.
+        config.disabled.insert("unresolved-macro-call".to_owned());
+        check_diagnostics_with_config(
+            config,
+            r#"
+macro_rules! call {
+    ($f:ident) => { $f() };
+}
+
+fn called_by_macro() {}
+fn called_by_test() {}
+
+fn main() {
+    call!(called_by_macro);
+}
+
+#[test]
+fn test() {
+    called_by_test();
+}
+
+#[no_mangle]
+extern "C" fn exported() {}
+
+extern "C" {
+    fn imported();
+}
+
+trait Trait {
+    fn required(&self);
+}
+
+impl Trait for () {
+    fn required(&self) {}
+}
+
+#[allow(dead_code)]
+fn allowed() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_reported_when_disabled() {
+        check_diagnostics_with_config(
+            DiagnosticsConfig::test_sample(),
+            r#"
+fn unused() {}
+
+fn main() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_unused_function() {
+        check_nth_fix_with_config(
+            config(),
+            0,
+            r#"
+fn main() {}
+
+fn unused$0() {
+    main();
+}
+"#,
+            r#"
+fn main() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_unused_field() {
+        check_nth_fix_with_config(
+            config(),
+            0,
+            r#"
+struct S { unused$0: u32, used: u32 }
+
+fn make() -> S {
+    loop {}
+}
+
+fn main() {
+    let _ = make().used;
+}
+"#,
+            r#"
+struct S { used: u32 }
+
+fn make() -> S {
+    loop {}
+}
+
+fn main() {
+    let _ = make().used;
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use hir::{FfiUnsafe, FfiUnsafeReason, HirDisplay, InFile};
+use ide_db::base_db::{FileId, FileRange};
+use syntax::{ast, AstNode};
+
+use crate::{
+    handlers::missing_repr_transparent::{has_foreign_abi, single_field_wrapper},
+    Diagnostic, DiagnosticCode, DiagnosticsContext,
+};
+
+// Diagnostic: improper-ctypes
+//
+// This diagnostic is triggered when a parameter or the return type of a function with a foreign
+// ABI has a type without a C equivalent, like `char`, a slice or a struct without `#[repr(C)]`.
+// Like rustc, it is reported as `improper_ctypes` in `extern` blocks and as
+// `improper_ctypes_definitions` on `extern fn` definitions.
+pub(crate) fn improper_ctypes(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let db = ctx.sema.db;
+    let source_file = ctx.sema.parse(file_id);
+    for func in source_file.syntax().descendants().filter_map(ast::Fn::cast) {
+        if !has_foreign_abi(&func) {
+            continue;
+        }
+        let lint = match func.body() {
+            Some(_) => "improper_ctypes_definitions",
+            None => "improper_ctypes",
+        };
+        let params = func
+            .param_list()
+            .into_iter()
+            .flat_map(|it| it.params())
+            .filter_map(|it| it.ty())
+            .map(|it| (it, false));
+        let ret = func.ret_type().and_then(|it| it.ty()).map(|it| (it, true));
+        for (ty, is_ret) in params.chain(ret) {
+            // Single field wrappers get the more specific `missing-repr-transparent` instead.
+            if single_field_wrapper(ctx, &ty).is_some() {
+                continue;
+            }
+            if let Some(fn_ptr) = rust_fn_ptr(&ty) {
+                acc.push(
+                    Diagnostic::new(
+                        DiagnosticCode::RustcLint(lint),
+                        format!(
+                            "`{fn_ptr}` is not FFI-safe: this function pointer has Rust-specific calling convention"
+                        ),
+                        FileRange { file_id, range: fn_ptr.syntax().text_range() },
+                    )
+                    .with_main_node(InFile::new(file_id.into(), ty.syntax().clone())),
+                );
+                continue;
+            }
+            let Some(resolved) = ctx.sema.resolve_type(&ty) else { continue };
+            if is_ret && (resolved.is_unit() || resolved.is_never()) {
+                continue;
+            }
+            let Err(FfiUnsafe { ty: culprit, reason }) = resolved.ffi_safety(db) else { continue };
+            let pointee = resolved.remove_ref().or_else(|| resolved.remove_raw_ptr());
+            let message = if culprit == resolved || Some(&culprit) == pointee.as_ref() {
+                format!("`{}` is not FFI-safe: {}", resolved.display(db), describe(reason))
+            } else {
+                format!(
+                    "`{}` is not FFI-safe because it contains `{}`: {}",
+                    resolved.display(db),
+                    culprit.display(db),
+                    describe(reason)
+                )
+            };
+            acc.push(
+                Diagnostic::new(
+                    DiagnosticCode::RustcLint(lint),
+                    message,
+                    FileRange { file_id, range: ty.syntax().text_range() },
+                )
+                .with_main_node(InFile::new(file_id.into(), ty.syntax().clone())),
+            );
+        }
+    }
+}
+
+/// A function pointer type without an ABI in `ty` that is passed by value, like the `fn()` of
+/// `Option<fn()>`.
+///
+/// This is checked on the syntax, as the ABI is ignored when comparing types.
+fn rust_fn_ptr(ty: &ast::Type) -> Option<ast::FnPtrType> {
+    let mut types = vec![ty.clone()];
+    while let Some(ty) = types.pop() {
+        match ty {
+            ast::Type::FnPtrType(it) if it.abi().is_none() => return Some(it),
+            ast::Type::ParenType(it) => types.extend(it.ty()),
+            ast::Type::PathType(it) => types.extend(
+                it.path()
+                    .and_then(|it| it.segment())
+                    .and_then(|it| it.generic_arg_list())
+                    .into_iter()
+                    .flat_map(|it| it.generic_args())
+                    .filter_map(|it| match it {
+                        ast::GenericArg::TypeArg(it) => it.ty(),
+                        _ => None,
+                    }),
+            ),
+            _ => (),
+        }
+    }
+    None
+}
+
+fn describe(reason: FfiUnsafeReason) -> &'static str {
+    match reason {
+        FfiUnsafeReason::Char => "the `char` type has no C equivalent",
+        FfiUnsafeReason::Str => "string slices have no C equivalent",
+        FfiUnsafeReason::Slice => "slices have no C equivalent",
+        FfiUnsafeReason::DynTrait => "trait objects have no C equivalent",
+        FfiUnsafeReason::Tuple => "tuples have unspecified layout",
+        FfiUnsafeReason::ArrayByValue => "passing raw arrays by value is not FFI-safe",
+        FfiUnsafeReason::Int128 => "128-bit integers don't currently have a known stable ABI",
+        FfiUnsafeReason::Opaque => "opaque types have no C equivalent",
+        FfiUnsafeReason::StructWithoutRepr => "this struct has unspecified layout",
+        FfiUnsafeReason::UnionWithoutRepr => "this union has unspecified layout",
+        FfiUnsafeReason::EnumWithoutRepr => "enum has no representation hint",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn extern_block() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+struct Opaque { a: u32, b: u32 }
+#[repr(C)]
+struct Point { x: f64, y: f64 }
+#[repr(C)]
+struct Labeled { point: Point, label: char }
+#[repr(u8)]
+enum Kind { A, B }
+enum NoRepr { A, B }
+extern "C" {
+    fn by_ptr(opaque: *mut Opaque, point: Point, labeled: &Labeled, kind: Kind) -> bool;
+    fn by_value(
+        opaque: Opaque,
+              //^^^^^^ warn: `Opaque` is not FFI-safe: this struct has unspecified layout
+        labeled: Labeled,
+               //^^^^^^^ warn: `Labeled` is not FFI-safe because it contains `char`: the `char` type has no C equivalent
+    ) -> NoRepr;
+       //^^^^^^ warn: `NoRepr` is not FFI-safe: enum has no representation hint
+    fn strings(
+        s: &str,
+         //^^^^ warn: `&str` is not FFI-safe: string slices have no C equivalent
+        bytes: &[u8],
+             //^^^^^ warn: `&[u8]` is not FFI-safe: slices have no C equivalent
+        c: char,
+         //^^^^ warn: `char` is not FFI-safe: the `char` type has no C equivalent
+    ) -> (u8, u8);
+       //^^^^^^^^ warn: `(u8, u8)` is not FFI-safe: tuples have unspecified layout
+    fn nullable(cb: Option<extern "C" fn()>, ptr: Option<&u8>);
+    fn rust_callback(cb: fn());
+                       //^^^^ warn: `fn()` is not FFI-safe: this function pointer has Rust-specific calling convention
+    fn maybe_rust_callback(cb: Option<fn()>);
+                                    //^^^^ warn: `fn()` is not FFI-safe: this function pointer has Rust-specific calling convention
+    fn wide(x: u128);
+             //^^^^ warn: `u128` is not FFI-safe: 128-bit integers don't currently have a known stable ABI
+    fn array(buf: [u8; 4], nested: *const [u8; 4]);
+                //^^^^^^^ warn: `[u8; 4]` is not FFI-safe: passing raw arrays by value is not FFI-safe
+    fn unit() -> ();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn extern_fn_definitions() {
+        check_diagnostics(
+            r#"
+//- minicore: option
+extern "C" fn callback(_c: char) {}
+                         //^^^^ warn: `char` is not FFI-safe: the `char` type has no C equivalent
+extern "C" fn niche() -> Option<bool> { None }
+                       //^^^^^^^^^^^^ warn: `Option<bool>` is not FFI-safe: enum has no representation hint
+extern "Rust" fn rust_abi(_c: char) {}
+fn plain(_c: char) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn allow_attribute() {
+        check_diagnostics(
+            r#"
+#[allow(improper_ctypes)]
+extern "C" {
+    fn allowed(c: char);
+}
+#[allow(improper_ctypes)]
+extern "C" fn not_this_lint(_c: char) {}
+                              //^^^^ warn: `char` is not FFI-safe: the `char` type has no C equivalent
+#[allow(improper_ctypes_definitions)]
+extern "C" fn allowed_definition(_c: char) {}
+"#,
+        );
+    }
+}
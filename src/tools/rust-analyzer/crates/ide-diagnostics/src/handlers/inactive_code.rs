@@ -103,6 +103,29 @@ fn inactive_item() {
         );
     }
 
+    #[test]
+    fn inactive_in_every_crate_variant() {
+        check(
+            r#"
+//- /x86.rs crate:x86 cfg:target_arch=x86_64
+#[path = "shared.rs"]
+mod shared;
+//- /wasm.rs crate:wasm cfg:target_arch=wasm32
+#[path = "shared.rs"]
+mod shared;
+//- /shared.rs
+#[cfg(target_arch = "wasm32")]
+fn wasm_only() {}
+
+#[cfg(target_arch = "x86_64")]
+fn x86_only() {}
+
+  #[cfg(target_arch = "arm")] fn arm_only() {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ weak: code is inactive due to #[cfg] directives: target_arch = "arm" is disabled
+"#,
+        );
+    }
+
     #[test]
     fn inactive_assoc_item() {
         check(
@@ -0,0 +1,154 @@
+use hir::{HasAttrs, ModuleDef, PathResolution};
+use ide_db::base_db::{FileId, FileRange, Version};
+use syntax::{ast, match_ast, AstNode, SyntaxNode, TextRange};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: incompatible_msrv
+//
+// This diagnostic is triggered when code uses an item of the standard library that was
+// stabilized in a later Rust version than the `rust-version` the crate's manifest declares.
+pub(crate) fn incompatible_msrv(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let db = ctx.sema.db;
+    let Some(module) = ctx.sema.to_module_def(file_id) else { return };
+    let Some(msrv) = module.krate().rust_version(db) else { return };
+
+    let source_file = ctx.sema.parse(file_id);
+    for node in source_file.syntax().descendants() {
+        let Some((def, range)) = used_item(ctx, &node) else { continue };
+        let lang_crate = def.module(db).map_or(false, |it| it.krate().origin(db).is_lang());
+        if !lang_crate {
+            continue;
+        }
+        let Some(since) = stable_since(db, def) else { continue };
+        if since <= msrv {
+            continue;
+        }
+        acc.push(
+            Diagnostic::new(
+                DiagnosticCode::Clippy("incompatible_msrv"),
+                format!(
+                    "current MSRV (Minimum Supported Rust Version) is `{msrv}` but this item is stable since `{since}`"
+                ),
+                FileRange { file_id, range },
+            )
+            .with_main_node(hir::InFile::new(file_id.into(), node)),
+        );
+    }
+}
+
+/// The item `node` refers to, and the range to report for it.
+///
+/// Imports aren't reported, as the paths that use the imported item are.
+fn used_item(ctx: &DiagnosticsContext<'_>, node: &SyntaxNode) -> Option<(ModuleDef, TextRange)> {
+    match_ast! {
+        match node {
+            ast::Path(path) => {
+                if path.syntax().ancestors().any(|it| ast::Use::can_cast(it.kind())) {
+                    return None;
+                }
+                let name_ref = path.segment()?.name_ref()?;
+                match ctx.sema.resolve_path(&path)? {
+                    PathResolution::Def(def) => Some((def, name_ref.syntax().text_range())),
+                    _ => None,
+                }
+            },
+            ast::MethodCallExpr(call) => {
+                let func = ctx.sema.resolve_method_call(&call)?;
+                Some((func.into(), call.name_ref()?.syntax().text_range()))
+            },
+            _ => None,
+        }
+    }
+}
+
+fn stable_since(db: &dyn hir::db::HirDatabase, def: ModuleDef) -> Option<Version> {
+    match def {
+        ModuleDef::Module(it) => it.stable_since(db),
+        ModuleDef::Function(it) => it.stable_since(db),
+        ModuleDef::Adt(it) => it.stable_since(db),
+        ModuleDef::Variant(it) => it.stable_since(db),
+        ModuleDef::Const(it) => it.stable_since(db),
+        ModuleDef::Static(it) => it.stable_since(db),
+        ModuleDef::Trait(it) => it.stable_since(db),
+        ModuleDef::TraitAlias(it) => it.stable_since(db),
+        ModuleDef::TypeAlias(it) => it.stable_since(db),
+        ModuleDef::Macro(it) => it.stable_since(db),
+        ModuleDef::BuiltinType(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn items_stabilized_after_msrv() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:std env:CARGO_PKG_RUST_VERSION=1.60
+use std::{old_fn, new_fn, Thing};
+
+fn main() {
+    old_fn();
+    new_fn();
+  //^^^^^^ weak: current MSRV (Minimum Supported Rust Version) is `1.60.0` but this item is stable since `1.70.0`
+    std::new_fn();
+       //^^^^^^ weak: current MSRV (Minimum Supported Rust Version) is `1.60.0` but this item is stable since `1.70.0`
+    let thing = Thing;
+    thing.old_method();
+    thing.new_method();
+        //^^^^^^^^^^ weak: current MSRV (Minimum Supported Rust Version) is `1.60.0` but this item is stable since `1.65.0`
+}
+//- /std.rs crate:std
+#[stable(feature = "old", since = "1.0.0")]
+pub fn old_fn() {}
+#[stable(feature = "new", since = "1.70.0")]
+pub fn new_fn() {}
+
+#[stable(feature = "thing", since = "1.0.0")]
+pub struct Thing;
+impl Thing {
+    #[stable(feature = "old", since = "1.0.0")]
+    pub fn old_method(&self) {}
+    #[stable(feature = "new_method", since = "1.65.0")]
+    pub fn new_method(&self) {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_msrv() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:std
+fn main() {
+    std::new_fn();
+}
+//- /std.rs crate:std
+#[stable(feature = "new", since = "1.70.0")]
+pub fn new_fn() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_std_items() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:dep env:CARGO_PKG_RUST_VERSION=1.60.0
+fn main() {
+    dep::new_fn();
+}
+//- /dep.rs crate:dep
+#[stable(feature = "new", since = "1.70.0")]
+pub fn new_fn() {}
+"#,
+        );
+    }
+}
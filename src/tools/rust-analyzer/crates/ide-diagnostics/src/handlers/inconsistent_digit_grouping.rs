@@ -0,0 +1,110 @@
+use hir::InFile;
+use ide_db::{
+    base_db::{FileId, FileRange},
+    source_change::SourceChange,
+    syntax_helpers::digit_grouping::NumberLiteral,
+};
+use syntax::{ast, AstNode, SyntaxNode};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticCode};
+
+// Diagnostic: inconsistent-digit-grouping
+//
+// This diagnostic is triggered when the `_` separators of a number literal split its digits into
+// groups of different sizes, like in `1_23_456`. It is only reported with
+// `rust-analyzer.diagnostics.style` set to `"clippy-lite"`.
+pub(crate) fn inconsistent_digit_grouping(
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    node: &SyntaxNode,
+) -> Option<()> {
+    let literal = ast::Literal::cast(node.clone())?;
+    let kind = literal.kind();
+    let parts = NumberLiteral::new(&kind)?;
+    if parts.is_consistently_grouped() {
+        return None;
+    }
+
+    let range = literal.syntax().text_range();
+    let regrouped = parts.regrouped();
+    let edit = TextEdit::replace(range, regrouped.clone());
+    acc.push(
+        Diagnostic::new(
+            DiagnosticCode::Clippy("inconsistent_digit_grouping"),
+            "digits grouped inconsistently by underscores",
+            FileRange { file_id, range },
+        )
+        .with_main_node(InFile::new(file_id.into(), node.clone()))
+        .with_fixes(Some(vec![fix(
+            "regroup_digits",
+            &format!("Regroup digits as `{regrouped}`"),
+            SourceChange::from_text_edit(file_id, edit),
+            range,
+        )])),
+    );
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_clippy_lite_diagnostics, check_clippy_lite_fix, check_diagnostics};
+
+    #[test]
+    fn inconsistent_grouping() {
+        check_clippy_lite_diagnostics(
+            r#"
+const A: u32 = 1_23_456;
+             //^^^^^^^^ 💡 weak: digits grouped inconsistently by underscores
+const B: f64 = 1_234.5_678f64;
+             //^^^^^^^^^^^^^^ 💡 weak: digits grouped inconsistently by underscores
+const C: u32 = 0x1234_56;
+             //^^^^^^^^^ 💡 weak: digits grouped inconsistently by underscores
+"#,
+        );
+    }
+
+    #[test]
+    fn consistent_grouping() {
+        check_clippy_lite_diagnostics(
+            r#"
+const A: u32 = 123_456;
+const B: u32 = 12_3456;
+const C: u32 = 0xFF_FFFF_u32;
+const D: f64 = 1_234.567_8;
+const E: u64 = 1234567;
+"#,
+        );
+    }
+
+    #[test]
+    fn only_reported_with_clippy_lite() {
+        check_diagnostics(
+            r#"
+const A: u32 = 1_23_456;
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_by_lint_attribute() {
+        check_clippy_lite_diagnostics(
+            r#"
+#[allow(clippy::inconsistent_digit_grouping)]
+const A: u32 = 1_23_456;
+"#,
+        );
+    }
+
+    #[test]
+    fn regroup_fix() {
+        check_clippy_lite_fix(
+            r#"
+const A: u64 = 1_23_4567_u64$0;
+"#,
+            r#"
+const A: u64 = 1_234_567u64;
+"#,
+        );
+    }
+}
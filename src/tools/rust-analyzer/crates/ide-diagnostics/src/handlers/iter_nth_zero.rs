@@ -0,0 +1,114 @@
+use hir::{db::ExpandDatabase, InFile};
+use ide_db::source_change::SourceChange;
+use syntax::{ast, AstNode, TextRange};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: iter-nth-zero
+//
+// This diagnostic is triggered when `.nth(0)` is called on an iterator, rather than the more
+// concise `.next()`. It is only reported with `rust-analyzer.diagnostics.style` set to
+// `"clippy-lite"`.
+pub(crate) fn iter_nth_zero(ctx: &DiagnosticsContext<'_>, d: &hir::IterNthZero) -> Diagnostic {
+    Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Clippy("iter_nth_zero"),
+        "called `.nth(0)` on an iterator, use `.next()` instead",
+        d.expr.map(Into::into),
+    )
+    .with_fixes(fixes(ctx, d))
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::IterNthZero) -> Option<Vec<Assist>> {
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id);
+    let nth_expr = d.expr.value.to_node(&root);
+    let nth_call = ast::MethodCallExpr::cast(nth_expr.syntax().clone())?;
+    let InFile { file_id, value: range } = d.expr.with_value(nth_call.syntax().text_range());
+    let file_id = file_id.file_id()?;
+
+    let name_range = nth_call.name_ref()?.syntax().text_range();
+    let range_to_replace = TextRange::new(name_range.start(), range.end());
+    let edit = TextEdit::replace(range_to_replace, "next()".to_owned());
+
+    Some(vec![fix(
+        "replace_with_next",
+        "Replace `.nth(0)` with `.next()`",
+        SourceChange::from_text_edit(file_id, edit),
+        range,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_clippy_lite_diagnostics, check_clippy_lite_fix, check_diagnostics};
+
+    #[test]
+    fn iter_nth_zero() {
+        check_clippy_lite_diagnostics(
+            r#"
+//- minicore: iterators
+fn foo() {
+    let _it = core::iter::repeat(()).nth(0);
+            //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ 💡 weak: called `.nth(0)` on an iterator, use `.next()` instead
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn iter_nth_nonzero() {
+        check_clippy_lite_diagnostics(
+            r#"
+//- minicore: iterators
+fn foo() {
+    let _it = core::iter::repeat(()).nth(1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_iterator_nth() {
+        check_clippy_lite_diagnostics(
+            r#"
+struct Foo;
+impl Foo {
+    fn nth(&self, _: usize) {}
+}
+fn foo() {
+    Foo.nth(0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_with_clippy_lite_style() {
+        check_diagnostics(
+            r#"
+//- minicore: iterators
+fn foo() {
+    let _it = core::iter::repeat(()).nth(0);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_with_next() {
+        check_clippy_lite_fix(
+            r#"
+//- minicore: iterators
+fn foo() {
+    let _it = core::iter::repeat(()).$0nth(0);
+}
+"#,
+            r#"
+fn foo() {
+    let _it = core::iter::repeat(()).next();
+}
+"#,
+        );
+    }
+}
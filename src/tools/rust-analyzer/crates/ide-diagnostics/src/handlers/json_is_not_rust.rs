@@ -137,6 +137,7 @@ pub(crate) fn json_in_items(
                                     config.insert_use.prefix_kind,
                                     config.prefer_no_std,
                                     config.prefer_prelude,
+                                    config.insert_use.prefer_reexports,
                                 ) {
                                     insert_use(&scope, mod_path_to_ast(&it), &config.insert_use);
                                 }
@@ -150,6 +151,7 @@ pub(crate) fn json_in_items(
                                     config.insert_use.prefix_kind,
                                     config.prefer_no_std,
                                     config.prefer_prelude,
+                                    config.insert_use.prefer_reexports,
                                 ) {
                                     insert_use(&scope, mod_path_to_ast(&it), &config.insert_use);
                                 }
@@ -0,0 +1,245 @@
+use hir::{CfgAtom, CfgExpr, Crate, HasAttrs, Semantics};
+use ide_db::{
+    base_db::{FileId, FileRange, SourceDatabaseExt, VfsPath},
+    RootDatabase,
+};
+use syntax::{ast, AstNode, SyntaxNode, TextRange};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, LEFTOVER_MACROS};
+
+// Diagnostic: leftover-macro
+//
+// This diagnostic is triggered when `todo!`, `unimplemented!`, `dbg!` or `println!` is called
+// outside of tests, benchmarks and examples. Calls are recognized by the macro they resolve to,
+// so macros of other crates with the same name aren't reported.
+//
+// Each macro is only reported when it's given a severity in
+// `rust-analyzer.diagnostics.leftoverMacros`.
+pub(crate) fn leftover_macro(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    if ctx.config.leftover_macros.is_empty() {
+        return;
+    }
+    let sema = &ctx.sema;
+    let db = sema.db;
+    let Some(module) = sema.to_module_def(file_id) else { return };
+    if is_test_target(db, module.krate()) {
+        return;
+    }
+
+    let source_file = sema.parse(file_id);
+    for macro_call in source_file.syntax().descendants().filter_map(ast::MacroCall::cast) {
+        let Some(mac) = sema.resolve_macro_call(&macro_call) else { continue };
+        if !mac.module(db).krate().origin(db).is_lang() {
+            continue;
+        }
+        let name = mac.name(db);
+        let Some(name) = name.as_str().filter(|it| LEFTOVER_MACROS.contains(it)) else {
+            continue;
+        };
+        let Some(&severity) = ctx.config.leftover_macros.get(name) else { continue };
+        if is_test_code(sema, macro_call.syntax()) {
+            continue;
+        }
+        let (Some(path), Some(bang)) = (macro_call.path(), macro_call.excl_token()) else {
+            continue;
+        };
+        let range = TextRange::new(path.syntax().text_range().start(), bang.text_range().end());
+        acc.push(
+            Diagnostic::new(
+                DiagnosticCode::Ra("leftover-macro", severity),
+                format!("`{name}!` left in non-test code"),
+                FileRange { file_id, range },
+            )
+            .with_main_node(hir::InFile::new(file_id.into(), macro_call.syntax().clone())),
+        );
+    }
+}
+
+/// Whether `krate` is an integration test, benchmark or example, going by Cargo's default target
+/// layout: `tests/foo.rs` or `tests/foo/main.rs`.
+fn is_test_target(db: &RootDatabase, krate: Crate) -> bool {
+    let root_file = krate.root_file(db);
+    let source_root = db.source_root(db.file_source_root(root_file));
+    let Some(path) = source_root.path_for_file(&root_file) else { return false };
+    let is_target_dir = |dir: Option<&_>| {
+        dir.and_then(VfsPath::name_and_extension)
+            .map_or(false, |(name, _)| matches!(name, "tests" | "benches" | "examples"))
+    };
+    let dir = path.parent();
+    is_target_dir(dir.as_ref())
+        || (path.name_and_extension() == Some(("main", Some("rs")))
+            && is_target_dir(dir.and_then(|it| it.parent()).as_ref()))
+}
+
+/// Whether `node` is in a test or benchmark function, or in an item or module that's only
+/// compiled for tests.
+fn is_test_code(sema: &Semantics<'_, RootDatabase>, node: &SyntaxNode) -> bool {
+    let db = sema.db;
+    let in_test_item = node.ancestors().filter_map(ast::Item::cast).any(|item| match item {
+        ast::Item::Fn(it) => sema.to_def(&it).map_or(false, |func| {
+            func.is_test(db)
+                || func.is_bench(db)
+                || func.attrs(db).cfg().map_or(false, |cfg| needs_test(&cfg))
+        }),
+        ast::Item::Impl(it) => {
+            sema.to_def(&it).and_then(|it| it.attrs(db).cfg()).map_or(false, |cfg| needs_test(&cfg))
+        }
+        _ => false,
+    });
+    in_test_item
+        || sema.scope(node).map_or(false, |scope| {
+            scope
+                .module()
+                .nearest_non_block_module(db)
+                .path_to_root(db)
+                .into_iter()
+                .any(|module| module.attrs(db).cfg().map_or(false, |cfg| needs_test(&cfg)))
+        })
+}
+
+/// Whether `cfg` can only be enabled with `cfg(test)`.
+fn needs_test(cfg: &CfgExpr) -> bool {
+    match cfg {
+        CfgExpr::Atom(CfgAtom::Flag(flag)) => flag == "test",
+        CfgExpr::All(cfgs) => cfgs.iter().any(needs_test),
+        CfgExpr::Any(cfgs) => !cfgs.is_empty() && cfgs.iter().all(needs_test),
+        CfgExpr::Invalid | CfgExpr::Atom(_) | CfgExpr::Not(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics, check_diagnostics_with_config},
+        DiagnosticsConfig, Severity,
+    };
+
+    fn config() -> DiagnosticsConfig {
+        let mut config = DiagnosticsConfig::test_sample();
+        config.leftover_macros = [
+            ("todo".to_owned(), Severity::Warning),
+            ("unimplemented".to_owned(), Severity::Error),
+            ("dbg".to_owned(), Severity::WeakWarning),
+        ]
+        .into_iter()
+        .collect();
+        config
+    }
+
+    #[test]
+    fn reports_configured_macros() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+//- minicore: todo, unimplemented
+fn f() {
+    todo!()
+  //^^^^^ warn: `todo!` left in non-test code
+}
+fn g() {
+    unimplemented!()
+  //^^^^^^^^^^^^^^ error: `unimplemented!` left in non-test code
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reports_std_macros() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+//- /main.rs crate:main deps:std
+fn f(x: u32) {
+    std::println!("{x}");
+    std::dbg!(x);
+  //^^^^^^^^^ weak: `dbg!` left in non-test code
+}
+//- /std.rs crate:std
+#[macro_export]
+macro_rules! dbg { ($e:expr) => { $e }; }
+#[macro_export]
+macro_rules! println { ($($t:tt)*) => {}; }
+"#,
+        );
+    }
+
+    #[test]
+    fn ignores_macros_of_other_crates() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+macro_rules! todo { () => { 0 }; }
+fn f() -> u32 {
+    todo!()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn ignores_test_code() {
+        let mut config = config();
+        // `#[test]` itself doesn't resolve without the real standard library.
+        config.disabled.insert("unresolved-macro-call".to_owned());
+        check_diagnostics_with_config(
+            config,
+            r#"
+//- minicore: todo
+//- /lib.rs cfg:test,unix
+#[test]
+fn test() {
+    todo!()
+}
+
+#[cfg(all(test, unix))]
+fn helper() {
+    todo!()
+}
+
+#[cfg(test)]
+mod tests {
+    mod nested {
+        fn f() {
+            todo!()
+        }
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn ignores_test_targets() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+//- minicore: todo
+//- /tests/it.rs crate:it
+fn f() {
+    todo!()
+}
+//- /examples/ex/main.rs crate:ex
+fn main() {
+    todo!()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_reported_by_default() {
+        check_diagnostics(
+            r#"
+//- minicore: todo
+fn f() {
+    todo!()
+}
+"#,
+        );
+    }
+}
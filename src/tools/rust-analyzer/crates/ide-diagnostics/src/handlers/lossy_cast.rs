@@ -0,0 +1,233 @@
+use hir::{db::ExpandDatabase, CastLossiness, HirDisplay};
+use ide_db::{base_db::Edition, source_change::SourceChange};
+use syntax::{ast, AstNode};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: lossy-cast
+//
+// This diagnostic is triggered by `as` casts between numeric types that may change the value:
+// casts to a narrower type that may truncate it, casts between signed and unsigned types that may
+// change its sign, and casts of floats to integers, which round toward zero and saturate at the
+// bounds of the integer type.
+//
+// Like the clippy lints `cast_possible_truncation`, `cast_sign_loss` and `cast_possible_wrap`,
+// it's allowed by default, and is reported when the lint is enabled with an attribute like
+// `#![warn(clippy::cast_possible_truncation)]` and `rust-analyzer.diagnostics.style` is set to
+// `"clippy-lite"`.
+pub(crate) fn lossy_cast(ctx: &DiagnosticsContext<'_>, d: &hir::LossyCast) -> Diagnostic {
+    let db = ctx.sema.db;
+    let (from, to) = (d.from.display(db), d.to.display(db));
+    let (lint, message) = match d.lossiness {
+        CastLossiness::Truncating => (
+            "cast_possible_truncation",
+            format!("casting `{from}` to `{to}` may truncate the value"),
+        ),
+        CastLossiness::SignChanging if is_signed(&d.from) => {
+            ("cast_sign_loss", format!("casting `{from}` to `{to}` may lose the sign of the value"))
+        }
+        CastLossiness::SignChanging => {
+            ("cast_possible_wrap", format!("casting `{from}` to `{to}` may wrap around the value"))
+        }
+        CastLossiness::Saturating => (
+            "cast_possible_truncation",
+            format!(
+                "casting `{from}` to `{to}` rounds toward zero and saturates out-of-range values"
+            ),
+        ),
+    };
+    let mut diag = Diagnostic::new_with_syntax_node_ptr(
+        ctx,
+        DiagnosticCode::Clippy(lint),
+        message,
+        d.expr.map(Into::into),
+    )
+    .with_fixes(fixes(ctx, d));
+    // The clippy lints are pedantic, so they have to be enabled explicitly.
+    diag.severity = Severity::Allow;
+    diag
+}
+
+fn is_signed(ty: &hir::Type) -> bool {
+    ty.as_builtin().map_or(false, |it| it.is_int())
+}
+
+fn is_integer(ty: &hir::Type) -> bool {
+    ty.as_builtin().map_or(false, |it| it.is_int() || it.is_uint())
+}
+
+/// Replaces casts between integers with a conversion that checks the value fits.
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::LossyCast) -> Option<Vec<Assist>> {
+    if !is_integer(&d.from) || !is_integer(&d.to) {
+        return None;
+    }
+    let file_id = d.expr.file_id.file_id()?;
+    // `TryFrom` is only in the prelude from edition 2021 on.
+    let krate = ctx.sema.to_module_def(file_id)?.krate();
+    if krate.edition(ctx.sema.db) < Edition::Edition2021 {
+        return None;
+    }
+
+    let root = ctx.sema.db.parse_or_expand(d.expr.file_id);
+    let cast = ast::CastExpr::cast(d.expr.value.to_node(&root).syntax().clone())?;
+    let ast::Type::PathType(ty) = cast.ty()? else { return None };
+    let expr = match cast.expr()? {
+        ast::Expr::ParenExpr(it) => it.expr()?,
+        it => it,
+    };
+    let range = cast.syntax().text_range();
+    let edit = TextEdit::replace(range, format!("{ty}::try_from({expr}).unwrap()"));
+    Some(vec![fix(
+        "replace_cast_with_try_from",
+        &format!("Replace with `{ty}::try_from`"),
+        SourceChange::from_text_edit(file_id, edit),
+        range,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{
+            check_clippy_lite_diagnostics, check_clippy_lite_fix, check_diagnostics,
+            check_no_fix_with_config,
+        },
+        DiagnosticsConfig, DiagnosticsStyle,
+    };
+
+    #[test]
+    fn truncating_casts() {
+        check_clippy_lite_diagnostics(
+            r#"
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: u32, b: i64, c: usize, d: f64, e: char) {
+    let _ = a as u8;
+          //^^^^^^^ 💡 warn: casting `u32` to `u8` may truncate the value
+    let _ = b as i32;
+          //^^^^^^^^ 💡 warn: casting `i64` to `i32` may truncate the value
+    let _ = c as u32;
+          //^^^^^^^^ 💡 warn: casting `usize` to `u32` may truncate the value
+    let _ = d as f32;
+          //^^^^^^^^ warn: casting `f64` to `f32` may truncate the value
+    let _ = e as u8;
+          //^^^^^^^ warn: casting `char` to `u8` may truncate the value
+    let _ = a as u64;
+    let _ = a as usize;
+    let _ = e as u32;
+    let _ = 300 as u8;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn sign_changing_casts() {
+        check_clippy_lite_diagnostics(
+            r#"
+#![warn(clippy::cast_sign_loss, clippy::cast_possible_wrap)]
+fn f(a: i32, b: u32, c: u8, d: usize) {
+    let _ = a as u32;
+          //^^^^^^^^ 💡 warn: casting `i32` to `u32` may lose the sign of the value
+    let _ = a as u64;
+          //^^^^^^^^ 💡 warn: casting `i32` to `u64` may lose the sign of the value
+    let _ = b as i32;
+          //^^^^^^^^ 💡 warn: casting `u32` to `i32` may wrap around the value
+    let _ = d as isize;
+          //^^^^^^^^^^ 💡 warn: casting `usize` to `isize` may wrap around the value
+    let _ = c as i16;
+    let _ = b as i64;
+    let _ = -1 as u32;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn saturating_casts() {
+        check_clippy_lite_diagnostics(
+            r#"
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: f64, b: f32) {
+    let _ = a as i32;
+          //^^^^^^^^ warn: casting `f64` to `i32` rounds toward zero and saturates out-of-range values
+    let _ = b as u8;
+          //^^^^^^^ warn: casting `f32` to `u8` rounds toward zero and saturates out-of-range values
+    let _ = b as f64;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed_by_default() {
+        check_clippy_lite_diagnostics(
+            r#"
+fn f(a: u32, b: f64) {
+    let _ = a as u8;
+    let _ = b as i32;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn enabled_by_lint_group() {
+        check_clippy_lite_diagnostics(
+            r#"
+#[warn(clippy::pedantic)]
+fn f(a: u32) -> u8 {
+    a as u8
+  //^^^^^^^ 💡 warn: casting `u32` to `u8` may truncate the value
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_with_clippy_lite_style() {
+        check_diagnostics(
+            r#"
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: u32) -> u8 {
+    a as u8
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn replace_with_try_from() {
+        check_clippy_lite_fix(
+            r#"
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: u32) -> u8 {
+    (a + 1) as$0 u8
+}
+"#,
+            r#"
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: u32) -> u8 {
+    u8::try_from(a + 1).unwrap()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_try_from_before_edition_2021() {
+        check_no_fix_with_config(
+            DiagnosticsConfig {
+                style: DiagnosticsStyle::ClippyLite,
+                ..DiagnosticsConfig::test_sample()
+            },
+            r#"
+//- /main.rs crate:main edition:2018
+#![warn(clippy::cast_possible_truncation)]
+fn f(a: u32) -> u8 {
+    a as$0 u8
+}
+"#,
+        );
+    }
+}
@@ -4,8 +4,12 @@
     known, AssocItem, HirDisplay, HirFileIdExt, InFile, Type,
 };
 use ide_db::{
-    assists::Assist, famous_defs::FamousDefs, imports::import_assets::item_for_path_search,
-    source_change::SourceChange, use_trivial_constructor::use_trivial_constructor, FxHashMap,
+    assists::Assist,
+    famous_defs::FamousDefs,
+    imports::import_assets::item_for_path_search,
+    source_change::{map_edit_to_original_file, SourceChange},
+    use_trivial_constructor::use_trivial_constructor,
+    FxHashMap,
 };
 use stdx::format_to;
 use syntax::{
@@ -69,17 +73,13 @@ fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::MissingFields) -> Option<Vec<Ass
                 return None;
             }
             let mut builder = TextEdit::builder();
-            if d.file.is_macro() {
-                // we can't map the diff up into the macro input unfortunately, as the macro loses all
-                // whitespace information so the diff wouldn't be applicable no matter what
-                // This has the downside that the cursor will be moved in macros by doing it without a diff
-                // but that is a trade off we can make.
-                // FIXME: this also currently discards a lot of whitespace in the input... we really need a formatter here
-                builder.replace(old_range.range, new_syntax.to_string());
-            } else {
-                algo::diff(old_syntax, new_syntax).into_text_edit(&mut builder);
+            algo::diff(old_syntax, new_syntax).into_text_edit(&mut builder);
+            match map_edit_to_original_file(ctx.sema.db, d.file, &builder.finish()) {
+                Some((file_id, edit)) if file_id == range.file_id => edit,
+                // The diff touches tokens the macro produced itself, so fall back to replacing
+                // the whole field list. This discards the formatting of the macro input.
+                _ => TextEdit::replace(old_range.range, new_syntax.to_string()),
             }
-            builder.finish()
         };
         Some(vec![fix(
             "fill_missing_fields",
@@ -344,7 +344,7 @@ fn here() {}
 macro_rules! id { ($($tt:tt)*) => { $($tt)*}; }
 
 fn main() {
-    let _x = id![Foo {a:42, b: 0 }];
+    let _x = id![Foo { a: 42, b: 0 }];
 }
 
 pub struct Foo { pub a: i32, pub b: i32 }
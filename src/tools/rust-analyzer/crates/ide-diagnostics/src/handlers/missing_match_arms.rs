@@ -974,6 +974,26 @@ fn f(ty: Enum) {
         );
     }
 
+    #[test]
+    fn attr_macro_expansion() {
+        let mut config = DiagnosticsConfig::test_sample();
+        config.proc_macros_enabled = true;
+        config.proc_attr_macros_enabled = true;
+        check_diagnostics_with_config(
+            config,
+            r#"
+//- proc_macros: wrap_body
+#[proc_macros::wrap_body]
+fn f() {
+    let x = 1;
+      //^ warn: unused variable
+    match 0u8 {}
+        //^^^ error: missing match arm: type `u8` is non-empty
+}
+"#,
+        );
+    }
+
     #[test]
     fn unexpected_ty_fndef() {
         cov_mark::check!(validate_match_bailed_out);
@@ -990,6 +1010,57 @@ fn f() {
         );
     }
 
+    #[test]
+    fn foreign_hidden_variants_and_non_exhaustive_enums() {
+        check_diagnostics_no_bails(
+            r#"
+//- /lib.rs crate:lib
+pub enum E { A, #[doc(hidden)] Hidden }
+#[non_exhaustive]
+pub enum N { A }
+pub enum Local { A, #[doc(hidden)] Hidden }
+fn local(e: Local) {
+    match e { Local::A => {} }
+        //^ error: missing match arm: `Hidden` not covered
+}
+//- /main.rs crate:main deps:lib
+fn f(e: lib::E, n: lib::N) {
+    match e { lib::E::A => {} }
+        //^ error: missing match arm: `_` not covered
+    match e { lib::E::Hidden => {} }
+        //^ error: missing match arm: `A` not covered
+    match e { lib::E::A => {}, lib::E::Hidden => {} }
+    match n { lib::N::A => {} }
+        //^ error: missing match arm: `_` not covered
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn const_block() {
+        check_diagnostics_no_bails(
+            r#"
+fn f(b: bool) -> u8 {
+    let _ = const {
+        match true {
+            //^^^^ error: missing match arm: `false` not covered
+            true => 1,
+        }
+    };
+    match b {
+        const { !false } => 1,
+        false => 2,
+    };
+    match b {
+        //^ error: missing match arm: `false` not covered
+        const { 1 + 1 == 2 } => 1,
+    }
+}
+"#,
+        );
+    }
+
     mod rust_unstable {
         use super::*;
 
@@ -0,0 +1,185 @@
+use hir::{Adt, HasSource, InFile, Struct};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    source_change::SourceChange,
+};
+use syntax::{
+    ast::{self, edit::IndentLevel, HasVisibility},
+    AstNode, SyntaxKind,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: missing-repr-transparent
+//
+// This diagnostic is triggered when a struct wrapping a single non-zero-sized field is passed by
+// value through a function with a foreign ABI, like `extern "C" fn` or a function of an `extern`
+// block, without being `#[repr(transparent)]`. Without it, the struct isn't guaranteed to be
+// passed the same way as its field.
+pub(crate) fn missing_repr_transparent(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let source_file = ctx.sema.parse(file_id);
+    for func in source_file.syntax().descendants().filter_map(ast::Fn::cast) {
+        if !has_foreign_abi(&func) {
+            continue;
+        }
+        let params =
+            func.param_list().into_iter().flat_map(|it| it.params()).filter_map(|it| it.ty());
+        let ret = func.ret_type().and_then(|it| it.ty());
+        for ty in params.chain(ret) {
+            let Some(strukt) = single_field_wrapper(ctx, &ty) else { continue };
+            let db = ctx.sema.db;
+            acc.push(
+                Diagnostic::new(
+                    DiagnosticCode::Ra("missing-repr-transparent", Severity::WeakWarning),
+                    format!(
+                        "`{}` is used in an FFI signature but isn't `#[repr(transparent)]`",
+                        strukt.name(db).display(db)
+                    ),
+                    FileRange { file_id, range: ty.syntax().text_range() },
+                )
+                .with_fixes(add_repr_transparent(
+                    ctx,
+                    strukt,
+                    ty.syntax().text_range(),
+                )),
+            );
+        }
+    }
+}
+
+/// Whether `func` is called or defined with an ABI other than the Rust one.
+pub(super) fn has_foreign_abi(func: &ast::Fn) -> bool {
+    let abi = match func.abi() {
+        Some(abi) => abi,
+        None => {
+            let Some(extern_block) = func
+                .syntax()
+                .parent()
+                .filter(|it| it.kind() == SyntaxKind::EXTERN_ITEM_LIST)
+                .and_then(|it| it.parent())
+                .and_then(ast::ExternBlock::cast)
+            else {
+                return false;
+            };
+            let Some(abi) = extern_block.abi() else { return true };
+            abi
+        }
+    };
+    // `extern fn` without an ABI string uses the C ABI.
+    abi.abi_string().and_then(|it| it.value().map(|it| it != "Rust")).unwrap_or(true)
+}
+
+/// The local struct `ty` refers to when it has no `#[repr]` and a single non-zero-sized field,
+/// all of its other fields being zero-sized and 1-aligned, like `PhantomData`.
+pub(super) fn single_field_wrapper(ctx: &DiagnosticsContext<'_>, ty: &ast::Type) -> Option<Struct> {
+    let db = ctx.sema.db;
+    let ty = ctx.sema.resolve_type(ty)?;
+    let Some(Adt::Struct(strukt)) = ty.as_adt() else { return None };
+    if strukt.repr(db).is_some() || !strukt.module(db).krate().origin(db).is_local() {
+        return None;
+    }
+    let mut non_zero_sized = 0;
+    for (_, field_ty) in ty.fields(db) {
+        let layout = field_ty.layout(db).ok()?;
+        if layout.size() != 0 {
+            non_zero_sized += 1;
+        } else if layout.align() != 1 {
+            return None;
+        }
+    }
+    (non_zero_sized == 1).then_some(strukt)
+}
+
+fn add_repr_transparent(
+    ctx: &DiagnosticsContext<'_>,
+    strukt: Struct,
+    trigger_range: syntax::TextRange,
+) -> Option<Vec<Assist>> {
+    let InFile { file_id, value: strukt } = strukt.source(ctx.sema.db)?;
+    let file_id = file_id.file_id()?;
+    // Keep the attribute after the doc comments and the other attributes of the struct.
+    let offset = match strukt.visibility() {
+        Some(vis) => vis.syntax().text_range().start(),
+        None => strukt.struct_token()?.text_range().start(),
+    };
+    let indent = IndentLevel::from_node(strukt.syntax());
+    Some(vec![fix(
+        "add_repr_transparent",
+        "Add `#[repr(transparent)]`",
+        SourceChange::from_text_edit(
+            file_id,
+            TextEdit::insert(offset, format!("#[repr(transparent)]\n{indent}")),
+        ),
+        trigger_range,
+    )])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_diagnostics, check_fix};
+
+    #[test]
+    fn ffi_signatures() {
+        check_diagnostics(
+            r#"
+//- minicore: phantom_data
+use core::marker::PhantomData;
+struct Handle(u32);
+struct Tagged<T> { raw: u64, _tag: PhantomData<T> }
+struct Tag;
+struct Pair(u32, u32);
+#[repr(transparent)]
+struct Fine(u32);
+#[repr(C)]
+struct AlsoFine(u32);
+extern "C" {
+    fn open() -> Handle;
+               //^^^^^^ 💡 weak: `Handle` is used in an FFI signature but isn't `#[repr(transparent)]`
+    fn close(handle: Handle, fine: Fine, also: AlsoFine, by_ref: &Handle);
+                   //^^^^^^ 💡 weak: `Handle` is used in an FFI signature but isn't `#[repr(transparent)]`
+    fn split(pair: Pair);
+                 //^^^^ warn: `Pair` is not FFI-safe: this struct has unspecified layout
+}
+extern "C" fn callback(_tagged: Tagged<Tag>) {}
+                              //^^^^^^^^^^^ 💡 weak: `Tagged` is used in an FFI signature but isn't `#[repr(transparent)]`
+extern fn no_abi_string(_handle: Handle) {}
+                               //^^^^^^ 💡 weak: `Handle` is used in an FFI signature but isn't `#[repr(transparent)]`
+extern "Rust" fn rust_abi(_handle: Handle) {}
+fn plain(_handle: Handle) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_repr_transparent() {
+        check_fix(
+            r#"
+//- minicore: derive, copy, clone
+mod ffi {
+    /// A file handle.
+    #[derive(Clone, Copy)]
+    pub struct Handle(u32);
+}
+extern "C" {
+    fn open() -> ffi::Handle$0;
+}
+"#,
+            r#"
+mod ffi {
+    /// A file handle.
+    #[derive(Clone, Copy)]
+    #[repr(transparent)]
+    pub struct Handle(u32);
+}
+extern "C" {
+    fn open() -> ffi::Handle;
+}
+"#,
+        );
+    }
+}
@@ -1,99 +1,73 @@
-use hir::db::ExpandDatabase;
-use hir::HirFileIdExt;
-use ide_db::{assists::Assist, source_change::SourceChange};
-use syntax::{ast, SyntaxNode};
-use syntax::{match_ast, AstNode};
+use hir::{db::ExpandDatabase, HirFileIdExt};
+use ide_db::{
+    assists::Assist,
+    base_db::Edition,
+    source_change::{map_edit_to_original_file, SourceChange},
+    syntax_helpers::node_ext::pick_best_node_to_add_unsafe_block,
+};
 use text_edit::TextEdit;
 
-use crate::{fix, Diagnostic, DiagnosticCode, DiagnosticsContext};
+use crate::{fix, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
 
 // Diagnostic: missing-unsafe
 //
 // This diagnostic is triggered if an operation marked as `unsafe` is used outside of an `unsafe` function or block.
+//
+// Inside of an `unsafe` function, the operation is reported by the `unsafe_op_in_unsafe_fn` lint
+// instead, which is only a warning by default from edition 2024 on, or when
+// `rust-analyzer.diagnostics.unsafeOpInUnsafeFn.enable` is set.
 pub(crate) fn missing_unsafe(ctx: &DiagnosticsContext<'_>, d: &hir::MissingUnsafe) -> Diagnostic {
-    Diagnostic::new_with_syntax_node_ptr(
-        ctx,
-        DiagnosticCode::RustcHardError("E0133"),
-        "this operation is unsafe and requires an unsafe function or block",
-        d.expr.map(|it| it.into()),
-    )
-    .with_fixes(fixes(ctx, d))
+    let (code, message) = if d.only_lint {
+        (
+            DiagnosticCode::RustcLint("unsafe_op_in_unsafe_fn"),
+            "this operation is unsafe and requires an unsafe block, even in an unsafe function",
+        )
+    } else {
+        (
+            DiagnosticCode::RustcHardError("E0133"),
+            "this operation is unsafe and requires an unsafe function or block",
+        )
+    };
+    let mut diag =
+        Diagnostic::new_with_syntax_node_ptr(ctx, code, message, d.expr.map(|it| it.into()))
+            .with_fixes(fixes(ctx, d));
+    if d.only_lint && !ctx.config.unsafe_op_in_unsafe_fn && edition(ctx, d) < Edition::Edition2024 {
+        diag.severity = Severity::Allow;
+    }
+    diag
 }
 
-fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::MissingUnsafe) -> Option<Vec<Assist>> {
-    // The fixit will not work correctly for macro expansions, so we don't offer it in that case.
-    if d.expr.file_id.is_macro() {
-        return None;
-    }
+fn edition(ctx: &DiagnosticsContext<'_>, d: &hir::MissingUnsafe) -> Edition {
+    let file_id = d.expr.file_id.original_file(ctx.sema.db);
+    ctx.sema.to_module_def(file_id).map_or(Edition::CURRENT, |it| it.krate().edition(ctx.sema.db))
+}
 
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::MissingUnsafe) -> Option<Vec<Assist>> {
     let root = ctx.sema.db.parse_or_expand(d.expr.file_id);
     let expr = d.expr.value.to_node(&root);
 
     let node_to_add_unsafe_block = pick_best_node_to_add_unsafe_block(&expr)?;
 
-    let replacement = format!("unsafe {{ {} }}", node_to_add_unsafe_block.text());
-    let edit = TextEdit::replace(node_to_add_unsafe_block.text_range(), replacement);
-    let source_change =
-        SourceChange::from_text_edit(d.expr.file_id.original_file(ctx.sema.db), edit);
-    Some(vec![fix("add_unsafe", "Add unsafe block", source_change, expr.syntax().text_range())])
-}
-
-// Pick the first ancestor expression of the unsafe `expr` that is not a
-// receiver of a method call, a field access, the left-hand side of an
-// assignment, or a reference. As all of those cases would incur a forced move
-// if wrapped which might not be wanted. That is:
-// - `unsafe_expr.foo` -> `unsafe { unsafe_expr.foo }`
-// - `unsafe_expr.foo.bar` -> `unsafe { unsafe_expr.foo.bar }`
-// - `unsafe_expr.foo()` -> `unsafe { unsafe_expr.foo() }`
-// - `unsafe_expr.foo.bar()` -> `unsafe { unsafe_expr.foo.bar() }`
-// - `unsafe_expr += 1` -> `unsafe { unsafe_expr += 1 }`
-// - `&unsafe_expr` -> `unsafe { &unsafe_expr }`
-// - `&&unsafe_expr` -> `unsafe { &&unsafe_expr }`
-fn pick_best_node_to_add_unsafe_block(unsafe_expr: &ast::Expr) -> Option<SyntaxNode> {
-    // The `unsafe_expr` might be:
-    // - `ast::CallExpr`: call an unsafe function
-    // - `ast::MethodCallExpr`: call an unsafe method
-    // - `ast::PrefixExpr`: dereference a raw pointer
-    // - `ast::PathExpr`: access a static mut variable
-    for (node, parent) in
-        unsafe_expr.syntax().ancestors().zip(unsafe_expr.syntax().ancestors().skip(1))
-    {
-        match_ast! {
-            match parent {
-                // If the `parent` is a `MethodCallExpr`, that means the `node`
-                // is the receiver of the method call, because only the receiver
-                // can be a direct child of a method call. The method name
-                // itself is not an expression but a `NameRef`, and an argument
-                // is a direct child of an `ArgList`.
-                ast::MethodCallExpr(_) => continue,
-                ast::FieldExpr(_) => continue,
-                ast::RefExpr(_) => continue,
-                ast::BinExpr(it) => {
-                    // Check if the `node` is the left-hand side of an
-                    // assignment, if so, we don't want to wrap it in an unsafe
-                    // block, e.g. `unsafe_expr += 1`
-                    let is_left_hand_side_of_assignment = {
-                        if let Some(ast::BinaryOp::Assignment { .. }) = it.op_kind() {
-                            it.lhs().map(|lhs| lhs.syntax().text_range().contains_range(node.text_range())).unwrap_or(false)
-                        } else {
-                            false
-                        }
-                    };
-                    if !is_left_hand_side_of_assignment {
-                        return Some(node);
-                    }
-                },
-                _ => { return Some(node); }
-
-            }
-        }
-    }
-    None
+    let range = node_to_add_unsafe_block.text_range();
+    let mut builder = TextEdit::builder();
+    builder.insert(range.start(), "unsafe { ".to_owned());
+    builder.insert(range.end(), " }".to_owned());
+    // Inside macro calls this edits the macro input, which only works if the node consists of
+    // tokens the user wrote.
+    let (file_id, edit) =
+        map_edit_to_original_file(ctx.sema.db, d.expr.file_id, &builder.finish())?;
+    let source_change = SourceChange::from_text_edit(file_id, edit);
+    let trigger_range =
+        d.expr.map(|it| it.text_range()).original_node_file_range_rooted(ctx.sema.db);
+    Some(vec![fix("add_unsafe", "Add unsafe block", source_change, trigger_range.range)])
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::{check_diagnostics, check_fix, check_no_fix};
+    use crate::{
+        tests::{check_diagnostics, check_diagnostics_with_config, check_fix, check_no_fix},
+        DiagnosticsConfig,
+    };
 
     #[test]
     fn missing_unsafe_diagnostic_with_raw_ptr() {
@@ -108,6 +82,20 @@ fn main() {
         )
     }
 
+    #[test]
+    fn missing_unsafe_diagnostic_in_const_block() {
+        check_diagnostics(
+            r#"
+const unsafe fn f() -> u8 { 0 }
+fn main() {
+    let _x = const { unsafe { f() } };
+    let _y = const { f() };
+                   //^^^💡 error: this operation is unsafe and requires an unsafe function or block
+}
+"#,
+        )
+    }
+
     #[test]
     fn missing_unsafe_diagnostic_with_unsafe_call() {
         check_diagnostics(
@@ -163,6 +151,28 @@ fn main() {
         );
     }
 
+    #[test]
+    fn missing_unsafe_diagnostic_with_union_field() {
+        check_diagnostics(
+            r#"
+union U {
+    a: u32,
+    b: f32,
+}
+
+fn main() {
+    let mut u = U { a: 0 };
+    u.b = 1.0;
+    let _x = u.a;
+           //^^^💡 error: this operation is unsafe and requires an unsafe function or block
+    unsafe {
+        let _x = u.a;
+    }
+}
+"#,
+        );
+    }
+
     #[test]
     fn no_missing_unsafe_diagnostic_with_safe_intrinsic() {
         check_diagnostics(
@@ -459,6 +469,48 @@ fn main() {
         )
     }
 
+    #[test]
+    fn add_unsafe_block_in_macro_input() {
+        check_fix(
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* }; }
+unsafe fn foo(x: u8) -> u8 {
+    x
+}
+
+fn main() {
+    let _x = id!(foo$0( 1 ));
+}
+"#,
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* }; }
+unsafe fn foo(x: u8) -> u8 {
+    x
+}
+
+fn main() {
+    let _x = id!(unsafe { foo( 1 ) });
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn no_add_unsafe_block_for_macro_generated_call() {
+        check_no_fix(
+            r#"
+macro_rules! call_foo { () => { foo() }; }
+unsafe fn foo() -> u8 {
+    0
+}
+
+fn main() {
+    let _x = call_foo$0!();
+}
+"#,
+        )
+    }
+
     #[test]
     fn unsafe_expr_in_macro_call() {
         check_no_fix(
@@ -473,4 +525,84 @@ fn main() {
             "#,
         )
     }
+
+    #[test]
+    fn unsafe_op_in_unsafe_fn_is_allowed_before_edition_2024() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main edition:2021
+unsafe fn foo() {}
+unsafe fn bar(ptr: *const u8) -> u8 {
+    foo();
+    *ptr
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsafe_op_in_unsafe_fn_warns_in_edition_2024() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main edition:2024
+unsafe fn foo() {}
+unsafe fn bar(ptr: *const u8) -> u8 {
+    foo();
+  //^^^^^💡 warn: this operation is unsafe and requires an unsafe block, even in an unsafe function
+    unsafe { *ptr }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsafe_op_in_unsafe_fn_can_be_enabled() {
+        check_diagnostics_with_config(
+            DiagnosticsConfig { unsafe_op_in_unsafe_fn: true, ..DiagnosticsConfig::test_sample() },
+            r#"
+//- /main.rs crate:main edition:2021
+unsafe fn bar(ptr: *const u8) -> u8 {
+    *ptr
+  //^^^^💡 warn: this operation is unsafe and requires an unsafe block, even in an unsafe function
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn unsafe_op_in_unsafe_fn_respects_lint_attributes() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main edition:2021
+#![warn(unsafe_op_in_unsafe_fn)]
+unsafe fn bar(ptr: *const u8) -> u8 {
+    *ptr
+  //^^^^💡 warn: this operation is unsafe and requires an unsafe block, even in an unsafe function
+}
+#[allow(unsafe_op_in_unsafe_fn)]
+unsafe fn baz(ptr: *const u8) -> u8 {
+    *ptr
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn add_unsafe_block_in_unsafe_fn() {
+        check_fix(
+            r#"
+//- /main.rs crate:main edition:2024
+unsafe fn foo(_ptr: *const u8) -> u8 { 0 }
+unsafe fn bar(ptr: *const u8) -> u8 {
+    foo(ptr$0) + 1
+}
+"#,
+            r#"
+unsafe fn foo(_ptr: *const u8) -> u8 { 0 }
+unsafe fn bar(ptr: *const u8) -> u8 {
+    unsafe { foo(ptr) } + 1
+}
+"#,
+        );
+    }
 }
@@ -0,0 +1,236 @@
+use hir::{
+    HasSource, InFile, MethodViolationCode, ModuleDef, ObjectSafetyViolation, PathResolution, Trait,
+};
+use ide_db::base_db::{FileId, FileRange};
+use stdx::format_to;
+use syntax::{
+    ast::{self, HasName},
+    AstNode,
+};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: non-object-safe-trait
+//
+// This diagnostic is triggered when `dyn Trait` is used with a trait that can't be made into a
+// trait object. It lists every method and associated item that breaks object safety along with
+// the rule it breaks, and points at each of them.
+pub(crate) fn non_object_safe_trait(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let source_file = ctx.sema.parse(file_id);
+    for dyn_trait in source_file.syntax().descendants().filter_map(ast::DynTraitType::cast) {
+        let Some(trait_) = principal_trait(ctx, &dyn_trait) else { continue };
+        let violations = trait_.object_safety_violations(ctx.sema.db);
+        if violations.is_empty() {
+            continue;
+        }
+
+        let mut message = format!(
+            "the trait `{}` cannot be made into an object:\n",
+            trait_.name(ctx.sema.db).display(ctx.sema.db)
+        );
+        let mut related = Vec::new();
+        for violation in violations {
+            let (reason, range) = describe(ctx, trait_, violation);
+            format_to!(message, "- {reason}\n");
+            related.extend(range.map(|range| (range, reason)));
+        }
+
+        let mut diagnostic = Diagnostic::new(
+            DiagnosticCode::RustcHardError("E0038"),
+            message,
+            FileRange { file_id, range: dyn_trait.syntax().text_range() },
+        );
+        for (range, label) in related {
+            diagnostic = diagnostic.with_related(range, label);
+        }
+        acc.push(diagnostic);
+    }
+}
+
+/// The first trait of the bounds that isn't an auto trait.
+fn principal_trait(ctx: &DiagnosticsContext<'_>, dyn_trait: &ast::DynTraitType) -> Option<Trait> {
+    dyn_trait.type_bound_list()?.bounds().find_map(|bound| {
+        let ast::Type::PathType(path_type) = bound.ty()? else { return None };
+        match ctx.sema.resolve_path(&path_type.path()?)? {
+            PathResolution::Def(ModuleDef::Trait(it)) if !it.is_auto(ctx.sema.db) => Some(it),
+            _ => None,
+        }
+    })
+}
+
+/// The reason `violation` breaks object safety, and where to point at for it.
+fn describe(
+    ctx: &DiagnosticsContext<'_>,
+    trait_: Trait,
+    violation: ObjectSafetyViolation,
+) -> (String, Option<FileRange>) {
+    let db = ctx.sema.db;
+    let name_range = |src: Option<InFile<ast::AnyHasName>>| {
+        let src = src?;
+        let name = src.value.name()?;
+        Some(src.with_value(name.syntax()).original_file_range(db))
+    };
+    match violation {
+        ObjectSafetyViolation::SizedSelf => {
+            ("it requires `Self: Sized`".to_owned(), name_range(any_name(trait_.source(db))))
+        }
+        ObjectSafetyViolation::SelfReferential => (
+            "it uses `Self` as a type parameter of a supertrait".to_owned(),
+            name_range(any_name(trait_.source(db))),
+        ),
+        ObjectSafetyViolation::Method(func, code) => {
+            let name = func.name(db);
+            let name = name.display(db);
+            let reason = match code {
+                MethodViolationCode::StaticMethod => {
+                    format!("associated function `{name}` has no `self` parameter")
+                }
+                MethodViolationCode::ReferencesSelfInput => {
+                    format!("method `{name}` references the `Self` type in a parameter")
+                }
+                MethodViolationCode::ReferencesSelfOutput => {
+                    format!("method `{name}` references the `Self` type in its return type")
+                }
+                MethodViolationCode::ReferencesImplTraitInTrait => {
+                    format!("method `{name}` returns an `impl Trait` type")
+                }
+                MethodViolationCode::AsyncFn => format!("method `{name}` is `async`"),
+                MethodViolationCode::Generic => {
+                    format!("method `{name}` has generic type parameters")
+                }
+                MethodViolationCode::UndispatchableReceiver => {
+                    format!("method `{name}`'s `self` parameter cannot be dispatched on")
+                }
+            };
+            (reason, name_range(any_name(func.source(db))))
+        }
+        ObjectSafetyViolation::AssocConst(konst) => {
+            let name = konst.name(db).map(|it| it.display(db).to_string()).unwrap_or_default();
+            (
+                format!("it contains the associated `const` `{name}`"),
+                name_range(any_name(konst.source(db))),
+            )
+        }
+        ObjectSafetyViolation::GAT(alias) => (
+            format!("it contains the generic associated type `{}`", alias.name(db).display(db)),
+            name_range(any_name(alias.source(db))),
+        ),
+        ObjectSafetyViolation::HasNonSafeSuperTrait(super_trait) => (
+            format!(
+                "its supertrait `{}` cannot be made into an object",
+                super_trait.name(db).display(db)
+            ),
+            name_range(any_name(super_trait.source(db))),
+        ),
+    }
+}
+
+fn any_name<N: HasName>(src: Option<InFile<N>>) -> Option<InFile<ast::AnyHasName>> {
+    src.map(|it| it.map(ast::AnyHasName::new))
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::{base_db::SourceDatabaseExt, RootDatabase};
+    use test_fixture::WithFixture;
+
+    use crate::{tests::check_diagnostics, AssistResolveStrategy, Diagnostic, DiagnosticsConfig};
+
+    /// The single diagnostic of `ra_fixture` along with the file text.
+    fn single_diagnostic(ra_fixture: &str) -> (Diagnostic, String) {
+        let (db, file_id) = RootDatabase::with_single_file(ra_fixture);
+        let mut diagnostics = crate::diagnostics(
+            &db,
+            &DiagnosticsConfig::test_sample(),
+            &AssistResolveStrategy::All,
+            file_id,
+        );
+        assert_eq!(diagnostics.len(), 1, "expected a single diagnostic, got {diagnostics:#?}");
+        (diagnostics.pop().unwrap(), db.file_text(file_id).to_string())
+    }
+
+    #[test]
+    fn lists_every_violation() {
+        let (diagnostic, _) = single_diagnostic(
+            r#"
+//- minicore: sized
+trait Base {
+    fn new() -> i32;
+}
+trait Shape: Base {
+    const SIDES: usize;
+    fn area(&self) -> f64;
+    fn scaled<T>(&self, by: T) -> f64;
+    fn dup(&self) -> Self;
+    fn fine<T>(&self, t: T) where Self: Sized;
+}
+fn draw(_: &dyn Shape) {}
+"#,
+        );
+        assert_eq!(
+            diagnostic.message,
+            "the trait `Shape` cannot be made into an object:
+- it contains the associated `const` `SIDES`
+- method `scaled` has generic type parameters
+- method `dup` references the `Self` type in its return type
+- associated function `new` has no `self` parameter
+"
+        );
+    }
+
+    #[test]
+    fn sized_self_and_auto_traits() {
+        check_diagnostics(
+            r#"
+//- minicore: sized, send
+trait Cloned: Sized {}
+fn f(_: &(dyn Send + Cloned)) {}
+        //^^^^^^^^^^^^^^^^^ error: the trait `Cloned` cannot be made into an object:
+                        //| - it requires `Self: Sized`
+"#,
+        );
+    }
+
+    #[test]
+    fn object_safe_traits() {
+        check_diagnostics(
+            r#"
+//- minicore: sized, send
+trait Draw {
+    fn draw(&self);
+    fn new() -> Self where Self: Sized;
+}
+fn f(_: &dyn Draw, _: &(dyn Draw + Send), _: &dyn Send) {}
+"#,
+        );
+    }
+
+    #[test]
+    fn points_at_offending_items() {
+        let (diagnostic, text) = single_diagnostic(
+            r#"
+trait Goal {
+    fn generic<T>(&self);
+    type Item<'a>;
+}
+fn f(_: &dyn Goal) {}
+"#,
+        );
+        let related: Vec<_> = diagnostic
+            .related
+            .iter()
+            .map(|(range, label)| (&text[range.range], label.as_str()))
+            .collect();
+        assert_eq!(
+            related,
+            [
+                ("generic", "method `generic` has generic type parameters"),
+                ("Item", "it contains the generic associated type `Item`"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use hir::Adt;
+use ide_db::base_db::{FileId, FileRange};
+use syntax::{
+    ast::{self, HasAttrs},
+    AstNode,
+};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: repr-inhibits-niche
+//
+// This diagnostic is triggered when the `#[repr]` of an enum makes it larger than it would be
+// without it, because the explicit tag prevents storing the discriminant in a niche of a field,
+// like the null value of a reference in `Option<&T>`.
+pub(crate) fn repr_inhibits_niche(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let db = ctx.sema.db;
+    let source_file = ctx.sema.parse(file_id);
+    for enum_ in source_file.syntax().descendants().filter_map(ast::Enum::cast) {
+        let Some(repr) = enum_.attrs().find(|it| it.simple_name().as_deref() == Some("repr"))
+        else {
+            continue;
+        };
+        let Some(def) = ctx.sema.to_def(&enum_) else { continue };
+        let adt = Adt::Enum(def);
+        let (Ok(layout), Ok(default_layout)) = (adt.layout(db), adt.layout_with_default_repr(db))
+        else {
+            continue;
+        };
+        // A tag of size 0 means the discriminant is stored in a niche.
+        if default_layout.enum_tag_size() != Some(0) || layout.size() <= default_layout.size() {
+            continue;
+        }
+        acc.push(Diagnostic::new(
+            DiagnosticCode::Ra("repr-inhibits-niche", Severity::WeakWarning),
+            format!(
+                "`{}` takes {} bytes instead of {} because its `#[repr]` prevents the niche optimization",
+                def.name(db).display(db),
+                layout.size(),
+                default_layout.size(),
+            ),
+            FileRange { file_id, range: repr.syntax().text_range() },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn repr_inhibits_niche() {
+        check_diagnostics(
+            r#"
+mod m {
+    #[repr(u8)]
+  //^^^^^^^^^^^ weak: `Ref` takes 16 bytes instead of 8 because its `#[repr]` prevents the niche optimization
+    enum Ref {
+        Some(&'static u32),
+        None,
+    }
+    #[repr(C)]
+  //^^^^^^^^^^ weak: `Flag` takes 8 bytes instead of 1 because its `#[repr]` prevents the niche optimization
+    enum Flag {
+        Set(bool),
+        Unset,
+    }
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_niche_to_lose() {
+        check_diagnostics(
+            r#"
+#[repr(u8)]
+enum Fieldless {
+    A,
+    B,
+}
+#[repr(u8)]
+enum NoNiche {
+    A(u32),
+    B(u32),
+}
+enum Default {
+    Some(&'static u32),
+    None,
+}
+#[repr(u8)]
+enum Generic<T> {
+    Some(T),
+    None,
+}
+"#,
+        );
+    }
+}
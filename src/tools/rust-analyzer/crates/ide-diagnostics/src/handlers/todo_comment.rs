@@ -0,0 +1,72 @@
+use ide_db::{
+    base_db::{FileId, FileRange},
+    todo_comments::todo_comments,
+};
+use syntax::SourceFile;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsConfig, Severity};
+
+// Diagnostic: todo-comment
+//
+// This diagnostic is triggered by `TODO`, `FIXME` and `HACK` comments, when
+// `rust-analyzer.diagnostics.todoComments.enable` is set. It's reported as a hint so that the
+// markers can be browsed in the problems panel of the editor.
+pub(crate) fn todo_comment(
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+    source_file: &SourceFile,
+    config: &DiagnosticsConfig,
+) {
+    if !config.todo_comments {
+        return;
+    }
+    for comment in todo_comments(source_file) {
+        let mut message = format!("{}: {}", comment.kind.as_str(), comment.text);
+        match (&comment.owner, comment.date) {
+            (Some(owner), Some(date)) => message += &format!(" ({owner}, {date})"),
+            (Some(owner), None) => message += &format!(" ({owner})"),
+            (None, Some(date)) => message += &format!(" ({date})"),
+            (None, None) => (),
+        }
+        acc.push(Diagnostic::new(
+            DiagnosticCode::Ra("todo-comment", Severity::WeakWarning),
+            message,
+            FileRange { file_id, range: comment.range },
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics, check_diagnostics_with_config},
+        DiagnosticsConfig,
+    };
+
+    #[test]
+    fn reports_todo_comments() {
+        let mut config = DiagnosticsConfig::test_sample();
+        config.todo_comments = true;
+        check_diagnostics_with_config(
+            config,
+            r#"
+/* TODO: handle errors */
+ //^^^^^^^^^^^^^^^^^^^ weak: TODO: handle errors
+fn f() {
+    /* FIXME(alice, 2024-01-31): this is quadratic */
+     //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ weak: FIXME: this is quadratic (alice, 2024-01-31)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn disabled_by_default() {
+        check_diagnostics(
+            r#"
+// TODO: handle errors
+fn f() {}
+"#,
+        );
+    }
+}
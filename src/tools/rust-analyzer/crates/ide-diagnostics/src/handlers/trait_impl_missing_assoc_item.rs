@@ -1,17 +1,22 @@
 use hir::InFile;
+use ide_db::{assists::Assist, source_change::SourceChange, traits::add_trait_assoc_items_to_impl};
 use itertools::Itertools;
-use syntax::{ast, AstNode};
+use syntax::{algo, ast, AstNode, SyntaxKind::*, T};
+use text_edit::TextEdit;
 
-use crate::{adjusted_display_range, Diagnostic, DiagnosticCode, DiagnosticsContext};
+use crate::{adjusted_display_range, fix, Diagnostic, DiagnosticCode, DiagnosticsContext};
 
 // Diagnostic: trait-impl-missing-assoc_item
 //
-// Diagnoses missing trait items in a trait impl.
+// Diagnoses missing trait items in a trait impl, listing the signature of each missing item.
 pub(crate) fn trait_impl_missing_assoc_item(
     ctx: &DiagnosticsContext<'_>,
     d: &hir::TraitImplMissingAssocItems,
 ) -> Diagnostic {
     let missing = d.missing.iter().format_with(", ", |(name, item), f| {
+        if let Some(signature) = signature(ctx, *item) {
+            return f(&format_args!("`{signature}`"));
+        }
         f(&match *item {
             hir::AssocItem::Function(_) => "`fn ",
             hir::AssocItem::Const(_) => "`const ",
@@ -29,11 +34,60 @@ pub(crate) fn trait_impl_missing_assoc_item(
             &|impl_| impl_.trait_().map(|t| t.syntax().text_range()),
         ),
     )
+    .with_fixes(fixes(ctx, d))
+}
+
+/// The signature of a trait item as written in the trait, without its attributes and default.
+fn signature(ctx: &DiagnosticsContext<'_>, item: hir::AssocItem) -> Option<String> {
+    let node = source(ctx, item)?.value;
+    let signature = node
+        .syntax()
+        .children_with_tokens()
+        .filter(|it| !matches!(it.kind(), ATTR | COMMENT))
+        .take_while(|it| !matches!(it.kind(), BLOCK_EXPR | T![;] | T![=]))
+        .map(|it| it.to_string())
+        .collect::<String>();
+    Some(signature.split_whitespace().join(" "))
+}
+
+fn source(ctx: &DiagnosticsContext<'_>, item: hir::AssocItem) -> Option<InFile<ast::AssocItem>> {
+    match item {
+        hir::AssocItem::Function(it) => ctx.sema.source(it).map(|it| it.map(ast::AssocItem::Fn)),
+        hir::AssocItem::Const(it) => ctx.sema.source(it).map(|it| it.map(ast::AssocItem::Const)),
+        hir::AssocItem::TypeAlias(it) => {
+            ctx.sema.source(it).map(|it| it.map(ast::AssocItem::TypeAlias))
+        }
+    }
+}
+
+fn fixes(ctx: &DiagnosticsContext<'_>, d: &hir::TraitImplMissingAssocItems) -> Option<Vec<Assist>> {
+    let db = ctx.sema.db;
+    let file_id = d.file_id.file_id()?;
+    let impl_def = d.impl_.to_node(&ctx.sema.parse_or_expand(d.file_id));
+    let trait_ = ctx.sema.to_def(&impl_def)?.trait_(db)?;
+    let target_scope = ctx.sema.scope(impl_def.syntax())?;
+
+    let items = d.missing.iter().filter_map(|&(_, item)| source(ctx, item)).collect::<Vec<_>>();
+    if items.is_empty() {
+        return None;
+    }
+
+    let new_impl_def = impl_def.clone_for_update();
+    add_trait_assoc_items_to_impl(&ctx.sema, &items, trait_, &new_impl_def, target_scope);
+    let mut builder = TextEdit::builder();
+    algo::diff(impl_def.syntax(), new_impl_def.syntax()).into_text_edit(&mut builder);
+
+    Some(vec![fix(
+        "add_missing_impl_members",
+        "Implement missing members",
+        SourceChange::from_text_edit(file_id, builder.finish()),
+        impl_def.syntax().text_range(),
+    )])
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::tests::check_diagnostics;
+    use crate::tests::{check_diagnostics, check_fix};
 
     #[test]
     fn trait_with_default_value() {
@@ -65,13 +119,13 @@ fn f() {}
 }
 
 impl Trait for () {
-   //^^^^^ error: not all trait items implemented, missing: `const C`
+   //^^^^^ 💡 error: not all trait items implemented, missing: `const C: ()`
     type T = ();
     fn f() {}
 }
 
 impl Trait for () {
-   //^^^^^ error: not all trait items implemented, missing: `const C`, `type T`, `fn f`
+   //^^^^^ 💡 error: not all trait items implemented, missing: `const C: ()`, `type T`, `fn f()`
 }
 
 "#,
@@ -95,18 +149,18 @@ fn f() {}
 }
 
 impl Trait for () {
-   //^^^^^ error: not all trait items implemented, missing: `const C`
+   //^^^^^ 💡 error: not all trait items implemented, missing: `const C: ()`
     type T = ();
     fn f() {}
 }
 
 impl Trait for () {
-   //^^^^^ error: not all trait items implemented, missing: `const C`
+   //^^^^^ 💡 error: not all trait items implemented, missing: `const C: ()`
      type T = ();
  }
 
 impl Trait for () {
-   //^^^^^ error: not all trait items implemented, missing: `const C`
+   //^^^^^ 💡 error: not all trait items implemented, missing: `const C: ()`
 }
 
 "#,
@@ -126,4 +180,78 @@ impl !Trait for () {}
 "#,
         )
     }
+
+    #[test]
+    fn signatures() {
+        check_diagnostics(
+            r#"
+//- minicore: iterator
+pub trait Trait<T> {
+    type Item<'a>: Clone where Self: 'a;
+    fn iter(&self) -> impl Iterator<Item = T>;
+    /// Returns the element at `index`.
+    #[must_use]
+    fn get<U: Copy>(&self, index: U)
+        -> Option<T>
+    where
+        T: Clone;
+}
+
+struct S;
+impl Trait<u32> for S {}
+   //^^^^^^^^^^ 💡 error: not all trait items implemented, missing: `type Item<'a>: Clone where Self: 'a`, `fn iter(&self) -> impl Iterator<Item = T>`, `fn get<U: Copy>(&self, index: U) -> Option<T> where T: Clone`
+"#,
+        );
+    }
+
+    #[test]
+    fn fix_inserts_stubs() {
+        check_fix(
+            r#"
+//- minicore: iterator
+mod m {
+    pub struct Wrapper<T>(pub T);
+    pub trait Trait<T> {
+        const C: usize;
+        type Item<'a> where Self: 'a;
+        fn iter(&self) -> impl Iterator<Item = Wrapper<T>>;
+        fn get<U: Copy>(&self, index: U) -> Option<T> where T: Clone;
+        fn provided(&self) {}
+    }
+}
+
+struct S;
+impl m::Trait<u32> for S$0 {
+    const C: usize = 0;
+}
+"#,
+            r#"
+mod m {
+    pub struct Wrapper<T>(pub T);
+    pub trait Trait<T> {
+        const C: usize;
+        type Item<'a> where Self: 'a;
+        fn iter(&self) -> impl Iterator<Item = Wrapper<T>>;
+        fn get<U: Copy>(&self, index: U) -> Option<T> where T: Clone;
+        fn provided(&self) {}
+    }
+}
+
+struct S;
+impl m::Trait<u32> for S {
+    const C: usize = 0;
+
+    type Item<'a> where Self: 'a;
+
+    fn iter(&self) -> impl Iterator<Item = m::Wrapper<u32>> {
+        todo!()
+    }
+
+    fn get<U: Copy>(&self, index: U) -> Option<u32> where u32: Clone {
+        todo!()
+    }
+}
+"#,
+        );
+    }
 }
@@ -4,20 +4,34 @@
 
 // Diagnostic: trait-impl-orphan
 //
-// Only traits defined in the current crate can be implemented for arbitrary types
+// Only traits defined in the current crate can be implemented for arbitrary types, and type
+// parameters must be covered by a local type.
 pub(crate) fn trait_impl_orphan(
     ctx: &DiagnosticsContext<'_>,
     d: &hir::TraitImplOrphan,
 ) -> Diagnostic {
-    Diagnostic::new_with_syntax_node_ptr(
-        ctx,
-        DiagnosticCode::RustcHardError("E0117"),
-        "only traits defined in the current crate can be implemented for arbitrary types"
-            .to_owned(),
-        InFile::new(d.file_id, d.impl_.into()),
-    )
-    // Not yet checked for false positives
-    .experimental()
+    let (code, message) = match d.uncovered_ty_param {
+        Some(param) => {
+            let param = param.name(ctx.sema.db);
+            let param = param.display(ctx.sema.db);
+            (
+                DiagnosticCode::RustcHardError("E0210"),
+                format!(
+                    "type parameter `{param}` must be covered by another type when it appears before the first local type, \
+                     consider wrapping it in a local newtype like `struct Wrapper<{param}>({param});`"
+                ),
+            )
+        }
+        None => (
+            DiagnosticCode::RustcHardError("E0117"),
+            "only traits defined in the current crate can be implemented for arbitrary types, \
+             consider defining a local trait or wrapping the type in a local newtype"
+                .to_owned(),
+        ),
+    };
+    Diagnostic::new_with_syntax_node_ptr(ctx, code, message, InFile::new(d.file_id, d.impl_.into()))
+        // Not yet checked for false positives
+        .experimental()
 }
 
 #[cfg(test)]
@@ -36,7 +50,7 @@ pub trait Foo {}
 struct LocalType;
 trait LocalTrait {}
   impl foo::Foo for bar::Bar {}
-//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types, consider defining a local trait or wrapping the type in a local newtype
 impl foo::Foo for LocalType {}
 impl LocalTrait for bar::Bar {}
 "#,
@@ -55,15 +69,15 @@ pub trait Foo<T> {}
 struct LocalType<T>;
 trait LocalTrait<T> {}
   impl<T> foo::Foo<T> for bar::Bar<T> {}
-//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: type parameter `T` must be covered by another type when it appears before the first local type, consider wrapping it in a local newtype like `struct Wrapper<T>(T);`
 
   impl<T> foo::Foo<T> for bar::Bar<LocalType<T>> {}
-//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: type parameter `T` must be covered by another type when it appears before the first local type, consider wrapping it in a local newtype like `struct Wrapper<T>(T);`
 
   impl<T> foo::Foo<LocalType<T>> for bar::Bar<T> {}
 
   impl<T> foo::Foo<bar::Bar<LocalType<T>>> for bar::Bar<LocalType<T>> {}
-//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types, consider defining a local trait or wrapping the type in a local newtype
 "#,
         );
     }
@@ -82,7 +96,7 @@ pub trait Foo<T> {}
 //- /main.rs crate:main deps:foo,bar
 struct LocalType;
   impl<T> foo::Foo<T> for bar::Box<T> {}
-//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: only traits defined in the current crate can be implemented for arbitrary types
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: type parameter `T` must be covered by another type when it appears before the first local type, consider wrapping it in a local newtype like `struct Wrapper<T>(T);`
   impl<T> foo::Foo<T> for &LocalType {}
   impl<T> foo::Foo<T> for bar::Box<LocalType> {}
 "#,
@@ -101,6 +115,30 @@ pub trait Foo<T> {}
 trait LocalTrait {}
 impl<T> foo::Foo<T> for dyn LocalTrait {}
 impl<T> foo::Foo<dyn LocalTrait> for Bar {}
+"#,
+        );
+    }
+
+    #[test]
+    fn uncovered_ty_param() {
+        check_diagnostics(
+            r#"
+//- /foo.rs crate:foo
+pub trait Foo<T> {}
+//- /bar.rs crate:bar
+#[lang = "owned_box"]
+#[fundamental]
+pub struct Box<T>(T);
+//- /main.rs crate:main deps:foo,bar
+struct LocalType;
+  impl<T> foo::Foo<LocalType> for T {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: type parameter `T` must be covered by another type when it appears before the first local type, consider wrapping it in a local newtype like `struct Wrapper<T>(T);`
+
+  impl<T> foo::Foo<LocalType> for &bar::Box<T> {}
+//^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^ error: type parameter `T` must be covered by another type when it appears before the first local type, consider wrapping it in a local newtype like `struct Wrapper<T>(T);`
+
+impl<T> foo::Foo<T> for LocalType {}
+impl<T> foo::Foo<T> for &bar::Box<LocalType> {}
 "#,
         );
     }
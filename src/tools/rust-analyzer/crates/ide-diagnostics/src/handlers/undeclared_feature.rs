@@ -0,0 +1,123 @@
+use hir::InFile;
+use ide_db::{
+    base_db::{FileId, FileRange},
+    crate_features::{cargo_features, cfg_features_in_file},
+    FxHashSet,
+};
+
+use syntax::AstNode;
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: undeclared-feature
+//
+// This diagnostic is triggered when a `feature = "..."` predicate in `#[cfg]`, `#[cfg_attr]` or
+// `cfg!` names a feature that the crate's `Cargo.toml` doesn't declare.
+pub(crate) fn undeclared_feature(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let sema = &ctx.sema;
+    let Some(krate) = sema.to_module_def(file_id).map(|it| it.krate()) else { return };
+    // Crates without a manifest, like the ones of `rust-project.json`, declare no features.
+    let Some(features) = cargo_features(sema.db, krate) else { return };
+    let potential_cfg = krate.potential_cfg(sema.db);
+    let declared = features
+        .declarations
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain(potential_cfg.get_cfg_values("feature").map(|it| it.as_str()))
+        .collect::<FxHashSet<_>>();
+
+    let source_file = sema.parse(file_id);
+    for (name, range) in cfg_features_in_file(sema, file_id) {
+        if declared.contains(name.as_str()) {
+            continue;
+        }
+        let node = match source_file.syntax().covering_element(range) {
+            syntax::NodeOrToken::Node(it) => it,
+            syntax::NodeOrToken::Token(it) => it.parent().unwrap_or(source_file.syntax().clone()),
+        };
+        acc.push(
+            Diagnostic::new(
+                DiagnosticCode::RustcLint("unexpected_cfgs"),
+                format!("feature `{name}` is not declared in `Cargo.toml`"),
+                FileRange { file_id, range },
+            )
+            .with_main_node(InFile::new(file_id.into(), node)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::check_diagnostics;
+
+    #[test]
+    fn undeclared_features() {
+        check_diagnostics(
+            r#"
+//- /src/lib.rs crate:foo
+#[rustc_builtin_macro]
+macro_rules! cfg {}
+#[cfg(feature = "std")]
+fn f() -> bool {
+    cfg!(any(feature = "alloc", feature = "serde"))
+                                         //^^^^^ warn: feature `serde` is not declared in `Cargo.toml`
+}
+#[cfg_attr(not(feature = "nightly"), allow(dead_code))]
+                        //^^^^^^^ warn: feature `nightly` is not declared in `Cargo.toml`
+struct S;
+#[doc(feature = "nightly")]
+struct T;
+//- /Cargo.toml
+[features]
+default = ["std"]
+std = ["alloc"]
+alloc = []
+nightly-features = []
+"#,
+        );
+    }
+
+    #[test]
+    fn undeclared_features_in_cfg_macro() {
+        check_diagnostics(
+            r#"
+//- /src/lib.rs crate:foo
+#[rustc_builtin_macro]
+macro_rules! cfg {}
+fn f() -> bool {
+    cfg!(feature = "std")
+                  //^^^ warn: feature `std` is not declared in `Cargo.toml`
+}
+//- /Cargo.toml
+[features]
+"#,
+        );
+    }
+
+    #[test]
+    fn no_manifest() {
+        check_diagnostics(
+            r#"
+#[cfg(feature = "std")]
+fn f() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn allowed() {
+        check_diagnostics(
+            r#"
+//- /src/lib.rs crate:foo
+#![allow(unexpected_cfgs)]
+#[cfg(feature = "std")]
+fn f() {}
+//- /Cargo.toml
+"#,
+        );
+    }
+}
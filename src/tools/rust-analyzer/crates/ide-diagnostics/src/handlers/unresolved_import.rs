@@ -1,13 +1,25 @@
-use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+use hir::db::ExpandDatabase;
+
+use crate::{
+    handlers::cfg_disabled_path::cfg_disabled_path_diagnostic, Diagnostic, DiagnosticCode,
+    DiagnosticsContext,
+};
 
 // Diagnostic: unresolved-import
 //
 // This diagnostic is triggered if rust-analyzer is unable to resolve a path in
 // a `use` declaration.
+//
+// When the path names an item that exists, but is disabled by a `#[cfg]`, the diagnostic says
+// which cfg that is. If it is a feature of a dependency, it offers to enable the feature in
+// `Cargo.toml`.
 pub(crate) fn unresolved_import(
     ctx: &DiagnosticsContext<'_>,
     d: &hir::UnresolvedImport,
 ) -> Diagnostic {
+    if let Some(diagnostic) = cfg_disabled_import(ctx, d) {
+        return diagnostic;
+    }
     Diagnostic::new_with_syntax_node_ptr(
         ctx,
         DiagnosticCode::RustcHardError("E0432"),
@@ -21,9 +33,20 @@ pub(crate) fn unresolved_import(
     .experimental()
 }
 
+fn cfg_disabled_import(
+    ctx: &DiagnosticsContext<'_>,
+    d: &hir::UnresolvedImport,
+) -> Option<Diagnostic> {
+    let root = ctx.sema.db.parse_or_expand(d.decl.file_id);
+    let path = d.decl.value.to_node(&root).path()?;
+    cfg_disabled_path_diagnostic(ctx, &path, d.decl.map(|it| it.into()), |_| {
+        (DiagnosticCode::RustcHardError("E0432"), "unresolved import")
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::tests::check_diagnostics;
+    use crate::tests::{check_diagnostics, check_fix};
 
     #[test]
     fn unresolved_import() {
@@ -85,6 +108,68 @@ mod m {
     use super::doesnotexist;
       //^^^^^^^^^^^^^^^^^^^ error: unresolved import
 }
+"#,
+        );
+    }
+
+    #[test]
+    fn import_disabled_by_dependency_feature() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main deps:dep
+use dep::gated::Thing;
+  //^^^^^^^^^^^^^^^^^ error: unresolved import: `gated` needs the `gated` feature of `dep`
+use dep::Always;
+//- /dep/lib.rs crate:dep
+pub struct Always;
+#[cfg(feature = "gated")]
+pub mod gated {
+    pub struct Thing;
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn import_disabled_by_cfg() {
+        check_diagnostics(
+            r#"
+//- /main.rs crate:main
+use self::testing::helper;
+  //^^^^^^^^^^^^^^^^^^^^^ error: unresolved import: `testing` is inactive because test is disabled
+
+#[cfg(test)]
+mod testing {
+    pub fn helper() {}
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn enable_feature_in_manifest() {
+        check_fix(
+            r#"
+//- /main.rs crate:main deps:dep
+use dep::gated::Thing$0;
+//- /Cargo.toml
+[package]
+name = "main"
+
+[dependencies]
+dep = "1.0"
+//- /dep/lib.rs crate:dep
+#[cfg(feature = "gated")]
+pub mod gated {
+    pub struct Thing;
+}
+"#,
+            r#"
+[package]
+name = "main"
+
+[dependencies]
+dep = { version = "1.0", features = ["gated"] }
 "#,
         );
     }
@@ -0,0 +1,172 @@
+use either::Either;
+use hir::{CallableKind, HasSource, HirDisplay};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    famous_defs::FamousDefs,
+};
+use syntax::{
+    ast::{self, HasName},
+    match_ast, AstNode,
+};
+
+use crate::{Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: unsized-generic-arg
+//
+// This diagnostic is triggered when a generic function is called with a type whose size isn't
+// known at compile time, like `str`, `[T]` or `dyn Trait`, for a type parameter that has the
+// implicit `Sized` bound. As the bound isn't written anywhere, it points at the type parameter
+// instead, where `?Sized` would relax it.
+pub(crate) fn unsized_generic_arg(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let sema = &ctx.sema;
+    let db = sema.db;
+    let Some(module) = sema.to_module_def(file_id) else { return };
+    let Some(sized) = FamousDefs(sema, module.krate()).core_marker_Sized() else { return };
+    let source_file = sema.parse(file_id);
+    for node in source_file.syntax().descendants() {
+        let (callable, range) = match_ast! {
+            match node {
+                ast::PathExpr(it) => {
+                    let callable =
+                        sema.type_of_expr(&it.clone().into()).and_then(|it| it.original.as_callable(db));
+                    (callable, it.syntax().text_range())
+                },
+                ast::MethodCallExpr(it) => {
+                    let range = it.name_ref().map_or(it.syntax().text_range(), |it| it.syntax().text_range());
+                    (sema.resolve_method_call_as_callable(&it), range)
+                },
+                _ => continue,
+            }
+        };
+        let Some(callable) = callable else { continue };
+        let CallableKind::Function(func) = callable.kind() else { continue };
+        for (param, ty) in callable.type_param_args(db) {
+            if ty.contains_unknown()
+                || !param.is_implicitly_sized(db)
+                || ty.impls_trait(db, sized, &[])
+            {
+                continue;
+            }
+            let mut diagnostic = Diagnostic::new(
+                DiagnosticCode::RustcHardError("E0277"),
+                format!(
+                    "the size for values of type `{}` cannot be known at compilation time",
+                    ty.display(db)
+                ),
+                FileRange { file_id, range },
+            );
+            let bound = param.merge().source(db).and_then(|src| match &src.value {
+                Either::Left(ast::TypeOrConstParam::Type(it)) => {
+                    let name = it.name()?;
+                    Some(src.with_value(name.syntax()).original_file_range(db))
+                }
+                _ => None,
+            });
+            if let Some(bound) = bound {
+                diagnostic = diagnostic.with_related(
+                    bound,
+                    format!(
+                        "required by the implicit `Sized` bound of `{}` in `{}`",
+                        param.name(db).display(db),
+                        func.name(db).display(db)
+                    ),
+                );
+            }
+            acc.push(diagnostic);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::{assists::AssistResolveStrategy, base_db::SourceDatabaseExt, RootDatabase};
+    use test_fixture::WithFixture;
+
+    use crate::{tests::check_diagnostics, DiagnosticsConfig};
+
+    #[test]
+    fn unsized_args() {
+        check_diagnostics(
+            r#"
+//- minicore: sized, slice
+trait Trait {}
+fn f<T>(_: &T) {}
+fn g<T: ?Sized>(_: &T) {}
+fn h<T>(_: &T) where T: ?Sized {}
+fn i<T: Clone>(_: &T) {}
+struct S;
+impl S {
+    fn m<T>(&self, _: &T) {}
+}
+
+fn main(s: &str, t: &[u8], d: &dyn Trait) {
+    f(s);
+  //^ error: the size for values of type `str` cannot be known at compilation time
+    f(t);
+  //^ error: the size for values of type `[u8]` cannot be known at compilation time
+    f(d);
+  //^ error: the size for values of type `dyn Trait` cannot be known at compilation time
+    f::<str>(s);
+  //^^^^^^^^ error: the size for values of type `str` cannot be known at compilation time
+    S.m(s);
+    //^ error: the size for values of type `str` cannot be known at compilation time
+    f(&s);
+    g(s);
+    h(t);
+    i(&1);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn caller_type_params() {
+        check_diagnostics(
+            r#"
+//- minicore: sized
+fn f<T>(_: &T) {}
+fn g<T, U: ?Sized>(t: &T, u: &U) {
+    f(t);
+    f(u);
+  //^ error: the size for values of type `U` cannot be known at compilation time
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn points_at_type_param() {
+        let (db, file_id) = RootDatabase::with_single_file(
+            r#"
+//- minicore: sized
+fn takes_ref<Value: Copy>(_: &Value) {}
+fn main() {
+    takes_ref("str");
+}
+"#,
+        );
+        let diagnostics = crate::diagnostics(
+            &db,
+            &DiagnosticsConfig::test_sample(),
+            &AssistResolveStrategy::All,
+            file_id,
+        );
+        let [diagnostic] = diagnostics.as_slice() else {
+            panic!("expected a single diagnostic, got {diagnostics:#?}");
+        };
+        let text = db.file_text(file_id);
+        let related: Vec<_> = diagnostic
+            .related
+            .iter()
+            .map(|(range, label)| (&text[range.range], label.as_str()))
+            .collect();
+        assert_eq!(
+            related,
+            [("Value", "required by the implicit `Sized` bound of `Value` in `takes_ref`")]
+        );
+    }
+}
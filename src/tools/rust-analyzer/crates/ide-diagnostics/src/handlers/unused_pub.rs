@@ -0,0 +1,324 @@
+use hir::{AsAssocItem, AssocItemContainer, HasAttrs, InFile, Module, Semantics};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    defs::Definition,
+    search::FileReference,
+    source_change::SourceChange,
+    RootDatabase,
+};
+use syntax::{
+    ast::{self, HasName, HasVisibility},
+    match_ast, AstNode, SyntaxKind, TextRange,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Assist, Diagnostic, DiagnosticCode, DiagnosticsContext, Severity};
+
+// Diagnostic: unused-pub
+//
+// This diagnostic is triggered when a `pub` item is never used outside of its crate, or a
+// `pub(crate)` item is never used outside of its module, anywhere in the workspace. Its
+// visibility could be reduced to shrink the API surface of the crate.
+//
+// Only reported when `rust-analyzer.diagnostics.unusedPub.enable` is set, as it searches for the
+// references of every such item.
+pub(crate) fn unused_pub(ctx: &DiagnosticsContext<'_>, acc: &mut Vec<Diagnostic>, file_id: FileId) {
+    let source_file = ctx.sema.parse(file_id);
+    for node in source_file.syntax().descendants() {
+        let (kind, def) = match_ast! {
+            match node {
+                ast::Fn(it) => ("function", fn_def(&ctx.sema, &it)),
+                ast::Struct(it) => ("struct", ctx.sema.to_def(&it).map(|it| Definition::Adt(it.into()))),
+                ast::Enum(it) => ("enum", ctx.sema.to_def(&it).map(|it| Definition::Adt(it.into()))),
+                ast::Union(it) => ("union", ctx.sema.to_def(&it).map(|it| Definition::Adt(it.into()))),
+                ast::Trait(it) => ("trait", ctx.sema.to_def(&it).map(Definition::Trait)),
+                ast::Const(it) => ("constant", ctx.sema.to_def(&it).map(Definition::Const)),
+                ast::Static(it) => ("static", ctx.sema.to_def(&it).map(Definition::Static)),
+                ast::TypeAlias(it) => ("type alias", ctx.sema.to_def(&it).map(Definition::TypeAlias)),
+                _ => continue,
+            }
+        };
+        let Some(def) = def.filter(|def| !is_inherited_visibility(ctx.sema.db, *def)) else {
+            continue;
+        };
+        let Some(visibility) =
+            ast::AnyHasVisibility::cast(node.clone()).and_then(|it| it.visibility())
+        else {
+            continue;
+        };
+        let Some(name) = ast::AnyHasName::cast(node.clone()).and_then(|it| it.name()) else {
+            continue;
+        };
+        let is_crate_visible = match visibility.kind() {
+            ast::VisibilityKind::Pub => false,
+            ast::VisibilityKind::PubCrate => true,
+            _ => continue,
+        };
+        let Some(module) = def.module(ctx.sema.db) else { continue };
+        let Some(usages) = Usages::of(&ctx.sema, def, module) else { continue };
+
+        let name_range = name.syntax().text_range();
+        let make_private = || {
+            // also remove the whitespace after the visibility
+            let mut range = visibility.syntax().text_range();
+            if let Some(ws) = visibility
+                .syntax()
+                .next_sibling_or_token()
+                .filter(|it| it.kind() == SyntaxKind::WHITESPACE)
+            {
+                range = TextRange::new(range.start(), ws.text_range().end());
+            }
+            fix(
+                "make_private",
+                "Make private",
+                SourceChange::from_text_edit(file_id, TextEdit::delete(range)),
+                name_range,
+            )
+        };
+        let (message, fixes): (_, Vec<Assist>) = if is_crate_visible {
+            // unused `pub(crate)` items are reported by the `dead_code` diagnostic
+            if usages.none || usages.outside_module {
+                continue;
+            }
+            (
+                format!("`pub(crate)` {kind} `{name}` is never used outside of its module"),
+                vec![make_private()],
+            )
+        } else {
+            if usages.outside_crate {
+                continue;
+            }
+            let reduce = fix(
+                "reduce_visibility_to_crate",
+                "Reduce visibility to `pub(crate)`",
+                SourceChange::from_text_edit(
+                    file_id,
+                    TextEdit::replace(visibility.syntax().text_range(), "pub(crate)".to_owned()),
+                ),
+                name_range,
+            );
+            let mut fixes = vec![reduce];
+            if !usages.outside_module {
+                fixes.push(make_private());
+            }
+            (format!("`pub` {kind} `{name}` is never used outside of its crate"), fixes)
+        };
+        acc.push(
+            Diagnostic::new(
+                DiagnosticCode::Ra("unused-pub", Severity::WeakWarning),
+                message,
+                FileRange { file_id, range: visibility.syntax().text_range() },
+            )
+            .with_main_node(InFile::new(file_id.into(), node.clone()))
+            .with_fixes(Some(fixes)),
+        );
+    }
+}
+
+fn fn_def(sema: &Semantics<'_, RootDatabase>, fn_: &ast::Fn) -> Option<Definition> {
+    let db = sema.db;
+    if fn_.syntax().parent().map_or(false, |it| it.kind() == SyntaxKind::EXTERN_ITEM_LIST) {
+        return None;
+    }
+    let func = sema.to_def(fn_)?;
+    let attrs = func.attrs(db);
+    // These are used by the compiler or linked to by name rather than through paths.
+    if func.is_main(db)
+        || func.is_test(db)
+        || func.is_bench(db)
+        || attrs.is_proc_macro()
+        || attrs.is_proc_macro_attribute()
+        || attrs.is_proc_macro_derive()
+        || attrs.by_key("no_mangle").exists()
+        || attrs.by_key("export_name").exists()
+    {
+        return None;
+    }
+    Some(Definition::Function(func))
+}
+
+/// Whether the visibility of `def` is that of its trait, so that it can't be reduced on its own.
+fn is_inherited_visibility(db: &RootDatabase, def: Definition) -> bool {
+    let Some(assoc) = def.as_assoc_item(db) else { return false };
+    match assoc.container(db) {
+        AssocItemContainer::Trait(_) => true,
+        AssocItemContainer::Impl(impl_) => impl_.trait_(db).is_some(),
+    }
+}
+
+/// Where the references of an item are, relative to the item.
+struct Usages {
+    none: bool,
+    outside_module: bool,
+    outside_crate: bool,
+}
+
+impl Usages {
+    /// Searches for the references of `def`, until one outside of the crate of `module` is found.
+    fn of(sema: &Semantics<'_, RootDatabase>, def: Definition, module: Module) -> Option<Usages> {
+        let db = sema.db;
+        let mut usages = Usages { none: true, outside_module: false, outside_crate: false };
+        let mut unknown = false;
+        def.usages(sema).search(&mut |_, reference: FileReference| {
+            usages.none = false;
+            let Some(ref_module) = reference
+                .name
+                .syntax()
+                .parent()
+                .and_then(|node| sema.scope(&node))
+                .map(|scope| scope.module())
+            else {
+                unknown = true;
+                return true;
+            };
+            // re-exports make the item as visible as the `use`, so treat them like uses in other
+            // crates
+            let in_reexport = reference
+                .name
+                .syntax()
+                .ancestors()
+                .any(|node| ast::Use::cast(node).map_or(false, |it| it.visibility().is_some()));
+            if in_reexport || ref_module.krate() != module.krate() {
+                usages.outside_module = true;
+                usages.outside_crate = true;
+                return true;
+            }
+            if !ref_module.path_to_root(db).contains(&module) {
+                usages.outside_module = true;
+            }
+            false
+        });
+        (!unknown).then_some(usages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        tests::{check_diagnostics_with_config, check_nth_fix_with_config},
+        DiagnosticsConfig,
+    };
+
+    fn config() -> DiagnosticsConfig {
+        DiagnosticsConfig { unused_pub: true, ..DiagnosticsConfig::test_sample() }
+    }
+
+    #[test]
+    fn pub_items_used_only_in_crate() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+//- /lib.rs crate:lib
+pub mod inner {
+    pub fn used_outside() {}
+    pub fn used_in_crate() {}
+  //^^^ 💡 weak: `pub` function `used_in_crate` is never used outside of its crate
+    pub struct Unused;
+  //^^^ 💡 weak: `pub` struct `Unused` is never used outside of its crate
+    pub(crate) fn used_in_module() {}
+  //^^^^^^^^^^ 💡 weak: `pub(crate)` function `used_in_module` is never used outside of its module
+    pub(crate) fn used_in_parent() {}
+    pub(crate) fn unused() {}
+    fn private() {
+        used_in_module();
+    }
+}
+
+fn f() {
+    inner::used_in_crate();
+    inner::used_in_parent();
+}
+//- /main.rs crate:main deps:lib
+fn main() {
+    lib::inner::used_outside();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn reexported_and_special_items() {
+        check_diagnostics_with_config(
+            config(),
+            r#"
+//- /lib.rs crate:lib
+mod inner {
+    pub struct Reexported;
+}
+pub use inner::Reexported;
+
+pub mod m {
+    pub trait Trait {
+  //^^^ 💡 weak: `pub` trait `Trait` is never used outside of its crate
+        fn method(&self);
+    }
+
+    impl Trait for () {
+        fn method(&self) {}
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn exported() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_reported_when_disabled() {
+        check_diagnostics_with_config(
+            DiagnosticsConfig::test_sample(),
+            r#"
+pub fn unused() {}
+"#,
+        );
+    }
+
+    #[test]
+    fn reduce_to_crate_visibility() {
+        check_nth_fix_with_config(
+            config(),
+            0,
+            r#"
+mod m {
+    pub fn f$0() {}
+}
+fn main() {
+    m::f();
+}
+"#,
+            r#"
+mod m {
+    pub(crate) fn f() {}
+}
+fn main() {
+    m::f();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn make_private() {
+        check_nth_fix_with_config(
+            config(),
+            0,
+            r#"
+mod m {
+    pub(crate) fn f$0() {}
+    fn g() {
+        f();
+    }
+}
+"#,
+            r#"
+mod m {
+    fn f() {}
+    fn g() {
+        f();
+    }
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,264 @@
+use hir::{AsAssocItem, HirDisplay, InFile, Type};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    famous_defs::FamousDefs,
+    source_change::SourceChange,
+};
+use syntax::{
+    ast::{self, HasArgList},
+    AstNode,
+};
+use text_edit::TextEdit;
+
+use crate::{fix, Diagnostic, DiagnosticCode, DiagnosticsContext};
+
+// Diagnostic: useless-conversion
+//
+// This diagnostic is triggered when a value is converted to the type it already has, with
+// `.into()`, `.as_ref()` or an `as` cast. Types are compared after inference, so conversions to a
+// type alias of the same type are reported as well. It is only reported with
+// `rust-analyzer.diagnostics.style` set to `"clippy-lite"`.
+pub(crate) fn useless_conversion(
+    ctx: &DiagnosticsContext<'_>,
+    acc: &mut Vec<Diagnostic>,
+    file_id: FileId,
+) {
+    let sema = &ctx.sema;
+    let Some(module) = sema.to_module_def(file_id) else { return };
+    let famous_defs = FamousDefs(sema, module.krate());
+    let source_file = sema.parse(file_id);
+    for expr in source_file.syntax().descendants().filter_map(ast::Expr::cast) {
+        let conversion = match &expr {
+            ast::Expr::MethodCallExpr(call) => method_conversion(ctx, &famous_defs, call),
+            ast::Expr::CastExpr(cast) => cast_conversion(ctx, cast),
+            _ => None,
+        };
+        let Some((lint, message, inner)) = conversion else { continue };
+
+        let range = expr.syntax().text_range();
+        let edit = TextEdit::replace(range, inner.syntax().text().to_string());
+        acc.push(
+            Diagnostic::new(DiagnosticCode::Clippy(lint), message, FileRange { file_id, range })
+                .with_main_node(InFile::new(file_id.into(), expr.syntax().clone()))
+                .with_fixes(Some(vec![fix(
+                    "remove_useless_conversion",
+                    "Remove the conversion",
+                    SourceChange::from_text_edit(file_id, edit),
+                    range,
+                )])),
+        );
+    }
+}
+
+/// Checks for `x.into()` and `x.as_ref()` that return `x` as it is.
+fn method_conversion(
+    ctx: &DiagnosticsContext<'_>,
+    famous_defs: &FamousDefs<'_, '_>,
+    call: &ast::MethodCallExpr,
+) -> Option<(&'static str, String, ast::Expr)> {
+    if call.arg_list()?.args().next().is_some() {
+        return None;
+    }
+    let db = ctx.sema.db;
+    let trait_ = ctx.sema.resolve_method_call(call)?.as_assoc_item(db)?;
+    let trait_ = trait_.container_or_implemented_trait(db)?;
+    let receiver = call.receiver()?;
+    // Autoref'd receivers can't simply take the place of the call, so only the unadjusted type
+    // of the receiver is compared.
+    let receiver_ty = ctx.sema.type_of_expr(&receiver)?.original;
+    let ty = ctx.sema.type_of_expr(&call.clone().into())?.original;
+    if !is_same_type(&receiver_ty, &ty) {
+        return None;
+    }
+    let (lint, message) = if Some(trait_) == famous_defs.core_convert_Into() {
+        ("useless_conversion", format!("useless conversion to the same type: `{}`", ty.display(db)))
+    } else if Some(trait_) == famous_defs.core_convert_AsRef() {
+        ("useless_asref", "this call to `as_ref` does nothing".to_owned())
+    } else {
+        return None;
+    };
+    Some((lint, message, receiver))
+}
+
+/// Checks for `x as T` where `x` already has type `T`.
+fn cast_conversion(
+    ctx: &DiagnosticsContext<'_>,
+    cast: &ast::CastExpr,
+) -> Option<(&'static str, String, ast::Expr)> {
+    let inner = cast.expr()?;
+    // The type of an unsuffixed literal may be inferred from the cast itself.
+    if is_unsuffixed_literal(&inner) {
+        return None;
+    }
+    let inner_ty = ctx.sema.type_of_expr(&inner)?.original;
+    let ty = ctx.sema.type_of_expr(&cast.clone().into())?.original;
+    if !is_same_type(&inner_ty, &ty) {
+        return None;
+    }
+    let ty = ty.display(ctx.sema.db);
+    Some((
+        "unnecessary_cast",
+        format!("casting to the same type is unnecessary (`{ty}` -> `{ty}`)"),
+        inner,
+    ))
+}
+
+fn is_same_type(from: &Type, to: &Type) -> bool {
+    from == to && !to.contains_unknown()
+}
+
+/// Whether `expr` is a number literal without a suffix, possibly negated or parenthesized.
+fn is_unsuffixed_literal(expr: &ast::Expr) -> bool {
+    match expr {
+        ast::Expr::Literal(it) => match it.kind() {
+            ast::LiteralKind::IntNumber(it) => it.suffix().is_none(),
+            ast::LiteralKind::FloatNumber(it) => it.suffix().is_none(),
+            _ => false,
+        },
+        ast::Expr::PrefixExpr(it) if it.op_kind() == Some(ast::UnaryOp::Neg) => {
+            it.expr().map_or(false, |it| is_unsuffixed_literal(&it))
+        }
+        ast::Expr::ParenExpr(it) => it.expr().map_or(false, |it| is_unsuffixed_literal(&it)),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{check_clippy_lite_diagnostics, check_clippy_lite_fix, check_diagnostics};
+
+    #[test]
+    fn into_same_type() {
+        check_clippy_lite_diagnostics(
+            r#"
+//- minicore: from
+struct S;
+type Alias = S;
+fn f(s: S, t: Alias) -> (S, S) {
+    (s.into(), t.into())
+   //^^^^^^^^ 💡 weak: useless conversion to the same type: `S`
+             //^^^^^^^^ 💡 weak: useless conversion to the same type: `S`
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn into_other_type() {
+        check_clippy_lite_diagnostics(
+            r#"
+//- minicore: from
+struct S;
+struct T;
+impl From<S> for T {
+    fn from(_: S) -> T { T }
+}
+fn f(s: S) -> T {
+    s.into()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn as_ref_same_type() {
+        check_clippy_lite_diagnostics(
+            r#"
+//- minicore: as_ref
+struct S;
+impl AsRef<S> for S {
+    fn as_ref(&self) -> &S { self }
+}
+struct T;
+impl AsRef<S> for T {
+    fn as_ref(&self) -> &S { &S }
+}
+fn f(s: &S, t: &T) {
+    let _: &S = s.as_ref();
+              //^^^^^^^^^^ 💡 weak: this call to `as_ref` does nothing
+    let _: &S = t.as_ref();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn cast_same_type() {
+        check_clippy_lite_diagnostics(
+            r#"
+fn f(x: i32, y: u8, p: *const u8) {
+    let _ = x as i32;
+          //^^^^^^^^ 💡 weak: casting to the same type is unnecessary (`i32` -> `i32`)
+    let _ = y as i32;
+    let _ = p as *const u8;
+          //^^^^^^^^^^^^^^ 💡 weak: casting to the same type is unnecessary (`*const u8` -> `*const u8`)
+    let _ = 1 as i64;
+    let _ = -1.0 as f32;
+    let _ = 1i64 as i64;
+          //^^^^^^^^^^^ 💡 weak: casting to the same type is unnecessary (`i64` -> `i64`)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn inherent_methods_are_not_conversions() {
+        check_clippy_lite_diagnostics(
+            r#"
+struct S;
+impl S {
+    fn into(self) -> S { self }
+}
+fn f(s: S) -> S {
+    s.into()
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn only_with_clippy_lite_style() {
+        check_diagnostics(
+            r#"
+fn f(x: i32) -> i32 {
+    x as i32
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_into() {
+        check_clippy_lite_fix(
+            r#"
+//- minicore: from
+struct S;
+fn f(s: S) -> S {
+    s.in$0to()
+}
+"#,
+            r#"
+struct S;
+fn f(s: S) -> S {
+    s
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn remove_cast() {
+        check_clippy_lite_fix(
+            r#"
+fn f(a: i32, b: i32) -> i32 {
+    (a + b) as i32$0 * 2
+}
+"#,
+            r#"
+fn f(a: i32, b: i32) -> i32 {
+    (a + b) * 2
+}
+"#,
+        );
+    }
+}
@@ -26,12 +26,18 @@
 #![warn(rust_2018_idioms, unused_lifetimes)]
 
 mod handlers {
+    pub(crate) mod array_index_out_of_bounds;
+    pub(crate) mod array_pattern_len_mismatch;
     pub(crate) mod break_outside_of_loop;
+    pub(crate) mod dead_code;
     pub(crate) mod expected_function;
     pub(crate) mod inactive_code;
     pub(crate) mod incoherent_impl;
+    pub(crate) mod incompatible_msrv;
     pub(crate) mod incorrect_case;
     pub(crate) mod invalid_derive_target;
+    pub(crate) mod iter_nth_zero;
+    pub(crate) mod lossy_cast;
     pub(crate) mod macro_error;
     pub(crate) mod malformed_derive;
     pub(crate) mod mismatched_arg_count;
@@ -63,13 +69,26 @@ mod handlers {
     pub(crate) mod unresolved_method;
     pub(crate) mod unresolved_module;
     pub(crate) mod unresolved_proc_macro;
+    pub(crate) mod unused_pub;
     pub(crate) mod unused_variables;
 
     // The handlers below are unusual, the implement the diagnostics as well.
+    pub(crate) mod cfg_disabled_path;
+    pub(crate) mod clashing_extern_declarations;
     pub(crate) mod field_shorthand;
+    pub(crate) mod improper_ctypes;
+    pub(crate) mod inconsistent_digit_grouping;
     pub(crate) mod json_is_not_rust;
+    pub(crate) mod leftover_macro;
+    pub(crate) mod missing_repr_transparent;
+    pub(crate) mod non_object_safe_trait;
+    pub(crate) mod repr_inhibits_niche;
+    pub(crate) mod todo_comment;
+    pub(crate) mod undeclared_feature;
     pub(crate) mod unlinked_file;
+    pub(crate) mod unsized_generic_arg;
     pub(crate) mod useless_braces;
+    pub(crate) mod useless_conversion;
 }
 
 #[cfg(test)]
@@ -90,7 +109,7 @@ mod handlers {
 use stdx::never;
 use syntax::{
     ast::{self, AstNode},
-    AstPtr, SyntaxNode, SyntaxNodePtr, TextRange,
+    AstPtr, SyntaxError, SyntaxNode, SyntaxNodePtr, TextRange,
 };
 
 // FIXME: Make this an enum
@@ -141,6 +160,8 @@ pub struct Diagnostic {
     pub fixes: Option<Vec<Assist>>,
     // The node that will be affected by `#[allow]` and similar attributes.
     pub main_node: Option<InFile<SyntaxNode>>,
+    /// Secondary locations that help explain the diagnostic, with a label for each.
+    pub related: Vec<(FileRange, String)>,
 }
 
 impl Diagnostic {
@@ -163,6 +184,7 @@ fn new(code: DiagnosticCode, message: impl Into<String>, range: FileRange) -> Di
             experimental: false,
             fixes: None,
             main_node: None,
+            related: Vec::new(),
         }
     }
 
@@ -187,6 +209,11 @@ fn with_main_node(mut self, main_node: InFile<SyntaxNode>) -> Diagnostic {
         self
     }
 
+    fn with_related(mut self, range: FileRange, label: impl Into<String>) -> Diagnostic {
+        self.related.push((range, label.into()));
+        self
+    }
+
     fn with_fixes(mut self, fixes: Option<Vec<Assist>>) -> Diagnostic {
         self.fixes = fixes;
         self
@@ -217,14 +244,54 @@ fn default() -> Self {
     }
 }
 
+/// Which set of diagnostics to report in addition to the compiler's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DiagnosticsStyle {
+    #[default]
+    Default,
+    /// Also report the clippy lints in [`CLIPPY_LITE_LINTS`], which rust-analyzer reimplements
+    /// so that they work without running clippy.
+    ClippyLite,
+}
+
+/// Clippy lints that are only reported with [`DiagnosticsStyle::ClippyLite`].
+pub const CLIPPY_LITE_LINTS: &[&str] = &[
+    "cast_possible_truncation",
+    "cast_possible_wrap",
+    "cast_sign_loss",
+    "inconsistent_digit_grouping",
+    "iter_nth_zero",
+    "unnecessary_cast",
+    "useless_asref",
+    "useless_conversion",
+];
+
+/// The standard library macros [`DiagnosticsConfig::leftover_macros`] can report.
+pub const LEFTOVER_MACROS: &[&str] = &["todo", "unimplemented", "dbg", "println"];
+
 #[derive(Debug, Clone)]
 pub struct DiagnosticsConfig {
     /// Whether native diagnostics are enabled.
     pub enabled: bool,
+    pub style: DiagnosticsStyle,
     pub proc_macros_enabled: bool,
     pub proc_attr_macros_enabled: bool,
     pub disable_experimental: bool,
     pub disabled: FxHashSet<String>,
+    /// Whether to report private items that are never used, which needs a reference search for
+    /// every such item.
+    pub dead_code: bool,
+    /// Whether to report `pub` and `pub(crate)` items whose visibility could be reduced, which
+    /// needs a reference search for every such item.
+    pub unused_pub: bool,
+    /// Whether to warn about unsafe operations outside of `unsafe` blocks in `unsafe` functions
+    /// in editions before 2024, where the `unsafe_op_in_unsafe_fn` lint is allowed by default.
+    pub unsafe_op_in_unsafe_fn: bool,
+    /// The severity to report each of the [`LEFTOVER_MACROS`] with when it's called outside of
+    /// test code. Macros that are missing aren't reported.
+    pub leftover_macros: FxHashMap<String, Severity>,
+    /// Whether to report `TODO`, `FIXME` and `HACK` comments as hints.
+    pub todo_comments: bool,
     pub expr_fill_default: ExprFillDefaultMode,
     // FIXME: We may want to include a whole `AssistConfig` here
     pub insert_use: InsertUseConfig,
@@ -235,21 +302,29 @@ pub struct DiagnosticsConfig {
 impl DiagnosticsConfig {
     pub fn test_sample() -> Self {
         use hir::PrefixKind;
-        use ide_db::imports::insert_use::ImportGranularity;
+        use ide_db::imports::insert_use::{ImportGranularity, ImportGroupStyle};
 
         Self {
             enabled: true,
+            style: DiagnosticsStyle::Default,
             proc_macros_enabled: Default::default(),
             proc_attr_macros_enabled: Default::default(),
             disable_experimental: Default::default(),
             disabled: Default::default(),
+            dead_code: false,
+            unused_pub: false,
+            unsafe_op_in_unsafe_fn: false,
+            leftover_macros: Default::default(),
+            todo_comments: false,
             expr_fill_default: Default::default(),
             insert_use: InsertUseConfig {
                 granularity: ImportGranularity::Preserve,
                 enforce_granularity: false,
                 prefix_kind: PrefixKind::Plain,
                 group: false,
+                group_style: ImportGroupStyle::Default,
                 skip_glob_imports: false,
+                prefer_reexports: false,
             },
             prefer_no_std: false,
             prefer_prelude: true,
@@ -284,6 +359,19 @@ fn resolve_precise_location(
     }
 }
 
+/// For an error about a block that was never closed, finds the `{` it was opened with.
+fn unclosed_delimiter(root: &SyntaxNode, err: &SyntaxError) -> Option<TextRange> {
+    let offset = err.range().start();
+    let token = root.token_at_offset(offset).left_biased()?;
+    token
+        .parent_ancestors()
+        .filter_map(ast::StmtList::cast)
+        .take_while(|it| it.syntax().text_range().end() == offset)
+        .find(|it| it.r_curly_token().is_none())
+        .and_then(|it| it.l_curly_token())
+        .map(|it| it.text_range())
+}
+
 /// Request diagnostics for the given [`FileId`]. The produced diagnostics may point to other files
 /// due to macros.
 pub fn diagnostics(
@@ -299,11 +387,16 @@ pub fn diagnostics(
 
     // [#34344] Only take first 128 errors to prevent slowing down editor/ide, the number 128 is chosen arbitrarily.
     res.extend(parse.errors().iter().take(128).map(|err| {
-        Diagnostic::new(
+        let diagnostic = Diagnostic::new(
             DiagnosticCode::RustcHardError("syntax-error"),
             format!("Syntax Error: {err}"),
             FileRange { file_id, range: err.range() },
-        )
+        );
+        match unclosed_delimiter(&parse.syntax_node(), err) {
+            Some(range) => diagnostic
+                .with_related(FileRange { file_id, range }, "unclosed delimiter opened here"),
+            None => diagnostic,
+        }
     }));
 
     let parse = sema.parse(file_id);
@@ -311,32 +404,68 @@ pub fn diagnostics(
     for node in parse.syntax().descendants() {
         handlers::useless_braces::useless_braces(&mut res, file_id, &node);
         handlers::field_shorthand::field_shorthand(&mut res, file_id, &node);
+        handlers::inconsistent_digit_grouping::inconsistent_digit_grouping(
+            &mut res, file_id, &node,
+        );
         handlers::json_is_not_rust::json_in_items(&sema, &mut res, file_id, &node, config);
     }
+    handlers::todo_comment::todo_comment(&mut res, file_id, &parse, config);
 
-    let module = sema.to_module_def(file_id);
+    // A file is part of several crates when they are variants of one crate, like when the
+    // workspace is analyzed for several targets. We report the diagnostics of all of them, but
+    // code is only inactive if it is inactive in every one.
+    let modules: Vec<_> = sema.to_module_defs(file_id).collect();
 
     let ctx = DiagnosticsContext { config, sema, resolve };
-    if module.is_none() {
+    if modules.is_empty() {
         handlers::unlinked_file::unlinked_file(&ctx, &mut res, file_id);
     }
+    if config.dead_code {
+        handlers::dead_code::dead_code(&ctx, &mut res, file_id);
+    }
+    if config.unused_pub {
+        handlers::unused_pub::unused_pub(&ctx, &mut res, file_id);
+    }
+    handlers::incompatible_msrv::incompatible_msrv(&ctx, &mut res, file_id);
+    handlers::leftover_macro::leftover_macro(&ctx, &mut res, file_id);
+    handlers::non_object_safe_trait::non_object_safe_trait(&ctx, &mut res, file_id);
+    handlers::missing_repr_transparent::missing_repr_transparent(&ctx, &mut res, file_id);
+    handlers::repr_inhibits_niche::repr_inhibits_niche(&ctx, &mut res, file_id);
+    handlers::improper_ctypes::improper_ctypes(&ctx, &mut res, file_id);
+    handlers::clashing_extern_declarations::clashing_extern_declarations(&ctx, &mut res, file_id);
+    handlers::useless_conversion::useless_conversion(&ctx, &mut res, file_id);
+    handlers::undeclared_feature::undeclared_feature(&ctx, &mut res, file_id);
+    handlers::cfg_disabled_path::cfg_disabled_path(&ctx, &mut res, file_id);
+    handlers::unsized_generic_arg::unsized_generic_arg(&ctx, &mut res, file_id);
 
     let mut diags = Vec::new();
-    if let Some(m) = module {
+    for m in &modules {
         m.diagnostics(db, &mut diags);
     }
 
+    let mut inactive_in_modules: FxHashMap<FileRange, usize> = FxHashMap::default();
     for diag in diags {
         #[rustfmt::skip]
         let d = match diag {
+            AnyDiagnostic::ArrayIndexOutOfBounds(d) => handlers::array_index_out_of_bounds::array_index_out_of_bounds(&ctx, &d),
+            AnyDiagnostic::ArrayPatternLenMismatch(d) => handlers::array_pattern_len_mismatch::array_pattern_len_mismatch(&ctx, &d),
             AnyDiagnostic::ExpectedFunction(d) => handlers::expected_function::expected_function(&ctx, &d),
             AnyDiagnostic::InactiveCode(d) => match handlers::inactive_code::inactive_code(&ctx, &d) {
-                Some(it) => it,
+                Some(it) => {
+                    let count = inactive_in_modules.entry(it.range).or_default();
+                    *count += 1;
+                    if *count > 1 {
+                        continue;
+                    }
+                    it
+                }
                 None => continue,
             }
             AnyDiagnostic::IncoherentImpl(d) => handlers::incoherent_impl::incoherent_impl(&ctx, &d),
             AnyDiagnostic::IncorrectCase(d) => handlers::incorrect_case::incorrect_case(&ctx, &d),
             AnyDiagnostic::InvalidDeriveTarget(d) => handlers::invalid_derive_target::invalid_derive_target(&ctx, &d),
+            AnyDiagnostic::IterNthZero(d) => handlers::iter_nth_zero::iter_nth_zero(&ctx, &d),
+            AnyDiagnostic::LossyCast(d) => handlers::lossy_cast::lossy_cast(&ctx, &d),
             AnyDiagnostic::MacroDefError(d) => handlers::macro_error::macro_def_error(&ctx, &d),
             AnyDiagnostic::MacroError(d) => handlers::macro_error::macro_error(&ctx, &d),
             AnyDiagnostic::MacroExpansionParseError(d) => {
@@ -390,6 +519,33 @@ pub fn diagnostics(
         res.push(d)
     }
 
+    // Files pulled in with `include!` share the modules of the including file, so each of them
+    // only reports the diagnostics located in itself.
+    let included_files: FxHashSet<_> = modules.iter().flat_map(|m| m.included_files(db)).collect();
+    if included_files.contains(&file_id) {
+        res.retain(|d| d.range.file_id == file_id);
+    } else {
+        res.retain(|d| !included_files.contains(&d.range.file_id));
+    }
+
+    // Code an attribute macro generated on its own can't be fixed by the user, and the only place
+    // to report it at would be the whole attribute, so don't report it at all.
+    res.retain(|d| {
+        !d.main_node
+            .as_ref()
+            .is_some_and(|node| node.as_ref().map(|it| it.text_range()).is_attr_macro_generated(db))
+    });
+
+    if modules.len() > 1 {
+        let mut seen = FxHashSet::default();
+        res.retain(|d| {
+            if d.code == DiagnosticCode::Ra("inactive-code", Severity::WeakWarning) {
+                return inactive_in_modules.get(&d.range) == Some(&modules.len());
+            }
+            seen.insert((d.code, d.range, d.message.clone()))
+        });
+    }
+
     let mut diagnostics_of_range =
         res.iter_mut().filter_map(|x| Some((x.main_node.clone()?, x))).collect::<FxHashMap<_, _>>();
 
@@ -408,18 +564,18 @@ pub fn diagnostics(
         d.severity != Severity::Allow
             && !ctx.config.disabled.contains(d.code.as_str())
             && !(ctx.config.disable_experimental && d.experimental)
+            && (ctx.config.style == DiagnosticsStyle::ClippyLite
+                || !matches!(d.code, DiagnosticCode::Clippy(lint) if CLIPPY_LITE_LINTS.contains(&lint)))
     });
 
     res
 }
 
-// `__RA_EVERY_LINT` is a fake lint group to allow every lint in proc macros
-
 static RUSTC_LINT_GROUPS_DICT: Lazy<FxHashMap<&str, Vec<&str>>> =
-    Lazy::new(|| build_group_dict(DEFAULT_LINT_GROUPS, &["warnings", "__RA_EVERY_LINT"], ""));
+    Lazy::new(|| build_group_dict(DEFAULT_LINT_GROUPS, &["warnings"], ""));
 
 static CLIPPY_LINT_GROUPS_DICT: Lazy<FxHashMap<&str, Vec<&str>>> =
-    Lazy::new(|| build_group_dict(CLIPPY_LINT_GROUPS, &["__RA_EVERY_LINT"], "clippy::"));
+    Lazy::new(|| build_group_dict(CLIPPY_LINT_GROUPS, &[], "clippy::"));
 
 fn build_group_dict(
     lint_group: &'static [LintGroup],
@@ -463,8 +619,12 @@ fn handle_lint_attributes(
                 {
                     const EMPTY_LINTS: &[&str] = &[];
                     let (names, stack) = match x.code {
-                        DiagnosticCode::RustcLint(name) => (
-                            RUSTC_LINT_GROUPS_DICT.get(name).map_or(EMPTY_LINTS, |x| &**x),
+                        // Lints that aren't part of any group, like `unconditional_panic`, can
+                        // still be set by their own name.
+                        DiagnosticCode::RustcLint(ref name) => (
+                            RUSTC_LINT_GROUPS_DICT
+                                .get(name)
+                                .map_or(std::slice::from_ref(name), |x| &**x),
                             &mut *rustc_stack,
                         ),
                         DiagnosticCode::Clippy(name) => (
@@ -481,12 +641,6 @@ fn handle_lint_attributes(
                 }
                 if let Some(item) = ast::Item::cast(node.clone()) {
                     if let Some(me) = sema.expand_attr_macro(&item) {
-                        for stack in [&mut *rustc_stack, &mut *clippy_stack] {
-                            stack
-                                .entry("__RA_EVERY_LINT".to_owned())
-                                .or_default()
-                                .push(Severity::Allow);
-                        }
                         handle_lint_attributes(
                             sema,
                             &me,
@@ -494,9 +648,6 @@ fn handle_lint_attributes(
                             clippy_stack,
                             diagnostics_of_range,
                         );
-                        for stack in [&mut *rustc_stack, &mut *clippy_stack] {
-                            stack.entry("__RA_EVERY_LINT".to_owned()).or_default().pop();
-                        }
                     }
                 }
                 if let Some(mc) = ast::MacroCall::cast(node) {
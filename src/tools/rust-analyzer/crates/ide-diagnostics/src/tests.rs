@@ -10,7 +10,7 @@
 use test_fixture::WithFixture;
 use test_utils::{assert_eq_text, extract_annotations, MiniCore};
 
-use crate::{DiagnosticsConfig, ExprFillDefaultMode, Severity};
+use crate::{DiagnosticsConfig, DiagnosticsStyle, ExprFillDefaultMode, Severity};
 
 /// Takes a multi-file input fixture with annotated cursor positions,
 /// and checks that:
@@ -39,6 +39,15 @@ fn check_nth_fix(nth: usize, ra_fixture_before: &str, ra_fixture_after: &str) {
     check_nth_fix_with_config(config, nth, ra_fixture_before, ra_fixture_after)
 }
 
+/// Like [`check_fix`], with the clippy lints only reported with [`DiagnosticsStyle::ClippyLite`].
+#[track_caller]
+pub(crate) fn check_clippy_lite_fix(ra_fixture_before: &str, ra_fixture_after: &str) {
+    let mut config = DiagnosticsConfig::test_sample();
+    config.expr_fill_default = ExprFillDefaultMode::Default;
+    config.style = DiagnosticsStyle::ClippyLite;
+    check_nth_fix_with_config(config, 0, ra_fixture_before, ra_fixture_after)
+}
+
 #[track_caller]
 pub(crate) fn check_fix_with_disabled(
     ra_fixture_before: &str,
@@ -52,7 +61,7 @@ pub(crate) fn check_fix_with_disabled(
 }
 
 #[track_caller]
-fn check_nth_fix_with_config(
+pub(crate) fn check_nth_fix_with_config(
     config: DiagnosticsConfig,
     nth: usize,
     ra_fixture_before: &str,
@@ -178,15 +187,15 @@ pub(crate) fn check_has_single_fix(ra_fixture_before: &str, ra_fixture_after: &s
 
 /// Checks that there's a diagnostic *without* fix at `$0`.
 pub(crate) fn check_no_fix(ra_fixture: &str) {
+    check_no_fix_with_config(DiagnosticsConfig::test_sample(), ra_fixture)
+}
+
+pub(crate) fn check_no_fix_with_config(config: DiagnosticsConfig, ra_fixture: &str) {
     let (db, file_position) = RootDatabase::with_position(ra_fixture);
-    let diagnostic = super::diagnostics(
-        &db,
-        &DiagnosticsConfig::test_sample(),
-        &AssistResolveStrategy::All,
-        file_position.file_id,
-    )
-    .pop()
-    .unwrap();
+    let diagnostic =
+        super::diagnostics(&db, &config, &AssistResolveStrategy::All, file_position.file_id)
+            .pop()
+            .unwrap();
     assert!(diagnostic.fixes.is_none(), "got a fix when none was expected: {diagnostic:?}");
 }
 
@@ -197,6 +206,16 @@ pub(crate) fn check_diagnostics(ra_fixture: &str) {
     check_diagnostics_with_config(config, ra_fixture)
 }
 
+/// Like [`check_diagnostics`], with the clippy lints only reported with
+/// [`DiagnosticsStyle::ClippyLite`].
+#[track_caller]
+pub(crate) fn check_clippy_lite_diagnostics(ra_fixture: &str) {
+    let mut config = DiagnosticsConfig::test_sample();
+    config.disabled.insert("inactive-code".to_owned());
+    config.style = DiagnosticsStyle::ClippyLite;
+    check_diagnostics_with_config(config, ra_fixture)
+}
+
 #[track_caller]
 pub(crate) fn check_diagnostics_with_disabled(
     ra_fixture: &str,
@@ -209,7 +228,21 @@ pub(crate) fn check_diagnostics_with_disabled(
 
 #[track_caller]
 pub(crate) fn check_diagnostics_with_config(config: DiagnosticsConfig, ra_fixture: &str) {
-    let (db, files) = RootDatabase::with_many_files(ra_fixture);
+    let (mut db, files) = RootDatabase::with_many_files(ra_fixture);
+    if config.proc_attr_macros_enabled {
+        db.enable_proc_attr_macros();
+    }
+    // Diagnostics are only computed for Rust files, while fixtures may contain manifests.
+    let files = files
+        .into_iter()
+        .filter(|&file_id| {
+            let source_root = db.source_root(db.file_source_root(file_id));
+            source_root
+                .path_for_file(&file_id)
+                .and_then(|it| it.name_and_extension())
+                .map_or(true, |(_, ext)| ext == Some("rs"))
+        })
+        .collect::<Vec<_>>();
     let mut annotations = files
         .iter()
         .copied()
@@ -286,6 +319,38 @@ fn test_disabled_diagnostics() {
     assert!(!diagnostics.is_empty());
 }
 
+#[test]
+fn unclosed_delimiter_points_at_opening_brace() {
+    let (db, file_id) = RootDatabase::with_single_file(
+        r#"
+fn foo() {
+    if true {
+        1;
+    let _x = 92;
+}
+
+fn bar() {}
+"#,
+    );
+
+    let diagnostics = super::diagnostics(
+        &db,
+        &DiagnosticsConfig::test_sample(),
+        &AssistResolveStrategy::All,
+        file_id,
+    );
+    let [diagnostic] = diagnostics.as_slice() else {
+        panic!("expected a single diagnostic, got {diagnostics:#?}");
+    };
+    assert_eq!(diagnostic.message, "Syntax Error: unclosed delimiter");
+    let [(related, label)] = diagnostic.related.as_slice() else {
+        panic!("expected a single related span, got {:#?}", diagnostic.related);
+    };
+    assert_eq!(label, "unclosed delimiter opened here");
+    assert_eq!(u32::from(related.range.start()), 9);
+    assert_eq!(&db.file_text(file_id)[related.range], "{");
+}
+
 #[test]
 fn minicore_smoke_test() {
     fn check(minicore: MiniCore) {
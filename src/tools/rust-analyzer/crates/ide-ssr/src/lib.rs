@@ -42,6 +42,10 @@
 // | not(a)        | Negates the constraint `a`
 // |===
 //
+// A search pattern on its own, without `==>>` and a replacement, only finds the matching code,
+// along with what each placeholder matched and its type. Editors can use this to build search
+// views, for example to list all `foo($a, None)` calls.
+//
 // Available via the command `rust-analyzer.ssr`.
 //
 // ```rust
@@ -85,11 +89,11 @@
 pub use crate::{errors::SsrError, from_comment::ssr_from_comment, matching::Match};
 
 use crate::{errors::bail, matching::MatchFailureReason};
-use hir::Semantics;
+use hir::{HirDisplay, Semantics};
 use ide_db::base_db::{FileId, FilePosition, FileRange};
 use nohash_hasher::IntMap;
 use resolving::ResolvedRule;
-use syntax::{ast, AstNode, SyntaxNode, TextRange};
+use syntax::{ast, match_ast, AstNode, SyntaxNode, TextRange};
 use text_edit::TextEdit;
 
 // A structured search replace rule. Create by calling `parse` on a str.
@@ -112,6 +116,26 @@ pub struct SsrMatches {
     pub matches: Vec<Match>,
 }
 
+/// A match of a search pattern, as reported by [`MatchFinder::find`].
+#[derive(Debug)]
+pub struct FoundMatch {
+    pub range: FileRange,
+    pub matched_text: String,
+    /// What each placeholder of the pattern matched, in source order.
+    pub placeholders: Vec<PlaceholderBinding>,
+}
+
+/// The code that a placeholder matched.
+#[derive(Debug)]
+pub struct PlaceholderBinding {
+    /// The name of the placeholder, without the leading `$`.
+    pub name: String,
+    pub range: FileRange,
+    pub matched_text: String,
+    /// The inferred type of the matched code, if it is an expression, pattern or type.
+    pub ty: Option<String>,
+}
+
 /// Searches a crate for pattern matches and possibly replaces them with something else.
 pub struct MatchFinder<'db> {
     /// Our source of information about the user's code.
@@ -217,6 +241,50 @@ pub fn matches(&self) -> SsrMatches {
         nester::nest_and_remove_collisions(matches, &self.sema)
     }
 
+    /// Returns matches for all added rules, including nested ones, together with the code each
+    /// placeholder matched. Unlike `edits`, this doesn't drop matches that overlap.
+    pub fn find(&self) -> Vec<FoundMatch> {
+        use ide_db::base_db::SourceDatabaseExt;
+        let mut res: Vec<_> = self
+            .matches()
+            .flattened()
+            .matches
+            .iter()
+            .map(|m| {
+                let mut placeholders: Vec<_> = m
+                    .placeholder_values
+                    .iter()
+                    .map(|(var, value)| PlaceholderBinding {
+                        name: var.0.clone(),
+                        range: value.range,
+                        matched_text: match &value.node {
+                            Some(node) => node.text().to_string(),
+                            None => self.sema.db.file_text(value.range.file_id)[value.range.range]
+                                .to_owned(),
+                        },
+                        ty: value.node.as_ref().and_then(|node| self.type_of_node(node)),
+                    })
+                    .collect();
+                placeholders.sort_by_key(|it| it.range.range.start());
+                FoundMatch { range: m.range, matched_text: m.matched_text(), placeholders }
+            })
+            .collect();
+        res.sort_by_key(|it| (it.range.file_id, it.range.range.start()));
+        res
+    }
+
+    fn type_of_node(&self, node: &SyntaxNode) -> Option<String> {
+        let ty = match_ast! {
+            match node {
+                ast::Expr(it) => self.sema.type_of_expr(&it)?.original,
+                ast::Pat(it) => self.sema.type_of_pat(&it)?.original,
+                ast::Type(it) => self.sema.resolve_type(&it)?,
+                _ => return None,
+            }
+        };
+        (!ty.is_unknown()).then(|| ty.display(self.sema.db).to_string())
+    }
+
     /// Finds all nodes in `file_id` whose text is exactly equal to `snippet` and attempts to match
     /// them, while recording reasons why they don't match. This API is useful for command
     /// line-based debugging where providing a range is difficult.
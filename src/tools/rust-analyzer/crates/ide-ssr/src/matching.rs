@@ -60,6 +60,9 @@ pub struct Match {
 #[derive(Debug)]
 pub(crate) struct PlaceholderMatch {
     pub(crate) range: FileRange,
+    /// The node that the placeholder matched, if it matched a whole node rather than a sequence of
+    /// tokens in a macro call.
+    pub(crate) node: Option<SyntaxNode>,
     /// More matches, found within `node`.
     pub(crate) inner_matches: SsrMatches,
     /// How many times the code that the placeholder matched needed to be dereferenced. Will only be
@@ -181,7 +184,7 @@ fn attempt_match_node(
                 self.validate_range(&original_range)?;
                 matches_out.placeholder_values.insert(
                     placeholder.ident.clone(),
-                    PlaceholderMatch::from_range(original_range),
+                    PlaceholderMatch::from_node(original_range, code.clone()),
                 );
             }
             return Ok(());
@@ -717,6 +720,17 @@ impl PlaceholderMatch {
     fn from_range(range: FileRange) -> Self {
         Self {
             range,
+            node: None,
+            inner_matches: SsrMatches::default(),
+            autoderef_count: 0,
+            autoref_kind: ast::SelfParamKind::Owned,
+        }
+    }
+
+    fn from_node(range: FileRange, node: SyntaxNode) -> Self {
+        Self {
+            range,
+            node: Some(node),
             inner_matches: SsrMatches::default(),
             autoderef_count: 0,
             autoref_kind: ast::SelfParamKind::Owned,
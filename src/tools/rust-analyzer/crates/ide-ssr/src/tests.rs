@@ -138,6 +138,21 @@ fn assert_matches(pattern: &str, code: &str, expected: &[&str]) {
     assert_eq!(matched_strings, expected);
 }
 
+fn assert_found(pattern: &str, code: &str, expected: Expect) {
+    let (db, position, selections) = single_file(code);
+    let mut match_finder = MatchFinder::in_context(&db, position, selections).unwrap();
+    match_finder.add_search_pattern(pattern.parse().unwrap()).unwrap();
+    let mut actual = String::new();
+    for m in match_finder.find() {
+        actual += &format!("{:?} {}\n", m.range.range, m.matched_text);
+        for p in m.placeholders {
+            let ty = p.ty.as_deref().unwrap_or("?");
+            actual += &format!("    ${} {:?} {}: {ty}\n", p.name, p.range.range, p.matched_text);
+        }
+    }
+    expected.assert_eq(&actual);
+}
+
 fn assert_no_match(pattern: &str, code: &str) {
     let (db, position, selections) = single_file(code);
     let mut match_finder = MatchFinder::in_context(&db, position, selections).unwrap();
@@ -1396,3 +1411,51 @@ fn main() {
         "#]],
     );
 }
+
+#[test]
+fn find_reports_placeholder_types() {
+    assert_found(
+        "foo($a, None)",
+        r#"
+enum Option<T> { None, Some(T) }
+use Option::*;
+fn foo<T>(_: T, _: Option<u8>) {}
+fn main() {
+    let x = 1u32;
+    foo(x + 1, None);
+    foo("s", Some(2));
+    foo(foo(true, None), None);
+}
+"#,
+        expect![[r#"
+            116..132 foo(x + 1, None)
+                $a 120..125 x + 1: u32
+            161..187 foo(foo(true, None), None)
+                $a 165..180 foo(true, None): ()
+            165..180 foo(true, None)
+                $a 169..173 true: bool
+        "#]],
+    );
+}
+
+#[test]
+fn find_in_macro_call() {
+    assert_found(
+        "Foo::bar($s, $a)",
+        r#"
+macro_rules! m { ($($t:tt)*) => { $($t)* } }
+struct Foo;
+impl Foo {
+    fn bar(&self, _: &str) {}
+}
+fn main(f: Foo) {
+    m!(f.bar("x"));
+}
+"#,
+        expect![[r#"
+            125..135 f.bar("x")
+                $s 125..126 f: Foo
+                $a 131..134 "x": &str
+        "#]],
+    );
+}
@@ -9,7 +9,9 @@
 use syntax::{ast::HasName, AstNode, TextRange};
 
 use crate::{
-    annotations::fn_references::find_all_methods,
+    annotations::{
+        fn_references::find_all_methods, stack_usage::stack_usage, variant_usages::variant_usages,
+    },
     goto_implementation::goto_implementation,
     references::find_all_refs,
     runnables::{runnables, Runnable},
@@ -17,6 +19,10 @@
 };
 
 mod fn_references;
+mod stack_usage;
+mod variant_usages;
+
+pub use self::{stack_usage::StackUsage, variant_usages::VariantUsages};
 
 // Feature: Annotations
 //
@@ -35,6 +41,8 @@ pub enum AnnotationKind {
     Runnable(Runnable),
     HasImpls { pos: FilePosition, data: Option<Vec<NavigationTarget>> },
     HasReferences { pos: FilePosition, data: Option<Vec<FileRange>> },
+    VariantUsages { pos: FilePosition, data: Option<VariantUsages> },
+    StackUsage { pos: FilePosition, data: Option<StackUsage> },
 }
 
 pub struct AnnotationConfig {
@@ -44,6 +52,8 @@ pub struct AnnotationConfig {
     pub annotate_references: bool,
     pub annotate_method_references: bool,
     pub annotate_enum_variant_references: bool,
+    pub annotate_enum_variant_usages: bool,
+    pub annotate_stack_usage: bool,
     pub location: AnnotationLocation,
 }
 
@@ -83,6 +93,17 @@ pub(crate) fn annotations(
 
     visit_file_defs(&Semantics::new(db), file_id, &mut |def| {
         let range = match def {
+            Definition::Function(func) if config.annotate_stack_usage => {
+                if let Some(range) = func.source(db).and_then(|node| name_range(db, node, file_id))
+                {
+                    let (annotation_range, target_pos) = mk_ranges(range);
+                    annotations.insert(Annotation {
+                        range: annotation_range,
+                        kind: AnnotationKind::StackUsage { pos: target_pos, data: None },
+                    });
+                }
+                None
+            }
             Definition::Const(konst) if config.annotate_references => {
                 konst.source(db).and_then(|node| name_range(db, node, file_id))
             }
@@ -91,7 +112,9 @@ pub(crate) fn annotations(
             }
             Definition::Adt(adt) => match adt {
                 hir::Adt::Enum(enum_) => {
-                    if config.annotate_enum_variant_references {
+                    if config.annotate_enum_variant_references
+                        || config.annotate_enum_variant_usages
+                    {
                         enum_
                             .variants(db)
                             .into_iter()
@@ -100,13 +123,24 @@ pub(crate) fn annotations(
                             })
                             .for_each(|range| {
                                 let (annotation_range, target_position) = mk_ranges(range);
-                                annotations.insert(Annotation {
-                                    range: annotation_range,
-                                    kind: AnnotationKind::HasReferences {
-                                        pos: target_position,
-                                        data: None,
-                                    },
-                                });
+                                if config.annotate_enum_variant_references {
+                                    annotations.insert(Annotation {
+                                        range: annotation_range,
+                                        kind: AnnotationKind::HasReferences {
+                                            pos: target_position,
+                                            data: None,
+                                        },
+                                    });
+                                }
+                                if config.annotate_enum_variant_usages {
+                                    annotations.insert(Annotation {
+                                        range: annotation_range,
+                                        kind: AnnotationKind::VariantUsages {
+                                            pos: target_position,
+                                            data: None,
+                                        },
+                                    });
+                                }
                             })
                     }
                     if config.annotate_references || config.annotate_impls {
@@ -191,6 +225,12 @@ pub(crate) fn resolve_annotation(db: &RootDatabase, mut annotation: Annotation)
                     .collect()
             });
         }
+        AnnotationKind::VariantUsages { pos, ref mut data } => {
+            *data = variant_usages(db, pos);
+        }
+        AnnotationKind::StackUsage { pos, ref mut data } => {
+            *data = stack_usage(db, pos);
+        }
         _ => {}
     };
 
@@ -208,7 +248,7 @@ fn should_skip_runnable(kind: &RunnableKind, binary_target: bool) -> bool {
 mod tests {
     use expect_test::{expect, Expect};
 
-    use crate::{fixture, Annotation, AnnotationConfig};
+    use crate::{fixture, Annotation, AnnotationConfig, AnnotationKind};
 
     use super::AnnotationLocation;
 
@@ -219,6 +259,8 @@ mod tests {
         annotate_references: true,
         annotate_method_references: true,
         annotate_enum_variant_references: true,
+        annotate_enum_variant_usages: false,
+        annotate_stack_usage: false,
         location: AnnotationLocation::AboveName,
     };
 
@@ -909,4 +951,159 @@ fn test_annotations_appear_above_whole_item_when_configured_to_do_so() {
             &AnnotationConfig { location: AnnotationLocation::AboveWholeItem, ..DEFAULT_CONFIG },
         );
     }
+
+    #[test]
+    fn enum_variant_usages() {
+        check_with_config(
+            r#"
+enum E {
+    A(u8),
+    B,
+    C,
+}
+
+fn f(e: E) -> E {
+    match e {
+        E::A(_) | E::B => E::A(0),
+        _ => E::A(1),
+    }
+}
+"#,
+            expect![[r#"
+                [
+                    Annotation {
+                        range: 13..14,
+                        kind: VariantUsages {
+                            pos: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 13,
+                            },
+                            data: Some(
+                                VariantUsages {
+                                    constructions: [
+                                        FileRange {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            range: 98..99,
+                                        },
+                                        FileRange {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            range: 120..121,
+                                        },
+                                    ],
+                                    matches: [
+                                        FileRange {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            range: 80..81,
+                                        },
+                                    ],
+                                },
+                            ),
+                        },
+                    },
+                    Annotation {
+                        range: 24..25,
+                        kind: VariantUsages {
+                            pos: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 24,
+                            },
+                            data: Some(
+                                VariantUsages {
+                                    constructions: [],
+                                    matches: [
+                                        FileRange {
+                                            file_id: FileId(
+                                                0,
+                                            ),
+                                            range: 90..91,
+                                        },
+                                    ],
+                                },
+                            ),
+                        },
+                    },
+                    Annotation {
+                        range: 31..32,
+                        kind: VariantUsages {
+                            pos: FilePosition {
+                                file_id: FileId(
+                                    0,
+                                ),
+                                offset: 31,
+                            },
+                            data: Some(
+                                VariantUsages {
+                                    constructions: [],
+                                    matches: [],
+                                },
+                            ),
+                        },
+                    },
+                ]
+            "#]],
+            &AnnotationConfig {
+                annotate_runnables: false,
+                annotate_impls: false,
+                annotate_references: false,
+                annotate_method_references: false,
+                annotate_enum_variant_references: false,
+                annotate_enum_variant_usages: true,
+                ..DEFAULT_CONFIG
+            },
+        );
+    }
+
+    #[test]
+    fn stack_usage() {
+        let (analysis, file_id) = fixture::file(
+            r#"
+fn leaf() -> [u8; 64] { [0; 64] }
+fn middle() -> u32 { let _buf = leaf(); 1 }
+fn recursive(n: u32) -> u32 { if n == 0 { 0 } else { recursive(n - 1) } }
+fn main() { middle(); recursive(3); }
+"#,
+        );
+        let config = AnnotationConfig {
+            annotate_runnables: false,
+            annotate_impls: false,
+            annotate_references: false,
+            annotate_method_references: false,
+            annotate_enum_variant_references: false,
+            annotate_stack_usage: true,
+            ..DEFAULT_CONFIG
+        };
+        let actual = analysis
+            .annotations(&config, file_id)
+            .unwrap()
+            .into_iter()
+            .map(|annotation| analysis.resolve_annotation(annotation).unwrap())
+            .map(|annotation| {
+                let AnnotationKind::StackUsage { data: Some(usage), .. } = annotation.kind else {
+                    panic!("unexpected annotation {annotation:?}")
+                };
+                let path = usage.worst_path.iter().map(|it| it.name.as_str()).collect::<Vec<_>>();
+                format!(
+                    "{:?} frame: {}, worst case: {}, path: {path:?}, incomplete: {}\n",
+                    annotation.range, usage.frame_size, usage.worst_case, usage.incomplete
+                )
+            })
+            .collect::<String>();
+        expect![[r#"
+            3..7 frame: 64, worst case: 64, path: [], incomplete: false
+            37..43 frame: 132, worst case: 196, path: ["leaf"], incomplete: false
+            81..90 frame: 13, worst case: 13, path: [], incomplete: true
+            155..159 frame: 8, worst case: 204, path: ["middle", "leaf"], incomplete: true
+        "#]]
+        .assert_eq(&actual);
+    }
 }
@@ -0,0 +1,40 @@
+//! Estimates the stack space a call to a function needs, for finding the call paths that risk a
+//! stack overflow on targets with small stacks.
+
+use hir::Semantics;
+use ide_db::{base_db::FilePosition, RootDatabase};
+use syntax::{algo::find_node_at_offset, ast, AstNode};
+
+use crate::{navigation_target::TryToNav, NavigationTarget, UpmappingResult};
+
+/// How many nested calls are followed before the estimate is cut off.
+const DEPTH_LIMIT: usize = 32;
+
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct StackUsage {
+    /// The size of the function's own frame, in bytes.
+    pub frame_size: u64,
+    /// The size of the frame plus the worst case stack usage of the callees, in bytes.
+    pub worst_case: u64,
+    /// The calls that need the most stack, starting with a callee of the function.
+    pub worst_path: Vec<NavigationTarget>,
+    /// Whether the estimate is only a lower bound.
+    pub incomplete: bool,
+}
+
+pub(super) fn stack_usage(db: &RootDatabase, pos: FilePosition) -> Option<StackUsage> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(pos.file_id);
+    let func = find_node_at_offset::<ast::Fn>(source_file.syntax(), pos.offset)?;
+    let usage = sema.to_def(&func)?.stack_usage(db, DEPTH_LIMIT)?;
+    Some(StackUsage {
+        frame_size: usage.frame_size,
+        worst_case: usage.worst_case,
+        worst_path: usage
+            .worst_path
+            .into_iter()
+            .filter_map(|it| it.try_to_nav(db).map(UpmappingResult::call_site))
+            .collect(),
+        incomplete: usage.incomplete,
+    })
+}
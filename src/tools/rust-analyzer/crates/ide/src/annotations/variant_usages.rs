@@ -0,0 +1,50 @@
+//! Sorts the usages of an enum variant into the places that construct it and the places
+//! that match on it, to make variants that are never constructed or never matched stand out.
+
+use hir::Semantics;
+use ide_db::{
+    base_db::{FilePosition, FileRange},
+    defs::Definition,
+    RootDatabase,
+};
+use syntax::{algo::find_node_at_offset, ast, AstNode};
+
+#[derive(Debug, Default, Hash, PartialEq, Eq)]
+pub struct VariantUsages {
+    pub constructions: Vec<FileRange>,
+    pub matches: Vec<FileRange>,
+}
+
+pub(super) fn variant_usages(db: &RootDatabase, pos: FilePosition) -> Option<VariantUsages> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(pos.file_id);
+    let variant = find_node_at_offset::<ast::Variant>(source_file.syntax(), pos.offset)?;
+    let variant = sema.to_def(&variant)?;
+
+    let mut usages = VariantUsages::default();
+    for (file_id, refs) in Definition::Variant(variant).usages(&sema).all() {
+        for reference in refs {
+            let Some(name_ref) = reference.name.as_name_ref() else { continue };
+            let file_range = FileRange { file_id, range: reference.range };
+            // The innermost pattern or expression around the path decides how it is used,
+            // paths in imports and types are neither.
+            let in_pattern = name_ref.syntax().ancestors().find_map(|node| {
+                if ast::Pat::can_cast(node.kind()) {
+                    Some(true)
+                } else if ast::Expr::can_cast(node.kind()) {
+                    Some(false)
+                } else {
+                    None
+                }
+            });
+            match in_pattern {
+                Some(true) => usages.matches.push(file_range),
+                Some(false) => usages.constructions.push(file_range),
+                None => (),
+            }
+        }
+    }
+    usages.constructions.sort_by_key(|it| (it.file_id, it.range.start()));
+    usages.matches.sort_by_key(|it| (it.file_id, it.range.start()));
+    Some(usages)
+}
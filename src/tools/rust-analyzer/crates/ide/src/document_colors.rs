@@ -0,0 +1,409 @@
+//! Recognizes color literals for the color picker of the editor.
+use hir::{ModuleDef, PathResolution, Semantics};
+use ide_db::{
+    base_db::{FileId, FileRange},
+    RootDatabase,
+};
+use syntax::{
+    ast::{self, HasArgList, Radix},
+    match_ast, AstNode, SyntaxNode, TextRange,
+};
+use text_edit::TextEdit;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+    pub alpha: u8,
+}
+
+#[derive(Debug)]
+pub struct ColorInformation {
+    pub range: TextRange,
+    pub color: Color,
+}
+
+#[derive(Debug)]
+pub struct ColorPresentation {
+    pub label: String,
+    pub edit: TextEdit,
+}
+
+enum ColorLiteral {
+    /// A `0xRRGGBB` or `0xRRGGBBAA` integer literal.
+    Hex { has_alpha: bool, uppercase: bool, suffix: String },
+    /// A call to a `const fn rgb` or `const fn rgba`, with whether each channel is a float.
+    Call { callee: String, float_channels: Vec<bool> },
+}
+
+struct FoundColor {
+    range: TextRange,
+    color: Color,
+    literal: ColorLiteral,
+}
+
+// Feature: Document Colors
+//
+// Shows a color picker next to color literals, and rewrites the literal when a new color is
+// picked. Recognized literals are:
+//
+// - hex `u32` literals like `0xRRGGBB` or `0xRRGGBBAA` in constants of types named `Color`, or
+//   passed to constructors of such types,
+// - calls to `const fn rgb(r, g, b)` and `const fn rgba(r, g, b, a)` with integer (`0..=255`)
+//   or float (`0.0..=1.0`) literal channels.
+pub(crate) fn document_colors(db: &RootDatabase, file_id: FileId) -> Vec<ColorInformation> {
+    let sema = Semantics::new(db);
+    find_colors(&sema, file_id)
+        .into_iter()
+        .map(|it| ColorInformation { range: it.range, color: it.color })
+        .collect()
+}
+
+pub(crate) fn color_presentations(
+    db: &RootDatabase,
+    FileRange { file_id, range }: FileRange,
+    color: Color,
+) -> Vec<ColorPresentation> {
+    let sema = Semantics::new(db);
+    let Some(found) = find_colors(&sema, file_id).into_iter().find(|it| it.range == range) else {
+        return Vec::new();
+    };
+    let label = match found.literal {
+        ColorLiteral::Hex { has_alpha, uppercase, suffix } => {
+            let Color { red, green, blue, alpha } = color;
+            let mut digits = format!("{red:02X}{green:02X}{blue:02X}");
+            if has_alpha {
+                digits += &format!("{alpha:02X}");
+            }
+            if !uppercase {
+                digits.make_ascii_lowercase();
+            }
+            format!("0x{digits}{suffix}")
+        }
+        ColorLiteral::Call { callee, float_channels } => {
+            let channels = [color.red, color.green, color.blue, color.alpha];
+            let args = float_channels
+                .iter()
+                .zip(channels)
+                .map(
+                    |(&is_float, value)| {
+                        if is_float {
+                            float_channel(value)
+                        } else {
+                            value.to_string()
+                        }
+                    },
+                )
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{callee}({args})")
+        }
+    };
+    let edit = TextEdit::replace(range, label.clone());
+    vec![ColorPresentation { label, edit }]
+}
+
+fn find_colors(sema: &Semantics<'_, RootDatabase>, file_id: FileId) -> Vec<FoundColor> {
+    let file = sema.parse(file_id);
+    file.syntax()
+        .descendants()
+        .filter_map(|node| {
+            match_ast! {
+                match node {
+                    ast::Literal(it) => hex_color(sema, &it),
+                    ast::CallExpr(it) => call_color(sema, &it),
+                    _ => None,
+                }
+            }
+        })
+        .collect()
+}
+
+fn hex_color(sema: &Semantics<'_, RootDatabase>, literal: &ast::Literal) -> Option<FoundColor> {
+    let ast::LiteralKind::IntNumber(num) = literal.kind() else { return None };
+    if num.radix() != Radix::Hexadecimal {
+        return None;
+    }
+    let (_, digits, suffix) = num.split_into_parts();
+    let digits = digits.replace('_', "");
+    let has_alpha = match digits.len() {
+        6 => false,
+        8 => true,
+        _ => return None,
+    };
+    let ty = sema.type_of_expr(&literal.clone().into())?.original;
+    if !ty.as_builtin().is_some_and(|it| it.name() == hir::known::u32) {
+        return None;
+    }
+    if !is_in_color_context(sema, literal.syntax())? {
+        return None;
+    }
+
+    let [a, b, c, d] = u32::try_from(num.value().ok()?).ok()?.to_be_bytes();
+    let color = if has_alpha {
+        Color { red: a, green: b, blue: c, alpha: d }
+    } else {
+        Color { red: b, green: c, blue: d, alpha: u8::MAX }
+    };
+    Some(FoundColor {
+        range: literal.syntax().text_range(),
+        color,
+        literal: ColorLiteral::Hex {
+            has_alpha,
+            uppercase: digits.chars().any(|it| it.is_ascii_uppercase()),
+            suffix: suffix.to_owned(),
+        },
+    })
+}
+
+/// Whether the literal is the value of a constant of, or in an impl of, a `Color` type, or an
+/// argument or field of an expression of a `Color` type.
+fn is_in_color_context(sema: &Semantics<'_, RootDatabase>, literal: &SyntaxNode) -> Option<bool> {
+    let parent = literal.parent()?;
+    let res = match_ast! {
+        match parent {
+            ast::Const(it) => is_color_item(sema, it.ty(), it.syntax()),
+            ast::Static(it) => is_color_item(sema, it.ty(), it.syntax()),
+            ast::ArgList(it) => {
+                let call = ast::Expr::cast(it.syntax().parent()?)?;
+                is_color_type(sema.db, &sema.type_of_expr(&call)?.original)
+            },
+            ast::RecordExprField(it) => {
+                let record = it.syntax().ancestors().find_map(ast::RecordExpr::cast)?;
+                is_color_type(sema.db, &sema.type_of_expr(&record.into())?.original)
+            },
+            _ => false,
+        }
+    };
+    Some(res)
+}
+
+fn is_color_item(
+    sema: &Semantics<'_, RootDatabase>,
+    ty: Option<ast::Type>,
+    item: &SyntaxNode,
+) -> bool {
+    let is_color_alias = match ty {
+        Some(ast::Type::PathType(it)) => {
+            it.path().and_then(|it| it.segment()?.name_ref()).is_some_and(|it| it.text() == "Color")
+        }
+        _ => false,
+    };
+    is_color_alias
+        || item
+            .ancestors()
+            .find_map(ast::Impl::cast)
+            .and_then(|it| sema.to_def(&it))
+            .is_some_and(|it| is_color_type(sema.db, &it.self_ty(sema.db)))
+}
+
+fn is_color_type(db: &RootDatabase, ty: &hir::Type) -> bool {
+    ty.as_adt().is_some_and(|it| it.name(db).to_smol_str() == "Color")
+}
+
+fn call_color(sema: &Semantics<'_, RootDatabase>, call: &ast::CallExpr) -> Option<FoundColor> {
+    let ast::Expr::PathExpr(callee) = call.expr()? else { return None };
+    let Some(PathResolution::Def(ModuleDef::Function(func))) = sema.resolve_path(&callee.path()?)
+    else {
+        return None;
+    };
+    if !func.is_const(sema.db) {
+        return None;
+    }
+    let channel_count = match func.name(sema.db).to_smol_str().as_str() {
+        "rgb" => 3,
+        "rgba" => 4,
+        _ => return None,
+    };
+
+    let mut channels = [u8::MAX; 4];
+    let mut float_channels = Vec::new();
+    let args = call.arg_list()?.args().collect::<Vec<_>>();
+    if args.len() != channel_count {
+        return None;
+    }
+    for (arg, channel) in args.iter().zip(&mut channels) {
+        let ast::Expr::Literal(literal) = arg else { return None };
+        match literal.kind() {
+            ast::LiteralKind::IntNumber(num) => {
+                *channel = u8::try_from(num.value().ok()?).ok()?;
+                float_channels.push(false);
+            }
+            ast::LiteralKind::FloatNumber(num) => {
+                let value = num.value().ok()?;
+                if !(0.0..=1.0).contains(&value) {
+                    return None;
+                }
+                *channel = (value * 255.0).round() as u8;
+                float_channels.push(true);
+            }
+            _ => return None,
+        }
+    }
+
+    let [red, green, blue, alpha] = channels;
+    Some(FoundColor {
+        range: call.syntax().text_range(),
+        color: Color { red, green, blue, alpha },
+        literal: ColorLiteral::Call { callee: callee.syntax().text().to_string(), float_channels },
+    })
+}
+
+/// Formats a channel as a float in `0.0..=1.0` with at most three decimals.
+fn float_channel(value: u8) -> String {
+    let mut res = format!("{:.3}", f32::from(value) / 255.0);
+    while res.ends_with('0') && !res.ends_with(".0") {
+        res.pop();
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::base_db::FileRange;
+
+    use crate::fixture;
+
+    use super::Color;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let text = analysis.file_text(file_id).unwrap();
+        let mut actual = String::new();
+        for info in analysis.document_colors(file_id).unwrap() {
+            let Color { red, green, blue, alpha } = info.color;
+            actual += &format!("`{}` rgba({red}, {green}, {blue}, {alpha})\n", &text[info.range]);
+        }
+        expect.assert_eq(&actual);
+    }
+
+    fn check_presentation(ra_fixture: &str, color: Color, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let range = analysis.document_colors(file_id).unwrap()[0].range;
+        let actual = analysis
+            .color_presentations(FileRange { file_id, range }, color)
+            .unwrap()
+            .into_iter()
+            .map(|it| it.label)
+            .collect::<Vec<_>>();
+        expect.assert_debug_eq(&actual);
+    }
+
+    const RED: Color = Color { red: 255, green: 0, blue: 0, alpha: 128 };
+
+    #[test]
+    fn hex_constants() {
+        check(
+            r#"
+struct Color(u32);
+impl Color {
+    const WHITE: Color = Color(0xFFFFFF);
+    const PURPLE: u32 = 0x800080_ff;
+    const NOT_A_COLOR: u32 = 0xFFF;
+}
+type Colour = u32;
+const SKY: Colour = 0x87ceeb;
+const MASK: u32 = 0x00ff00;
+fn new() -> Color {
+    Color(0x123456)
+}
+"#,
+            expect![[r#"
+                `0xFFFFFF` rgba(255, 255, 255, 255)
+                `0x800080_ff` rgba(128, 0, 128, 255)
+                `0x123456` rgba(18, 52, 86, 255)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hex_in_type_alias_and_record() {
+        check(
+            r#"
+mod alias {
+    type Color = u32;
+    const SKY: Color = 0x87ceeb;
+}
+struct Color { rgb: u32 }
+struct Theme { background: u32 }
+fn theme() -> (Color, Theme) {
+    (Color { rgb: 0xff8800 }, Theme { background: 0x000000 })
+}
+"#,
+            expect![[r#"
+                `0x87ceeb` rgba(135, 206, 235, 255)
+                `0xff8800` rgba(255, 136, 0, 255)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn rgb_calls() {
+        check(
+            r#"
+const fn rgb(r: u8, g: u8, b: u8) -> u32 { 0 }
+const fn rgba(r: f32, g: f32, b: f32, a: f32) -> u32 { 0 }
+fn rgb_non_const(r: u8, g: u8, b: u8) -> u32 { 0 }
+fn colors() {
+    rgb(255, 128, 0);
+    rgba(1.0, 0.5, 0.0, 0.25);
+    rgb(256, 0, 0);
+    let x = 1;
+    rgb(x, 0, 0);
+}
+"#,
+            expect![[r#"
+                `rgb(255, 128, 0)` rgba(255, 128, 0, 255)
+                `rgba(1.0, 0.5, 0.0, 0.25)` rgba(255, 128, 0, 64)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn hex_presentation() {
+        check_presentation(
+            r#"
+struct Color(u32);
+const RED: Color = Color(0xabcdef);
+"#,
+            RED,
+            expect![[r#"
+                [
+                    "0xff0000",
+                ]
+            "#]],
+        );
+        check_presentation(
+            r#"
+struct Color(u32);
+const RED: Color = Color(0xABCDEF00u32);
+"#,
+            RED,
+            expect![[r#"
+                [
+                    "0xFF000080u32",
+                ]
+            "#]],
+        );
+    }
+
+    #[test]
+    fn call_presentation() {
+        check_presentation(
+            r#"
+mod color {
+    pub const fn rgba(r: u8, g: u8, b: u8, a: f32) -> u32 { 0 }
+}
+const RED: u32 = color::rgba(0, 0, 0, 1.0);
+"#,
+            RED,
+            expect![[r#"
+                [
+                    "color::rgba(255, 0, 0, 0.502)",
+                ]
+            "#]],
+        );
+    }
+}
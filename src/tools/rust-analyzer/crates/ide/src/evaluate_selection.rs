@@ -0,0 +1,292 @@
+use hir::{ConstEvalError, HirDisplay, Local, PathResolution, Semantics};
+use ide_db::{
+    base_db::{FileRange, SourceDatabaseExt},
+    FxHashMap, LineIndexDatabase, RootDatabase,
+};
+use syntax::{ast, hacks::parse_expr_from_str, AstNode, TextRange};
+
+/// The result of evaluating a selected expression.
+#[derive(Debug, PartialEq, Eq)]
+pub enum SelectionEvaluation {
+    /// The variables used by the selection that are defined outside of it and have no value yet,
+    /// by name and type. The selection is evaluated once all of them have a value.
+    MissingValues(Vec<(String, String)>),
+    Value(String),
+    Error(String),
+}
+
+// Feature: Evaluate Selection
+//
+// Evaluates the selected expression with the MIR interpreter, which is handy to check bit
+// twiddling or const arithmetic without a scratch file. The variables the expression uses from
+// outside of it are asked for first, as literals of numbers, `bool`s or `char`s.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **rust-analyzer: Evaluate Selection**
+// |===
+pub(crate) fn evaluate_selection(
+    db: &RootDatabase,
+    frange: FileRange,
+    values: &FxHashMap<String, String>,
+) -> Option<SelectionEvaluation> {
+    let sema = Semantics::new(db);
+    let source_file = sema.parse(frange.file_id);
+    let expr = source_file
+        .syntax()
+        .covering_element(frange.range)
+        .ancestors()
+        .find_map(ast::Expr::cast)?;
+
+    let mut missing = Vec::new();
+    let mut args = Vec::new();
+    for local in free_locals(&sema, &expr) {
+        let name = local.name(db).display(db).to_string();
+        match values.get(&name) {
+            Some(value) => match parse_expr_from_str(value) {
+                Some(value) => args.push((local, value)),
+                None => {
+                    return Some(SelectionEvaluation::Error(format!(
+                        "`{value}` is not a valid value for `{name}`"
+                    )))
+                }
+            },
+            None => missing.push((name, local.ty(db).display(db).to_string())),
+        }
+    }
+    if !missing.is_empty() {
+        return Some(SelectionEvaluation::MissingValues(missing));
+    }
+
+    let res = match sema.eval_expr(&expr, &args)? {
+        Ok(value) => SelectionEvaluation::Value(value),
+        Err(e) => SelectionEvaluation::Error(render_error(db, e)),
+    };
+    Some(res)
+}
+
+/// The locals `expr` uses that are defined outside of it, in the order of their first use.
+fn free_locals(sema: &Semantics<'_, RootDatabase>, expr: &ast::Expr) -> Vec<Local> {
+    let range = expr.syntax().text_range();
+    let mut res = Vec::new();
+    for path in expr.syntax().descendants().filter_map(ast::PathExpr::cast) {
+        let Some(PathResolution::Local(local)) = path.path().and_then(|it| sema.resolve_path(&it))
+        else {
+            continue;
+        };
+        let src = local.primary_source(sema.db);
+        let defined_inside = src.file() == sema.hir_file_for(expr.syntax())
+            && range.contains_range(src.syntax().text_range());
+        if !defined_inside && !res.contains(&local) {
+            res.push(local);
+        }
+    }
+    res
+}
+
+fn render_error(db: &RootDatabase, e: ConstEvalError) -> String {
+    let span_formatter = |file_id, text_range: TextRange| {
+        let path = &db
+            .source_root(db.file_source_root(file_id))
+            .path_for_file(&file_id)
+            .map(|x| x.to_string());
+        let path = path.as_deref().unwrap_or("<unknown file>");
+        match db.line_index(file_id).try_line_col(text_range.start()) {
+            Some(line_col) => format!("file://{path}#{}:{}", line_col.line + 1, line_col.col),
+            None => format!("file://{path} range {:?}", text_range),
+        }
+    };
+    let mut r = String::new();
+    _ = match e {
+        ConstEvalError::MirLowerError(e) => e.pretty_print(&mut r, db, span_formatter),
+        ConstEvalError::MirEvalError(e) => e.pretty_print(&mut r, db, span_formatter),
+    };
+    r.trim_end().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::FxHashMap;
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, values: &[(&str, &str)], expect: Expect) {
+        let (analysis, frange) = fixture::range(ra_fixture);
+        let values: FxHashMap<_, _> =
+            values.iter().map(|&(name, value)| (name.to_owned(), value.to_owned())).collect();
+        let res = analysis.evaluate_selection(frange, &values).unwrap();
+        expect.assert_debug_eq(&res);
+    }
+
+    #[test]
+    fn const_arithmetic() {
+        check(
+            r#"
+fn main() {
+    let x = $0(1u32 << 12) | 0x0F$0;
+}
+"#,
+            &[],
+            expect![[r#"
+                Some(
+                    Value(
+                        "4111 (0x100F)",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn asks_for_free_variables() {
+        check(
+            r#"
+fn main() {
+    let flags: u8 = 0b1010;
+    let mask = 0b0110u8;
+    let on = true;
+    let x = $0if on { flags & !mask } else { flags }$0;
+}
+"#,
+            &[],
+            expect![[r#"
+                Some(
+                    MissingValues(
+                        [
+                            (
+                                "on",
+                                "bool",
+                            ),
+                            (
+                                "flags",
+                                "u8",
+                            ),
+                            (
+                                "mask",
+                                "u8",
+                            ),
+                        ],
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn uses_supplied_values() {
+        check(
+            r#"
+fn main() {
+    let flags: u8 = 0b1010;
+    let mask = 0b0110u8;
+    let on = true;
+    let x = $0if on { flags & !mask } else { flags }$0;
+}
+"#,
+            &[("flags", "0xFF"), ("mask", "0b0110"), ("on", "true")],
+            expect![[r#"
+                Some(
+                    Value(
+                        "249 (0xF9)",
+                    ),
+                )
+            "#]],
+        );
+        check(
+            r#"
+fn f(a: i32, c: char) -> bool {
+    $0a * -3 > 0 && c == '4'$0
+}
+"#,
+            &[("a", "-7"), ("c", "'4'")],
+            expect![[r#"
+                Some(
+                    Value(
+                        "true",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn locals_of_the_selection_are_not_free() {
+        check(
+            r#"
+fn main() {
+    let shift = 3;
+    let x = $0{
+        let base = 1u64;
+        base << shift
+    }$0;
+}
+"#,
+            &[("shift", "40")],
+            expect![[r#"
+                Some(
+                    Value(
+                        "1099511627776 (0x10000000000)",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        check(
+            r#"
+fn main() {
+    let on = true;
+    let x = $0!on$0;
+}
+"#,
+            &[("on", "1")],
+            expect![[r#"
+                Some(
+                    Error(
+                        "Mir eval error:\nNotSupported(\"`1` as a value of type `bool`\")",
+                    ),
+                )
+            "#]],
+        );
+        check(
+            r#"
+fn main() {
+    let on = true;
+    let x = $0!on$0;
+}
+"#,
+            &[("on", "tru e")],
+            expect![[r#"
+                Some(
+                    Error(
+                        "`tru e` is not a valid value for `on`",
+                    ),
+                )
+            "#]],
+        );
+    }
+
+    #[test]
+    fn panics() {
+        check(
+            r#"
+//- minicore: panic
+fn main() {
+    let x = $0{ let d = 0u32; 10 / d }$0;
+}
+"#,
+            &[],
+            expect![[r#"
+                Some(
+                    Error(
+                        "Mir eval error:\nPanic with message:\n\"Overflow in Div\"",
+                    ),
+                )
+            "#]],
+        );
+    }
+}
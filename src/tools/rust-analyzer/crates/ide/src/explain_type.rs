@@ -0,0 +1,292 @@
+use std::fmt;
+
+use hir::{DescendPreference, PathResolution, Semantics, TypeTreeNodeKind};
+use ide_db::{defs::Definition, helpers::pick_best_token, RootDatabase};
+use syntax::{ast, AstNode, SyntaxKind, T};
+
+use crate::{FilePosition, NavigationTarget, TryToNav, UpmappingResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplainedTypeNodeKind {
+    /// A type or one of its components, like a generic argument or a tuple field.
+    Type,
+    /// A type alias, its only child is the aliased type.
+    Alias,
+    /// The type an associated type projection normalizes to.
+    Normalized,
+    /// The type hidden behind an `impl Trait` return type.
+    HiddenType,
+}
+
+#[derive(Debug)]
+pub struct ExplainedTypeNode {
+    pub label: String,
+    pub kind: ExplainedTypeNodeKind,
+    pub nav: Option<NavigationTarget>,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+/// The type at a position as a tree, the first node is the root.
+#[derive(Debug)]
+pub struct ExplainedType {
+    pub nodes: Vec<ExplainedTypeNode>,
+}
+
+impl fmt::Display for ExplainedType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn process(
+            f: &mut fmt::Formatter<'_>,
+            nodes: &[ExplainedTypeNode],
+            idx: usize,
+            depth: usize,
+        ) -> fmt::Result {
+            let node = &nodes[idx];
+            write!(f, "{}{}", "\t".repeat(depth), node.label)?;
+            match node.kind {
+                ExplainedTypeNodeKind::Type => {}
+                ExplainedTypeNodeKind::Alias => write!(f, " (alias)")?,
+                ExplainedTypeNodeKind::Normalized => write!(f, " (normalized)")?,
+                ExplainedTypeNodeKind::HiddenType => write!(f, " (hidden type)")?,
+            }
+            if let Some(nav) = &node.nav {
+                write!(f, " -> {}", nav.name)?;
+            }
+            writeln!(f)?;
+            node.children.iter().try_for_each(|&child| process(f, nodes, child, depth + 1))
+        }
+
+        if self.nodes.is_empty() {
+            return Ok(());
+        }
+        process(f, &self.nodes, 0, 0)
+    }
+}
+
+// Feature: Explain Type
+//
+// Shows the type of the expression, pattern or type under the cursor as an expandable tree of
+// its generic arguments, the types associated type projections normalize to and the hidden
+// types of `impl Trait` return types.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **rust-analyzer: Explain Type**
+// |===
+pub(crate) fn explain_type(db: &RootDatabase, position: FilePosition) -> Option<ExplainedType> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token =
+        pick_best_token(file.syntax().token_at_offset(position.offset), |kind| match kind {
+            SyntaxKind::IDENT | SyntaxKind::INT_NUMBER | T![self] | T![Self] => 3,
+            T!['('] | T![')'] => 2,
+            kind if kind.is_trivia() => 0,
+            _ => 1,
+        })?;
+    let token = sema.descend_into_macros_single(DescendPreference::None, token);
+
+    let mut nodes = Vec::new();
+    for node in token.parent_ancestors() {
+        if let Some(alias) = ast::TypeAlias::cast(node.clone()) {
+            let alias = sema.to_def(&alias)?;
+            let root = push_alias(db, &mut nodes, alias);
+            push_type(db, &mut nodes, &alias.ty(db), Some(root));
+        } else if let Some(ty) = ast::Type::cast(node.clone()) {
+            let resolved = sema.resolve_type(&ty)?;
+            let alias = match &ty {
+                ast::Type::PathType(path) => match sema.resolve_path(&path.path()?) {
+                    Some(PathResolution::Def(hir::ModuleDef::TypeAlias(alias))) => Some(alias),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let parent = alias.map(|alias| push_alias(db, &mut nodes, alias));
+            push_type(db, &mut nodes, &resolved, parent);
+        } else if let Some(pat) = ast::Pat::cast(node.clone()) {
+            push_type(db, &mut nodes, &sema.type_of_pat(&pat)?.original, None);
+        } else if let Some(expr) = ast::Expr::cast(node) {
+            push_type(db, &mut nodes, &sema.type_of_expr(&expr)?.original, None);
+        } else {
+            continue;
+        }
+        return Some(ExplainedType { nodes });
+    }
+    None
+}
+
+fn push_alias(
+    db: &RootDatabase,
+    nodes: &mut Vec<ExplainedTypeNode>,
+    alias: hir::TypeAlias,
+) -> usize {
+    nodes.push(ExplainedTypeNode {
+        label: alias.name(db).display(db).to_string(),
+        kind: ExplainedTypeNodeKind::Alias,
+        nav: nav_for(db, alias.into()),
+        parent: None,
+        children: Vec::new(),
+    });
+    nodes.len() - 1
+}
+
+fn push_type(
+    db: &RootDatabase,
+    nodes: &mut Vec<ExplainedTypeNode>,
+    ty: &hir::Type,
+    parent: Option<usize>,
+) {
+    let base = nodes.len();
+    let tree = ty.display_tree(db);
+    if tree.nodes.is_empty() {
+        return;
+    }
+    if let Some(parent) = parent {
+        nodes[parent].children.push(base);
+    }
+    nodes.extend(tree.nodes.into_iter().map(|node| ExplainedTypeNode {
+        label: node.label,
+        kind: match node.kind {
+            TypeTreeNodeKind::Type => ExplainedTypeNodeKind::Type,
+            TypeTreeNodeKind::Normalized => ExplainedTypeNodeKind::Normalized,
+            TypeTreeNodeKind::HiddenType => ExplainedTypeNodeKind::HiddenType,
+        },
+        nav: node.definition.and_then(|def| nav_for(db, def.into())),
+        parent: node.parent.map(|it| base + it).or(parent),
+        children: node.children.into_iter().map(|it| base + it).collect(),
+    }));
+}
+
+fn nav_for(db: &RootDatabase, def: Definition) -> Option<NavigationTarget> {
+    def.try_to_nav(db).map(UpmappingResult::call_site)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let explained = analysis.explain_type(position).unwrap();
+        let actual = explained.map_or_else(|| "no type".to_owned(), |it| it.to_string());
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn explain_type_none() {
+        check(r#"$0"#, expect!["no type"]);
+    }
+
+    #[test]
+    fn explain_type_generic_args() {
+        check(
+            r#"
+struct Foo<T, U>(T, U);
+struct Bar;
+fn main() {
+    let foo$0 = Foo(Bar, (0u8, Bar));
+}
+"#,
+            expect![[r#"
+                Foo<Bar, (u8, Bar)> -> Foo
+                	Bar -> Bar
+                	(u8, Bar)
+                		u8
+                		Bar -> Bar
+            "#]],
+        );
+    }
+
+    #[test]
+    fn explain_type_expr() {
+        check(
+            r#"
+struct Foo;
+fn main() {
+    let foo = &Foo;
+    foo$0;
+}
+"#,
+            expect![[r#"
+                &Foo
+                	Foo -> Foo
+            "#]],
+        );
+    }
+
+    #[test]
+    fn explain_type_alias() {
+        check(
+            r#"
+struct Foo<T>(T);
+type Alias$0 = Foo<u32>;
+"#,
+            expect![[r#"
+                Alias (alias) -> Alias
+                	Foo<u32> -> Foo
+                		u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn explain_type_alias_use() {
+        check(
+            r#"
+struct Foo<T>(T);
+type Alias = Foo<u32>;
+fn f(_: Alias$0) {}
+"#,
+            expect![[r#"
+                Alias (alias) -> Alias
+                	Foo<u32> -> Foo
+                		u32
+            "#]],
+        );
+    }
+
+    #[test]
+    fn explain_type_impl_trait_hidden_type() {
+        check(
+            r#"
+//- minicore: sized
+trait Trait {}
+struct Foo;
+impl Trait for Foo {}
+fn foo() -> impl Trait { Foo }
+fn main() {
+    let it$0 = foo();
+}
+"#,
+            expect![[r#"
+                impl Trait -> Trait
+                	Foo (hidden type) -> Foo
+            "#]],
+        );
+    }
+
+    #[test]
+    fn explain_type_normalized_projection() {
+        check(
+            r#"
+trait Trait {
+    type Assoc;
+}
+struct Foo;
+struct Bar;
+impl Trait for Foo {
+    type Assoc = Bar;
+}
+fn f(_: <Foo as Trait>::Assoc$0) {}
+"#,
+            expect![[r#"
+                Assoc (alias) -> Assoc
+                	<Foo as Trait>::Assoc -> Trait
+                		Foo -> Foo
+                		Bar (normalized) -> Bar
+            "#]],
+        );
+    }
+}
@@ -0,0 +1,421 @@
+use std::fmt;
+
+use hir::{DescendPreference, HirDisplay, Impl, PathResolution, Semantics, Trait, Type};
+use ide_db::{famous_defs::FamousDefs, helpers::pick_best_token, RootDatabase};
+use syntax::{
+    ast::{self, make},
+    match_ast, AstNode, SyntaxKind, T,
+};
+
+use crate::FilePosition;
+
+/// How deep to follow fields before giving up, so that recursive types can't loop forever.
+const MAX_DEPTH: usize = 16;
+
+/// Why a type doesn't implement a trait.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnmetBound {
+    /// One sentence per step from the type to the component that is at fault, like
+    /// "`S` is not `Send` because field `rc: Rc<String>` is not `Send`".
+    pub reasons: Vec<String>,
+}
+
+impl fmt::Display for UnmetBound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.reasons.iter().try_for_each(|reason| writeln!(f, "{reason}"))
+    }
+}
+
+// Feature: Explain Unmet Bound
+//
+// Explains why the type of the expression, pattern or type under the cursor doesn't implement a
+// trait like `Send`, `Sync` or `Sized`, by following the fields, elements and captures that don't
+// implement it down to the one that is at fault.
+//
+// Hovering an argument that doesn't satisfy an auto trait bound of the called function shows the
+// same explanation.
+//
+// Available on the command line as `rust-analyzer explain-unmet-bound`.
+pub(crate) fn explain_unmet_bound(
+    db: &RootDatabase,
+    position: FilePosition,
+    trait_name: &str,
+) -> Option<UnmetBound> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token =
+        pick_best_token(file.syntax().token_at_offset(position.offset), |kind| match kind {
+            SyntaxKind::IDENT | SyntaxKind::INT_NUMBER | T![self] | T![Self] => 3,
+            T!['('] | T![')'] => 2,
+            kind if kind.is_trivia() => 0,
+            _ => 1,
+        })?;
+    let token = sema.descend_into_macros_single(DescendPreference::None, token);
+
+    let (node, ty) = token.parent_ancestors().find_map(|node| {
+        let ty = if let Some(ty) = ast::Type::cast(node.clone()) {
+            sema.resolve_type(&ty)?
+        } else if let Some(pat) = ast::Pat::cast(node.clone()) {
+            sema.type_of_pat(&pat)?.original
+        } else {
+            sema.type_of_expr(&ast::Expr::cast(node.clone())?)?.original
+        };
+        Some((node, ty))
+    })?;
+
+    let scope = sema.scope(&node)?;
+    let path = make::path_from_text(trait_name);
+    let trait_ = match scope.speculative_resolve(&path)? {
+        PathResolution::Def(hir::ModuleDef::Trait(it)) => it,
+        _ => return None,
+    };
+    explain(&FamousDefs(&sema, scope.krate()), &ty, trait_)
+}
+
+/// Explains the auto trait bounds of the called function's parameter that `arg` doesn't satisfy.
+pub(crate) fn unmet_arg_bounds(
+    sema: &Semantics<'_, RootDatabase>,
+    arg: &ast::Expr,
+) -> Vec<UnmetBound> {
+    (|| {
+        let db = sema.db;
+        let arg_list = ast::ArgList::cast(arg.syntax().parent()?)?;
+        let idx = arg_list.args().position(|it| it == *arg)?;
+        let params = match_ast! {
+            match (arg_list.syntax().parent()?) {
+                ast::CallExpr(call) => {
+                    let callee = sema.type_of_expr(&call.expr()?)?.original;
+                    match callee.as_callable(db)?.kind() {
+                        hir::CallableKind::Function(func) => func.assoc_fn_params(db),
+                        _ => return None,
+                    }
+                },
+                ast::MethodCallExpr(call) => sema.resolve_method_call(&call)?.params_without_self(db),
+                _ => return None,
+            }
+        };
+        let type_param = params.get(idx)?.ty().as_type_param(db)?;
+        let arg_ty = sema.type_of_expr(arg)?.original;
+        let famous_defs = FamousDefs(sema, sema.scope(arg.syntax())?.krate());
+        let unmet = type_param
+            .trait_bounds(db)
+            .into_iter()
+            .filter(|it| it.is_auto(db))
+            .filter_map(|it| explain(&famous_defs, &arg_ty, it))
+            .collect();
+        Some(unmet)
+    })()
+    .unwrap_or_default()
+}
+
+/// Explains why `ty` doesn't implement `trait_`, `None` if it does.
+fn explain(famous_defs: &FamousDefs<'_, '_>, ty: &Type, trait_: Trait) -> Option<UnmetBound> {
+    let db = famous_defs.0.db;
+    if implements(db, ty, trait_) {
+        return None;
+    }
+    let mut reasons = Vec::new();
+    let (mut ty, mut trait_) = (ty.clone(), trait_);
+    for _ in 0..MAX_DEPTH {
+        let ty_name = ty.display(db).to_string();
+        let trait_name = trait_.name(db).display(db).to_string();
+        match culprit(famous_defs, &ty, trait_) {
+            Culprit::Component { description, ty: component, trait_: component_trait } => {
+                let component_trait_name = component_trait.name(db).display(db).to_string();
+                reasons.push(format!(
+                    "`{ty_name}` is not `{trait_name}` because {description} is not `{component_trait_name}`"
+                ));
+                (ty, trait_) = (component, component_trait);
+            }
+            Culprit::Leaf(reason) => {
+                reasons.push(reason.render(&ty_name, &trait_name));
+                break;
+            }
+        }
+    }
+    Some(UnmetBound { reasons })
+}
+
+enum Culprit {
+    /// A part of the type that doesn't implement the trait it has to for the type to implement it.
+    Component {
+        description: String,
+        ty: Type,
+        trait_: Trait,
+    },
+    Leaf(LeafReason),
+}
+
+enum LeafReason {
+    NegativeImpl,
+    UnmetImplBounds,
+    RawPointer,
+    Unsized,
+    TypeParam,
+    Other,
+}
+
+impl LeafReason {
+    fn render(self, ty: &str, trait_: &str) -> String {
+        match self {
+            LeafReason::NegativeImpl => format!("`{ty}` explicitly opts out of `{trait_}`"),
+            LeafReason::UnmetImplBounds => {
+                format!(
+                    "`{ty}` is not `{trait_}` because the bounds of its `{trait_}` impl aren't met"
+                )
+            }
+            LeafReason::RawPointer => format!("raw pointers like `{ty}` are never `{trait_}`"),
+            LeafReason::Unsized => format!("`{ty}` doesn't have a size known at compile-time"),
+            LeafReason::TypeParam => {
+                format!("`{ty}` is not `{trait_}` because it isn't bounded by `{trait_}`")
+            }
+            LeafReason::Other => format!("`{ty}` is not `{trait_}`"),
+        }
+    }
+}
+
+/// Finds the part of `ty` that keeps it from implementing `trait_`.
+///
+/// Only auto traits and `Sized` are implemented structurally, for other traits this is always a
+/// leaf.
+fn culprit(famous_defs: &FamousDefs<'_, '_>, ty: &Type, trait_: Trait) -> Culprit {
+    let db = famous_defs.0.db;
+    let is_sized = famous_defs.core_marker_Sized() == Some(trait_);
+    if !trait_.is_auto(db) && !is_sized {
+        return Culprit::Leaf(LeafReason::Other);
+    }
+    if is_sized
+        && (ty.is_slice()
+            || ty.as_builtin().map_or(false, |it| it.is_str())
+            || ty.as_dyn_trait().is_some())
+    {
+        return Culprit::Leaf(LeafReason::Unsized);
+    }
+    if ty.as_type_param(db).is_some() {
+        return Culprit::Leaf(LeafReason::TypeParam);
+    }
+    if ty.is_raw_ptr() && !is_sized {
+        return Culprit::Leaf(LeafReason::RawPointer);
+    }
+
+    if let Some((pointee, mutability)) = ty.as_reference() {
+        if is_sized {
+            return Culprit::Leaf(LeafReason::Other);
+        }
+        // `&T` is only `Send` if `T` is `Sync`.
+        let needed = match famous_defs.core_marker_Sync() {
+            Some(sync) if mutability == hir::Mutability::Shared => sync,
+            _ => trait_,
+        };
+        return Culprit::Component {
+            description: format!("the referenced type `{}`", pointee.display(db)),
+            ty: pointee,
+            trait_: needed,
+        };
+    }
+
+    if !is_sized {
+        let explicit_impl = Impl::all_for_type(db, ty.clone())
+            .into_iter()
+            .find(|impl_| impl_.trait_(db) == Some(trait_));
+        if let Some(impl_) = explicit_impl {
+            return Culprit::Leaf(if impl_.is_negative(db) {
+                LeafReason::NegativeImpl
+            } else {
+                LeafReason::UnmetImplBounds
+            });
+        }
+    }
+
+    let mut components = components(db, ty);
+    if is_sized {
+        // Only the last field of a struct or tuple may be unsized.
+        components = components.pop().into_iter().collect();
+    }
+    match components.into_iter().find(|(_, it)| !implements(db, it, trait_)) {
+        Some((description, component)) => Culprit::Component { description, ty: component, trait_ },
+        None => Culprit::Leaf(LeafReason::Other),
+    }
+}
+
+fn implements(db: &RootDatabase, ty: &Type, trait_: Trait) -> bool {
+    match ty.as_closure() {
+        // The trait solver doesn't know about captures, so closures are always `Send` and `Sync`
+        // to it.
+        Some(closure) if trait_.is_auto(db) => {
+            closure.capture_types(db).iter().all(|it| implements(db, it, trait_))
+        }
+        _ => ty.impls_trait(db, trait_, &[]),
+    }
+}
+
+/// The types `ty` is made of, with a description of where they appear.
+fn components(db: &RootDatabase, ty: &Type) -> Vec<(String, Type)> {
+    let field = |name: hir::Name, ty: Type| {
+        (format!("field `{}: {}`", name.display(db), ty.display(db)), ty)
+    };
+    if let Some(closure) = ty.as_closure() {
+        return closure
+            .captured_items(db)
+            .into_iter()
+            .zip(closure.capture_types(db))
+            .map(|(capture, ty)| {
+                (format!("captured `{}: {}`", capture.display_place(db), ty.display(db)), ty)
+            })
+            .collect();
+    }
+    if let Some((elem, _)) = ty.as_array(db) {
+        return vec![(format!("the element type `{}`", elem.display(db)), elem)];
+    }
+    if ty.is_tuple() {
+        return ty
+            .tuple_fields(db)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, ty)| (format!("element `{idx}: {}`", ty.display(db)), ty))
+            .collect();
+    }
+    match ty.as_adt() {
+        Some(hir::Adt::Enum(enum_)) => {
+            let args: Vec<_> = ty.type_arguments().collect();
+            enum_
+                .variants(db)
+                .into_iter()
+                .flat_map(|variant| {
+                    let args = &args;
+                    variant.fields(db).into_iter().map(move |it| {
+                        let ty = it.ty_with_args(db, args.iter().cloned());
+                        let (description, ty) = field(it.name(db), ty);
+                        (format!("{description} of variant `{}`", variant.name(db).display(db)), ty)
+                    })
+                })
+                .collect()
+        }
+        Some(_) => ty.fields(db).into_iter().map(|(it, ty)| field(it.name(db), ty)).collect(),
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(trait_name: &str, ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let explained = analysis.explain_unmet_bound(position, trait_name).unwrap();
+        let actual = explained.map_or_else(|| "implemented\n".to_owned(), |it| it.to_string());
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn field_with_negative_impl() {
+        check(
+            "Send",
+            r#"
+//- minicore: send, sync
+#![feature(negative_impls)]
+struct Rc<T>(T);
+impl<T> !Send for Rc<T> {}
+
+struct Inner { rc: Rc<String> }
+struct String;
+struct Outer { ok: u32, inner: Inner }
+
+fn f(outer$0: Outer) {}
+"#,
+            expect![[r#"
+                `Outer` is not `Send` because field `inner: Inner` is not `Send`
+                `Inner` is not `Send` because field `rc: Rc<String>` is not `Send`
+                `Rc<String>` explicitly opts out of `Send`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn shared_reference_needs_sync() {
+        check(
+            "Send",
+            r#"
+//- minicore: send, sync
+#![feature(negative_impls)]
+struct Cell(u32);
+impl !Sync for Cell {}
+
+fn f(cell: &Cell) {
+    let pair = (1, cell);
+    pair$0;
+}
+"#,
+            expect![[r#"
+                `(i32, &Cell)` is not `Send` because element `1: &Cell` is not `Send`
+                `&Cell` is not `Send` because the referenced type `Cell` is not `Sync`
+                `Cell` explicitly opts out of `Sync`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn enum_variants_and_closure_captures() {
+        check(
+            "Sync",
+            r#"
+//- minicore: send, sync, fn
+fn f(p: *const u8) {
+    let e = E::B { ptr: p };
+    let c = move || e;
+    c$0;
+}
+enum E { A(u32), B { ptr: *const u8 } }
+"#,
+            expect![[r#"
+                `impl FnOnce() -> E` is not `Sync` because captured `e: E` is not `Sync`
+                `E` is not `Sync` because field `ptr: *const u8` of variant `B` is not `Sync`
+                raw pointers like `*const u8` are never `Sync`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unsized_and_type_params() {
+        check(
+            "Sized",
+            r#"
+//- minicore: sized, slice
+struct Wrapper<T: ?Sized> { len: usize, data: T }
+
+fn f(w: &Wrapper$0<[u8]>) {}
+"#,
+            expect![[r#"
+                `Wrapper<[u8]>` is not `Sized` because field `data: [u8]` is not `Sized`
+                `[u8]` doesn't have a size known at compile-time
+            "#]],
+        );
+        check(
+            "Send",
+            r#"
+//- minicore: send
+fn f<T>(t$0: T) {}
+"#,
+            expect![[r#"
+                `T` is not `Send` because it isn't bounded by `Send`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn implemented() {
+        check(
+            "Send",
+            r#"
+//- minicore: send
+struct S { x: u32 }
+fn f(s$0: S) {}
+"#,
+            expect![[r#"
+                implemented
+            "#]],
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use ide_db::{syntax_helpers::node_ext::vis_eq, FxHashSet};
 use syntax::{
     ast::{self, AstNode, AstToken},
-    match_ast, Direction, NodeOrToken, SourceFile,
+    match_ast, Direction, NodeOrToken, SourceFile, SyntaxNode,
     SyntaxKind::{self, *},
     TextRange, TextSize,
 };
@@ -11,7 +11,7 @@
 const REGION_START: &str = "// region:";
 const REGION_END: &str = "// endregion";
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FoldKind {
     Comment,
     Imports,
@@ -27,7 +27,7 @@ pub enum FoldKind {
     MatchArm,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Fold {
     pub range: TextRange,
     pub kind: FoldKind,
@@ -38,6 +38,30 @@ pub struct Fold {
 // Defines folding regions for curly braced blocks, runs of consecutive use, mod, const or static
 // items, and `region` / `endregion` comment markers.
 pub(crate) fn folding_ranges(file: &SourceFile) -> Vec<Fold> {
+    folds_in(file.syntax())
+}
+
+/// Computes the folds inside the function at `fn_range`, to update the folds of a file after an
+/// edit inside that function, see [`crate::edited_fn_range`].
+///
+/// Returns `None` if the function has `region` markers, which can pair with markers outside it.
+pub(crate) fn fn_folding_ranges(file: &SourceFile, fn_range: TextRange) -> Option<Vec<Fold>> {
+    let func = file
+        .syntax()
+        .covering_element(fn_range)
+        .ancestors()
+        .filter_map(ast::Fn::cast)
+        .find(|it| it.syntax().text_range() == fn_range)?;
+    let has_region_markers = func.syntax().descendants_with_tokens().any(|it| {
+        it.into_token().and_then(ast::Comment::cast).map_or(false, |comment| {
+            let text = comment.text().trim_start();
+            text.starts_with(REGION_START) || text.starts_with(REGION_END)
+        })
+    });
+    (!has_region_markers).then(|| folds_in(func.syntax()))
+}
+
+fn folds_in(root: &SyntaxNode) -> Vec<Fold> {
     let mut res = vec![];
     let mut visited_comments = FxHashSet::default();
     let mut visited_imports = FxHashSet::default();
@@ -48,7 +72,7 @@ pub(crate) fn folding_ranges(file: &SourceFile) -> Vec<Fold> {
     // regions can be nested, here is a LIFO buffer
     let mut region_starts: Vec<TextSize> = vec![];
 
-    for element in file.syntax().descendants_with_tokens() {
+    for element in root.descendants_with_tokens() {
         // Fold items that span multiple lines
         if let Some(kind) = fold_kind(element.kind()) {
             let is_multiline = match &element {
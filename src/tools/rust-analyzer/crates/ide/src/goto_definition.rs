@@ -4,11 +4,12 @@
     doc_links::token_as_doc_comment, navigation_target::ToNav, FilePosition, NavigationTarget,
     RangeInfo, TryToNav,
 };
-use hir::{AsAssocItem, AssocItem, DescendPreference, ModuleDef, Semantics};
+use hir::{AsAssocItem, AssocItem, Crate, DescendPreference, ModuleDef, Semantics};
 use ide_db::{
     base_db::{AnchoredPath, FileId, FileLoader},
+    crate_features::{cargo_features, cfg_feature_name},
     defs::{Definition, IdentClass},
-    helpers::pick_best_token,
+    helpers::{env_var_name, pick_best_token},
     RootDatabase,
 };
 use itertools::Itertools;
@@ -68,6 +69,14 @@ pub(crate) fn goto_definition(
         ));
     }
 
+    if let Some(nav) = try_lookup_env_var(sema, &original_token) {
+        return Some(RangeInfo::new(original_token.text_range(), vec![nav]));
+    }
+
+    if let Some(nav) = try_lookup_cfg_feature(sema, &original_token) {
+        return Some(RangeInfo::new(original_token.text_range(), vec![nav]));
+    }
+
     let navs = sema
         .descend_into_macros(DescendPreference::None, original_token.clone())
         .into_iter()
@@ -145,6 +154,66 @@ fn try_lookup_include_path(
     })
 }
 
+/// Navigates from the variable name of an `env!` call to the build script line setting it with
+/// `cargo:rustc-env`.
+/// Finds the declaration of the feature in `#[cfg(feature = "name")]` in the `Cargo.toml`.
+fn try_lookup_cfg_feature(
+    sema: &Semantics<'_, RootDatabase>,
+    token: &SyntaxToken,
+) -> Option<NavigationTarget> {
+    let name = cfg_feature_name(sema, token)?;
+    let krate = sema.scope(&token.parent()?)?.krate();
+    let features = cargo_features(sema.db, krate)?;
+    let range = features.declaration(&name)?;
+    Some(NavigationTarget {
+        file_id: features.file_id,
+        full_range: range,
+        name: name.into(),
+        alias: None,
+        focus_range: None,
+        kind: None,
+        container_name: None,
+        description: None,
+        docs: None,
+    })
+}
+
+fn try_lookup_env_var(
+    sema: &Semantics<'_, RootDatabase>,
+    token: &SyntaxToken,
+) -> Option<NavigationTarget> {
+    use ide_db::base_db::SourceDatabaseExt;
+
+    let var = env_var_name(sema, token)?;
+    let db = sema.db;
+    let krate = sema.scope(&token.parent()?)?.krate();
+    let source_root = db.file_source_root(krate.root_file(db));
+    let build_script = Crate::all(db).into_iter().find(|it| {
+        it.display_name(db).is_some_and(|name| &*name == "build_script_build")
+            && db.file_source_root(it.root_file(db)) == source_root
+    })?;
+
+    let file_id = build_script.root_file(db);
+    let needle = format!("rustc-env={var}=");
+    let string = sema
+        .parse(file_id)
+        .syntax()
+        .descendants_with_tokens()
+        .filter_map(|it| it.into_token().and_then(ast::String::cast))
+        .find(|it| it.text().contains(&needle))?;
+    Some(NavigationTarget {
+        file_id,
+        full_range: string.syntax().text_range(),
+        name: var.into(),
+        alias: None,
+        focus_range: None,
+        kind: None,
+        container_name: None,
+        description: None,
+        docs: None,
+    })
+}
+
 fn try_lookup_macro_def_in_macro_use(
     sema: &Semantics<'_, RootDatabase>,
     token: SyntaxToken,
@@ -1548,6 +1617,90 @@ macro_rules! include {
         );
     }
 
+    #[test]
+    fn goto_env_var_in_build_script() {
+        check(
+            r#"
+//- minicore: env
+//- /main.rs crate:main env:GIT_HASH=abc123
+fn main() {
+    let hash = env!("GIT_HASH$0");
+}
+//- /build.rs crate:build_script_build
+fn main() {
+    println!("cargo:rustc-env=OTHER=1");
+    println!("cargo:rustc-env=GIT_HASH={}", "abc123");
+           //^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_cfg_feature_in_cargo_toml() {
+        check(
+            r#"
+//- /main.rs crate:main cfg:feature=std
+#[cfg(all(unix, feature = "std$0"))]
+fn f() {}
+//- /Cargo.toml
+[package]
+name = "main"
+
+[features]
+default = ["std"]
+  std = []
+//^^^
+"#,
+        );
+        check(
+            r#"
+//- /src/main.rs crate:main
+fn f() -> bool {
+    cfg!(feature = "al$0loc")
+}
+//- /Cargo.toml
+[features]
+  alloc = []
+//^^^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn goto_cfg_feature_unresolved() {
+        let check = |ra_fixture: &str| {
+            let (analysis, position) = fixture::position(ra_fixture);
+            let navs = analysis.goto_definition(position).unwrap();
+            assert!(
+                navs.as_ref().map_or(true, |it| it.info.is_empty()),
+                "unexpected definition: {navs:?}"
+            );
+        };
+        // Undeclared features.
+        check(
+            r#"
+//- /main.rs crate:main
+#[cfg_attr(feature = "std$0", derive(Debug))]
+struct S;
+//- /Cargo.toml
+[features]
+alloc = []
+"#,
+        );
+        // Not a `cfg` predicate.
+        check(
+            r#"
+//- /main.rs crate:main
+#[doc(feature = "std$0")]
+struct S;
+//- /Cargo.toml
+[features]
+std = []
+"#,
+        );
+    }
+
     mod goto_impl_of_trait_fn {
         use super::check;
         #[test]
@@ -2159,6 +2312,63 @@ fn test() {
         );
     }
 
+    #[test]
+    fn implicit_format_args_in_logging_macros() {
+        check(
+            r#"
+//- /main.rs crate:main deps:log
+fn test() {
+    let a = "world";
+     // ^
+    log::info!("hello {a$0}");
+}
+//- /log.rs crate:log
+#[rustc_builtin_macro]
+#[macro_export]
+macro_rules! format_args {}
+#[doc(hidden)]
+pub fn __private_api_log(_: ()) {}
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $lvl:expr, $($arg:tt)+) => {
+        $crate::__private_api_log($crate::format_args!($($arg)+))
+    };
+    ($lvl:expr, $($arg:tt)+) => ($crate::log!(target: "", $lvl, $($arg)+));
+}
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)+) => ($crate::log!(1, $($arg)+));
+}
+"#,
+        );
+        check(
+            r#"
+//- /main.rs crate:main deps:tracing
+fn test() {
+    let a = "world";
+     // ^
+    tracing::info!(target: "app", "hello {a$0}");
+}
+//- /tracing.rs crate:tracing
+#[rustc_builtin_macro]
+#[macro_export]
+macro_rules! format_args {}
+#[doc(hidden)]
+pub fn __event(_: ()) {}
+#[macro_export]
+macro_rules! event {
+    (target: $target:expr, $lvl:expr, $fmt:literal $(, $arg:expr)*) => {
+        $crate::__event($crate::format_args!($fmt $(, $arg)*))
+    };
+}
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => ($crate::event!(target: $target, 1, $($arg)+));
+}
+"#,
+        );
+    }
+
     #[test]
     fn goto_macro_def_from_macro_use() {
         check(
@@ -2,6 +2,7 @@
 use ide_db::{
     defs::{Definition, NameClass, NameRefClass},
     helpers::pick_best_token,
+    impl_search::ImplPattern,
     RootDatabase,
 };
 use syntax::{ast, AstNode, SyntaxKind::*, T};
@@ -12,6 +13,9 @@
 //
 // Navigates to the impl blocks of types.
 //
+// On a trait path with generic arguments, like `From<Io>` in `T: From<Io>`, only the impls matching
+// the arguments are shown.
+//
 // |===
 // | Editor  | Shortcut
 //
@@ -32,11 +36,13 @@ pub(crate) fn goto_implementation(
         _ => 0,
     })?;
     let range = original_token.text_range();
+    let node = sema
+        .descend_into_macros_single(DescendPreference::SameText, original_token)
+        .parent()
+        .and_then(ast::NameLike::cast);
     let navs =
-        sema.descend_into_macros_single(DescendPreference::SameText, original_token)
-            .parent()
-            .and_then(ast::NameLike::cast)
-            .and_then(|node| match &node {
+        node.as_ref()
+            .and_then(|node| match node {
                 ast::NameLike::Name(name) => {
                     NameClass::classify(&sema, name).and_then(|class| match class {
                         NameClass::Definition(it) | NameClass::ConstReference(it) => Some(it),
@@ -53,7 +59,17 @@ pub(crate) fn goto_implementation(
             })
             .and_then(|def| {
                 let navs = match def {
-                    Definition::Trait(trait_) => impls_for_trait(&sema, trait_),
+                    Definition::Trait(trait_) => {
+                        match node.as_ref().and_then(|it| trait_pattern(&sema, it)) {
+                            Some(pattern) => pattern
+                                .search(sema.db)
+                                .into_iter()
+                                .filter_map(|imp| imp.try_to_nav(sema.db))
+                                .flatten()
+                                .collect(),
+                            None => impls_for_trait(&sema, trait_),
+                        }
+                    }
                     Definition::Adt(adt) => impls_for_ty(&sema, adt.ty(sema.db)),
                     Definition::TypeAlias(alias) => impls_for_ty(&sema, alias.ty(sema.db)),
                     Definition::BuiltinType(builtin) => impls_for_ty(&sema, builtin.ty(sema.db)),
@@ -78,6 +94,15 @@ pub(crate) fn goto_implementation(
     Some(RangeInfo { range, info: navs })
 }
 
+/// The pattern of a trait path with generic arguments, like `From<Io>`.
+fn trait_pattern(sema: &Semantics<'_, RootDatabase>, node: &ast::NameLike) -> Option<ImplPattern> {
+    let ast::NameLike::NameRef(name_ref) = node else { return None };
+    let segment = name_ref.syntax().parent().and_then(ast::PathSegment::cast)?;
+    segment.generic_arg_list()?;
+    let path = segment.parent_path();
+    ImplPattern::from_trait_path(&sema.scope(path.syntax())?, &path, None).ok()
+}
+
 fn impls_for_ty(sema: &Semantics<'_, RootDatabase>, ty: hir::Type) -> Vec<NavigationTarget> {
     Impl::all_for_type(sema.db, ty)
         .into_iter()
@@ -229,6 +254,25 @@ impl crate::T for crate::Foo {}
         );
     }
 
+    #[test]
+    fn goto_implementation_for_trait_with_args() {
+        check(
+            r#"
+trait Into<T> {}
+struct Io;
+struct Fmt;
+struct MyError;
+struct Other;
+impl Into<MyError> for Io {}
+                     //^^
+impl Into<Other> for Io {}
+impl Into<MyError> for Fmt {}
+                     //^^^
+fn convert<E: Into$0<MyError>>(e: E) {}
+"#,
+        );
+    }
+
     #[test]
     fn goto_implementation_all_impls() {
         check(
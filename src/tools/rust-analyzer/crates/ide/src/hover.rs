@@ -11,14 +11,17 @@
     base_db::FileRange,
     defs::{Definition, IdentClass, NameRefClass, OperatorClass},
     famous_defs::FamousDefs,
-    helpers::pick_best_token,
+    helpers::{env_var_name, pick_best_token},
+    lifetime_elision::ElidedLifetimes,
     FxIndexSet, RootDatabase,
 };
 use itertools::Itertools;
+use stdx::format_to;
 use syntax::{ast, match_ast, AstNode, AstToken, SyntaxKind::*, SyntaxNode, T};
 
 use crate::{
     doc_links::token_as_doc_comment,
+    explain_unmet_bound, macro_provenance,
     markdown_remove::remove_markdown,
     markup::Markup,
     navigation_target::UpmappingResult,
@@ -40,6 +43,7 @@ pub struct MemoryLayoutHoverConfig {
     pub offset: Option<MemoryLayoutHoverRenderKind>,
     pub alignment: Option<MemoryLayoutHoverRenderKind>,
     pub niches: bool,
+    pub variants: bool,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -165,6 +169,14 @@ fn hover_simple(
         return Some(RangeInfo::new(range, res));
     }
 
+    if let Some(var) = env_var_name(sema, &original_token) {
+        let krate = sema.scope(&original_token.parent()?)?.krate();
+        return Some(RangeInfo::new(
+            original_token.text_range(),
+            render::env_var(sema.db, krate, &var),
+        ));
+    }
+
     let in_attr = original_token
         .parent_ancestors()
         .filter_map(ast::Item::cast)
@@ -331,6 +343,9 @@ fn hover_simple(
 
     result.map(|mut res: HoverResult| {
         res.actions = dedupe_or_merge_hover_actions(res.actions);
+        if let Some(expr) = original_token.parent_ancestors().find_map(ast::Expr::cast) {
+            push_unmet_arg_bounds(sema, &expr, &mut res);
+        }
         RangeInfo::new(original_token.text_range(), res)
     })
 }
@@ -357,7 +372,10 @@ fn hover_ranged(
         _ => None,
     };
     let res = res.or_else(|| render::type_info_of(sema, config, &expr_or_pat));
-    res.map(|it| {
+    res.map(|mut it| {
+        if let Either::Left(expr) = &expr_or_pat {
+            push_unmet_arg_bounds(sema, expr, &mut it);
+        }
         let range = match expr_or_pat {
             Either::Left(it) => it.syntax().text_range(),
             Either::Right(it) => it.syntax().text_range(),
@@ -396,20 +414,84 @@ pub(crate) fn hover_for_definition(
     };
     let notable_traits = def_ty.map(|ty| notable_traits(db, &ty)).unwrap_or_default();
 
-    render::definition(sema.db, def, famous_defs.as_ref(), &notable_traits, config).map(|markup| {
-        HoverResult {
-            markup: render::process_markup(sema.db, def, &markup, config),
-            actions: [
-                show_implementations_action(sema.db, def),
-                show_fn_references_action(sema.db, def),
-                runnable_action(sema, def, file_id),
-                goto_type_action_for_def(sema.db, def, &notable_traits),
-            ]
-            .into_iter()
-            .flatten()
-            .collect(),
-        }
-    })
+    let reexport_docs = render::reexport_docs(sema, def, scope_node, config);
+    render::definition(sema.db, def, famous_defs.as_ref(), &notable_traits, reexport_docs, config)
+        .map(|markup| {
+            let mut markup = render::process_markup(sema.db, def, &markup, config);
+            if let Definition::Function(func) = def {
+                push_lifetime_elision(sema, func, &mut markup);
+            }
+            push_generated_by(sema, def, &mut markup);
+            HoverResult {
+                markup,
+                actions: [
+                    show_implementations_action(sema.db, def),
+                    show_fn_references_action(sema.db, def),
+                    runnable_action(sema, def, file_id),
+                    goto_type_action_for_def(sema.db, def, &notable_traits),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            }
+        })
+}
+
+/// Explains why `arg` doesn't satisfy the auto trait bounds of the function it is passed to.
+fn push_unmet_arg_bounds(
+    sema: &Semantics<'_, RootDatabase>,
+    arg: &ast::Expr,
+    res: &mut HoverResult,
+) {
+    for unmet in explain_unmet_bound::unmet_arg_bounds(sema, arg) {
+        let reasons = unmet.reasons.iter().join("\n\n");
+        res.markup = Markup::from(format!("{}\n---\n{reasons}", res.markup));
+    }
+}
+
+/// Shows the signature of `func` with its elided lifetimes named, if the return type borrows from
+/// the parameters through elided lifetimes or `func` is an `async fn`.
+fn push_lifetime_elision(
+    sema: &Semantics<'_, RootDatabase>,
+    func: hir::Function,
+    markup: &mut Markup,
+) {
+    let Some(source) = sema.source(func) else { return };
+    if source.file_id.is_macro() {
+        return;
+    }
+    let Some(elided) = ElidedLifetimes::new(sema, &source.value) else { return };
+    let is_async = source.value.async_token().is_some();
+    if elided.output.is_none() && !is_async {
+        return;
+    }
+
+    let desugared = source.value.clone_for_update();
+    elided.apply(&desugared);
+    let signature = desugared
+        .syntax()
+        .children_with_tokens()
+        .filter(|it| !matches!(it.kind(), ATTR | COMMENT))
+        .take_while(|it| !matches!(it.kind(), BLOCK_EXPR | T![;]))
+        .join("");
+    let mut text = format!("{markup}\n___\n\nWith elided lifetimes named:\n\n");
+    format_to!(text, "{}", Markup::fenced_block(signature.trim()));
+    if is_async && !elided.input_lifetimes.is_empty() {
+        let captures =
+            elided.input_lifetimes.iter().format_with(", ", |it, f| f(&format_args!("`{it}`")));
+        format_to!(text, "\n\nThe returned future captures {captures}.");
+    }
+    *markup = Markup::from(text);
+}
+
+/// Notes the macro invocation that generated `def`, e.g. "Generated by `#[derive(Serialize)]`".
+fn push_generated_by(sema: &Semantics<'_, RootDatabase>, def: Definition, markup: &mut Markup) {
+    let Some(label) = macro_provenance::generated_by(sema, def)
+        .and_then(|invocation| macro_provenance::invocation_label(sema.db, &invocation))
+    else {
+        return;
+    };
+    *markup = Markup::from(format!("{markup}\n___\n\nGenerated by `{label}`"));
 }
 
 fn notable_traits(
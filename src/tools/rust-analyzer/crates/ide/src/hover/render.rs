@@ -3,13 +3,13 @@
 
 use either::Either;
 use hir::{
-    Adt, AsAssocItem, CaptureKind, HasCrate, HasSource, HirDisplay, Layout, LayoutError, Name,
-    Semantics, Trait, Type, TypeInfo,
+    Adt, AsAssocItem, CaptureKind, CfgAtom, CfgExpr, EnumTag, HasAttrs, HasCrate, HasSource,
+    HirDisplay, Layout, LayoutError, Name, PathResolution, Semantics, Trait, Type, TypeInfo,
 };
 use ide_db::{
     base_db::SourceDatabase,
     defs::Definition,
-    documentation::HasDocs,
+    documentation::{docs_from_attrs, HasDocs},
     famous_defs::FamousDefs,
     generated::lints::{CLIPPY_LINTS, DEFAULT_LINTS, FEATURES},
     syntax_helpers::insert_whitespace_into_node,
@@ -20,7 +20,7 @@
 use syntax::{
     algo,
     ast::{self, RecordPat},
-    match_ast, AstNode, Direction, SyntaxToken, T,
+    match_ast, AstNode, Direction, SyntaxNode, SyntaxToken, T,
 };
 
 use crate::{
@@ -353,6 +353,14 @@ pub(super) fn try_for_lint(attr: &ast::Attr, token: &SyntaxToken) -> Option<Hove
     })
 }
 
+pub(super) fn env_var(db: &RootDatabase, krate: hir::Crate, name: &str) -> HoverResult {
+    let markup = match krate.env_var(db, name) {
+        Some(value) => Markup::fenced_block_text(format_args!("{name}={value}")),
+        None => Markup::from(format!("`{name}` is not set in the build environment")),
+    };
+    HoverResult { markup, ..Default::default() }
+}
+
 pub(super) fn process_markup(
     db: &RootDatabase,
     def: Definition,
@@ -390,16 +398,57 @@ pub(super) fn path(db: &RootDatabase, module: hir::Module, item_name: Option<Str
     crate_name.into_iter().chain(module_path).chain(item_name).join("::")
 }
 
+/// The docs of the `use` items through which `def` is accessed at `scope_node`, outermost first.
+/// Their links are resolved here already, as they resolve in the scope of the `use` rather than in
+/// the one of the definition.
+pub(super) fn reexport_docs(
+    sema: &Semantics<'_, RootDatabase>,
+    def: Definition,
+    scope_node: &SyntaxNode,
+    config: &HoverConfig,
+) -> Option<String> {
+    let path = ast::NameRef::cast(scope_node.clone())?
+        .syntax()
+        .parent()
+        .and_then(ast::PathSegment::cast)?
+        .parent_path();
+    match sema.resolve_path(&path)? {
+        PathResolution::Def(it) if Definition::from(it) == def => (),
+        _ => return None,
+    }
+    let docs = sema
+        .imports_of_path(&path)
+        .into_iter()
+        .filter(|(_, attrs)| !attrs.has_doc_no_inline())
+        .filter_map(|(module, attrs)| {
+            let docs = docs_from_attrs(&attrs)?;
+            Some(match config.links_in_hover {
+                true => rewrite_links(sema.db, &docs, Definition::Module(module)),
+                false => docs,
+            })
+        })
+        .join("\n\n");
+    (!docs.is_empty()).then_some(docs)
+}
+
 pub(super) fn definition(
     db: &RootDatabase,
     def: Definition,
     famous_defs: Option<&FamousDefs<'_, '_>>,
     notable_traits: &[(Trait, Vec<(Option<Type>, Name)>)],
+    reexport_docs: Option<String>,
     config: &HoverConfig,
 ) -> Option<Markup> {
     let mod_path = definition_mod_path(db, &def);
     let label = def.label(db)?;
-    let docs = def.docs(db, famous_defs);
+    let availability = doc_cfg(db, def)
+        .and_then(|cfg| render_doc_cfg(&cfg, false))
+        .map(|cfg| format!("Available on **{cfg}** only."));
+    let docs = [availability, reexport_docs, def.docs(db, famous_defs).map(String::from)]
+        .into_iter()
+        .flatten()
+        .join("\n\n");
+    let docs = (!docs.is_empty()).then_some(docs);
     let value = match def {
         Definition::Variant(it) => {
             if !it.parent_enum(db).is_data_carrying(db) {
@@ -471,6 +520,19 @@ pub(super) fn definition(
         _ => None,
     };
 
+    let docs = match def {
+        Definition::Adt(Adt::Enum(it)) => {
+            match render_enum_variants(db, config.memory_layout, it) {
+                Some(table) => Some(match docs {
+                    Some(docs) => format!("{table}\n\n___\n\n{docs}"),
+                    None => table,
+                }),
+                None => docs,
+            }
+        }
+        _ => docs,
+    };
+
     let mut desc = String::new();
     if let Some(notable_traits) = render_notable_trait_comment(db, notable_traits) {
         desc.push_str(&notable_traits);
@@ -486,7 +548,50 @@ pub(super) fn definition(
         desc.push_str(&value);
     }
 
-    markup(docs.map(Into::into), desc, mod_path)
+    markup(docs, desc, mod_path)
+}
+
+fn doc_cfg(db: &RootDatabase, def: Definition) -> Option<CfgExpr> {
+    let attrs = match def {
+        Definition::Macro(it) => it.attrs(db),
+        Definition::Field(it) => it.attrs(db),
+        Definition::Module(it) => it.attrs(db),
+        Definition::Function(it) => it.attrs(db),
+        Definition::Adt(it) => it.attrs(db),
+        Definition::Variant(it) => it.attrs(db),
+        Definition::Const(it) => it.attrs(db),
+        Definition::Static(it) => it.attrs(db),
+        Definition::Trait(it) => it.attrs(db),
+        Definition::TraitAlias(it) => it.attrs(db),
+        Definition::TypeAlias(it) => it.attrs(db),
+        _ => return None,
+    };
+    attrs.doc_cfg()
+}
+
+/// Renders the condition of a `#[doc(cfg(..))]` the way rustdoc does, as in
+/// "crate feature `std` and non-`test`".
+fn render_doc_cfg(cfg: &CfgExpr, nested: bool) -> Option<String> {
+    let res = match cfg {
+        CfgExpr::Invalid => return None,
+        CfgExpr::Atom(CfgAtom::KeyValue { key, value }) if key == "feature" => {
+            format!("crate feature `{value}`")
+        }
+        CfgExpr::Atom(atom) => format!("`{atom}`"),
+        CfgExpr::Not(it) => format!("non-{}", render_doc_cfg(it, true)?),
+        CfgExpr::All(preds) | CfgExpr::Any(preds) => {
+            let sep = if matches!(cfg, CfgExpr::All(_)) { " and " } else { " or " };
+            let preds =
+                preds.iter().map(|it| render_doc_cfg(it, true)).collect::<Option<Vec<_>>>()?;
+            match preds.len() {
+                0 => return None,
+                1 => preds.join(sep),
+                _ if nested => format!("({})", preds.join(sep)),
+                _ => preds.join(sep),
+            }
+        }
+    };
+    Some(res)
 }
 
 fn render_notable_trait_comment(
@@ -721,51 +826,19 @@ fn render_memory_layout(
 
     if let Some(render) = config.size {
         let size = match tag(&layout) {
-            Some(tag) => layout.size() as usize - tag,
-            None => layout.size() as usize,
+            Some(tag) => layout.size() - tag as u64,
+            None => layout.size(),
         };
-        format_to!(label, "size = ");
-        match render {
-            MemoryLayoutHoverRenderKind::Decimal => format_to!(label, "{size}"),
-            MemoryLayoutHoverRenderKind::Hexadecimal => format_to!(label, "{size:#X}"),
-            MemoryLayoutHoverRenderKind::Both if size >= 10 => {
-                format_to!(label, "{size} ({size:#X})")
-            }
-            MemoryLayoutHoverRenderKind::Both => format_to!(label, "{size}"),
-        }
-        format_to!(label, ", ");
+        format_to!(label, "size = {}, ", render_number(render, size));
     }
 
     if let Some(render) = config.alignment {
-        let align = layout.align();
-        format_to!(label, "align = ");
-        match render {
-            MemoryLayoutHoverRenderKind::Decimal => format_to!(label, "{align}",),
-            MemoryLayoutHoverRenderKind::Hexadecimal => format_to!(label, "{align:#X}",),
-            MemoryLayoutHoverRenderKind::Both if align >= 10 => {
-                format_to!(label, "{align} ({align:#X})")
-            }
-            MemoryLayoutHoverRenderKind::Both => {
-                format_to!(label, "{align}")
-            }
-        }
-        format_to!(label, ", ");
+        format_to!(label, "align = {}, ", render_number(render, layout.align()));
     }
 
     if let Some(render) = config.offset {
         if let Some(offset) = offset(&layout) {
-            format_to!(label, "offset = ");
-            match render {
-                MemoryLayoutHoverRenderKind::Decimal => format_to!(label, "{offset}"),
-                MemoryLayoutHoverRenderKind::Hexadecimal => format_to!(label, "{offset:#X}"),
-                MemoryLayoutHoverRenderKind::Both if offset >= 10 => {
-                    format_to!(label, "{offset} ({offset:#X})")
-                }
-                MemoryLayoutHoverRenderKind::Both => {
-                    format_to!(label, "{offset}")
-                }
-            }
-            format_to!(label, ", ");
+            format_to!(label, "offset = {}, ", render_number(render, offset));
         }
     }
 
@@ -779,6 +852,78 @@ fn render_memory_layout(
     Some(label)
 }
 
+/// A table of the discriminants and payload sizes of the variants of an enum, followed by its size
+/// and where it stores which variant it holds. The payload size of a variant is the sum of the
+/// sizes of its fields.
+fn render_enum_variants(
+    db: &RootDatabase,
+    config: Option<MemoryLayoutHoverConfig>,
+    enum_: hir::Enum,
+) -> Option<String> {
+    let config = config.filter(|it| it.variants)?;
+    let variants = enum_.variants(db);
+    if variants.is_empty() {
+        return None;
+    }
+    let layout = Adt::from(enum_).layout(db).ok()?;
+    let render = config.size.unwrap_or(MemoryLayoutHoverRenderKind::Decimal);
+    let tag = layout.enum_tag();
+
+    let mut table = String::from("| Variant | Discriminant | Payload size |\n|---|---:|---:|\n");
+    for variant in &variants {
+        let discriminant = match variant.eval(db) {
+            Ok(it) if it >= 10 => format!("{it} ({it:#X})"),
+            Ok(it) => it.to_string(),
+            Err(_) => "?".to_owned(),
+        };
+        let payload_size: Result<u64, _> =
+            variant.fields(db).iter().map(|it| it.layout(db).map(|it| it.size())).sum();
+        let payload_size = match payload_size {
+            Ok(it) => render_number(render, it),
+            Err(_) => "?".to_owned(),
+        };
+        format_to!(
+            table,
+            "| `{}` | {discriminant} | {payload_size} |\n",
+            variant.name(db).display(db)
+        );
+    }
+    format_to!(table, "\nSize: {}", render_number(render, layout.size()));
+    match tag {
+        Some(EnumTag::Direct { offset, size }) => {
+            format_to!(table, ", tag: {} at offset {offset}", bytes(size));
+        }
+        Some(EnumTag::Niche { offset, size, untagged_variant }) => {
+            let variant = variants.get(untagged_variant)?;
+            format_to!(
+                table,
+                ", tag: in a niche of `{}`, {} at offset {offset}",
+                variant.name(db).display(db),
+                bytes(size)
+            );
+        }
+        None => (),
+    }
+    Some(table)
+}
+
+fn bytes(n: u64) -> String {
+    if n == 1 {
+        "1 byte".to_owned()
+    } else {
+        format!("{n} bytes")
+    }
+}
+
+fn render_number(render: MemoryLayoutHoverRenderKind, n: u64) -> String {
+    match render {
+        MemoryLayoutHoverRenderKind::Decimal => format!("{n}"),
+        MemoryLayoutHoverRenderKind::Hexadecimal => format!("{n:#X}"),
+        MemoryLayoutHoverRenderKind::Both if n >= 10 => format!("{n} ({n:#X})"),
+        MemoryLayoutHoverRenderKind::Both => format!("{n}"),
+    }
+}
+
 struct KeywordHint {
     description: String,
     keyword_mod: String,
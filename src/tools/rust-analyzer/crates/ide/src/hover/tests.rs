@@ -13,6 +13,7 @@
         offset: Some(MemoryLayoutHoverRenderKind::Both),
         alignment: Some(MemoryLayoutHoverRenderKind::Both),
         niches: true,
+        variants: false,
     }),
     documentation: true,
     format: HoverDocFormat::Markdown,
@@ -82,6 +83,25 @@ fn check_hover_no_memory_layout(ra_fixture: &str, expect: Expect) {
     expect.assert_eq(&actual)
 }
 
+fn check_hover_enum_variants(ra_fixture: &str, expect: Expect) {
+    let (analysis, position) = fixture::position(ra_fixture);
+    let memory_layout =
+        HOVER_BASE_CONFIG.memory_layout.map(|it| MemoryLayoutHoverConfig { variants: true, ..it });
+    let hover = analysis
+        .hover(
+            &HoverConfig { memory_layout, ..HOVER_BASE_CONFIG },
+            FileRange { file_id: position.file_id, range: TextRange::empty(position.offset) },
+        )
+        .unwrap()
+        .unwrap();
+
+    let content = analysis.db.file_text(position.file_id);
+    let hovered_element = &content[hover.range];
+
+    let actual = format!("*{hovered_element}*\n{}\n", hover.info.markup);
+    expect.assert_eq(&actual)
+}
+
 fn check_hover_no_markdown(ra_fixture: &str, expect: Expect) {
     let (analysis, position) = fixture::position(ra_fixture);
     let hover = analysis
@@ -196,6 +216,9 @@ fn abc()
             ---
 
             Inner
+            ___
+
+            Generated by `m!`
             ---
 
             ```rust
@@ -209,6 +232,9 @@ fn abc()
             ---
 
             Outer
+            ___
+
+            Generated by `m!`
         "#]],
     );
 }
@@ -406,8 +432,8 @@ fn main() {
                                 file_id: FileId(
                                     1,
                                 ),
-                                full_range: 632..867,
-                                focus_range: 693..699,
+                                full_range: 633..868,
+                                focus_range: 694..700,
                                 name: "FnOnce",
                                 kind: Trait,
                                 container_name: "function",
@@ -993,6 +1019,115 @@ enum Option<T> { Some(T) }
     );
 }
 
+#[test]
+fn hover_enum_variants_table() {
+    check_hover_enum_variants(
+        r#"
+/// A message
+#[repr(u8)]
+enum Message$0 {
+    Quit,
+    Move { x: i32, y: i32 } = 10,
+    Write(u64),
+}
+"#,
+        expect![[r#"
+            *Message*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+             // size = 16 (0x10), align = 8, niches = 244
+            enum Message {
+                Quit,
+                Move { x: i32, y: i32 },
+                Write(u64),
+            }
+            ```
+
+            ---
+
+            |Variant|Discriminant|Payload size|
+            |-------|-----------:|-----------:|
+            |`Quit`|0|0|
+            |`Move`|10 (0xA)|8|
+            |`Write`|11 (0xB)|8|
+
+            Size: 16 (0x10), tag: 1 byte at offset 0
+
+            ---
+
+            A message
+        "#]],
+    );
+    check_hover_enum_variants(
+        r#"
+enum MaybeRef$0<'a> {
+    Some(&'a u8),
+    None,
+}
+"#,
+        expect![[r#"
+            *MaybeRef*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+             // size = 8, align = 8
+            enum MaybeRef<'a> {
+                Some(&'a u8),
+                None,
+            }
+            ```
+
+            ---
+
+            |Variant|Discriminant|Payload size|
+            |-------|-----------:|-----------:|
+            |`Some`|0|8|
+            |`None`|1|0|
+
+            Size: 8, tag: in a niche of `Some`, 8 bytes at offset 0
+        "#]],
+    );
+    check_hover_enum_variants(
+        r#"
+enum Wide$0 {
+    A = -1,
+    B = 300,
+}
+"#,
+        expect![[r#"
+            *Wide*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+             // size = 2, align = 2, niches = 65234
+            enum Wide {
+                A,
+                B,
+            }
+            ```
+
+            ---
+
+            |Variant|Discriminant|Payload size|
+            |-------|-----------:|-----------:|
+            |`A`|-1|0|
+            |`B`|300 (0x12C)|0|
+
+            Size: 2, tag: 2 bytes at offset 0
+        "#]],
+    );
+}
+
 #[test]
 fn hover_enum_variant() {
     check(
@@ -1431,6 +1566,9 @@ fn foo$0() {}
             ```rust
             fn foo()
             ```
+            ___
+
+            Generated by `#[identity]`
         "#]],
     );
 }
@@ -2185,6 +2323,9 @@ fn foo(&self)
             ---
 
             Do the foo
+            ___
+
+            Generated by `bar!`
         "#]],
     );
 }
@@ -2223,6 +2364,9 @@ fn foo(&self)
             ---
 
             Do the foo
+            ___
+
+            Generated by `bar!`
         "#]],
     );
 }
@@ -5175,6 +5319,9 @@ fn bar<'t, T>(s: &mut S<'t, T>, t: u32) -> *mut u32
                 't: 't + 't,
                 for<'a> T: Clone + 'a,
             ```
+            ___
+
+            Generated by `foo!`
         "#]],
     )
 }
@@ -5214,6 +5361,130 @@ pub struct TheItem
     );
 }
 
+#[test]
+fn hover_reexport_docs() {
+    check(
+        r#"
+//- /main.rs crate:main deps:lib
+fn f(_: lib::Foo$0) {}
+//- /lib.rs crate:lib
+mod inner {
+    /// The original docs, see [`Helper`].
+    pub struct Foo;
+    pub struct Helper;
+}
+mod reexports {
+    /// Re-exported by [`Local`].
+    pub use crate::inner::Foo;
+    pub struct Local;
+}
+/// Re-exported again, with [`Bar`].
+pub use reexports::Foo;
+pub struct Bar;
+"#,
+        expect![[r#"
+            *Foo*
+
+            ```rust
+            lib::inner
+            ```
+
+            ```rust
+             // size = 0, align = 1
+            pub struct Foo
+            ```
+
+            ---
+
+            Re-exported again, with [`Bar`](https://docs.rs/lib/*/lib/struct.Bar.html).
+
+            Re-exported by [`Local`](https://docs.rs/lib/*/lib/reexports/struct.Local.html).
+
+            The original docs, see [`Helper`](https://docs.rs/lib/*/lib/inner/struct.Helper.html).
+        "#]],
+    );
+    check(
+        r#"
+//- /main.rs crate:main deps:lib
+fn f(_: lib::Foo$0) {}
+//- /lib.rs crate:lib
+mod inner {
+    /// The original docs.
+    pub struct Foo;
+}
+/// Not inlined.
+#[doc(no_inline)]
+pub use inner::Foo;
+"#,
+        expect![[r#"
+            *Foo*
+
+            ```rust
+            lib::inner
+            ```
+
+            ```rust
+             // size = 0, align = 1
+            pub struct Foo
+            ```
+
+            ---
+
+            The original docs.
+        "#]],
+    );
+}
+
+#[test]
+fn hover_doc_cfg() {
+    check(
+        r#"
+#[doc(cfg(all(feature = "std", not(test))))]
+/// Reads the file.
+pub fn read$0() {}
+"#,
+        expect![[r#"
+            *read*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+            pub fn read()
+            ```
+
+            ---
+
+            Available on **crate feature `std` and non-`test`** only.
+
+            Reads the file.
+        "#]],
+    );
+    check(
+        r#"
+#[doc(cfg(any(unix, target_os = "wasi")))]
+pub struct Fd$0;
+"#,
+        expect![[r#"
+            *Fd*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+             // size = 0, align = 1
+            pub struct Fd
+            ```
+
+            ---
+
+            Available on **`unix` or `target_os = "wasi"`** only.
+        "#]],
+    );
+}
+
 #[test]
 fn test_hover_trait_assoc_typealias() {
     check(
@@ -6037,6 +6308,9 @@ pub struct Foo
             ---
 
             Doc comment for [`Foo`](https://docs.rs/test/*/test/struct.Foo.html)
+            ___
+
+            Generated by `foo_macro!`
         "#]],
     );
 }
@@ -6136,6 +6410,9 @@ macro_rules! m {
             ```rust
             const KONST: dep::Type = Type
             ```
+            ___
+
+            Generated by `m!`
         "#]],
     );
 }
@@ -6277,6 +6554,13 @@ fn f() {
             ```rust
             fn deref(&self) -> &Self::Target
             ```
+            ___
+
+            With elided lifetimes named:
+
+            ```rust
+            fn deref<'a>(&'a self) -> &'a Self::Target
+            ```
         "#]],
     );
 }
@@ -6309,6 +6593,9 @@ macro_rules! m {
               f(e)
             }
             ```
+            ___
+
+            Generated by `m!`
         "#]],
     );
     check(
@@ -6335,6 +6622,9 @@ macro_rules! m {
               let e = 123;
             }
             ```
+            ___
+
+            Generated by `m!`
         "#]],
     );
 }
@@ -6893,6 +7183,38 @@ fn main() {
     );
 }
 
+#[test]
+fn env_macro_var() {
+    check(
+        r#"
+//- minicore: env
+//- /main.rs crate:main env:GIT_HASH=abc123
+fn main() {
+    env!("GIT_$0HASH");
+}
+"#,
+        expect![[r#"
+            *"GIT_HASH"*
+            ```text
+            GIT_HASH=abc123
+            ```
+        "#]],
+    );
+    check(
+        r#"
+//- minicore: env
+//- /main.rs crate:main
+fn main() {
+    option_env!("GIT_$0HASH");
+}
+"#,
+        expect![[r#"
+            *"GIT_HASH"*
+            `GIT_HASH` is not set in the build environment
+        "#]],
+    );
+}
+
 #[test]
 fn byte_string_literal() {
     check(
@@ -7263,8 +7585,8 @@ impl Iterator for S {
                                 file_id: FileId(
                                     1,
                                 ),
-                                full_range: 6290..6498,
-                                focus_range: 6355..6361,
+                                full_range: 6340..6548,
+                                focus_range: 6405..6411,
                                 name: "Future",
                                 kind: Trait,
                                 container_name: "future",
@@ -7277,8 +7599,8 @@ impl Iterator for S {
                                 file_id: FileId(
                                     1,
                                 ),
-                                full_range: 7128..7594,
-                                focus_range: 7172..7180,
+                                full_range: 7178..7644,
+                                focus_range: 7222..7230,
                                 name: "Iterator",
                                 kind: Trait,
                                 container_name: "iterator",
@@ -7317,3 +7639,120 @@ impl Iterator for S {
         "#]],
     );
 }
+
+#[test]
+fn hover_explains_unmet_auto_trait_bound_of_argument() {
+    check(
+        r#"
+//- minicore: send
+#![feature(negative_impls)]
+struct Rc(u32);
+impl !Send for Rc {}
+struct Wrapper { rc: Rc }
+
+fn spawn<T: Send>(t: T) {}
+
+fn main() {
+    let w = Wrapper { rc: Rc(0) };
+    spawn(w$0);
+}
+"#,
+        expect![[r#"
+            *w*
+
+            ```rust
+             // size = 4, align = 4
+            let w: Wrapper
+            ```
+            ---
+            `Wrapper` is not `Send` because field `rc: Rc` is not `Send`
+
+            `Rc` explicitly opts out of `Send`
+        "#]],
+    );
+}
+
+#[test]
+fn hover_shows_elided_lifetimes() {
+    check(
+        r#"
+struct Parser<'a>(&'a str);
+impl Parser<'_> {
+    /// Returns the next token.
+    fn next_token(&mut self, skip: &str) -> Option<&str> { None }
+}
+fn main(p: Parser) { p.next_tok$0en(""); }
+"#,
+        expect![[r#"
+            *next_token*
+
+            ```rust
+            test::Parser
+            ```
+
+            ```rust
+            fn next_token(&mut self, skip: &str) -> Option<&str>
+            ```
+
+            ---
+
+            Returns the next token.
+            ___
+
+            With elided lifetimes named:
+
+            ```rust
+            fn next_token<'a, 'b>(&'a mut self, skip: &'b str) -> Option<&'a str>
+            ```
+        "#]],
+    );
+}
+
+#[test]
+fn hover_shows_elided_lifetimes_of_async_fn() {
+    check(
+        r#"
+async fn fe$0tch(url: &str, buf: &mut [u8]) {}
+"#,
+        expect![[r#"
+            *fetch*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+            async fn fetch(url: &str, buf: &mut [u8])
+            ```
+            ___
+
+            With elided lifetimes named:
+
+            ```rust
+            async fn fetch<'a, 'b>(url: &'a str, buf: &'b mut [u8])
+            ```
+
+            The returned future captures `'a`, `'b`.
+        "#]],
+    );
+}
+
+#[test]
+fn hover_skips_elided_lifetimes_not_reaching_the_output() {
+    check(
+        r#"
+fn le$0n(s: &str) -> usize { 0 }
+"#,
+        expect![[r#"
+            *len*
+
+            ```rust
+            test
+            ```
+
+            ```rust
+            fn len(s: &str) -> usize
+            ```
+        "#]],
+    );
+}
@@ -27,10 +27,13 @@
 mod closing_brace;
 mod closure_captures;
 mod closure_ret;
+mod conversion;
 mod discriminant;
 mod fn_lifetime_fn;
+mod generic_args;
 mod implicit_drop;
 mod implicit_static;
+mod macro_expansion;
 mod param_name;
 mod range_exclusive;
 
@@ -53,6 +56,14 @@ pub struct InlayHintsConfig {
     pub hide_named_constructor_hints: bool,
     pub hide_closure_initialization_hints: bool,
     pub range_exclusive_hints: bool,
+    pub conversion_hints: bool,
+    pub generic_arg_hints: bool,
+    /// Patterns of the names of the functions to show generic argument hints for, or empty for all
+    /// functions.
+    pub generic_arg_hints_functions: Vec<String>,
+    pub macro_expansion_hints: bool,
+    /// Names of the macros to show expansion hints for, or empty for all macros.
+    pub macro_expansion_hints_macros: Vec<String>,
     pub closure_style: ClosureStyle,
     pub max_length: Option<usize>,
     pub closing_brace_hints_min_lines: Option<usize>,
@@ -130,6 +141,9 @@ pub enum InlayKind {
     Type,
     Drop,
     RangeExclusive,
+    Conversion,
+    GenericArgs,
+    MacroExpansion,
 }
 
 #[derive(Debug)]
@@ -511,16 +525,23 @@ fn hints(
             ast::Expr(expr) => {
                 chaining::hints(hints, famous_defs, config, file_id, &expr);
                 adjustment::hints(hints, sema, config, &expr);
+                generic_args::hints(hints, famous_defs, config, &expr);
                 match expr {
-                    ast::Expr::CallExpr(it) => param_name::hints(hints, sema, config, ast::Expr::from(it)),
+                    ast::Expr::CallExpr(it) => {
+                        conversion::deref_coercion_hints(hints, famous_defs, config, &it);
+                        param_name::hints(hints, sema, config, ast::Expr::from(it))
+                    }
                     ast::Expr::MethodCallExpr(it) => {
+                        conversion::deref_coercion_hints(hints, famous_defs, config, &it);
                         param_name::hints(hints, sema, config, ast::Expr::from(it))
                     }
+                    ast::Expr::TryExpr(it) => conversion::try_hints(hints, famous_defs, config, &it),
                     ast::Expr::ClosureExpr(it) => {
                         closure_captures::hints(hints, famous_defs, config, file_id, it.clone());
                         closure_ret::hints(hints, famous_defs, config, file_id, it)
                     },
                     ast::Expr::RangeExpr(it) => range_exclusive::hints(hints, config, it),
+                    ast::Expr::MacroExpr(it) => macro_expansion::hints(hints, sema, config, &it),
                     _ => None,
                 }
             },
@@ -632,15 +653,41 @@ mod tests {
         fields_to_resolve: InlayFieldsToResolve::empty(),
         implicit_drop_hints: false,
         range_exclusive_hints: false,
+        conversion_hints: false,
+        generic_arg_hints: false,
+        generic_arg_hints_functions: Vec::new(),
+        macro_expansion_hints: false,
+        macro_expansion_hints_macros: Vec::new(),
     };
+
+    // Spelled out, as the `Vec` in the config can't be dropped in a const struct update.
     pub(super) const TEST_CONFIG: InlayHintsConfig = InlayHintsConfig {
+        discriminant_hints: DiscriminantHints::Never,
+        render_colons: false,
         type_hints: true,
         parameter_hints: true,
         chaining_hints: true,
+        lifetime_elision_hints: LifetimeElisionHints::Always,
         closure_return_type_hints: ClosureReturnTypeHints::WithBlock,
+        closure_capture_hints: false,
+        adjustment_hints: AdjustmentHints::Never,
+        adjustment_hints_mode: AdjustmentHintsMode::Prefix,
+        adjustment_hints_hide_outside_unsafe: false,
         binding_mode_hints: true,
-        lifetime_elision_hints: LifetimeElisionHints::Always,
-        ..DISABLED_CONFIG
+        hide_named_constructor_hints: false,
+        hide_closure_initialization_hints: false,
+        closure_style: ClosureStyle::ImplFn,
+        param_names_for_lifetime_elision_hints: false,
+        max_length: None,
+        closing_brace_hints_min_lines: None,
+        fields_to_resolve: InlayFieldsToResolve::empty(),
+        implicit_drop_hints: false,
+        range_exclusive_hints: false,
+        conversion_hints: false,
+        generic_arg_hints: false,
+        generic_arg_hints_functions: Vec::new(),
+        macro_expansion_hints: false,
+        macro_expansion_hints_macros: Vec::new(),
     };
 
     #[track_caller]
@@ -0,0 +1,175 @@
+//! Implementation of "conversion" inlay hints:
+//! ```no_run
+//! fn read() -> Result<String, MyError> {
+//!     let text = fs::read_to_string("foo")?/* into MyError */;
+//!     takes_str(&text/* as &str */);
+//! }
+//! ```
+use hir::{Adjust, HirDisplay, OverloadedDeref};
+use ide_db::famous_defs::FamousDefs;
+use syntax::ast::{self, AstNode, HasArgList};
+
+use crate::{
+    InlayHint, InlayHintLabel, InlayHintPosition, InlayHintsConfig, InlayKind, InlayTooltip,
+};
+
+/// Hints the error type `?` converts the error of its operand into with `From::from`.
+pub(super) fn try_hints(
+    acc: &mut Vec<InlayHint>,
+    famous_defs @ FamousDefs(sema, _): &FamousDefs<'_, '_>,
+    config: &InlayHintsConfig,
+    try_expr: &ast::TryExpr,
+) -> Option<()> {
+    if !config.conversion_hints {
+        return None;
+    }
+
+    let (residual, target) = sema.try_expr_conversion(try_expr)?;
+    let result = famous_defs.core_result_Result()?.into();
+    if residual.as_adt() != Some(result) || target.as_adt() != Some(result) {
+        return None;
+    }
+    let source_err = residual.type_arguments().nth(1)?;
+    let target_err = target.type_arguments().nth(1)?;
+    if source_err == target_err || source_err.is_unknown() || target_err.is_unknown() {
+        return None;
+    }
+
+    let label = InlayHintLabel::simple(
+        format!("into {}", target_err.display_truncated(sema.db, config.max_length)),
+        Some(InlayTooltip::Markdown(format!(
+            "`{}` → `{}` (`From::from` conversion)",
+            source_err.display(sema.db),
+            target_err.display(sema.db),
+        ))),
+        None,
+    );
+    acc.push(InlayHint {
+        needs_resolve: label.needs_resolve(),
+        range: try_expr.syntax().text_range(),
+        position: InlayHintPosition::After,
+        pad_left: true,
+        pad_right: false,
+        kind: InlayKind::Conversion,
+        label,
+        text_edit: None,
+    });
+    Some(())
+}
+
+/// Hints the type the arguments of a call are deref-coerced to.
+pub(super) fn deref_coercion_hints(
+    acc: &mut Vec<InlayHint>,
+    FamousDefs(sema, _): &FamousDefs<'_, '_>,
+    config: &InlayHintsConfig,
+    call: &impl HasArgList,
+) -> Option<()> {
+    if !config.conversion_hints {
+        return None;
+    }
+
+    for arg in call.arg_list()?.args() {
+        let Some(adjustments) = sema.expr_adjustments(&arg) else { continue };
+        if !adjustments.iter().any(|it| matches!(it.kind, Adjust::Deref(Some(OverloadedDeref(_)))))
+        {
+            continue;
+        }
+        let (Some(first), Some(last)) = (adjustments.first(), adjustments.last()) else {
+            continue;
+        };
+        let label = InlayHintLabel::simple(
+            format!("as {}", last.target.display_truncated(sema.db, config.max_length)),
+            Some(InlayTooltip::Markdown(format!(
+                "`{}` → `{}` (deref coercion)",
+                first.source.display(sema.db),
+                last.target.display(sema.db),
+            ))),
+            None,
+        );
+        acc.push(InlayHint {
+            needs_resolve: label.needs_resolve(),
+            range: arg.syntax().text_range(),
+            position: InlayHintPosition::After,
+            pad_left: true,
+            pad_right: false,
+            kind: InlayKind::Conversion,
+            label,
+            text_edit: None,
+        });
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        inlay_hints::tests::{check_with_config, DISABLED_CONFIG},
+        InlayHintsConfig,
+    };
+
+    #[test]
+    fn try_error_conversion() {
+        check_with_config(
+            InlayHintsConfig { conversion_hints: true, ..DISABLED_CONFIG },
+            r#"
+//- minicore: try, result, from
+struct IoError;
+struct MyError;
+impl From<IoError> for MyError {
+    fn from(_: IoError) -> Self { MyError }
+}
+fn read() -> Result<u32, IoError> { Err(IoError) }
+fn foo() -> Result<u32, MyError> {
+    let a = read()?;
+          //^^^^^^^into MyError
+    Ok(a)
+}
+fn bar() -> Result<u32, IoError> {
+    let a = read()?;
+    Ok(a)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn no_hint_for_option_try() {
+        check_with_config(
+            InlayHintsConfig { conversion_hints: true, ..DISABLED_CONFIG },
+            r#"
+//- minicore: try, option
+fn foo(x: Option<u32>) -> Option<u32> {
+    let a = x?;
+    Some(a)
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn deref_coercion_at_call_site() {
+        check_with_config(
+            InlayHintsConfig { conversion_hints: true, ..DISABLED_CONFIG },
+            r#"
+//- minicore: deref
+struct Wrapper;
+impl core::ops::Deref for Wrapper {
+    type Target = str;
+    fn deref(&self) -> &str { "" }
+}
+fn takes_str(_: &str) {}
+struct S;
+impl S {
+    fn takes_str(&self, _: &str, _: &str) {}
+}
+fn main(w: &Wrapper, s: &str) {
+    takes_str(w);
+            //^as &str
+    takes_str(s);
+    S.takes_str(s, &w);
+                 //^^as &str
+}
+"#,
+        );
+    }
+}
@@ -0,0 +1,200 @@
+//! Implementation of "generic argument" inlay hints:
+//! ```no_run
+//! let names = words.iter().map(|it| it.to_string()).collect/* ::<Vec<String>> */();
+//! let port: u16 = text./* FromStr:: */parse/* ::<u16> */().unwrap();
+//! let error: MyError = io_error./* Into<MyError>:: */into();
+//! ```
+use hir::{HirDisplay, Type};
+use ide_db::{famous_defs::FamousDefs, RootDatabase};
+use itertools::Itertools;
+use syntax::ast::{self, AstNode};
+
+use crate::{InlayHint, InlayHintLabel, InlayHintPosition, InlayHintsConfig, InlayKind};
+
+pub(super) fn hints(
+    acc: &mut Vec<InlayHint>,
+    FamousDefs(sema, _): &FamousDefs<'_, '_>,
+    config: &InlayHintsConfig,
+    expr: &ast::Expr,
+) -> Option<()> {
+    if !config.generic_arg_hints {
+        return None;
+    }
+
+    let name_ref = match expr {
+        ast::Expr::MethodCallExpr(call) if call.generic_arg_list().is_none() => call.name_ref()?,
+        ast::Expr::CallExpr(call) => {
+            let ast::Expr::PathExpr(callee) = call.expr()? else { return None };
+            let segment = callee.path()?.segment()?;
+            if segment.generic_arg_list().is_some() {
+                return None;
+            }
+            segment.name_ref()?
+        }
+        _ => return None,
+    };
+    let args = sema.inferred_generic_args(expr)?;
+    let name = args.function.name(sema.db).display(sema.db).to_string();
+    let patterns = &config.generic_arg_hints_functions;
+    if !patterns.is_empty() && !patterns.iter().any(|it| matches_pattern(it, &name)) {
+        return None;
+    }
+
+    if let Some(args) = render_args(sema.db, config, &args.own) {
+        acc.push(InlayHint {
+            needs_resolve: false,
+            range: name_ref.syntax().text_range(),
+            position: InlayHintPosition::After,
+            pad_left: false,
+            pad_right: false,
+            kind: InlayKind::GenericArgs,
+            label: InlayHintLabel::from(format!("::<{args}>")),
+            text_edit: None,
+        });
+    }
+    // For the trait arguments there is no turbofish, only `<_ as Trait<Args>>::method`, so they're
+    // shown as a qualifier of the method instead.
+    if let Some((trait_, args)) =
+        args.trait_.filter(|_| matches!(expr, ast::Expr::MethodCallExpr(_)))
+    {
+        if let Some(args) = render_args(sema.db, config, &args) {
+            let trait_name = trait_.name(sema.db);
+            acc.push(InlayHint {
+                needs_resolve: false,
+                range: name_ref.syntax().text_range(),
+                position: InlayHintPosition::Before,
+                pad_left: false,
+                pad_right: false,
+                kind: InlayKind::GenericArgs,
+                label: InlayHintLabel::from(format!("{}<{args}>::", trait_name.display(sema.db))),
+                text_edit: None,
+            });
+        }
+    }
+    Some(())
+}
+
+/// Renders the arguments as in a turbofish, with `_` for the closure types that can't be named.
+/// `None` if there's nothing worth showing.
+fn render_args(db: &RootDatabase, config: &InlayHintsConfig, args: &[Type]) -> Option<String> {
+    if args.iter().all(|it| it.is_closure()) || args.iter().any(|it| it.is_unknown()) {
+        return None;
+    }
+    Some(
+        args.iter()
+            .map(|it| match it.is_closure() {
+                true => "_".to_owned(),
+                false => it.display_truncated(db, config.max_length).to_string(),
+            })
+            .join(", "),
+    )
+}
+
+/// Whether `name` matches `pattern`, where `*` in the pattern matches any sequence of characters.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = name.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    let Some((last, middle)) = parts.split_last() else { return rest.is_empty() };
+    for part in middle {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        inlay_hints::tests::{check_with_config, DISABLED_CONFIG},
+        InlayHintsConfig,
+    };
+
+    use super::matches_pattern;
+
+    #[test]
+    fn method_calls() {
+        check_with_config(
+            InlayHintsConfig { generic_arg_hints: true, ..DISABLED_CONFIG },
+            r#"
+//- minicore: iterators, from, option
+struct MyError;
+struct IoError;
+impl From<IoError> for MyError {
+    fn from(_: IoError) -> Self { MyError }
+}
+struct Wrapper<T>(T);
+impl<T> Wrapper<T> {
+    fn convert<U: From<T>>(self) -> Wrapper<U> { loop {} }
+}
+fn f(it: impl Iterator<Item = u8>, e: IoError, w: Wrapper<IoError>) {
+    let v: Wrapper<MyError> = w.convert();
+                              //^^^^^^^::<MyError>
+    let _: MyError = e.into();
+                     //^^^^Into<MyError>::
+    let _ = it.filter_map(|x| Some(x as u32));
+             //^^^^^^^^^^::<u32, _>
+    let _ = w.convert::<MyError>();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn path_calls() {
+        check_with_config(
+            InlayHintsConfig { generic_arg_hints: true, ..DISABLED_CONFIG },
+            r#"
+fn make<T: Default>() -> T { loop {} }
+fn pair<A, B>(a: A, b: B) -> (A, B) { (a, b) }
+fn with_impl<T>(_: T, _: impl Sized) {}
+fn with_const<const N: usize>() -> [u8; N] { loop {} }
+fn f() {
+    let x: u16 = make();
+               //^^^^::<u16>
+    pair(1u8, "s");
+  //^^^^::<u8, &str>
+    make::<u8>();
+    with_impl(1u8, 2u8);
+    let _: [u8; 2] = with_const();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn filtered_by_function_name() {
+        check_with_config(
+            InlayHintsConfig {
+                generic_arg_hints: true,
+                generic_arg_hints_functions: vec!["par*".to_owned()],
+                ..DISABLED_CONFIG
+            },
+            r#"
+fn make<T: Default>() -> T { loop {} }
+fn parse<T>() -> T { loop {} }
+fn f() {
+    let _: u16 = make();
+    let _: u32 = parse();
+               //^^^^^::<u32>
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn patterns() {
+        assert!(matches_pattern("into", "into"));
+        assert!(!matches_pattern("into", "into_iter"));
+        assert!(matches_pattern("into*", "into_iter"));
+        assert!(matches_pattern("*_into", "try_into"));
+        assert!(matches_pattern("*", "collect"));
+        assert!(matches_pattern("c*l*t", "collect"));
+        assert!(!matches_pattern("c*l*ts", "collect"));
+        assert!(!matches_pattern("a*a", "a"));
+    }
+}
@@ -127,13 +127,10 @@ mod tests {
         InlayHintsConfig,
     };
 
-    const ONLY_DROP_CONFIG: InlayHintsConfig =
-        InlayHintsConfig { implicit_drop_hints: true, ..DISABLED_CONFIG };
-
     #[test]
     fn basic() {
         check_with_config(
-            ONLY_DROP_CONFIG,
+            InlayHintsConfig { implicit_drop_hints: true, ..DISABLED_CONFIG },
             r#"
     struct X;
     fn f() {
@@ -152,7 +149,7 @@ fn f() {
     fn no_hint_for_copy_types_and_mutable_references() {
         // `T: Copy` and `T = &mut U` types do nothing on drop, so we should hide drop inlay hint for them.
         check_with_config(
-            ONLY_DROP_CONFIG,
+            InlayHintsConfig { implicit_drop_hints: true, ..DISABLED_CONFIG },
             r#"
 //- minicore: copy, derive
 
@@ -178,7 +175,7 @@ fn try_operator() {
         // We currently show drop inlay hint for every `?` operator that may potentially drop something. We probably need to
         // make it configurable as it doesn't seem very useful.
         check_with_config(
-            ONLY_DROP_CONFIG,
+            InlayHintsConfig { implicit_drop_hints: true, ..DISABLED_CONFIG },
             r#"
 //- minicore: copy, try, option
 
@@ -198,7 +195,7 @@ fn f() -> Option<()> {
     #[test]
     fn if_let() {
         check_with_config(
-            ONLY_DROP_CONFIG,
+            InlayHintsConfig { implicit_drop_hints: true, ..DISABLED_CONFIG },
             r#"
     struct X;
     fn f() {
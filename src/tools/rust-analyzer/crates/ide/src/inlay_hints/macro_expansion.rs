@@ -0,0 +1,134 @@
+//! Implementation of "macro expansion" inlay hints:
+//! ```no_run
+//! let is_some = matches!(x, Some(_))/* ⇒ match x { Some(_) => true, _ => false } */;
+//! write!(f, "{}", self.0)/* ⇒ f.write_fmt(format_args!("{}", self.0)) */?;
+//! ```
+use std::mem;
+
+use hir::Semantics;
+use ide_db::{syntax_helpers::insert_whitespace_into_node::insert_ws_into, RootDatabase};
+use syntax::{
+    ast::{self, AstNode},
+    NodeOrToken, SyntaxKind,
+};
+
+use crate::{
+    InlayHint, InlayHintLabel, InlayHintPosition, InlayHintsConfig, InlayKind, InlayTooltip,
+};
+
+pub(super) fn hints(
+    acc: &mut Vec<InlayHint>,
+    sema: &Semantics<'_, RootDatabase>,
+    config: &InlayHintsConfig,
+    macro_expr: &ast::MacroExpr,
+) -> Option<()> {
+    if !config.macro_expansion_hints {
+        return None;
+    }
+
+    let macro_call = macro_expr.macro_call()?;
+    let name = sema.resolve_macro_call(&macro_call)?.name(sema.db);
+    let macros = &config.macro_expansion_hints_macros;
+    if !macros.is_empty() && !macros.iter().any(|it| name.to_smol_str() == it.as_str()) {
+        return None;
+    }
+
+    // Only the call itself is expanded, macro calls in the expansion are shown as they are.
+    let expansion = insert_ws_into(sema.expand(&macro_call)?);
+    let mut preview = String::new();
+    let mut needs_space = false;
+    for token in expansion.descendants_with_tokens().filter_map(NodeOrToken::into_token) {
+        if token.kind() == SyntaxKind::WHITESPACE {
+            needs_space = !preview.is_empty();
+            continue;
+        }
+        if mem::take(&mut needs_space) {
+            preview.push(' ');
+        }
+        preview.push_str(token.text());
+    }
+    if preview.is_empty() {
+        return None;
+    }
+    let preview = match config.max_length {
+        Some(max_length) if preview.chars().count() > max_length => {
+            format!("{}…", preview.chars().take(max_length).collect::<String>())
+        }
+        _ => preview,
+    };
+
+    let label = InlayHintLabel::simple(
+        format!("⇒ {preview}"),
+        Some(InlayTooltip::Markdown(format!(
+            "```rust\n{}\n```",
+            expansion.text().to_string().trim()
+        ))),
+        None,
+    );
+    acc.push(InlayHint {
+        needs_resolve: label.needs_resolve(),
+        range: macro_call.syntax().text_range(),
+        position: InlayHintPosition::After,
+        pad_left: true,
+        pad_right: false,
+        kind: InlayKind::MacroExpansion,
+        label,
+        text_edit: None,
+    });
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        inlay_hints::tests::{check_with_config, DISABLED_CONFIG},
+        InlayHintsConfig,
+    };
+
+    #[test]
+    fn macro_expansion_hints() {
+        check_with_config(
+            InlayHintsConfig { macro_expansion_hints: true, ..DISABLED_CONFIG },
+            r#"
+//- minicore: option, fmt
+macro_rules! matches {
+    ($e:expr, $p:pat) => { match $e { $p => true, _ => false } };
+}
+macro_rules! write {
+    ($dst:expr, $($arg:tt)*) => { $dst.write_fmt(format_args!($($arg)*)) };
+}
+struct W;
+impl W {
+    fn write_fmt(&mut self, _: core::fmt::Arguments<'_>) {}
+}
+fn f(x: Option<u8>, mut w: W) {
+    let _ = matches!(x, Some(_));
+          //^^^^^^^^^^^^^^^^^^^^ ⇒ match x { Some(_) => true, _ => false }
+    write!(w, "{}", 1);
+  //^^^^^^^^^^^^^^^^^^ ⇒ w.write_fmt(format_args!("{}",1))
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn filtered_by_macro_name() {
+        check_with_config(
+            InlayHintsConfig {
+                macro_expansion_hints: true,
+                macro_expansion_hints_macros: vec!["two".to_owned()],
+                max_length: Some(3),
+                ..DISABLED_CONFIG
+            },
+            r#"
+macro_rules! one { () => { 1 } }
+macro_rules! two { () => { 1 + 1 + 1 } }
+fn f() {
+    let _ = one!();
+    let _ = two!();
+          //^^^^^^ ⇒ 1+1…
+}
+"#,
+        );
+    }
+}
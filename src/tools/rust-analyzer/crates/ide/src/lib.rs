@@ -22,7 +22,11 @@
 mod annotations;
 mod call_hierarchy;
 mod doc_links;
+mod document_colors;
+mod evaluate_selection;
 mod expand_macro;
+mod explain_type;
+mod explain_unmet_bound;
 mod extend_selection;
 mod fetch_crates;
 mod file_structure;
@@ -36,14 +40,18 @@
 mod inlay_hints;
 mod interpret_function;
 mod join_lines;
+mod macro_provenance;
 mod markdown_remove;
 mod matching_brace;
 mod moniker;
+mod monomorphizations;
 mod move_item;
 mod parent_module;
+mod public_api;
 mod references;
 mod rename;
 mod runnables;
+mod search_impls;
 mod shuffle_crate_graph;
 mod signature_help;
 mod ssr;
@@ -51,7 +59,9 @@
 mod status;
 mod syntax_highlighting;
 mod syntax_tree;
+mod todo_index;
 mod typing;
+mod unsafe_audit;
 mod view_crate_graph;
 mod view_hir;
 mod view_item_tree;
@@ -77,9 +87,15 @@
 use crate::navigation_target::ToNav;
 
 pub use crate::{
-    annotations::{Annotation, AnnotationConfig, AnnotationKind, AnnotationLocation},
+    annotations::{
+        Annotation, AnnotationConfig, AnnotationKind, AnnotationLocation, StackUsage, VariantUsages,
+    },
     call_hierarchy::CallItem,
+    document_colors::{Color, ColorInformation, ColorPresentation},
+    evaluate_selection::SelectionEvaluation,
     expand_macro::ExpandedMacro,
+    explain_type::{ExplainedType, ExplainedTypeNode, ExplainedTypeNodeKind},
+    explain_unmet_bound::UnmetBound,
     file_structure::{StructureNode, StructureNodeKind},
     folding_ranges::{Fold, FoldKind},
     highlight_related::{HighlightRelatedConfig, HighlightedRange},
@@ -98,22 +114,31 @@
         MonikerDescriptorKind, MonikerKind, MonikerResult, PackageInformation,
         SymbolInformationKind,
     },
+    monomorphizations::Monomorphization,
     move_item::Direction,
     navigation_target::{NavigationTarget, TryToNav, UpmappingResult},
     prime_caches::ParallelPrimeCachesProgress,
+    public_api::{diff_public_api, ApiChange, ApiChangeKind, ApiItem, ApiItemKind, SemverImpact},
     references::ReferenceSearchResult,
     rename::RenameError,
     runnables::{Runnable, RunnableKind, TestId},
     signature_help::SignatureHelp,
-    static_index::{StaticIndex, StaticIndexedFile, TokenId, TokenStaticData},
+    static_index::{StaticIndex, StaticIndexConfig, StaticIndexedFile, TokenId, TokenStaticData},
     syntax_highlighting::{
+        edited_fn_range,
         tags::{Highlight, HlMod, HlMods, HlOperator, HlPunct, HlTag},
         HighlightConfig, HlRange,
     },
+    todo_index::TodoItem,
+    unsafe_audit::{UnsafeBlock, UnsafeBlockKind, UnsafeOperation},
+    view_crate_graph::{
+        CrateGraphConfig, CrateGraphEdge, CrateGraphNode, CrateMetrics, ExportedCrateGraph,
+    },
 };
-pub use hir::Semantics;
+pub use hir::{Semantics, UnsafeExprKind};
 pub use ide_assists::{
-    Assist, AssistConfig, AssistId, AssistKind, AssistResolveStrategy, SingleResolve,
+    Assist, AssistConfig, AssistId, AssistKind, AssistResolveStrategy, BoundsStyle,
+    ErrorHandlingStyle, SingleResolve,
 };
 pub use ide_completion::{
     CallableSnippets, CompletionConfig, CompletionItem, CompletionItemKind, CompletionRelevance,
@@ -121,10 +146,11 @@
 };
 pub use ide_db::{
     base_db::{
-        Cancelled, CrateGraph, CrateId, Edition, FileChange, FileId, FilePosition, FileRange,
-        SourceRoot, SourceRootId,
+        Cancelled, CrateGraph, CrateId, DependencyKind, Edition, FileChange, FileId, FilePosition,
+        FileRange, SourceRoot, SourceRootId,
     },
     documentation::Documentation,
+    impl_search::ImplPatternError,
     label::Label,
     line_index::{LineCol, LineIndex},
     search::{ReferenceCategory, SearchScope},
@@ -133,9 +159,9 @@
     RootDatabase, SymbolKind,
 };
 pub use ide_diagnostics::{
-    Diagnostic, DiagnosticCode, DiagnosticsConfig, ExprFillDefaultMode, Severity,
+    Diagnostic, DiagnosticCode, DiagnosticsConfig, DiagnosticsStyle, ExprFillDefaultMode, Severity,
 };
-pub use ide_ssr::SsrError;
+pub use ide_ssr::{FoundMatch, PlaceholderBinding, SsrError};
 pub use syntax::{TextRange, TextSize};
 pub use text_edit::{Indel, TextEdit};
 
@@ -185,6 +211,17 @@ pub fn apply_change(&mut self, change: Change) {
         self.db.apply_change(change);
     }
 
+    /// Loads the item trees of library crates, or only of the sysroot crates if `sysroot_only`
+    /// is set, persisted in `dir` by an earlier session of the same rust-analyzer `version`.
+    pub fn load_persisted_item_trees(
+        &mut self,
+        dir: &std::path::Path,
+        sysroot_only: bool,
+        version: &str,
+    ) {
+        ide_db::item_tree_cache::load(&mut self.db, dir, sysroot_only, version);
+    }
+
     /// NB: this clears the database
     pub fn per_query_memory_usage(&mut self) -> Vec<(String, profile::Bytes, usize)> {
         self.db.per_query_memory_usage()
@@ -274,6 +311,17 @@ pub fn parallel_prime_caches<F>(&self, num_worker_threads: u8, cb: F) -> Cancell
         self.with_db(move |db| prime_caches::parallel_prime_caches(db, num_worker_threads, &cb))
     }
 
+    /// Persists the item trees of library crates, or only of the sysroot crates if `sysroot_only`
+    /// is set, in `dir`, to be loaded by the next session of the same rust-analyzer `version`.
+    pub fn persist_item_trees(
+        &self,
+        dir: &std::path::Path,
+        sysroot_only: bool,
+        version: &str,
+    ) -> Cancellable<std::io::Result<()>> {
+        self.with_db(|db| ide_db::item_tree_cache::save(db, dir, sysroot_only, version))
+    }
+
     /// Gets the text of the source file.
     pub fn file_text(&self, file_id: FileId) -> Cancellable<Arc<str>> {
         self.with_db(|db| db.file_text(file_id))
@@ -333,23 +381,65 @@ pub fn interpret_function(&self, position: FilePosition) -> Cancellable<String>
         self.with_db(|db| interpret_function::interpret_function(db, position))
     }
 
+    /// Evaluates the expression covering `frange`, with `values` as the values of the variables
+    /// it uses from outside of it, by name.
+    pub fn evaluate_selection(
+        &self,
+        frange: FileRange,
+        values: &FxHashMap<String, String>,
+    ) -> Cancellable<Option<SelectionEvaluation>> {
+        self.with_db(|db| evaluate_selection::evaluate_selection(db, frange, values))
+    }
+
     pub fn view_item_tree(&self, file_id: FileId) -> Cancellable<String> {
         self.with_db(|db| view_item_tree::view_item_tree(db, file_id))
     }
 
     /// Renders the crate graph to GraphViz "dot" syntax.
-    pub fn view_crate_graph(&self, full: bool) -> Cancellable<Result<String, String>> {
-        self.with_db(|db| view_crate_graph::view_crate_graph(db, full))
+    pub fn view_crate_graph(
+        &self,
+        config: &CrateGraphConfig,
+    ) -> Cancellable<Result<String, String>> {
+        self.with_db(|db| view_crate_graph::view_crate_graph(db, config))
+    }
+
+    /// Returns the crates and dependency edges of the crate graph, for exporting it.
+    pub fn export_crate_graph(&self, config: &CrateGraphConfig) -> Cancellable<ExportedCrateGraph> {
+        self.with_db(|db| view_crate_graph::export_crate_graph(db, config))
     }
 
     pub fn fetch_crates(&self) -> Cancellable<FxIndexSet<CrateInfo>> {
         self.with_db(fetch_crates::fetch_crates)
     }
 
+    /// Returns the items of the public API of a crate, see [`diff_public_api`].
+    pub fn public_api(&self, crate_id: CrateId) -> Cancellable<Vec<ApiItem>> {
+        self.with_db(|db| public_api::public_api(db, crate_id))
+    }
+
+    /// Lists the `unsafe` blocks of a crate and the operations in them that require `unsafe`.
+    pub fn unsafe_audit(&self, crate_id: CrateId) -> Cancellable<Vec<UnsafeBlock>> {
+        self.with_db(|db| unsafe_audit::unsafe_audit(db, crate_id))
+    }
+
     pub fn expand_macro(&self, position: FilePosition) -> Cancellable<Option<ExpandedMacro>> {
         self.with_db(|db| expand_macro::expand_macro(db, position))
     }
 
+    /// Returns the type at the given position as a tree of its component types.
+    pub fn explain_type(&self, position: FilePosition) -> Cancellable<Option<ExplainedType>> {
+        self.with_db(|db| explain_type::explain_type(db, position))
+    }
+
+    /// Explains why the type at `position` doesn't implement the trait named `trait_name`.
+    pub fn explain_unmet_bound(
+        &self,
+        position: FilePosition,
+        trait_name: &str,
+    ) -> Cancellable<Option<UnmetBound>> {
+        self.with_db(|db| explain_unmet_bound::explain_unmet_bound(db, position, trait_name))
+    }
+
     /// Returns an edit to remove all newlines in the range, cleaning up minor
     /// stuff like trailing commas.
     pub fn join_lines(&self, config: &JoinLinesConfig, frange: FileRange) -> Cancellable<TextEdit> {
@@ -408,6 +498,35 @@ pub fn folding_ranges(&self, file_id: FileId) -> Cancellable<Vec<Fold>> {
         self.with_db(|db| folding_ranges::folding_ranges(&db.parse(file_id).tree()))
     }
 
+    /// Returns the folds inside the function at `fn_range`, unless they can't be computed
+    /// separately from the rest of the file.
+    pub fn fn_folding_ranges(
+        &self,
+        file_id: FileId,
+        fn_range: TextRange,
+    ) -> Cancellable<Option<Vec<Fold>>> {
+        self.with_db(|db| folding_ranges::fn_folding_ranges(&db.parse(file_id).tree(), fn_range))
+    }
+
+    /// Returns the color literals of the file.
+    pub fn document_colors(&self, file_id: FileId) -> Cancellable<Vec<ColorInformation>> {
+        self.with_db(|db| document_colors::document_colors(db, file_id))
+    }
+
+    /// Returns the ways to rewrite the color literal at `frange` to `color`.
+    pub fn color_presentations(
+        &self,
+        frange: FileRange,
+        color: Color,
+    ) -> Cancellable<Vec<ColorPresentation>> {
+        self.with_db(|db| document_colors::color_presentations(db, frange, color))
+    }
+
+    /// Returns the `TODO`, `FIXME` and `HACK` comments of the file, or of the whole workspace.
+    pub fn todo_index(&self, file_id: Option<FileId>) -> Cancellable<Vec<TodoItem>> {
+        self.with_db(|db| todo_index::todo_index(db, file_id))
+    }
+
     /// Fuzzy searches for a symbol.
     pub fn symbol_search(&self, query: Query, limit: usize) -> Cancellable<Vec<NavigationTarget>> {
         self.with_db(|db| {
@@ -436,6 +555,16 @@ pub fn goto_declaration(
         self.with_db(|db| goto_declaration::goto_declaration(db, position))
     }
 
+    /// Returns the impls of a trait matching `pattern`, like `From<_> for MyError`, whose paths
+    /// are resolved at `position`.
+    pub fn search_impls(
+        &self,
+        position: FilePosition,
+        pattern: &str,
+    ) -> Cancellable<Result<Vec<NavigationTarget>, ImplPatternError>> {
+        self.with_db(|db| search_impls::search_impls(db, position, pattern))
+    }
+
     /// Returns the impls from the symbol at `position`.
     pub fn goto_implementation(
         &self,
@@ -444,6 +573,22 @@ pub fn goto_implementation(
         self.with_db(|db| goto_implementation::goto_implementation(db, position))
     }
 
+    /// Returns the macro invocation that generated the symbol at `position`.
+    pub fn goto_generating_macro(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| macro_provenance::goto_generating_macro(db, position))
+    }
+
+    /// Returns the items generated by the macro invocation or derive at `position`.
+    pub fn generated_items(
+        &self,
+        position: FilePosition,
+    ) -> Cancellable<Option<RangeInfo<Vec<NavigationTarget>>>> {
+        self.with_db(|db| macro_provenance::generated_items(db, position))
+    }
+
     /// Returns the type definitions for the symbol at `position`.
     pub fn goto_type_definition(
         &self,
@@ -711,6 +856,22 @@ pub fn structural_search_replace(
         })
     }
 
+    /// Finds the code matching the search pattern `query`, without replacing it.
+    pub fn structural_search(
+        &self,
+        query: &str,
+        resolve_context: FilePosition,
+        selections: Vec<FileRange>,
+    ) -> Cancellable<Result<Vec<FoundMatch>, SsrError>> {
+        self.with_db(|db| {
+            let pattern: ide_ssr::SsrPattern = query.parse()?;
+            let mut match_finder =
+                ide_ssr::MatchFinder::in_context(db, resolve_context, selections)?;
+            match_finder.add_search_pattern(pattern)?;
+            Ok(match_finder.find())
+        })
+    }
+
     pub fn annotations(
         &self,
         config: &AnnotationConfig,
@@ -738,6 +899,18 @@ pub fn get_recursive_memory_layout(
         self.with_db(|db| view_memory_layout(db, position))
     }
 
+    /// Finds the instantiations of the generic function at `position`, calling `cb` with each
+    /// of them as soon as it's found. Returns `None` if there's no generic function there.
+    pub fn monomorphizations<F>(&self, position: FilePosition, cb: F) -> Cancellable<Option<()>>
+    where
+        F: FnMut(Monomorphization),
+    {
+        // The instantiations reported before a cancellation are still valid, so a callback that
+        // is unwound through is fine.
+        let mut cb = std::panic::AssertUnwindSafe(cb);
+        self.with_db(move |db| monomorphizations::monomorphizations(db, position, &mut *cb))
+    }
+
     /// Performs an operation on the database that may be canceled.
     ///
     /// rust-analyzer needs to be able to answer semantic questions about the
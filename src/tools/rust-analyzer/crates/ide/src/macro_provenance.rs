@@ -0,0 +1,340 @@
+use hir::{
+    DescendPreference, HasSource, HirFileId, InFile, MacroInvocation, MacroInvocationKind,
+    Semantics,
+};
+use ide_db::{
+    defs::{Definition, IdentClass},
+    helpers::pick_best_token,
+    RootDatabase, SymbolKind,
+};
+use syntax::{ast, AstNode, NodeOrToken, SyntaxKind::*, T};
+
+use crate::{navigation_target::ToNav, FilePosition, NavigationTarget, RangeInfo, TryToNav};
+
+// Feature: Go to Generating Macro
+//
+// Navigates from an item produced by a macro expansion, or a use of such an item, to the macro
+// invocation, attribute or derive that generated it.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **rust-analyzer: Go to generating macro**
+// |===
+pub(crate) fn goto_generating_macro(
+    db: &RootDatabase,
+    FilePosition { file_id, offset }: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id).syntax().clone();
+    let original_token = pick_best_token(file.token_at_offset(offset), |kind| match kind {
+        IDENT | INT_NUMBER | T![self] => 1,
+        _ => 0,
+    })?;
+    let range = original_token.text_range();
+
+    let mut navs: Vec<_> = sema
+        .descend_into_macros(DescendPreference::None, original_token)
+        .into_iter()
+        .filter_map(|token| IdentClass::classify_token(&sema, &token))
+        .flat_map(IdentClass::definitions_no_ops)
+        .filter_map(|def| generated_by(&sema, def))
+        .filter_map(|invocation| invocation_nav(&sema, &invocation))
+        .collect();
+    navs.dedup();
+
+    Some(RangeInfo::new(range, navs))
+}
+
+// Feature: Go to Generated Items
+//
+// Navigates from a macro call, an attribute macro or a derive to the items its expansion
+// defines.
+//
+// |===
+// | Editor  | Action Name
+//
+// | VS Code | **rust-analyzer: Go to generated items**
+// |===
+pub(crate) fn generated_items(
+    db: &RootDatabase,
+    FilePosition { file_id, offset }: FilePosition,
+) -> Option<RangeInfo<Vec<NavigationTarget>>> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(file_id).syntax().clone();
+    let token = pick_best_token(file.token_at_offset(offset), |kind| match kind {
+        IDENT => 2,
+        T![!] | T![#] => 1,
+        _ => 0,
+    })?;
+    let range = token.text_range();
+
+    let expansions = token.parent_ancestors().find_map(|node| {
+        if let Some(attr) = ast::Attr::cast(node.clone()) {
+            if let Some(expansions) = sema.expand_derive_macro(&attr) {
+                // only the derive under the cursor, or all of them if the cursor is on `derive`
+                let Some(tt) =
+                    attr.token_tree().filter(|tt| tt.syntax().text_range().contains_range(range))
+                else {
+                    return Some(expansions);
+                };
+                let idx = tt
+                    .token_trees_and_tokens()
+                    .filter_map(NodeOrToken::into_token)
+                    .take_while(|it| it != &token)
+                    .filter(|it| it.kind() == T![,])
+                    .count();
+                return Some(expansions.into_iter().nth(idx).into_iter().collect());
+            }
+            let item = ast::Item::cast(attr.syntax().parent()?)?;
+            sema.resolve_attr_macro_call(&item)?;
+            return sema.expand_attr_macro(&item).map(|it| vec![it]);
+        }
+        let mac = ast::MacroCall::cast(node)?;
+        sema.expand(&mac).map(|it| vec![it])
+    })?;
+
+    let navs = expansions
+        .iter()
+        .flat_map(|root| root.children().filter_map(ast::Item::cast))
+        .filter_map(|item| item_nav(&sema, item))
+        .flatten()
+        .collect();
+
+    Some(RangeInfo::new(range, navs))
+}
+
+/// Returns the macro invocation the definition of `def` was produced by.
+pub(crate) fn generated_by(
+    sema: &Semantics<'_, RootDatabase>,
+    def: Definition,
+) -> Option<MacroInvocation> {
+    fn source_file<T: HasSource>(db: &RootDatabase, it: T) -> Option<HirFileId> {
+        Some(it.source(db)?.file_id)
+    }
+
+    let db = sema.db;
+    let file_id = match def {
+        Definition::Macro(it) => source_file(db, it),
+        Definition::Field(it) => source_file(db, it),
+        Definition::Function(it) => source_file(db, it),
+        Definition::Adt(hir::Adt::Struct(it)) => source_file(db, it),
+        Definition::Adt(hir::Adt::Union(it)) => source_file(db, it),
+        Definition::Adt(hir::Adt::Enum(it)) => source_file(db, it),
+        Definition::Variant(it) => source_file(db, it),
+        Definition::Const(it) => source_file(db, it),
+        Definition::Static(it) => source_file(db, it),
+        Definition::Trait(it) => source_file(db, it),
+        Definition::TraitAlias(it) => source_file(db, it),
+        Definition::TypeAlias(it) => source_file(db, it),
+        Definition::SelfType(it) => source_file(db, it),
+        _ => None,
+    }?;
+    sema.macro_invocation(file_id)
+}
+
+/// Renders the invocation the way it is written, e.g. `#[derive(Serialize)]`, `#[test]` or
+/// `vec!`.
+pub(crate) fn invocation_label(db: &RootDatabase, invocation: &MacroInvocation) -> Option<String> {
+    let name = match invocation.macro_ {
+        Some(mac) => mac.name(db).display(db).to_string(),
+        None => match invocation.kind {
+            MacroInvocationKind::FnLike => {
+                ast::MacroCall::cast(invocation.call.value.clone())?.path()?.to_string()
+            }
+            MacroInvocationKind::Attr => {
+                ast::Attr::cast(invocation.call.value.clone())?.path()?.to_string()
+            }
+            MacroInvocationKind::Derive => return None,
+        },
+    };
+    Some(match invocation.kind {
+        MacroInvocationKind::FnLike => format!("{name}!"),
+        MacroInvocationKind::Derive => format!("#[derive({name})]"),
+        MacroInvocationKind::Attr => format!("#[{name}]"),
+    })
+}
+
+fn invocation_nav(
+    sema: &Semantics<'_, RootDatabase>,
+    invocation: &MacroInvocation,
+) -> Option<NavigationTarget> {
+    let db = sema.db;
+    let InFile { file_id, value: call } = &invocation.call;
+    let full_range = InFile::new(*file_id, call).original_file_range(db);
+    let name = invocation
+        .macro_
+        .map(|mac| mac.name(db).to_smol_str())
+        .unwrap_or_else(|| call.text().to_string().into());
+
+    let focus_range = match invocation.kind {
+        MacroInvocationKind::FnLike => ast::MacroCall::cast(call.clone()).and_then(|it| it.path()),
+        MacroInvocationKind::Attr => ast::Attr::cast(call.clone()).and_then(|it| it.path()),
+        MacroInvocationKind::Derive => None,
+    }
+    .and_then(|path| {
+        InFile::new(*file_id, path.syntax()).original_file_range_opt(db).map(|(it, _)| it)
+    })
+    .or_else(|| {
+        // derives are only visible as tokens in the `#[derive]` token tree
+        let token = ast::Attr::cast(call.clone())?
+            .token_tree()?
+            .token_trees_and_tokens()
+            .filter_map(NodeOrToken::into_token)
+            .find(|it| it.kind() == IDENT && it.text() == name)?;
+        InFile::new(*file_id, token).original_file_range_opt(db)
+    })
+    .filter(|it| it.file_id == full_range.file_id && full_range.range.contains_range(it.range))
+    .map(|it| it.range);
+
+    let mut nav = NavigationTarget::from_syntax(
+        full_range.file_id,
+        name,
+        focus_range,
+        full_range.range,
+        SymbolKind::Macro,
+    );
+    nav.description = invocation_label(db, invocation);
+    Some(nav)
+}
+
+fn item_nav(
+    sema: &Semantics<'_, RootDatabase>,
+    item: ast::Item,
+) -> Option<impl Iterator<Item = NavigationTarget>> {
+    let nav = match item {
+        ast::Item::Const(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Enum(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Fn(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Impl(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::MacroRules(it) => sema.to_def(&ast::Macro::from(it))?.try_to_nav(sema.db)?,
+        ast::Item::MacroDef(it) => sema.to_def(&ast::Macro::from(it))?.try_to_nav(sema.db)?,
+        ast::Item::Module(it) => sema.to_def(&it)?.to_nav(sema.db),
+        ast::Item::Static(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Struct(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Trait(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::TraitAlias(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::TypeAlias(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::Union(it) => sema.to_def(&it)?.try_to_nav(sema.db)?,
+        ast::Item::ExternBlock(_)
+        | ast::Item::ExternCrate(_)
+        | ast::Item::MacroCall(_)
+        | ast::Item::Use(_) => return None,
+    };
+    Some(nav.into_iter())
+}
+
+#[cfg(test)]
+mod tests {
+    use ide_db::base_db::FileRange;
+    use itertools::Itertools;
+
+    use crate::fixture;
+
+    fn check_generating_macro(ra_fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(ra_fixture);
+        let navs = analysis.goto_generating_macro(position).unwrap().unwrap().info;
+        check_navs(navs, expected);
+    }
+
+    fn check_generated_items(ra_fixture: &str) {
+        let (analysis, position, expected) = fixture::annotations(ra_fixture);
+        let navs = analysis.generated_items(position).unwrap().unwrap().info;
+        check_navs(navs, expected);
+    }
+
+    fn check_navs(navs: Vec<crate::NavigationTarget>, expected: Vec<(FileRange, String)>) {
+        let cmp = |frange: &FileRange| (frange.file_id, frange.range.start());
+        let actual = navs
+            .into_iter()
+            .map(|nav| FileRange { file_id: nav.file_id, range: nav.focus_or_full_range() })
+            .sorted_by_key(cmp)
+            .collect::<Vec<_>>();
+        let expected =
+            expected.into_iter().map(|(range, _)| range).sorted_by_key(cmp).collect::<Vec<_>>();
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn generating_macro_rules() {
+        check_generating_macro(
+            r#"
+macro_rules! m {
+    ($name:ident) => { fn $name() {} };
+}
+  m!(foo);
+//^
+fn main() {
+    foo$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generating_attr_macro() {
+        check_generating_macro(
+            r#"
+//- proc_macros: identity
+#[proc_macros::identity]
+//^^^^^^^^^^^^^^^^^^^^^
+fn foo() {}
+fn main() {
+    foo$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generating_derive() {
+        check_generating_macro(
+            r#"
+//- minicore: derive, clone
+#[derive(Clone)]
+       //^^^^^
+struct Foo;
+fn main() {
+    <Foo as Clone>::clone$0(&Foo);
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn not_generated() {
+        check_generating_macro(
+            r#"
+fn foo() {}
+fn main() {
+    foo$0();
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn generated_items_of_macro_call() {
+        check_generated_items(
+            r#"
+macro_rules! m {
+    ($a:ident, $b:ident) => { fn $a() {} struct $b; };
+}
+m$0!(foo, Bar);
+// ^^^  ^^^
+"#,
+        );
+    }
+
+    #[test]
+    fn generated_items_of_derive() {
+        check_generated_items(
+            r#"
+//- minicore: derive, clone, copy
+#[derive(Copy, Clone$0)]
+             //^^^^^
+struct Foo;
+"#,
+        );
+    }
+}
@@ -0,0 +1,223 @@
+use hir::{
+    AsAssocItem, AssocItemContainer, CallableKind, Crate, Function, GenericDef, HirDisplay,
+    ModuleDef, Semantics, Type, TypeParam,
+};
+use ide_db::{
+    base_db::{salsa::Database, FilePosition, FileRange, Upcast},
+    defs::Definition,
+    helpers::{get_definition, pick_best_token},
+    FxHashMap, FxHashSet, RootDatabase,
+};
+use syntax::{ast, match_ast, AstNode, SyntaxKind};
+
+#[derive(Debug)]
+pub struct Monomorphization {
+    /// The names of the type parameters of the function, with the types they're instantiated with.
+    pub type_args: Vec<(String, String)>,
+    /// The first call found that instantiates the function with these types.
+    pub call_site: FileRange,
+}
+
+// Feature: Find Monomorphizations
+//
+// Lists the concrete instantiations of a generic function that are reachable from the
+// non-generic functions of the workspace, by following calls and substituting the type arguments
+// of each caller into its callees. Calls made from library crates and through trait methods aren't
+// followed.
+//
+// The instantiations are reported to `cb` as they're found, and the search can be cancelled.
+pub(crate) fn monomorphizations(
+    db: &RootDatabase,
+    position: FilePosition,
+    cb: &mut dyn FnMut(Monomorphization),
+) -> Option<()> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let token =
+        pick_best_token(file.syntax().token_at_offset(position.offset), |kind| match kind {
+            SyntaxKind::IDENT => 3,
+            _ => 0,
+        })?;
+    let target = match get_definition(&sema, token)? {
+        Definition::Function(it) if is_generic(db, it) => it,
+        _ => return None,
+    };
+
+    let mut work = workspace_roots(db);
+    let mut seen: FxHashSet<(Function, Vec<Type>)> =
+        work.iter().map(|&(func, _)| (func, Vec::new())).collect();
+    while let Some((func, args)) = work.pop() {
+        db.unwind_if_cancelled();
+        let Some(body) = sema.source(func).and_then(|it| it.value.body()) else { continue };
+        for node in body.syntax().descendants() {
+            let callable = match_ast! {
+                match node {
+                    ast::PathExpr(it) => {
+                        sema.type_of_expr(&it.into()).and_then(|it| it.original.as_callable(db))
+                    },
+                    ast::MethodCallExpr(it) => sema.resolve_method_call_as_callable(&it),
+                    _ => None,
+                }
+            };
+            let Some(callable) = callable else { continue };
+            let CallableKind::Function(callee) = callable.kind() else { continue };
+            let callee_args: Vec<(TypeParam, Type)> = callable
+                .type_param_args(db)
+                .into_iter()
+                .map(|(param, ty)| (param, ty.substitute_type_params(db, &args)))
+                .collect();
+            if callee_args
+                .iter()
+                .any(|(_, ty)| ty.contains_unknown() || !ty.generic_params(db).is_empty())
+            {
+                continue;
+            }
+            let key = (callee, callee_args.iter().map(|(_, ty)| ty.clone()).collect());
+            if !seen.insert(key) {
+                continue;
+            }
+            if callee == target {
+                cb(Monomorphization {
+                    type_args: callee_args
+                        .iter()
+                        .map(|(param, ty)| {
+                            (
+                                param.name(db).display(db.upcast()).to_string(),
+                                ty.display(db).to_string(),
+                            )
+                        })
+                        .collect(),
+                    call_site: sema.original_range(&node),
+                });
+            }
+            if callee.module(db).krate().origin(db).is_local() {
+                work.push((callee, callee_args.into_iter().collect()));
+            }
+        }
+    }
+    Some(())
+}
+
+/// The non-generic functions of the local crates, the starting points of the search.
+fn workspace_roots(db: &RootDatabase) -> Vec<(Function, FxHashMap<TypeParam, Type>)> {
+    let mut roots = Vec::new();
+    for krate in Crate::all(db).into_iter().filter(|it| it.origin(db).is_local()) {
+        for module in krate.modules(db) {
+            let functions = module
+                .declarations(db)
+                .into_iter()
+                .filter_map(|it| match it {
+                    ModuleDef::Function(it) => Some(it),
+                    _ => None,
+                })
+                .chain(
+                    module
+                        .impl_defs(db)
+                        .into_iter()
+                        .flat_map(|it| it.items(db))
+                        .filter_map(|it| it.as_function()),
+                );
+            roots.extend(
+                functions.filter(|&it| !is_generic(db, it)).map(|it| (it, FxHashMap::default())),
+            );
+        }
+    }
+    roots
+}
+
+fn is_generic(db: &RootDatabase, func: Function) -> bool {
+    let parent = match func.as_assoc_item(db).map(|it| it.container(db)) {
+        Some(AssocItemContainer::Impl(it)) => Some(GenericDef::Impl(it)),
+        // Trait methods are generic over `Self`.
+        Some(AssocItemContainer::Trait(_)) => return true,
+        None => None,
+    };
+    Some(GenericDef::Function(func))
+        .into_iter()
+        .chain(parent)
+        .any(|it| it.type_or_const_params(db).into_iter().any(|it| it.as_type_param(db).is_some()))
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let mut actual = String::new();
+        let found = analysis
+            .monomorphizations(position, |it| {
+                let args = it
+                    .type_args
+                    .iter()
+                    .map(|(param, ty)| format!("{param} = {ty}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                actual += &format!("{args} @ {:?}\n", it.call_site.range);
+            })
+            .unwrap();
+        if found.is_none() {
+            actual += "not a generic function\n";
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn direct_calls() {
+        check(
+            r#"
+fn id$0<T>(t: T) -> T { t }
+fn main() {
+    id(1u32);
+    id("");
+    id(2u32);
+    let _: fn(bool) -> bool = id;
+}
+"#,
+            expect![[r#"
+                T = u32 @ 42..44
+                T = &str @ 56..58
+                T = bool @ 108..110
+            "#]],
+        );
+    }
+
+    #[test]
+    fn through_generic_callers() {
+        check(
+            r#"
+struct Wrapper<T>(T);
+impl<T> Wrapper<T> {
+    fn get(&self) -> &T { inner(&self.0) }
+}
+fn inner<U>(u: &U) -> &U { u }
+fn twice<T>(t: T) { inner$0(&t); inner(&(t, t)); }
+fn never_called<T>(t: T) { inner(&t); }
+fn main() {
+    twice(1i32);
+    Wrapper(2u8).get();
+}
+"#,
+            expect![[r#"
+                U = u8 @ 69..74
+                U = i32 @ 139..144
+                U = (i32, i32) @ 150..155
+            "#]],
+        );
+    }
+
+    #[test]
+    fn not_generic() {
+        check(
+            r#"
+fn foo$0() {}
+fn main() { foo(); }
+"#,
+            expect![[r#"
+                not a generic function
+            "#]],
+        );
+    }
+}
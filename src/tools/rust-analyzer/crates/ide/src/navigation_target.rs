@@ -122,13 +122,11 @@ pub(crate) fn from_module_to_decl(
 
     #[cfg(test)]
     pub(crate) fn debug_render(&self) -> String {
-        let mut buf = format!(
-            "{} {:?} {:?} {:?}",
-            self.name,
-            self.kind.unwrap(),
-            self.file_id,
-            self.full_range
-        );
+        let mut buf = self.name.to_string();
+        if let Some(kind) = self.kind {
+            buf.push_str(&format!(" {kind:?}"));
+        }
+        buf.push_str(&format!(" {:?} {:?}", self.file_id, self.full_range));
         if let Some(focus_range) = self.focus_range {
             buf.push_str(&format!(" {focus_range:?}"))
         }
@@ -153,7 +151,7 @@ pub(crate) fn from_named(
         )
     }
 
-    fn from_syntax(
+    pub(crate) fn from_syntax(
         file_id: FileId,
         name: SmolStr,
         focus_range: Option<TextRange>,
@@ -0,0 +1,523 @@
+use std::collections::VecDeque;
+
+use either::Either;
+use hir::{
+    Adt, AssocItem, Crate, GenericDef, HasAttrs, HasSource, HasVisibility, HirDisplay, Impl,
+    ModuleDef, ScopeDef, StructKind, Visibility,
+};
+use ide_db::{base_db::CrateId, FxHashMap, FxHashSet, RootDatabase};
+use itertools::Itertools;
+use syntax::AstNode;
+
+/// An item of the public API of a crate, see [`public_api`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiItem {
+    /// The path the item is reachable at, like `krate::module::Type::method`. Trait
+    /// implementations are identified by their header instead, like `impl Clone for Type`.
+    pub path: String,
+    /// The path of the type, trait or enum this is a field, associated item or variant of.
+    pub parent: Option<String>,
+    pub kind: ApiItemKind,
+    pub signature: String,
+    /// Whether users may rely on this item not gaining fields or variants: structs that can be
+    /// constructed with a literal, and enums that can be matched on exhaustively.
+    pub exhaustive: bool,
+    /// Whether this trait item has a default, so that implementations don't need to define it.
+    pub has_default: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ApiItemKind {
+    Module,
+    Function,
+    Struct,
+    Enum,
+    Union,
+    Variant,
+    Field,
+    Trait,
+    TraitItem,
+    AssocItem,
+    TraitImpl,
+    TypeAlias,
+    Const,
+    Static,
+    Macro,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiChange {
+    pub kind: ApiChangeKind,
+    pub path: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    pub impact: SemverImpact,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// The version component that has to be bumped for a change, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SemverImpact {
+    Minor,
+    Major,
+}
+
+// Feature: API Diff
+//
+// Compares the public API of the library crates of two versions of a workspace and classifies
+// every added, removed and changed item as a minor or major (breaking) change.
+//
+// Items are compared by the path they are reachable at and their signature, so moving an item
+// while re-exporting it at its old path isn't a change. Implementations of auto traits aren't
+// compared.
+//
+// Available on the command line as `rust-analyzer api-diff <old> <new>`.
+pub(crate) fn public_api(db: &RootDatabase, crate_id: CrateId) -> Vec<ApiItem> {
+    let krate = Crate::from(crate_id);
+    let crate_name =
+        krate.display_name(db).map_or_else(|| "{unknown}".to_owned(), |it| it.to_string());
+    let mut res = Vec::new();
+
+    // breadth first, so that items are listed at their shortest path
+    let mut visited = FxHashSet::default();
+    let mut queue = VecDeque::from([(krate.root_module(), crate_name)]);
+    while let Some((module, path)) = queue.pop_front() {
+        if !visited.insert(module) {
+            continue;
+        }
+        let entries = module
+            .public_scope(db)
+            .into_iter()
+            .map(|(name, def)| (name.display(db).to_string(), def))
+            .sorted_by(|(a, _), (b, _)| a.cmp(b));
+        for (name, def) in entries {
+            let ScopeDef::ModuleDef(def) = def else { continue };
+            let item_path = format!("{path}::{name}");
+            if let ModuleDef::Module(it) = def {
+                // only modules of this crate, re-exported dependencies have APIs of their own
+                if it.krate() == krate {
+                    res.push(item(&item_path, None, ApiItemKind::Module, format!("mod {name}")));
+                    queue.push_back((it, item_path));
+                }
+                continue;
+            }
+            if def.module(db).map(|it| it.krate()) != Some(krate) {
+                continue;
+            }
+            add_module_def(db, &mut res, def, item_path);
+        }
+    }
+
+    for module in krate.modules(db) {
+        for impl_ in module.impl_defs(db) {
+            add_trait_impl(db, &mut res, impl_);
+        }
+    }
+    res.sort_by(|a, b| a.path.cmp(&b.path));
+    res.dedup_by(|a, b| a.path == b.path);
+    res
+}
+
+/// Compares two versions of a public API, see [`public_api`].
+pub fn diff_public_api(old: &[ApiItem], new: &[ApiItem]) -> Vec<ApiChange> {
+    let old_items: FxHashMap<_, _> = old.iter().map(|it| (it.path.as_str(), it)).collect();
+    let new_items: FxHashMap<_, _> = new.iter().map(|it| (it.path.as_str(), it)).collect();
+
+    let mut res = Vec::new();
+    for old_item in old {
+        match new_items.get(old_item.path.as_str()) {
+            None => res.push(ApiChange {
+                kind: ApiChangeKind::Removed,
+                path: old_item.path.clone(),
+                old: Some(old_item.signature.clone()),
+                new: None,
+                impact: SemverImpact::Major,
+            }),
+            Some(new_item) => {
+                if let Some(impact) = change_impact(old_item, new_item) {
+                    res.push(ApiChange {
+                        kind: ApiChangeKind::Changed,
+                        path: old_item.path.clone(),
+                        old: Some(old_item.signature.clone()),
+                        new: Some(new_item.signature.clone()),
+                        impact,
+                    });
+                }
+            }
+        }
+    }
+    for new_item in new {
+        if old_items.contains_key(new_item.path.as_str()) {
+            continue;
+        }
+        // new members of existing items can break code that relies on the item being complete
+        let old_parent = new_item.parent.as_deref().and_then(|it| old_items.get(it));
+        let breaking = old_parent.map_or(false, |parent| match new_item.kind {
+            ApiItemKind::Variant | ApiItemKind::Field => parent.exhaustive,
+            ApiItemKind::TraitItem => !new_item.has_default,
+            _ => false,
+        });
+        res.push(ApiChange {
+            kind: ApiChangeKind::Added,
+            path: new_item.path.clone(),
+            old: None,
+            new: Some(new_item.signature.clone()),
+            impact: if breaking { SemverImpact::Major } else { SemverImpact::Minor },
+        });
+    }
+    res.sort_by(|a, b| b.impact.cmp(&a.impact).then_with(|| a.path.cmp(&b.path)));
+    res
+}
+
+fn change_impact(old: &ApiItem, new: &ApiItem) -> Option<SemverImpact> {
+    if old.signature != new.signature || old.kind != new.kind {
+        return Some(SemverImpact::Major);
+    }
+    // users can rely on less after `#[non_exhaustive]` is added or a default is removed
+    let breaking = (old.exhaustive && !new.exhaustive) || (old.has_default && !new.has_default);
+    let extending = (!old.exhaustive && new.exhaustive) || (!old.has_default && new.has_default);
+    if breaking {
+        Some(SemverImpact::Major)
+    } else if extending {
+        Some(SemverImpact::Minor)
+    } else {
+        None
+    }
+}
+
+fn item(path: &str, parent: Option<&str>, kind: ApiItemKind, signature: String) -> ApiItem {
+    ApiItem {
+        path: path.to_owned(),
+        parent: parent.map(ToOwned::to_owned),
+        kind,
+        signature,
+        exhaustive: false,
+        has_default: false,
+    }
+}
+
+fn add_module_def(db: &RootDatabase, acc: &mut Vec<ApiItem>, def: ModuleDef, path: String) {
+    match def {
+        ModuleDef::Function(it) => {
+            acc.push(item(&path, None, ApiItemKind::Function, it.display(db).to_string()))
+        }
+        ModuleDef::Adt(adt) => add_adt(db, acc, adt, path),
+        ModuleDef::Variant(it) => {
+            acc.push(item(&path, None, ApiItemKind::Variant, it.display(db).to_string()));
+        }
+        ModuleDef::Trait(trait_) => {
+            acc.push(item(&path, None, ApiItemKind::Trait, trait_.display(db).to_string()));
+            for assoc in trait_.items(db) {
+                let Some(name) = assoc.name(db) else { continue };
+                let mut trait_item = item(
+                    &format!("{path}::{}", name.display(db)),
+                    Some(&path),
+                    ApiItemKind::TraitItem,
+                    assoc_signature(db, assoc),
+                );
+                trait_item.has_default = has_default(db, assoc);
+                acc.push(trait_item);
+            }
+        }
+        ModuleDef::TraitAlias(it) => {
+            acc.push(item(&path, None, ApiItemKind::Trait, it.display(db).to_string()))
+        }
+        ModuleDef::TypeAlias(it) => {
+            acc.push(item(&path, None, ApiItemKind::TypeAlias, it.display(db).to_string()))
+        }
+        ModuleDef::Const(it) => {
+            acc.push(item(&path, None, ApiItemKind::Const, it.display(db).to_string()))
+        }
+        ModuleDef::Static(it) => {
+            acc.push(item(&path, None, ApiItemKind::Static, it.display(db).to_string()))
+        }
+        ModuleDef::Macro(it) => {
+            acc.push(item(&path, None, ApiItemKind::Macro, it.display(db).to_string()))
+        }
+        ModuleDef::Module(_) | ModuleDef::BuiltinType(_) => (),
+    }
+}
+
+fn add_adt(db: &RootDatabase, acc: &mut Vec<ApiItem>, adt: Adt, path: String) {
+    let non_exhaustive = adt.attrs(db).by_key("non_exhaustive").exists();
+    let generics = generics(db, GenericDef::Adt(adt));
+    let name = adt.name(db).display(db).to_string();
+    let (kind, signature, exhaustive) = match adt {
+        Adt::Struct(it) => {
+            let fields = it.fields(db);
+            let shape = match it.kind(db) {
+                StructKind::Record => " { .. }",
+                StructKind::Tuple => "(..)",
+                StructKind::Unit => "",
+            };
+            let constructible = fields.iter().all(|it| it.visibility(db) == Visibility::Public);
+            for field in fields.into_iter().filter(|it| it.visibility(db) == Visibility::Public) {
+                acc.push(item(
+                    &format!("{path}::{}", field.name(db).display(db)),
+                    Some(&path),
+                    ApiItemKind::Field,
+                    field.display(db).to_string(),
+                ));
+            }
+            let signature = format!("struct {name}{generics}{shape}");
+            (ApiItemKind::Struct, signature, constructible && !non_exhaustive)
+        }
+        Adt::Enum(it) => {
+            for variant in it.variants(db) {
+                acc.push(item(
+                    &format!("{path}::{}", variant.name(db).display(db)),
+                    Some(&path),
+                    ApiItemKind::Variant,
+                    variant.display(db).to_string(),
+                ));
+            }
+            (ApiItemKind::Enum, format!("enum {name}{generics}"), !non_exhaustive)
+        }
+        Adt::Union(it) => {
+            for field in
+                it.fields(db).into_iter().filter(|it| it.visibility(db) == Visibility::Public)
+            {
+                acc.push(item(
+                    &format!("{path}::{}", field.name(db).display(db)),
+                    Some(&path),
+                    ApiItemKind::Field,
+                    field.display(db).to_string(),
+                ));
+            }
+            (ApiItemKind::Union, format!("union {name}{generics}"), false)
+        }
+    };
+    let mut adt_item = item(&path, None, kind, signature);
+    adt_item.exhaustive = exhaustive;
+    acc.push(adt_item);
+
+    let krate = adt.module(db).krate();
+    for impl_ in Impl::all_for_type(db, adt.ty(db)) {
+        if impl_.trait_(db).is_some() || impl_.module(db).krate() != krate {
+            continue;
+        }
+        for assoc in impl_.items(db) {
+            let Some(name) = assoc.name(db) else { continue };
+            if assoc.visibility(db) != Visibility::Public {
+                continue;
+            }
+            acc.push(item(
+                &format!("{path}::{}", name.display(db)),
+                Some(&path),
+                ApiItemKind::AssocItem,
+                assoc_signature(db, assoc),
+            ));
+        }
+    }
+}
+
+fn add_trait_impl(db: &RootDatabase, acc: &mut Vec<ApiItem>, impl_: Impl) {
+    let Some(trait_) = impl_.trait_(db) else { return };
+    if trait_.visibility(db) != Visibility::Public {
+        return;
+    }
+    let self_ty = impl_.self_ty(db);
+    if self_ty.as_adt().map_or(false, |adt| adt.visibility(db) != Visibility::Public) {
+        return;
+    }
+    // the trait's generic arguments are only available in the source
+    let Some(trait_ref) = impl_.source(db).and_then(|it| it.value.trait_()) else { return };
+    let trait_ref = trait_ref.syntax().text().to_string().split_whitespace().join(" ");
+    let negative = if impl_.is_negative(db) { "!" } else { "" };
+    let header = format!("impl {negative}{trait_ref} for {}", self_ty.display(db));
+    acc.push(item(&header, None, ApiItemKind::TraitImpl, header.clone()));
+}
+
+fn assoc_signature(db: &RootDatabase, assoc: AssocItem) -> String {
+    match assoc {
+        AssocItem::Function(it) => it.display(db).to_string(),
+        AssocItem::Const(it) => it.display(db).to_string(),
+        AssocItem::TypeAlias(it) => it.display(db).to_string(),
+    }
+}
+
+fn has_default(db: &RootDatabase, assoc: AssocItem) -> bool {
+    match assoc {
+        AssocItem::Function(it) => it.has_body(db),
+        AssocItem::Const(it) => it.source(db).map_or(false, |it| it.value.body().is_some()),
+        AssocItem::TypeAlias(it) => it.source(db).map_or(false, |it| it.value.ty().is_some()),
+    }
+}
+
+/// Renders the generic parameters of `def` together with their trait bounds, like
+/// `<'a, T: Clone, const N: usize>`.
+fn generics(db: &RootDatabase, def: GenericDef) -> String {
+    let lifetimes =
+        def.lifetime_params(db).into_iter().map(|it| it.name(db).display(db).to_string());
+    let params = def.type_or_const_params(db).into_iter().filter_map(|it| match it.split(db) {
+        Either::Left(it) => {
+            Some(format!("const {}: {}", it.name(db).display(db), it.ty(db).display(db)))
+        }
+        Either::Right(it) if it.is_implicit(db) => None,
+        Either::Right(it) => {
+            let bounds =
+                it.trait_bounds(db).into_iter().map(|it| it.name(db).display(db).to_string());
+            let bounds = bounds.sorted().join(" + ");
+            let name = it.name(db).display(db).to_string();
+            Some(if bounds.is_empty() { name } else { format!("{name}: {bounds}") })
+        }
+    });
+    let params = lifetimes.chain(params).join(", ");
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{params}>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::{fixture, ApiChangeKind};
+
+    use super::diff_public_api;
+
+    fn check(old: &str, new: &str, expect: Expect) {
+        let api = |ra_fixture| {
+            let (analysis, file_id) = fixture::file(ra_fixture);
+            let crate_id = analysis.crates_for(file_id).unwrap()[0];
+            analysis.public_api(crate_id).unwrap()
+        };
+        let changes = diff_public_api(&api(old), &api(new));
+        let actual = changes
+            .iter()
+            .map(|change| {
+                let kind = match change.kind {
+                    ApiChangeKind::Added => "added",
+                    ApiChangeKind::Removed => "removed",
+                    ApiChangeKind::Changed => "changed",
+                };
+                format!("{:?} {kind} {}\n", change.impact, change.path)
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn added_and_removed_items() {
+        check(
+            r#"
+//- /lib.rs crate:foo
+pub fn removed() {}
+pub fn kept() {}
+fn private() {}
+"#,
+            r#"
+//- /lib.rs crate:foo
+pub fn kept() {}
+pub fn added() {}
+fn other_private() {}
+"#,
+            expect![[r#"
+                Major removed foo::removed
+                Minor added foo::added
+            "#]],
+        );
+    }
+
+    #[test]
+    fn changed_signatures() {
+        check(
+            r#"
+//- minicore: clone
+//- /lib.rs crate:foo
+pub fn f(x: i32) {}
+pub struct S<T> { pub a: T, b: u32 }
+impl<T> S<T> {
+    pub fn new() -> Self { loop {} }
+}
+"#,
+            r#"
+//- minicore: clone
+//- /lib.rs crate:foo
+pub fn f(x: i64) {}
+pub struct S<T: Clone> { pub a: T, c: u64 }
+impl<T: Clone> S<T> {
+    pub fn new() -> Self { loop {} }
+}
+"#,
+            expect![[r#"
+                Major changed foo::S
+                Major changed foo::f
+            "#]],
+        );
+    }
+
+    #[test]
+    fn exhaustiveness() {
+        check(
+            r#"
+//- /lib.rs crate:foo
+pub enum Exhaustive { A }
+#[non_exhaustive]
+pub enum NonExhaustive { A }
+pub struct Literal { pub a: u32 }
+pub struct Private { pub a: u32, b: u32 }
+"#,
+            r#"
+//- /lib.rs crate:foo
+pub enum Exhaustive { A, B }
+#[non_exhaustive]
+pub enum NonExhaustive { A, B }
+pub struct Literal { pub a: u32, pub c: u32 }
+pub struct Private { pub a: u32, b: u32, pub c: u32 }
+"#,
+            expect![[r#"
+                Major added foo::Exhaustive::B
+                Major added foo::Literal::c
+                Minor added foo::NonExhaustive::B
+                Minor added foo::Private::c
+            "#]],
+        );
+    }
+
+    #[test]
+    fn traits_and_reexports() {
+        check(
+            r#"
+//- minicore: clone
+//- /lib.rs crate:foo
+mod imp {
+    pub trait Tr {
+        fn required(&self);
+    }
+    pub struct S;
+}
+pub use imp::{S, Tr};
+"#,
+            r#"
+//- minicore: clone
+//- /lib.rs crate:foo
+mod imp {
+    pub trait Tr {
+        fn required(&self);
+        fn new_required(&self);
+        fn provided(&self) {}
+    }
+    pub struct S;
+    impl Clone for S {
+        fn clone(&self) -> Self { S }
+    }
+}
+pub use imp::{S, Tr};
+"#,
+            expect![[r#"
+                Major added foo::Tr::new_required
+                Minor added foo::Tr::provided
+                Minor added impl Clone for S
+            "#]],
+        );
+    }
+}
@@ -12,6 +12,7 @@
 use hir::{DescendPreference, PathResolution, Semantics};
 use ide_db::{
     base_db::FileId,
+    crate_features::{cargo_features, cfg_feature_name, cfg_feature_references},
     defs::{Definition, NameClass, NameRefClass},
     search::{ReferenceCategory, SearchScope, UsageSearchResult},
     RootDatabase,
@@ -57,6 +58,9 @@ pub(crate) fn find_all_refs(
 ) -> Option<Vec<ReferenceSearchResult>> {
     let _p = tracing::span!(tracing::Level::INFO, "find_all_refs").entered();
     let syntax = sema.parse(position.file_id).syntax().clone();
+    if let Some(res) = find_cfg_feature_refs(sema, &syntax, position.offset) {
+        return Some(vec![res]);
+    }
     let make_searcher = |literal_search: bool| {
         move |def: Definition| {
             let mut usages =
@@ -121,6 +125,43 @@ pub(crate) fn find_all_refs(
     }
 }
 
+/// Finds the `feature = "name"` predicates naming the feature at `offset`, and the feature's
+/// entries in the `Cargo.toml`.
+fn find_cfg_feature_refs(
+    sema: &Semantics<'_, RootDatabase>,
+    syntax: &SyntaxNode,
+    offset: TextSize,
+) -> Option<ReferenceSearchResult> {
+    let token = syntax.token_at_offset(offset).find(|it| it.kind() == STRING)?;
+    let name = cfg_feature_name(sema, &token)?;
+    let krate = sema.scope(&token.parent()?)?.krate();
+
+    let mut references: IntMap<FileId, Vec<_>> = IntMap::default();
+    for (file_id, range) in cfg_feature_references(sema, krate, &name) {
+        references.entry(file_id).or_default().push((range, None));
+    }
+    let declaration = cargo_features(sema.db, krate).and_then(|features| {
+        // Features enabled by other features.
+        for &(_, range) in features.references.iter().filter(|(it, _)| *it == name) {
+            references.entry(features.file_id).or_default().push((range, None));
+        }
+        let range = features.declaration(&name)?;
+        let nav = NavigationTarget {
+            file_id: features.file_id,
+            full_range: range,
+            name: name.into(),
+            alias: None,
+            focus_range: None,
+            kind: None,
+            container_name: None,
+            description: None,
+            docs: None,
+        };
+        Some(Declaration { nav, is_mut: false })
+    });
+    Some(ReferenceSearchResult { declaration, references })
+}
+
 pub(crate) fn find_defs<'a>(
     sema: &'a Semantics<'_, RootDatabase>,
     syntax: &SyntaxNode,
@@ -803,6 +844,34 @@ fn f() {
         );
     }
 
+    #[test]
+    fn test_find_all_refs_in_included_file() {
+        check(
+            r#"
+//- minicore: include
+//- /lib.rs
+include!("foo.rs");
+
+fn foo$0() {}
+
+fn f() {
+    foo();
+}
+
+//- /foo.rs
+fn g() {
+    foo();
+}
+"#,
+            expect![[r#"
+                foo Function FileId(0) 21..32 24..27
+
+                FileId(0) 47..50
+                FileId(1) 13..16
+            "#]],
+        );
+    }
+
     #[test]
     fn test_find_all_refs_decl_module() {
         check(
@@ -1207,6 +1276,45 @@ fn check_with_scope(ra_fixture: &str, search_scope: Option<SearchScope>, expect:
         expect.assert_eq(actual.trim_start())
     }
 
+    #[test]
+    fn test_find_cfg_feature_refs() {
+        check(
+            r#"
+//- /src/lib.rs crate:foo cfg:feature=std
+#[cfg(feature = "std$0")]
+mod imp;
+#[cfg(not(feature = "std"))]
+fn f() -> bool {
+    cfg!(feature = "std")
+}
+#[doc(feature = "std")]
+#[cfg_attr(feature = "alloc", derive(Debug))]
+struct S;
+//- /src/imp.rs
+#[cfg_attr(all(unix, feature = "std"), derive(Debug))]
+struct S;
+//- /src/main.rs crate:main deps:foo
+#[cfg(feature = "std")]
+fn main() {}
+//- /Cargo.toml
+[features]
+default = ["std"]
+std = ["alloc"]
+alloc = []
+"#,
+            expect![[r#"
+                std FileId(3) 29..32
+
+                FileId(0) 17..20
+                FileId(0) 54..57
+                FileId(0) 99..102
+                FileId(1) 32..35
+                FileId(2) 17..20
+                FileId(3) 23..26
+            "#]],
+        );
+    }
+
     #[test]
     fn test_find_lifetimes_function() {
         check(
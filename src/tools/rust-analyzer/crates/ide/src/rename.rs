@@ -7,6 +7,9 @@
 use hir::{AsAssocItem, HirFileIdExt, InFile, Semantics};
 use ide_db::{
     base_db::{FileId, FileRange},
+    crate_features::{
+        cargo_features, cfg_feature_name, cfg_feature_references, is_valid_feature_name,
+    },
     defs::{Definition, NameClass, NameRefClass},
     rename::{bail, format_err, source_edit_from_references, IdentifierKind},
     RootDatabase,
@@ -14,7 +17,9 @@
 use itertools::Itertools;
 use stdx::{always, never};
 use syntax::{
-    ast, utils::is_raw_identifier, AstNode, SmolStr, SyntaxKind, SyntaxNode, TextRange, TextSize,
+    ast::{self, IsString},
+    utils::is_raw_identifier,
+    AstNode, AstToken, SmolStr, SyntaxKind, SyntaxNode, TextRange, TextSize,
 };
 
 use text_edit::TextEdit;
@@ -35,6 +40,10 @@ pub(crate) fn prepare_rename(
     let source_file = sema.parse(position.file_id);
     let syntax = source_file.syntax();
 
+    if let Some((_, range, _)) = find_cfg_feature(&sema, syntax, position.offset) {
+        return Ok(RangeInfo::new(range, ()));
+    }
+
     let res = find_definitions(&sema, syntax, position)?
         .map(|(frange, kind, def)| {
             // ensure all ranges are valid
@@ -89,6 +98,10 @@ pub(crate) fn rename(
     let source_file = sema.parse(position.file_id);
     let syntax = source_file.syntax();
 
+    if let Some((name, _, krate)) = find_cfg_feature(&sema, syntax, position.offset) {
+        return rename_cfg_feature(&sema, krate, &name, new_name);
+    }
+
     let defs = find_definitions(&sema, syntax, position)?;
 
     let ops: RenameResult<Vec<SourceChange>> = defs
@@ -130,6 +143,55 @@ pub(crate) fn will_rename_file(
     Some(change)
 }
 
+/// The feature of the `feature = "name"` predicate at `offset`, with the range of its name.
+fn find_cfg_feature(
+    sema: &Semantics<'_, RootDatabase>,
+    syntax: &SyntaxNode,
+    offset: TextSize,
+) -> Option<(String, TextRange, hir::Crate)> {
+    let token = syntax.token_at_offset(offset).find(|it| it.kind() == SyntaxKind::STRING)?;
+    let name = cfg_feature_name(sema, &token)?;
+    let range = ast::String::cast(token.clone())?.text_range_between_quotes()?;
+    let krate = sema.scope(&token.parent()?)?.krate();
+    Some((name, range, krate))
+}
+
+/// Renames a feature in the `cfg` predicates of its package and in the `Cargo.toml`.
+fn rename_cfg_feature(
+    sema: &Semantics<'_, RootDatabase>,
+    krate: hir::Crate,
+    name: &str,
+    new_name: &str,
+) -> RenameResult<SourceChange> {
+    if !is_valid_feature_name(new_name) {
+        bail!("Invalid name `{}`: not a feature name", new_name);
+    }
+    let mut ranges = cfg_feature_references(sema, krate, name);
+    if let Some(features) = cargo_features(sema.db, krate) {
+        let file_id = features.file_id;
+        ranges.extend(
+            features
+                .declarations
+                .into_iter()
+                .chain(features.references)
+                .filter(|(it, _)| it == name)
+                .map(|(_, range)| (file_id, range)),
+        );
+    }
+    Ok(ranges
+        .into_iter()
+        .into_group_map()
+        .into_iter()
+        .map(|(file_id, ranges)| {
+            let mut builder = TextEdit::builder();
+            for range in ranges {
+                builder.replace(range, new_name.to_owned());
+            }
+            (file_id, builder.finish())
+        })
+        .collect())
+}
+
 fn find_definitions(
     sema: &Semantics<'_, RootDatabase>,
     syntax: &SyntaxNode,
@@ -2686,4 +2748,107 @@ fn test() {
 "#,
         );
     }
+
+    #[test]
+    fn implicit_format_args_through_macros() {
+        check(
+            "bar",
+            r#"
+//- minicore: fmt
+macro_rules! log {
+    ($lvl:expr, $($arg:tt)+) => { format_args!($($arg)+) };
+}
+macro_rules! info {
+    ($($arg:tt)+) => { log!(1, $($arg)+) };
+}
+fn test() {
+    let foo = "foo";
+    info!("hello {foo$0}");
+}
+"#,
+            r#"
+macro_rules! log {
+    ($lvl:expr, $($arg:tt)+) => { format_args!($($arg)+) };
+}
+macro_rules! info {
+    ($($arg:tt)+) => { log!(1, $($arg)+) };
+}
+fn test() {
+    let bar = "foo";
+    info!("hello {bar}");
+}
+"#,
+        );
+    }
+
+    #[test]
+    fn rename_cfg_feature() {
+        check_prepare(
+            r#"
+//- /main.rs crate:main
+#[cfg(feature = "st$0d")]
+fn f() {}
+"#,
+            expect![[r#"17..20: std"#]],
+        );
+        check_expect(
+            "no-std",
+            r#"
+//- /src/lib.rs crate:foo
+#[cfg(feature = "st$0d")]
+fn f() -> bool {
+    cfg!(not(feature = "std"))
+}
+#[doc(feature = "std")]
+struct S;
+//- /Cargo.toml
+[features]
+default = ["std"]
+std = []
+"#,
+            expect![[r#"
+                source_file_edits: [
+                    (
+                        FileId(
+                            0,
+                        ),
+                        [
+                            Indel {
+                                insert: "no-std",
+                                delete: 17..20,
+                            },
+                            Indel {
+                                insert: "no-std",
+                                delete: 65..68,
+                            },
+                        ],
+                    ),
+                    (
+                        FileId(
+                            1,
+                        ),
+                        [
+                            Indel {
+                                insert: "no-std",
+                                delete: 23..26,
+                            },
+                            Indel {
+                                insert: "no-std",
+                                delete: 29..32,
+                            },
+                        ],
+                    ),
+                ]
+                file_system_edits: []
+            "#]],
+        );
+        check(
+            "no std",
+            r#"
+#[cfg(feature = "st$0d")]
+fn f() {}
+"#,
+            "error: Invalid name `no std`: not a feature name",
+        );
+    }
 }
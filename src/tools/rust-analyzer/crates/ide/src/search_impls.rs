@@ -0,0 +1,160 @@
+use hir::Semantics;
+use ide_db::{
+    base_db::FilePosition,
+    impl_search::{ImplPattern, ImplPatternError},
+    RootDatabase,
+};
+use syntax::AstNode;
+
+use crate::{NavigationTarget, TryToNav};
+
+// Feature: Search Impls
+//
+// Finds the impls of a trait matching a pattern like `From<_> for MyError` or
+// `Iterator<Item = String>`, across the crates that can see the trait. The paths of the pattern are
+// resolved at the cursor, and `_` matches any type.
+pub(crate) fn search_impls(
+    db: &RootDatabase,
+    position: FilePosition,
+    pattern: &str,
+) -> Result<Vec<NavigationTarget>, ImplPatternError> {
+    let sema = Semantics::new(db);
+    let file = sema.parse(position.file_id);
+    let node = file
+        .syntax()
+        .token_at_offset(position.offset)
+        .left_biased()
+        .and_then(|token| token.parent())
+        .unwrap_or_else(|| file.syntax().clone());
+    let Some(scope) = sema.scope(&node) else { return Ok(Vec::new()) };
+    let pattern = ImplPattern::parse(&scope, pattern)?;
+    Ok(pattern.search(db).into_iter().filter_map(|it| it.try_to_nav(db)).flatten().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(pattern: &str, ra_fixture: &str, expect: Expect) {
+        let (analysis, position) = fixture::position(ra_fixture);
+        let actual = match analysis.search_impls(position, pattern).unwrap() {
+            Ok(navs) => {
+                let mut navs: Vec<_> = navs
+                    .into_iter()
+                    .map(|nav| {
+                        let text = analysis.file_text(nav.file_id).unwrap();
+                        text[nav.full_range].lines().next().unwrap_or_default().to_owned()
+                    })
+                    .collect();
+                navs.sort();
+                navs.join("\n") + "\n"
+            }
+            Err(err) => format!("{err}\n"),
+        };
+        expect.assert_eq(&actual);
+    }
+
+    const FIXTURE: &str = r#"
+//- minicore: from, iterator
+struct MyError;
+struct Other;
+struct Io;
+impl From<Io> for MyError { fn from(_: Io) -> Self { MyError } }
+impl From<u32> for MyError { fn from(_: u32) -> Self { MyError } }
+impl From<Io> for Other { fn from(_: Io) -> Self { Other } }
+impl<'a> From<&'a str> for Other { fn from(_: &'a str) -> Self { Other } }
+struct Words;
+impl Iterator for Words {
+    type Item = &'static str;
+    fn next(&mut self) -> Option<&'static str> { None }
+}
+struct Numbers;
+impl Iterator for Numbers {
+    type Item = u32;
+    fn next(&mut self) -> Option<u32> { None }
+}
+struct Wrapper<T>(T);
+impl<T> Iterator for Wrapper<T> {
+    type Item = T;
+    fn next(&mut self) -> Option<T> { None }
+}
+fn main() { $0 }
+"#;
+
+    #[test]
+    fn trait_args_and_self_type() {
+        check(
+            "From<_> for MyError",
+            FIXTURE,
+            expect![[r#"
+                impl From<Io> for MyError { fn from(_: Io) -> Self { MyError } }
+                impl From<u32> for MyError { fn from(_: u32) -> Self { MyError } }
+                impl<T> From<T> for T {
+            "#]],
+        );
+        check(
+            "From<Io>",
+            FIXTURE,
+            expect![[r#"
+                impl From<Io> for MyError { fn from(_: Io) -> Self { MyError } }
+                impl From<Io> for Other { fn from(_: Io) -> Self { Other } }
+                impl<T> From<T> for T {
+            "#]],
+        );
+        check(
+            "From<&str> for Other",
+            FIXTURE,
+            expect![[r#"
+                impl<'a> From<&'a str> for Other { fn from(_: &'a str) -> Self { Other } }
+                impl<T> From<T> for T {
+            "#]],
+        );
+    }
+
+    #[test]
+    fn assoc_types() {
+        check(
+            "Iterator<Item = u32>",
+            FIXTURE,
+            expect![[r#"
+                impl Iterator for Numbers {
+                impl<T, const N: usize> Iterator for IntoIter<T, N> {
+                impl<T> Iterator for Wrapper<T> {
+            "#]],
+        );
+        check(
+            "Iterator<Item = u32> for Wrapper<u8>",
+            FIXTURE,
+            expect![[r#"
+                impl<T> Iterator for Wrapper<T> {
+            "#]],
+        );
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        check(
+            "Missing<_>",
+            FIXTURE,
+            expect![[r#"
+                Invalid impl pattern: failed to resolve `Missing<_>` to a trait
+            "#]],
+        );
+        check(
+            "From<_> for Nope",
+            FIXTURE,
+            expect![[r#"
+                Invalid impl pattern: failed to resolve `Nope` to a type
+            "#]],
+        );
+        check(
+            "From<",
+            FIXTURE,
+            expect![[r#"
+                Invalid impl pattern: failed to parse `From<`: expected R_ANGLE
+            "#]],
+        );
+    }
+}
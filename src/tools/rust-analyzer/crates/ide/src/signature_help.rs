@@ -12,7 +12,7 @@
     active_parameter::{callable_for_node, generic_def_for_node},
     base_db::FilePosition,
     documentation::{Documentation, HasDocs},
-    FxIndexMap,
+    FxHashMap, FxIndexMap,
 };
 use stdx::format_to;
 use syntax::{
@@ -141,10 +141,17 @@ pub(crate) fn signature_help(
         }
 
         // Stop at multi-line expressions, since the signature of the outer call is not very
-        // helpful inside them.
+        // helpful inside them. Method chains are the exception, builder chains are commonly
+        // split over several lines inside a call.
         if let Some(expr) = ast::Expr::cast(node.clone()) {
-            if !matches!(expr, ast::Expr::RecordExpr(..))
-                && expr.syntax().text().contains_char('\n')
+            if !matches!(
+                expr,
+                ast::Expr::RecordExpr(..)
+                    | ast::Expr::MethodCallExpr(..)
+                    | ast::Expr::FieldExpr(..)
+                    | ast::Expr::AwaitExpr(..)
+                    | ast::Expr::TryExpr(..)
+            ) && expr.syntax().text().contains_char('\n')
             {
                 break;
             }
@@ -183,6 +190,19 @@ fn signature_help_for_call(
         hir::CallableKind::Function(func) => {
             res.doc = func.docs(db);
             format_to!(res.signature, "fn {}", func.name(db).display(db));
+            let generic_params: Vec<_> = hir::GenericDef::Function(func)
+                .params(db)
+                .into_iter()
+                .filter_map(|param| match param {
+                    GenericParam::TypeParam(it) if it.is_implicit(db) => None,
+                    GenericParam::TypeParam(it) => Some(it.name(db).display(db).to_string()),
+                    GenericParam::LifetimeParam(it) => Some(it.name(db).display(db).to_string()),
+                    GenericParam::ConstParam(it) => Some(it.display(db).to_string()),
+                })
+                .collect();
+            if !generic_params.is_empty() {
+                format_to!(res.signature, "<{}>", generic_params.join(", "));
+            }
             fn_params = Some(match callable.receiver_param(db) {
                 Some(_self) => func.params_without_self(db),
                 None => func.assoc_fn_params(db),
@@ -204,6 +224,11 @@ fn signature_help_for_call(
         hir::CallableKind::Closure | hir::CallableKind::FnPtr | hir::CallableKind::Other => (),
     }
 
+    // The generic parameters (including `Self`) whose arguments are known at the call site, used
+    // to instantiate the declared types we fall back to.
+    let known_args: FxHashMap<_, _> =
+        callable.type_param_args(db).into_iter().filter(|(_, ty)| !ty.contains_unknown()).collect();
+
     res.signature.push('(');
     {
         if let Some((self_param, _)) = callable.receiver_param(db) {
@@ -220,11 +245,16 @@ fn signature_help_for_call(
             }
             // APITs (argument position `impl Trait`s) are inferred as {unknown} as the user is
             // in the middle of entering call arguments.
-            // In that case, fall back to render definitions of the respective parameters.
-            // This is overly conservative: we do not substitute known type vars
-            // (see FIXME in tests::impl_trait) and falling back on any unknowns.
+            // In that case, fall back to render definitions of the respective parameters, with
+            // the generic parameters that are already known substituted.
+            // This is overly conservative: we do not substitute known type vars in the bounds of
+            // APITs (see FIXME in tests::impl_trait) and falling back on any unknowns.
             match (ty.contains_unknown(), fn_params.as_deref()) {
-                (true, Some(fn_params)) => format_to!(buf, "{}", fn_params[idx].ty().display(db)),
+                (true, Some(fn_params)) => format_to!(
+                    buf,
+                    "{}",
+                    fn_params[idx].ty().substitute_type_params(db, &known_args).display(db)
+                ),
                 _ => format_to!(buf, "{}", ty.display(db)),
             }
             res.push_call_param(&buf);
@@ -239,7 +269,7 @@ fn signature_help_for_call(
     };
     match callable.kind() {
         hir::CallableKind::Function(func) if callable.return_type().contains_unknown() => {
-            render(func.ret_type(db))
+            render(func.ret_type(db).substitute_type_params(db, &known_args))
         }
         hir::CallableKind::Function(_)
         | hir::CallableKind::Closure
@@ -774,8 +804,8 @@ fn foo<T, U: Copy + Display>(x: T, y: U) -> u32
 fn bar() { foo($03, ); }
 "#,
             expect![[r#"
-                fn foo(x: i32, y: U) -> u32
-                       ^^^^^^  ----
+                fn foo<T, U>(x: i32, y: U) -> u32
+                             ^^^^^^  ----
             "#]],
         );
     }
@@ -788,7 +818,7 @@ fn foo<T>() -> T where T: Copy + Display {}
 fn bar() { foo($0); }
 "#,
             expect![[r#"
-                fn foo() -> T
+                fn foo<T>() -> T
             "#]],
         );
     }
@@ -1334,7 +1364,7 @@ fn foo<'a>(&'a mut self) {}
 fn test() { S.foo($0); }
 "#,
             expect![[r#"
-                fn foo(&'a mut self)
+                fn foo<'a>(&'a mut self)
             "#]],
         );
     }
@@ -1586,8 +1616,8 @@ fn sup() {
 }
 "#,
             expect![[r#"
-                fn test(&mut self, val: V)
-                                   ^^^^^^
+                fn test<V>(&mut self, val: V)
+                                      ^^^^^^
             "#]],
         );
     }
@@ -1763,8 +1793,107 @@ fn f() {
 }
 "#,
             expect![[r#"
-                fn foo(x: Wrap<impl Trait<U>>)
-                       ^^^^^^^^^^^^^^^^^^^^^^
+                fn foo<U>(x: Wrap<impl Trait<U>>)
+                          ^^^^^^^^^^^^^^^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn substitutes_known_generic_args_in_declared_types() {
+        check(
+            r#"
+struct Pair<A, B>(A, B);
+impl<A, B> Pair<A, B> {
+    fn set_second<C>(self, a: A, c: C, pair: (Self, C)) -> Pair<A, C> { loop {} }
+}
+fn f() {
+    Pair(1u8, 2u16).set_second($0)
+}
+"#,
+            expect![[r#"
+                fn set_second<C>(self, a: u8, c: C, pair: (Pair<u8, u16>, C)) -> Pair<u8, C>
+                                       ^^^^^  ----  ------------------------
+            "#]],
+        );
+        check(
+            r#"
+trait Merge<T> {
+    fn merge(&self, with: (Self, T)) where Self: Sized;
+}
+struct Foo;
+impl Merge<u8> for Foo { fn merge(&self, with: (Self, u8)) {} }
+impl Merge<u16> for Foo { fn merge(&self, with: (Self, u16)) {} }
+fn f() {
+    Foo.merge($0)
+}
+"#,
+            expect![[r#"
+                fn merge(&self, with: (Foo, T))
+                                ^^^^^^^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn const_generic_params_in_call() {
+        check(
+            r#"
+fn zeroed<T, const N: usize>(fill: T) -> [T; N] { loop {} }
+fn f() {
+    zeroed::<u8, 3>($0)
+}
+"#,
+            expect![[r#"
+                fn zeroed<T, const N: usize>(fill: u8) -> [u8; 3]
+                                             ^^^^^^^^
+            "#]],
+        );
+    }
+
+    #[test]
+    fn builder_chain_argument() {
+        check(
+            r#"
+struct Builder;
+impl Builder {
+    fn new() -> Self { Builder }
+    fn name(self, name: u8) -> Self { self }
+}
+fn callee(id: u8, builder: Builder) {}
+fn f() {
+    callee(
+        1,
+        Builder::new()
+            .name(2)$0
+    );
+}
+"#,
+            expect![[r#"
+                fn callee(id: u8, builder: Builder)
+                          ------  ^^^^^^^^^^^^^^^^
+            "#]],
+        );
+        check(
+            r#"
+struct Builder;
+impl Builder {
+    fn new() -> Self { Builder }
+    fn name(self, name: u8) -> Self { self }
+    fn size(self, width: u8, height: u8) -> Self { self }
+}
+fn f() {
+    Builder::new()
+        .name(1)
+        .size(
+            1,
+            $0
+        );
+}
+"#,
+            expect![[r#"
+                fn size(self, width: u8, height: u8) -> Builder
+                              ---------  ^^^^^^^^^^
             "#]],
         );
     }
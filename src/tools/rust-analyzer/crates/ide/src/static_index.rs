@@ -1,8 +1,11 @@
 //! This module provides `StaticIndex` which is used for powering
 //! read-only code browsers and emitting LSIF
 
-use hir::{db::HirDatabase, Crate, HirFileIdExt, Module, Semantics};
+use std::mem::discriminant;
+
+use hir::{db::HirDatabase, AsAssocItem, Crate, HirFileIdExt, Module, Semantics};
 use ide_db::{
+    assists::AssistResolveStrategy,
     base_db::{FileId, FileRange, SourceDatabaseExt},
     defs::Definition,
     documentation::Documentation,
@@ -10,7 +13,9 @@
     helpers::get_definition,
     FxHashMap, FxHashSet, RootDatabase,
 };
-use syntax::{AstNode, SyntaxKind::*, SyntaxNode, TextRange, T};
+use ide_diagnostics::{Diagnostic, DiagnosticsConfig};
+use itertools::Itertools;
+use syntax::{AstNode, SyntaxKind::*, SyntaxNode, SyntaxToken, TextRange, T};
 
 use crate::inlay_hints::InlayFieldsToResolve;
 use crate::navigation_target::UpmappingResult;
@@ -34,6 +39,16 @@ pub struct StaticIndex<'a> {
     def_map: FxHashMap<Definition, TokenId>,
 }
 
+/// What [`StaticIndex::compute`] indexes.
+#[derive(Debug, Clone, Default)]
+pub struct StaticIndexConfig {
+    /// Only index these files, instead of all files of the workspace crates. Definitions in other
+    /// files are still referred to, but their references are not collected.
+    pub files: Option<FxHashSet<FileId>>,
+    /// Compute the diagnostics of the indexed files.
+    pub diagnostics: bool,
+}
+
 #[derive(Debug)]
 pub struct ReferenceData {
     pub range: FileRange,
@@ -51,6 +66,9 @@ pub struct TokenStaticData {
     pub enclosing_moniker: Option<MonikerResult>,
     pub signature: Option<String>,
     pub kind: SymbolInformationKind,
+    /// The traits a type implements, or the trait items an associated item of a trait impl
+    /// implements.
+    pub implements: Vec<TokenId>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -91,6 +109,8 @@ pub struct StaticIndexedFile {
     pub folds: Vec<Fold>,
     pub inlay_hints: Vec<InlayHint>,
     pub tokens: Vec<(TextRange, TokenId)>,
+    /// Empty unless [`StaticIndexConfig::diagnostics`] is set.
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 fn all_modules(db: &dyn HirDatabase) -> Vec<Module> {
@@ -119,8 +139,36 @@ fn documentation_for_definition(
     def.docs(sema.db, famous_defs.as_ref())
 }
 
+/// The traits, or trait items, that `def` implements.
+fn implemented_definitions(db: &RootDatabase, def: Definition) -> Vec<Definition> {
+    match def {
+        Definition::Adt(adt) => hir::Impl::all_for_type(db, adt.ty(db))
+            .into_iter()
+            .filter_map(|impl_| impl_.trait_(db))
+            .unique()
+            .map(Definition::Trait)
+            .collect(),
+        Definition::Function(_) | Definition::Const(_) | Definition::TypeAlias(_) => {
+            let Some(assoc) = def.as_assoc_item(db) else { return Vec::new() };
+            let Some(trait_) = assoc.implemented_trait(db) else { return Vec::new() };
+            let name = def.name(db);
+            trait_
+                .items(db)
+                .into_iter()
+                .find(|it| {
+                    discriminant(it) == discriminant(&assoc)
+                        && Definition::from(*it).name(db) == name
+                })
+                .map(Definition::from)
+                .into_iter()
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
 impl StaticIndex<'_> {
-    fn add_file(&mut self, file_id: FileId) {
+    fn add_file(&mut self, file_id: FileId, config: &StaticIndexConfig) {
         let current_crate = crates_for(self.db, file_id).pop().map(Into::into);
         let folds = self.analysis.folding_ranges(file_id).unwrap();
         let inlay_hints = self
@@ -148,11 +196,27 @@ fn add_file(&mut self, file_id: FileId) {
                     closing_brace_hints_min_lines: Some(25),
                     fields_to_resolve: InlayFieldsToResolve::empty(),
                     range_exclusive_hints: false,
+                    conversion_hints: false,
+                    generic_arg_hints: false,
+                    generic_arg_hints_functions: Vec::new(),
+                    macro_expansion_hints: false,
+                    macro_expansion_hints_macros: Vec::new(),
                 },
                 file_id,
                 None,
             )
             .unwrap();
+        let diagnostics = if config.diagnostics {
+            self.analysis
+                .diagnostics(
+                    &DiagnosticsConfig::test_sample(),
+                    AssistResolveStrategy::None,
+                    file_id,
+                )
+                .unwrap()
+        } else {
+            Vec::new()
+        };
         // hovers
         let sema = hir::Semantics::new(self.db);
         let tokens_or_nodes = sema.parse(file_id).syntax().clone();
@@ -160,48 +224,21 @@ fn add_file(&mut self, file_id: FileId) {
             syntax::NodeOrToken::Node(_) => None,
             syntax::NodeOrToken::Token(it) => Some(it),
         });
-        let hover_config = HoverConfig {
-            links_in_hover: true,
-            memory_layout: None,
-            documentation: true,
-            keywords: true,
-            format: crate::HoverDocFormat::Markdown,
-        };
         let tokens = tokens.filter(|token| {
             matches!(
                 token.kind(),
                 IDENT | INT_NUMBER | LIFETIME_IDENT | T![self] | T![super] | T![crate] | T![Self]
             )
         });
-        let mut result = StaticIndexedFile { file_id, inlay_hints, folds, tokens: vec![] };
+        let mut result =
+            StaticIndexedFile { file_id, inlay_hints, folds, tokens: vec![], diagnostics };
         for token in tokens {
             let range = token.text_range();
-            let node = token.parent().unwrap();
             let def = match get_definition(&sema, token.clone()) {
                 Some(it) => it,
                 None => continue,
             };
-            let id = if let Some(it) = self.def_map.get(&def) {
-                *it
-            } else {
-                let it = self.tokens.insert(TokenStaticData {
-                    documentation: documentation_for_definition(&sema, def, &node),
-                    hover: hover_for_definition(&sema, file_id, def, &node, &hover_config),
-                    definition: def.try_to_nav(self.db).map(UpmappingResult::call_site).map(|it| {
-                        FileRange { file_id: it.file_id, range: it.focus_or_full_range() }
-                    }),
-                    references: vec![],
-                    moniker: current_crate.and_then(|cc| def_to_moniker(self.db, def, cc)),
-                    display_name: def.name(self.db).map(|name| name.display(self.db).to_string()),
-                    enclosing_moniker: current_crate
-                        .zip(def.enclosing_definition(self.db))
-                        .and_then(|(cc, enclosing_def)| def_to_moniker(self.db, enclosing_def, cc)),
-                    signature: def.label(self.db),
-                    kind: def_to_kind(self.db, def),
-                });
-                self.def_map.insert(def, it);
-                it
-            };
+            let id = self.token_for_definition(&sema, file_id, current_crate, def, &token);
             let token = self.tokens.get_mut(id).unwrap();
             token.references.push(ReferenceData {
                 range: FileRange { range, file_id },
@@ -215,13 +252,62 @@ fn add_file(&mut self, file_id: FileId) {
         self.files.push(result);
     }
 
-    pub fn compute(analysis: &Analysis) -> StaticIndex<'_> {
+    fn token_for_definition(
+        &mut self,
+        sema: &Semantics<'_, RootDatabase>,
+        file_id: FileId,
+        current_crate: Option<Crate>,
+        def: Definition,
+        token: &SyntaxToken,
+    ) -> TokenId {
+        if let Some(it) = self.def_map.get(&def) {
+            return *it;
+        }
+        let hover_config = HoverConfig {
+            links_in_hover: true,
+            memory_layout: None,
+            documentation: true,
+            keywords: true,
+            format: crate::HoverDocFormat::Markdown,
+        };
+        let node = token.parent().unwrap();
+        let id = self.tokens.insert(TokenStaticData {
+            documentation: documentation_for_definition(sema, def, &node),
+            hover: hover_for_definition(sema, file_id, def, &node, &hover_config),
+            definition: def
+                .try_to_nav(self.db)
+                .map(UpmappingResult::call_site)
+                .map(|it| FileRange { file_id: it.file_id, range: it.focus_or_full_range() }),
+            references: vec![],
+            moniker: current_crate.and_then(|cc| def_to_moniker(self.db, def, cc)),
+            display_name: def.name(self.db).map(|name| name.display(self.db).to_string()),
+            enclosing_moniker: current_crate
+                .zip(def.enclosing_definition(self.db))
+                .and_then(|(cc, enclosing_def)| def_to_moniker(self.db, enclosing_def, cc)),
+            signature: def.label(self.db),
+            kind: def_to_kind(self.db, def),
+            implements: vec![],
+        });
+        self.def_map.insert(def, id);
+
+        // traits and trait items don't implement anything themselves, so this doesn't recurse
+        // any further
+        for implemented in implemented_definitions(self.db, def) {
+            let implemented =
+                self.token_for_definition(sema, file_id, current_crate, implemented, token);
+            self.tokens.get_mut(id).unwrap().implements.push(implemented);
+        }
+        id
+    }
+
+    pub fn compute<'a>(analysis: &'a Analysis, config: &StaticIndexConfig) -> StaticIndex<'a> {
         let db = &*analysis.db;
         let work = all_modules(db).into_iter().filter(|module| {
             let file_id = module.definition_source_file_id(db).original_file(db);
             let source_root = db.file_source_root(file_id);
             let source_root = db.source_root(source_root);
             !source_root.is_library
+                && config.files.as_ref().map_or(true, |files| files.contains(&file_id))
         });
         let mut this = StaticIndex {
             files: vec![],
@@ -236,7 +322,7 @@ pub fn compute(analysis: &Analysis) -> StaticIndex<'_> {
             if visited_files.contains(&file_id) {
                 continue;
             }
-            this.add_file(file_id);
+            this.add_file(file_id, config);
             // mark the file
             visited_files.insert(file_id);
         }
@@ -246,13 +332,13 @@ pub fn compute(analysis: &Analysis) -> StaticIndex<'_> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{fixture, StaticIndex};
+    use crate::{fixture, StaticIndex, StaticIndexConfig};
     use ide_db::{base_db::FileRange, FxHashSet};
     use syntax::TextSize;
 
     fn check_all_ranges(ra_fixture: &str) {
         let (analysis, ranges) = fixture::annotations_without_marker(ra_fixture);
-        let s = StaticIndex::compute(&analysis);
+        let s = StaticIndex::compute(&analysis, &StaticIndexConfig::default());
         let mut range_set: FxHashSet<_> = ranges.iter().map(|it| it.0).collect();
         for f in s.files {
             for (range, _) in f.tokens {
@@ -271,7 +357,7 @@ fn check_all_ranges(ra_fixture: &str) {
     #[track_caller]
     fn check_definitions(ra_fixture: &str) {
         let (analysis, ranges) = fixture::annotations_without_marker(ra_fixture);
-        let s = StaticIndex::compute(&analysis);
+        let s = StaticIndex::compute(&analysis, &StaticIndexConfig::default());
         let mut range_set: FxHashSet<_> = ranges.iter().map(|it| it.0).collect();
         for (_, t) in s.tokens.iter() {
             if let Some(t) = t.definition {
@@ -348,4 +434,49 @@ fn derives() {
 "#,
         );
     }
+
+    #[test]
+    fn implementations() {
+        let (analysis, _) = fixture::file(
+            r#"
+trait Tr { fn f(&self); }
+struct S;
+impl Tr for S { fn f(&self) {} }
+"#,
+        );
+        let s = StaticIndex::compute(&analysis, &StaticIndexConfig::default());
+        let tokens: Vec<_> = s.tokens.iter().collect();
+        let name =
+            |id| tokens.iter().find(|(it, _)| *it == id).unwrap().1.display_name.clone().unwrap();
+        let mut implements: Vec<_> = tokens
+            .iter()
+            .flat_map(|(_, token)| {
+                token
+                    .implements
+                    .iter()
+                    .map(|&it| format!("{} -> {}", token.display_name.clone().unwrap(), name(it)))
+            })
+            .collect();
+        implements.sort();
+        assert_eq!(implements, ["S -> Tr", "f -> f"]);
+    }
+
+    #[test]
+    fn diagnostics_of_indexed_files() {
+        let (analysis, file_id) = fixture::file(
+            r#"
+//- /main.rs crate:main
+mod foo;
+fn main() { let x: i32 = ""; }
+//- /foo.rs
+fn bar() { let x: i32 = ""; }
+"#,
+        );
+        let config =
+            StaticIndexConfig { files: Some(FxHashSet::from_iter([file_id])), diagnostics: true };
+        let s = StaticIndex::compute(&analysis, &config);
+        assert_eq!(s.files.len(), 1);
+        let codes: Vec<_> = s.files[0].diagnostics.iter().map(|it| it.code.as_str()).collect();
+        assert_eq!(codes, ["E0308"]);
+    }
 }
@@ -2,7 +2,7 @@
 
 use hir::{
     db::{AstIdMapQuery, AttrsQuery, BlockDefMapQuery, ParseMacroExpansionQuery},
-    Attr, Attrs, ExpandResult, MacroFileId, Module,
+    Attr, Attrs, Crate, ExpandResult, MacroFileId, Module,
 };
 use ide_db::{
     base_db::{
@@ -12,7 +12,8 @@
         },
         CrateData, FileId, FileTextQuery, ParseQuery, SourceDatabase, SourceRootId,
     },
-    symbol_index::ModuleSymbolsQuery,
+    query_stats::{self, QueryStat},
+    symbol_index::{CrateSymbolShardQuery, ModuleSymbolsQuery},
 };
 use ide_db::{
     symbol_index::{LibrarySymbolsQuery, SymbolIndex},
@@ -43,10 +44,14 @@ pub(crate) fn status(db: &RootDatabase, file_id: Option<FileId>) -> String {
     format_to!(buf, "{}\n", collect_query(ParseMacroExpansionQuery.in_db(db)));
     format_to!(buf, "{}\n", collect_query(LibrarySymbolsQuery.in_db(db)));
     format_to!(buf, "{}\n", collect_query(ModuleSymbolsQuery.in_db(db)));
+    format_to!(buf, "{}\n", collect_query(CrateSymbolShardQuery.in_db(db)));
     format_to!(buf, "{} in total\n", memory_usage());
     if env::var("RA_COUNT").is_ok() {
         format_to!(buf, "\nCounts:\n{}", profile::countme::get_all());
     }
+    if let Some(stats) = query_stats::current() {
+        format_query_stats(&mut buf, stats.queries);
+    }
 
     format_to!(buf, "\nDebug info:\n");
     format_to!(buf, "{}\n", collect_query(AttrsQuery.in_db(db)));
@@ -100,6 +105,24 @@ pub(crate) fn status(db: &RootDatabase, file_id: Option<FileId>) -> String {
     buf.trim().to_owned()
 }
 
+/// Shows the queries that took the most time and were executed the most since the last edit, as
+/// collected when rust-analyzer runs with `RA_QUERY_STATS=1`.
+fn format_query_stats(buf: &mut String, mut queries: Vec<QueryStat>) {
+    const TOP_N: usize = 10;
+
+    format_to!(buf, "\nSlowest queries since the last edit:\n");
+    queries.sort_by(|a, b| b.total_time.cmp(&a.total_time).then_with(|| a.name.cmp(&b.name)));
+    for query in queries.iter().take(TOP_N) {
+        format_to!(buf, "{:>10.2?} {:>8} {}\n", query.total_time, query.executions, query.name);
+    }
+
+    format_to!(buf, "\nMost executed queries since the last edit:\n");
+    queries.sort_by(|a, b| b.executions.cmp(&a.executions).then_with(|| a.name.cmp(&b.name)));
+    for query in queries.iter().take(TOP_N) {
+        format_to!(buf, "{:>10.2?} {:>8} {}\n", query.total_time, query.executions, query.name);
+    }
+}
+
 fn collect_query<'q, Q>(table: QueryTable<'q, Q>) -> <Q as QueryCollect>::Collector
 where
     QueryTable<'q, Q>: DebugQueryTable,
@@ -168,6 +191,10 @@ impl QueryCollect for ModuleSymbolsQuery {
     type Collector = SymbolsStats<Module>;
 }
 
+impl QueryCollect for CrateSymbolShardQuery {
+    type Collector = SymbolsStats<(Crate, usize)>;
+}
+
 impl QueryCollect for AttrsQuery {
     type Collector = AttrsStats;
 }
@@ -248,6 +275,11 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{} of module index symbols ({})", self.size, self.total)
     }
 }
+impl fmt::Display for SymbolsStats<(Crate, usize)> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{} of crate index shards ({} symbols)", self.size, self.total)
+    }
+}
 impl fmt::Display for SymbolsStats<SourceRootId> {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(fmt, "{} of library index symbols ({})", self.size, self.total)
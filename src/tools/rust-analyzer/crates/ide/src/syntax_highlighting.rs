@@ -214,6 +214,28 @@ pub(crate) fn highlight(
     hl.to_vec()
 }
 
+/// If `edit` lies within the body of a function, returns the range of the outermost such function.
+///
+/// Edits inside a function body don't change how the code around the function is highlighted, so
+/// after such an edit only that function has to be highlighted again, see
+/// [`crate::Analysis::highlight_range`]. Functions returning `impl Trait` are an exception, as
+/// their bodies determine the types seen by their callers, and so are bodies containing impls.
+pub fn edited_fn_range(file: &ast::SourceFile, edit: TextRange) -> Option<TextRange> {
+    let element = file.syntax().covering_element(edit);
+    let func = element.ancestors().filter_map(ast::Fn::cast).last()?;
+    let stmt_list = func.body()?.stmt_list()?;
+    let inner = TextRange::new(
+        stmt_list.l_curly_token()?.text_range().end(),
+        stmt_list.r_curly_token()?.text_range().start(),
+    );
+    let returns_impl_trait = func.ret_type().and_then(|it| it.ty()).map_or(false, |ty| {
+        ty.syntax().descendants().any(|it| ast::ImplTraitType::can_cast(it.kind()))
+    });
+    let contains_impls = stmt_list.syntax().descendants().any(|it| ast::Impl::can_cast(it.kind()));
+    (inner.contains_range(edit) && !returns_impl_trait && !contains_impls)
+        .then(|| func.syntax().text_range())
+}
+
 fn traverse(
     hl: &mut Highlights,
     sema: &Semantics<'_, RootDatabase>,
@@ -0,0 +1,100 @@
+use ide_db::{
+    base_db::{FileId, SourceDatabase, SourceDatabaseExt},
+    symbol_index::SymbolsDatabase,
+    todo_comments::{todo_comments, TodoComment},
+    RootDatabase,
+};
+
+#[derive(Debug)]
+pub struct TodoItem {
+    pub file_id: FileId,
+    pub comment: TodoComment,
+}
+
+// Feature: TODO Index
+//
+// Lists the `TODO`, `FIXME` and `HACK` comments of a file, or of all Rust files of the workspace,
+// together with the owner and date of their `(owner, YYYY-MM-DD)` tag.
+pub(crate) fn todo_index(db: &RootDatabase, file_id: Option<FileId>) -> Vec<TodoItem> {
+    let files = match file_id {
+        Some(file_id) => vec![file_id],
+        None => workspace_files(db),
+    };
+    files
+        .into_iter()
+        .flat_map(|file_id| {
+            todo_comments(&db.parse(file_id).tree())
+                .into_iter()
+                .map(move |comment| TodoItem { file_id, comment })
+        })
+        .collect()
+}
+
+/// The Rust files of the local source roots, ordered by path.
+fn workspace_files(db: &RootDatabase) -> Vec<FileId> {
+    let mut files = Vec::new();
+    for &root in db.local_roots().iter() {
+        let source_root = db.source_root(root);
+        files.extend(source_root.iter().filter_map(|file_id| {
+            let path = source_root.path_for_file(&file_id)?;
+            let (_, ext) = path.name_and_extension()?;
+            (ext == Some("rs")).then(|| (path.to_string(), file_id))
+        }));
+    }
+    files.sort();
+    files.into_iter().map(|(_, file_id)| file_id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, whole_workspace: bool, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let items = analysis.todo_index(Some(file_id).filter(|_| !whole_workspace)).unwrap();
+        let actual = items
+            .into_iter()
+            .map(|it| {
+                let text = analysis.file_text(it.file_id).unwrap();
+                format!("{:?} `{}`\n", it.file_id, &text[it.comment.range])
+            })
+            .collect::<String>();
+        expect.assert_eq(&actual);
+    }
+
+    const FIXTURE: &str = r#"
+//- /main.rs
+mod foo;
+// TODO(alice): main
+fn main() {}
+//- /foo.rs
+// FIXME: foo
+//- /notes.txt
+TODO: not rust
+"#;
+
+    #[test]
+    fn file_todos() {
+        check(
+            FIXTURE,
+            false,
+            expect![[r#"
+                FileId(0) `TODO(alice): main`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn workspace_todos() {
+        check(
+            FIXTURE,
+            true,
+            expect![[r#"
+                FileId(1) `FIXME: foo`
+                FileId(0) `TODO(alice): main`
+            "#]],
+        );
+    }
+}
@@ -22,7 +22,7 @@
 use syntax::{
     algo::{ancestors_at_offset, find_node_at_offset},
     ast::{self, edit::IndentLevel, AstToken},
-    AstNode, Parse, SourceFile, SyntaxKind, TextRange, TextSize, T,
+    AstNode, Edition, Parse, SourceFile, SyntaxKind, TextRange, TextSize, T,
 };
 
 use text_edit::{Indel, TextEdit};
@@ -72,7 +72,8 @@ pub(crate) fn on_char_typed(
     if !stdx::always!(file.tree().syntax().text().char_at(position.offset) == Some(char_typed)) {
         return None;
     }
-    let edit = on_char_typed_inner(file, position.offset, char_typed)?;
+    let edition = db.file_edition(position.file_id);
+    let edit = on_char_typed_inner(file, position.offset, char_typed, edition)?;
     let mut sc = SourceChange::from_text_edit(position.file_id, edit.edit);
     sc.is_snippet = edit.is_snippet;
     Some(sc)
@@ -82,6 +83,7 @@ fn on_char_typed_inner(
     file: &Parse<SourceFile>,
     offset: TextSize,
     char_typed: char,
+    edition: Edition,
 ) -> Option<ExtendedTextEdit> {
     if !stdx::always!(TRIGGER_CHARS.contains(char_typed)) {
         return None;
@@ -94,8 +96,8 @@ fn on_char_typed_inner(
         '=' => conv(on_eq_typed(&file.tree(), offset)),
         '<' => on_left_angle_typed(&file.tree(), offset),
         '>' => conv(on_right_angle_typed(&file.tree(), offset)),
-        '{' => conv(on_opening_bracket_typed(file, offset, '{')),
-        '(' => conv(on_opening_bracket_typed(file, offset, '(')),
+        '{' => conv(on_opening_bracket_typed(file, offset, '{', edition)),
+        '(' => conv(on_opening_bracket_typed(file, offset, '(', edition)),
         _ => None,
     }
 }
@@ -106,6 +108,7 @@ fn on_opening_bracket_typed(
     file: &Parse<SourceFile>,
     offset: TextSize,
     opening_bracket: char,
+    edition: Edition,
 ) -> Option<TextEdit> {
     let (closing_bracket, expected_ast_bracket) = match opening_bracket {
         '{' => ('}', SyntaxKind::L_CURLY),
@@ -127,7 +130,7 @@ fn on_opening_bracket_typed(
     if !stdx::always!(range.len() == TextSize::of(opening_bracket)) {
         return None;
     }
-    let file = file.reparse(&Indel::delete(range));
+    let file = file.reparse(&Indel::delete(range), edition);
 
     if let Some(edit) = bracket_expr(&file.tree(), offset, opening_bracket, closing_bracket) {
         return Some(edit);
@@ -400,7 +403,7 @@ fn do_type_char(char_typed: char, before: &str) -> Option<String> {
         let edit = TextEdit::insert(offset, char_typed.to_string());
         edit.apply(&mut before);
         let parse = SourceFile::parse(&before);
-        on_char_typed_inner(&parse, offset, char_typed).map(|it| {
+        on_char_typed_inner(&parse, offset, char_typed, Edition::CURRENT).map(|it| {
             it.apply(&mut before);
             before.to_string()
         })
@@ -0,0 +1,214 @@
+use hir::{
+    Adt, AssocItem, Crate, DefWithBody, HasSource, InFile, ModuleDef, Semantics, UnsafeExprKind,
+};
+use ide_db::{
+    base_db::{CrateId, FileRange},
+    FxIndexMap, RootDatabase,
+};
+use itertools::Itertools;
+use syntax::{ast, AstNode, AstPtr};
+
+/// An `unsafe` block, or the body of an `unsafe fn`, and the operations in it that need `unsafe`.
+#[derive(Debug)]
+pub struct UnsafeBlock {
+    pub kind: UnsafeBlockKind,
+    /// The range of the block, or of the whole function for [`UnsafeBlockKind::UnsafeFn`].
+    pub range: FileRange,
+    /// The path of the item whose body contains the block.
+    pub container: String,
+    pub operations: Vec<UnsafeOperation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnsafeBlockKind {
+    Block,
+    UnsafeFn,
+}
+
+#[derive(Debug)]
+pub struct UnsafeOperation {
+    pub kind: UnsafeExprKind,
+    pub range: FileRange,
+}
+
+// Feature: Unsafe Audit
+//
+// Lists every `unsafe` block of a crate together with the operations in it that require `unsafe`:
+// raw pointer dereferences, calls to `unsafe` and foreign functions, union field reads and
+// accesses to mutable statics. Operations in the body of an `unsafe fn` are listed under the
+// function itself. Blocks that don't contain any such operation aren't listed.
+//
+// Available on the command line as `rust-analyzer unsafe-audit`, which can also print JSON.
+pub(crate) fn unsafe_audit(db: &RootDatabase, crate_id: CrateId) -> Vec<UnsafeBlock> {
+    let sema = Semantics::new(db);
+    let mut res = Vec::new();
+    for body in crate_bodies(db, crate_id.into()) {
+        let container = container_path(db, body);
+        let is_unsafe_fn = match body {
+            DefWithBody::Function(it) => it.is_unsafe_to_call(db),
+            _ => false,
+        };
+
+        let mut blocks: FxIndexMap<_, Vec<UnsafeOperation>> = FxIndexMap::default();
+        for op in body.unsafe_operations(db) {
+            let block = match op.unsafe_block {
+                Some(block) => Some(block),
+                None if is_unsafe_fn => None,
+                // A missing `unsafe` block, which is reported as an error instead.
+                None => continue,
+            };
+            let operation =
+                UnsafeOperation { kind: op.kind, range: original_range(&sema, op.expr) };
+            blocks.entry(block).or_default().push(operation);
+        }
+
+        for (block, operations) in blocks {
+            let (kind, range) = match block {
+                Some(block) => (UnsafeBlockKind::Block, original_range(&sema, block)),
+                None => {
+                    let DefWithBody::Function(func) = body else { continue };
+                    let Some(source) = func.source(db) else { continue };
+                    (UnsafeBlockKind::UnsafeFn, source.syntax().original_file_range(db))
+                }
+            };
+            res.push(UnsafeBlock { kind, range, container: container.clone(), operations });
+        }
+    }
+    res
+}
+
+fn crate_bodies(db: &RootDatabase, krate: Crate) -> Vec<DefWithBody> {
+    let mut bodies = Vec::new();
+    for module in krate.modules(db) {
+        for decl in module.declarations(db) {
+            match decl {
+                ModuleDef::Adt(Adt::Enum(e)) => {
+                    bodies.extend(e.variants(db).into_iter().map(DefWithBody::from))
+                }
+                ModuleDef::Trait(t) => {
+                    bodies.extend(t.items(db).into_iter().filter_map(assoc_body))
+                }
+                _ => bodies.extend(decl.as_def_with_body()),
+            }
+        }
+        for impl_def in module.impl_defs(db) {
+            bodies.extend(impl_def.items(db).into_iter().filter_map(assoc_body));
+        }
+    }
+    bodies
+}
+
+fn assoc_body(item: AssocItem) -> Option<DefWithBody> {
+    match item {
+        AssocItem::Function(it) => Some(it.into()),
+        AssocItem::Const(it) => Some(it.into()),
+        AssocItem::TypeAlias(_) => None,
+    }
+}
+
+fn container_path(db: &RootDatabase, body: DefWithBody) -> String {
+    let module = body.module(db);
+    let crate_name = module.krate().display_name(db).map(|it| it.to_string());
+    crate_name
+        .into_iter()
+        .chain(
+            module
+                .path_to_root(db)
+                .into_iter()
+                .rev()
+                .filter_map(|it| it.name(db))
+                .map(|it| it.display(db).to_string()),
+        )
+        .chain(body.name(db).map(|it| it.display(db).to_string()))
+        .join("::")
+}
+
+fn original_range(sema: &Semantics<'_, RootDatabase>, ptr: InFile<AstPtr<ast::Expr>>) -> FileRange {
+    let root = sema.parse_or_expand(ptr.file_id);
+    let node = ptr.value.to_node(&root);
+    InFile::new(ptr.file_id, node.syntax()).original_file_range(sema.db)
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+
+    use crate::fixture;
+
+    fn check(ra_fixture: &str, expect: Expect) {
+        let (analysis, file_id) = fixture::file(ra_fixture);
+        let crate_id = analysis.crates_for(file_id).unwrap()[0];
+        let text = analysis.file_text(file_id).unwrap();
+        let mut actual = String::new();
+        for block in analysis.unsafe_audit(crate_id).unwrap() {
+            actual +=
+                &format!("{:?} `{}` in {:?}\n", block.kind, block.container, block.range.range);
+            for op in block.operations {
+                actual += &format!("    {:?} `{}`\n", op.kind, &text[op.range.range]);
+            }
+        }
+        expect.assert_eq(&actual);
+    }
+
+    #[test]
+    fn classifies_operations() {
+        check(
+            r#"
+union U { a: u32, b: f32 }
+static mut COUNTER: u32 = 0;
+extern "C" {
+    fn ffi(x: u32);
+}
+unsafe fn helper() {}
+
+fn main() {
+    let x = 0;
+    let p = &x as *const i32;
+    let mut u = U { a: 0 };
+    u.b = 1.0;
+    unsafe {
+        let _ = *p;
+        ffi(u.a);
+        COUNTER += 1;
+    }
+    unsafe { helper() };
+}
+"#,
+            expect![[r#"
+                Block `test::main` in 218..292
+                    RawPtrDeref `*p`
+                    UnsafeFnCall `ffi(u.a)`
+                    UnionField `u.a`
+                    MutableStatic `COUNTER`
+                Block `test::main` in 297..316
+                    UnsafeFnCall `helper()`
+            "#]],
+        );
+    }
+
+    #[test]
+    fn unsafe_fn_bodies_and_nested_blocks() {
+        check(
+            r#"
+mod m {
+    pub struct S(*const u8);
+    impl S {
+        pub unsafe fn read(&self) -> u8 {
+            let outer = *self.0;
+            unsafe { outer + *self.0 }
+        }
+    }
+}
+fn missing_unsafe(p: *const u8) -> u8 {
+    *p
+}
+"#,
+            expect![[r#"
+                UnsafeFn `test::m::read` in 58..173
+                    RawPtrDeref `*self.0`
+                Block `test::m::read` in 137..163
+                    RawPtrDeref `*self.0`
+            "#]],
+        );
+    }
+}
@@ -1,9 +1,58 @@
-use dot::{Id, LabelText};
+use dot::{Id, LabelText, Style};
+use hir::{Crate, HirFileIdExt};
 use ide_db::{
-    base_db::{CrateGraph, CrateId, Dependency, SourceDatabase, SourceDatabaseExt},
+    base_db::{CrateId, DependencyKind, FileId, SourceDatabase, SourceDatabaseExt},
+    symbol_index::SymbolsDatabase,
     FxHashSet, RootDatabase,
 };
-use triomphe::Arc;
+use itertools::Itertools;
+
+/// Which crates [`view_crate_graph`] and [`export_crate_graph`] include, and what is computed for
+/// them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CrateGraphConfig {
+    /// Include non-workspace crates, like crates.io dependencies and sysroot crates.
+    pub full: bool,
+    /// Only include the crates the crates of this file transitively depend on.
+    pub transitive_from: Option<FileId>,
+    /// Compute [`CrateMetrics`] for every included crate, this analyzes all of them.
+    pub metrics: bool,
+}
+
+#[derive(Debug)]
+pub struct ExportedCrateGraph {
+    pub nodes: Vec<CrateGraphNode>,
+    pub edges: Vec<CrateGraphEdge>,
+}
+
+#[derive(Debug)]
+pub struct CrateGraphNode {
+    pub crate_id: CrateId,
+    pub name: String,
+    pub version: Option<String>,
+    /// The enabled cfg options, sorted.
+    pub cfg: Vec<String>,
+    pub metrics: Option<CrateMetrics>,
+}
+
+#[derive(Debug)]
+pub struct CrateGraphEdge {
+    pub from: CrateId,
+    pub to: CrateId,
+    /// The name the dependency is available under in `from`.
+    pub name: String,
+    pub kind: DependencyKind,
+}
+
+#[derive(Debug)]
+pub struct CrateMetrics {
+    /// Lines in the files of the crate's modules, blank lines and comments included.
+    pub lines_of_code: usize,
+    /// Module level items declared in the crate, impls included.
+    pub items: usize,
+    /// Bytes of memory used for the crate's source text and symbol index.
+    pub memory: usize,
+}
 
 // Feature: View Crate Graph
 //
@@ -11,18 +60,42 @@
 // is part of graphviz, to be installed.
 //
 // Only workspace crates are included, no crates.io dependencies or sysroot crates.
+// Dev-dependency edges are dashed and build-dependency edges are dotted.
 //
 // |===
 // | Editor  | Action Name
 //
 // | VS Code | **rust-analyzer: View Crate Graph**
 // |===
-pub(crate) fn view_crate_graph(db: &RootDatabase, full: bool) -> Result<String, String> {
+pub(crate) fn view_crate_graph(
+    db: &RootDatabase,
+    config: &CrateGraphConfig,
+) -> Result<String, String> {
+    let graph = export_crate_graph(db, config);
+
+    let mut dot = Vec::new();
+    dot::render(&graph, &mut dot).unwrap();
+    Ok(String::from_utf8(dot).unwrap())
+}
+
+pub(crate) fn export_crate_graph(
+    db: &RootDatabase,
+    config: &CrateGraphConfig,
+) -> ExportedCrateGraph {
     let crate_graph = db.crate_graph();
-    let crates_to_render = crate_graph
+    let closure: Option<FxHashSet<CrateId>> = config.transitive_from.map(|file_id| {
+        crate::parent_module::crates_for(db, file_id)
+            .into_iter()
+            .flat_map(|krate| crate_graph.transitive_deps(krate))
+            .collect()
+    });
+    let crates_to_render: FxHashSet<CrateId> = crate_graph
         .iter()
         .filter(|krate| {
-            if full {
+            if closure.as_ref().is_some_and(|closure| !closure.contains(krate)) {
+                return false;
+            }
+            if config.full {
                 true
             } else {
                 // Only render workspace crates
@@ -31,48 +104,97 @@ pub(crate) fn view_crate_graph(db: &RootDatabase, full: bool) -> Result<String,
             }
         })
         .collect();
-    let graph = DotCrateGraph { graph: crate_graph, crates_to_render };
 
-    let mut dot = Vec::new();
-    dot::render(&graph, &mut dot).unwrap();
-    Ok(String::from_utf8(dot).unwrap())
+    let nodes = crates_to_render
+        .iter()
+        .sorted_by_key(|krate| krate.into_raw())
+        .map(|&crate_id| {
+            let data = &crate_graph[crate_id];
+            CrateGraphNode {
+                crate_id,
+                name: data.display_name.as_ref().map_or("(unnamed crate)", |name| name).to_owned(),
+                version: data.version.clone(),
+                cfg: (&data.cfg_options)
+                    .into_iter()
+                    .map(|atom| atom.to_string())
+                    .sorted()
+                    .collect(),
+                metrics: config.metrics.then(|| crate_metrics(db, crate_id)),
+            }
+        })
+        .collect();
+    let edges = crate_graph_edges(&crate_graph, &crates_to_render);
+
+    ExportedCrateGraph { nodes, edges }
 }
 
-struct DotCrateGraph {
-    graph: Arc<CrateGraph>,
-    crates_to_render: FxHashSet<CrateId>,
+fn crate_graph_edges(
+    crate_graph: &ide_db::base_db::CrateGraph,
+    crates_to_render: &FxHashSet<CrateId>,
+) -> Vec<CrateGraphEdge> {
+    crates_to_render
+        .iter()
+        .sorted_by_key(|krate| krate.into_raw())
+        .flat_map(|&from| {
+            crate_graph[from]
+                .dependencies
+                .iter()
+                .filter(|dep| crates_to_render.contains(&dep.crate_id))
+                .map(move |dep| CrateGraphEdge {
+                    from,
+                    to: dep.crate_id,
+                    name: dep.name.to_string(),
+                    kind: dep.kind(),
+                })
+        })
+        .collect()
 }
 
-type Edge<'a> = (CrateId, &'a Dependency);
+fn crate_metrics(db: &RootDatabase, crate_id: CrateId) -> CrateMetrics {
+    let mut files = FxHashSet::default();
+    let mut items = 0;
+    let mut memory = 0;
+    for module in Crate::from(crate_id).modules(db) {
+        items += module.declarations(db).len() + module.impl_defs(db).len();
+        memory += db.module_symbols(module).memory_size();
+        files.insert(module.definition_source_file_id(db).original_file(db));
+    }
+    let mut lines_of_code = 0;
+    for file_id in files {
+        let text = db.file_text(file_id);
+        lines_of_code += text.lines().count();
+        memory += text.len();
+    }
+    CrateMetrics { lines_of_code, items, memory }
+}
 
-impl<'a> dot::GraphWalk<'a, CrateId, Edge<'a>> for DotCrateGraph {
+impl ExportedCrateGraph {
+    fn node(&self, crate_id: CrateId) -> Option<&CrateGraphNode> {
+        self.nodes.iter().find(|node| node.crate_id == crate_id)
+    }
+}
+
+type Edge<'a> = &'a CrateGraphEdge;
+
+impl<'a> dot::GraphWalk<'a, CrateId, Edge<'a>> for ExportedCrateGraph {
     fn nodes(&'a self) -> dot::Nodes<'a, CrateId> {
-        self.crates_to_render.iter().copied().collect()
+        self.nodes.iter().map(|node| node.crate_id).collect()
     }
 
     fn edges(&'a self) -> dot::Edges<'a, Edge<'a>> {
-        self.crates_to_render
-            .iter()
-            .flat_map(|krate| {
-                self.graph[*krate]
-                    .dependencies
-                    .iter()
-                    .filter(|dep| self.crates_to_render.contains(&dep.crate_id))
-                    .map(move |dep| (*krate, dep))
-            })
-            .collect()
+        self.edges.iter().collect()
     }
 
     fn source(&'a self, edge: &Edge<'a>) -> CrateId {
-        edge.0
+        edge.from
     }
 
     fn target(&'a self, edge: &Edge<'a>) -> CrateId {
-        edge.1.crate_id
+        edge.to
     }
 }
 
-impl<'a> dot::Labeller<'a, CrateId, Edge<'a>> for DotCrateGraph {
+impl<'a> dot::Labeller<'a, CrateId, Edge<'a>> for ExportedCrateGraph {
     fn graph_id(&'a self) -> Id<'a> {
         Id::new("rust_analyzer_crate_graph").unwrap()
     }
@@ -86,7 +208,99 @@ fn node_shape(&'a self, _node: &CrateId) -> Option<LabelText<'a>> {
     }
 
     fn node_label(&'a self, n: &CrateId) -> LabelText<'a> {
-        let name = self.graph[*n].display_name.as_ref().map_or("(unnamed crate)", |name| name);
-        LabelText::LabelStr(name.into())
+        let Some(node) = self.node(*n) else { return LabelText::LabelStr("".into()) };
+        match &node.metrics {
+            Some(CrateMetrics { lines_of_code, items, memory }) => {
+                let memory = profile::Bytes::new(*memory as isize);
+                LabelText::EscStr(
+                    format!("{}\\n{lines_of_code} lines, {items} items, {memory}", node.name)
+                        .into(),
+                )
+            }
+            None => LabelText::LabelStr(node.name.as_str().into()),
+        }
+    }
+
+    fn edge_label(&'a self, e: &Edge<'a>) -> LabelText<'a> {
+        LabelText::LabelStr(
+            match e.kind {
+                DependencyKind::Normal => "",
+                DependencyKind::Dev => "dev",
+                DependencyKind::Build => "build",
+            }
+            .into(),
+        )
+    }
+
+    fn edge_style(&'a self, e: &Edge<'a>) -> Style {
+        match e.kind {
+            DependencyKind::Normal => Style::None,
+            DependencyKind::Dev => Style::Dashed,
+            DependencyKind::Build => Style::Dotted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use expect_test::{expect, Expect};
+    use ide_db::base_db::FileId;
+
+    use crate::{fixture, CrateGraphConfig};
+
+    fn check(ra_fixture: &str, config: CrateGraphConfig, expect: Expect) {
+        let (analysis, _) = fixture::file(ra_fixture);
+        let graph = analysis.export_crate_graph(&config).unwrap();
+        let nodes = graph.nodes.iter().map(|node| {
+            let metrics = node.metrics.as_ref().map_or(String::new(), |it| {
+                format!(" {} lines, {} items", it.lines_of_code, it.items)
+            });
+            format!("{}{metrics}\n", node.name)
+        });
+        let edges = graph.edges.iter().map(|edge| {
+            let name = |id| &graph.nodes.iter().find(|node| node.crate_id == id).unwrap().name;
+            format!("{} -> {} ({:?})\n", name(edge.from), name(edge.to), edge.kind)
+        });
+        expect.assert_eq(&nodes.chain(edges).collect::<String>());
+    }
+
+    #[test]
+    fn transitive_closure() {
+        check(
+            r#"
+//- /main.rs crate:main deps:a
+//- /a.rs crate:a deps:b
+//- /b.rs crate:b
+//- /c.rs crate:c deps:b
+"#,
+            CrateGraphConfig { transitive_from: Some(FileId::from_raw(1)), ..Default::default() },
+            expect![[r#"
+                a
+                b
+                a -> b (Normal)
+            "#]],
+        );
+    }
+
+    #[test]
+    fn metrics() {
+        check(
+            r#"
+//- /main.rs crate:main deps:dep
+mod foo;
+fn main() {}
+struct S;
+impl S {}
+//- /foo.rs
+pub fn f() {}
+//- /dep.rs crate:dep
+"#,
+            CrateGraphConfig { metrics: true, ..Default::default() },
+            expect![[r#"
+                main 5 lines, 5 items
+                dep 0 lines, 0 items
+                main -> dep (Normal)
+            "#]],
+        );
     }
 }
@@ -0,0 +1,35 @@
+//! Prints every item of the workspace members of a Cargo project, together with
+//! its type and how often it is referenced.
+//!
+//! ```text
+//! cargo run -p load-cargo --example list_items -- path/to/project
+//! ```
+
+#![allow(clippy::print_stdout)]
+
+use std::{env, path::PathBuf};
+
+use hir::HirDisplay;
+use load_cargo::Workspace;
+
+fn main() -> anyhow::Result<()> {
+    let root = env::args().nth(1).map_or_else(|| PathBuf::from("."), PathBuf::from);
+    let ws = Workspace::load(&root)?;
+    let db = ws.db();
+
+    for krate in ws.local_crates() {
+        let crate_name = krate.display_name(db).map_or_else(String::new, |it| it.to_string());
+        println!("crate {crate_name}");
+        for module in ws.modules(krate) {
+            for item in ws.items(module) {
+                let Some(path) = item.canonical_path(db) else { continue };
+                let references = ws.references(item).len();
+                match ws.ty(item) {
+                    Some(ty) => println!("  {path}: {} ({references} references)", ty.display(db)),
+                    None => println!("  {path} ({references} references)"),
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -20,6 +20,10 @@
 use span::Span;
 use vfs::{file_set::FileSetConfig, loader::Handle, AbsPath, AbsPathBuf, VfsPath};
 
+mod workspace;
+
+pub use crate::workspace::Workspace;
+
 pub struct LoadCargoConfig {
     pub load_out_dirs_from_check: bool,
     pub with_proc_macro_server: ProcMacroServerChoice,
@@ -190,16 +194,24 @@ pub fn new(workspaces: &[ProjectWorkspace], global_excludes: &[AbsPathBuf]) -> P
                 for v in v {
                     let r = mem::replace(
                         &mut roots[v],
-                        PackageRoot { is_local: false, include: vec![], exclude: vec![] },
+                        PackageRoot {
+                            is_local: false,
+                            include: vec![],
+                            exclude: vec![],
+                            exclude_globs: vec![],
+                        },
                     );
                     roots[k].is_local |= r.is_local;
                     roots[k].include.extend(r.include);
                     roots[k].exclude.extend(r.exclude);
+                    roots[k].exclude_globs.extend(r.exclude_globs);
                 }
                 roots[k].include.sort();
                 roots[k].exclude.sort();
                 roots[k].include.dedup();
                 roots[k].exclude.dedup();
+                roots[k].exclude_globs.sort();
+                roots[k].exclude_globs.dedup();
             }
         }
 
@@ -213,6 +225,7 @@ pub fn new(workspaces: &[ProjectWorkspace], global_excludes: &[AbsPathBuf]) -> P
                 dirs.extensions.push("toml".into());
                 dirs.include.extend(root.include);
                 dirs.exclude.extend(root.exclude);
+                dirs.exclude_globs.extend(root.exclude_globs);
                 for excl in global_excludes {
                     if dirs
                         .include
@@ -237,6 +250,18 @@ pub fn new(workspaces: &[ProjectWorkspace], global_excludes: &[AbsPathBuf]) -> P
             fsc.add_file_set(file_set_roots)
         }
 
+        let build_files: Vec<AbsPathBuf> = workspaces
+            .iter()
+            .flat_map(|ws| ws.build_files().into_iter().chain(ws.build_script_inputs()))
+            .unique()
+            .collect();
+        if !build_files.is_empty() {
+            // Build files and build script inputs are not part of any source root, we only load
+            // them to get notified about their changes.
+            res.watch.push(res.load.len());
+            res.load.push(vfs::loader::Entry::Files(build_files));
+        }
+
         let fsc = fsc.build();
         res.source_root_config = SourceRootConfig { fsc, local_filesets };
 
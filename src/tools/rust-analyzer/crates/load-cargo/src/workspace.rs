@@ -0,0 +1,111 @@
+//! A loaded Cargo workspace for tools that want to walk it through [`hir`].
+//!
+//! This is the entry point for external analyzers and code generators: it hides
+//! how the database is assembled and only hands out `hir` and `ide_db` types,
+//! which change far less often than the queries underneath them.
+
+use std::path::Path;
+
+use hir::{Crate, Module, ModuleDef, Semantics};
+use ide::AnalysisHost;
+use ide_db::{
+    base_db::{FileId, FileRange},
+    defs::Definition,
+    RootDatabase,
+};
+use proc_macro_api::ProcMacroServer;
+use project_model::{CargoConfig, RustLibSource};
+use vfs::{Vfs, VfsPath};
+
+use crate::{load_workspace_at, LoadCargoConfig, ProcMacroServerChoice};
+
+/// A Cargo workspace loaded into an analysis database.
+///
+/// The workspace is a snapshot: changes on disk after loading are not picked up.
+pub struct Workspace {
+    host: AnalysisHost,
+    vfs: Vfs,
+    _proc_macro_server: Option<ProcMacroServer>,
+}
+
+impl Workspace {
+    /// Loads the workspace containing `root`, running build scripts and
+    /// expanding proc macros with the sysroot's proc macro server.
+    pub fn load(root: &Path) -> anyhow::Result<Workspace> {
+        let cargo_config =
+            CargoConfig { sysroot: Some(RustLibSource::Discover), ..Default::default() };
+        let load_config = LoadCargoConfig {
+            load_out_dirs_from_check: true,
+            with_proc_macro_server: ProcMacroServerChoice::Sysroot,
+            prefill_caches: false,
+        };
+        Workspace::load_with_config(root, &cargo_config, &load_config)
+    }
+
+    pub fn load_with_config(
+        root: &Path,
+        cargo_config: &CargoConfig,
+        load_config: &LoadCargoConfig,
+    ) -> anyhow::Result<Workspace> {
+        let (host, vfs, proc_macro_server) =
+            load_workspace_at(root, cargo_config, load_config, &|_| {})?;
+        Ok(Workspace { host, vfs, _proc_macro_server: proc_macro_server })
+    }
+
+    pub fn db(&self) -> &RootDatabase {
+        self.host.raw_database()
+    }
+
+    pub fn semantics(&self) -> Semantics<'_, RootDatabase> {
+        Semantics::new(self.db())
+    }
+
+    /// All crates in the crate graph, including dependencies and the sysroot.
+    pub fn crates(&self) -> Vec<Crate> {
+        Crate::all(self.db())
+    }
+
+    /// The crates that are members of the workspace itself.
+    pub fn local_crates(&self) -> Vec<Crate> {
+        let db = self.db();
+        Crate::all(db).into_iter().filter(|krate| krate.origin(db).is_local()).collect()
+    }
+
+    /// All modules of `krate`, including inline and nested ones.
+    pub fn modules(&self, krate: Crate) -> Vec<Module> {
+        krate.modules(self.db())
+    }
+
+    /// The items declared directly in `module`, not counting associated items.
+    pub fn items(&self, module: Module) -> Vec<ModuleDef> {
+        module.declarations(self.db())
+    }
+
+    /// The type of `item`, for items that have one.
+    pub fn ty(&self, item: ModuleDef) -> Option<hir::Type> {
+        let db = self.db();
+        match item {
+            ModuleDef::Adt(it) => Some(it.ty(db)),
+            ModuleDef::Function(it) => Some(it.ty(db)),
+            ModuleDef::Const(it) => Some(it.ty(db)),
+            ModuleDef::Static(it) => Some(it.ty(db)),
+            ModuleDef::TypeAlias(it) => Some(it.ty(db)),
+            ModuleDef::BuiltinType(it) => Some(it.ty(db)),
+            ModuleDef::Module(_)
+            | ModuleDef::Variant(_)
+            | ModuleDef::Trait(_)
+            | ModuleDef::TraitAlias(_)
+            | ModuleDef::Macro(_) => None,
+        }
+    }
+
+    /// Every place in the workspace that resolves to `def`.
+    pub fn references(&self, def: impl Into<Definition>) -> Vec<FileRange> {
+        let sema = self.semantics();
+        def.into().usages(&sema).all().file_ranges().collect()
+    }
+
+    pub fn file_path(&self, file_id: FileId) -> VfsPath {
+        self.vfs.file_path(file_id)
+    }
+}
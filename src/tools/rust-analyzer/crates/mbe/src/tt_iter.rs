@@ -137,7 +137,8 @@ pub(crate) fn expect_fragment(
     ) -> ExpandResult<Option<tt::TokenTree<S>>> {
         let buffer = tt::buffer::TokenBuffer::from_tokens(self.inner.as_slice());
         let parser_input = to_parser_input(&buffer);
-        let tree_traversal = entry_point.parse(&parser_input);
+        // FIXME: Parse fragments with the edition of the macro definition.
+        let tree_traversal = entry_point.parse(&parser_input, parser::Edition::CURRENT);
         let mut cursor = buffer.begin();
         let mut error = false;
         for step in tree_traversal.iter() {
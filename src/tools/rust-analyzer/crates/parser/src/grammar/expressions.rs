@@ -4,7 +4,7 @@
 
 use super::*;
 
-pub(crate) use atom::{block_expr, match_arm_list};
+pub(crate) use atom::{block_expr, match_arm_list, match_guard};
 pub(super) use atom::{literal, LITERAL_FIRST};
 
 #[derive(PartialEq, Eq)]
@@ -160,10 +160,34 @@ pub(super) fn let_stmt(p: &mut Parser<'_>, with_semi: Semicolon) {
     }
 }
 
-pub(super) fn expr_block_contents(p: &mut Parser<'_>) {
+/// Items that, when found at the start of an unindented line, are taken to mean
+/// that the block we are in was never closed.
+const UNCLOSED_BLOCK_RECOVERY_SET: TokenSet = TokenSet::new(&[
+    T![fn],
+    T![struct],
+    T![enum],
+    T![impl],
+    T![trait],
+    T![mod],
+    T![use],
+    T![pub],
+    T![const],
+    T![static],
+    T![type],
+    T![extern],
+    T![#],
+]);
+
+/// Returns `false` if parsing stopped before the closing `}` because the block
+/// looks unclosed.
+pub(super) fn expr_block_contents(p: &mut Parser<'_>) -> bool {
     attributes::inner_attrs(p);
 
     while !p.at(EOF) && !p.at(T!['}']) {
+        if at_unclosed_block_end(p) {
+            return false;
+        }
+
         // test nocontentexpr
         // fn foo(){
         //     ;;;some_expr();;;;{;;;};;;;Ok(())
@@ -180,6 +204,11 @@ pub(super) fn expr_block_contents(p: &mut Parser<'_>) {
         // }
         stmt(p, Semicolon::Required);
     }
+    true
+}
+
+fn at_unclosed_block_end(p: &Parser<'_>) -> bool {
+    p.at_unindented_line_start() && p.at_ts(UNCLOSED_BLOCK_RECOVERY_SET)
 }
 
 #[derive(Clone, Copy)]
@@ -472,6 +501,28 @@ fn postfix_dot_expr<const FLOAT_RECOVERY: bool>(
         return Ok(m.complete(p, AWAIT_EXPR));
     }
 
+    // test postfix_match_expr
+    // fn foo() {
+    //     x.match { _ => () };
+    //     x.0.match { _ => () }.foo();
+    //     x.await.match { _ => () };
+    // }
+    if p.nth(nth1) == T![match] {
+        let m = lhs.precede(p);
+        if !FLOAT_RECOVERY {
+            p.bump(T![.]);
+        }
+        p.bump(T![match]);
+        // test_err postfix_match_without_arms
+        // fn foo() { x.match; }
+        if p.at(T!['{']) {
+            match_arm_list(p);
+        } else {
+            p.error("expected `{`");
+        }
+        return Ok(m.complete(p, MATCH_EXPR));
+    }
+
     if p.at(T![..=]) || p.at(T![..]) {
         return Err(lhs);
     }
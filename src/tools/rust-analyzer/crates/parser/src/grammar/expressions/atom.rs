@@ -79,6 +79,11 @@ pub(super) fn atom_expr(
     if p.at_contextual_kw(T![builtin]) && p.nth_at(1, T![#]) {
         return Some((builtin_expr(p)?, BlockLike::NotBlock));
     }
+    // `gen` is not a keyword before the 2024 edition, so there `gen {}` is a record expression of a
+    // type called `gen`.
+    if p.at_contextual_kw(T![gen]) && !r.forbid_structs && gen_block_follows(p, 1) {
+        return Some((gen_block_expr(p), BlockLike::Block));
+    }
     if paths::is_path_start(p) {
         return Some(path_expr(p, r));
     }
@@ -151,6 +156,14 @@ pub(super) fn atom_expr(
             stmt_list(p);
             m.complete(p, BLOCK_EXPR)
         }
+        T![async] if p.nth_at_contextual_kw(1, T![gen]) && gen_block_follows(p, 2) => {
+            let m = p.start();
+            p.bump(T![async]);
+            p.bump_remap(T![gen]);
+            p.eat(T![move]);
+            stmt_list(p);
+            m.complete(p, BLOCK_EXPR)
+        }
         T!['{'] => {
             // test for_range_from
             // fn foo() {
@@ -177,6 +190,34 @@ pub(super) fn atom_expr(
     Some((done, blocklike))
 }
 
+/// Whether the tokens starting at `n` make the `gen` before them start a `gen` block, which needs
+/// the 2024 edition.
+fn gen_block_follows(p: &Parser<'_>, n: usize) -> bool {
+    p.edition().at_least_2024()
+        && match p.nth(n) {
+            T![move] => p.nth_at(n + 1, T!['{']),
+            T!['{'] => true,
+            _ => false,
+        }
+}
+
+// test gen_blocks
+// fn f() {
+//     gen { yield 1; };
+//     gen move { yield 1; };
+//     async gen { yield 1; };
+//     async gen move { yield 1; };
+//     gen {};
+// }
+fn gen_block_expr(p: &mut Parser<'_>) -> CompletedMarker {
+    assert!(p.at_contextual_kw(T![gen]));
+    let m = p.start();
+    p.bump_remap(T![gen]);
+    p.eat(T![move]);
+    stmt_list(p);
+    m.complete(p, BLOCK_EXPR)
+}
+
 // test tuple_expr
 // fn foo() {
 //     ();
@@ -576,7 +617,7 @@ fn match_arm(p: &mut Parser<'_>) {
 //         _ if let foo = bar => (),
 //     }
 // }
-fn match_guard(p: &mut Parser<'_>) -> CompletedMarker {
+pub(crate) fn match_guard(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(T![if]));
     let m = p.start();
     p.bump(T![if]);
@@ -599,12 +640,23 @@ pub(crate) fn block_expr(p: &mut Parser<'_>) {
     m.complete(p, BLOCK_EXPR);
 }
 
+// test_err unclosed_block_before_item
+// fn foo() {
+//     if true {
+//         1;
+//     let x = 92;
+// }
+//
+// fn bar() {}
 fn stmt_list(p: &mut Parser<'_>) -> CompletedMarker {
     assert!(p.at(T!['{']));
     let m = p.start();
     p.bump(T!['{']);
-    expr_block_contents(p);
-    p.expect(T!['}']);
+    if expr_block_contents(p) {
+        p.expect(T!['}']);
+    } else {
+        p.error("unclosed delimiter");
+    }
     m.complete(p, STMT_LIST)
 }
 
@@ -113,7 +113,10 @@ pub(super) fn opt_item(p: &mut Parser<'_>, m: Marker) -> Result<(), Marker> {
 
     // test_err async_without_semicolon
     // fn foo() { let _ = async {} }
-    if p.at(T![async]) && !matches!(p.nth(1), T!['{'] | T![move] | T![|]) {
+    if p.at(T![async])
+        && !matches!(p.nth(1), T!['{'] | T![move] | T![|])
+        && !(p.edition().at_least_2024() && p.nth_at_contextual_kw(1, T![gen]))
+    {
         p.eat(T![async]);
         has_mods = true;
     }
@@ -40,6 +40,30 @@ pub(super) fn pattern_top_r(p: &mut Parser<'_>, recovery_set: TokenSet) {
     pattern_r(p, recovery_set);
 }
 
+/// Parses a pattern list separated by pipes `|`, optionally followed by a guard. Guards are only
+/// accepted where the pattern is nested in another one, top-level match arm guards are parsed by
+/// the match arm itself.
+
+// test guard_pat
+// fn main() {
+//     match () {
+//         (Some(x if x > 0) | None, y if y) => (),
+//         [a if a, ..] => (),
+//         S { f: g if g } => (),
+//         (_ if true) => (),
+//     }
+// }
+fn pattern_top_guarded(p: &mut Parser<'_>) {
+    let m = p.start();
+    pattern_top(p);
+    if p.at(T![if]) {
+        expressions::match_guard(p);
+        m.complete(p, GUARD_PAT);
+    } else {
+        m.abandon(p);
+    }
+}
+
 /// Parses a pattern list separated by pipes `|`, with no leading `|`,using the
 /// given `recovery_set`.
 
@@ -321,7 +345,7 @@ fn record_pat_field(p: &mut Parser<'_>) {
         IDENT | INT_NUMBER if p.nth(1) == T![:] => {
             name_ref_or_index(p);
             p.bump(T![:]);
-            pattern(p);
+            pattern_top_guarded(p);
         }
         // test_err record_pat_field_eq_recovery
         // fn main() {
@@ -440,7 +464,7 @@ fn tuple_pat(p: &mut Parser<'_>) -> CompletedMarker {
         }
         has_rest |= p.at(T![..]);
 
-        pattern_top(p);
+        pattern_top_guarded(p);
         if !p.at(T![')']) {
             has_comma = true;
             p.expect(T![,]);
@@ -467,7 +491,7 @@ fn slice_pat(p: &mut Parser<'_>) -> CompletedMarker {
 
 fn pat_list(p: &mut Parser<'_>, ket: SyntaxKind) {
     while !p.at(EOF) && !p.at(ket) {
-        pattern_top(p);
+        pattern_top_guarded(p);
         if !p.at(T![,]) {
             if p.at_ts(PAT_TOP_FIRST) {
                 p.error(format!("expected {:?}, got {:?}", T![,], p.current()));
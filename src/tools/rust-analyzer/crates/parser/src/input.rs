@@ -17,6 +17,7 @@ pub struct Input {
     kind: Vec<SyntaxKind>,
     joint: Vec<bits>,
     contextual_kind: Vec<SyntaxKind>,
+    unindented_line_start: Vec<bits>,
 }
 
 /// `pub` impl used by callers to create `Tokens`.
@@ -51,11 +52,24 @@ pub fn was_joint(&mut self) {
         let (idx, b_idx) = self.bit_index(n);
         self.joint[idx] |= 1 << b_idx;
     }
+    /// Marks the last token we've pushed as the first thing on a line, with no
+    /// indentation in front of it.
+    ///
+    /// This is used purely as a recovery hint: when braces are unbalanced, an
+    /// item at column zero most likely means that the preceding block was
+    /// never closed.
+    #[inline]
+    pub fn starts_unindented_line(&mut self) {
+        let n = self.len() - 1;
+        let (idx, b_idx) = self.bit_index(n);
+        self.unindented_line_start[idx] |= 1 << b_idx;
+    }
     #[inline]
     fn push_impl(&mut self, kind: SyntaxKind, contextual_kind: SyntaxKind) {
         let idx = self.len();
         if idx % (bits::BITS as usize) == 0 {
             self.joint.push(0);
+            self.unindented_line_start.push(0);
         }
         self.kind.push(kind);
         self.contextual_kind.push(contextual_kind);
@@ -74,6 +88,13 @@ pub(crate) fn is_joint(&self, n: usize) -> bool {
         let (idx, b_idx) = self.bit_index(n);
         self.joint[idx] & 1 << b_idx != 0
     }
+    pub(crate) fn is_unindented_line_start(&self, n: usize) -> bool {
+        if n >= self.len() {
+            return false;
+        }
+        let (idx, b_idx) = self.bit_index(n);
+        self.unindented_line_start[idx] & 1 << b_idx != 0
+    }
 }
 
 impl Input {
@@ -8,6 +8,7 @@
 use crate::{
     event::Event,
     input::Input,
+    Edition,
     SyntaxKind::{self, EOF, ERROR, TOMBSTONE},
     TokenSet, T,
 };
@@ -24,6 +25,7 @@
 pub(crate) struct Parser<'t> {
     inp: &'t Input,
     pos: usize,
+    edition: Edition,
     events: Vec<Event>,
     steps: Cell<u32>,
 }
@@ -31,8 +33,14 @@ pub(crate) struct Parser<'t> {
 static PARSER_STEP_LIMIT: Limit = Limit::new(15_000_000);
 
 impl<'t> Parser<'t> {
-    pub(super) fn new(inp: &'t Input) -> Parser<'t> {
-        Parser { inp, pos: 0, events: Vec::new(), steps: Cell::new(0) }
+    pub(super) fn new(inp: &'t Input, edition: Edition) -> Parser<'t> {
+        Parser { inp, pos: 0, edition, events: Vec::new(), steps: Cell::new(0) }
+    }
+
+    /// The edition of the crate the input belongs to, some keywords are only reserved in newer
+    /// editions.
+    pub(crate) fn edition(&self) -> Edition {
+        self.edition
     }
 
     pub(crate) fn finish(self) -> Vec<Event> {
@@ -153,6 +161,12 @@ pub(crate) fn at_contextual_kw(&self, kw: SyntaxKind) -> bool {
         self.inp.contextual_kind(self.pos) == kw
     }
 
+    /// Checks if the current token is the first one on a line and isn't
+    /// indented. Only ever true when the input has unbalanced curly braces.
+    pub(crate) fn at_unindented_line_start(&self) -> bool {
+        self.inp.is_unindented_line_start(self.pos)
+    }
+
     /// Checks if the nth token is contextual keyword `kw`.
     pub(crate) fn nth_at_contextual_kw(&self, n: usize, kw: SyntaxKind) -> bool {
         self.inp.contextual_kind(self.pos + n) == kw
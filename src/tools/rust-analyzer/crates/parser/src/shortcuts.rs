@@ -28,6 +28,9 @@ impl LexedStr<'_> {
     pub fn to_input(&self) -> crate::Input {
         let mut res = crate::Input::default();
         let mut was_joint = false;
+        // Indentation is only used to recover from unclosed blocks, so don't
+        // let it affect how well-formed code is parsed.
+        let track_indentation = self.has_unclosed_curly();
         for i in 0..self.len() {
             let kind = self.kind(i);
             if kind.is_trivia() {
@@ -37,11 +40,17 @@ pub fn to_input(&self) -> crate::Input {
                 let contextual_kw =
                     SyntaxKind::from_contextual_keyword(token_text).unwrap_or(SyntaxKind::IDENT);
                 res.push_ident(contextual_kw);
+                if track_indentation && self.starts_unindented_line(i) {
+                    res.starts_unindented_line();
+                }
             } else {
                 if was_joint {
                     res.was_joint();
                 }
                 res.push(kind);
+                if track_indentation && self.starts_unindented_line(i) {
+                    res.starts_unindented_line();
+                }
                 // Tag the token as joint if it is float with a fractional part
                 // we use this jointness to inform the parser about what token split
                 // event to emit when we encounter a float literal in a field access
@@ -59,6 +68,22 @@ pub fn to_input(&self) -> crate::Input {
         res
     }
 
+    fn has_unclosed_curly(&self) -> bool {
+        let mut balance = 0i32;
+        for i in 0..self.len() {
+            match self.kind(i) {
+                SyntaxKind::L_CURLY => balance += 1,
+                SyntaxKind::R_CURLY => balance -= 1,
+                _ => (),
+            }
+        }
+        balance > 0
+    }
+
+    fn starts_unindented_line(&self, i: usize) -> bool {
+        i == 0 || self.kind(i - 1) == SyntaxKind::WHITESPACE && self.text(i - 1).ends_with('\n')
+    }
+
     /// NB: only valid to call with Output from Reparser/TopLevelEntry.
     pub fn intersperse_trivia(
         &self,
@@ -1,6 +1,6 @@
 use expect_test::expect;
 
-use crate::TopEntryPoint;
+use crate::{Edition, TopEntryPoint};
 
 #[test]
 fn source_file() {
@@ -305,8 +305,46 @@ fn expr() {
     );
 }
 
+#[test]
+fn gen_block_needs_2024_edition() {
+    check_edition(
+        TopEntryPoint::Expr,
+        "gen {}",
+        Edition::Edition2021,
+        expect![[r#"
+            RECORD_EXPR
+              PATH
+                PATH_SEGMENT
+                  NAME_REF
+                    IDENT "gen"
+              WHITESPACE " "
+              RECORD_EXPR_FIELD_LIST
+                L_CURLY "{"
+                R_CURLY "}"
+        "#]],
+    );
+    check_edition(
+        TopEntryPoint::Expr,
+        "gen {}",
+        Edition::Edition2024,
+        expect![[r#"
+            BLOCK_EXPR
+              GEN_KW "gen"
+              WHITESPACE " "
+              STMT_LIST
+                L_CURLY "{"
+                R_CURLY "}"
+        "#]],
+    );
+}
+
 #[track_caller]
 fn check(entry: TopEntryPoint, input: &str, expect: expect_test::Expect) {
-    let (parsed, _errors) = super::parse(entry, input);
+    check_edition(entry, input, Edition::CURRENT, expect)
+}
+
+#[track_caller]
+fn check_edition(entry: TopEntryPoint, input: &str, edition: Edition, expect: expect_test::Expect) {
+    let (parsed, _errors) = super::parse(entry, input, edition);
     expect.assert_eq(&parsed)
 }
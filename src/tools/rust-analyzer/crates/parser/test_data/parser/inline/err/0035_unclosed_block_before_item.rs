@@ -0,0 +1,7 @@
+fn foo() {
+    if true {
+        1;
+    let x = 92;
+}
+
+fn bar() {}
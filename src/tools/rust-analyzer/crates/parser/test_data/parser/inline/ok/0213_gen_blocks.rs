@@ -0,0 +1,7 @@
+fn f() {
+    gen { yield 1; };
+    gen move { yield 1; };
+    async gen { yield 1; };
+    async gen move { yield 1; };
+    gen {};
+}
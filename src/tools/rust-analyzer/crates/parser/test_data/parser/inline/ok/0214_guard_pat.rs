@@ -0,0 +1,8 @@
+fn main() {
+    match () {
+        (Some(x if x > 0) | None, y if y) => (),
+        [a if a, ..] => (),
+        S { f: g if g } => (),
+        (_ if true) => (),
+    }
+}
@@ -0,0 +1,5 @@
+fn foo() {
+    x.match { _ => () };
+    x.0.match { _ => () }.foo();
+    x.await.match { _ => () };
+}
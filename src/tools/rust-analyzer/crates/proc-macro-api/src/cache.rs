@@ -0,0 +1,323 @@
+//! A content-addressed cache of proc-macro expansions.
+//!
+//! Expansions are keyed by a SHA-256 hash of the whole expansion request: the macro, its input
+//! and attribute token trees including their spans, and the environment it is expanded in. So an
+//! expansion is only ever reused for an identical request. The entries of each dylib can also be
+//! persisted to an append-only file per build of the dylib, to be reused across sessions.
+//!
+//! Both the entries kept in memory and the persisted files are bounded in size. The least recently
+//! used entries are evicted from memory, and a persisted file that grows too large is rewritten
+//! with the entries still in memory.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    hash::{Hash, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    sync::{Arc, Mutex},
+};
+
+use paths::{AbsPath, AbsPathBuf};
+use rustc_hash::{FxHashMap, FxHasher};
+use sha2::{Digest, Sha256};
+
+use crate::msg::{Request, Response};
+
+/// Bumped whenever the format of the persisted entries changes.
+const CACHE_VERSION: u32 = 2;
+/// The total size of the responses of a dylib kept in memory. Beyond this, the least recently
+/// used ones are evicted.
+const MAX_MEMORY_SIZE: usize = 16 * 1024 * 1024;
+/// Persisted caches growing beyond this are rewritten with the entries kept in memory.
+const MAX_PERSISTED_SIZE: u64 = 32 * 1024 * 1024;
+
+pub(crate) type CacheKey = [u8; 32];
+
+#[derive(Debug)]
+pub(crate) struct ExpansionCache {
+    persist_dir: Option<AbsPathBuf>,
+    dylibs: Mutex<FxHashMap<AbsPathBuf, DylibCache>>,
+}
+
+#[derive(Debug, Default)]
+struct DylibCache {
+    entries: FxHashMap<CacheKey, Entry>,
+    /// The total length of the responses in `entries`.
+    size: usize,
+    /// Advanced on every access, to find the least recently used entries.
+    clock: u64,
+    log: Option<Log>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    response: Arc<str>,
+    last_used: u64,
+}
+
+/// The persisted cache file entries are appended to.
+#[derive(Debug)]
+struct Log {
+    path: AbsPathBuf,
+    file: fs::File,
+    size: u64,
+}
+
+impl ExpansionCache {
+    pub(crate) fn new(persist_dir: Option<AbsPathBuf>) -> ExpansionCache {
+        ExpansionCache { persist_dir, dylibs: Mutex::default() }
+    }
+
+    /// Returns the cache key of an expansion request sent to a server of the given version.
+    pub(crate) fn key(version: u32, request: &Request) -> Option<CacheKey> {
+        let request = serde_json::to_string(request).ok()?;
+        Some(
+            Sha256::new()
+                .chain_update(CACHE_VERSION.to_le_bytes())
+                .chain_update(version.to_le_bytes())
+                .chain_update(request)
+                .finalize()
+                .into(),
+        )
+    }
+
+    pub(crate) fn get(&self, dylib: &AbsPath, key: CacheKey) -> Option<Response> {
+        let response = self.with_dylib(dylib, |cache| cache.get(&key))?;
+        serde_json::from_str(&response).ok()
+    }
+
+    pub(crate) fn insert(&self, dylib: &AbsPath, key: CacheKey, response: &Response) {
+        let Ok(response) = serde_json::to_string(response) else { return };
+        self.with_dylib(dylib, |cache| {
+            if let Some(log) = &mut cache.log {
+                let line = format!("{} {response}\n", encode_key(&key));
+                match log.file.write_all(line.as_bytes()) {
+                    Ok(()) => log.size += line.len() as u64,
+                    Err(e) => {
+                        tracing::warn!("failed to persist proc-macro expansion: {e}");
+                        cache.log = None;
+                    }
+                }
+            }
+            cache.insert(key, response.into());
+            if cache.log.as_ref().is_some_and(|log| log.size > MAX_PERSISTED_SIZE) {
+                cache.compact();
+            }
+        })
+    }
+
+    fn with_dylib<R>(&self, dylib: &AbsPath, f: impl FnOnce(&mut DylibCache) -> R) -> R {
+        let mut dylibs = self.dylibs.lock().unwrap_or_else(|e| e.into_inner());
+        let cache = dylibs.entry(dylib.to_path_buf()).or_insert_with(|| match &self.persist_dir {
+            Some(dir) => open_persisted(dir, dylib),
+            None => DylibCache::default(),
+        });
+        f(cache)
+    }
+}
+
+impl DylibCache {
+    fn get(&mut self, key: &CacheKey) -> Option<Arc<str>> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        entry.last_used = self.clock;
+        Some(entry.response.clone())
+    }
+
+    fn insert(&mut self, key: CacheKey, response: Arc<str>) {
+        self.clock += 1;
+        self.size += response.len();
+        let entry = Entry { response, last_used: self.clock };
+        if let Some(old) = self.entries.insert(key, entry) {
+            self.size -= old.response.len();
+        }
+        if self.size > MAX_MEMORY_SIZE {
+            self.evict();
+        }
+    }
+
+    /// Evicts the least recently used entries until a quarter of the memory budget is free again,
+    /// so that evictions don't happen on every insertion.
+    fn evict(&mut self) {
+        let mut by_use: Vec<_> =
+            self.entries.iter().map(|(key, entry)| (entry.last_used, *key)).collect();
+        by_use.sort_unstable();
+        for (_, key) in by_use {
+            if self.size <= MAX_MEMORY_SIZE / 4 * 3 {
+                break;
+            }
+            if let Some(entry) = self.entries.remove(&key) {
+                self.size -= entry.response.len();
+            }
+        }
+    }
+
+    /// Rewrites the persisted cache with only the entries kept in memory, least recently used
+    /// first, so that they are also the first to be evicted when the cache is loaded again.
+    fn compact(&mut self) {
+        let Some(log) = self.log.take() else { return };
+        let mut tmp_path = log.path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        let tmp_path = AbsPathBuf::assert(tmp_path.into());
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_unstable_by_key(|(_, entry)| entry.last_used);
+        let mut size = 0;
+        let res = (|| {
+            let mut file = io::BufWriter::new(fs::File::create(&tmp_path)?);
+            for (key, entry) in entries {
+                let line = format!("{} {}\n", encode_key(key), entry.response);
+                file.write_all(line.as_bytes())?;
+                size += line.len() as u64;
+            }
+            file.flush()?;
+            drop(file);
+            fs::rename(&tmp_path, &log.path)?;
+            fs::OpenOptions::new().append(true).open(&log.path)
+        })();
+        match res {
+            Ok(file) => self.log = Some(Log { path: log.path, file, size }),
+            Err(e) => {
+                tracing::warn!("failed to compact proc-macro expansion cache `{}`: {e}", log.path);
+                _ = fs::remove_file(&tmp_path);
+                _ = fs::remove_file(&log.path);
+            }
+        }
+    }
+}
+
+fn encode_key(key: &CacheKey) -> String {
+    key.iter().fold(String::with_capacity(64), |mut acc, byte| {
+        _ = write!(acc, "{byte:02x}");
+        acc
+    })
+}
+
+fn decode_key(key: &str) -> Option<CacheKey> {
+    if key.len() != 64 || !key.bytes().all(|it| it.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut res = [0; 32];
+    for (i, byte) in res.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(res)
+}
+
+/// Loads the persisted cache of the current build of `dylib`, removing the caches of its previous
+/// builds, and opens it to append new entries to.
+fn open_persisted(dir: &AbsPath, dylib: &AbsPath) -> DylibCache {
+    let hash = |value: &dyn Fn(&mut FxHasher)| {
+        let mut hasher = FxHasher::default();
+        value(&mut hasher);
+        hasher.finish()
+    };
+    let path_hash = hash(&|hasher| dylib.hash(hasher));
+    let build_hash = hash(&|hasher| {
+        let metadata = fs::metadata(dylib).ok();
+        metadata.as_ref().map(|it| it.len()).hash(hasher);
+        metadata.and_then(|it| it.modified().ok()).hash(hasher);
+    });
+    let prefix = format!("{path_hash:016x}-");
+    let file_name = format!("{prefix}{build_hash:016x}.jsonl");
+
+    if let Err(e) = fs::create_dir_all(dir) {
+        tracing::warn!("failed to create proc-macro expansion cache directory `{dir}`: {e}");
+        return DylibCache::default();
+    }
+    for entry in fs::read_dir(dir).into_iter().flatten().filter_map(Result::ok) {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name.starts_with(&prefix) && name != file_name {
+            _ = fs::remove_file(entry.path());
+        }
+    }
+
+    let path = dir.join(file_name);
+    let mut cache = DylibCache::default();
+    if let Ok(file) = fs::File::open(&path) {
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Some((key, response)) = line.split_once(' ') else { continue };
+            if let Some(key) = decode_key(key) {
+                cache.insert(key, response.into());
+            }
+        }
+    }
+    match fs::OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(file) => {
+            let size = file.metadata().map_or(0, |it| it.len());
+            cache.log = Some(Log { path, file, size });
+            if size > MAX_PERSISTED_SIZE {
+                cache.compact();
+            }
+        }
+        Err(e) => tracing::warn!("failed to open proc-macro expansion cache `{path}`: {e}"),
+    }
+    cache
+}
+
+#[cfg(test)]
+mod tests {
+    use test_utils::TempDir;
+
+    use super::*;
+
+    #[test]
+    fn persisted_expansions() {
+        let temp_dir = TempDir::new("proc-macro-cache");
+        let dir = AbsPathBuf::assert(temp_dir.path().to_path_buf());
+        let dylib = dir.join("libmacros.so");
+        fs::write(&dylib, "v1").unwrap();
+        let request = |name: &str| Request::ListMacros { dylib_path: name.into() };
+        let key = ExpansionCache::key(1, &request("a")).unwrap();
+        assert_ne!(Some(key), ExpansionCache::key(1, &request("b")));
+        assert_ne!(Some(key), ExpansionCache::key(2, &request("a")));
+
+        let cache = ExpansionCache::new(Some(dir.join("cache")));
+        assert!(cache.get(&dylib, key).is_none());
+        cache.insert(&dylib, key, &Response::ApiVersionCheck(7));
+        assert!(matches!(cache.get(&dylib, key), Some(Response::ApiVersionCheck(7))));
+        drop(cache);
+
+        let cache = ExpansionCache::new(Some(dir.join("cache")));
+        assert!(matches!(cache.get(&dylib, key), Some(Response::ApiVersionCheck(7))));
+        drop(cache);
+
+        // A rebuilt dylib doesn't reuse the expansions of the previous build.
+        fs::write(&dylib, "v2 with a different length").unwrap();
+        let cache = ExpansionCache::new(Some(dir.join("cache")));
+        assert!(cache.get(&dylib, key).is_none());
+        assert_eq!(fs::read_dir(dir.join("cache")).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn cache_size_is_bounded() {
+        let temp_dir = TempDir::new("proc-macro-cache-bounds");
+        let dir = AbsPathBuf::assert(temp_dir.path().to_path_buf());
+        let dylib = dir.join("libmacros.so");
+        fs::write(&dylib, "v1").unwrap();
+        let request = |i: usize| Request::ListMacros { dylib_path: i.to_string().into() };
+        let response = Response::ListMacros(Err("x".repeat(1024 * 1024)));
+
+        let cache = ExpansionCache::new(Some(dir.join("cache")));
+        let first = ExpansionCache::key(1, &request(0)).unwrap();
+        for i in 0..64 {
+            cache.insert(&dylib, ExpansionCache::key(1, &request(i)).unwrap(), &response);
+            // Keep the first entry in use.
+            assert!(cache.get(&dylib, first).is_some());
+        }
+        let last = ExpansionCache::key(1, &request(63)).unwrap();
+        assert!(cache.get(&dylib, last).is_some());
+        assert!(cache.get(&dylib, ExpansionCache::key(1, &request(1)).unwrap()).is_none());
+        cache.with_dylib(&dylib, |cache| assert!(cache.size <= MAX_MEMORY_SIZE));
+        drop(cache);
+
+        let persisted_size: u64 = fs::read_dir(dir.join("cache"))
+            .unwrap()
+            .map(|entry| entry.unwrap().metadata().unwrap().len())
+            .sum();
+        assert!(persisted_size <= MAX_PERSISTED_SIZE, "{persisted_size}");
+        let cache = ExpansionCache::new(Some(dir.join("cache")));
+        assert!(cache.get(&dylib, last).is_some());
+        cache.with_dylib(&dylib, |cache| assert!(cache.size <= MAX_MEMORY_SIZE));
+    }
+}
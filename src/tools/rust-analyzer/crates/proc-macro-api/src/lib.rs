@@ -7,6 +7,7 @@
 
 #![warn(rust_2018_idioms, unused_lifetimes)]
 
+mod cache;
 pub mod msg;
 mod process;
 mod version;
@@ -18,11 +19,13 @@
 use std::{
     fmt, io,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    cache::ExpansionCache,
     msg::{
         deserialize_span_data_index_map, flat::serialize_span_data_index_map, ExpandMacro,
         ExpnGlobals, FlatTree, PanicMessage, HAS_GLOBAL_SPANS, RUST_ANALYZER_SPAN_SUPPORT,
@@ -50,6 +53,7 @@ pub struct ProcMacroServer {
     ///
     /// Therefore, we just wrap the `ProcMacroProcessSrv` in a mutex here.
     process: Arc<Mutex<ProcMacroProcessSrv>>,
+    cache: Option<Arc<ExpansionCache>>,
 }
 
 pub struct MacroDylib {
@@ -69,6 +73,7 @@ pub fn new(path: AbsPathBuf) -> MacroDylib {
 #[derive(Debug, Clone)]
 pub struct ProcMacro {
     process: Arc<Mutex<ProcMacroProcessSrv>>,
+    cache: Option<Arc<ExpansionCache>>,
     dylib_path: AbsPathBuf,
     name: String,
     kind: ProcMacroKind,
@@ -113,7 +118,42 @@ pub fn spawn(
         env: &FxHashMap<String, String>,
     ) -> io::Result<ProcMacroServer> {
         let process = ProcMacroProcessSrv::run(process_path, env)?;
-        Ok(ProcMacroServer { process: Arc::new(Mutex::new(process)) })
+        Ok(ProcMacroServer { process: Arc::new(Mutex::new(process)), cache: None })
+    }
+
+    /// Reuses the expansions of identical expansion requests of the macros loaded afterwards. If
+    /// `persist_dir` is given, expansions are also stored there to be reused by later sessions.
+    pub fn with_expansion_cache(mut self, persist_dir: Option<AbsPathBuf>) -> ProcMacroServer {
+        self.cache = Some(Arc::new(ExpansionCache::new(persist_dir)));
+        self
+    }
+
+    /// Aborts expansions that run longer than `timeout` by restarting the server, reporting them
+    /// as failed, and, if `sandbox` is set, makes the server deny the macros it runs writing to
+    /// the filesystem and using TCP sockets.
+    ///
+    /// Fails if the sandbox was requested but the server could not enable it.
+    pub fn with_expansion_limits(
+        self,
+        timeout: Option<Duration>,
+        sandbox: bool,
+    ) -> Result<ProcMacroServer, ServerError> {
+        if timeout.is_none() && !sandbox {
+            return Ok(self);
+        }
+        let sandboxed = self
+            .process
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .set_expansion_limits(timeout, sandbox)?;
+        if sandbox && !sandboxed {
+            return Err(ServerError {
+                message: "the proc-macro server could not sandbox proc-macros on this platform"
+                    .to_owned(),
+                io: None,
+            });
+        }
+        Ok(self)
     }
 
     pub fn load_dylib(&self, dylib: MacroDylib) -> Result<Vec<ProcMacro>, ServerError> {
@@ -126,6 +166,7 @@ pub fn load_dylib(&self, dylib: MacroDylib) -> Result<Vec<ProcMacro>, ServerErro
                 .into_iter()
                 .map(|(name, kind)| ProcMacro {
                     process: self.process.clone(),
+                    cache: self.cache.clone(),
                     name,
                     kind,
                     dylib_path: dylib.path.clone(),
@@ -184,11 +225,40 @@ pub fn expand(
             },
         };
 
-        let response = self
-            .process
-            .lock()
-            .unwrap_or_else(|e| e.into_inner())
-            .send_task(msg::Request::ExpandMacro(Box::new(task)))?;
+        let request = msg::Request::ExpandMacro(Box::new(task));
+        let cache_key = match &self.cache {
+            Some(_) => ExpansionCache::key(version, &request),
+            None => None,
+        };
+        let cached = self.cache.as_ref().zip(cache_key).and_then(|(cache, key)| {
+            let _p =
+                tracing::span!(tracing::Level::INFO, "ProcMacro::expand::cache_lookup").entered();
+            cache.get(&self.dylib_path, key)
+        });
+        let response = match cached {
+            Some(response) => response,
+            None => {
+                let response = match self
+                    .process
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .send_expand_task(request)?
+                {
+                    Ok(response) => response,
+                    Err(aborted) => return Ok(Err(aborted)),
+                };
+                let is_expansion = matches!(
+                    response,
+                    msg::Response::ExpandMacro(_) | msg::Response::ExpandMacroExtended(_)
+                );
+                if let Some((cache, key)) = self.cache.as_ref().zip(cache_key) {
+                    if is_expansion {
+                        cache.insert(&self.dylib_path, key, &response);
+                    }
+                }
+                response
+            }
+        };
 
         match response {
             msg::Response::ExpandMacro(it) => {
@@ -61,6 +61,10 @@ pub enum Response {
 #[serde(default)]
 pub struct ServerConfig {
     pub span_mode: SpanMode,
+    /// Deny proc-macros writing to the filesystem and opening network connections. Servers that
+    /// could not enable the sandbox respond with this unset.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub sandbox: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
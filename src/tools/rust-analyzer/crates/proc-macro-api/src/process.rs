@@ -3,7 +3,9 @@
 use std::{
     io::{self, BufRead, BufReader, Read, Write},
     process::{Child, ChildStdin, ChildStdout, Command, Stdio},
-    sync::Arc,
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
 };
 
 use paths::{AbsPath, AbsPathBuf};
@@ -11,7 +13,10 @@
 use stdx::JodChild;
 
 use crate::{
-    msg::{Message, Request, Response, SpanMode, CURRENT_API_VERSION, RUST_ANALYZER_SPAN_SUPPORT},
+    msg::{
+        Message, PanicMessage, Request, Response, ServerConfig, SpanMode, CURRENT_API_VERSION,
+        RUST_ANALYZER_SPAN_SUPPORT,
+    },
     ProcMacroKind, ServerError,
 };
 
@@ -24,6 +29,11 @@ pub(crate) struct ProcMacroProcessSrv {
     server_exited: Option<ServerError>,
     version: u32,
     mode: SpanMode,
+    process_path: AbsPathBuf,
+    env: FxHashMap<String, String>,
+    /// Expansions that don't finish within this time are aborted by restarting the server.
+    expansion_timeout: Option<Duration>,
+    sandbox: bool,
 }
 
 impl ProcMacroProcessSrv {
@@ -42,6 +52,10 @@ pub(crate) fn run(
                 server_exited: None,
                 version: 0,
                 mode: SpanMode::Id,
+                process_path: process_path.clone(),
+                env: env.clone(),
+                expansion_timeout: None,
+                sandbox: false,
             })
         };
         let mut srv = create_srv(true)?;
@@ -87,13 +101,39 @@ pub(crate) fn version_check(&mut self) -> Result<u32, ServerError> {
     }
 
     fn enable_rust_analyzer_spans(&mut self) -> Result<SpanMode, ServerError> {
-        let request = Request::SetConfig(crate::msg::ServerConfig {
-            span_mode: crate::msg::SpanMode::RustAnalyzer,
+        let request = Request::SetConfig(ServerConfig {
+            span_mode: SpanMode::RustAnalyzer,
+            ..ServerConfig::default()
         });
         let response = self.send_task(request)?;
 
         match response {
-            Response::SetConfig(crate::msg::ServerConfig { span_mode }) => Ok(span_mode),
+            Response::SetConfig(ServerConfig { span_mode, .. }) => Ok(span_mode),
+            _ => Err(ServerError { message: "unexpected response".to_owned(), io: None }),
+        }
+    }
+
+    /// Makes [`Self::send_expand_task`] abort expansions that run longer than `timeout` and
+    /// configures the server to sandbox the macros it runs. Returns whether the server enabled
+    /// the sandbox.
+    pub(crate) fn set_expansion_limits(
+        &mut self,
+        timeout: Option<Duration>,
+        sandbox: bool,
+    ) -> Result<bool, ServerError> {
+        self.expansion_timeout = timeout;
+        if !sandbox || self.version < RUST_ANALYZER_SPAN_SUPPORT {
+            // Older servers do not understand `SetConfig`.
+            return Ok(false);
+        }
+        let request = Request::SetConfig(ServerConfig { span_mode: self.mode, sandbox });
+        let response = self.send_task(request)?;
+
+        match response {
+            Response::SetConfig(ServerConfig { sandbox, .. }) => {
+                self.sandbox = sandbox;
+                Ok(sandbox)
+            }
             _ => Err(ServerError { message: "unexpected response".to_owned(), io: None }),
         }
     }
@@ -118,30 +158,102 @@ pub(crate) fn send_task(&mut self, req: Request) -> Result<Response, ServerError
         }
 
         let mut buf = String::new();
-        send_request(&mut self.stdin, &mut self.stdout, req, &mut buf).map_err(|e| {
-            if e.io.as_ref().map(|it| it.kind()) == Some(io::ErrorKind::BrokenPipe) {
-                match self.process.child.try_wait() {
-                    Ok(None) => e,
-                    Ok(Some(status)) => {
-                        let mut msg = String::new();
-                        if !status.success() {
-                            if let Some(stderr) = self.process.child.stderr.as_mut() {
-                                _ = stderr.read_to_string(&mut msg);
-                            }
-                        }
-                        let server_error = ServerError {
-                            message: format!("server exited with {status}: {msg}"),
-                            io: None,
-                        };
-                        self.server_exited = Some(server_error.clone());
-                        server_error
+        send_request(&mut self.stdin, &mut self.stdout, req, &mut buf)
+            .map_err(|e| self.check_exited(e))
+    }
+
+    /// Sends an expansion request, aborting it if it runs longer than the expansion timeout.
+    ///
+    /// A proc-macro can't be interrupted while it runs, so an aborted expansion kills the whole
+    /// server, and a new one is spawned in its place. Aborted expansions are reported as an
+    /// `Err` with the reason.
+    pub(crate) fn send_expand_task(
+        &mut self,
+        req: Request,
+    ) -> Result<Result<Response, PanicMessage>, ServerError> {
+        let Some(timeout) = self.expansion_timeout else {
+            return self.send_task(req).map(Ok);
+        };
+        if let Some(server_error) = &self.server_exited {
+            return Err(server_error.clone());
+        }
+
+        let (finished, finished_receiver) = mpsc::channel::<()>();
+        let ProcMacroProcessSrv { process, stdin, stdout, .. } = self;
+        let (res, timed_out) = thread::scope(|s| {
+            let watchdog = s.spawn(move || {
+                // `finished` is dropped once the response has been read.
+                let timed_out =
+                    finished_receiver.recv_timeout(timeout) == Err(mpsc::RecvTimeoutError::Timeout);
+                if timed_out {
+                    // This closes the server's stdout, so reading the response fails.
+                    _ = process.child.kill();
+                }
+                timed_out
+            });
+            let mut buf = String::new();
+            let res = send_request(stdin, stdout, req, &mut buf);
+            drop(finished);
+            let timed_out = watchdog.join().unwrap_or_else(|e| std::panic::resume_unwind(e));
+            (res, timed_out)
+        });
+
+        if timed_out {
+            self.restart()?;
+            // The response may have arrived just before the server was killed.
+            if res.is_err() {
+                return Ok(Err(PanicMessage(format!(
+                    "proc-macro expansion did not finish within {}s and was aborted",
+                    timeout.as_secs_f32()
+                ))));
+            }
+        }
+        res.map(Ok).map_err(|e| self.check_exited(e))
+    }
+
+    /// Replaces the server with a newly spawned one with the same configuration.
+    fn restart(&mut self) -> Result<(), ServerError> {
+        tracing::info!("restarting the proc-macro server");
+        let mut srv =
+            ProcMacroProcessSrv::run(self.process_path.clone(), &self.env).map_err(|err| {
+                ServerError {
+                    message: "failed to restart the proc-macro server".to_owned(),
+                    io: Some(Arc::new(err)),
+                }
+            })?;
+        let sandboxed = srv.set_expansion_limits(self.expansion_timeout, self.sandbox)?;
+        if sandboxed != self.sandbox {
+            return Err(ServerError {
+                message: "the restarted proc-macro server could not sandbox proc-macros".to_owned(),
+                io: None,
+            });
+        }
+        *self = srv;
+        Ok(())
+    }
+
+    /// Records that the server exited if that is why `e` happened.
+    fn check_exited(&mut self, e: ServerError) -> ServerError {
+        if e.io.as_ref().map(|it| it.kind()) != Some(io::ErrorKind::BrokenPipe) {
+            return e;
+        }
+        match self.process.child.try_wait() {
+            Ok(None) | Err(_) => e,
+            Ok(Some(status)) => {
+                let mut msg = String::new();
+                if !status.success() {
+                    if let Some(stderr) = self.process.child.stderr.as_mut() {
+                        _ = stderr.read_to_string(&mut msg);
                     }
-                    Err(_) => e,
                 }
-            } else {
-                e
+                let server_error = ServerError {
+                    message: format!("server exited with {status}: {msg}"),
+                    io: None,
+                };
+                self.server_exited = Some(server_error.clone());
+                server_error
             }
-        })
+        }
     }
 }
 
@@ -60,8 +60,16 @@ fn run() -> io::Result<()> {
             msg::Request::ApiVersionCheck {} => {
                 msg::Response::ApiVersionCheck(proc_macro_api::msg::CURRENT_API_VERSION)
             }
-            msg::Request::SetConfig(config) => {
+            msg::Request::SetConfig(mut config) => {
                 srv.set_span_mode(config.span_mode);
+                if config.sandbox {
+                    // Expansions run on threads spawned from this one, so they inherit the
+                    // sandbox.
+                    if let Err(err) = proc_macro_srv::sandbox::enable() {
+                        eprintln!("Failed to sandbox proc-macros: {err}");
+                        config.sandbox = false;
+                    }
+                }
                 msg::Response::SetConfig(config)
             }
         };
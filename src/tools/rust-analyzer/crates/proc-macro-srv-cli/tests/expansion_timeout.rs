@@ -0,0 +1,41 @@
+//! Tests that proc-macro expansions running past the timeout are aborted by restarting the server.
+#![cfg(any(feature = "sysroot-abi", rust_analyzer))]
+
+use std::time::Duration;
+
+use paths::AbsPathBuf;
+use proc_macro_api::{MacroDylib, ProcMacroServer};
+use span::{FileId, Span, SpanAnchor, SyntaxContextId, TextRange, ROOT_ERASED_FILE_AST_ID};
+
+#[test]
+fn expansions_exceeding_the_timeout_are_aborted() {
+    let server = ProcMacroServer::spawn(
+        AbsPathBuf::assert(env!("CARGO_BIN_EXE_rust-analyzer-proc-macro-srv").into()),
+        &Default::default(),
+    )
+    .unwrap()
+    .with_expansion_limits(Some(Duration::from_secs(1)), false)
+    .unwrap();
+    let macros = server
+        .load_dylib(MacroDylib::new(AbsPathBuf::assert(
+            proc_macro_test::PROC_MACRO_TEST_LOCATION.into(),
+        )))
+        .unwrap();
+
+    let span = Span {
+        range: TextRange::empty(0.into()),
+        anchor: SpanAnchor { file_id: FileId::from_raw(0), ast_id: ROOT_ERASED_FILE_AST_ID },
+        ctx: SyntaxContextId::ROOT,
+    };
+    let input = tt::Subtree::empty(tt::DelimSpan { open: span, close: span });
+    let expand = |name: &str| {
+        let proc_macro = macros.iter().find(|it| it.name() == name).unwrap();
+        proc_macro.expand(&input, None, Vec::new(), span, span, span).unwrap()
+    };
+
+    let err = expand("fn_like_loop").unwrap_err();
+    assert_eq!(err.0, "proc-macro expansion did not finish within 1s and was aborted");
+
+    // The restarted server keeps expanding macros.
+    assert!(expand("fn_like_noop").is_ok());
+}
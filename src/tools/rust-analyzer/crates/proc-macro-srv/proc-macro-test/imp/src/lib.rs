@@ -21,6 +21,13 @@ pub fn fn_like_error(args: TokenStream) -> TokenStream {
     format!("compile_error!(\"fn_like_error!({})\");", args).parse().unwrap()
 }
 
+#[proc_macro]
+pub fn fn_like_loop(_args: TokenStream) -> TokenStream {
+    loop {
+        std::hint::spin_loop();
+    }
+}
+
 #[proc_macro]
 pub fn fn_like_clone_tokens(args: TokenStream) -> TokenStream {
     clone_stream(args)
@@ -27,6 +27,7 @@
 
 mod dylib;
 mod proc_macros;
+pub mod sandbox;
 mod server;
 
 use std::{
@@ -0,0 +1,133 @@
+//! Restricts what the proc-macros run by this process may do.
+//!
+//! On Linux this uses [Landlock](https://docs.kernel.org/userspace-api/landlock.html) to deny
+//! writing to the filesystem and, on kernels that support it, binding and connecting TCP
+//! sockets. Reading files stays allowed, as many macros legitimately read their inputs (for
+//! example `include_str!`-like macros or `sqlx`'s offline data). The restrictions apply to the
+//! calling thread and all threads and processes it spawns afterwards, and cannot be lifted again.
+//!
+//! Landlock does not restrict anything else: macros can still run other programs (which inherit
+//! the restrictions), use UDP and other non-TCP sockets, and, on kernels older than 6.7, TCP.
+
+/// Enables the sandbox for the current thread, returning why it could not be enabled otherwise.
+pub fn enable() -> Result<(), String> {
+    imp::enable()
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::io;
+
+    const LANDLOCK_CREATE_RULESET_VERSION: u32 = 1 << 0;
+
+    const LANDLOCK_ACCESS_FS_WRITE_FILE: u64 = 1 << 1;
+    const LANDLOCK_ACCESS_FS_REMOVE_DIR: u64 = 1 << 4;
+    const LANDLOCK_ACCESS_FS_REMOVE_FILE: u64 = 1 << 5;
+    const LANDLOCK_ACCESS_FS_MAKE_CHAR: u64 = 1 << 6;
+    const LANDLOCK_ACCESS_FS_MAKE_DIR: u64 = 1 << 7;
+    const LANDLOCK_ACCESS_FS_MAKE_REG: u64 = 1 << 8;
+    const LANDLOCK_ACCESS_FS_MAKE_SOCK: u64 = 1 << 9;
+    const LANDLOCK_ACCESS_FS_MAKE_FIFO: u64 = 1 << 10;
+    const LANDLOCK_ACCESS_FS_MAKE_BLOCK: u64 = 1 << 11;
+    const LANDLOCK_ACCESS_FS_MAKE_SYM: u64 = 1 << 12;
+    /// Since ABI version 2.
+    const LANDLOCK_ACCESS_FS_REFER: u64 = 1 << 13;
+    /// Since ABI version 3.
+    const LANDLOCK_ACCESS_FS_TRUNCATE: u64 = 1 << 14;
+
+    /// Since ABI version 4.
+    const LANDLOCK_ACCESS_NET_BIND_TCP: u64 = 1 << 0;
+    /// Since ABI version 4.
+    const LANDLOCK_ACCESS_NET_CONNECT_TCP: u64 = 1 << 1;
+
+    #[repr(C)]
+    struct RulesetAttr {
+        handled_access_fs: u64,
+        handled_access_net: u64,
+    }
+
+    pub(super) fn enable() -> Result<(), String> {
+        // SAFETY: querying the ABI version takes no pointers.
+        let abi = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<RulesetAttr>(),
+                0usize,
+                LANDLOCK_CREATE_RULESET_VERSION,
+            )
+        };
+        if abi < 1 {
+            return Err(format!(
+                "Landlock is not supported by this kernel: {}",
+                io::Error::last_os_error()
+            ));
+        }
+
+        let mut handled_access_fs = LANDLOCK_ACCESS_FS_WRITE_FILE
+            | LANDLOCK_ACCESS_FS_REMOVE_DIR
+            | LANDLOCK_ACCESS_FS_REMOVE_FILE
+            | LANDLOCK_ACCESS_FS_MAKE_CHAR
+            | LANDLOCK_ACCESS_FS_MAKE_DIR
+            | LANDLOCK_ACCESS_FS_MAKE_REG
+            | LANDLOCK_ACCESS_FS_MAKE_SOCK
+            | LANDLOCK_ACCESS_FS_MAKE_FIFO
+            | LANDLOCK_ACCESS_FS_MAKE_BLOCK
+            | LANDLOCK_ACCESS_FS_MAKE_SYM;
+        if abi >= 2 {
+            handled_access_fs |= LANDLOCK_ACCESS_FS_REFER;
+        }
+        if abi >= 3 {
+            handled_access_fs |= LANDLOCK_ACCESS_FS_TRUNCATE;
+        }
+        let handled_access_net = if abi >= 4 {
+            LANDLOCK_ACCESS_NET_BIND_TCP | LANDLOCK_ACCESS_NET_CONNECT_TCP
+        } else {
+            0
+        };
+        // Kernels before ABI version 4 only know about `handled_access_fs`, so don't pass them
+        // the net field at all.
+        let attr_size =
+            if abi >= 4 { std::mem::size_of::<RulesetAttr>() } else { std::mem::size_of::<u64>() };
+        let attr = RulesetAttr { handled_access_fs, handled_access_net };
+
+        // SAFETY: `attr` is a valid `landlock_ruleset_attr` of at least `attr_size` bytes.
+        let ruleset_fd = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                &attr as *const RulesetAttr,
+                attr_size,
+                0u32,
+            )
+        };
+        if ruleset_fd < 0 {
+            return Err(format!(
+                "failed to create a Landlock ruleset: {}",
+                io::Error::last_os_error()
+            ));
+        }
+        let ruleset_fd = ruleset_fd as libc::c_int;
+
+        // The ruleset has no rules, so everything it handles is denied everywhere.
+        // SAFETY: plain syscalls on integer arguments; `ruleset_fd` is owned by us.
+        let res = unsafe {
+            if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+                Err("failed to set `no_new_privs`")
+            } else if libc::syscall(libc::SYS_landlock_restrict_self, ruleset_fd, 0u32) != 0 {
+                Err("failed to restrict the process with Landlock")
+            } else {
+                Ok(())
+            }
+        };
+        let res = res.map_err(|msg| format!("{msg}: {}", io::Error::last_os_error()));
+        // SAFETY: `ruleset_fd` is a file descriptor we own and don't use afterwards.
+        unsafe { libc::close(ruleset_fd) };
+        res
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod imp {
+    pub(super) fn enable() -> Result<(), String> {
+        Err("sandboxing proc-macros is only supported on Linux".to_owned())
+    }
+}
@@ -240,6 +240,7 @@ fn list_test_macros() {
         fn_like_noop [FuncLike]
         fn_like_panic [FuncLike]
         fn_like_error [FuncLike]
+        fn_like_loop [FuncLike]
         fn_like_clone_tokens [FuncLike]
         fn_like_mk_literals [FuncLike]
         fn_like_mk_idents [FuncLike]
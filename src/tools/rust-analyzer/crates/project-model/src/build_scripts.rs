@@ -8,6 +8,8 @@
 
 use std::{
     cell::RefCell,
+    fs,
+    hash::{Hash, Hasher},
     io, mem,
     path::{self, PathBuf},
     process::Command,
@@ -17,14 +19,14 @@
 use itertools::Itertools;
 use la_arena::ArenaMap;
 use paths::{AbsPath, AbsPathBuf};
-use rustc_hash::{FxHashMap, FxHashSet};
+use rustc_hash::{FxHashMap, FxHashSet, FxHasher};
 use semver::Version;
 use serde::Deserialize;
 use toolchain::Tool;
 
 use crate::{
     cfg_flag::CfgFlag, utf8_stdout, CargoConfig, CargoFeatures, CargoWorkspace, InvocationLocation,
-    InvocationStrategy, Package, Sysroot, TargetKind,
+    InvocationStrategy, Package, PackageData, Sysroot, TargetKind,
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -46,6 +48,13 @@ pub(crate) struct BuildScriptOutput {
     pub(crate) out_dir: Option<AbsPathBuf>,
     /// Path to the proc-macro library file if this package exposes proc-macros.
     pub(crate) proc_macro_dylib_path: Option<AbsPathBuf>,
+    /// Files the build script declared as its inputs with `cargo:rerun-if-changed`, with
+    /// directories expanded to the files they contained when it ran.
+    pub(crate) rerun_if_changed: Vec<AbsPathBuf>,
+    /// Env vars the build script declared as its inputs with `cargo:rerun-if-env-changed`.
+    pub(crate) rerun_if_env_changed: Vec<String>,
+    /// Hash of the build script's inputs when it ran.
+    pub(crate) inputs_fingerprint: u64,
 }
 
 impl BuildScriptOutput {
@@ -54,6 +63,67 @@ fn is_unchanged(&self) -> bool {
             && self.envs.is_empty()
             && self.out_dir.is_none()
             && self.proc_macro_dylib_path.is_none()
+            && self.rerun_if_changed.is_empty()
+            && self.rerun_if_env_changed.is_empty()
+    }
+
+    /// Reads the `rerun-if-changed` and `rerun-if-env-changed` directives of the build script
+    /// from its `output` file next to `out_dir`.
+    pub(crate) fn read_rerun_directives(
+        &mut self,
+        package_root: &AbsPath,
+        out_dir: &AbsPath,
+        extra_env: &FxHashMap<String, String>,
+    ) {
+        let Some(output) =
+            out_dir.parent().and_then(|it| fs::read_to_string(it.join("output")).ok())
+        else {
+            return;
+        };
+        for line in output.lines() {
+            let directive = line.strip_prefix("cargo::").or_else(|| line.strip_prefix("cargo:"));
+            match directive.and_then(|it| it.split_once('=')) {
+                Some(("rerun-if-changed", path)) => {
+                    push_files(package_root.join(path), &mut self.rerun_if_changed)
+                }
+                Some(("rerun-if-env-changed", var)) => {
+                    self.rerun_if_env_changed.push(var.to_owned())
+                }
+                _ => {}
+            }
+        }
+        self.inputs_fingerprint = self.current_inputs_fingerprint(extra_env);
+    }
+
+    /// Hashes the contents of the input files and the values of the input env vars, as a build
+    /// script run with `extra_env` would see them.
+    pub(crate) fn current_inputs_fingerprint(&self, extra_env: &FxHashMap<String, String>) -> u64 {
+        let mut hasher = FxHasher::default();
+        for file in &self.rerun_if_changed {
+            fs::read(file).ok().hash(&mut hasher);
+        }
+        for var in &self.rerun_if_env_changed {
+            extra_env.get(var).cloned().or_else(|| std::env::var(var).ok()).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+/// Pushes `path`, or the files below it if it is a directory, to `acc`.
+fn push_files(path: AbsPathBuf, acc: &mut Vec<AbsPathBuf>) {
+    if !fs::metadata(&path).is_ok_and(|it| it.is_dir()) {
+        acc.push(path);
+        return;
+    }
+    let Ok(entries) = fs::read_dir(&path) else { return };
+    for entry in entries.filter_map(Result::ok) {
+        let path = AbsPathBuf::assert(entry.path());
+        // Don't follow symlinks to directories, they may form cycles.
+        match entry.file_type() {
+            Ok(it) if it.is_dir() => push_files(path, acc),
+            Ok(it) if it.is_symlink() => {}
+            _ => acc.push(path),
+        }
     }
 }
 
@@ -63,6 +133,7 @@ fn build_command(
         allowed_features: &FxHashSet<String>,
         workspace_root: &AbsPathBuf,
         sysroot: Option<&Sysroot>,
+        packages: &[String],
     ) -> io::Result<Command> {
         let mut cmd = match config.run_build_script_command.as_deref() {
             Some([program, args @ ..]) => {
@@ -74,7 +145,13 @@ fn build_command(
                 let mut cmd = Command::new(Tool::Cargo.path());
                 Sysroot::set_rustup_toolchain_env(&mut cmd, sysroot);
 
-                cmd.args(["check", "--quiet", "--workspace", "--message-format=json"]);
+                cmd.args(["check", "--quiet", "--message-format=json"]);
+                if packages.is_empty() {
+                    cmd.arg("--workspace");
+                }
+                for package in packages {
+                    cmd.args(["--package", package]);
+                }
                 cmd.args(&config.extra_args);
 
                 cmd.arg("--manifest-path");
@@ -130,13 +207,15 @@ fn build_command(
         Ok(cmd)
     }
 
-    /// Runs the build scripts for the given workspace
+    /// Runs the build scripts for the given workspace, or only for the given package specs and
+    /// their dependencies if there are any.
     pub(crate) fn run_for_workspace(
         config: &CargoConfig,
         workspace: &CargoWorkspace,
         progress: &dyn Fn(String),
         toolchain: &Option<Version>,
         sysroot: Option<&Sysroot>,
+        packages: &[String],
     ) -> io::Result<WorkspaceBuildScripts> {
         const RUST_1_62: Version = Version::new(1, 62, 0);
 
@@ -156,6 +235,7 @@ pub(crate) fn run_for_workspace(
                 &allowed_features,
                 &workspace.workspace_root().to_path_buf(),
                 sysroot,
+                packages,
             )?,
             workspace,
             current_dir,
@@ -171,6 +251,7 @@ pub(crate) fn run_for_workspace(
                     &allowed_features,
                     &workspace.workspace_root().to_path_buf(),
                     sysroot,
+                    packages,
                 )?;
                 cmd.args(["-Z", "unstable-options", "--keep-going"]).env("RUSTC_BOOTSTRAP", "1");
                 let mut res = Self::run_per_ws(cmd, workspace, current_dir, progress)?;
@@ -182,12 +263,15 @@ pub(crate) fn run_for_workspace(
     }
 
     /// Runs the build scripts by invoking the configured command *once*.
-    /// This populates the outputs for all passed in workspaces.
+    /// This populates the outputs for all passed in workspaces. If they were loaded for one of
+    /// the extra targets, the command is told to build for `extra_target` via
+    /// `CARGO_BUILD_TARGET`, as the configured command doesn't take a `--target` argument.
     pub(crate) fn run_once(
         config: &CargoConfig,
         workspaces: &[&CargoWorkspace],
         progress: &dyn Fn(String),
         workspace_root: &AbsPathBuf,
+        extra_target: Option<&str>,
     ) -> io::Result<Vec<WorkspaceBuildScripts>> {
         assert_eq!(config.invocation_strategy, InvocationStrategy::Once);
 
@@ -200,7 +284,10 @@ pub(crate) fn run_once(
                 ))
             }
         };
-        let cmd = Self::build_command(config, &Default::default(), workspace_root, None)?;
+        let mut cmd = Self::build_command(config, &Default::default(), workspace_root, None, &[])?;
+        if let Some(target) = extra_target {
+            cmd.env("CARGO_BUILD_TARGET", target);
+        }
         // NB: Cargo.toml could have been modified between `cargo metadata` and
         // `cargo check`. We shouldn't assume that package ids we see here are
         // exactly those from `config`.
@@ -230,7 +317,7 @@ pub(crate) fn run_once(
             current_dir.as_path().as_ref(),
             |package, cb| {
                 if let Some(&(package, workspace)) = by_id.get(package) {
-                    cb(&workspaces[workspace][package].name, &mut res[workspace].outputs[package]);
+                    cb(&workspaces[workspace][package], &mut res[workspace].outputs[package]);
                 }
             },
             progress,
@@ -281,7 +368,7 @@ fn run_per_ws(
             current_dir,
             |package, cb| {
                 if let Some(&package) = by_id.get(package) {
-                    cb(&workspace[package].name, &mut outputs[package]);
+                    cb(&workspace[package], &mut outputs[package]);
                 }
             },
             progress,
@@ -306,9 +393,9 @@ fn run_command(
         mut cmd: Command,
         current_dir: &path::Path,
         // ideally this would be something like:
-        // with_output_for: impl FnMut(&str, dyn FnOnce(&mut BuildScriptOutput)),
+        // with_output_for: impl FnMut(&str, dyn FnOnce(&PackageData, &mut BuildScriptOutput)),
         // but owned trait objects aren't a thing
-        mut with_output_for: impl FnMut(&str, &mut dyn FnMut(&str, &mut BuildScriptOutput)),
+        mut with_output_for: impl FnMut(&str, &mut dyn FnMut(&PackageData, &mut BuildScriptOutput)),
         progress: &dyn Fn(String),
     ) -> io::Result<Option<String>> {
         let errors = RefCell::new(String::new());
@@ -318,6 +405,12 @@ fn run_command(
             e.push('\n');
         };
 
+        let extra_env: FxHashMap<String, String> = cmd
+            .get_envs()
+            .filter_map(|(key, value)| {
+                Some((key.to_str()?.to_owned(), value?.to_str()?.to_owned()))
+            })
+            .collect();
         tracing::info!("Running build scripts in {}: {:?}", current_dir.display(), cmd);
         cmd.current_dir(current_dir);
         let output = stdx::process::spawn_with_streaming_output(
@@ -332,8 +425,8 @@ fn run_command(
 
                 match message {
                     Message::BuildScriptExecuted(mut message) => {
-                        with_output_for(&message.package_id.repr, &mut |name, data| {
-                            progress(format!("running build-script: {name}"));
+                        with_output_for(&message.package_id.repr, &mut |package, data| {
+                            progress(format!("running build-script: {}", package.name));
                             let cfgs = {
                                 let mut acc = Vec::new();
                                 for cfg in &message.cfgs {
@@ -364,14 +457,19 @@ fn run_command(
                                 {
                                     data.envs.push(("OUT_DIR".to_owned(), out_dir));
                                 }
+                                data.read_rerun_directives(
+                                    package.manifest.parent(),
+                                    &out_dir,
+                                    &extra_env,
+                                );
                                 data.out_dir = Some(out_dir);
                                 data.cfgs = cfgs;
                             }
                         });
                     }
                     Message::CompilerArtifact(message) => {
-                        with_output_for(&message.package_id.repr, &mut |name, data| {
-                            progress(format!("building proc-macros: {name}"));
+                        with_output_for(&message.package_id.repr, &mut |package, data| {
+                            progress(format!("building proc-macros: {}", package.name));
                             if message.target.kind.iter().any(|k| k == "proc-macro") {
                                 // Skip rmeta file
                                 if let Some(filename) =
@@ -417,6 +515,41 @@ pub(crate) fn get_output(&self, idx: Package) -> Option<&BuildScriptOutput> {
         self.outputs.get(idx)
     }
 
+    /// Overwrites the outputs of the packages `other` has build data for.
+    pub(crate) fn merge(&mut self, other: WorkspaceBuildScripts) {
+        for (package, output) in other.outputs.into_iter() {
+            if !output.is_unchanged() {
+                self.outputs.insert(package, output);
+            }
+        }
+        self.error = other.error;
+    }
+
+    /// Returns the packages whose build scripts read one of the files `is_changed` accepts, and
+    /// whose inputs no longer are what they were when the build scripts ran.
+    pub(crate) fn stale_packages(
+        &self,
+        is_changed: &dyn Fn(&AbsPath) -> bool,
+        extra_env: &FxHashMap<String, String>,
+    ) -> Vec<Package> {
+        self.outputs
+            .iter()
+            .filter(|(_, output)| {
+                !output.rerun_if_env_changed.is_empty()
+                    || output.rerun_if_changed.iter().any(|it| is_changed(it))
+            })
+            .filter(|(_, output)| {
+                output.current_inputs_fingerprint(extra_env) != output.inputs_fingerprint
+            })
+            .map(|(package, _)| package)
+            .collect()
+    }
+
+    /// Returns the input files of all build scripts.
+    pub(crate) fn inputs(&self) -> impl Iterator<Item = &AbsPath> + '_ {
+        self.outputs.values().flat_map(|it| &it.rerun_if_changed).map(|it| &**it)
+    }
+
     pub(crate) fn rustc_crates(
         rustc: &CargoWorkspace,
         current_dir: &AbsPath,
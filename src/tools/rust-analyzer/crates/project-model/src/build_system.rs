@@ -0,0 +1,559 @@
+//! Projects built with build systems other than cargo.
+//!
+//! Buck2 and Bazel projects can describe themselves to rust-analyzer with a generated
+//! `rust-project.json`, but then somebody has to keep that file up to date. Instead, this module
+//! asks the build system for the crate graph directly:
+//!
+//! * For Buck2, we run `buck2 cquery` for the attributes of the Rust targets, and resolve their
+//!   sources with the cell paths from `buck2 audit cell`. Projects with their own way to describe
+//!   themselves can configure a BXL script printing a `rust-project.json` instead.
+//! * For Bazel, we run `bazel aquery` for the `Rustc` actions and read the crates, their
+//!   dependencies, cfgs and env vars off the rustc command lines.
+//!
+//! Either way, the result is a [`ProjectJson`], which is lowered to a crate graph like any other.
+//! Its build files are the `BUILD`/`BUCK` files of the workspace crates, so the project is
+//! queried again when one of those changes.
+
+use std::{fs, process::Command};
+
+use anyhow::{format_err, Context};
+use paths::{AbsPath, AbsPathBuf};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{utf8_stdout, ManifestPath, ProjectJson, ProjectJsonData};
+
+/// A build system we know how to query for a crate graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum BuildSystem {
+    Buck2,
+    Bazel,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BuildSystemConfig {
+    /// Target patterns to load, all targets of the project if empty.
+    pub targets: Vec<String>,
+    /// The BXL script printing a `rust-project.json` for the given `--targets`, used instead of
+    /// `buck2 cquery` if set.
+    pub buck2_bxl_script: Option<String>,
+}
+
+/// Names of the files declaring targets, a change to one of them may change the crate graph.
+const BUILD_FILE_NAMES: &[&str] = &["BUILD.bazel", "BUILD", "BUCK", "TARGETS"];
+
+impl BuildSystem {
+    /// Files marking the root of a project, in order of preference.
+    pub(crate) const ROOT_MARKERS: &'static [(&'static str, BuildSystem)] = &[
+        (".buckconfig", BuildSystem::Buck2),
+        ("MODULE.bazel", BuildSystem::Bazel),
+        ("WORKSPACE.bazel", BuildSystem::Bazel),
+        ("WORKSPACE", BuildSystem::Bazel),
+    ];
+
+    pub(crate) fn from_root_marker(file_name: &str) -> Option<BuildSystem> {
+        BuildSystem::ROOT_MARKERS.iter().find(|(it, _)| *it == file_name).map(|&(_, it)| it)
+    }
+
+    pub(crate) fn load(
+        self,
+        root_marker: &ManifestPath,
+        config: &BuildSystemConfig,
+        extra_env: &FxHashMap<String, String>,
+    ) -> anyhow::Result<ProjectJson> {
+        let root = root_marker.parent();
+        let targets = if config.targets.is_empty() {
+            vec!["//...".to_owned()]
+        } else {
+            config.targets.clone()
+        };
+        let data: ProjectJsonData = match self {
+            BuildSystem::Buck2 => {
+                let buck2 = |args: &[&str]| {
+                    let mut cmd = Command::new("buck2");
+                    cmd.current_dir(root).envs(extra_env).args(args);
+                    utf8_stdout(cmd)
+                };
+                match &config.buck2_bxl_script {
+                    Some(script) => {
+                        let mut args = vec!["bxl", script, "--", "--targets"];
+                        args.extend(targets.iter().map(String::as_str));
+                        let stdout = buck2(&args)?;
+                        serde_json::from_str(&stdout).with_context(|| {
+                            format!("Failed to deserialize the output of {script}")
+                        })?
+                    }
+                    None => {
+                        let cells: FxHashMap<String, String> =
+                            serde_json::from_str(&buck2(&["audit", "cell", "--json"])?)
+                                .context("Failed to deserialize the output of buck2 audit cell")?;
+                        let cells = cells
+                            .into_iter()
+                            .filter_map(|(cell, path)| {
+                                Some((cell, AbsPathBuf::try_from(path.as_str()).ok()?))
+                            })
+                            .collect();
+                        let query = format!(
+                            "kind('^rust_(library|binary|test)$', deps(set({})))",
+                            targets.join(" ")
+                        );
+                        let stdout = buck2(&[
+                            "cquery",
+                            "--json",
+                            "--output-attribute",
+                            CQUERY_ATTRIBUTES,
+                            &query,
+                        ])?;
+                        let cquery: FxHashMap<String, CqueryTarget> = serde_json::from_str(&stdout)
+                            .context("Failed to deserialize the output of buck2 cquery")?;
+                        serde_json::from_value(cquery_to_project_json(&cquery, &cells, root))?
+                    }
+                }
+            }
+            BuildSystem::Bazel => {
+                let bazel = |args: &[&str]| {
+                    let mut cmd = Command::new("bazel");
+                    cmd.current_dir(root).envs(extra_env).args(args);
+                    utf8_stdout(cmd)
+                };
+                let execution_root = bazel(&["info", "execution_root"])?;
+                let execution_root =
+                    AbsPathBuf::try_from(execution_root.as_str()).map_err(|it| {
+                        format_err!("bazel returned a relative execution root: {}", it.display())
+                    })?;
+                let query = format!("mnemonic(\"Rustc\", deps({}))", targets.join(" + "));
+                let stdout = bazel(&["aquery", "--output=jsonproto", &query])?;
+                let aquery: AqueryOutput = serde_json::from_str(&stdout)
+                    .context("Failed to deserialize the output of bazel aquery")?;
+                serde_json::from_value(aquery_to_project_json(&aquery, root, &execution_root))?
+            }
+        };
+
+        let mut project = ProjectJson::new(root, data);
+        project.manifest = Some(root_marker.to_path_buf());
+        let build_files = find_build_files(root, &project);
+        project.build_files.extend(build_files);
+        Ok(project)
+    }
+}
+
+/// Returns the closest build file above each crate root module of the workspace.
+fn find_build_files(root: &AbsPath, project: &ProjectJson) -> Vec<AbsPathBuf> {
+    let mut res = FxHashSet::default();
+    for (_, krate) in project.crates() {
+        if !krate.is_workspace_member {
+            continue;
+        }
+        let mut dir = krate.root_module.parent();
+        while let Some(it) = dir.filter(|it| it.starts_with(root)) {
+            let build_file = BUILD_FILE_NAMES
+                .iter()
+                .map(|name| it.join(name))
+                .find(|path| fs::metadata(path).is_ok());
+            if let Some(build_file) = build_file {
+                res.insert(build_file);
+                break;
+            }
+            dir = it.parent();
+        }
+    }
+    let mut res: Vec<_> = res.into_iter().collect();
+    res.sort();
+    res
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AqueryOutput {
+    #[serde(default)]
+    artifacts: Vec<Artifact>,
+    #[serde(default)]
+    actions: Vec<Action>,
+    #[serde(default)]
+    path_fragments: Vec<PathFragment>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Artifact {
+    id: u32,
+    path_fragment_id: u32,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Action {
+    mnemonic: String,
+    #[serde(default)]
+    arguments: Vec<String>,
+    #[serde(default)]
+    environment_variables: Vec<KeyValue>,
+    #[serde(default)]
+    output_ids: Vec<u32>,
+}
+
+#[derive(Deserialize, Debug)]
+struct KeyValue {
+    key: String,
+    #[serde(default)]
+    value: String,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct PathFragment {
+    id: u32,
+    label: String,
+    /// `0` for fragments without a parent, as protobuf's JSON omits default values.
+    #[serde(default)]
+    parent_id: u32,
+}
+
+/// The parts of a rustc command line that make up a crate in the crate graph.
+#[derive(Debug, Default)]
+struct RustcInvocation<'a> {
+    rustc: Option<&'a str>,
+    crate_name: Option<&'a str>,
+    crate_type: Option<&'a str>,
+    edition: Option<&'a str>,
+    target: Option<&'a str>,
+    root_module: Option<&'a str>,
+    cfg: Vec<&'a str>,
+    externs: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> RustcInvocation<'a> {
+    /// Flags whose value may be passed as the next argument, and which we otherwise ignore.
+    const FLAGS_WITH_VALUE: &'static [&'static str] = &[
+        "-A",
+        "-C",
+        "-D",
+        "-F",
+        "-L",
+        "-W",
+        "-l",
+        "-o",
+        "--cap-lints",
+        "--codegen",
+        "--color",
+        "--emit",
+        "--error-format",
+        "--json",
+        "--out-dir",
+        "--remap-path-prefix",
+        "--sysroot",
+    ];
+
+    fn parse(arguments: &'a [String]) -> RustcInvocation<'a> {
+        // rules_rust runs rustc through a process wrapper, whose own arguments end at `--`.
+        let arguments = match arguments.iter().position(|it| it == "--") {
+            Some(idx) => &arguments[idx + 1..],
+            None => arguments,
+        };
+        let mut res = RustcInvocation::default();
+        let mut args = arguments.iter().map(String::as_str);
+        res.rustc = args.next();
+        while let Some(arg) = args.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with('-') => (flag, Some(value)),
+                _ => (arg, None),
+            };
+            let mut value = || inline_value.or_else(|| args.next());
+            match flag {
+                "--crate-name" => res.crate_name = value(),
+                "--crate-type" => res.crate_type = value(),
+                "--edition" => res.edition = value(),
+                "--target" => res.target = value(),
+                "--cfg" => res.cfg.extend(value()),
+                "--extern" => res.externs.extend(value().and_then(|it| it.split_once('='))),
+                _ if Self::FLAGS_WITH_VALUE.contains(&flag) => {
+                    value();
+                }
+                _ if !arg.starts_with('-') && arg.ends_with(".rs") => {
+                    res.root_module.get_or_insert(arg);
+                }
+                _ => {}
+            }
+        }
+        res
+    }
+}
+
+/// What makes two `Rustc` actions build the same crate.
+#[derive(PartialEq, Eq, Hash)]
+struct CrateKey<'a> {
+    crate_name: &'a str,
+    root_module: &'a str,
+    crate_type: &'a str,
+    cfg: Vec<&'a str>,
+    externs: Vec<(&'a str, &'a str)>,
+}
+
+/// Strips the configuration directory off an output path, like `bazel-out/k8-fastbuild/`, so
+/// that the outputs of one target in different configurations compare equal.
+fn without_configuration(path: &str) -> &str {
+    match path.strip_prefix("bazel-out/") {
+        Some(rest) => rest.split_once('/').map_or(rest, |(_, it)| it),
+        None => path,
+    }
+}
+
+/// Lowers the `Rustc` actions of a `bazel aquery` to the `rust-project.json` format.
+///
+/// Paths in the actions are relative to the execution root. Sources of the workspace itself are
+/// mapped back to the workspace, so that they are the files the user edits, while generated files
+/// and external repositories stay in the execution root.
+pub(crate) fn aquery_to_project_json(
+    aquery: &AqueryOutput,
+    workspace_root: &AbsPath,
+    execution_root: &AbsPath,
+) -> Value {
+    let fragments: FxHashMap<u32, &PathFragment> =
+        aquery.path_fragments.iter().map(|it| (it.id, it)).collect();
+    let fragment_path = |mut id: u32| {
+        let mut components = Vec::new();
+        while let Some(fragment) = fragments.get(&id) {
+            components.push(fragment.label.as_str());
+            id = fragment.parent_id;
+        }
+        components.reverse();
+        components.join("/")
+    };
+    let artifact_paths: FxHashMap<u32, String> =
+        aquery.artifacts.iter().map(|it| (it.id, fragment_path(it.path_fragment_id))).collect();
+    let is_generated = |path: &str| path.starts_with("bazel-out/") || path.starts_with("external/");
+    let resolve = |path: &str| {
+        if is_generated(path) {
+            execution_root.join(path)
+        } else {
+            workspace_root.join(path)
+        }
+    };
+
+    let mut sysroot = None;
+    let mut crates: Vec<Value> = Vec::new();
+    let mut externs: Vec<Vec<(&str, &str)>> = Vec::new();
+    let mut crate_by_key: FxHashMap<CrateKey<'_>, usize> = FxHashMap::default();
+    let mut crate_by_output: FxHashMap<&str, usize> = FxHashMap::default();
+    for action in aquery.actions.iter().filter(|it| it.mnemonic == "Rustc") {
+        let invocation = RustcInvocation::parse(&action.arguments);
+        let (Some(crate_name), Some(root_module)) = (invocation.crate_name, invocation.root_module)
+        else {
+            continue;
+        };
+        let crate_type = invocation.crate_type.unwrap_or("lib");
+        let outputs = action.output_ids.iter().filter_map(|id| artifact_paths.get(id));
+
+        // The same crate is built once per configuration it is used in, like proc-macros for
+        // the host, but we only want it once in the crate graph. Builds with different cfgs or
+        // dependencies are different crates though.
+        let mut cfg = invocation.cfg.clone();
+        cfg.sort_unstable();
+        let mut extern_key: Vec<_> = invocation
+            .externs
+            .iter()
+            .map(|&(name, path)| (name, without_configuration(path)))
+            .collect();
+        extern_key.sort_unstable();
+        let key = CrateKey { crate_name, root_module, crate_type, cfg, externs: extern_key };
+        if let Some(&idx) = crate_by_key.get(&key) {
+            crate_by_output.extend(outputs.map(|it| (it.as_str(), idx)));
+            continue;
+        }
+        let idx = crates.len();
+        crate_by_key.insert(key, idx);
+
+        if sysroot.is_none() {
+            sysroot = invocation
+                .rustc
+                .and_then(|rustc| resolve(rustc).parent()?.parent().map(ToOwned::to_owned));
+        }
+        let is_proc_macro = crate_type == "proc-macro";
+        let proc_macro_dylib_path = outputs
+            .clone()
+            .find(|it| [".so", ".dylib", ".dll"].iter().any(|ext| it.ends_with(ext)))
+            .filter(|_| is_proc_macro)
+            .map(|it| execution_root.join(it).to_string());
+        crate_by_output.extend(outputs.map(|it| (it.as_str(), idx)));
+        let env: FxHashMap<&str, String> = action
+            .environment_variables
+            .iter()
+            .map(|it| (it.key.as_str(), it.value.replace("${pwd}", &execution_root.to_string())))
+            .collect();
+        crates.push(json!({
+            "display_name": crate_name,
+            "root_module": resolve(root_module).to_string(),
+            "edition": invocation.edition.unwrap_or("2015"),
+            "deps": [],
+            "cfg": invocation.cfg,
+            "target": invocation.target,
+            "env": env,
+            "is_workspace_member": !is_generated(root_module),
+            "is_proc_macro": is_proc_macro,
+            "proc_macro_dylib_path": proc_macro_dylib_path,
+        }));
+        externs.push(invocation.externs);
+    }
+
+    for (krate, externs) in crates.iter_mut().zip(externs) {
+        let deps: Vec<Value> = externs
+            .into_iter()
+            .filter_map(|(name, path)| {
+                let &dep = crate_by_output.get(path)?;
+                Some(json!({ "crate": dep, "name": name }))
+            })
+            .collect();
+        krate["deps"] = deps.into();
+    }
+
+    json!({
+        "sysroot": sysroot.map(|it| it.to_string()),
+        "crates": crates,
+    })
+}
+
+/// The attributes of Rust targets `buck2 cquery` is asked for.
+const CQUERY_ATTRIBUTES: &str = concat!(
+    "^(buck\\.type|buck\\.package|name|crate|crate_root|edition|srcs|features|deps|named_deps|",
+    "env|proc_macro)$"
+);
+
+/// The attributes of a Rust target in the output of `buck2 cquery --json`.
+#[derive(Deserialize, Debug, Default)]
+pub(crate) struct CqueryTarget {
+    #[serde(rename = "buck.type", default)]
+    kind: String,
+    #[serde(rename = "buck.package", default)]
+    package: String,
+    #[serde(default)]
+    name: String,
+    #[serde(rename = "crate", default)]
+    crate_name: Option<String>,
+    #[serde(default)]
+    crate_root: Option<String>,
+    #[serde(default)]
+    edition: Option<String>,
+    #[serde(default)]
+    srcs: Vec<String>,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    deps: Vec<String>,
+    /// Either a map from the dependency name to the target, or a list of such pairs.
+    #[serde(default)]
+    named_deps: Value,
+    #[serde(default)]
+    env: FxHashMap<String, String>,
+    #[serde(default)]
+    proc_macro: Option<bool>,
+}
+
+/// Strips the configuration off a configured target label, like
+/// `root//foo:bar (prelude//platforms:default#0123)`.
+fn unconfigured(label: &str) -> &str {
+    label.split_once(" (").map_or(label, |(it, _)| it)
+}
+
+/// Splits `cell//path` into the cell and the path.
+fn split_cell(path: &str) -> Option<(&str, &str)> {
+    path.split_once("//")
+}
+
+/// Lowers the Rust targets of a `buck2 cquery` to the `rust-project.json` format.
+///
+/// Sources are given relative to their cell, which `cells` maps to its path. Crates of the cell
+/// at `workspace_root` are workspace members, the ones of other cells, like vendored third-party
+/// crates, are not.
+pub(crate) fn cquery_to_project_json(
+    cquery: &FxHashMap<String, CqueryTarget>,
+    cells: &FxHashMap<String, AbsPathBuf>,
+    workspace_root: &AbsPath,
+) -> Value {
+    let mut labels: Vec<&str> = cquery.keys().map(|it| it.as_str()).collect();
+    labels.sort_unstable();
+    labels.dedup_by_key(|it| unconfigured(it));
+
+    let mut crates: Vec<Value> = Vec::new();
+    let mut crate_by_label: FxHashMap<&str, (usize, String)> = FxHashMap::default();
+    let mut target_of_crate: Vec<&CqueryTarget> = Vec::new();
+    for label in labels {
+        let target = &cquery[label];
+        let Some((cell, package)) = split_cell(&target.package) else { continue };
+        let Some(cell_root) = cells.get(cell) else { continue };
+        let package_dir = cell_root.join(package);
+        let crate_name = target.crate_name.clone().unwrap_or_else(|| target.name.replace('-', "_"));
+
+        let resolve = |src: &str| match split_cell(src) {
+            Some((cell, path)) => cells.get(cell).map(|it| it.join(path)),
+            None => Some(package_dir.join(src)),
+        };
+        let root_module = match &target.crate_root {
+            Some(crate_root) => resolve(crate_root),
+            // Buck2 picks the crate root among the sources by its file name.
+            None => {
+                let default_roots = ["lib.rs", "main.rs", &format!("{crate_name}.rs")];
+                target
+                    .srcs
+                    .iter()
+                    .find(|src| {
+                        let file_name = src.rsplit('/').next().unwrap_or(src);
+                        default_roots.contains(&file_name)
+                    })
+                    .or_else(|| target.srcs.first().filter(|_| target.srcs.len() == 1))
+                    .and_then(|src| resolve(src))
+            }
+        };
+        let Some(root_module) = root_module else { continue };
+
+        let mut cfg: Vec<String> =
+            target.features.iter().map(|feature| format!("feature=\"{feature}\"")).collect();
+        if target.kind.ends_with("rust_test") {
+            cfg.push("test".to_owned());
+        }
+        let is_proc_macro =
+            target.proc_macro.unwrap_or(false) || target.kind.ends_with("rust_proc_macro");
+        crate_by_label.insert(unconfigured(label), (crates.len(), crate_name.clone()));
+        crates.push(json!({
+            "display_name": crate_name,
+            "root_module": root_module.to_string(),
+            "edition": target.edition.as_deref().unwrap_or("2015"),
+            "deps": [],
+            "cfg": cfg,
+            "env": target.env,
+            "is_workspace_member": cell_root == workspace_root,
+            "is_proc_macro": is_proc_macro,
+        }));
+        target_of_crate.push(target);
+    }
+
+    for (krate, target) in crates.iter_mut().zip(target_of_crate) {
+        let named_deps: Vec<(Option<&str>, &str)> = match &target.named_deps {
+            Value::Object(deps) => deps
+                .iter()
+                .filter_map(|(name, dep)| Some((Some(name.as_str()), dep.as_str()?)))
+                .collect(),
+            Value::Array(deps) => deps
+                .iter()
+                .filter_map(|it| match it.as_array()?.as_slice() {
+                    [name, dep] => Some((Some(name.as_str()?), dep.as_str()?)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let deps: Vec<Value> = target
+            .deps
+            .iter()
+            .map(|dep| (None, dep.as_str()))
+            .chain(named_deps)
+            .filter_map(|(name, dep)| {
+                let (idx, crate_name) = crate_by_label.get(unconfigured(dep))?;
+                Some(json!({ "crate": idx, "name": name.unwrap_or(crate_name) }))
+            })
+            .collect();
+        krate["deps"] = deps.into();
+    }
+
+    json!({ "crates": crates })
+}
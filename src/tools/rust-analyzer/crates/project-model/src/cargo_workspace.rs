@@ -7,6 +7,7 @@
 use anyhow::Context;
 use base_db::Edition;
 use cargo_metadata::{CargoOpt, MetadataCommand};
+use cfg::CfgDiff;
 use la_arena::{Arena, Idx};
 use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::{FxHashMap, FxHashSet};
@@ -14,7 +15,7 @@
 use serde_json::from_value;
 use toolchain::Tool;
 
-use crate::{utf8_stdout, InvocationLocation, ManifestPath, Sysroot};
+use crate::{utf8_stdout, BuildSystemConfig, InvocationLocation, ManifestPath, Sysroot};
 use crate::{CfgOverrides, InvocationStrategy};
 
 /// [`CargoWorkspace`] represents the logical structure of, well, a Cargo
@@ -81,10 +82,18 @@ pub struct CargoConfig {
     pub features: CargoFeatures,
     /// rustc target
     pub target: Option<String>,
+    /// Additional rustc targets to analyze the workspace for, each adding a variant of the
+    /// workspace's crates to the crate graph.
+    pub extra_targets: Vec<String>,
+    /// Additional sets of cfgs to analyze the workspace with, each adding a variant of the
+    /// workspace's local crates with its cfgs enabled to the crate graph.
+    pub extra_cfg_sets: Vec<CfgDiff>,
     /// Sysroot loading behavior
     pub sysroot: Option<RustLibSource>,
     /// Whether to invoke `cargo metadata` on the sysroot crate.
     pub sysroot_query_metadata: bool,
+    /// Directory to cache the `cargo metadata` output of each sysroot in.
+    pub sysroot_cache_dir: Option<AbsPathBuf>,
     pub sysroot_src: Option<AbsPathBuf>,
     /// rustc private crate source
     pub rustc_source: Option<RustLibSource>,
@@ -101,6 +110,8 @@ pub struct CargoConfig {
     pub invocation_location: InvocationLocation,
     /// Optional path to use instead of `target` when building
     pub target_dir: Option<PathBuf>,
+    /// How to query Buck2 and Bazel projects.
+    pub build_system: BuildSystemConfig,
 }
 
 pub type Package = Idx<PackageData>;
@@ -128,6 +139,8 @@ pub struct PackageData {
     pub dependencies: Vec<PackageDependency>,
     /// Rust edition for this package
     pub edition: Edition,
+    /// The minimum supported Rust version, as given in the `Cargo.toml`
+    pub rust_version: Option<semver::Version>,
     /// Features provided by the crate, mapped to the features required by that feature.
     pub features: FxHashMap<String, Vec<String>>,
     /// List of features enabled on this package
@@ -328,6 +341,7 @@ pub fn new(mut meta: cargo_metadata::Metadata) -> CargoWorkspace {
                 manifest_path,
                 repository,
                 edition,
+                rust_version,
                 metadata,
                 ..
             } = meta_pkg;
@@ -356,6 +370,7 @@ pub fn new(mut meta: cargo_metadata::Metadata) -> CargoWorkspace {
                 is_local,
                 is_member,
                 edition,
+                rust_version,
                 repository,
                 dependencies: Vec::new(),
                 features: features.into_iter().collect(),
@@ -0,0 +1,132 @@
+//! Reads crates.io metadata from cargo's local copy of the registry index.
+//!
+//! Cargo caches the index entries of every crate it resolves in
+//! `$CARGO_HOME/registry/index/<registry>/.cache`, so this works offline, but only knows about
+//! crates some project on this machine depended on, as of the last time cargo updated them.
+
+use std::{fs, path::PathBuf};
+
+use paths::AbsPathBuf;
+use rustc_hash::FxHashMap;
+use serde::Deserialize;
+
+/// The crates.io entries of cargo's local registry index.
+#[derive(Debug, Clone, Default)]
+pub struct CratesIndex {
+    cache_dirs: Vec<AbsPathBuf>,
+}
+
+/// A published version of a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedVersion {
+    pub version: semver::Version,
+    pub features: Vec<String>,
+    pub yanked: bool,
+}
+
+#[derive(Deserialize)]
+struct IndexEntry {
+    vers: semver::Version,
+    #[serde(default)]
+    features: FxHashMap<String, Vec<String>>,
+    #[serde(default)]
+    features2: FxHashMap<String, Vec<String>>,
+    #[serde(default)]
+    yanked: bool,
+}
+
+impl CratesIndex {
+    /// Finds the crates.io index caches in cargo's home directory, honoring a `CARGO_HOME` set
+    /// in `extra_env`.
+    pub fn discover(extra_env: &FxHashMap<String, String>) -> CratesIndex {
+        let cargo_home = match extra_env.get("CARGO_HOME") {
+            Some(it) => Some(PathBuf::from(it)),
+            None => toolchain::cargo_home(),
+        };
+        let Some(index_dir) = cargo_home
+            .and_then(|it| AbsPathBuf::try_from(it).ok())
+            .map(|it| it.join("registry").join("index"))
+        else {
+            return CratesIndex::default();
+        };
+        CratesIndex::with_index_dir(index_dir)
+    }
+
+    /// Uses the crates.io index caches in `index_dir`, the `registry/index` directory of a cargo
+    /// home.
+    pub fn with_index_dir(index_dir: AbsPathBuf) -> CratesIndex {
+        let Ok(entries) = fs::read_dir(&index_dir) else { return CratesIndex::default() };
+        let cache_dirs = entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                // The sparse and the git protocol are cached in different directories.
+                name.starts_with("index.crates.io-") || name.starts_with("github.com-")
+            })
+            .map(|entry| index_dir.join(entry.file_name()).join(".cache"))
+            .collect();
+        CratesIndex { cache_dirs }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache_dirs.is_empty()
+    }
+
+    /// Returns the versions of the crate `name` known to the index, oldest first.
+    pub fn versions(&self, name: &str) -> Vec<IndexedVersion> {
+        let name = name.to_lowercase();
+        let Some(path) = cache_file_path(&name) else { return Vec::new() };
+        let Some(contents) = self.cache_dirs.iter().find_map(|dir| fs::read(dir.join(&path)).ok())
+        else {
+            return Vec::new();
+        };
+        let mut res = parse_cache_file(&contents);
+        res.sort_by(|a, b| a.version.cmp(&b.version));
+        res
+    }
+}
+
+/// Returns the latest version that isn't yanked, preferring stable versions.
+pub fn latest_version(versions: &[IndexedVersion]) -> Option<&IndexedVersion> {
+    let available = || versions.iter().rev().filter(|it| !it.yanked);
+    available().find(|it| it.version.pre.is_empty()).or_else(|| available().next())
+}
+
+/// Returns the path of a crate's entry relative to the index root, as laid out by cargo.
+fn cache_file_path(name: &str) -> Option<PathBuf> {
+    let path = match name.len() {
+        0 => return None,
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", name.get(..1)?),
+        _ => format!("{}/{}/{name}", name.get(..2)?, name.get(2..4)?),
+    };
+    Some(path.into())
+}
+
+/// Parses one of cargo's index cache files. They start with a cache version byte and a 4 byte
+/// index version, followed by the null terminated index revision and then pairs of null
+/// terminated version strings and JSON entries.
+pub(crate) fn parse_cache_file(contents: &[u8]) -> Vec<IndexedVersion> {
+    let Some(contents) = contents.get(5..) else { return Vec::new() };
+    contents
+        .split(|&it| it == 0)
+        // Skip the index revision and the first version string.
+        .skip(2)
+        .step_by(2)
+        .filter_map(|entry| serde_json::from_slice::<IndexEntry>(entry).ok())
+        .map(|entry| {
+            let mut features: Vec<_> =
+                entry.features.into_keys().chain(entry.features2.into_keys()).collect();
+            features.sort();
+            IndexedVersion { version: entry.vers, features, yanked: entry.yanked }
+        })
+        .collect()
+}
+
+/// Returns whether `id`, a cargo package id, belongs to a package from crates.io.
+pub fn is_crates_io_package(id: &str) -> bool {
+    id.contains("registry+https://github.com/rust-lang/crates.io-index")
+        || id.contains("sparse+https://index.crates.io/")
+}
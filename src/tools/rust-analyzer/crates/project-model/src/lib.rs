@@ -18,10 +18,12 @@
 #![warn(rust_2018_idioms, unused_lifetimes)]
 
 mod build_scripts;
+mod build_system;
 mod cargo_workspace;
 mod cfg_flag;
+mod crates_index;
 mod manifest_path;
-mod project_json;
+pub mod project_json;
 mod rustc_cfg;
 mod sysroot;
 pub mod target_data_layout;
@@ -32,7 +34,7 @@
 
 use std::{
     fmt,
-    fs::{self, read_dir, ReadDir},
+    fs::{self, read_dir},
     io,
     process::Command,
 };
@@ -43,10 +45,12 @@
 
 pub use crate::{
     build_scripts::WorkspaceBuildScripts,
+    build_system::{BuildSystem, BuildSystemConfig},
     cargo_workspace::{
         CargoConfig, CargoFeatures, CargoWorkspace, Package, PackageData, PackageDependency,
         RustLibSource, Target, TargetData, TargetKind,
     },
+    crates_index::{is_crates_io_package, latest_version, CratesIndex, IndexedVersion},
     manifest_path::ManifestPath,
     project_json::{ProjectJson, ProjectJsonData},
     sysroot::Sysroot,
@@ -57,6 +61,8 @@
 pub enum ProjectManifest {
     ProjectJson(ManifestPath),
     CargoToml(ManifestPath),
+    /// The root marker of a Buck2 or Bazel project, like `.buckconfig` or `MODULE.bazel`.
+    BuildSystem(BuildSystem, ManifestPath),
 }
 
 impl ProjectManifest {
@@ -69,7 +75,20 @@ pub fn from_manifest_file(path: AbsPathBuf) -> anyhow::Result<ProjectManifest> {
         if path.file_name().unwrap_or_default() == "Cargo.toml" {
             return Ok(ProjectManifest::CargoToml(path));
         }
-        bail!("project root must point to Cargo.toml or rust-project.json: {path}");
+        if let Some(build_system) =
+            path.file_name().and_then(|it| it.to_str()).and_then(BuildSystem::from_root_marker)
+        {
+            return Ok(ProjectManifest::BuildSystem(build_system, path));
+        }
+        bail!("project root must point to Cargo.toml, rust-project.json or a Buck2 or Bazel root: {path}");
+    }
+
+    pub fn manifest_path(&self) -> &ManifestPath {
+        match self {
+            ProjectManifest::ProjectJson(it)
+            | ProjectManifest::CargoToml(it)
+            | ProjectManifest::BuildSystem(_, it) => it,
+        }
     }
 
     pub fn discover_single(path: &AbsPath) -> anyhow::Result<ProjectManifest> {
@@ -86,57 +105,92 @@ pub fn discover_single(path: &AbsPath) -> anyhow::Result<ProjectManifest> {
     }
 
     pub fn discover(path: &AbsPath) -> io::Result<Vec<ProjectManifest>> {
+        ProjectManifest::discover_nested(path, 1)
+    }
+
+    /// Like [`ProjectManifest::discover`], but if `path` isn't part of a project, looks for cargo
+    /// workspaces up to `max_depth` directories below it instead of only in its children. With a
+    /// `max_depth` of 0, only `path` itself and its parents are considered.
+    pub fn discover_nested(path: &AbsPath, max_depth: usize) -> io::Result<Vec<ProjectManifest>> {
         if let Some(project_json) = find_in_parent_dirs(path, "rust-project.json") {
             return Ok(vec![ProjectManifest::ProjectJson(project_json)]);
         }
-        return find_cargo_toml(path)
-            .map(|paths| paths.into_iter().map(ProjectManifest::CargoToml).collect());
-
-        fn find_cargo_toml(path: &AbsPath) -> io::Result<Vec<ManifestPath>> {
-            match find_in_parent_dirs(path, "Cargo.toml") {
-                Some(it) => Ok(vec![it]),
-                None => Ok(find_cargo_toml_in_child_dir(read_dir(path)?)),
-            }
+        if let Some(cargo_toml) = find_in_parent_dirs(path, "Cargo.toml") {
+            return Ok(vec![ProjectManifest::CargoToml(cargo_toml)]);
         }
+        return Ok(find_cargo_toml_in_child_dirs(path, max_depth)?
+            .into_iter()
+            .map(ProjectManifest::CargoToml)
+            .collect());
 
-        fn find_in_parent_dirs(path: &AbsPath, target_file_name: &str) -> Option<ManifestPath> {
-            if path.file_name().unwrap_or_default() == target_file_name {
-                if let Ok(manifest) = ManifestPath::try_from(path.to_path_buf()) {
-                    return Some(manifest);
-                }
+        fn find_cargo_toml_in_child_dirs(
+            path: &AbsPath,
+            max_depth: usize,
+        ) -> io::Result<Vec<ManifestPath>> {
+            let mut res = Vec::new();
+            if max_depth == 0 {
+                return Ok(res);
             }
-
-            let mut curr = Some(path);
-
-            while let Some(path) = curr {
-                let candidate = path.join(target_file_name);
-                if fs::metadata(&candidate).is_ok() {
-                    if let Ok(manifest) = ManifestPath::try_from(candidate) {
-                        return Some(manifest);
+            let mut dirs = vec![(path.to_path_buf(), 1)];
+            while let Some((dir, depth)) = dirs.pop() {
+                let entries = match read_dir(&dir) {
+                    Ok(it) => it,
+                    Err(e) if depth == 1 => return Err(e),
+                    Err(_) => continue,
+                };
+                for entry in entries.filter_map(Result::ok) {
+                    let child = AbsPathBuf::assert(entry.path());
+                    let cargo_toml = child.join("Cargo.toml");
+                    if fs::metadata(&cargo_toml).is_ok() {
+                        // Packages below a cargo workspace are almost always its members, which
+                        // we get from `cargo metadata` anyway.
+                        res.extend(ManifestPath::try_from(cargo_toml).ok());
+                        continue;
+                    }
+                    // Skip symlinks to not run into cycles, and directories of build outputs and
+                    // dependencies, which are large and don't contain the user's workspaces.
+                    let skip = !entry.file_type().is_ok_and(|it| it.is_dir())
+                        || entry.file_name().to_str().map_or(true, |it| {
+                            it.starts_with('.') || it == "target" || it == "node_modules"
+                        });
+                    if depth < max_depth && !skip {
+                        dirs.push((child, depth + 1));
                     }
                 }
-                curr = path.parent();
             }
-
-            None
+            Ok(res)
         }
+    }
 
-        fn find_cargo_toml_in_child_dir(entities: ReadDir) -> Vec<ManifestPath> {
-            // Only one level down to avoid cycles the easy way and stop a runaway scan with large projects
-            entities
-                .filter_map(Result::ok)
-                .map(|it| it.path().join("Cargo.toml"))
-                .filter(|it| it.exists())
-                .map(AbsPathBuf::assert)
-                .filter_map(|it| it.try_into().ok())
-                .collect()
-        }
+    /// Looks for the root of a Buck2 or Bazel project containing `path`.
+    ///
+    /// Querying these build systems for the crate graph runs them, so this is separate from
+    /// [`ProjectManifest::discover`].
+    pub fn discover_build_system(path: &AbsPath) -> Option<ProjectManifest> {
+        BuildSystem::ROOT_MARKERS.iter().find_map(|&(marker, build_system)| {
+            let root_marker = find_in_parent_dirs(path, marker)?;
+            Some(ProjectManifest::BuildSystem(build_system, root_marker))
+        })
     }
 
-    pub fn discover_all(paths: &[AbsPathBuf]) -> Vec<ProjectManifest> {
+    /// Discovers the projects of all `paths`. With `discover_build_systems`, paths that aren't
+    /// part of a `rust-project.json` or cargo project are looked up in Buck2 and Bazel projects.
+    pub fn discover_all(
+        paths: &[AbsPathBuf],
+        max_depth: usize,
+        discover_build_systems: bool,
+    ) -> Vec<ProjectManifest> {
         let mut res = paths
             .iter()
-            .filter_map(|it| ProjectManifest::discover(it.as_ref()).ok())
+            .filter_map(|it| {
+                let res = ProjectManifest::discover_nested(it.as_ref(), max_depth).ok()?;
+                // Buck2 and Bazel projects are only loaded when there is nothing else, as their
+                // roots tend to be far above the crates and may well contain cargo projects.
+                if res.is_empty() && discover_build_systems {
+                    return Some(ProjectManifest::discover_build_system(it).into_iter().collect());
+                }
+                Some(res)
+            })
             .flatten()
             .collect::<FxHashSet<_>>()
             .into_iter()
@@ -146,13 +200,31 @@ pub fn discover_all(paths: &[AbsPathBuf]) -> Vec<ProjectManifest> {
     }
 }
 
-impl fmt::Display for ProjectManifest {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ProjectManifest::ProjectJson(it) | ProjectManifest::CargoToml(it) => {
-                fmt::Display::fmt(&it, f)
+fn find_in_parent_dirs(path: &AbsPath, target_file_name: &str) -> Option<ManifestPath> {
+    if path.file_name().unwrap_or_default() == target_file_name {
+        if let Ok(manifest) = ManifestPath::try_from(path.to_path_buf()) {
+            return Some(manifest);
+        }
+    }
+
+    let mut curr = Some(path);
+
+    while let Some(path) = curr {
+        let candidate = path.join(target_file_name);
+        if fs::metadata(&candidate).is_ok() {
+            if let Ok(manifest) = ManifestPath::try_from(candidate) {
+                return Some(manifest);
             }
         }
+        curr = path.parent();
+    }
+
+    None
+}
+
+impl fmt::Display for ProjectManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.manifest_path(), f)
     }
 }
 
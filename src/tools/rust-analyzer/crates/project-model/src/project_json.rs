@@ -66,6 +66,9 @@ pub struct ProjectJson {
     /// e.g. `path/to/sysroot/lib/rustlib/src/rust`
     pub(crate) sysroot_src: Option<AbsPathBuf>,
     project_root: AbsPathBuf,
+    /// The `rust-project.json` this project was read from, if it wasn't passed inline.
+    pub(crate) manifest: Option<AbsPathBuf>,
+    pub(crate) build_files: Vec<AbsPathBuf>,
     crates: Vec<Crate>,
 }
 
@@ -85,8 +88,29 @@ pub struct Crate {
     pub(crate) is_workspace_member: bool,
     pub(crate) include: Vec<AbsPathBuf>,
     pub(crate) exclude: Vec<AbsPathBuf>,
+    pub(crate) exclude_globs: Vec<String>,
     pub(crate) is_proc_macro: bool,
     pub(crate) repository: Option<String>,
+    runnables: Vec<Runnable>,
+}
+
+/// A command template for running or testing (parts of) a crate, used by build systems that
+/// aren't cargo.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Runnable {
+    pub kind: RunnableKind,
+    pub program: String,
+    /// Arguments to `program`, `{test_id}` is replaced with the path of the test to run.
+    pub args: Vec<String>,
+    pub cwd: AbsPathBuf,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RunnableKind {
+    /// Runs a binary crate.
+    Run,
+    /// Runs a single test or all tests in a module.
+    Test,
 }
 
 impl ProjectJson {
@@ -103,6 +127,8 @@ pub fn new(base: &AbsPath, data: ProjectJsonData) -> ProjectJson {
             sysroot: data.sysroot.map(absolutize_on_base),
             sysroot_src: data.sysroot_src.map(absolutize_on_base),
             project_root: base.to_path_buf(),
+            manifest: None,
+            build_files: data.build_files.into_iter().map(absolutize_on_base).collect(),
             crates: data
                 .crates
                 .into_iter()
@@ -111,14 +137,22 @@ pub fn new(base: &AbsPath, data: ProjectJsonData) -> ProjectJson {
                     let is_workspace_member = crate_data
                         .is_workspace_member
                         .unwrap_or_else(|| root_module.starts_with(base));
-                    let (include, exclude) = match crate_data.source {
+                    let (include, exclude, exclude_globs) = match crate_data.source {
                         Some(src) => {
                             let absolutize = |dirs: Vec<PathBuf>| {
                                 dirs.into_iter().map(absolutize_on_base).collect::<Vec<_>>()
                             };
-                            (absolutize(src.include_dirs), absolutize(src.exclude_dirs))
+                            (
+                                absolutize(src.include_dirs),
+                                absolutize(src.exclude_dirs),
+                                src.exclude_globs,
+                            )
                         }
-                        None => (vec![root_module.parent().unwrap().to_path_buf()], Vec::new()),
+                        None => (
+                            vec![root_module.parent().unwrap().to_path_buf()],
+                            Vec::new(),
+                            Vec::new(),
+                        ),
                     };
 
                     Crate {
@@ -147,8 +181,21 @@ pub fn new(base: &AbsPath, data: ProjectJsonData) -> ProjectJson {
                         is_workspace_member,
                         include,
                         exclude,
+                        exclude_globs,
                         is_proc_macro: crate_data.is_proc_macro,
                         repository: crate_data.repository,
+                        runnables: crate_data
+                            .runnables
+                            .into_iter()
+                            .map(|runnable| Runnable {
+                                kind: runnable.kind.into(),
+                                program: runnable.program,
+                                args: runnable.args,
+                                cwd: runnable
+                                    .cwd
+                                    .map_or_else(|| base.to_path_buf(), absolutize_on_base),
+                            })
+                            .collect(),
                     }
                 })
                 .collect(),
@@ -172,12 +219,32 @@ pub fn crates(&self) -> impl Iterator<Item = (CrateId, &Crate)> + '_ {
     pub fn path(&self) -> &AbsPath {
         &self.project_root
     }
+
+    /// Returns the crate whose root module is `root_module`.
+    pub fn crate_by_root(&self, root_module: &AbsPath) -> Option<&Crate> {
+        self.crates.iter().find(|krate| *krate.root_module == *root_module)
+    }
+
+    /// Returns the files that cause the project to be reloaded when they change: the
+    /// `rust-project.json` itself and the `build_files` it lists.
+    pub fn build_files(&self) -> impl Iterator<Item = &AbsPath> + '_ {
+        self.manifest.iter().chain(&self.build_files).map(|it| &**it)
+    }
+}
+
+impl Crate {
+    /// Returns the command templates for running this crate and its tests.
+    pub fn runnables(&self) -> &[Runnable] {
+        &self.runnables
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct ProjectJsonData {
     sysroot: Option<PathBuf>,
     sysroot_src: Option<PathBuf>,
+    #[serde(default)]
+    build_files: Vec<PathBuf>,
     crates: Vec<CrateData>,
 }
 
@@ -201,6 +268,33 @@ struct CrateData {
     is_proc_macro: bool,
     #[serde(default)]
     repository: Option<String>,
+    #[serde(default)]
+    runnables: Vec<RunnableData>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct RunnableData {
+    kind: RunnableKindData,
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum RunnableKindData {
+    Run,
+    Test,
+}
+
+impl From<RunnableKindData> for RunnableKind {
+    fn from(data: RunnableKindData) -> Self {
+        match data {
+            RunnableKindData::Run => RunnableKind::Run,
+            RunnableKindData::Test => RunnableKind::Test,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -240,6 +334,8 @@ struct DepData {
 struct CrateSource {
     include_dirs: Vec<PathBuf>,
     exclude_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    exclude_globs: Vec<String>,
 }
 
 fn deserialize_crate_name<'de, D>(de: D) -> std::result::Result<CrateName, D::Error>
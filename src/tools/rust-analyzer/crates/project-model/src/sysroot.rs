@@ -4,14 +4,21 @@
 //! but we can't process `.rlib` and need source code instead. The source code
 //! is typically installed with `rustup component add rust-src` command.
 
-use std::{env, fs, iter, ops, path::PathBuf, process::Command, sync::Arc};
+use std::{
+    env, fs,
+    hash::{Hash, Hasher},
+    iter, ops,
+    path::PathBuf,
+    process::Command,
+    sync::Arc,
+};
 
 use anyhow::{format_err, Result};
 use base_db::CrateName;
 use itertools::Itertools;
 use la_arena::{Arena, Idx};
 use paths::{AbsPath, AbsPathBuf};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHasher};
 use toolchain::probe_for_binary;
 
 use crate::{utf8_stdout, CargoConfig, CargoWorkspace, ManifestPath};
@@ -152,12 +159,13 @@ pub fn discover(
         dir: &AbsPath,
         extra_env: &FxHashMap<String, String>,
         metadata: bool,
+        cache_dir: Option<&AbsPath>,
     ) -> Result<Sysroot> {
         tracing::debug!("discovering sysroot for {dir}");
         let sysroot_dir = discover_sysroot_dir(dir, extra_env)?;
         let sysroot_src_dir =
             discover_sysroot_src_dir_or_add_component(&sysroot_dir, dir, extra_env);
-        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), metadata))
+        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), metadata, cache_dir))
     }
 
     pub fn discover_no_source(
@@ -168,7 +176,7 @@ pub fn discover_no_source(
         let sysroot_dir = discover_sysroot_dir(dir, extra_env)?;
         let sysroot_src_dir =
             discover_sysroot_src_dir_or_add_component(&sysroot_dir, dir, extra_env);
-        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), false))
+        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), false, None))
     }
 
     pub fn discover_with_src_override(
@@ -176,21 +184,26 @@ pub fn discover_with_src_override(
         extra_env: &FxHashMap<String, String>,
         src: AbsPathBuf,
         metadata: bool,
+        cache_dir: Option<&AbsPath>,
     ) -> Result<Sysroot> {
         tracing::debug!("discovering sysroot for {current_dir}");
         let sysroot_dir = discover_sysroot_dir(current_dir, extra_env)?;
-        Ok(Sysroot::load(sysroot_dir, Some(Ok(src)), metadata))
+        Ok(Sysroot::load(sysroot_dir, Some(Ok(src)), metadata, cache_dir))
     }
 
     pub fn discover_rustc_src(&self) -> Option<ManifestPath> {
         get_rustc_src(&self.root)
     }
 
-    pub fn with_sysroot_dir(sysroot_dir: AbsPathBuf, metadata: bool) -> Result<Sysroot> {
+    pub fn with_sysroot_dir(
+        sysroot_dir: AbsPathBuf,
+        metadata: bool,
+        cache_dir: Option<&AbsPath>,
+    ) -> Result<Sysroot> {
         let sysroot_src_dir = discover_sysroot_src_dir(&sysroot_dir).ok_or_else(|| {
             format_err!("can't load standard library from sysroot path {sysroot_dir}")
         });
-        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), metadata))
+        Ok(Sysroot::load(sysroot_dir, Some(sysroot_src_dir), metadata, cache_dir))
     }
 
     pub fn set_rustup_toolchain_env(cmd: &mut Command, sysroot: Option<&Self>) {
@@ -210,10 +223,13 @@ pub fn discover_proc_macro_srv(&self) -> anyhow::Result<AbsPathBuf> {
             })
     }
 
+    /// Loads the sysroot at `sysroot_dir`. If `metadata` is set, its library is loaded with
+    /// `cargo metadata`, caching the result in `cache_dir` if given.
     pub fn load(
         sysroot_dir: AbsPathBuf,
         sysroot_src_dir: Option<Result<AbsPathBuf, anyhow::Error>>,
         metadata: bool,
+        cache_dir: Option<&AbsPath>,
     ) -> Sysroot {
         let sysroot_src_dir = match sysroot_src_dir {
             Some(Ok(sysroot_src_dir)) => sysroot_src_dir,
@@ -233,115 +249,18 @@ pub fn load(
             }
         };
         if metadata {
-            let sysroot: Option<_> = (|| {
-                let sysroot_cargo_toml = ManifestPath::try_from(
-                    AbsPathBuf::try_from(&*format!("{sysroot_src_dir}/sysroot/Cargo.toml")).ok()?,
-                )
-                .ok()?;
-                let current_dir =
-                    AbsPathBuf::try_from(&*format!("{sysroot_src_dir}/sysroot")).ok()?;
-
-                let mut cargo_config = CargoConfig::default();
-                // the sysroot uses `public-dependency`, so we make cargo think it's a nightly
-                cargo_config.extra_env.insert(
-                    "__CARGO_TEST_CHANNEL_OVERRIDE_DO_NOT_USE_THIS".to_owned(),
-                    "nightly".to_owned(),
-                );
-
-                let res = CargoWorkspace::fetch_metadata(
-                    &sysroot_cargo_toml,
-                    &current_dir,
-                    &cargo_config,
-                    None,
-                    &|_| (),
-                )
-                .map_err(|e| {
-                    tracing::error!(
-                        "failed to load sysroot `{sysroot_src_dir}/sysroot/Cargo.toml`: {}",
-                        e
-                    );
-                    e
-                });
-                if let Err(e) =
-                    std::fs::remove_file(format!("{sysroot_src_dir}/sysroot/Cargo.lock"))
-                {
-                    tracing::error!(
-                        "failed to remove sysroot `{sysroot_src_dir}/sysroot/Cargo.lock`: {}",
-                        e
-                    )
+            let res = match cache_dir {
+                Some(cache_dir) => {
+                    load_cached_sysroot_metadata(&sysroot_dir, &sysroot_src_dir, cache_dir)
                 }
-                let mut res = res.ok()?;
-
-                // Patch out `rustc-std-workspace-*` crates to point to the real crates.
-                // This is done prior to `CrateGraph` construction to avoid having duplicate `std` targets.
-
-                let mut fake_core = None;
-                let mut fake_alloc = None;
-                let mut fake_std = None;
-                let mut real_core = None;
-                let mut real_alloc = None;
-                let mut real_std = None;
-                res.packages.iter().enumerate().for_each(|(idx, package)| {
-                    match package.name.strip_prefix("rustc-std-workspace-") {
-                        Some("core") => fake_core = Some((idx, package.id.clone())),
-                        Some("alloc") => fake_alloc = Some((idx, package.id.clone())),
-                        Some("std") => fake_std = Some((idx, package.id.clone())),
-                        Some(_) => {
-                            tracing::warn!("unknown rustc-std-workspace-* crate: {}", package.name)
-                        }
-                        None => match &*package.name {
-                            "core" => real_core = Some(package.id.clone()),
-                            "alloc" => real_alloc = Some(package.id.clone()),
-                            "std" => real_std = Some(package.id.clone()),
-                            _ => (),
-                        },
-                    }
-                });
-
-                let patches =
-                    [fake_core.zip(real_core), fake_alloc.zip(real_alloc), fake_std.zip(real_std)]
-                        .into_iter()
-                        .flatten();
-
-                let resolve = res.resolve.as_mut().expect("metadata executed with deps");
-                let mut remove_nodes = vec![];
-                for (idx, node) in resolve.nodes.iter_mut().enumerate() {
-                    // Replace them in the dependency list
-                    node.deps.iter_mut().for_each(|dep| {
-                        if let Some((_, real)) =
-                            patches.clone().find(|((_, fake_id), _)| *fake_id == dep.pkg)
-                        {
-                            dep.pkg = real;
-                        }
-                    });
-                    if patches.clone().any(|((_, fake), _)| fake == node.id) {
-                        remove_nodes.push(idx);
-                    }
-                }
-                // Remove the fake ones from the resolve data
-                remove_nodes.into_iter().rev().for_each(|r| {
-                    resolve.nodes.remove(r);
-                });
-                // Remove the fake ones from the packages
-                patches.map(|((r, _), _)| r).sorted().rev().for_each(|r| {
-                    res.packages.remove(r);
-                });
-
-                res.workspace_members = res
-                    .packages
-                    .iter()
-                    .filter(|&package| RELEVANT_SYSROOT_CRATES.contains(&&*package.name))
-                    .map(|package| package.id.clone())
-                    .collect();
-                let cargo_workspace = CargoWorkspace::new(res);
-                Some(Sysroot {
-                    root: sysroot_dir.clone(),
-                    src_root: Some(Ok(sysroot_src_dir.clone())),
-                    mode: SysrootMode::Workspace(cargo_workspace),
-                })
-            })();
-            if let Some(sysroot) = sysroot {
-                return sysroot;
+                None => fetch_sysroot_metadata(&sysroot_src_dir),
+            };
+            if let Some(res) = res {
+                return Sysroot {
+                    root: sysroot_dir,
+                    src_root: Some(Ok(sysroot_src_dir)),
+                    mode: SysrootMode::Workspace(CargoWorkspace::new(res)),
+                };
             }
         }
         let mut stitched = Stitched { crates: Arena::default() };
@@ -394,6 +313,209 @@ pub fn load(
     }
 }
 
+/// How many sysroots' metadata is kept in the cache directory at most.
+const MAX_CACHED_SYSROOTS: usize = 16;
+
+/// Bump this when the format of the cached metadata changes, so that old files are replaced.
+const METADATA_CACHE_VERSION: u32 = 1;
+
+/// Loads the cargo metadata of the sysroot library from `cache_dir`, or fetches and stores it
+/// there.
+fn load_cached_sysroot_metadata(
+    sysroot_dir: &AbsPath,
+    sysroot_src_dir: &AbsPath,
+    cache_dir: &AbsPath,
+) -> Option<cargo_metadata::Metadata> {
+    let cache_file = sysroot_metadata_cache_file(sysroot_dir, sysroot_src_dir, cache_dir);
+    match fs::read(&cache_file).map(|it| serde_json::from_slice(&it)) {
+        Ok(Ok(res)) => return Some(res),
+        Ok(Err(e)) => {
+            tracing::warn!("discarding sysroot metadata cache `{}`: {e}", cache_file.display())
+        }
+        Err(_) => (),
+    }
+    let res = fetch_sysroot_metadata(sysroot_src_dir)?;
+    // Write to a temporary file first, other rust-analyzer instances may read the cache meanwhile.
+    let tmp_file = cache_file.with_extension(format!("{}.tmp", std::process::id()));
+    let stored = fs::create_dir_all(cache_dir)
+        .and_then(|()| fs::write(&tmp_file, serde_json::to_vec(&res)?))
+        .and_then(|()| fs::rename(&tmp_file, &cache_file));
+    match stored {
+        Ok(()) => remove_stale_sysroot_metadata(&cache_file, cache_dir),
+        Err(e) => {
+            tracing::warn!("failed to store sysroot metadata cache `{}`: {e}", cache_file.display())
+        }
+    }
+    Some(res)
+}
+
+/// Returns the file caching the cargo metadata of the sysroot library in `cache_dir`.
+///
+/// The file name consists of a hash of the library's location, followed by a hash of the
+/// toolchain's `rustc -vV` output and the sizes and modification times of the library's lock file
+/// and manifests, so that an updated toolchain invalidates the cached metadata while switching
+/// between installed toolchains doesn't require fetching it again.
+pub(crate) fn sysroot_metadata_cache_file(
+    sysroot_dir: &AbsPath,
+    sysroot_src_dir: &AbsPath,
+    cache_dir: &AbsPath,
+) -> PathBuf {
+    let mut location = FxHasher::default();
+    sysroot_src_dir.hash(&mut location);
+
+    let mut contents = FxHasher::default();
+    METADATA_CACHE_VERSION.hash(&mut contents);
+    sysroot_dir.hash(&mut contents);
+    let rustc = probe_for_binary(sysroot_dir.join("bin").join("rustc").into());
+    rustc
+        .and_then(|rustc| {
+            let mut cmd = Command::new(rustc);
+            cmd.arg("-vV");
+            utf8_stdout(cmd).ok()
+        })
+        .hash(&mut contents);
+    let lock_file = sysroot_src_dir.parent().map(|it| it.join("Cargo.lock"));
+    let manifests = ["sysroot", "core", "alloc", "std"]
+        .map(|krate| Some(sysroot_src_dir.join(krate).join("Cargo.toml")));
+    for file in iter::once(lock_file).chain(manifests).flatten() {
+        fs::metadata(&file).ok().map(|it| (it.len(), it.modified().ok())).hash(&mut contents);
+    }
+
+    cache_dir.join(format!("{:016x}-{:016x}.json", location.finish(), contents.finish())).into()
+}
+
+/// Removes the cached metadata that `cache_file` replaces, that is the files of the same library
+/// location, and the least recently written files beyond [`MAX_CACHED_SYSROOTS`].
+pub(crate) fn remove_stale_sysroot_metadata(cache_file: &std::path::Path, cache_dir: &AbsPath) {
+    let Some(file_name) = cache_file.file_name().and_then(|it| it.to_str()) else { return };
+    let Some((location, _)) = file_name.split_once('-') else { return };
+    let Ok(entries) = fs::read_dir(cache_dir) else { return };
+
+    let mut cached = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == cache_file || path.extension().map_or(true, |it| it != "json") {
+            continue;
+        }
+        let same_location = path
+            .file_name()
+            .and_then(|it| it.to_str())
+            .and_then(|it| it.split_once('-'))
+            .map_or(false, |(it, _)| it == location);
+        if same_location {
+            if let Err(e) = fs::remove_file(&path) {
+                tracing::warn!("failed to remove sysroot metadata cache `{}`: {e}", path.display());
+            }
+            continue;
+        }
+        let modified = entry.metadata().and_then(|it| it.modified()).ok();
+        cached.push((modified, path));
+    }
+
+    // `cache_file` itself counts towards the limit.
+    if cached.len() < MAX_CACHED_SYSROOTS {
+        return;
+    }
+    cached.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+    for (_, path) in cached.drain(MAX_CACHED_SYSROOTS - 1..) {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!("failed to remove sysroot metadata cache `{}`: {e}", path.display());
+        }
+    }
+}
+
+/// Runs `cargo metadata` on the sysroot library, with the `rustc-std-workspace-*` crates patched
+/// out.
+fn fetch_sysroot_metadata(sysroot_src_dir: &AbsPath) -> Option<cargo_metadata::Metadata> {
+    let sysroot_cargo_toml = ManifestPath::try_from(
+        AbsPathBuf::try_from(&*format!("{sysroot_src_dir}/sysroot/Cargo.toml")).ok()?,
+    )
+    .ok()?;
+    let current_dir = AbsPathBuf::try_from(&*format!("{sysroot_src_dir}/sysroot")).ok()?;
+
+    let mut cargo_config = CargoConfig::default();
+    // the sysroot uses `public-dependency`, so we make cargo think it's a nightly
+    cargo_config
+        .extra_env
+        .insert("__CARGO_TEST_CHANNEL_OVERRIDE_DO_NOT_USE_THIS".to_owned(), "nightly".to_owned());
+
+    let res = CargoWorkspace::fetch_metadata(
+        &sysroot_cargo_toml,
+        &current_dir,
+        &cargo_config,
+        None,
+        &|_| (),
+    )
+    .map_err(|e| {
+        tracing::error!("failed to load sysroot `{sysroot_src_dir}/sysroot/Cargo.toml`: {}", e);
+        e
+    });
+    if let Err(e) = std::fs::remove_file(format!("{sysroot_src_dir}/sysroot/Cargo.lock")) {
+        tracing::error!("failed to remove sysroot `{sysroot_src_dir}/sysroot/Cargo.lock`: {}", e)
+    }
+    let mut res = res.ok()?;
+
+    // Patch out `rustc-std-workspace-*` crates to point to the real crates.
+    // This is done prior to `CrateGraph` construction to avoid having duplicate `std` targets.
+
+    let mut fake_core = None;
+    let mut fake_alloc = None;
+    let mut fake_std = None;
+    let mut real_core = None;
+    let mut real_alloc = None;
+    let mut real_std = None;
+    res.packages.iter().enumerate().for_each(|(idx, package)| {
+        match package.name.strip_prefix("rustc-std-workspace-") {
+            Some("core") => fake_core = Some((idx, package.id.clone())),
+            Some("alloc") => fake_alloc = Some((idx, package.id.clone())),
+            Some("std") => fake_std = Some((idx, package.id.clone())),
+            Some(_) => {
+                tracing::warn!("unknown rustc-std-workspace-* crate: {}", package.name)
+            }
+            None => match &*package.name {
+                "core" => real_core = Some(package.id.clone()),
+                "alloc" => real_alloc = Some(package.id.clone()),
+                "std" => real_std = Some(package.id.clone()),
+                _ => (),
+            },
+        }
+    });
+
+    let patches = [fake_core.zip(real_core), fake_alloc.zip(real_alloc), fake_std.zip(real_std)]
+        .into_iter()
+        .flatten();
+
+    let resolve = res.resolve.as_mut().expect("metadata executed with deps");
+    let mut remove_nodes = vec![];
+    for (idx, node) in resolve.nodes.iter_mut().enumerate() {
+        // Replace them in the dependency list
+        node.deps.iter_mut().for_each(|dep| {
+            if let Some((_, real)) = patches.clone().find(|((_, fake_id), _)| *fake_id == dep.pkg) {
+                dep.pkg = real;
+            }
+        });
+        if patches.clone().any(|((_, fake), _)| fake == node.id) {
+            remove_nodes.push(idx);
+        }
+    }
+    // Remove the fake ones from the resolve data
+    remove_nodes.into_iter().rev().for_each(|r| {
+        resolve.nodes.remove(r);
+    });
+    // Remove the fake ones from the packages
+    patches.map(|((r, _), _)| r).sorted().rev().for_each(|r| {
+        res.packages.remove(r);
+    });
+
+    res.workspace_members = res
+        .packages
+        .iter()
+        .filter(|&package| RELEVANT_SYSROOT_CRATES.contains(&&*package.name))
+        .map(|package| package.id.clone())
+        .collect();
+    Some(res)
+}
+
 fn discover_sysroot_dir(
     current_dir: &AbsPath,
     extra_env: &FxHashMap<String, String>,
@@ -4,25 +4,34 @@
 };
 
 use base_db::{CrateGraph, FileId, ProcMacroPaths};
-use cfg::{CfgAtom, CfgDiff};
+use cfg::{CfgAtom, CfgDiff, CfgExpr};
 use expect_test::{expect_file, ExpectFile};
 use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::FxHashMap;
 use serde::de::DeserializeOwned;
+use test_utils::TempDir;
 use triomphe::Arc;
 
 use crate::{
-    CargoWorkspace, CfgOverrides, ProjectJson, ProjectJsonData, ProjectWorkspace, Sysroot,
-    WorkspaceBuildScripts,
+    build_scripts::BuildScriptOutput,
+    build_system::BuildSystem,
+    build_system::{aquery_to_project_json, cquery_to_project_json, AqueryOutput, CqueryTarget},
+    crates_index::parse_cache_file,
+    latest_version,
+    project_json::RunnableKind,
+    sysroot::{remove_stale_sysroot_metadata, sysroot_metadata_cache_file, SysrootMode},
+    CargoWorkspace, CfgOverrides, ProjectJson, ProjectJsonData, ProjectManifest, ProjectWorkspace,
+    Sysroot, WorkspaceBuildScripts,
 };
 
 fn load_cargo(file: &str) -> (CrateGraph, ProcMacroPaths) {
-    load_cargo_with_overrides(file, CfgOverrides::default())
+    load_cargo_with_overrides(file, CfgOverrides::default(), None)
 }
 
 fn load_cargo_with_overrides(
     file: &str,
     cfg_overrides: CfgOverrides,
+    extra_cfgs: Option<CfgDiff>,
 ) -> (CrateGraph, ProcMacroPaths) {
     let meta = get_test_json_file(file);
     let cargo_workspace = CargoWorkspace::new(meta);
@@ -36,6 +45,8 @@ fn load_cargo_with_overrides(
         toolchain: None,
         target_layout: Err("target_data_layout not loaded".into()),
         cargo_config_extra_env: Default::default(),
+        extra_target: None,
+        extra_cfgs,
     };
     to_crate_graph(project_workspace)
 }
@@ -56,6 +67,8 @@ fn load_cargo_with_fake_sysroot(
         toolchain: None,
         target_layout: Err("target_data_layout not loaded".into()),
         cargo_config_extra_env: Default::default(),
+        extra_target: None,
+        extra_cfgs: None,
     };
     project_workspace.to_crate_graph(
         &mut {
@@ -133,7 +146,7 @@ fn get_fake_sysroot() -> Sysroot {
     // fake sysroot, so we give them both the same path:
     let sysroot_dir = AbsPathBuf::assert(sysroot_path);
     let sysroot_src_dir = sysroot_dir.clone();
-    Sysroot::load(sysroot_dir, Some(Ok(sysroot_src_dir)), false)
+    Sysroot::load(sysroot_dir, Some(Ok(sysroot_src_dir)), false, None)
 }
 
 fn rooted_project_json(data: ProjectJsonData) -> ProjectJson {
@@ -171,7 +184,7 @@ fn cargo_hello_world_project_model_with_wildcard_overrides() {
         selective: Default::default(),
     };
     let (crate_graph, _proc_macros) =
-        load_cargo_with_overrides("hello-world-metadata.json", cfg_overrides);
+        load_cargo_with_overrides("hello-world-metadata.json", cfg_overrides, None);
     check_crate_graph(
         crate_graph,
         expect_file![
@@ -191,7 +204,7 @@ fn cargo_hello_world_project_model_with_selective_overrides() {
         .collect(),
     };
     let (crate_graph, _proc_macros) =
-        load_cargo_with_overrides("hello-world-metadata.json", cfg_overrides);
+        load_cargo_with_overrides("hello-world-metadata.json", cfg_overrides, None);
     check_crate_graph(
         crate_graph,
         expect_file![
@@ -200,6 +213,27 @@ fn cargo_hello_world_project_model_with_selective_overrides() {
     )
 }
 
+#[test]
+fn cargo_hello_world_project_model_with_extra_cfgs() {
+    let feature = CfgAtom::KeyValue { key: "feature".into(), value: "extra".into() };
+    let extra_cfgs = CfgDiff::new(vec![feature.clone()], Vec::new()).unwrap();
+    let (crate_graph, _proc_macros) = load_cargo_with_overrides(
+        "hello-world-metadata.json",
+        CfgOverrides::default(),
+        Some(extra_cfgs),
+    );
+    let feature = CfgExpr::Atom(feature);
+    for krate in crate_graph.iter() {
+        let data = &crate_graph[krate];
+        assert_eq!(
+            data.cfg_options.check(&feature),
+            Some(data.origin.is_local()),
+            "{:?}",
+            data.display_name
+        );
+    }
+}
+
 #[test]
 fn cargo_hello_world_project_model() {
     let (crate_graph, _proc_macros) = load_cargo("hello-world-metadata.json");
@@ -230,6 +264,262 @@ fn rust_project_is_proc_macro_has_proc_macro_dep() {
     crate_data.dependencies.iter().find(|&dep| dep.name.deref() == "proc_macro").unwrap();
 }
 
+#[test]
+fn rust_project_runnables_and_build_files() {
+    let project = rooted_project_json(get_test_json_file("runnables-project.json"));
+    let root = project.path().to_path_buf();
+
+    let build_files: Vec<_> = project.build_files().map(ToOwned::to_owned).collect();
+    assert_eq!(build_files, [root.join("BUCK"), root.join("buck.lock")]);
+
+    let krate = project.crate_by_root(&root.join("src/lib.rs")).unwrap();
+    let runnables = krate.runnables();
+    assert_eq!(runnables.len(), 2);
+    assert_eq!(runnables[0].kind, RunnableKind::Test);
+    assert_eq!(runnables[0].args.last().map(String::as_str), Some("{test_id}"));
+    assert_eq!(runnables[0].cwd, root);
+    assert_eq!(runnables[1].kind, RunnableKind::Run);
+    assert_eq!(runnables[1].cwd, root.join("src"));
+}
+
+#[test]
+fn bazel_aquery_to_project_json() {
+    let aquery: AqueryOutput = get_test_json_file("bazel-aquery.json");
+    let mut root = "$ROOT$".to_owned();
+    replace_root(&mut root, true);
+    let workspace_root = AbsPathBuf::assert(PathBuf::from(&root));
+    let execution_root = workspace_root.join("execroot");
+
+    let data = aquery_to_project_json(&aquery, &workspace_root, &execution_root);
+    let project = rooted_project_json(serde_json::from_value(data).unwrap());
+    assert_eq!(project.sysroot, Some(execution_root.join("external/rust_toolchain")));
+
+    let crates: Vec<_> = project.crates().map(|(_, krate)| krate).collect();
+    // The two builds of serde with serde_derive are one crate, the one without it is another.
+    assert_eq!(crates.len(), 4);
+    let [hello, serde, serde_without_derive, serde_derive] = &crates[..] else { unreachable!() };
+
+    assert_eq!(hello.root_module, workspace_root.join("src/lib.rs"));
+    assert!(hello.is_workspace_member);
+    assert_eq!(hello.target.as_deref(), Some("x86_64-unknown-linux-gnu"));
+    assert_eq!(hello.cfg.iter().map(ToString::to_string).collect::<Vec<_>>(), ["feature=std"]);
+    assert_eq!(hello.env["CARGO_MANIFEST_DIR"], format!("{execution_root}/src"));
+    let deps: Vec<_> = hello.deps.iter().map(|it| it.name.to_string()).collect();
+    assert_eq!(deps, ["serde", "serde_derive"]);
+
+    assert_eq!(serde.root_module, execution_root.join("external/crates__serde/src/lib.rs"));
+    assert!(!serde.is_workspace_member);
+    assert_eq!(serde.deps.len(), 1);
+    assert_eq!(serde_without_derive.root_module, serde.root_module);
+    assert!(serde_without_derive.deps.is_empty());
+
+    assert!(serde_derive.is_proc_macro);
+    assert_eq!(
+        serde_derive.proc_macro_dylib_path,
+        Some(execution_root.join("bazel-out/k8-opt-exec/bin/libserde_derive-3.so"))
+    );
+}
+
+#[test]
+fn buck2_cquery_to_project_json() {
+    let cquery: FxHashMap<String, CqueryTarget> = get_test_json_file("buck2-cquery.json");
+    let mut root = "$ROOT$".to_owned();
+    replace_root(&mut root, true);
+    let workspace_root = AbsPathBuf::assert(PathBuf::from(&root));
+    let third_party = workspace_root.join("third-party");
+    let cells = FxHashMap::from_iter([
+        ("root".to_owned(), workspace_root.clone()),
+        ("third-party".to_owned(), third_party.clone()),
+    ]);
+
+    let data = cquery_to_project_json(&cquery, &cells, &workspace_root);
+    let project = rooted_project_json(serde_json::from_value(data).unwrap());
+    let crates: Vec<_> = project.crates().map(|(_, krate)| krate).collect();
+    assert_eq!(crates.len(), 4);
+    let [hello, hello_test, serde, serde_derive] = &crates[..] else { unreachable!() };
+
+    assert_eq!(hello.root_module, workspace_root.join("hello/src/lib.rs"));
+    assert!(hello.is_workspace_member);
+    assert_eq!(hello.cfg.iter().map(ToString::to_string).collect::<Vec<_>>(), ["feature=std"]);
+    assert_eq!(hello.env["HELLO"], "1");
+    let deps: Vec<_> = hello.deps.iter().map(|it| it.name.to_string()).collect();
+    assert_eq!(deps, ["serde", "derive"]);
+
+    assert_eq!(hello_test.root_module, hello.root_module);
+    assert_eq!(hello_test.cfg.iter().map(ToString::to_string).collect::<Vec<_>>(), ["test"]);
+
+    assert_eq!(serde.root_module, third_party.join("serde/vendor/src/lib.rs"));
+    assert!(!serde.is_workspace_member);
+    assert!(serde_derive.is_proc_macro);
+}
+
+#[test]
+fn discover_nested_cargo_workspaces() {
+    let temp_dir = TempDir::new("nested-workspaces");
+    let root = temp_dir.path();
+    for manifest in [
+        "a/Cargo.toml",
+        "a/member/Cargo.toml",
+        "group/b/Cargo.toml",
+        "group/deeper/c/Cargo.toml",
+        "target/debug/Cargo.toml",
+        ".hidden/d/Cargo.toml",
+    ] {
+        let path = root.join(manifest);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, "").unwrap();
+    }
+    let root = AbsPathBuf::assert(root.to_path_buf());
+    let discover = |max_depth| {
+        let mut res: Vec<_> = ProjectManifest::discover_nested(&root, max_depth)
+            .unwrap()
+            .iter()
+            .map(|it| it.manifest_path().strip_prefix(&root).unwrap().as_ref().to_owned())
+            .collect();
+        res.sort();
+        res
+    };
+
+    assert!(discover(0).is_empty());
+    assert_eq!(discover(1), [Path::new("a/Cargo.toml")]);
+    assert_eq!(discover(2), [Path::new("a/Cargo.toml"), Path::new("group/b/Cargo.toml")]);
+    assert_eq!(
+        discover(5),
+        [
+            Path::new("a/Cargo.toml"),
+            Path::new("group/b/Cargo.toml"),
+            Path::new("group/deeper/c/Cargo.toml")
+        ]
+    );
+}
+
+#[test]
+fn build_systems_are_only_discovered_when_enabled() {
+    let temp_dir = TempDir::new("build-system");
+    let root = temp_dir.path();
+    let crate_dir = root.join("hello/src");
+    std::fs::create_dir_all(&crate_dir).unwrap();
+    std::fs::write(root.join("MODULE.bazel"), "").unwrap();
+    let crate_dir = AbsPathBuf::assert(crate_dir);
+
+    assert!(ProjectManifest::discover_all(&[crate_dir.clone()], 1, false).is_empty());
+    let discovered = ProjectManifest::discover_all(&[crate_dir], 1, true);
+    assert_eq!(discovered.len(), 1);
+    assert!(matches!(discovered[0], ProjectManifest::BuildSystem(BuildSystem::Bazel, _)));
+}
+
+#[test]
+fn build_script_rerun_directives() {
+    let temp_dir = TempDir::new("build-script-inputs");
+    let root = temp_dir.path();
+    let package = root.join("pkg");
+    let out_dir = root.join("target/debug/build/pkg-0123/out");
+    for (path, contents) in [
+        ("pkg/schema.txt", "v1"),
+        ("pkg/proto/a.proto", "a"),
+        (
+            "target/debug/build/pkg-0123/output",
+            "cargo:rerun-if-changed=schema.txt\n\
+             cargo::rerun-if-changed=proto\n\
+             cargo:rerun-if-env-changed=RA_TEST_SCHEMA\n\
+             cargo:rustc-cfg=foo\n",
+        ),
+    ] {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+    let package = AbsPathBuf::assert(package);
+    let env = FxHashMap::from_iter([("RA_TEST_SCHEMA".to_owned(), "1".to_owned())]);
+
+    let mut output = BuildScriptOutput::default();
+    output.read_rerun_directives(&package, &AbsPathBuf::assert(out_dir), &env);
+    assert_eq!(
+        output.rerun_if_changed,
+        [package.join("schema.txt"), package.join("proto/a.proto")]
+    );
+    assert_eq!(output.rerun_if_env_changed, ["RA_TEST_SCHEMA"]);
+    assert_eq!(output.current_inputs_fingerprint(&env), output.inputs_fingerprint);
+
+    std::fs::write(package.join("schema.txt"), "v2").unwrap();
+    assert_ne!(output.current_inputs_fingerprint(&env), output.inputs_fingerprint);
+    std::fs::write(package.join("schema.txt"), "v1").unwrap();
+    assert_eq!(output.current_inputs_fingerprint(&env), output.inputs_fingerprint);
+    let env = FxHashMap::from_iter([("RA_TEST_SCHEMA".to_owned(), "2".to_owned())]);
+    assert_ne!(output.current_inputs_fingerprint(&env), output.inputs_fingerprint);
+}
+
+#[test]
+fn sysroot_metadata_is_loaded_from_cache() {
+    let temp_dir = TempDir::new("sysroot-cache");
+    let cache_dir = AbsPathBuf::assert(temp_dir.path().to_path_buf());
+    let sysroot_dir = AbsPathBuf::assert(get_test_path("fake-sysroot"));
+    let cache_file = sysroot_metadata_cache_file(&sysroot_dir, &sysroot_dir, &cache_dir);
+    let metadata: cargo_metadata::Metadata = get_test_json_file("hello-world-metadata.json");
+    std::fs::write(cache_file, serde_json::to_vec(&metadata).unwrap()).unwrap();
+    let num_packages = CargoWorkspace::new(metadata).packages().count();
+
+    let sysroot = Sysroot::load(sysroot_dir.clone(), Some(Ok(sysroot_dir)), true, Some(&cache_dir));
+    assert!(matches!(sysroot.mode(), SysrootMode::Workspace(_)));
+    assert_eq!(sysroot.num_packages(), num_packages);
+}
+
+#[test]
+fn stale_sysroot_metadata_is_removed() {
+    let temp_dir = TempDir::new("sysroot-stale");
+    let cache_dir = AbsPathBuf::assert(temp_dir.path().to_path_buf());
+    let sysroot_dir = AbsPathBuf::assert(get_test_path("fake-sysroot"));
+    let cache_file = sysroot_metadata_cache_file(&sysroot_dir, &sysroot_dir, &cache_dir);
+    let file_name = cache_file.file_stem().unwrap().to_str().unwrap();
+    let (location, contents) = file_name.split_once('-').unwrap();
+    // The metadata of an older toolchain at the same location.
+    let outdated_contents = u64::from_str_radix(contents, 16).unwrap().wrapping_add(1);
+    let outdated: PathBuf =
+        cache_dir.join(format!("{location}-{outdated_contents:016x}.json")).into();
+    let other_sysroot: PathBuf = cache_dir.join(format!("{contents}-{contents}.json")).into();
+    for file in [&cache_file, &outdated, &other_sysroot] {
+        std::fs::write(file, "{}").unwrap();
+    }
+
+    remove_stale_sysroot_metadata(&cache_file, &cache_dir);
+    assert!(cache_file.exists());
+    assert!(!outdated.exists());
+    assert!(other_sysroot.exists());
+}
+
+#[test]
+fn crates_index_cache_file() {
+    let mut contents = vec![3, 2, 0, 0, 0];
+    contents.extend_from_slice(b"etag: \"abc\"\0");
+    for (version, entry) in [
+        ("1.0.0", r#"{"name":"foo","vers":"1.0.0","features":{"std":[]},"yanked":false}"#),
+        (
+            "1.1.0",
+            r#"{"name":"foo","vers":"1.1.0","features":{"std":[]},"features2":{"serde":["dep:serde"]},"yanked":true}"#,
+        ),
+        ("2.0.0-rc.1", r#"{"name":"foo","vers":"2.0.0-rc.1","features":{},"yanked":false}"#),
+    ] {
+        contents.extend_from_slice(version.as_bytes());
+        contents.push(0);
+        contents.extend_from_slice(entry.as_bytes());
+        contents.push(0);
+    }
+    let versions = parse_cache_file(&contents);
+    let summary: Vec<_> = versions
+        .iter()
+        .map(|it| (it.version.to_string(), it.features.clone(), it.yanked))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("1.0.0".to_owned(), vec!["std".to_owned()], false),
+            ("1.1.0".to_owned(), vec!["serde".to_owned(), "std".to_owned()], true),
+            ("2.0.0-rc.1".to_owned(), vec![], false),
+        ]
+    );
+    assert_eq!(latest_version(&versions).unwrap().version.to_string(), "1.0.0");
+}
+
 #[test]
 fn crate_graph_dedup_identical() {
     let (mut crate_graph, proc_macros) =
@@ -271,6 +561,7 @@ fn smoke_test_real_sysroot_cargo() {
         AbsPath::assert(Path::new(env!("CARGO_MANIFEST_DIR"))),
         &Default::default(),
         true,
+        None,
     )
     .unwrap());
 
@@ -284,6 +575,8 @@ fn smoke_test_real_sysroot_cargo() {
         toolchain: None,
         target_layout: Err("target_data_layout not loaded".into()),
         cargo_config_extra_env: Default::default(),
+        extra_target: None,
+        extra_cfgs: None,
     };
     project_workspace.to_crate_graph(
         &mut {
@@ -6,10 +6,11 @@
 
 use anyhow::{format_err, Context};
 use base_db::{
-    CrateDisplayName, CrateGraph, CrateId, CrateName, CrateOrigin, Dependency, Edition, Env,
-    FileId, LangCrateOrigin, ProcMacroPaths, TargetLayoutLoadResult,
+    CrateDisplayName, CrateGraph, CrateId, CrateName, CrateOrigin, Dependency, DependencyKind,
+    Edition, Env, FileId, LangCrateOrigin, ProcMacroPaths, TargetLayoutLoadResult,
 };
 use cfg::{CfgAtom, CfgDiff, CfgOptions};
+use itertools::Itertools;
 use paths::{AbsPath, AbsPathBuf};
 use rustc_hash::{FxHashMap, FxHashSet};
 use semver::Version;
@@ -53,6 +54,8 @@ pub struct PackageRoot {
     pub is_local: bool,
     pub include: Vec<AbsPathBuf>,
     pub exclude: Vec<AbsPathBuf>,
+    /// Globs of paths to exclude, relative to the `include` path they are under.
+    pub exclude_globs: Vec<String>,
 }
 
 #[derive(Clone)]
@@ -73,6 +76,12 @@ pub enum ProjectWorkspace {
         toolchain: Option<Version>,
         target_layout: TargetLayoutLoadResult,
         cargo_config_extra_env: FxHashMap<String, String>,
+        /// Set if this workspace was loaded for one of [`CargoConfig::extra_targets`], making its
+        /// crates variants of the ones of the workspace loaded for the primary target.
+        extra_target: Option<String>,
+        /// Set if this workspace is the variant for one of [`CargoConfig::extra_cfg_sets`], whose
+        /// cfgs are enabled for the local crates of the workspace.
+        extra_cfgs: Option<CfgDiff>,
     },
     /// Project workspace was manually specified using a `rust-project.json` file.
     Json {
@@ -117,6 +126,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 toolchain,
                 target_layout,
                 cargo_config_extra_env,
+                extra_target,
+                extra_cfgs,
             } => f
                 .debug_struct("Cargo")
                 .field("root", &cargo.workspace_root().file_name())
@@ -131,6 +142,8 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 .field("toolchain", &toolchain)
                 .field("data_layout", &target_layout)
                 .field("cargo_config_extra_env", &cargo_config_extra_env)
+                .field("extra_target", &extra_target)
+                .field("n_extra_cfgs", &extra_cfgs.as_ref().map_or(0, |it| it.len()))
                 .finish(),
             ProjectWorkspace::Json {
                 project,
@@ -192,6 +205,58 @@ pub fn load(
             .with_context(|| format!("Failed to load the project at {manifest}"))
     }
 
+    /// Loads the workspace for the primary target, and then once more for each of the
+    /// [`CargoConfig::extra_targets`] if it is a cargo workspace. Each of the loaded cargo
+    /// workspaces gets a variant for each of the [`CargoConfig::extra_cfg_sets`] as well.
+    pub fn load_all_variants(
+        manifest: ProjectManifest,
+        config: &CargoConfig,
+        progress: &dyn Fn(String),
+    ) -> Vec<anyhow::Result<ProjectWorkspace>> {
+        let extra_targets: Vec<_> = match manifest {
+            ProjectManifest::CargoToml(_) => config
+                .extra_targets
+                .iter()
+                .filter(|&it| Some(it) != config.target.as_ref())
+                .unique()
+                .collect(),
+            _ => Vec::new(),
+        };
+        let mut res = Vec::with_capacity(1 + extra_targets.len());
+        res.push(ProjectWorkspace::load(manifest.clone(), config, progress));
+        for target in extra_targets {
+            let config = CargoConfig { target: Some(target.clone()), ..config.clone() };
+            let ws = ProjectWorkspace::load(manifest.clone(), &config, progress).map(|mut ws| {
+                if let ProjectWorkspace::Cargo { extra_target, .. } = &mut ws {
+                    *extra_target = Some(target.clone());
+                }
+                ws
+            });
+            res.push(ws.with_context(|| format!("Failed to load the project for {target}")));
+        }
+
+        // The cfg sets don't change what cargo reports, so their variants are copies of the
+        // workspaces loaded for each target.
+        let cfg_variants: Vec<_> = res
+            .iter()
+            .filter_map(|ws| ws.as_ref().ok())
+            .flat_map(|ws| {
+                config.extra_cfg_sets.iter().unique().filter_map(move |cfgs| match ws {
+                    ProjectWorkspace::Cargo { .. } if !cfgs.is_empty() => {
+                        let mut ws = ws.clone();
+                        if let ProjectWorkspace::Cargo { extra_cfgs, .. } = &mut ws {
+                            *extra_cfgs = Some(cfgs.clone());
+                        }
+                        Some(Ok(ws))
+                    }
+                    _ => None,
+                })
+            })
+            .collect();
+        res.extend(cfg_variants);
+        res
+    }
+
     fn load_inner(
         manifest: &ProjectManifest,
         config: &CargoConfig,
@@ -204,33 +269,37 @@ fn load_inner(
                 let data = serde_json::from_str(&file)
                     .with_context(|| format!("Failed to deserialize json file {project_json}"))?;
                 let project_location = project_json.parent().to_path_buf();
-                let project_json: ProjectJson = ProjectJson::new(&project_location, data);
-                ProjectWorkspace::load_inline(
-                    project_json,
-                    config.target.as_deref(),
-                    &config.extra_env,
-                )
+                let mut project: ProjectJson = ProjectJson::new(&project_location, data);
+                project.manifest = Some(project_json.to_path_buf());
+                ProjectWorkspace::load_inline(project, config.target.as_deref(), &config.extra_env)
+            }
+            ProjectManifest::BuildSystem(build_system, root_marker) => {
+                progress(format!("querying {build_system:?} for the crate graph"));
+                let project =
+                    build_system.load(root_marker, &config.build_system, &config.extra_env)?;
+                ProjectWorkspace::load_inline(project, config.target.as_deref(), &config.extra_env)
             }
             ProjectManifest::CargoToml(cargo_toml) => {
+                let cache_dir = config.sysroot_cache_dir.as_deref();
                 let sysroot = match (&config.sysroot, &config.sysroot_src) {
                     (Some(RustLibSource::Path(path)), None) => {
-                        Sysroot::with_sysroot_dir(path.clone(), config.sysroot_query_metadata).map_err(|e| {
+                        Sysroot::with_sysroot_dir(path.clone(), config.sysroot_query_metadata, cache_dir).map_err(|e| {
                           Some(format!("Failed to find sysroot at {path}:{e}"))
                         })
                     }
                     (Some(RustLibSource::Discover), None) => {
-                        Sysroot::discover(cargo_toml.parent(), &config.extra_env, config.sysroot_query_metadata).map_err(|e| {
+                        Sysroot::discover(cargo_toml.parent(), &config.extra_env, config.sysroot_query_metadata, cache_dir).map_err(|e| {
                             Some(format!("Failed to find sysroot for Cargo.toml file {cargo_toml}. Is rust-src installed? {e}"))
                         })
                     }
                     (Some(RustLibSource::Path(sysroot)), Some(sysroot_src)) => {
-                        Ok(Sysroot::load(sysroot.clone(), Some(Ok(sysroot_src.clone())), config.sysroot_query_metadata))
+                        Ok(Sysroot::load(sysroot.clone(), Some(Ok(sysroot_src.clone())), config.sysroot_query_metadata, cache_dir))
                     }
                     (Some(RustLibSource::Discover), Some(sysroot_src)) => {
                         Sysroot::discover_with_src_override(
                             cargo_toml.parent(),
                             &config.extra_env,
-                            sysroot_src.clone(), config.sysroot_query_metadata,
+                            sysroot_src.clone(), config.sysroot_query_metadata, cache_dir,
                         ).map_err(|e| {
                             Some(format!("Failed to find sysroot for Cargo.toml file {cargo_toml}. Is rust-src installed? {e}"))
                         })
@@ -339,6 +408,8 @@ fn load_inner(
                         .map(Arc::from)
                         .map_err(|it| Arc::from(it.to_string())),
                     cargo_config_extra_env,
+                    extra_target: None,
+                    extra_cfgs: None,
                 }
             }
         };
@@ -353,13 +424,13 @@ pub fn load_inline(
     ) -> ProjectWorkspace {
         let sysroot = match (project_json.sysroot.clone(), project_json.sysroot_src.clone()) {
             (Some(sysroot), Some(sysroot_src)) => {
-                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false))
+                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false, None))
             }
             (Some(sysroot), None) => {
                 // assume sysroot is structured like rustup's and guess `sysroot_src`
                 let sysroot_src =
                     sysroot.join("lib").join("rustlib").join("src").join("rust").join("library");
-                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false))
+                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false, None))
             }
             (None, Some(sysroot_src)) => {
                 // assume sysroot is structured like rustup's and guess `sysroot`
@@ -367,7 +438,7 @@ pub fn load_inline(
                 for _ in 0..5 {
                     sysroot.pop();
                 }
-                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false))
+                Ok(Sysroot::load(sysroot, Some(Ok(sysroot_src)), false, None))
             }
             (None, None) => Err(None),
         };
@@ -403,9 +474,10 @@ pub fn load_detached_files(
         detached_files: Vec<AbsPathBuf>,
         config: &CargoConfig,
     ) -> anyhow::Result<ProjectWorkspace> {
+        let cache_dir = config.sysroot_cache_dir.as_deref();
         let sysroot = match &config.sysroot {
             Some(RustLibSource::Path(path)) => {
-                Sysroot::with_sysroot_dir(path.clone(), config.sysroot_query_metadata)
+                Sysroot::with_sysroot_dir(path.clone(), config.sysroot_query_metadata, cache_dir)
                     .map_err(|e| Some(format!("Failed to find sysroot at {path}:{e}")))
             }
             Some(RustLibSource::Discover) => {
@@ -413,13 +485,12 @@ pub fn load_detached_files(
                     .first()
                     .and_then(|it| it.parent())
                     .ok_or_else(|| format_err!("No detached files to load"))?;
-                Sysroot::discover(dir, &config.extra_env, config.sysroot_query_metadata).map_err(
-                    |e| {
+                Sysroot::discover(dir, &config.extra_env, config.sysroot_query_metadata, cache_dir)
+                    .map_err(|e| {
                         Some(format!(
                             "Failed to find sysroot for {dir}. Is rust-src installed? {e}"
                         ))
-                    },
-                )
+                    })
             }
             None => Err(None),
         };
@@ -437,14 +508,87 @@ pub fn run_build_scripts(
         config: &CargoConfig,
         progress: &dyn Fn(String),
     ) -> anyhow::Result<WorkspaceBuildScripts> {
+        self.run_build_scripts_for(config, progress, &[])
+    }
+
+    /// Re-runs the build scripts of the given packages of this [`ProjectWorkspace`] and returns
+    /// the current build data with their outputs updated.
+    ///
+    /// Falls back to running all build scripts if a custom build script command is configured,
+    /// as that can't be restricted to a set of packages.
+    pub fn rerun_build_scripts(
+        &self,
+        packages: &[Package],
+        config: &CargoConfig,
+        progress: &dyn Fn(String),
+    ) -> anyhow::Result<WorkspaceBuildScripts> {
+        let ProjectWorkspace::Cargo { cargo, build_scripts, .. } = self else {
+            return Ok(WorkspaceBuildScripts::default());
+        };
+        if packages.is_empty() {
+            return Ok(build_scripts.clone());
+        }
+        if config.run_build_script_command.is_some() {
+            return self.run_build_scripts(config, progress);
+        }
+        let specs: Vec<_> = packages
+            .iter()
+            .map(|&pkg| format!("{}@{}", cargo[pkg].name, cargo[pkg].version))
+            .collect();
+        let mut res = build_scripts.clone();
+        res.merge(self.run_build_scripts_for(config, progress, &specs)?);
+        Ok(res)
+    }
+
+    /// Returns the packages whose build scripts have inputs that changed since they last ran.
+    /// Only packages with an input file `is_changed` accepts, or with env var inputs, are checked.
+    pub fn stale_build_scripts(
+        &self,
+        is_changed: &dyn Fn(&AbsPath) -> bool,
+        extra_env: &FxHashMap<String, String>,
+    ) -> Vec<Package> {
+        match self {
+            ProjectWorkspace::Cargo { build_scripts, .. } => {
+                build_scripts.stale_packages(is_changed, extra_env)
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns the files the build scripts of this workspace declared as their inputs.
+    pub fn build_script_inputs(&self) -> Vec<AbsPathBuf> {
         match self {
-            ProjectWorkspace::Cargo { cargo, toolchain, sysroot, .. } => {
+            ProjectWorkspace::Cargo { build_scripts, .. } => {
+                build_scripts.inputs().map(ToOwned::to_owned).collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn run_build_scripts_for(
+        &self,
+        config: &CargoConfig,
+        progress: &dyn Fn(String),
+        packages: &[String],
+    ) -> anyhow::Result<WorkspaceBuildScripts> {
+        match self {
+            ProjectWorkspace::Cargo { cargo, toolchain, sysroot, extra_target, .. } => {
+                let extra_target_config;
+                let config = match extra_target {
+                    Some(target) => {
+                        extra_target_config =
+                            CargoConfig { target: Some(target.clone()), ..config.clone() };
+                        &extra_target_config
+                    }
+                    None => config,
+                };
                 WorkspaceBuildScripts::run_for_workspace(
                     config,
                     cargo,
                     progress,
                     toolchain,
                     sysroot.as_ref().ok(),
+                    packages,
                 )
                 .with_context(|| {
                     format!("Failed to run build scripts for {}", cargo.workspace_root())
@@ -456,6 +600,23 @@ pub fn run_build_scripts(
         }
     }
 
+    /// Returns the index of the workspace in `workspaces` this workspace is the variant for one
+    /// of the [`CargoConfig::extra_cfg_sets`] of.
+    fn cfg_variant_of(&self, workspaces: &[ProjectWorkspace]) -> Option<usize> {
+        let ProjectWorkspace::Cargo { cargo, extra_target, extra_cfgs: Some(_), .. } = self else {
+            return None;
+        };
+        workspaces.iter().position(|it| match it {
+            ProjectWorkspace::Cargo {
+                cargo: o_cargo,
+                extra_target: o_target,
+                extra_cfgs: None,
+                ..
+            } => o_cargo.workspace_root() == cargo.workspace_root() && o_target == extra_target,
+            _ => false,
+        })
+    }
+
     /// Runs the build scripts for the given [`ProjectWorkspace`]s. Depending on the invocation
     /// strategy this may run a single build process for all project workspaces.
     pub fn run_all_build_scripts(
@@ -467,36 +628,70 @@ pub fn run_all_build_scripts(
         if matches!(config.invocation_strategy, InvocationStrategy::PerWorkspace)
             || config.run_build_script_command.is_none()
         {
-            return workspaces.iter().map(|it| it.run_build_scripts(config, progress)).collect();
+            let mut res: Vec<anyhow::Result<WorkspaceBuildScripts>> =
+                Vec::with_capacity(workspaces.len());
+            for ws in workspaces {
+                // The variants for the extra cfg sets are copies of the workspace loaded for their
+                // target, so they share its build script outputs.
+                let copied = ws.cfg_variant_of(workspaces).and_then(|idx| res.get(idx));
+                res.push(match copied {
+                    Some(Ok(build_scripts)) => Ok(build_scripts.clone()),
+                    Some(Err(e)) => Err(anyhow::format_err!("{e:#}")),
+                    None => ws.run_build_scripts(config, progress),
+                });
+            }
+            return res;
         }
 
-        let cargo_ws: Vec<_> = workspaces
+        // The workspaces loaded for extra targets need the build script outputs of their own
+        // target, so the command is run once per target.
+        let targets: Vec<_> = workspaces
             .iter()
             .filter_map(|it| match it {
-                ProjectWorkspace::Cargo { cargo, .. } => Some(cargo),
+                ProjectWorkspace::Cargo { extra_target, .. } => Some(extra_target.as_deref()),
                 _ => None,
             })
+            .unique()
             .collect();
-        let outputs =
-            &mut match WorkspaceBuildScripts::run_once(config, &cargo_ws, progress, workspace_root)
-            {
-                Ok(it) => Ok(it.into_iter()),
-                // io::Error is not Clone?
-                Err(e) => Err(sync::Arc::new(e)),
-            };
-
-        workspaces
-            .iter()
-            .map(|it| match it {
-                ProjectWorkspace::Cargo { cargo, .. } => match outputs {
-                    Ok(outputs) => Ok(outputs.next().unwrap()),
-                    Err(e) => Err(e.clone()).with_context(|| {
-                        format!("Failed to run build scripts for {}", cargo.workspace_root())
-                    }),
-                },
-                _ => Ok(WorkspaceBuildScripts::default()),
-            })
-            .collect()
+        let mut res: Vec<_> =
+            workspaces.iter().map(|_| Ok(WorkspaceBuildScripts::default())).collect();
+        for target in targets {
+            let (indices, cargo_ws): (Vec<_>, Vec<_>) = workspaces
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, it)| match it {
+                    ProjectWorkspace::Cargo { cargo, extra_target, .. }
+                        if extra_target.as_deref() == target =>
+                    {
+                        Some((idx, cargo))
+                    }
+                    _ => None,
+                })
+                .unzip();
+            match WorkspaceBuildScripts::run_once(
+                config,
+                &cargo_ws,
+                progress,
+                workspace_root,
+                target,
+            ) {
+                Ok(outputs) => {
+                    for (idx, output) in indices.into_iter().zip(outputs) {
+                        res[idx] = Ok(output);
+                    }
+                }
+                Err(e) => {
+                    // io::Error is not Clone?
+                    let e = sync::Arc::new(e);
+                    for (idx, cargo) in indices.into_iter().zip(cargo_ws) {
+                        res[idx] = Err(e.clone()).with_context(|| {
+                            format!("Failed to run build scripts for {}", cargo.workspace_root())
+                        });
+                    }
+                }
+            }
+        }
+        res
     }
 
     pub fn set_build_scripts(&mut self, bs: WorkspaceBuildScripts) {
@@ -516,6 +711,36 @@ pub fn workspace_definition_path(&self) -> Option<&AbsPath> {
         }
     }
 
+    /// Returns the files, besides cargo's manifests and lockfiles, whose changes require the
+    /// workspace to be reloaded.
+    pub fn build_files(&self) -> Vec<AbsPathBuf> {
+        let mut res = match self {
+            ProjectWorkspace::Json { project, .. } => {
+                project.build_files().map(ToOwned::to_owned).collect()
+            }
+            ProjectWorkspace::Cargo { .. } | ProjectWorkspace::DetachedFiles { .. } => Vec::new(),
+        };
+        res.extend(self.toolchain_file());
+        res
+    }
+
+    /// Returns the `rust-toolchain.toml` or `rust-toolchain` file selecting the toolchain, and with
+    /// it the sysroot, of this workspace. Like rustup, this looks in the workspace root and its
+    /// ancestors.
+    fn toolchain_file(&self) -> Option<AbsPathBuf> {
+        let root = match self {
+            ProjectWorkspace::Cargo { cargo, .. } => cargo.workspace_root(),
+            ProjectWorkspace::Json { project, .. } => project.path(),
+            ProjectWorkspace::DetachedFiles { files, .. } => files.first()?.parent()?,
+        };
+        iter::successors(Some(root), |it| it.parent()).find_map(|dir| {
+            ["rust-toolchain.toml", "rust-toolchain"]
+                .into_iter()
+                .map(|file| dir.join(file))
+                .find(|it| fs::metadata(it).is_ok())
+        })
+    }
+
     pub fn find_sysroot_proc_macro_srv(&self) -> anyhow::Result<AbsPathBuf> {
         match self {
             ProjectWorkspace::Cargo { sysroot: Ok(sysroot), .. }
@@ -562,7 +787,12 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                                 pkg_root.join("examples"),
                                 pkg_root.join("benches"),
                             ];
-                            Some(PackageRoot { is_local: false, include, exclude })
+                            Some(PackageRoot {
+                                is_local: false,
+                                include,
+                                exclude,
+                                exclude_globs: Vec::new(),
+                            })
                         })
                         .collect(),
                     SysrootMode::Stitched(_) => vec![],
@@ -572,6 +802,7 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                     is_local: false,
                     include: sysroot.src_root().map(|it| it.to_path_buf()).into_iter().collect(),
                     exclude: Vec::new(),
+                    exclude_globs: Vec::new(),
                 });
                 r
             })
@@ -589,6 +820,7 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                     is_local: krate.is_workspace_member,
                     include: krate.include.clone(),
                     exclude: krate.exclude.clone(),
+                    exclude_globs: krate.exclude_globs.clone(),
                 })
                 .collect::<FxHashSet<_>>()
                 .into_iter()
@@ -604,6 +836,8 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                 toolchain: _,
                 target_layout: _,
                 cargo_config_extra_env: _,
+                extra_target: _,
+                extra_cfgs: _,
             } => {
                 cargo
                     .packages()
@@ -641,7 +875,7 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                             exclude.push(pkg_root.join("examples"));
                             exclude.push(pkg_root.join("benches"));
                         }
-                        PackageRoot { is_local, include, exclude }
+                        PackageRoot { is_local, include, exclude, exclude_globs: Vec::new() }
                     })
                     .chain(mk_sysroot(sysroot.as_ref()))
                     .chain(rustc.iter().map(|a| a.as_ref()).flat_map(|(rustc, _)| {
@@ -649,6 +883,7 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                             is_local: false,
                             include: vec![rustc[krate].manifest.parent().to_path_buf()],
                             exclude: Vec::new(),
+                            exclude_globs: Vec::new(),
                         })
                     }))
                     .collect()
@@ -659,6 +894,7 @@ pub fn to_roots(&self) -> Vec<PackageRoot> {
                     is_local: true,
                     include: vec![detached_file.clone()],
                     exclude: Vec::new(),
+                    exclude_globs: Vec::new(),
                 })
                 .chain(mk_sysroot(sysroot.as_ref()))
                 .collect(),
@@ -715,6 +951,8 @@ pub fn to_crate_graph(
                 toolchain: _,
                 target_layout: _,
                 cargo_config_extra_env: _,
+                extra_target: _,
+                extra_cfgs,
             } => cargo_to_crate_graph(
                 load,
                 rustc.as_ref().map(|a| a.as_ref()).ok(),
@@ -722,6 +960,7 @@ pub fn to_crate_graph(
                 sysroot.as_ref().ok(),
                 rustc_cfg.clone(),
                 cfg_overrides,
+                extra_cfgs.as_ref(),
                 build_scripts,
             ),
             ProjectWorkspace::DetachedFiles { files, sysroot, rustc_cfg } => {
@@ -747,6 +986,8 @@ pub fn eq_ignore_build_data(&self, other: &Self) -> bool {
                     cfg_overrides,
                     toolchain,
                     cargo_config_extra_env,
+                    extra_target,
+                    extra_cfgs,
                     build_scripts: _,
                     target_layout: _,
                 },
@@ -758,6 +999,8 @@ pub fn eq_ignore_build_data(&self, other: &Self) -> bool {
                     cfg_overrides: o_cfg_overrides,
                     toolchain: o_toolchain,
                     cargo_config_extra_env: o_cargo_config_extra_env,
+                    extra_target: o_extra_target,
+                    extra_cfgs: o_extra_cfgs,
                     build_scripts: _,
                     target_layout: _,
                 },
@@ -769,6 +1012,8 @@ pub fn eq_ignore_build_data(&self, other: &Self) -> bool {
                     && toolchain == o_toolchain
                     && sysroot == o_sysroot
                     && cargo_config_extra_env == o_cargo_config_extra_env
+                    && extra_target == o_extra_target
+                    && extra_cfgs == o_extra_cfgs
             }
             (
                 Self::Json { project, sysroot, rustc_cfg, toolchain, target_layout: _ },
@@ -909,6 +1154,7 @@ fn cargo_to_crate_graph(
     sysroot: Option<&Sysroot>,
     rustc_cfg: Vec<CfgFlag>,
     override_cfg: &CfgOverrides,
+    extra_cfgs: Option<&CfgDiff>,
     build_scripts: &WorkspaceBuildScripts,
 ) -> (CrateGraph, ProcMacroPaths) {
     let _p = tracing::span!(tracing::Level::INFO, "cargo_to_crate_graph").entered();
@@ -954,6 +1200,11 @@ fn cargo_to_crate_graph(
 
                 cfg_options.apply_diff(diff.clone());
             };
+            // Dependencies are left alone, so that the variants share them with the primary
+            // workspace in the crate graph.
+            if let Some(extra_cfgs) = extra_cfgs.filter(|_| cargo[pkg].is_local) {
+                cfg_options.apply_diff(extra_cfgs.clone());
+            }
             cfg_options
         };
 
@@ -1046,7 +1297,12 @@ fn cargo_to_crate_graph(
                     continue;
                 }
 
-                add_dep(crate_graph, from, name.clone(), to)
+                let kind = match dep.kind {
+                    DepKind::Normal => DependencyKind::Normal,
+                    DepKind::Dev => DependencyKind::Dev,
+                    DepKind::Build => DependencyKind::Build,
+                };
+                add_dep_inner(crate_graph, from, Dependency::with_kind(name.clone(), to, kind))
             }
         }
     }
@@ -1345,6 +1601,7 @@ fn sysroot_to_crate_graph(
                 None,
                 rustc_cfg,
                 &CfgOverrides::default(),
+                None,
                 &WorkspaceBuildScripts::default(),
             );
 
@@ -1498,6 +1755,10 @@ fn inject_cargo_env(package: &PackageData, env: &mut Env) {
     env.set("CARGO_PKG_VERSION_MINOR", package.version.minor.to_string());
     env.set("CARGO_PKG_VERSION_PATCH", package.version.patch.to_string());
     env.set("CARGO_PKG_VERSION_PRE", package.version.pre.to_string());
+    env.set(
+        "CARGO_PKG_RUST_VERSION",
+        package.rust_version.as_ref().map(ToString::to_string).unwrap_or_default(),
+    );
 
     env.set("CARGO_PKG_AUTHORS", String::new());
 
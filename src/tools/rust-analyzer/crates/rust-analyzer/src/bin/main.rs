@@ -66,11 +66,9 @@ fn actual_main() -> anyhow::Result<ExitCode> {
             // a secondary latency-sensitive thread with an increased stack size.
             // We use this thread intent because any delay in the main loop
             // will make actions like hitting enter in the editor slow.
-            with_extra_thread(
-                "LspServer",
-                stdx::thread::ThreadIntent::LatencySensitive,
-                run_server,
-            )?;
+            with_extra_thread("LspServer", stdx::thread::ThreadIntent::LatencySensitive, || {
+                run_server(cmd.listen)
+            })?;
         }
         flags::RustAnalyzerCmd::Parse(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::Symbols(cmd) => cmd.run()?,
@@ -81,6 +79,9 @@ fn actual_main() -> anyhow::Result<ExitCode> {
         flags::RustAnalyzerCmd::Search(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::Lsif(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::Scip(cmd) => cmd.run()?,
+        flags::RustAnalyzerCmd::UnsafeAudit(cmd) => cmd.run()?,
+        flags::RustAnalyzerCmd::ApiDiff(cmd) => cmd.run()?,
+        flags::RustAnalyzerCmd::ExplainUnmetBound(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::RunTests(cmd) => cmd.run()?,
         flags::RustAnalyzerCmd::RustcTests(cmd) => cmd.run()?,
     }
@@ -157,15 +158,24 @@ fn with_extra_thread(
     Ok(())
 }
 
-fn run_server() -> anyhow::Result<()> {
+fn run_server(listen: Option<String>) -> anyhow::Result<()> {
     tracing::info!("server version {} will start", rust_analyzer::version());
 
-    let (connection, io_threads) = Connection::stdio();
+    let (connection, io_threads) = match listen {
+        Some(addr) => {
+            tracing::info!("waiting for clients on {addr}");
+            (rust_analyzer::daemon::listen(&addr)?, None)
+        }
+        None => {
+            let (connection, io_threads) = Connection::stdio();
+            (connection, Some(io_threads))
+        }
+    };
 
     let (initialize_id, initialize_params) = match connection.initialize_start() {
         Ok(it) => it,
         Err(e) => {
-            if e.channel_is_disconnected() {
+            if let (true, Some(io_threads)) = (e.channel_is_disconnected(), io_threads) {
                 io_threads.join()?;
             }
             return Err(e.into());
@@ -239,7 +249,7 @@ fn run_server() -> anyhow::Result<()> {
     let initialize_result = serde_json::to_value(initialize_result).unwrap();
 
     if let Err(e) = connection.initialize_finish(initialize_id, initialize_result) {
-        if e.channel_is_disconnected() {
+        if let (true, Some(io_threads)) = (e.channel_is_disconnected(), io_threads) {
             io_threads.join()?;
         }
         return Err(e.into());
@@ -251,7 +261,9 @@ fn run_server() -> anyhow::Result<()> {
 
     rust_analyzer::main_loop(config, connection)?;
 
-    io_threads.join()?;
+    if let Some(io_threads) = io_threads {
+        io_threads.join()?;
+    }
     tracing::info!("server did shut down");
     Ok(())
 }
@@ -2,7 +2,7 @@
 use ide_db::line_index::WideEncoding;
 use lsp_types::{
     CallHierarchyServerCapability, ClientCapabilities, CodeActionKind, CodeActionOptions,
-    CodeActionProviderCapability, CodeLensOptions, CompletionOptions,
+    CodeActionProviderCapability, CodeLensOptions, ColorProviderCapability, CompletionOptions,
     CompletionOptionsCompletionItem, DeclarationCapability, DocumentOnTypeFormattingOptions,
     FileOperationFilter, FileOperationPattern, FileOperationPatternKind,
     FileOperationRegistrationOptions, FoldingRangeProviderCapability, HoverProviderCapability,
@@ -85,7 +85,7 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
         })),
         linked_editing_range_provider: None,
         document_link_provider: None,
-        color_provider: None,
+        color_provider: Some(ColorProviderCapability::Simple(true)),
         execute_command_provider: None,
         workspace: Some(WorkspaceServerCapabilities {
             workspace_folders: Some(WorkspaceFoldersServerCapabilities {
@@ -152,7 +152,7 @@ pub fn server_capabilities(config: &Config) -> ServerCapabilities {
             "openCargoToml": true,
             "parentModule": true,
             "runnables": {
-                "kinds": [ "cargo" ],
+                "kinds": [ "cargo", "shell" ],
             },
             "ssr": true,
             "workspaceSymbolScopeKindFiltering": true,
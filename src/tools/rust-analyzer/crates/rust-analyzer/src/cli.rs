@@ -3,7 +3,9 @@
 #![allow(clippy::print_stdout, clippy::print_stderr)]
 
 mod analysis_stats;
+mod api_diff;
 mod diagnostics;
+mod explain_unmet_bound;
 pub mod flags;
 mod highlight;
 mod lsif;
@@ -13,17 +15,19 @@
 mod scip;
 mod ssr;
 mod symbols;
+mod unsafe_audit;
 
 mod progress_report;
 
-use std::io::Read;
+use std::{io::Read, path::Path};
 
 use anyhow::Result;
 use hir::{Module, Name};
 use hir_ty::db::HirDatabase;
-use ide::AnalysisHost;
+use ide::{AnalysisHost, FileId};
 use itertools::Itertools;
-use vfs::Vfs;
+use rustc_hash::FxHashSet;
+use vfs::{AbsPathBuf, Vfs, VfsPath};
 
 #[derive(Clone, Copy)]
 pub enum Verbosity {
@@ -48,6 +52,22 @@ fn read_stdin() -> anyhow::Result<String> {
     Ok(buff)
 }
 
+/// Reads the files listed in `path`, one per line and relative to the current directory, like the
+/// output of `git diff --name-only`. Files that aren't loaded, like deleted ones, are skipped.
+fn read_file_list(vfs: &Vfs, path: &Path) -> anyhow::Result<FxHashSet<FileId>> {
+    let current_dir = std::env::current_dir()?;
+    let files = std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(|line| {
+            let path = AbsPathBuf::assert(current_dir.join(line)).normalize();
+            vfs.file_id(&VfsPath::from(path))
+        })
+        .collect();
+    Ok(files)
+}
+
 fn report_metric(metric: &str, value: u64, unit: &str) {
     if std::env::var("RA_METRICS").is_err() {
         return;
@@ -100,6 +100,13 @@ pub fn run(self, verbosity: Verbosity) -> anyhow::Result<()> {
         }
         eprintln!(")");
 
+        let collect_query_stats = self.query_stats.is_some()
+            || self.query_flamegraph.is_some()
+            || self.baseline.is_some();
+        if collect_query_stats {
+            ide_db::query_stats::start();
+        }
+
         let mut analysis_sw = self.stop_watch();
 
         let mut krates = Crate::all(db);
@@ -251,6 +258,40 @@ pub fn run(self, verbosity: Verbosity) -> anyhow::Result<()> {
             eprintln!("{}", profile::countme::get_all());
         }
 
+        let mut query_regressions = 0;
+        if collect_query_stats {
+            let stats = ide_db::query_stats::finish();
+            let stats = QueryStatsFile {
+                queries: stats
+                    .queries
+                    .into_iter()
+                    .map(|it| QueryStatEntry {
+                        name: it.name,
+                        executions: it.executions,
+                        total_time_us: it.total_time.as_micros() as u64,
+                        self_time_us: it.self_time.as_micros() as u64,
+                        self_memory_bytes: it.self_memory.bytes() as i64,
+                    })
+                    .collect(),
+                folded_stacks: stats.folded_stacks,
+            };
+            if let Some(path) = &self.query_stats {
+                std::fs::write(path, serde_json::to_string_pretty(&stats)?)?;
+            }
+            if let Some(path) = &self.query_flamegraph {
+                std::fs::write(path, &stats.folded_stacks)?;
+            }
+            if let Some(path) = &self.baseline {
+                let baseline: QueryStatsFile =
+                    serde_json::from_str(&std::fs::read_to_string(path)?)?;
+                query_regressions = report_query_regressions(
+                    &baseline,
+                    &stats,
+                    self.regression_threshold.unwrap_or(10),
+                );
+            }
+        }
+
         if self.source_stats {
             let mut total_file_size = Bytes::default();
             for e in ide_db::base_db::ParseQuery.in_db(db).entries::<Vec<_>>() {
@@ -269,6 +310,9 @@ pub fn run(self, verbosity: Verbosity) -> anyhow::Result<()> {
             print_memory_usage(host, vfs);
         }
 
+        if query_regressions > 0 {
+            anyhow::bail!("{query_regressions} queries regressed compared to the baseline");
+        }
         Ok(())
     }
 
@@ -963,17 +1007,25 @@ fn run_ide_things(&self, analysis: Analysis, mut file_ids: Vec<FileId>) {
             _ = analysis.diagnostics(
                 &DiagnosticsConfig {
                     enabled: true,
+                    style: Default::default(),
                     proc_macros_enabled: true,
                     proc_attr_macros_enabled: true,
                     disable_experimental: false,
                     disabled: Default::default(),
+                    dead_code: false,
+                    unused_pub: false,
+                    unsafe_op_in_unsafe_fn: false,
+                    leftover_macros: Default::default(),
+                    todo_comments: false,
                     expr_fill_default: Default::default(),
                     insert_use: ide_db::imports::insert_use::InsertUseConfig {
                         granularity: ide_db::imports::insert_use::ImportGranularity::Crate,
                         enforce_granularity: true,
                         prefix_kind: hir::PrefixKind::ByCrate,
                         group: true,
+                        group_style: ide_db::imports::insert_use::ImportGroupStyle::Default,
                         skip_glob_imports: true,
+                        prefer_reexports: false,
                     },
                     prefer_no_std: false,
                     prefer_prelude: true,
@@ -1006,6 +1058,11 @@ fn run_ide_things(&self, analysis: Analysis, mut file_ids: Vec<FileId>) {
                     closing_brace_hints_min_lines: Some(20),
                     fields_to_resolve: InlayFieldsToResolve::empty(),
                     range_exclusive_hints: true,
+                    conversion_hints: true,
+                    generic_arg_hints: true,
+                    generic_arg_hints_functions: Vec::new(),
+                    macro_expansion_hints: true,
+                    macro_expansion_hints_macros: Vec::new(),
                 },
                 file_id,
                 None,
@@ -1021,6 +1078,8 @@ fn run_ide_things(&self, analysis: Analysis, mut file_ids: Vec<FileId>) {
                         annotate_references: false,
                         annotate_method_references: false,
                         annotate_enum_variant_references: false,
+                        annotate_enum_variant_usages: false,
+                        annotate_stack_usage: false,
                         location: ide::AnnotationLocation::AboveName,
                     },
                     file_id,
@@ -1040,6 +1099,68 @@ fn stop_watch(&self) -> StopWatch {
     }
 }
 
+/// The `--query-stats` output, and `--baseline` input.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct QueryStatsFile {
+    queries: Vec<QueryStatEntry>,
+    #[serde(skip)]
+    folded_stacks: String,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct QueryStatEntry {
+    name: String,
+    executions: u64,
+    total_time_us: u64,
+    self_time_us: u64,
+    self_memory_bytes: i64,
+}
+
+/// Prints the queries that were executed more often than in the `baseline`, or took more than
+/// `threshold` percent more self time or memory, and returns how many there are.
+fn report_query_regressions(
+    baseline: &QueryStatsFile,
+    current: &QueryStatsFile,
+    threshold: u32,
+) -> usize {
+    // differences below these are noise
+    const MIN_TIME_US: u64 = 10_000;
+    const MIN_MEMORY_BYTES: i64 = 1024 * 1024;
+
+    let exceeds = |current: i64, baseline: i64, min: i64| {
+        current - baseline >= min && (current - baseline) * 100 > baseline * threshold as i64
+    };
+    let baseline: FxHashMap<_, _> =
+        baseline.queries.iter().map(|query| (query.name.as_str(), query)).collect();
+    let mut regressions = 0;
+    for query in &current.queries {
+        let Some(base) = baseline.get(query.name.as_str()) else { continue };
+        let mut reasons = Vec::new();
+        if query.executions > base.executions {
+            reasons.push(format!("executed {} times, was {}", query.executions, base.executions));
+        }
+        if exceeds(query.self_time_us as i64, base.self_time_us as i64, MIN_TIME_US as i64) {
+            reasons.push(format!(
+                "took {}ms, was {}ms",
+                query.self_time_us / 1000,
+                base.self_time_us / 1000
+            ));
+        }
+        if exceeds(query.self_memory_bytes, base.self_memory_bytes, MIN_MEMORY_BYTES) {
+            reasons.push(format!(
+                "allocated {}, was {}",
+                Bytes::new(query.self_memory_bytes as isize),
+                Bytes::new(base.self_memory_bytes as isize)
+            ));
+        }
+        if !reasons.is_empty() {
+            regressions += 1;
+            eprintln!("{}: {}", query.name, reasons.join(", "));
+        }
+    }
+    regressions
+}
+
 fn location_csv_expr(db: &RootDatabase, vfs: &Vfs, sm: &BodySourceMap, expr_id: ExprId) -> String {
     let src = match sm.expr_syntax(expr_id) {
         Ok(s) => s,
@@ -0,0 +1,123 @@
+//! Compares the public API of two versions of a workspace, for checking semver compatibility.
+
+use std::{env, path::Path};
+
+use hir::Crate;
+use ide::{ApiChange, ApiChangeKind, ApiItem, SemverImpact};
+use ide_db::{base_db::SourceDatabase, FxHashSet};
+use load_cargo::{load_workspace, LoadCargoConfig, ProcMacroServerChoice};
+use project_model::{CargoConfig, ProjectManifest, ProjectWorkspace, RustLibSource, TargetKind};
+use serde_json::json;
+use vfs::{AbsPathBuf, VfsPath};
+
+use crate::cli::flags;
+
+impl flags::ApiDiff {
+    pub fn run(self) -> anyhow::Result<()> {
+        let old = self.public_api(&self.old)?;
+        let new = self.public_api(&self.new)?;
+        let changes = ide::diff_public_api(&old, &new);
+
+        if self.json {
+            let changes: Vec<_> = changes
+                .iter()
+                .map(|change| {
+                    json!({
+                        "kind": change_kind(change.kind),
+                        "path": change.path,
+                        "old": change.old,
+                        "new": change.new,
+                        "impact": impact(change.impact),
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&changes)?);
+            return Ok(());
+        }
+        for ApiChange { kind, path, old, new, impact: change_impact } in &changes {
+            println!("{} {} {path}", impact(*change_impact), change_kind(*kind));
+            if let Some(old) = old.as_ref().filter(|_| *kind == ApiChangeKind::Changed) {
+                println!("    - {old}");
+            }
+            if let Some(new) = new.as_ref().filter(|_| *kind == ApiChangeKind::Changed) {
+                println!("    + {new}");
+            }
+        }
+        println!();
+        let bump = match changes.iter().map(|change| change.impact).max() {
+            Some(SemverImpact::Major) => "a major",
+            Some(SemverImpact::Minor) => "a minor",
+            None => "no",
+        };
+        println!("{} changes, {bump} version bump is required", changes.len());
+        Ok(())
+    }
+
+    /// The public API of the library crates of the workspace members at `path`.
+    fn public_api(&self, path: &Path) -> anyhow::Result<Vec<ApiItem>> {
+        let cargo_config =
+            CargoConfig { sysroot: Some(RustLibSource::Discover), ..Default::default() };
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro_server: ProcMacroServerChoice::Sysroot,
+            prefill_caches: false,
+        };
+        let no_progress = &|_| ();
+        let path = AbsPathBuf::assert(env::current_dir()?.join(path));
+        let manifest = ProjectManifest::discover_single(&path)?;
+        let mut workspace = ProjectWorkspace::load(manifest, &cargo_config, no_progress)?;
+        if load_cargo_config.load_out_dirs_from_check {
+            let build_scripts = workspace.run_build_scripts(&cargo_config, no_progress)?;
+            workspace.set_build_scripts(build_scripts)
+        }
+
+        let lib_roots: Option<Vec<AbsPathBuf>> = match &workspace {
+            ProjectWorkspace::Cargo { cargo, .. } => Some(
+                cargo
+                    .packages()
+                    .filter(|&pkg| cargo[pkg].is_member)
+                    .flat_map(|pkg| cargo[pkg].targets.iter())
+                    .filter(|&&target| matches!(cargo[target].kind, TargetKind::Lib { .. }))
+                    .map(|&target| cargo[target].root.clone())
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        let (host, vfs, _proc_macro) =
+            load_workspace(workspace, &cargo_config.extra_env, &load_cargo_config)?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+
+        let lib_files: Option<FxHashSet<_>> = lib_roots.map(|roots| {
+            roots.into_iter().filter_map(|root| vfs.file_id(&VfsPath::from(root))).collect()
+        });
+        let crate_graph = db.crate_graph();
+        let mut res = Vec::new();
+        for krate in Crate::all(db) {
+            let is_lib = match &lib_files {
+                Some(files) => files.contains(&crate_graph[krate.into()].root_file_id),
+                None => krate.origin(db).is_local(),
+            };
+            if is_lib {
+                res.extend(analysis.public_api(krate.into())?);
+            }
+        }
+        Ok(res)
+    }
+}
+
+fn change_kind(kind: ApiChangeKind) -> &'static str {
+    match kind {
+        ApiChangeKind::Added => "added",
+        ApiChangeKind::Removed => "removed",
+        ApiChangeKind::Changed => "changed",
+    }
+}
+
+fn impact(impact: SemverImpact) -> &'static str {
+    match impact {
+        SemverImpact::Minor => "minor",
+        SemverImpact::Major => "major",
+    }
+}
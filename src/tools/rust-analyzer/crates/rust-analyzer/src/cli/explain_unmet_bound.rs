@@ -0,0 +1,40 @@
+//! Explains why a type doesn't implement a trait, from the command line.
+
+use anyhow::Context;
+use ide::{FilePosition, LineCol};
+use load_cargo::{load_workspace_at, LoadCargoConfig, ProcMacroServerChoice};
+use project_model::{CargoConfig, RustLibSource};
+use vfs::{AbsPathBuf, VfsPath};
+
+use crate::cli::flags;
+
+impl flags::ExplainUnmetBound {
+    pub fn run(self) -> anyhow::Result<()> {
+        let file = AbsPathBuf::assert(std::env::current_dir()?.join(&self.file));
+        let root = file.parent().context("file has no parent directory")?;
+        let cargo_config =
+            CargoConfig { sysroot: Some(RustLibSource::Discover), ..Default::default() };
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro_server: ProcMacroServerChoice::Sysroot,
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(root.as_ref(), &cargo_config, &load_cargo_config, &|_| {})?;
+        let analysis = host.analysis();
+
+        let file_id = vfs
+            .file_id(&VfsPath::from(file.clone()))
+            .with_context(|| format!("{file} is not part of the workspace"))?;
+        let line_index = analysis.file_line_index(file_id)?;
+        let line_col =
+            LineCol { line: self.line.saturating_sub(1), col: self.column.saturating_sub(1) };
+        let offset = line_index.offset(line_col).context("position is outside of the file")?;
+
+        match analysis.explain_unmet_bound(FilePosition { file_id, offset }, &self.trait_path)? {
+            Some(unmet) => print!("{unmet}"),
+            None => println!("the type implements `{}`", self.trait_path),
+        }
+        Ok(())
+    }
+}
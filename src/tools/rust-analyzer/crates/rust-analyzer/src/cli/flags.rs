@@ -34,6 +34,10 @@
 
             /// Dump a LSP config JSON schema.
             optional --print-config-schema
+
+            /// [Unstable] Serve clients connecting to this TCP address instead of stdin/stdout.
+            /// All clients share one analysis state, which is kept alive while none is connected.
+            optional --listen addr: String
         }
 
         /// Parse stdin.
@@ -98,6 +102,19 @@
             /// Validate term search by running `cargo check` on every response.
             /// Note that this also temporarily modifies the files on disk, use with caution!
             optional --validate-term-search
+
+            /// Write how often each salsa query was executed, and the time and memory that took,
+            /// to this file as JSON.
+            optional --query-stats path: PathBuf
+            /// Write the time spent in each stack of salsa queries to this file, in the folded
+            /// stacks format flamegraph tools like `inferno-flamegraph` render.
+            optional --query-flamegraph path: PathBuf
+            /// Compare the query statistics to a file written by `--query-stats` and fail if
+            /// a query was executed more often, or took more time or memory.
+            optional --baseline path: PathBuf
+            /// By how many percent a query may exceed its `--baseline` time or memory before it
+            /// is reported, 10 by default.
+            optional --regression-threshold percent: u32
         }
 
         /// Run unit tests of the project using mir interpreter
@@ -141,6 +158,10 @@
 
         cmd lsif {
             required path: PathBuf
+
+            /// Only emit the files listed in this file, one path per line, like the output of
+            /// `git diff --name-only`. Other files are still analyzed, but not indexed.
+            optional --changed-files changed_files: PathBuf
         }
 
         cmd scip {
@@ -151,6 +172,51 @@
 
             /// A path to an json configuration file that can be used to customize cargo behavior.
             optional --config-path config_path: PathBuf
+
+            /// Only emit the documents of the files listed in this file, one path per line, like
+            /// the output of `git diff --name-only`. Other files are still analyzed, but not
+            /// indexed.
+            optional --changed-files changed_files: PathBuf
+        }
+
+        /// Explain why the type at a position doesn't implement a trait like `Send`.
+        cmd explain-unmet-bound {
+            /// The file containing the type, in a Cargo workspace.
+            required file: PathBuf
+            /// One-based line of the position.
+            required line: u32
+            /// One-based column of the position.
+            required column: u32
+            /// The path of the trait, resolved at the position.
+            required trait_path: String
+
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+        }
+
+        /// List the unsafe blocks of the workspace members and the operations that require them.
+        cmd unsafe-audit {
+            /// Directory with Cargo.toml.
+            required path: PathBuf
+
+            /// Print the blocks as JSON.
+            optional --json
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
+        }
+
+        /// Compare the public API of the library crates of two versions of a workspace, and
+        /// classify the changes as minor or major.
+        cmd api-diff {
+            /// Directory with Cargo.toml of the old version.
+            required old: PathBuf
+            /// Directory with Cargo.toml of the new version.
+            required new: PathBuf
+
+            /// Print the changes as JSON.
+            optional --json
+            /// Don't run build scripts or load `OUT_DIR` values by running `cargo check` before analysis.
+            optional --disable-build-scripts
         }
     }
 }
@@ -182,12 +248,16 @@ pub enum RustAnalyzerCmd {
     Search(Search),
     Lsif(Lsif),
     Scip(Scip),
+    ExplainUnmetBound(ExplainUnmetBound),
+    UnsafeAudit(UnsafeAudit),
+    ApiDiff(ApiDiff),
 }
 
 #[derive(Debug)]
 pub struct LspServer {
     pub version: bool,
     pub print_config_schema: bool,
+    pub listen: Option<String>,
 }
 
 #[derive(Debug)]
@@ -225,6 +295,10 @@ pub struct AnalysisStats {
     pub run_all_ide_things: bool,
     pub run_term_search: bool,
     pub validate_term_search: bool,
+    pub query_stats: Option<PathBuf>,
+    pub query_flamegraph: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    pub regression_threshold: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -235,6 +309,7 @@ pub struct RunTests {
 #[derive(Debug)]
 pub struct RustcTests {
     pub rustc_repo: PathBuf,
+
     pub filter: Option<String>,
 }
 
@@ -262,6 +337,8 @@ pub struct Search {
 #[derive(Debug)]
 pub struct Lsif {
     pub path: PathBuf,
+
+    pub changed_files: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -270,6 +347,34 @@ pub struct Scip {
 
     pub output: Option<PathBuf>,
     pub config_path: Option<PathBuf>,
+    pub changed_files: Option<PathBuf>,
+}
+
+#[derive(Debug)]
+pub struct ExplainUnmetBound {
+    pub file: PathBuf,
+    pub line: u32,
+    pub column: u32,
+    pub trait_path: String,
+
+    pub disable_build_scripts: bool,
+}
+
+#[derive(Debug)]
+pub struct UnsafeAudit {
+    pub path: PathBuf,
+
+    pub json: bool,
+    pub disable_build_scripts: bool,
+}
+
+#[derive(Debug)]
+pub struct ApiDiff {
+    pub old: PathBuf,
+    pub new: PathBuf,
+
+    pub json: bool,
+    pub disable_build_scripts: bool,
 }
 
 impl RustAnalyzer {
@@ -4,8 +4,8 @@
 use std::time::Instant;
 
 use ide::{
-    Analysis, FileId, FileRange, MonikerKind, PackageInformation, RootDatabase, StaticIndex,
-    StaticIndexedFile, TokenId, TokenStaticData,
+    Analysis, Diagnostic, FileId, FileRange, MonikerKind, PackageInformation, RootDatabase,
+    StaticIndex, StaticIndexConfig, StaticIndexedFile, TokenId, TokenStaticData,
 };
 use ide_db::{
     base_db::salsa::{self, ParallelDatabase},
@@ -19,7 +19,7 @@
 use vfs::{AbsPathBuf, Vfs};
 
 use crate::{
-    cli::flags,
+    cli::{flags, read_file_list},
     line_index::{LineEndings, LineIndex, PositionEncoding},
     lsp::to_proto,
     version::version,
@@ -141,6 +141,14 @@ fn get_range_id(&mut self, id: FileRange) -> Id {
         range_id
     }
 
+    fn line_index(&self, file_id: FileId) -> LineIndex {
+        LineIndex {
+            index: self.db.line_index(file_id),
+            encoding: PositionEncoding::Wide(WideEncoding::Utf16),
+            endings: LineEndings::Unix,
+        }
+    }
+
     fn get_file_id(&mut self, id: FileId) -> Id {
         if let Some(it) = self.file_map.get(&id) {
             return *it;
@@ -155,7 +163,25 @@ fn get_file_id(&mut self, id: FileId) -> Id {
         doc_id
     }
 
-    fn add_token(&mut self, id: TokenId, token: TokenStaticData) {
+    /// Adds `ranges` as items of `result_id`, grouped by the document they are in.
+    fn add_items(&mut self, result_id: Id, ranges: impl Iterator<Item = FileRange>) {
+        let mut by_file = FxHashMap::<FileId, Vec<lsp_types::NumberOrString>>::default();
+        for range in ranges {
+            let range_id = self.get_range_id(range);
+            by_file.entry(range.file_id).or_default().push(range_id.into());
+        }
+        let mut by_file: Vec<_> = by_file.into_iter().collect();
+        by_file.sort_by_key(|(file_id, _)| *file_id);
+        for (file_id, in_vs) in by_file {
+            self.add_edge(lsif::Edge::Item(lsif::Item {
+                document: (*self.file_map.get(&file_id).unwrap()).into(),
+                property: None,
+                edge_data: lsif::EdgeDataMultiIn { in_vs, out_v: result_id.into() },
+            }));
+        }
+    }
+
+    fn add_token(&mut self, id: TokenId, token: TokenStaticData, implementations: Vec<FileRange>) {
         let result_set_id = self.get_token_id(id);
         if let Some(hover) = token.hover {
             let hover_id = self.add_vertex(lsif::Vertex::HoverResult {
@@ -208,6 +234,14 @@ fn add_token(&mut self, id: TokenId, token: TokenStaticData) {
                 out_v: result_set_id.into(),
             }));
         }
+        if !implementations.is_empty() {
+            let result_id = self.add_vertex(lsif::Vertex::ImplementationResult);
+            self.add_edge(lsif::Edge::Implementation(lsif::EdgeData {
+                in_v: result_id.into(),
+                out_v: result_set_id.into(),
+            }));
+            self.add_items(result_id, implementations.into_iter());
+        }
         if !token.references.is_empty() {
             let result_id = self.add_vertex(lsif::Vertex::ReferenceResult);
             self.add_edge(lsif::Edge::References(lsif::EdgeData {
@@ -242,15 +276,10 @@ fn add_token(&mut self, id: TokenId, token: TokenStaticData) {
     }
 
     fn add_file(&mut self, file: StaticIndexedFile) {
-        let StaticIndexedFile { file_id, tokens, folds, .. } = file;
+        let StaticIndexedFile { file_id, tokens, folds, diagnostics, .. } = file;
         let doc_id = self.get_file_id(file_id);
         let text = self.analysis.file_text(file_id).unwrap();
-        let line_index = self.db.line_index(file_id);
-        let line_index = LineIndex {
-            index: line_index,
-            encoding: PositionEncoding::Wide(WideEncoding::Utf16),
-            endings: LineEndings::Unix,
-        };
+        let line_index = self.line_index(file_id);
         let result = folds
             .into_iter()
             .map(|it| to_proto::folding_range(&text, &line_index, false, it))
@@ -280,6 +309,47 @@ fn add_file(&mut self, file: StaticIndexedFile) {
             in_vs: tokens_id,
             out_v: doc_id.into(),
         }));
+        if !diagnostics.is_empty() {
+            let result = diagnostics
+                .into_iter()
+                .filter(|it| it.range.file_id == file_id)
+                .map(|it| self.diagnostic(&line_index, it))
+                .collect();
+            let diagnostics_id = self.add_vertex(lsif::Vertex::DiagnosticResult { result });
+            self.add_edge(lsif::Edge::Diagnostic(lsif::EdgeData {
+                in_v: diagnostics_id.into(),
+                out_v: doc_id.into(),
+            }));
+        }
+    }
+
+    fn diagnostic(&self, line_index: &LineIndex, diagnostic: Diagnostic) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic {
+            range: to_proto::range(line_index, diagnostic.range.range),
+            severity: Some(to_proto::diagnostic_severity(diagnostic.severity)),
+            code: Some(lsp_types::NumberOrString::String(diagnostic.code.as_str().to_owned())),
+            code_description: Some(lsp_types::CodeDescription {
+                href: lsp_types::Url::parse(&diagnostic.code.url()).unwrap(),
+            }),
+            source: Some("rust-analyzer".to_owned()),
+            message: diagnostic.message,
+            related_information: (!diagnostic.related.is_empty()).then(|| {
+                diagnostic
+                    .related
+                    .into_iter()
+                    .filter_map(|(frange, message)| {
+                        let path = self.vfs.file_path(frange.file_id);
+                        let location = lsp_types::Location {
+                            uri: lsp_types::Url::from_file_path(path.as_path()?).ok()?,
+                            range: to_proto::range(&self.line_index(frange.file_id), frange.range),
+                        };
+                        Some(lsp_types::DiagnosticRelatedInformation { location, message })
+                    })
+                    .collect()
+            }),
+            tags: diagnostic.unused.then(|| vec![lsp_types::DiagnosticTag::UNNECESSARY]),
+            data: None,
+        }
     }
 }
 
@@ -305,7 +375,8 @@ pub fn run(self) -> anyhow::Result<()> {
         let db = host.raw_database();
         let analysis = host.analysis();
 
-        let si = StaticIndex::compute(&analysis);
+        let files = self.changed_files.map(|path| read_file_list(&vfs, &path)).transpose()?;
+        let si = StaticIndex::compute(&analysis, &StaticIndexConfig { files, diagnostics: true });
 
         let mut lsif = LsifManager::new(&analysis, db, &vfs);
         lsif.add_vertex(lsif::Vertex::MetaData(lsif::MetaData {
@@ -321,8 +392,15 @@ pub fn run(self) -> anyhow::Result<()> {
         for file in si.files {
             lsif.add_file(file);
         }
-        for (id, token) in si.tokens.iter() {
-            lsif.add_token(id, token);
+        let tokens: Vec<_> = si.tokens.iter().collect();
+        let mut implementations = FxHashMap::<TokenId, Vec<FileRange>>::default();
+        for (_, token) in &tokens {
+            for &implemented in &token.implements {
+                implementations.entry(implemented).or_default().extend(token.definition);
+            }
+        }
+        for (id, token) in tokens {
+            lsif.add_token(id, token, implementations.remove(&id).unwrap_or_default());
         }
         eprintln!("Generating LSIF finished in {:?}", now.elapsed());
         Ok(())
@@ -66,6 +66,7 @@ fn new() -> Result<Self> {
                 tmp_file.parent().unwrap(),
                 &cargo_config.extra_env,
                 false,
+                None,
             )
             .unwrap()),
             rustc_cfg: vec![],
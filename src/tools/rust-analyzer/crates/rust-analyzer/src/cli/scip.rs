@@ -3,8 +3,8 @@
 use std::{path::PathBuf, time::Instant};
 
 use ide::{
-    LineCol, MonikerDescriptorKind, MonikerResult, StaticIndex, StaticIndexedFile,
-    SymbolInformationKind, TextRange, TokenId,
+    Diagnostic, LineCol, MonikerDescriptorKind, MonikerResult, Severity, StaticIndex,
+    StaticIndexConfig, StaticIndexedFile, SymbolInformationKind, TextRange, TokenId,
 };
 use ide_db::LineIndexDatabase;
 use load_cargo::{load_workspace_at, LoadCargoConfig, ProcMacroServerChoice};
@@ -12,7 +12,7 @@
 use scip::types as scip_types;
 
 use crate::{
-    cli::flags,
+    cli::{flags, read_file_list},
     line_index::{LineEndings, LineIndex, PositionEncoding},
 };
 
@@ -51,7 +51,8 @@ pub fn run(self) -> anyhow::Result<()> {
         let db = host.raw_database();
         let analysis = host.analysis();
 
-        let si = StaticIndex::compute(&analysis);
+        let files = self.changed_files.map(|path| read_file_list(&vfs, &path)).transpose()?;
+        let si = StaticIndex::compute(&analysis, &StaticIndexConfig { files, diagnostics: true });
 
         let metadata = scip_types::Metadata {
             version: scip_types::ProtocolVersion::UnspecifiedProtocolVersion.into(),
@@ -78,7 +79,7 @@ pub fn run(self) -> anyhow::Result<()> {
         let mut tokens_to_enclosing_symbol: FxHashMap<TokenId, Option<String>> =
             FxHashMap::default();
 
-        for StaticIndexedFile { file_id, tokens, .. } in si.files {
+        for StaticIndexedFile { file_id, tokens, diagnostics, .. } in si.files {
             let mut local_count = 0;
             let mut new_local_symbol = || {
                 let new_symbol = scip::types::Symbol::new_local(local_count);
@@ -135,10 +136,24 @@ pub fn run(self) -> anyhow::Result<()> {
                     }
 
                     if symbols_emitted.insert(id) {
-                        let documentation = match &token.documentation {
-                            Some(doc) => vec![doc.as_str().to_owned()],
-                            None => vec![],
+                        // the hover includes the type of the symbol, like that of locals
+                        let documentation = match (&token.hover, &token.documentation) {
+                            (Some(hover), _) => vec![hover.markup.as_str().to_owned()],
+                            (None, Some(doc)) => vec![doc.as_str().to_owned()],
+                            (None, None) => vec![],
                         };
+                        let relationships = token
+                            .implements
+                            .iter()
+                            .filter_map(|&implemented| {
+                                let moniker = si.tokens.get(implemented)?.moniker.as_ref()?;
+                                Some(scip_types::Relationship {
+                                    symbol: scip::symbol::format_symbol(moniker_to_symbol(moniker)),
+                                    is_implementation: true,
+                                    ..Default::default()
+                                })
+                            })
+                            .collect();
 
                         let position_encoding =
                             scip_types::PositionEncoding::UTF8CodeUnitOffsetFromLineStart.into();
@@ -153,7 +168,7 @@ pub fn run(self) -> anyhow::Result<()> {
                         let symbol_info = scip_types::SymbolInformation {
                             symbol: symbol.clone(),
                             documentation,
-                            relationships: Vec::new(),
+                            relationships,
                             special_fields: Default::default(),
                             kind: symbol_kind(token.kind).into(),
                             display_name: token.display_name.clone().unwrap_or_default(),
@@ -177,6 +192,17 @@ pub fn run(self) -> anyhow::Result<()> {
                 });
             });
 
+            occurrences.extend(
+                diagnostics
+                    .into_iter()
+                    .filter(|diagnostic| diagnostic.range.file_id == file_id)
+                    .map(|diagnostic| scip_types::Occurrence {
+                        range: text_range_to_scip_range(&line_index, diagnostic.range.range),
+                        diagnostics: vec![diagnostic_to_scip(diagnostic)],
+                        ..Default::default()
+                    }),
+            );
+
             if occurrences.is_empty() {
                 continue;
             }
@@ -233,6 +259,28 @@ fn text_range_to_scip_range(line_index: &LineIndex, range: TextRange) -> Vec<i32
     }
 }
 
+fn diagnostic_to_scip(diagnostic: Diagnostic) -> scip_types::Diagnostic {
+    use scip_types::Severity as ScipSeverity;
+    let severity = match diagnostic.severity {
+        Severity::Error => ScipSeverity::Error,
+        Severity::Warning => ScipSeverity::Warning,
+        Severity::WeakWarning => ScipSeverity::Hint,
+        Severity::Allow => ScipSeverity::UnspecifiedSeverity,
+    };
+    scip_types::Diagnostic {
+        severity: severity.into(),
+        code: diagnostic.code.as_str().to_owned(),
+        message: diagnostic.message,
+        source: "rust-analyzer".to_owned(),
+        tags: if diagnostic.unused {
+            vec![scip_types::DiagnosticTag::Unnecessary.into()]
+        } else {
+            vec![]
+        },
+        special_fields: Default::default(),
+    }
+}
+
 fn new_descriptor_str(
     name: &str,
     suffix: scip_types::descriptor::Suffix,
@@ -344,7 +392,7 @@ fn check_symbol(ra_fixture: &str, expected: &str) {
         let (host, position) = position(ra_fixture);
 
         let analysis = host.analysis();
-        let si = StaticIndex::compute(&analysis);
+        let si = StaticIndex::compute(&analysis, &StaticIndexConfig::default());
 
         let FilePosition { file_id, offset } = position;
 
@@ -608,7 +656,7 @@ fn documentation_matches_doc_comment() {
         host.raw_database_mut().apply_change(change_fixture.change);
 
         let analysis = host.analysis();
-        let si = StaticIndex::compute(&analysis);
+        let si = StaticIndex::compute(&analysis, &StaticIndexConfig::default());
 
         let file = si.files.first().unwrap();
         let (_, token_id) = file.tokens.first().unwrap();
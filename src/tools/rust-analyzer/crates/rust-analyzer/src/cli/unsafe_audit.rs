@@ -0,0 +1,85 @@
+//! Lists the unsafe blocks of the workspace members, for reviewing unsafe code.
+
+use hir::Crate;
+use ide::{Analysis, FileRange, LineCol, UnsafeBlockKind, UnsafeExprKind};
+use load_cargo::{load_workspace_at, LoadCargoConfig, ProcMacroServerChoice};
+use project_model::{CargoConfig, RustLibSource};
+use serde_json::{json, Value};
+use vfs::Vfs;
+
+use crate::cli::flags;
+
+impl flags::UnsafeAudit {
+    pub fn run(self) -> anyhow::Result<()> {
+        let cargo_config =
+            CargoConfig { sysroot: Some(RustLibSource::Discover), ..Default::default() };
+        let load_cargo_config = LoadCargoConfig {
+            load_out_dirs_from_check: !self.disable_build_scripts,
+            with_proc_macro_server: ProcMacroServerChoice::Sysroot,
+            prefill_caches: false,
+        };
+        let (host, vfs, _proc_macro) =
+            load_workspace_at(&self.path, &cargo_config, &load_cargo_config, &|_| {})?;
+        let db = host.raw_database();
+        let analysis = host.analysis();
+
+        let mut blocks = Vec::new();
+        for krate in Crate::all(db).into_iter().filter(|krate| krate.origin(db).is_local()) {
+            let crate_id = krate.into();
+            for block in analysis.unsafe_audit(crate_id)? {
+                let operations = block
+                    .operations
+                    .iter()
+                    .map(|op| {
+                        Ok(json!({
+                            "kind": operation_kind(op.kind),
+                            "location": location(&analysis, &vfs, op.range)?,
+                        }))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let kind = match block.kind {
+                    UnsafeBlockKind::Block => "block",
+                    UnsafeBlockKind::UnsafeFn => "unsafe_fn",
+                };
+                blocks.push(json!({
+                    "kind": kind,
+                    "container": block.container,
+                    "location": location(&analysis, &vfs, block.range)?,
+                    "operations": operations,
+                }));
+            }
+        }
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&blocks)?);
+            return Ok(());
+        }
+        let str = |value: &Value| value.as_str().unwrap_or_default().to_owned();
+        for block in &blocks {
+            let (location, kind) = (str(&block["location"]), str(&block["kind"]));
+            println!("{location} {kind} ({})", str(&block["container"]));
+            for op in block["operations"].as_array().into_iter().flatten() {
+                println!("    {} {}", str(&op["location"]), str(&op["kind"]));
+            }
+        }
+        println!();
+        println!("{} unsafe blocks", blocks.len());
+        Ok(())
+    }
+}
+
+fn operation_kind(kind: UnsafeExprKind) -> &'static str {
+    match kind {
+        UnsafeExprKind::RawPtrDeref => "raw_ptr_deref",
+        UnsafeExprKind::UnsafeFnCall => "unsafe_fn_call",
+        UnsafeExprKind::UnionField => "union_field",
+        UnsafeExprKind::MutableStatic => "mutable_static",
+    }
+}
+
+/// `path:line:column` of the start of `range`, with one-based lines and columns.
+fn location(analysis: &Analysis, vfs: &Vfs, range: FileRange) -> anyhow::Result<Value> {
+    let line_index = analysis.file_line_index(range.file_id)?;
+    let LineCol { line, col } = line_index.line_col(range.range.start());
+    Ok(Value::String(format!("{}:{}:{}", vfs.file_path(range.file_id), line + 1, col + 1)))
+}
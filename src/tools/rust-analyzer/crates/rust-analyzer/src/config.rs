@@ -8,21 +8,23 @@
 //! tweak things like automatic insertion of `()` in completions.
 
 use std::{
-    fmt, iter,
+    fmt, iter, mem,
     ops::Not,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use cfg::{CfgAtom, CfgDiff};
 use flycheck::FlycheckConfig;
 use ide::{
-    AssistConfig, CallableSnippets, CompletionConfig, DiagnosticsConfig, ExprFillDefaultMode,
-    HighlightConfig, HighlightRelatedConfig, HoverConfig, HoverDocFormat, InlayFieldsToResolve,
-    InlayHintsConfig, JoinLinesConfig, MemoryLayoutHoverConfig, MemoryLayoutHoverRenderKind,
-    Snippet, SnippetScope,
+    AssistConfig, BoundsStyle, CallableSnippets, CompletionConfig, DiagnosticsConfig,
+    DiagnosticsStyle, ErrorHandlingStyle, ExprFillDefaultMode, HighlightConfig,
+    HighlightRelatedConfig, HoverConfig, HoverDocFormat, InlayFieldsToResolve, InlayHintsConfig,
+    JoinLinesConfig, MemoryLayoutHoverConfig, MemoryLayoutHoverRenderKind, Severity, Snippet,
+    SnippetScope,
 };
 use ide_db::{
-    imports::insert_use::{ImportGranularity, InsertUseConfig, PrefixKind},
+    imports::insert_use::{ImportGranularity, ImportGroupStyle, InsertUseConfig, PrefixKind},
     SnippetCap,
 };
 use itertools::Itertools;
@@ -62,16 +64,37 @@
 // parsing the old name.
 config_data! {
     struct ConfigData {
+        /// Where assists that rewrite the bounds of generic parameters put them.
+        assist_boundsStyle: BoundsStyleDef = "\"where_clause\"",
         /// Whether to insert #[must_use] when generating `as_` methods
         /// for enum variants.
         assist_emitMustUse: bool               = "false",
+        /// The error handling style `Box<dyn Error>` returns get converted to.
+        assist_errorHandlingStyle: ErrorHandlingStyleDef = "\"anyhow\"",
         /// Placeholder expression to use for missing expressions in assists.
         assist_expressionFillDefault: ExprFillDefaultDef              = "\"todo\"",
 
+        /// BXL script used to query Buck2 projects for their crate graph instead of `buck2 cquery`,
+        /// like `root//tools/rust-analyzer.bxl:main`. It is passed the targets to load with
+        /// `--targets` and has to print a `rust-project.json` to stdout.
+        buildSystem_buck2BxlScript: Option<String> = "null",
+        /// Look for Buck2 and Bazel projects when there is neither a `rust-project.json` nor a
+        /// `Cargo.toml` to load. Querying them for the crate graph runs `buck2` or `bazel`.
+        buildSystem_discover: bool = "false",
+        /// Target patterns to load when the workspace is a Buck2 or Bazel project, all targets
+        /// (`//...`) if empty.
+        buildSystem_targets: Vec<String> = "[]",
+
         /// Warm up caches on project load.
         cachePriming_enable: bool = "true",
         /// How many worker threads to handle priming caches. The default `0` means to pick automatically.
         cachePriming_numThreads: ParallelCachePrimingNumThreads = "0",
+        /// Persist the item trees of dependencies in the user's cache directory after priming
+        /// caches, and reuse them for unchanged files when the project is loaded again.
+        ///
+        /// The item trees of the standard library are always persisted, per toolchain, in
+        /// `#rust-analyzer.cargo.sysrootCacheDir#`.
+        cachePriming_persist: bool = "false",
 
         /// Automatically refresh project info via `cargo metadata` on
         /// `Cargo.toml` or `.cargo/config.toml` changes.
@@ -118,11 +141,36 @@ struct ConfigData {
         cargo_buildScripts_useRustcWrapper: bool = "true",
         /// List of cfg options to enable with the given values.
         cargo_cfgs: FxHashMap<String, String> = "{}",
+        /// Whether to use cargo's local copy of the crates.io index to show the versions and
+        /// features of dependencies when hovering them in `Cargo.toml` files, and to warn about
+        /// dependencies resolved to yanked versions. The index is only read, never updated.
+        cargo_cratesIndex_enable: bool = "true",
+        /// Whether to only load the cargo workspaces found below a workspace folder once one of
+        /// their files is opened, if there are several of them.
+        cargo_discoverWorkspaces_lazy: bool = "false",
+        /// How many directories deep to look for cargo workspaces below a workspace folder that
+        /// is not part of a workspace itself. `0` only loads a workspace at the root of the
+        /// folder.
+        cargo_discoverWorkspaces_maxDepth: usize = "1",
         /// Extra arguments that are passed to every cargo invocation.
         cargo_extraArgs: Vec<String> = "[]",
+        /// Additional sets of cfgs to analyze the workspace with, like
+        /// `[["feature=\"serde\""], ["unix", "feature=\"std\""]]`.
+        ///
+        /// The local crates of the workspace are analyzed once more for each of these sets, with
+        /// its cfgs enabled, so that code only compiled with them is analyzed instead of being
+        /// reported as inactive.
+        cargo_extraCfgSets: Vec<Vec<String>> = "[]",
         /// Extra environment variables that will be set when running cargo, rustc
         /// or other commands within the workspace. Useful for setting RUSTFLAGS.
         cargo_extraEnv: FxHashMap<String, String> = "{}",
+        /// Additional targets to analyze the workspace for, like `wasm32-unknown-unknown`.
+        ///
+        /// The crates of the workspace are analyzed once more for each of these targets, so that
+        /// code only compiled for them is analyzed instead of being reported as inactive.
+        /// When `#rust-analyzer.cargo.buildScripts.invocationStrategy#` is set to `once`, the
+        /// build script command is run once more per target, with `CARGO_BUILD_TARGET` set.
+        cargo_extraTargets: Vec<String> = "[]",
         /// List of features to activate.
         ///
         /// Set this to `"all"` to pass `--all-features` to cargo.
@@ -136,6 +184,14 @@ struct ConfigData {
         ///
         /// This option does not take effect until rust-analyzer is restarted.
         cargo_sysroot: Option<String>    = "\"discover\"",
+        /// Directory to cache the standard library analysis of each toolchain in, so that
+        /// switching between toolchains, for example with a `rust-toolchain.toml` file, doesn't
+        /// analyze it again. This holds the item trees of the standard library crates and, when
+        /// `#rust-analyzer.cargo.sysrootQueryMetadata#` is enabled, its metadata. Relative paths
+        /// are resolved against the workspace root.
+        ///
+        /// Defaults to `rust-analyzer/sysroots` in the user's cache directory.
+        cargo_sysrootCacheDir: Option<String> = "null",
         /// Whether to run cargo metadata on the sysroot library allowing rust-analyzer to analyze
         /// third-party dependencies of the standard libraries.
         ///
@@ -294,6 +350,13 @@ struct ConfigData {
         /// Whether to enable term search based snippets like `Some(foo.bar().baz())`.
         completion_termSearch_enable: bool = "false",
 
+        /// Whether to report private functions, structs and struct fields that are never used
+        /// anywhere in the workspace. This searches for the references of each of them, so it can
+        /// be slow in large files.
+        diagnostics_deadCode_enable: bool = "false",
+        /// How long to wait, in milliseconds, after the last change before recomputing native
+        /// rust-analyzer diagnostics.
+        diagnostics_debounce: usize = "50",
         /// List of rust-analyzer diagnostics to disable.
         diagnostics_disabled: FxHashSet<String> = "[]",
         /// Whether to show native rust-analyzer diagnostics.
@@ -301,9 +364,34 @@ struct ConfigData {
         /// Whether to show experimental rust-analyzer diagnostics that might
         /// have more false positives than usual.
         diagnostics_experimental_enable: bool    = "false",
+        /// The severity to report calls of `todo!`, `unimplemented!`, `dbg!` and `println!` with,
+        /// when they are outside of tests, benchmarks and examples. Only the standard library's
+        /// macros are reported, and only those with a severity other than `"off"`.
+        diagnostics_leftoverMacros: FxHashMap<String, DiagnosticSeverityDef> = r#"{
+            "todo": "off",
+            "unimplemented": "off",
+            "dbg": "off",
+            "println": "off"
+        }"#,
         /// Map of prefixes to be substituted when parsing diagnostic file paths.
         /// This should be the reverse mapping of what is passed to `rustc` as `--remap-path-prefix`.
         diagnostics_remapPrefix: FxHashMap<String, String> = "{}",
+        /// Which set of diagnostics rust-analyzer reports on its own.
+        ///
+        /// `clippy-lite` additionally reports a subset of clippy's lints, computed by
+        /// rust-analyzer itself so that they show up without running `cargo clippy`.
+        diagnostics_style: DiagnosticsStyleDef = "\"default\"",
+        /// Whether to report `TODO`, `FIXME` and `HACK` comments as hints.
+        diagnostics_todoComments_enable: bool = "false",
+        /// Whether to warn about unsafe operations outside of `unsafe` blocks in the bodies of
+        /// `unsafe` functions, as the `unsafe_op_in_unsafe_fn` lint does by default from edition
+        /// 2024 on. This helps migrating to the new edition.
+        diagnostics_unsafeOpInUnsafeFn_enable: bool = "false",
+        /// Whether to report `pub` items that are never used outside of their crate, and
+        /// `pub(crate)` items that are never used outside of their module, anywhere in the
+        /// workspace. This searches for the references of each of them, so it can be slow in large
+        /// files.
+        diagnostics_unusedPub_enable: bool = "false",
         /// List of warnings that should be displayed with hint severity.
         ///
         /// The warnings will be indicated by faded text or three dots in code
@@ -367,6 +455,9 @@ struct ConfigData {
         hover_memoryLayout_offset: Option<MemoryLayoutHoverRenderKindDef> = "\"hexadecimal\"",
         /// How to render the size information in a memory layout hover.
         hover_memoryLayout_size: Option<MemoryLayoutHoverRenderKindDef> = "\"both\"",
+        /// Whether to show a table of the discriminants and payload sizes of the variants of an
+        /// enum, with where it stores its tag, in a memory layout hover.
+        hover_memoryLayout_variants: bool = "false",
 
         /// Whether to enforce the import granularity setting for all files. If set to false rust-analyzer will try to keep import styles consistent per file.
         imports_granularity_enforce: bool              = "false",
@@ -380,8 +471,12 @@ struct ConfigData {
         imports_preferNoStd | imports_prefer_no_std: bool = "false",
         /// Whether to prefer import paths containing a `prelude` module.
         imports_preferPrelude: bool                       = "false",
+        /// Whether to import items of other crates through the public re-exports of facade crates, even if the crate defining them is a dependency as well.
+        imports_preferReexports: bool                     = "false",
         /// The path structure for newly inserted paths to use.
         imports_prefix: ImportPrefixDef               = "\"plain\"",
+        /// Whether to follow the `imports_granularity` and `group_imports` options of a `rustfmt.toml` or `.rustfmt.toml` in the workspace root when inserting imports. When set, they take precedence over `#rust-analyzer.imports.granularity.group#`, `#rust-analyzer.imports.granularity.enforce#` and `#rust-analyzer.imports.group.enable#`.
+        imports_rustfmt_enable: bool                  = "true",
 
         /// Whether to show inlay type hints for binding modes.
         inlayHints_bindingModeHints_enable: bool                   = "false",
@@ -398,6 +493,9 @@ struct ConfigData {
         inlayHints_closureReturnTypeHints_enable: ClosureReturnTypeHintsDef  = "\"never\"",
         /// Closure notation in type and chaining inlay hints.
         inlayHints_closureStyle: ClosureStyle                                = "\"impl_fn\"",
+        /// Whether to show inlay hints for the error type `?` converts into and for the type
+        /// arguments are deref-coerced to at call sites.
+        inlayHints_conversionHints_enable: bool                              = "false",
         /// Whether to show enum variant discriminant hints.
         inlayHints_discriminantHints_enable: DiscriminantHintsDef            = "\"never\"",
         /// Whether to show inlay hints for type adjustments.
@@ -406,12 +504,26 @@ struct ConfigData {
         inlayHints_expressionAdjustmentHints_hideOutsideUnsafe: bool = "false",
         /// Whether to show inlay hints as postfix ops (`.*` instead of `*`, etc).
         inlayHints_expressionAdjustmentHints_mode: AdjustmentHintsModeDef = "\"prefix\"",
+        /// Whether to show inlay hints for the generic arguments inferred for calls that don't spell
+        /// them out, like `::<Vec<String>>` after `collect`, and for the generic arguments of the
+        /// trait of a method call, like `Into<MyError>::` before `into`.
+        inlayHints_genericArgumentHints_enable: bool               = "false",
+        /// Only show generic argument hints for calls of the functions whose name matches one of
+        /// these patterns, in which `*` matches any characters, e.g. `["into", "parse", "collect"]`.
+        /// Hints are shown for all calls if this is empty.
+        inlayHints_genericArgumentHints_functions: Vec<String>     = "[]",
         /// Whether to show implicit drop hints.
         inlayHints_implicitDrops_enable: bool                      = "false",
         /// Whether to show inlay type hints for elided lifetimes in function signatures.
         inlayHints_lifetimeElisionHints_enable: LifetimeElisionDef = "\"never\"",
         /// Whether to prefer using parameter names as the name for elided lifetime hints if possible.
         inlayHints_lifetimeElisionHints_useParameterNames: bool    = "false",
+        /// Whether to show a one-line preview of what macro calls in bodies expand to, like the
+        /// `match` of a `matches!` or the receiver of the `write_fmt` call of a `write!`.
+        inlayHints_macroExpansionHints_enable: bool                = "false",
+        /// The names of the macros to show macro expansion hints for. Hints are shown for all macros
+        /// if this is empty.
+        inlayHints_macroExpansionHints_macros: Vec<String>         = "[\"matches\", \"write\", \"writeln\", \"assert\", \"assert_eq\", \"assert_ne\"]",
         /// Maximum length for inlay hints. Set to null to have an unlimited length.
         inlayHints_maxLength: Option<usize>                        = "25",
         /// Whether to show function parameter name inlay hints at the call
@@ -449,6 +561,9 @@ struct ConfigData {
         lens_debug_enable: bool            = "true",
         /// Whether to show CodeLens in Rust files.
         lens_enable: bool           = "true",
+        /// Whether to show how often each enum variant is constructed and matched on.
+        /// Only applies when `#rust-analyzer.lens.enable#` is set.
+        lens_enumVariantUsages_enable: bool = "false",
         /// Internal config: use custom client-side commands even when the
         /// client doesn't set the corresponding capability.
         lens_forceCustomCommands: bool = "true",
@@ -472,6 +587,10 @@ struct ConfigData {
         /// Whether to show `Run` lens. Only applies when
         /// `#rust-analyzer.lens.enable#` is set.
         lens_run_enable: bool              = "true",
+        /// Whether to show an estimate of the stack space each function needs, from the sizes of
+        /// its locals and those of the calls that need the most stack.
+        /// Only applies when `#rust-analyzer.lens.enable#` is set.
+        lens_stackUsage_enable: bool = "false",
 
         /// Disable project auto-discovery in favor of explicitly specified set
         /// of projects.
@@ -482,6 +601,10 @@ struct ConfigData {
 
         /// Number of syntax trees rust-analyzer keeps in memory. Defaults to 128.
         lru_capacity: Option<usize>                 = "null",
+        /// Memory budget in megabytes. When the server allocates more than this, the function
+        /// bodies and inference results of crates without open documents are evicted, starting
+        /// with the crates that had one open least recently, until it is back under the budget.
+        lru_memoryBudget: Option<usize>             = "null",
         /// Sets the LRU capacity of the specified queries.
         lru_query_capacities: FxHashMap<Box<str>, usize> = "{}",
 
@@ -496,12 +619,33 @@ struct ConfigData {
 
         /// Expand attribute macros. Requires `#rust-analyzer.procMacro.enable#` to be set.
         procMacro_attributes_enable: bool = "true",
+        /// Reuse the expansion of a proc-macro invocation when the macro is invoked again with the
+        /// same input and environment, instead of running it again.
+        ///
+        /// Macros that read files or other state besides their input may show outdated
+        /// expansions with this enabled.
+        procMacro_cache_enable: bool = "false",
+        /// Store the expansions reused with `#rust-analyzer.procMacro.cache.enable#` in
+        /// `rust-analyzer/proc-macros` in the user's cache directory, to reuse them in later
+        /// sessions.
+        procMacro_cache_persist: bool = "false",
         /// Enable support for procedural macros, implies `#rust-analyzer.cargo.buildScripts.enable#`.
         procMacro_enable: bool                     = "true",
+        /// Time in seconds after which a proc-macro expansion is aborted and reported as failed,
+        /// so that a macro that never finishes does not stall the proc-macro server. Aborting an
+        /// expansion restarts the proc-macro server. `null` lets expansions run for as long as
+        /// they take.
+        procMacro_expansionTimeout: Option<usize>  = "30",
         /// These proc-macros will be ignored when trying to expand them.
         ///
         /// This config takes a map of crate names with the exported proc-macro names to ignore as values.
         procMacro_ignored: FxHashMap<Box<str>, Box<[Box<str>]>>          = "{}",
+        /// Deny proc-macros writing to the filesystem and, on Linux 6.7 and newer, binding and
+        /// connecting TCP sockets.
+        ///
+        /// This is only supported on Linux, using Landlock. Macros can still read files, use UDP
+        /// and other non-TCP sockets, and run other programs, which are restricted in the same way.
+        procMacro_sandbox_enable: bool = "false",
         /// Internal config, path to proc-macro server executable.
         procMacro_server: Option<PathBuf>          = "null",
 
@@ -549,6 +693,12 @@ struct ConfigData {
         /// available on a nightly build.
         rustfmt_rangeFormatting_enable: bool = "false",
 
+        /// Names of semantic token types and modifiers that are not emitted, like `"unsafe"` or
+        /// `"unresolvedReference"`. A disabled token type drops the token, a disabled modifier is
+        /// dropped from the tokens it is on.
+        ///
+        /// Useful for clients that only color a few token kinds, as it shrinks the responses.
+        semanticHighlighting_disabledTokens: FxHashSet<String> = "[]",
         /// Inject additional highlighting into doc comments.
         ///
         /// When enabled, rust-analyzer will highlight rust source in doc comments as well as intra
@@ -585,6 +735,10 @@ struct ConfigData {
         /// By disabling semantic tokens for strings, other grammars can be used to highlight
         /// their contents.
         semanticHighlighting_strings_enable: bool = "true",
+        /// Maps the names of semantic token types and modifiers to the names of other ones to emit
+        /// them as, like `{ "builtinAttribute": "decorator", "consuming": "mutable" }`. Types can
+        /// only be mapped to types and modifiers to modifiers. Disabled tokens are not mapped.
+        semanticHighlighting_tokenMapping: FxHashMap<String, String> = "{}",
 
         /// Show full signature of the callable. Only shows parameters if disabled.
         signatureInfo_detail: SignatureDetail                           = "\"full\"",
@@ -614,6 +768,8 @@ fn default() -> Self {
 #[derive(Debug, Clone)]
 pub struct Config {
     discovered_projects: Vec<ProjectManifest>,
+    /// Discovered projects that are only loaded once one of their files is opened.
+    unloaded_projects: Vec<ProjectManifest>,
     /// The workspace roots as registered by the LSP client
     workspace_roots: Vec<AbsPathBuf>,
     caps: lsp_types::ClientCapabilities,
@@ -622,6 +778,8 @@ pub struct Config {
     detached_files: Vec<AbsPathBuf>,
     snippets: Vec<Snippet>,
     is_visual_studio_code: bool,
+    /// The contents of the rustfmt config file in the workspace root.
+    rustfmt_toml: Option<String>,
 }
 
 type ParallelCachePrimingNumThreads = u8;
@@ -665,6 +823,12 @@ pub struct LensConfig {
     pub refs_trait: bool, // for Struct, Enum, Union and Trait
     pub enum_variant_refs: bool,
 
+    // usages
+    pub enum_variant_usages: bool,
+
+    // stack usage
+    pub stack_usage: bool,
+
     // annotations
     pub location: AnnotationLocation,
 }
@@ -694,6 +858,8 @@ pub fn any(&self) -> bool {
             || self.refs_adt
             || self.refs_trait
             || self.enum_variant_refs
+            || self.enum_variant_usages
+            || self.stack_usage
     }
 
     pub fn none(&self) -> bool {
@@ -784,6 +950,19 @@ pub struct WorkspaceSymbolConfig {
     pub search_limit: usize,
 }
 
+/// Configuration for how highlights are turned into semantic tokens.
+#[derive(Debug, Clone, Default)]
+pub struct SemanticTokensConfig {
+    /// Whether tokens that only repeat what syntax highlighting does are left out.
+    pub augments_syntax_tokens: bool,
+    /// Whether token types and modifiers that aren't in the LSP spec can be emitted.
+    pub non_standard_tokens: bool,
+    /// Names of the token types and modifiers that are not emitted.
+    pub disabled: FxHashSet<String>,
+    /// Maps names of token types and modifiers to the names of the ones to emit instead.
+    pub mapping: FxHashMap<String, String>,
+}
+
 pub struct ClientCommandsConfig {
     pub run_single: bool,
     pub debug_single: bool,
@@ -827,20 +1006,58 @@ pub fn new(
             data: ConfigData::default(),
             detached_files: Vec::new(),
             discovered_projects: Vec::new(),
+            unloaded_projects: Vec::new(),
             root_path,
             snippets: Default::default(),
             workspace_roots,
             is_visual_studio_code,
+            rustfmt_toml: None,
         }
+        .with_rustfmt_toml()
+    }
+
+    fn with_rustfmt_toml(mut self) -> Self {
+        // Same lookup order as rustfmt itself.
+        self.rustfmt_toml = [".rustfmt.toml", "rustfmt.toml"]
+            .into_iter()
+            .find_map(|name| std::fs::read_to_string(self.root_path.join(name)).ok());
+        self
     }
 
     pub fn rediscover_workspaces(&mut self) {
-        let discovered = ProjectManifest::discover_all(&self.workspace_roots);
+        let discovered = ProjectManifest::discover_all(
+            &self.workspace_roots,
+            self.data.cargo_discoverWorkspaces_maxDepth,
+            self.data.buildSystem_discover,
+        );
         tracing::info!("discovered projects: {:?}", discovered);
         if discovered.is_empty() {
             tracing::error!("failed to find any projects in {:?}", &self.workspace_roots);
         }
-        self.discovered_projects = discovered;
+        if self.data.cargo_discoverWorkspaces_lazy && discovered.len() > 1 {
+            (self.discovered_projects, self.unloaded_projects) =
+                discovered.into_iter().partition(|it| self.discovered_projects.contains(it));
+        } else {
+            self.discovered_projects = discovered;
+            self.unloaded_projects = Vec::new();
+        }
+    }
+
+    /// Marks the unloaded projects containing `path` as discovered, so that they are loaded with
+    /// the next workspace fetch. Returns whether there were any.
+    pub fn load_projects_containing(&mut self, path: &AbsPath) -> bool {
+        let (to_load, unloaded): (Vec<_>, _) = mem::take(&mut self.unloaded_projects)
+            .into_iter()
+            .partition(|it| path.starts_with(it.manifest_path().parent()));
+        self.unloaded_projects = unloaded;
+        let any = !to_load.is_empty();
+        self.discovered_projects.extend(to_load);
+        self.discovered_projects.sort();
+        any
+    }
+
+    pub fn unloaded_projects(&self) -> &[ProjectManifest] {
+        &self.unloaded_projects
     }
 
     pub fn remove_workspace(&mut self, path: &AbsPath) {
@@ -971,12 +1188,7 @@ pub fn linked_or_discovered_projects(&self) -> Vec<LinkedProject> {
                     self.data.files_excludeDirs.iter().map(|p| self.root_path.join(p)).collect();
                 self.discovered_projects
                     .iter()
-                    .filter(
-                        |(ProjectManifest::ProjectJson(path)
-                         | ProjectManifest::CargoToml(path))| {
-                            !exclude_dirs.iter().any(|p| path.starts_with(p))
-                        },
-                    )
+                    .filter(|it| !exclude_dirs.iter().any(|p| it.manifest_path().starts_with(p)))
                     .cloned()
                     .map(LinkedProject::from)
                     .collect()
@@ -1014,6 +1226,23 @@ pub fn prefill_caches(&self) -> bool {
         self.data.cachePriming_enable
     }
 
+    /// The directory to persist item trees in, and whether only those of the sysroot crates are
+    /// persisted.
+    pub fn item_tree_cache(&self) -> Option<(AbsPathBuf, bool)> {
+        if self.data.cachePriming_persist {
+            user_cache_dir().map(|it| (it.join("item-trees"), false))
+        } else {
+            self.sysroot_cache_dir().map(|it| (it.join("item-trees"), true))
+        }
+    }
+
+    fn sysroot_cache_dir(&self) -> Option<AbsPathBuf> {
+        match &self.data.cargo_sysrootCacheDir {
+            Some(dir) => Some(self.root_path.join(dir)),
+            None => user_cache_dir().map(|it| it.join("sysroots")),
+        }
+    }
+
     pub fn location_link(&self) -> bool {
         try_or_def!(self.caps.text_document.as_ref()?.definition?.link_support?)
     }
@@ -1150,10 +1379,32 @@ pub fn publish_diagnostics(&self) -> bool {
     pub fn diagnostics(&self) -> DiagnosticsConfig {
         DiagnosticsConfig {
             enabled: self.data.diagnostics_enable,
+            style: match self.data.diagnostics_style {
+                DiagnosticsStyleDef::Default => DiagnosticsStyle::Default,
+                DiagnosticsStyleDef::ClippyLite => DiagnosticsStyle::ClippyLite,
+            },
             proc_attr_macros_enabled: self.expand_proc_attr_macros(),
             proc_macros_enabled: self.data.procMacro_enable,
             disable_experimental: !self.data.diagnostics_experimental_enable,
             disabled: self.data.diagnostics_disabled.clone(),
+            dead_code: self.data.diagnostics_deadCode_enable,
+            unused_pub: self.data.diagnostics_unusedPub_enable,
+            unsafe_op_in_unsafe_fn: self.data.diagnostics_unsafeOpInUnsafeFn_enable,
+            todo_comments: self.data.diagnostics_todoComments_enable,
+            leftover_macros: self
+                .data
+                .diagnostics_leftoverMacros
+                .iter()
+                .filter_map(|(name, severity)| {
+                    let severity = match severity {
+                        DiagnosticSeverityDef::Off => return None,
+                        DiagnosticSeverityDef::Hint => Severity::WeakWarning,
+                        DiagnosticSeverityDef::Warning => Severity::Warning,
+                        DiagnosticSeverityDef::Error => Severity::Error,
+                    };
+                    Some((name.clone(), severity))
+                })
+                .collect(),
             expr_fill_default: match self.data.assist_expressionFillDefault {
                 ExprFillDefaultDef::Todo => ExprFillDefaultMode::Todo,
                 ExprFillDefaultDef::Default => ExprFillDefaultMode::Default,
@@ -1164,6 +1415,10 @@ pub fn diagnostics(&self) -> DiagnosticsConfig {
         }
     }
 
+    pub fn diagnostics_debounce(&self) -> Duration {
+        Duration::from_millis(self.data.diagnostics_debounce as u64)
+    }
+
     pub fn diagnostics_map(&self) -> DiagnosticsMapConfig {
         DiagnosticsMapConfig {
             remap_prefix: self.data.diagnostics_remapPrefix.clone(),
@@ -1177,6 +1432,10 @@ pub fn extra_args(&self) -> &Vec<String> {
         &self.data.cargo_extraArgs
     }
 
+    pub fn crates_index_enable(&self) -> bool {
+        self.data.cargo_cratesIndex_enable
+    }
+
     pub fn extra_env(&self) -> &FxHashMap<String, String> {
         &self.data.cargo_extraEnv
     }
@@ -1201,6 +1460,10 @@ pub fn lru_query_capacities(&self) -> Option<&FxHashMap<Box<str>, usize>> {
         self.data.lru_query_capacities.is_empty().not().then_some(&self.data.lru_query_capacities)
     }
 
+    pub fn lru_memory_budget(&self) -> Option<usize> {
+        self.data.lru_memoryBudget
+    }
+
     pub fn proc_macro_srv(&self) -> Option<AbsPathBuf> {
         let path = self.data.procMacro_server.clone()?;
         Some(AbsPathBuf::try_from(path).unwrap_or_else(|path| self.root_path.join(path)))
@@ -1214,6 +1477,26 @@ pub fn expand_proc_macros(&self) -> bool {
         self.data.procMacro_enable
     }
 
+    /// Returns whether proc-macro expansions should be cached, and the directory to persist them
+    /// in, if any.
+    pub fn proc_macro_cache(&self) -> Option<Option<AbsPathBuf>> {
+        self.data.procMacro_cache_enable.then(|| {
+            self.data
+                .procMacro_cache_persist
+                .then(user_cache_dir)
+                .flatten()
+                .map(|it| it.join("proc-macros"))
+        })
+    }
+
+    pub fn proc_macro_expansion_timeout(&self) -> Option<Duration> {
+        self.data.procMacro_expansionTimeout.map(|secs| Duration::from_secs(secs as u64))
+    }
+
+    pub fn proc_macro_sandbox(&self) -> bool {
+        self.data.procMacro_sandbox_enable
+    }
+
     pub fn expand_proc_attr_macros(&self) -> bool {
         self.data.procMacro_enable && self.data.procMacro_attributes_enable
     }
@@ -1263,6 +1546,7 @@ pub fn cargo(&self) -> CargoConfig {
         let sysroot_src =
             self.data.cargo_sysrootSrc.as_ref().map(|sysroot| self.root_path.join(sysroot));
         let sysroot_query_metadata = self.data.cargo_sysrootQueryMetadata;
+        let sysroot_cache_dir = self.sysroot_cache_dir();
 
         CargoConfig {
             features: match &self.data.cargo_features {
@@ -1273,8 +1557,29 @@ pub fn cargo(&self) -> CargoConfig {
                 },
             },
             target: self.data.cargo_target.clone(),
+            extra_targets: self.data.cargo_extraTargets.clone(),
+            extra_cfg_sets: self
+                .data
+                .cargo_extraCfgSets
+                .iter()
+                .map(|cfgs| {
+                    let cfgs = cfgs
+                        .iter()
+                        .map(|cfg| match cfg.split_once('=') {
+                            Some((key, value)) => CfgAtom::KeyValue {
+                                key: key.into(),
+                                value: value.trim_matches('"').into(),
+                            },
+                            None => CfgAtom::Flag(cfg.into()),
+                        })
+                        .unique()
+                        .collect();
+                    CfgDiff::new(cfgs, vec![]).unwrap()
+                })
+                .collect(),
             sysroot,
             sysroot_query_metadata,
+            sysroot_cache_dir,
             sysroot_src,
             rustc_source,
             cfg_overrides: project_model::CfgOverrides {
@@ -1320,6 +1625,10 @@ pub fn cargo(&self) -> CargoConfig {
             extra_args: self.data.cargo_extraArgs.clone(),
             extra_env: self.data.cargo_extraEnv.clone(),
             target_dir: self.target_dir_from_config(),
+            build_system: project_model::BuildSystemConfig {
+                targets: self.data.buildSystem_targets.clone(),
+                buck2_bxl_script: self.data.buildSystem_buck2BxlScript.clone(),
+            },
         }
     }
 
@@ -1502,6 +1811,14 @@ pub fn inlay_hints(&self) -> InlayHintsConfig {
                 None
             },
             range_exclusive_hints: self.data.inlayHints_rangeExclusiveHints_enable,
+            conversion_hints: self.data.inlayHints_conversionHints_enable,
+            generic_arg_hints: self.data.inlayHints_genericArgumentHints_enable,
+            generic_arg_hints_functions: self
+                .data
+                .inlayHints_genericArgumentHints_functions
+                .clone(),
+            macro_expansion_hints: self.data.inlayHints_macroExpansionHints_enable,
+            macro_expansion_hints_macros: self.data.inlayHints_macroExpansionHints_macros.clone(),
             fields_to_resolve: InlayFieldsToResolve {
                 resolve_text_edits: client_capability_fields.contains("textEdits"),
                 resolve_hint_tooltip: client_capability_fields.contains("tooltip"),
@@ -1513,7 +1830,7 @@ pub fn inlay_hints(&self) -> InlayHintsConfig {
     }
 
     fn insert_use_config(&self) -> InsertUseConfig {
-        InsertUseConfig {
+        let config = InsertUseConfig {
             granularity: match self.data.imports_granularity_group {
                 ImportGranularityDef::Preserve => ImportGranularity::Preserve,
                 ImportGranularityDef::Item => ImportGranularity::Item,
@@ -1528,7 +1845,15 @@ fn insert_use_config(&self) -> InsertUseConfig {
                 ImportPrefixDef::BySelf => PrefixKind::BySelf,
             },
             group: self.data.imports_group_enable,
+            group_style: ImportGroupStyle::Default,
             skip_glob_imports: !self.data.imports_merge_glob,
+            prefer_reexports: self.data.imports_preferReexports,
+        };
+        match &self.rustfmt_toml {
+            Some(rustfmt_toml) if self.data.imports_rustfmt_enable => {
+                config.with_rustfmt_config(rustfmt_toml)
+            }
+            _ => config,
         }
     }
 
@@ -1584,6 +1909,14 @@ pub fn assist(&self) -> AssistConfig {
             prefer_no_std: self.data.imports_preferNoStd,
             prefer_prelude: self.data.imports_preferPrelude,
             assist_emit_must_use: self.data.assist_emitMustUse,
+            error_handling_style: match self.data.assist_errorHandlingStyle {
+                ErrorHandlingStyleDef::Anyhow => ErrorHandlingStyle::Anyhow,
+                ErrorHandlingStyleDef::Thiserror => ErrorHandlingStyle::Thiserror,
+            },
+            bounds_style: match self.data.assist_boundsStyle {
+                BoundsStyleDef::WhereClause => BoundsStyle::WhereClause,
+                BoundsStyleDef::Inline => BoundsStyle::Inline,
+            },
         }
     }
 
@@ -1616,6 +1949,8 @@ pub fn lens(&self) -> LensConfig {
             refs_trait: self.data.lens_enable && self.data.lens_references_trait_enable,
             enum_variant_refs: self.data.lens_enable
                 && self.data.lens_references_enumVariant_enable,
+            enum_variant_usages: self.data.lens_enable && self.data.lens_enumVariantUsages_enable,
+            stack_usage: self.data.lens_enable && self.data.lens_stackUsage_enable,
             location: self.data.lens_location,
         }
     }
@@ -1631,8 +1966,13 @@ pub fn hover_actions(&self) -> HoverActionsConfig {
         }
     }
 
-    pub fn highlighting_non_standard_tokens(&self) -> bool {
-        self.data.semanticHighlighting_nonStandardTokens
+    pub fn semantic_tokens_config(&self) -> SemanticTokensConfig {
+        SemanticTokensConfig {
+            augments_syntax_tokens: self.semantics_tokens_augments_syntax_tokens(),
+            non_standard_tokens: self.data.semanticHighlighting_nonStandardTokens,
+            disabled: self.data.semanticHighlighting_disabledTokens.clone(),
+            mapping: self.data.semanticHighlighting_tokenMapping.clone(),
+        }
     }
 
     pub fn highlighting_config(&self) -> HighlightConfig {
@@ -1663,6 +2003,7 @@ pub fn hover(&self) -> HoverConfig {
                 offset: self.data.hover_memoryLayout_offset.map(mem_kind),
                 alignment: self.data.hover_memoryLayout_alignment.map(mem_kind),
                 niches: self.data.hover_memoryLayout_niches.unwrap_or_default(),
+                variants: self.data.hover_memoryLayout_variants,
             }),
             documentation: self.data.hover_documentation_enable,
             format: {
@@ -1777,6 +2118,16 @@ pub fn is_visual_studio_code(&self) -> bool {
         self.is_visual_studio_code
     }
 }
+
+/// Returns the `rust-analyzer` directory in the user's cache directory.
+fn user_cache_dir() -> Option<AbsPathBuf> {
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .or_else(|| std::env::var_os("LOCALAPPDATA"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|it| PathBuf::from(it).join(".cache")))?;
+    AbsPathBuf::try_from(cache_dir.join("rust-analyzer")).ok()
+}
+
 // Deserialization definitions
 
 macro_rules! create_bool_or_string_de {
@@ -1954,6 +2305,36 @@ enum ExprFillDefaultDef {
     Default,
 }
 
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum ErrorHandlingStyleDef {
+    Anyhow,
+    Thiserror,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+enum BoundsStyleDef {
+    WhereClause,
+    Inline,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+enum DiagnosticsStyleDef {
+    Default,
+    ClippyLite,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum DiagnosticSeverityDef {
+    Off,
+    Hint,
+    Warning,
+    Error,
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 enum ImportGranularityDef {
@@ -2255,6 +2636,13 @@ macro_rules! set {
             "type": "array",
             "items": { "type": "string" },
         },
+        "Vec<Vec<String>>" => set! {
+            "type": "array",
+            "items": {
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
         "Vec<PathBuf>" => set! {
             "type": "array",
             "items": { "type": "string" },
@@ -2273,6 +2661,13 @@ macro_rules! set {
         "FxHashMap<String, String>" => set! {
             "type": "object",
         },
+        "FxHashMap<String, DiagnosticSeverityDef>" => set! {
+            "type": "object",
+            "additionalProperties": {
+                "type": "string",
+                "enum": ["off", "hint", "warning", "error"],
+            },
+        },
         "FxHashMap<Box<str>, usize>" => set! {
             "type": "object",
         },
@@ -2301,6 +2696,30 @@ macro_rules! set {
                 "Fill missing expressions with reasonable defaults, `new` or `default` constructors."
             ],
         },
+        "ErrorHandlingStyleDef" => set! {
+            "type": "string",
+            "enum": ["anyhow", "thiserror"],
+            "enumDescriptions": [
+                "Return `anyhow::Result` and create errors with the `anyhow!` macro.",
+                "Return a dedicated error enum deriving `thiserror::Error`, with a variant for each error type."
+            ],
+        },
+        "BoundsStyleDef" => set! {
+            "type": "string",
+            "enum": ["where_clause", "inline"],
+            "enumDescriptions": [
+                "Put bounds in the `where` clause.",
+                "Put bounds in the generic parameter list, keeping only the bounds that can't be written there in the `where` clause."
+            ],
+        },
+        "DiagnosticsStyleDef" => set! {
+            "type": "string",
+            "enum": ["default", "clippy-lite"],
+            "enumDescriptions": [
+                "Only report rust-analyzer's own diagnostics.",
+                "Also report a subset of clippy's lints, like `iter_nth_zero`."
+            ],
+        },
         "ImportGranularityDef" => set! {
             "type": "string",
             "enum": ["preserve", "crate", "module", "item", "one"],
@@ -0,0 +1,751 @@
+//! Serving any number of clients over TCP from a single analysis state, see `--listen`.
+//!
+//! The main loop talks to exactly one client, so a hub thread sits in between the main loop and
+//! the connected clients, and makes them look like a single one:
+//!
+//! * The `initialize` request of the first client is handled by the server, later clients get the
+//!   same result. The server is set up for the capabilities, initialization options and workspace
+//!   folders of the first client, so later clients with different ones are refused.
+//! * The server pulls its configuration from the longest connected client. Configuration changes
+//!   of other clients are ignored, and they are told so.
+//! * Request ids of clients are renumbered so that they don't collide, and the responses are routed
+//!   back to the client that sent the request. Requests of the server go to the longest connected
+//!   client, capability registrations go to every client.
+//! * Notifications of the server go to every client, and the latest diagnostics of each file are
+//!   replayed to clients that connect later.
+//! * `shutdown` and `exit` only disconnect the client that sends them. When a client disconnects,
+//!   cleanly or not, the documents it had open are closed, unless another client has them open.
+//!
+//! The server itself keeps running with the loaded workspaces while no client is connected.
+
+use std::{
+    io::{self, BufReader},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+};
+
+use crossbeam_channel::{select, unbounded, Receiver, Sender};
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{
+        Cancel, DidChangeConfiguration, DidCloseTextDocument, DidOpenTextDocument, Exit,
+        Initialized, Notification as _, PublishDiagnostics, ShowMessage,
+    },
+    request::{
+        Initialize, RegisterCapability, Request as _, Shutdown as ShutdownRequest,
+        UnregisterCapability,
+    },
+};
+use rustc_hash::FxHashMap;
+use stdx::thread::ThreadIntent;
+
+type ClientId = u32;
+
+/// Listens for clients on `addr`, returning the connection the server should be run on.
+pub fn listen(addr: impl ToSocketAddrs) -> io::Result<Connection> {
+    serve(TcpListener::bind(addr)?)
+}
+
+fn serve(listener: TcpListener) -> io::Result<Connection> {
+    let (server, hub) = Connection::memory();
+    let (events, inbox) = unbounded();
+    spawn("LspListener", move || {
+        for (id, stream) in (0..).zip(listener.incoming()) {
+            match stream.and_then(|stream| connect(id, stream, events.clone())) {
+                Ok(()) => tracing::info!("client {id} connected"),
+                Err(e) => tracing::error!("failed to accept a client: {e}"),
+            }
+        }
+    })?;
+    spawn("LspHub", move || Hub::new(hub).run(inbox))?;
+    Ok(server)
+}
+
+fn spawn(name: &str, f: impl FnOnce() + Send + 'static) -> io::Result<()> {
+    stdx::thread::Builder::new(ThreadIntent::Worker)
+        .name(name.to_owned())
+        .allow_leak(true)
+        .spawn(f)
+        .map(drop)
+}
+
+enum Event {
+    Connected(ClientId, Sender<Message>),
+    Message(ClientId, Message),
+    Disconnected(ClientId),
+}
+
+/// Spawns the threads reading from and writing to the client on `stream`.
+fn connect(id: ClientId, stream: TcpStream, events: Sender<Event>) -> io::Result<()> {
+    let (sender, receiver) = unbounded::<Message>();
+    let mut write_stream = stream.try_clone()?;
+    spawn("LspClientWriter", move || {
+        for message in receiver {
+            if let Err(e) = message.write(&mut write_stream) {
+                tracing::warn!("failed to write to client {id}: {e}");
+                break;
+            }
+        }
+        // Wakes up the reader, for clients that are dropped by the hub or can't be written to.
+        let _ = write_stream.shutdown(Shutdown::Both);
+    })?;
+    events.send(Event::Connected(id, sender)).unwrap();
+    spawn("LspClientReader", move || {
+        let mut reader = BufReader::new(stream);
+        loop {
+            match Message::read(&mut reader) {
+                Ok(Some(message)) => events.send(Event::Message(id, message)).unwrap(),
+                Ok(None) => break,
+                Err(e) => {
+                    tracing::warn!("failed to read from client {id}: {e}");
+                    break;
+                }
+            }
+        }
+        events.send(Event::Disconnected(id)).unwrap();
+    })
+}
+
+struct Client {
+    id: ClientId,
+    sender: Sender<Message>,
+    /// Whether the client got the `initialize` result, and can be sent anything else.
+    initialized: bool,
+}
+
+struct Hub {
+    server: Connection,
+    /// In the order they connected.
+    clients: Vec<Client>,
+    /// The id of the forwarded `initialize` request, while it is in flight.
+    initialize_request: Option<RequestId>,
+    /// The parts of the params of the forwarded `initialize` request the server is set up for,
+    /// which later clients have to match.
+    initialize_params: Option<serde_json::Value>,
+    initialize_result: Option<serde_json::Value>,
+    /// Whether the server got the `initialized` notification.
+    server_initialized: bool,
+    /// Clients waiting for the result of the forwarded `initialize` request.
+    waiting_for_initialize: Vec<(ClientId, RequestId)>,
+    next_request_id: i32,
+    /// The client and original id of requests forwarded to the server, by their forwarded id.
+    client_requests: FxHashMap<RequestId, (ClientId, RequestId)>,
+    /// The client each unanswered request of the server waits on. Of requests sent to every
+    /// client, only the first response is forwarded.
+    server_requests: FxHashMap<RequestId, ClientId>,
+    /// The clients that have each document open.
+    open_documents: FxHashMap<lsp_types::Url, Vec<ClientId>>,
+    /// The latest diagnostics of each file, for clients connecting later.
+    diagnostics: FxHashMap<lsp_types::Url, Notification>,
+    /// The capabilities the server registered, by registration id.
+    registrations: FxHashMap<String, lsp_types::Registration>,
+}
+
+impl Hub {
+    fn new(server: Connection) -> Hub {
+        Hub {
+            server,
+            clients: Vec::new(),
+            initialize_request: None,
+            initialize_params: None,
+            initialize_result: None,
+            server_initialized: false,
+            waiting_for_initialize: Vec::new(),
+            next_request_id: 0,
+            client_requests: FxHashMap::default(),
+            server_requests: FxHashMap::default(),
+            open_documents: FxHashMap::default(),
+            diagnostics: FxHashMap::default(),
+            registrations: FxHashMap::default(),
+        }
+    }
+
+    fn run(mut self, inbox: Receiver<Event>) {
+        loop {
+            select! {
+                recv(inbox) -> event => match event {
+                    Ok(Event::Connected(id, sender)) => {
+                        self.clients.push(Client { id, sender, initialized: false })
+                    }
+                    Ok(Event::Message(id, message)) => self.handle_client_message(id, message),
+                    Ok(Event::Disconnected(id)) => self.disconnect(id),
+                    Err(_) => return,
+                },
+                recv(self.server.receiver) -> message => match message {
+                    Ok(message) => self.handle_server_message(message),
+                    Err(_) => return,
+                },
+            }
+        }
+    }
+
+    fn handle_client_message(&mut self, client: ClientId, message: Message) {
+        if !self.clients.iter().any(|it| it.id == client) {
+            // Messages sent after `exit`.
+            return;
+        }
+        match message {
+            Message::Request(req) => match &*req.method {
+                Initialize::METHOD => {
+                    let params = shared_initialize_params(&req.params);
+                    if self.initialize_params.as_ref().is_some_and(|it| *it != params) {
+                        let resp = Response::new_err(
+                            req.id,
+                            ErrorCode::InvalidRequest as i32,
+                            "the server is shared with a client with other capabilities, \
+                             initialization options or workspace folders"
+                                .to_owned(),
+                        );
+                        self.send_to(client, resp.into());
+                        return;
+                    }
+                    match &self.initialize_result {
+                        Some(result) => {
+                            let result = result.clone();
+                            self.initialize_client(client, req.id, result);
+                        }
+                        None => {
+                            self.waiting_for_initialize.push((client, req.id.clone()));
+                            if self.initialize_request.is_none() {
+                                self.initialize_params = Some(params);
+                                self.initialize_request = Some(self.forward_request(client, req));
+                            }
+                        }
+                    }
+                }
+                ShutdownRequest::METHOD => {
+                    self.send_to(client, Response::new_ok(req.id, ()).into());
+                }
+                _ => {
+                    self.forward_request(client, req);
+                }
+            },
+            Message::Response(resp) => {
+                // Responses to requests sent to several clients are only forwarded once.
+                if self.server_requests.remove(&resp.id).is_some() {
+                    self.send_to_server(resp.into());
+                }
+            }
+            Message::Notification(not) => match &*not.method {
+                Exit::METHOD => self.disconnect(client),
+                Initialized::METHOD => {
+                    if self.server_initialized {
+                        self.register_capabilities(client);
+                        self.replay_diagnostics(client);
+                    } else {
+                        self.server_initialized = true;
+                        self.send_to_server(not.into());
+                    }
+                }
+                DidChangeConfiguration::METHOD => {
+                    if self.primary_client() == Some(client) {
+                        self.send_to_server(not.into());
+                    } else {
+                        let params = lsp_types::ShowMessageParams {
+                            typ: lsp_types::MessageType::WARNING,
+                            message: "rust-analyzer is shared with another editor, whose \
+                                      configuration is used instead"
+                                .to_owned(),
+                        };
+                        let not = Notification::new(ShowMessage::METHOD.to_owned(), params);
+                        self.send_to(client, not.into());
+                    }
+                }
+                Cancel::METHOD => {
+                    let Ok(params) = not.extract::<lsp_types::CancelParams>(Cancel::METHOD) else {
+                        return;
+                    };
+                    let id = match params.id {
+                        lsp_types::NumberOrString::Number(it) => RequestId::from(it),
+                        lsp_types::NumberOrString::String(it) => RequestId::from(it),
+                    };
+                    let forwarded = self
+                        .client_requests
+                        .iter()
+                        .find(|(_, it)| **it == (client, id.clone()))
+                        .map(|(forwarded, _)| forwarded.clone());
+                    if let Some(forwarded) = forwarded {
+                        self.cancel_on_server(forwarded);
+                    }
+                }
+                DidOpenTextDocument::METHOD => {
+                    if let Some(uri) = document_uri(&not) {
+                        let clients = self.open_documents.entry(uri).or_default();
+                        if !clients.contains(&client) {
+                            clients.push(client);
+                        }
+                    }
+                    self.send_to_server(not.into());
+                }
+                DidCloseTextDocument::METHOD => {
+                    if let Some(uri) = document_uri(&not) {
+                        if self.close_document(&uri, client) {
+                            self.send_to_server(not.into());
+                        }
+                    }
+                }
+                _ => self.send_to_server(not.into()),
+            },
+        }
+    }
+
+    fn handle_server_message(&mut self, message: Message) {
+        match message {
+            Message::Request(req) => {
+                let broadcast = matches!(
+                    &*req.method,
+                    RegisterCapability::METHOD | UnregisterCapability::METHOD
+                );
+                if broadcast {
+                    self.record_registrations(&req);
+                }
+                let clients: Vec<_> = self.clients.iter().filter(|it| it.initialized).collect();
+                let clients =
+                    if broadcast { &clients[..] } else { &clients[..clients.len().min(1)] };
+                let Some(first) = clients.first() else {
+                    let resp = Response::new_err(
+                        req.id,
+                        ErrorCode::RequestCanceled as i32,
+                        "no client is connected".to_owned(),
+                    );
+                    self.send_to_server(resp.into());
+                    return;
+                };
+                self.server_requests.insert(req.id.clone(), first.id);
+                for client in clients {
+                    let _ = client.sender.send(req.clone().into());
+                }
+            }
+            Message::Response(resp) => {
+                let Some((client, id)) = self.client_requests.remove(&resp.id) else { return };
+                if self.initialize_request.as_ref() == Some(&resp.id) {
+                    self.initialize_request = None;
+                    let waiting = std::mem::take(&mut self.waiting_for_initialize);
+                    let Some(result) = resp.result else {
+                        // Let the next client initialize the server with its own params.
+                        self.initialize_params = None;
+                        for (client, id) in waiting {
+                            self.send_to(client, Response { id, ..resp.clone() }.into());
+                        }
+                        return;
+                    };
+                    self.initialize_result = Some(result.clone());
+                    for (client, id) in waiting {
+                        self.initialize_client(client, id, result.clone());
+                    }
+                    if !self.clients.iter().any(|it| it.initialized) {
+                        self.initialize_server();
+                    }
+                    return;
+                }
+                self.send_to(client, Response { id, ..resp }.into());
+            }
+            Message::Notification(not) => {
+                if not.method == PublishDiagnostics::METHOD {
+                    if let Ok(params) = serde_json::from_value::<lsp_types::PublishDiagnosticsParams>(
+                        not.params.clone(),
+                    ) {
+                        if params.diagnostics.is_empty() {
+                            self.diagnostics.remove(&params.uri);
+                        } else {
+                            self.diagnostics.insert(params.uri, not.clone());
+                        }
+                    }
+                }
+                for client in self.clients.iter().filter(|it| it.initialized) {
+                    let _ = client.sender.send(not.clone().into());
+                }
+            }
+        }
+    }
+
+    fn initialize_client(&mut self, client: ClientId, id: RequestId, result: serde_json::Value) {
+        if let Some(it) = self.clients.iter_mut().find(|it| it.id == client) {
+            it.initialized = true;
+            let _ = it.sender.send(Response::new_ok(id, result).into());
+        }
+    }
+
+    /// Sends `initialized` to the server if the client it was meant to come from is gone.
+    fn initialize_server(&mut self) {
+        if !self.server_initialized && self.initialize_result.is_some() {
+            self.server_initialized = true;
+            let not =
+                Notification::new(Initialized::METHOD.to_owned(), lsp_types::InitializedParams {});
+            self.send_to_server(not.into());
+        }
+    }
+
+    /// The client requests of the server go to, and the configuration is pulled from.
+    fn primary_client(&self) -> Option<ClientId> {
+        self.clients.iter().find(|it| it.initialized).map(|it| it.id)
+    }
+
+    fn disconnect(&mut self, client: ClientId) {
+        let Some(idx) = self.clients.iter().position(|it| it.id == client) else { return };
+        let was_primary = self.primary_client() == Some(client);
+        self.clients.remove(idx);
+        tracing::info!("client {client} disconnected");
+
+        if was_primary && self.primary_client().is_some() {
+            // Makes the server pull the configuration of the new primary client.
+            let not = Notification::new(
+                DidChangeConfiguration::METHOD.to_owned(),
+                lsp_types::DidChangeConfigurationParams { settings: serde_json::Value::Null },
+            );
+            self.send_to_server(not.into());
+        }
+
+        self.waiting_for_initialize.retain(|(it, _)| *it != client);
+        if self.clients.iter().all(|it| !it.initialized) {
+            self.initialize_server();
+        }
+
+        let documents: Vec<_> = self
+            .open_documents
+            .iter()
+            .filter(|(_, clients)| clients.contains(&client))
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        for uri in documents {
+            if self.close_document(&uri, client) {
+                let not = Notification::new(
+                    DidCloseTextDocument::METHOD.to_owned(),
+                    lsp_types::DidCloseTextDocumentParams {
+                        text_document: lsp_types::TextDocumentIdentifier { uri },
+                    },
+                );
+                self.send_to_server(not.into());
+            }
+        }
+
+        let requests: Vec<_> = self
+            .client_requests
+            .iter()
+            .filter(|(_, (it, _))| *it == client)
+            .map(|(forwarded, _)| forwarded.clone())
+            .filter(|forwarded| self.initialize_request.as_ref() != Some(forwarded))
+            .collect();
+        for forwarded in requests {
+            self.client_requests.remove(&forwarded);
+            self.cancel_on_server(forwarded);
+        }
+
+        // Hand requests of the server that are left unanswered over to the next client.
+        let unanswered: Vec<_> = self
+            .server_requests
+            .iter()
+            .filter(|(_, it)| **it == client)
+            .map(|(id, _)| id.clone())
+            .collect();
+        for id in unanswered {
+            self.server_requests.remove(&id);
+            let resp = Response::new_err(
+                id,
+                ErrorCode::RequestCanceled as i32,
+                "the client disconnected".to_owned(),
+            );
+            self.send_to_server(resp.into());
+        }
+    }
+
+    /// Returns whether no client has the document open anymore.
+    fn close_document(&mut self, uri: &lsp_types::Url, client: ClientId) -> bool {
+        let Some(clients) = self.open_documents.get_mut(uri) else { return true };
+        clients.retain(|it| *it != client);
+        if clients.is_empty() {
+            self.open_documents.remove(uri);
+            return true;
+        }
+        false
+    }
+
+    fn forward_request(&mut self, client: ClientId, req: Request) -> RequestId {
+        let id = RequestId::from(self.next_request_id);
+        self.next_request_id += 1;
+        self.client_requests.insert(id.clone(), (client, req.id));
+        self.send_to_server(Request { id: id.clone(), ..req }.into());
+        id
+    }
+
+    fn cancel_on_server(&mut self, forwarded: RequestId) {
+        let id = forwarded.to_string().parse().map_or_else(
+            |_| lsp_types::NumberOrString::String(forwarded.to_string()),
+            lsp_types::NumberOrString::Number,
+        );
+        let not = Notification::new(Cancel::METHOD.to_owned(), lsp_types::CancelParams { id });
+        self.send_to_server(not.into());
+    }
+
+    fn record_registrations(&mut self, req: &Request) {
+        if req.method == RegisterCapability::METHOD {
+            if let Ok(params) =
+                serde_json::from_value::<lsp_types::RegistrationParams>(req.params.clone())
+            {
+                for registration in params.registrations {
+                    self.registrations.insert(registration.id.clone(), registration);
+                }
+            }
+        } else if let Ok(params) =
+            serde_json::from_value::<lsp_types::UnregistrationParams>(req.params.clone())
+        {
+            for unregistration in params.unregisterations {
+                self.registrations.remove(&unregistration.id);
+            }
+        }
+    }
+
+    /// Registers the capabilities the server registered with earlier clients. The responses
+    /// aren't forwarded, as the server doesn't know about the request.
+    fn register_capabilities(&mut self, client: ClientId) {
+        if self.registrations.is_empty() {
+            return;
+        }
+        let params = lsp_types::RegistrationParams {
+            registrations: self.registrations.values().cloned().collect(),
+        };
+        let id = RequestId::from(format!("rust-analyzer-daemon/{}", self.next_request_id));
+        self.next_request_id += 1;
+        self.send_to(
+            client,
+            Request::new(id, RegisterCapability::METHOD.to_owned(), params).into(),
+        );
+    }
+
+    fn replay_diagnostics(&self, client: ClientId) {
+        for not in self.diagnostics.values() {
+            self.send_to(client, not.clone().into());
+        }
+    }
+
+    fn send_to(&self, client: ClientId, message: Message) {
+        if let Some(client) = self.clients.iter().find(|it| it.id == client) {
+            // The client may be gone already, its reader will tell us soon.
+            let _ = client.sender.send(message);
+        }
+    }
+
+    fn send_to_server(&self, message: Message) {
+        // The server only goes away when the process exits.
+        let _ = self.server.sender.send(message);
+    }
+}
+
+/// The parts of the params of an `initialize` request the server is set up for.
+fn shared_initialize_params(params: &serde_json::Value) -> serde_json::Value {
+    const SHARED: [&str; 5] =
+        ["capabilities", "initializationOptions", "rootPath", "rootUri", "workspaceFolders"];
+    SHARED
+        .into_iter()
+        .filter_map(|key| Some((key.to_owned(), params.get(key)?.clone())))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+fn document_uri(not: &Notification) -> Option<lsp_types::Url> {
+    not.params.get("textDocument")?.get("uri")?.as_str()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufReader, Write},
+        net::{Shutdown, TcpListener, TcpStream},
+        time::Duration,
+    };
+
+    use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+    use serde_json::json;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    struct TestClient {
+        stream: TcpStream,
+        reader: BufReader<TcpStream>,
+    }
+
+    impl TestClient {
+        fn connect(listener_addr: std::net::SocketAddr) -> TestClient {
+            let stream = TcpStream::connect(listener_addr).unwrap();
+            stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+            let reader = BufReader::new(stream.try_clone().unwrap());
+            TestClient { stream, reader }
+        }
+
+        fn send(&mut self, message: impl Into<Message>) {
+            message.into().write(&mut self.stream).unwrap();
+            self.stream.flush().unwrap();
+        }
+
+        fn recv(&mut self) -> Message {
+            Message::read(&mut self.reader).unwrap().unwrap()
+        }
+
+        fn initialize(&mut self) -> Message {
+            self.send(Request::new(0.into(), "initialize".to_owned(), json!({})));
+            let resp = self.recv();
+            self.send(Notification::new("initialized".to_owned(), json!({})));
+            resp
+        }
+
+        fn open(&mut self, uri: &str) {
+            self.send(Notification::new(
+                "textDocument/didOpen".to_owned(),
+                json!({ "textDocument": { "uri": uri, "languageId": "rust", "version": 0, "text": "" } }),
+            ));
+        }
+    }
+
+    fn json(message: impl Into<Message>) -> serde_json::Value {
+        serde_json::to_value(message.into()).unwrap()
+    }
+
+    fn recv(server: &Connection) -> Message {
+        server.receiver.recv_timeout(TIMEOUT).unwrap()
+    }
+
+    fn start() -> (Connection, std::net::SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        (super::serve(listener).unwrap(), addr)
+    }
+
+    fn initialize_server(server: &Connection, client: &mut TestClient) {
+        client.send(Request::new(0.into(), "initialize".to_owned(), json!({})));
+        let Message::Request(req) = recv(server) else { panic!("expected `initialize`") };
+        assert_eq!(req.method, "initialize");
+        server.sender.send(Response::new_ok(req.id, json!({ "capabilities": {} })).into()).unwrap();
+        assert_eq!(
+            json(client.recv()),
+            json(Response::new_ok(0.into(), json!({ "capabilities": {} })))
+        );
+        client.send(Notification::new("initialized".to_owned(), json!({})));
+        let Message::Notification(not) = recv(server) else { panic!("expected `initialized`") };
+        assert_eq!(not.method, "initialized");
+    }
+
+    #[test]
+    fn reconnect_after_unclean_disconnect() {
+        let (server, addr) = start();
+
+        let mut first = TestClient::connect(addr);
+        initialize_server(&server, &mut first);
+        first.open("file:///main.rs");
+        let Message::Notification(not) = recv(&server) else { panic!("expected `didOpen`") };
+        assert_eq!(not.method, "textDocument/didOpen");
+        let diagnostics = Notification::new(
+            "textDocument/publishDiagnostics".to_owned(),
+            json!({ "uri": "file:///main.rs", "diagnostics": [{ "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 0 } }, "message": "error" }] }),
+        );
+        server.sender.send(diagnostics.clone().into()).unwrap();
+        assert_eq!(json(first.recv()), json(diagnostics.clone()));
+
+        // Disconnect without `shutdown` and `exit`.
+        first.stream.shutdown(Shutdown::Both).unwrap();
+        drop(first);
+        let Message::Notification(not) = recv(&server) else { panic!("expected `didClose`") };
+        assert_eq!(not.method, "textDocument/didClose");
+        assert_eq!(not.params, json!({ "textDocument": { "uri": "file:///main.rs" } }));
+
+        // The next client is served by the same server, without initializing it again.
+        let mut second = TestClient::connect(addr);
+        assert_eq!(
+            json(second.initialize()),
+            json(Response::new_ok(0.into(), json!({ "capabilities": {} })))
+        );
+        assert_eq!(json(second.recv()), json(diagnostics));
+        second.send(Request::new(0.into(), "textDocument/hover".to_owned(), json!({})));
+        let Message::Request(req) = recv(&server) else { panic!("expected `hover`") };
+        assert_eq!(req.method, "textDocument/hover");
+        server.sender.send(Response::new_ok(req.id, "hover").into()).unwrap();
+        assert_eq!(json(second.recv()), json(Response::new_ok(0.into(), "hover")));
+
+        // A clean disconnect is answered by the hub, the server keeps running.
+        second.send(Request::new(1.into(), "shutdown".to_owned(), ()));
+        let Message::Response(resp) = second.recv() else { panic!("expected a response") };
+        assert_eq!((resp.id, resp.error.is_none()), (1.into(), true));
+        second.send(Notification::new("exit".to_owned(), ()));
+        assert!(Message::read(&mut second.reader).unwrap().is_none());
+        let mut third = TestClient::connect(addr);
+        third.initialize();
+        third.send(Request::new(RequestId::from("id".to_owned()), "custom".to_owned(), ()));
+        let Message::Request(req) = recv(&server) else { panic!("expected `custom`") };
+        assert_eq!(req.method, "custom");
+    }
+
+    #[test]
+    fn serves_concurrent_clients() {
+        let (server, addr) = start();
+        let mut first = TestClient::connect(addr);
+        initialize_server(&server, &mut first);
+        let mut second = TestClient::connect(addr);
+        second.initialize();
+
+        // Both clients use the same request id.
+        first.send(Request::new(7.into(), "first".to_owned(), ()));
+        let Message::Request(first_req) = recv(&server) else { panic!("expected a request") };
+        second.send(Request::new(7.into(), "second".to_owned(), ()));
+        let Message::Request(second_req) = recv(&server) else { panic!("expected a request") };
+        assert_ne!(first_req.id, second_req.id);
+        server.sender.send(Response::new_ok(second_req.id, "second").into()).unwrap();
+        server.sender.send(Response::new_ok(first_req.id, "first").into()).unwrap();
+        assert_eq!(json(first.recv()), json(Response::new_ok(7.into(), "first")));
+        assert_eq!(json(second.recv()), json(Response::new_ok(7.into(), "second")));
+
+        // Notifications go to everyone, requests to the longest connected client.
+        let status = Notification::new("experimental/serverStatus".to_owned(), json!({}));
+        server.sender.send(status.clone().into()).unwrap();
+        assert_eq!(json(first.recv()), json(status.clone()));
+        assert_eq!(json(second.recv()), json(status));
+        let config = Request::new(1.into(), "workspace/configuration".to_owned(), json!({}));
+        server.sender.send(config.clone().into()).unwrap();
+        assert_eq!(json(first.recv()), json(config));
+        first.send(Response::new_ok(1.into(), json!([])));
+        assert_eq!(json(recv(&server)), json(Response::new_ok(1.into(), json!([]))));
+
+        // A document open in both clients is only closed once both closed it.
+        first.open("file:///lib.rs");
+        second.open("file:///lib.rs");
+        recv(&server);
+        recv(&server);
+        first.stream.shutdown(Shutdown::Both).unwrap();
+        let Message::Notification(not) = recv(&server) else { panic!("expected a notification") };
+        assert_eq!(not.method, "workspace/didChangeConfiguration");
+        second.send(Notification::new(
+            "textDocument/didClose".to_owned(),
+            json!({ "textDocument": { "uri": "file:///lib.rs" } }),
+        ));
+        let Message::Notification(not) = recv(&server) else { panic!("expected `didClose`") };
+        assert_eq!(not.method, "textDocument/didClose");
+    }
+
+    #[test]
+    fn refuses_mismatched_clients() {
+        let (server, addr) = start();
+        let mut first = TestClient::connect(addr);
+        initialize_server(&server, &mut first);
+
+        // The server is set up for the capabilities of the first client.
+        let mut second = TestClient::connect(addr);
+        let params = json!({ "capabilities": { "workspace": { "configuration": true } } });
+        second.send(Request::new(0.into(), "initialize".to_owned(), params));
+        let Message::Response(resp) = second.recv() else { panic!("expected a response") };
+        assert_eq!((resp.id, resp.result), (0.into(), None));
+
+        // Only the first client's configuration is used.
+        let mut third = TestClient::connect(addr);
+        third.initialize();
+        let change = Notification::new(
+            "workspace/didChangeConfiguration".to_owned(),
+            json!({ "settings": null }),
+        );
+        third.send(change.clone());
+        let Message::Notification(not) = third.recv() else { panic!("expected `showMessage`") };
+        assert_eq!(not.method, "window/showMessage");
+        first.send(change.clone());
+        assert_eq!(json(recv(&server)), json(change.clone()));
+
+        // Once the first client is gone, the server pulls the configuration of the next one.
+        first.stream.shutdown(Shutdown::Both).unwrap();
+        assert_eq!(json(recv(&server)), json(change));
+    }
+}
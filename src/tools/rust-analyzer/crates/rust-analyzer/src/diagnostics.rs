@@ -3,16 +3,27 @@
 
 use std::mem;
 
+use flycheck::PackageId;
 use ide::FileId;
-use ide_db::FxHashMap;
+use ide_db::{cargo_manifest::manifest_dependencies, FxHashMap};
 use itertools::Itertools;
 use nohash_hasher::{IntMap, IntSet};
+use project_model::{is_crates_io_package, CratesIndex, ProjectWorkspace};
 use rustc_hash::FxHashSet;
+use syntax::{TextRange, TextSize};
 use triomphe::Arc;
 
-use crate::{global_state::GlobalStateSnapshot, lsp, lsp_ext};
+use crate::{
+    global_state::GlobalStateSnapshot,
+    line_index::{LineEndings, LineIndex, PositionEncoding},
+    lsp, lsp_ext,
+};
 
-pub(crate) type CheckFixes = Arc<IntMap<usize, IntMap<FileId, Vec<Fix>>>>;
+pub(crate) type CheckFixes = Arc<IntMap<usize, PackageMap<IntMap<FileId, Vec<Fix>>>>>;
+
+/// Flycheck results per package they were reported for, `None` if the check
+/// command doesn't tell.
+pub(crate) type PackageMap<T> = FxHashMap<Option<PackageId>, T>;
 
 #[derive(Debug, Default, Clone)]
 pub struct DiagnosticsMapConfig {
@@ -27,7 +38,7 @@ pub(crate) struct DiagnosticCollection {
     // FIXME: should be IntMap<FileId, Vec<ra_id::Diagnostic>>
     pub(crate) native: IntMap<FileId, Vec<lsp_types::Diagnostic>>,
     // FIXME: should be Vec<flycheck::Diagnostic>
-    pub(crate) check: IntMap<usize, IntMap<FileId, Vec<lsp_types::Diagnostic>>>,
+    pub(crate) check: IntMap<usize, PackageMap<IntMap<FileId, Vec<lsp_types::Diagnostic>>>>,
     pub(crate) check_fixes: CheckFixes,
     changes: IntSet<FileId>,
 }
@@ -45,14 +56,33 @@ pub(crate) fn clear_check(&mut self, flycheck_id: usize) {
             it.clear();
         }
         if let Some(it) = self.check.get_mut(&flycheck_id) {
-            self.changes.extend(it.drain().map(|(key, _value)| key));
+            self.changes.extend(it.drain().flat_map(|(_, files)| files.into_keys()));
+        }
+    }
+
+    /// Clears the diagnostics a flycheck reported for a single package, or
+    /// those it reported without a package if `package_id` is `None`.
+    pub(crate) fn clear_check_for_package(
+        &mut self,
+        flycheck_id: usize,
+        package_id: Option<PackageId>,
+    ) {
+        if let Some(it) = Arc::make_mut(&mut self.check_fixes).get_mut(&flycheck_id) {
+            it.remove(&package_id);
+        }
+        if let Some(files) = self.check.get_mut(&flycheck_id).and_then(|it| it.remove(&package_id))
+        {
+            self.changes.extend(files.into_keys());
         }
     }
 
     pub(crate) fn clear_check_all(&mut self) {
         Arc::make_mut(&mut self.check_fixes).clear();
-        self.changes
-            .extend(self.check.values_mut().flat_map(|it| it.drain().map(|(key, _value)| key)))
+        self.changes.extend(
+            self.check
+                .values_mut()
+                .flat_map(|it| it.drain().flat_map(|(_, files)| files.into_keys())),
+        )
     }
 
     pub(crate) fn clear_native_for(&mut self, file_id: FileId) {
@@ -63,11 +93,19 @@ pub(crate) fn clear_native_for(&mut self, file_id: FileId) {
     pub(crate) fn add_check_diagnostic(
         &mut self,
         flycheck_id: usize,
+        package_id: &Option<PackageId>,
         file_id: FileId,
         diagnostic: lsp_types::Diagnostic,
         fix: Option<Fix>,
     ) {
-        let diagnostics = self.check.entry(flycheck_id).or_default().entry(file_id).or_default();
+        let diagnostics = self
+            .check
+            .entry(flycheck_id)
+            .or_default()
+            .entry(package_id.clone())
+            .or_default()
+            .entry(file_id)
+            .or_default();
         for existing_diagnostic in diagnostics.iter() {
             if are_diagnostics_equal(existing_diagnostic, &diagnostic) {
                 return;
@@ -75,7 +113,14 @@ pub(crate) fn add_check_diagnostic(
         }
 
         let check_fixes = Arc::make_mut(&mut self.check_fixes);
-        check_fixes.entry(flycheck_id).or_default().entry(file_id).or_default().extend(fix);
+        check_fixes
+            .entry(flycheck_id)
+            .or_default()
+            .entry(package_id.clone())
+            .or_default()
+            .entry(file_id)
+            .or_default()
+            .extend(fix);
         diagnostics.push(diagnostic);
         self.changes.insert(file_id);
     }
@@ -105,7 +150,12 @@ pub(crate) fn diagnostics_for(
         file_id: FileId,
     ) -> impl Iterator<Item = &lsp_types::Diagnostic> {
         let native = self.native.get(&file_id).into_iter().flatten();
-        let check = self.check.values().filter_map(move |it| it.get(&file_id)).flatten();
+        let check = self
+            .check
+            .values()
+            .flat_map(|it| it.values())
+            .filter_map(move |it| it.get(&file_id))
+            .flatten();
         native.chain(check)
     }
 
@@ -127,6 +177,7 @@ fn are_diagnostics_equal(left: &lsp_types::Diagnostic, right: &lsp_types::Diagno
 pub(crate) fn fetch_native_diagnostics(
     snapshot: GlobalStateSnapshot,
     subscriptions: Vec<FileId>,
+    is_superseded: impl Fn() -> bool,
 ) -> Vec<(FileId, Vec<lsp_types::Diagnostic>)> {
     let _p = tracing::span!(tracing::Level::INFO, "fetch_native_diagnostics").entered();
     let _ctx = stdx::panic_context::enter("fetch_native_diagnostics".to_owned());
@@ -141,7 +192,15 @@ pub(crate) fn fetch_native_diagnostics(
             }),
             source: Some("rust-analyzer".to_owned()),
             message: d.message,
-            related_information: None,
+            related_information: (!d.related.is_empty()).then(|| {
+                d.related
+                    .into_iter()
+                    .filter_map(|(frange, message)| {
+                        let location = lsp::to_proto::location(&snapshot, frange).ok()?;
+                        Some(lsp_types::DiagnosticRelatedInformation { location, message })
+                    })
+                    .collect()
+            }),
             tags: d.unused.then(|| vec![lsp_types::DiagnosticTag::UNNECESSARY]),
             data: None,
         };
@@ -152,6 +211,8 @@ pub(crate) fn fetch_native_diagnostics(
     let mut diagnostics = subscriptions
         .iter()
         .copied()
+        // Bail out early if a newer computation was requested, its results would be dropped anyway.
+        .take_while(|_| !is_superseded())
         .filter_map(|file_id| {
             let line_index = snapshot.file_line_index(file_id).ok()?;
             let diagnostics = snapshot
@@ -199,3 +260,117 @@ pub(crate) fn fetch_native_diagnostics(
     }
     diagnostics
 }
+
+/// Warns about dependencies of the cargo workspace members that resolved to versions yanked from
+/// crates.io, on their declarations in the members' `Cargo.toml` files. Every member manifest is
+/// returned, so that the warnings of fixed dependencies get cleared.
+pub(crate) fn fetch_yanked_dependency_diagnostics(
+    workspaces: &[ProjectWorkspace],
+    index: &CratesIndex,
+    encoding: PositionEncoding,
+) -> Vec<(lsp_types::Url, Vec<lsp_types::Diagnostic>)> {
+    let _p = tracing::span!(tracing::Level::INFO, "fetch_yanked_dependency_diagnostics").entered();
+    let mut res = FxHashMap::default();
+    for ws in workspaces {
+        let ProjectWorkspace::Cargo { cargo, .. } = ws else { continue };
+        for pkg in cargo.packages().filter(|&it| cargo[it].is_member) {
+            let manifest = &cargo[pkg].manifest;
+            let url = lsp::to_proto::url_from_abs_path(manifest);
+            if res.contains_key(&url) {
+                continue;
+            }
+            let Ok(text) = std::fs::read_to_string(manifest) else { continue };
+            let (text, endings) = LineEndings::normalize(text);
+            let line_index =
+                LineIndex { index: Arc::new(ide::LineIndex::new(&text)), endings, encoding };
+            let declared = manifest_dependencies(&text);
+
+            let diagnostics = cargo[pkg]
+                .dependencies
+                .iter()
+                .unique_by(|dep| dep.pkg)
+                .filter_map(|dep| {
+                    let data = &cargo[dep.pkg];
+                    if !is_crates_io_package(&data.id)
+                        || !index
+                            .versions(&data.name)
+                            .iter()
+                            .any(|it| it.yanked && it.version == data.version)
+                    {
+                        return None;
+                    }
+                    let decl = declared
+                        .iter()
+                        .find(|it| !it.is_workspace && it.key.replace('-', "_") == dep.name)?;
+                    let range = TextRange::new(
+                        TextSize::try_from(decl.range.start).ok()?,
+                        TextSize::try_from(decl.range.end).ok()?,
+                    );
+                    Some(lsp_types::Diagnostic {
+                        range: lsp::to_proto::range(&line_index, range),
+                        severity: Some(lsp_types::DiagnosticSeverity::WARNING),
+                        code: Some(lsp_types::NumberOrString::String(
+                            "yanked-dependency".to_owned(),
+                        )),
+                        code_description: None,
+                        source: Some("rust-analyzer".to_owned()),
+                        message: format!(
+                            "`{}` {} has been yanked from crates.io, \
+                             consider running `cargo update -p {}`",
+                            data.name, data.version, data.name
+                        ),
+                        related_information: None,
+                        tags: None,
+                        data: None,
+                    })
+                })
+                .collect();
+            res.insert(url, diagnostics);
+        }
+    }
+    res.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnostic(message: &str) -> lsp_types::Diagnostic {
+        lsp_types::Diagnostic { message: message.to_owned(), ..Default::default() }
+    }
+
+    fn package(name: &str) -> Option<PackageId> {
+        Some(PackageId { repr: name.to_owned() })
+    }
+
+    fn messages(diagnostics: &DiagnosticCollection, file_id: FileId) -> Vec<&str> {
+        diagnostics.diagnostics_for(file_id).map(|it| it.message.as_str()).sorted().collect()
+    }
+
+    #[test]
+    fn clearing_a_package_keeps_other_packages() {
+        let (file_a, file_b) = (FileId::from_raw(0), FileId::from_raw(1));
+        let mut diagnostics = DiagnosticCollection::default();
+        diagnostics.add_check_diagnostic(0, &package("a"), file_a, diagnostic("a in a"), None);
+        diagnostics.add_check_diagnostic(0, &package("b"), file_a, diagnostic("b in a"), None);
+        diagnostics.add_check_diagnostic(0, &package("b"), file_b, diagnostic("b in b"), None);
+        diagnostics.add_check_diagnostic(0, &None, file_b, diagnostic("unknown in b"), None);
+        diagnostics.add_check_diagnostic(1, &package("a"), file_b, diagnostic("other check"), None);
+        diagnostics.take_changes();
+
+        diagnostics.clear_check_for_package(0, package("a"));
+        assert_eq!(messages(&diagnostics, file_a), ["b in a"]);
+        assert_eq!(messages(&diagnostics, file_b), ["b in b", "other check", "unknown in b"]);
+        assert_eq!(diagnostics.take_changes(), Some(IntSet::from_iter([file_a])));
+        assert!(!diagnostics.check_fixes[&0].contains_key(&package("a")));
+        assert!(diagnostics.check_fixes[&1].contains_key(&package("a")));
+
+        diagnostics.clear_check_for_package(0, None);
+        assert_eq!(messages(&diagnostics, file_b), ["b in b", "other check"]);
+        assert_eq!(diagnostics.take_changes(), Some(IntSet::from_iter([file_b])));
+
+        // Packages that didn't report anything have nothing to clear.
+        diagnostics.clear_check_for_package(0, package("c"));
+        assert_eq!(diagnostics.take_changes(), None);
+    }
+}
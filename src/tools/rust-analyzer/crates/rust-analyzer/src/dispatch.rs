@@ -28,6 +28,10 @@
 /// Some requests modify the state, and are run on the main thread to get
 /// `&mut` (`on_sync_mut`).
 ///
+/// Requests whose results are not awaited by the user typing, like code lenses,
+/// are handled on a separate pool (`on_background`), so that they can't starve
+/// the interactive requests.
+///
 /// Read-only requests are wrapped into `catch_unwind` -- they don't modify the
 /// state, so it's OK to recover from their failures.
 pub(crate) struct RequestDispatcher<'a> {
@@ -95,9 +99,9 @@ pub(crate) fn on_sync<R>(
         self
     }
 
-    /// Dispatches a non-latency-sensitive request onto the thread pool
-    /// without retrying it if it panics.
-    pub(crate) fn on_no_retry<R>(
+    /// Dispatches a latency-sensitive request onto the thread pool without
+    /// retrying it if it panics.
+    pub(crate) fn on_latency_sensitive_no_retry<R>(
         &mut self,
         f: fn(GlobalStateSnapshot, R::Params) -> anyhow::Result<R::Result>,
     ) -> &mut Self
@@ -111,7 +115,7 @@ pub(crate) fn on_no_retry<R>(
             None => return self,
         };
 
-        self.global_state.task_pool.handle.spawn(ThreadIntent::Worker, {
+        self.global_state.task_pool.handle.spawn(ThreadIntent::LatencySensitive, {
             let world = self.global_state.snapshot();
             move || {
                 let result = panic::catch_unwind(move || {
@@ -142,7 +146,21 @@ pub(crate) fn on<R>(
         R::Params: DeserializeOwned + panic::UnwindSafe + Send + fmt::Debug,
         R::Result: Serialize,
     {
-        self.on_with_thread_intent::<true, R>(ThreadIntent::Worker, f)
+        self.on_with_thread_intent::<R>(Pool::Main, ThreadIntent::Worker, f)
+    }
+
+    /// Dispatches a request whose result is not awaited by the user onto the
+    /// background thread pool.
+    pub(crate) fn on_background<R>(
+        &mut self,
+        f: fn(GlobalStateSnapshot, R::Params) -> anyhow::Result<R::Result>,
+    ) -> &mut Self
+    where
+        R: lsp_types::request::Request + 'static,
+        R::Params: DeserializeOwned + panic::UnwindSafe + Send + fmt::Debug,
+        R::Result: Serialize,
+    {
+        self.on_with_thread_intent::<R>(Pool::Background, ThreadIntent::Worker, f)
     }
 
     /// Dispatches a latency-sensitive request onto the thread pool.
@@ -155,7 +173,7 @@ pub(crate) fn on_latency_sensitive<R>(
         R::Params: DeserializeOwned + panic::UnwindSafe + Send + fmt::Debug,
         R::Result: Serialize,
     {
-        self.on_with_thread_intent::<true, R>(ThreadIntent::LatencySensitive, f)
+        self.on_with_thread_intent::<R>(Pool::Main, ThreadIntent::LatencySensitive, f)
     }
 
     /// Formatting requests should never block on waiting a for task thread to open up, editors will wait
@@ -170,7 +188,7 @@ pub(crate) fn on_fmt_thread<R>(
         R::Params: DeserializeOwned + panic::UnwindSafe + Send + fmt::Debug,
         R::Result: Serialize,
     {
-        self.on_with_thread_intent::<false, R>(ThreadIntent::LatencySensitive, f)
+        self.on_with_thread_intent::<R>(Pool::Fmt, ThreadIntent::LatencySensitive, f)
     }
 
     pub(crate) fn finish(&mut self) {
@@ -185,8 +203,9 @@ pub(crate) fn finish(&mut self) {
         }
     }
 
-    fn on_with_thread_intent<const MAIN_POOL: bool, R>(
+    fn on_with_thread_intent<R>(
         &mut self,
+        pool: Pool,
         intent: ThreadIntent,
         f: fn(GlobalStateSnapshot, R::Params) -> anyhow::Result<R::Result>,
     ) -> &mut Self
@@ -203,10 +222,10 @@ fn on_with_thread_intent<const MAIN_POOL: bool, R>(
         tracing::debug!(?params);
 
         let world = self.global_state.snapshot();
-        if MAIN_POOL {
-            &mut self.global_state.task_pool.handle
-        } else {
-            &mut self.global_state.fmt_pool.handle
+        match pool {
+            Pool::Main => &mut self.global_state.task_pool.handle,
+            Pool::Background => &mut self.global_state.background_pool.handle,
+            Pool::Fmt => &mut self.global_state.fmt_pool.handle,
         }
         .spawn(intent, move || {
             let result = panic::catch_unwind(move || {
@@ -252,6 +271,13 @@ fn parse<R>(&mut self) -> Option<(lsp_server::Request, R::Params, String)>
     }
 }
 
+#[derive(Clone, Copy)]
+enum Pool {
+    Main,
+    Background,
+    Fmt,
+}
+
 fn thread_result_to_response<R>(
     id: lsp_server::RequestId,
     result: thread::Result<anyhow::Result<R::Result>>,
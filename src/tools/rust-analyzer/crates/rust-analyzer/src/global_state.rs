@@ -3,7 +3,7 @@
 //!
 //! Each tick provides an immutable snapshot of the state as `WorldSnapshot`.
 
-use std::{collections::hash_map::Entry, time::Instant};
+use std::{collections::hash_map::Entry, sync::atomic::AtomicUsize, time::Instant};
 
 use crossbeam_channel::{unbounded, Receiver, Sender};
 use flycheck::FlycheckHandle;
@@ -18,14 +18,17 @@
     RwLockWriteGuard,
 };
 use proc_macro_api::ProcMacroServer;
-use project_model::{CargoWorkspace, ProjectWorkspace, Target, WorkspaceBuildScripts};
+use project_model::{
+    project_json, CargoWorkspace, Package, ProjectWorkspace, Target, WorkspaceBuildScripts,
+};
 use rustc_hash::{FxHashMap, FxHashSet};
 use triomphe::Arc;
-use vfs::{AnchoredPathBuf, ChangedFile, Vfs};
+use vfs::{AbsPathBuf, AnchoredPathBuf, ChangedFile, Vfs};
 
 use crate::{
     config::{Config, ConfigError},
     diagnostics::{CheckFixes, DiagnosticCollection},
+    highlight_cache::HighlightCache,
     line_index::{LineEndings, LineIndex},
     lsp::{from_proto, to_proto::url_from_abs_path},
     lsp_ext,
@@ -58,15 +61,31 @@ pub(crate) struct GlobalState {
     req_queue: ReqQueue,
 
     pub(crate) task_pool: Handle<TaskPool<Task>, Receiver<Task>>,
+    /// Runs work the user is not directly waiting on, like native diagnostics
+    /// and code lenses, so that it doesn't delay completions and the like.
+    pub(crate) background_pool: Handle<TaskPool<Task>, Receiver<Task>>,
     pub(crate) fmt_pool: Handle<TaskPool<Task>, Receiver<Task>>,
 
     pub(crate) config: Arc<Config>,
     pub(crate) config_errors: Option<ConfigError>,
     pub(crate) analysis_host: AnalysisHost,
     pub(crate) diagnostics: DiagnosticCollection,
+    /// Native diagnostics are recomputed once this deadline passes, which
+    /// debounces bursts of changes into a single computation.
+    pub(crate) native_diagnostics_deadline: Option<Instant>,
+    /// Bumped every time native diagnostics are recomputed, so that results of
+    /// superseded computations can be dropped.
+    pub(crate) native_diagnostics_generation: Arc<AtomicUsize>,
     pub(crate) mem_docs: MemDocs,
     pub(crate) source_root_config: SourceRootConfig,
+    /// When a document of each crate was last seen open, checked along with the
+    /// memory budget. The crates seen least recently are evicted first.
+    pub(crate) crate_touches: FxHashMap<CrateId, Instant>,
+    pub(crate) last_memory_budget_check: Instant,
+    /// Number of values evicted from each query to stay under the memory budget.
+    pub(crate) memory_budget_evictions: Arc<FxHashMap<&'static str, usize>>,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) highlight_cache: Arc<Mutex<HighlightCache>>,
 
     // status
     pub(crate) shutdown_requested: bool,
@@ -118,14 +137,24 @@ pub(crate) struct GlobalState {
     /// to invalidate any salsa caches.
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
     pub(crate) crate_graph_file_dependencies: FxHashSet<vfs::VfsPath>,
+    /// Non-cargo files, like `rust-project.json` and the build files it lists, that cause the
+    /// workspaces to be reloaded when they change.
+    pub(crate) workspace_build_files: FxHashSet<AbsPathBuf>,
+    /// Files build scripts declared as their inputs with `cargo:rerun-if-changed`.
+    pub(crate) build_script_inputs: FxHashSet<AbsPathBuf>,
+    /// Packages, per workspace, whose build scripts need to be re-run as their inputs changed.
+    pub(crate) stale_build_scripts: FxHashMap<usize, Vec<Package>>,
 
     // op queues
     pub(crate) fetch_workspaces_queue:
         OpQueue<bool, Option<(Vec<anyhow::Result<ProjectWorkspace>>, bool)>>,
+    /// The argument is whether to only re-run the build scripts in `stale_build_scripts`.
     pub(crate) fetch_build_data_queue:
-        OpQueue<(), (Arc<Vec<ProjectWorkspace>>, Vec<anyhow::Result<WorkspaceBuildScripts>>)>,
+        OpQueue<bool, (Arc<Vec<ProjectWorkspace>>, Vec<anyhow::Result<WorkspaceBuildScripts>>)>,
     pub(crate) fetch_proc_macros_queue: OpQueue<Vec<ProcMacroPaths>, bool>,
     pub(crate) prime_caches_queue: OpQueue,
+    /// Whether the persisted item trees have been loaded and saved for the current crate graph.
+    pub(crate) item_tree_cache_loaded: bool,
 
     /// A deferred task queue.
     ///
@@ -146,11 +175,15 @@ pub(crate) struct GlobalStateSnapshot {
     pub(crate) check_fixes: CheckFixes,
     mem_docs: MemDocs,
     pub(crate) semantic_tokens_cache: Arc<Mutex<FxHashMap<Url, SemanticTokens>>>,
+    pub(crate) highlight_cache: Arc<Mutex<HighlightCache>>,
+    /// The revision of the highlight cache this snapshot was taken at.
+    pub(crate) highlight_cache_revision: usize,
     vfs: Arc<RwLock<(vfs::Vfs, IntMap<FileId, LineEndings>)>>,
     pub(crate) workspaces: Arc<Vec<ProjectWorkspace>>,
     // used to signal semantic highlighting to fall back to syntax based highlighting until proc-macros have been loaded
     pub(crate) proc_macros_loaded: bool,
     pub(crate) flycheck: Arc<[FlycheckHandle]>,
+    pub(crate) memory_budget_evictions: Arc<FxHashMap<&'static str, usize>>,
 }
 
 impl std::panic::UnwindSafe for GlobalStateSnapshot {}
@@ -170,6 +203,11 @@ pub(crate) fn new(sender: Sender<lsp_server::Message>, config: Config) -> Global
             let handle = TaskPool::new_with_threads(sender, config.main_loop_num_threads());
             Handle { handle, receiver }
         };
+        let background_pool = {
+            let (sender, receiver) = unbounded();
+            let handle = TaskPool::new_with_threads(sender, config.main_loop_num_threads());
+            Handle { handle, receiver }
+        };
         let fmt_pool = {
             let (sender, receiver) = unbounded();
             let handle = TaskPool::new_with_threads(sender, 1);
@@ -185,22 +223,32 @@ pub(crate) fn new(sender: Sender<lsp_server::Message>, config: Config) -> Global
         if let Some(capacities) = config.lru_query_capacities() {
             analysis_host.update_lru_capacities(capacities);
         }
+        if std::env::var("RA_QUERY_STATS").is_ok() {
+            ide_db::query_stats::start();
+        }
         let (flycheck_sender, flycheck_receiver) = unbounded();
         let mut this = GlobalState {
             sender,
             req_queue: ReqQueue::default(),
             task_pool,
+            background_pool,
             fmt_pool,
             loader,
             config: Arc::new(config.clone()),
             analysis_host,
             diagnostics: Default::default(),
+            native_diagnostics_deadline: None,
+            native_diagnostics_generation: Arc::new(AtomicUsize::new(0)),
             mem_docs: MemDocs::default(),
             semantic_tokens_cache: Arc::new(Default::default()),
+            highlight_cache: Arc::new(Default::default()),
             shutdown_requested: false,
             send_hint_refresh_query: false,
             last_reported_status: None,
             source_root_config: SourceRootConfig::default(),
+            crate_touches: FxHashMap::default(),
+            last_memory_budget_check: Instant::now(),
+            memory_budget_evictions: Arc::new(FxHashMap::default()),
             config_errors: Default::default(),
 
             proc_macro_clients: Arc::from_iter([]),
@@ -220,11 +268,15 @@ pub(crate) fn new(sender: Sender<lsp_server::Message>, config: Config) -> Global
 
             workspaces: Arc::from(Vec::new()),
             crate_graph_file_dependencies: FxHashSet::default(),
+            workspace_build_files: FxHashSet::default(),
+            build_script_inputs: FxHashSet::default(),
+            stale_build_scripts: FxHashMap::default(),
             fetch_workspaces_queue: OpQueue::default(),
             fetch_build_data_queue: OpQueue::default(),
             fetch_proc_macros_queue: OpQueue::default(),
 
             prime_caches_queue: OpQueue::default(),
+            item_tree_cache_loaded: false,
 
             deferred_task_queue: task_queue,
         };
@@ -237,7 +289,7 @@ pub(crate) fn process_changes(&mut self) -> bool {
         let _p = tracing::span!(tracing::Level::INFO, "GlobalState::process_changes").entered();
 
         let mut file_changes = FxHashMap::<_, (bool, ChangedFile)>::default();
-        let (change, modified_rust_files, workspace_structure_change) = {
+        let (change, modified_rust_files, changed_build_script_inputs, workspace_structure_change) = {
             let mut change = Change::new();
             let mut guard = self.vfs.write();
             let changed_files = guard.0.take_changes();
@@ -296,17 +348,23 @@ pub(crate) fn process_changes(&mut self) -> bool {
             let mut has_structure_changes = false;
             let mut bytes = vec![];
             let mut modified_rust_files = vec![];
+            let mut changed_build_script_inputs = vec![];
             for file in changed_files {
                 let vfs_path = &vfs.file_path(file.file_id);
                 if let Some(path) = vfs_path.as_path() {
                     let path = path.to_path_buf();
-                    if reload::should_refresh_for_change(&path, file.kind()) {
+                    if reload::should_refresh_for_change(&path, file.kind())
+                        || self.workspace_build_files.contains(&path)
+                    {
                         workspace_structure_change = Some((
                             path.clone(),
                             false,
                             AsRef::<std::path::Path>::as_ref(&path).ends_with("build.rs"),
                         ));
                     }
+                    if self.build_script_inputs.contains(&path) {
+                        changed_build_script_inputs.push(path.clone());
+                    }
                     if file.is_created_or_deleted() {
                         has_structure_changes = true;
                         workspace_structure_change = Some((
@@ -338,6 +396,12 @@ pub(crate) fn process_changes(&mut self) -> bool {
                 // this allows delaying the re-acquisition of the write lock
                 bytes.push((file.file_id, text));
             }
+            // highlighting can only be reused when a single file changed
+            let changed_file = match &*bytes {
+                [(file_id, _)] if !has_structure_changes => Some(*file_id),
+                _ => None,
+            };
+            self.highlight_cache.lock().record_change(changed_file);
             let (vfs, line_endings_map) = &mut *RwLockUpgradableReadGuard::upgrade(guard);
             bytes.into_iter().for_each(|(file_id, text)| match text {
                 None => change.change_file(file_id, None),
@@ -350,10 +414,12 @@ pub(crate) fn process_changes(&mut self) -> bool {
                 let roots = self.source_root_config.partition(vfs);
                 change.set_roots(roots);
             }
-            (change, modified_rust_files, workspace_structure_change)
+            (change, modified_rust_files, changed_build_script_inputs, workspace_structure_change)
         };
 
         self.analysis_host.apply_change(change);
+        // the status request shows the query stats since the last edit
+        ide_db::query_stats::reset();
 
         {
             if !matches!(&workspace_structure_change, Some((.., true))) {
@@ -362,6 +428,13 @@ pub(crate) fn process_changes(&mut self) -> bool {
                     .sender
                     .send(crate::main_loop::QueuedTask::CheckProcMacroSources(modified_rust_files));
             }
+            if !changed_build_script_inputs.is_empty() {
+                _ = self.deferred_task_queue.sender.send(
+                    crate::main_loop::QueuedTask::CheckBuildScriptInputs(
+                        changed_build_script_inputs,
+                    ),
+                );
+            }
             // FIXME: ideally we should only trigger a workspace fetch for non-library changes
             // but something's going wrong with the source root business when we add a new local
             // crate see https://github.com/rust-lang/rust-analyzer/issues/13029
@@ -373,7 +446,8 @@ pub(crate) fn process_changes(&mut self) -> bool {
                     force_crate_graph_reload,
                 );
                 if build_scripts_touched {
-                    self.fetch_build_data_queue.request_op(format!("build.rs changed: {path}"), ());
+                    self.fetch_build_data_queue
+                        .request_op(format!("build.rs changed: {path}"), false);
                 }
             }
         }
@@ -390,9 +464,12 @@ pub(crate) fn snapshot(&self) -> GlobalStateSnapshot {
             check_fixes: Arc::clone(&self.diagnostics.check_fixes),
             mem_docs: self.mem_docs.clone(),
             semantic_tokens_cache: Arc::clone(&self.semantic_tokens_cache),
+            highlight_cache: Arc::clone(&self.highlight_cache),
+            highlight_cache_revision: self.highlight_cache.lock().revision(),
             proc_macros_loaded: !self.config.expand_proc_macros()
                 || *self.fetch_proc_macros_queue.last_op_result(),
             flycheck: self.flycheck.clone(),
+            memory_budget_evictions: Arc::clone(&self.memory_budget_evictions),
         }
     }
 
@@ -520,6 +597,19 @@ pub(crate) fn cargo_target_for_crate_root(
         })
     }
 
+    pub(crate) fn project_json_crate_for_crate_root(
+        &self,
+        crate_id: CrateId,
+    ) -> Option<&project_json::Crate> {
+        let file_id = self.analysis.crate_root(crate_id).ok()?;
+        let path = self.vfs_read().file_path(file_id);
+        let path = path.as_path()?;
+        self.workspaces.iter().find_map(|ws| match ws {
+            ProjectWorkspace::Json { project, .. } => project.crate_by_root(path),
+            ProjectWorkspace::Cargo { .. } | ProjectWorkspace::DetachedFiles { .. } => None,
+        })
+    }
+
     pub(crate) fn file_exists(&self, file_id: FileId) -> bool {
         self.vfs.read().0.exists(file_id)
     }
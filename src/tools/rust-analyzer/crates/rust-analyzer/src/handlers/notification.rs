@@ -71,6 +71,20 @@ pub(crate) fn handle_did_open_text_document(
             tracing::error!("duplicate DidOpenTextDocument: {}", path);
         }
 
+        if let Some(abs_path) = path.as_path() {
+            if state
+                .config
+                .unloaded_projects()
+                .iter()
+                .any(|it| abs_path.starts_with(it.manifest_path().parent()))
+            {
+                Arc::make_mut(&mut state.config).load_projects_containing(abs_path);
+                state
+                    .fetch_workspaces_queue
+                    .request_op(format!("opened a file of unloaded workspace: {abs_path}"), false);
+            }
+        }
+
         state.vfs.write().0.set_file_contents(path, Some(params.text_document.text.into_bytes()));
         if state.config.notifications().unindexed_project {
             tracing::debug!("queuing task");
@@ -149,13 +163,15 @@ pub(crate) fn handle_did_save_text_document(
         state.build_deps_changed = false;
         state
             .fetch_build_data_queue
-            .request_op("build_deps_changed - save notification".to_owned(), ());
+            .request_op("build_deps_changed - save notification".to_owned(), false);
     }
 
     if let Ok(vfs_path) = from_proto::vfs_path(&params.text_document.uri) {
         // Re-fetch workspaces if a workspace related file has changed
         if let Some(abs_path) = vfs_path.as_path() {
-            if reload::should_refresh_for_change(abs_path, ChangeKind::Modify) {
+            if reload::should_refresh_for_change(abs_path, ChangeKind::Modify)
+                || state.workspace_build_files.contains(abs_path)
+            {
                 state
                     .fetch_workspaces_queue
                     .request_op(format!("workspace vfs file change saved {abs_path}"), false);
@@ -288,17 +304,22 @@ fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath) -> bool {
                 .collect::<ide::Cancellable<_>>()?;
             let crate_root_paths: Vec<_> = crate_root_paths.iter().map(Deref::deref).collect();
 
-            // Find all workspaces that have at least one target containing the saved file
+            // Find all workspaces that have at least one crate containing or depending on the saved
+            // file, along with the packages of those crates
             let workspace_ids = world.workspaces.iter().enumerate().filter_map(|(idx, ws)| {
-                let package = match ws {
+                let packages = match ws {
                     project_model::ProjectWorkspace::Cargo { cargo, .. } => {
-                        cargo.packages().find_map(|pkg| {
-                            let has_target_with_root = cargo[pkg]
-                                .targets
-                                .iter()
-                                .any(|&it| crate_root_paths.contains(&cargo[it].root.as_path()));
-                            has_target_with_root.then(|| cargo[pkg].name.clone())
-                        })
+                        let packages: Vec<_> = cargo
+                            .packages()
+                            .filter(|&pkg| {
+                                cargo[pkg]
+                                    .targets
+                                    .iter()
+                                    .any(|&it| crate_root_paths.contains(&cargo[it].root.as_path()))
+                            })
+                            .map(|pkg| cargo[pkg].name.clone())
+                            .collect();
+                        (!packages.is_empty()).then_some(packages)
                     }
                     project_model::ProjectWorkspace::Json { project, .. } => {
                         if !project
@@ -311,18 +332,18 @@ fn run_flycheck(state: &mut GlobalState, vfs_path: VfsPath) -> bool {
                     }
                     project_model::ProjectWorkspace::DetachedFiles { .. } => return None,
                 };
-                Some((idx, package))
+                Some((idx, packages))
             });
 
             let saved_file = vfs_path.as_path().map(|p| p.to_owned());
 
             // Find and trigger corresponding flychecks
             for flycheck in world.flycheck.iter() {
-                for (id, package) in workspace_ids.clone() {
+                for (id, packages) in workspace_ids.clone() {
                     if id == flycheck.id() {
                         updated = true;
-                        match package.filter(|_| !world.config.flycheck_workspace()) {
-                            Some(package) => flycheck.restart_for_package(package),
+                        match packages.filter(|_| !world.config.flycheck_workspace()) {
+                            Some(packages) => flycheck.restart_for_packages(packages),
                             None => flycheck.restart_workspace(saved_file.clone()),
                         }
                         continue;
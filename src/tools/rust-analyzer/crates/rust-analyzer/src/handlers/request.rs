@@ -6,16 +6,18 @@
     io::Write as _,
     path::PathBuf,
     process::{self, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Context;
 
 use ide::{
-    AnnotationConfig, AssistKind, AssistResolveStrategy, Cancellable, FilePosition, FileRange,
-    HoverAction, HoverGotoTypeData, InlayFieldsToResolve, Query, RangeInfo, RangeLimit,
-    ReferenceCategory, Runnable, RunnableKind, SingleResolve, SourceChange, TextEdit,
+    AnnotationConfig, AssistKind, AssistResolveStrategy, Cancellable, CrateGraphConfig,
+    DependencyKind, FilePosition, FileRange, HoverAction, HoverGotoTypeData, InlayFieldsToResolve,
+    Query, RangeInfo, RangeLimit, ReferenceCategory, Runnable, RunnableKind, SelectionEvaluation,
+    SingleResolve, SourceChange, TextEdit,
 };
-use ide_db::SymbolKind;
+use ide_db::{cargo_manifest::manifest_dependencies, SymbolKind};
 use lsp_server::ErrorCode;
 use lsp_types::{
     CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
@@ -26,7 +28,7 @@
     SemanticTokensParams, SemanticTokensRangeParams, SemanticTokensRangeResult,
     SemanticTokensResult, SymbolInformation, SymbolTag, TextDocumentIdentifier, Url, WorkspaceEdit,
 };
-use project_model::{ManifestPath, ProjectWorkspace, TargetKind};
+use project_model::{latest_version, CratesIndex, ManifestPath, ProjectWorkspace, TargetKind};
 use serde_json::json;
 use stdx::{format_to, never};
 use syntax::{algo, ast, AstNode, TextRange, TextSize};
@@ -38,7 +40,7 @@
     config::{Config, RustfmtConfig, WorkspaceSymbolConfig},
     diff::diff,
     global_state::{GlobalState, GlobalStateSnapshot},
-    line_index::LineEndings,
+    line_index::{LineEndings, LineIndex},
     lsp::{
         from_proto, to_proto,
         utils::{all_edits_are_disjoint, invalid_params_error},
@@ -62,7 +64,7 @@ pub(crate) fn handle_proc_macros_rebuild(state: &mut GlobalState, _: ()) -> anyh
     state.proc_macro_clients = Arc::from_iter([]);
     state.build_deps_changed = false;
 
-    state.fetch_build_data_queue.request_op("rebuild proc macros request".to_owned(), ());
+    state.fetch_build_data_queue.request_op("rebuild proc macros request".to_owned(), false);
     Ok(())
 }
 
@@ -103,6 +105,23 @@ pub(crate) fn handle_analyzer_status(
                 .collect::<Vec<&AbsPath>>()
         );
     }
+    let unloaded = snap.config.unloaded_projects();
+    if !unloaded.is_empty() {
+        format_to!(
+            buf,
+            "\nUnloaded workspace root folders, loaded once one of their files is opened: {:?}",
+            unloaded.iter().map(|it| it.manifest_path().parent()).collect::<Vec<&AbsPath>>()
+        );
+    }
+    if let Some(budget) = snap.config.lru_memory_budget() {
+        format_to!(buf, "\nMemory budget: {budget}mb\n");
+        if snap.memory_budget_evictions.is_empty() {
+            buf.push_str("Nothing evicted\n");
+        }
+        for (query, count) in snap.memory_budget_evictions.iter() {
+            format_to!(buf, "{count:>8} {query} values evicted\n");
+        }
+    }
     buf.push_str("\nAnalysis:\n");
     buf.push_str(
         &snap
@@ -131,6 +150,11 @@ pub(crate) fn handle_shuffle_crate_graph(state: &mut GlobalState, _: ()) -> anyh
     Ok(())
 }
 
+pub(crate) fn handle_reset_query_stats(_: &mut GlobalState, _: ()) -> anyhow::Result<()> {
+    ide_db::query_stats::reset();
+    Ok(())
+}
+
 pub(crate) fn handle_syntax_tree(
     snap: GlobalStateSnapshot,
     params: lsp_ext::SyntaxTreeParams,
@@ -173,6 +197,28 @@ pub(crate) fn handle_interpret_function(
     Ok(res)
 }
 
+pub(crate) fn handle_evaluate_selection(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::EvaluateSelectionParams,
+) -> anyhow::Result<Option<lsp_ext::EvaluateSelectionResult>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_evaluate_selection").entered();
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let Some(evaluation) = snap.analysis.evaluate_selection(frange, &params.values)? else {
+        return Ok(None);
+    };
+    let mut res =
+        lsp_ext::EvaluateSelectionResult { missing_values: Vec::new(), value: None, error: None };
+    match evaluation {
+        SelectionEvaluation::MissingValues(values) => {
+            res.missing_values =
+                values.into_iter().map(|(name, ty)| lsp_ext::MissingValue { name, ty }).collect()
+        }
+        SelectionEvaluation::Value(value) => res.value = Some(value),
+        SelectionEvaluation::Error(error) => res.error = Some(error),
+    }
+    Ok(Some(res))
+}
+
 pub(crate) fn handle_view_file_text(
     snap: GlobalStateSnapshot,
     params: lsp_types::TextDocumentIdentifier,
@@ -196,8 +242,53 @@ pub(crate) fn handle_view_crate_graph(
     params: ViewCrateGraphParams,
 ) -> anyhow::Result<String> {
     let _p = tracing::span!(tracing::Level::INFO, "handle_view_crate_graph").entered();
-    let dot = snap.analysis.view_crate_graph(params.full)?.map_err(anyhow::Error::msg)?;
-    Ok(dot)
+    let config = CrateGraphConfig {
+        full: params.full,
+        transitive_from: params
+            .transitive_from
+            .map(|it| from_proto::file_id(&snap, &it.uri))
+            .transpose()?,
+        metrics: params.metrics,
+    };
+    match params.format {
+        lsp_ext::CrateGraphFormat::Dot => {
+            let dot = snap.analysis.view_crate_graph(&config)?.map_err(anyhow::Error::msg)?;
+            Ok(dot)
+        }
+        lsp_ext::CrateGraphFormat::Json => {
+            let graph = snap.analysis.export_crate_graph(&config)?;
+            let nodes = graph
+                .nodes
+                .into_iter()
+                .map(|node| lsp_ext::ExportedCrateGraphNode {
+                    id: node.crate_id.into_raw().into(),
+                    name: node.name,
+                    version: node.version,
+                    cfg: node.cfg,
+                    metrics: node.metrics.map(|it| lsp_ext::CrateMetrics {
+                        lines_of_code: it.lines_of_code as u64,
+                        items: it.items as u64,
+                        memory: it.memory as u64,
+                    }),
+                })
+                .collect();
+            let edges = graph
+                .edges
+                .into_iter()
+                .map(|edge| lsp_ext::ExportedCrateGraphEdge {
+                    from: edge.from.into_raw().into(),
+                    to: edge.to.into_raw().into(),
+                    name: edge.name,
+                    kind: match edge.kind {
+                        DependencyKind::Normal => lsp_ext::DependencyKind::Normal,
+                        DependencyKind::Dev => lsp_ext::DependencyKind::Dev,
+                        DependencyKind::Build => lsp_ext::DependencyKind::Build,
+                    },
+                })
+                .collect();
+            Ok(serde_json::to_string(&lsp_ext::ExportedCrateGraph { nodes, edges })?)
+        }
+    }
 }
 
 pub(crate) fn handle_expand_macro(
@@ -761,7 +852,9 @@ pub(crate) fn handle_runnables(
         let mut runnable = to_proto::runnable(&snap, runnable)?;
         if expect_test {
             runnable.label = format!("{} + expect", runnable.label);
-            runnable.args.expect_test = Some(true);
+            if let lsp_ext::RunnableArgs::Cargo(args) = &mut runnable.args {
+                args.expect_test = Some(true);
+            }
         }
         res.push(runnable);
     }
@@ -785,14 +878,14 @@ pub(crate) fn handle_runnables(
                     ),
                     location: None,
                     kind: lsp_ext::RunnableKind::Cargo,
-                    args: lsp_ext::CargoRunnable {
+                    args: lsp_ext::RunnableArgs::Cargo(lsp_ext::CargoRunnable {
                         workspace_root: Some(spec.workspace_root.clone().into()),
                         override_cargo: config.override_cargo.clone(),
                         cargo_args,
                         cargo_extra_args: config.cargo_extra_args.clone(),
                         executable_args: Vec::new(),
                         expect_test: None,
-                    },
+                    }),
                 })
             }
         }
@@ -802,14 +895,14 @@ pub(crate) fn handle_runnables(
                     label: "cargo check --workspace".to_owned(),
                     location: None,
                     kind: lsp_ext::RunnableKind::Cargo,
-                    args: lsp_ext::CargoRunnable {
+                    args: lsp_ext::RunnableArgs::Cargo(lsp_ext::CargoRunnable {
                         workspace_root: None,
                         override_cargo: config.override_cargo,
                         cargo_args: vec!["check".to_owned(), "--workspace".to_owned()],
                         cargo_extra_args: config.cargo_extra_args,
                         executable_args: Vec::new(),
                         expect_test: None,
-                    },
+                    }),
                 });
             }
         }
@@ -933,7 +1026,7 @@ pub(crate) fn handle_folding_range(
 ) -> anyhow::Result<Option<Vec<FoldingRange>>> {
     let _p = tracing::span!(tracing::Level::INFO, "handle_folding_range").entered();
     let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
-    let folds = snap.analysis.folding_ranges(file_id)?;
+    let folds = crate::highlight_cache::folding_ranges(&snap, file_id)?;
     let text = snap.analysis.file_text(file_id)?;
     let line_index = snap.file_line_index(file_id)?;
     let line_folding_only = snap.config.line_folding_only();
@@ -944,6 +1037,38 @@ pub(crate) fn handle_folding_range(
     Ok(Some(res))
 }
 
+pub(crate) fn handle_document_color(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::DocumentColorParams,
+) -> anyhow::Result<Vec<lsp_types::ColorInformation>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_document_color").entered();
+    let file_id = from_proto::file_id(&snap, &params.text_document.uri)?;
+    let line_index = snap.file_line_index(file_id)?;
+    let res = snap
+        .analysis
+        .document_colors(file_id)?
+        .into_iter()
+        .map(|it| to_proto::color_information(&line_index, it))
+        .collect();
+    Ok(res)
+}
+
+pub(crate) fn handle_color_presentation(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::ColorPresentationParams,
+) -> anyhow::Result<Vec<lsp_types::ColorPresentation>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_color_presentation").entered();
+    let frange = from_proto::file_range(&snap, &params.text_document, params.range)?;
+    let line_index = snap.file_line_index(frange.file_id)?;
+    let res = snap
+        .analysis
+        .color_presentations(frange, from_proto::color(params.color))?
+        .into_iter()
+        .map(|it| to_proto::color_presentation(&line_index, it))
+        .collect();
+    Ok(res)
+}
+
 pub(crate) fn handle_signature_help(
     snap: GlobalStateSnapshot,
     params: lsp_types::SignatureHelpParams,
@@ -969,6 +1094,12 @@ pub(crate) fn handle_hover(
         PositionOrRange::Range(range) => range,
     };
 
+    if let Ok(path) = from_proto::abs_path(&params.text_document.uri) {
+        if path.file_name() == Some("Cargo.toml".as_ref()) {
+            return handle_cargo_toml_hover(&snap, &path, range.start);
+        }
+    }
+
     let file_range = from_proto::file_range(&snap, &params.text_document, range)?;
     let info = match snap.analysis.hover(&snap.config.hover(), file_range)? {
         None => return Ok(None),
@@ -996,6 +1127,94 @@ pub(crate) fn handle_hover(
     Ok(Some(hover))
 }
 
+/// Shows the versions and features of the dependency under the cursor in a `Cargo.toml` file, as
+/// recorded in cargo's local copy of the crates.io index.
+fn handle_cargo_toml_hover(
+    snap: &GlobalStateSnapshot,
+    manifest: &AbsPath,
+    position: Position,
+) -> anyhow::Result<Option<lsp_ext::Hover>> {
+    if !snap.config.crates_index_enable() {
+        return Ok(None);
+    }
+    let (text, endings) = LineEndings::normalize(fs::read_to_string(manifest)?);
+    let line_index = LineIndex {
+        index: Arc::new(ide::LineIndex::new(&text)),
+        endings,
+        encoding: snap.config.position_encoding(),
+    };
+    let offset = usize::from(from_proto::offset(&line_index, position)?);
+    let Some(dep) = manifest_dependencies(&text)
+        .into_iter()
+        .find(|it| it.range.start <= offset && offset <= it.range.end)
+    else {
+        return Ok(None);
+    };
+
+    let versions = CratesIndex::discover(snap.config.extra_env()).versions(&dep.package);
+    let resolved = snap.workspaces.iter().find_map(|ws| match ws {
+        ProjectWorkspace::Cargo { cargo, .. } => {
+            let pkg = cargo.packages().find(|&it| &*cargo[it].manifest == manifest)?;
+            let name = dep.key.replace('-', "_");
+            let dep = cargo[pkg].dependencies.iter().find(|it| it.name == name)?;
+            Some(&cargo[dep.pkg])
+        }
+        _ => None,
+    });
+
+    let mut markup = format!("**{}**\n\n", dep.package);
+    match latest_version(&versions) {
+        Some(latest) => format_to!(markup, "Latest version: `{}`", latest.version),
+        None => markup.push_str("Not found in the local crates.io index"),
+    }
+    if let Some(req) = &dep.version_req {
+        format_to!(markup, "\n\nRequested: `{req}`");
+    }
+    if let Some(resolved) = resolved {
+        format_to!(markup, "\n\nResolved: `{}`", resolved.version);
+        if versions.iter().any(|it| it.yanked && it.version == resolved.version) {
+            markup.push_str(" (yanked)");
+        }
+    }
+    let enabled = match resolved {
+        Some(resolved) => resolved.active_features.clone(),
+        None => {
+            let default = dep.default_features.then(|| "default".to_owned());
+            dep.features.iter().cloned().chain(default).collect()
+        }
+    };
+    let features = versions
+        .iter()
+        .find(|it| Some(&it.version) == resolved.map(|it| &it.version))
+        .or_else(|| latest_version(&versions))
+        .map(|it| &*it.features)
+        .unwrap_or_default();
+    if !features.is_empty() {
+        markup.push_str("\n\nFeatures (enabled ones in bold): ");
+        let features: Vec<_> = features
+            .iter()
+            .map(|it| match enabled.contains(it) {
+                true => format!("**`{it}`**"),
+                false => format!("`{it}`"),
+            })
+            .collect();
+        markup.push_str(&features.join(", "));
+    }
+
+    let range =
+        TextRange::new(TextSize::try_from(dep.range.start)?, TextSize::try_from(dep.range.end)?);
+    Ok(Some(lsp_ext::Hover {
+        hover: lsp_types::Hover {
+            contents: HoverContents::Markup(to_proto::markup_content(
+                markup.into(),
+                snap.config.hover().format,
+            )),
+            range: Some(to_proto::range(&line_index, range)),
+        },
+        actions: Vec::new(),
+    }))
+}
+
 pub(crate) fn handle_prepare_rename(
     snap: GlobalStateSnapshot,
     params: lsp_types::TextDocumentPositionParams,
@@ -1165,7 +1384,13 @@ pub(crate) fn handle_code_action(
     }
 
     // Fixes from `cargo check`.
-    for fix in snap.check_fixes.values().filter_map(|it| it.get(&frange.file_id)).flatten() {
+    for fix in snap
+        .check_fixes
+        .values()
+        .flat_map(|it| it.values())
+        .filter_map(|it| it.get(&frange.file_id))
+        .flatten()
+    {
         // FIXME: this mapping is awkward and shouldn't exist. Refactor
         // `snap.check_fixes` to not convert to LSP prematurely.
         let intersect_fix_range = fix
@@ -1302,6 +1527,8 @@ pub(crate) fn handle_code_lens(
             annotate_references: lens_config.refs_adt,
             annotate_method_references: lens_config.method_refs,
             annotate_enum_variant_references: lens_config.enum_variant_refs,
+            annotate_enum_variant_usages: lens_config.enum_variant_usages,
+            annotate_stack_usage: lens_config.stack_usage,
             location: lens_config.location.into(),
         },
         file_id,
@@ -1384,6 +1611,43 @@ pub(crate) fn handle_ssr(
     to_proto::workspace_edit(&snap, source_change).map_err(Into::into)
 }
 
+pub(crate) fn handle_structural_search(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::StructuralSearchParams,
+) -> anyhow::Result<Vec<lsp_ext::StructuralSearchMatch>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_structural_search").entered();
+    let selections = params
+        .selections
+        .iter()
+        .map(|range| from_proto::file_range(&snap, &params.position.text_document, *range))
+        .collect::<Result<Vec<_>, _>>()?;
+    let position = from_proto::file_position(&snap, params.position)?;
+    let matches = snap.analysis.structural_search(&params.query, position, selections)??;
+    let res = matches
+        .into_iter()
+        .map(|m| {
+            let placeholders = m
+                .placeholders
+                .into_iter()
+                .map(|p| {
+                    Ok(lsp_ext::PlaceholderBinding {
+                        name: p.name,
+                        location: to_proto::location(&snap, p.range)?,
+                        matched_text: p.matched_text,
+                        ty: p.ty,
+                    })
+                })
+                .collect::<Cancellable<Vec<_>>>()?;
+            Ok(lsp_ext::StructuralSearchMatch {
+                location: to_proto::location(&snap, m.range)?,
+                matched_text: m.matched_text,
+                placeholders,
+            })
+        })
+        .collect::<Cancellable<Vec<_>>>()?;
+    Ok(res)
+}
+
 pub(crate) fn handle_inlay_hints(
     snap: GlobalStateSnapshot,
     params: InlayHintParams,
@@ -1568,13 +1832,12 @@ pub(crate) fn handle_semantic_tokens_full(
     highlight_config.syntactic_name_ref_highlighting =
         snap.workspaces.is_empty() || !snap.proc_macros_loaded;
 
-    let highlights = snap.analysis.highlight(highlight_config, file_id)?;
+    let highlights = crate::highlight_cache::highlight(&snap, file_id, highlight_config)?;
     let semantic_tokens = to_proto::semantic_tokens(
         &text,
         &line_index,
         highlights,
-        snap.config.semantics_tokens_augments_syntax_tokens(),
-        snap.config.highlighting_non_standard_tokens(),
+        &snap.config.semantic_tokens_config(),
     );
 
     // Unconditionally cache the tokens
@@ -1598,13 +1861,12 @@ pub(crate) fn handle_semantic_tokens_full_delta(
     highlight_config.syntactic_name_ref_highlighting =
         snap.workspaces.is_empty() || !snap.proc_macros_loaded;
 
-    let highlights = snap.analysis.highlight(highlight_config, file_id)?;
+    let highlights = crate::highlight_cache::highlight(&snap, file_id, highlight_config)?;
     let semantic_tokens = to_proto::semantic_tokens(
         &text,
         &line_index,
         highlights,
-        snap.config.semantics_tokens_augments_syntax_tokens(),
-        snap.config.highlighting_non_standard_tokens(),
+        &snap.config.semantic_tokens_config(),
     );
 
     let cached_tokens = snap.semantic_tokens_cache.lock().remove(&params.text_document.uri);
@@ -1641,13 +1903,12 @@ pub(crate) fn handle_semantic_tokens_range(
     highlight_config.syntactic_name_ref_highlighting =
         snap.workspaces.is_empty() || !snap.proc_macros_loaded;
 
-    let highlights = snap.analysis.highlight_range(highlight_config, frange)?;
+    let highlights = crate::highlight_cache::highlight_range(&snap, frange, highlight_config)?;
     let semantic_tokens = to_proto::semantic_tokens(
         &text,
         &line_index,
         highlights,
-        snap.config.semantics_tokens_augments_syntax_tokens(),
-        snap.config.highlighting_non_standard_tokens(),
+        &snap.config.semantic_tokens_config(),
     );
     Ok(Some(semantic_tokens.into()))
 }
@@ -1759,6 +2020,130 @@ pub(crate) fn handle_view_recursive_memory_layout(
     }))
 }
 
+pub(crate) fn handle_explain_type(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<Option<lsp_ext::ExplainedType>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_explain_type").entered();
+    let position = from_proto::file_position(&snap, params)?;
+
+    let Some(explained) = snap.analysis.explain_type(position)? else { return Ok(None) };
+    let nodes = explained
+        .nodes
+        .into_iter()
+        .map(|node| {
+            Ok(lsp_ext::ExplainedTypeNode {
+                label: node.label,
+                kind: match node.kind {
+                    ide::ExplainedTypeNodeKind::Type => lsp_ext::ExplainedTypeNodeKind::Type,
+                    ide::ExplainedTypeNodeKind::Alias => lsp_ext::ExplainedTypeNodeKind::Alias,
+                    ide::ExplainedTypeNodeKind::Normalized => {
+                        lsp_ext::ExplainedTypeNodeKind::Normalized
+                    }
+                    ide::ExplainedTypeNodeKind::HiddenType => {
+                        lsp_ext::ExplainedTypeNodeKind::HiddenType
+                    }
+                },
+                location: node
+                    .nav
+                    .map(|nav| to_proto::location_from_nav(&snap, nav))
+                    .transpose()?,
+                parent: node.parent.map(|it| it as u32),
+                children: node.children.into_iter().map(|it| it as u32).collect(),
+            })
+        })
+        .collect::<Cancellable<Vec<_>>>()?;
+    Ok(Some(lsp_ext::ExplainedType { nodes }))
+}
+
+pub(crate) fn handle_explain_unmet_bound(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::ExplainUnmetBoundParams,
+) -> anyhow::Result<Option<lsp_ext::UnmetBound>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_explain_unmet_bound").entered();
+    let position = from_proto::file_position(&snap, params.text_document_position)?;
+
+    let unmet = snap.analysis.explain_unmet_bound(position, &params.trait_)?;
+    Ok(unmet.map(|it| lsp_ext::UnmetBound { reasons: it.reasons }))
+}
+
+pub(crate) fn handle_generating_macro(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<Option<lsp_types::GotoDefinitionResponse>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_generating_macro").entered();
+    let position = from_proto::file_position(&snap, params)?;
+    let nav_info = match snap.analysis.goto_generating_macro(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let src = FileRange { file_id: position.file_id, range: nav_info.range };
+    let res = to_proto::goto_definition_response(&snap, Some(src), nav_info.info)?;
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_generated_items(
+    snap: GlobalStateSnapshot,
+    params: lsp_types::TextDocumentPositionParams,
+) -> anyhow::Result<Option<lsp_types::GotoDefinitionResponse>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_generated_items").entered();
+    let position = from_proto::file_position(&snap, params)?;
+    let nav_info = match snap.analysis.generated_items(position)? {
+        None => return Ok(None),
+        Some(it) => it,
+    };
+    let src = FileRange { file_id: position.file_id, range: nav_info.range };
+    let res = to_proto::goto_definition_response(&snap, Some(src), nav_info.info)?;
+    Ok(Some(res))
+}
+
+pub(crate) fn handle_todo_list(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::TodoListParams,
+) -> anyhow::Result<Vec<lsp_ext::TodoItem>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_todo_list").entered();
+    let file_id = match params.text_document {
+        Some(text_document) => Some(from_proto::file_id(&snap, &text_document.uri)?),
+        None => None,
+    };
+    let today = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |it| (it.as_secs() / (24 * 60 * 60)) as i64);
+    let items = snap.analysis.todo_index(file_id)?;
+    items
+        .into_iter()
+        .map(|it| {
+            let location = to_proto::location(
+                &snap,
+                FileRange { file_id: it.file_id, range: it.comment.range },
+            )?;
+            let date = it.comment.date;
+            Ok(lsp_ext::TodoItem {
+                location,
+                kind: it.comment.kind.as_str().to_owned(),
+                owner: it.comment.owner,
+                date: date.map(|it| it.to_string()),
+                age_days: date.map(|it| today - it.days_since_epoch()),
+                text: it.comment.text,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn handle_search_impls(
+    snap: GlobalStateSnapshot,
+    params: lsp_ext::SearchImplsParams,
+) -> anyhow::Result<Vec<lsp_types::Location>> {
+    let _p = tracing::span!(tracing::Level::INFO, "handle_search_impls").entered();
+    let position = from_proto::file_position(&snap, params.position)?;
+    let navs = snap.analysis.search_impls(position, &params.pattern)??;
+    let locations = navs
+        .into_iter()
+        .map(|nav| to_proto::location_from_nav(&snap, nav))
+        .collect::<Cancellable<Vec<_>>>()?;
+    Ok(locations)
+}
+
 fn to_command_link(command: lsp_types::Command, tooltip: String) -> lsp_ext::CommandLink {
     lsp_ext::CommandLink { tooltip: Some(tooltip), command }
 }
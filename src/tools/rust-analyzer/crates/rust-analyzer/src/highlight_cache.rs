@@ -0,0 +1,451 @@
+//! Keeps the highlighting and folding ranges of files around, so that after an edit confined to a
+//! function body only that function has to be highlighted and folded again.
+//!
+//! Highlighting is only reused if the file it belongs to is the only one that changed since it was
+//! computed, as changes elsewhere may change the meaning of any code. Folding ranges are purely
+//! syntactic, but follow the same rule to keep things simple.
+
+use std::{collections::VecDeque, ops::Range};
+
+use ide::{
+    Analysis, Cancellable, FileId, FileRange, Fold, HighlightConfig, HlRange, TextRange, TextSize,
+};
+use ide_db::FxHashMap;
+use syntax::{AstNode, GreenNode, SourceFile, SyntaxNode};
+use triomphe::Arc;
+
+use crate::global_state::GlobalStateSnapshot;
+
+/// How many changes are remembered to check whether highlighting computed for an older revision
+/// can still be cached.
+const CHANGE_LOG_LEN: usize = 16;
+
+#[derive(Default)]
+pub(crate) struct HighlightCache {
+    revision: usize,
+    /// The file changed by each of the last revisions, `None` if it wasn't a single file.
+    changes: VecDeque<Option<FileId>>,
+    entries: FxHashMap<FileId, Entry>,
+    folds: FxHashMap<FileId, FoldsEntry>,
+}
+
+struct Entry {
+    config: HighlightConfig,
+    source: Source,
+    highlights: Vec<HlRange>,
+}
+
+struct FoldsEntry {
+    source: Source,
+    folds: Vec<Fold>,
+}
+
+/// The text and syntax tree of a file the cached results were computed for.
+struct Source {
+    text: Arc<str>,
+    tree: GreenNode,
+}
+
+impl Source {
+    fn new(analysis: &Analysis, file_id: FileId) -> Cancellable<Source> {
+        let tree = analysis.parse(file_id)?.syntax().green().into_owned();
+        Ok(Source { text: analysis.file_text(file_id)?, tree })
+    }
+
+    fn file(&self) -> SourceFile {
+        SourceFile::cast(SyntaxNode::new_root(self.tree.clone())).unwrap()
+    }
+}
+
+impl HighlightCache {
+    pub(crate) fn revision(&self) -> usize {
+        self.revision
+    }
+
+    /// Records that `file`, or several files or the workspace if `None`, changed.
+    pub(crate) fn record_change(&mut self, file: Option<FileId>) {
+        self.revision += 1;
+        if self.changes.len() == CHANGE_LOG_LEN {
+            self.changes.pop_front();
+        }
+        self.changes.push_back(file);
+        self.entries.retain(|&it, _| Some(it) == file);
+        self.folds.retain(|&it, _| Some(it) == file);
+    }
+
+    /// Whether results for `file` computed at `revision` can be cached, which they can unless
+    /// other files have changed since then.
+    fn is_cacheable(&self, file: FileId, revision: usize) -> bool {
+        let Some(changes) = self.revision.checked_sub(revision) else { return false };
+        changes <= self.changes.len()
+            && self.changes.iter().rev().take(changes).all(|&it| it == Some(file))
+    }
+}
+
+/// Highlights `file_id`, highlighting only the edited function again if possible.
+pub(crate) fn highlight(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+    config: HighlightConfig,
+) -> anyhow::Result<Vec<HlRange>> {
+    let source = Source::new(&snap.analysis, file_id)?;
+    let entry = snap.highlight_cache.lock().entries.remove(&file_id);
+    let highlights = match entry.filter(|it| it.config == config) {
+        Some(entry) => match rehighlight(&snap.analysis, file_id, config, &source, entry)? {
+            Some(it) => it,
+            None => snap.analysis.highlight(config, file_id)?,
+        },
+        None => snap.analysis.highlight(config, file_id)?,
+    };
+    let mut cache = snap.highlight_cache.lock();
+    if cache.is_cacheable(file_id, snap.highlight_cache_revision) {
+        cache.entries.insert(file_id, Entry { config, source, highlights: highlights.clone() });
+    }
+    Ok(highlights)
+}
+
+/// Highlights `frange`, taking the highlights from the cache if the file didn't change since it
+/// was highlighted.
+pub(crate) fn highlight_range(
+    snap: &GlobalStateSnapshot,
+    frange: FileRange,
+    config: HighlightConfig,
+) -> anyhow::Result<Vec<HlRange>> {
+    let text = snap.analysis.file_text(frange.file_id)?;
+    {
+        let cache = snap.highlight_cache.lock();
+        let entry = cache
+            .entries
+            .get(&frange.file_id)
+            .filter(|it| it.config == config && it.source.text == text);
+        if let Some(entry) = entry {
+            return Ok(entry
+                .highlights
+                .iter()
+                .filter(|it| it.range.intersect(frange.range).is_some())
+                .cloned()
+                .collect());
+        }
+    }
+    Ok(snap.analysis.highlight_range(config, frange)?)
+}
+
+/// Computes the folding ranges of `file_id`, folding only the edited function again if possible.
+pub(crate) fn folding_ranges(
+    snap: &GlobalStateSnapshot,
+    file_id: FileId,
+) -> anyhow::Result<Vec<Fold>> {
+    let source = Source::new(&snap.analysis, file_id)?;
+    let entry = snap.highlight_cache.lock().folds.remove(&file_id);
+    let folds = match entry {
+        Some(entry) => match refold(&snap.analysis, file_id, &source, entry)? {
+            Some(it) => it,
+            None => snap.analysis.folding_ranges(file_id)?,
+        },
+        None => snap.analysis.folding_ranges(file_id)?,
+    };
+    let mut cache = snap.highlight_cache.lock();
+    if cache.is_cacheable(file_id, snap.highlight_cache_revision) {
+        cache.folds.insert(file_id, FoldsEntry { source, folds: folds.clone() });
+    }
+    Ok(folds)
+}
+
+/// Updates the highlighting of the previous text of the file if the text was only changed inside
+/// a single function body.
+fn rehighlight(
+    analysis: &Analysis,
+    file_id: FileId,
+    config: HighlightConfig,
+    source: &Source,
+    entry: Entry,
+) -> anyhow::Result<Option<Vec<HlRange>>> {
+    if entry.source.text == source.text {
+        return Ok(Some(entry.highlights));
+    }
+    let Some((old_fn_range, fn_range)) = edited_fn(&entry.source, source) else {
+        return Ok(None);
+    };
+
+    let fn_highlights = analysis.highlight_range(config, FileRange { file_id, range: fn_range })?;
+    let clip = |it: &HlRange, range: TextRange| {
+        it.range.intersect(range).filter(|it| !it.is_empty()).map(|range| HlRange { range, ..*it })
+    };
+    let before = TextRange::up_to(old_fn_range.start());
+    let after = TextRange::new(old_fn_range.end(), TextSize::of(&*entry.source.text));
+    let mut highlights = Vec::with_capacity(entry.highlights.len());
+    highlights.extend(entry.highlights.iter().filter_map(|it| clip(it, before)));
+    highlights.extend(fn_highlights.iter().filter_map(|it| clip(it, fn_range)));
+    highlights.extend(entry.highlights.iter().filter_map(|it| {
+        let it = clip(it, after)?;
+        let range = it.range - old_fn_range.end() + fn_range.end();
+        Some(HlRange { range, ..it })
+    }));
+    Ok(Some(highlights))
+}
+
+/// Updates the folding ranges of the previous text of the file if the text was only changed
+/// inside a single function body.
+fn refold(
+    analysis: &Analysis,
+    file_id: FileId,
+    source: &Source,
+    entry: FoldsEntry,
+) -> anyhow::Result<Option<Vec<Fold>>> {
+    if entry.source.text == source.text {
+        return Ok(Some(entry.folds));
+    }
+    let Some((old_fn_range, fn_range)) = edited_fn(&entry.source, source) else {
+        return Ok(None);
+    };
+    // Folds around the function stay folds if it spans several lines before and after the edit.
+    let is_multiline = |text: &str, range: TextRange| text[range].contains('\n');
+    if !is_multiline(&entry.source.text, old_fn_range) || !is_multiline(&source.text, fn_range) {
+        return Ok(None);
+    }
+    let Some(fn_folds) = analysis.fn_folding_ranges(file_id, fn_range)? else {
+        return Ok(None);
+    };
+
+    let mut folds = Vec::with_capacity(entry.folds.len());
+    for fold in entry.folds {
+        let range = if fold.range.end() <= old_fn_range.start() {
+            fold.range
+        } else if fold.range.start() >= old_fn_range.end() {
+            fold.range - old_fn_range.end() + fn_range.end()
+        } else if old_fn_range.contains_range(fold.range) {
+            continue;
+        } else if fold.range.contains_range(old_fn_range) {
+            TextRange::new(
+                fold.range.start(),
+                fold.range.end() - old_fn_range.end() + fn_range.end(),
+            )
+        } else {
+            // A region that starts or ends inside the function.
+            return Ok(None);
+        };
+        folds.push(Fold { range, ..fold });
+    }
+    folds.extend(fn_folds);
+    Ok(Some(folds))
+}
+
+/// The range of the function the edit between `old` and `new` is confined to, in the old and the
+/// new text, if it only changed the function's body.
+fn edited_fn(old: &Source, new: &Source) -> Option<(TextRange, TextRange)> {
+    let (old_text, text) = (&*old.text, &*new.text);
+    let prefix = common_prefix_len(old_text, text);
+    let suffix = common_suffix_len(&old_text[prefix..], &text[prefix..]);
+    let old_edit = text_range(prefix..old_text.len() - suffix);
+    let edit = text_range(prefix..text.len() - suffix);
+
+    let fn_range = ide::edited_fn_range(&new.file(), edit)?;
+    let old_fn_range = ide::edited_fn_range(&old.file(), old_edit)?;
+    // Check that the function was edited, rather than turned into a different one.
+    let is_same_fn = old_fn_range.start() == fn_range.start()
+        && old_text.len() - usize::from(old_fn_range.end())
+            == text.len() - usize::from(fn_range.end());
+    is_same_fn.then_some((old_fn_range, fn_range))
+}
+
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    let len = a.bytes().zip(b.bytes()).take_while(|(a, b)| a == b).count();
+    (0..=len).rev().find(|&it| a.is_char_boundary(it) && b.is_char_boundary(it)).unwrap_or(0)
+}
+
+fn common_suffix_len(a: &str, b: &str) -> usize {
+    let len = a.bytes().rev().zip(b.bytes().rev()).take_while(|(a, b)| a == b).count();
+    (0..=len)
+        .rev()
+        .find(|&it| a.is_char_boundary(a.len() - it) && b.is_char_boundary(b.len() - it))
+        .unwrap_or(0)
+}
+
+fn text_range(range: Range<usize>) -> TextRange {
+    TextRange::new(TextSize::new(range.start as u32), TextSize::new(range.end as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use ide::HighlightConfig;
+
+    use super::*;
+
+    const CONFIG: HighlightConfig = HighlightConfig {
+        strings: true,
+        punctuation: true,
+        specialize_punctuation: true,
+        specialize_operator: true,
+        operator: true,
+        inject_doc_comment: true,
+        macro_bang: true,
+        syntactic_name_ref_highlighting: false,
+    };
+
+    fn check(before: &str, after: &str, reused: bool) {
+        check_with_folds(before, after, reused, reused);
+    }
+
+    fn check_with_folds(before: &str, after: &str, reused: bool, folds_reused: bool) {
+        let (analysis, file_id) = Analysis::from_single_file(before.to_owned());
+        let entry = Entry {
+            config: CONFIG,
+            source: Source::new(&analysis, file_id).unwrap(),
+            highlights: analysis.highlight(CONFIG, file_id).unwrap(),
+        };
+        let folds_entry = FoldsEntry {
+            source: Source::new(&analysis, file_id).unwrap(),
+            folds: analysis.folding_ranges(file_id).unwrap(),
+        };
+        let (analysis, file_id) = Analysis::from_single_file(after.to_owned());
+        let after = Source::new(&analysis, file_id).unwrap();
+
+        let highlights = rehighlight(&analysis, file_id, CONFIG, &after, entry).unwrap();
+        match highlights {
+            Some(highlights) => {
+                assert!(reused, "highlighting was unexpectedly reused");
+                let expected = analysis.highlight(CONFIG, file_id).unwrap();
+                assert_eq!(format!("{highlights:?}"), format!("{expected:?}"));
+            }
+            None => assert!(!reused, "highlighting was unexpectedly not reused"),
+        }
+
+        let sorted = |mut folds: Vec<Fold>| {
+            folds.sort_by_key(|it| (it.range.start(), it.range.end()));
+            format!("{folds:?}")
+        };
+        match refold(&analysis, file_id, &after, folds_entry).unwrap() {
+            Some(folds) => {
+                assert!(folds_reused, "folding ranges were unexpectedly reused");
+                let expected = analysis.folding_ranges(file_id).unwrap();
+                assert_eq!(sorted(folds), sorted(expected));
+            }
+            None => assert!(!folds_reused, "folding ranges were unexpectedly not reused"),
+        }
+    }
+
+    #[test]
+    fn body_edit_reuses_highlighting() {
+        check(
+            r#"
+struct S;
+fn foo() {
+    let x = 92;
+}
+fn bar() -> S { S }
+"#,
+            r#"
+struct S;
+fn foo() {
+    let x = 92;
+    let s = S;
+}
+fn bar() -> S { S }
+"#,
+            true,
+        );
+    }
+
+    #[test]
+    fn signature_edit_highlights_everything() {
+        check(
+            r#"
+struct S;
+fn foo() {}
+fn bar() -> S { S }
+"#,
+            r#"
+struct S;
+fn foo(s: S) {}
+fn bar() -> S { S }
+"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn impl_added_to_body_highlights_everything() {
+        check(
+            r#"
+struct S;
+fn foo() {}
+fn bar() { S.baz() }
+"#,
+            r#"
+struct S;
+fn foo() {
+    impl S { fn baz(&self) {} }
+}
+fn bar() { S.baz() }
+"#,
+            false,
+        );
+    }
+
+    #[test]
+    fn body_edit_reuses_folds_around_the_function() {
+        check(
+            r#"
+mod m {
+    use std::fmt;
+    use std::io;
+
+    fn foo() {
+        let x = 92;
+    }
+
+    // one
+    // two
+    fn bar(
+        a: u32,
+    ) {}
+}
+"#,
+            r#"
+mod m {
+    use std::fmt;
+    use std::io;
+
+    fn foo() {
+        let x = 92;
+        match x {
+            0 => {}
+            _ => {
+            }
+        }
+    }
+
+    // one
+    // two
+    fn bar(
+        a: u32,
+    ) {}
+}
+"#,
+            true,
+        );
+    }
+
+    #[test]
+    fn region_markers_in_body_fold_everything() {
+        check_with_folds(
+            r#"
+// region: outer
+fn foo() {
+    let x = 92;
+}
+// endregion
+"#,
+            r#"
+// region: outer
+fn foo() {
+    // endregion
+    let x = 92;
+}
+// endregion
+"#,
+            true,
+            false,
+        );
+    }
+}
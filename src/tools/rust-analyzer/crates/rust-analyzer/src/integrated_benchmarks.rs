@@ -13,7 +13,7 @@
 use hir::Change;
 use ide::{CallableSnippets, CompletionConfig, FilePosition, TextSize};
 use ide_db::{
-    imports::insert_use::{ImportGranularity, InsertUseConfig},
+    imports::insert_use::{ImportGranularity, ImportGroupStyle, InsertUseConfig},
     SnippetCap,
 };
 use project_model::CargoConfig;
@@ -141,7 +141,9 @@ fn integrated_completion_benchmark() {
                 prefix_kind: hir::PrefixKind::ByCrate,
                 enforce_granularity: true,
                 group: true,
+                group_style: ImportGroupStyle::Default,
                 skip_glob_imports: true,
+                prefer_reexports: false,
             },
             snippets: Vec::new(),
             prefer_no_std: false,
@@ -185,7 +187,9 @@ fn integrated_completion_benchmark() {
                 prefix_kind: hir::PrefixKind::ByCrate,
                 enforce_granularity: true,
                 group: true,
+                group_style: ImportGroupStyle::Default,
                 skip_glob_imports: true,
+                prefer_reexports: false,
             },
             snippets: Vec::new(),
             prefer_no_std: false,
@@ -227,7 +231,9 @@ fn integrated_completion_benchmark() {
                 prefix_kind: hir::PrefixKind::ByCrate,
                 enforce_granularity: true,
                 group: true,
+                group_style: ImportGroupStyle::Default,
                 skip_glob_imports: true,
+                prefer_reexports: false,
             },
             snippets: Vec::new(),
             prefer_no_std: false,
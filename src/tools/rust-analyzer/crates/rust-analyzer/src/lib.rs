@@ -12,6 +12,7 @@
 #![warn(rust_2018_idioms, unused_lifetimes)]
 
 pub mod cli;
+pub mod daemon;
 
 mod caps;
 mod cargo_target_spec;
@@ -19,6 +20,7 @@
 mod diff;
 mod dispatch;
 mod global_state;
+mod highlight_cache;
 mod line_index;
 mod main_loop;
 mod mem_docs;
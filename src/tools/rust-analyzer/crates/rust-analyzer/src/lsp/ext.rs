@@ -71,6 +71,14 @@ impl Request for ShuffleCrateGraph {
     const METHOD: &'static str = "rust-analyzer/shuffleCrateGraph";
 }
 
+pub enum ResetQueryStats {}
+
+impl Request for ResetQueryStats {
+    type Params = ();
+    type Result = ();
+    const METHOD: &'static str = "rust-analyzer/resetQueryStats";
+}
+
 pub enum ReloadWorkspace {}
 
 impl Request for ReloadWorkspace {
@@ -126,6 +134,41 @@ impl Request for InterpretFunction {
     const METHOD: &'static str = "rust-analyzer/interpretFunction";
 }
 
+pub enum EvaluateSelection {}
+
+impl Request for EvaluateSelection {
+    type Params = EvaluateSelectionParams;
+    type Result = Option<EvaluateSelectionResult>;
+    const METHOD: &'static str = "rust-analyzer/evaluateSelection";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateSelectionParams {
+    pub text_document: TextDocumentIdentifier,
+    pub range: Range,
+    /// The values of the variables the selection uses from outside of it, by name.
+    #[serde(default)]
+    pub values: FxHashMap<String, String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateSelectionResult {
+    /// The variables without a value in `values`. The selection is only evaluated when there are
+    /// none.
+    pub missing_values: Vec<MissingValue>,
+    pub value: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct MissingValue {
+    pub name: String,
+    pub ty: String,
+}
+
 pub enum ViewFileText {}
 
 impl Request for ViewFileText {
@@ -139,6 +182,69 @@ impl Request for ViewFileText {
 pub struct ViewCrateGraphParams {
     /// Include *all* crates, not just crates in the workspace.
     pub full: bool,
+    #[serde(default)]
+    pub format: CrateGraphFormat,
+    /// Only include the crates this document's crates transitively depend on.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transitive_from: Option<lsp_types::TextDocumentIdentifier>,
+    /// Annotate crates with their lines of code, item count and memory usage.
+    #[serde(default)]
+    pub metrics: bool,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum CrateGraphFormat {
+    /// GraphViz `dot` syntax.
+    #[default]
+    Dot,
+    /// An [`ExportedCrateGraph`] serialized as JSON.
+    Json,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedCrateGraph {
+    pub nodes: Vec<ExportedCrateGraphNode>,
+    pub edges: Vec<ExportedCrateGraphEdge>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedCrateGraphNode {
+    pub id: u32,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    pub cfg: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<CrateMetrics>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CrateMetrics {
+    pub lines_of_code: u64,
+    pub items: u64,
+    /// In bytes.
+    pub memory: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedCrateGraphEdge {
+    pub from: u32,
+    pub to: u32,
+    pub name: String,
+    pub kind: DependencyKind,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum DependencyKind {
+    Normal,
+    Dev,
+    Build,
 }
 
 pub enum ViewCrateGraph {}
@@ -212,6 +318,130 @@ pub struct MemoryLayoutNode {
     pub children_len: u64,
 }
 
+pub enum ExplainType {}
+
+impl Request for ExplainType {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = Option<ExplainedType>;
+    const METHOD: &'static str = "rust-analyzer/explainType";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainedType {
+    pub nodes: Vec<ExplainedTypeNode>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainedTypeNode {
+    pub label: String,
+    pub kind: ExplainedTypeNodeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<lsp_types::Location>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<u32>,
+    pub children: Vec<u32>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum ExplainedTypeNodeKind {
+    Type,
+    Alias,
+    Normalized,
+    HiddenType,
+}
+
+pub enum ExplainUnmetBound {}
+
+impl Request for ExplainUnmetBound {
+    type Params = ExplainUnmetBoundParams;
+    type Result = Option<UnmetBound>;
+    const METHOD: &'static str = "rust-analyzer/explainUnmetBound";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExplainUnmetBoundParams {
+    #[serde(flatten)]
+    pub text_document_position: lsp_types::TextDocumentPositionParams,
+    /// The path of the trait, like `Send`, resolved at the position.
+    #[serde(rename = "trait")]
+    pub trait_: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UnmetBound {
+    pub reasons: Vec<String>,
+}
+
+pub enum GeneratingMacro {}
+
+impl Request for GeneratingMacro {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = Option<lsp_types::GotoDefinitionResponse>;
+    const METHOD: &'static str = "rust-analyzer/generatingMacro";
+}
+
+pub enum GeneratedItems {}
+
+impl Request for GeneratedItems {
+    type Params = lsp_types::TextDocumentPositionParams;
+    type Result = Option<lsp_types::GotoDefinitionResponse>;
+    const METHOD: &'static str = "rust-analyzer/generatedItems";
+}
+
+pub enum TodoList {}
+
+impl Request for TodoList {
+    type Params = TodoListParams;
+    type Result = Vec<TodoItem>;
+    const METHOD: &'static str = "rust-analyzer/todoList";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListParams {
+    /// The document to list the comments of, or the whole workspace if absent.
+    pub text_document: Option<lsp_types::TextDocumentIdentifier>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoItem {
+    pub location: lsp_types::Location,
+    /// `TODO`, `FIXME` or `HACK`.
+    pub kind: String,
+    pub owner: Option<String>,
+    /// The date of the tag, as `YYYY-MM-DD`.
+    pub date: Option<String>,
+    /// The number of days since the date of the tag.
+    pub age_days: Option<i64>,
+    pub text: String,
+}
+
+pub enum SearchImpls {}
+
+impl Request for SearchImpls {
+    type Params = SearchImplsParams;
+    type Result = Vec<lsp_types::Location>;
+    const METHOD: &'static str = "rust-analyzer/searchImpls";
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchImplsParams {
+    /// A trait with optional arguments and self type, like `From<_> for MyError`.
+    pub pattern: String,
+
+    /// File position where the search was invoked. Paths in `pattern` will be resolved relative to
+    /// this position.
+    #[serde(flatten)]
+    pub position: lsp_types::TextDocumentPositionParams,
+}
+
 pub enum CancelFlycheck {}
 
 impl Notification for CancelFlycheck {
@@ -314,13 +544,21 @@ pub struct Runnable {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub location: Option<lsp_types::LocationLink>,
     pub kind: RunnableKind,
-    pub args: CargoRunnable,
+    pub args: RunnableArgs,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(untagged)]
+pub enum RunnableArgs {
+    Cargo(CargoRunnable),
+    Shell(ShellRunnable),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "lowercase")]
 pub enum RunnableKind {
     Cargo,
+    Shell,
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -340,6 +578,18 @@ pub struct CargoRunnable {
     pub expect_test: Option<bool>,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ShellRunnable {
+    pub program: String,
+    /// May contain `{test_id}`, which the client replaces with `test_id`.
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    /// The test or test module to run, `None` to run a binary or all tests of a crate.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_id: Option<String>,
+}
+
 pub enum RelatedTests {}
 
 impl Request for RelatedTests {
@@ -383,6 +633,47 @@ pub struct SsrParams {
     pub selections: Vec<lsp_types::Range>,
 }
 
+pub enum StructuralSearch {}
+
+impl Request for StructuralSearch {
+    type Params = StructuralSearchParams;
+    type Result = Vec<StructuralSearchMatch>;
+    const METHOD: &'static str = "experimental/structuralSearch";
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralSearchParams {
+    pub query: String,
+
+    /// File position where the search was invoked. Paths in `query` will be resolved relative to
+    /// this position.
+    #[serde(flatten)]
+    pub position: lsp_types::TextDocumentPositionParams,
+
+    /// Current selections. The search will be restricted to these if non-empty.
+    pub selections: Vec<lsp_types::Range>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuralSearchMatch {
+    pub location: lsp_types::Location,
+    pub matched_text: String,
+    pub placeholders: Vec<PlaceholderBinding>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaceholderBinding {
+    /// The name of the placeholder, without the leading `$`.
+    pub name: String,
+    pub location: lsp_types::Location,
+    pub matched_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ty: Option<String>,
+}
+
 pub enum ServerStatusNotification {}
 
 impl Notification for ServerStatusNotification {
@@ -587,6 +878,8 @@ pub struct CodeLensResolveData {
 pub enum CodeLensResolveDataKind {
     Impls(lsp_types::request::GotoImplementationParams),
     References(lsp_types::TextDocumentPositionParams),
+    VariantUsages(lsp_types::TextDocumentPositionParams),
+    StackUsage(lsp_types::TextDocumentPositionParams),
 }
 
 pub fn negotiated_encoding(caps: &lsp_types::ClientCapabilities) -> PositionEncoding {
@@ -1,6 +1,6 @@
 //! Conversion lsp_types types to rust-analyzer specific ones.
 use anyhow::format_err;
-use ide::{Annotation, AnnotationKind, AssistKind, LineCol};
+use ide::{Annotation, AnnotationKind, AssistKind, Color, LineCol};
 use ide_db::{
     base_db::{FileId, FilePosition, FileRange},
     line_index::WideLineCol,
@@ -103,6 +103,16 @@ pub(crate) fn assist_kind(kind: lsp_types::CodeActionKind) -> Option<AssistKind>
     Some(assist_kind)
 }
 
+pub(crate) fn color(color: lsp_types::Color) -> Color {
+    let channel = |it: f32| (it.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Color {
+        red: channel(color.red),
+        green: channel(color.green),
+        blue: channel(color.blue),
+        alpha: channel(color.alpha),
+    }
+}
+
 pub(crate) fn annotation(
     snap: &GlobalStateSnapshot,
     code_lens: lsp_types::CodeLens,
@@ -139,6 +149,30 @@ pub(crate) fn annotation(
                 kind: AnnotationKind::HasReferences { pos, data: None },
             })
         }
+        lsp_ext::CodeLensResolveDataKind::VariantUsages(params) => {
+            if snap.url_file_version(&params.text_document.uri) != Some(resolve.version) {
+                return Ok(None);
+            }
+            let pos @ FilePosition { file_id, .. } = file_position(snap, params)?;
+            let line_index = snap.file_line_index(file_id)?;
+
+            Ok(Annotation {
+                range: text_range(&line_index, code_lens.range)?,
+                kind: AnnotationKind::VariantUsages { pos, data: None },
+            })
+        }
+        lsp_ext::CodeLensResolveDataKind::StackUsage(params) => {
+            if snap.url_file_version(&params.text_document.uri) != Some(resolve.version) {
+                return Ok(None);
+            }
+            let pos @ FilePosition { file_id, .. } = file_position(snap, params)?;
+            let line_index = snap.file_line_index(file_id)?;
+
+            Ok(Annotation {
+                range: text_range(&line_index, code_lens.range)?,
+                kind: AnnotationKind::StackUsage { pos, data: None },
+            })
+        }
     }
     .map(Some)
 }
@@ -159,6 +159,30 @@ pub(crate) fn standard_fallback(&mut self) {
         // Remove all non standard modifiers
         self.0 &= !(!0u32 << LAST_STANDARD_MOD)
     }
+
+    /// Replaces every modifier in the set with the one `f` maps it to, or drops it for `None`.
+    pub(crate) fn remap(
+        &mut self,
+        f: impl Fn(&SemanticTokenModifier) -> Option<SemanticTokenModifier>,
+    ) {
+        let mut res = ModifierSet::default();
+        for (idx, modifier) in SUPPORTED_MODIFIERS.iter().enumerate() {
+            if self.0 & (1 << idx) != 0 {
+                if let Some(modifier) = f(modifier) {
+                    res |= modifier;
+                }
+            }
+        }
+        *self = res;
+    }
+}
+
+pub(crate) fn type_by_name(name: &str) -> Option<SemanticTokenType> {
+    SUPPORTED_TYPES.iter().find(|it| it.as_str() == name).cloned()
+}
+
+pub(crate) fn modifier_by_name(name: &str) -> Option<SemanticTokenModifier> {
+    SUPPORTED_MODIFIERS.iter().find(|it| it.as_str() == name).cloned()
 }
 
 impl ops::BitOrAssign<SemanticTokenModifier> for ModifierSet {
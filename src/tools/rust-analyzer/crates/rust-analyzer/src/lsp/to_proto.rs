@@ -6,21 +6,23 @@
 };
 
 use ide::{
-    Annotation, AnnotationKind, Assist, AssistKind, Cancellable, CompletionItem,
-    CompletionItemKind, CompletionRelevance, Documentation, FileId, FileRange, FileSystemEdit,
-    Fold, FoldKind, Highlight, HlMod, HlOperator, HlPunct, HlRange, HlTag, Indel,
-    InlayFieldsToResolve, InlayHint, InlayHintLabel, InlayHintLabelPart, InlayKind, Markup,
-    NavigationTarget, ReferenceCategory, RenameError, Runnable, Severity, SignatureHelp,
-    SnippetEdit, SourceChange, StructureNodeKind, SymbolKind, TextEdit, TextRange, TextSize,
+    Annotation, AnnotationKind, Assist, AssistKind, Cancellable, Color, ColorInformation,
+    ColorPresentation, CompletionItem, CompletionItemKind, CompletionRelevance, Documentation,
+    FileId, FileRange, FileSystemEdit, Fold, FoldKind, Highlight, HlMod, HlOperator, HlPunct,
+    HlRange, HlTag, Indel, InlayFieldsToResolve, InlayHint, InlayHintLabel, InlayHintLabelPart,
+    InlayKind, Markup, NavigationTarget, ReferenceCategory, RenameError, Runnable, Severity,
+    SignatureHelp, SnippetEdit, SourceChange, StructureNodeKind, SymbolKind, TextEdit, TextRange,
+    TextSize,
 };
 use ide_db::rust_doc::format_docs;
 use itertools::Itertools;
+use project_model::project_json::RunnableKind as ProjectJsonRunnableKind;
 use serde_json::to_value;
 use vfs::AbsPath;
 
 use crate::{
     cargo_target_spec::CargoTargetSpec,
-    config::{CallInfoConfig, Config},
+    config::{CallInfoConfig, Config, SemanticTokensConfig},
     global_state::GlobalStateSnapshot,
     line_index::{LineEndings, LineIndex, PositionEncoding},
     lsp::{
@@ -469,7 +471,9 @@ pub(crate) fn inlay_hint(
         padding_right: Some(inlay_hint.pad_right),
         kind: match inlay_hint.kind {
             InlayKind::Parameter => Some(lsp_types::InlayHintKind::PARAMETER),
-            InlayKind::Type | InlayKind::Chaining => Some(lsp_types::InlayHintKind::TYPE),
+            InlayKind::Type | InlayKind::Chaining | InlayKind::GenericArgs => {
+                Some(lsp_types::InlayHintKind::TYPE)
+            }
             _ => None,
         },
         text_edits,
@@ -558,8 +562,7 @@ pub(crate) fn semantic_tokens(
     text: &str,
     line_index: &LineIndex,
     highlights: Vec<HlRange>,
-    semantics_tokens_augments_syntax_tokens: bool,
-    non_standard_tokens: bool,
+    config: &SemanticTokensConfig,
 ) -> lsp_types::SemanticTokens {
     let id = TOKEN_RESULT_COUNTER.fetch_add(1, Ordering::SeqCst).to_string();
     let mut builder = semantic_tokens::SemanticTokensBuilder::new(id);
@@ -569,7 +572,7 @@ pub(crate) fn semantic_tokens(
             continue;
         }
 
-        if semantics_tokens_augments_syntax_tokens {
+        if config.augments_syntax_tokens {
             match highlight_range.highlight.tag {
                 HlTag::BoolLiteral
                 | HlTag::ByteLiteral
@@ -591,7 +594,28 @@ pub(crate) fn semantic_tokens(
 
         let (mut ty, mut mods) = semantic_token_type_and_modifiers(highlight_range.highlight);
 
-        if !non_standard_tokens {
+        if !config.disabled.is_empty() || !config.mapping.is_empty() {
+            if config.disabled.contains(ty.as_str()) {
+                continue;
+            }
+            if let Some(mapped) =
+                config.mapping.get(ty.as_str()).and_then(|it| semantic_tokens::type_by_name(it))
+            {
+                ty = mapped;
+            }
+            mods.remap(|modifier| {
+                if config.disabled.contains(modifier.as_str()) {
+                    return None;
+                }
+                let mapped = config
+                    .mapping
+                    .get(modifier.as_str())
+                    .and_then(|it| semantic_tokens::modifier_by_name(it));
+                Some(mapped.unwrap_or_else(|| modifier.clone()))
+            });
+        }
+
+        if !config.non_standard_tokens {
             ty = match standard_fallback_type(ty) {
                 Some(ty) => ty,
                 None => continue,
@@ -792,6 +816,37 @@ pub(crate) fn folding_range(
     }
 }
 
+pub(crate) fn color(color: Color) -> lsp_types::Color {
+    let channel = |it: u8| f32::from(it) / 255.0;
+    lsp_types::Color {
+        red: channel(color.red),
+        green: channel(color.green),
+        blue: channel(color.blue),
+        alpha: channel(color.alpha),
+    }
+}
+
+pub(crate) fn color_information(
+    line_index: &LineIndex,
+    info: ColorInformation,
+) -> lsp_types::ColorInformation {
+    lsp_types::ColorInformation { range: range(line_index, info.range), color: color(info.color) }
+}
+
+pub(crate) fn color_presentation(
+    line_index: &LineIndex,
+    presentation: ColorPresentation,
+) -> lsp_types::ColorPresentation {
+    let mut edits = text_edit_vec(line_index, presentation.edit).into_iter();
+    let text_edit = edits.next();
+    let additional_text_edits = edits.collect::<Vec<_>>();
+    lsp_types::ColorPresentation {
+        label: presentation.label,
+        text_edit,
+        additional_text_edits: (!additional_text_edits.is_empty()).then_some(additional_text_edits),
+    }
+}
+
 pub(crate) fn url(snap: &GlobalStateSnapshot, file_id: FileId) -> lsp_types::Url {
     snap.file_id_to_url(file_id)
 }
@@ -1332,6 +1387,17 @@ pub(crate) fn runnable(
     snap: &GlobalStateSnapshot,
     runnable: Runnable,
 ) -> Cancellable<lsp_ext::Runnable> {
+    if let Some(args) = project_json_runnable(snap, &runnable)? {
+        let label = runnable.label(None);
+        let location = location_link(snap, None, runnable.nav)?;
+        return Ok(lsp_ext::Runnable {
+            label,
+            location: Some(location),
+            kind: lsp_ext::RunnableKind::Shell,
+            args: lsp_ext::RunnableArgs::Shell(args),
+        });
+    }
+
     let config = snap.config.runnables();
     let spec = CargoTargetSpec::for_file(snap, runnable.nav.file_id)?;
     let workspace_root = spec.as_ref().map(|it| it.workspace_root.clone());
@@ -1345,17 +1411,49 @@ pub(crate) fn runnable(
         label,
         location: Some(location),
         kind: lsp_ext::RunnableKind::Cargo,
-        args: lsp_ext::CargoRunnable {
+        args: lsp_ext::RunnableArgs::Cargo(lsp_ext::CargoRunnable {
             workspace_root: workspace_root.map(|it| it.into()),
             override_cargo: config.override_cargo,
             cargo_args,
             cargo_extra_args: config.cargo_extra_args,
             executable_args,
             expect_test: None,
-        },
+        }),
     })
 }
 
+/// Returns the command template a `rust-project.json` gives for the runnable's crate, if any.
+/// The client fills in the test id.
+fn project_json_runnable(
+    snap: &GlobalStateSnapshot,
+    runnable: &Runnable,
+) -> Cancellable<Option<lsp_ext::ShellRunnable>> {
+    let (kind, test_id) = match &runnable.kind {
+        ide::RunnableKind::Test { test_id, .. } | ide::RunnableKind::Bench { test_id } => {
+            (ProjectJsonRunnableKind::Test, Some(test_id.to_string()))
+        }
+        // The test module of a crate root has an empty path, it runs all tests.
+        ide::RunnableKind::TestMod { path } => {
+            (ProjectJsonRunnableKind::Test, Some(path.clone()).filter(|it| !it.is_empty()))
+        }
+        ide::RunnableKind::Bin => (ProjectJsonRunnableKind::Run, None),
+        ide::RunnableKind::DocTest { .. } => return Ok(None),
+    };
+    let crate_id = match &*snap.analysis.crates_for(runnable.nav.file_id)? {
+        &[crate_id, ..] => crate_id,
+        _ => return Ok(None),
+    };
+    let Some(krate) = snap.project_json_crate_for_crate_root(crate_id) else {
+        return Ok(None);
+    };
+    Ok(krate.runnables().iter().find(|it| it.kind == kind).map(|template| lsp_ext::ShellRunnable {
+        program: template.program.clone(),
+        args: template.args.clone(),
+        cwd: template.cwd.clone().into(),
+        test_id,
+    }))
+}
+
 pub(crate) fn code_lens(
     acc: &mut Vec<lsp_types::CodeLens>,
     snap: &GlobalStateSnapshot,
@@ -1378,10 +1476,11 @@ pub(crate) fn code_lens(
             let r = runnable(snap, run)?;
 
             let lens_config = snap.config.lens();
-            if lens_config.run
-                && client_commands_config.run_single
-                && r.args.workspace_root.is_some()
-            {
+            let has_root = match &r.args {
+                lsp_ext::RunnableArgs::Cargo(args) => args.workspace_root.is_some(),
+                lsp_ext::RunnableArgs::Shell(_) => true,
+            };
+            if lens_config.run && client_commands_config.run_single && has_root {
                 let command = command::run_single(&r, &title);
                 acc.push(lsp_types::CodeLens {
                     range: annotation_range,
@@ -1495,6 +1594,85 @@ pub(crate) fn code_lens(
                 })(),
             })
         }
+        AnnotationKind::VariantUsages { pos, data } => {
+            if !client_commands_config.show_reference {
+                return Ok(());
+            }
+            let line_index = snap.file_line_index(pos.file_id)?;
+            let annotation_range = range(&line_index, annotation.range);
+            let url = url(snap, pos.file_id);
+            let pos = position(&line_index, pos.offset);
+
+            let id = lsp_types::TextDocumentIdentifier { uri: url.clone() };
+
+            let doc_pos = lsp_types::TextDocumentPositionParams::new(id, pos);
+
+            let command = data.map(|usages| {
+                let title = variant_usages_title(usages.constructions.len(), usages.matches.len());
+                let locations: Vec<lsp_types::Location> = usages
+                    .constructions
+                    .into_iter()
+                    .chain(usages.matches)
+                    .filter_map(|range| location(snap, range).ok())
+                    .collect();
+
+                command::show_references(title, &url, pos, locations)
+            });
+
+            acc.push(lsp_types::CodeLens {
+                range: annotation_range,
+                command,
+                data: (|| {
+                    let version = snap.url_file_version(&url)?;
+                    Some(
+                        to_value(lsp_ext::CodeLensResolveData {
+                            version,
+                            kind: lsp_ext::CodeLensResolveDataKind::VariantUsages(doc_pos),
+                        })
+                        .unwrap(),
+                    )
+                })(),
+            })
+        }
+        AnnotationKind::StackUsage { pos, data } => {
+            if !client_commands_config.show_reference {
+                return Ok(());
+            }
+            let line_index = snap.file_line_index(pos.file_id)?;
+            let annotation_range = range(&line_index, annotation.range);
+            let url = url(snap, pos.file_id);
+            let pos = position(&line_index, pos.offset);
+
+            let id = lsp_types::TextDocumentIdentifier { uri: url.clone() };
+
+            let doc_pos = lsp_types::TextDocumentPositionParams::new(id, pos);
+
+            let command = data.map(|usage| {
+                let title = stack_usage_title(&usage);
+                let locations: Vec<lsp_types::Location> = usage
+                    .worst_path
+                    .into_iter()
+                    .filter_map(|nav| location_from_nav(snap, nav).ok())
+                    .collect();
+
+                command::show_references(title, &url, pos, locations)
+            });
+
+            acc.push(lsp_types::CodeLens {
+                range: annotation_range,
+                command,
+                data: (|| {
+                    let version = snap.url_file_version(&url)?;
+                    Some(
+                        to_value(lsp_ext::CodeLensResolveData {
+                            version,
+                            kind: lsp_ext::CodeLensResolveDataKind::StackUsage(doc_pos),
+                        })
+                        .unwrap(),
+                    )
+                })(),
+            })
+        }
     }
     Ok(())
 }
@@ -1600,6 +1778,32 @@ pub(crate) fn reference_title(count: usize) -> String {
     }
 }
 
+pub(crate) fn variant_usages_title(constructions: usize, matches: usize) -> String {
+    let constructions = match constructions {
+        0 => "never constructed".to_owned(),
+        1 => "constructed once".to_owned(),
+        n => format!("constructed {n} times"),
+    };
+    let matches = match matches {
+        0 => "never matched".to_owned(),
+        1 => "matched once".to_owned(),
+        n => format!("matched {n} times"),
+    };
+    format!("{constructions}, {matches}")
+}
+
+pub(crate) fn stack_usage_title(usage: &ide::StackUsage) -> String {
+    let at_least = if usage.incomplete { "at least " } else { "" };
+    if usage.worst_path.is_empty() {
+        format!("stack: {at_least}{} bytes", usage.frame_size)
+    } else {
+        format!(
+            "stack: {} bytes, {at_least}{} bytes with callees",
+            usage.frame_size, usage.worst_case
+        )
+    }
+}
+
 pub(crate) fn markup_content(
     markup: Markup,
     kind: ide::HoverDocFormat,
@@ -1664,6 +1868,84 @@ fn main() {
         }
     }
 
+    #[test]
+    fn semantic_tokens_disabled_and_mapped() {
+        let text = "unsafe fn f(mut x: u8) { x = 0; }";
+        let (analysis, file_id) = Analysis::from_single_file(text.to_owned());
+        let highlight_config = ide::HighlightConfig {
+            strings: true,
+            punctuation: false,
+            specialize_punctuation: false,
+            operator: false,
+            specialize_operator: false,
+            inject_doc_comment: false,
+            macro_bang: false,
+            syntactic_name_ref_highlighting: false,
+        };
+        let line_index = LineIndex {
+            index: Arc::new(ide::LineIndex::new(text)),
+            endings: LineEndings::Unix,
+            encoding: PositionEncoding::Utf8,
+        };
+        let check = |config: &SemanticTokensConfig, expect: Expect| {
+            let highlights = analysis.highlight(highlight_config, file_id).unwrap();
+            let tokens = semantic_tokens(text, &line_index, highlights, config);
+            let mut start = 0;
+            let actual = tokens
+                .data
+                .iter()
+                .map(|token| {
+                    start += token.delta_start;
+                    let text = &text[start as usize..][..token.length as usize];
+                    let ty = semantic_tokens::SUPPORTED_TYPES[token.token_type as usize].as_str();
+                    let mods = semantic_tokens::SUPPORTED_MODIFIERS
+                        .iter()
+                        .enumerate()
+                        .filter(|(idx, _)| token.token_modifiers_bitset & (1 << idx) != 0)
+                        .map(|(_, it)| it.as_str())
+                        .join(".");
+                    format!("{text}: {}\n", format!("{ty} {mods}").trim_end())
+                })
+                .collect::<String>();
+            expect.assert_eq(&actual);
+        };
+
+        let config = SemanticTokensConfig { non_standard_tokens: true, ..Default::default() };
+        check(
+            &config,
+            expect![[r#"
+                unsafe: keyword unsafe
+                fn: keyword
+                f: function declaration.unsafe
+                mut: keyword
+                x: parameter declaration.mutable
+                u8: builtinType
+                x: parameter mutable
+                0: number
+            "#]],
+        );
+        check(
+            &SemanticTokensConfig {
+                disabled: ["keyword".to_owned(), "unsafe".to_owned()].into_iter().collect(),
+                mapping: [
+                    ("builtinType".to_owned(), "struct".to_owned()),
+                    ("mutable".to_owned(), "static".to_owned()),
+                    ("number".to_owned(), "static".to_owned()),
+                ]
+                .into_iter()
+                .collect(),
+                ..config
+            },
+            expect![[r#"
+                f: function declaration
+                x: parameter declaration.static
+                u8: struct
+                x: parameter static
+                0: number
+            "#]],
+        );
+    }
+
     #[test]
     fn calling_function_with_ignored_code_in_signature() {
         let text = r#"
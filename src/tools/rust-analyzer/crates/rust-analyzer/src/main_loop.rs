@@ -3,6 +3,7 @@
 use crate::lsp::ext;
 use std::{
     fmt,
+    sync::atomic::Ordering,
     time::{Duration, Instant},
 };
 
@@ -11,12 +12,15 @@
 use ide_db::base_db::{SourceDatabase, SourceDatabaseExt, VfsPath};
 use lsp_server::{Connection, Notification, Request};
 use lsp_types::notification::Notification as _;
+use project_model::{CratesIndex, Package};
+use rustc_hash::FxHashSet;
 use stdx::thread::ThreadIntent;
-use vfs::FileId;
+use triomphe::Arc;
+use vfs::{AbsPathBuf, FileId};
 
 use crate::{
     config::Config,
-    diagnostics::fetch_native_diagnostics,
+    diagnostics::{fetch_native_diagnostics, fetch_yanked_dependency_diagnostics},
     dispatch::{NotificationDispatcher, RequestDispatcher},
     global_state::{file_id_to_url, url_to_file_id, GlobalState},
     lsp::{
@@ -58,6 +62,7 @@ enum Event {
     QueuedTask(QueuedTask),
     Vfs(vfs::loader::Message),
     Flycheck(flycheck::Message),
+    NativeDiagnosticsDue,
 }
 
 impl fmt::Display for Event {
@@ -68,6 +73,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Event::Vfs(_) => write!(f, "Event::Vfs"),
             Event::Flycheck(_) => write!(f, "Event::Flycheck"),
             Event::QueuedTask(_) => write!(f, "Event::QueuedTask"),
+            Event::NativeDiagnosticsDue => write!(f, "Event::NativeDiagnosticsDue"),
         }
     }
 }
@@ -76,6 +82,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 pub(crate) enum QueuedTask {
     CheckIfIndexed(lsp_types::Url),
     CheckProcMacroSources(Vec<FileId>),
+    CheckBuildScriptInputs(Vec<AbsPathBuf>),
 }
 
 #[derive(Debug)]
@@ -83,12 +90,17 @@ pub(crate) enum Task {
     Response(lsp_server::Response),
     ClientNotification(ext::UnindexedProjectParams),
     Retry(lsp_server::Request),
-    Diagnostics(Vec<(FileId, Vec<lsp_types::Diagnostic>)>),
+    /// Native diagnostics, tagged with the generation of the computation that produced them.
+    Diagnostics(usize, Vec<(FileId, Vec<lsp_types::Diagnostic>)>),
     PrimeCaches(PrimeCachesProgress),
     FetchWorkspace(ProjectWorkspaceProgress),
     FetchBuildData(BuildDataProgress),
     LoadProcMacros(ProcMacroProgress),
     BuildDepsHaveChanged,
+    /// Diagnostics for `Cargo.toml` files, which aren't part of the analysis.
+    ManifestDiagnostics(Vec<(lsp_types::Url, Vec<lsp_types::Diagnostic>)>),
+    /// Packages, per workspace, whose build scripts have inputs that changed.
+    BuildScriptsAreStale(Vec<(usize, Vec<Package>)>),
 }
 
 #[derive(Debug)]
@@ -127,6 +139,7 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
             Event::QueuedTask(it) => fmt::Debug::fmt(it, f),
             Event::Vfs(it) => fmt::Debug::fmt(it, f),
             Event::Flycheck(it) => fmt::Debug::fmt(it, f),
+            Event::NativeDiagnosticsDue => f.write_str("NativeDiagnosticsDue"),
         }
     }
 }
@@ -196,6 +209,11 @@ fn register_did_save_capability(&mut self) {
     }
 
     fn next_event(&self, inbox: &Receiver<lsp_server::Message>) -> Option<Event> {
+        let native_diagnostics_due = match self.native_diagnostics_deadline {
+            Some(deadline) => crossbeam_channel::at(deadline),
+            None => crossbeam_channel::never(),
+        };
+
         select! {
             recv(inbox) -> msg =>
                 msg.ok().map(Event::Lsp),
@@ -206,6 +224,9 @@ fn next_event(&self, inbox: &Receiver<lsp_server::Message>) -> Option<Event> {
             recv(self.deferred_task_queue.receiver) -> task =>
                 Some(Event::QueuedTask(task.unwrap())),
 
+            recv(self.background_pool.receiver) -> task =>
+                Some(Event::Task(task.unwrap())),
+
             recv(self.fmt_pool.receiver) -> task =>
                 Some(Event::Task(task.unwrap())),
 
@@ -214,6 +235,9 @@ fn next_event(&self, inbox: &Receiver<lsp_server::Message>) -> Option<Event> {
 
             recv(self.flycheck_receiver) -> task =>
                 Some(Event::Flycheck(task.unwrap())),
+
+            recv(native_diagnostics_due) -> _ =>
+                Some(Event::NativeDiagnosticsDue),
         }
     }
 
@@ -259,6 +283,9 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
                 while let Ok(task) = self.task_pool.receiver.try_recv() {
                     self.handle_task(&mut prime_caches_progress, task);
                 }
+                while let Ok(task) = self.background_pool.receiver.try_recv() {
+                    self.handle_task(&mut prime_caches_progress, task);
+                }
 
                 for progress in prime_caches_progress {
                     let (state, message, fraction);
@@ -322,6 +349,13 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
                     self.handle_flycheck_msg(message);
                 }
             }
+            Event::NativeDiagnosticsDue => {
+                self.native_diagnostics_deadline = None;
+                // The config may have changed since the computation was scheduled.
+                if self.config.publish_diagnostics() {
+                    self.update_diagnostics();
+                }
+            }
         }
         let event_handling_duration = loop_start.elapsed();
 
@@ -367,7 +401,7 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
             let update_diagnostics = (!was_quiescent || state_changed || memdocs_added_or_removed)
                 && self.config.publish_diagnostics();
             if update_diagnostics {
-                self.update_diagnostics()
+                self.schedule_diagnostics()
             }
         }
 
@@ -420,8 +454,8 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
         }
 
         if !self.fetch_workspaces_queue.op_in_progress() {
-            if let Some((cause, ())) = self.fetch_build_data_queue.should_start_op() {
-                self.fetch_build_data(cause);
+            if let Some((cause, only_stale)) = self.fetch_build_data_queue.should_start_op() {
+                self.fetch_build_data(cause, only_stale);
             } else if let Some((cause, paths)) = self.fetch_proc_macros_queue.should_start_op() {
                 self.fetch_proc_macros(cause, paths);
             }
@@ -431,6 +465,7 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
             self.prime_caches(cause);
         }
 
+        self.enforce_memory_budget();
         self.update_status_or_notify();
 
         let loop_duration = loop_start.elapsed();
@@ -443,11 +478,77 @@ fn handle_event(&mut self, event: Event) -> anyhow::Result<()> {
         Ok(())
     }
 
+    /// Evicts the function bodies and inference results of the half of the crates without open
+    /// documents that had one open least recently, while the server allocates more than the
+    /// configured memory budget. Crates that never had an open document go first.
+    fn enforce_memory_budget(&mut self) {
+        const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+        let Some(budget) = self.config.lru_memory_budget() else { return };
+        if self.last_memory_budget_check.elapsed() < CHECK_INTERVAL {
+            return;
+        }
+        let now = Instant::now();
+        self.last_memory_budget_check = now;
+
+        let open_crates: FxHashSet<_> = {
+            let analysis = self.analysis_host.analysis();
+            let vfs = &self.vfs.read().0;
+            self.mem_docs
+                .iter()
+                .filter_map(|path| vfs.file_id(path))
+                .flat_map(|file_id| analysis.relevant_crates_for(file_id).unwrap_or_default())
+                .collect()
+        };
+        for &krate in &open_crates {
+            self.crate_touches.insert(krate, now);
+        }
+
+        let allocated = profile::memory_usage().allocated;
+        if allocated.megabytes() <= budget as isize {
+            return;
+        }
+        let db = self.analysis_host.raw_database_mut();
+        let mut candidates: Vec<_> = db
+            .crates_with_bodies()
+            .into_iter()
+            .filter(|krate| !open_crates.contains(krate))
+            .collect();
+        if candidates.is_empty() {
+            return;
+        }
+        candidates.sort_by_key(|krate| self.crate_touches.get(krate));
+        candidates.truncate(candidates.len().div_ceil(2));
+        tracing::info!(
+            "{allocated} allocated exceeds the memory budget of {budget}mb, \
+             evicting the bodies of {} crates",
+            candidates.len()
+        );
+        let evicted = db.evict_crate_bodies(&candidates.into_iter().collect());
+        let evictions = Arc::make_mut(&mut self.memory_budget_evictions);
+        for (query, count) in evicted {
+            *evictions.entry(query).or_default() += count;
+        }
+    }
+
     fn prime_caches(&mut self, cause: String) {
         tracing::debug!(%cause, "will prime caches");
         let num_worker_threads = self.config.prime_caches_num_threads();
+        // Library source roots are only known once the VFS has loaded them, which is the case by
+        // the time we prime caches, so the persisted item trees are loaded and saved here, once
+        // per crate graph. Only the trees of crates that weren't loaded before are read.
+        let version = crate::version::version().to_string();
+        let item_tree_cache = match self.config.item_tree_cache() {
+            Some((dir, sysroot_only))
+                if !std::mem::replace(&mut self.item_tree_cache_loaded, true) =>
+            {
+                self.analysis_host.load_persisted_item_trees(dir.as_ref(), sysroot_only, &version);
+                Some((dir, sysroot_only))
+            }
+            _ => None,
+        };
 
-        self.task_pool.handle.spawn_with_sender(ThreadIntent::Worker, {
+        self.background_pool.handle.spawn_with_sender(ThreadIntent::Worker, {
             let analysis = self.snapshot().analysis;
             move |sender| {
                 sender.send(Task::PrimeCaches(PrimeCachesProgress::Begin)).unwrap();
@@ -455,6 +556,13 @@ fn prime_caches(&mut self, cause: String) {
                     let report = PrimeCachesProgress::Report(progress);
                     sender.send(Task::PrimeCaches(report)).unwrap();
                 });
+                if let (Ok(()), Some((dir, sysroot_only))) = (&res, item_tree_cache) {
+                    if let Ok(Err(e)) =
+                        analysis.persist_item_trees(dir.as_ref(), sysroot_only, &version)
+                    {
+                        tracing::warn!("failed to persist item trees: {e}");
+                    }
+                }
                 sender
                     .send(Task::PrimeCaches(PrimeCachesProgress::End { cancelled: res.is_err() }))
                     .unwrap();
@@ -462,6 +570,17 @@ fn prime_caches(&mut self, cause: String) {
         });
     }
 
+    /// Schedules recomputing native diagnostics once no further changes arrive
+    /// within the configured debounce period.
+    fn schedule_diagnostics(&mut self) {
+        let debounce = self.config.diagnostics_debounce();
+        if debounce.is_zero() {
+            self.update_diagnostics();
+        } else {
+            self.native_diagnostics_deadline = Some(Instant::now() + debounce);
+        }
+    }
+
     fn update_diagnostics(&mut self) {
         let db = self.analysis_host.raw_database();
         let subscriptions = self
@@ -480,11 +599,36 @@ fn update_diagnostics(&mut self) {
             .collect::<Vec<_>>();
         tracing::trace!("updating notifications for {:?}", subscriptions);
 
-        // Diagnostics are triggered by the user typing
-        // so we run them on a latency sensitive thread.
-        self.task_pool.handle.spawn(ThreadIntent::LatencySensitive, {
+        // Diagnostics are triggered by the user typing, but nobody is waiting
+        // on them, so we run them as worker tasks on the background pool to not
+        // delay completions and other interactive requests. Any still running
+        // computation is superseded by this one.
+        let generation = self.native_diagnostics_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        self.background_pool.handle.spawn(ThreadIntent::Worker, {
             let snapshot = self.snapshot();
-            move || Task::Diagnostics(fetch_native_diagnostics(snapshot, subscriptions))
+            let latest_generation = self.native_diagnostics_generation.clone();
+            move || {
+                let is_superseded = || latest_generation.load(Ordering::SeqCst) != generation;
+                let diagnostics = fetch_native_diagnostics(snapshot, subscriptions, is_superseded);
+                Task::Diagnostics(generation, diagnostics)
+            }
+        });
+    }
+
+    /// Checks the dependencies of the workspace members for yanked versions.
+    pub(crate) fn update_manifest_diagnostics(&mut self) {
+        if !self.config.crates_index_enable() {
+            return;
+        }
+        let workspaces = Arc::clone(&self.workspaces);
+        let index = CratesIndex::discover(self.config.extra_env());
+        let encoding = self.config.position_encoding();
+        self.task_pool.handle.spawn(ThreadIntent::Worker, move || {
+            Task::ManifestDiagnostics(fetch_yanked_dependency_diagnostics(
+                &workspaces,
+                &index,
+                encoding,
+            ))
         });
     }
 
@@ -525,11 +669,20 @@ fn handle_task(&mut self, prime_caches_progress: &mut Vec<PrimeCachesProgress>,
             // Only retry requests that haven't been cancelled. Otherwise we do unnecessary work.
             Task::Retry(req) if !self.is_completed(&req) => self.on_request(req),
             Task::Retry(_) => (),
-            Task::Diagnostics(diagnostics_per_file) => {
+            Task::Diagnostics(generation, _)
+                if generation != self.native_diagnostics_generation.load(Ordering::SeqCst) => {}
+            Task::Diagnostics(_, diagnostics_per_file) => {
                 for (file_id, diagnostics) in diagnostics_per_file {
                     self.diagnostics.set_native_diagnostics(file_id, diagnostics)
                 }
             }
+            Task::ManifestDiagnostics(diagnostics) => {
+                for (uri, diagnostics) in diagnostics {
+                    self.send_notification::<lsp_types::notification::PublishDiagnostics>(
+                        lsp_types::PublishDiagnosticsParams { uri, diagnostics, version: None },
+                    );
+                }
+            }
             Task::PrimeCaches(progress) => match progress {
                 PrimeCachesProgress::Begin => prime_caches_progress.push(progress),
                 PrimeCachesProgress::Report(_) => {
@@ -598,6 +751,19 @@ fn handle_task(&mut self, prime_caches_progress: &mut Vec<PrimeCachesProgress>,
                 }
             }
             Task::BuildDepsHaveChanged => self.build_deps_changed = true,
+            Task::BuildScriptsAreStale(stale) => {
+                for (idx, packages) in stale {
+                    let entry = self.stale_build_scripts.entry(idx).or_default();
+                    entry.extend(packages);
+                    entry.sort_unstable();
+                    entry.dedup();
+                }
+                // A pending full run covers the stale build scripts as well.
+                if self.config.run_build_scripts() && !self.fetch_build_data_queue.op_requested() {
+                    self.fetch_build_data_queue
+                        .request_op("build script inputs changed".to_owned(), true);
+                }
+            }
         }
     }
 
@@ -695,12 +861,35 @@ fn handle_queued_task(&mut self, task: QueuedTask) {
                     }
                 });
             }
+            QueuedTask::CheckBuildScriptInputs(changed) => {
+                let workspaces = Arc::clone(&self.workspaces);
+                let extra_env = self.config.extra_env().clone();
+                self.task_pool.handle.spawn_with_sender(ThreadIntent::Worker, move |sender| {
+                    let stale: Vec<_> = workspaces
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, ws)| {
+                            (
+                                idx,
+                                ws.stale_build_scripts(
+                                    &|it| changed.iter().any(|c| c == it),
+                                    &extra_env,
+                                ),
+                            )
+                        })
+                        .filter(|(_, packages)| !packages.is_empty())
+                        .collect();
+                    if !stale.is_empty() {
+                        sender.send(Task::BuildScriptsAreStale(stale)).unwrap();
+                    }
+                });
+            }
         }
     }
 
     fn handle_flycheck_msg(&mut self, message: flycheck::Message) {
         match message {
-            flycheck::Message::AddDiagnostic { id, workspace_root, diagnostic } => {
+            flycheck::Message::AddDiagnostic { id, workspace_root, diagnostic, package_id } => {
                 let snap = self.snapshot();
                 let diagnostics = crate::diagnostics::to_proto::map_rust_diagnostic_to_lsp(
                     &self.config.diagnostics_map(),
@@ -712,6 +901,7 @@ fn handle_flycheck_msg(&mut self, message: flycheck::Message) {
                     match url_to_file_id(&self.vfs.read().0, &diag.url) {
                         Ok(file_id) => self.diagnostics.add_check_diagnostic(
                             id,
+                            &package_id,
                             file_id,
                             diag.diagnostic,
                             diag.fix,
@@ -726,12 +916,18 @@ fn handle_flycheck_msg(&mut self, message: flycheck::Message) {
                 }
             }
 
+            flycheck::Message::ClearDiagnostics {
+                id,
+                kind: flycheck::ClearDiagnosticsKind::All,
+            } => self.diagnostics.clear_check(id),
+            flycheck::Message::ClearDiagnostics {
+                id,
+                kind: flycheck::ClearDiagnosticsKind::Package(package_id),
+            } => self.diagnostics.clear_check_for_package(id, package_id),
+
             flycheck::Message::Progress { id, progress } => {
                 let (state, message) = match progress {
-                    flycheck::Progress::DidStart => {
-                        self.diagnostics.clear_check(id);
-                        (Progress::Begin, None)
-                    }
+                    flycheck::Progress::DidStart => (Progress::Begin, None),
                     flycheck::Progress::DidCheckCrate(target) => (Progress::Report, Some(target)),
                     flycheck::Progress::DidCancel => {
                         self.last_flycheck_error = None;
@@ -803,6 +999,7 @@ fn on_request(&mut self, req: Request) {
             .on_sync_mut::<lsp_ext::RebuildProcMacros>(handlers::handle_proc_macros_rebuild)
             .on_sync_mut::<lsp_ext::MemoryUsage>(handlers::handle_memory_usage)
             .on_sync_mut::<lsp_ext::ShuffleCrateGraph>(handlers::handle_shuffle_crate_graph)
+            .on_sync_mut::<lsp_ext::ResetQueryStats>(handlers::handle_reset_query_stats)
             // Request handlers which are related to the user typing
             // are run on the main thread to reduce latency:
             .on_sync::<lsp_ext::JoinLines>(handlers::handle_join_lines)
@@ -824,6 +1021,9 @@ fn on_request(&mut self, req: Request) {
             .on_latency_sensitive::<lsp_request::ResolveCompletionItem>(
                 handlers::handle_completion_resolve,
             )
+            .on_latency_sensitive_no_retry::<lsp_request::InlayHintRequest>(
+                handlers::handle_inlay_hints,
+            )
             .on_latency_sensitive::<lsp_request::SemanticTokensFullRequest>(
                 handlers::handle_semantic_tokens_full,
             )
@@ -833,20 +1033,27 @@ fn on_request(&mut self, req: Request) {
             .on_latency_sensitive::<lsp_request::SemanticTokensRangeRequest>(
                 handlers::handle_semantic_tokens_range,
             )
+            // Request handlers whose results nobody is waiting on right away
+            // run on the background pool, so that they can't delay the above.
+            .on_background::<lsp_ext::FetchDependencyList>(handlers::fetch_dependency_list)
+            .on_background::<lsp_ext::Runnables>(handlers::handle_runnables)
+            .on_background::<lsp_ext::RelatedTests>(handlers::handle_related_tests)
+            .on_background::<lsp_request::CodeLensRequest>(handlers::handle_code_lens)
+            .on_background::<lsp_request::CodeLensResolve>(handlers::handle_code_lens_resolve)
+            .on_background::<lsp_request::FoldingRangeRequest>(handlers::handle_folding_range)
+            .on_background::<lsp_request::DocumentColor>(handlers::handle_document_color)
             // All other request handlers
-            .on::<lsp_ext::FetchDependencyList>(handlers::fetch_dependency_list)
             .on::<lsp_ext::AnalyzerStatus>(handlers::handle_analyzer_status)
             .on::<lsp_ext::SyntaxTree>(handlers::handle_syntax_tree)
             .on::<lsp_ext::ViewHir>(handlers::handle_view_hir)
             .on::<lsp_ext::ViewMir>(handlers::handle_view_mir)
             .on::<lsp_ext::InterpretFunction>(handlers::handle_interpret_function)
+            .on::<lsp_ext::EvaluateSelection>(handlers::handle_evaluate_selection)
             .on::<lsp_ext::ViewFileText>(handlers::handle_view_file_text)
             .on::<lsp_ext::ViewCrateGraph>(handlers::handle_view_crate_graph)
             .on::<lsp_ext::ViewItemTree>(handlers::handle_view_item_tree)
             .on::<lsp_ext::ExpandMacro>(handlers::handle_expand_macro)
             .on::<lsp_ext::ParentModule>(handlers::handle_parent_module)
-            .on::<lsp_ext::Runnables>(handlers::handle_runnables)
-            .on::<lsp_ext::RelatedTests>(handlers::handle_related_tests)
             .on::<lsp_ext::CodeActionRequest>(handlers::handle_code_action)
             .on::<lsp_ext::CodeActionResolveRequest>(handlers::handle_code_action_resolve)
             .on::<lsp_ext::HoverRequest>(handlers::handle_hover)
@@ -859,12 +1066,9 @@ fn on_request(&mut self, req: Request) {
             .on::<lsp_request::GotoDeclaration>(handlers::handle_goto_declaration)
             .on::<lsp_request::GotoImplementation>(handlers::handle_goto_implementation)
             .on::<lsp_request::GotoTypeDefinition>(handlers::handle_goto_type_definition)
-            .on_no_retry::<lsp_request::InlayHintRequest>(handlers::handle_inlay_hints)
             .on::<lsp_request::InlayHintResolveRequest>(handlers::handle_inlay_hints_resolve)
-            .on::<lsp_request::CodeLensRequest>(handlers::handle_code_lens)
-            .on::<lsp_request::CodeLensResolve>(handlers::handle_code_lens_resolve)
-            .on::<lsp_request::FoldingRangeRequest>(handlers::handle_folding_range)
             .on::<lsp_request::SignatureHelpRequest>(handlers::handle_signature_help)
+            .on::<lsp_request::ColorPresentationRequest>(handlers::handle_color_presentation)
             .on::<lsp_request::PrepareRenameRequest>(handlers::handle_prepare_rename)
             .on::<lsp_request::Rename>(handlers::handle_rename)
             .on::<lsp_request::References>(handlers::handle_references)
@@ -874,7 +1078,14 @@ fn on_request(&mut self, req: Request) {
             .on::<lsp_request::CallHierarchyOutgoingCalls>(handlers::handle_call_hierarchy_outgoing)
             .on::<lsp_request::WillRenameFiles>(handlers::handle_will_rename_files)
             .on::<lsp_ext::Ssr>(handlers::handle_ssr)
+            .on::<lsp_ext::StructuralSearch>(handlers::handle_structural_search)
             .on::<lsp_ext::ViewRecursiveMemoryLayout>(handlers::handle_view_recursive_memory_layout)
+            .on::<lsp_ext::ExplainType>(handlers::handle_explain_type)
+            .on::<lsp_ext::ExplainUnmetBound>(handlers::handle_explain_unmet_bound)
+            .on::<lsp_ext::GeneratingMacro>(handlers::handle_generating_macro)
+            .on::<lsp_ext::GeneratedItems>(handlers::handle_generated_items)
+            .on::<lsp_ext::TodoList>(handlers::handle_todo_list)
+            .on::<lsp_ext::SearchImpls>(handlers::handle_search_impls)
             .finish();
     }
 
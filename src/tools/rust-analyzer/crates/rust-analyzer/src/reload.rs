@@ -96,6 +96,7 @@ pub(crate) fn update_configuration(&mut self, config: Config) {
                 self.config.expand_proc_attr_macros(),
                 Durability::HIGH,
             );
+            self.highlight_cache.lock().record_change(None);
         }
     }
 
@@ -134,6 +135,7 @@ pub(crate) fn current_status(&self) -> lsp_ext::ServerStatusParams {
             message.push_str("Auto-reloading is disabled and the workspace has changed, a manual workspace reload is required.\n\n");
         }
         if self.config.linked_or_discovered_projects().is_empty()
+            && self.config.unloaded_projects().is_empty()
             && self.config.detached_files().is_empty()
             && self.config.notifications().cargo_toml_not_found
         {
@@ -224,20 +226,20 @@ pub(crate) fn fetch_workspaces(&mut self, cause: Cause, force_crate_graph_reload
 
                 let mut workspaces = linked_projects
                     .iter()
-                    .map(|project| match project {
+                    .flat_map(|project| match project {
                         LinkedProject::ProjectManifest(manifest) => {
-                            project_model::ProjectWorkspace::load(
+                            project_model::ProjectWorkspace::load_all_variants(
                                 manifest.clone(),
                                 &cargo_config,
                                 &progress,
                             )
                         }
                         LinkedProject::InlineJsonProject(it) => {
-                            Ok(project_model::ProjectWorkspace::load_inline(
+                            vec![Ok(project_model::ProjectWorkspace::load_inline(
                                 it.clone(),
                                 cargo_config.target.as_deref(),
                                 &cargo_config.extra_env,
-                            ))
+                            ))]
                         }
                     })
                     .collect::<Vec<_>>();
@@ -274,11 +276,12 @@ pub(crate) fn fetch_workspaces(&mut self, cause: Cause, force_crate_graph_reload
         });
     }
 
-    pub(crate) fn fetch_build_data(&mut self, cause: Cause) {
-        tracing::info!(%cause, "will fetch build data");
+    pub(crate) fn fetch_build_data(&mut self, cause: Cause, only_stale: bool) {
+        tracing::info!(%cause, only_stale, "will fetch build data");
         let workspaces = Arc::clone(&self.workspaces);
         let config = self.config.cargo();
         let root_path = self.config.root_path().clone();
+        let stale = mem::take(&mut self.stale_build_scripts);
 
         self.task_pool.handle.spawn_with_sender(ThreadIntent::Worker, move |sender| {
             sender.send(Task::FetchBuildData(BuildDataProgress::Begin)).unwrap();
@@ -289,12 +292,18 @@ pub(crate) fn fetch_build_data(&mut self, cause: Cause) {
                     sender.send(Task::FetchBuildData(BuildDataProgress::Report(msg))).unwrap()
                 }
             };
-            let res = ProjectWorkspace::run_all_build_scripts(
-                &workspaces,
-                &config,
-                &progress,
-                &root_path,
-            );
+            let res = if only_stale {
+                workspaces
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, ws)| {
+                        let packages = stale.get(&idx).map_or(&[][..], |it| &it[..]);
+                        ws.rerun_build_scripts(packages, &config, &progress)
+                    })
+                    .collect()
+            } else {
+                ProjectWorkspace::run_all_build_scripts(&workspaces, &config, &progress, &root_path)
+            };
 
             sender.send(Task::FetchBuildData(BuildDataProgress::End((workspaces, res)))).unwrap();
         });
@@ -360,6 +369,7 @@ pub(crate) fn set_proc_macros(&mut self, proc_macros: ProcMacros) {
         let mut change = Change::new();
         change.set_proc_macros(proc_macros);
         self.analysis_host.apply_change(change);
+        self.highlight_cache.lock().record_change(None);
     }
 
     pub(crate) fn switch_workspaces(&mut self, cause: Cause) {
@@ -413,7 +423,7 @@ pub(crate) fn switch_workspaces(&mut self, cause: Cause) {
                 }
                 if self.build_deps_changed && self.config.run_build_scripts() {
                     self.build_deps_changed = false;
-                    self.fetch_build_data_queue.request_op("build_deps_changed".to_owned(), ());
+                    self.fetch_build_data_queue.request_op("build_deps_changed".to_owned(), false);
                 }
                 // Current build scripts do not match the version of the active
                 // workspace, so there's nothing for us to update.
@@ -426,13 +436,19 @@ pub(crate) fn switch_workspaces(&mut self, cause: Cause) {
             // we don't care about build-script results, they are stale.
             // FIXME: can we abort the build scripts here?
             self.workspaces = Arc::new(workspaces);
+            self.workspace_build_files =
+                self.workspaces.iter().flat_map(|ws| ws.build_files()).collect();
+            self.update_manifest_diagnostics();
 
             if self.config.run_build_scripts() {
                 self.build_deps_changed = false;
-                self.fetch_build_data_queue.request_op("workspace updated".to_owned(), ());
+                self.fetch_build_data_queue.request_op("workspace updated".to_owned(), false);
             }
         }
 
+        self.build_script_inputs =
+            self.workspaces.iter().flat_map(|ws| ws.build_script_inputs()).collect();
+
         if let FilesWatcher::Client = self.config.files().watcher {
             let registration_options = lsp_types::DidChangeWatchedFilesRegistrationOptions {
                 watchers: self
@@ -449,6 +465,8 @@ pub(crate) fn switch_workspaces(&mut self, cause: Cause) {
                             ]
                         })
                     })
+                    .chain(self.workspace_build_files.iter().map(|it| it.to_string()))
+                    .chain(self.build_script_inputs.iter().map(|it| it.to_string()))
                     .map(|glob_pattern| lsp_types::FileSystemWatcher {
                         glob_pattern: lsp_types::GlobPattern::String(glob_pattern),
                         kind: None,
@@ -496,14 +514,28 @@ pub(crate) fn switch_workspaces(&mut self, cause: Cause) {
                     };
                 tracing::info!("Using proc-macro server at {path}");
 
-                ProcMacroServer::spawn(path.clone(), &env).map_err(|err| {
-                    tracing::error!(
-                        "Failed to run proc-macro server from path {path}, error: {err:?}",
-                    );
-                    anyhow::format_err!(
-                        "Failed to run proc-macro server from path {path}, error: {err:?}",
-                    )
-                })
+                let cache = self.config.proc_macro_cache();
+                let timeout = self.config.proc_macro_expansion_timeout();
+                let sandbox = self.config.proc_macro_sandbox();
+                ProcMacroServer::spawn(path.clone(), &env)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|server| {
+                        server
+                            .with_expansion_limits(timeout, sandbox)
+                            .map_err(|err| anyhow::format_err!("{err}"))
+                    })
+                    .map(|server| match cache {
+                        Some(persist_dir) => server.with_expansion_cache(persist_dir),
+                        None => server,
+                    })
+                    .map_err(|err| {
+                        tracing::error!(
+                            "Failed to run proc-macro server from path {path}, error: {err:?}",
+                        );
+                        anyhow::format_err!(
+                            "Failed to run proc-macro server from path {path}, error: {err:?}",
+                        )
+                    })
             }))
         }
 
@@ -565,6 +597,8 @@ fn recreate_crate_graph(&mut self, cause: String) {
             change.set_target_data_layouts(layouts);
             change.set_toolchains(toolchains);
             self.analysis_host.apply_change(change);
+            self.highlight_cache.lock().record_change(None);
+            self.item_tree_cache_loaded = false;
             self.crate_graph_file_dependencies = crate_graph_file_dependencies;
         }
         self.process_changes();
@@ -638,6 +672,10 @@ fn reload_flycheck(&mut self) {
                     .iter()
                     .enumerate()
                     .filter_map(|(id, w)| match w {
+                        // Flycheck has its own `check.targets`, checking the workspaces of the
+                        // extra targets or cfg sets would only run the same command again.
+                        ProjectWorkspace::Cargo { extra_target: Some(_), .. }
+                        | ProjectWorkspace::Cargo { extra_cfgs: Some(_), .. } => None,
                         ProjectWorkspace::Cargo { cargo, sysroot, .. } => Some((
                             id,
                             cargo.workspace_root(),
@@ -746,7 +784,12 @@ pub(crate) fn should_refresh_for_change(path: &AbsPath, change_kind: ChangeKind)
         None => return false,
     };
 
-    if let "Cargo.toml" | "Cargo.lock" = file_name {
+    if let "Cargo.toml"
+    | "Cargo.lock"
+    | "rust-project.json"
+    | "rust-toolchain.toml"
+    | "rust-toolchain" = file_name
+    {
         return true;
     }
     if change_kind == ChangeKind::Modify {
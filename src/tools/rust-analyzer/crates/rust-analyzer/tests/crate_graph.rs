@@ -19,6 +19,8 @@ fn load_cargo_with_fake_sysroot(file: &str) -> ProjectWorkspace {
         toolchain: None,
         target_layout: Err("target_data_layout not loaded".into()),
         cargo_config_extra_env: Default::default(),
+        extra_target: None,
+        extra_cfgs: None,
     }
 }
 
@@ -62,7 +64,7 @@ fn get_fake_sysroot() -> Sysroot {
     // fake sysroot, so we give them both the same path:
     let sysroot_dir = AbsPathBuf::assert(sysroot_path);
     let sysroot_src_dir = sysroot_dir.clone();
-    Sysroot::load(sysroot_dir, Some(Ok(sysroot_src_dir)), false)
+    Sysroot::load(sysroot_dir, Some(Ok(sysroot_src_dir)), false, None)
 }
 
 #[test]
@@ -203,6 +203,100 @@ fn main() {}
     );
 }
 
+#[test]
+fn test_runnables_rust_project_json() {
+    if skip_slow_tests() {
+        return;
+    }
+
+    let tmp_dir = TestDir::new();
+    let path = tmp_dir.path();
+
+    let project = json!({
+        "crates": [ {
+            "root_module": path.join("src/lib.rs"),
+            "deps": [],
+            "edition": "2021",
+            "runnables": [ {
+                "kind": "test",
+                "program": "buck2",
+                "args": ["test", "//foo:test", "--", "{test_id}"],
+            } ],
+        } ]
+    });
+
+    let code = format!(
+        r#"
+//- /rust-project.json
+{project}
+
+//- /src/lib.rs
+#[test]
+fn test_eggs() {{}}
+"#,
+    );
+
+    let server =
+        Project::with_fixture(&code).tmp_dir(tmp_dir).server().wait_until_workspace_is_loaded();
+
+    server.request::<Runnables>(
+        RunnablesParams { text_document: server.doc_id("src/lib.rs"), position: None },
+        json!([
+          {
+            "args": {
+              "program": "buck2",
+              "args": ["test", "//foo:test", "--", "{test_id}"],
+              "cwd": server.path(),
+              "testId": "test_eggs"
+            },
+            "kind": "shell",
+            "label": "test test_eggs",
+            "location": {
+              "targetRange": {
+                "end": { "character": 17, "line": 1 },
+                "start": { "character": 0, "line": 0 }
+              },
+              "targetSelectionRange": {
+                "end": { "character": 12, "line": 1 },
+                "start": { "character": 3, "line": 1 }
+              },
+              "targetUri": "file:///[..]/src/lib.rs"
+            }
+          },
+          {
+            "args": {
+              "program": "buck2",
+              "args": ["test", "//foo:test", "--", "{test_id}"],
+              "cwd": server.path()
+            },
+            "kind": "shell",
+            "label": "test-mod ",
+            "location": {
+              "targetRange": {
+                "end": { "character": 0, "line": 2 },
+                "start": { "character": 0, "line": 0 }
+              },
+              "targetSelectionRange": {
+                "end": { "character": 0, "line": 2 },
+                "start": { "character": 0, "line": 0 }
+              },
+              "targetUri": "file:///[..]/src/lib.rs"
+            }
+          },
+          {
+            "args": {
+              "cargoArgs": ["check", "--workspace"],
+              "executableArgs": [],
+              "cargoExtraArgs": [],
+              "overrideCargo": null
+            },
+            "kind": "cargo",
+            "label": "cargo check --workspace"
+          }
+        ]),
+    );
+}
+
 // Each package in these workspaces should be run from its own root
 #[test]
 fn test_path_dependency_runnables() {
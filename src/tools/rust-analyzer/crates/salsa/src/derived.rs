@@ -229,4 +229,13 @@ fn invalidate<S>(&self, runtime: &mut Runtime, key: &S)
             None
         })
     }
+
+    fn evict<S>(&self, key: &S) -> bool
+    where
+        S: Eq + Hash,
+        Q::Key: Borrow<S>,
+    {
+        let map_read = self.slot_map.read();
+        map_read.get(key).map_or(false, |slot| slot.evict())
+    }
 }
@@ -261,6 +261,11 @@ fn execute(
             }
         };
 
+        db.salsa_event(Event {
+            runtime_id: db.salsa_runtime().id(),
+            kind: EventKind::DidExecute { database_key: self.database_key_index },
+        });
+
         let mut revisions = active_query.pop();
 
         // We assume that query is side-effect free -- that is, does
@@ -405,7 +410,8 @@ pub(super) fn as_table_entry(&self) -> Option<TableEntry<Q::Key, Q::Value>> {
         }
     }
 
-    pub(super) fn evict(&self) {
+    /// Drops the memoized value, returning whether there was one.
+    pub(super) fn evict(&self) -> bool {
         let mut state = self.state.write();
         if let QueryState::Memoized(memo) = &mut *state {
             // Evicting a value with an untracked input could
@@ -413,10 +419,11 @@ pub(super) fn evict(&self) {
             // `has_untracked_input` when we add the value to the cache,
             // because inputs can become untracked in the next revision.
             if memo.has_untracked_input() {
-                return;
+                return false;
             }
-            memo.value = None;
+            return memo.value.take().is_some();
         }
+        false
     }
 
     pub(super) fn invalidate(&self, new_revision: Revision) -> Option<Durability> {
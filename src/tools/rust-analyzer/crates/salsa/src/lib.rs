@@ -193,6 +193,15 @@ pub enum EventKind {
         database_key: DatabaseKeyIndex,
     },
 
+    /// Indicates that the function for this query has finished executing, following a
+    /// `WillExecute` event for the same query on the same thread.
+    ///
+    /// Not sent if the function panicked or unwound because of a cycle.
+    DidExecute {
+        /// The database-key for the affected value. Implements `Debug`.
+        database_key: DatabaseKeyIndex,
+    },
+
     /// Indicates that `unwind_if_cancelled` was called and salsa will check if
     /// the current revision has been cancelled.
     WillCheckCancellation,
@@ -224,6 +233,9 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
             EventKind::WillExecute { database_key } => {
                 fmt.debug_struct("WillExecute").field("database_key", database_key).finish()
             }
+            EventKind::DidExecute { database_key } => {
+                fmt.debug_struct("DidExecute").field("database_key", database_key).finish()
+            }
             EventKind::WillCheckCancellation => fmt.debug_struct("WillCheckCancellation").finish(),
         }
     }
@@ -256,6 +268,10 @@ fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
                 .debug_struct("WillExecute")
                 .field("database_key", &database_key.debug(self.db))
                 .finish(),
+            EventKind::DidExecute { database_key } => fmt
+                .debug_struct("DidExecute")
+                .field("database_key", &database_key.debug(self.db))
+                .finish(),
             EventKind::WillCheckCancellation => fmt.debug_struct("WillCheckCancellation").finish(),
         }
     }
@@ -589,6 +605,19 @@ pub fn invalidate(&mut self, key: &Q::Key)
     {
         self.storage.invalidate(self.runtime, key)
     }
+
+    /// Drops the computed value, returning whether there was one.
+    ///
+    /// Unlike [`QueryTableMut::invalidate`] this doesn't create a new revision:
+    /// the value is computed again on the next access to the query, but only
+    /// changes dependent queries if it turns out to be different. This works
+    /// like the eviction of values that don't fit into the LRU cache.
+    pub fn evict(&mut self, key: &Q::Key) -> bool
+    where
+        Q::Storage: plumbing::DerivedQueryStorageOps<Q>,
+    {
+        self.storage.evict(key)
+    }
 }
 
 /// A panic payload indicating that execution of a salsa query was cancelled.
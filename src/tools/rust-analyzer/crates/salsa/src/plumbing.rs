@@ -233,6 +233,12 @@ fn invalidate<S>(&self, runtime: &mut Runtime, key: &S)
     where
         S: Eq + Hash,
         Q::Key: Borrow<S>;
+
+    /// Drops the memoized value of `key`, returning whether there was one.
+    fn evict<S>(&self, key: &S) -> bool
+    where
+        S: Eq + Hash,
+        Q::Key: Borrow<S>;
 }
 
 pub type CycleParticipants = Arc<Vec<DatabaseKeyIndex>>;
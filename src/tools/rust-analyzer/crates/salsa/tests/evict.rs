@@ -0,0 +1,57 @@
+//! Test that evicting values drops them and that they are computed again.
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static N_COMPUTED: AtomicUsize = AtomicUsize::new(0);
+
+#[salsa::query_group(QueryGroupStorage)]
+trait QueryGroup: salsa::Database {
+    #[salsa::input]
+    fn input(&self, x: u32) -> u32;
+    fn double(&self, x: u32) -> u32;
+    fn quadruple(&self, x: u32) -> u32;
+}
+
+fn double(db: &dyn QueryGroup, x: u32) -> u32 {
+    N_COMPUTED.fetch_add(1, Ordering::SeqCst);
+    db.input(x) * 2
+}
+
+fn quadruple(db: &dyn QueryGroup, x: u32) -> u32 {
+    N_COMPUTED.fetch_add(1, Ordering::SeqCst);
+    db.double(x) * 2
+}
+
+#[salsa::database(QueryGroupStorage)]
+#[derive(Default)]
+struct Database {
+    storage: salsa::Storage<Self>,
+}
+
+impl salsa::Database for Database {}
+
+#[test]
+fn evict_works() {
+    let mut db = Database::default();
+    db.set_input(0, 1);
+    db.set_input(1, 2);
+    assert_eq!(db.quadruple(0), 4);
+    assert_eq!(db.quadruple(1), 8);
+    assert_eq!(N_COMPUTED.load(Ordering::SeqCst), 4);
+
+    assert!(DoubleQuery.in_db_mut(&mut db).evict(&0));
+    assert!(!DoubleQuery.in_db_mut(&mut db).evict(&0));
+    assert!(!DoubleQuery.in_db_mut(&mut db).evict(&2));
+
+    // Only the evicted value is computed again, and as it didn't change, the
+    // queries depending on it are not.
+    assert_eq!(db.double(0), 2);
+    assert_eq!(db.double(1), 4);
+    assert_eq!(db.quadruple(0), 4);
+    assert_eq!(N_COMPUTED.load(Ordering::SeqCst), 5);
+
+    // Evicted values are still computed again after input changes.
+    assert!(DoubleQuery.in_db_mut(&mut db).evict(&0));
+    db.set_input(0, 3);
+    assert_eq!(db.quadruple(0), 12);
+    assert_eq!(N_COMPUTED.load(Ordering::SeqCst), 7);
+}
@@ -346,6 +346,7 @@ pub fn kind(&self) -> LiteralKind {
 
 pub enum BlockModifier {
     Async(SyntaxToken),
+    Gen(SyntaxToken),
     Unsafe(SyntaxToken),
     Try(SyntaxToken),
     Const(SyntaxToken),
@@ -356,6 +357,7 @@ impl ast::BlockExpr {
     pub fn modifier(&self) -> Option<BlockModifier> {
         self.async_token()
             .map(BlockModifier::Async)
+            .or_else(|| self.gen_token().map(BlockModifier::Gen))
             .or_else(|| self.unsafe_token().map(BlockModifier::Unsafe))
             .or_else(|| self.try_token().map(BlockModifier::Try))
             .or_else(|| self.const_token().map(BlockModifier::Const))
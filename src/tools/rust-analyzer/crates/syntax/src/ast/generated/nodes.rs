@@ -579,6 +579,7 @@ pub fn label(&self) -> Option<Label> { support::child(&self.syntax) }
     pub fn try_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![try]) }
     pub fn unsafe_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![unsafe]) }
     pub fn async_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![async]) }
+    pub fn gen_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![gen]) }
     pub fn const_token(&self) -> Option<SyntaxToken> { support::token(&self.syntax, T![const]) }
     pub fn stmt_list(&self) -> Option<StmtList> { support::child(&self.syntax) }
 }
@@ -1473,6 +1474,15 @@ impl OrPat {
     pub fn pats(&self) -> AstChildren<Pat> { support::children(&self.syntax) }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GuardPat {
+    pub(crate) syntax: SyntaxNode,
+}
+impl GuardPat {
+    pub fn pat(&self) -> Option<Pat> { support::child(&self.syntax) }
+    pub fn guard(&self) -> Option<MatchGuard> { support::child(&self.syntax) }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ParenPat {
     pub(crate) syntax: SyntaxNode,
@@ -1692,6 +1702,7 @@ pub enum Pat {
     LiteralPat(LiteralPat),
     MacroPat(MacroPat),
     OrPat(OrPat),
+    GuardPat(GuardPat),
     ParenPat(ParenPat),
     PathPat(PathPat),
     WildcardPat(WildcardPat),
@@ -3211,6 +3222,17 @@ fn cast(syntax: SyntaxNode) -> Option<Self> {
     }
     fn syntax(&self) -> &SyntaxNode { &self.syntax }
 }
+impl AstNode for GuardPat {
+    fn can_cast(kind: SyntaxKind) -> bool { kind == GUARD_PAT }
+    fn cast(syntax: SyntaxNode) -> Option<Self> {
+        if Self::can_cast(syntax.kind()) {
+            Some(Self { syntax })
+        } else {
+            None
+        }
+    }
+    fn syntax(&self) -> &SyntaxNode { &self.syntax }
+}
 impl AstNode for ParenPat {
     fn can_cast(kind: SyntaxKind) -> bool { kind == PAREN_PAT }
     fn cast(syntax: SyntaxNode) -> Option<Self> {
@@ -3858,6 +3880,9 @@ fn from(node: MacroPat) -> Pat { Pat::MacroPat(node) }
 impl From<OrPat> for Pat {
     fn from(node: OrPat) -> Pat { Pat::OrPat(node) }
 }
+impl From<GuardPat> for Pat {
+    fn from(node: GuardPat) -> Pat { Pat::GuardPat(node) }
+}
 impl From<ParenPat> for Pat {
     fn from(node: ParenPat) -> Pat { Pat::ParenPat(node) }
 }
@@ -3898,6 +3923,7 @@ fn can_cast(kind: SyntaxKind) -> bool {
                 | LITERAL_PAT
                 | MACRO_PAT
                 | OR_PAT
+                | GUARD_PAT
                 | PAREN_PAT
                 | PATH_PAT
                 | WILDCARD_PAT
@@ -3918,6 +3944,7 @@ fn cast(syntax: SyntaxNode) -> Option<Self> {
             LITERAL_PAT => Pat::LiteralPat(LiteralPat { syntax }),
             MACRO_PAT => Pat::MacroPat(MacroPat { syntax }),
             OR_PAT => Pat::OrPat(OrPat { syntax }),
+            GUARD_PAT => Pat::GuardPat(GuardPat { syntax }),
             PAREN_PAT => Pat::ParenPat(ParenPat { syntax }),
             PATH_PAT => Pat::PathPat(PathPat { syntax }),
             WILDCARD_PAT => Pat::WildcardPat(WildcardPat { syntax }),
@@ -3940,6 +3967,7 @@ fn syntax(&self) -> &SyntaxNode {
             Pat::LiteralPat(it) => &it.syntax,
             Pat::MacroPat(it) => &it.syntax,
             Pat::OrPat(it) => &it.syntax,
+            Pat::GuardPat(it) => &it.syntax,
             Pat::ParenPat(it) => &it.syntax,
             Pat::PathPat(it) => &it.syntax,
             Pat::WildcardPat(it) => &it.syntax,
@@ -5066,6 +5094,11 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(self.syntax(), f)
     }
 }
+impl std::fmt::Display for GuardPat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(self.syntax(), f)
+    }
+}
 impl std::fmt::Display for ParenPat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         std::fmt::Display::fmt(self.syntax(), f)
@@ -3,7 +3,9 @@
 //!
 //! Note that all functions here intended to be stupid constructors, which just
 //! assemble a finish node from immediate children. If you want to do something
-//! smarter than that, it belongs to the `ext` submodule.
+//! smarter than that, it belongs to the `ext` submodule. The one exception is
+//! precedence: builders of operator expressions parenthesize their operands
+//! exactly when needed, so `expr_prefix(T![-], a as i64)` gives `-(a as i64)`.
 //!
 //! Keep in mind that `from_text` functions should be kept private. The public
 //! API should require to assemble every node piecewise. The trick of
@@ -533,7 +535,7 @@ pub fn expr_continue(label: Option<ast::Lifetime>) -> ast::Expr {
 }
 // Consider `op: SyntaxKind` instead for nicer syntax at the call-site?
 pub fn expr_bin_op(lhs: ast::Expr, op: ast::BinaryOp, rhs: ast::Expr) -> ast::Expr {
-    expr_from_text(&format!("{lhs} {op} {rhs}"))
+    expr_with_operands_from_text(&format!("({lhs}) {op} ({rhs})"))
 }
 pub fn expr_break(label: Option<ast::Lifetime>, expr: Option<ast::Expr>) -> ast::Expr {
     let mut s = String::from("break");
@@ -555,10 +557,10 @@ pub fn expr_return(expr: Option<ast::Expr>) -> ast::Expr {
     }
 }
 pub fn expr_try(expr: ast::Expr) -> ast::Expr {
-    expr_from_text(&format!("{expr}?"))
+    expr_with_operands_from_text(&format!("({expr})?"))
 }
 pub fn expr_await(expr: ast::Expr) -> ast::Expr {
-    expr_from_text(&format!("{expr}.await"))
+    expr_with_operands_from_text(&format!("({expr}).await"))
 }
 pub fn expr_match(expr: ast::Expr, match_arm_list: ast::MatchArmList) -> ast::Expr {
     expr_from_text(&format!("match {expr} {match_arm_list}"))
@@ -585,33 +587,40 @@ pub fn expr_loop(block: ast::BlockExpr) -> ast::Expr {
 
 pub fn expr_prefix(op: SyntaxKind, expr: ast::Expr) -> ast::Expr {
     let token = token(op);
-    expr_from_text(&format!("{token}{expr}"))
+    expr_with_operands_from_text(&format!("{token}({expr})"))
 }
 pub fn expr_call(f: ast::Expr, arg_list: ast::ArgList) -> ast::Expr {
-    expr_from_text(&format!("{f}{arg_list}"))
+    expr_with_operands_from_text(&format!("({f}){arg_list}"))
 }
 pub fn expr_method_call(
     receiver: ast::Expr,
     method: ast::NameRef,
     arg_list: ast::ArgList,
 ) -> ast::Expr {
-    expr_from_text(&format!("{receiver}.{method}{arg_list}"))
+    expr_with_operands_from_text(&format!("({receiver}).{method}{arg_list}"))
 }
 pub fn expr_macro_call(f: ast::Expr, arg_list: ast::ArgList) -> ast::Expr {
     expr_from_text(&format!("{f}!{arg_list}"))
 }
 pub fn expr_ref(expr: ast::Expr, exclusive: bool) -> ast::Expr {
-    expr_from_text(&if exclusive { format!("&mut {expr}") } else { format!("&{expr}") })
+    expr_with_operands_from_text(&if exclusive {
+        format!("&mut ({expr})")
+    } else {
+        format!("&({expr})")
+    })
 }
 pub fn expr_reborrow(expr: ast::Expr) -> ast::Expr {
-    expr_from_text(&format!("&mut *{expr}"))
+    expr_ref(expr_prefix(T![*], expr), true)
+}
+pub fn expr_cast(expr: ast::Expr, ty: ast::Type) -> ast::Expr {
+    expr_with_operands_from_text(&format!("({expr}) as {ty}"))
 }
 pub fn expr_closure(pats: impl IntoIterator<Item = ast::Param>, expr: ast::Expr) -> ast::Expr {
     let params = pats.into_iter().join(", ");
     expr_from_text(&format!("|{params}| {expr}"))
 }
 pub fn expr_field(receiver: ast::Expr, field: &str) -> ast::Expr {
-    expr_from_text(&format!("{receiver}.{field}"))
+    expr_with_operands_from_text(&format!("({receiver}).{field}"))
 }
 pub fn expr_paren(expr: ast::Expr) -> ast::Expr {
     expr_from_text(&format!("({expr})"))
@@ -621,11 +630,31 @@ pub fn expr_tuple(elements: impl IntoIterator<Item = ast::Expr>) -> ast::Expr {
     expr_from_text(&format!("({expr})"))
 }
 pub fn expr_assignment(lhs: ast::Expr, rhs: ast::Expr) -> ast::Expr {
-    expr_from_text(&format!("{lhs} = {rhs}"))
+    expr_with_operands_from_text(&format!("({lhs}) = ({rhs})"))
 }
 fn expr_from_text(text: &str) -> ast::Expr {
     ast_from_text(&format!("const C: () = {text};"))
 }
+/// Parses an expression whose operands are all wrapped in parentheses, and then drops the
+/// parentheses that precedence doesn't require, so that `-(a as i64)` stays as it is while
+/// `-(a)` becomes `-a`.
+fn expr_with_operands_from_text(text: &str) -> ast::Expr {
+    let expr = expr_from_text(text);
+    let text = expr
+        .syntax()
+        .children_with_tokens()
+        .map(|child| match child {
+            NodeOrToken::Node(node) => match ast::ParenExpr::cast(node.clone())
+                .and_then(|it| it.expr())
+            {
+                Some(inner) if !inner.needs_parens_in(expr.syntax().clone()) => inner.to_string(),
+                _ => node.to_string(),
+            },
+            NodeOrToken::Token(token) => token.to_string(),
+        })
+        .collect::<String>();
+    expr_from_text(&text)
+}
 pub fn expr_let(pattern: ast::Pat, expr: ast::Expr) -> ast::LetExpr {
     ast_from_text(&format!("const _: () = while let {pattern} = {expr} {{}};"))
 }
@@ -656,6 +685,10 @@ fn from_text(text: &str) -> ast::WildcardPat {
     }
 }
 
+pub fn rest_pat() -> ast::RestPat {
+    ast_from_text("fn f(S(..): ())")
+}
+
 pub fn literal_pat(lit: &str) -> ast::LiteralPat {
     return from_text(lit);
 
@@ -716,8 +749,15 @@ pub fn record_pat_with_fields(path: ast::Path, fields: ast::RecordPatFieldList)
 
 pub fn record_pat_field_list(
     fields: impl IntoIterator<Item = ast::RecordPatField>,
+    rest_pat: Option<ast::RestPat>,
 ) -> ast::RecordPatFieldList {
-    let fields = fields.into_iter().join(", ");
+    let mut fields = fields.into_iter().join(", ");
+    if let Some(rest_pat) = rest_pat {
+        if !fields.is_empty() {
+            fields.push_str(", ");
+        }
+        format_to!(fields, "{rest_pat}");
+    }
     ast_from_text(&format!("fn f(S {{ {fields} }}: ()))"))
 }
 
@@ -729,6 +769,20 @@ pub fn record_pat_field_shorthand(name_ref: ast::NameRef) -> ast::RecordPatField
     ast_from_text(&format!("fn f(S {{ {name_ref} }}: ()))"))
 }
 
+pub fn record_pat_field_shorthand_binding(pat: ast::IdentPat) -> ast::RecordPatField {
+    ast_from_text(&format!("fn f(S {{ {pat} }}: ()))"))
+}
+
+pub fn ref_pat(pat: ast::Pat, mut_: bool) -> ast::RefPat {
+    let text = match &pat {
+        // `&mut x` would be a `&mut` pattern
+        ast::Pat::IdentPat(it) if !mut_ && it.mut_token().is_some() => format!("&({it})"),
+        _ if mut_ => format!("&mut {pat}"),
+        _ => format!("&{pat}"),
+    };
+    ast_from_text(&format!("fn f({text}: ())"))
+}
+
 /// Returns a `BindPat` if the path has just one segment, a `PathPat` otherwise.
 pub fn path_pat(path: ast::Path) -> ast::Pat {
     return from_text(&path.to_string());
@@ -938,6 +992,10 @@ pub fn const_param(name: ast::Name, ty: ast::Type) -> ast::ConstParam {
     ast_from_text(&format!("fn f<const {name}: {ty}>() {{ }}"))
 }
 
+pub fn label(lifetime: ast::Lifetime) -> ast::Label {
+    ast_from_text(&format!("fn f() {{ {lifetime}: loop {{}} }}"))
+}
+
 pub fn lifetime_param(lifetime: ast::Lifetime) -> ast::LifetimeParam {
     ast_from_text(&format!("fn f<{lifetime}>() {{ }}"))
 }
@@ -2,7 +2,7 @@
 
 use crate::{
     ast::{self, BinaryOp, Expr, HasArgList, RangeItem},
-    match_ast, AstNode, SyntaxNode,
+    match_ast, AstNode, SourceFile, SyntaxNode, TextSize,
 };
 
 impl Expr {
@@ -26,6 +26,34 @@ pub fn needs_parens_in(&self, parent: SyntaxNode) -> bool {
         }
     }
 
+    /// Returns `true` if `self` would need to be wrapped in parentheses if it replaced `place`.
+    ///
+    /// Unlike [`Expr::needs_parens_in`], `self` doesn't have to be a part of the tree it is checked
+    /// against, so this can be used for expressions created by `make` or taken from elsewhere.
+    pub fn needs_parens_in_place_of(&self, place: &Expr) -> bool {
+        // Splice `(self)` into the enclosing item and check whether the parentheses are needed
+        // there. Ancestors matter as well as the parent, e.g. for `if (S {}) == s {}`.
+        let Some(item) = place.syntax().ancestors().find(|it| ast::Item::can_cast(it.kind()))
+        else {
+            return !self.is_paren_like();
+        };
+        let range = place.syntax().text_range() - item.text_range().start();
+        let mut text = item.to_string();
+        text.replace_range(std::ops::Range::<usize>::from(range), &format!("({self})"));
+
+        let file = SourceFile::parse(&text).tree();
+        let paren = file
+            .syntax()
+            .token_at_offset(range.start() + TextSize::of('('))
+            .left_biased()
+            .and_then(|token| token.parent())
+            .and_then(ast::ParenExpr::cast);
+        match paren.and_then(|paren| Some((paren.expr()?, paren.syntax().parent()?))) {
+            Some((expr, parent)) => expr.needs_parens_in(parent),
+            None => !self.is_paren_like(),
+        }
+    }
+
     fn needs_parens_in_expr(&self, parent: &Expr) -> bool {
         // Special-case block weirdness
         if parent.child_is_followed_by_a_block() {
@@ -50,6 +78,19 @@ fn needs_parens_in_expr(&self, parent: &Expr) -> bool {
             return false;
         }
 
+        // `a as u8 < b` and `a as u8 << b` parse the `<` as the start of generic arguments
+        if let (Expr::CastExpr(_), Expr::BinExpr(e)) = (self, parent) {
+            if self.is_ordered_before(parent)
+                && matches!(
+                    e.op_kind(),
+                    Some(BinaryOp::CmpOp(ast::CmpOp::Ord { ordering: ast::Ordering::Less, .. }))
+                        | Some(BinaryOp::ArithOp(ast::ArithOp::Shl))
+                )
+            {
+                return true;
+            }
+        }
+
         if self.is_paren_like()
             || parent.is_paren_like()
             || self.is_prefix() && (parent.is_prefix() || !self.is_ordered_before(parent))
@@ -82,6 +123,10 @@ fn needs_parens_in_stmt(&self, stmt: Option<&ast::Stmt>) -> bool {
                 CallExpr(e) => e.expr(),
                 CastExpr(e) => e.expr(),
                 IndexExpr(e) => e.base(),
+                MethodCallExpr(e) => e.receiver(),
+                FieldExpr(e) => e.expr(),
+                TryExpr(e) => e.expr(),
+                AwaitExpr(e) => e.expr(),
                 _ => break,
             };
 
@@ -6,7 +6,7 @@
 
 use text_edit::Indel;
 
-use crate::{validation, AstNode, SourceFile, TextRange};
+use crate::{validation, AstNode, Edition, SourceFile, TextRange};
 
 fn check_file_invariants(file: &SourceFile) {
     let root = file.syntax();
@@ -49,7 +49,7 @@ pub fn from_data(data: &[u8]) -> Option<Self> {
     #[allow(clippy::print_stderr)]
     pub fn run(&self) {
         let parse = SourceFile::parse(&self.text);
-        let new_parse = parse.reparse(&self.edit);
+        let new_parse = parse.reparse(&self.edit, Edition::CURRENT);
         check_file_invariants(&new_parse.tree());
         assert_eq!(&new_parse.tree().syntax().text().to_string(), &self.edited_text);
         let full_reparse = SourceFile::parse(&self.edited_text);
@@ -60,7 +60,7 @@
     },
     token_text::TokenText,
 };
-pub use parser::{SyntaxKind, T};
+pub use parser::{Edition, SyntaxKind, T};
 pub use rowan::{
     api::Preorder, Direction, GreenNode, NodeOrToken, SyntaxText, TextRange, TextSize,
     TokenAtOffset, WalkEvent,
@@ -138,16 +138,18 @@ pub fn debug_dump(&self) -> String {
         buf
     }
 
-    pub fn reparse(&self, indel: &Indel) -> Parse<SourceFile> {
-        self.incremental_reparse(indel).unwrap_or_else(|| self.full_reparse(indel))
+    pub fn reparse(&self, indel: &Indel, edition: Edition) -> Parse<SourceFile> {
+        self.incremental_reparse(indel, edition)
+            .unwrap_or_else(|| self.full_reparse(indel, edition))
     }
 
-    fn incremental_reparse(&self, indel: &Indel) -> Option<Parse<SourceFile>> {
+    fn incremental_reparse(&self, indel: &Indel, edition: Edition) -> Option<Parse<SourceFile>> {
         // FIXME: validation errors are not handled here
         parsing::incremental_reparse(
             self.tree().syntax(),
             indel,
             self.errors.as_deref().unwrap_or_default().iter().cloned(),
+            edition,
         )
         .map(|(green_node, errors, _reparsed_range)| Parse {
             green: green_node,
@@ -156,10 +158,10 @@ fn incremental_reparse(&self, indel: &Indel) -> Option<Parse<SourceFile>> {
         })
     }
 
-    fn full_reparse(&self, indel: &Indel) -> Parse<SourceFile> {
+    fn full_reparse(&self, indel: &Indel, edition: Edition) -> Parse<SourceFile> {
         let mut text = self.tree().syntax().text().to_string();
         indel.apply(&mut text);
-        SourceFile::parse(&text)
+        SourceFile::parse_with_edition(&text, edition)
     }
 }
 
@@ -168,7 +170,13 @@ fn full_reparse(&self, indel: &Indel) -> Parse<SourceFile> {
 
 impl SourceFile {
     pub fn parse(text: &str) -> Parse<SourceFile> {
-        let (green, mut errors) = parsing::parse_text(text);
+        SourceFile::parse_with_edition(text, Edition::CURRENT)
+    }
+
+    /// Parses `text` as a file of a crate using `edition`, which decides e.g. whether `gen {}` is a
+    /// `gen` block or a record expression.
+    pub fn parse_with_edition(text: &str, edition: Edition) -> Parse<SourceFile> {
+        let (green, mut errors) = parsing::parse_text(text, edition);
         let root = SyntaxNode::new_root(green.clone());
 
         errors.extend(validation::validate(&root));
@@ -217,7 +225,9 @@ pub fn reparse_as_comma_separated_expr(self) -> Parse<ast::MacroEagerInput> {
             }
         }
 
-        let parser_output = parser::TopEntryPoint::MacroEagerInput.parse(&parser_input);
+        // FIXME: Use the edition of the macro call's crate.
+        let parser_output =
+            parser::TopEntryPoint::MacroEagerInput.parse(&parser_input, Edition::CURRENT);
 
         let mut tokens =
             self.syntax().descendants_with_tokens().filter_map(NodeOrToken::into_token);
@@ -83,6 +83,7 @@ pub(crate) struct KindsSrc<'a> {
         "offset_of",
         "asm",
         "format_args",
+        "gen",
     ],
     literals: &["INT_NUMBER", "FLOAT_NUMBER", "CHAR", "BYTE", "STRING", "BYTE_STRING", "C_STRING"],
     tokens: &["ERROR", "IDENT", "WHITESPACE", "LIFETIME_IDENT", "COMMENT", "SHEBANG"],
@@ -122,6 +123,7 @@ pub(crate) struct KindsSrc<'a> {
         "IMPL_TRAIT_TYPE",
         "DYN_TRAIT_TYPE",
         "OR_PAT",
+        "GUARD_PAT",
         "PAREN_PAT",
         "REF_PAT",
         "BOX_PAT",
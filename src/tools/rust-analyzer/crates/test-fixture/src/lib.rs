@@ -333,7 +333,7 @@ pub fn parse_with_proc_macros(
     }
 }
 
-fn default_test_proc_macros() -> [(String, ProcMacro); 5] {
+fn default_test_proc_macros() -> [(String, ProcMacro); 7] {
     [
         (
             r#"
@@ -410,6 +410,36 @@ pub fn shorten(input: TokenStream) -> TokenStream {
                 disabled: false,
             },
         ),
+        (
+            r#"
+#[proc_macro_attribute]
+pub fn wrap_body(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+"#
+            .into(),
+            ProcMacro {
+                name: "wrap_body".into(),
+                kind: ProcMacroKind::Attr,
+                expander: sync::Arc::new(WrapBodyProcMacroExpander),
+                disabled: false,
+            },
+        ),
+        (
+            r#"
+#[proc_macro_attribute]
+pub fn reject_incomplete(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+"#
+            .into(),
+            ProcMacro {
+                name: "reject_incomplete".into(),
+                kind: ProcMacroKind::Attr,
+                expander: sync::Arc::new(RejectIncompleteProcMacroExpander),
+                disabled: false,
+            },
+        ),
     ]
 }
 
@@ -626,3 +656,75 @@ fn modify_leaf(leaf: &Leaf<Span>) -> Leaf<Span> {
         }
     }
 }
+
+// Wraps the body of the annotated function into a block that first runs `match 0u8 {}`, made of
+// tokens with the call-site span. Useful for testing how we treat code generated by attribute
+// macros next to the user-written code they pass through.
+#[derive(Debug)]
+struct WrapBodyProcMacroExpander;
+impl ProcMacroExpander for WrapBodyProcMacroExpander {
+    fn expand(
+        &self,
+        item: &Subtree<Span>,
+        _: Option<&Subtree<Span>>,
+        _: &Env,
+        _: Span,
+        call_site: Span,
+        _: Span,
+    ) -> Result<Subtree<Span>, ProcMacroExpansionError> {
+        let mut token_trees = item.token_trees.to_vec();
+        let Some(TokenTree::Subtree(body)) = token_trees.pop() else {
+            return Err(ProcMacroExpansionError::Panic("Expected a function".into()));
+        };
+        let braces = |token_trees: Vec<TokenTree<Span>>| {
+            TokenTree::Subtree(Subtree {
+                delimiter: tt::Delimiter {
+                    open: call_site,
+                    close: call_site,
+                    kind: tt::DelimiterKind::Brace,
+                },
+                token_trees: token_trees.into_boxed_slice(),
+            })
+        };
+        token_trees.push(braces(vec![
+            TokenTree::Leaf(Leaf::Ident(tt::Ident::new("match", call_site))),
+            TokenTree::Leaf(Leaf::Literal(tt::Literal { text: "0u8".into(), span: call_site })),
+            braces(vec![]),
+            TokenTree::Leaf(Leaf::Punct(tt::Punct {
+                char: ';',
+                spacing: tt::Spacing::Alone,
+                span: call_site,
+            })),
+            TokenTree::Subtree(body),
+        ]));
+        Ok(Subtree { delimiter: item.delimiter, token_trees: token_trees.into_boxed_slice() })
+    }
+}
+
+// Panics if the item needed syntax fixups to be passed to the macro, and returns it unchanged
+// otherwise. Useful for testing attribute macros that fail to parse code that is still being typed.
+#[derive(Debug)]
+struct RejectIncompleteProcMacroExpander;
+impl ProcMacroExpander for RejectIncompleteProcMacroExpander {
+    fn expand(
+        &self,
+        item: &Subtree<Span>,
+        _: Option<&Subtree<Span>>,
+        _: &Env,
+        _: Span,
+        _: Span,
+        _: Span,
+    ) -> Result<Subtree<Span>, ProcMacroExpansionError> {
+        fn is_incomplete(subtree: &Subtree<Span>) -> bool {
+            subtree.token_trees.iter().any(|tt| match tt {
+                TokenTree::Leaf(Leaf::Ident(ident)) => ident.text == "__ra_fixup",
+                TokenTree::Leaf(_) => false,
+                TokenTree::Subtree(subtree) => is_incomplete(subtree),
+            })
+        }
+        if is_incomplete(item) {
+            return Err(ProcMacroExpansionError::Panic("expected an expression".into()));
+        }
+        Ok(item.clone())
+    }
+}
@@ -12,6 +12,7 @@
 mod assert_linear;
 pub mod bench_fixture;
 mod fixture;
+mod temp_dir;
 
 use std::{
     collections::BTreeMap,
@@ -29,6 +30,7 @@
 pub use crate::{
     assert_linear::AssertLinear,
     fixture::{Fixture, FixtureWithProjectMeta, MiniCore},
+    temp_dir::TempDir,
 };
 
 pub const CURSOR_MARKER: &str = "$0";
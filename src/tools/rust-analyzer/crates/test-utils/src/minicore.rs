@@ -24,6 +24,7 @@
 //!     deref: sized
 //!     derive:
 //!     discriminant:
+//!     dispatch_from_dyn: unsize, pin
 //!     drop:
 //!     env: option
 //!     eq: sized
@@ -443,6 +444,21 @@ impl<T: ?Sized + Unsize<U>, U: ?Sized> CoerceUnsized<*const U> for *const T {}
     pub use self::unsize::CoerceUnsized;
     // endregion:coerce_unsized
 
+    // region:dispatch_from_dyn
+    mod dispatch_from_dyn {
+        use crate::marker::Unsize;
+
+        #[lang = "dispatch_from_dyn"]
+        pub trait DispatchFromDyn<T> {}
+
+        impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<&'a U> for &'a T {}
+        impl<'a, T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<&'a mut U> for &'a mut T {}
+        impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<*const U> for *const T {}
+        impl<T: ?Sized + Unsize<U>, U: ?Sized> DispatchFromDyn<*mut U> for *mut T {}
+    }
+    pub use self::dispatch_from_dyn::DispatchFromDyn;
+    // endregion:dispatch_from_dyn
+
     // region:deref
     mod deref {
         #[lang = "deref"]
@@ -913,6 +929,7 @@ pub trait Display {
     }
 
     mod rt {
+        use super::{Display, Formatter, Result};
 
         extern "C" {
             type Opaque;
@@ -1158,6 +1175,9 @@ pub fn new(_pointer: P) -> Pin<P> {
             loop {}
         }
     }
+    // region:dispatch_from_dyn
+    impl<Ptr: crate::ops::DispatchFromDyn<U>, U> crate::ops::DispatchFromDyn<Pin<U>> for Pin<Ptr> {}
+    // endregion:dispatch_from_dyn
     // region:deref
     impl<P: crate::ops::Deref> crate::ops::Deref for Pin<P> {
         type Target = P::Target;
@@ -0,0 +1,41 @@
+//! Temporary directories for tests that need to write to the file system, removed with their
+//! contents when dropped, also when the test fails.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// Creates a new, empty directory whose name starts with `ra-{prefix}`.
+    pub fn new(prefix: &str) -> TempDir {
+        static CNT: AtomicUsize = AtomicUsize::new(0);
+        let pid = std::process::id();
+        for _ in 0..100 {
+            let cnt = CNT.fetch_add(1, Ordering::Relaxed);
+            let path = std::env::temp_dir().join(format!("ra-{prefix}-{pid}-{cnt}"));
+            if path.exists() {
+                continue;
+            }
+            fs::create_dir_all(&path).unwrap();
+            return TempDir { path };
+        }
+        panic!("failed to create a temporary directory")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        // Don't panic here, that would abort the test run if we're dropped while unwinding.
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
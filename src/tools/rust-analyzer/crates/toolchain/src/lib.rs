@@ -78,7 +78,7 @@ fn get_path_for_executable(executable_name: &'static str) -> PathBuf {
         return executable_name.into();
     }
 
-    if let Some(mut path) = get_cargo_home() {
+    if let Some(mut path) = cargo_home() {
         path.push("bin");
         path.push(executable_name);
         if let Some(path) = probe_for_binary(path) {
@@ -94,7 +94,8 @@ fn lookup_in_path(exec: &str) -> bool {
     env::split_paths(&paths).map(|path| path.join(exec)).find_map(probe_for_binary).is_some()
 }
 
-fn get_cargo_home() -> Option<PathBuf> {
+/// Returns cargo's home directory, where it keeps installed binaries and the registry caches.
+pub fn cargo_home() -> Option<PathBuf> {
     if let Some(path) = env::var_os("CARGO_HOME") {
         return Some(path.into());
     }
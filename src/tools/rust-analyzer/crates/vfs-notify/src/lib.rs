@@ -202,11 +202,15 @@ fn load_entry(
                     self.send(make_message(root.clone()));
                     let walkdir =
                         WalkDir::new(root).follow_links(true).into_iter().filter_entry(|entry| {
-                            if !entry.file_type().is_dir() {
+                            let path = AbsPath::assert(entry.path());
+                            if root == path {
                                 return true;
                             }
-                            let path = AbsPath::assert(entry.path());
-                            root == path
+                            // Parents were matched already, so only `path` itself can match here.
+                            if dirs.is_excluded_by_glob(root, path) {
+                                return false;
+                            }
+                            !entry.file_type().is_dir()
                                 || dirs.exclude.iter().chain(&dirs.include).all(|it| it != path)
                         });
 
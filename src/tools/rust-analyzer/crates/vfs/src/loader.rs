@@ -18,6 +18,7 @@ pub enum Entry {
 ///   * it has included extension
 ///   * it is under an `include` path
 ///   * it is not under `exclude` path
+///   * it doesn't match any of `exclude_globs`, and isn't under a directory that does
 ///
 /// If many include/exclude paths match, the longest one wins.
 ///
@@ -27,6 +28,10 @@ pub struct Directories {
     pub extensions: Vec<String>,
     pub include: Vec<AbsPathBuf>,
     pub exclude: Vec<AbsPathBuf>,
+    /// Globs like `**/buck-out`, matched against paths relative to the `include` path they are
+    /// under. `**` matches any number of components, `*` and `?` match any number of characters
+    /// and a single character within one component.
+    pub exclude_globs: Vec<String>,
 }
 
 /// [`Handle`]'s configuration.
@@ -188,6 +193,52 @@ fn includes_path(&self, path: &AbsPath) -> bool {
         };
 
         !self.exclude.iter().any(|excl| path.starts_with(excl) && excl.starts_with(include))
+            && !self.is_excluded_by_glob(include, path)
+    }
+
+    /// Returns `true` if `path` or one of its parents below `include` matches one of
+    /// `self.exclude_globs`.
+    pub fn is_excluded_by_glob(&self, include: &AbsPath, path: &AbsPath) -> bool {
+        if self.exclude_globs.is_empty() {
+            return false;
+        }
+        let Some(path) = path.strip_prefix(include) else { return false };
+        let components: Vec<_> = AsRef::<std::path::Path>::as_ref(path)
+            .components()
+            .map(|it| it.as_os_str().to_string_lossy())
+            .collect();
+        let components: Vec<&str> = components.iter().map(|it| &**it).collect();
+        self.exclude_globs.iter().any(|glob| {
+            let glob: Vec<&str> = glob.split('/').collect();
+            (1..=components.len()).any(|len| glob_matches(&glob, &components[..len]))
+        })
+    }
+}
+
+/// Matches a relative path against a glob, both split into components.
+fn glob_matches(glob: &[&str], path: &[&str]) -> bool {
+    match (glob.split_first(), path.split_first()) {
+        (None, None) => true,
+        (Some((&"**", rest)), _) => {
+            glob_matches(rest, path) || (!path.is_empty() && glob_matches(glob, &path[1..]))
+        }
+        (Some((pat, glob_rest)), Some((component, path_rest))) => {
+            component_matches(pat.as_bytes(), component.as_bytes())
+                && glob_matches(glob_rest, path_rest)
+        }
+        _ => false,
+    }
+}
+
+fn component_matches(pat: &[u8], s: &[u8]) -> bool {
+    match (pat.split_first(), s.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            component_matches(rest, s) || (!s.is_empty() && component_matches(pat, &s[1..]))
+        }
+        (Some((b'?', pat_rest)), Some((_, s_rest))) => component_matches(pat_rest, s_rest),
+        (Some((p, pat_rest)), Some((c, s_rest))) => p == c && component_matches(pat_rest, s_rest),
+        _ => false,
     }
 }
 
@@ -201,7 +252,12 @@ fn includes_path(&self, path: &AbsPath) -> bool {
 /// ```
 fn dirs(base: AbsPathBuf, exclude: &[&str]) -> Directories {
     let exclude = exclude.iter().map(|it| base.join(it)).collect::<Vec<_>>();
-    Directories { extensions: vec!["rs".to_owned()], include: vec![base], exclude }
+    Directories {
+        extensions: vec!["rs".to_owned()],
+        include: vec![base],
+        exclude,
+        exclude_globs: Vec::new(),
+    }
 }
 
 impl fmt::Debug for Message {
@@ -228,3 +284,38 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 fn handle_is_object_safe() {
     fn _assert(_: &dyn Handle) {}
 }
+
+#[test]
+fn exclude_glob_matching() {
+    let matches = |glob: &str, path: &str| {
+        glob_matches(&glob.split('/').collect::<Vec<_>>(), &path.split('/').collect::<Vec<_>>())
+    };
+    assert!(matches("target", "target"));
+    assert!(!matches("target", "src/target"));
+    assert!(matches("**/target", "target"));
+    assert!(matches("**/target", "src/nested/target"));
+    assert!(matches("src/*-out", "src/buck-out"));
+    assert!(!matches("src/*-out", "src/nested/buck-out"));
+    assert!(matches("gen?", "gen2"));
+    assert!(!matches("gen?", "gen"));
+    assert!(matches("**/testdata/**", "a/testdata/b/c"));
+}
+
+#[test]
+fn exclude_globs() {
+    let root = if cfg!(windows) { "C:\\root" } else { "/root" };
+    let path = |it: &str| AbsPathBuf::assert(std::path::Path::new(root).join(it));
+    let dirs = Directories {
+        extensions: vec!["rs".to_owned()],
+        include: vec![path(""), path("gen/kept")],
+        exclude: Vec::new(),
+        exclude_globs: vec!["**/buck-out".to_owned(), "gen".to_owned()],
+    };
+    assert!(dirs.contains_file(&path("src/lib.rs")));
+    assert!(!dirs.contains_dir(&path("buck-out")));
+    assert!(!dirs.contains_file(&path("src/buck-out/gen/lib.rs")));
+    assert!(!dirs.contains_file(&path("gen/lib.rs")));
+    // Globs are relative to the include path the file is under.
+    assert!(dirs.contains_file(&path("gen/kept/lib.rs")));
+    assert!(dirs.contains_file(&path("src/gen/lib.rs")));
+}
@@ -374,6 +374,7 @@ export async function createClient(
     // To turn on all proposed features use: client.registerProposedFeatures();
     client.registerFeature(new ExperimentalFeatures());
     client.registerFeature(new OverrideFeatures());
+    client.registerFeature(new CargoTomlHoverFeature(client));
 
     return client;
 }
@@ -429,6 +430,47 @@ class OverrideFeatures implements lc.StaticFeature {
     dispose(): void {}
 }
 
+// Hovers on dependencies in `Cargo.toml` files show their crates.io metadata. The manifests are
+// only selected for hovers, the other requests of the document selector are for Rust files only.
+class CargoTomlHoverFeature implements lc.StaticFeature {
+    private disposable: vscode.Disposable | undefined;
+
+    constructor(private readonly client: lc.BaseLanguageClient) {}
+
+    getState(): lc.FeatureState {
+        return { kind: "static" };
+    }
+    fillClientCapabilities(_capabilities: lc.ClientCapabilities): void {}
+    initialize(
+        _capabilities: lc.ServerCapabilities,
+        _documentSelector: lc.DocumentSelector | undefined,
+    ): void {
+        const client = this.client;
+        const c2p = client.code2ProtocolConverter;
+        this.disposable = vscode.languages.registerHoverProvider(
+            { scheme: "file", pattern: "**/Cargo.toml" },
+            {
+                provideHover(document, position, token) {
+                    const params = {
+                        textDocument: c2p.asTextDocumentIdentifier(document),
+                        position: c2p.asPosition(position),
+                    };
+                    return client.sendRequest(ra.hover, params, token).then(
+                        (result) => (result ? client.protocol2CodeConverter.asHover(result) : null),
+                        (error) => {
+                            client.handleFailedRequest(lc.HoverRequest.type, token, error, null);
+                            return null;
+                        },
+                    );
+                },
+            },
+        );
+    }
+    dispose(): void {
+        this.disposable?.dispose();
+    }
+}
+
 function isCodeActionWithoutEditsAndCommands(value: any): boolean {
     const candidate: lc.CodeAction = value;
     return (
@@ -97,6 +97,12 @@ export function shuffleCrateGraph(ctx: CtxInit): Cmd {
     };
 }
 
+export function resetQueryStats(ctx: CtxInit): Cmd {
+    return async () => {
+        return ctx.client.sendRequest(ra.resetQueryStats);
+    };
+}
+
 export function triggerParameterHints(_: CtxInit): Cmd {
     return async () => {
         const parameterHintsEnabled = vscode.workspace
@@ -607,6 +613,68 @@ export function interpretFunction(ctx: CtxInit): Cmd {
     );
 }
 
+// Evaluates the selected expression with the MIR interpreter, after asking for the values of the
+// variables it uses from outside of it, and shows the result after the selection
+export function evaluateSelection(ctx: CtxInit): Cmd {
+    const decorationType = vscode.window.createTextEditorDecorationType({
+        after: {
+            color: new vscode.ThemeColor("editorCodeLens.foreground"),
+            fontStyle: "italic",
+            margin: "0 0 0 1em",
+        },
+    });
+    ctx.pushExtCleanup(decorationType);
+    vscode.workspace.onDidChangeTextDocument(
+        (event) => {
+            for (const editor of vscode.window.visibleTextEditors) {
+                if (editor.document === event.document) {
+                    editor.setDecorations(decorationType, []);
+                }
+            }
+        },
+        null,
+        ctx.subscriptions,
+    );
+
+    return async () => {
+        const editor = ctx.activeRustEditor;
+        if (!editor) return;
+
+        const client = ctx.client;
+        const params: ra.EvaluateSelectionParams = {
+            textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
+            range: client.code2ProtocolConverter.asRange(editor.selection),
+            values: {},
+        };
+        for (;;) {
+            const result = await client.sendRequest(ra.evaluateSelection, params);
+            if (!result) {
+                await vscode.window.showInformationMessage("No expression is selected");
+                return;
+            }
+            if (result.error) {
+                await vscode.window.showErrorMessage(result.error);
+                return;
+            }
+            if (result.value) {
+                const range = new vscode.Range(editor.selection.end, editor.selection.end);
+                editor.setDecorations(decorationType, [
+                    { range, renderOptions: { after: { contentText: `= ${result.value}` } } },
+                ]);
+                return;
+            }
+            if (result.missingValues.length === 0) return;
+            for (const { name, ty } of result.missingValues) {
+                const value = await vscode.window.showInputBox({
+                    prompt: `Enter the value of \`${name}: ${ty}\`, like \`42\`, \`-1\`, \`true\` or \`'a'\``,
+                });
+                if (value === undefined) return;
+                params.values[name] = value;
+            }
+        }
+    };
+}
+
 export function viewFileText(ctx: CtxInit): Cmd {
     const tdcp = new (class implements vscode.TextDocumentContentProvider {
         readonly uri = vscode.Uri.parse("rust-analyzer-file-text://viewFileText/file.rs");
@@ -1100,9 +1168,9 @@ export function copyRunCommandLine(ctx: CtxInit) {
         const item = await selectRunnable(ctx, prevRunnable);
         if (!item) return;
         const args = createArgs(item.runnable);
-        const commandLine = ["cargo", ...args].join(" ");
+        const commandLine = (item.runnable.kind === "cargo" ? ["cargo", ...args] : args).join(" ");
         await vscode.env.clipboard.writeText(commandLine);
-        await vscode.window.showInformationMessage("Cargo invocation copied to the clipboard.");
+        await vscode.window.showInformationMessage("Run command line copied to the clipboard.");
     };
 }
 
@@ -1144,6 +1212,57 @@ export function linkToCommand(_: Ctx): Cmd {
     };
 }
 
+export function explainType(ctx: CtxInit): Cmd {
+    type NodeItem = vscode.QuickPickItem & { index?: number };
+
+    return async () => {
+        const editor = ctx.activeRustEditor;
+        if (!editor) return;
+        const client = ctx.client;
+
+        const explained = await client.sendRequest(ra.explainType, {
+            textDocument: client.code2ProtocolConverter.asTextDocumentIdentifier(editor.document),
+            position: client.code2ProtocolConverter.asPosition(editor.selection.active),
+        });
+        if (!explained) {
+            await vscode.window.showInformationMessage("No type at the cursor");
+            return;
+        }
+
+        // Walk the tree one level at a time, picking a child expands it.
+        let current = 0;
+        for (;;) {
+            const node = unwrapUndefinable(explained.nodes[current]);
+            const items: NodeItem[] = node.children.map((index) => {
+                const child = unwrapUndefinable(explained.nodes[index]);
+                return { label: child.label, description: child.kind, index };
+            });
+            if (node.parent !== undefined) {
+                items.unshift({ label: "$(arrow-up) ..", index: node.parent });
+            }
+            if (node.location) {
+                items.unshift({ label: "$(go-to-file) Go to Definition" });
+            }
+            const picked = await vscode.window.showQuickPick(items, {
+                title: node.label,
+                placeHolder: node.kind,
+            });
+            if (!picked) return;
+            if (picked.index !== undefined) {
+                current = picked.index;
+                continue;
+            }
+            if (node.location) {
+                const location = client.protocol2CodeConverter.asLocation(node.location);
+                await vscode.window.showTextDocument(location.uri, {
+                    selection: location.range,
+                });
+            }
+            return;
+        }
+    };
+}
+
 export function viewMemoryLayout(ctx: CtxInit): Cmd {
     return async () => {
         const editor = vscode.window.activeTextEditor;
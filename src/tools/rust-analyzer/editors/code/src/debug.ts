@@ -10,7 +10,7 @@ import { unwrapUndefinable } from "./undefinable";
 
 const debugOutput = vscode.window.createOutputChannel("Debug");
 type DebugConfigProvider = (
-    config: ra.Runnable,
+    config: ra.CargoRunnable,
     executable: string,
     cargoWorkspace: string,
     env: Record<string, string>,
@@ -80,6 +80,13 @@ async function getDebugConfiguration(
     const editor = ctx.activeRustEditor;
     if (!editor) return;
 
+    if (runnable.kind !== "cargo") {
+        await vscode.window.showErrorMessage(
+            `Debugging is only supported for cargo runnables, '${runnable.label}' is not one.`,
+        );
+        return;
+    }
+
     const knownEngines: Record<string, DebugConfigProvider> = {
         "vadimcn.vscode-lldb": getLldbDebugConfig,
         "ms-vscode.cpptools": getCppvsDebugConfig,
@@ -174,7 +181,7 @@ async function getDebugConfiguration(
 }
 
 async function getDebugExecutableInfo(
-    runnable: ra.Runnable,
+    runnable: ra.CargoRunnable,
     env: Record<string, string>,
 ): Promise<ExecutableInfo> {
     const cargo = new Cargo(runnable.args.workspaceRoot || ".", debugOutput, env);
@@ -185,7 +192,7 @@ async function getDebugExecutableInfo(
 }
 
 function getLldbDebugConfig(
-    runnable: ra.Runnable,
+    runnable: ra.CargoRunnable,
     executable: string,
     cargoWorkspace: string,
     env: Record<string, string>,
@@ -205,7 +212,7 @@ function getLldbDebugConfig(
 }
 
 function getCppvsDebugConfig(
-    runnable: ra.Runnable,
+    runnable: ra.CargoRunnable,
     executable: string,
     cargoWorkspace: string,
     env: Record<string, string>,
@@ -46,6 +46,7 @@ export const runFlycheck = new lc.NotificationType<{
     textDocument: lc.TextDocumentIdentifier | null;
 }>("rust-analyzer/runFlycheck");
 export const shuffleCrateGraph = new lc.RequestType0<null, void>("rust-analyzer/shuffleCrateGraph");
+export const resetQueryStats = new lc.RequestType0<null, void>("rust-analyzer/resetQueryStats");
 export const syntaxTree = new lc.RequestType<SyntaxTreeParams, string, void>(
     "rust-analyzer/syntaxTree",
 );
@@ -64,6 +65,11 @@ export const viewMir = new lc.RequestType<lc.TextDocumentPositionParams, string,
 export const interpretFunction = new lc.RequestType<lc.TextDocumentPositionParams, string, void>(
     "rust-analyzer/interpretFunction",
 );
+export const evaluateSelection = new lc.RequestType<
+    EvaluateSelectionParams,
+    EvaluateSelectionResult | null,
+    void
+>("rust-analyzer/evaluateSelection");
 export const viewItemTree = new lc.RequestType<ViewItemTreeParams, string, void>(
     "rust-analyzer/viewItemTree",
 );
@@ -115,8 +121,23 @@ export type SyntaxTreeParams = {
     textDocument: lc.TextDocumentIdentifier;
     range: lc.Range | null;
 };
-export type ViewCrateGraphParams = { full: boolean };
+export type ViewCrateGraphParams = {
+    full: boolean;
+    format?: "dot" | "json";
+    transitiveFrom?: lc.TextDocumentIdentifier;
+    metrics?: boolean;
+};
 export type ViewItemTreeParams = { textDocument: lc.TextDocumentIdentifier };
+export type EvaluateSelectionParams = {
+    textDocument: lc.TextDocumentIdentifier;
+    range: lc.Range;
+    values: Record<string, string>;
+};
+export type EvaluateSelectionResult = {
+    missingValues: { name: string; ty: string }[];
+    value?: string;
+    error?: string;
+};
 
 // experimental extensions
 
@@ -154,6 +175,11 @@ export const serverStatus = new lc.NotificationType<ServerStatusParams>(
     "experimental/serverStatus",
 );
 export const ssr = new lc.RequestType<SsrParams, lc.WorkspaceEdit, void>("experimental/ssr");
+export const explainType = new lc.RequestType<
+    lc.TextDocumentPositionParams,
+    ExplainedType | null,
+    void
+>("rust-analyzer/explainType");
 export const viewRecursiveMemoryLayout = new lc.RequestType<
     lc.TextDocumentPositionParams,
     RecursiveMemoryLayout | null,
@@ -177,7 +203,8 @@ export type Direction = "Up" | "Down";
 export type OpenCargoTomlParams = {
     textDocument: lc.TextDocumentIdentifier;
 };
-export type Runnable = {
+export type Runnable = CargoRunnable | ShellRunnable;
+export type CargoRunnable = {
     label: string;
     location?: lc.LocationLink;
     kind: "cargo";
@@ -190,6 +217,17 @@ export type Runnable = {
         overrideCargo?: string;
     };
 };
+export type ShellRunnable = {
+    label: string;
+    location?: lc.LocationLink;
+    kind: "shell";
+    args: {
+        program: string;
+        args: string[];
+        cwd: string;
+        testId?: string;
+    };
+};
 export type RunnablesParams = {
     textDocument: lc.TextDocumentIdentifier;
     position: lc.Position | null;
@@ -221,6 +259,17 @@ export type RecursiveMemoryLayout = {
     nodes: RecursiveMemoryLayoutNode[];
 };
 
+export type ExplainedTypeNode = {
+    label: string;
+    kind: "type" | "alias" | "normalized" | "hiddenType";
+    location?: lc.Location;
+    parent?: number;
+    children: number[];
+};
+export type ExplainedType = {
+    nodes: ExplainedTypeNode[];
+};
+
 export const unindexedProject = new lc.NotificationType<UnindexedProjectParams>(
     "rust-analyzer/unindexedProject",
 );
@@ -72,7 +72,7 @@ export function prepareEnv(
 ): Record<string, string> {
     const env: Record<string, string> = { RUST_BACKTRACE: "short" };
 
-    if (runnable.args.expectTest) {
+    if (runnable.kind === "cargo" && runnable.args.expectTest) {
         env["UPDATE_EXPECT"] = "1";
     }
 
@@ -104,11 +104,29 @@ export function prepareEnv(
 }
 
 export async function createTask(runnable: ra.Runnable, config: Config): Promise<vscode.Task> {
-    if (runnable.kind !== "cargo") {
-        // rust-analyzer supports only one kind, "cargo"
-        // do not use tasks.TASK_TYPE here, these are completely different meanings.
-
-        throw `Unexpected runnable kind: ${runnable.kind}`;
+    if (runnable.kind === "shell") {
+        // Runnables of projects that aren't built with cargo, as given by `rust-project.json`.
+        const definition: tasks.ShellTaskDefinition = {
+            type: tasks.SHELL_TASK_TYPE,
+            program: runnable.args.program,
+            args: createShellArgs(runnable),
+            cwd: runnable.args.cwd,
+            env: prepareEnv(runnable, config.runnablesExtraEnv),
+        };
+        const exec = new vscode.ProcessExecution(definition.program, definition.args, definition);
+        // eslint-disable-next-line @typescript-eslint/no-unnecessary-type-assertion
+        const target = vscode.workspace.workspaceFolders![0]; // safe, see main activate()
+        const shellTask = new vscode.Task(
+            definition,
+            target,
+            runnable.label,
+            tasks.TASK_SOURCE,
+            exec,
+            config.problemMatcher,
+        );
+        shellTask.presentationOptions.clear = true;
+        shellTask.presentationOptions.focus = false;
+        return shellTask;
     }
 
     const args = createArgs(runnable);
@@ -143,6 +161,9 @@ export async function createTask(runnable: ra.Runnable, config: Config): Promise
 }
 
 export function createArgs(runnable: ra.Runnable): string[] {
+    if (runnable.kind === "shell") {
+        return [runnable.args.program, ...createShellArgs(runnable)];
+    }
     const args = [...runnable.args.cargoArgs]; // should be a copy!
     if (runnable.args.cargoExtraArgs) {
         args.push(...runnable.args.cargoExtraArgs); // Append user-specified cargo options.
@@ -153,6 +174,19 @@ export function createArgs(runnable: ra.Runnable): string[] {
     return args;
 }
 
+// Fills in the `{test_id}` placeholders of the arguments to a `rust-project.json` command.
+function createShellArgs(runnable: ra.ShellRunnable): string[] {
+    const placeholder = "{test_id}";
+    const testId = runnable.args.testId;
+    if (testId === undefined) {
+        // Running a binary or all tests of a crate.
+        return runnable.args.args
+            .filter((arg) => arg !== placeholder)
+            .map((arg) => arg.replaceAll(placeholder, ""));
+    }
+    return runnable.args.args.map((arg) => arg.replaceAll(placeholder, testId));
+}
+
 async function getRunnables(
     client: LanguageClient,
     editor: RustEditor,
@@ -8,6 +8,9 @@ import { unwrapUndefinable } from "./undefinable";
 // our configuration should be compatible with it so use the same key.
 export const TASK_TYPE = "cargo";
 export const TASK_SOURCE = "rust";
+// The `type` of tasks running the commands `rust-project.json` gives for its crates, which
+// aren't cargo invocations.
+export const SHELL_TASK_TYPE = "rust-analyzer-shell";
 
 export interface CargoTaskDefinition extends vscode.TaskDefinition {
     command?: string;
@@ -17,6 +20,13 @@ export interface CargoTaskDefinition extends vscode.TaskDefinition {
     overrideCargo?: string;
 }
 
+export interface ShellTaskDefinition extends vscode.TaskDefinition {
+    program: string;
+    args: string[];
+    cwd: string;
+    env?: { [key: string]: string };
+}
+
 class CargoTaskProvider implements vscode.TaskProvider {
     private readonly config: Config;
 